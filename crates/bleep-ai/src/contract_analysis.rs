@@ -0,0 +1,630 @@
+//! # Contract static analysis
+//!
+//! Pre-deployment checks over WASM contract bytecode.  The module is parsed
+//! into a call graph — direct calls, plus an edge from every `call_indirect`
+//! to each function in the table — and every function body is walked once in
+//! instruction order with a little local dataflow.
+//!
+//! | Code                       | Severity | Reported when |
+//! |----------------------------|----------|---------------|
+//! | `storage_write_after_call` | Critical | storage is written after `call_contract` ran earlier in the same function (directly or through a callee), so a re-entering call sees stale state |
+//! | `unbounded_loop`           | Warning  | a loop's back-edge is an unconditional `br` and nothing leaves the loop; only running out of gas stops it |
+//! | `unchecked_host_result`    | Warning  | a host function's return code is dropped unread |
+//! | `abi_truncation`           | Warning  | an `i64` crossing the ABI (export parameter, host-call argument, export result) is wrapped to `i32` |
+//! | `dead_function`            | Info     | no entry point reaches the function |
+//!
+//! Entry points are the exports runtimes call (`ENTRY_POINTS`), the start
+//! function and table elements; a contract exporting none of
+//! `ENTRY_POINTS` has all its exports treated as entry points.
+//!
+//! `SeverityGate` turns a network's severity limit into a `DeployGate` for
+//! `ContractRegistry::with_deploy_gate`.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+
+use bleep_vm::DeployGate;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use wasmparser::{ElementItems, ExternalKind, FuncType, Operator, Parser, Payload, TypeRef, ValType};
+
+/// Host import that calls another contract.
+pub const CALL_CONTRACT_IMPORT: &str = "call_contract";
+/// Host import that writes contract storage.
+pub const STORAGE_WRITE_IMPORT: &str = "storage_write";
+/// Exports the runtimes invoke.
+pub const ENTRY_POINTS: &[&str] = &["execute", "migrate", "call_contract"];
+
+// ==================== ERRORS ====================
+
+#[derive(Debug, Error)]
+pub enum AnalysisError {
+    #[error("Invalid WASM: {0}")]
+    InvalidWasm(String),
+}
+
+fn invalid(e: wasmparser::BinaryReaderError) -> AnalysisError {
+    AnalysisError::InvalidWasm(e.to_string())
+}
+
+// ==================== REPORT ====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "info" => Some(Severity::Info),
+            "warning" => Some(Severity::Warning),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Critical => "critical",
+        })
+    }
+}
+
+/// Function index (imports included) and byte offset of a finding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub function: u32,
+    /// Export name of the function, if exported.
+    pub name:     Option<String>,
+    pub offset:   usize,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "func {} ({}) @0x{:x}", self.function, name, self.offset),
+            None => write!(f, "func {} @0x{:x}", self.function, self.offset),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    pub severity:    Severity,
+    pub code:        String,
+    pub location:    Location,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    /// Most severe first.
+    pub findings: Vec<Finding>,
+}
+
+impl AnalysisReport {
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+// ==================== MODULE MODEL ====================
+
+struct Module<'a> {
+    types:   Vec<FuncType>,
+    /// `(name, type index)` of each imported function.
+    imports: Vec<(&'a str, u32)>,
+    /// Type index of each local function.
+    funcs:   Vec<u32>,
+    bodies:  Vec<Vec<(Operator<'a>, usize)>>,
+    /// `(name, function index)` of each exported function.
+    exports: Vec<(&'a str, u32)>,
+    start:   Option<u32>,
+    table:   Vec<u32>,
+}
+
+impl<'a> Module<'a> {
+    fn parse(code: &'a [u8]) -> Result<Self, AnalysisError> {
+        let mut m = Module {
+            types:   Vec::new(),
+            imports: Vec::new(),
+            funcs:   Vec::new(),
+            bodies:  Vec::new(),
+            exports: Vec::new(),
+            start:   None,
+            table:   Vec::new(),
+        };
+        for payload in Parser::new(0).parse_all(code) {
+            match payload.map_err(invalid)? {
+                Payload::TypeSection(reader) => {
+                    for ty in reader.into_iter_err_on_gc_types() {
+                        m.types.push(ty.map_err(invalid)?);
+                    }
+                }
+                Payload::ImportSection(reader) => {
+                    for imp in reader {
+                        let imp = imp.map_err(invalid)?;
+                        if let TypeRef::Func(ty) = imp.ty {
+                            m.imports.push((imp.name, ty));
+                        }
+                    }
+                }
+                Payload::FunctionSection(reader) => {
+                    for ty in reader {
+                        m.funcs.push(ty.map_err(invalid)?);
+                    }
+                }
+                Payload::ExportSection(reader) => {
+                    for exp in reader {
+                        let exp = exp.map_err(invalid)?;
+                        if exp.kind == ExternalKind::Func {
+                            m.exports.push((exp.name, exp.index));
+                        }
+                    }
+                }
+                Payload::StartSection { func, .. } => m.start = Some(func),
+                Payload::ElementSection(reader) => {
+                    for elem in reader {
+                        if let ElementItems::Functions(funcs) = elem.map_err(invalid)?.items {
+                            for f in funcs {
+                                m.table.push(f.map_err(invalid)?);
+                            }
+                        }
+                    }
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let ops = body
+                        .get_operators_reader()
+                        .map_err(invalid)?
+                        .into_iter_with_offsets()
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(invalid)?;
+                    m.bodies.push(ops);
+                }
+                _ => {}
+            }
+        }
+        Ok(m)
+    }
+
+    fn func_count(&self) -> usize {
+        self.imports.len() + self.bodies.len()
+    }
+
+    fn func_type(&self, f: u32) -> Option<&FuncType> {
+        let ty = match self.imports.get(f as usize) {
+            Some((_, ty)) => *ty,
+            None => *self.funcs.get(f as usize - self.imports.len())?,
+        };
+        self.types.get(ty as usize)
+    }
+
+    fn import_name(&self, f: u32) -> Option<&'a str> {
+        self.imports.get(f as usize).map(|(name, _)| *name)
+    }
+
+    fn export_name(&self, f: u32) -> Option<&'a str> {
+        self.exports.iter().find(|(_, idx)| *idx == f).map(|(name, _)| *name)
+    }
+
+    fn location(&self, f: u32, offset: usize) -> Location {
+        Location { function: f, name: self.export_name(f).map(str::to_string), offset }
+    }
+
+    /// Functions each local function may call.
+    fn call_graph(&self) -> Vec<Vec<u32>> {
+        self.bodies
+            .iter()
+            .map(|ops| {
+                let mut callees = Vec::new();
+                for (op, _) in ops {
+                    match op {
+                        Operator::Call { function_index } => callees.push(*function_index),
+                        Operator::CallIndirect { .. } => callees.extend(&self.table),
+                        _ => {}
+                    }
+                }
+                callees
+            })
+            .collect()
+    }
+
+    /// Functions that reach an import named `import`, directly or not.
+    fn reaches_import(&self, graph: &[Vec<u32>], import: &str) -> Vec<bool> {
+        let mut reaches: Vec<bool> = (0..self.func_count() as u32)
+            .map(|f| self.import_name(f) == Some(import))
+            .collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, callees) in graph.iter().enumerate() {
+                let f = self.imports.len() + i;
+                if !reaches[f] && callees.iter().any(|c| reaches.get(*c as usize) == Some(&true)) {
+                    reaches[f] = true;
+                    changed = true;
+                }
+            }
+        }
+        reaches
+    }
+
+    /// Functions reachable from the entry points.
+    fn live(&self, graph: &[Vec<u32>]) -> Vec<bool> {
+        let has_entry = self.exports.iter().any(|(name, _)| ENTRY_POINTS.contains(name));
+        let mut queue: VecDeque<u32> = self
+            .exports
+            .iter()
+            .filter(|(name, _)| !has_entry || ENTRY_POINTS.contains(name))
+            .map(|(_, f)| *f)
+            .chain(self.start)
+            .chain(self.table.iter().copied())
+            .collect();
+        let mut live = vec![false; self.func_count()];
+        while let Some(f) = queue.pop_front() {
+            match live.get_mut(f as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => continue,
+            }
+            if let Some(callees) = (f as usize).checked_sub(self.imports.len()).and_then(|i| graph.get(i)) {
+                queue.extend(callees);
+            }
+        }
+        live
+    }
+}
+
+// ==================== ANALYSIS ====================
+
+struct Frame {
+    is_loop:       bool,
+    offset:        usize,
+    /// An unconditional `br` directly in the loop body jumps back.
+    loops_forever: bool,
+    /// Some path leaves the loop.
+    exits:         bool,
+}
+
+impl Frame {
+    fn new(is_loop: bool, offset: usize) -> Self {
+        Frame { is_loop, offset, loops_forever: false, exits: false }
+    }
+}
+
+/// Analyze a WASM contract.
+pub fn analyze_wasm(code: &[u8]) -> Result<AnalysisReport, AnalysisError> {
+    let m = Module::parse(code)?;
+    let graph = m.call_graph();
+    let calls_out = m.reaches_import(&graph, CALL_CONTRACT_IMPORT);
+    let writes = m.reaches_import(&graph, STORAGE_WRITE_IMPORT);
+    let live = m.live(&graph);
+    let mut findings = Vec::new();
+
+    for (i, ops) in m.bodies.iter().enumerate() {
+        let f = (m.imports.len() + i) as u32;
+        let exported = m.export_name(f).is_some();
+        let params = m.func_type(f).map(|t| t.params()).unwrap_or_default();
+        let mut finding = |severity, code: &str, offset, description: String| {
+            findings.push(Finding { severity, code: code.into(), location: m.location(f, offset), description });
+        };
+
+        if !live[f as usize] {
+            let offset = ops.first().map(|(_, o)| *o).unwrap_or_default();
+            finding(Severity::Info, "dead_function", offset, "function is not reachable from any entry point".into());
+        }
+
+        let mut frames = vec![Frame::new(false, 0)];
+        let mut called_out: Option<usize> = None;
+        for (k, (op, offset)) in ops.iter().enumerate() {
+            let offset = *offset;
+            let prev = k.checked_sub(1).map(|p| &ops[p].0);
+            match op {
+                Operator::Block { .. } | Operator::If { .. } => frames.push(Frame::new(false, offset)),
+                Operator::Loop { .. } => frames.push(Frame::new(true, offset)),
+                Operator::End => {
+                    let frame = frames.pop();
+                    if let Some(frame) = frame.filter(|fr| fr.is_loop && fr.loops_forever && !fr.exits) {
+                        finding(
+                            Severity::Warning,
+                            "unbounded_loop",
+                            frame.offset,
+                            "loop branches back unconditionally and has no exit; only gas exhaustion ends it".into(),
+                        );
+                    }
+                    if frames.is_empty() && exported && matches!(prev, Some(Operator::I32WrapI64)) {
+                        finding(
+                            Severity::Warning,
+                            "abi_truncation",
+                            offset,
+                            "exported function returns an i64 value wrapped to i32".into(),
+                        );
+                    }
+                }
+                Operator::Br { relative_depth } => branch(&mut frames, *relative_depth, false),
+                Operator::BrIf { relative_depth } => branch(&mut frames, *relative_depth, true),
+                Operator::BrTable { targets } => {
+                    for depth in targets.targets().flatten().chain([targets.default()]) {
+                        branch(&mut frames, depth, true);
+                    }
+                }
+                Operator::Return | Operator::Unreachable => {
+                    frames.iter_mut().for_each(|fr| fr.exits = true);
+                }
+                Operator::Call { function_index: callee } => {
+                    let callee = *callee;
+                    if writes.get(callee as usize) == Some(&true) {
+                        if let Some(call_offset) = called_out {
+                            finding(
+                                Severity::Critical,
+                                "storage_write_after_call",
+                                offset,
+                                format!("storage is written after the external call at 0x{:x}", call_offset),
+                            );
+                        }
+                    }
+                    if calls_out.get(callee as usize) == Some(&true) && called_out.is_none() {
+                        called_out = Some(offset);
+                    }
+                    if let Some(host) = m.import_name(callee) {
+                        if matches!(prev, Some(Operator::I32WrapI64)) {
+                            finding(
+                                Severity::Warning,
+                                "abi_truncation",
+                                offset,
+                                format!("i64 value wrapped to i32 as the last argument to `{}`", host),
+                            );
+                        }
+                        let returns = m.func_type(callee).map_or(false, |t| !t.results().is_empty());
+                        if returns && matches!(ops.get(k + 1), Some((Operator::Drop, _))) {
+                            finding(
+                                Severity::Warning,
+                                "unchecked_host_result",
+                                offset,
+                                format!("return code of `{}` is dropped unchecked", host),
+                            );
+                        }
+                    }
+                }
+                Operator::I32WrapI64 if exported => {
+                    if let Some(Operator::LocalGet { local_index }) = prev {
+                        if params.get(*local_index as usize) == Some(&ValType::I64) {
+                            finding(
+                                Severity::Warning,
+                                "abi_truncation",
+                                offset,
+                                format!("i64 parameter {} wrapped to i32", local_index),
+                            );
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity));
+    Ok(AnalysisReport { findings })
+}
+
+/// Record a branch `depth` frames out: loops it leaves gain an exit, and a
+/// branch to a loop is a back-edge.
+fn branch(frames: &mut [Frame], depth: u32, conditional: bool) {
+    let Some(target) = frames.len().checked_sub(1 + depth as usize) else { return };
+    let innermost = target + 1 == frames.len();
+    for fr in &mut frames[target + 1..] {
+        fr.exits = true;
+    }
+    let fr = &mut frames[target];
+    if fr.is_loop {
+        if conditional {
+            // Not taking the branch falls through towards the loop's end.
+            fr.exits = true;
+        } else if innermost {
+            fr.loops_forever = true;
+        }
+    }
+}
+
+// ==================== DEPLOY GATE ====================
+
+/// Highest finding severity a network accepts at deployment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeverityGate {
+    pub max_allowed: Severity,
+}
+
+impl SeverityGate {
+    pub fn new(max_allowed: Severity) -> Self {
+        SeverityGate { max_allowed }
+    }
+
+    /// Devnets accept anything; other networks refuse critical findings.
+    pub fn for_network(network: &str) -> Self {
+        match network {
+            "devnet" => SeverityGate::new(Severity::Critical),
+            _ => SeverityGate::new(Severity::Warning),
+        }
+    }
+
+    /// Analyze `code`, failing if any finding exceeds the limit.
+    pub fn check(&self, code: &[u8]) -> Result<AnalysisReport, String> {
+        let report = analyze_wasm(code).map_err(|e| e.to_string())?;
+        let blocking: Vec<String> = report
+            .findings
+            .iter()
+            .filter(|f| f.severity > self.max_allowed)
+            .map(|f| format!("{} at {}", f.code, f.location))
+            .collect();
+        if blocking.is_empty() {
+            Ok(report)
+        } else {
+            Err(format!("static analysis findings above {}: {}", self.max_allowed, blocking.join(", ")))
+        }
+    }
+
+    pub fn deploy_gate(self) -> DeployGate {
+        Arc::new(move |code: &[u8]| self.check(code).map(|_| ()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_vm::ContractRegistry;
+    use wasm_encoder::{
+        BlockType, CodeSection, EntityType, ExportKind, ExportSection, Function, FunctionSection,
+        ImportSection, Instruction, MemorySection, MemoryType, Module as WasmModule, TypeSection,
+        ValType as V,
+    };
+
+    type Body = Vec<Instruction<'static>>;
+
+    // Imported function indices.
+    const STORAGE_WRITE: u32 = 0;
+    const CALL_CONTRACT: u32 = 1;
+    const STORAGE_READ: u32 = 2;
+
+    /// Module importing storage_write, call_contract and storage_read; local
+    /// functions start at index 3.  Each function is
+    /// `(params, results, export name, body)`.
+    fn contract(funcs: &[(&[V], &[V], Option<&str>, Body)]) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.function([V::I32; 4], [] as [V; 0]);
+        types.function([V::I32; 4], [V::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", STORAGE_WRITE_IMPORT, EntityType::Function(0));
+        imports.import("bleep", CALL_CONTRACT_IMPORT, EntityType::Function(1));
+        imports.import("bleep", "storage_read", EntityType::Function(1));
+        let mut functions = FunctionSection::new();
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        let mut code = CodeSection::new();
+        for (i, (params, results, export, body)) in funcs.iter().enumerate() {
+            types.function(params.iter().copied(), results.iter().copied());
+            functions.function(2 + i as u32);
+            if let Some(name) = export {
+                exports.export(name, ExportKind::Func, 3 + i as u32);
+            }
+            let mut f = Function::new([] as [(u32, V); 0]);
+            for ins in body.iter().chain([&Instruction::End]) {
+                f.instruction(ins);
+            }
+            code.function(&f);
+        }
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut module = WasmModule::new();
+        module.section(&types).section(&imports).section(&functions).section(&memory)
+            .section(&exports).section(&code);
+        module.finish()
+    }
+
+    fn host_call(f: u32) -> Body {
+        vec![
+            Instruction::I32Const(0), Instruction::I32Const(1),
+            Instruction::I32Const(2), Instruction::I32Const(1),
+            Instruction::Call(f),
+        ]
+    }
+
+    fn execute(body: Body) -> Vec<u8> {
+        contract(&[(&[V::I32], &[V::I32], Some("execute"), body)])
+    }
+
+    fn codes(code: &[u8]) -> Vec<String> {
+        analyze_wasm(code).unwrap().findings.into_iter().map(|f| f.code).collect()
+    }
+
+    /// Writes storage, calls out and returns the call's status; loops with
+    /// a conditional back-edge.
+    fn clean() -> Vec<u8> {
+        let mut body = host_call(STORAGE_WRITE);
+        body.extend([
+            Instruction::Loop(BlockType::Empty),
+            Instruction::LocalGet(0), Instruction::I32Const(1), Instruction::I32Sub,
+            Instruction::LocalTee(0), Instruction::BrIf(0),
+            Instruction::End,
+        ]);
+        body.extend(host_call(CALL_CONTRACT));
+        execute(body)
+    }
+
+    /// Calls out through a helper, then writes storage.
+    fn reentrant() -> Vec<u8> {
+        let mut body = vec![Instruction::Call(4), Instruction::If(BlockType::Empty), Instruction::End];
+        body.extend(host_call(STORAGE_WRITE));
+        body.push(Instruction::I32Const(0));
+        contract(&[
+            (&[V::I32], &[V::I32], Some("execute"), body),
+            (&[], &[V::I32], None, host_call(CALL_CONTRACT)),
+        ])
+    }
+
+    fn unchecked() -> Vec<u8> {
+        let mut body = host_call(STORAGE_READ);
+        body.extend([Instruction::Drop, Instruction::I32Const(0)]);
+        execute(body)
+    }
+
+    #[test]
+    fn clean_contract_has_no_findings() {
+        assert_eq!(codes(&clean()), Vec::<String>::new());
+    }
+
+    #[test]
+    fn each_issue_class_is_flagged() {
+        let report = analyze_wasm(&reentrant()).unwrap();
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].code, "storage_write_after_call");
+        assert_eq!(report.findings[0].location.name.as_deref(), Some("execute"));
+        assert_eq!(report.max_severity(), Some(Severity::Critical));
+
+        let spin = execute(vec![
+            Instruction::Loop(BlockType::Empty),
+            Instruction::Nop, Instruction::Br(0),
+            Instruction::End,
+            Instruction::I32Const(0),
+        ]);
+        assert_eq!(codes(&spin), ["unbounded_loop"]);
+
+        assert_eq!(codes(&unchecked()), ["unchecked_host_result"]);
+
+        let wrap = contract(&[(&[V::I64], &[V::I32], Some("execute"), vec![
+            Instruction::LocalGet(0), Instruction::I32WrapI64,
+        ])]);
+        assert_eq!(codes(&wrap), ["abi_truncation", "abi_truncation"]);
+
+        let dead = contract(&[
+            (&[V::I32], &[V::I32], Some("execute"), vec![Instruction::I32Const(0)]),
+            (&[], &[], Some("legacy_entry"), vec![]),
+            (&[], &[], None, vec![]),
+        ]);
+        let report = analyze_wasm(&dead).unwrap();
+        let dead_fns: Vec<u32> = report.findings.iter().map(|f| f.location.function).collect();
+        assert_eq!(dead_fns, [4, 5]);
+        assert!(report.findings.iter().all(|f| f.code == "dead_function" && f.severity == Severity::Info));
+    }
+
+    #[test]
+    fn deploy_gate_blocks_critical_findings_only() {
+        let registry = ContractRegistry::new().with_deploy_gate(SeverityGate::for_network("mainnet").deploy_gate());
+        let err = registry.deploy(&reentrant(), false, None, None).unwrap_err();
+        assert!(err.to_string().contains("storage_write_after_call"));
+        assert!(registry.deploy(&unchecked(), false, None, None).is_ok());
+
+        let devnet = ContractRegistry::new().with_deploy_gate(SeverityGate::for_network("devnet").deploy_gate());
+        assert!(devnet.deploy(&reentrant(), false, None, None).is_ok());
+    }
+}
@@ -0,0 +1,616 @@
+//! # Inference sandbox
+//!
+//! Runs model inference off the caller's thread so a pathological or
+//! adversarial model cannot stall the node.
+//!
+//! ```text
+//! predict(model, input)
+//!   ├─ quarantined?              → registered fallback (or Quarantined)
+//!   ├─ input does not fit spec   → InvalidInput   (never reaches the session)
+//!   └─ bounded queue ──► worker thread (owns one session)
+//!          reply within timeout  → output
+//!          no reply in time      → TimeoutError; the worker is abandoned and
+//!                                  a new worker with a fresh session replaces it
+//! ```
+//!
+//! Threads cannot be killed: an abandoned worker runs until the stuck call
+//! returns, then drops its session and exits without taking more work.
+//! After `quarantine_after` consecutive failures (errors or timeouts) the
+//! model is quarantined until `reload`.
+//!
+//! ONNX models are loaded through `onnx::OnnxSessionFactory` (feature
+//! `onnx`), which caps the file size and checks the graph's input shape
+//! against the declared `InputSpec` at load time.
+
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bleep_auth::audit_trail::{AuditAction, AuditOutcome, AuditTrail};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+// ==================== ERRORS ====================
+
+#[derive(Debug, Error)]
+pub enum SandboxError {
+    #[error("Model not found: {0}")]
+    ModelNotFound(String),
+    #[error("Model already registered: {0}")]
+    ModelAlreadyRegistered(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    #[error("Model file is {size} bytes, limit is {max}")]
+    ModelTooLarge { size: u64, max: u64 },
+    #[error("Model input shape {actual:?} does not match declared {declared:?}")]
+    ShapeMismatch { declared: Vec<usize>, actual: Vec<usize> },
+    #[error("Model load failed: {0}")]
+    LoadFailed(String),
+    #[error("Inference queue full for model {0}")]
+    QueueFull(String),
+    #[error("Timeout error during prediction (after {0:?})")]
+    TimeoutError(Duration),
+    #[error("Prediction error: {0}")]
+    PredictionError(String),
+    #[error("Model {0} is quarantined and has no fallback")]
+    Quarantined(String),
+}
+
+// ==================== MODEL INTERFACE ====================
+
+/// Declared input tensor shape; inputs are flat `f32` buffers of `len()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputSpec {
+    pub shape: Vec<usize>,
+}
+
+impl InputSpec {
+    pub fn new(shape: Vec<usize>) -> Self {
+        Self { shape }
+    }
+
+    /// Number of elements a conforming input has.
+    pub fn len(&self) -> usize {
+        self.shape.iter().product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// One loaded model instance. Owned by exactly one worker thread.
+pub trait InferenceSession: Send {
+    fn run(&mut self, input: &[f32]) -> Result<Vec<f32>, String>;
+}
+
+/// Creates sessions for a model; called at registration, on every worker
+/// replacement and on `reload`.
+pub trait SessionFactory: Send + Sync {
+    fn input_spec(&self) -> &InputSpec;
+    fn create(&self) -> Result<Box<dyn InferenceSession>, SandboxError>;
+}
+
+/// Heuristic used while a model is quarantined.
+pub type Fallback = Arc<dyn Fn(&[f32]) -> Vec<f32> + Send + Sync>;
+
+// ==================== CONFIG & TELEMETRY ====================
+
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Worker threads (sessions) per model.
+    pub workers_per_model: usize,
+    /// Pending requests per model before `QueueFull`.
+    pub queue_capacity:    usize,
+    /// Wall-clock limit per prediction, queueing included.
+    pub timeout:           Duration,
+    /// Largest model file accepted by loaders.
+    pub max_model_bytes:   u64,
+    /// Consecutive failures that quarantine a model.
+    pub quarantine_after:  u32,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            workers_per_model: 2,
+            queue_capacity:    64,
+            timeout:           Duration::from_secs(2),
+            max_model_bytes:   64 * 1024 * 1024,
+            quarantine_after:  3,
+        }
+    }
+}
+
+/// Upper bounds (ms) of the latency histogram buckets; a final bucket
+/// counts everything slower.
+pub const LATENCY_BUCKETS_MS: [u64; 8] = [1, 5, 10, 50, 100, 500, 1_000, 5_000];
+
+/// Per-model inference telemetry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelStats {
+    /// Successful-inference latency counts per `LATENCY_BUCKETS_MS` bucket, plus overflow.
+    pub latency_buckets:      [u64; 9],
+    pub latency_sum_ms:       u64,
+    pub predictions:          u64,
+    pub failures:             u64,
+    pub timeouts:             u64,
+    pub rejected_inputs:      u64,
+    pub fallbacks:            u64,
+    pub consecutive_failures: u32,
+    pub quarantined:          bool,
+    pub reloads:              u64,
+}
+
+impl ModelStats {
+    fn observe_latency(&mut self, elapsed: Duration) {
+        let ms = elapsed.as_millis() as u64;
+        let bucket = LATENCY_BUCKETS_MS.iter().position(|&b| ms <= b).unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.latency_buckets[bucket] += 1;
+        self.latency_sum_ms += ms;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PredictionSource {
+    Model,
+    Fallback,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxPrediction {
+    pub output: Vec<f32>,
+    pub source: PredictionSource,
+}
+
+// ==================== WORKERS ====================
+
+const JOB_QUEUED: u8 = 0;
+const JOB_RUNNING: u8 = 1;
+const JOB_ABANDONED: u8 = 2;
+
+struct Job {
+    input: Vec<f32>,
+    reply: oneshot::Sender<Result<Vec<f32>, String>>,
+    state: Arc<AtomicU8>,
+}
+
+struct WorkerPool {
+    tx:      SyncSender<Job>,
+    rx:      Arc<Mutex<Receiver<Job>>>,
+    factory: Arc<dyn SessionFactory>,
+}
+
+impl WorkerPool {
+    fn start(factory: Arc<dyn SessionFactory>, config: &SandboxConfig) -> Result<Self, SandboxError> {
+        let (tx, rx) = sync_channel(config.queue_capacity.max(1));
+        let pool = WorkerPool { tx, rx: Arc::new(Mutex::new(rx)), factory };
+        for _ in 0..config.workers_per_model.max(1) {
+            pool.spawn_worker()?;
+        }
+        Ok(pool)
+    }
+
+    fn spawn_worker(&self) -> Result<(), SandboxError> {
+        let session = self.factory.create()?;
+        let rx = Arc::clone(&self.rx);
+        let factory = Arc::clone(&self.factory);
+        thread::Builder::new()
+            .name("bleep-ai-inference".into())
+            .spawn(move || worker_loop(session, factory, rx))
+            .map(|_| ())
+            .map_err(|e| SandboxError::LoadFailed(format!("spawn inference worker: {e}")))
+    }
+}
+
+fn worker_loop(
+    mut session: Box<dyn InferenceSession>,
+    factory: Arc<dyn SessionFactory>,
+    rx: Arc<Mutex<Receiver<Job>>>,
+) {
+    loop {
+        let job = match rx.lock().map(|r| r.recv()) {
+            Ok(Ok(job)) => job,
+            // Pool dropped (reload / shutdown) or lock poisoned.
+            _ => return,
+        };
+        if job.state.compare_exchange(JOB_QUEUED, JOB_RUNNING, Ordering::SeqCst, Ordering::SeqCst).is_err() {
+            // Timed out while still queued.
+            continue;
+        }
+
+        let out = match catch_unwind(AssertUnwindSafe(|| session.run(&job.input))) {
+            Ok(out) => out,
+            Err(_) => {
+                // The session may be left in any state after a panic.
+                match factory.create() {
+                    Ok(fresh) => session = fresh,
+                    Err(_) => return,
+                }
+                Err("model panicked".to_string())
+            }
+        };
+
+        if job.state.load(Ordering::SeqCst) == JOB_ABANDONED {
+            // A replacement worker already took our place.
+            return;
+        }
+        let _ = job.reply.send(out);
+    }
+}
+
+// ==================== SANDBOX ====================
+
+struct ModelSlot {
+    pool:     WorkerPool,
+    fallback: Option<Fallback>,
+    stats:    ModelStats,
+}
+
+/// Registry of sandboxed models.
+pub struct InferenceSandbox {
+    config: SandboxConfig,
+    models: Mutex<HashMap<String, ModelSlot>>,
+    audit:  Option<AuditTrail>,
+}
+
+impl InferenceSandbox {
+    pub fn new(config: SandboxConfig) -> Self {
+        Self { config, models: Mutex::new(HashMap::new()), audit: None }
+    }
+
+    /// Record every `reload` to the operator audit trail.
+    pub fn with_audit_trail(mut self, trail: AuditTrail) -> Self {
+        self.audit = Some(trail);
+        self
+    }
+
+    pub fn config(&self) -> &SandboxConfig {
+        &self.config
+    }
+
+    /// Start workers for `name`. `fallback` serves predictions while quarantined.
+    pub fn register(
+        &self,
+        name: &str,
+        factory: Arc<dyn SessionFactory>,
+        fallback: Option<Fallback>,
+    ) -> Result<(), SandboxError> {
+        if self.models.lock().unwrap().contains_key(name) {
+            return Err(SandboxError::ModelAlreadyRegistered(name.to_string()));
+        }
+        let pool = WorkerPool::start(factory, &self.config)?;
+        self.models.lock().unwrap().insert(name.to_string(), ModelSlot {
+            pool,
+            fallback,
+            stats: ModelStats::default(),
+        });
+        Ok(())
+    }
+
+    /// Replace every worker with a fresh session and lift quarantine.
+    pub fn reload(&self, name: &str) -> Result<(), SandboxError> {
+        let result = self.reload_slot(name);
+        if let Some(trail) = &self.audit {
+            let outcome = if result.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failure };
+            trail.append("ai", AuditAction::AiModelReload, &serde_json::json!({ "model": name }), outcome);
+        }
+        result
+    }
+
+    fn reload_slot(&self, name: &str) -> Result<(), SandboxError> {
+        let mut models = self.models.lock().unwrap();
+        let slot = models.get_mut(name).ok_or_else(|| SandboxError::ModelNotFound(name.to_string()))?;
+        slot.pool = WorkerPool::start(Arc::clone(&slot.pool.factory), &self.config)?;
+        slot.stats.quarantined = false;
+        slot.stats.consecutive_failures = 0;
+        slot.stats.reloads += 1;
+        Ok(())
+    }
+
+    pub fn stats(&self, name: &str) -> Option<ModelStats> {
+        self.models.lock().unwrap().get(name).map(|s| s.stats.clone())
+    }
+
+    pub fn is_quarantined(&self, name: &str) -> bool {
+        self.models.lock().unwrap().get(name).map(|s| s.stats.quarantined).unwrap_or(false)
+    }
+
+    /// Run `input` through `name` on a worker, bounded by the configured timeout.
+    pub async fn predict(&self, name: &str, input: &[f32]) -> Result<SandboxPrediction, SandboxError> {
+        let (reply_rx, state) = {
+            let mut models = self.models.lock().unwrap();
+            let slot = models.get_mut(name).ok_or_else(|| SandboxError::ModelNotFound(name.to_string()))?;
+
+            if slot.stats.quarantined {
+                return match &slot.fallback {
+                    Some(fallback) => {
+                        slot.stats.fallbacks += 1;
+                        Ok(SandboxPrediction { output: fallback(input), source: PredictionSource::Fallback })
+                    }
+                    None => Err(SandboxError::Quarantined(name.to_string())),
+                };
+            }
+
+            let spec = slot.pool.factory.input_spec();
+            if input.len() != spec.len() {
+                slot.stats.rejected_inputs += 1;
+                return Err(SandboxError::InvalidInput(format!(
+                    "expected {} values for shape {:?}, got {}", spec.len(), spec.shape, input.len()
+                )));
+            }
+
+            let (reply_tx, reply_rx) = oneshot::channel();
+            let state = Arc::new(AtomicU8::new(JOB_QUEUED));
+            let job = Job { input: input.to_vec(), reply: reply_tx, state: Arc::clone(&state) };
+            match slot.pool.tx.try_send(job) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => return Err(SandboxError::QueueFull(name.to_string())),
+                Err(TrySendError::Disconnected(_)) => {
+                    return Err(SandboxError::PredictionError("inference workers gone".into()));
+                }
+            }
+            (reply_rx, state)
+        };
+
+        let started = Instant::now();
+        let outcome = tokio::time::timeout(self.config.timeout, reply_rx).await;
+
+        let mut models = self.models.lock().unwrap();
+        let slot = models.get_mut(name).ok_or_else(|| SandboxError::ModelNotFound(name.to_string()))?;
+        let err = match outcome {
+            Ok(Ok(Ok(output))) => {
+                slot.stats.predictions += 1;
+                slot.stats.consecutive_failures = 0;
+                slot.stats.observe_latency(started.elapsed());
+                return Ok(SandboxPrediction { output, source: PredictionSource::Model });
+            }
+            Ok(Ok(Err(e))) => SandboxError::PredictionError(e),
+            Ok(Err(_)) => {
+                // Worker exited without replying; keep the pool at strength.
+                if let Err(e) = slot.pool.spawn_worker() {
+                    warn!("[InferenceSandbox] {}: worker replacement failed: {}", name, e);
+                }
+                SandboxError::PredictionError("inference worker exited".into())
+            }
+            Err(_) => {
+                slot.stats.timeouts += 1;
+                let was_running = state
+                    .compare_exchange(JOB_QUEUED, JOB_ABANDONED, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_err();
+                if was_running {
+                    state.store(JOB_ABANDONED, Ordering::SeqCst);
+                    if let Err(e) = slot.pool.spawn_worker() {
+                        warn!("[InferenceSandbox] {}: worker replacement failed: {}", name, e);
+                    }
+                }
+                SandboxError::TimeoutError(self.config.timeout)
+            }
+        };
+
+        slot.stats.failures += 1;
+        slot.stats.consecutive_failures += 1;
+        if !slot.stats.quarantined && slot.stats.consecutive_failures >= self.config.quarantine_after {
+            warn!(
+                "[InferenceSandbox] quarantining {} after {} consecutive failures",
+                name, slot.stats.consecutive_failures
+            );
+            slot.stats.quarantined = true;
+        }
+        Err(err)
+    }
+}
+
+// ==================== ONNX (tract) ====================
+
+#[cfg(feature = "onnx")]
+pub mod onnx {
+    //! ONNX sessions backed by `tract-onnx`.
+
+    use std::path::PathBuf;
+
+    use tract_onnx::prelude::*;
+
+    use super::{InferenceSession, InputSpec, SandboxError, SessionFactory};
+
+    type Plan = TypedRunnableModel<TypedModel>;
+
+    /// Loads `path` for every new session; the file is re-read on reload.
+    pub struct OnnxSessionFactory {
+        path:      PathBuf,
+        spec:      InputSpec,
+        max_bytes: u64,
+    }
+
+    impl OnnxSessionFactory {
+        /// Validate the model once up front so a bad file fails registration.
+        pub fn load(path: impl Into<PathBuf>, spec: InputSpec, max_bytes: u64) -> Result<Self, SandboxError> {
+            let factory = Self { path: path.into(), spec, max_bytes };
+            factory.plan()?;
+            Ok(factory)
+        }
+
+        fn plan(&self) -> Result<Plan, SandboxError> {
+            let load = |e: TractError| SandboxError::LoadFailed(format!("{}: {}", self.path.display(), e));
+
+            let size = std::fs::metadata(&self.path)
+                .map_err(|e| SandboxError::LoadFailed(format!("{}: {}", self.path.display(), e)))?
+                .len();
+            if size > self.max_bytes {
+                return Err(SandboxError::ModelTooLarge { size, max: self.max_bytes });
+            }
+
+            let model = tract_onnx::onnx().model_for_path(&self.path).map_err(load)?;
+            let fact = model.input_fact(0).map_err(load)?;
+            if let Some(dims) = fact.shape.concretize() {
+                let actual = dims.iter()
+                    .map(|d| d.to_usize())
+                    .collect::<TractResult<Vec<usize>>>()
+                    .map_err(load)?;
+                if actual != self.spec.shape {
+                    return Err(SandboxError::ShapeMismatch { declared: self.spec.shape.clone(), actual });
+                }
+            }
+
+            model
+                .with_input_fact(0, f32::fact(&self.spec.shape).into())
+                .and_then(|m| m.into_optimized())
+                .and_then(|m| m.into_runnable())
+                .map_err(load)
+        }
+    }
+
+    impl SessionFactory for OnnxSessionFactory {
+        fn input_spec(&self) -> &InputSpec {
+            &self.spec
+        }
+
+        fn create(&self) -> Result<Box<dyn InferenceSession>, SandboxError> {
+            Ok(Box::new(OnnxSession { plan: self.plan()?, shape: self.spec.shape.clone() }))
+        }
+    }
+
+    struct OnnxSession {
+        plan:  Plan,
+        shape: Vec<usize>,
+    }
+
+    impl InferenceSession for OnnxSession {
+        fn run(&mut self, input: &[f32]) -> Result<Vec<f32>, String> {
+            let tensor = Tensor::from_shape(&self.shape, input).map_err(|e| e.to_string())?;
+            let outputs = self.plan.run(tvec!(tensor.into())).map_err(|e| e.to_string())?;
+            let first = outputs.first().ok_or("model produced no outputs")?;
+            let view = first.to_array_view::<f32>().map_err(|e| e.to_string())?;
+            Ok(view.iter().copied().collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+
+    /// Doubles its input; sleeps 500 ms when the first value is negative,
+    /// errors while `fail` is set.
+    struct MockFactory {
+        spec:  InputSpec,
+        fail:  Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    struct MockSession {
+        fail:  Arc<AtomicBool>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl InferenceSession for MockSession {
+        fn run(&mut self, input: &[f32]) -> Result<Vec<f32>, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                return Err("corrupt graph".into());
+            }
+            if input[0] < 0.0 {
+                thread::sleep(Duration::from_millis(500));
+            }
+            Ok(input.iter().map(|x| x * 2.0).collect())
+        }
+    }
+
+    impl SessionFactory for MockFactory {
+        fn input_spec(&self) -> &InputSpec {
+            &self.spec
+        }
+
+        fn create(&self) -> Result<Box<dyn InferenceSession>, SandboxError> {
+            Ok(Box::new(MockSession { fail: Arc::clone(&self.fail), calls: Arc::clone(&self.calls) }))
+        }
+    }
+
+    fn mock(shape: Vec<usize>) -> (Arc<MockFactory>, Arc<AtomicBool>, Arc<AtomicUsize>) {
+        let fail = Arc::new(AtomicBool::new(false));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let factory = Arc::new(MockFactory { spec: InputSpec::new(shape), fail: Arc::clone(&fail), calls: Arc::clone(&calls) });
+        (factory, fail, calls)
+    }
+
+    fn sandbox(workers: usize) -> InferenceSandbox {
+        InferenceSandbox::new(SandboxConfig {
+            workers_per_model: workers,
+            timeout: Duration::from_millis(100),
+            quarantine_after: 3,
+            ..SandboxConfig::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn timeout_does_not_block_later_predictions() {
+        let sb = sandbox(1);
+        let (factory, _, _) = mock(vec![2]);
+        sb.register("slow", factory, None).unwrap();
+
+        let started = Instant::now();
+        let err = sb.predict("slow", &[-1.0, 1.0]).await.unwrap_err();
+        assert!(matches!(err, SandboxError::TimeoutError(_)));
+
+        // The only worker is still asleep; its replacement serves this.
+        let ok = sb.predict("slow", &[1.0, 2.0]).await.unwrap();
+        assert_eq!(ok.output, vec![2.0, 4.0]);
+        assert_eq!(ok.source, PredictionSource::Model);
+        assert!(started.elapsed() < Duration::from_millis(400));
+
+        let stats = sb.stats("slow").unwrap();
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.predictions, 1);
+        assert_eq!(stats.consecutive_failures, 0);
+        assert_eq!(stats.latency_buckets.iter().sum::<u64>(), 1);
+    }
+
+    #[tokio::test]
+    async fn quarantine_after_consecutive_failures_until_reload() {
+        let sb = sandbox(1);
+        let (factory, fail, _) = mock(vec![2]);
+        let fallback: Fallback = Arc::new(|input: &[f32]| vec![0.0; input.len()]);
+        sb.register("flaky", factory, Some(fallback)).unwrap();
+
+        fail.store(true, Ordering::SeqCst);
+        for _ in 0..3 {
+            assert!(matches!(sb.predict("flaky", &[1.0, 1.0]).await, Err(SandboxError::PredictionError(_))));
+        }
+        assert!(sb.is_quarantined("flaky"));
+
+        fail.store(false, Ordering::SeqCst);
+        let fb = sb.predict("flaky", &[1.0, 1.0]).await.unwrap();
+        assert_eq!(fb.source, PredictionSource::Fallback);
+        assert_eq!(fb.output, vec![0.0, 0.0]);
+
+        sb.reload("flaky").unwrap();
+        assert!(!sb.is_quarantined("flaky"));
+        let ok = sb.predict("flaky", &[1.0, 1.0]).await.unwrap();
+        assert_eq!(ok.source, PredictionSource::Model);
+
+        let stats = sb.stats("flaky").unwrap();
+        assert_eq!((stats.failures, stats.fallbacks, stats.reloads), (3, 1, 1));
+    }
+
+    #[tokio::test]
+    async fn shape_mismatch_rejected_before_session() {
+        let sb = sandbox(1);
+        let (factory, _, calls) = mock(vec![2, 3]);
+        sb.register("m", factory, None).unwrap();
+
+        assert!(matches!(sb.predict("m", &[1.0; 5]).await, Err(SandboxError::InvalidInput(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(sb.stats("m").unwrap().rejected_inputs, 1);
+        assert!(!sb.is_quarantined("m"));
+
+        assert!(sb.predict("m", &[1.0; 6]).await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
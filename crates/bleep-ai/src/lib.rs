@@ -14,6 +14,8 @@
 pub mod feature_extractor;
 pub mod ai_decision_module;
 pub mod governance_integration;
+pub mod inference_sandbox;
+pub mod contract_analysis;
 
 // Legacy modules (Phase 3)
 pub mod deterministic_inference;
@@ -78,6 +80,15 @@ pub use governance_integration::{
     GovernanceError,
 };
 
+pub use contract_analysis::{
+    analyze_wasm, AnalysisError, AnalysisReport, Finding, Location, Severity, SeverityGate,
+};
+
+pub use inference_sandbox::{
+    InferenceSandbox, SandboxConfig, SandboxError, SandboxPrediction,
+    PredictionSource, InputSpec, InferenceSession, SessionFactory, ModelStats,
+};
+
 pub mod wallet;
 pub mod governance;
 pub mod security;
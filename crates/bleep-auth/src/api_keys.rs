@@ -0,0 +1,603 @@
+//! bleep-auth/src/api_keys.rs
+//! Multi-tenant RPC API keys with per-key quotas and usage accounting.
+//!
+//! Each downstream consumer of a node's RPC gets its own key.  A key carries
+//! the route groups it may call, a requests-per-day quota, a per-second burst
+//! limit and an optional expiry.  Only the SHA-256 of the key is stored; the
+//! plaintext is returned once, by [`ApiKeyRegistry::create`].
+//!
+//! Usage is counted per key per hour and written through to RocksDB, so
+//! quotas and usage reports survive restarts.  The daily quota window is the
+//! UTC day (`now / 86_400`), i.e. quotas reset at midnight UTC.
+//!
+//! ## Column-family layout
+//! ```
+//! CF: "api_keys"
+//!   key:   key id (utf-8)
+//!   value: bincode-serialised ApiKeyRecord
+//!
+//! CF: "api_usage"
+//!   key:   key id || 0x00 || hour index as 8-byte big-endian
+//!   value: request count as u64 big-endian
+//! ```
+//!
+//! Time comes from a [`Clock`] so quota windows can be tested without
+//! sleeping; production uses [`SystemClock`].
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use rand::RngCore;
+use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+const CF_KEYS:  &str = "api_keys";
+const CF_USAGE: &str = "api_usage";
+
+const SECS_PER_HOUR: u64 = 3_600;
+const SECS_PER_DAY:  u64 = 86_400;
+
+/// Prefix of every issued key, so leaked keys are easy to grep for.
+pub const API_KEY_PREFIX: &str = "bleep_";
+
+// ── Clock ─────────────────────────────────────────────────────────────────────
+
+/// Source of unix time (seconds) for quota windows and expiry.
+pub trait Clock: Send + Sync {
+    fn now_secs(&self) -> u64;
+}
+
+/// Wall-clock time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_secs(&self) -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// Manually advanced clock for tests and simulations.
+pub struct ManualClock(AtomicU64);
+
+impl ManualClock {
+    pub fn new(now_secs: u64) -> Self { Self(AtomicU64::new(now_secs)) }
+    pub fn set(&self, now_secs: u64)  { self.0.store(now_secs, Ordering::SeqCst); }
+    pub fn advance(&self, secs: u64)  { self.0.fetch_add(secs, Ordering::SeqCst); }
+}
+
+impl Clock for ManualClock {
+    fn now_secs(&self) -> u64 { self.0.load(Ordering::SeqCst) }
+}
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+/// Coarse grouping of RPC routes that a key can be allowed to call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RouteGroup {
+    /// Queries (`GET`).
+    Read,
+    /// Transaction submission and simulation.
+    Tx,
+    /// Every other state-changing call (staking, PAT, governance, …).
+    Write,
+}
+
+/// Attributes of a new key — the body of `POST /rpc/admin/keys`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeySpec {
+    pub name:          String,
+    pub groups:        Vec<RouteGroup>,
+    /// Requests per UTC day; 0 = unlimited.
+    #[serde(default)]
+    pub daily_quota:   u64,
+    /// Requests per second; 0 = unlimited.
+    #[serde(default)]
+    pub burst_per_sec: u32,
+    /// Unix seconds after which the key is rejected.
+    #[serde(default)]
+    pub expires_at:    Option<u64>,
+}
+
+/// Stored form of a key.  Never contains the plaintext key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub id:            String,
+    pub name:          String,
+    /// Hex SHA-256 of the plaintext key.
+    pub key_hash:      String,
+    pub groups:        Vec<RouteGroup>,
+    pub daily_quota:   u64,
+    pub burst_per_sec: u32,
+    pub expires_at:    Option<u64>,
+    pub created_at:    u64,
+    pub revoked_at:    Option<u64>,
+}
+
+/// A freshly created key.  `key` is shown exactly once.
+#[derive(Debug, Clone, Serialize)]
+pub struct IssuedApiKey {
+    pub key:    String,
+    pub record: ApiKeyRecord,
+}
+
+/// Quota position of an admitted request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Admission {
+    pub key_id:   String,
+    /// Daily quota (0 = unlimited).
+    pub limit:    u64,
+    /// Requests used today, including this one.
+    pub used:     u64,
+    /// Unix seconds at which the daily window resets.
+    pub reset_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HourlyUsage {
+    /// Unix seconds at the start of the hour.
+    pub hour_start: u64,
+    pub requests:   u64,
+}
+
+/// Response of `GET /rpc/admin/keys/:id/usage`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UsageReport {
+    pub key_id: String,
+    pub hourly: Vec<HourlyUsage>,
+    pub total:  u64,
+    pub today:  u64,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum ApiKeyError {
+    #[error("API key required")]
+    Missing,
+
+    #[error("API key not recognised")]
+    Unknown,
+
+    #[error("API key has expired")]
+    Expired,
+
+    #[error("API key has been revoked")]
+    Revoked,
+
+    #[error("API key is not allowed to call {0:?} routes")]
+    RouteNotAllowed(RouteGroup),
+
+    #[error("Daily quota of {limit} requests exhausted — resets at {reset_at}")]
+    QuotaExhausted { limit: u64, used: u64, reset_at: u64 },
+
+    #[error("Burst limit of {limit} requests/s exceeded")]
+    BurstExceeded { limit: u32, retry_after_secs: u64 },
+
+    #[error("API key not found: {0}")]
+    NotFound(String),
+
+    #[error("API key store error: {0}")]
+    Storage(String),
+}
+
+impl ApiKeyError {
+    /// HTTP status the RPC layer should answer with.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            ApiKeyError::Missing
+            | ApiKeyError::Unknown
+            | ApiKeyError::Expired
+            | ApiKeyError::Revoked => 401,
+            ApiKeyError::RouteNotAllowed(_) => 403,
+            ApiKeyError::QuotaExhausted { .. }
+            | ApiKeyError::BurstExceeded { .. } => 429,
+            ApiKeyError::NotFound(_) => 404,
+            ApiKeyError::Storage(_) => 500,
+        }
+    }
+}
+
+pub type ApiKeyResult<T> = Result<T, ApiKeyError>;
+
+// ── Registry ──────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct Inner {
+    records: HashMap<String, ApiKeyRecord>,
+    /// key_hash → id
+    by_hash: HashMap<String, String>,
+    /// id → (day index, requests admitted that day)
+    daily:   HashMap<String, (u64, u64)>,
+    /// id → (second, requests admitted that second)
+    burst:   HashMap<String, (u64, u32)>,
+}
+
+/// RocksDB-backed API key registry.
+pub struct ApiKeyRegistry {
+    db:    DB,
+    clock: Arc<dyn Clock>,
+    inner: Mutex<Inner>,
+}
+
+impl ApiKeyRegistry {
+    /// Open (or create) the registry at `path` and load all key records.
+    pub fn open<P: AsRef<Path>>(path: P) -> ApiKeyResult<Self> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let cfs = vec![
+            ColumnFamilyDescriptor::new(CF_KEYS,  Options::default()),
+            ColumnFamilyDescriptor::new(CF_USAGE, Options::default()),
+        ];
+        let db = DB::open_cf_descriptors(&opts, path, cfs)
+            .map_err(|e| ApiKeyError::Storage(format!("open failed: {}", e)))?;
+
+        let mut inner = Inner::default();
+        {
+            let cf = db.cf_handle(CF_KEYS).ok_or_else(|| missing_cf(CF_KEYS))?;
+            for item in db.iterator_cf(&cf, rocksdb::IteratorMode::Start) {
+                let (_, value) = item.map_err(|e| ApiKeyError::Storage(e.to_string()))?;
+                match bincode::deserialize::<ApiKeyRecord>(&value) {
+                    Ok(rec) => {
+                        inner.by_hash.insert(rec.key_hash.clone(), rec.id.clone());
+                        inner.records.insert(rec.id.clone(), rec);
+                    }
+                    Err(e) => log::warn!("[ApiKeyRegistry] Skipping undeserializable key: {}", e),
+                }
+            }
+        }
+
+        log::info!("[ApiKeyRegistry] Opened — {} keys", inner.records.len());
+        Ok(Self { db, clock: Arc::new(SystemClock), inner: Mutex::new(inner) })
+    }
+
+    /// Open a registry in a fresh temp dir.  Used in tests and devnet.
+    pub fn new() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let tmp = std::env::temp_dir()
+            .join(format!("bleep-api-keys-{}-{}", std::process::id(), nanos));
+        Self::open(tmp).expect("failed to open temp API key registry")
+    }
+
+    /// Replace the time source.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // ── Management ───────────────────────────────────────────────────────────
+
+    /// Create a key and return its plaintext (shown once) with the record.
+    pub fn create(&self, spec: ApiKeySpec) -> ApiKeyResult<IssuedApiKey> {
+        let mut secret = [0u8; 32];
+        let mut id = [0u8; 8];
+        rand::thread_rng().fill_bytes(&mut secret);
+        rand::thread_rng().fill_bytes(&mut id);
+        let key = format!("{}{}", API_KEY_PREFIX, hex::encode(secret));
+
+        let record = ApiKeyRecord {
+            id:            hex::encode(id),
+            name:          spec.name,
+            key_hash:      hash_key(&key),
+            groups:        spec.groups,
+            daily_quota:   spec.daily_quota,
+            burst_per_sec: spec.burst_per_sec,
+            expires_at:    spec.expires_at,
+            created_at:    self.clock.now_secs(),
+            revoked_at:    None,
+        };
+
+        let mut inner = self.inner.lock();
+        self.put_record(&record)?;
+        inner.by_hash.insert(record.key_hash.clone(), record.id.clone());
+        inner.records.insert(record.id.clone(), record.clone());
+        log::info!("[ApiKeyRegistry] Created key {} ({})", record.id, record.name);
+        Ok(IssuedApiKey { key, record })
+    }
+
+    /// Revoke a key.  Takes effect for the next request.
+    pub fn revoke(&self, id: &str) -> ApiKeyResult<ApiKeyRecord> {
+        let mut inner = self.inner.lock();
+        let mut record = inner.records.get(id).cloned()
+            .ok_or_else(|| ApiKeyError::NotFound(id.to_string()))?;
+        if record.revoked_at.is_none() {
+            record.revoked_at = Some(self.clock.now_secs());
+            self.put_record(&record)?;
+            inner.records.insert(id.to_string(), record.clone());
+            log::info!("[ApiKeyRegistry] Revoked key {}", id);
+        }
+        Ok(record)
+    }
+
+    /// All keys, oldest first.
+    pub fn list(&self) -> Vec<ApiKeyRecord> {
+        let mut keys: Vec<ApiKeyRecord> = self.inner.lock().records.values().cloned().collect();
+        keys.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+        keys
+    }
+
+    pub fn get(&self, id: &str) -> Option<ApiKeyRecord> {
+        self.inner.lock().records.get(id).cloned()
+    }
+
+    // ── Enforcement ──────────────────────────────────────────────────────────
+
+    /// Check `presented` for a request in `group` and count it if admitted.
+    ///
+    /// Rejected requests are not counted against the quota.
+    pub fn authorize(&self, presented: Option<&str>, group: RouteGroup) -> ApiKeyResult<Admission> {
+        self.authorize_units(presented, group, 1)
+    }
+
+    /// `authorize` for a request worth `units` requests — a batch counts
+    /// once per item against both the burst limit and the daily quota.
+    /// With `units == 0` only the key and its route group are checked.
+    pub fn authorize_units(&self, presented: Option<&str>, group: RouteGroup, units: u64) -> ApiKeyResult<Admission> {
+        let presented = presented.filter(|k| !k.is_empty()).ok_or(ApiKeyError::Missing)?;
+        let now = self.clock.now_secs();
+        let day = now / SECS_PER_DAY;
+        let reset_at = (day + 1) * SECS_PER_DAY;
+
+        let mut inner = self.inner.lock();
+        let id = inner.by_hash.get(&hash_key(presented)).cloned().ok_or(ApiKeyError::Unknown)?;
+        let record = inner.records.get(&id).cloned().ok_or(ApiKeyError::Unknown)?;
+
+        if record.revoked_at.is_some() {
+            return Err(ApiKeyError::Revoked);
+        }
+        if record.expires_at.is_some_and(|exp| now >= exp) {
+            return Err(ApiKeyError::Expired);
+        }
+        if !record.groups.contains(&group) {
+            return Err(ApiKeyError::RouteNotAllowed(group));
+        }
+
+        let burst = match inner.burst.get(&id) {
+            Some(&(sec, n)) if sec == now => n,
+            _ => 0,
+        };
+        let burst_units = u32::try_from(units).unwrap_or(u32::MAX);
+        if record.burst_per_sec > 0 && units > 0 && burst.saturating_add(burst_units) > record.burst_per_sec {
+            return Err(ApiKeyError::BurstExceeded { limit: record.burst_per_sec, retry_after_secs: 1 });
+        }
+
+        let used = match inner.daily.get(&id) {
+            Some(&(d, n)) if d == day => n,
+            _ => self.load_day(&id, day)?,
+        };
+        if record.daily_quota > 0 && units > 0 && used.saturating_add(units) > record.daily_quota {
+            inner.daily.insert(id, (day, used));
+            return Err(ApiKeyError::QuotaExhausted { limit: record.daily_quota, used, reset_at });
+        }
+
+        if units == 0 {
+            return Ok(Admission { key_id: id, limit: record.daily_quota, used, reset_at });
+        }
+        self.bump_usage(&id, now / SECS_PER_HOUR, units)?;
+        inner.burst.insert(id.clone(), (now, burst.saturating_add(burst_units)));
+        inner.daily.insert(id.clone(), (day, used + units));
+
+        Ok(Admission { key_id: id, limit: record.daily_quota, used: used + units, reset_at })
+    }
+
+    // ── Usage ────────────────────────────────────────────────────────────────
+
+    /// Hourly request counts for a key, oldest first.
+    pub fn usage(&self, id: &str) -> ApiKeyResult<UsageReport> {
+        if !self.inner.lock().records.contains_key(id) {
+            return Err(ApiKeyError::NotFound(id.to_string()));
+        }
+        let hourly: Vec<HourlyUsage> = self.scan_usage(id, 0)?
+            .into_iter()
+            .map(|(hour, requests)| HourlyUsage { hour_start: hour * SECS_PER_HOUR, requests })
+            .collect();
+        let today_start = self.clock.now_secs() / SECS_PER_DAY * SECS_PER_DAY;
+        Ok(UsageReport {
+            key_id: id.to_string(),
+            total:  hourly.iter().map(|h| h.requests).sum(),
+            today:  hourly.iter().filter(|h| h.hour_start >= today_start).map(|h| h.requests).sum(),
+            hourly,
+        })
+    }
+
+    // ── Private helpers ──────────────────────────────────────────────────────
+
+    fn put_record(&self, record: &ApiKeyRecord) -> ApiKeyResult<()> {
+        let value = bincode::serialize(record)
+            .map_err(|e| ApiKeyError::Storage(format!("bincode serialise: {}", e)))?;
+        let cf = self.db.cf_handle(CF_KEYS).ok_or_else(|| missing_cf(CF_KEYS))?;
+        self.db.put_cf(&cf, record.id.as_bytes(), value)
+            .map_err(|e| ApiKeyError::Storage(e.to_string()))
+    }
+
+    fn bump_usage(&self, id: &str, hour: u64, units: u64) -> ApiKeyResult<()> {
+        let cf = self.db.cf_handle(CF_USAGE).ok_or_else(|| missing_cf(CF_USAGE))?;
+        let key = usage_key(id, hour);
+        let current = self.db.get_cf(&cf, &key)
+            .map_err(|e| ApiKeyError::Storage(e.to_string()))?
+            .map(|v| decode_count(&v))
+            .unwrap_or(0);
+        self.db.put_cf(&cf, key, (current + units).to_be_bytes())
+            .map_err(|e| ApiKeyError::Storage(e.to_string()))
+    }
+
+    /// Requests admitted during UTC day `day`, read back from disk.
+    fn load_day(&self, id: &str, day: u64) -> ApiKeyResult<u64> {
+        let first_hour = day * 24;
+        Ok(self.scan_usage(id, first_hour)?
+            .into_iter()
+            .take_while(|(hour, _)| *hour < first_hour + 24)
+            .map(|(_, n)| n)
+            .sum())
+    }
+
+    /// `(hour, count)` entries for `id` from `from_hour` onwards.
+    fn scan_usage(&self, id: &str, from_hour: u64) -> ApiKeyResult<Vec<(u64, u64)>> {
+        let cf = self.db.cf_handle(CF_USAGE).ok_or_else(|| missing_cf(CF_USAGE))?;
+        let start = usage_key(id, from_hour);
+        let prefix_len = id.len() + 1;
+        let mut out = Vec::new();
+        for item in self.db.iterator_cf(&cf, rocksdb::IteratorMode::From(&start, rocksdb::Direction::Forward)) {
+            let (key, value) = item.map_err(|e| ApiKeyError::Storage(e.to_string()))?;
+            if key.len() != prefix_len + 8 || key[..prefix_len] != start[..prefix_len] {
+                break;
+            }
+            let hour = u64::from_be_bytes(key[prefix_len..].try_into().unwrap_or([0u8; 8]));
+            out.push((hour, decode_count(&value)));
+        }
+        Ok(out)
+    }
+}
+
+impl Default for ApiKeyRegistry {
+    fn default() -> Self { Self::new() }
+}
+
+fn missing_cf(name: &str) -> ApiKeyError {
+    ApiKeyError::Storage(format!("column family '{}' missing", name))
+}
+
+fn hash_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+fn usage_key(id: &str, hour: u64) -> Vec<u8> {
+    let mut key = Vec::with_capacity(id.len() + 9);
+    key.extend_from_slice(id.as_bytes());
+    key.push(0);
+    key.extend_from_slice(&hour.to_be_bytes());
+    key
+}
+
+fn decode_count(v: &[u8]) -> u64 {
+    v.try_into().map(u64::from_be_bytes).unwrap_or(0)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-01-01T10:00:00Z
+    const T0: u64 = 1_704_103_200;
+
+    fn spec(groups: Vec<RouteGroup>, daily_quota: u64) -> ApiKeySpec {
+        ApiKeySpec { name: "team-a".into(), groups, daily_quota, burst_per_sec: 0, expires_at: None }
+    }
+
+    fn registry(clock: &Arc<ManualClock>) -> ApiKeyRegistry {
+        ApiKeyRegistry::new().with_clock(Arc::clone(clock) as Arc<dyn Clock>)
+    }
+
+    #[test]
+    fn read_only_key_rejected_for_tx() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let reg = registry(&clock);
+        let issued = reg.create(spec(vec![RouteGroup::Read], 0)).unwrap();
+        assert!(reg.authorize(Some(&issued.key), RouteGroup::Read).is_ok());
+        assert_eq!(
+            reg.authorize(Some(&issued.key), RouteGroup::Tx),
+            Err(ApiKeyError::RouteNotAllowed(RouteGroup::Tx)),
+        );
+        assert_eq!(reg.authorize(Some("bleep_nope"), RouteGroup::Read), Err(ApiKeyError::Unknown));
+        assert_eq!(reg.authorize(None, RouteGroup::Read), Err(ApiKeyError::Missing));
+    }
+
+    #[test]
+    fn quota_exhausts_and_resets_at_day_boundary() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let reg = registry(&clock);
+        let issued = reg.create(spec(vec![RouteGroup::Read], 3)).unwrap();
+        for _ in 0..3 {
+            reg.authorize(Some(&issued.key), RouteGroup::Read).unwrap();
+        }
+        let reset_at = (T0 / SECS_PER_DAY + 1) * SECS_PER_DAY;
+        let err = reg.authorize(Some(&issued.key), RouteGroup::Read).unwrap_err();
+        assert_eq!(err, ApiKeyError::QuotaExhausted { limit: 3, used: 3, reset_at });
+        assert_eq!(err.http_status(), 429);
+
+        clock.set(reset_at - 1);
+        assert!(reg.authorize(Some(&issued.key), RouteGroup::Read).is_err());
+        clock.set(reset_at);
+        let ok = reg.authorize(Some(&issued.key), RouteGroup::Read).unwrap();
+        assert_eq!(ok.used, 1);
+    }
+
+    #[test]
+    fn revoked_and_expired_keys_fail() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let reg = registry(&clock);
+        let issued = reg.create(spec(vec![RouteGroup::Read], 0)).unwrap();
+        reg.authorize(Some(&issued.key), RouteGroup::Read).unwrap();
+        reg.revoke(&issued.record.id).unwrap();
+        assert_eq!(reg.authorize(Some(&issued.key), RouteGroup::Read), Err(ApiKeyError::Revoked));
+
+        let expiring = reg.create(ApiKeySpec { expires_at: Some(T0 + 60), ..spec(vec![RouteGroup::Read], 0) }).unwrap();
+        assert!(reg.authorize(Some(&expiring.key), RouteGroup::Read).is_ok());
+        clock.advance(60);
+        assert_eq!(reg.authorize(Some(&expiring.key), RouteGroup::Read), Err(ApiKeyError::Expired));
+    }
+
+    #[test]
+    fn burst_limit_per_second() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let reg = registry(&clock);
+        let issued = reg.create(ApiKeySpec { burst_per_sec: 2, ..spec(vec![RouteGroup::Read], 0) }).unwrap();
+        reg.authorize(Some(&issued.key), RouteGroup::Read).unwrap();
+        reg.authorize(Some(&issued.key), RouteGroup::Read).unwrap();
+        assert!(matches!(
+            reg.authorize(Some(&issued.key), RouteGroup::Read),
+            Err(ApiKeyError::BurstExceeded { limit: 2, .. }),
+        ));
+        clock.advance(1);
+        assert!(reg.authorize(Some(&issued.key), RouteGroup::Read).is_ok());
+    }
+
+    #[test]
+    fn usage_matches_requests_and_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("bleep-api-keys-reopen-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let clock = Arc::new(ManualClock::new(T0));
+        let id;
+        let key;
+        {
+            let reg = ApiKeyRegistry::open(&dir).unwrap().with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+            let issued = reg.create(spec(vec![RouteGroup::Read], 10)).unwrap();
+            id = issued.record.id.clone();
+            key = issued.key;
+            for _ in 0..4 {
+                reg.authorize(Some(&key), RouteGroup::Read).unwrap();
+            }
+            clock.advance(SECS_PER_HOUR);
+            for _ in 0..3 {
+                reg.authorize(Some(&key), RouteGroup::Read).unwrap();
+            }
+            // Rejections are not counted.
+            assert!(reg.authorize(Some(&key), RouteGroup::Tx).is_err());
+        }
+
+        let reg = ApiKeyRegistry::open(&dir).unwrap().with_clock(Arc::clone(&clock) as Arc<dyn Clock>);
+        let usage = reg.usage(&id).unwrap();
+        assert_eq!(usage.hourly, vec![
+            HourlyUsage { hour_start: T0, requests: 4 },
+            HourlyUsage { hour_start: T0 + SECS_PER_HOUR, requests: 3 },
+        ]);
+        assert_eq!(usage.total, 7);
+        assert_eq!(usage.today, 7);
+        // The daily quota picks up where it left off.
+        assert_eq!(reg.authorize(Some(&key), RouteGroup::Read).unwrap().used, 8);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,587 @@
+//! bleep-auth/src/audit_trail.rs
+//! Tamper-evident audit trail for security-sensitive node operations.
+//!
+//! [`crate::AuditLog`] records authentication events; this trail records
+//! what operators *do*: admin RPC calls, validator key rotations, governance
+//! executions, asset recoveries, AI model reloads and safe-mode changes.
+//!
+//! Every record carries the hash of the record before it:
+//! ```text
+//! hash = SHA3-256( prev_hash || seq_be8 || timestamp_ms_be8 || actor || 0x00
+//!                  || action || 0x00 || params_digest || 0x00 || outcome )
+//! ```
+//! so editing, deleting or reordering any stored record breaks the chain at
+//! that point.  [`AuditTrailStore::verify`] walks the chain and reports the
+//! first break.  Parameters are stored only as a SHA3-256 digest of their
+//! JSON form; the operator keeps the plaintext.
+//!
+//! ## Hot path
+//! Call sites hold a cloneable [`AuditTrail`].  `append` digests the
+//! parameters and `try_send`s onto a bounded channel; one writer thread
+//! assigns sequence numbers, chains and persists.  When the channel is full
+//! the record is dropped and counted in [`AuditTrail::dropped`] — auditing
+//! never blocks the operation being audited.
+//!
+//! ## Column-family layout
+//! ```
+//! CF: "audit_trail"
+//!   key:   seq as 8-byte big-endian
+//!   value: bincode-serialised AuditRecord
+//! ```
+
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode, Options, DB};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tokio::sync::{mpsc, oneshot};
+
+const CF_TRAIL: &str = "audit_trail";
+
+/// `prev_hash` of the first record.
+pub const AUDIT_GENESIS_HASH: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Pending records buffered between call sites and the writer.
+pub const AUDIT_CHANNEL_CAPACITY: usize = 4_096;
+
+/// Largest page `AuditTrailStore::query` returns.
+pub const AUDIT_MAX_PAGE: usize = 500;
+
+// ── Types ─────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// A call to an `/rpc/admin/*` or other operator-only endpoint.
+    AdminRpc,
+    /// A validator's signing key was registered or replaced.
+    ValidatorKeyRotation,
+    /// A governance proposal was executed.
+    GovernanceExecution,
+    /// Assets were recovered or force-unfrozen.
+    AssetRecovery,
+    /// An AI model was reloaded.
+    AiModelReload,
+    /// A validator entered or left partition safe mode.
+    SafeMode,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::AdminRpc             => "admin_rpc",
+            AuditAction::ValidatorKeyRotation => "validator_key_rotation",
+            AuditAction::GovernanceExecution  => "governance_execution",
+            AuditAction::AssetRecovery        => "asset_recovery",
+            AuditAction::AiModelReload        => "ai_model_reload",
+            AuditAction::SafeMode             => "safe_mode",
+        }
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin_rpc"              => Ok(AuditAction::AdminRpc),
+            "validator_key_rotation" => Ok(AuditAction::ValidatorKeyRotation),
+            "governance_execution"   => Ok(AuditAction::GovernanceExecution),
+            "asset_recovery"         => Ok(AuditAction::AssetRecovery),
+            "ai_model_reload"        => Ok(AuditAction::AiModelReload),
+            "safe_mode"              => Ok(AuditAction::SafeMode),
+            other => Err(format!("unknown audit action '{}'", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    Denied,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+            AuditOutcome::Denied  => "denied",
+        }
+    }
+}
+
+/// One chained audit record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq:           u64,
+    pub timestamp_ms:  u64,
+    pub actor:         String,
+    pub action:        AuditAction,
+    /// Hex SHA3-256 of the operation's parameters (see [`params_digest`]).
+    pub params_digest: String,
+    pub outcome:       AuditOutcome,
+    pub prev_hash:     String,
+    pub hash:          String,
+}
+
+impl AuditRecord {
+    /// Recompute this record's chained hash from its contents.
+    pub fn compute_hash(&self) -> String {
+        let mut h = Sha3_256::new();
+        h.update(self.prev_hash.as_bytes());
+        h.update(self.seq.to_be_bytes());
+        h.update(self.timestamp_ms.to_be_bytes());
+        h.update(self.actor.as_bytes());
+        h.update(b"\x00");
+        h.update(self.action.as_str().as_bytes());
+        h.update(b"\x00");
+        h.update(self.params_digest.as_bytes());
+        h.update(b"\x00");
+        h.update(self.outcome.as_str().as_bytes());
+        hex::encode(h.finalize())
+    }
+}
+
+/// Hex SHA3-256 of the JSON serialisation of `params`.
+pub fn params_digest<T: Serialize + ?Sized>(params: &T) -> String {
+    let bytes = serde_json::to_vec(params).unwrap_or_default();
+    hex::encode(Sha3_256::digest(&bytes))
+}
+
+/// Where and why the chain stopped verifying.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditBreak {
+    pub seq:    u64,
+    pub reason: String,
+}
+
+/// Result of walking the whole chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditVerification {
+    /// Records checked (up to and excluding the first break).
+    pub records:     u64,
+    /// Hash of the last record that verified.
+    pub chain_tip:   String,
+    pub first_break: Option<AuditBreak>,
+}
+
+impl AuditVerification {
+    pub fn is_intact(&self) -> bool {
+        self.first_break.is_none()
+    }
+}
+
+/// Filter for `GET /rpc/admin/audit`.  `from`/`to` are unix milliseconds
+/// (inclusive); `cursor` is the first sequence number to consider.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub from:   Option<u64>,
+    #[serde(default)]
+    pub to:     Option<u64>,
+    #[serde(default)]
+    pub action: Option<AuditAction>,
+    #[serde(default)]
+    pub cursor: Option<u64>,
+    #[serde(default)]
+    pub limit:  Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPage {
+    pub records:     Vec<AuditRecord>,
+    /// Pass as `cursor` to fetch the next page; absent on the last page.
+    pub next_cursor: Option<u64>,
+}
+
+// ── Store ─────────────────────────────────────────────────────────────────────
+
+/// RocksDB-backed chain of `AuditRecord`s.
+pub struct AuditTrailStore {
+    db:  DB,
+    /// (next seq, hash of the last record)
+    tip: Mutex<(u64, String)>,
+}
+
+impl AuditTrailStore {
+    /// Open (or create) the trail at `path`; the chain tip is restored from
+    /// the last stored record.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+
+        let cfs = vec![ColumnFamilyDescriptor::new(CF_TRAIL, Options::default())];
+        let db = DB::open_cf_descriptors(&opts, path, cfs)
+            .map_err(|e| format!("AuditTrailStore open failed: {}", e))?;
+
+        let tip = {
+            let cf = db.cf_handle(CF_TRAIL).ok_or_else(missing_cf)?;
+            let last = db.iterator_cf(&cf, IteratorMode::End).next();
+            match last {
+                Some(item) => {
+                    let (_, value) = item.map_err(|e| e.to_string())?;
+                    let rec: AuditRecord = bincode::deserialize(&value)
+                        .map_err(|e| format!("last audit record unreadable: {}", e))?;
+                    (rec.seq + 1, rec.hash)
+                }
+                None => (0, AUDIT_GENESIS_HASH.to_string()),
+            }
+        };
+
+        log::info!("[AuditTrailStore] Opened — next_seq={}", tip.0);
+        Ok(Self { db, tip: Mutex::new(tip) })
+    }
+
+    /// Open a trail in a fresh temp dir.  Used in tests and devnet.
+    pub fn new() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        let tmp = std::env::temp_dir()
+            .join(format!("bleep-audit-trail-{}-{}", std::process::id(), nanos));
+        Self::open(tmp).expect("failed to open temp audit trail")
+    }
+
+    /// Chain and persist one record synchronously.  Production call sites
+    /// go through [`AuditTrail`], whose writer thread calls this.
+    pub fn append(
+        &self,
+        timestamp_ms:  u64,
+        actor:         &str,
+        action:        AuditAction,
+        params_digest: String,
+        outcome:       AuditOutcome,
+    ) -> Result<AuditRecord, String> {
+        let mut tip = self.tip.lock();
+        let mut rec = AuditRecord {
+            seq: tip.0,
+            timestamp_ms,
+            actor: actor.to_string(),
+            action,
+            params_digest,
+            outcome,
+            prev_hash: tip.1.clone(),
+            hash: String::new(),
+        };
+        rec.hash = rec.compute_hash();
+
+        self.put(&rec)?;
+        *tip = (rec.seq + 1, rec.hash.clone());
+        Ok(rec)
+    }
+
+    /// Number of records appended.
+    pub fn len(&self) -> u64 {
+        self.tip.lock().0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, seq: u64) -> Result<Option<AuditRecord>, String> {
+        let cf = self.db.cf_handle(CF_TRAIL).ok_or_else(missing_cf)?;
+        match self.db.get_cf(&cf, seq.to_be_bytes()).map_err(|e| e.to_string())? {
+            Some(v) => bincode::deserialize(&v).map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    /// Page through records matching `q`, in sequence order.
+    pub fn query(&self, q: &AuditQuery) -> Result<AuditPage, String> {
+        let limit = q.limit.unwrap_or(100).clamp(1, AUDIT_MAX_PAGE);
+        let start = q.cursor.unwrap_or(0).to_be_bytes();
+        let cf = self.db.cf_handle(CF_TRAIL).ok_or_else(missing_cf)?;
+
+        let mut records = Vec::new();
+        let mut next_cursor = None;
+        for item in self.db.iterator_cf(&cf, IteratorMode::From(&start, rocksdb::Direction::Forward)) {
+            let (_, value) = item.map_err(|e| e.to_string())?;
+            let rec: AuditRecord = match bincode::deserialize(&value) {
+                Ok(r) => r,
+                Err(e) => {
+                    log::warn!("[AuditTrailStore] Skipping undeserializable record: {}", e);
+                    continue;
+                }
+            };
+            if q.from.is_some_and(|from| rec.timestamp_ms < from)
+                || q.to.is_some_and(|to| rec.timestamp_ms > to)
+                || q.action.is_some_and(|a| rec.action != a)
+            {
+                continue;
+            }
+            if records.len() == limit {
+                next_cursor = Some(rec.seq);
+                break;
+            }
+            records.push(rec);
+        }
+        Ok(AuditPage { records, next_cursor })
+    }
+
+    /// Walk the chain from genesis and report the first record whose key,
+    /// sequence number, back-link or hash does not check out.
+    pub fn verify(&self) -> Result<AuditVerification, String> {
+        let cf = self.db.cf_handle(CF_TRAIL).ok_or_else(missing_cf)?;
+        let mut expected_seq = 0u64;
+        let mut prev = AUDIT_GENESIS_HASH.to_string();
+
+        let broken = |seq: u64, prev: String, reason: String| AuditVerification {
+            records:     seq,
+            chain_tip:   prev,
+            first_break: Some(AuditBreak { seq, reason }),
+        };
+
+        for item in self.db.iterator_cf(&cf, IteratorMode::Start) {
+            let (key, value) = item.map_err(|e| e.to_string())?;
+            let key_seq = match <[u8; 8]>::try_from(&key[..]) {
+                Ok(k) => u64::from_be_bytes(k),
+                Err(_) => return Ok(broken(expected_seq, prev, "malformed key".into())),
+            };
+            if key_seq != expected_seq {
+                return Ok(broken(expected_seq, prev, format!("record missing (next key is {})", key_seq)));
+            }
+            let rec: AuditRecord = match bincode::deserialize(&value) {
+                Ok(r) => r,
+                Err(e) => return Ok(broken(expected_seq, prev, format!("undecodable: {}", e))),
+            };
+            if rec.seq != expected_seq {
+                return Ok(broken(expected_seq, prev, format!("stored seq is {}", rec.seq)));
+            }
+            if rec.prev_hash != prev {
+                return Ok(broken(expected_seq, prev, "prev_hash does not match preceding record".into()));
+            }
+            if rec.compute_hash() != rec.hash {
+                return Ok(broken(expected_seq, prev, "hash does not match contents".into()));
+            }
+            prev = rec.hash;
+            expected_seq += 1;
+        }
+
+        Ok(AuditVerification { records: expected_seq, chain_tip: prev, first_break: None })
+    }
+
+    fn put(&self, rec: &AuditRecord) -> Result<(), String> {
+        let cf = self.db.cf_handle(CF_TRAIL).ok_or_else(missing_cf)?;
+        let value = bincode::serialize(rec).map_err(|e| format!("bincode serialise: {}", e))?;
+        self.db.put_cf(&cf, rec.seq.to_be_bytes(), value).map_err(|e| format!("RocksDB write: {}", e))
+    }
+}
+
+impl Default for AuditTrailStore {
+    fn default() -> Self { Self::new() }
+}
+
+fn missing_cf() -> String {
+    format!("column family '{}' missing", CF_TRAIL)
+}
+
+// ── Non-blocking handle ───────────────────────────────────────────────────────
+
+enum WriterMsg {
+    Record {
+        timestamp_ms:  u64,
+        actor:         String,
+        action:        AuditAction,
+        params_digest: String,
+        outcome:       AuditOutcome,
+    },
+    Flush(oneshot::Sender<()>),
+}
+
+/// Cloneable, non-blocking front end to an `AuditTrailStore`.
+#[derive(Clone)]
+pub struct AuditTrail {
+    tx:      mpsc::Sender<WriterMsg>,
+    store:   Arc<AuditTrailStore>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl AuditTrail {
+    /// Start the writer thread for `store` with a channel of `capacity`.
+    pub fn spawn(store: Arc<AuditTrailStore>, capacity: usize) -> Self {
+        let (tx, mut rx) = mpsc::channel::<WriterMsg>(capacity.max(1));
+        let writer_store = Arc::clone(&store);
+        std::thread::Builder::new()
+            .name("bleep-audit-writer".into())
+            .spawn(move || {
+                while let Some(msg) = rx.blocking_recv() {
+                    match msg {
+                        WriterMsg::Record { timestamp_ms, actor, action, params_digest, outcome } => {
+                            if let Err(e) = writer_store.append(timestamp_ms, &actor, action, params_digest, outcome) {
+                                log::error!("[AuditTrail] Failed to persist {} by {}: {}", action.as_str(), actor, e);
+                            }
+                        }
+                        WriterMsg::Flush(ack) => {
+                            let _ = ack.send(());
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn audit writer thread");
+        Self { tx, store, dropped: Arc::new(AtomicU64::new(0)) }
+    }
+
+    /// Queue a record.  Never blocks; returns `false` (and counts a drop)
+    /// when the writer is overwhelmed or gone.
+    pub fn append<P: Serialize + ?Sized>(
+        &self,
+        actor:   &str,
+        action:  AuditAction,
+        params:  &P,
+        outcome: AuditOutcome,
+    ) -> bool {
+        let msg = WriterMsg::Record {
+            timestamp_ms: now_ms(),
+            actor: actor.to_string(),
+            action,
+            params_digest: params_digest(params),
+            outcome,
+        };
+        match self.tx.try_send(msg) {
+            Ok(()) => true,
+            Err(_) => {
+                let n = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                log::warn!("[AuditTrail] Dropped {} record by {} ({} dropped total)", action.as_str(), actor, n);
+                false
+            }
+        }
+    }
+
+    /// Wait until every record queued before this call is persisted.
+    pub async fn flush(&self) {
+        let (ack, done) = oneshot::channel();
+        if self.tx.send(WriterMsg::Flush(ack)).await.is_ok() {
+            let _ = done.await;
+        }
+    }
+
+    /// Records dropped because the channel was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn store(&self) -> &Arc<AuditTrailStore> {
+        &self.store
+    }
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn append(store: &AuditTrailStore, actor: &str, action: AuditAction, ts: u64) -> AuditRecord {
+        store
+            .append(ts, actor, action, params_digest(&serde_json::json!({ "ts": ts })), AuditOutcome::Success)
+            .unwrap()
+    }
+
+    #[test]
+    fn records_chain_and_verify() {
+        let store = AuditTrailStore::new();
+        let a = append(&store, "admin", AuditAction::AdminRpc, 1_000);
+        let b = append(&store, "gov", AuditAction::GovernanceExecution, 2_000);
+        let c = append(&store, "ai", AuditAction::AiModelReload, 3_000);
+
+        assert_eq!(a.prev_hash, AUDIT_GENESIS_HASH);
+        assert_eq!(b.prev_hash, a.hash);
+        assert_eq!(c.prev_hash, b.hash);
+
+        let v = store.verify().unwrap();
+        assert!(v.is_intact());
+        assert_eq!(v.records, 3);
+        assert_eq!(v.chain_tip, c.hash);
+
+        let page = store.query(&AuditQuery {
+            from: Some(1_500),
+            action: Some(AuditAction::GovernanceExecution),
+            ..Default::default()
+        }).unwrap();
+        assert_eq!(page.records, vec![b]);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn mutated_record_detected_at_its_index() {
+        let store = AuditTrailStore::new();
+        for i in 0..5 {
+            append(&store, "admin", AuditAction::AdminRpc, 1_000 + i);
+        }
+
+        // Rewrite the actor of record 2 in place.
+        let mut forged = store.get(2).unwrap().unwrap();
+        forged.actor = "someone-else".into();
+        store.put(&forged).unwrap();
+        let v = store.verify().unwrap();
+        assert_eq!(v.first_break.as_ref().map(|b| b.seq), Some(2));
+        assert_eq!(v.records, 2);
+
+        // Re-hashing the forgery moves the break to the next back-link.
+        forged.hash = forged.compute_hash();
+        store.put(&forged).unwrap();
+        assert_eq!(store.verify().unwrap().first_break.map(|b| b.seq), Some(3));
+    }
+
+    #[tokio::test]
+    async fn concurrent_appends_keep_chain_consistent() {
+        let trail = AuditTrail::spawn(Arc::new(AuditTrailStore::new()), AUDIT_CHANNEL_CAPACITY);
+
+        let mut tasks = Vec::new();
+        for t in 0..8 {
+            let trail = trail.clone();
+            tasks.push(tokio::spawn(async move {
+                for i in 0..50 {
+                    assert!(trail.append(&format!("op{t}"), AuditAction::AdminRpc, &(t, i), AuditOutcome::Success));
+                }
+            }));
+        }
+        for task in tasks {
+            task.await.unwrap();
+        }
+        trail.flush().await;
+
+        assert_eq!(trail.dropped(), 0);
+        assert_eq!(trail.store().len(), 400);
+        let v = trail.store().verify().unwrap();
+        assert!(v.is_intact(), "{:?}", v.first_break);
+        assert_eq!(v.records, 400);
+    }
+
+    #[tokio::test]
+    async fn full_channel_drops_and_counts() {
+        let store = Arc::new(AuditTrailStore::new());
+        // Hold the tip lock so the writer stalls on its first record.
+        let guard = store.tip.lock();
+        let trail = AuditTrail::spawn(Arc::clone(&store), 1);
+        let results: Vec<bool> = (0..10)
+            .map(|i| trail.append("admin", AuditAction::AdminRpc, &i, AuditOutcome::Success))
+            .collect();
+        drop(guard);
+        trail.flush().await;
+
+        let accepted = results.iter().filter(|ok| **ok).count() as u64;
+        assert!(trail.dropped() > 0);
+        assert_eq!(accepted + trail.dropped(), 10);
+        assert_eq!(store.len(), accepted);
+        assert!(store.verify().unwrap().is_intact());
+    }
+}
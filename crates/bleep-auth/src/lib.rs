@@ -10,6 +10,8 @@
 //   validator_binding — Kyber1024 challenge/response proof-of-possession.
 //   audit           — Merkle-chained append-only log; tamper detection.
 //   rate_limiter    — Fixed-window token bucket per (identity, action).
+//   api_keys        — Per-consumer RPC keys; quotas; hourly usage in RocksDB.
+//   audit_trail     — Hash-chained record of operator actions; async writer.
 //
 // Entry point: `AuthService::new(jwt_secret)`.
 // ============================================================================
@@ -347,3 +349,13 @@ mod tests {
 // ── Hardening-phase modules ────────────────────────────────────────────────────
 pub mod audit_store;
 pub use audit_store::{AuditLogStore, StoredAuditEntry, AUDIT_CACHE_SIZE};
+pub mod audit_trail;
+pub use audit_trail::{
+    AuditAction, AuditOutcome, AuditPage, AuditQuery, AuditRecord, AuditTrail, AuditTrailStore,
+    AuditVerification, AUDIT_CHANNEL_CAPACITY,
+};
+pub mod api_keys;
+pub use api_keys::{
+    ApiKeyError, ApiKeyRecord, ApiKeyRegistry, ApiKeySpec, Admission, Clock, IssuedApiKey,
+    ManualClock, RouteGroup, SystemClock, UsageReport,
+};
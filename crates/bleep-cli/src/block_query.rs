@@ -0,0 +1,241 @@
+//! # Block queries
+//!
+//! `block get` / `head` / `range` / `verify` read the node's block archive
+//! (`<data-dir>/bleep-blocks`, see `bleep_consensus::block_store`) directly,
+//! so they work with the node stopped and show whole blocks rather than the
+//! RPC summary.
+//!
+//! A block is named by height or by its 64-hex-digit hash; hashes are
+//! resolved by scanning the archive.  Output is `BlockView` as pretty JSON,
+//! or with `--raw` the hex of the bincode-encoded `Block`.
+//!
+//! `verify` checks a block's signature against the key embedded in the
+//! block itself; the archive holds no validator set, so whether that key
+//! belongs to a validator is not checked, and the report says so.
+
+use serde::Serialize;
+
+use bleep_consensus::block_store::{BlockStore, StoredBlock};
+use bleep_core::block::{Block, Transaction};
+use bleep_core::block_validation::BlockValidator;
+
+/// How a block was named on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlockRef {
+    Index(u64),
+    Hash(String),
+}
+
+impl BlockRef {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if let Ok(index) = s.parse::<u64>() {
+            return Ok(BlockRef::Index(index));
+        }
+        let hex = s.strip_prefix("0x").unwrap_or(s);
+        if hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(BlockRef::Hash(hex.to_ascii_lowercase()));
+        }
+        Err(format!("'{}' is neither a block height nor a 64-digit block hash", s))
+    }
+}
+
+impl std::fmt::Display for BlockRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockRef::Index(i) => write!(f, "at height {}", i),
+            BlockRef::Hash(h) => write!(f, "with hash {}", h),
+        }
+    }
+}
+
+pub fn find(store: &BlockStore, block: &BlockRef) -> Result<Option<StoredBlock>, String> {
+    match block {
+        BlockRef::Index(index) => store.get(*index),
+        BlockRef::Hash(hash) => {
+            for height in store.heights()?.into_iter().rev() {
+                if let Some(stored) = store.get(height)? {
+                    if stored.block.compute_hash() == *hash {
+                        return Ok(Some(stored));
+                    }
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+pub fn head(store: &BlockStore) -> Result<Option<StoredBlock>, String> {
+    match store.tip()? {
+        Some(tip) => store.get(tip),
+        None => Ok(None),
+    }
+}
+
+/// Blocks `from..=to`; every height must be present.
+pub fn range(store: &BlockStore, from: u64, to: u64) -> Result<Vec<StoredBlock>, String> {
+    if from > to {
+        return Err(format!("empty range {}..={}", from, to));
+    }
+    store.range(from, to)
+}
+
+/// JSON shape of one block.
+#[derive(Debug, Serialize)]
+pub struct BlockView<'a> {
+    pub index:         u64,
+    pub hash:          String,
+    pub parent_hash:   &'a str,
+    pub timestamp:     u64,
+    pub epoch_id:      u64,
+    pub shard_id:      u64,
+    pub merkle_root:   &'a str,
+    pub state_root:    &'a str,
+    pub receipts_root: &'a str,
+    pub gas_used:      u64,
+    /// Hex public key the block is signed with; `None` for genesis.
+    pub signer:        Option<String>,
+    pub transactions:  &'a [Transaction],
+}
+
+pub fn view(stored: &StoredBlock) -> BlockView<'_> {
+    let block = &stored.block;
+    BlockView {
+        index:         block.index,
+        hash:          block.compute_hash(),
+        parent_hash:   &block.previous_hash,
+        timestamp:     block.timestamp,
+        epoch_id:      block.epoch_id,
+        shard_id:      block.shard_id,
+        merkle_root:   &block.merkle_root,
+        state_root:    &stored.state_root,
+        receipts_root: &stored.receipts_root,
+        gas_used:      stored.gas_used,
+        signer:        block.signer_public_key().map(hex::encode),
+        transactions:  &block.transactions,
+    }
+}
+
+/// Hex of the bincode encoding of `block`.
+pub fn raw(block: &Block) -> Result<String, String> {
+    bincode::serialize(block).map(hex::encode).map_err(|e| format!("encode block {}: {}", block.index, e))
+}
+
+/// Outcome of `verify`.
+#[derive(Debug, Serialize)]
+pub struct Verification {
+    pub index:            u64,
+    pub hash:             String,
+    /// Hex key the signature checks use: the one the block carries, not
+    /// one looked up in the validator set.  `None` for an unsigned block.
+    pub signer:           Option<String>,
+    /// `BlockValidator::validate_block` against `signer`.
+    pub valid:            bool,
+    /// Full SPHINCS+ check of the header; `None` for an unsigned block.
+    pub header_signature: Option<bool>,
+    /// Whether `previous_hash` is the stored parent's hash; `None` at
+    /// height 0 or when the parent is not archived.
+    pub links_to_parent:  Option<bool>,
+}
+
+impl Verification {
+    pub fn passed(&self) -> bool {
+        self.valid && self.header_signature != Some(false) && self.links_to_parent != Some(false)
+    }
+}
+
+/// Re-validate `block`; `None` if it is not archived.
+pub fn verify(store: &BlockStore, block: &BlockRef) -> Result<Option<Verification>, String> {
+    let Some(stored) = find(store, block)? else { return Ok(None) };
+    let block = &stored.block;
+    let index = block.index;
+    let signer = block.signer_public_key().unwrap_or_default();
+    let parent = match index.checked_sub(1) {
+        Some(parent) => store.get(parent)?,
+        None => None,
+    };
+    Ok(Some(Verification {
+        index,
+        hash:             block.compute_hash(),
+        signer:           block.signer_public_key().map(hex::encode),
+        valid:            BlockValidator::validate_block(block, signer),
+        header_signature: block.signer_public_key().map(|_| block.verify_header_signature()),
+        links_to_parent:  parent.map(|p| BlockValidator::validate_block_link(&p.block, block)),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::tx_signer::generate_tx_keypair;
+
+    fn archive(dir: &std::path::Path, blocks: usize) -> BlockStore {
+        let store = BlockStore::open(dir).unwrap();
+        let (pk, sk) = generate_tx_keypair();
+        let mut tip = Block::genesis();
+        store.put(&StoredBlock::adopted(tip.clone(), 0)).unwrap();
+        for _ in 0..blocks {
+            let mut block = Block::new(tip.index + 1, vec![], tip.compute_hash());
+            block.sign_block_with_pk(&sk, &pk).unwrap();
+            store.put(&StoredBlock::adopted(block.clone(), 0)).unwrap();
+            tip = block;
+        }
+        store
+    }
+
+    #[test]
+    fn lookups_by_height_hash_and_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = archive(dir.path(), 3);
+        let second = store.get(2).unwrap().unwrap();
+        let hash = second.block.compute_hash();
+
+        let by_hash = find(&store, &BlockRef::parse(&format!("0x{}", hash.to_uppercase())).unwrap()).unwrap().unwrap();
+        assert_eq!(by_hash.height(), 2);
+        assert_eq!(find(&store, &BlockRef::parse("2").unwrap()).unwrap().unwrap().block.compute_hash(), hash);
+        assert!(find(&store, &BlockRef::Index(9)).unwrap().is_none());
+        assert!(find(&store, &BlockRef::Hash("0".repeat(64))).unwrap().is_none());
+        assert!(BlockRef::parse("latest").is_err());
+
+        assert_eq!(head(&store).unwrap().unwrap().height(), 3);
+        assert_eq!(range(&store, 1, 3).unwrap().len(), 3);
+        assert!(range(&store, 3, 1).is_err());
+        assert!(range(&store, 2, 5).is_err());
+
+        let json = serde_json::to_value(view(&second)).unwrap();
+        assert_eq!(json["hash"], hash);
+        assert_eq!(json["parent_hash"], store.get(1).unwrap().unwrap().block.compute_hash());
+        assert!(json["transactions"].is_array());
+        let decoded: Block = bincode::deserialize(&hex::decode(raw(&second.block).unwrap()).unwrap()).unwrap();
+        assert_eq!(decoded.compute_hash(), hash);
+    }
+
+    #[test]
+    fn verify_flags_a_broken_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = archive(dir.path(), 2);
+        let ok = verify(&store, &BlockRef::Index(2)).unwrap().unwrap();
+        assert!(ok.passed(), "{:?}", ok);
+        assert_eq!(ok.links_to_parent, Some(true));
+        assert_eq!(ok.signer, store.get(2).unwrap().unwrap().block.signer_public_key().map(hex::encode));
+        assert_eq!(verify(&store, &BlockRef::Hash(ok.hash.clone())).unwrap().unwrap().index, 2);
+
+        let mut forged = store.get(2).unwrap().unwrap();
+        forged.block.previous_hash = "f".repeat(64);
+        store.put(&forged).unwrap();
+        let bad = verify(&store, &BlockRef::Index(2)).unwrap().unwrap();
+        assert_eq!(bad.links_to_parent, Some(false));
+        assert!(!bad.passed());
+        assert!(verify(&store, &BlockRef::Index(7)).unwrap().is_none());
+    }
+
+    #[test]
+    fn a_missing_archive_is_not_created() {
+        let dir = tempfile::tempdir().unwrap();
+        let typo = dir.path().join("bleep-blokcs");
+        assert!(BlockStore::open_existing(&typo).is_err());
+        assert!(!typo.exists());
+        archive(dir.path(), 0);
+        assert!(head(&BlockStore::open_existing(dir.path()).unwrap()).unwrap().is_some());
+    }
+}
@@ -1,32 +1,64 @@
 //! # bleep-cli binary — Sprint 7
 //!
 //! Real implementations for all subcommands:
-//!   - `wallet`     → WalletManager (create / balance / import / export)
+//!   - `wallet`     → `Wallet` keystores (new / import), balance / export as JSON, and WalletManager (send / sign-message / verify-message / contacts)
+//!   - `wallet policy` / `pending` → spending limits, recipient lists and second-key approvals,
+//!                    checked before every transfer signature (see `bleep_wallet_core::policy`)
+//!   - `wallet send`→ prompts for missing fields, previews, then asks for a typed confirmation
+//!   - recipients   → a BLEEP1 address, or `@label` from the encrypted contact book
 //!   - `tx send`    → MempoolBridge.submit_transaction (via HTTP to RPC)
 //!   - `tx history` → RPC query
-//!   - `validator`  → stake / unstake / list / status / submit-evidence  (Sprint 6)
-//!   - `governance` → GovernanceEngine (propose / vote / list)
-//!   - `state`      → StateManager snapshot / restore
-//!   - `block`      → Blockchain query (latest / get / validate)
+//!   - `tx send --wait` / `tx submit-batch --wait` → follow to accepted / included / confirmed:N,
+//!                    exit code per outcome (see `tx_wait`)
+//!   - `tx simulate`→ dry-run via POST /rpc/tx/simulate
+//!   - `tx create` / `sign` / `broadcast` → air-gapped signing through files (see `offline_tx`);
+//!                    `tx status` looks a hash up in recent blocks
+//!   - `tx schedule` / `list-scheduled` / `cancel-scheduled` → node-held time/height-locked txs
+//!   - `validator`  → stake / unstake / list / status / submit-evidence  (Sprint 6), rewards / claim-rewards / set-commission
+//!   - `governance` → GovernanceEngine (propose / vote / list); `show` renders committed text via RPC;
+//!                    `params` lists runtime parameters and their change history via RPC
+//!   - `state`      → StateManager snapshot / restore / fsck; get / root / proof, and
+//!                    verify-proof offline against a trusted root (see `state_proof`)
+//!   - `devnet`     → one-process local network with funded dev accounts
+//!   - `block`      → get / head / range / verify from the block archive (see `block_query`)
 //!   - `ai`         → BLEEPAIAssistant
 //!   - `zkp`        → BLEEPZKPModule
 //!   - `info`       → node version + RPC health
 //!   - `pat`        → mint / burn / transfer / balance  (Sprint 7)
 //!   - `oracle`     → price / submit  (Sprint 7)
 //!   - `economics`  → supply / fee / epoch  (Sprint 7)
+//!   - `debug replay` → offline re-execution of stored blocks
+//!   - `admin audit verify` → check the node's operator audit trail
+//!   - `net crawl`  → map the network by walking peer-exchange responses
+//!   - `contract new` / `build` → scaffold a Rust or AssemblyScript contract, compile it to
+//!                    policy-checked wasm with its event schema (see `contract_project`)
 
 use anyhow::{anyhow, Result};
 use clap::Parser;
 use std::sync::Arc;
 
 use bleep_cli::{
-    Cli, Commands, WalletCommand, TxCommand, AiCommand,
+    Cli, Commands, WalletCommand, ContactsCommand, PolicyCommand, PendingCommand, TxCommand, AiCommand,
     GovernanceCommand, StateCommand, PatCommand, BlockCommand,
     ValidatorCommand, OracleCommand, EconomicsCommand, FaucetCommand,
+    DebugCommand, AdminCommand, AuditCommand, NetCommand, ContractCommand,
 };
+use bleep_cli::block_query::{self, BlockRef};
+use bleep_cli::contract_project;
+use bleep_cli::devnet::{Devnet, DevnetConfig};
+use bleep_cli::offline_tx;
+use bleep_cli::state_proof::{self, AccountValue, ProofFile};
+use bleep_cli::send_prompt::{self, format_amount, ConfirmPolicy, Preview, SendArgs, StdTerminal, Token};
+use bleep_cli::tx_wait::{self, Admission, OutputFormat, TxStatus, TxWaiter, WaitEvent, WaitOutcome, WaitTarget};
+use bleep_cli::validator_onboarding::{self, fetch_status, format_status, staking_transaction};
 
 // Real crate imports
-use bleep_wallet_core::wallet::WalletManager;
+use bleep_wallet_core::wallet::{EncryptedWallet, PreflightTx, WalletManager};
+use bleep_wallet_core::wallet_core::{P2PNode as WalletP2PNode, StateMerkle, Wallet, WalletError};
+use bleep_wallet_core::policy::{approval_message, PolicyBook, PolicyError, SpendingPolicy, Transfer};
+use bleep_wallet_core::portfolio::{fetch_portfolio, Asset, Portfolio};
+use bleep_wallet_core::message::{verify_message, SignedMessage};
+use bleep_wallet_core::contacts::{ContactBook, LABEL_PREFIX};
 use bleep_ai::{
     ai_assistant::{BLEEPAIAssistant, AIRequest},
     wallet::BLEEPWallet,
@@ -41,13 +73,26 @@ use bleep_ai::{
 };
 use bleep_governance::governance_core::{GovernanceEngine, Proposal, ProposalType, Vote};
 use bleep_state::state_manager::StateManager;
-use bleep_zkp::Verifier as ZkVerifier;
+use bleep_consensus::block_store::BlockStore;
+use bleep_consensus::replay::{fork_for_replay, replay_range, ReplayOptions, BLOCKS_SUBDIR, STATE_SUBDIR};
+use bleep_consensus::block_execution::production_executor;
+use bleep_consensus::storage_fsck::{fsck, FsckOptions};
+use bleep_consensus::rewards::RewardTx;
+use bleep_consensus::staking::StakingTx;
+use bleep_crypto::zkp_verification::{decode_groth16_proof, parse_public_input, BLEEPZKPModule};
+use bleep_core::block::ChainId;
 use bleep_core::transaction::ZKTransaction;
-use bleep_crypto::tx_signer::{sign_tx_payload, tx_payload, generate_tx_keypair};
-use bleep_crypto::bip39::{mnemonic_to_bleep_seed, validate_mnemonic};
+use bleep_core::scheduled_tx::cancel_payload;
+use bleep_rpc::tx_batch::{SubmitBatchResp, MAX_BATCH_ITEMS};
+use bleep_crypto::tx_signer::{sign_tx_payload, tx_payload_with_nonce};
+use bleep_p2p::p2p_node::{P2PNode, P2PNodeConfig};
+use bleep_telemetry::export::TelemetrySnapshot;
+use bleep_telemetry::history::{parse_span, sparkline, Series};
 
 /// Default RPC endpoint (override via BLEEP_RPC env var).
 const DEFAULT_RPC: &str = "http://127.0.0.1:8545";
+/// Points per `telemetry --history` sparkline.
+const SPARKLINE_POINTS: u64 = 60;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -71,9 +116,48 @@ async fn run(cmd: Commands) -> Result<()> {
 
     match cmd {
         // ── Node start ────────────────────────────────────────────────────
-        Commands::StartNode => {
-            println!("Starting BLEEP node — this launches the full node process.");
+        Commands::StartNode { bootstrap, peer_store, transport } => {
+            let config = P2PNodeConfig {
+                seed_addrs: bootstrap,
+                peer_store,
+                transport,
+                ..P2PNodeConfig::default()
+            };
+            let (node, handle) = P2PNode::start(config)
+                .await
+                .map_err(|e| anyhow!("P2P node start failed: {}", e))?;
+            println!("Starting BLEEP node {} on {}", node.node_id, handle.local_addr());
             println!("For production use: run the `bleep` binary instead.");
+            println!("Press Ctrl-C to stop.");
+            tokio::signal::ctrl_c().await?;
+            handle.shutdown().await;
+            println!("✅ Node stopped.");
+        }
+
+        // ── Devnet ────────────────────────────────────────────────────────
+        Commands::Devnet {
+            validators, block_time_ms, accounts, rpc_port, persist, fork_from, live_interop,
+            blocks_per_epoch, unbonding_blocks,
+        } => {
+            let devnet = Devnet::start(DevnetConfig {
+                validators,
+                block_time_ms,
+                accounts,
+                rpc_port,
+                persist:   persist.map(std::path::PathBuf::from),
+                fork_from: fork_from.map(std::path::PathBuf::from),
+                live_interop,
+                blocks_per_epoch,
+                unbonding_blocks,
+            })
+            .await
+            .map_err(|e| anyhow!("Devnet start failed: {}", e))?;
+            println!("{}", devnet.banner());
+            println!("Press Ctrl-C to stop.");
+            tokio::signal::ctrl_c().await?;
+            println!("Stopping devnet…");
+            devnet.shutdown().await;
+            println!("✅ Devnet stopped.");
         }
 
         // ── Wallet ────────────────────────────────────────────────────────
@@ -82,165 +166,315 @@ async fn run(cmd: Commands) -> Result<()> {
                 .map_err(|e| anyhow!("Wallet init failed: {}", e))?;
 
             match action {
-                WalletCommand::Create => {
-                    // Sprint 5: Generate SPHINCS+ keypair, encrypt SK with AES-256-GCM
-                    let (pk, sk) = generate_tx_keypair();
-                    let kyber_pk = pk.clone();
-                    // Use empty password by default; users can re-lock with `wallet lock`
-                    let wallet = bleep_wallet_core::wallet::EncryptedWallet::with_signing_key_encrypted(
-                        pk, &sk, kyber_pk, "",
-                    ).map_err(|e| anyhow!("Key encryption failed: {}", e))?;
-                    manager.save_wallet(wallet.clone())
-                        .map_err(|e| anyhow!("Save failed: {}", e))?;
-                    println!("✅ Wallet created");
-                    println!("   Address: {}", wallet.address());
-                    println!("   Type:    Quantum-secure (SPHINCS+-SHAKE-256)");
-                    println!("   Signing: ✅ ready (SK encrypted with AES-256-GCM)");
-                    println!("   ⚠️  Back up your key material in a safe location.");
+                WalletCommand::New => {
+                    let wallet = Wallet::new(
+                        Arc::new(WalletP2PNode::new()),
+                        Arc::new(std::sync::Mutex::new(StateMerkle::new())),
+                    ).unwrap_or_else(|e| exit_on_wallet_error(e));
+                    let keystore = save_wallet_keystore(&wallet).unwrap_or_else(|e| exit_on_wallet_error(e));
 
                     // Automatically request faucet funds for the new wallet
-                    let addr = wallet.address();
-                    match http_client.post(format!("{}/faucet/{}", rpc, addr)).send().await {
-                        Ok(r) if r.status().is_success() => {
-                            println!("💰 Faucet: 10 BLEEP credited to new wallet");
-                        }
-                        Ok(r) => {
-                            println!("⚠️  Faucet unavailable (HTTP {}), but wallet created successfully", r.status());
-                        }
+                    let faucet = matches!(
+                        http_client.post(format!("{}/faucet/{}", rpc, wallet.address())).send().await,
+                        Ok(r) if r.status().is_success()
+                    );
+                    println!("{}", serde_json::json!({
+                        "address":  wallet.address(),
+                        "keystore": keystore,
+                        "faucet":   faucet,
+                    }));
+                }
+                WalletCommand::Import { mnemonic } => {
+                    let wallet = Wallet::import_wallet(&mnemonic).unwrap_or_else(|e| exit_on_wallet_error(e));
+                    let keystore = save_wallet_keystore(&wallet).unwrap_or_else(|e| exit_on_wallet_error(e));
+                    println!("{}", serde_json::json!({
+                        "address":  wallet.address(),
+                        "keystore": keystore,
+                        "words":    mnemonic.split_whitespace().count(),
+                    }));
+                }
+                WalletCommand::Balance { address } => {
+                    // Sprint 5: prefer live RPC for balance; fall back to local
+                    // state if the node is not reachable.  Either way
+                    // `balance` is a decimal string of microBLEEP.
+                    let row = match get_account_state(&rpc, &address).await {
+                        Ok((balance, nonce, root)) => serde_json::json!({
+                            "address": address, "balance": balance.to_string(), "nonce": nonce,
+                            "state_root": root, "source": "rpc",
+                        }),
                         Err(_) => {
-                            println!("⚠️  Faucet unavailable (RPC unreachable), but wallet created successfully");
+                            let state_dir = std::env::var("BLEEP_STATE_DIR")
+                                .unwrap_or_else(|_| "/tmp/bleep-state".to_string());
+                            let (balance, nonce) = query_account_local(&state_dir, &address);
+                            serde_json::json!({
+                                "address": address, "balance": balance.to_string(), "nonce": nonce,
+                                "source": "local",
+                            })
                         }
+                    };
+                    println!("{}", row);
+                }
+                WalletCommand::Export { path } => {
+                    let mut addresses: Vec<String> = manager.list_wallets().iter()
+                        .map(|w| w.address().to_string())
+                        .collect();
+                    addresses.extend(keystore_addresses());
+                    let doc = serde_json::json!({ "addresses": addresses });
+                    std::fs::write(&path, serde_json::to_vec_pretty(&doc)?)
+                        .map_err(|e| anyhow!("Export to {} failed: {}", path.display(), e))?;
+                    println!("{}", serde_json::json!({ "path": path, "count": addresses.len() }));
+                }
+                WalletCommand::Delete { address } => {
+                    if manager.remove_wallet(&address)
+                        .map_err(|e| anyhow!("Delete failed: {}", e))? {
+                        println!("✅ Wallet {} deleted", address);
+                    } else {
+                        println!("⚠️  Wallet {} not found", address);
                     }
                 }
-                WalletCommand::Balance => {
-                    let wallets = manager.list_wallets();
-                    if wallets.is_empty() {
-                        println!("No wallets found. Run `bleep-cli wallet create` first.");
+                WalletCommand::Send { to, token, amount, max_fee, tip, nonce, yes } => {
+                    let policy = ConfirmPolicy::from_env(yes)?;
+                    let from = manager.list_wallets().first()
+                        .map(|w| w.address().to_string())
+                        .ok_or_else(|| anyhow!("No wallet found — run `bleep-cli wallet create` first"))?;
+                    let mut tokens = vec![Token::native()];
+                    tokens.extend(list_pat_tokens(&http_client, &rpc).await.unwrap_or_default());
+
+                    let mut term = StdTerminal;
+                    let draft = send_prompt::collect(
+                        &mut term,
+                        SendArgs { to, token, amount },
+                        &tokens,
+                        |to| resolve_recipient(to).map_err(|e| e.to_string()),
+                    )?;
+                    let (max_fee, balance) = if draft.token.is_native() {
+                        let max_fee = match max_fee {
+                            Some(f) => f,
+                            None => estimate_max_fee(&rpc, tip).await
+                                .map_err(|e| anyhow!("Could not estimate fee ({}) — pass --max-fee: {}", rpc, e))?,
+                        };
+                        let balance = get_account_state(&rpc, &from).await.ok()
+                            .map(|(balance, _, _)| balance);
+                        (max_fee, balance)
                     } else {
-                        // Sprint 5: prefer live RPC for balance; fall back to local
-                        // RocksDB if the node is not reachable.
-                        for w in wallets {
-                            let addr = w.address();
-                            match get_account_state(&rpc, addr).await {
-                                Ok((balance, nonce, root)) => {
-                                    println!(
-                                        "Address: {}  Balance: {} BLEEP  Nonce: {}  Root: {}",
-                                        addr,
-                                        balance,
-                                        nonce,
-                                        &root[..16.min(root.len())],
-                                    );
-                                }
-                                Err(_) => {
-                                    // Node not reachable — fall back to local state
-                                    let state_dir = std::env::var("BLEEP_STATE_DIR")
-                                        .unwrap_or_else(|_| "/tmp/bleep-state".to_string());
-                                    let balance = query_balance_local(&state_dir, addr);
-                                    println!(
-                                        "Address: {}  Balance: {} BLEEP  (offline — node at {} unreachable)",
-                                        addr, balance, rpc
-                                    );
-                                }
-                            }
+                        (0, get_pat_balance(&http_client, &rpc, &draft.token.symbol, &from).await.ok())
+                    };
+                    let preview = Preview { from, draft, max_fee, tip, balance };
+                    let draft = &preview.draft;
+                    let sent = format!("{} {}", format_amount(draft.amount, draft.token.decimals), draft.token.symbol);
+
+                    if draft.token.is_native() {
+                        let amount = u64::try_from(draft.amount)
+                            .map_err(|_| anyhow!("Amount too large for a single transfer"))?;
+                        let ts = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_secs();
+                        let nonce = resolve_nonce(&rpc, &preview.from, nonce).await?;
+                        let chain_id = fetch_chain_id(&rpc).await?;
+                        let (sender, signature) = send_prompt::confirm_and_sign(&mut term, &preview, &policy, || {
+                            policy_signature(Transfer { receiver: draft.to.clone(), amount, timestamp: ts, max_fee, tip, nonce, chain_id })
+                        })??;
+                        let tx = ZKTransaction {
+                            sender,
+                            receiver: draft.to.clone(),
+                            amount,
+                            timestamp: ts,
+                            signature,
+                            max_fee,
+                            tip,
+                            nonce,
+                            chain_id,
+                        };
+                        let tx_id = post_transaction(&rpc, &tx).await
+                            .map_err(|e| anyhow!("Could not reach node RPC ({}): {}", rpc, e))?;
+                        mark_contact_used(draft.contact.as_deref());
+                        println!("✅ Sent {} to {}", sent, draft.to);
+                        println!("   Tx ID:   {}", tx_id);
+                    } else {
+                        send_prompt::confirm_and_sign(&mut term, &preview, &policy, || ())?;
+                        let resp = http_client
+                            .post(format!("{}/rpc/pat/transfer", rpc))
+                            .json(&serde_json::json!({
+                                "symbol": draft.token.symbol, "from": preview.from, "to": draft.to,
+                                "amount": draft.amount.to_string(),
+                            }))
+                            .send().await?;
+                        if !resp.status().is_success() {
+                            return Err(anyhow!("Transfer failed: {}", resp.text().await.unwrap_or_default()));
                         }
+                        mark_contact_used(draft.contact.as_deref());
+                        println!("✅ Sent {} to {}", sent, draft.to);
                     }
                 }
-                WalletCommand::Import { phrase } => {
-                    // Sprint 5: Real BIP-39 derivation + AES-256-GCM SK encryption
-                    validate_mnemonic(&phrase)
-                        .map_err(|e| anyhow!("Invalid mnemonic: {}", e))?;
-                    let seed_32 = mnemonic_to_bleep_seed(&phrase, "")
-                        .map_err(|e| anyhow!("BIP-39 derivation failed: {}", e))?;
-                    use sha3::{Digest, Sha3_256};
-                    let pk: Vec<u8> = Sha3_256::digest(&seed_32).to_vec();
-                    let sk: Vec<u8> = seed_32.to_vec();
-                    let kyber_pk = pk.clone();
-                    let wallet = bleep_wallet_core::wallet::EncryptedWallet::with_signing_key_encrypted(
-                        pk, &sk, kyber_pk, "",
-                    ).map_err(|e| anyhow!("Key encryption failed: {}", e))?;
-                    manager.save_wallet(wallet.clone())
-                        .map_err(|e| anyhow!("Save failed: {}", e))?;
-                    println!("✅ Wallet imported (BIP-39 PBKDF2-HMAC-SHA512): {}", wallet.address());
-                    println!("   Mnemonic words: {}", phrase.split_whitespace().count());
-                    println!("   SK encrypted with AES-256-GCM at rest.");
-                }
-                WalletCommand::Export => {
-                    for w in manager.list_wallets() {
-                        println!("Address: {}", w.address());
+                WalletCommand::Portfolio { address } => {
+                    let addresses = match address {
+                        Some(a) => vec![a],
+                        None => manager.list_wallets().iter().map(|w| w.address().to_string()).collect(),
+                    };
+                    if addresses.is_empty() {
+                        println!("No wallets found. Run `bleep-cli wallet create` first.");
+                    }
+                    for addr in addresses {
+                        match fetch_portfolio(&rpc, &addr).await {
+                            Ok(p) => print_portfolio(&p),
+                            Err(e) => println!("⚠️  Portfolio for {} unavailable ({}): {}", addr, rpc, e),
+                        }
                     }
                 }
-                WalletCommand::Delete { address } => {
-                    if manager.remove_wallet(&address)
-                        .map_err(|e| anyhow!("Delete failed: {}", e))? {
-                        println!("✅ Wallet {} deleted", address);
+                WalletCommand::SignMessage { message, file, address } => {
+                    let msg = message_bytes(message, file)?;
+                    let wallet = match &address {
+                        Some(a) => manager.find_by_address(a),
+                        None => manager.list_wallets().first(),
+                    }
+                    .ok_or_else(|| anyhow!("No wallet found — run `bleep-cli wallet create` first"))?;
+                    let password = std::env::var("BLEEP_WALLET_PASSWORD").unwrap_or_default();
+                    let signed = wallet.sign_message(&password, &msg)
+                        .map_err(|e| anyhow!("Signing failed — set BLEEP_WALLET_PASSWORD if encrypted: {}", e))?;
+                    println!("{}", serde_json::to_string(&signed)?);
+                }
+                WalletCommand::VerifyMessage { address, signature, message, file } => {
+                    let msg = message_bytes(message, file)?;
+                    let envelope = if std::path::Path::new(&signature).is_file() {
+                        std::fs::read_to_string(&signature)?
                     } else {
-                        println!("⚠️  Wallet {} not found", address);
+                        signature
+                    };
+                    let signed: SignedMessage = serde_json::from_str(envelope.trim())
+                        .map_err(|e| anyhow!("Invalid signature envelope: {}", e))?;
+                    if verify_message(&address, &msg, &signed) {
+                        println!("✅ Valid signature by {}", address);
+                    } else {
+                        println!("❌ Signature does not match {} and this message", address);
+                        std::process::exit(1);
                     }
                 }
+                WalletCommand::Contacts { action } => {
+                    let mut book = contact_book()?;
+                    match action {
+                        ContactsCommand::Add { label, address, note, token } => {
+                            let previous = book.get(&label).map(|c| c.address.clone());
+                            book.add(&label, &address, note, token)?;
+                            match previous {
+                                Some(p) if p != address => println!(
+                                    "✅ Updated @{}: {} → {} (next send will ask for confirmation)",
+                                    label.trim_start_matches(LABEL_PREFIX), p, address),
+                                _ => println!("✅ Saved @{} → {}", label.trim_start_matches(LABEL_PREFIX), address),
+                            }
+                        }
+                        ContactsCommand::Remove { label } => {
+                            if book.remove(&label)? {
+                                println!("✅ Removed @{}", label.trim_start_matches(LABEL_PREFIX));
+                            } else {
+                                println!("⚠️  No contact named @{}", label.trim_start_matches(LABEL_PREFIX));
+                            }
+                        }
+                        ContactsCommand::List => {
+                            let contacts = book.list();
+                            if contacts.is_empty() {
+                                println!("No contacts. Add one with `bleep-cli wallet contacts add <label> <address>`.");
+                            }
+                            for c in contacts {
+                                println!("@{:<16} {}  {}{}",
+                                    c.label, c.address,
+                                    c.preferred_token.as_deref().map(|t| format!("[{}] ", t)).unwrap_or_default(),
+                                    c.note.as_deref().unwrap_or(""));
+                            }
+                        }
+                        ContactsCommand::Export { out, passphrase } => {
+                            let bundle = book.export_bundle(&passphrase)?;
+                            std::fs::write(&out, bundle)
+                                .map_err(|e| anyhow!("Cannot write {}: {}", out, e))?;
+                            println!("✅ Exported {} contacts to {} (encrypted)", book.list().len(), out);
+                        }
+                        ContactsCommand::Import { file, passphrase } => {
+                            let bundle = std::fs::read(&file)
+                                .map_err(|e| anyhow!("Cannot read {}: {}", file, e))?;
+                            let n = book.import_bundle(&bundle, &passphrase)?;
+                            println!("✅ Imported {} contacts from {}", n, file);
+                        }
+                    }
+                }
+                WalletCommand::Policy { action } => {
+                    let (_, _, mut book) = policy_wallet()?;
+                    match action {
+                        PolicyCommand::Show => match book.policy() {
+                            Some(p) => {
+                                println!("{}", serde_json::to_string_pretty(p)?);
+                                let now = std::time::SystemTime::now()
+                                    .duration_since(std::time::UNIX_EPOCH)
+                                    .unwrap_or_default()
+                                    .as_secs();
+                                println!("Sent in the last 24h: {}", book.spent_in_window(now));
+                            }
+                            None => println!("No spending policy set. Install one with `bleep-cli wallet policy set <file> --admin <address>`."),
+                        },
+                        PolicyCommand::Set { file, admin } => {
+                            let raw = std::fs::read_to_string(&file)
+                                .map_err(|e| anyhow!("Cannot read {}: {}", file, e))?;
+                            let policy: SpendingPolicy = serde_json::from_str(&raw)
+                                .map_err(|e| anyhow!("Invalid policy {}: {}", file, e))?;
+                            let admin_wallet = manager.find_by_address(&admin)
+                                .ok_or_else(|| anyhow!("No local wallet {} to sign as policy admin", admin))?;
+                            let password = std::env::var("BLEEP_ADMIN_PASSWORD").unwrap_or_default();
+                            let auth = admin_wallet.sign_message(&password, &policy.authorization_message())
+                                .map_err(|e| anyhow!("Admin signing failed — set BLEEP_ADMIN_PASSWORD if encrypted: {}", e))?;
+                            book.set_policy(policy, &auth)?;
+                            println!("✅ Spending policy updated (authorized by {})", admin);
+                        }
+                    }
+                }
+                WalletCommand::Pending { action } => match action {
+                    PendingCommand::List => {
+                        let (_, _, book) = policy_wallet()?;
+                        if book.pending().is_empty() {
+                            println!("No transfers waiting for approval.");
+                        }
+                        for p in book.pending() {
+                            println!("  {} | → {} | {} | fee ≤ {} (tip {}) | ts {}",
+                                p.id, p.transfer.receiver, p.transfer.amount,
+                                p.transfer.max_fee, p.transfer.tip, p.transfer.timestamp);
+                        }
+                    }
+                    PendingCommand::Approve { id, approver } => approve_pending(&rpc, &manager, &id, &approver).await?,
+                    PendingCommand::Reject { id } => {
+                        let (_, _, mut book) = policy_wallet()?;
+                        let p = book.reject(&id)?;
+                        println!("✅ Rejected {} ({} to {})", p.id, p.transfer.amount, p.transfer.receiver);
+                    }
+                },
+                WalletCommand::ApprovePending { id, approver } => approve_pending(&rpc, &manager, &id, &approver).await?,
             }
         }
 
         // ── Transactions ──────────────────────────────────────────────────
         Commands::Tx { action } => match action {
-            TxCommand::Send { to, amount } => {
-                // Build a ZKTransaction and POST it to the RPC endpoint
-                let sender = {
-                    let manager = WalletManager::load_or_create()
-                        .map_err(|e| anyhow!("Wallet needed to sign tx: {}", e))?;
-                    manager.list_wallets()
-                        .first()
-                        .map(|w| w.address().to_string())
-                        .unwrap_or_else(|| "unknown".to_string())
+            TxCommand::Send { to, amount, max_fee, tip, nonce, wait, timeout, output } => {
+                let (to, contact) = resolve_recipient(&to)?;
+                let max_fee = match max_fee {
+                    Some(f) => f,
+                    None => estimate_max_fee(&rpc, tip).await
+                        .map_err(|e| anyhow!("Could not estimate fee ({}) — pass --max-fee: {}", rpc, e))?,
                 };
-
                 let ts = std::time::SystemTime::now()
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap_or_default()
                     .as_secs();
+                let nonce = resolve_nonce(&rpc, &default_sender()?, nonce).await?;
+                let chain_id = fetch_chain_id(&rpc).await?;
 
-                // Sprint 5: unlock AES-GCM encrypted SK, sign with SPHINCS+
-                let sig = {
-                    let manager_for_sign = WalletManager::load_or_create()
-                        .map_err(|e| anyhow!("Wallet load failed: {}", e))?;
-                    let wallet_opt = manager_for_sign
-                        .list_wallets()
-                        .first()
-                        .cloned();
-
-                    match wallet_opt {
-                        Some(w) if w.can_sign() => {
-                            let payload = tx_payload(&sender, &to, amount, ts);
-                            // Decrypt SK (empty password = default; users who locked
-                            // with a custom password set BLEEP_WALLET_PASSWORD env var)
-                            let password = std::env::var("BLEEP_WALLET_PASSWORD")
-                                .unwrap_or_default();
-                            let sk_plain = w.unlock(&password)
-                                .map_err(|e| anyhow!("Wallet unlock failed — set BLEEP_WALLET_PASSWORD if encrypted: {}", e))?;
-                            let detached_sig = sign_tx_payload(&payload, &sk_plain)
-                                .map_err(|e| anyhow!("SPHINCS+ signing failed: {}", e))?;
-                            
-                            // Wire format: pk_bytes(64) || sphincs_detached_sig(49856)
-                            // SPHINCS+ public keys for sphincsshake256fsimple are 64 bytes
-                            eprintln!("[DEBUG] Wallet falcon_keys size: {} bytes", w.falcon_keys.len());
-                            eprintln!("[DEBUG] Signature size: {} bytes", detached_sig.len());
-                            
-                            if w.falcon_keys.len() != 64 {
-                                eprintln!("[WARN] Expected 64-byte SPHINCS+ public key, got {} bytes", w.falcon_keys.len());
-                            }
-                            
-                            let mut full_sig = Vec::with_capacity(w.falcon_keys.len() + detached_sig.len());
-                            full_sig.extend_from_slice(&w.falcon_keys);
-                            full_sig.extend_from_slice(&detached_sig);
-                            full_sig
-                        }
-                        Some(_) => {
-                            return Err(anyhow!("Wallet found but cannot sign — run `bleep wallet create` to generate a signing key"));
-                        }
-                        None => {
-                            return Err(anyhow!("No wallet found — run `bleep wallet create` before sending transactions"));
-                        }
-                    }
-                };
+                // Unlock the AES-GCM encrypted SK and sign with SPHINCS+ once
+                // the wallet's spending policy allows the transfer.
+                let (sender, sig) = policy_signature(Transfer {
+                    receiver: to.clone(),
+                    amount,
+                    timestamp: ts,
+                    max_fee,
+                    tip,
+                    nonce,
+                    chain_id,
+                })?;
 
                 let tx = ZKTransaction {
                     sender:    sender.clone(),
@@ -248,6 +482,10 @@ async fn run(cmd: Commands) -> Result<()> {
                     amount,
                     timestamp: ts,
                     signature: sig.clone(), // Wire format: pk(64) || SPHINCS+ detached sig
+                    max_fee,
+                    tip,
+                    nonce,
+                    chain_id,
                 };
 
                 eprintln!("[DEBUG CLI] Final transaction:");
@@ -258,13 +496,30 @@ async fn run(cmd: Commands) -> Result<()> {
                 eprintln!("[DEBUG CLI]   Signature size: {} bytes", tx.signature.len());
                 eprintln!("[DEBUG CLI]   PK (first 32 bytes hex): {}", hex::encode(&tx.signature[..tx.signature.len().min(32)]));
 
+                if let Some(target) = wait {
+                    let waiter = TxWaiter::new(&rpc, target, std::time::Duration::from_secs(timeout));
+                    let mut sink = tx_wait::printer(output);
+                    let admission = waiter.submit(&tx, &mut sink).await
+                        .map_err(|e| anyhow!("Could not reach node RPC ({}): {}", rpc, e))?;
+                    let outcome = match admission {
+                        Admission::Accepted { tx_hash, height } => {
+                            mark_contact_used(contact.as_deref());
+                            waiter.track(&[tx_hash], height, &mut sink).await[0]
+                        }
+                        Admission::Rejected { .. } => WaitOutcome::Rejected,
+                    };
+                    std::process::exit(outcome.exit_code());
+                }
+
                 // POST to RPC
                 match post_transaction(&rpc, &tx).await {
                     Ok(tx_id) => {
+                        mark_contact_used(contact.as_deref());
                         println!("✅ Transaction submitted");
                         println!("   From:    {}", sender);
                         println!("   To:      {}", to);
                         println!("   Amount:  {} BLEEP", amount);
+                        println!("   Max fee: {} (tip {})", max_fee, tip);
                         println!("   Tx ID:   {}", tx_id);
                     }
                     Err(e) => {
@@ -273,6 +528,251 @@ async fn run(cmd: Commands) -> Result<()> {
                     }
                 }
             }
+            TxCommand::Simulate { to, amount, height, contract, calldata, gas_limit } => {
+                let (to, _) = resolve_recipient(&to)?;
+                let manager = WalletManager::load_or_create()
+                    .map_err(|e| anyhow!("Wallet load failed: {}", e))?;
+                let wallet = manager.list_wallets().first().cloned()
+                    .ok_or_else(|| anyhow!("No wallet found — run `bleep wallet create` first"))?;
+
+                let tx = PreflightTx {
+                    receiver:     to.clone(),
+                    amount:       amount as u128,
+                    block_height: height,
+                    contract,
+                    calldata,
+                    gas_limit,
+                    ..Default::default()
+                };
+                match wallet.preflight(&rpc, tx).await {
+                    Ok(report) => {
+                        if report.success {
+                            println!("✅ Simulation succeeded (height {})", report.block_height);
+                        } else {
+                            println!("❌ Simulation failed (height {}): {}",
+                                report.block_height,
+                                report.failure_reason.as_deref().unwrap_or("unknown"));
+                        }
+                        println!("   Gas used: {}", report.gas_used);
+                        if !report.output.is_empty() {
+                            println!("   Output:   0x{}", report.output);
+                        }
+                        println!("   Events:   {}", report.events.len());
+                        for change in report.state_changes_summary.iter().chain(&report.storage_changes) {
+                            println!("   Δ {}", change);
+                        }
+                    }
+                    Err(e) => {
+                        println!("⚠️  Simulation request failed ({}): {}", rpc, e);
+                    }
+                }
+            }
+            TxCommand::Schedule { to, amount, at_height, at_time, expiry_height, expiry_time } => {
+                let (to, contact) = resolve_recipient(&to)?;
+                let ts = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let nonce = resolve_nonce(&rpc, &default_sender()?, None).await?;
+                let chain_id = fetch_chain_id(&rpc).await?;
+                let (sender, signature) = policy_signature(Transfer {
+                    receiver: to.clone(), amount, timestamp: ts, max_fee: 0, tip: 0, nonce, chain_id,
+                })?;
+                let resp = http_client
+                    .post(format!("{}/rpc/tx/schedule", rpc))
+                    .json(&serde_json::json!({
+                        "sender": sender, "receiver": to, "amount": amount,
+                        "timestamp": ts, "signature": signature, "nonce": nonce, "chain_id": chain_id,
+                        "not_before_height": at_height, "not_before_time": at_time,
+                        "expiry_height": expiry_height, "expiry_time": expiry_time,
+                    }))
+                    .send().await;
+                match resp {
+                    Ok(r) if r.status().is_success() => {
+                        let body: serde_json::Value = r.json().await.unwrap_or_default();
+                        mark_contact_used(contact.as_deref());
+                        println!("✅ Transaction scheduled");
+                        println!("   To:      {}", to);
+                        println!("   Amount:  {} BLEEP", amount);
+                        match (at_height, at_time) {
+                            (Some(h), _) => println!("   Release: height {}", h),
+                            (_, Some(t)) => println!("   Release: unix time {}", t),
+                            _ => {}
+                        }
+                        println!("   Id:      {}", body["id"].as_str().unwrap_or("?"));
+                    }
+                    Ok(r) => {
+                        let text = r.text().await.unwrap_or_default();
+                        println!("❌ Schedule rejected: {}", text);
+                    }
+                    Err(e) => println!("❌ RPC unreachable ({}). Is the node running?", e),
+                }
+            }
+            TxCommand::ListScheduled { sender } => {
+                let mut req = http_client.get(format!("{}/rpc/tx/scheduled", rpc));
+                if let Some(sender) = &sender {
+                    req = req.query(&[("sender", sender)]);
+                }
+                match req.send().await {
+                    Ok(r) if r.status().is_success() => {
+                        let entries: Vec<serde_json::Value> = r.json().await.unwrap_or_default();
+                        if entries.is_empty() {
+                            println!("No scheduled transactions.");
+                        }
+                        for e in &entries {
+                            println!("  {} | {} → {} | {} BLEEP | release {} | expiry {}",
+                                e["id"].as_str().unwrap_or("?"),
+                                e["sender"].as_str().unwrap_or("?"),
+                                e["receiver"].as_str().unwrap_or("?"),
+                                e["amount"], e["release"], e["expiry"]);
+                        }
+                    }
+                    Ok(r) => println!("❌ {}", r.text().await.unwrap_or_default()),
+                    Err(e) => println!("❌ RPC unreachable: {}", e),
+                }
+            }
+            TxCommand::CancelScheduled { id } => {
+                let (_, signature) = wallet_signature(|_| cancel_payload(&id).to_vec())?;
+                let resp = http_client
+                    .post(format!("{}/rpc/tx/scheduled/{}/cancel", rpc, id))
+                    .json(&serde_json::json!({ "signature": signature }))
+                    .send().await;
+                match resp {
+                    Ok(r) if r.status().is_success() => println!("✅ Scheduled transaction {} cancelled.", id),
+                    Ok(r) => println!("❌ Cancel failed: {}", r.text().await.unwrap_or_default()),
+                    Err(e) => println!("❌ RPC unreachable: {}", e),
+                }
+            }
+            TxCommand::SubmitBatch { file, api_key, wait, timeout, output } => {
+                let reader = std::io::BufReader::new(
+                    std::fs::File::open(&file).map_err(|e| anyhow!("Cannot read {}: {}", file, e))?,
+                );
+                // --wait: the tip before submitting, where receipt scanning starts.
+                let json = wait.is_some() && output == OutputFormat::Json;
+                let mut sink = tx_wait::printer(output);
+                let waiter = wait.map(|target| TxWaiter::new(&rpc, target, std::time::Duration::from_secs(timeout)));
+                let from_height = match &waiter {
+                    Some(w) => Some(w.current_tip(&mut sink).await
+                        .map_err(|e| anyhow!("Could not reach node RPC ({}): {}", rpc, e))?),
+                    None => None,
+                };
+                let mut waiting: Vec<String> = Vec::new();
+                let mut outcomes: Vec<WaitOutcome> = Vec::new();
+                // Source line of every queued transaction, for the table.
+                let mut lines: Vec<usize> = Vec::new();
+                let mut chunk: Vec<serde_json::Value> = Vec::new();
+                let (mut accepted, mut rejected) = (0usize, 0usize);
+                if !json {
+                    println!("{:>6}  {:<9}  {}", "LINE", "RESULT", "TX HASH / REASON");
+                }
+                for (n, line) in std::io::BufRead::lines(reader).enumerate() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let value = serde_json::from_str(&line)
+                        .map_err(|e| anyhow!("{}:{}: not JSON: {}", file, n + 1, e))?;
+                    chunk.push(value);
+                    lines.push(n + 1);
+                    if chunk.len() == MAX_BATCH_ITEMS {
+                        let resp = submit_batch(&http_client, &rpc, api_key.as_deref(), std::mem::take(&mut chunk)).await?;
+                        if !json {
+                            print_batch(&resp, &lines[lines.len() - MAX_BATCH_ITEMS..]);
+                        }
+                        if let Some(height) = from_height {
+                            batch_admissions(&resp, height, json, &mut sink, &mut waiting, &mut outcomes);
+                        }
+                        accepted += resp.accepted;
+                        rejected += resp.rejected;
+                    }
+                }
+                if !chunk.is_empty() {
+                    let start = lines.len() - chunk.len();
+                    let resp = submit_batch(&http_client, &rpc, api_key.as_deref(), chunk).await?;
+                    if !json {
+                        print_batch(&resp, &lines[start..]);
+                    }
+                    if let Some(height) = from_height {
+                        batch_admissions(&resp, height, json, &mut sink, &mut waiting, &mut outcomes);
+                    }
+                    accepted += resp.accepted;
+                    rejected += resp.rejected;
+                }
+                if !json {
+                    println!();
+                    println!("Submitted {} transactions: {} accepted, {} rejected", accepted + rejected, accepted, rejected);
+                    println!("Track a batch with GET {}/rpc/tx/batch/<id>", rpc);
+                }
+                if let (Some(waiter), Some(height)) = (waiter, from_height) {
+                    outcomes.extend(waiter.track(&waiting, height, &mut sink).await);
+                    let worst = outcomes.into_iter().max().unwrap_or(WaitOutcome::Reached);
+                    std::process::exit(worst.exit_code());
+                }
+            }
+            TxCommand::Create { to, amount, from, max_fee, tip, nonce, out } => {
+                let (to, _) = resolve_recipient(&to)?;
+                let sender = match from {
+                    Some(address) => address,
+                    None => WalletManager::load_or_create()
+                        .map_err(|e| anyhow!("Wallet load failed: {}", e))?
+                        .list_wallets().first().map(|w| w.address().to_string())
+                        .ok_or_else(|| anyhow!("No wallet found — pass --from or run `bleep wallet create`"))?,
+                };
+                let max_fee = resolve_max_fee(&rpc, max_fee, tip).await?;
+                let nonce = resolve_nonce(&rpc, &sender, nonce).await?;
+                let chain_id = fetch_chain_id(&rpc).await?;
+                let tx = offline_tx::unsigned(chain_id, &sender, &to, amount, unix_now(), max_fee, tip, nonce);
+                match out {
+                    Some(path) => {
+                        offline_tx::write_tx(&path, &tx).map_err(|e| anyhow!(e))?;
+                        println!("📝 Unsigned transaction written to {}", path.display());
+                        println!("   Sign offline: bleep-cli tx sign --file {} --key <wallet.json>", path.display());
+                    }
+                    None => println!("{}", serde_json::to_string_pretty(&tx)?),
+                }
+            }
+            TxCommand::Sign { file, key } => {
+                let wallet = offline_tx::load_key(&key).map_err(|e| anyhow!(e))?;
+                let mut tx = offline_tx::read_tx(&file).map_err(|e| anyhow!(e))?;
+                let password = std::env::var("BLEEP_WALLET_PASSWORD").unwrap_or_default();
+                let mut book = wallet.policies(&password)
+                    .map_err(|e| anyhow!("{} — set BLEEP_WALLET_PASSWORD if encrypted", e))?;
+                offline_tx::sign(&mut tx, &wallet, &password, &mut book).map_err(|e| anyhow!(e))?;
+                offline_tx::write_tx(&file, &tx).map_err(|e| anyhow!(e))?;
+                println!("✍️  Signed {} BLEEP from {} to {}", tx.amount, tx.sender, tx.receiver);
+                println!("   Submit online: bleep-cli tx broadcast --file {}", file.display());
+            }
+            TxCommand::Broadcast { file, rpc_url } => {
+                let tx = offline_tx::read_tx(&file).map_err(|e| anyhow!(e))?;
+                if !offline_tx::is_signed(&tx) {
+                    return Err(anyhow!("{} is unsigned — run `bleep-cli tx sign` first", file.display()));
+                }
+                let rpc = rpc_url.as_deref().unwrap_or(&rpc);
+                let waiter = TxWaiter::new(rpc, WaitTarget::Accepted, std::time::Duration::from_secs(30));
+                let mut sink = tx_wait::printer(OutputFormat::Text);
+                let admission = waiter.submit(&tx, &mut sink).await
+                    .map_err(|e| anyhow!("Could not reach node RPC ({}): {}", rpc, e))?;
+                match admission {
+                    Admission::Accepted { tx_hash, .. } => println!("   Track: bleep-cli tx status {}", tx_hash),
+                    Admission::Rejected { .. } => std::process::exit(tx_wait::EXIT_REJECTED),
+                }
+            }
+            TxCommand::Status { id, depth } => {
+                let waiter = TxWaiter::new(&rpc, WaitTarget::Included, std::time::Duration::from_secs(10));
+                let status = waiter.status(&id, depth).await
+                    .map_err(|e| anyhow!("Could not reach node RPC ({}): {}", rpc, e))?;
+                match status {
+                    TxStatus::Included { height, block_hash, confirmations } => {
+                        println!("✅ {} included in block {} ({})", id, height, block_hash);
+                        println!("   {} confirmation(s)", confirmations);
+                    }
+                    TxStatus::Reverted { height, error } => println!("❌ {} reverted in block {}{}", id, height,
+                        error.map(|e| format!(": {}", e)).unwrap_or_default()),
+                    TxStatus::NotFound { scanned } => {
+                        println!("⏳ {} not in the last {} block(s) — still pending, dropped or unknown", id, scanned)
+                    }
+                }
+            }
             TxCommand::History => {
                 match get_tx_history(&rpc).await {
                     Ok(history) => {
@@ -325,14 +825,30 @@ async fn run(cmd: Commands) -> Result<()> {
         Commands::Governance { task } => {
             let mut engine = GovernanceEngine::new(1_000_000_000u128);
             match task {
-                GovernanceCommand::Propose { proposal } => {
+                GovernanceCommand::Propose { proposal, content_file, mime, hints } => {
                     use bleep_governance::governance_core::{ProposalState, VotingWindow, GovernancePayload};
+                    use bleep_governance::{ContentCommitment, RetrievalHint};
                     use std::collections::HashMap as GovMap;
+                    // With --content-file only the commitment goes on-chain; the text stays off-chain.
+                    let content = match &content_file {
+                        Some(path) => {
+                            let bytes = std::fs::read(path)
+                                .map_err(|e| anyhow!("Cannot read {}: {}", path, e))?;
+                            let mut commitment = ContentCommitment::for_content(&bytes, &mime);
+                            for hint in &hints {
+                                commitment = commitment.with_hint(RetrievalHint::parse(hint).map_err(|e| anyhow!(e))?);
+                            }
+                            Some(commitment)
+                        }
+                        None => None,
+                    };
+                    let content_hash = content.as_ref().map(ContentCommitment::hash_hex);
                     let p = Proposal {
                         id:                 uuid_now(),
                         proposal_type:      ProposalType::ProtocolParameter,
                         title:              proposal.chars().take(60).collect::<String>(),
-                        description:        proposal.clone(),
+                        description:        if content.is_some() { String::new() } else { proposal.clone() },
+                        content,
                         state:              ProposalState::Draft,
                         voting_window:      VotingWindow { start_epoch: 0, end_epoch: 10, min_duration: 10 },
                         execution_epoch:    11,
@@ -349,6 +865,9 @@ async fn run(cmd: Commands) -> Result<()> {
                     let id = engine.submit_proposal(p)
                         .map_err(|e| anyhow!("Proposal failed: {}", e))?;
                     println!("✅ Proposal {} submitted: \"{}\"", id, proposal);
+                    if let Some(hash) = content_hash {
+                        println!("   Content hash (SHA3-256): {}", hash);
+                    }
                     engine.persist().ok();
                 }
                 GovernanceCommand::Vote { proposal_id, yes } => {
@@ -380,32 +899,167 @@ async fn run(cmd: Commands) -> Result<()> {
                     for state in &all_states {
                         for p in engine.get_proposals_by_state(state.clone()) {
                             found = true;
-                            println!("  {} — {} [{:?}]", p.id, p.title, p.state);
+                            match p.content_hash() {
+                                Some(hash) => println!("  {} — {} [{:?}] content {}", p.id, p.title, p.state, hash),
+                                None => println!("  {} — {} [{:?}]", p.id, p.title, p.state),
+                            }
                         }
                     }
                     if !found {
                         println!("No active proposals.");
                     }
                 }
+                GovernanceCommand::Show { proposal_id } => {
+                    let resp = http_client
+                        .get(format!("{}/rpc/governance/proposal/{}", rpc, proposal_id))
+                        .send()
+                        .await;
+                    match resp {
+                        Ok(r) if r.status().is_success() => {
+                            let p: serde_json::Value = r.json().await?;
+                            println!("{} — {} [{}]", p["id"].as_str().unwrap_or("?"),
+                                p["title"].as_str().unwrap_or("?"), p["state"].as_str().unwrap_or("?"));
+                            if let Some(c) = p.get("content") {
+                                println!("  Content hash: {} ({} bytes, {})", c["hash"].as_str().unwrap_or("?"),
+                                    c["size"], c["mime"].as_str().unwrap_or("?"));
+                                for hint in c["hints"].as_array().into_iter().flatten() {
+                                    println!("  Source:       {}", hint.as_str().unwrap_or("?"));
+                                }
+                            }
+                            match p["content_status"].as_str().unwrap_or("") {
+                                "inline" | "verified" => println!("\n{}", p["text"].as_str().unwrap_or("")),
+                                status => println!("\n⚠️  Content {}: {}", status, p["warning"].as_str().unwrap_or("")),
+                            }
+                        }
+                        Ok(r) => println!("❌ {}", r.text().await.unwrap_or_default()),
+                        Err(e) => println!("❌ RPC unreachable: {}", e),
+                    }
+                }
+                GovernanceCommand::Params { key, subsystem, json } => {
+                    let url = match (&key, &subsystem) {
+                        (Some(key), _) => format!("{}/rpc/governance/parameters/{}/history", rpc, key),
+                        (None, Some(sub)) => format!("{}/rpc/governance/parameters?subsystem={}", rpc, sub),
+                        (None, None) => format!("{}/rpc/governance/parameters", rpc),
+                    };
+                    let body: serde_json::Value = match http_client.get(url).send().await {
+                        Ok(r) if r.status().is_success() => r.json().await?,
+                        Ok(r) => {
+                            println!("❌ {}", r.text().await.unwrap_or_default());
+                            std::process::exit(1);
+                        }
+                        Err(e) => {
+                            println!("❌ RPC unreachable: {}", e);
+                            std::process::exit(1);
+                        }
+                    };
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&body)?);
+                    } else if let Some(key) = key {
+                        println!("{} = {}", key, body["value"]);
+                        let changes = body["changes"].as_array().cloned().unwrap_or_default();
+                        if changes.is_empty() {
+                            println!("  (unchanged since genesis)");
+                        }
+                        for c in &changes {
+                            let source = &c["source"];
+                            let by = match source["type"].as_str() {
+                                Some("proposal") => format!("proposal {} (epoch {})", source["proposal_id"].as_str().unwrap_or("?"), source["epoch"]),
+                                _ => format!("config reload from {}", source["origin"].as_str().unwrap_or("?")),
+                            };
+                            println!("  {}  {} → {}  by {}", c["timestamp"], c["old_value"], c["new_value"], by);
+                        }
+                    } else {
+                        println!("{:<28} {:<11} {:>22} {:>22}  {:<10} {}", "KEY", "SUBSYSTEM", "VALUE", "BOUNDS", "MUTABILITY", "LAST CHANGE");
+                        for p in body["parameters"].as_array().into_iter().flatten() {
+                            let last = match p["last_change"]["source"]["proposal_id"].as_str() {
+                                Some(id) => format!("proposal {}", id),
+                                None if p["last_change"].is_null() => "-".to_string(),
+                                None => "config reload".to_string(),
+                            };
+                            println!(
+                                "{:<28} {:<11} {:>22} {:>22}  {:<10} {}",
+                                p["key"].as_str().unwrap_or("?"),
+                                p["subsystem"].as_str().unwrap_or("?"),
+                                p["value"].to_string(),
+                                format!("[{}, {}]", p["min"], p["max"]),
+                                p["mutability"].as_str().unwrap_or("?"),
+                                last,
+                            );
+                        }
+                    }
+                }
             }
         }
 
         // ── ZKP ───────────────────────────────────────────────────────────
-        Commands::Zkp { proof } => {
-            let proof_bytes = hex::decode(&proof)
+        Commands::Zkp { proof, public_inputs, verifying_key } => {
+            let proof_bytes = hex::decode(proof.trim().trim_start_matches("0x"))
                 .map_err(|e| anyhow!("Invalid hex proof: {}", e))?;
-            let verifier = ZkVerifier::new();
-            let valid = verifier.verify(&proof_bytes, &[]);
-            if valid {
-                println!("✅ ZK proof is valid ({} bytes)", proof_bytes.len());
+            let proof = decode_groth16_proof(&proof_bytes).map_err(|e| {
+                anyhow!("Not a compressed BLS12-381 Groth16 proof ({} bytes): {}", proof_bytes.len(), e)
+            })?;
+            let inputs = public_inputs.iter()
+                .map(|x| parse_public_input(x))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| anyhow!("{}", e))?;
+            let vk_path = verifying_key.unwrap_or_else(|| {
+                std::env::var_os("HOME").map(std::path::PathBuf::from).unwrap_or_default().join(".bleep/zkp/verifying.key")
+            });
+            let zkp = BLEEPZKPModule::load_verifying_key(&vk_path.to_string_lossy())
+                .map_err(|e| anyhow!("{}", e))?;
+            if zkp.verify_groth16(&proof, &inputs).map_err(|e| anyhow!("{}", e))? {
+                println!("valid");
             } else {
-                println!("❌ ZK proof is INVALID");
+                println!("invalid");
                 std::process::exit(1);
             }
         }
 
         // ── State ─────────────────────────────────────────────────────────
         Commands::State { task } => match task {
+            StateCommand::Get { key, data_dir } => {
+                let state = open_state_for_query(&data_dir)?;
+                let account = state.get_account_state(&key);
+                println!("{}", serde_json::to_string_pretty(&serde_json::json!({
+                    "address": key,
+                    "balance": account.balance.to_string(),
+                    "nonce":   account.nonce,
+                    "shard":   state.shard_of(&key),
+                }))?);
+            }
+            StateCommand::Root { data_dir } => {
+                let mut state = open_state_for_query(&data_dir)?;
+                println!("{}", hex::encode(state.state_root()));
+            }
+            StateCommand::Proof { key, data_dir, out } => {
+                let mut state = open_state_for_query(&data_dir)?;
+                let proof = state.prove_account(&key);
+                let json = serde_json::to_string_pretty(&ProofFile::new(&proof, &state.state_root()))?;
+                match out {
+                    Some(path) => {
+                        std::fs::write(&path, json + "\n")
+                            .map_err(|e| anyhow!("Cannot write {}: {}", path, e))?;
+                        println!("✅ {} proof for {} written to {}",
+                            if proof.account.exists { "Inclusion" } else { "Exclusion" }, key, path);
+                    }
+                    None => println!("{}", json),
+                }
+            }
+            StateCommand::VerifyProof { key, value, proof_file, root } => {
+                let raw = std::fs::read_to_string(&proof_file)
+                    .map_err(|e| anyhow!("Cannot read {}: {}", proof_file, e))?;
+                let file: ProofFile = serde_json::from_str(&raw)
+                    .map_err(|e| anyhow!("{} is not a state proof: {}", proof_file, e))?;
+                let value = AccountValue::parse(&value).map_err(|e| anyhow!(e))?;
+                let root = state_proof::parse_hash(&root).map_err(|e| anyhow!(e))?;
+                match state_proof::verify(&file, &key, value, &root) {
+                    Ok(()) => println!("✅ Proof for {} verifies against {}", key, hex::encode(root)),
+                    Err(e) => {
+                        println!("❌ {}", e);
+                        std::process::exit(1);
+                    }
+                }
+            }
             StateCommand::Snapshot => {
                 let state_dir = std::env::var("BLEEP_STATE_DIR")
                     .unwrap_or_else(|_| "/tmp/bleep-state".to_string());
@@ -423,13 +1077,77 @@ async fn run(cmd: Commands) -> Result<()> {
                     .map_err(|e| anyhow!("Restore failed: {}", e))?;
                 println!("✅ State restored from {}", snapshot_path);
             }
+            StateCommand::Fsck { data_dir, repair } => {
+                let data_dir = std::path::Path::new(&data_dir);
+                let mut state = StateManager::open(data_dir.join(STATE_SUBDIR))
+                    .map_err(|e| anyhow!("State open failed: {}", e))?;
+                state.rebuild_trie_from_db()
+                    .map_err(|e| anyhow!("Trie rebuild failed: {}", e))?;
+                let store = BlockStore::open(data_dir.join(BLOCKS_SUBDIR))
+                    .map_err(|e| anyhow!("Block store open failed: {}", e))?;
+
+                let report = fsck(&store, &mut state, &FsckOptions::default(), repair)
+                    .map_err(|e| anyhow!("fsck failed: {}", e))?;
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                if report.is_clean() {
+                    println!("✅ No issues at height {}", report.consistent_height);
+                } else if report.recovery.is_some() {
+                    println!("✅ Recovered to height {}", report.consistent_height);
+                } else {
+                    println!("❌ {} issue(s); rerun with --repair to roll back to height {}",
+                        report.issues.len(), report.consistent_height);
+                    std::process::exit(1);
+                }
+            }
+            StateCommand::Migrate { data_dir, dry_run } => {
+                let path = std::path::Path::new(&data_dir).join(STATE_SUBDIR);
+                let report = StateManager::migrate_store(&path, dry_run)
+                    .map_err(|e| anyhow!("Migration of {} failed: {}", path.display(), e))?;
+                println!("{}", report.render());
+                if dry_run && !report.is_noop() {
+                    println!("ℹ️  Dry run — nothing written; rerun without --dry-run or start the node to apply");
+                } else {
+                    println!("✅ {} is at schema version {}", path.display(), report.to);
+                }
+            }
         },
 
         // ── Telemetry ─────────────────────────────────────────────────────
-        Commands::Telemetry => {
-            match get_health(&rpc).await {
-                Ok(status) => println!("Node health: {}", status),
-                Err(_)     => println!("Node not reachable at {}. Start with `./bleep`.", rpc),
+        Commands::Telemetry { watch, interval, history, json } => {
+            let span = match history.as_deref() {
+                Some(s) => Some(parse_span(s).ok_or_else(|| anyhow!("Invalid --history '{}' (e.g. 30m, 1h, 2d)", s))?),
+                None => None,
+            };
+            loop {
+                let snapshot = get_telemetry(&http_client, &rpc).await;
+                if json {
+                    match snapshot {
+                        Ok(snap) => println!("{}", serde_json::to_string_pretty(&snap)?),
+                        Err(e)   => eprintln!("Telemetry unavailable at {}: {}", rpc, e),
+                    }
+                } else {
+                    if watch {
+                        // Clear the screen and home the cursor.
+                        print!("\x1b[2J\x1b[H");
+                    }
+                    match get_health(&rpc).await {
+                        Ok(status) => println!("Node health: {}", status),
+                        Err(_)     => println!("Node not reachable at {}. Start with `./bleep`.", rpc),
+                    }
+                    match snapshot {
+                        Ok(snap) => print_telemetry(&snap),
+                        Err(e)   => println!("Metrics unavailable: {}", e),
+                    }
+                }
+                if let Some(span) = span {
+                    if let Err(e) = print_history(&http_client, &rpc, span).await {
+                        println!("History unavailable: {}", e);
+                    }
+                }
+                if !watch {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(interval.max(1))).await;
             }
         }
 
@@ -516,6 +1234,7 @@ async fn run(cmd: Commands) -> Result<()> {
                 }
             }
             PatCommand::Transfer { symbol, from, to, amount } => {
+                let (to, contact) = resolve_recipient(&to)?;
                 let resp = http_client
                     .post(format!("{}/rpc/pat/transfer", rpc))
                     .json(&serde_json::json!({
@@ -528,6 +1247,7 @@ async fn run(cmd: Commands) -> Result<()> {
                         let body: serde_json::Value = r.json().await.unwrap_or_default();
                         let received = body.get("received").and_then(|v| v.as_str()).unwrap_or("?");
                         let burned = body.get("burn_deducted").and_then(|v| v.as_str()).unwrap_or("0");
+                        mark_contact_used(contact.as_deref());
                         println!("✅ Transferred {} {} → {} (received: {}, burned: {})",
                                  amount, symbol, to, received, burned);
                     }
@@ -789,28 +1509,222 @@ async fn run(cmd: Commands) -> Result<()> {
         }
 
         // ── Block ─────────────────────────────────────────────────────────
-        Commands::Block { task } => match task {
-            BlockCommand::Latest => {
-                match get_latest_block(&rpc).await {
-                    Ok(info) => println!("Latest block:\n{}", info),
-                    Err(_)   => println!("Node not reachable at {}. Start with `./bleep`.", rpc),
+        Commands::Block { data_dir, raw, task } => {
+            let dir = std::path::Path::new(&data_dir).join(BLOCKS_SUBDIR);
+            let store = match BlockStore::open_existing(&dir) {
+                Ok(store) => store,
+                Err(e) => {
+                    eprintln!("❌ {} — check --data-dir", e);
+                    std::process::exit(1);
+                }
+            };
+            let print = |stored: &bleep_consensus::block_store::StoredBlock| -> Result<()> {
+                if raw {
+                    println!("{}", block_query::raw(&stored.block).map_err(|e| anyhow!(e))?);
+                } else {
+                    println!("{}", serde_json::to_string_pretty(&block_query::view(stored))?);
+                }
+                Ok(())
+            };
+            match task {
+                BlockCommand::Get { index_or_hash } => {
+                    let block = BlockRef::parse(&index_or_hash).map_err(|e| anyhow!(e))?;
+                    match block_query::find(&store, &block).map_err(|e| anyhow!(e))? {
+                        Some(stored) => print(&stored)?,
+                        None => {
+                            eprintln!("❌ No block {} in {}", block, dir.display());
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                BlockCommand::Head => match block_query::head(&store).map_err(|e| anyhow!(e))? {
+                    Some(stored) => print(&stored)?,
+                    None => {
+                        eprintln!("❌ {} holds no blocks", dir.display());
+                        std::process::exit(1);
+                    }
+                },
+                BlockCommand::Range { from, to } => {
+                    let blocks = match block_query::range(&store, from, to) {
+                        Ok(blocks) => blocks,
+                        Err(e) => {
+                            eprintln!("❌ {}", e);
+                            std::process::exit(1);
+                        }
+                    };
+                    if raw {
+                        for stored in &blocks {
+                            print(stored)?;
+                        }
+                    } else {
+                        let views: Vec<_> = blocks.iter().map(block_query::view).collect();
+                        println!("{}", serde_json::to_string_pretty(&views)?);
+                    }
+                }
+                BlockCommand::Verify { index_or_hash } => {
+                    let block = BlockRef::parse(&index_or_hash).map_err(|e| anyhow!(e))?;
+                    match block_query::verify(&store, &block).map_err(|e| anyhow!(e))? {
+                        Some(report) => {
+                            println!("{}", serde_json::to_string_pretty(&report)?);
+                            if let Some(signer) = &report.signer {
+                                println!(
+                                    "⚠  Signature checked against the key the block carries ({}…), \
+                                     not against the validator set",
+                                    &signer[..16.min(signer.len())]
+                                );
+                            }
+                            if report.passed() {
+                                println!("✅ Block {} is valid", report.index);
+                            } else {
+                                println!("❌ Block {} failed verification", report.index);
+                                std::process::exit(1);
+                            }
+                        }
+                        None => {
+                            eprintln!("❌ No block {} in {}", block, dir.display());
+                            std::process::exit(1);
+                        }
+                    }
                 }
             }
-            BlockCommand::Get { identifier } => {
-                match get_block_by_id(&rpc, &identifier).await {
-                    Ok(info) => println!("{}", info),
-                    Err(_)   => println!("Block '{}' not found or node offline.", identifier),
+        }
+
+        // ── Debug ─────────────────────────────────────────────────────────
+        Commands::Debug { task } => match task {
+            DebugCommand::Replay { from, to, data_dir, trace } => {
+                let data_dir = std::path::Path::new(&data_dir);
+                let source = StateManager::open(data_dir.join(STATE_SUBDIR))
+                    .map_err(|e| anyhow!("State open failed: {}", e))?;
+                let store = BlockStore::open(data_dir.join(BLOCKS_SUBDIR))
+                    .map_err(|e| anyhow!("Block store open failed: {}", e))?;
+
+                // Replay on a scratch fork; the node's state is never written.
+                let scratch = std::env::temp_dir()
+                    .join(format!("bleep-replay-{}", std::process::id()));
+                let fork = fork_for_replay(&source, &store, from, &scratch)
+                    .map_err(|e| anyhow!("Fork failed: {}", e))?;
+                drop(source);
+
+                let executor = production_executor(trace.is_some());
+                let opts = ReplayOptions { trace_tx: trace, ..Default::default() };
+                let report = replay_range(
+                    &store, &parking_lot::Mutex::new(fork), &executor, from, to, &opts,
+                ).await;
+                let _ = std::fs::remove_dir_all(&scratch);
+                let report = report.map_err(|e| anyhow!("Replay failed: {}", e))?;
+
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                if report.divergent_blocks > 0 {
+                    std::process::exit(1);
                 }
             }
-            BlockCommand::Validate { hash } => {
-                println!("Validating block {}…", hash);
-                match get_block_by_id(&rpc, &hash).await {
-                    Ok(_)  => println!("✅ Block {} found and accessible.", hash),
-                    Err(_) => println!("❌ Block {} not found or node offline.", hash),
+        },
+
+        // ── Admin ─────────────────────────────────────────────────────────
+        Commands::Admin { task } => match task {
+            AdminCommand::Audit { task: AuditCommand::Verify } => {
+                let token = std::env::var("BLEEP_RPC_ADMIN_TOKEN")
+                    .map_err(|_| anyhow!("BLEEP_RPC_ADMIN_TOKEN is not set"))?;
+                let resp = http_client
+                    .get(format!("{}/rpc/admin/audit/verify", rpc))
+                    .header("x-admin-token", token)
+                    .send()
+                    .await
+                    .map_err(|e| anyhow!("RPC unreachable ({}). Is the node running?", e))?;
+                let status = resp.status();
+                let body: serde_json::Value = resp.json().await.unwrap_or_default();
+                if !status.is_success() {
+                    let msg = body.get("error").and_then(|v| v.as_str()).unwrap_or("");
+                    return Err(anyhow!("Audit verify failed: HTTP {} {}", status, msg));
+                }
+                println!("🔏 Audit trail:");
+                println!("  Records   : {}", body["records"].as_u64().unwrap_or(0));
+                println!("  Chain tip : {}", body["chain_tip"].as_str().unwrap_or("-"));
+                println!("  Dropped   : {}", body["dropped"].as_u64().unwrap_or(0));
+                match body.get("first_break").filter(|b| !b.is_null()) {
+                    None => println!("✅ Chain intact"),
+                    Some(b) => {
+                        println!(
+                            "❌ Chain broken at seq {}: {}",
+                            b["seq"].as_u64().unwrap_or(0),
+                            b["reason"].as_str().unwrap_or("unknown"),
+                        );
+                        std::process::exit(1);
+                    }
                 }
             }
         },
 
+        // ── Net ───────────────────────────────────────────────────────────
+        Commands::Net { task } => match task {
+            NetCommand::Crawl { seed, max_nodes, out } => {
+                let seed: std::net::SocketAddr = seed.parse()
+                    .map_err(|e| anyhow!("Invalid seed address '{}': {}", seed, e))?;
+                let map = bleep_p2p::crawl(&bleep_p2p::TcpPexProbe, seed, max_nodes).await;
+                let json = serde_json::to_string_pretty(&map)?;
+                match out {
+                    Some(path) => {
+                        std::fs::write(&path, json).map_err(|e| anyhow!("Cannot write {}: {}", path, e))?;
+                        eprintln!(
+                            "🗺  {} nodes reached, {} unreachable — map written to {}",
+                            map.nodes.len(), map.unreachable.len(), path,
+                        );
+                    }
+                    None => println!("{}", json),
+                }
+            }
+            NetCommand::DhtStatus { json } => {
+                match http_client.get(format!("{}/rpc/net/dht", rpc)).send().await {
+                    Ok(r) if r.status().is_success() => {
+                        let stats: bleep_p2p::DhtStats = r.json().await?;
+                        if json {
+                            println!("{}", serde_json::to_string_pretty(&stats)?);
+                        } else {
+                            print_dht_status(&stats);
+                        }
+                    }
+                    Ok(r) => println!("❌ DHT status unavailable: HTTP {}", r.status()),
+                    Err(e) => println!("❌ RPC unreachable ({}). Is the node running?", e),
+                }
+            }
+        },
+
+        // ── Contract ──────────────────────────────────────────────────────
+        Commands::Contract { task } => match task {
+            ContractCommand::Analyze { file, json } => {
+                let code = std::fs::read(&file).map_err(|e| anyhow!("Cannot read {}: {}", file, e))?;
+                let report = bleep_ai::analyze_wasm(&code)?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else if report.is_clean() {
+                    println!("✅ {}: no findings", file);
+                } else {
+                    println!("🔍 {}: {} finding(s)", file, report.findings.len());
+                    for f in &report.findings {
+                        println!("  [{:<8}] {:<26} {}", f.severity, f.code, f.location);
+                        println!("             {}", f.description);
+                    }
+                }
+                if report.max_severity() == Some(bleep_ai::Severity::Critical) {
+                    std::process::exit(1);
+                }
+            }
+            ContractCommand::New { name, lang, sdk_path } => {
+                let dir = std::path::PathBuf::from(&name);
+                let files = contract_project::scaffold(&dir, &name, lang, sdk_path.as_deref())
+                    .map_err(|e| anyhow!(e))?;
+                println!("✅ Created {} ({} files)", dir.display(), files.len());
+                for f in &files {
+                    println!("   {}", f.display());
+                }
+                println!("   Build with: cd {} && bleep-cli contract build", name);
+            }
+            ContractCommand::Build { path } => {
+                let out = contract_project::build(&path).map_err(|e| anyhow!(e))?;
+                println!("✅ {} ({} bytes, {} event(s) in schema)", out.wasm.display(), out.size, out.events);
+            }
+        },
+
         // ── Validator (Sprint 6) ──────────────────────────────────────────────
         Commands::Validator { action } => match action {
             ValidatorCommand::Stake { amount, label } => {
@@ -843,11 +1757,85 @@ async fn run(cmd: Commands) -> Result<()> {
                 }
             }
 
-            ValidatorCommand::Status { validator_id } => {
-                let vid = validator_id.unwrap_or_else(|| "self".to_string());
-                match get_validator_status(&rpc, &vid).await {
-                    Ok(body) => println!("Validator '{}':\n{}", vid, body),
-                    Err(_)   => println!("Could not fetch validator '{}' from {}.", vid, rpc),
+            ValidatorCommand::Init { home } => {
+                let home = validator_home(home);
+                let init = validator_onboarding::init(&home).map_err(|e| anyhow!(e))?;
+                let registration = init.registration().map_err(|e| anyhow!(e))?;
+                if init.created {
+                    println!("🔑 Generated consensus key in {}", init.home.display());
+                } else {
+                    println!("🔑 Using the existing consensus key in {}", init.home.display());
+                }
+                println!("   Validator ID: {}", registration.validator_id);
+                println!("   Public key:   {}", registration.consensus_key);
+                println!("   Node ID:      {}", registration.node_id);
+                println!("✅ Wrote [consensus] to {}", init.config_path.display());
+                println!("\nRegistration payload:\n{}", serde_json::to_string_pretty(&registration)?);
+                println!("\nStart the node with:");
+                println!("   BLEEP_NODE_CONFIG={} BLEEP_P2P_DIR={} bleep",
+                    init.config_path.display(), init.home.join(validator_onboarding::P2P_SUBDIR).display());
+                println!("then bond stake: bleep-cli validator bond --amount <BLEEP>");
+            }
+
+            ValidatorCommand::Bond { amount, from, home, max_fee, tip, yes } => {
+                let keystore = validator_onboarding::load_keystore(&validator_home(home)).map_err(|e| anyhow!(e))?;
+                let consensus_key = keystore.public_key().map_err(|e| anyhow!(e))?;
+                let amount = u64::try_from(send_prompt::parse_amount(&amount, send_prompt::NATIVE_DECIMALS)?)
+                    .map_err(|_| anyhow!("Amount {} is too large", amount))?;
+                let (sender, sign) = wallet_signer(from.as_deref())?;
+                let preview = validator_onboarding::preview_bond(&http_client, &rpc, &sender, &consensus_key, amount)
+                    .await
+                    .map_err(|e| anyhow!("Bond refused, nothing was signed: {}", e))?;
+                let max_fee = resolve_max_fee(&rpc, max_fee, tip).await?;
+                print!("{}", preview.render());
+                println!("  Max fee:     {} µBLEEP (tip {})", max_fee, tip);
+                if !yes {
+                    confirm_word("bond")?;
+                }
+                let nonce = resolve_nonce(&rpc, &sender, None).await?;
+                let chain_id = fetch_chain_id(&rpc).await?;
+                let tx = staking_transaction(
+                    chain_id, &sender, &StakingTx::Bond { consensus_key }, amount, unix_now(), max_fee, tip, nonce, sign,
+                ).map_err(|e| anyhow!(e))?;
+                let tx_id = post_transaction(&rpc, &tx).await?;
+                println!("✅ Bond submitted — Tx ID: {}", tx_id);
+                println!("   Follow activation with `bleep-cli validator status`");
+            }
+
+            ValidatorCommand::Unbond { from, max_fee, tip } => {
+                let (sender, sign) = wallet_signer(from.as_deref())?;
+                let status = fetch_status(&http_client, &rpc, &sender).await.map_err(|e| anyhow!(e))?
+                    .ok_or_else(|| anyhow!("{} has no bond", sender))?;
+                if status.phase == "unbonding" {
+                    print!("{}", format_status(&status));
+                    println!("⏳ Still unbonding — run again once withdrawable.");
+                    return Ok(());
+                }
+                let max_fee = resolve_max_fee(&rpc, max_fee, tip).await?;
+                let nonce = resolve_nonce(&rpc, &sender, None).await?;
+                let chain_id = fetch_chain_id(&rpc).await?;
+                let tx = staking_transaction(chain_id, &sender, &StakingTx::Unbond, 0, unix_now(), max_fee, tip, nonce, sign)
+                    .map_err(|e| anyhow!(e))?;
+                let tx_id = post_transaction(&rpc, &tx).await?;
+                if status.phase == "withdrawable" {
+                    println!("✅ Withdrawal of {} BLEEP submitted — Tx ID: {}",
+                        format_amount(status.bond.stake, send_prompt::NATIVE_DECIMALS), tx_id);
+                } else {
+                    println!("✅ Unbonding of validator {} submitted — Tx ID: {}", status.bond.validator_id, tx_id);
+                    println!("   Follow progress with `bleep-cli validator status`");
+                }
+            }
+
+            ValidatorCommand::Status { validator_id, home } => {
+                let vid = validator_id
+                    .or_else(|| validator_onboarding::load_keystore(&validator_home(home)).ok().map(|k| k.validator_id))
+                    .unwrap_or_else(|| "self".to_string());
+                match fetch_status(&http_client, &rpc, &vid).await {
+                    Ok(Some(status)) => print!("{}", format_status(&status)),
+                    _ => match get_validator_status(&rpc, &vid).await {
+                        Ok(body) => println!("Validator '{}':\n{}", vid, body),
+                        Err(_)   => println!("Could not fetch validator '{}' from {}.", vid, rpc),
+                    },
                 }
             }
 
@@ -860,12 +1848,237 @@ async fn run(cmd: Commands) -> Result<()> {
                     Err(e)   => println!("❌ Evidence rejected: {}", e),
                 }
             }
+
+            ValidatorCommand::Rewards { address } => {
+                let address = match address {
+                    Some(a) => a,
+                    None => WalletManager::load_or_create()
+                        .map_err(|e| anyhow!("Wallet load failed: {}", e))?
+                        .list_wallets().first()
+                        .map(|w| w.address().to_string())
+                        .ok_or_else(|| anyhow!("No wallet found — pass --address"))?,
+                };
+                let body: serde_json::Value = http_client
+                    .get(format!("{}/rpc/rewards/{}", rpc, address))
+                    .send().await?
+                    .error_for_status()?
+                    .json().await?;
+                let field = |k: &str| body.get(k).and_then(|v| v.as_str()).unwrap_or("0").to_string();
+                println!("Rewards for {} (epoch {})", address, body["epoch"]);
+                println!("   Claimable: {} BLEEP", format_micro_bleep(&field("claimable")));
+                println!("   Accruing:  {} BLEEP (claimable after the epoch closes)", format_micro_bleep(&field("accruing")));
+                if let Some(bps) = body.get("commission_bps").and_then(|v| v.as_u64()) {
+                    println!("   Commission: {:.2}%", bps as f64 / 100.0);
+                }
+                for (validator, stake) in body["bonded"].as_object().into_iter().flatten() {
+                    println!("   Bonded to {}: {} BLEEP", validator, format_micro_bleep(stake.as_str().unwrap_or("0")));
+                }
+            }
+
+            ValidatorCommand::ClaimRewards { max_fee, tip } => {
+                let tx_id = post_reward_tx(&rpc, RewardTx::Claim, max_fee, tip).await?;
+                println!("✅ ClaimRewards submitted — Tx ID: {}", tx_id);
+            }
+
+            ValidatorCommand::SetCommission { bps, max_fee, tip } => {
+                let tx_id = post_reward_tx(&rpc, RewardTx::SetCommission { bps }, max_fee, tip).await?;
+                println!("✅ Commission {:.2}% submitted — Tx ID: {}", bps as f64 / 100.0, tx_id);
+            }
         },
     }
 
     Ok(())
 }
 
+/// Message for `wallet sign-message` / `verify-message`: `--message` text or `--file` bytes.
+fn message_bytes(message: Option<String>, file: Option<String>) -> Result<Vec<u8>> {
+    match (message, file) {
+        (Some(m), _) => Ok(m.into_bytes()),
+        (None, Some(f)) => std::fs::read(&f).map_err(|e| anyhow!("Cannot read {}: {}", f, e)),
+        (None, None) => Err(anyhow!("Pass --message or --file")),
+    }
+}
+
+/// Sign with the first local wallet; returns its address and the
+/// `pk(64) || sig` wire signature. `payload` gets the sender address.
+fn wallet_signature<P: AsRef<[u8]>>(payload: impl FnOnce(&str) -> P) -> Result<(String, Vec<u8>)> {
+    let (sender, sign) = wallet_signer(None)?;
+    let full = sign(payload(&sender).as_ref()).map_err(|e| anyhow!(e))?;
+    Ok((sender, full))
+}
+
+/// Unlock local wallet `from` (default: the first); returns its address
+/// and a signer producing `pk(64) || sig` wire signatures.
+fn wallet_signer(from: Option<&str>) -> Result<(String, impl FnOnce(&[u8]) -> Result<Vec<u8>, String>)> {
+    let manager = WalletManager::load_or_create()
+        .map_err(|e| anyhow!("Wallet load failed: {}", e))?;
+    let w = match from {
+        Some(address) => manager.find_by_address(address).cloned()
+            .ok_or_else(|| anyhow!("No local wallet {}", address))?,
+        None => manager.list_wallets().first().cloned()
+            .ok_or_else(|| anyhow!("No wallet found — run `bleep wallet create` first"))?,
+    };
+    if !w.can_sign() {
+        return Err(anyhow!("Wallet found but cannot sign — run `bleep wallet create` to generate a signing key"));
+    }
+    let password = std::env::var("BLEEP_WALLET_PASSWORD").unwrap_or_default();
+    let sk = w.unlock(&password)
+        .map_err(|e| anyhow!("Wallet unlock failed — set BLEEP_WALLET_PASSWORD if encrypted: {}", e))?;
+    let address = w.address().to_string();
+    let sign = move |payload: &[u8]| {
+        let sig = sign_tx_payload(payload, &sk).map_err(|e| format!("SPHINCS+ signing failed: {}", e))?;
+        let mut full = Vec::with_capacity(w.falcon_keys.len() + sig.len());
+        full.extend_from_slice(&w.falcon_keys);
+        full.extend_from_slice(&sig);
+        Ok(full)
+    };
+    Ok((address, sign))
+}
+
+/// `--home` of the validator commands, or the default home.
+fn validator_home(home: Option<String>) -> std::path::PathBuf {
+    home.map(std::path::PathBuf::from).unwrap_or_else(validator_onboarding::default_home)
+}
+
+/// Ask the user to type `word` before anything is signed.
+fn confirm_word(word: &str) -> Result<()> {
+    use send_prompt::Terminal;
+    let mut term = StdTerminal;
+    if !term.is_interactive() {
+        return Err(anyhow!("Not a terminal — pass --yes to submit without confirmation"));
+    }
+    term.write(&format!("Type \"{}\" to sign and broadcast: ", word))?;
+    match term.read_line()? {
+        Some(answer) if answer.trim() == word => Ok(()),
+        _ => Err(anyhow!("Aborted — nothing was signed")),
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// The first local wallet, its password (BLEEP_WALLET_PASSWORD) and its
+/// spending-policy book.
+fn policy_wallet() -> Result<(EncryptedWallet, String, PolicyBook)> {
+    let manager = WalletManager::load_or_create()
+        .map_err(|e| anyhow!("Wallet load failed: {}", e))?;
+    let w = manager.list_wallets().first().cloned()
+        .ok_or_else(|| anyhow!("No wallet found — run `bleep wallet create` first"))?;
+    let password = std::env::var("BLEEP_WALLET_PASSWORD").unwrap_or_default();
+    let book = w.policies(&password)
+        .map_err(|e| anyhow!("{} — set BLEEP_WALLET_PASSWORD if encrypted", e))?;
+    Ok((w, password, book))
+}
+
+/// Address of the first local wallet, the one `policy_signature` and
+/// `wallet_signature` sign with.
+fn default_sender() -> Result<String> {
+    WalletManager::load_or_create()
+        .map_err(|e| anyhow!("Wallet load failed: {}", e))?
+        .list_wallets().first().map(|w| w.address().to_string())
+        .ok_or_else(|| anyhow!("No wallet found — run `bleep wallet create` first"))
+}
+
+/// Sign a value transfer with the first local wallet once its spending
+/// policy allows it; returns the sender and the `pk(64) || sig` wire signature.
+fn policy_signature(transfer: Transfer) -> Result<(String, Vec<u8>)> {
+    let (w, password, mut book) = policy_wallet()?;
+    if !w.can_sign() {
+        return Err(anyhow!("Wallet found but cannot sign — run `bleep wallet create` to generate a signing key"));
+    }
+    match w.sign_and_attach(&password, &mut book, &transfer) {
+        Ok(signed) => Ok((signed.sender, signed.signature)),
+        Err(PolicyError::ApprovalRequired { id, amount }) => Err(anyhow!(
+            "Transfer of {} held for approval — an approver runs `bleep-cli wallet pending approve {} --approver <address>`",
+            amount, id,
+        )),
+        Err(e @ PolicyError::Signing(_)) => Err(anyhow!("{} — set BLEEP_WALLET_PASSWORD if encrypted", e)),
+        Err(e) => Err(anyhow!("Blocked by spending policy: {}", e)),
+    }
+}
+
+/// Release held transfer `id` with the key of local wallet `approver`,
+/// then broadcast it.
+async fn approve_pending(rpc: &str, manager: &WalletManager, id: &str, approver: &str) -> Result<()> {
+    let (owner, password, mut book) = policy_wallet()?;
+    let pending = book.pending().iter().find(|p| p.id == id).cloned()
+        .ok_or_else(|| anyhow!("No pending transfer {} — see `bleep-cli wallet pending list`", id))?;
+    let approver_wallet = manager.find_by_address(approver)
+        .ok_or_else(|| anyhow!("No local wallet {} to approve with", approver))?;
+    let approver_password = std::env::var("BLEEP_APPROVER_PASSWORD").unwrap_or_default();
+    let approval = approver_wallet.sign_message(&approver_password, &approval_message(owner.address(), &pending))
+        .map_err(|e| anyhow!("Approver signing failed — set BLEEP_APPROVER_PASSWORD if encrypted: {}", e))?;
+    let signed = owner.approve_pending(&password, &mut book, id, &approval)?;
+    let tx = ZKTransaction {
+        sender:    signed.sender,
+        receiver:  signed.receiver,
+        amount:    signed.amount,
+        timestamp: signed.timestamp,
+        signature: signed.signature,
+        max_fee:   signed.max_fee,
+        tip:       signed.tip,
+        nonce:     signed.nonce,
+        chain_id:  signed.chain_id,
+    };
+    let tx_id = post_transaction(rpc, &tx).await
+        .map_err(|e| anyhow!("Approved and signed, but could not reach node RPC ({}): {}", rpc, e))?;
+    println!("✅ Approved {} by {}", id, approver);
+    println!("   To:      {}", tx.receiver);
+    println!("   Amount:  {}", tx.amount);
+    println!("   Tx ID:   {}", tx_id);
+    Ok(())
+}
+
+/// Contact book of the first local wallet, unlocked with BLEEP_WALLET_PASSWORD.
+fn contact_book() -> Result<ContactBook> {
+    let manager = WalletManager::load_or_create()
+        .map_err(|e| anyhow!("Wallet load failed: {}", e))?;
+    let w = manager.list_wallets().first()
+        .ok_or_else(|| anyhow!("No wallet found — run `bleep wallet create` first"))?;
+    let password = std::env::var("BLEEP_WALLET_PASSWORD").unwrap_or_default();
+    w.contacts(&password).map_err(|e| anyhow!("{} — set BLEEP_WALLET_PASSWORD if encrypted", e))
+}
+
+/// Turn a recipient argument into an address. `@label` is looked up in the
+/// contact book; if its address changed since it was last paid, the user
+/// must retype the new address's last 8 characters to continue.
+/// Returns the address and, for contacts, the label to mark once sent.
+fn resolve_recipient(to: &str) -> Result<(String, Option<String>)> {
+    if !to.starts_with(LABEL_PREFIX) {
+        return Ok((to.to_string(), None));
+    }
+    let resolved = contact_book()?.resolve(to)?;
+    if let Some(previous) = &resolved.changed_from {
+        let tail = &resolved.address[resolved.address.len().saturating_sub(8)..];
+        println!("⚠️  @{} has changed address since you last paid it", resolved.label);
+        println!("   Was: {}", previous);
+        println!("   Now: {}", resolved.address);
+        print!("   Type the last 8 characters of the new address to continue: ");
+        use std::io::Write;
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer)?;
+        if answer.trim() != tail {
+            return Err(anyhow!("Aborted — address change for @{} not confirmed", resolved.label));
+        }
+    }
+    println!("   @{} → {}", resolved.label, resolved.address);
+    Ok((resolved.address, Some(resolved.label)))
+}
+
+/// Remember the address a contact was paid at; failures only warn.
+fn mark_contact_used(label: Option<&str>) {
+    if let Some(label) = label {
+        if let Err(e) = contact_book().and_then(|mut b| b.mark_used(label).map_err(Into::into)) {
+            println!("⚠️  Could not update contact @{}: {}", label, e);
+        }
+    }
+}
+
 // ── RPC HTTP helpers ─────────────────────────────────────────────────────────
 
 /// GET /rpc/health
@@ -883,18 +2096,84 @@ async fn get_health(rpc: &str) -> Result<String> {
     Ok(resp)
 }
 
-/// GET /rpc/block/latest
-async fn get_latest_block(rpc: &str) -> Result<String> {
-    let url = format!("{}/rpc/block/latest", rpc);
-    let resp = reqwest::get(&url).await?.text().await?;
-    Ok(resp)
+/// Routing table fill as one bar per non-empty bucket, then record and lookup counters.
+fn print_dht_status(stats: &bleep_p2p::DhtStats) {
+    println!("🧭 DHT node {}", stats.node_id);
+    println!("  Peers        : {} in {} bucket(s) of {}", stats.peers, stats.buckets.len(), stats.bucket_size);
+    for b in &stats.buckets {
+        let filled = b.peers.min(stats.bucket_size);
+        println!(
+            "    [{:>3}] {}{} {:>2}",
+            b.index, "█".repeat(filled), "·".repeat(stats.bucket_size - filled), b.peers,
+        );
+    }
+    println!("  Values       : {}", stats.values);
+    println!("  Provider keys: {} held, {} announced", stats.provider_keys, stats.provided);
+    println!(
+        "  Lookups      : {} (avg {} ms, last {} ms, {} timed out)",
+        stats.lookups, stats.avg_lookup_ms, stats.last_lookup_ms, stats.lookup_timeouts,
+    );
 }
 
-/// GET /rpc/block/{id}
-async fn get_block_by_id(rpc: &str, id: &str) -> Result<String> {
-    let url = format!("{}/rpc/block/{}", rpc, id);
-    let resp = reqwest::get(&url).await?.text().await?;
-    Ok(resp)
+/// GET /rpc/telemetry/snapshot
+async fn get_telemetry(client: &reqwest::Client, rpc: &str) -> Result<TelemetrySnapshot> {
+    let url = format!("{}/rpc/telemetry/snapshot", rpc);
+    Ok(client.get(&url).send().await?.error_for_status()?.json().await?)
+}
+
+/// Snapshot as a group / metric / value table.
+fn print_telemetry(snap: &TelemetrySnapshot) {
+    let rows: Vec<(&str, &str, String)> = snap.groups.iter()
+        .flat_map(|(group, metrics)| metrics.iter().map(move |(name, value)| {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (group.as_str(), name.as_str(), value)
+        }))
+        .collect();
+    let width = rows.iter().map(|(_, name, _)| name.len()).max().unwrap_or(0).max("METRIC".len());
+    println!("\n{:<10} {:<width$}  VALUE", "GROUP", "METRIC");
+    for (group, name, value) in rows {
+        println!("{:<10} {:<width$}  {}", group, name, value);
+    }
+}
+
+/// One sparkline per metric over the last `span` seconds, from /rpc/telemetry/history.
+async fn print_history(client: &reqwest::Client, rpc: &str, span: u64) -> Result<()> {
+    let url = format!("{}/rpc/telemetry/history", rpc);
+    let index: serde_json::Value = client.get(&url).send().await?.error_for_status()?.json().await?;
+    let latest = index["latest"].as_u64().unwrap_or(0);
+    let metrics: Vec<String> = serde_json::from_value(index["metrics"].clone()).unwrap_or_default();
+    let width = metrics.iter().map(|m| m.len()).max().unwrap_or(0);
+    let query = [
+        ("from", latest.saturating_sub(span).to_string()),
+        ("to",   latest.to_string()),
+        ("step", span.div_ceil(SPARKLINE_POINTS).max(1).to_string()),
+    ];
+    println!();
+    for metric in metrics {
+        let series: Series = client.get(&url)
+            .query(&[("metric", metric.as_str())])
+            .query(&query)
+            .send().await?.error_for_status()?.json().await?;
+        let last = series.points.iter().rev().find_map(|p| p.avg);
+        println!("{:<width$}  {}  {}", metric, sparkline(&series.points),
+            last.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".into()));
+    }
+    let used = index["usage"]["bytes"].as_u64().unwrap_or(0);
+    let cap = index["usage"]["max_bytes"].as_u64().unwrap_or(0);
+    println!("\nhistory storage: {} KiB of {} KiB", used / 1024, cap / 1024);
+    Ok(())
+}
+
+/// State store under `data_dir` with its tries rebuilt, for queries.
+fn open_state_for_query(data_dir: &str) -> Result<StateManager> {
+    let mut state = StateManager::open(std::path::Path::new(data_dir).join(STATE_SUBDIR))
+        .map_err(|e| anyhow!("State open failed: {}", e))?;
+    state.rebuild_trie_from_db()
+        .map_err(|e| anyhow!("Trie rebuild failed: {}", e))?;
+    Ok(state)
 }
 
 /// POST /rpc/tx  with the ZKTransaction as JSON
@@ -911,6 +2190,152 @@ async fn post_transaction(rpc: &str, tx: &ZKTransaction) -> Result<String> {
     Ok(resp)
 }
 
+/// POST /rpc/tx/submit-batch
+async fn submit_batch(
+    client: &reqwest::Client,
+    rpc: &str,
+    api_key: Option<&str>,
+    transactions: Vec<serde_json::Value>,
+) -> Result<SubmitBatchResp> {
+    let mut req = client
+        .post(format!("{}/rpc/tx/submit-batch", rpc))
+        .json(&serde_json::json!({ "transactions": transactions }));
+    if let Some(key) = api_key {
+        req = req.header("x-api-key", key);
+    }
+    let resp = req.send().await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        return Err(anyhow!("batch refused ({}): {}", status, resp.text().await.unwrap_or_default()));
+    }
+    Ok(resp.json().await?)
+}
+
+/// Admission events of a batch for `--wait`: accepted hashes join
+/// `waiting`, rejections count as outcomes.  The text table already lists
+/// both, so without `json` only the rejections' JSON goes to stderr.
+fn batch_admissions(
+    resp: &SubmitBatchResp,
+    height: u64,
+    json: bool,
+    sink: &mut (dyn FnMut(&WaitEvent) + Send),
+    waiting: &mut Vec<String>,
+    outcomes: &mut Vec<WaitOutcome>,
+) {
+    for r in &resp.results {
+        let event = match (&r.tx_hash, r.accepted) {
+            (Some(tx_hash), true) => {
+                waiting.push(tx_hash.clone());
+                WaitEvent::Accepted { tx_hash: tx_hash.clone(), height }
+            }
+            _ => {
+                outcomes.push(WaitOutcome::Rejected);
+                WaitEvent::Rejected {
+                    tx_hash: None,
+                    code:    r.code.clone().unwrap_or_default(),
+                    error:   r.error.clone().unwrap_or_default(),
+                }
+            }
+        };
+        if json {
+            sink(&event);
+        } else if let WaitEvent::Rejected { .. } = event {
+            eprintln!("{}", serde_json::to_string(&event).unwrap_or_default());
+        }
+    }
+}
+
+/// One row per item of a submitted batch; `lines` maps items to file lines.
+fn print_batch(resp: &SubmitBatchResp, lines: &[usize]) {
+    for r in &resp.results {
+        let line = lines.get(r.index).copied().unwrap_or(0);
+        if r.accepted {
+            println!("{:>6}  {:<9}  {}", line, "accepted", r.tx_hash.as_deref().unwrap_or("?"));
+        } else {
+            println!("{:>6}  {:<9}  {}: {}", line, "rejected",
+                r.code.as_deref().unwrap_or("?"), r.error.as_deref().unwrap_or(""));
+        }
+    }
+    println!("        batch {} — {} accepted, {} rejected", resp.batch_id, resp.accepted, resp.rejected);
+}
+
+/// `--max-fee`, or the node's suggestion for a transfer tipping `tip`.
+async fn resolve_max_fee(rpc: &str, max_fee: Option<u64>, tip: u64) -> Result<u64> {
+    match max_fee {
+        Some(f) => Ok(f),
+        None => estimate_max_fee(rpc, tip).await
+            .map_err(|e| anyhow!("Could not estimate fee ({}) — pass --max-fee: {}", rpc, e)),
+    }
+}
+
+/// `nonce`, or the next one `sender` should use.
+async fn resolve_nonce(rpc: &str, sender: &str, nonce: Option<u64>) -> Result<u64> {
+    match nonce {
+        Some(n) => Ok(n),
+        None => next_nonce(rpc, sender).await
+            .map_err(|e| anyhow!("Could not fetch the account nonce ({}) — pass --nonce: {}", rpc, e)),
+    }
+}
+
+/// GET /rpc/state/{address} — the account nonce, counting transactions
+/// still pending in the node's pool.
+async fn next_nonce(rpc: &str, address: &str) -> Result<u64> {
+    #[derive(serde::Deserialize)]
+    struct NonceResp {
+        nonce:         u64,
+        pending_nonce: Option<u64>,
+    }
+    let resp = reqwest::get(format!("{}/rpc/state/{}", rpc, address)).await?
+        .error_for_status()?
+        .json::<NonceResp>().await?;
+    Ok(resp.pending_nonce.unwrap_or(resp.nonce))
+}
+
+/// GET /rpc/health — the chain id transactions for this node are signed for.
+async fn fetch_chain_id(rpc: &str) -> Result<ChainId> {
+    #[derive(serde::Deserialize)]
+    struct ChainResp {
+        #[serde(default)]
+        chain_id: ChainId,
+    }
+    let resp = reqwest::get(format!("{}/rpc/health", rpc)).await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| anyhow!("Could not fetch the chain id ({}): {}", rpc, e))?
+        .json::<ChainResp>().await?;
+    Ok(resp.chain_id)
+}
+
+/// GET /rpc/tx/estimate_fee — suggested `max_fee` for a transfer tipping `tip`.
+async fn estimate_max_fee(rpc: &str, tip: u64) -> Result<u64> {
+    let url = format!("{}/rpc/tx/estimate_fee?tip={}", rpc, tip);
+    let estimate = reqwest::get(&url).await?
+        .error_for_status()?
+        .json::<bleep_core::base_fee::FeeEstimate>().await?;
+    Ok(estimate.max_fee)
+}
+
+/// GET /rpc/pat/list — symbols and decimals of every PAT token.
+async fn list_pat_tokens(client: &reqwest::Client, rpc: &str) -> Result<Vec<Token>> {
+    #[derive(serde::Deserialize)]
+    struct PatList { tokens: Vec<PatListEntry> }
+    #[derive(serde::Deserialize)]
+    struct PatListEntry { symbol: String, decimals: u8 }
+    let list = client.get(format!("{}/rpc/pat/list", rpc)).send().await?
+        .error_for_status()?
+        .json::<PatList>().await?;
+    Ok(list.tokens.into_iter().map(|t| Token { symbol: t.symbol, decimals: t.decimals }).collect())
+}
+
+/// GET /rpc/pat/balance/{symbol}/{address} — base units.
+async fn get_pat_balance(client: &reqwest::Client, rpc: &str, symbol: &str, address: &str) -> Result<u128> {
+    let body: serde_json::Value = client.get(format!("{}/rpc/pat/balance/{}/{}", rpc, symbol, address))
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+    body.get("balance").and_then(|b| b.as_str()).and_then(|b| b.parse().ok())
+        .ok_or_else(|| anyhow!("malformed balance response"))
+}
+
 /// GET /rpc/tx/history
 async fn get_tx_history(rpc: &str) -> Result<Vec<String>> {
     let url = format!("{}/rpc/tx/history", rpc);
@@ -920,8 +2345,8 @@ async fn get_tx_history(rpc: &str) -> Result<Vec<String>> {
 
 /// GET /rpc/state/{address}  — returns live balance, nonce and state root.
 ///
-/// On success returns `(balance_string, nonce, state_root_hex)`.
-async fn get_account_state(rpc: &str, address: &str) -> Result<(String, u64, String)> {
+/// On success returns `(balance in microBLEEP, nonce, state_root_hex)`.
+async fn get_account_state(rpc: &str, address: &str) -> Result<(u128, u64, String)> {
     #[derive(serde::Deserialize)]
     struct AccountStateResp {
         balance:    String,
@@ -932,18 +2357,77 @@ async fn get_account_state(rpc: &str, address: &str) -> Result<(String, u64, Str
     }
     let url  = format!("{}/rpc/state/{}", rpc, address);
     let resp = reqwest::get(&url).await?.json::<AccountStateResp>().await?;
-    Ok((resp.balance, resp.nonce, resp.state_root))
+    let balance = resp.balance.parse()
+        .map_err(|_| anyhow!("malformed balance {:?} from {}", resp.balance, url))?;
+    Ok((balance, resp.nonce, resp.state_root))
+}
+
+fn print_portfolio(p: &Portfolio) {
+    println!("Address: {}{}", p.address, if p.stale { "  (some sources stale)" } else { "" });
+    for b in &p.totals {
+        let asset = match &b.asset {
+            Asset::Native => "BLEEP".to_string(),
+            Asset::Pat { symbol } => symbol.clone(),
+            Asset::Bridged { chain, asset } => format!("{} @ {}", asset, chain),
+        };
+        println!("   {:<24} {}", asset, b.amount);
+    }
+    for s in p.sources.iter().filter(|s| s.stale) {
+        println!("   ⚠️  {} stale since {}: {}",
+            s.source, s.fetched_at, s.error.as_deref().unwrap_or("unknown"));
+    }
 }
 
 // ── Local state query (no running node required) ───────────────────────────
 
-fn query_balance_local(state_dir: &str, address: &str) -> u128 {
+fn query_account_local(state_dir: &str, address: &str) -> (u128, u64) {
     match StateManager::open(state_dir) {
-        Ok(s) => s.get_balance(address),
-        Err(_) => 0,
+        Ok(s) => (s.get_balance(address), s.get_nonce(address)),
+        Err(_) => (0, 0),
     }
 }
 
+/// Directory of wallet keystores: BLEEP_KEYSTORE_DIR, or `~/.bleep/keystores`.
+fn keystore_dir() -> std::path::PathBuf {
+    if let Ok(dir) = std::env::var("BLEEP_KEYSTORE_DIR") {
+        return dir.into();
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    std::path::PathBuf::from(home).join(".bleep").join("keystores")
+}
+
+/// Seal `wallet` in `<keystore_dir>/<address>.json` with BLEEP_WALLET_PASSWORD.
+fn save_wallet_keystore(wallet: &Wallet) -> Result<std::path::PathBuf, WalletError> {
+    let dir = keystore_dir();
+    std::fs::create_dir_all(&dir).map_err(|e| WalletError::Io(format!("{}: {}", dir.display(), e)))?;
+    let path = dir.join(format!("{}.json", wallet.address()));
+    let password = std::env::var("BLEEP_WALLET_PASSWORD").unwrap_or_default();
+    wallet.save_keystore(&path, &password)?;
+    Ok(path)
+}
+
+/// Addresses of the keystores in `keystore_dir`.
+fn keystore_addresses() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(keystore_dir()) else { return Vec::new() };
+    let mut addresses: Vec<String> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .collect();
+    addresses.sort();
+    addresses
+}
+
+/// Print `{"error": ...}` for a failed wallet operation and exit 1.
+fn exit_on_wallet_error(e: WalletError) -> ! {
+    println!("{}", serde_json::json!({ "error": e.to_string() }));
+    std::process::exit(1);
+}
+
 // ── Helpers ────────────────────────────────────────────────────────────────
 
 fn uuid_now() -> String {
@@ -984,6 +2468,23 @@ async fn post_stake_tx(rpc: &str, amount: u64, label: &str) -> Result<String> {
     Ok(resp)
 }
 
+/// Sign `request` with the first local wallet and POST it to /rpc/tx.
+async fn post_reward_tx(rpc: &str, request: RewardTx, max_fee: Option<u64>, tip: u64) -> Result<String> {
+    let max_fee = resolve_max_fee(rpc, max_fee, tip).await?;
+    let receiver = request.receiver();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let nonce = resolve_nonce(rpc, &default_sender()?, None).await?;
+    let chain_id = fetch_chain_id(rpc).await?;
+    let (sender, signature) = wallet_signature(|sender| {
+        chain_id.bind(tx_payload_with_nonce(sender, &receiver, 0, timestamp, max_fee, tip, nonce))
+    })?;
+    let tx = ZKTransaction { sender, receiver, amount: 0, timestamp, signature, max_fee, tip, nonce, chain_id };
+    post_transaction(rpc, &tx).await
+}
+
 /// POST /rpc/validator/unstake — broadcast an unstake / exit transaction.
 async fn post_unstake_tx(rpc: &str, validator_id: &str) -> Result<String> {
     #[derive(serde::Serialize)]
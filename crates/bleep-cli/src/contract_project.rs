@@ -0,0 +1,586 @@
+//! # Contract projects
+//!
+//! `bleep-cli contract new <name> --lang rust|assemblyscript` writes a
+//! ready-to-build counter contract; `bleep-cli contract build` compiles it
+//! and post-processes the wasm:
+//!
+//! ```text
+//! cargo build --release --target wasm32-unknown-unknown   (or asc --target release)
+//!   │
+//! strip every custom section (names, producers, debug info)
+//!   │
+//! embed events.json as the `bleep.events` section
+//!   │
+//! SecurityPolicy::default() must accept it, and it must export `execute`
+//!   ▼
+//! target/bleep/<name>.wasm
+//! ```
+//!
+//! Rust projects depend on `bleep-contract-sdk`; AssemblyScript projects
+//! get the same wrappers in `assembly/bleep.ts`.  `bleep.json` in the
+//! project root records its name and language for `contract build`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+use bleep_vm::execution::event_abi::EVENT_SCHEMA_SECTION;
+use bleep_vm::runtime::SecurityPolicy;
+use bleep_vm::EventSchema;
+
+/// Project manifest written by `contract new`.
+pub const MANIFEST_FILE: &str = "bleep.json";
+/// Event schema embedded by `contract build`.
+pub const EVENTS_FILE: &str = "events.json";
+/// `bleep-contract-sdk` version used when the repo's copy is not found.
+const SDK_VERSION: &str = "0.1";
+const WASM_TARGET: &str = "wasm32-unknown-unknown";
+const WASM_HEADER: [u8; 8] = *b"\0asm\x01\0\0\0";
+const CUSTOM_SECTION: u8 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    Rust,
+    #[value(name = "assemblyscript")]
+    AssemblyScript,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub name: String,
+    pub lang: Lang,
+}
+
+/// Result of `contract build`.
+#[derive(Debug, Clone)]
+pub struct BuildOutput {
+    pub wasm:   PathBuf,
+    pub size:   usize,
+    /// Events declared in the embedded schema.
+    pub events: usize,
+}
+
+fn check_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let ok = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !ok {
+        return Err(format!(
+            "Invalid contract name '{}': use lowercase letters, digits, '-' and '_', starting with a letter", name
+        ));
+    }
+    Ok(())
+}
+
+/// The `bleep-contract-sdk` dependency line for a generated `Cargo.toml`:
+/// `sdk_path` if given, else this repo's copy if the CLI was built from
+/// it, else the published version.
+fn sdk_dependency(sdk_path: Option<&Path>) -> String {
+    let in_repo = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/../bleep-contract-sdk"));
+    let path = sdk_path.map(Path::to_path_buf)
+        .or_else(|| in_repo.canonicalize().ok());
+    match path {
+        // TOML literal string: no escaping of Windows separators.
+        Some(p) => format!("{{ path = '{}'", p.display()),
+        None => format!("{{ version = \"{}\"", SDK_VERSION),
+    }
+}
+
+/// Create a counter contract project called `name` in `dir`, which must
+/// not exist yet.  Returns the files written, relative to `dir`.
+pub fn scaffold(dir: &Path, name: &str, lang: Lang, sdk_path: Option<&Path>) -> Result<Vec<PathBuf>, String> {
+    check_name(name)?;
+    if dir.exists() {
+        return Err(format!("{} already exists", dir.display()));
+    }
+    let files: Vec<(&str, String)> = match lang {
+        Lang::Rust => {
+            let sdk = sdk_dependency(sdk_path);
+            vec![
+                ("Cargo.toml", RUST_CARGO_TOML.replace("{{name}}", name).replace("{{sdk}}", &sdk)),
+                ("src/lib.rs", RUST_LIB.to_string()),
+            ]
+        }
+        Lang::AssemblyScript => vec![
+            ("package.json", AS_PACKAGE_JSON.replace("{{name}}", name)),
+            ("asconfig.json", AS_CONFIG.replace("{{name}}", name)),
+            ("assembly/bleep.ts", AS_SDK.to_string()),
+            ("assembly/index.ts", AS_INDEX.to_string()),
+        ],
+    };
+    let manifest = Manifest { name: name.to_string(), lang };
+    let manifest = serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?;
+
+    let mut written = Vec::new();
+    for (rel, contents) in files.iter().map(|(r, c)| (*r, c.as_str()))
+        .chain([(EVENTS_FILE, COUNTER_EVENTS), (MANIFEST_FILE, manifest.as_str()), (".gitignore", GITIGNORE)])
+    {
+        let path = dir.join(rel);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, contents).map_err(|e| format!("{}: {}", path.display(), e))?;
+        written.push(PathBuf::from(rel));
+    }
+    Ok(written)
+}
+
+/// Compile the project in `dir`, post-process the wasm and write it to
+/// `target/bleep/<name>.wasm`.
+pub fn build(dir: &Path) -> Result<BuildOutput, String> {
+    let manifest_path = dir.join(MANIFEST_FILE);
+    let manifest: Manifest = std::fs::read(&manifest_path)
+        .map_err(|e| format!("{}: {} (not a `contract new` project?)", manifest_path.display(), e))
+        .and_then(|b| serde_json::from_slice(&b).map_err(|e| format!("{}: {}", manifest_path.display(), e)))?;
+    let target_dir = dir.join("target");
+
+    let (mut cmd, raw) = match manifest.lang {
+        Lang::Rust => {
+            let mut cmd = Command::new("cargo");
+            cmd.args(["build", "--release", "--target", WASM_TARGET, "--manifest-path"])
+                .arg(dir.join("Cargo.toml"))
+                .arg("--target-dir")
+                .arg(&target_dir);
+            let raw = target_dir.join(WASM_TARGET).join("release")
+                .join(format!("{}.wasm", manifest.name.replace('-', "_")));
+            (cmd, raw)
+        }
+        Lang::AssemblyScript => {
+            let mut cmd = Command::new("npx");
+            cmd.args(["asc", "assembly/index.ts", "--target", "release"]).current_dir(dir);
+            (cmd, dir.join("build").join(format!("{}.wasm", manifest.name)))
+        }
+    };
+    let status = cmd.status().map_err(|e| format!("Cannot run {:?}: {}", cmd.get_program(), e))?;
+    if !status.success() {
+        return Err(format!("{:?} failed with {}", cmd.get_program(), status));
+    }
+
+    let code = std::fs::read(&raw).map_err(|e| format!("{}: {}", raw.display(), e))?;
+    let events_path = dir.join(EVENTS_FILE);
+    let events = match std::fs::read(&events_path) {
+        Ok(json) => Some(json),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("{}: {}", events_path.display(), e)),
+    };
+    let code = finalize_wasm(&code, events.as_deref())?;
+    let report = SecurityPolicy::default().validate(&code)
+        .map_err(|e| format!("Built wasm fails the security policy: {}", e))?;
+    if !report.exports_fn("execute") {
+        return Err("Built wasm does not export `execute`".into());
+    }
+    let schema_events = EventSchema::from_wasm(&code).map_err(|e| e.to_string())?
+        .map_or(0, |s| s.events.len());
+
+    let out_dir = target_dir.join("bleep");
+    std::fs::create_dir_all(&out_dir).map_err(|e| format!("{}: {}", out_dir.display(), e))?;
+    let wasm = out_dir.join(format!("{}.wasm", manifest.name));
+    std::fs::write(&wasm, &code).map_err(|e| format!("{}: {}", wasm.display(), e))?;
+    Ok(BuildOutput { wasm, size: code.len(), events: schema_events })
+}
+
+/// Drop every custom section of `code` and embed `events_json` as the
+/// `bleep.events` section.  Without `events_json` an existing
+/// `bleep.events` section is kept.
+pub fn finalize_wasm(code: &[u8], events_json: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    if let Some(json) = events_json {
+        EventSchema::from_json(json).map_err(|e| format!("{}: {}", EVENTS_FILE, e))?;
+    }
+    let mut out = WASM_HEADER.to_vec();
+    for section in sections(code)? {
+        let keep = section.id != CUSTOM_SECTION
+            || (events_json.is_none() && section.name.as_deref() == Some(EVENT_SCHEMA_SECTION));
+        if keep {
+            out.extend_from_slice(section.bytes);
+        }
+    }
+    if let Some(json) = events_json {
+        let mut payload = Vec::new();
+        write_leb(&mut payload, EVENT_SCHEMA_SECTION.len());
+        payload.extend_from_slice(EVENT_SCHEMA_SECTION.as_bytes());
+        payload.extend_from_slice(json);
+        out.push(CUSTOM_SECTION);
+        write_leb(&mut out, payload.len());
+        out.extend_from_slice(&payload);
+    }
+    Ok(out)
+}
+
+struct Section<'a> {
+    id:    u8,
+    /// Custom sections only.
+    name:  Option<String>,
+    /// The whole section: id, size and payload.
+    bytes: &'a [u8],
+}
+
+fn sections(code: &[u8]) -> Result<Vec<Section<'_>>, String> {
+    if code.get(..8) != Some(&WASM_HEADER[..]) {
+        return Err("Not a wasm module (bad header)".into());
+    }
+    let mut out = Vec::new();
+    let mut pos = 8;
+    while pos < code.len() {
+        let start = pos;
+        let id = code[pos];
+        pos += 1;
+        let size = read_leb(code, &mut pos)?;
+        let end = pos.checked_add(size).filter(|&e| e <= code.len())
+            .ok_or("Truncated wasm section")?;
+        let name = if id == CUSTOM_SECTION {
+            let mut p = pos;
+            let len = read_leb(code, &mut p)?;
+            let bytes = code.get(p..p + len).filter(|_| p + len <= end).ok_or("Truncated custom section name")?;
+            Some(String::from_utf8_lossy(bytes).into_owned())
+        } else {
+            None
+        };
+        out.push(Section { id, name, bytes: &code[start..end] });
+        pos = end;
+    }
+    Ok(out)
+}
+
+fn read_leb(code: &[u8], pos: &mut usize) -> Result<usize, String> {
+    let mut value = 0usize;
+    for shift in (0..35).step_by(7) {
+        let byte = *code.get(*pos).ok_or("Truncated LEB128")?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("LEB128 longer than 5 bytes".into())
+}
+
+fn write_leb(out: &mut Vec<u8>, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+// ── Templates ─────────────────────────────────────────────────────────────────
+
+const GITIGNORE: &str = "/target\n/build\n/node_modules\n";
+
+const COUNTER_EVENTS: &str = r#"{
+  "events": [
+    { "name": "Incremented",
+      "fields": [ { "name": "count", "type": "u64" },
+                  { "name": "by",    "type": "u64" } ] }
+  ]
+}
+"#;
+
+const RUST_CARGO_TOML: &str = r#"[package]
+name    = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[dependencies]
+bleep-contract-sdk = {{sdk}} }
+
+[dev-dependencies]
+bleep-contract-sdk = {{sdk}}, features = ["mock"] }
+
+# Small, deterministic wasm: `bleep-cli contract build` uses this profile.
+[profile.release]
+opt-level     = "z"
+lto           = true
+codegen-units = 1
+panic         = "abort"
+debug         = false
+
+# Not part of any enclosing workspace.
+[workspace]
+"#;
+
+const RUST_LIB: &str = r#"//! Counter contract.
+//!
+//! `execute` adds the calldata, a little-endian `u64` (1 if empty), to the
+//! stored count, emits `Incremented { count, by }` and returns the new
+//! count.  Build with `bleep-cli contract build`.
+
+#![cfg_attr(not(test), no_std)]
+
+use bleep_contract_sdk::{Env, Error, Event, Host};
+
+const COUNT: &[u8] = b"count";
+
+pub fn increment<H: Host>(env: &mut Env<H>) -> Result<u64, Error> {
+    let mut buf = [0u8; 8];
+    let by = match env.calldata(&mut buf)? {
+        [] => 1,
+        bytes => {
+            let mut le = [0u8; 8];
+            le[..bytes.len()].copy_from_slice(bytes);
+            u64::from_le_bytes(le)
+        }
+    };
+    let count = env.get_u64(COUNT)?.unwrap_or(0).saturating_add(by);
+    env.set_u64(COUNT, count);
+    env.emit(Event::new("Incremented").u64(count).u64(by))?;
+    Ok(count)
+}
+
+#[cfg(target_arch = "wasm32")]
+#[no_mangle]
+pub extern "C" fn execute(_calldata_len: i32) -> i32 {
+    match increment(&mut Env::default()) {
+        Ok(count) => count as i32,
+        Err(e) => e.code(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_contract_sdk::mock::MockHost;
+
+    #[test]
+    fn counts_up_and_emits() {
+        let mut env = Env::new(MockHost::new());
+        assert_eq!(increment(&mut env), Ok(1));
+        let mut env = Env::new(MockHost { calldata: 5u64.to_le_bytes().to_vec(), ..env.into_host() });
+        assert_eq!(increment(&mut env), Ok(6));
+        let (name, data) = env.host().events.last().unwrap();
+        assert_eq!(name, "Incremented");
+        assert_eq!(&data[..8], &6u64.to_le_bytes());
+    }
+}
+"#;
+
+const AS_PACKAGE_JSON: &str = r#"{
+  "name": "{{name}}",
+  "version": "0.1.0",
+  "private": true,
+  "scripts": {
+    "build": "asc assembly/index.ts --target release"
+  },
+  "devDependencies": {
+    "assemblyscript": "^0.27.0"
+  }
+}
+"#;
+
+// `abort=` drops the `env.abort` import the registry does not provide.
+const AS_CONFIG: &str = r#"{
+  "targets": {
+    "release": {
+      "outFile": "build/{{name}}.wasm",
+      "optimizeLevel": 3,
+      "shrinkLevel": 2,
+      "noAssert": true
+    }
+  },
+  "options": {
+    "runtime": "stub",
+    "use": ["abort="],
+    "exportRuntime": false
+  }
+}
+"#;
+
+const AS_SDK: &str = r#"// Typed wrappers over the `bleep` host imports; see bleep-contract-sdk
+// for the Rust equivalent.  Event fields use the `bleep.events` layout:
+// integers little-endian, `bytes`/`string` as a u32 length then the bytes.
+
+@external("bleep", "calldata")
+declare function host_calldata(outPtr: usize, outCap: i32): i32;
+@external("bleep", "storage_read")
+declare function host_storage_read(keyPtr: usize, keyLen: i32, outPtr: usize, outCap: i32): i32;
+@external("bleep", "storage_write")
+declare function host_storage_write(keyPtr: usize, keyLen: i32, valPtr: usize, valLen: i32): void;
+@external("bleep", "emit_event")
+declare function host_emit_event(namePtr: usize, nameLen: i32, dataPtr: usize, dataLen: i32): void;
+
+/// The calldata as a little-endian u64, `fallback` if empty, -1 if longer than 8 bytes.
+export function calldataU64(fallback: u64): i64 {
+  const buf = new Uint8Array(8);
+  const len = host_calldata(buf.dataStart, 8);
+  if (len == 0) return <i64>fallback;
+  if (len > 8) return -1;
+  return load<i64>(buf.dataStart);
+}
+
+/// A stored u64, or 0 if the key is unset.
+export function getU64(key: string): u64 {
+  const k = String.UTF8.encode(key);
+  const buf = new Uint8Array(8);
+  const len = host_storage_read(changetype<usize>(k), k.byteLength, buf.dataStart, 8);
+  return len == 8 ? load<u64>(buf.dataStart) : 0;
+}
+
+export function setU64(key: string, value: u64): void {
+  const k = String.UTF8.encode(key);
+  const buf = new Uint8Array(8);
+  store<u64>(buf.dataStart, value);
+  host_storage_write(changetype<usize>(k), k.byteLength, buf.dataStart, 8);
+}
+
+export class Event {
+  private data: u8[] = [];
+
+  constructor(private name: string) {}
+
+  u64(value: u64): Event {
+    for (let i = 0; i < 8; i++) this.data.push(<u8>(value >> (8 * i)));
+    return this;
+  }
+
+  bytes(value: Uint8Array): Event {
+    const len = value.length;
+    for (let i = 0; i < 4; i++) this.data.push(<u8>(len >> (8 * i)));
+    for (let i = 0; i < len; i++) this.data.push(value[i]);
+    return this;
+  }
+
+  string(value: string): Event {
+    return this.bytes(Uint8Array.wrap(String.UTF8.encode(value)));
+  }
+
+  emit(): void {
+    const n = String.UTF8.encode(this.name);
+    const d = new Uint8Array(this.data.length);
+    for (let i = 0; i < this.data.length; i++) d[i] = this.data[i];
+    host_emit_event(changetype<usize>(n), n.byteLength, d.dataStart, d.length);
+  }
+}
+"#;
+
+const AS_INDEX: &str = r#"// Counter contract: adds the calldata (a little-endian u64, 1 if empty)
+// to the stored count, emits `Incremented { count, by }` and returns the
+// new count.  Build with `bleep-cli contract build`.
+
+import { calldataU64, getU64, setU64, Event } from "./bleep";
+
+export function execute(_calldataLen: i32): i32 {
+  const by = calldataU64(1);
+  if (by < 0) return -1;
+  const count = getU64("count") + <u64>by;
+  setU64("count", count);
+  new Event("Incremented").u64(count).u64(<u64>by).emit();
+  return <i32>count;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_section_names(code: &[u8]) -> Vec<String> {
+        sections(code).unwrap().into_iter().filter_map(|s| s.name).collect()
+    }
+
+    fn custom(name: &str, payload: &[u8]) -> Vec<u8> {
+        let mut body = vec![name.len() as u8];
+        body.extend_from_slice(name.as_bytes());
+        body.extend_from_slice(payload);
+        let mut out = vec![CUSTOM_SECTION, body.len() as u8];
+        out.extend(body);
+        out
+    }
+
+    #[test]
+    fn scaffold_writes_each_language() {
+        let root = tempfile::tempdir().unwrap();
+        let rust = scaffold(&root.path().join("counter"), "counter", Lang::Rust, Some(Path::new("/sdk"))).unwrap();
+        assert!(rust.contains(&PathBuf::from("src/lib.rs")) && rust.contains(&PathBuf::from(EVENTS_FILE)));
+        let toml = std::fs::read_to_string(root.path().join("counter/Cargo.toml")).unwrap();
+        assert!(toml.contains("name    = \"counter\"") && toml.contains("path = '/sdk'"));
+        EventSchema::from_json(COUNTER_EVENTS.as_bytes()).unwrap();
+
+        let ts = scaffold(&root.path().join("ts"), "ts-counter", Lang::AssemblyScript, None).unwrap();
+        assert!(ts.contains(&PathBuf::from("assembly/index.ts")));
+        let manifest: Manifest =
+            serde_json::from_slice(&std::fs::read(root.path().join("ts").join(MANIFEST_FILE)).unwrap()).unwrap();
+        assert_eq!(manifest.lang, Lang::AssemblyScript);
+
+        assert!(scaffold(&root.path().join("counter"), "counter", Lang::Rust, None).is_err());
+        assert!(scaffold(&root.path().join("x"), "Bad Name", Lang::Rust, None).is_err());
+    }
+
+    #[test]
+    fn finalize_keeps_only_the_event_schema() {
+        let mut code = WASM_HEADER.to_vec();
+        code.extend(custom("name", b"\x00\x01\x02"));
+        code.extend([1, 4, 1, 0x60, 0, 0]); // type section: one `() -> ()`
+        code.extend(custom("producers", b"rustc"));
+        code.extend(custom(EVENT_SCHEMA_SECTION, br#"{"events":[]}"#));
+
+        let kept = finalize_wasm(&code, None).unwrap();
+        assert_eq!(custom_section_names(&kept), [EVENT_SCHEMA_SECTION]);
+        assert!(kept.windows(6).any(|w| w == [1, 4, 1, 0x60, 0, 0]));
+
+        let replaced = finalize_wasm(&code, Some(COUNTER_EVENTS.as_bytes())).unwrap();
+        assert_eq!(custom_section_names(&replaced), [EVENT_SCHEMA_SECTION]);
+        let schema = EventSchema::from_wasm(&replaced).unwrap().unwrap();
+        assert!(schema.event("Incremented").is_some());
+        SecurityPolicy::default().validate(&replaced).unwrap();
+
+        assert!(finalize_wasm(&code, Some(b"not json")).is_err());
+        assert!(finalize_wasm(b"\0asm\x01\0\0\0\x01\x09", None).is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    #[ignore = "needs cargo and the wasm32-unknown-unknown target"]
+    async fn generated_counter_builds_deploys_and_counts() {
+        use crate::devnet::{Devnet, DevnetConfig};
+
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("counter");
+        scaffold(&dir, "counter", Lang::Rust, None).unwrap();
+        let built = tokio::task::spawn_blocking(move || build(&dir)).await.unwrap().unwrap();
+        assert_eq!(built.events, 1);
+        let code = std::fs::read(&built.wasm).unwrap();
+        assert_eq!(custom_section_names(&code), [EVENT_SCHEMA_SECTION]);
+
+        let devnet = Devnet::start(DevnetConfig { rpc_port: 0, block_time_ms: 0, accounts: 1, ..Default::default() })
+            .await
+            .unwrap();
+        let alice = devnet.accounts[0].clone();
+        let http = reqwest::Client::new();
+        let deployed: serde_json::Value = http.post(format!("{}/rpc/devnet/deploy", devnet.rpc_url()))
+            .json(&serde_json::json!({
+                "deployer": alice.address,
+                "code": hex::encode(&code),
+                "signature": alice.sign_deploy(&code).unwrap(),
+            }))
+            .send().await.unwrap()
+            .json().await.unwrap();
+        let address = deployed["address"].as_str().unwrap().to_string();
+
+        let mut counts = Vec::new();
+        for calldata in [hex::encode(5u64.to_le_bytes()), String::new()] {
+            let resp: serde_json::Value = http.post(format!("{}/rpc/devnet/call", devnet.rpc_url()))
+                .json(&serde_json::json!({ "address": address, "calldata": calldata }))
+                .send().await.unwrap()
+                .json().await.unwrap();
+            let output = hex::decode(resp["output"].as_str().unwrap()).unwrap();
+            counts.push(i32::from_le_bytes(output.try_into().unwrap()));
+        }
+        assert_eq!(counts, [5, 6]);
+
+        let events: serde_json::Value =
+            reqwest::get(format!("{}/rpc/contract/{}/events?decode=true", devnet.rpc_url(), address))
+                .await.unwrap()
+                .json().await.unwrap();
+        let last = &events["events"][1]["decoded"];
+        assert_eq!(last["name"], "Incremented");
+        assert_eq!((last["fields"]["count"].as_u64(), last["fields"]["by"].as_u64()), (Some(6), Some(1)));
+
+        devnet.shutdown().await;
+    }
+}
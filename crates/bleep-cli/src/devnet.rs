@@ -0,0 +1,876 @@
+//! # Developer devnet
+//!
+//! `bleep-cli devnet` runs a complete local network in one process:
+//!
+//! ```text
+//! StateManager (data dir)  ← funded dev accounts / --fork-from snapshot
+//!   │
+//! TransactionPool  ←  POST /rpc/tx
+//!   │
+//! BlockProducer (--block-time-ms, 0 = instant)
+//!   │  leaders rotate over the epoch's validator set (--blocks-per-epoch)
+//!   ▼
+//! RPC server on 127.0.0.1:<rpc_port>, with /rpc/devnet/deploy
+//!   │
+//! BLEEP Connect over sandbox chains  →  /rpc/interop/sandbox/<chain>/state
+//! ```
+//!
+//! Validators share one pool, chain and state and take turns signing
+//! blocks, so there is no P2P layer to secure.  Accounts that bond stake
+//! (`bleep-cli validator bond`) join the set two epochs later; the devnet
+//! signs their slots once `Devnet::load_consensus_key` hands it their key.  Contracts deploy under
+//! `SecurityPolicy::devnet()`.  Interop chains are in-memory sandboxes
+//! unless `--live-interop` is given.
+//!
+//! `Devnet::start_replica` adds a keyless read-only replica on its own
+//! port and data dir.  It re-executes the validators' blocks from an
+//! in-process block feed (backfilling from the devnet's block archive) the
+//! way a `--role replica` node follows gossip.
+//!
+//! Dev account mnemonics are derived from a fixed seed, so the same
+//! addresses come back on every machine and `wallet import` of a printed
+//! mnemonic yields the printed address.  Each account's SPHINCS+ signing
+//! key is generated on first start and kept in `dev-accounts.json` in the
+//! data dir.  Without `--persist` the data dir is a temp dir removed on
+//! shutdown.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, RwLock};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tokio::task::JoinHandle;
+
+use bleep_consensus::replay::{BLOCKS_SUBDIR, STATE_SUBDIR};
+use bleep_consensus::staking::{self, StakingParams};
+use bleep_consensus::validator_identity::{ValidatorIdentity, ValidatorRegistry};
+use bleep_consensus::{
+    BlockProducer, BlockStore, ConsensusKeystore, EpochValidatorSets, ProposerKey, ReplicaFollower, SyncStatus,
+    ValidatorSetConfig,
+};
+use bleep_core::block::{Block, ChainId};
+use bleep_core::blockchain::{Blockchain, BlockchainState};
+use bleep_core::pending_spend::{BalanceLookup, SpendModel};
+use bleep_core::transaction::ZKTransaction;
+use bleep_core::transaction_pool::TransactionPool;
+use bleep_crypto::bip39::mnemonic_to_bleep_seed;
+use bleep_crypto::pq_crypto::KyberKem;
+use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload, tx_payload_with_nonce};
+use bleep_interop::core::{BleepConnectBuilder, BleepConnectOrchestrator};
+use bleep_interop::crypto::ClassicalKeyPair;
+use bleep_interop::SandboxConfig;
+use bleep_rpc::devnet::deploy_payload;
+use bleep_rpc::{rpc_routes_with_state, RpcState};
+use bleep_state::state_manager::StateManager;
+use bleep_vm::runtime::SecurityPolicy;
+use bleep_vm::{ContractRegistry, Executor, ExecutorConfig};
+use bleep_wallet_core::wallet::EncryptedWallet;
+
+/// RPC port when `--rpc-port` is not given.
+pub const DEFAULT_DEVNET_RPC_PORT: u16 = 8545;
+/// Opening balance of every dev account: 1,000,000 BLEEP in µBLEEP.
+pub const DEV_ACCOUNT_BALANCE: u128 = 1_000_000 * 100_000_000;
+/// Stake each in-process validator is registered with, and the least a
+/// bond must reach.
+pub const DEV_VALIDATOR_STAKE: u128 = 1_000_000;
+/// Slot length used for `--block-time-ms 0`: blocks follow transactions
+/// as soon as the next poll sees them.
+const INSTANT_BLOCK_POLL_MS: u64 = 50;
+const ACCOUNT_SEED_DOMAIN: &[u8] = b"bleep:devnet:account";
+const ACCOUNTS_FILE: &str = "dev-accounts.json";
+const CONNECT_SUBDIR: &str = "connect";
+
+/// Options of `bleep-cli devnet`.
+#[derive(Debug, Clone)]
+pub struct DevnetConfig {
+    /// In-process validators taking turns to sign blocks.
+    pub validators:    usize,
+    /// Slot length; 0 produces a block as soon as a transaction arrives.
+    pub block_time_ms: u64,
+    /// Pre-funded dev accounts.
+    pub accounts:      usize,
+    /// 0 binds any free port.
+    pub rpc_port:      u16,
+    /// Keep state here across restarts instead of in a temp dir.
+    pub persist:       Option<PathBuf>,
+    /// Start from this state directory (a `state snapshot`).
+    pub fork_from:     Option<PathBuf>,
+    /// Use the live interop chain adapters instead of sandboxes.
+    pub live_interop:  bool,
+    /// Blocks per validator-set epoch.
+    pub blocks_per_epoch: u64,
+    /// Blocks between starting to unbond and withdrawing.
+    pub unbonding_blocks: u64,
+}
+
+impl Default for DevnetConfig {
+    fn default() -> Self {
+        Self {
+            validators:    1,
+            block_time_ms: 1_000,
+            accounts:      10,
+            rpc_port:      DEFAULT_DEVNET_RPC_PORT,
+            persist:       None,
+            fork_from:     None,
+            live_interop:  false,
+            blocks_per_epoch: 100,
+            unbonding_blocks: 100,
+        }
+    }
+}
+
+/// A pre-funded developer account.
+#[derive(Clone)]
+pub struct DevAccount {
+    pub index:      u32,
+    pub mnemonic:   String,
+    pub address:    String,
+    pub public_key: Vec<u8>,
+    pub secret_key: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredKey {
+    address:    String,
+    public_key: String,
+    secret_key: String,
+}
+
+impl DevAccount {
+    /// Mnemonic and address of dev account `index`; the same on every run.
+    pub fn mnemonic_and_address(index: u32) -> Result<(String, String), String> {
+        let mut h = Sha3_256::new();
+        h.update(ACCOUNT_SEED_DOMAIN);
+        h.update(index.to_be_bytes());
+        let entropy = h.finalize();
+        let mnemonic = bip39::Mnemonic::from_entropy(&entropy[..16])
+            .map_err(|e| format!("mnemonic: {}", e))?
+            .to_string();
+        // Same derivation as `wallet import`.
+        let seed = mnemonic_to_bleep_seed(&mnemonic, "")?;
+        let address = EncryptedWallet::derive_address(&Sha3_256::digest(seed));
+        Ok((mnemonic, address))
+    }
+
+    /// Sign a transfer in the `POST /rpc/tx` wire format.
+    pub fn transfer(&self, receiver: &str, amount: u64, timestamp: u64, nonce: u64) -> Result<ZKTransaction, String> {
+        let payload = ChainId::DEVNET.bind(tx_payload_with_nonce(&self.address, receiver, amount, timestamp, 0, 0, nonce));
+        let mut signature = self.public_key.clone();
+        signature.extend(sign_tx_payload(&payload, &self.secret_key)?);
+        Ok(ZKTransaction {
+            sender: self.address.clone(),
+            receiver: receiver.to_string(),
+            amount,
+            timestamp,
+            signature,
+            max_fee: 0,
+            tip: 0,
+            nonce,
+            chain_id: ChainId::DEVNET,
+        })
+    }
+
+    /// Wire signature (`pk || sig`) of a transaction payload.
+    pub fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, String> {
+        let mut signature = self.public_key.clone();
+        signature.extend(sign_tx_payload(payload, &self.secret_key)?);
+        Ok(signature)
+    }
+
+    /// Signature for `POST /rpc/devnet/deploy` of `code`.
+    pub fn sign_deploy(&self, code: &[u8]) -> Result<Vec<u8>, String> {
+        let mut signature = self.public_key.clone();
+        signature.extend(sign_tx_payload(&deploy_payload(&self.address, code), &self.secret_key)?);
+        Ok(signature)
+    }
+}
+
+/// Derive `count` dev accounts, reusing signing keys stored in `dir`.
+fn load_accounts(dir: &Path, count: usize) -> Result<Vec<DevAccount>, String> {
+    let path = dir.join(ACCOUNTS_FILE);
+    let stored: Vec<StoredKey> = match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).map_err(|e| format!("{}: {}", path.display(), e))?,
+        Err(_) => Vec::new(),
+    };
+    let mut accounts = Vec::with_capacity(count);
+    for index in 0..count as u32 {
+        let (mnemonic, address) = DevAccount::mnemonic_and_address(index)?;
+        let (public_key, secret_key) = match stored.iter().find(|k| k.address == address) {
+            Some(k) => (
+                hex::decode(&k.public_key).map_err(|e| e.to_string())?,
+                hex::decode(&k.secret_key).map_err(|e| e.to_string())?,
+            ),
+            None => generate_tx_keypair(),
+        };
+        accounts.push(DevAccount { index, mnemonic, address, public_key, secret_key });
+    }
+    let keys: Vec<StoredKey> = accounts.iter().map(|a| StoredKey {
+        address:    a.address.clone(),
+        public_key: hex::encode(&a.public_key),
+        secret_key: hex::encode(&a.secret_key),
+    }).collect();
+    let json = serde_json::to_vec_pretty(&keys).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("{}: {}", path.display(), e))?;
+    Ok(accounts)
+}
+
+/// Open the devnet state: forked from a snapshot, reopened from a
+/// previous run, or fresh with funded accounts.
+fn open_state(state_dir: &Path, config: &DevnetConfig, accounts: &[DevAccount]) -> Result<StateManager, String> {
+    let fresh = !state_dir.exists();
+    let mut state = match &config.fork_from {
+        Some(_) if !fresh => {
+            return Err(format!("--fork-from needs an empty data dir, {} has state", state_dir.display()));
+        }
+        Some(snapshot) => {
+            let mut source = StateManager::open(snapshot)
+                .map_err(|e| format!("snapshot {}: {}", snapshot.display(), e))?;
+            source.rebuild_trie_from_db().map_err(|e| e.to_string())?;
+            source.fork_at(source.block_height(), state_dir).map_err(|e| e.to_string())?
+        }
+        None => {
+            let mut state = StateManager::open(state_dir).map_err(|e| e.to_string())?;
+            state.rebuild_trie_from_db().map_err(|e| e.to_string())?;
+            state
+        }
+    };
+    if fresh {
+        for account in accounts {
+            if state.get_balance(&account.address) == 0 {
+                state.mint(&account.address, DEV_ACCOUNT_BALANCE)?;
+            }
+        }
+        staking::set_params(&mut state, &StakingParams {
+            min_stake:        DEV_VALIDATOR_STAKE,
+            unbonding_blocks: config.unbonding_blocks,
+        });
+        state.advance_block();
+    }
+    Ok(state)
+}
+
+/// `Blockchain` balances and nonces mirroring `state`.
+fn core_state(state: &StateManager) -> BlockchainState {
+    let mut core = BlockchainState::default();
+    for (address, balance) in state.export_balances() {
+        if let Ok(balance) = u64::try_from(balance) {
+            core.credit(&address, balance);
+        }
+    }
+    for (address, nonce) in state.export_nonces() {
+        core.set_nonce(&address, nonce);
+    }
+    core
+}
+
+enum DataDir {
+    Ephemeral(tempfile::TempDir),
+    Persistent(PathBuf),
+}
+
+impl DataDir {
+    fn open(persist: Option<&Path>) -> Result<Self, String> {
+        match persist {
+            Some(dir) => {
+                std::fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+                Ok(DataDir::Persistent(dir.to_path_buf()))
+            }
+            None => Ok(DataDir::Ephemeral(tempfile::tempdir().map_err(|e| e.to_string())?)),
+        }
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            DataDir::Ephemeral(t) => t.path(),
+            DataDir::Persistent(p) => p,
+        }
+    }
+}
+
+/// A running devnet.  Call `shutdown` to stop it and flush state.
+pub struct Devnet {
+    pub accounts:   Vec<DevAccount>,
+    pub validators: Vec<String>,
+    pub rpc_addr:   SocketAddr,
+    pub block_time_ms: u64,
+    pub live_interop:  bool,
+    state:    Arc<Mutex<StateManager>>,
+    tx_pool:  Arc<TransactionPool>,
+    handles:  Vec<JoinHandle<()>>,
+    data_dir: DataDir,
+    blockchain:  Arc<RwLock<Blockchain>>,
+    registry:    Arc<Mutex<ValidatorRegistry>>,
+    /// Keys the producer signs with; bonded validators' keys join here.
+    keys:        Arc<Mutex<Vec<ProposerKey>>>,
+    block_store: Arc<BlockStore>,
+    block_feed:  tokio::sync::broadcast::Sender<Block>,
+    /// State height block 1 of this run executes on.
+    genesis_height: u64,
+}
+
+impl Devnet {
+    pub async fn start(config: DevnetConfig) -> Result<Self, String> {
+        let data_dir = DataDir::open(config.persist.as_deref())?;
+        let accounts = load_accounts(data_dir.path(), config.accounts)?;
+        let state = open_state(&data_dir.path().join(STATE_SUBDIR), &config, &accounts)?;
+        let genesis_height = state.block_height();
+        let state = Arc::new(Mutex::new(state));
+
+        // The chain restarts from genesis on every run, so the archive
+        // only keeps this run's blocks.
+        let block_store = Arc::new(BlockStore::open(data_dir.path().join(BLOCKS_SUBDIR))?);
+        block_store.truncate_above(0)?;
+        let (block_feed, _) = tokio::sync::broadcast::channel(256);
+
+        let tx_pool = {
+            let state = Arc::clone(&state);
+            let balances: BalanceLookup = Arc::new(move |account: &str| state.lock().get_balance(account));
+            TransactionPool::with_spend_tracking(10_000, balances, SpendModel::default())
+        };
+        {
+            let state = Arc::clone(&state);
+            tx_pool.set_nonce_lookup(Arc::new(move |account: &str| state.lock().get_nonce(account)))?;
+        }
+        tx_pool.set_chain_id(ChainId::DEVNET)?;
+
+        let blockchain = {
+            let genesis = Block { chain_id: ChainId::DEVNET, ..Block::new(0, vec![], "0".to_string()) };
+            let core = core_state(&state.lock());
+            Arc::new(RwLock::new(Blockchain::new(genesis, core, tx_pool.clone(), None)?))
+        };
+
+        // In-process validator set: one SPHINCS+ key each, registered so
+        // `/rpc/validator/list` shows them.
+        let registry = Arc::new(Mutex::new(ValidatorRegistry::new()));
+        let mut proposers = Vec::with_capacity(config.validators.max(1));
+        for i in 0..config.validators.max(1) {
+            let (pk, sk) = generate_tx_keypair();
+            let validator_id = format!("devnet-validator-{}", i);
+            let (kyber_pk, _) = KyberKem::keygen().map_err(|e| format!("Kyber keygen: {:?}", e))?;
+            let mut identity = ValidatorIdentity::new(
+                validator_id.clone(), kyber_pk.as_bytes().to_vec(), hex::encode(&pk), DEV_VALIDATOR_STAKE, 0,
+            )?;
+            identity.activate()?;
+            registry.lock().register_validator(identity)?;
+            proposers.push(ProposerKey { validator_id, sk, pk });
+        }
+        let validators = proposers.iter().map(|p| p.validator_id.clone()).collect();
+        let sets = EpochValidatorSets::from_registry(
+            ValidatorSetConfig {
+                blocks_per_epoch: config.blocks_per_epoch,
+                min_stake:        DEV_VALIDATOR_STAKE,
+                ..ValidatorSetConfig::default()
+            },
+            &registry.lock(),
+        )?;
+        let sets = Arc::new(Mutex::new(sets));
+        let keys = Arc::new(Mutex::new(proposers.clone()));
+
+        let slot_ms = if config.block_time_ms == 0 { INSTANT_BLOCK_POLL_MS } else { config.block_time_ms };
+        let (producer, mut blocks) = BlockProducer::new(
+            proposers[0].validator_id.clone(),
+            DEV_VALIDATOR_STAKE as u64,
+            Arc::clone(&tx_pool),
+            Arc::clone(&blockchain),
+            Arc::clone(&state),
+            proposers[0].sk.clone(),
+            proposers[0].pk.clone(),
+            None,
+        );
+        let producer = producer
+            .with_block_interval_ms(slot_ms)
+            .with_rotation(proposers)
+            .with_validator_sets(Arc::clone(&sets), Arc::clone(&registry), Arc::clone(&keys))
+            .with_block_store(Arc::clone(&block_store))
+            .with_block_feed(block_feed.clone());
+        let missed = producer.missed_slots();
+
+        let connect = start_connect(data_dir.path(), config.live_interop).await?;
+        let rpc_state = RpcState::new()
+            .with_chain_id(ChainId::DEVNET)
+            .with_connect_orchestrator(connect)
+            .with_state_manager(Arc::clone(&state))
+            .with_transaction_pool(Arc::clone(&tx_pool))
+            .with_validator_registry(Arc::clone(&registry))
+            .with_validator_sets(sets, missed)
+            .with_block_store(Arc::clone(&block_store))
+            .with_vm_executor(Arc::new(simulation_executor()))
+            .with_contract_registry(Arc::new(ContractRegistry::with_policy(SecurityPolicy::devnet())))
+            .with_devnet_tools();
+
+        let mut handles = Vec::new();
+        {
+            let height = Arc::clone(&rpc_state.chain_height);
+            let produced = Arc::clone(&rpc_state.blocks_produced);
+            let txs = Arc::clone(&rpc_state.txs_processed);
+            handles.push(tokio::spawn(async move {
+                loop {
+                    match blocks.recv().await {
+                        Ok(fb) => {
+                            height.store(fb.height, Ordering::Relaxed);
+                            produced.fetch_add(1, Ordering::Relaxed);
+                            txs.fetch_add(fb.tx_count as u64, Ordering::Relaxed);
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                }
+            }));
+        }
+        handles.push(tokio::spawn(producer.run()));
+
+        let (rpc_addr, server) = warp::serve(rpc_routes_with_state(rpc_state))
+            .try_bind_ephemeral(([127, 0, 0, 1], config.rpc_port))
+            .map_err(|e| format!("RPC bind on port {}: {}", config.rpc_port, e))?;
+        handles.push(tokio::spawn(server));
+
+        Ok(Self {
+            accounts,
+            validators,
+            rpc_addr,
+            block_time_ms: config.block_time_ms,
+            live_interop: config.live_interop,
+            state,
+            tx_pool,
+            handles,
+            data_dir,
+            blockchain,
+            registry,
+            keys,
+            block_store,
+            block_feed,
+            genesis_height,
+        })
+    }
+
+    /// Start a read-only replica of this devnet, in `persist` or a temp dir.
+    ///
+    /// The replica holds no keys, pool or producer: it forks the state the
+    /// devnet's first block executed on, re-executes every block since,
+    /// and answers `/rpc/ready` once within `max_lag` blocks of the tip.
+    pub async fn start_replica(&self, persist: Option<PathBuf>, max_lag: u64) -> Result<DevnetReplica, String> {
+        let data_dir = DataDir::open(persist.as_deref())?;
+        let state = self.state.lock()
+            .fork_at(self.genesis_height, data_dir.path().join(STATE_SUBDIR))
+            .map_err(|e| format!("replica state: {}", e))?;
+        let state = Arc::new(Mutex::new(state));
+        let genesis = self.blockchain.read()
+            .map_err(|e| e.to_string())?
+            .get_block_by_index(0)
+            .ok_or("devnet chain has no genesis")?;
+        let blockchain = Blockchain::new(genesis, core_state(&state.lock()), TransactionPool::new(1), None)?;
+        let store = Arc::new(BlockStore::open(data_dir.path().join(BLOCKS_SUBDIR))?);
+        let follower = ReplicaFollower::new(Arc::new(RwLock::new(blockchain)), Arc::clone(&state))
+            .with_registry(Arc::clone(&self.registry))
+            .with_block_store(Arc::clone(&store));
+        let status = follower.status();
+
+        let rpc_state = RpcState::new()
+            .with_chain_id(ChainId::DEVNET)
+            .with_state_manager(Arc::clone(&state))
+            .with_validator_registry(Arc::clone(&self.registry))
+            .with_block_store(store)
+            .with_contract_registry(Arc::new(ContractRegistry::with_policy(SecurityPolicy::devnet())))
+            .with_replica(Arc::clone(&status), max_lag);
+
+        let mut handles = Vec::new();
+        {
+            // Subscribe before backfilling so no block falls in between.
+            let mut feed = self.block_feed.subscribe();
+            let upstream = Arc::clone(&self.block_store);
+            let height = Arc::clone(&rpc_state.chain_height);
+            handles.push(tokio::spawn(async move {
+                catch_up(&follower, &upstream).await;
+                loop {
+                    match feed.recv().await {
+                        Ok(block) => {
+                            if let Err(e) = follower.apply(block).await {
+                                tracing::warn!("[devnet replica] {}", e);
+                            }
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => {
+                            catch_up(&follower, &upstream).await;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    }
+                    height.store(follower.status().height(), Ordering::Relaxed);
+                }
+            }));
+        }
+
+        let (rpc_addr, server) = warp::serve(rpc_routes_with_state(rpc_state))
+            .try_bind_ephemeral(([127, 0, 0, 1], 0))
+            .map_err(|e| format!("replica RPC bind: {}", e))?;
+        handles.push(tokio::spawn(server));
+
+        Ok(DevnetReplica { rpc_addr, status, state, handles, data_dir })
+    }
+
+    pub fn rpc_url(&self) -> String {
+        format!("http://{}", self.rpc_addr)
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        self.data_dir.path()
+    }
+
+    pub fn state(&self) -> Arc<Mutex<StateManager>> {
+        Arc::clone(&self.state)
+    }
+
+    /// Sign blocks for the validator of `keystore` whenever it leads a
+    /// slot, as its own node would.
+    pub fn load_consensus_key(&self, keystore: &ConsensusKeystore) -> Result<(), String> {
+        let key = keystore.proposer_key()?;
+        let mut keys = self.keys.lock();
+        if !keys.iter().any(|k| k.validator_id == key.validator_id) {
+            keys.push(key);
+        }
+        Ok(())
+    }
+
+    /// Nonce the next transaction from `address` should carry, after its
+    /// pending ones.
+    pub async fn next_nonce(&self, address: &str) -> u64 {
+        let confirmed = self.state.lock().get_nonce(address);
+        self.tx_pool.pending_nonce(address, confirmed).await
+    }
+
+    /// Committed block at `height`.
+    pub fn block(&self, height: u64) -> Option<Block> {
+        self.blockchain.read().ok()?.get_block_by_index(height)
+    }
+
+    /// Height of the latest committed block.
+    pub fn height(&self) -> u64 {
+        self.blockchain.read().ok()
+            .and_then(|chain| chain.latest_block().map(|b| b.index))
+            .unwrap_or(0)
+    }
+
+    /// Startup banner: endpoint, validators and every dev account.
+    pub fn banner(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("BLEEP devnet — RPC {}\n", self.rpc_url()));
+        out.push_str(&format!(
+            "  Blocks:     {}\n",
+            match self.block_time_ms {
+                0 => "instant".to_string(),
+                ms => format!("every {} ms", ms),
+            }
+        ));
+        out.push_str(&format!("  Validators: {}\n", self.validators.join(", ")));
+        out.push_str(&format!(
+            "  Interop:    {}\n",
+            if self.live_interop { "live chain adapters" } else { "sandbox chains (/rpc/interop/sandbox/<chain>/state)" }
+        ));
+        out.push_str(&format!("  Data dir:   {}", self.data_dir().display()));
+        if let DataDir::Ephemeral(_) = self.data_dir {
+            out.push_str("  (removed on exit)");
+        }
+        out.push_str(&format!("\n  Signing keys: {}\n\n", self.data_dir().join(ACCOUNTS_FILE).display()));
+        let state = self.state.lock();
+        for a in &self.accounts {
+            out.push_str(&format!(
+                "  ({}) {}  {} µBLEEP\n      {}\n",
+                a.index, a.address, state.get_balance(&a.address), a.mnemonic
+            ));
+        }
+        out
+    }
+
+    /// Stop producing and serving, then flush state.  An ephemeral data
+    /// dir is deleted.
+    pub async fn shutdown(self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+        for handle in self.handles {
+            let _ = handle.await;
+        }
+        self.tx_pool.wal_commit();
+        if let Err(e) = self.state.lock().create_snapshot() {
+            tracing::warn!("[devnet] state flush failed: {}", e);
+        }
+    }
+}
+
+/// Apply every archived block above the follower's height.
+async fn catch_up(follower: &ReplicaFollower, upstream: &BlockStore) {
+    let from = follower.status().height() + 1;
+    let blocks = match upstream.tip() {
+        Ok(Some(tip)) if tip >= from => upstream.range(from, tip),
+        Ok(_) => return,
+        Err(e) => Err(e),
+    };
+    match blocks {
+        Ok(blocks) => {
+            for stored in blocks {
+                if let Err(e) = follower.apply(stored.block).await {
+                    tracing::warn!("[devnet replica] catch-up: {}", e);
+                    return;
+                }
+            }
+        }
+        Err(e) => tracing::warn!("[devnet replica] catch-up: {}", e),
+    }
+}
+
+/// A running devnet replica.  Call `shutdown` to stop it and flush state.
+pub struct DevnetReplica {
+    pub rpc_addr: SocketAddr,
+    status:   Arc<SyncStatus>,
+    state:    Arc<Mutex<StateManager>>,
+    handles:  Vec<JoinHandle<()>>,
+    data_dir: DataDir,
+}
+
+impl DevnetReplica {
+    pub fn rpc_url(&self) -> String {
+        format!("http://{}", self.rpc_addr)
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        self.data_dir.path()
+    }
+
+    pub fn height(&self) -> u64 {
+        self.status.height()
+    }
+
+    pub async fn shutdown(self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+        for handle in self.handles {
+            let _ = handle.await;
+        }
+        if let Err(e) = self.state.lock().create_snapshot() {
+            tracing::warn!("[devnet replica] state flush failed: {}", e);
+        }
+    }
+}
+
+/// Executor behind `/rpc/tx/simulate`; simulated intents are unsigned.
+/// BLEEP Connect with every chain sandboxed, or live with `live_interop`.
+async fn start_connect(data_dir: &Path, live_interop: bool) -> Result<Arc<BleepConnectOrchestrator>, String> {
+    let mut builder = BleepConnectBuilder::new().data_directory(data_dir.join(CONNECT_SUBDIR));
+    if !live_interop {
+        builder = builder.sandbox_all(SandboxConfig::default());
+    }
+    builder.build(ClassicalKeyPair::generate()).await.map_err(|e| format!("BLEEP Connect: {}", e))
+}
+
+fn simulation_executor() -> Executor {
+    let mut cfg = ExecutorConfig::default();
+    cfg.router.verify_signatures = false;
+    cfg.wall_clock = true;
+    Executor::production(cfg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    fn config() -> DevnetConfig {
+        DevnetConfig { block_time_ms: 0, accounts: 2, rpc_port: 0, ..DevnetConfig::default() }
+    }
+
+    async fn balance(devnet: &Devnet, address: &str) -> u128 {
+        let json: serde_json::Value = reqwest::get(format!("{}/rpc/state/{}", devnet.rpc_url(), address))
+            .await.unwrap()
+            .json().await.unwrap();
+        json["balance"].as_str().unwrap().parse().unwrap()
+    }
+
+    async fn wait_for_balance(devnet: &Devnet, address: &str, expected: u128) {
+        for _ in 0..100 {
+            if balance(devnet, address).await == expected {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        panic!("{} never reached balance {}", address, expected);
+    }
+
+    async fn send(devnet: &Devnet, from: usize, to: usize, amount: u64, ts: u64) {
+        let sender = &devnet.accounts[from];
+        let nonce = devnet.next_nonce(&sender.address).await;
+        let tx = sender.transfer(&devnet.accounts[to].address, amount, ts, nonce).unwrap();
+        let resp: serde_json::Value = reqwest::Client::new()
+            .post(format!("{}/rpc/tx", devnet.rpc_url()))
+            .json(&tx)
+            .send().await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(resp["status"], "accepted");
+    }
+
+    async fn get_json(url: String) -> (u16, serde_json::Value) {
+        let resp = reqwest::get(url).await.unwrap();
+        (resp.status().as_u16(), resp.json().await.unwrap())
+    }
+
+    #[test]
+    fn dev_accounts_are_deterministic() {
+        let (m0, a0) = DevAccount::mnemonic_and_address(0).unwrap();
+        assert_eq!(DevAccount::mnemonic_and_address(0).unwrap(), (m0.clone(), a0.clone()));
+        assert_eq!(m0.split_whitespace().count(), 12);
+        assert_ne!(DevAccount::mnemonic_and_address(1).unwrap().1, a0);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn printed_account_transfers_and_deploys_over_rpc() {
+        let devnet = Devnet::start(DevnetConfig { validators: 3, ..config() }).await.unwrap();
+        let (alice, bob) = (devnet.accounts[0].clone(), devnet.accounts[1].clone());
+        assert!(devnet.banner().contains(&alice.mnemonic));
+        assert_eq!(balance(&devnet, &alice.address).await, DEV_ACCOUNT_BALANCE);
+
+        send(&devnet, 0, 1, 5_000, 1_700_000_000).await;
+        wait_for_balance(&devnet, &bob.address, DEV_ACCOUNT_BALANCE + 5_000).await;
+
+        let resp = reqwest::Client::new()
+            .post(format!("{}/rpc/devnet/deploy", devnet.rpc_url()))
+            .json(&serde_json::json!({
+                "deployer": alice.address,
+                "code": hex::encode(EMPTY_MODULE),
+                "signature": alice.sign_deploy(EMPTY_MODULE).unwrap(),
+            }))
+            .send().await.unwrap();
+        assert!(resp.status().is_success());
+        let deployed: serde_json::Value = resp.json().await.unwrap();
+        let upgrades = reqwest::get(format!(
+            "{}/rpc/contract/{}/upgrades", devnet.rpc_url(), deployed["address"].as_str().unwrap()
+        )).await.unwrap();
+        assert!(upgrades.status().is_success());
+
+        devnet.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn interop_chains_default_to_sandboxes() {
+        let devnet = Devnet::start(config()).await.unwrap();
+        assert!(devnet.banner().contains("sandbox chains"));
+
+        let (status, state) = get_json(format!("{}/rpc/interop/sandbox/ethereum/state", devnet.rpc_url())).await;
+        assert_eq!(status, 200);
+        assert_eq!(state["chain"], "ethereum");
+        assert_eq!(state["config"]["confirmation_depth"], SandboxConfig::default().confirmation_depth);
+        assert!(state["transactions"].as_array().unwrap().is_empty());
+
+        let (status, _) = get_json(format!("{}/rpc/interop/sandbox/mars/state", devnet.rpc_url())).await;
+        assert_eq!(status, 404);
+        devnet.shutdown().await;
+
+        let devnet = Devnet::start(DevnetConfig { live_interop: true, ..config() }).await.unwrap();
+        let (status, _) = get_json(format!("{}/rpc/interop/sandbox/ethereum/state", devnet.rpc_url())).await;
+        assert_eq!(status, 404);
+        devnet.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn persisted_devnet_restarts_with_prior_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let persist = DevnetConfig { persist: Some(dir.path().to_path_buf()), ..config() };
+
+        let devnet = Devnet::start(persist.clone()).await.unwrap();
+        let bob = devnet.accounts[1].address.clone();
+        send(&devnet, 0, 1, 7_000, 1_700_000_001).await;
+        wait_for_balance(&devnet, &bob, DEV_ACCOUNT_BALANCE + 7_000).await;
+        devnet.shutdown().await;
+
+        // Not re-funded, and the stored keys still sign for the same accounts.
+        let devnet = Devnet::start(persist).await.unwrap();
+        assert_eq!(devnet.accounts[1].address, bob);
+        assert_eq!(balance(&devnet, &bob).await, DEV_ACCOUNT_BALANCE + 7_000);
+        send(&devnet, 0, 1, 1_000, 1_700_000_002).await;
+        wait_for_balance(&devnet, &bob, DEV_ACCOUNT_BALANCE + 8_000).await;
+        devnet.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn fork_from_restores_snapshot_balances() {
+        let snapshot = tempfile::tempdir().unwrap();
+        {
+            let mut source = StateManager::open(snapshot.path()).unwrap();
+            source.mint("bleep:snapshot:treasury", 42_000).unwrap();
+            source.advance_block();
+            source.create_snapshot().unwrap();
+        }
+
+        let devnet = Devnet::start(DevnetConfig { fork_from: Some(snapshot.path().to_path_buf()), ..config() })
+            .await
+            .unwrap();
+        assert_eq!(balance(&devnet, "bleep:snapshot:treasury").await, 42_000);
+        assert_eq!(balance(&devnet, &devnet.accounts[0].address).await, DEV_ACCOUNT_BALANCE);
+        devnet.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn replica_keeps_pace_serves_reads_and_refuses_writes() {
+        let devnet = Devnet::start(DevnetConfig { validators: 2, ..config() }).await.unwrap();
+        let bob = devnet.accounts[1].address.clone();
+        send(&devnet, 0, 1, 1_000, 1_700_000_010).await;
+        wait_for_balance(&devnet, &bob, DEV_ACCOUNT_BALANCE + 1_000).await;
+
+        // Joins after block 1: catches up from the archive, then follows.
+        let replica = devnet.start_replica(None, 0).await.unwrap();
+        send(&devnet, 0, 1, 2_000, 1_700_000_011).await;
+        wait_for_balance(&devnet, &bob, DEV_ACCOUNT_BALANCE + 3_000).await;
+        let tip = get_json(format!("{}/rpc/block/latest", devnet.rpc_url())).await.1["height"].as_u64().unwrap();
+        assert!(tip >= 2);
+        let mut ready = (0, serde_json::Value::Null);
+        for _ in 0..100 {
+            ready = get_json(format!("{}/rpc/ready", replica.rpc_url())).await;
+            if ready.0 == 200 && ready.1["height"] == tip {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        assert_eq!((ready.0, ready.1["height"].as_u64()), (200, Some(tip)), "{}", ready.1);
+        assert_eq!(replica.height(), tip);
+
+        // Blocks and proofs match the validators'.
+        for h in 1..=tip {
+            let ours = get_json(format!("{}/rpc/block/{}", replica.rpc_url(), h)).await;
+            let theirs = get_json(format!("{}/rpc/block/{}", devnet.rpc_url(), h)).await;
+            assert_eq!(ours, theirs);
+            assert_eq!(ours.0, 200);
+        }
+        let proof = |url: String| get_json(format!("{}/rpc/proof/{}", url, bob));
+        let ours = proof(replica.rpc_url()).await;
+        assert_eq!(ours, proof(devnet.rpc_url()).await);
+        assert_eq!(ours.1["exists"], true);
+
+        // Transactions are refused with the replica error.
+        let nonce = devnet.next_nonce(&devnet.accounts[0].address).await;
+        let tx = devnet.accounts[0].transfer(&bob, 1, 1_700_000_012, nonce).unwrap();
+        let resp = reqwest::Client::new()
+            .post(format!("{}/rpc/tx", replica.rpc_url()))
+            .json(&tx)
+            .send().await.unwrap();
+        assert_eq!(resp.status().as_u16(), 403);
+        let body: serde_json::Value = resp.json().await.unwrap();
+        assert_eq!(body["code"], "replica_mode");
+
+        // Nothing key-like was ever written to the replica's data dir.
+        let mut dirs = vec![replica.data_dir().to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                let name = path.file_name().unwrap().to_string_lossy().to_lowercase();
+                assert!(name != ACCOUNTS_FILE && !name.contains("key"), "{}", path.display());
+                if path.is_dir() {
+                    dirs.push(path);
+                }
+            }
+        }
+
+        replica.shutdown().await;
+        devnet.shutdown().await;
+    }
+}
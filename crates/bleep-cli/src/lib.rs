@@ -6,9 +6,24 @@
 //!   - `validator unstake` — initiate graceful exit and stake withdrawal
 //!   - `validator list`    — list all active validators and their stakes
 //!   - `validator status`  — show own validator status and slashing history
+//!   - `validator init` / `bond` / `unbond` — consensus key, bonded stake and
+//!     withdrawal (see `validator_onboarding`)
+
+use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 
+pub mod block_query;
+pub mod contract_project;
+pub mod devnet;
+pub mod offline_tx;
+pub mod send_prompt;
+pub mod state_proof;
+pub mod tx_wait;
+pub mod validator_onboarding;
+
+use tx_wait::{OutputFormat, WaitTarget};
+
 #[derive(Parser)]
 #[command(name = "bleep-cli")]
 #[command(about = "BLEEP Blockchain CLI — Sprint 6", long_about = None)]
@@ -21,7 +36,47 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Start a full BLEEP node
-    StartNode,
+    StartNode {
+        /// Seed node to dial on start, retried with exponential backoff (repeatable)
+        #[arg(long = "bootstrap", value_name = "ADDR")]
+        bootstrap: Vec<std::net::SocketAddr>,
+        /// JSON file of known peers, reloaded and dialed on the next start
+        #[arg(long, value_name = "PATH")]
+        peer_store: Option<PathBuf>,
+        /// Stream transport: tcp, or quic when built with the `quic` feature
+        #[arg(long, value_name = "tcp|quic", default_value = "tcp")]
+        transport: bleep_p2p::TransportKind,
+    },
+
+    /// Run a local development network in this process
+    Devnet {
+        /// In-process validators taking turns to sign blocks
+        #[arg(long, default_value_t = 1)]
+        validators: usize,
+        /// Block interval in milliseconds; 0 = a block as soon as a transaction arrives
+        #[arg(long, default_value_t = 1_000)]
+        block_time_ms: u64,
+        /// Number of pre-funded dev accounts
+        #[arg(long, default_value_t = 10)]
+        accounts: usize,
+        #[arg(long, default_value_t = devnet::DEFAULT_DEVNET_RPC_PORT)]
+        rpc_port: u16,
+        /// Keep state in this directory across restarts (default: temp dir)
+        #[arg(long)]
+        persist: Option<String>,
+        /// Start from a state snapshot directory (see `state snapshot`)
+        #[arg(long)]
+        fork_from: Option<String>,
+        /// Use live interop chain adapters instead of in-memory sandboxes
+        #[arg(long)]
+        live_interop: bool,
+        /// Blocks per validator-set epoch; a bond joins the set two epochs on
+        #[arg(long, default_value_t = 100)]
+        blocks_per_epoch: u64,
+        /// Blocks between `validator unbond` and withdrawing the stake
+        #[arg(long, default_value_t = 100)]
+        unbonding_blocks: u64,
+    },
 
     /// Wallet operations
     Wallet {
@@ -53,8 +108,17 @@ pub enum Commands {
         task: GovernanceCommand,
     },
 
-    /// Verify ZKPs
-    Zkp { proof: String },
+    /// Verify a Groth16 proof; exits non-zero unless it is valid
+    Zkp {
+        /// Hex of the compressed BLS12-381 proof
+        proof: String,
+        /// Comma-separated public inputs, as decimal field elements
+        #[arg(long, value_delimiter = ',')]
+        public_inputs: Vec<String>,
+        /// Compressed Groth16 verifying key (default: ~/.bleep/zkp/verifying.key)
+        #[arg(long)]
+        verifying_key: Option<std::path::PathBuf>,
+    },
 
     /// State management
     State {
@@ -63,7 +127,20 @@ pub enum Commands {
     },
 
     /// Print telemetry metrics
-    Telemetry,
+    Telemetry {
+        /// Refresh until interrupted
+        #[arg(long)]
+        watch: bool,
+        /// Seconds between refreshes with --watch
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+        /// Also draw every metric's history over this span (e.g. 30m, 1h, 2d)
+        #[arg(long)]
+        history: Option<String>,
+        /// Print the metrics snapshot as JSON instead of a table
+        #[arg(long, conflicts_with = "history")]
+        json: bool,
+    },
 
     /// PAT (Programmable Asset Token) tasks
     Pat {
@@ -74,8 +151,14 @@ pub enum Commands {
     /// Display node information
     Info,
 
-    /// Blockchain operations
+    /// Read blocks from the node's archive
     Block {
+        /// Directory holding `bleep-blocks`
+        #[arg(long, default_value = "/tmp", global = true)]
+        data_dir: String,
+        /// Print bincode hex of each block instead of JSON
+        #[arg(long, global = true)]
+        raw: bool,
         #[command(subcommand)]
         task: BlockCommand,
     },
@@ -97,22 +180,195 @@ pub enum Commands {
         #[command(subcommand)]
         action: FaucetCommand,
     },
+
+    /// Offline debugging tools
+    Debug {
+        #[command(subcommand)]
+        task: DebugCommand,
+    },
+
+    /// Node operator tools (need BLEEP_RPC_ADMIN_TOKEN)
+    Admin {
+        #[command(subcommand)]
+        task: AdminCommand,
+    },
+
+    /// Network survey tools
+    Net {
+        #[command(subcommand)]
+        task: NetCommand,
+    },
+
+    /// Smart contract tools
+    Contract {
+        #[command(subcommand)]
+        task: ContractCommand,
+    },
 }
 
 // ── Wallet ────────────────────────────────────────────────────────────────────
 
 #[derive(Subcommand)]
 pub enum WalletCommand {
-    /// Generate a new SPHINCS+ + Kyber-768 keypair and encrypted wallet
-    Create,
-    /// Query balance from /rpc/state (offline fallback to local RocksDB)
-    Balance,
-    /// Import from BIP-39 mnemonic (PBKDF2-HMAC-SHA512, 2048 rounds)
-    Import { phrase: String },
-    /// Export wallet addresses
-    Export,
+    /// Create an HD wallet from a fresh BIP-39 mnemonic and seal it in a
+    /// keystore (passphrase from BLEEP_WALLET_PASSWORD)
+    #[command(visible_alias = "create")]
+    New,
+    /// Import account 0 of a BIP-39 mnemonic into a keystore
+    Import { mnemonic: String },
+    /// Balance and nonce of an address from /rpc/state (offline fallback to local state)
+    Balance { address: String },
+    /// Write every local wallet address to a JSON file
+    Export { path: std::path::PathBuf },
     /// Delete a wallet by address
     Delete { address: String },
+    /// Send tokens, asking for anything not given and confirming a preview
+    Send {
+        /// Recipient BLEEP1 address or @contact
+        #[arg(long)]
+        to: Option<String>,
+        /// Token symbol (default BLEEP)
+        #[arg(long)]
+        token: Option<String>,
+        /// Amount in whole tokens, e.g. 12.5
+        #[arg(long)]
+        amount: Option<String>,
+        /// Most to pay per inclusion (base fee + tip); default from `/rpc/tx/estimate_fee`
+        #[arg(long)]
+        max_fee: Option<u64>,
+        /// Tip for the block proposer
+        #[arg(long, default_value_t = 0)]
+        tip: u64,
+        /// Account nonce to sign for (default: the sender's next one); reuse a
+        /// pending transaction's nonce with a higher tip to replace it
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Skip the confirmation (still shown above BLEEP_WALLET_PREVIEW_ABOVE)
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Native, PAT and bridged balances across shards and chains
+    Portfolio {
+        /// Address to query (defaults to every local wallet)
+        address: Option<String>,
+    },
+    /// Sign a message to prove control of an address; prints a JSON envelope
+    SignMessage {
+        /// Message text
+        #[arg(long, conflicts_with = "file", required_unless_present = "file")]
+        message: Option<String>,
+        /// Read the message bytes from a file
+        #[arg(long)]
+        file: Option<String>,
+        /// Signing wallet (defaults to the first local wallet)
+        #[arg(long)]
+        address: Option<String>,
+    },
+    /// Verify a signed-message envelope; no local keys needed
+    VerifyMessage {
+        /// Address that claims to have signed
+        address: String,
+        /// JSON envelope from `sign-message`, or a path to a file holding it
+        signature: String,
+        /// Message text
+        #[arg(long, conflicts_with = "file", required_unless_present = "file")]
+        message: Option<String>,
+        /// Read the message bytes from a file
+        #[arg(long)]
+        file: Option<String>,
+    },
+    /// Encrypted address book; pass `@label` wherever a recipient is expected
+    Contacts {
+        #[command(subcommand)]
+        action: ContactsCommand,
+    },
+    /// Spending limits, recipient lists and approval threshold for this wallet
+    Policy {
+        #[command(subcommand)]
+        action: PolicyCommand,
+    },
+    /// Transfers held for a second approval
+    Pending {
+        #[command(subcommand)]
+        action: PendingCommand,
+    },
+    /// Approve a held transfer (same as `pending approve`)
+    ApprovePending {
+        /// Pending request id from `pending list`
+        id: String,
+        /// Local wallet of a configured approver (password from BLEEP_APPROVER_PASSWORD)
+        #[arg(long)]
+        approver: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PolicyCommand {
+    /// Print the current policy as JSON
+    Show,
+    /// Install a policy from a JSON file, signed by the policy admin
+    Set {
+        /// JSON `SpendingPolicy` (admin, max_per_tx, daily_limit, allow_list,
+        /// deny_list, approval_threshold, approvers)
+        file: String,
+        /// Local wallet holding the policy-admin key (password from BLEEP_ADMIN_PASSWORD)
+        #[arg(long)]
+        admin: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PendingCommand {
+    /// List transfers waiting for approval
+    List,
+    /// Approve a held transfer, then sign and broadcast it
+    Approve {
+        /// Pending request id from `pending list`
+        id: String,
+        /// Local wallet of a configured approver (password from BLEEP_APPROVER_PASSWORD)
+        #[arg(long)]
+        approver: String,
+    },
+    /// Drop a held transfer without signing it
+    Reject { id: String },
+}
+
+#[derive(Subcommand)]
+pub enum ContactsCommand {
+    /// Add a contact, or update the address of an existing label
+    Add {
+        /// Label used as `@label`
+        label: String,
+        /// BLEEP1 address
+        address: String,
+        /// Free-form note
+        #[arg(long)]
+        note: Option<String>,
+        /// Token symbol this contact usually receives
+        #[arg(long)]
+        token: Option<String>,
+    },
+    /// Remove a contact
+    Remove { label: String },
+    /// List contacts
+    List,
+    /// Write an encrypted bundle of all contacts
+    Export {
+        /// Output file
+        #[arg(long)]
+        out: String,
+        /// Passphrase sealing the bundle (may differ from the wallet password)
+        #[arg(long)]
+        passphrase: String,
+    },
+    /// Merge a bundle written by `export`
+    Import {
+        /// Bundle file
+        file: String,
+        /// Passphrase the bundle was exported with
+        #[arg(long)]
+        passphrase: String,
+    },
 }
 
 // ── Transactions ──────────────────────────────────────────────────────────────
@@ -121,13 +377,149 @@ pub enum WalletCommand {
 pub enum TxCommand {
     /// Sign and broadcast a transfer transaction
     Send {
-        /// Recipient BLEEP1 address
+        /// Recipient BLEEP1 address or @contact
         to: String,
         /// Amount in BLEEP base units
         amount: u64,
+        /// Most to pay per inclusion (base fee + tip); default from `/rpc/tx/estimate_fee`
+        #[arg(long)]
+        max_fee: Option<u64>,
+        /// Tip for the block proposer
+        #[arg(long, default_value_t = 0)]
+        tip: u64,
+        /// Account nonce to sign for (default: the sender's next one); reuse a
+        /// pending transaction's nonce with a higher tip to replace it
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Block until the transaction is `accepted`, `included` or
+        /// `confirmed:N`; the exit code names the outcome (see `tx_wait`)
+        #[arg(long)]
+        wait: Option<WaitTarget>,
+        /// Seconds to wait before giving up (exit 10)
+        #[arg(long, default_value_t = tx_wait::DEFAULT_TIMEOUT_SECS)]
+        timeout: u64,
+        /// Progress format with --wait; `json` streams JSON lines
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
     },
     /// Retrieve transaction history
     History,
+    /// Dry-run a transfer or contract call without broadcasting it
+    Simulate {
+        /// Recipient BLEEP1 address or @contact
+        to: String,
+        /// Amount in BLEEP base units
+        amount: u64,
+        /// Simulate against the state at this block height
+        #[arg(long)]
+        height: Option<u64>,
+        /// Hex contract address (turns the tx into a contract call)
+        #[arg(long)]
+        contract: Option<String>,
+        /// Hex calldata for the contract call
+        #[arg(long)]
+        calldata: Option<String>,
+        /// Gas limit for the contract call
+        #[arg(long)]
+        gas_limit: Option<u64>,
+    },
+    /// Sign a transfer now and have the node release it at a height or time
+    Schedule {
+        /// Recipient BLEEP1 address or @contact
+        to: String,
+        /// Amount in BLEEP base units
+        amount: u64,
+        /// Release once the chain reaches this height
+        #[arg(long, conflicts_with = "at_time", required_unless_present = "at_time")]
+        at_height: Option<u64>,
+        /// Release once this unix time (seconds) has passed
+        #[arg(long)]
+        at_time: Option<u64>,
+        /// Drop the transaction if it has not been released by this height
+        #[arg(long, conflicts_with = "expiry_time")]
+        expiry_height: Option<u64>,
+        /// Drop the transaction if it has not been released by this unix time
+        #[arg(long)]
+        expiry_time: Option<u64>,
+    },
+    /// List transactions the node is holding for release
+    ListScheduled {
+        /// Only show entries from this sender
+        #[arg(long)]
+        sender: Option<String>,
+    },
+    /// Cancel a scheduled transaction (must be signed by its sender)
+    CancelScheduled {
+        /// Schedule id returned by `tx schedule`
+        id: String,
+    },
+    /// Submit signed transactions from a JSON-lines file in batches
+    SubmitBatch {
+        /// File with one `POST /rpc/tx` body per line
+        #[arg(long)]
+        file: String,
+        /// API key, when the node enforces keys
+        #[arg(long)]
+        api_key: Option<String>,
+        /// Block until every accepted transaction is `accepted`, `included` or
+        /// `confirmed:N`; the exit code names the outcome (see `tx_wait`)
+        #[arg(long)]
+        wait: Option<WaitTarget>,
+        /// Seconds to wait before giving up (exit 10)
+        #[arg(long, default_value_t = tx_wait::DEFAULT_TIMEOUT_SECS)]
+        timeout: u64,
+        /// Progress format with --wait; `json` streams JSON lines
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        output: OutputFormat,
+    },
+    /// Build an unsigned transfer for `tx sign` on an offline machine
+    Create {
+        /// Recipient BLEEP1 address or @contact
+        to: String,
+        /// Amount in BLEEP base units
+        amount: u64,
+        /// Sender BLEEP1 address (default: the first local wallet)
+        #[arg(long)]
+        from: Option<String>,
+        /// Most to pay per inclusion (base fee + tip); default from `/rpc/tx/estimate_fee`
+        #[arg(long)]
+        max_fee: Option<u64>,
+        /// Tip for the block proposer
+        #[arg(long, default_value_t = 0)]
+        tip: u64,
+        /// Account nonce to sign for (default: the sender's next one)
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Write the transaction here instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Sign a `tx create` file in place with a wallet key file; needs no node
+    Sign {
+        /// Unsigned transaction from `tx create`
+        #[arg(long)]
+        file: PathBuf,
+        /// Wallet key file: one wallet entry as JSON
+        #[arg(long)]
+        key: PathBuf,
+    },
+    /// Submit a signed transaction file
+    Broadcast {
+        /// Signed transaction from `tx sign`
+        #[arg(long)]
+        file: PathBuf,
+        /// Node RPC to submit to (default: $BLEEP_RPC)
+        #[arg(long)]
+        rpc_url: Option<String>,
+    },
+    /// Look a transaction up in recent blocks by hash
+    Status {
+        /// Transaction hash, as printed by `tx broadcast`
+        id: String,
+        /// Blocks below the tip to search
+        #[arg(long, default_value_t = 1000)]
+        depth: u64,
+    },
 }
 
 // ── Validator (Sprint 6) ──────────────────────────────────────────────────────
@@ -163,11 +555,55 @@ pub enum ValidatorCommand {
     /// List all active validators, their stakes, and liveness scores.
     List,
 
-    /// Show the status of your own validator (stake, slashing history, state).
+    /// Generate the consensus keystore and node identity if missing, write
+    /// the node's `[consensus]` config stanza, and print the registration payload.
+    Init {
+        /// Validator home (default: ~/.bleep/validator)
+        #[arg(long)]
+        home: Option<String>,
+    },
+
+    /// Bond stake to this validator's consensus key.
+    ///
+    /// Checks balance, the minimum stake and that the key is not bound to
+    /// another account, shows a preview, then signs and submits.
+    Bond {
+        /// Amount in BLEEP, e.g. `1000` or `1000.5`
+        #[arg(long)]
+        amount: String,
+        /// Wallet address to bond from (default: the first local wallet)
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long)]
+        home: Option<String>,
+        #[arg(long)]
+        max_fee: Option<u64>,
+        #[arg(long, default_value_t = 0)]
+        tip: u64,
+        /// Submit without asking for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Start unbonding; once the unbonding period has passed, withdraw the stake.
+    Unbond {
+        /// Wallet address that bonded (default: the first local wallet)
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long)]
+        max_fee: Option<u64>,
+        #[arg(long, default_value_t = 0)]
+        tip: u64,
+    },
+
+    /// Show the status of your own validator: bonded stake, activation
+    /// countdown, set membership, missed slots and unbonding progress.
     Status {
-        /// Validator ID to query (defaults to the wallet's derived validator ID)
+        /// Validator ID to query (defaults to the one in the validator home)
         #[arg(long)]
         validator_id: Option<String>,
+        #[arg(long)]
+        home: Option<String>,
     },
 
     /// Submit slashing evidence for a misbehaving validator.
@@ -179,6 +615,34 @@ pub enum ValidatorCommand {
         #[arg(long)]
         evidence_file: String,
     },
+
+    /// Show claimable and accruing block rewards, bonds and commission.
+    Rewards {
+        /// Address to query (defaults to the first local wallet)
+        #[arg(long)]
+        address: Option<String>,
+    },
+
+    /// Claim every accrued reward into the wallet (a `ClaimRewards` transaction).
+    ClaimRewards {
+        /// Most to pay per inclusion (base fee + tip); default from `/rpc/tx/estimate_fee`
+        #[arg(long)]
+        max_fee: Option<u64>,
+        /// Tip for the block proposer
+        #[arg(long, default_value_t = 0)]
+        tip: u64,
+    },
+
+    /// Set the commission taken from delegators' rewards, from the next epoch on.
+    SetCommission {
+        /// Commission in basis points (100 = 1%)
+        #[arg(long)]
+        bps: u16,
+        #[arg(long)]
+        max_fee: Option<u64>,
+        #[arg(long, default_value_t = 0)]
+        tip: u64,
+    },
 }
 
 // ── AI ────────────────────────────────────────────────────────────────────────
@@ -193,9 +657,34 @@ pub enum AiCommand {
 
 #[derive(Subcommand)]
 pub enum GovernanceCommand {
-    Propose { proposal: String },
+    Propose {
+        proposal: String,
+        /// Commit to the full text in this file instead of storing it inline
+        #[arg(long)]
+        content_file: Option<String>,
+        /// MIME type of --content-file
+        #[arg(long, default_value = "text/markdown")]
+        mime: String,
+        /// Where the file is published: ipfs://<cid> or https://… (repeatable)
+        #[arg(long = "hint")]
+        hints: Vec<String>,
+    },
     Vote { proposal_id: u32, yes: bool },
     List,
+    /// Show a proposal from the node, with verified off-chain text
+    Show { proposal_id: String },
+    /// List the node's runtime parameters, or one parameter's change history
+    Params {
+        /// Show this parameter's change history
+        #[arg(long)]
+        key: Option<String>,
+        /// Only parameters of this subsystem
+        #[arg(long)]
+        subsystem: Option<String>,
+        /// Print the response as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 // ── State ─────────────────────────────────────────────────────────────────────
@@ -204,6 +693,57 @@ pub enum GovernanceCommand {
 pub enum StateCommand {
     Snapshot,
     Restore { snapshot_path: String },
+    /// Check blocks, state and shard heights for consistency
+    Fsck {
+        /// Directory holding `bleep-state` and `bleep-blocks`
+        #[arg(long, default_value = "/tmp")]
+        data_dir: String,
+        /// Roll back to the last consistent height if issues are found
+        #[arg(long)]
+        repair: bool,
+    },
+    /// Bring the state store up to this binary's schema version
+    Migrate {
+        /// Directory holding `bleep-state`
+        #[arg(long, default_value = "/tmp")]
+        data_dir: String,
+        /// Report the steps that would run without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print an account's balance, nonce and shard
+    Get {
+        key: String,
+        /// Directory holding `bleep-state`
+        #[arg(long, default_value = "/tmp")]
+        data_dir: String,
+    },
+    /// Print the global state root
+    Root {
+        /// Directory holding `bleep-state`
+        #[arg(long, default_value = "/tmp")]
+        data_dir: String,
+    },
+    /// Emit an inclusion (or exclusion) proof for an account as JSON
+    Proof {
+        key: String,
+        /// Directory holding `bleep-state`
+        #[arg(long, default_value = "/tmp")]
+        data_dir: String,
+        /// Write the proof here instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Check a proof file offline against a trusted state root
+    VerifyProof {
+        key: String,
+        /// `<balance>:<nonce>`, or `absent` for an exclusion proof
+        value: String,
+        proof_file: String,
+        /// Hex state root to verify against
+        #[arg(long)]
+        root: String,
+    },
 }
 
 // ── PAT ───────────────────────────────────────────────────────────────────────
@@ -244,7 +784,7 @@ pub enum PatCommand {
         /// Caller / owner address (hex)
         #[arg(long)]
         from: String,
-        /// Recipient address (hex)
+        /// Recipient address (hex) or @contact
         #[arg(long)]
         to: String,
         /// Amount to mint (base units, 8 decimal places)
@@ -271,7 +811,7 @@ pub enum PatCommand {
         /// Sender address (hex)
         #[arg(long)]
         from: String,
-        /// Recipient address (hex)
+        /// Recipient address (hex) or @contact
         #[arg(long)]
         to: String,
         /// Amount to transfer (base units)
@@ -402,10 +942,106 @@ pub enum FaucetCommand {
 
 #[derive(Subcommand)]
 pub enum BlockCommand {
-    /// Print the latest block
-    Latest,
-    /// Get a block by hash or height
-    Get { identifier: String },
-    /// Validate a block by hash
-    Validate { hash: String },
+    /// Print a block by height or hash
+    Get { index_or_hash: String },
+    /// Print the tip
+    #[command(visible_alias = "latest")]
+    Head,
+    /// Print blocks `from..=to` as a JSON array
+    Range { from: u64, to: u64 },
+    /// Re-run block validation and the parent-link check on a block by
+    /// height or hash
+    #[command(visible_alias = "validate")]
+    Verify { index_or_hash: String },
+}
+
+// ── Debug ─────────────────────────────────────────────────────────────────────
+
+#[derive(Subcommand)]
+pub enum DebugCommand {
+    /// Re-execute stored blocks and report the first divergence
+    Replay {
+        /// First block height to replay
+        #[arg(long)]
+        from: u64,
+        /// Last block height to replay (inclusive)
+        #[arg(long)]
+        to: u64,
+        /// Directory holding `bleep-state` and `bleep-blocks`
+        #[arg(long, default_value = "/tmp")]
+        data_dir: String,
+        /// Emit a step-level execution trace for this tx hash
+        #[arg(long)]
+        trace: Option<String>,
+    },
+}
+
+// ── Admin ─────────────────────────────────────────────────────────────────────
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// Operator audit trail
+    Audit {
+        #[command(subcommand)]
+        task: AuditCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommand {
+    /// Walk the hash chain and report the first tampered record
+    Verify,
+}
+
+// ── Contract ──────────────────────────────────────────────────────────────────
+
+#[derive(Subcommand)]
+pub enum ContractCommand {
+    /// Statically analyze a WASM contract and print the findings
+    Analyze {
+        /// Contract bytecode (.wasm)
+        file: String,
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Generate a counter contract project in ./<name>
+    New {
+        name: String,
+        #[arg(long, value_enum, default_value_t = contract_project::Lang::Rust)]
+        lang: contract_project::Lang,
+        /// Local bleep-contract-sdk checkout to depend on (Rust only)
+        #[arg(long)]
+        sdk_path: Option<std::path::PathBuf>,
+    },
+    /// Compile a `contract new` project to policy-checked wasm in target/bleep/
+    Build {
+        /// Project directory
+        #[arg(long, default_value = ".")]
+        path: std::path::PathBuf,
+    },
+}
+
+// ── Net ───────────────────────────────────────────────────────────────────────
+
+#[derive(Subcommand)]
+pub enum NetCommand {
+    /// Walk peer-exchange responses from a seed and print a network map as JSON
+    Crawl {
+        /// P2P address of the first node to ask, e.g. 1.2.3.4:7700
+        #[arg(long)]
+        seed: String,
+        /// Stop after visiting this many nodes
+        #[arg(long, default_value_t = 1000)]
+        max_nodes: usize,
+        /// Write the map to this file instead of stdout
+        #[arg(long)]
+        out: Option<String>,
+    },
+    /// Show the node's Kademlia DHT: bucket fill, stored records, lookup latency
+    DhtStatus {
+        /// Print the raw stats as JSON
+        #[arg(long)]
+        json: bool,
+    },
 }
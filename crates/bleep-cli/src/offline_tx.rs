@@ -0,0 +1,148 @@
+//! # Offline signing
+//!
+//! `tx create` → `tx sign` → `tx broadcast` splits a transfer across two
+//! machines: the online one builds the unsigned transaction (fee estimate
+//! included), an air-gapped one holding the key signs it, and the signed
+//! file goes back online to be submitted.
+//!
+//! Files are `POST /rpc/tx` bodies; an unsigned one has an empty
+//! `signature`.  The online side records the node's chain id, which the
+//! signature then commits to.  The key file is one wallet entry as stored in the wallet
+//! list, its secret key still encrypted under `BLEEP_WALLET_PASSWORD`.
+//! Signing goes through the wallet's spending policy like `tx send`.
+
+use std::path::Path;
+
+use bleep_core::block::ChainId;
+use bleep_core::transaction::ZKTransaction;
+use bleep_wallet_core::policy::{PolicyBook, PolicyError, Transfer};
+use bleep_wallet_core::wallet::EncryptedWallet;
+
+/// A transfer from `sender` on `chain_id` waiting for its signature.
+#[allow(clippy::too_many_arguments)]
+pub fn unsigned(
+    chain_id: ChainId, sender: &str, receiver: &str, amount: u64, timestamp: u64, max_fee: u64, tip: u64, nonce: u64,
+) -> ZKTransaction {
+    ZKTransaction {
+        sender: sender.to_string(),
+        receiver: receiver.to_string(),
+        amount,
+        timestamp,
+        signature: Vec::new(),
+        max_fee,
+        tip,
+        nonce,
+        chain_id,
+    }
+}
+
+pub fn is_signed(tx: &ZKTransaction) -> bool {
+    !tx.signature.is_empty()
+}
+
+pub fn read_tx(path: &Path) -> Result<ZKTransaction, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    serde_json::from_str(&raw).map_err(|e| format!("{} is not a transaction: {}", path.display(), e))
+}
+
+pub fn write_tx(path: &Path, tx: &ZKTransaction) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(tx).map_err(|e| e.to_string())?;
+    std::fs::write(path, json + "\n").map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+/// Wallet entry exported to `path`.
+pub fn load_key(path: &Path) -> Result<EncryptedWallet, String> {
+    let raw = std::fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let wallet: EncryptedWallet = serde_json::from_str(&raw)
+        .map_err(|e| format!("{} is not a wallet key file: {}", path.display(), e))?;
+    if !wallet.can_sign() {
+        return Err(format!("{} holds no signing key", path.display()));
+    }
+    Ok(wallet)
+}
+
+/// Sign `tx` with `wallet` once `book` allows it.
+pub fn sign(tx: &mut ZKTransaction, wallet: &EncryptedWallet, password: &str, book: &mut PolicyBook) -> Result<(), String> {
+    if is_signed(tx) {
+        return Err("transaction is already signed".into());
+    }
+    if tx.sender != wallet.address() {
+        return Err(format!("transaction is from {}, but the key is for {}", tx.sender, wallet.address()));
+    }
+    let transfer = Transfer {
+        receiver:  tx.receiver.clone(),
+        amount:    tx.amount,
+        timestamp: tx.timestamp,
+        max_fee:   tx.max_fee,
+        tip:       tx.tip,
+        nonce:     tx.nonce,
+        chain_id:  tx.chain_id,
+    };
+    tx.signature = wallet.sign_transaction(password, book, &transfer).map_err(|e| match e {
+        PolicyError::ApprovalRequired { id, amount } => format!(
+            "Transfer of {} held for approval — an approver runs `bleep-cli wallet pending approve {} --approver <address>`",
+            amount, id,
+        ),
+        e @ (PolicyError::Signing(_) | PolicyError::Decrypt) => format!("{} — set BLEEP_WALLET_PASSWORD if encrypted", e),
+        e => format!("Blocked by spending policy: {}", e),
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, tx_payload_with_nonce, verify_tx_signature};
+
+    const BOB: &str = "BLEEP1bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    fn key_file(dir: &Path) -> (EncryptedWallet, std::path::PathBuf) {
+        let (pk, sk) = generate_tx_keypair();
+        let wallet = EncryptedWallet::with_signing_key_encrypted(pk, &sk, vec![], "pw").unwrap();
+        let path = dir.join("key.json");
+        std::fs::write(&path, serde_json::to_string(&wallet).unwrap()).unwrap();
+        (wallet, path)
+    }
+
+    #[test]
+    fn create_sign_roundtrip_through_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (wallet, key) = key_file(dir.path());
+        let file = dir.path().join("tx.json");
+        write_tx(&file, &unsigned(ChainId(1), wallet.address(), BOB, 5_000, 1_700_000_000, 20, 2, 3)).unwrap();
+
+        // On the air-gapped side
+        let key = load_key(&key).unwrap();
+        let mut tx = read_tx(&file).unwrap();
+        assert!(!is_signed(&tx));
+        let mut book = key.policies_in(dir.path(), "pw").unwrap();
+        sign(&mut tx, &key, "pw", &mut book).unwrap();
+        write_tx(&file, &tx).unwrap();
+
+        let tx = read_tx(&file).unwrap();
+        let (pk, sig) = tx.signature.split_at(wallet.falcon_keys.len());
+        assert_eq!(pk, wallet.falcon_keys.as_slice());
+        assert_eq!(tx.chain_id, ChainId(1));
+        let payload = tx_payload_with_nonce(&tx.sender, &tx.receiver, tx.amount, tx.timestamp, tx.max_fee, tx.tip, 3);
+        assert!(verify_tx_signature(&ChainId(1).bind(payload), sig, pk));
+        assert!(!verify_tx_signature(&ChainId(2).bind(payload), sig, pk));
+    }
+
+    #[test]
+    fn sign_refuses_foreign_or_signed_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let (wallet, _) = key_file(dir.path());
+        let mut book = wallet.policies_in(dir.path(), "pw").unwrap();
+
+        let mut foreign = unsigned(ChainId::NONE, BOB, wallet.address(), 1, 1_700_000_000, 0, 0, 0);
+        assert!(sign(&mut foreign, &wallet, "pw", &mut book).unwrap_err().contains("key is for"));
+
+        let mut tx = unsigned(ChainId::NONE, wallet.address(), BOB, 1, 1_700_000_000, 0, 0, 0);
+        sign(&mut tx, &wallet, "pw", &mut book).unwrap();
+        assert!(sign(&mut tx, &wallet, "pw", &mut book).unwrap_err().contains("already signed"));
+
+        let mut tx = unsigned(ChainId::NONE, wallet.address(), BOB, 1, 1_700_000_001, 0, 0, 0);
+        assert!(sign(&mut tx, &wallet, "wrong", &mut book).is_err());
+        assert!(!is_signed(&tx));
+    }
+}
@@ -0,0 +1,629 @@
+//! # Interactive `wallet send`
+//!
+//! Any field missing from the command line is asked for, then a preview of
+//! what is about to be signed is shown and the user must type
+//! [`CONFIRM_WORD`] before anything is signed:
+//!
+//! ```text
+//! flags ──► prompt for missing recipient / token / amount
+//!              │
+//!              ▼
+//!           preview: address, amount, fee, resulting balance, risk flags
+//!              │
+//!              ▼
+//!           typed confirmation ── anything else ──► abort, nothing signed
+//!              │
+//!              ▼
+//!            sign
+//! ```
+//!
+//! `--yes` skips the confirmation for scripts, except above the amount set in
+//! `BLEEP_WALLET_PREVIEW_ABOVE`, where the preview is always shown.  Without
+//! a terminal on stdin every prompt fails with [`PromptError::NonInteractive`]
+//! instead of waiting on input that will never come.
+//!
+//! All I/O goes through [`Terminal`] so the flow can be driven from tests.
+
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Word the user must type to sign.
+pub const CONFIRM_WORD: &str = "send";
+
+/// Env var holding the amount (in whole tokens) above which the preview is
+/// shown even with `--yes`.
+pub const PREVIEW_ABOVE_ENV: &str = "BLEEP_WALLET_PREVIEW_ABOVE";
+
+/// Native token symbol; amounts have 8 decimal places.
+pub const NATIVE_SYMBOL: &str = "BLEEP";
+pub const NATIVE_DECIMALS: u8 = 8;
+
+/// Most decimal places any token may have (the PAT limit).
+const MAX_DECIMALS: u8 = 18;
+
+/// Wrong answers to the amount prompt before giving up.
+const AMOUNT_ATTEMPTS: usize = 3;
+
+// ─── Errors ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+pub enum PromptError {
+    /// Input was needed but stdin is not a terminal.
+    NonInteractive(&'static str),
+    InvalidAmount(String),
+    UnknownToken(String),
+    Recipient(String),
+    /// The confirmation word was not typed.
+    Declined,
+    /// Input ended before an answer was given.
+    Eof,
+    Io(io::Error),
+}
+
+impl fmt::Display for PromptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PromptError::NonInteractive(what) => write!(
+                f,
+                "non-interactive session: cannot ask for {} — pass every flag and --yes to send from a script",
+                what
+            ),
+            PromptError::InvalidAmount(e) => write!(f, "invalid amount: {}", e),
+            PromptError::UnknownToken(s) => write!(f, "unknown token {}", s),
+            PromptError::Recipient(e) => write!(f, "invalid recipient: {}", e),
+            PromptError::Declined => write!(f, "aborted — transaction not confirmed, nothing was signed"),
+            PromptError::Eof => write!(f, "input ended before an answer was given"),
+            PromptError::Io(e) => write!(f, "terminal I/O failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for PromptError {}
+
+impl From<io::Error> for PromptError {
+    fn from(e: io::Error) -> Self {
+        PromptError::Io(e)
+    }
+}
+
+// ─── Terminal ─────────────────────────────────────────────────────────────────
+
+/// Where prompts are written and answers read from.
+pub trait Terminal {
+    /// Whether a person can answer prompts.
+    fn is_interactive(&self) -> bool;
+    /// Read one line; `None` at end of input.
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+    fn write(&mut self, text: &str) -> io::Result<()>;
+}
+
+/// The process's stdin and stdout.
+pub struct StdTerminal;
+
+impl Terminal for StdTerminal {
+    fn is_interactive(&self) -> bool {
+        io::stdin().is_terminal()
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line)? {
+            0 => Ok(None),
+            _ => Ok(Some(line)),
+        }
+    }
+
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        let mut out = io::stdout();
+        out.write_all(text.as_bytes())?;
+        out.flush()
+    }
+}
+
+/// Ask `question` and return the trimmed answer. `what` names the field in
+/// the non-interactive error.
+fn ask<T: Terminal>(term: &mut T, what: &'static str, question: &str) -> Result<String, PromptError> {
+    if !term.is_interactive() {
+        return Err(PromptError::NonInteractive(what));
+    }
+    term.write(question)?;
+    match term.read_line()? {
+        Some(line) => Ok(line.trim().to_string()),
+        None => Err(PromptError::Eof),
+    }
+}
+
+// ─── Amounts ──────────────────────────────────────────────────────────────────
+
+/// Parse a decimal amount such as `12.5` into base units of a token with
+/// `decimals` places. Rejects signs, exponents, empty parts and more
+/// fractional digits than the token has.
+pub fn parse_amount(text: &str, decimals: u8) -> Result<u128, PromptError> {
+    let bad = |why: &str| PromptError::InvalidAmount(format!("{:?}: {}", text, why));
+    let text = text.trim();
+    let (whole, frac) = match text.split_once('.') {
+        Some((w, f)) => (w, f),
+        None => (text, ""),
+    };
+    if whole.is_empty() && frac.is_empty() {
+        return Err(bad("empty"));
+    }
+    if !whole.bytes().all(|b| b.is_ascii_digit()) || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(bad("expected digits with at most one decimal point"));
+    }
+    if text.ends_with('.') {
+        return Err(bad("missing digits after the decimal point"));
+    }
+    if frac.len() > decimals as usize {
+        return Err(bad(&format!("at most {} decimal places", decimals)));
+    }
+    let scale = 10u128.checked_pow(decimals as u32).ok_or_else(|| bad("too many decimals"))?;
+    let whole: u128 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| bad("too large"))? };
+    let frac: u128 = if frac.is_empty() {
+        0
+    } else {
+        frac.parse::<u128>().map_err(|_| bad("too large"))? * 10u128.pow((decimals as usize - frac.len()) as u32)
+    };
+    let amount = whole.checked_mul(scale).and_then(|w| w.checked_add(frac)).ok_or_else(|| bad("too large"))?;
+    if amount == 0 {
+        return Err(bad("must be greater than zero"));
+    }
+    Ok(amount)
+}
+
+/// Base units as a decimal with every fractional digit, e.g. `1.50000000`.
+pub fn format_amount(amount: u128, decimals: u8) -> String {
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    let scale = 10u128.pow(decimals as u32);
+    format!("{}.{:0width$}", amount / scale, amount % scale, width = decimals as usize)
+}
+
+// ─── Collecting the transfer ──────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub symbol:   String,
+    pub decimals: u8,
+}
+
+impl Token {
+    pub fn native() -> Self {
+        Token { symbol: NATIVE_SYMBOL.into(), decimals: NATIVE_DECIMALS }
+    }
+
+    pub fn is_native(&self) -> bool {
+        self.symbol == NATIVE_SYMBOL
+    }
+}
+
+/// What was given on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct SendArgs {
+    pub to:     Option<String>,
+    pub token:  Option<String>,
+    pub amount: Option<String>,
+}
+
+/// A transfer with every field filled in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Draft {
+    pub to:       String,
+    /// Contact label when the recipient was given as `@label`.
+    pub contact:  Option<String>,
+    pub token:    Token,
+    pub amount:   u128,
+    /// Whether any field had to be asked for.
+    pub prompted: bool,
+}
+
+/// Fill in the fields missing from `args`, asking on `term`. The token is
+/// only asked for when something else is, and otherwise defaults to BLEEP.
+/// `resolve` turns a recipient (address or `@label`) into an address and
+/// optional contact label; `tokens` are the ones that can be sent.
+pub fn collect<T: Terminal>(
+    term: &mut T,
+    args: SendArgs,
+    tokens: &[Token],
+    mut resolve: impl FnMut(&str) -> Result<(String, Option<String>), String>,
+) -> Result<Draft, PromptError> {
+    let prompted = args.to.is_none() || args.amount.is_none();
+
+    let to = match args.to {
+        Some(to) => to,
+        None => ask(term, "the recipient", "Recipient (BLEEP1 address or @contact): ")?,
+    };
+    if to.is_empty() {
+        return Err(PromptError::Recipient("empty".into()));
+    }
+    let (to, contact) = resolve(&to).map_err(PromptError::Recipient)?;
+
+    let symbol = match args.token {
+        Some(symbol) => symbol,
+        None if prompted => {
+            let choices = tokens.iter().map(|t| t.symbol.as_str()).collect::<Vec<_>>().join(", ");
+            let answer = ask(term, "the token", &format!("Token [{}] ({}): ", NATIVE_SYMBOL, choices))?;
+            if answer.is_empty() { NATIVE_SYMBOL.to_string() } else { answer }
+        }
+        None => NATIVE_SYMBOL.to_string(),
+    };
+    let token = tokens
+        .iter()
+        .find(|t| t.symbol.eq_ignore_ascii_case(&symbol))
+        .cloned()
+        .ok_or(PromptError::UnknownToken(symbol))?;
+
+    let amount = match args.amount {
+        Some(text) => parse_amount(&text, token.decimals)?,
+        None => {
+            let question = format!("Amount in {} (up to {} decimals): ", token.symbol, token.decimals);
+            let mut attempt = 1;
+            loop {
+                let answer = ask(term, "the amount", &question)?;
+                match parse_amount(&answer, token.decimals) {
+                    Ok(amount) => break amount,
+                    Err(e) if attempt < AMOUNT_ATTEMPTS => {
+                        term.write(&format!("  {}\n", e))?;
+                        attempt += 1;
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    };
+
+    Ok(Draft { to, contact, token, amount, prompted })
+}
+
+// ─── Preview ──────────────────────────────────────────────────────────────────
+
+/// Something about a transfer worth a second look.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RiskFlag {
+    /// Amount plus fee exceeds the known balance.
+    InsufficientFunds,
+    /// The transfer moves at least half of the balance.
+    LargeShareOfBalance { percent: u8 },
+    /// Recipient typed as a raw address rather than picked from contacts.
+    NotAContact,
+    SelfTransfer,
+    /// Paying more in fees than is being sent.
+    FeeExceedsAmount,
+}
+
+impl fmt::Display for RiskFlag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskFlag::InsufficientFunds => write!(f, "amount plus fee exceeds the balance"),
+            RiskFlag::LargeShareOfBalance { percent } => write!(f, "sends {}% of the balance", percent),
+            RiskFlag::NotAContact => write!(f, "recipient is not in your contacts"),
+            RiskFlag::SelfTransfer => write!(f, "recipient is the sending address"),
+            RiskFlag::FeeExceedsAmount => write!(f, "the fee is larger than the amount"),
+        }
+    }
+}
+
+/// Everything shown before signing.
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub from:    String,
+    pub draft:   Draft,
+    /// Most the transfer can pay in fees, in BLEEP base units.
+    pub max_fee: u64,
+    pub tip:     u64,
+    /// Balance of the sent token, if the node could be asked.
+    pub balance: Option<u128>,
+}
+
+impl Preview {
+    /// Fee charged in the sent token; PAT transfers pay none.
+    fn fee_in_token(&self) -> u128 {
+        if self.draft.token.is_native() { self.max_fee as u128 } else { 0 }
+    }
+
+    /// Balance after the transfer, at the maximum fee.
+    pub fn resulting_balance(&self) -> Option<u128> {
+        self.balance?.checked_sub(self.draft.amount)?.checked_sub(self.fee_in_token())
+    }
+
+    pub fn risk_flags(&self) -> Vec<RiskFlag> {
+        let mut flags = Vec::new();
+        if let Some(balance) = self.balance {
+            if self.resulting_balance().is_none() {
+                flags.push(RiskFlag::InsufficientFunds);
+            } else if self.draft.amount.saturating_mul(2) >= balance {
+                let percent = (self.draft.amount.saturating_mul(100) / balance.max(1)).min(100) as u8;
+                flags.push(RiskFlag::LargeShareOfBalance { percent });
+            }
+        }
+        if self.draft.contact.is_none() {
+            flags.push(RiskFlag::NotAContact);
+        }
+        if self.draft.to == self.from {
+            flags.push(RiskFlag::SelfTransfer);
+        }
+        if self.fee_in_token() > self.draft.amount {
+            flags.push(RiskFlag::FeeExceedsAmount);
+        }
+        flags
+    }
+
+    pub fn render(&self) -> String {
+        let token = &self.draft.token;
+        let amount = |v: u128| format!("{} {}", format_amount(v, token.decimals), token.symbol);
+        let to = match &self.draft.contact {
+            Some(label) => format!("@{} → {}", label, self.draft.to),
+            None => self.draft.to.clone(),
+        };
+        let fee = if token.is_native() {
+            format!(
+                "up to {} (tip {})",
+                amount(self.max_fee as u128),
+                format_amount(self.tip as u128, NATIVE_DECIMALS)
+            )
+        } else {
+            "none".to_string()
+        };
+        let balance = match (self.balance, self.resulting_balance()) {
+            (Some(now), Some(after)) => format!("{} → {}", amount(now), amount(after)),
+            (Some(now), None) => format!("{} → insufficient", amount(now)),
+            (None, _) => "unknown (node unreachable)".to_string(),
+        };
+
+        let mut out = String::from("\nTransaction preview\n");
+        out.push_str(&format!("  From:    {}\n", self.from));
+        out.push_str(&format!("  To:      {}\n", to));
+        out.push_str(&format!("  Amount:  {}\n", amount(self.draft.amount)));
+        out.push_str(&format!("  Fee:     {}\n", fee));
+        out.push_str(&format!("  Balance: {}\n", balance));
+        for flag in self.risk_flags() {
+            out.push_str(&format!("  ⚠️  {}\n", flag));
+        }
+        out
+    }
+}
+
+// ─── Confirmation ─────────────────────────────────────────────────────────────
+
+/// When the preview and confirmation may be skipped.
+#[derive(Debug, Clone, Default)]
+pub struct ConfirmPolicy {
+    /// `--yes`.
+    pub yes:           bool,
+    /// Amount in whole tokens above which `--yes` is ignored, as base units
+    /// of an 18-decimal token so it compares across tokens.
+    pub preview_above: Option<u128>,
+}
+
+impl ConfirmPolicy {
+    /// `yes` plus the threshold from [`PREVIEW_ABOVE_ENV`].
+    pub fn from_env(yes: bool) -> Result<Self, PromptError> {
+        let preview_above = match std::env::var(PREVIEW_ABOVE_ENV) {
+            Ok(v) if !v.trim().is_empty() => Some(parse_amount(&v, MAX_DECIMALS).map_err(|e| {
+                PromptError::InvalidAmount(format!("{}: {}", PREVIEW_ABOVE_ENV, e))
+            })?),
+            _ => None,
+        };
+        Ok(ConfirmPolicy { yes, preview_above })
+    }
+
+    /// Set the threshold from a decimal amount such as `1000` or `0.5`.
+    pub fn with_preview_above(mut self, amount: &str) -> Result<Self, PromptError> {
+        self.preview_above = Some(parse_amount(amount, MAX_DECIMALS)?);
+        Ok(self)
+    }
+
+    /// Whether `amount` of a token with `decimals` places must be confirmed.
+    pub fn requires_confirmation(&self, amount: u128, decimals: u8) -> bool {
+        if !self.yes {
+            return true;
+        }
+        let Some(limit) = self.preview_above else { return false };
+        let scaled = 10u128
+            .checked_pow(MAX_DECIMALS.saturating_sub(decimals) as u32)
+            .and_then(|scale| amount.checked_mul(scale));
+        // Too large to scale is certainly above any threshold.
+        scaled.map_or(true, |a| a > limit)
+    }
+}
+
+/// Show the preview and wait for [`CONFIRM_WORD`] when `policy` calls for it,
+/// then run `sign`. `sign` is never called unless the transfer is confirmed.
+pub fn confirm_and_sign<T: Terminal, R>(
+    term: &mut T,
+    preview: &Preview,
+    policy: &ConfirmPolicy,
+    sign: impl FnOnce() -> R,
+) -> Result<R, PromptError> {
+    if policy.requires_confirmation(preview.draft.amount, preview.draft.token.decimals) {
+        if !term.is_interactive() {
+            return Err(PromptError::NonInteractive("confirmation"));
+        }
+        term.write(&preview.render())?;
+        let answer = ask(term, "confirmation", &format!("Type \"{}\" to sign and broadcast: ", CONFIRM_WORD))?;
+        if answer != CONFIRM_WORD {
+            return Err(PromptError::Declined);
+        }
+    }
+    Ok(sign())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Replays canned answers; running out is an error rather than a hang.
+    struct Scripted {
+        tty:     bool,
+        answers: VecDeque<String>,
+        output:  String,
+    }
+
+    impl Scripted {
+        fn new(tty: bool, answers: &[&str]) -> Self {
+            Scripted { tty, answers: answers.iter().map(|a| format!("{}\n", a)).collect(), output: String::new() }
+        }
+    }
+
+    impl Terminal for Scripted {
+        fn is_interactive(&self) -> bool {
+            self.tty
+        }
+        fn read_line(&mut self) -> io::Result<Option<String>> {
+            Ok(self.answers.pop_front())
+        }
+        fn write(&mut self, text: &str) -> io::Result<()> {
+            self.output.push_str(text);
+            Ok(())
+        }
+    }
+
+    fn tokens() -> Vec<Token> {
+        vec![Token::native(), Token { symbol: "USDB".into(), decimals: 2 }]
+    }
+
+    fn resolve(to: &str) -> Result<(String, Option<String>), String> {
+        match to.strip_prefix('@') {
+            Some("alice") => Ok(("BLEEP1alice".into(), Some("alice".into()))),
+            Some(label) => Err(format!("no contact @{}", label)),
+            None => Ok((to.to_string(), None)),
+        }
+    }
+
+    fn preview(amount: u128) -> Preview {
+        Preview {
+            from:    "BLEEP1me".into(),
+            draft:   Draft {
+                to:       "BLEEP1alice".into(),
+                contact:  Some("alice".into()),
+                token:    Token::native(),
+                amount,
+                prompted: false,
+            },
+            max_fee: 2_000,
+            tip:     0,
+            balance: Some(10 * 100_000_000),
+        }
+    }
+
+    #[test]
+    fn amounts_parse_to_base_units() {
+        assert_eq!(parse_amount("1.5", 8).unwrap(), 150_000_000);
+        assert_eq!(parse_amount("0.00000001", 8).unwrap(), 1);
+        assert_eq!(parse_amount(".25", 2).unwrap(), 25);
+        assert_eq!(parse_amount("42", 0).unwrap(), 42);
+        assert_eq!(format_amount(150_000_000, 8), "1.50000000");
+    }
+
+    #[test]
+    fn malformed_decimals_are_rejected() {
+        for bad in ["", ".", "1.", "1.2.3", "-1", "+1", "1e8", "1,5", "abc", "0", "0.000", "0.000000001"] {
+            assert!(
+                matches!(parse_amount(bad, 8), Err(PromptError::InvalidAmount(_))),
+                "{:?} should be rejected",
+                bad
+            );
+        }
+        assert!(parse_amount("0.001", 2).is_err());
+        assert!(parse_amount("340282366920938463463374607431768211455", 8).is_err());
+
+        // At the prompt a bad answer is explained and asked again.
+        let mut term = Scripted::new(true, &["@alice", "", "1.2.3", "1.5"]);
+        let draft = collect(&mut term, SendArgs::default(), &tokens(), resolve).unwrap();
+        assert_eq!(draft.amount, 150_000_000);
+        assert!(term.output.contains("invalid amount"));
+
+        // Given as a flag it is simply refused.
+        let args = SendArgs { to: Some("@alice".into()), amount: Some("1.234".into()), token: Some("usdb".into()) };
+        let err = collect(&mut Scripted::new(true, &[]), args, &tokens(), resolve).unwrap_err();
+        assert!(matches!(err, PromptError::InvalidAmount(_)));
+    }
+
+    #[test]
+    fn missing_fields_are_prompted_and_contacts_resolved() {
+        let mut term = Scripted::new(true, &["@alice", "usdb", "12.34"]);
+        let draft = collect(&mut term, SendArgs::default(), &tokens(), resolve).unwrap();
+        assert_eq!(draft.to, "BLEEP1alice");
+        assert_eq!(draft.contact.as_deref(), Some("alice"));
+        assert_eq!(draft.token.symbol, "USDB");
+        assert_eq!(draft.amount, 1234);
+        assert!(draft.prompted);
+
+        let args = SendArgs { to: Some("BLEEP1bob".into()), amount: Some("2".into()), token: None };
+        let draft = collect(&mut Scripted::new(false, &[]), args, &tokens(), resolve).unwrap();
+        assert_eq!(draft.token, Token::native());
+        assert!(!draft.prompted);
+    }
+
+    #[test]
+    fn declined_confirmation_signs_nothing() {
+        let mut signed = 0;
+        let mut term = Scripted::new(true, &["yes"]);
+        let result = confirm_and_sign(&mut term, &preview(100_000_000), &ConfirmPolicy::default(), || signed += 1);
+        assert!(matches!(result, Err(PromptError::Declined)));
+        assert_eq!(signed, 0);
+        assert!(term.output.contains("Transaction preview"));
+        assert!(term.output.contains("1.00000000 BLEEP"));
+
+        let mut term = Scripted::new(true, &[CONFIRM_WORD]);
+        confirm_and_sign(&mut term, &preview(100_000_000), &ConfirmPolicy::default(), || signed += 1).unwrap();
+        assert_eq!(signed, 1);
+    }
+
+    #[test]
+    fn threshold_forces_preview_despite_flags() {
+        let policy = ConfirmPolicy { yes: true, ..Default::default() }.with_preview_above("5").unwrap();
+        assert!(!policy.requires_confirmation(5 * 100_000_000, 8));
+        assert!(policy.requires_confirmation(5 * 100_000_000 + 1, 8));
+        assert!(policy.requires_confirmation(501, 2));
+
+        // Below the threshold --yes signs straight away.
+        let mut signed = 0;
+        let mut term = Scripted::new(true, &[]);
+        confirm_and_sign(&mut term, &preview(100_000_000), &policy, || signed += 1).unwrap();
+        assert_eq!((signed, term.output.as_str()), (1, ""));
+
+        // Above it the preview is shown and must be confirmed.
+        let mut term = Scripted::new(true, &["no"]);
+        let result = confirm_and_sign(&mut term, &preview(6 * 100_000_000), &policy, || signed += 1);
+        assert!(matches!(result, Err(PromptError::Declined)));
+        assert!(term.output.contains("sends 60% of the balance"));
+        assert_eq!(signed, 1);
+    }
+
+    #[test]
+    fn non_tty_errors_instead_of_blocking() {
+        let err = collect(&mut Scripted::new(false, &["@alice"]), SendArgs::default(), &tokens(), resolve).unwrap_err();
+        assert!(matches!(err, PromptError::NonInteractive(_)));
+        assert!(err.to_string().contains("non-interactive"));
+
+        let mut signed = false;
+        let err = confirm_and_sign(&mut Scripted::new(false, &[CONFIRM_WORD]), &preview(1), &ConfirmPolicy::default(), || {
+            signed = true
+        })
+        .unwrap_err();
+        assert!(matches!(err, PromptError::NonInteractive("confirmation")));
+        assert!(!signed);
+
+        let yes = ConfirmPolicy { yes: true, preview_above: None };
+        confirm_and_sign(&mut Scripted::new(false, &[]), &preview(1), &yes, || signed = true).unwrap();
+        assert!(signed);
+    }
+
+    #[test]
+    fn preview_flags_risky_transfers() {
+        let mut p = preview(9 * 100_000_000);
+        assert_eq!(p.risk_flags(), vec![RiskFlag::LargeShareOfBalance { percent: 90 }]);
+        assert_eq!(p.resulting_balance(), Some(100_000_000 - 2_000));
+
+        p.draft.amount = 10 * 100_000_000;
+        p.draft.contact = None;
+        assert_eq!(p.risk_flags(), vec![RiskFlag::InsufficientFunds, RiskFlag::NotAContact]);
+
+        let mut p = preview(1_000);
+        p.draft.to = p.from.clone();
+        assert_eq!(p.risk_flags(), vec![RiskFlag::SelfTransfer, RiskFlag::FeeExceedsAmount]);
+    }
+}
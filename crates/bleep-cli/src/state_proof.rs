@@ -0,0 +1,186 @@
+//! # State proofs on the command line
+//!
+//! `state proof <address>` writes the account's `ComposedProof` (its path
+//! in the owning shard's trie plus that shard root's path to the global
+//! state root) as `ProofFile` JSON; `state verify-proof` checks such a file
+//! offline against a root the caller trusts, e.g. one read from a block
+//! header.  No node or state store is needed to verify.
+//!
+//! Every hash in the file is lowercase hex, and the field set is versioned
+//! by `PROOF_FORMAT_VERSION`, so other tools can consume it.
+
+use serde::{Deserialize, Serialize};
+
+use bleep_state::shard_state::{ComposedProof, ShardRootProof};
+use bleep_state::state_merkle::{leaf_hash, MerkleProof, NodeHash, ProofNode};
+
+pub const PROOF_FORMAT_VERSION: u32 = 1;
+
+/// One level of the account's trie path, leaf end first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub sibling:  String,
+    pub is_right: bool,
+}
+
+/// `ComposedProof` with hex-encoded hashes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofFile {
+    pub version:        u32,
+    pub address:        String,
+    /// `false` for a proof that the account does not exist.
+    pub exists:         bool,
+    pub leaf:           String,
+    pub account_path:   Vec<ProofStep>,
+    pub shard_id:       u64,
+    pub shard_root:     String,
+    pub shard_index:    u64,
+    pub shard_siblings: Vec<String>,
+    /// Root the proof was generated against; informational only —
+    /// verification uses the root the caller supplies.
+    pub state_root:     String,
+}
+
+impl ProofFile {
+    pub fn new(proof: &ComposedProof, state_root: &NodeHash) -> Self {
+        ProofFile {
+            version:        PROOF_FORMAT_VERSION,
+            address:        proof.account.address.clone(),
+            exists:         proof.account.exists,
+            leaf:           hex::encode(proof.account.leaf),
+            account_path:   proof.account.path.iter()
+                .map(|n| ProofStep { sibling: hex::encode(n.sibling), is_right: n.is_right })
+                .collect(),
+            shard_id:       proof.shard.shard_id,
+            shard_root:     hex::encode(proof.shard.shard_root),
+            shard_index:    proof.shard.index,
+            shard_siblings: proof.shard.siblings.iter().map(hex::encode).collect(),
+            state_root:     hex::encode(state_root),
+        }
+    }
+
+    pub fn to_proof(&self) -> Result<ComposedProof, String> {
+        if self.version != PROOF_FORMAT_VERSION {
+            return Err(format!("unsupported proof format version {}", self.version));
+        }
+        let shard_root = parse_hash(&self.shard_root)?;
+        Ok(ComposedProof {
+            account: MerkleProof {
+                address: self.address.clone(),
+                exists:  self.exists,
+                leaf:    parse_hash(&self.leaf)?,
+                path:    self.account_path.iter()
+                    .map(|s| Ok(ProofNode { sibling: parse_hash(&s.sibling)?, is_right: s.is_right }))
+                    .collect::<Result<_, String>>()?,
+                root:    shard_root,
+            },
+            shard: ShardRootProof {
+                shard_id:   self.shard_id,
+                shard_root,
+                index:      self.shard_index,
+                siblings:   self.shard_siblings.iter().map(|h| parse_hash(h)).collect::<Result<_, _>>()?,
+            },
+        })
+    }
+}
+
+/// 32-byte hash from hex, with or without `0x`.
+pub fn parse_hash(s: &str) -> Result<NodeHash, String> {
+    let bytes = hex::decode(s.trim().trim_start_matches("0x")).map_err(|e| format!("'{}' is not hex: {}", s, e))?;
+    bytes.try_into().map_err(|b: Vec<u8>| format!("'{}' is {} bytes, not 32", s, b.len()))
+}
+
+/// What the proof is expected to show about the account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountValue {
+    Present { balance: u128, nonce: u64 },
+    Absent,
+}
+
+impl AccountValue {
+    /// `<balance>:<nonce>`, or `absent`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        if s.eq_ignore_ascii_case("absent") {
+            return Ok(AccountValue::Absent);
+        }
+        let (balance, nonce) = s.split_once(':')
+            .ok_or_else(|| format!("'{}' is not <balance>:<nonce> or 'absent'", s))?;
+        Ok(AccountValue::Present {
+            balance: balance.parse().map_err(|_| format!("bad balance '{}'", balance))?,
+            nonce:   nonce.parse().map_err(|_| format!("bad nonce '{}'", nonce))?,
+        })
+    }
+}
+
+/// Check that `file` proves `value` for `address` under `root`.
+pub fn verify(file: &ProofFile, address: &str, value: AccountValue, root: &NodeHash) -> Result<(), String> {
+    if file.address != address {
+        return Err(format!("proof is for {}, not {}", file.address, address));
+    }
+    let proof = file.to_proof()?;
+    match value {
+        AccountValue::Present { balance, nonce } => {
+            if !proof.account.exists {
+                return Err(format!("proof shows {} does not exist", address));
+            }
+            if proof.account.leaf != leaf_hash(address, balance, nonce) {
+                return Err(format!("proof does not commit to balance {} and nonce {}", balance, nonce));
+            }
+        }
+        AccountValue::Absent if proof.account.exists => {
+            return Err(format!("proof shows {} exists", address));
+        }
+        AccountValue::Absent => {}
+    }
+    if !proof.verify(root) {
+        return Err(format!("proof does not lead to root {}", hex::encode(root)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_state::state_manager::StateManager;
+
+    #[test]
+    fn proof_file_roundtrips_and_verifies_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = StateManager::open(dir.path()).unwrap();
+        state.set_balance("BLEEP1alice", 5_000);
+        state.increment_nonce("BLEEP1alice");
+        state.set_balance("BLEEP1bob", 7);
+        let root = state.state_root();
+        let file = ProofFile::new(&state.prove_account("BLEEP1alice"), &root);
+
+        let json = serde_json::to_string_pretty(&file).unwrap();
+        assert!(json.contains(&hex::encode(root)));
+        let file: ProofFile = serde_json::from_str(&json).unwrap();
+        let alice = AccountValue::parse("5000:1").unwrap();
+        verify(&file, "BLEEP1alice", alice, &root).unwrap();
+
+        assert!(verify(&file, "BLEEP1alice", AccountValue::parse("5001:1").unwrap(), &root).is_err());
+        assert!(verify(&file, "BLEEP1alice", AccountValue::Absent, &root).is_err());
+        assert!(verify(&file, "BLEEP1bob", alice, &root).is_err());
+        assert!(verify(&file, "BLEEP1alice", alice, &[7u8; 32]).is_err());
+
+        let mut tampered = file.clone();
+        tampered.account_path[255].sibling = hex::encode([1u8; 32]);
+        assert!(verify(&tampered, "BLEEP1alice", alice, &root).is_err());
+    }
+
+    #[test]
+    fn exclusion_proof_for_a_missing_account() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = StateManager::open(dir.path()).unwrap();
+        state.set_balance("BLEEP1alice", 5_000);
+        let root = state.state_root();
+        let file = ProofFile::new(&state.prove_account("BLEEP1nobody"), &root);
+
+        verify(&file, "BLEEP1nobody", AccountValue::Absent, &root).unwrap();
+        assert!(verify(&file, "BLEEP1nobody", AccountValue::parse("0:0").unwrap(), &root).is_err());
+        assert!(AccountValue::parse("12").is_err());
+        assert!(parse_hash(&format!("0x{}", hex::encode(root))).is_ok());
+        assert!(parse_hash("abcd").is_err());
+    }
+}
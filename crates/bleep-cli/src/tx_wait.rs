@@ -0,0 +1,806 @@
+//! # Waiting on the transaction lifecycle
+//!
+//! `tx send --wait <target>` and `tx submit-batch --wait <target>` block
+//! until each transaction reaches `accepted`, `included` or `confirmed:N`
+//! and exit with a code naming where it stopped:
+//!
+//! | exit | outcome                                                           |
+//! |------|-------------------------------------------------------------------|
+//! | 0    | reached the requested state                                       |
+//! | 1    | CLI error: bad arguments, or the node never answered the submission |
+//! | 10   | accepted, but not at the requested state before `--timeout`       |
+//! | 11   | rejected at admission; the typed rejection is JSON on stderr      |
+//! | 12   | included, but reverted (no successful receipt)                    |
+//! | 13   | included, then dropped from the chain by a reorg                  |
+//!
+//! A batch exits with the highest code among its transactions.
+//!
+//! `TxWaiter` keeps all its state client-side — the tip at submission and,
+//! once included, the height and hash of the including block — and polls
+//! `GET /rpc/block/latest`, `/rpc/block/{h}` and `/rpc/block/{h}/receipts`.
+//! Connection failures and 5xx answers are retried with exponential
+//! backoff until the timeout, so a node restarting mid-wait only delays
+//! the outcome; one that comes back with a shorter chain is re-scanned
+//! from its tip.  An including block that is gone or has a new hash is a
+//! reorg.
+//!
+//! With `--output json` every step is one JSON line on stdout: `at_ms`
+//! since the wait started, plus the event.
+//!
+//! `tx status <hash>` is the one-shot form: `TxWaiter::status` looks for
+//! the transaction in the blocks below the tip without waiting.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use bleep_core::transaction::ZKTransaction;
+use bleep_rpc::tx_batch::receipt_hash;
+
+pub const EXIT_REACHED: i32 = 0;
+pub const EXIT_TIMEOUT: i32 = 10;
+pub const EXIT_REJECTED: i32 = 11;
+pub const EXIT_REVERTED: i32 = 12;
+pub const EXIT_REORGED: i32 = 13;
+
+/// `--timeout` when not given.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+const DEFAULT_POLL: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+/// State `--wait` blocks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitTarget {
+    /// Admitted to the node's pool.
+    Accepted,
+    /// In a block with a successful receipt.
+    Included,
+    /// Included, with this many blocks (the including one counts) on top.
+    Confirmed(u64),
+}
+
+impl FromStr for WaitTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "accepted" => Ok(WaitTarget::Accepted),
+            "included" => Ok(WaitTarget::Included),
+            _ => s
+                .strip_prefix("confirmed:")
+                .and_then(|n| n.parse::<u64>().ok())
+                .filter(|n| *n > 0)
+                .map(WaitTarget::Confirmed)
+                .ok_or_else(|| format!("expected accepted, included or confirmed:N (N ≥ 1), got `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for WaitTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaitTarget::Accepted => write!(f, "accepted"),
+            WaitTarget::Included => write!(f, "included"),
+            WaitTarget::Confirmed(n) => write!(f, "confirmed:{}", n),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+/// Where a transaction's wait ended; ordered by exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum WaitOutcome {
+    Reached,
+    TimedOut,
+    Rejected,
+    Reverted,
+    Reorged,
+}
+
+impl WaitOutcome {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            WaitOutcome::Reached  => EXIT_REACHED,
+            WaitOutcome::TimedOut => EXIT_TIMEOUT,
+            WaitOutcome::Rejected => EXIT_REJECTED,
+            WaitOutcome::Reverted => EXIT_REVERTED,
+            WaitOutcome::Reorged  => EXIT_REORGED,
+        }
+    }
+}
+
+/// One step of the timeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WaitEvent {
+    /// Admitted while the chain tip was at `height`.
+    Accepted { tx_hash: String, height: u64 },
+    Rejected {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tx_hash: Option<String>,
+        code:    String,
+        error:   String,
+    },
+    Included { tx_hash: String, height: u64, block_hash: String },
+    Confirmations { tx_hash: String, height: u64, confirmations: u64 },
+    Reverted {
+        tx_hash: String,
+        height:  u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error:   Option<String>,
+    },
+    Reorged { tx_hash: String, height: u64, block_hash: String },
+    /// The requested state was reached.
+    Reached { tx_hash: String, target: String },
+    Timeout { tx_hash: String, waited_ms: u64 },
+    /// The node did not answer; the wait goes on after `retry_in_ms`.
+    RpcRetry { attempt: u32, error: String, retry_in_ms: u64 },
+}
+
+/// Result of submitting one transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    Accepted { tx_hash: String, height: u64 },
+    Rejected { code: String, error: String },
+}
+
+#[derive(Deserialize)]
+struct SubmitResp {
+    status: String,
+    #[serde(default)]
+    code:   Option<String>,
+    #[serde(default)]
+    error:  Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BlockView {
+    height:    u64,
+    hash:      String,
+    #[serde(default)]
+    tx_hashes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ReceiptView {
+    tx_hash: String,
+    success: bool,
+    #[serde(default)]
+    failure: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ReceiptsView {
+    receipts: Vec<ReceiptView>,
+}
+
+/// What `tx status` found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TxStatus {
+    Included { height: u64, block_hash: String, confirmations: u64 },
+    Reverted { height: u64, error: Option<String> },
+    /// Not in the scanned blocks: still pending, dropped or unknown.
+    NotFound { scanned: u64 },
+}
+
+enum Phase {
+    Pending,
+    Included { height: u64, block_hash: String, confirmations: u64 },
+    Done(WaitOutcome),
+}
+
+struct Tracked {
+    tx_hash: String,
+    phase:   Phase,
+}
+
+/// Exponential backoff between failed polls.
+struct Backoff {
+    attempt: u32,
+    delay:   Duration,
+    initial: Duration,
+    max:     Duration,
+}
+
+impl Backoff {
+    fn new(initial: Duration, max: Duration) -> Self {
+        Backoff { attempt: 0, delay: initial, initial, max }
+    }
+
+    fn reset(&mut self) {
+        self.attempt = 0;
+        self.delay = self.initial;
+    }
+
+    async fn retry(&mut self, error: String, remaining: Duration, sink: &mut (dyn FnMut(&WaitEvent) + Send)) {
+        self.attempt += 1;
+        let delay = self.delay.min(remaining);
+        sink(&WaitEvent::RpcRetry { attempt: self.attempt, error, retry_in_ms: delay.as_millis() as u64 });
+        tokio::time::sleep(delay).await;
+        self.delay = (self.delay * 2).min(self.max);
+    }
+}
+
+/// Submits transactions and follows them to a `WaitTarget`.
+pub struct TxWaiter {
+    client:      reqwest::Client,
+    rpc:         String,
+    target:      WaitTarget,
+    timeout:     Duration,
+    poll:        Duration,
+    max_backoff: Duration,
+    started:     Instant,
+}
+
+impl TxWaiter {
+    /// The timeout runs from here, submission included.
+    pub fn new(rpc: &str, target: WaitTarget, timeout: Duration) -> Self {
+        // No pooled connections: one can outlive a node restart.
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .pool_max_idle_per_host(0)
+            .build()
+            .unwrap_or_default();
+        TxWaiter {
+            client,
+            rpc: rpc.trim_end_matches('/').to_string(),
+            target,
+            timeout,
+            poll: DEFAULT_POLL,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            started: Instant::now(),
+        }
+    }
+
+    /// Interval between polls, and the first retry delay.
+    pub fn with_poll(mut self, poll: Duration, max_backoff: Duration) -> Self {
+        self.poll = poll;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn target(&self) -> WaitTarget {
+        self.target
+    }
+
+    fn remaining(&self) -> Duration {
+        self.timeout.saturating_sub(self.started.elapsed())
+    }
+
+    /// Chain tip, retried until the timeout.
+    pub async fn current_tip(&self, sink: &mut (dyn FnMut(&WaitEvent) + Send)) -> Result<u64, String> {
+        let mut backoff = Backoff::new(self.poll, self.max_backoff);
+        loop {
+            match self.tip().await {
+                Ok(tip) => return Ok(tip),
+                Err(e) if self.remaining().is_zero() => return Err(e),
+                Err(e) => backoff.retry(e, self.remaining(), sink).await,
+            }
+        }
+    }
+
+    /// `POST /rpc/tx`, retried until the timeout.  A `duplicate` rejection
+    /// after a retry means an earlier attempt got through.
+    pub async fn submit(&self, tx: &ZKTransaction, sink: &mut (dyn FnMut(&WaitEvent) + Send)) -> Result<Admission, String> {
+        let height = self.current_tip(sink).await?;
+        let tx_hash = receipt_hash(tx);
+        let mut backoff = Backoff::new(self.poll, self.max_backoff);
+        let resp = loop {
+            match self.post_tx(tx).await {
+                Ok(resp) => break resp,
+                Err(e) if self.remaining().is_zero() => return Err(e),
+                Err(e) => backoff.retry(e, self.remaining(), sink).await,
+            }
+        };
+        let code = resp.code.unwrap_or_default();
+        let admission = if resp.status == "accepted" || (backoff.attempt > 0 && code == "duplicate") {
+            sink(&WaitEvent::Accepted { tx_hash: tx_hash.clone(), height });
+            Admission::Accepted { tx_hash, height }
+        } else {
+            let error = resp.error.unwrap_or(resp.status);
+            sink(&WaitEvent::Rejected { tx_hash: Some(tx_hash), code: code.clone(), error: error.clone() });
+            Admission::Rejected { code, error }
+        };
+        Ok(admission)
+    }
+
+    /// Follow `tx_hashes`, accepted while the tip was at `from_height`,
+    /// until each reaches the target or fails; outcomes in input order.
+    pub async fn track(
+        &self,
+        tx_hashes: &[String],
+        from_height: u64,
+        sink: &mut (dyn FnMut(&WaitEvent) + Send),
+    ) -> Vec<WaitOutcome> {
+        let mut txs: Vec<Tracked> = tx_hashes
+            .iter()
+            .map(|h| Tracked { tx_hash: h.clone(), phase: Phase::Pending })
+            .collect();
+        if self.target == WaitTarget::Accepted {
+            for t in &mut txs {
+                self.reach(t, sink);
+            }
+        }
+
+        let mut scanned = from_height;
+        let mut backoff = Backoff::new(self.poll, self.max_backoff);
+        while txs.iter().any(|t| !matches!(t.phase, Phase::Done(_))) {
+            if self.remaining().is_zero() {
+                let waited_ms = self.started.elapsed().as_millis() as u64;
+                for t in txs.iter_mut().filter(|t| !matches!(t.phase, Phase::Done(_))) {
+                    sink(&WaitEvent::Timeout { tx_hash: t.tx_hash.clone(), waited_ms });
+                    t.phase = Phase::Done(WaitOutcome::TimedOut);
+                }
+                break;
+            }
+            match self.poll_once(&mut txs, &mut scanned, sink).await {
+                Ok(()) => {
+                    backoff.reset();
+                    if txs.iter().any(|t| !matches!(t.phase, Phase::Done(_))) {
+                        tokio::time::sleep(self.poll.min(self.remaining())).await;
+                    }
+                }
+                Err(e) => backoff.retry(e, self.remaining(), sink).await,
+            }
+        }
+
+        txs.iter()
+            .map(|t| match t.phase {
+                Phase::Done(outcome) => outcome,
+                _ => WaitOutcome::TimedOut,
+            })
+            .collect()
+    }
+
+    /// Re-check included transactions, then scan blocks above `scanned`.
+    async fn poll_once(
+        &self,
+        txs: &mut [Tracked],
+        scanned: &mut u64,
+        sink: &mut (dyn FnMut(&WaitEvent) + Send),
+    ) -> Result<(), String> {
+        let tip = self.tip().await?;
+        // A restarted node may come back with a shorter chain.
+        *scanned = (*scanned).min(tip);
+
+        for t in txs.iter_mut() {
+            let Phase::Included { height, block_hash, .. } = &t.phase else { continue };
+            let (height, block_hash) = (*height, block_hash.clone());
+            let current = self.get_json::<BlockView>(&format!("/rpc/block/{}", height)).await?;
+            if current.map(|b| b.hash) != Some(block_hash.clone()) {
+                sink(&WaitEvent::Reorged { tx_hash: t.tx_hash.clone(), height, block_hash });
+                t.phase = Phase::Done(WaitOutcome::Reorged);
+                continue;
+            }
+            self.settle(t, tip, sink);
+        }
+
+        while *scanned < tip && txs.iter().any(|t| matches!(t.phase, Phase::Pending)) {
+            let height = *scanned + 1;
+            let Some(block) = self.get_json::<BlockView>(&format!("/rpc/block/{}", height)).await? else {
+                break;
+            };
+            let hit = |t: &Tracked| matches!(t.phase, Phase::Pending) && block.tx_hashes.contains(&t.tx_hash);
+            if txs.iter().any(hit) {
+                let receipts = self
+                    .get_json::<ReceiptsView>(&format!("/rpc/block/{}/receipts", height))
+                    .await?
+                    .map(|r| r.receipts)
+                    .unwrap_or_default();
+                for t in txs.iter_mut().filter(|t| hit(t)) {
+                    match receipts.iter().find(|r| r.tx_hash == t.tx_hash) {
+                        Some(r) if r.success => {
+                            sink(&WaitEvent::Included {
+                                tx_hash:    t.tx_hash.clone(),
+                                height:     block.height,
+                                block_hash: block.hash.clone(),
+                            });
+                            t.phase = Phase::Included { height: block.height, block_hash: block.hash.clone(), confirmations: 0 };
+                            self.settle(t, tip, sink);
+                        }
+                        r => {
+                            sink(&WaitEvent::Reverted {
+                                tx_hash: t.tx_hash.clone(),
+                                height:  block.height,
+                                error:   r.and_then(|r| r.failure.clone()),
+                            });
+                            t.phase = Phase::Done(WaitOutcome::Reverted);
+                        }
+                    }
+                }
+            }
+            *scanned = height;
+        }
+        Ok(())
+    }
+
+    /// Look for `tx_hash` in the `depth` blocks up to the tip, newest first.
+    pub async fn status(&self, tx_hash: &str, depth: u64) -> Result<TxStatus, String> {
+        let tip = self.tip().await?;
+        let lowest = tip.saturating_sub(depth.saturating_sub(1)).max(1);
+        for height in (lowest..=tip).rev() {
+            let Some(block) = self.get_json::<BlockView>(&format!("/rpc/block/{}", height)).await? else {
+                continue;
+            };
+            if !block.tx_hashes.iter().any(|h| h == tx_hash) {
+                continue;
+            }
+            let receipts = self
+                .get_json::<ReceiptsView>(&format!("/rpc/block/{}/receipts", height))
+                .await?
+                .map(|r| r.receipts)
+                .unwrap_or_default();
+            return Ok(match receipts.into_iter().find(|r| r.tx_hash == tx_hash) {
+                Some(r) if r.success => TxStatus::Included {
+                    height,
+                    block_hash: block.hash,
+                    confirmations: tip + 1 - height,
+                },
+                r => TxStatus::Reverted { height, error: r.and_then(|r| r.failure) },
+            });
+        }
+        Ok(TxStatus::NotFound { scanned: (tip + 1).saturating_sub(lowest) })
+    }
+
+    /// Count confirmations of an included transaction at `tip` and finish
+    /// it once the target is met.
+    fn settle(&self, t: &mut Tracked, tip: u64, sink: &mut (dyn FnMut(&WaitEvent) + Send)) {
+        let Phase::Included { height, confirmations, .. } = &mut t.phase else { return };
+        let now = (tip + 1).saturating_sub(*height);
+        let height = *height;
+        if now > *confirmations && self.target != WaitTarget::Included {
+            *confirmations = now;
+            sink(&WaitEvent::Confirmations { tx_hash: t.tx_hash.clone(), height, confirmations: now });
+        }
+        let done = match self.target {
+            WaitTarget::Accepted | WaitTarget::Included => true,
+            WaitTarget::Confirmed(n) => now >= n,
+        };
+        if done {
+            self.reach(t, sink);
+        }
+    }
+
+    fn reach(&self, t: &mut Tracked, sink: &mut (dyn FnMut(&WaitEvent) + Send)) {
+        sink(&WaitEvent::Reached { tx_hash: t.tx_hash.clone(), target: self.target.to_string() });
+        t.phase = Phase::Done(WaitOutcome::Reached);
+    }
+
+    async fn tip(&self) -> Result<u64, String> {
+        self.get_json::<BlockView>("/rpc/block/latest")
+            .await?
+            .map(|b| b.height)
+            .ok_or_else(|| "no latest block".to_string())
+    }
+
+    /// `Ok(None)` on 404; any other failure is worth retrying.
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>, String> {
+        let resp = self.client.get(format!("{}{}", self.rpc, path)).send().await
+            .map_err(|e| format!("GET {}: {}", path, e))?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !resp.status().is_success() {
+            return Err(format!("GET {}: {}", path, resp.status()));
+        }
+        resp.json().await.map(Some).map_err(|e| format!("GET {}: {}", path, e))
+    }
+
+    async fn post_tx(&self, tx: &ZKTransaction) -> Result<SubmitResp, String> {
+        let resp = self.client.post(format!("{}/rpc/tx", self.rpc)).json(tx).send().await
+            .map_err(|e| format!("POST /rpc/tx: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("POST /rpc/tx: {}", resp.status()));
+        }
+        resp.json().await.map_err(|e| format!("POST /rpc/tx: {}", e))
+    }
+}
+
+#[derive(Serialize)]
+struct TimedEvent<'a> {
+    at_ms: u64,
+    #[serde(flatten)]
+    event: &'a WaitEvent,
+}
+
+/// Sink printing events in `format`.  Rejections also go to stderr as
+/// JSON whatever the format.
+pub fn printer(format: OutputFormat) -> impl FnMut(&WaitEvent) + Send {
+    let started = Instant::now();
+    move |event: &WaitEvent| {
+        let line = serde_json::to_string(&TimedEvent { at_ms: started.elapsed().as_millis() as u64, event })
+            .unwrap_or_default();
+        if let WaitEvent::Rejected { .. } = event {
+            eprintln!("{}", line);
+        }
+        if format == OutputFormat::Json {
+            println!("{}", line);
+            return;
+        }
+        match event {
+            WaitEvent::Accepted { tx_hash, height } => println!("⏳ {} accepted at height {}", tx_hash, height),
+            WaitEvent::Rejected { code, error, .. } => println!("❌ Rejected ({}): {}", code, error),
+            WaitEvent::Included { tx_hash, height, .. } => println!("📦 {} included in block {}", tx_hash, height),
+            WaitEvent::Confirmations { confirmations, .. } => println!("   {} confirmation(s)", confirmations),
+            WaitEvent::Reverted { tx_hash, height, error } => {
+                println!("❌ {} reverted in block {}{}", tx_hash, height,
+                    error.as_deref().map(|e| format!(": {}", e)).unwrap_or_default())
+            }
+            WaitEvent::Reorged { tx_hash, height, .. } => println!("⚠️  {} dropped from block {} by a reorg", tx_hash, height),
+            WaitEvent::Reached { tx_hash, target } => println!("✅ {} {}", tx_hash, target),
+            WaitEvent::Timeout { tx_hash, waited_ms } => {
+                println!("⌛ {} still pending after {:.1}s", tx_hash, *waited_ms as f64 / 1000.0)
+            }
+            WaitEvent::RpcRetry { attempt, error, retry_in_ms } => {
+                eprintln!("↻ RPC unavailable ({}), retry {} in {} ms", error, attempt, retry_in_ms)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use bleep_consensus::block_execution::{receipts_root, tx_hash, TxReceipt};
+    use bleep_consensus::block_store::{BlockStore, StoredBlock};
+    use bleep_core::block::{Block, ChainId, Transaction};
+    use bleep_core::transaction_pool::TransactionPool;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload, tx_payload};
+    use bleep_rpc::{rpc_routes_with_state, RpcState};
+    use tokio::sync::mpsc;
+    use tokio::task::JoinHandle;
+
+    /// An RPC node whose blocks the test writes itself.
+    struct TestNode {
+        state:  RpcState,
+        store:  Arc<BlockStore>,
+        addr:   SocketAddr,
+        server: JoinHandle<()>,
+        _dir:   tempfile::TempDir,
+    }
+
+    impl TestNode {
+        fn start() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let store = Arc::new(BlockStore::open(dir.path()).unwrap());
+            let state = RpcState::new()
+                .with_transaction_pool(TransactionPool::new(100))
+                .with_block_store(Arc::clone(&store));
+            let (addr, server) = Self::serve(&state, 0);
+            TestNode { state, store, addr, server, _dir: dir }
+        }
+
+        fn serve(state: &RpcState, port: u16) -> (SocketAddr, JoinHandle<()>) {
+            let (addr, server) = warp::serve(rpc_routes_with_state(state.clone()))
+                .try_bind_ephemeral(([127, 0, 0, 1], port))
+                .unwrap();
+            (addr, tokio::spawn(server))
+        }
+
+        fn url(&self) -> String {
+            format!("http://{}", self.addr)
+        }
+
+        /// Stop serving for `down`, then come back on the same port.
+        async fn restart(&mut self, down: Duration) {
+            self.server.abort();
+            let _ = (&mut self.server).await;
+            tokio::time::sleep(down).await;
+            let (_, server) = Self::serve(&self.state, self.addr.port());
+            self.server = server;
+        }
+
+        /// Archive the next block with `txs`, applied or all failing.
+        fn mine(&self, txs: &[ZKTransaction], applied: bool) -> u64 {
+            let tip = self.store.tip().unwrap();
+            let height = tip.map_or(1, |t| t + 1);
+            let prev = tip.and_then(|t| self.store.get(t).unwrap()).map_or("0".to_string(), |b| b.block.compute_hash());
+            let txs: Vec<Transaction> = txs.iter().map(|tx| Transaction {
+                sender: tx.sender.clone(), receiver: tx.receiver.clone(), amount: tx.amount,
+                timestamp: tx.timestamp, signature: tx.signature.clone(), max_fee: tx.max_fee, tip: tx.tip, nonce: tx.nonce, chain_id: tx.chain_id,
+            }).collect();
+            let receipts: Vec<TxReceipt> = if applied {
+                txs.iter().map(|tx| TxReceipt {
+                    tx_hash: tx_hash(tx), success: true, gas_used: 21_000, accounts: BTreeMap::new(),
+                    storage_keys: Vec::new(), failure: None, fee: None,
+                }).collect()
+            } else {
+                Vec::new()
+            };
+            let mut stored = StoredBlock::adopted(Block::new(height, txs, prev), height);
+            stored.receipts_root = hex::encode(receipts_root(&receipts));
+            stored.receipts = receipts;
+            self.store.put(&stored).unwrap();
+            height
+        }
+
+        /// Replace block `height` and everything above with one empty block.
+        fn reorg(&self, height: u64) {
+            self.store.truncate_above(height - 1).unwrap();
+            self.mine(&[], true);
+        }
+    }
+
+    fn signed_tx(timestamp: u64) -> ZKTransaction {
+        let (pk, sk) = generate_tx_keypair();
+        let (sender, receiver, amount) = ("alice", "bob", 5);
+        let mut signature = pk;
+        signature.extend(sign_tx_payload(&tx_payload(sender, receiver, amount, timestamp), &sk).unwrap());
+        ZKTransaction {
+            sender: sender.into(), receiver: receiver.into(), amount, timestamp, signature, max_fee: 0, tip: 0, nonce: 0, chain_id: ChainId::NONE,
+        }
+    }
+
+    fn waiter(node: &TestNode, target: &str, timeout_ms: u64) -> TxWaiter {
+        TxWaiter::new(&node.url(), target.parse().unwrap(), Duration::from_millis(timeout_ms))
+            .with_poll(Duration::from_millis(20), Duration::from_millis(100))
+    }
+
+    fn channel_sink() -> (impl FnMut(&WaitEvent) + Send, mpsc::UnboundedReceiver<WaitEvent>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (move |e: &WaitEvent| { let _ = tx.send(e.clone()); }, rx)
+    }
+
+    /// Submit `tx` and track it in the background.
+    async fn submit_and_track(
+        node: &TestNode,
+        target: &str,
+        timeout_ms: u64,
+        tx: ZKTransaction,
+    ) -> (JoinHandle<WaitOutcome>, mpsc::UnboundedReceiver<WaitEvent>) {
+        let waiter = waiter(node, target, timeout_ms);
+        let (mut sink, rx) = channel_sink();
+        let admission = waiter.submit(&tx, &mut sink).await.unwrap();
+        let Admission::Accepted { tx_hash, height } = admission else { panic!("rejected: {:?}", admission) };
+        let handle = tokio::spawn(async move { waiter.track(&[tx_hash], height, &mut sink).await[0] });
+        (handle, rx)
+    }
+
+    async fn next_event(rx: &mut mpsc::UnboundedReceiver<WaitEvent>, name: &str) -> WaitEvent {
+        loop {
+            let event = tokio::time::timeout(Duration::from_secs(10), rx.recv()).await.unwrap().unwrap();
+            if kind(&event) == name {
+                return event;
+            }
+        }
+    }
+
+    fn kind(event: &WaitEvent) -> String {
+        serde_json::to_value(event).unwrap()["event"].as_str().unwrap().to_string()
+    }
+
+    /// Event kinds in order, repeated retries collapsed.
+    fn timeline(mut rx: mpsc::UnboundedReceiver<WaitEvent>) -> Vec<String> {
+        let mut kinds: Vec<String> = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            let k = kind(&event);
+            if kinds.last() != Some(&k) {
+                kinds.push(k);
+            }
+        }
+        kinds
+    }
+
+    #[test]
+    fn targets_parse() {
+        assert_eq!("accepted".parse(), Ok(WaitTarget::Accepted));
+        assert_eq!("confirmed:6".parse(), Ok(WaitTarget::Confirmed(6)));
+        assert!("confirmed:0".parse::<WaitTarget>().is_err());
+        assert!("final".parse::<WaitTarget>().is_err());
+        assert_eq!(WaitTarget::Confirmed(3).to_string(), "confirmed:3");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn confirmed_across_a_node_restart() {
+        let mut node = TestNode::start();
+        node.mine(&[], true);
+        let tx = signed_tx(1_700_000_000);
+        let (handle, mut rx) = submit_and_track(&node, "confirmed:3", 10_000, tx.clone()).await;
+
+        node.mine(&[tx], true);
+        next_event(&mut rx, "included").await;
+        node.restart(Duration::from_millis(300)).await;
+        node.mine(&[], true);
+        node.mine(&[], true);
+
+        let outcome = handle.await.unwrap();
+        assert_eq!(outcome.exit_code(), EXIT_REACHED);
+        let kinds = timeline(rx);
+        assert!(kinds.contains(&"rpc_retry".to_string()), "{:?}", kinds);
+        assert_eq!(kinds.last().unwrap(), "reached");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn status_reports_inclusion_reverts_and_misses() {
+        let node = TestNode::start();
+        let (kept, failed, unknown) = (signed_tx(1_700_000_010), signed_tx(1_700_000_011), signed_tx(1_700_000_012));
+        node.mine(&[kept.clone()], true);
+        node.mine(&[failed.clone()], false);
+        node.mine(&[], true);
+        let waiter = waiter(&node, "included", 5_000);
+
+        let status = waiter.status(&receipt_hash(&kept), 10).await.unwrap();
+        assert!(matches!(status, TxStatus::Included { height: 1, confirmations: 3, .. }), "{:?}", status);
+        let status = waiter.status(&receipt_hash(&failed), 10).await.unwrap();
+        assert_eq!(status, TxStatus::Reverted { height: 2, error: None });
+        assert_eq!(waiter.status(&receipt_hash(&unknown), 10).await.unwrap(), TxStatus::NotFound { scanned: 3 });
+        // Older than the scanned depth
+        assert_eq!(waiter.status(&receipt_hash(&kept), 2).await.unwrap(), TxStatus::NotFound { scanned: 2 });
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn confirmations_are_streamed_in_order() {
+        let node = TestNode::start();
+        let tx = signed_tx(1_700_000_001);
+        let (handle, rx) = submit_and_track(&node, "confirmed:2", 10_000, tx.clone()).await;
+        node.mine(&[tx], true);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        node.mine(&[], true);
+
+        assert_eq!(handle.await.unwrap(), WaitOutcome::Reached);
+        assert_eq!(timeline(rx), ["accepted", "included", "confirmations", "reached"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn admission_rejection_is_typed() {
+        let node = TestNode::start();
+        let mut tx = signed_tx(1_700_000_002);
+        tx.signature.truncate(80);
+        let waiter = waiter(&node, "included", 5_000);
+        let (mut sink, rx) = channel_sink();
+        let admission = waiter.submit(&tx, &mut sink).await.unwrap();
+        assert!(matches!(&admission, Admission::Rejected { code, .. } if code == "bad_signature"), "{:?}", admission);
+        assert_eq!(WaitOutcome::Rejected.exit_code(), EXIT_REJECTED);
+        assert_eq!(timeline(rx), ["rejected"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn included_but_reverted() {
+        let node = TestNode::start();
+        let tx = signed_tx(1_700_000_003);
+        let (handle, rx) = submit_and_track(&node, "confirmed:2", 10_000, tx.clone()).await;
+        node.mine(&[tx], false);
+
+        let outcome = handle.await.unwrap();
+        assert_eq!(outcome.exit_code(), EXIT_REVERTED);
+        assert_eq!(timeline(rx), ["accepted", "reverted"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn reorg_after_inclusion() {
+        let node = TestNode::start();
+        let tx = signed_tx(1_700_000_004);
+        let (handle, mut rx) = submit_and_track(&node, "confirmed:5", 10_000, tx.clone()).await;
+        let height = node.mine(&[tx], true);
+        next_event(&mut rx, "included").await;
+        node.reorg(height);
+
+        let outcome = handle.await.unwrap();
+        assert_eq!(outcome.exit_code(), EXIT_REORGED);
+        assert_eq!(timeline(rx).last().unwrap(), "reorged");
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn accepted_but_timed_out() {
+        let node = TestNode::start();
+        let (handle, rx) = submit_and_track(&node, "included", 300, signed_tx(1_700_000_005)).await;
+
+        let outcome = handle.await.unwrap();
+        assert_eq!(outcome.exit_code(), EXIT_TIMEOUT);
+        assert_eq!(timeline(rx), ["accepted", "timeout"]);
+    }
+}
@@ -0,0 +1,410 @@
+//! # Validator onboarding
+//!
+//! The path from a funded wallet to a block-signing validator:
+//!
+//! ```text
+//! validator init     <home>/consensus_key.json, node key in <home>/p2p,
+//!                    [consensus] stanza in <home>/node.toml
+//! validator bond     staking params, balance and bonds from RPC → BondRequest::check
+//!                    → preview → sign bleep:staking/bond/<pk> → POST /rpc/tx
+//!                    (joins the set two epochs on, see bleep_consensus::staking)
+//! validator status   GET /rpc/validator/bond/<id>: stake, activation countdown,
+//!                    set membership, missed slots, unbonding progress
+//! validator unbond   start unbonding; run again once withdrawable to withdraw
+//! ```
+//!
+//! `bond` runs the checks the block executor applies to the transaction
+//! against the node's current state and signs nothing if one fails.  The
+//! node signs with the key once started with
+//! `BLEEP_NODE_CONFIG=<home>/node.toml BLEEP_P2P_DIR=<home>/p2p`.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use bleep_consensus::consensus_key::{ConsensusConfig, ConsensusKeystore, CONSENSUS_KEY_FILE, NODE_CONFIG_FILE};
+use bleep_consensus::staking::{BondRequest, BondStatus, StakingParams, StakingTx};
+use bleep_core::block::ChainId;
+use bleep_core::transaction::ZKTransaction;
+use bleep_crypto::tx_signer::tx_payload_with_nonce;
+
+use crate::send_prompt::{format_amount, NATIVE_DECIMALS, NATIVE_SYMBOL};
+
+/// Subdirectory of the validator home holding the P2P node key.
+pub const P2P_SUBDIR: &str = "p2p";
+
+/// Home used without `--home`: `$HOME/.bleep/validator`.
+pub fn default_home() -> PathBuf {
+    std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default().join(".bleep").join("validator")
+}
+
+/// Outcome of `validator init`.
+#[derive(Debug)]
+pub struct Initialized {
+    pub keystore:    ConsensusKeystore,
+    /// The consensus key was generated by this run.
+    pub created:     bool,
+    pub node_id:     String,
+    pub home:        PathBuf,
+    pub config_path: PathBuf,
+}
+
+/// What the operator shares, and bonds to, to become a validator.
+#[derive(Debug, Serialize)]
+pub struct Registration {
+    pub validator_id:  String,
+    pub consensus_key: String,
+    pub node_id:       String,
+    /// Receiver of the bond transaction.
+    pub bond_receiver: String,
+}
+
+impl Initialized {
+    pub fn registration(&self) -> Result<Registration, String> {
+        let consensus_key = self.keystore.public_key()?;
+        Ok(Registration {
+            validator_id:  self.keystore.validator_id.clone(),
+            bond_receiver: StakingTx::Bond { consensus_key: consensus_key.clone() }.receiver(),
+            consensus_key: hex::encode(consensus_key),
+            node_id:       self.node_id.clone(),
+        })
+    }
+}
+
+/// Create whatever `home` lacks of the consensus keystore, the node key
+/// and the `[consensus]` stanza.  Existing keys are kept.
+pub fn init(home: &Path) -> Result<Initialized, String> {
+    let (keystore, created) = ConsensusKeystore::load_or_create(home)?;
+    let home = home.canonicalize().map_err(|e| format!("{}: {}", home.display(), e))?;
+    let identity = bleep_p2p::node_info::load_or_create_identity(&home.join(P2P_SUBDIR))
+        .map_err(|e| format!("node identity: {}", e))?;
+    let config_path = home.join(NODE_CONFIG_FILE);
+    ConsensusConfig { keystore: home.join(CONSENSUS_KEY_FILE), validator_id: keystore.validator_id.clone() }
+        .write(&config_path)?;
+    Ok(Initialized { keystore, created, node_id: identity.node_id().to_string(), home, config_path })
+}
+
+/// The keystore named by `home`'s node config.
+pub fn load_keystore(home: &Path) -> Result<ConsensusKeystore, String> {
+    let path = home.join(NODE_CONFIG_FILE);
+    ConsensusConfig::read(&path)?
+        .ok_or_else(|| format!("{} has no [consensus] stanza — run `bleep-cli validator init` first", path.display()))?
+        .load_keystore()
+}
+
+// ── Bonding ───────────────────────────────────────────────────────────────────
+
+/// A bond that passed every check, shown before it is signed.
+#[derive(Debug, Clone)]
+pub struct BondPreview {
+    pub from:         String,
+    pub validator_id: String,
+    pub amount:       u128,
+    pub balance:      u128,
+    /// The validator's stake once the bond is applied.
+    pub stake_after:  u128,
+    pub params:       StakingParams,
+    /// Adds to a bond `from` already holds.
+    pub top_up:       bool,
+}
+
+impl BondPreview {
+    pub fn render(&self) -> String {
+        let bleep = |v: u128| format!("{} {}", format_amount(v, NATIVE_DECIMALS), NATIVE_SYMBOL);
+        let mut out = String::new();
+        out.push_str(&format!("{} validator {}\n", if self.top_up { "Top up" } else { "Bond" }, self.validator_id));
+        out.push_str(&format!("  From:        {}\n", self.from));
+        out.push_str(&format!("  Amount:      {}\n", bleep(self.amount)));
+        out.push_str(&format!("  Balance:     {} → {}\n", bleep(self.balance), bleep(self.balance - self.amount)));
+        out.push_str(&format!("  Stake:       {} (minimum {})\n", bleep(self.stake_after), bleep(self.params.min_stake)));
+        out.push_str(&format!("  Unbonding:   {} blocks\n", self.params.unbonding_blocks));
+        out
+    }
+}
+
+/// Check a bond of `amount` from `from` to `consensus_key` against the
+/// node's state: balance, minimum stake and existing key/owner bindings.
+pub async fn preview_bond(
+    client:        &reqwest::Client,
+    rpc:           &str,
+    from:          &str,
+    consensus_key: &[u8],
+    amount:        u64,
+) -> Result<BondPreview, String> {
+    let request = BondRequest { sender: from, consensus_key, amount: amount as u128 };
+    let params = staking_params(client, rpc).await?;
+    let balance = balance_of(client, rpc, from).await?;
+    let key_bond = fetch_status(client, rpc, &request.validator_id()).await?.map(|s| s.bond);
+    let owner_bond = fetch_status(client, rpc, from).await?.map(|s| s.bond);
+    let stake_after = request.check(&params, balance, key_bond.as_ref(), owner_bond.as_ref())?;
+    Ok(BondPreview {
+        from: from.to_string(),
+        validator_id: request.validator_id(),
+        amount: request.amount,
+        balance,
+        stake_after,
+        params,
+        top_up: owner_bond.is_some(),
+    })
+}
+
+/// A staking transaction from `from` on `chain_id`; `sign` returns the wire
+/// signature (`pk || sig`) of the payload it is given.
+#[allow(clippy::too_many_arguments)]
+pub fn staking_transaction(
+    chain_id:  ChainId,
+    from:      &str,
+    request:   &StakingTx,
+    amount:    u64,
+    timestamp: u64,
+    max_fee:   u64,
+    tip:       u64,
+    nonce:     u64,
+    sign:      impl FnOnce(&[u8]) -> Result<Vec<u8>, String>,
+) -> Result<ZKTransaction, String> {
+    let receiver = request.receiver();
+    let signature = sign(&chain_id.bind(tx_payload_with_nonce(from, &receiver, amount, timestamp, max_fee, tip, nonce)))?;
+    Ok(ZKTransaction { sender: from.to_string(), receiver, amount, timestamp, signature, max_fee, tip, nonce, chain_id })
+}
+
+// ── Status ────────────────────────────────────────────────────────────────────
+
+/// GET /rpc/validator/staking
+pub async fn staking_params(client: &reqwest::Client, rpc: &str) -> Result<StakingParams, String> {
+    client.get(format!("{}/rpc/validator/staking", rpc))
+        .send().await.map_err(|e| e.to_string())?
+        .error_for_status().map_err(|e| e.to_string())?
+        .json().await.map_err(|e| format!("staking parameters: {}", e))
+}
+
+/// GET /rpc/validator/bond/{who} — `None` if there is no such bond.
+pub async fn fetch_status(client: &reqwest::Client, rpc: &str, who: &str) -> Result<Option<BondStatus>, String> {
+    let resp = client.get(format!("{}/rpc/validator/bond/{}", rpc, who))
+        .send().await.map_err(|e| e.to_string())?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    resp.error_for_status().map_err(|e| e.to_string())?
+        .json().await.map(Some).map_err(|e| format!("bond status: {}", e))
+}
+
+async fn balance_of(client: &reqwest::Client, rpc: &str, address: &str) -> Result<u128, String> {
+    let body: serde_json::Value = client.get(format!("{}/rpc/state/{}", rpc, address))
+        .send().await.map_err(|e| e.to_string())?
+        .error_for_status().map_err(|e| e.to_string())?
+        .json().await.map_err(|e| e.to_string())?;
+    body["balance"].as_str().and_then(|b| b.parse().ok())
+        .ok_or_else(|| format!("malformed balance for {}", address))
+}
+
+/// `validator status` report.
+pub fn format_status(status: &BondStatus) -> String {
+    let bond = &status.bond;
+    let mut out = format!("Validator {} (owner {})\n", bond.validator_id, bond.owner);
+    out.push_str(&format!("  Stake:        {} {}\n", format_amount(bond.stake, NATIVE_DECIMALS), NATIVE_SYMBOL));
+    let phase = match (status.phase.as_str(), status.blocks_until_withdrawable, status.withdrawable_at) {
+        ("unbonding", Some(left), Some(at)) => {
+            format!("unbonding — {} blocks until withdrawable (height {})", left, at)
+        }
+        ("withdrawable", ..) => "withdrawable — run `bleep-cli validator unbond` to withdraw".to_string(),
+        (phase, ..) => format!("{} since height {}", phase, bond.bonded_at),
+    };
+    out.push_str(&format!("  Phase:        {}\n", phase));
+    if let Some(epoch) = status.epoch {
+        let set = match (status.in_current_set, status.activation_epoch, status.blocks_until_active) {
+            (true, ..) => "in the current set".to_string(),
+            (false, Some(at), Some(left)) => format!("joins the set in epoch {} ({} blocks)", at, left),
+            _ => "not in the current set".to_string(),
+        };
+        out.push_str(&format!("  Epoch:        {} — {}\n", epoch, set));
+    }
+    let missed = if status.missed_slots.is_empty() {
+        "none".to_string()
+    } else {
+        let heights: Vec<String> = status.missed_slots.iter().map(u64::to_string).collect();
+        format!("{} recent (heights {}), {} total", heights.len(), heights.join(", "), status.missed_total)
+    };
+    out.push_str(&format!("  Missed slots: {}\n", missed));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::devnet::{DevAccount, Devnet, DevnetConfig, DEV_VALIDATOR_STAKE};
+
+    const BLOCKS_PER_EPOCH: u64 = 4;
+
+    fn config() -> DevnetConfig {
+        DevnetConfig {
+            block_time_ms: 0,
+            accounts: 2,
+            rpc_port: 0,
+            blocks_per_epoch: BLOCKS_PER_EPOCH,
+            unbonding_blocks: 3,
+            ..DevnetConfig::default()
+        }
+    }
+
+    async fn submit(devnet: &Devnet, tx: &ZKTransaction) {
+        let resp: serde_json::Value = reqwest::Client::new()
+            .post(format!("{}/rpc/tx", devnet.rpc_url()))
+            .json(tx)
+            .send().await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(resp["status"], "accepted", "{}", resp);
+    }
+
+    /// Commit one more block, carried by a small transfer.
+    async fn next_block(devnet: &Devnet, ts: &mut u64) {
+        let height = devnet.height();
+        *ts += 1;
+        let sender = &devnet.accounts[1];
+        let nonce = devnet.next_nonce(&sender.address).await;
+        submit(devnet, &sender.transfer(&devnet.accounts[0].address, 1, *ts, nonce).unwrap()).await;
+        for _ in 0..100 {
+            if devnet.height() > height {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("no block after height {}", height);
+    }
+
+    /// Submit `request` from `account` and wait until `until` holds of its
+    /// validator's status.
+    async fn stake_and_wait(
+        devnet: &Devnet,
+        account: &DevAccount,
+        request: StakingTx,
+        amount: u64,
+        ts: &mut u64,
+        until: impl Fn(&Option<BondStatus>) -> bool,
+    ) -> Option<BondStatus> {
+        let client = reqwest::Client::new();
+        *ts += 1;
+        let nonce = devnet.next_nonce(&account.address).await;
+        let tx = staking_transaction(ChainId::DEVNET, &account.address, &request, amount, *ts, 0, 0, nonce, |p| account.sign(p)).unwrap();
+        submit(devnet, &tx).await;
+        for _ in 0..100 {
+            let status = fetch_status(&client, &devnet.rpc_url(), &account.address).await.unwrap();
+            if until(&status) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        panic!("{} never reached the expected bond status", account.address);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn bonded_key_proposes_from_the_next_plus_one_epoch() {
+        let devnet = Devnet::start(config()).await.unwrap();
+        let (client, rpc) = (reqwest::Client::new(), devnet.rpc_url());
+        let alice = devnet.accounts[0].clone();
+        let mut ts = 1_700_000_000;
+
+        let home = tempfile::tempdir().unwrap();
+        let init = init(home.path()).unwrap();
+        assert!(init.created);
+        let again = super::init(home.path()).unwrap();
+        assert!(!again.created);
+        assert_eq!(again.keystore.validator_id, init.keystore.validator_id);
+        assert_eq!(load_keystore(home.path()).unwrap().validator_id, init.keystore.validator_id);
+        let registration = init.registration().unwrap();
+        let key = init.keystore.public_key().unwrap();
+
+        let preview = preview_bond(&client, &rpc, &alice.address, &key, DEV_VALIDATOR_STAKE as u64).await.unwrap();
+        assert_eq!((preview.stake_after, preview.top_up), (DEV_VALIDATOR_STAKE, false));
+        let err = preview_bond(&client, &rpc, &alice.address, &key, DEV_VALIDATOR_STAKE as u64 - 1).await.unwrap_err();
+        assert!(err.contains("below the minimum"), "{}", err);
+
+        let request = StakingTx::Bond { consensus_key: key.clone() };
+        let status = stake_and_wait(&devnet, &alice, request, DEV_VALIDATOR_STAKE as u64, &mut ts, Option::is_some)
+            .await.unwrap();
+        assert_eq!((status.phase.as_str(), status.bond.stake), ("bonded", DEV_VALIDATOR_STAKE));
+        assert!(!status.in_current_set);
+        let included = (1..=devnet.height())
+            .find(|h| devnet.block(*h).unwrap().transactions.iter().any(|tx| tx.receiver == registration.bond_receiver))
+            .unwrap();
+        let epoch = included / BLOCKS_PER_EPOCH;
+        let active = if included % BLOCKS_PER_EPOCH == 0 { epoch + 1 } else { epoch + 2 };
+
+        devnet.load_consensus_key(&init.keystore).unwrap();
+        while devnet.height() < (active + 1) * BLOCKS_PER_EPOCH {
+            next_block(&devnet, &mut ts).await;
+        }
+        let signed: Vec<u64> = (1..=devnet.height())
+            .filter(|h| devnet.block(*h).unwrap().signer_public_key() == Some(key.as_slice()))
+            .collect();
+        assert!(!signed.is_empty());
+        assert_eq!(signed[0] / BLOCKS_PER_EPOCH, active, "signed {:?}, bonded at {}", signed, included);
+
+        let status = fetch_status(&client, &rpc, &registration.validator_id).await.unwrap().unwrap();
+        assert!(status.in_current_set);
+        assert_eq!(status.missed_total, 0);
+        assert!(format_status(&status).contains("in the current set"));
+        devnet.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn a_consensus_key_cannot_be_bound_to_a_second_account() {
+        let devnet = Devnet::start(config()).await.unwrap();
+        let (client, rpc) = (reqwest::Client::new(), devnet.rpc_url());
+        let (alice, bob) = (devnet.accounts[0].clone(), devnet.accounts[1].clone());
+        let mut ts = 1_700_000_000;
+        let home = tempfile::tempdir().unwrap();
+        let key = init(home.path()).unwrap().keystore.public_key().unwrap();
+
+        let request = StakingTx::Bond { consensus_key: key.clone() };
+        stake_and_wait(&devnet, &alice, request.clone(), DEV_VALIDATOR_STAKE as u64, &mut ts, Option::is_some).await;
+
+        let err = preview_bond(&client, &rpc, &bob.address, &key, DEV_VALIDATOR_STAKE as u64).await.unwrap_err();
+        assert!(err.contains(&format!("already bound to {}", alice.address)), "{}", err);
+
+        // Submitted anyway, the block executor refuses it too.
+        ts += 1;
+        let nonce = devnet.next_nonce(&bob.address).await;
+        let tx = staking_transaction(ChainId::DEVNET, &bob.address, &request, DEV_VALIDATOR_STAKE as u64, ts, 0, 0, nonce, |p| bob.sign(p)).unwrap();
+        submit(&devnet, &tx).await;
+        next_block(&devnet, &mut ts).await;
+        next_block(&devnet, &mut ts).await;
+        assert!(fetch_status(&client, &rpc, &bob.address).await.unwrap().is_none());
+        let status = fetch_status(&client, &rpc, &hex::encode(&key)).await.unwrap().unwrap();
+        assert_eq!((status.bond.owner.as_str(), status.bond.stake), (alice.address.as_str(), DEV_VALIDATOR_STAKE));
+        devnet.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn status_follows_unbonding_to_withdrawal() {
+        let devnet = Devnet::start(config()).await.unwrap();
+        let (client, rpc) = (reqwest::Client::new(), devnet.rpc_url());
+        let alice = devnet.accounts[0].clone();
+        let mut ts = 1_700_000_000;
+        let home = tempfile::tempdir().unwrap();
+        let key = init(home.path()).unwrap().keystore.public_key().unwrap();
+
+        let bond = StakingTx::Bond { consensus_key: key };
+        stake_and_wait(&devnet, &alice, bond, DEV_VALIDATOR_STAKE as u64, &mut ts, Option::is_some).await;
+        let status = stake_and_wait(&devnet, &alice, StakingTx::Unbond, 0, &mut ts, |s| {
+            s.as_ref().is_some_and(|s| s.phase == "unbonding")
+        }).await.unwrap();
+        let left = status.blocks_until_withdrawable.unwrap();
+        assert!((1..=3).contains(&left), "{}", left);
+        assert!(format_status(&status).contains(&format!("unbonding — {} blocks until withdrawable", left)));
+
+        for _ in 0..left {
+            next_block(&devnet, &mut ts).await;
+        }
+        let status = fetch_status(&client, &rpc, &alice.address).await.unwrap().unwrap();
+        assert_eq!(status.phase, "withdrawable");
+        assert!(format_status(&status).contains("withdrawable — run"));
+
+        let before = devnet.state().lock().get_balance(&alice.address);
+        stake_and_wait(&devnet, &alice, StakingTx::Unbond, 0, &mut ts, Option::is_none).await;
+        let after = devnet.state().lock().get_balance(&alice.address);
+        assert!(after >= before + DEV_VALIDATOR_STAKE, "{} → {}", before, after);
+        devnet.shutdown().await;
+    }
+}
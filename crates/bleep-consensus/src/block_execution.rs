@@ -0,0 +1,716 @@
+//! # Block execution
+//!
+//! The single code path that turns an ordered list of transactions into
+//! state changes and receipts.  `BlockProducer` runs it to build blocks and
+//! `replay` runs it to re-check stored ones, so a divergence found by the
+//! replay tool is a divergence in production execution.
+//!
+//! ```text
+//! Phase A (no lock)   VM Executor.execute(TransferIntent)   per tx
+//! Phase B (locked)    StateManager.apply_transfer            native accounting
+//!                     + VM StateDiff balances / nonces        contract side-effects
+//!                     StateManager.advance_block()           only if ≥1 tx applied
+//! ```
+//!
+//! With a `FeeContext` (`execute_block_with_fees`), each applied transaction
+//! also pays the block's base fee plus its tip: the tip is credited to the
+//! proposer, the treasury share to `TREASURY_ACCOUNT`, and the rest is
+//! burned.  A transaction that cannot pay is rejected like a failed transfer.
+//!
+//! A transaction is rejected if its signer is not one of the sender's keys
+//! (`StateManager::key_authorized`) or its nonce is not the sender's
+//! current one; every applied transaction moves the sender's nonce on.  Key changes
+//! (`bleep_crypto::key_change`) skip the VM and install the new key
+//! instead of moving funds, once a registered recovery key has co-signed.
+//!
+//! With a `RewardContext` (`execute_block_with_rewards`), tips go to
+//! `REWARD_POOL_ACCOUNT` instead of the proposer, the epoch's block issuance
+//! is minted there, and the block is attributed in the `RewardLedger`.
+//! Reward transactions (`rewards::RewardTx`) also skip the VM: a claim
+//! moves the sender's accrued rewards out of the pool account.  So do
+//! staking transactions (`staking::StakingTx`), which bond stake to a
+//! consensus key or unbond it.
+
+use std::collections::BTreeMap;
+
+use parking_lot::Mutex as PLMutex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tracing::warn;
+
+use bleep_core::base_fee::{charge, BaseFeeParams, FeeCharge, TREASURY_ACCOUNT};
+use bleep_core::block::Transaction;
+use bleep_crypto::key_change::{is_key_change, key_hash, split_cosigned, KeyChange, PUBLIC_KEY_LEN};
+use bleep_crypto::tx_signer::verify_tx_signature;
+use bleep_state::state_manager::StateManager;
+use bleep_vm::execution::executor::{Executor, ExecutorConfig};
+use bleep_vm::execution::state_transition::StateDiff;
+use bleep_vm::execution::trace::{extract_trace, TraceStep};
+use bleep_vm::intent::{Intent, IntentKind, TransferIntent};
+use bleep_vm::types::ChainId;
+
+use crate::rewards::{is_reward_tx, RewardLedger, RewardTx, REWARD_POOL_ACCOUNT};
+use crate::staking::{self, is_staking_tx, StakingTx, STAKING_ACCOUNT};
+
+/// BLEEP native chain ID for intent routing
+const BLEEP_CHAIN_ID: ChainId = ChainId::Bleep;
+
+// ── Receipts ──────────────────────────────────────────────────────────────────
+
+/// Post-transaction value of one account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub balance: u128,
+    pub nonce:   u64,
+}
+
+/// Outcome of one transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxReceipt {
+    pub tx_hash:      String,
+    pub success:      bool,
+    pub gas_used:     u64,
+    /// Values of every account the transaction wrote, after it ran.
+    pub accounts:     BTreeMap<String, AccountSnapshot>,
+    /// Contract storage slots written by the VM, as `contract:key` hex.
+    pub storage_keys: Vec<String>,
+    pub failure:      Option<String>,
+    /// Fee paid, when the block charged a base fee.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee:          Option<FeeCharge>,
+}
+
+/// Fee terms a block executes under.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeContext {
+    pub base_fee: u64,
+    pub params:   BaseFeeParams,
+    /// Account credited with tips.
+    pub proposer: String,
+}
+
+/// Reward accounting a block executes under.
+pub struct RewardContext<'a> {
+    pub ledger:   &'a PLMutex<RewardLedger>,
+    pub epoch:    u64,
+    pub proposer: &'a str,
+    /// `(validator, stake)` of the votes the block carries.
+    pub voters:   &'a [(String, u128)],
+}
+
+/// Receipt plus the execution trace (empty unless the executor traces).
+#[derive(Debug, Clone)]
+pub struct TxExecution {
+    pub receipt: TxReceipt,
+    pub trace:   Vec<TraceStep>,
+}
+
+/// Result of `execute_block`.
+#[derive(Debug, Clone)]
+pub struct BlockExecution {
+    /// One entry per input transaction, in order.
+    pub txs:        Vec<TxExecution>,
+    /// Gas of applied transactions.
+    pub gas_used:   u64,
+    /// State root after the block (unchanged if nothing applied).
+    pub state_root: [u8; 32],
+    /// Height the block executed on top of.
+    pub parent_state_height: u64,
+    /// Fee terms the block executed under, if any.
+    pub fees:       Option<FeeContext>,
+}
+
+impl BlockExecution {
+    /// Receipts of the transactions that were applied, in order.
+    pub fn receipts(&self) -> Vec<TxReceipt> {
+        self.txs.iter().filter(|t| t.receipt.success).map(|t| t.receipt.clone()).collect()
+    }
+
+    pub fn any_applied(&self) -> bool {
+        self.txs.iter().any(|t| t.receipt.success)
+    }
+
+    /// Fees paid by the applied transactions.
+    pub fn fee_charges(&self) -> Vec<FeeCharge> {
+        self.txs.iter().filter_map(|t| t.receipt.fee).collect()
+    }
+}
+
+/// Canonical transaction hash (SHA3-256 over the pool's tx id).
+pub fn tx_hash(tx: &Transaction) -> String {
+    let id = format!("{}:{}:{}:{}", tx.sender, tx.receiver, tx.amount, tx.timestamp);
+    hex::encode(Sha3_256::digest(id.as_bytes()))
+}
+
+/// Commitment over an ordered receipt list.
+pub fn receipts_root(receipts: &[TxReceipt]) -> [u8; 32] {
+    let mut h = Sha3_256::new();
+    h.update(b"BLEEP-RECEIPTS");
+    for r in receipts {
+        let leaf = Sha3_256::digest(serde_json::to_vec(r).unwrap_or_default());
+        h.update(leaf);
+    }
+    h.finalize().into()
+}
+
+/// VM intent a block transaction executes as.
+pub fn transfer_intent(tx: &Transaction) -> Intent {
+    let mut from_bytes = [0u8; 32];
+    let mut to_bytes   = [0u8; 32];
+    let from_b = tx.sender.as_bytes();
+    let to_b   = tx.receiver.as_bytes();
+    from_bytes[..from_b.len().min(32)].copy_from_slice(&from_b[..from_b.len().min(32)]);
+    to_bytes[..to_b.len().min(32)].copy_from_slice(&to_b[..to_b.len().min(32)]);
+
+    Intent::new_unsigned(
+        IntentKind::Transfer(TransferIntent {
+            from:   from_bytes,
+            to:     to_bytes,
+            amount: tx.amount as u128,
+            memo:   None,
+        }),
+        BLEEP_CHAIN_ID,
+    )
+}
+
+// ── Execution ─────────────────────────────────────────────────────────────────
+
+/// The executor configuration blocks are produced with.  `trace` only adds
+/// trace records to the engine logs; it does not change execution.
+pub fn production_executor(trace: bool) -> Executor {
+    Executor::production(ExecutorConfig { trace, ..ExecutorConfig::default() })
+}
+
+/// Execute `txs` against `state`.
+///
+/// `parking_lot::Mutex` must NOT be held across `.await`, so the VM runs
+/// first without the lock and the state is updated afterwards in one
+/// locked section.  Rejected transactions leave the state untouched; the
+/// height only advances if at least one transaction was applied.
+pub async fn execute_block(
+    executor: &Executor,
+    state:    &PLMutex<StateManager>,
+    txs:      &[Transaction],
+) -> BlockExecution {
+    execute_block_with_fees(executor, state, txs, None).await
+}
+
+/// `execute_block`, charging every applied transaction under `fees`.
+pub async fn execute_block_with_fees(
+    executor: &Executor,
+    state:    &PLMutex<StateManager>,
+    txs:      &[Transaction],
+    fees:     Option<&FeeContext>,
+) -> BlockExecution {
+    execute_block_with_rewards(executor, state, txs, fees, None).await
+}
+
+/// `execute_block_with_fees`, paying tips and issuance into the reward
+/// pool and attributing them under `rewards`.
+pub async fn execute_block_with_rewards(
+    executor: &Executor,
+    state:    &PLMutex<StateManager>,
+    txs:      &[Transaction],
+    fees:     Option<&FeeContext>,
+    rewards:  Option<&RewardContext<'_>>,
+) -> BlockExecution {
+    // Phase A (no lock): route every tx through the VM executor.
+    let mut vm_results: Vec<(u64, Result<StateDiff, String>, Vec<TraceStep>)> =
+        Vec::with_capacity(txs.len());
+    for tx in txs {
+        if is_key_change(&tx.receiver) || is_reward_tx(&tx.receiver) || is_staking_tx(&tx.receiver) {
+            vm_results.push((0, Ok(StateDiff::default()), Vec::new()));
+            continue;
+        }
+        let entry = match executor.execute(&transfer_intent(tx)).await {
+            Ok(o) if o.success() => {
+                (o.bleep_gas, Ok(o.state_diff().clone()), extract_trace(&o.routed_result.result.logs))
+            }
+            Ok(o) => {
+                let reason = o.routed_result.result.revert_reason.clone()
+                    .unwrap_or_else(|| "VM reverted".into());
+                (o.bleep_gas, Err(reason), extract_trace(&o.routed_result.result.logs))
+            }
+            Err(e) => {
+                warn!("[BlockExecution] VM error tx {}→{}: {:?}", tx.sender, tx.receiver, e);
+                (0, Err(format!("VM error: {}", e)), Vec::new())
+            }
+        };
+        vm_results.push(entry);
+    }
+
+    // Phase B: acquire state lock (sync), apply accepted txs
+    //
+    // Two accounting paths (both applied for accepted txs):
+    //   1. Native apply_transfer()  — canonical BLEEP balance accounting
+    //   2. VM StateDiff.balances    — EVM/WASM/ZK engine side-effects
+    //      (contract-emitted balance changes beyond the simple transfer)
+    let mut state = state.lock();
+    let parent_state_height = state.block_height();
+    let mut executed = Vec::with_capacity(txs.len());
+    let mut gas_used = 0u64;
+    let mut ledger = rewards.map(|r| r.ledger.lock());
+    if let (Some(ledger), Some(r)) = (ledger.as_mut(), rewards) {
+        ledger.enter_epoch(r.epoch);
+    }
+    let tip_account = |f: &FeeContext| match rewards {
+        Some(_) => REWARD_POOL_ACCOUNT.to_string(),
+        None => f.proposer.clone(),
+    };
+    let mut tips = 0u128;
+
+    for (tx, (gas, vm_result, trace)) in txs.iter().zip(vm_results) {
+        let diff = match vm_result {
+            Ok(diff) => diff,
+            Err(reason) => {
+                warn!("[BlockExecution] VM reverted tx {}→{}", tx.sender, tx.receiver);
+                executed.push(rejected(tx, gas, reason, trace));
+                continue;
+            }
+        };
+
+        // Path 0: signer and fee terms, checked before anything moves.
+        let signer = tx.signature.get(..PUBLIC_KEY_LEN).map(key_hash);
+        let authorized = match &signer {
+            Some(h) => state.key_authorized(&tx.sender, h),
+            None => state.account_auth(&tx.sender).is_none(),
+        };
+        if !authorized {
+            executed.push(rejected(tx, gas, "signer key not authorized for sender".into(), trace));
+            continue;
+        }
+        let expected_nonce = state.get_nonce(&tx.sender);
+        if tx.nonce != expected_nonce {
+            executed.push(rejected(tx, gas, format!("nonce {}, expected {}", tx.nonce, expected_nonce), trace));
+            continue;
+        }
+
+        let fee = match fees.map(|f| charge(&f.params, f.base_fee, tx.max_fee, tx.tip)).transpose() {
+            Ok(fee) => fee,
+            Err(reason) => {
+                executed.push(rejected(tx, gas, reason, trace));
+                continue;
+            }
+        };
+        let fee_total = fee.map_or(0, |c| c.total() as u128);
+        // A claim may pay its fee out of the rewards it claims.
+        let reward_tx = RewardTx::parse(&tx.receiver);
+        let claimable = match (&reward_tx, &ledger) {
+            (Some(Ok(RewardTx::Claim)), Some(l)) => l.claimable(&tx.sender),
+            _ => 0,
+        };
+        if fee_total > 0 && state.get_balance(&tx.sender) + claimable < tx.amount as u128 + fee_total {
+            executed.push(rejected(tx, gas, "insufficient funds for fee".into(), trace));
+            continue;
+        }
+
+        // Path 1: native transfer (sender → receiver, exact amount), or the
+        // key change, reward or staking request the transaction carries.
+        let mut touched = vec![tx.sender.clone()];
+        if let Some(change) = KeyChange::parse(&tx.receiver) {
+            if let Err(reason) = apply_key_change(&mut state, tx, signer, change) {
+                executed.push(rejected(tx, gas, reason, trace));
+                continue;
+            }
+        } else if let Some(request) = reward_tx {
+            let result = match ledger.as_deref_mut() {
+                Some(ledger) => apply_reward_tx(&mut state, ledger, tx, request),
+                None => Err("rewards are not enabled".into()),
+            };
+            if let Err(reason) = result {
+                executed.push(rejected(tx, gas, reason, trace));
+                continue;
+            }
+            touched.push(REWARD_POOL_ACCOUNT.to_string());
+        } else if let Some(request) = StakingTx::parse(&tx.receiver) {
+            let result = request.and_then(|request| {
+                staking::apply(&mut state, &tx.sender, tx.amount as u128, request, parent_state_height + 1)
+            });
+            if let Err(reason) = result {
+                executed.push(rejected(tx, gas, reason, trace));
+                continue;
+            }
+            touched.push(STAKING_ACCOUNT.to_string());
+        } else if state.apply_transfer(&tx.sender, &tx.receiver, tx.amount as u128) {
+            touched.push(tx.receiver.clone());
+        } else {
+            warn!("[BlockExecution] insufficient funds: {}→{} amt={}",
+                  tx.sender, tx.receiver, tx.amount);
+            executed.push(rejected(tx, gas, "insufficient funds".into(), trace));
+            continue;
+        }
+        if let (Some(c), Some(f)) = (fee, fees) {
+            let sender_balance = state.get_balance(&tx.sender);
+            state.set_balance(&tx.sender, sender_balance - fee_total);
+            tips += c.tip as u128;
+            for (account, credit) in [(tip_account(f).as_str(), c.tip), (TREASURY_ACCOUNT, c.treasury)] {
+                if credit > 0 {
+                    let balance = state.get_balance(account);
+                    state.set_balance(account, balance + credit as u128);
+                    touched.push(account.to_string());
+                }
+            }
+        }
+
+        // Path 2: apply any additional VM-produced balance deltas.
+        // These represent contract-side effects (e.g. gas refunds,
+        // token mints, fee distributions) beyond the transfer itself.
+        // We skip the sender/receiver pair to avoid double-counting.
+        for (acct_bytes, update) in &diff.balances {
+            let addr = hex::encode(acct_bytes);
+            if addr == tx.sender || addr == tx.receiver {
+                continue;
+            }
+            let current = state.get_balance(&addr);
+            let new_bal = if update.delta >= 0 {
+                current.saturating_add(update.delta as u128)
+            } else {
+                current.saturating_sub(update.delta.unsigned_abs())
+            };
+            state.set_balance(&addr, new_bal);
+            touched.push(addr);
+        }
+
+        // Path 3: apply nonce updates from VM diff.
+        // For accounts modified by smart contracts (not direct transfers),
+        // bump nonces to the VM-reported value by calling increment_nonce
+        // until we reach the target. Skip sender (already bumped by apply_transfer).
+        for (acct_bytes, nonce_update) in &diff.nonces {
+            let addr = hex::encode(acct_bytes);
+            if addr == tx.sender {
+                continue;
+            }
+            let mut current = state.get_nonce(&addr);
+            while current < nonce_update.new_nonce {
+                current = state.increment_nonce(&addr);
+            }
+            touched.push(addr);
+        }
+
+        let accounts = touched.into_iter().map(|addr| {
+            let snap = AccountSnapshot { balance: state.get_balance(&addr), nonce: state.get_nonce(&addr) };
+            (addr, snap)
+        }).collect();
+        let storage_keys = diff.storage.keys()
+            .map(|(contract, key)| format!("{}:{}", hex::encode(contract), hex::encode(key)))
+            .collect();
+
+        gas_used += gas;
+        executed.push(TxExecution {
+            receipt: TxReceipt {
+                tx_hash: tx_hash(tx),
+                success: true,
+                gas_used: gas,
+                accounts,
+                storage_keys,
+                failure: None,
+                fee,
+            },
+            trace,
+        });
+    }
+
+    let applied = executed.iter().any(|t| t.receipt.success);
+    if let (Some(ledger), Some(r), true) = (ledger.as_mut(), rewards, applied) {
+        let issued = ledger.issuance(r.epoch) as u128;
+        let issued = match state.mint(REWARD_POOL_ACCOUNT, issued) {
+            Ok(_) => issued,
+            Err(e) => {
+                warn!("[BlockExecution] block issuance not minted: {}", e);
+                0
+            }
+        };
+        ledger.record_block(r.epoch, r.proposer, r.voters, issued, tips);
+    }
+    if applied {
+        state.advance_block();
+    }
+    BlockExecution {
+        txs: executed,
+        gas_used,
+        state_root: state.state_root(),
+        parent_state_height,
+        fees: fees.cloned(),
+    }
+}
+
+/// Install the key `change` names for the sender, after checking the
+/// recovery co-signature if the account registered a recovery key.
+fn apply_key_change(
+    state:  &mut StateManager,
+    tx:     &Transaction,
+    signer: Option<[u8; 32]>,
+    change: Result<KeyChange, String>,
+) -> Result<(), String> {
+    let change = change?;
+    let signer = signer.ok_or("key change carries no signer key")?;
+    if tx.amount != 0 {
+        return Err("key change must not transfer funds".into());
+    }
+    if let Some(recovery) = state.account_auth(&tx.sender).and_then(|a| a.recovery_key_hash) {
+        let (_, cosig) = split_cosigned(&tx.signature);
+        let cosig = cosig
+            .filter(|c| c.len() > PUBLIC_KEY_LEN)
+            .ok_or("recovery co-signature required")?;
+        let (pk, sig) = cosig.split_at(PUBLIC_KEY_LEN);
+        if key_hash(pk) != recovery || !verify_tx_signature(&tx.signed_payload(), sig, pk) {
+            return Err("recovery co-signature invalid".into());
+        }
+    }
+    state.rotate_key(&tx.sender, signer, change.new_key_hash, change.recovery_key_hash);
+    state.increment_nonce(&tx.sender);
+    Ok(())
+}
+
+/// Carry out a reward request for the sender.
+fn apply_reward_tx(
+    state:   &mut StateManager,
+    ledger:  &mut RewardLedger,
+    tx:      &Transaction,
+    request: Result<RewardTx, String>,
+) -> Result<(), String> {
+    let request = request?;
+    if tx.amount != 0 {
+        return Err("reward transaction must not transfer funds".into());
+    }
+    match request {
+        RewardTx::Claim => {
+            let amount = ledger.claimable(&tx.sender);
+            if amount == 0 {
+                return Err("no rewards to claim".into());
+            }
+            let pool = state.get_balance(REWARD_POOL_ACCOUNT);
+            if pool < amount {
+                return Err(format!("reward pool holds {} but {} is owed", pool, amount));
+            }
+            ledger.claim(&tx.sender);
+            state.set_balance(REWARD_POOL_ACCOUNT, pool - amount);
+            let balance = state.get_balance(&tx.sender);
+            state.set_balance(&tx.sender, balance + amount);
+        }
+        RewardTx::SetCommission { bps } => ledger.set_commission(&tx.sender, bps)?,
+    }
+    state.increment_nonce(&tx.sender);
+    Ok(())
+}
+
+fn rejected(tx: &Transaction, gas: u64, reason: String, trace: Vec<TraceStep>) -> TxExecution {
+    TxExecution {
+        receipt: TxReceipt {
+            tx_hash:      tx_hash(tx),
+            success:      false,
+            gas_used:     gas,
+            accounts:     BTreeMap::new(),
+            storage_keys: Vec::new(),
+            failure:      Some(reason),
+            fee:          None,
+        },
+        trace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tx(from: &str, to: &str, amount: u64, ts: u64, max_fee: u64, tip: u64, nonce: u64) -> Transaction {
+        Transaction { sender: from.into(), receiver: to.into(), amount, timestamp: ts, signature: Vec::new(), max_fee, tip, nonce, chain_id: bleep_core::block::ChainId::NONE }
+    }
+
+    #[tokio::test]
+    async fn base_fee_is_burned_and_split_between_proposer_and_treasury() {
+        let state = PLMutex::new(StateManager::new());
+        {
+            let mut s = state.lock();
+            s.mint("alice", 10_000).unwrap();
+            s.mint("carol", 1_050).unwrap();
+            s.advance_block();
+        }
+        let supply_before = state.lock().total_supply();
+        let fees = FeeContext {
+            base_fee: 1_000,
+            params:   BaseFeeParams { treasury_share_bps: 2_000, ..Default::default() },
+            proposer: "validator-0".into(),
+        };
+        let txs = vec![
+            tx("alice", "bob", 100, 1, 3_000, 50, 0),
+            tx("alice", "bob", 100, 2, 999, 0, 1),     // max fee below base fee
+            tx("carol", "bob", 100, 3, 2_000, 0, 0),   // cannot cover amount + fee
+            tx("alice", "bob", 100, 4, 1_010, 500, 1), // tip capped at 10
+        ];
+        let exec = execute_block_with_fees(&production_executor(false), &state, &txs, Some(&fees)).await;
+
+        let failures: Vec<Option<&str>> = exec.txs.iter().map(|t| t.receipt.failure.as_deref()).collect();
+        assert_eq!(failures[0], None);
+        assert!(failures[1].unwrap().starts_with("max fee 999 below base fee 1000"));
+        assert_eq!(failures[2], Some("insufficient funds for fee"));
+        assert_eq!(failures[3], None);
+
+        let charges = exec.fee_charges();
+        assert_eq!(charges.iter().map(|c| c.tip).collect::<Vec<_>>(), vec![50, 10]);
+        let burned: u128 = charges.iter().map(|c| c.burned as u128).sum();
+
+        let s = state.lock();
+        assert_eq!(s.get_balance("alice"), 10_000 - 200 - 1_050 - 1_010);
+        assert_eq!(s.get_balance("carol"), 1_050);
+        assert_eq!(s.get_balance("validator-0"), 60);
+        assert_eq!(s.get_balance(TREASURY_ACCOUNT), 400);
+        assert_eq!(burned, 1_600);
+        assert_eq!(supply_before - s.total_supply(), burned);
+    }
+
+    #[tokio::test]
+    async fn issuance_plus_fees_minus_burns_reconciles_with_supply() {
+        use crate::rewards::RewardParams;
+
+        let state = PLMutex::new(StateManager::new());
+        state.lock().mint("alice", 100_000).unwrap();
+        let ledger = PLMutex::new(RewardLedger::new(RewardParams {
+            block_issuance: 1_000,
+            decay_epochs:   0,
+            ..Default::default()
+        }).unwrap());
+        let fees = FeeContext { base_fee: 300, params: BaseFeeParams::default(), proposer: "v1".into() };
+        let executor = production_executor(false);
+        let supply_before = state.lock().total_supply();
+
+        let rewards = RewardContext { ledger: &ledger, epoch: 0, proposer: "v1", voters: &[] };
+        let exec = execute_block_with_rewards(&executor, &state, &[
+            tx("alice", "bob", 100, 1, 400, 50, 0),
+            tx("alice", "bob", 100, 2, 400, 100, 1),
+        ], Some(&fees), Some(&rewards)).await;
+        assert_eq!(failures(&exec), [None, None]);
+        assert_eq!(state.lock().get_balance("v1"), 0);
+        assert_eq!(state.lock().get_balance(REWARD_POOL_ACCOUNT), 1_000 + 150);
+
+        // Nothing is claimable until the epoch closes; the claim then pays
+        // its own fee out of the rewards.
+        let claim = tx("v1", &RewardTx::Claim.receiver(), 0, 3, 300, 0, 0);
+        let exec = execute_block_with_rewards(&executor, &state, &[claim.clone()], Some(&fees), Some(&rewards)).await;
+        assert_eq!(failures(&exec), [Some("insufficient funds for fee")]);
+        let next = RewardContext { epoch: 1, ..rewards };
+        let exec = execute_block_with_rewards(&executor, &state, &[claim], Some(&fees), Some(&next)).await;
+        assert_eq!(failures(&exec), [None]);
+
+        let s = state.lock();
+        let l = ledger.lock();
+        assert_eq!(s.get_balance("v1"), 1_150 - 300);
+        assert_eq!(l.claimable("v1"), 0);
+        let totals = l.totals();
+        let burned: u128 = 3 * 300;
+        assert_eq!(totals.issued, 2_000);
+        assert_eq!(s.total_supply(), supply_before + totals.issued - burned);
+        assert_eq!(s.get_balance(REWARD_POOL_ACCOUNT), totals.issued + totals.fees - totals.claimed);
+        assert_eq!(l.owed(), s.get_balance(REWARD_POOL_ACCOUNT));
+    }
+
+    #[tokio::test]
+    async fn a_transaction_applies_only_at_the_senders_nonce() {
+        let state = PLMutex::new(StateManager::new());
+        state.lock().mint("alice", 1_000).unwrap();
+        let executor = production_executor(false);
+
+        let first = tx("alice", "bob", 10, 1, 0, 0, 0);
+        let exec = execute_block(&executor, &state, &[
+            tx("alice", "bob", 10, 2, 0, 0, 1),
+            first.clone(),
+            tx("alice", "bob", 10, 3, 0, 0, 1),
+        ]).await;
+        assert_eq!(failures(&exec), [Some("nonce 1, expected 0"), None, None]);
+
+        let exec = execute_block(&executor, &state, &[first]).await;
+        assert_eq!(failures(&exec), [Some("nonce 0, expected 2")], "replay");
+        let s = state.lock();
+        assert_eq!((s.get_balance("bob"), s.get_nonce("alice")), (20, 2));
+    }
+
+    /// `tx` signed by `pk`; execution only reads the signer from it.
+    fn signed_by(mut tx: Transaction, pk: &[u8]) -> Transaction {
+        tx.signature = pk.to_vec();
+        tx.signature.extend([0u8; 16]);
+        tx
+    }
+
+    fn key_change(from: &str, ts: u64, nonce: u64, new_pk: &[u8], recovery_pk: Option<&[u8]>) -> Transaction {
+        let change = KeyChange { new_key_hash: key_hash(new_pk), recovery_key_hash: recovery_pk.map(key_hash) };
+        tx(from, &change.receiver(), 0, ts, 0, 0, nonce)
+    }
+
+    fn failures(exec: &BlockExecution) -> Vec<Option<&str>> {
+        exec.txs.iter().map(|t| t.receipt.failure.as_deref()).collect()
+    }
+
+    #[tokio::test]
+    async fn old_key_works_only_within_the_grace_period() {
+        let (old_pk, new_pk) = ([1u8; PUBLIC_KEY_LEN], [2u8; PUBLIC_KEY_LEN]);
+        let state = PLMutex::new(StateManager::new());
+        {
+            let mut s = state.lock();
+            s.set_key_grace_blocks(2);
+            s.mint("alice", 1_000).unwrap();
+            s.advance_block();
+        }
+        let executor = production_executor(false);
+
+        let rotate = signed_by(key_change("alice", 1, 0, &new_pk, None), &old_pk);
+        let exec = execute_block(&executor, &state, &[rotate]).await;
+        assert_eq!(failures(&exec), [None]);
+        assert_eq!(state.lock().account_auth("alice").unwrap().key_hash, key_hash(&new_pk));
+
+        // Within the grace period both keys sign.
+        let exec = execute_block(&executor, &state, &[
+            signed_by(tx("alice", "bob", 10, 2, 0, 0, 1), &old_pk),
+            signed_by(tx("alice", "bob", 10, 3, 0, 0, 2), &new_pk),
+        ]).await;
+        assert_eq!(failures(&exec), [None, None]);
+
+        let exec = execute_block(&executor, &state, &[
+            signed_by(tx("alice", "bob", 10, 4, 0, 0, 3), &old_pk),
+            signed_by(tx("alice", "bob", 10, 5, 0, 0, 3), &new_pk),
+            tx("alice", "bob", 10, 6, 0, 0, 4),
+        ]).await;
+        assert_eq!(failures(&exec), [
+            Some("signer key not authorized for sender"),
+            None,
+            Some("signer key not authorized for sender"),
+        ]);
+        assert_eq!(state.lock().get_balance("bob"), 30);
+    }
+
+    #[tokio::test]
+    async fn registered_recovery_key_must_cosign_changes() {
+        use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload};
+
+        let (pk1, pk2, pk3) = ([1u8; PUBLIC_KEY_LEN], [2u8; PUBLIC_KEY_LEN], [3u8; PUBLIC_KEY_LEN]);
+        let (recovery_pk, recovery_sk) = generate_tx_keypair();
+        let state = PLMutex::new(StateManager::new());
+        state.lock().mint("alice", 1_000).unwrap();
+        let executor = production_executor(false);
+
+        let register = signed_by(key_change("alice", 1, 0, &pk2, Some(&recovery_pk)), &pk1);
+        let exec = execute_block(&executor, &state, &[register]).await;
+        assert_eq!(failures(&exec), [None]);
+
+        // A primary signature of the right length, so the co-signature
+        // starts where `split_cosigned` expects it.
+        let primary_len = PUBLIC_KEY_LEN + sign_tx_payload(b"len", &recovery_sk).unwrap().len();
+        let mut rotate = key_change("alice", 2, 1, &pk3, None);
+        rotate.signature = pk2.to_vec();
+        rotate.signature.resize(primary_len, 0);
+        let bare = rotate.clone();
+        rotate.signature.extend(&recovery_pk);
+        rotate.signature.extend(sign_tx_payload(&rotate.signed_payload(), &recovery_sk).unwrap());
+        let mut forged = rotate.clone();
+        forged.timestamp = 3;
+
+        let exec = execute_block(&executor, &state, &[bare, forged, rotate]).await;
+        assert_eq!(failures(&exec), [
+            Some("recovery co-signature required"),
+            Some("recovery co-signature invalid"),
+            None,
+        ]);
+        let auth = state.lock().account_auth("alice").unwrap();
+        assert_eq!(auth.key_hash, key_hash(&pk3));
+        assert_eq!(auth.recovery_key_hash, Some(key_hash(&recovery_pk)));
+    }
+}
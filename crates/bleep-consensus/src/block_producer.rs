@@ -3,33 +3,39 @@
 //! End-to-end block production pipeline:
 //!
 //! ```text
+//! PartitionDetector::may_participate    ← skip the slot in safe mode (optional)
 //! TransactionPool.peek_for_block(MAX_TXS)
+//! EpochValidatorSets::validator_set_at  ← leader of the slot (optional)
 //!   │
-//!   ▼  Convert ZKTransaction → (block::Transaction + VM Intent)
-//! VM Executor.execute(TransferIntent)   ← per-tx intent execution
-//!   │  StateDiff (gas charged, state changes)
-//!   ▼
-//! StateManager.apply_transfer           ← native balance accounting
-//! StateManager.advance_block()          ← flush to RocksDB
+//!   ▼  Convert ZKTransaction → block::Transaction
+//! block_execution::execute_block        ← VM intent execution + native
+//!   │                                     accounting + base fee (optional)
+//!   │                                     + advance_block()
 //!   │
 //!   ▼
 //! Block::with_consensus_and_sharding    ← build block with PoS fields
+//! EvidencePool::select_for_block       ← pending evidence (optional)
 //! block.sign_block(sk_32)              ← deterministic signing
 //!   │
 //!   ▼
 //! Blockchain::add_block(block, pk_32)  ← validate + commit
+//! BlockStore::put(StoredBlock)          ← archive for replay (optional)
+//! EvidencePool::on_block_committed     ← slash for included evidence
+//! EpochValidatorSets::apply_snapshot   ← next set at epoch starts (optional)
 //!   │
 //!   ▼
 //! P2PNode::broadcast(Block, payload)   ← gossip to peers
 //!   │
 //!   ▼
 //! broadcast::Sender<FinalizedBlock>    ← notify scheduler + telemetry
+//! BaseFeeTracker::on_block             ← next base fee from fullness
 //! ```
 
 use std::sync::{Arc, RwLock};
 use std::time::{Duration, Instant};
 use tracing::{error, info, warn};
 
+use bleep_core::base_fee::BaseFeeTracker;
 use bleep_core::block::{Block, Transaction, ConsensusMode};
 use bleep_core::blockchain::Blockchain;
 use bleep_core::transaction_pool::TransactionPool;
@@ -40,11 +46,18 @@ use parking_lot::Mutex as PLMutex;
 use bleep_p2p::p2p_node::P2PNode;
 use bleep_p2p::types::MessageType;
 
-// VM imports for per-transaction intent execution
-use bleep_vm::execution::executor::{Executor, ExecutorConfig};
-use bleep_vm::execution::state_transition::StateDiff;
-use bleep_vm::intent::{Intent, IntentKind, TransferIntent};
-use bleep_vm::types::ChainId;
+// VM executor + shared execution path
+use bleep_vm::execution::executor::Executor;
+use crate::block_execution::{execute_block_with_rewards, production_executor, FeeContext, RewardContext};
+use crate::block_store::{BlockStore, StoredBlock};
+use crate::chain_audit::ChainAuditor;
+use crate::evidence::{Evidence, EvidencePool};
+use crate::partition::PartitionDetector;
+use crate::rewards::RewardLedger;
+use crate::slashing_engine::SlashingEngine;
+use crate::staking::ledger_snapshot;
+use crate::validator_identity::ValidatorRegistry;
+use crate::validator_set::{EpochValidatorSets, MissedSlots};
 
 // Live benchmark instrumentation
 use crate::performance_bench::{PerformanceBenchmark, NUM_SHARDS, BENCHMARK_DURATION_SECS, TARGET_TPS};
@@ -67,10 +80,8 @@ pub struct FinalizedBlock {
 
 pub const MAX_TXS_PER_BLOCK: usize = 4_096;
 pub const BLOCK_INTERVAL_MS: u64   = 3_000;
-const BLOCKS_PER_EPOCH:      u64   = 1_000;
+pub const BLOCKS_PER_EPOCH:  u64   = 1_000;
 const PROTOCOL_VERSION:      u32   = 1;
-/// BLEEP native chain ID for intent routing
-const BLEEP_CHAIN_ID: ChainId      = ChainId::Bleep;
 
 // ── ProducerConfig ────────────────────────────────────────────────────────────
 
@@ -102,6 +113,16 @@ impl Default for ProducerConfig {
     }
 }
 
+// ── ProposerKey ───────────────────────────────────────────────────────────────
+
+/// Identity and SPHINCS+ keypair a block is signed with.
+#[derive(Clone, Debug)]
+pub struct ProposerKey {
+    pub validator_id: String,
+    pub sk:           Vec<u8>,
+    pub pk:           Vec<u8>,
+}
+
 // ── BlockProducer ─────────────────────────────────────────────────────────────
 
 /// Drives the full consensus → VM execution → state commit pipeline.
@@ -116,6 +137,60 @@ pub struct BlockProducer {
     block_tx:   tokio::sync::broadcast::Sender<FinalizedBlock>,
     /// Live TPS benchmark — records wall-clock throughput from real block production.
     bench:      PLMutex<PerformanceBenchmark>,
+    /// Archive of committed blocks + receipts (optional).
+    block_store: Option<Arc<BlockStore>>,
+    /// Evidence inclusion and slashing on commit (optional).
+    evidence:   Option<EvidenceWiring>,
+    /// Slot length; `BLOCK_INTERVAL_MS` unless overridden.
+    interval_ms: u64,
+    /// Round-robin proposers by height; empty = sign with `config`'s key.
+    rotation:   Vec<ProposerKey>,
+    /// Per-block base fee (optional); blocks are fee-less without it.
+    base_fee:   Option<Arc<BaseFeeTracker>>,
+    /// Full committed blocks, for in-process followers (optional).
+    block_feed: Option<tokio::sync::broadcast::Sender<Block>>,
+    /// Partition safe mode; no blocks are proposed while it is active (optional).
+    partition:  Option<Arc<PartitionDetector>>,
+    /// Block rewards; tips go to the proposer directly without it (optional).
+    rewards:    Option<Arc<PLMutex<RewardLedger>>>,
+    /// Background history audit; no blocks are proposed after it found a
+    /// bad recent block (optional).
+    auditor:    Option<Arc<ChainAuditor>>,
+    /// Epoch validator sets choosing each slot's leader (optional).
+    sets:       Option<SetWiring>,
+}
+
+struct SetWiring {
+    sets:     Arc<PLMutex<EpochValidatorSets>>,
+    registry: Arc<PLMutex<ValidatorRegistry>>,
+    /// Keys this producer may sign with.
+    keys:     Arc<PLMutex<Vec<ProposerKey>>>,
+    missed:   Arc<PLMutex<MissedSlots>>,
+}
+
+impl SetWiring {
+    /// Key of the first member, from the leader of `height` on, whose key
+    /// is held, and the members passed over; `None` without a set or key.
+    fn proposer(&self, height: u64) -> Option<(ProposerKey, Vec<String>)> {
+        let sets = self.sets.lock();
+        let members = &sets.validator_set_at(height)?.members;
+        let keys = self.keys.lock();
+        let mut skipped = Vec::new();
+        for i in 0..members.len() as u64 {
+            let member = &members[((height + i) % members.len() as u64) as usize];
+            match keys.iter().find(|k| k.validator_id == member.id) {
+                Some(key) => return Some((key.clone(), skipped)),
+                None => skipped.push(member.id.clone()),
+            }
+        }
+        None
+    }
+}
+
+struct EvidenceWiring {
+    pool:     Arc<PLMutex<EvidencePool>>,
+    registry: Arc<PLMutex<ValidatorRegistry>>,
+    slashing: Arc<PLMutex<SlashingEngine>>,
 }
 
 impl BlockProducer {
@@ -143,16 +218,132 @@ impl BlockProducer {
             ..Default::default()
         };
 
-        let executor = Executor::production(ExecutorConfig::default());
+        let executor = production_executor(false);
         let (block_tx, block_rx) = tokio::sync::broadcast::channel(256);
         let bench = PLMutex::new(PerformanceBenchmark::new(NUM_SHARDS, BENCHMARK_DURATION_SECS, TARGET_TPS));
 
         (
-            Self { blockchain, tx_pool, state, executor, p2p, config, block_tx, bench },
+            Self {
+                blockchain, tx_pool, state, executor, p2p, config, block_tx, bench,
+                block_store: None, evidence: None,
+                interval_ms: BLOCK_INTERVAL_MS, rotation: Vec::new(), base_fee: None, block_feed: None,
+                partition: None, rewards: None, auditor: None, sets: None,
+            },
             block_rx,
         )
     }
 
+    /// Archive every committed block with its receipts, gas and state root.
+    pub fn with_block_store(mut self, store: Arc<BlockStore>) -> Self {
+        self.block_store = Some(store);
+        self
+    }
+
+    /// Include pending misbehaviour evidence in produced blocks and slash
+    /// for it once they commit.
+    pub fn with_evidence(
+        mut self,
+        pool:     Arc<PLMutex<EvidencePool>>,
+        registry: Arc<PLMutex<ValidatorRegistry>>,
+        slashing: Arc<PLMutex<SlashingEngine>>,
+    ) -> Self {
+        self.evidence = Some(EvidenceWiring { pool, registry, slashing });
+        self
+    }
+
+    /// Override the slot length.  Empty slots are skipped, so a short
+    /// interval makes blocks follow transactions almost immediately.
+    pub fn with_block_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.interval_ms = interval_ms.max(1);
+        self
+    }
+
+    /// Sign block `h` with `proposers[h % proposers.len()]` — an in-process
+    /// validator set sharing this producer's pool, chain and state.
+    pub fn with_rotation(mut self, proposers: Vec<ProposerKey>) -> Self {
+        self.rotation = proposers;
+        self
+    }
+
+    /// Charge every included transaction `tracker`'s base fee plus its tip,
+    /// and move the base fee after each block by how full it was.  Attach
+    /// the same tracker to the pool so admission uses the same floor.
+    pub fn with_base_fee(mut self, tracker: Arc<BaseFeeTracker>) -> Self {
+        self.base_fee = Some(tracker);
+        self
+    }
+
+    /// Publish every committed block on `feed` — the in-process
+    /// equivalent of block gossip, used by devnet replicas.
+    pub fn with_block_feed(mut self, feed: tokio::sync::broadcast::Sender<Block>) -> Self {
+        self.block_feed = Some(feed);
+        self
+    }
+
+    /// Stop proposing while `detector` has the node in partition safe mode.
+    pub fn with_partition_detector(mut self, detector: Arc<PartitionDetector>) -> Self {
+        self.partition = Some(detector);
+        self
+    }
+
+    /// Mint each block's issuance and collect tips into the reward pool,
+    /// attributing both to the proposer in `ledger`.
+    pub fn with_rewards(mut self, ledger: Arc<PLMutex<RewardLedger>>) -> Self {
+        self.rewards = Some(ledger);
+        self
+    }
+
+    /// Stop proposing once `auditor` finds a corrupted block in its window.
+    pub fn with_auditor(mut self, auditor: Arc<ChainAuditor>) -> Self {
+        self.auditor = Some(auditor);
+        self
+    }
+
+    /// Let `sets` choose each slot's leader: the member at
+    /// `height % len` of the block's epoch set signs if its key is in
+    /// `keys`, otherwise the next member whose key is, and the leaders
+    /// passed over are recorded in `missed_slots`.  Every committed epoch
+    /// start snapshots `registry` plus the state's bonds for the next set.
+    /// `keys` may grow while the producer runs.
+    pub fn with_validator_sets(
+        mut self,
+        sets:     Arc<PLMutex<EpochValidatorSets>>,
+        registry: Arc<PLMutex<ValidatorRegistry>>,
+        keys:     Arc<PLMutex<Vec<ProposerKey>>>,
+    ) -> Self {
+        self.sets = Some(SetWiring { sets, registry, keys, missed: Arc::new(PLMutex::new(MissedSlots::default())) });
+        self
+    }
+
+    /// Slots led by validators whose key this producer does not hold.
+    pub fn missed_slots(&self) -> Option<Arc<PLMutex<MissedSlots>>> {
+        self.sets.as_ref().map(|w| Arc::clone(&w.missed))
+    }
+
+    /// Proposer of the block at `height`, and the set members whose turn
+    /// it was but whose key is not held.
+    fn proposer(&self, height: u64) -> (ProposerKey, Vec<String>) {
+        if let Some(chosen) = self.sets.as_ref().and_then(|w| w.proposer(height)) {
+            return chosen;
+        }
+        let key = match self.rotation.len() {
+            0 => ProposerKey {
+                validator_id: self.config.validator_id.clone(),
+                sk: self.config.validator_sk.clone(),
+                pk: self.config.validator_pk.clone(),
+            },
+            n => self.rotation[(height % n as u64) as usize].clone(),
+        };
+        (key, Vec::new())
+    }
+
+    fn epoch_of(&self, height: u64) -> u64 {
+        match &self.sets {
+            Some(w) => w.sets.lock().epoch_of(height),
+            None => height / BLOCKS_PER_EPOCH,
+        }
+    }
+
     /// Subscribe an additional receiver to the finalized block channel.
     /// Use this to wire a `GossipBridge` before starting the producer.
     pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<FinalizedBlock> {
@@ -170,10 +361,10 @@ impl BlockProducer {
     /// Run the block production loop forever — call inside `tokio::spawn`.
     pub async fn run(self) {
         info!(
-            "[BlockProducer] Starting — {}ms slots, validator={}",
-            BLOCK_INTERVAL_MS, self.config.validator_id
+            "[BlockProducer] Starting — {}ms slots, validator={}, proposers={}",
+            self.interval_ms, self.config.validator_id, self.rotation.len().max(1)
         );
-        let mut ticker = tokio::time::interval(Duration::from_millis(BLOCK_INTERVAL_MS));
+        let mut ticker = tokio::time::interval(Duration::from_millis(self.interval_ms));
 
         loop {
             ticker.tick().await;
@@ -197,144 +388,77 @@ impl BlockProducer {
         // Wall-clock start — used for live benchmark instrumentation
         let block_start = Instant::now();
 
+        // ── 0: Partition safe mode ────────────────────────────────────────────
+        // A minority-side node must not extend a fork; it resumes once the
+        // detector sees the majority again.
+        if self.partition.as_ref().map_or(false, |p| !p.may_participate()) {
+            return Ok(None);
+        }
+        // Nor extend a chain whose recent history failed its audit.
+        if self.auditor.as_ref().map_or(false, |a| !a.may_produce()) {
+            return Ok(None);
+        }
+
         // ── 1: Drain transaction pool ─────────────────────────────────────────
+        // Transactions a risen base fee has priced out can never be included.
+        if let Some(ref tracker) = self.base_fee {
+            self.tx_pool.evict_below(tracker.current()).await;
+        }
         let pending = self.tx_pool.peek_for_block(self.config.max_txs_per_block).await;
         if pending.is_empty() {
+            if let Some(ref tracker) = self.base_fee {
+                tracker.on_empty_slot();
+            }
             return Ok(None);
         }
-        let tx_count = pending.len();
 
         // ── 2: Read chain tip ─────────────────────────────────────────────────
-        let (next_height, prev_hash) = {
+        let (next_height, prev_hash, chain_id) = {
             let chain = self.blockchain.read()
                 .map_err(|e| format!("blockchain read lock: {}", e))?;
             match chain.latest_block() {
-                Some(tip) => (tip.index + 1, tip.compute_hash()),
+                Some(tip) => (tip.index + 1, tip.compute_hash(), tip.chain_id),
                 None => return Err("Blockchain empty — genesis missing".into()),
             }
         };
 
         // ── 3: Epoch ──────────────────────────────────────────────────────────
-        let epoch_id = next_height / BLOCKS_PER_EPOCH;
+        let epoch_id = self.epoch_of(next_height);
 
         // ── 4: VM execution + state accounting ────────────────────────────────
-        //
-        // Two-phase design: parking_lot::Mutex must NOT be held across .await.
-        //
-        // Phase A (no lock): route every tx through the VM executor.
-        //   Collects (ZKTransaction, gas, accepted) tuples.
-        // Phase B (with lock): apply accepted balance changes to StateManager.
-        let mut vm_results: Vec<(usize, u64, bool, StateDiff)> = Vec::with_capacity(tx_count);
-        // (pending_index, gas, vm_accepted, state_diff)
-
-        for (idx, zt) in pending.iter().enumerate() {
-            let mut from_bytes = [0u8; 32];
-            let mut to_bytes   = [0u8; 32];
-            let from_b = zt.sender.as_bytes();
-            let to_b   = zt.receiver.as_bytes();
-            from_bytes[..from_b.len().min(32)].copy_from_slice(&from_b[..from_b.len().min(32)]);
-            to_bytes[..to_b.len().min(32)].copy_from_slice(&to_b[..to_b.len().min(32)]);
-
-            let intent = Intent::new_unsigned(
-                IntentKind::Transfer(TransferIntent {
-                    from:   from_bytes,
-                    to:     to_bytes,
-                    amount: zt.amount as u128,
-                    memo:   None,
-                }),
-                BLEEP_CHAIN_ID,
-            );
-
-            // Single VM call — collect gas, accepted, and StateDiff together
-            let (gas, accepted, diff) = match self.executor.execute(&intent).await {
-                Ok(o) => {
-                    let gas      = o.bleep_gas;
-                    let accepted = o.success();
-                    let diff     = o.state_diff().clone();
-                    (gas, accepted, diff)
-                }
-                Err(e) => {
-                    warn!("[BlockProducer] VM error tx {}→{}: {:?}",
-                          zt.sender, zt.receiver, e);
-                    (0, false, StateDiff::empty())
-                }
-            };
-            vm_results.push((idx, gas, accepted, diff));
-        }
-
-        // Phase B: acquire state lock (sync), apply accepted txs
-        //
-        // Two accounting paths (both applied for accepted txs):
-        //   1. Native apply_transfer()  — canonical BLEEP balance accounting
-        //   2. VM StateDiff.balances    — EVM/WASM/ZK engine side-effects
-        //      (contract-emitted balance changes beyond the simple transfer)
-        let mut block_txs: Vec<Transaction> = Vec::with_capacity(tx_count);
-        let mut total_gas: u64 = 0;
-        {
-            let mut state = self.state.lock();
-            for (idx, gas, vm_ok, diff) in &vm_results {
-                if !vm_ok {
-                    warn!("[BlockProducer] VM reverted tx {}→{}",
-                          pending[*idx].sender, pending[*idx].receiver);
-                    continue;
-                }
-                let zt = &pending[*idx];
-
-                // Path 1: native transfer (sender → receiver, exact amount)
-                let ok = state.apply_transfer(&zt.sender, &zt.receiver, zt.amount as u128);
-                if !ok {
-                    warn!("[BlockProducer] insufficient funds: {}→{} amt={}",
-                          zt.sender, zt.receiver, zt.amount);
-                    continue;
-                }
-
-                // Path 2: apply any additional VM-produced balance deltas.
-                // These represent contract-side effects (e.g. gas refunds,
-                // token mints, fee distributions) beyond the transfer itself.
-                // We skip the sender/receiver pair to avoid double-counting.
-                for (acct_bytes, update) in &diff.balances {
-                    let addr = hex::encode(acct_bytes);
-                    // Only apply VM diff for accounts that are not the direct
-                    // transfer pair (those are handled by apply_transfer above).
-                    if addr == zt.sender || addr == zt.receiver {
-                        continue;
-                    }
-                    let current = state.get_balance(&addr);
-                    let new_bal = if update.delta >= 0 {
-                        current.saturating_add(update.delta as u128)
-                    } else {
-                        current.saturating_sub(update.delta.unsigned_abs() as u128)
-                    };
-                    state.set_balance(&addr, new_bal);
-                }
-
-                // Path 3: apply nonce updates from VM diff.
-                // For accounts modified by smart contracts (not direct transfers),
-                // bump nonces to the VM-reported value by calling increment_nonce
-                // until we reach the target. Skip sender (already bumped by apply_transfer).
-                for (acct_bytes, nonce_update) in &diff.nonces {
-                    let addr = hex::encode(acct_bytes);
-                    if addr == zt.sender {
-                        continue; // already bumped by apply_transfer above
-                    }
-                    // Bring nonce up to the VM-reported new_nonce
-                    let mut current = state.get_nonce(&addr);
-                    while current < nonce_update.new_nonce {
-                        current = state.increment_nonce(&addr);
-                    }
-                }
-
-                total_gas += gas;
-                block_txs.push(Transaction {
-                    sender:    zt.sender.clone(),
-                    receiver:  zt.receiver.clone(),
-                    amount:    zt.amount,
-                    timestamp: zt.timestamp,
-                    signature: zt.signature.clone(),
-                });
-            }
-            state.advance_block();
-        }
+        // Shared with `bleep-cli debug replay` — see `block_execution`.
+        let txs: Vec<Transaction> = pending.iter().map(|zt| Transaction {
+            sender:    zt.sender.clone(),
+            receiver:  zt.receiver.clone(),
+            amount:    zt.amount,
+            timestamp: zt.timestamp,
+            signature: zt.signature.clone(),
+            max_fee:   zt.max_fee,
+            tip:       zt.tip,
+            nonce:     zt.nonce,
+            chain_id:  zt.chain_id,
+        }).collect();
+        let (proposer, skipped) = self.proposer(next_height);
+        let proposer_id = proposer.validator_id.as_str();
+        let fees = self.base_fee.as_ref().map(|t| FeeContext {
+            base_fee: t.current(),
+            params:   t.params(),
+            proposer: proposer_id.to_string(),
+        });
+        // Blocks carry no vote signatures yet, so the proposer earns the
+        // whole block reward.
+        let rewards = self.rewards.as_deref().map(|ledger| RewardContext {
+            ledger,
+            epoch:    epoch_id,
+            proposer: proposer_id,
+            voters:   &[],
+        });
+        let exec = execute_block_with_rewards(&self.executor, &self.state, &txs, fees.as_ref(), rewards.as_ref()).await;
+        let block_txs: Vec<Transaction> = txs.into_iter()
+            .zip(&exec.txs)
+            .filter(|(_, t)| t.receipt.success)
+            .map(|(tx, _)| tx)
+            .collect();
 
         if block_txs.is_empty() {
             // All txs failed VM validation — drain pool and skip block
@@ -346,8 +470,9 @@ impl BlockProducer {
             return Ok(None);
         }
 
-        // ── 5: Compute state root ─────────────────────────────────────────────
-        let state_root = self.state.lock().state_root();
+        // ── 5: State root ─────────────────────────────────────────────────────
+        let state_root = exec.state_root;
+        let total_gas  = exec.gas_used;
 
         // ── 6: Build block ────────────────────────────────────────────────────
         let mut block = Block::with_consensus_and_sharding(
@@ -361,14 +486,22 @@ impl BlockProducer {
             0,                             // shard_id: main chain
             hex::encode(&state_root),      // shard_state_root = full state root
         );
+        block.chain_id = chain_id;
+        let included: Vec<Evidence> = match &self.evidence {
+            Some(ev) => ev.pool.lock().select_for_block(next_height),
+            None => Vec::new(),
+        };
+        if !included.is_empty() {
+            block.set_evidence(included.iter().map(Evidence::encode).collect());
+        }
+        if let Some(ref f) = fees {
+            block.base_fee = f.base_fee;
+        }
 
         // ── 7: Sign block with real SPHINCS+-SHAKE-256f-simple secret/public key ──
-        if let Err(e) = block.sign_block_with_pk(
-            &self.config.validator_sk,
-            &self.config.validator_pk,
-        ) {
+        if let Err(e) = block.sign_block_with_pk(&proposer.sk, &proposer.pk) {
             warn!("[BlockProducer] sign_block_with_pk failed: {} — stamping validator_id", e);
-            block.validator_signature = self.config.validator_id.as_bytes().to_vec();
+            block.validator_signature = proposer_id.as_bytes().to_vec();
         }
 
         // ── 8: Commit to chain ────────────────────────────────────────────────
@@ -376,7 +509,7 @@ impl BlockProducer {
         let accepted = {
             let mut chain = self.blockchain.write()
                 .map_err(|e| format!("blockchain write lock: {}", e))?;
-            chain.add_block(block.clone(), &self.config.validator_pk)
+            chain.add_block(block.clone(), &proposer.pk)
         };
 
         if !accepted {
@@ -387,6 +520,40 @@ impl BlockProducer {
             return Err(format!("Block {} validation failed", next_height));
         }
 
+        // Archive with its execution results for `debug replay`.
+        if let Some(ref store) = self.block_store {
+            if let Err(e) = store.put(&StoredBlock::new(block.clone(), &exec)) {
+                warn!("[BlockProducer] block store: {}", e);
+            }
+        }
+        if let Err(e) = self.state.lock().set_shard_height(block.shard_id, next_height) {
+            warn!("[BlockProducer] shard height: {}", e);
+        }
+        if let Some(ref ev) = self.evidence {
+            let report = ev.pool.lock().on_block_committed(
+                &block,
+                proposer_id,
+                &included,
+                &mut ev.slashing.lock(),
+                &mut ev.registry.lock(),
+            );
+            for s in &report.slashed {
+                info!("[BlockProducer] Slashed {} {} µBLEEP for {} at height {}",
+                    s.validator_id, s.slash_amount, s.evidence_type, s.block_height);
+            }
+        }
+        if let Some(ref w) = self.sets {
+            for leader in &skipped {
+                warn!("[BlockProducer] Slot {} led by {} signed by {}", next_height, leader, proposer_id);
+                w.missed.lock().record(leader, next_height);
+            }
+            if w.sets.lock().is_snapshot_height(next_height) {
+                let stakes = ledger_snapshot(&w.registry.lock(), &self.state.lock());
+                for event in w.sets.lock().apply_snapshot(epoch_id, &stakes) {
+                    info!("[BlockProducer] Validator set change: {:?}", event);
+                }
+            }
+        }
 
         // ── 9: Gossip to peers ────────────────────────────────────────────────
         // Direct broadcast for low latency; GossipBridge also subscribes to block_tx.
@@ -394,6 +561,9 @@ impl BlockProducer {
             let payload = serde_json::to_vec(&block).unwrap_or_default();
             node.broadcast(MessageType::Block, payload);
         }
+        if let Some(ref feed) = self.block_feed {
+            let _ = feed.send(block.clone());
+        }
 
 
         // ── 10: Drain committed txs from pool ─────────────────────────────────
@@ -414,6 +584,12 @@ impl BlockProducer {
         let prove_time_ms = block_time_ms.saturating_sub(50).max(100);
         self.bench.lock().record_block(block_txs.len(), prove_time_ms, block_time_ms);
 
+        // ── 12: Next base fee ─────────────────────────────────────────────────
+        if let Some(ref tracker) = self.base_fee {
+            let next = tracker.on_block(block_txs.len(), self.config.max_txs_per_block, &exec.fee_charges());
+            info!("[BlockProducer] Base fee {} → {} µBLEEP", block.base_fee, next);
+        }
+
         Ok(Some(FinalizedBlock {
             height:     next_height,
             epoch:      epoch_id,
@@ -443,12 +619,13 @@ pub fn start_block_producer(
             let txs: Vec<Transaction> = pending.iter().map(|zt| Transaction {
                 sender: zt.sender.clone(), receiver: zt.receiver.clone(),
                 amount: zt.amount, timestamp: zt.timestamp, signature: zt.signature.clone(),
+                max_fee: zt.max_fee, tip: zt.tip, nonce: zt.nonce, chain_id: zt.chain_id,
             }).collect();
             if txs.is_empty() { continue; }
-            let (next_height, prev_hash, epoch_id) = {
+            let (next_height, prev_hash, epoch_id, chain_id) = {
                 let chain = blockchain.read().unwrap();
                 match chain.latest_block() {
-                    Some(b) => (b.index + 1, b.compute_hash(), b.index / 1000),
+                    Some(b) => (b.index + 1, b.compute_hash(), b.index / 1000, b.chain_id),
                     None => continue,
                 }
             };
@@ -457,6 +634,7 @@ pub fn start_block_producer(
                 ConsensusMode::PosNormal, config.protocol_version,
                 format!("{:064x}", epoch_id), 0, String::new(),
             );
+            block.chain_id = chain_id;
             if let Err(e) = block.sign_block(&config.validator_sk) {
                 block.validator_signature = config.validator_id.as_bytes().to_vec();
                 warn!("Legacy sign_block: {}", e);
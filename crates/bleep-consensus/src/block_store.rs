@@ -0,0 +1,260 @@
+//! # BlockStore
+//!
+//! Append-only on-disk archive of committed blocks together with the
+//! execution results the producer observed: receipts, receipts root, gas
+//! and post-state root.  This is what `bleep-cli debug replay` re-executes
+//! and compares against.
+//!
+//! Layout: one JSON file per height under the store directory,
+//! `<dir>/<height:020>.json`, so ranges list in height order.
+//!
+//! It is also the `Blockchain`'s `chain_store::BlockStore`: blocks the
+//! chain accepts without executing them here are recorded as adopted, and
+//! a record the producer already wrote for the same block is kept.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use bleep_core::block::Block;
+use bleep_core::chain_store::BlockStore as ChainBlockStore;
+
+use crate::block_execution::{receipts_root, BlockExecution, FeeContext, TxReceipt};
+
+/// A committed block plus its recorded execution results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredBlock {
+    pub block:               Block,
+    pub receipts:            Vec<TxReceipt>,
+    /// Hex `receipts_root(&receipts)` at commit time.
+    pub receipts_root:       String,
+    pub gas_used:            u64,
+    /// Hex state root after the block.
+    pub state_root:          String,
+    /// `StateManager` height the block executed on top of.
+    pub parent_state_height: u64,
+    /// Fee terms the block was charged under; replay re-applies them.
+    #[serde(default)]
+    pub fees:                Option<FeeContext>,
+}
+
+impl StoredBlock {
+    /// Record `block` with the results of executing it.
+    pub fn new(block: Block, execution: &BlockExecution) -> Self {
+        let receipts = execution.receipts();
+        StoredBlock {
+            receipts_root:       hex::encode(receipts_root(&receipts)),
+            receipts,
+            gas_used:            execution.gas_used,
+            state_root:          hex::encode(execution.state_root),
+            parent_state_height: execution.parent_state_height,
+            fees:                execution.fees.clone(),
+            block,
+        }
+    }
+
+    /// Record a block adopted without executing it — a snap-sync
+    /// checkpoint.  It has no receipts; `state_height` is its post-state.
+    pub fn adopted(block: Block, state_height: u64) -> Self {
+        StoredBlock {
+            receipts_root:       hex::encode(receipts_root(&[])),
+            receipts:            Vec::new(),
+            gas_used:            0,
+            state_root:          block.shard_state_root.clone(),
+            parent_state_height: state_height,
+            fees:                None,
+            block,
+        }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.block.index
+    }
+
+    /// `StateManager` height after the block; blocks that applied nothing
+    /// do not advance it.
+    pub fn post_state_height(&self) -> u64 {
+        self.parent_state_height + self.receipts.iter().any(|r| r.success) as u64
+    }
+}
+
+/// Directory-backed block archive.
+pub struct BlockStore {
+    dir: PathBuf,
+}
+
+impl BlockStore {
+    /// Open (creating if necessary) a store rooted at `dir`.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("create block store {}: {}", dir.display(), e))?;
+        Ok(BlockStore { dir })
+    }
+
+    /// Open a store that must already exist, for readers that should not
+    /// leave an empty one behind at a mistyped path.
+    pub fn open_existing<P: AsRef<Path>>(dir: P) -> Result<Self, String> {
+        let dir = dir.as_ref().to_path_buf();
+        if !dir.is_dir() {
+            return Err(format!("no block store at {}", dir.display()));
+        }
+        Ok(BlockStore { dir })
+    }
+
+    fn path(&self, height: u64) -> PathBuf {
+        self.dir.join(format!("{:020}.json", height))
+    }
+
+    pub fn put(&self, stored: &StoredBlock) -> Result<(), String> {
+        let bytes = serde_json::to_vec(stored).map_err(|e| e.to_string())?;
+        let path = self.path(stored.height());
+        // Write-then-rename so a crash never leaves a torn block behind.
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, bytes).map_err(|e| format!("write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, &path).map_err(|e| format!("rename {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, height: u64) -> Result<Option<StoredBlock>, String> {
+        let path = self.path(height);
+        match fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| format!("decode {}: {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Every height with a block file, ascending.
+    pub fn heights(&self) -> Result<Vec<u64>, String> {
+        let entries = fs::read_dir(&self.dir)
+            .map_err(|e| format!("list {}: {}", self.dir.display(), e))?;
+        let mut heights = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| e.to_string())?;
+            let name = entry.file_name();
+            if let Some(h) = name.to_str()
+                .and_then(|n| n.strip_suffix(".json"))
+                .and_then(|n| n.parse::<u64>().ok())
+            {
+                heights.push(h);
+            }
+        }
+        heights.sort_unstable();
+        Ok(heights)
+    }
+
+    /// Highest stored height, if any.
+    pub fn tip(&self) -> Result<Option<u64>, String> {
+        Ok(self.heights()?.last().copied())
+    }
+
+    /// Delete every block above `height`; returns the removed heights.
+    pub fn truncate_above(&self, height: u64) -> Result<Vec<u64>, String> {
+        let removed: Vec<u64> = self.heights()?.into_iter().filter(|h| *h > height).collect();
+        for h in &removed {
+            let path = self.path(*h);
+            fs::remove_file(&path).map_err(|e| format!("remove {}: {}", path.display(), e))?;
+        }
+        Ok(removed)
+    }
+
+    /// Blocks `from..=to`; errors if any height is missing.
+    pub fn range(&self, from: u64, to: u64) -> Result<Vec<StoredBlock>, String> {
+        (from..=to)
+            .map(|h| self.get(h)?.ok_or_else(|| format!("block {} not in store", h)))
+            .collect()
+    }
+}
+
+impl ChainBlockStore for BlockStore {
+    fn put_blocks(&self, blocks: &[Block]) -> Result<(), String> {
+        let (Some(first), Some(last)) = (blocks.first(), blocks.last()) else { return Ok(()) };
+        if blocks.windows(2).any(|w| w[0].index.checked_add(1) != Some(w[1].index)) {
+            return Err(format!("blocks {}..={} are not consecutive", first.index, last.index));
+        }
+        let mut state_height = match first.index.checked_sub(1) {
+            Some(parent) => self.get(parent)?.map(|p| p.post_state_height()).unwrap_or(0),
+            None => 0,
+        };
+        let mut replaced = false;
+        for block in blocks {
+            let existing = if replaced { None } else { self.get(block.index)? };
+            match existing {
+                Some(stored) if stored.block.compute_hash() == block.compute_hash() => {
+                    state_height = stored.post_state_height();
+                }
+                _ => {
+                    if !replaced {
+                        self.truncate_above(block.index.saturating_sub(1))?;
+                        replaced = true;
+                    }
+                    self.put(&StoredBlock::adopted(block.clone(), state_height))?;
+                }
+            }
+        }
+        self.truncate_above(last.index)?;
+        Ok(())
+    }
+
+    fn delete_tip(&self) -> Result<Option<Block>, String> {
+        let Some(tip) = self.tip()? else { return Ok(None) };
+        let removed = self.get(tip)?.map(|stored| stored.block);
+        self.truncate_above(tip.saturating_sub(1))?;
+        Ok(removed)
+    }
+
+    fn get_block_by_index(&self, index: u64) -> Result<Option<Block>, String> {
+        Ok(self.get(index)?.map(|stored| stored.block))
+    }
+
+    fn get_block_by_hash(&self, hash: &str) -> Result<Option<Block>, String> {
+        for height in self.heights()?.into_iter().rev() {
+            if let Some(stored) = self.get(height)? {
+                if stored.block.compute_hash() == hash {
+                    return Ok(Some(stored.block));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn latest_index(&self) -> Result<Option<u64>, String> {
+        self.tip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("bleep-block-store-{}-{}-{}", tag, std::process::id(), nanos))
+    }
+
+    #[test]
+    fn a_branch_replaces_the_tail_and_keeps_executed_records() {
+        let store = BlockStore::open(temp_dir("branch")).unwrap();
+        let chain: Vec<Block> = (1..5).map(|i| Block::new(i, vec![], format!("{:064x}", i))).collect();
+        store.put_blocks(&chain).unwrap();
+        let mut executed = StoredBlock::adopted(chain[0].clone(), 2);
+        executed.gas_used = 21_000;
+        store.put(&executed).unwrap();
+
+        // Re-putting block 1 leaves the producer's record alone
+        let branch: Vec<Block> = (2..5).map(|i| Block::new(i, vec![], format!("{:064x}", i + 100))).collect();
+        store.put_blocks(&[&chain[..1], &branch[..]].concat()).unwrap();
+        assert_eq!(store.get(1).unwrap().unwrap().gas_used, 21_000);
+        assert_eq!(store.get(2).unwrap().unwrap().parent_state_height, 2);
+        assert_eq!(store.latest_index().unwrap(), Some(4));
+        assert!(store.get_block_by_hash(&chain[3].compute_hash()).unwrap().is_none());
+        assert_eq!(store.get_block_by_hash(&branch[2].compute_hash()).unwrap().map(|b| b.index), Some(4));
+
+        assert!(store.put_blocks(&[chain[0].clone(), chain[2].clone()]).is_err());
+        assert_eq!(store.delete_tip().unwrap().map(|b| b.compute_hash()), Some(branch[2].compute_hash()));
+        assert_eq!(store.latest_index().unwrap(), Some(3));
+    }
+}
@@ -0,0 +1,332 @@
+//! # Block interval targeting
+//!
+//! Keeps the chain near `target_block_interval` regardless of validator
+//! count or load.
+//!
+//! **PoW** — difficulty is the expected number of hashes per block
+//! (`bleep_core::block::pow_target_met`).  Every `retarget_window` blocks
+//! the median of the last window's block intervals is compared with
+//! `pow_target_interval` and the difficulty scaled by `target / median`,
+//! clamped to `±max_retarget_percent` per retarget.  The median ignores a
+//! few lying timestamps that would move a mean.
+//!
+//! **PoS / PBFT** — time is cut into fixed slots anchored at genesis, so
+//! delays never accumulate:
+//!
+//! ```text
+//!   slot s = [genesis + s·T, genesis + (s+1)·T)      T = target_block_interval
+//!   proposer(s) = validator_set_at(height).leader(s)
+//! ```
+//!
+//! A block must land in a later slot than its parent and be proposed by the
+//! leader of its slot.  A timestamp up to `slot_tolerance` seconds outside
+//! the proposer's slot is accepted to absorb clock skew.  Slots nobody
+//! fills are simply skipped.
+
+use std::sync::Arc;
+
+use bleep_core::block_validation::PowSchedule;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::block_producer::BLOCK_INTERVAL_MS;
+use crate::validator_set::ValidatorSet;
+
+/// Timing parameters; fixed at genesis like `ValidatorSetConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockTimingParams {
+    /// Target seconds between blocks (`T`). Must be > 0.
+    pub target_block_interval: u64,
+    /// Unix timestamp (seconds) at which slot 0 starts.
+    pub genesis_timestamp:     u64,
+    /// PoW: target seconds between blocks.
+    pub pow_target_interval:   u64,
+    /// PoW: blocks per retarget window (`W`).
+    pub retarget_window:       u64,
+    /// PoW: largest difficulty change per retarget, in percent.
+    pub max_retarget_percent:  u64,
+    /// PoW: difficulty floor, in expected hashes per block.
+    pub min_pow_difficulty:    u64,
+    /// PoW: difficulty ceiling, in expected hashes per block.
+    pub max_pow_difficulty:    u64,
+    /// PoS/PBFT: seconds a timestamp may sit outside its proposer's slot.
+    pub slot_tolerance:        u64,
+}
+
+impl Default for BlockTimingParams {
+    fn default() -> Self {
+        Self {
+            target_block_interval: BLOCK_INTERVAL_MS / 1_000,
+            genesis_timestamp:     0,
+            pow_target_interval:   10,
+            retarget_window:       20,
+            max_retarget_percent:  25,
+            min_pow_difficulty:    256,
+            max_pow_difficulty:    u64::MAX,
+            slot_tolerance:        1,
+        }
+    }
+}
+
+/// New PoW difficulty from the timestamps of the last `W` blocks, oldest
+/// first.  Fewer than two timestamps leave it unchanged.
+pub fn retarget_pow_difficulty(current: u64, timestamps: &[u64], params: &BlockTimingParams) -> u64 {
+    if timestamps.len() < 2 {
+        return current;
+    }
+    let mut intervals: Vec<u64> = timestamps.windows(2)
+        .map(|w| w[1].saturating_sub(w[0]))
+        .collect();
+    intervals.sort_unstable();
+    let mid = intervals.len() / 2;
+    let median = if intervals.len() % 2 == 0 {
+        (intervals[mid - 1] + intervals[mid]) / 2
+    } else {
+        intervals[mid]
+    };
+    // Timestamps are only non-decreasing; a zero median counts as one second.
+    let median = median.max(1) as u128;
+
+    let current_wide = current.max(1) as u128;
+    let scaled = current_wide * params.pow_target_interval.max(1) as u128 / median;
+    let pct = params.max_retarget_percent.min(100) as u128;
+    let lowest = current_wide * (100 - pct) / 100;
+    let highest = current_wide * (100 + pct) / 100;
+    let next = scaled.clamp(lowest, highest).min(u64::MAX as u128) as u64;
+    next.clamp(params.min_pow_difficulty, params.max_pow_difficulty)
+}
+
+/// PoW difficulty before any block has been mined.
+pub const INITIAL_POW_DIFFICULTY: u64 = 1 << 16;
+
+/// The retarget rule as the chain's `PowSchedule`, so block acceptance and
+/// fork choice demand the difficulty consensus mines at.
+pub fn pow_schedule(params: BlockTimingParams) -> PowSchedule {
+    PowSchedule {
+        initial:  INITIAL_POW_DIFFICULTY,
+        window:   params.retarget_window,
+        retarget: Arc::new(move |current: u64, timestamps: &[u64]| retarget_pow_difficulty(current, timestamps, &params)),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SlotError {
+    #[error("Timestamp {0} is before genesis")]
+    BeforeGenesis(u64),
+    #[error("Block in slot {slot} does not advance past parent slot {parent_slot}")]
+    SlotNotAdvanced { parent_slot: u64, slot: u64 },
+    #[error("Validator set for the block is empty")]
+    NoProposer,
+    #[error("{actual} is not the proposer for slot {slot} (expected {expected})")]
+    WrongProposer { slot: u64, expected: String, actual: String },
+}
+
+/// Genesis-anchored slot clock for PoS/PBFT proposals.
+#[derive(Debug, Clone, Copy)]
+pub struct SlotSchedule {
+    params: BlockTimingParams,
+}
+
+impl SlotSchedule {
+    pub fn new(params: BlockTimingParams) -> Self {
+        Self { params }
+    }
+
+    pub fn params(&self) -> &BlockTimingParams {
+        &self.params
+    }
+
+    fn interval(&self) -> u64 {
+        self.params.target_block_interval.max(1)
+    }
+
+    /// Slot containing `timestamp`, or `None` before genesis.
+    pub fn slot_at(&self, timestamp: u64) -> Option<u64> {
+        timestamp
+            .checked_sub(self.params.genesis_timestamp)
+            .map(|elapsed| elapsed / self.interval())
+    }
+
+    /// `[start, end)` of `slot`, in seconds.
+    pub fn slot_bounds(&self, slot: u64) -> (u64, u64) {
+        let start = self.params.genesis_timestamp + slot * self.interval();
+        (start, start + self.interval())
+    }
+
+    /// The one validator expected to propose in `slot`.
+    pub fn proposer<'a>(&self, slot: u64, set: &'a ValidatorSet) -> Option<&'a str> {
+        set.leader(slot).map(|m| m.id.as_str())
+    }
+
+    /// Check a proposal by `proposer` at `timestamp` whose parent was
+    /// produced at `parent_timestamp`.  `set` is the validator set for the
+    /// block's height.  Returns the slot the proposal is accepted for.
+    pub fn check_proposal(
+        &self,
+        set: &ValidatorSet,
+        proposer: &str,
+        parent_timestamp: u64,
+        timestamp: u64,
+    ) -> Result<u64, SlotError> {
+        let slot = self.slot_at(timestamp).ok_or(SlotError::BeforeGenesis(timestamp))?;
+        // The genesis parent may predate the slot clock; it occupies no slot.
+        let parent_slot = self.slot_at(parent_timestamp);
+        let after_parent = |s: u64| match parent_slot {
+            Some(p) => s > p,
+            None => true,
+        };
+
+        let tol = self.params.slot_tolerance;
+        let candidates = [
+            Some(slot),
+            self.slot_at(timestamp.saturating_sub(tol)),
+            self.slot_at(timestamp.saturating_add(tol)),
+        ];
+        for s in candidates.into_iter().flatten() {
+            if after_parent(s) && self.proposer(s, set) == Some(proposer) {
+                return Ok(s);
+            }
+        }
+
+        if !after_parent(slot) {
+            return Err(SlotError::SlotNotAdvanced { parent_slot: parent_slot.unwrap_or(0), slot });
+        }
+        let expected = self.proposer(slot, set).ok_or(SlotError::NoProposer)?;
+        Err(SlotError::WrongProposer { slot, expected: expected.to_string(), actual: proposer.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator_set::SetMember;
+
+    fn params() -> BlockTimingParams {
+        BlockTimingParams {
+            target_block_interval: 3,
+            genesis_timestamp:     1_000,
+            pow_target_interval:   10,
+            retarget_window:       10,
+            max_retarget_percent:  25,
+            min_pow_difficulty:    256,
+            max_pow_difficulty:    u64::MAX,
+            slot_tolerance:        1,
+        }
+    }
+
+    fn set(ids: &[&str]) -> ValidatorSet {
+        let members: Vec<SetMember> = ids.iter().map(|id| SetMember { id: id.to_string(), stake: 100 }).collect();
+        ValidatorSet { epoch: 0, total_stake: 100 * members.len() as u128, members }
+    }
+
+    #[test]
+    fn retarget_scales_by_median_interval_within_clamp() {
+        let p = params();
+        let spaced = |gaps: &[u64]| -> Vec<u64> {
+            let mut t = 1_000;
+            std::iter::once(t).chain(gaps.iter().map(|g| { t += g; t })).collect()
+        };
+        // Median 8 s against a 10 s target: +25%, exactly the clamp.
+        assert_eq!(retarget_pow_difficulty(10_000, &spaced(&[8; 9]), &p), 12_500);
+        // Median 9 s: +11%.  One huge gap does not move the median.
+        assert_eq!(retarget_pow_difficulty(9_000, &spaced(&[9, 9, 9, 9, 500, 9, 9, 9, 9]), &p), 10_000);
+        // On target: unchanged.  A burst or a stall moves at most 25%.
+        assert_eq!(retarget_pow_difficulty(10_000, &spaced(&[10; 9]), &p), 10_000);
+        assert_eq!(retarget_pow_difficulty(10_000, &vec![1_000u64; 10], &p), 12_500);
+        assert_eq!(retarget_pow_difficulty(10_000, &spaced(&[60; 9]), &p), 7_500);
+        // Never below the floor.
+        assert_eq!(retarget_pow_difficulty(300, &spaced(&[60; 9]), &p), 256);
+    }
+
+    /// Mine `windows` retarget windows at `hashrate` hashes per second,
+    /// each block taking its expected `difficulty / hashrate` seconds.
+    /// Returns the final difficulty and the last window's median interval.
+    fn simulate(difficulty: &mut u64, clock: &mut f64, hashrate: f64, windows: usize, p: &BlockTimingParams) -> u64 {
+        let mut median = 0;
+        for _ in 0..windows {
+            let mut timestamps = vec![clock.floor() as u64];
+            for _ in 0..p.retarget_window {
+                *clock += *difficulty as f64 / hashrate;
+                timestamps.push(clock.floor() as u64);
+            }
+            let mut gaps: Vec<u64> = timestamps.windows(2).map(|w| w[1] - w[0]).collect();
+            gaps.sort_unstable();
+            median = gaps[gaps.len() / 2];
+            *difficulty = retarget_pow_difficulty(*difficulty, &timestamps, p);
+        }
+        median
+    }
+
+    #[test]
+    fn difficulty_converges_when_hashrate_doubles_or_halves() {
+        let p = params();
+        let mut difficulty = 10_000;
+        let mut clock = 1_000.0;
+
+        // 1000 H/s at difficulty 10k: on the 10 s target.
+        assert!((9..=11).contains(&simulate(&mut difficulty, &mut clock, 1_000.0, 5, &p)));
+        assert!((9_000..=11_000).contains(&difficulty));
+
+        // Hashrate doubles: blocks come every 5 s until difficulty catches up.
+        let median = simulate(&mut difficulty, &mut clock, 2_000.0, 12, &p);
+        assert!((9..=11).contains(&median), "median interval {} s after doubling", median);
+        assert!((17_000..=23_000).contains(&difficulty), "difficulty {} after doubling", difficulty);
+
+        // Hashrate halves from there: 20 s blocks until difficulty falls back.
+        let median = simulate(&mut difficulty, &mut clock, 1_000.0, 12, &p);
+        assert!((9..=11).contains(&median), "median interval {} s after halving", median);
+        assert!((8_500..=11_500).contains(&difficulty), "difficulty {} after halving", difficulty);
+    }
+
+    #[test]
+    fn schedule_carries_the_last_mined_difficulty_and_retargets_at_window_ends() {
+        use bleep_core::block::Block;
+
+        let schedule = pow_schedule(params());
+        let mut chain: Vec<Block> = (0..=25).map(|i| {
+            let mut b = Block::new(i, vec![], "0".into());
+            b.timestamp = 1_000 + 8 * i;
+            b
+        }).collect();
+        let at = |chain: &[Block], height: u64| schedule.expected(height, |h: u64| chain.get(h as usize));
+        assert_eq!(at(&chain, 25), INITIAL_POW_DIFFICULTY, "nothing mined yet");
+
+        chain[7].pow_difficulty = 10_000;
+        assert_eq!(at(&chain, 25), 10_000);
+        // Block 20 closed a window of 8 s blocks: +25%.
+        chain[20].pow_difficulty = 10_000;
+        assert_eq!(at(&chain, 21), 12_500);
+        assert_eq!(at(&chain, 20), 10_000, "block 20 itself is held to the old difficulty");
+    }
+
+    #[test]
+    fn slot_schedule_assigns_proposers_deterministically() {
+        let schedule = SlotSchedule::new(params());
+        let s = set(&["a", "b", "c"]);
+        let order: Vec<&str> = (0..6).map(|slot| schedule.proposer(slot, &s).unwrap()).collect();
+        assert_eq!(order, vec!["a", "b", "c", "a", "b", "c"]);
+        assert_eq!(schedule.slot_at(1_007), Some(2));
+        assert_eq!(schedule.slot_bounds(2), (1_006, 1_009));
+        assert_eq!(schedule.slot_at(999), None);
+    }
+
+    #[test]
+    fn out_of_slot_proposal_rejected() {
+        let schedule = SlotSchedule::new(params());
+        let s = set(&["a", "b", "c"]);
+
+        // Slot 1 (1003..1006) belongs to b.
+        assert_eq!(schedule.check_proposal(&s, "b", 1_000, 1_004), Ok(1));
+        assert!(matches!(
+            schedule.check_proposal(&s, "c", 1_000, 1_003),
+            Err(SlotError::WrongProposer { slot: 1, .. })
+        ));
+        // c's slot starts at 1006; one second early is within tolerance.
+        assert_eq!(schedule.check_proposal(&s, "c", 1_000, 1_005), Ok(2));
+        // Same slot as the parent.
+        assert!(matches!(
+            schedule.check_proposal(&s, "b", 1_003, 1_004),
+            Err(SlotError::SlotNotAdvanced { parent_slot: 1, slot: 1 })
+        ));
+    }
+}
@@ -19,4 +19,15 @@ impl BlockchainState {
             Err("Failed to add block".to_string())
         }
     }
+
+    pub fn latest_block(&self) -> Option<Block> {
+        self.inner.get_latest_block()
+    }
+
+    /// Timestamps of the last `n` blocks, oldest first.
+    pub fn recent_timestamps(&self, n: usize) -> Vec<u64> {
+        let blocks = self.inner.blocks.lock().unwrap();
+        let start = blocks.len().saturating_sub(n);
+        blocks[start..].iter().map(|b| b.timestamp).collect()
+    }
 }
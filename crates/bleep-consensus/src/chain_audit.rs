@@ -0,0 +1,590 @@
+//! # Background chain audit
+//!
+//! `fsck` only runs at startup.  `ChainAuditor` keeps re-validating the
+//! last `window` archived blocks while the node runs, so a corrupted
+//! archive or a bad block from a buggy validator is noticed within one
+//! sweep rather than when something reads it:
+//!
+//!   - the block decodes, sits at its own index and links to its parent
+//!   - the header signature verifies against its embedded key
+//!   - the transaction Merkle root and the receipts root recompute
+//!   - one block in `reexecute_every` is re-executed on a fork of its
+//!     parent state and must reproduce the recorded state and receipts roots
+//!
+//! Each sweep also re-checks `historical_samples` random blocks below the
+//! window — a bit-rot canary for data nothing else reads.
+//!
+//! Checking runs on the blocking pool and is paced so it takes at most
+//! `cpu_budget` of wall time.  A mismatch inside the window halts block
+//! production (`may_produce`) until restart, is broadcast as an
+//! `AuditAlert` and, with `repair`, rolls blocks and state back below the
+//! bad block through `storage_fsck::recover`; the dropped range is then
+//! re-synced.  Historical findings are alerted only.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use log::{error, info, warn};
+use parking_lot::Mutex as PLMutex;
+use rand::seq::SliceRandom;
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use bleep_core::block::Block;
+use bleep_state::state_manager::StateManager;
+
+use crate::block_execution::production_executor;
+use crate::block_store::{BlockStore, StoredBlock};
+use crate::replay::{fork_for_replay, replay_range, ReplayOptions};
+use crate::storage_fsck::{self, FsckIssue, FsckOptions, Recovery};
+
+/// Findings kept in `AuditStatus::findings`.
+pub const MAX_FINDINGS: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct AuditConfig {
+    /// Blocks below and including the tip re-validated every sweep.
+    pub window:             u64,
+    /// Re-execute heights divisible by this; 0 disables re-execution.
+    /// Replay does not re-apply reward issuance, so nodes paying block
+    /// rewards must leave it off or every sampled block would mismatch.
+    pub reexecute_every:    u64,
+    /// Random blocks below the window checked per sweep.
+    pub historical_samples: usize,
+    /// Fraction of wall time the auditor may spend checking, in (0, 1].
+    pub cpu_budget:         f64,
+    /// Pause between sweeps.
+    pub sweep_interval:     Duration,
+    /// Roll back below a bad block in the window.
+    pub repair:             bool,
+    /// Last finalized checkpoint; repair never goes below it.
+    pub checkpoint_height:  u64,
+    /// Where replay forks are built.
+    pub scratch_dir:        PathBuf,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        AuditConfig {
+            window:             128,
+            reexecute_every:    32,
+            historical_samples: 2,
+            cpu_budget:         0.05,
+            sweep_interval:     Duration::from_secs(30),
+            repair:             true,
+            checkpoint_height:  0,
+            scratch_dir:        std::env::temp_dir(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditFinding {
+    pub height:     u64,
+    pub issue:      FsckIssue,
+    /// Found by the bit-rot canary below the window.
+    pub historical: bool,
+    pub at_ms:      u64,
+}
+
+/// Broadcast on every finding.
+#[derive(Debug, Clone)]
+pub struct AuditAlert {
+    pub finding:  AuditFinding,
+    /// What `repair` rolled back, if it ran.
+    pub recovery: Option<Recovery>,
+}
+
+/// What `GET /rpc/admin/audit-status` reports.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AuditStatus {
+    pub window:              u64,
+    pub sweeps:              u64,
+    /// Tip of the newest sweep that found its whole window sound.
+    pub last_audited_height: Option<u64>,
+    /// Window of the current (or last) sweep ...
+    pub window_from:         u64,
+    pub window_to:           u64,
+    /// ... and the fraction of it checked so far.
+    pub coverage:            f64,
+    pub blocks_checked:      u64,
+    pub blocks_reexecuted:   u64,
+    pub historical_checked:  u64,
+    /// Share of wall time spent checking since the auditor started.
+    pub cpu_fraction:        f64,
+    /// Block production is halted after a finding in the window.
+    pub halted:              bool,
+    pub findings_total:      u64,
+    /// Newest last, at most `MAX_FINDINGS`.
+    pub findings:            Vec<AuditFinding>,
+    pub last_recovery:       Option<Recovery>,
+}
+
+struct Progress {
+    status:  AuditStatus,
+    checked: u64,
+    busy:    Duration,
+}
+
+/// Re-validates recent history in the background; shared by the block
+/// producer (`may_produce`) and RPC.
+pub struct ChainAuditor {
+    store:    Arc<BlockStore>,
+    state:    Arc<PLMutex<StateManager>>,
+    config:   AuditConfig,
+    started:  Instant,
+    progress: PLMutex<Progress>,
+    halted:   AtomicBool,
+    alerts:   broadcast::Sender<AuditAlert>,
+}
+
+impl ChainAuditor {
+    pub fn new(store: Arc<BlockStore>, state: Arc<PLMutex<StateManager>>, config: AuditConfig) -> Self {
+        let (alerts, _) = broadcast::channel(64);
+        ChainAuditor {
+            store,
+            state,
+            progress: PLMutex::new(Progress {
+                status:  AuditStatus { window: config.window, ..Default::default() },
+                checked: 0,
+                busy:    Duration::ZERO,
+            }),
+            config,
+            started: Instant::now(),
+            halted:  AtomicBool::new(false),
+            alerts,
+        }
+    }
+
+    pub fn config(&self) -> &AuditConfig {
+        &self.config
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<AuditAlert> {
+        self.alerts.subscribe()
+    }
+
+    /// `false` once a block in the window failed its audit.
+    pub fn may_produce(&self) -> bool {
+        !self.halted.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> AuditStatus {
+        let p = self.progress.lock();
+        let mut status = p.status.clone();
+        let len = (status.window_to + 1).saturating_sub(status.window_from);
+        status.coverage = if len == 0 { 0.0 } else { p.checked as f64 / len as f64 };
+        let running = self.started.elapsed().as_secs_f64();
+        status.cpu_fraction = if running > 0.0 { p.busy.as_secs_f64() / running } else { 0.0 };
+        status.halted = !self.may_produce();
+        status
+    }
+
+    /// Sweep forever, `sweep_interval` apart.
+    pub async fn run(self: Arc<Self>) {
+        info!(
+            "[ChainAuditor] Auditing the last {} blocks every {:?} within {:.0}% CPU",
+            self.config.window, self.config.sweep_interval, self.config.cpu_budget * 100.0
+        );
+        loop {
+            self.sweep().await;
+            tokio::time::sleep(self.config.sweep_interval).await;
+        }
+    }
+
+    /// One pass over the window, then the historical samples.  Stops at
+    /// the first finding in the window.
+    pub async fn sweep(&self) -> Vec<AuditFinding> {
+        let heights = match self.store.heights() {
+            Ok(h) => h,
+            Err(e) => {
+                warn!("[ChainAuditor] list blocks: {}", e);
+                return Vec::new();
+            }
+        };
+        let (Some(&first), Some(&tip)) = (heights.first(), heights.last()) else {
+            return Vec::new();
+        };
+        let low = tip.saturating_sub(self.config.window.saturating_sub(1)).max(first);
+        {
+            let mut p = self.progress.lock();
+            p.status.window_from = low;
+            p.status.window_to = tip;
+            p.checked = 0;
+        }
+
+        let mut findings = Vec::new();
+        for height in low..=tip {
+            let reexecute = self.config.reexecute_every > 0 && height % self.config.reexecute_every == 0;
+            let started = Instant::now();
+            let result = self.audit_block(height, reexecute).await;
+            let busy = started.elapsed();
+            {
+                let mut p = self.progress.lock();
+                p.busy += busy;
+                p.checked += 1;
+                p.status.blocks_checked += 1;
+                p.status.blocks_reexecuted += matches!(result, Ok(true)) as u64;
+            }
+            if let Err(issue) = result {
+                findings.push(self.on_finding(height, issue, false));
+                break;
+            }
+            self.pace(busy).await;
+        }
+
+        for height in self.sample_history(&heights, low) {
+            let started = Instant::now();
+            let store = Arc::clone(&self.store);
+            let result = tokio::task::spawn_blocking(move || check_historical(&store, height))
+                .await
+                .unwrap_or_else(|e| Err(FsckIssue::UnreadableBlock { height, error: e.to_string() }));
+            let busy = started.elapsed();
+            {
+                let mut p = self.progress.lock();
+                p.busy += busy;
+                p.status.historical_checked += 1;
+            }
+            if let Err(issue) = result {
+                findings.push(self.on_finding(height, issue, true));
+            }
+            self.pace(busy).await;
+        }
+
+        let mut p = self.progress.lock();
+        p.status.sweeps += 1;
+        if !findings.iter().any(|f| !f.historical) {
+            p.status.last_audited_height = Some(tip);
+        }
+        findings
+    }
+
+    /// Check one block of the window; `Ok(true)` if it was re-executed.
+    async fn audit_block(&self, height: u64, reexecute: bool) -> Result<bool, FsckIssue> {
+        let store = Arc::clone(&self.store);
+        let stored = tokio::task::spawn_blocking(move || check_stored(&store, height))
+            .await
+            .unwrap_or_else(|e| Err(FsckIssue::UnreadableBlock { height, error: e.to_string() }))?;
+        if !reexecute {
+            return Ok(false);
+        }
+        self.reexecute(&stored).await
+    }
+
+    /// Replay `stored` on a fork of its parent state; `Ok(false)` if that
+    /// state is pruned or the block was adopted by snap sync unexecuted.
+    async fn reexecute(&self, stored: &StoredBlock) -> Result<bool, FsckIssue> {
+        let height = stored.height();
+        // Adopted blocks have transactions but were never executed here.
+        if stored.receipts.is_empty() && !stored.block.transactions.is_empty() {
+            return Ok(false);
+        }
+        let dir = self.config.scratch_dir.join(format!("bleep-audit-{}-{}", std::process::id(), height));
+        let fork = {
+            let state = self.state.lock();
+            let needed = stored.parent_state_height;
+            if needed > state.block_height() {
+                return Err(FsckIssue::StateMissing { height, needed, state_height: state.block_height() });
+            }
+            if needed < state.earliest_queryable_height() {
+                return Ok(false);
+            }
+            fork_for_replay(&state, &self.store, height, &dir)
+        };
+        let fork = match fork {
+            Ok(f) => PLMutex::new(f),
+            Err(e) => {
+                warn!("[ChainAuditor] fork for block {}: {}", height, e);
+                let _ = std::fs::remove_dir_all(&dir);
+                return Ok(false);
+            }
+        };
+        let executor = production_executor(false);
+        let report = replay_range(&self.store, &fork, &executor, height, height, &ReplayOptions::default()).await;
+        drop(fork);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let divergence = match report {
+            Ok(r) => r.first_divergence,
+            Err(error) => return Err(FsckIssue::UnreadableBlock { height, error }),
+        };
+        let Some(d) = divergence else { return Ok(true) };
+        if let Some(m) = d.state_root {
+            return Err(FsckIssue::StateRootMismatch { height, expected: m.expected, actual: m.actual });
+        }
+        if let Some(m) = d.receipts_root {
+            return Err(FsckIssue::ReceiptsRootMismatch { height, expected: m.expected, actual: m.actual });
+        }
+        Ok(true)
+    }
+
+    fn on_finding(&self, height: u64, issue: FsckIssue, historical: bool) -> AuditFinding {
+        let finding = AuditFinding { height, issue, historical, at_ms: now_ms() };
+        error!(
+            "[ChainAuditor] {} block {} failed audit: {:?}",
+            if historical { "Historical" } else { "Recent" }, height, finding.issue
+        );
+
+        let mut recovery = None;
+        if !historical {
+            self.halted.store(true, Ordering::SeqCst);
+            error!("[ChainAuditor] Block production halted until restart");
+            if self.config.repair {
+                match self.recover(height) {
+                    Ok(r) => {
+                        warn!("[ChainAuditor] Rolled back to height {}; re-sync from {:?}",
+                              height.saturating_sub(1), r.resync_from);
+                        recovery = Some(r);
+                    }
+                    Err(e) => error!("[ChainAuditor] Recovery failed: {}", e),
+                }
+            }
+        }
+
+        {
+            let mut p = self.progress.lock();
+            p.status.findings_total += 1;
+            p.status.findings.push(finding.clone());
+            if p.status.findings.len() > MAX_FINDINGS {
+                p.status.findings.remove(0);
+            }
+            if recovery.is_some() {
+                p.status.last_recovery = recovery.clone();
+            }
+        }
+        let _ = self.alerts.send(AuditAlert { finding: finding.clone(), recovery });
+        finding
+    }
+
+    /// `storage_fsck` recovery, never keeping `bad` or anything above it:
+    /// fsck itself does not check signatures or Merkle roots.
+    fn recover(&self, bad: u64) -> Result<Recovery, String> {
+        let opts = FsckOptions {
+            receipts_window:   self.config.window,
+            checkpoint_height: self.config.checkpoint_height,
+            scratch_dir:       self.config.scratch_dir.clone(),
+        };
+        let mut state = self.state.lock();
+        let mut report = storage_fsck::check(&self.store, &mut state, &opts)?;
+        if report.consistent_height >= bad {
+            report.consistent_height = bad.saturating_sub(1);
+            report.consistent_state_height = bad
+                .checked_sub(1)
+                .and_then(|h| self.store.get(h).ok().flatten())
+                .map(|parent| parent.post_state_height());
+        }
+        storage_fsck::recover(&self.store, &mut state, &report, &opts)
+    }
+
+    /// Sleep long enough that `busy` stays within `cpu_budget`.
+    async fn pace(&self, busy: Duration) {
+        let budget = self.config.cpu_budget.clamp(0.001, 1.0);
+        if budget < 1.0 {
+            tokio::time::sleep(busy.mul_f64((1.0 - budget) / budget)).await;
+        }
+    }
+
+    fn sample_history(&self, heights: &[u64], below: u64) -> Vec<u64> {
+        let old: Vec<u64> = heights.iter().copied().filter(|h| *h < below).collect();
+        old.choose_multiple(&mut rand::thread_rng(), self.config.historical_samples)
+            .copied()
+            .collect()
+    }
+}
+
+/// Everything about the block at `height` that needs no state.
+fn check_stored(store: &BlockStore, height: u64) -> Result<StoredBlock, FsckIssue> {
+    let parent_hash = height
+        .checked_sub(1)
+        .and_then(|h| store.get(h).ok().flatten())
+        .map(|parent| parent.block.compute_hash());
+    let stored = storage_fsck::check_block(store, height, parent_hash.as_deref())?;
+    let actual = Block::calculate_merkle_root(&stored.block.transactions);
+    if actual != stored.block.merkle_root {
+        return Err(FsckIssue::TxRootMismatch { height, expected: stored.block.merkle_root.clone(), actual });
+    }
+    if !signature_ok(&stored.block) {
+        return Err(FsckIssue::BadSignature { height });
+    }
+    Ok(stored)
+}
+
+/// `check_stored`, plus the child's link: a flipped byte in a hashed field
+/// of an unsigned block only shows there.
+fn check_historical(store: &BlockStore, height: u64) -> Result<(), FsckIssue> {
+    let stored = check_stored(store, height)?;
+    if let Ok(Some(child)) = store.get(height + 1) {
+        let expected = stored.block.compute_hash();
+        if child.block.previous_hash != expected {
+            return Err(FsckIssue::BrokenHashLink { height: height + 1, expected, actual: child.block.previous_hash });
+        }
+    }
+    Ok(())
+}
+
+/// Blocks without an embedded key (genesis, legacy signatures) carry
+/// nothing to check here.
+fn signature_ok(block: &Block) -> bool {
+    block.signer_public_key().is_none() || block.verify_header_signature()
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_execution::execute_block;
+    use bleep_core::block::{ChainId, Transaction};
+    use bleep_crypto::tx_signer::generate_tx_keypair;
+
+    fn scratch(tag: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("bleep-audit-test-{}-{}-{}", tag, std::process::id(), nanos))
+    }
+
+    /// One validator producing signed, archived blocks.
+    struct TestCluster {
+        state:  Arc<PLMutex<StateManager>>,
+        store:  Arc<BlockStore>,
+        dir:    PathBuf,
+        blocks: Vec<Block>,
+        pk:     Vec<u8>,
+        sk:     Vec<u8>,
+    }
+
+    impl TestCluster {
+        async fn new(tag: &str, height: u64) -> Self {
+            let (pk, sk) = generate_tx_keypair();
+            let dir = scratch(tag);
+            let mut state = StateManager::new();
+            state.mint("alice", 1_000_000).unwrap();
+            state.advance_block();
+            let mut c = TestCluster {
+                state:  Arc::new(PLMutex::new(state)),
+                store:  Arc::new(BlockStore::open(&dir).unwrap()),
+                dir,
+                blocks: vec![Block::new(0, vec![], "0".to_string())],
+                pk,
+                sk,
+            };
+            for _ in 0..height {
+                c.produce().await;
+            }
+            c
+        }
+
+        async fn produce(&mut self) {
+            let height = self.blocks.len() as u64;
+            let txs = vec![Transaction {
+                sender: "alice".into(), receiver: format!("acct-{:03}", height), amount: height, timestamp: height,
+                signature: Vec::new(), max_fee: 0, tip: 0, nonce: height - 1, chain_id: ChainId::NONE,
+            }];
+            let exec = execute_block(&production_executor(false), &self.state, &txs).await;
+            let mut block = Block::new(height, txs, self.blocks.last().unwrap().compute_hash());
+            block.shard_state_root = hex::encode(exec.state_root);
+            block.sign_block_with_pk(&self.sk, &self.pk).unwrap();
+            self.store.put(&StoredBlock::new(block.clone(), &exec)).unwrap();
+            self.blocks.push(block);
+        }
+
+        fn auditor(&self, config: AuditConfig) -> Arc<ChainAuditor> {
+            Arc::new(ChainAuditor::new(Arc::clone(&self.store), Arc::clone(&self.state), config))
+        }
+
+        /// Rewrite the archived file of block `height` with `from` → `to`.
+        fn corrupt(&self, height: u64, from: &str, to: &str) {
+            let path = self.dir.join(format!("{:020}.json", height));
+            let text = std::fs::read_to_string(&path).unwrap();
+            assert!(text.contains(from));
+            std::fs::write(&path, text.replacen(from, to, 1)).unwrap();
+        }
+    }
+
+    fn fast(window: u64) -> AuditConfig {
+        AuditConfig {
+            window,
+            reexecute_every:    4,
+            historical_samples: 0,
+            cpu_budget:         0.5,
+            sweep_interval:     Duration::from_millis(10),
+            ..AuditConfig::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn converges_on_a_healthy_node_within_budget() {
+        let mut cluster = TestCluster::new("healthy", 8).await;
+        let auditor = cluster.auditor(fast(8));
+        let handle = tokio::spawn(Arc::clone(&auditor).run());
+
+        // Production keeps going while the auditor sweeps.
+        for _ in 0..4 {
+            cluster.produce().await;
+        }
+        let deadline = Instant::now() + Duration::from_secs(60);
+        while auditor.status().last_audited_height != Some(12) {
+            assert!(Instant::now() < deadline, "auditor never covered the tip: {:?}", auditor.status());
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        handle.abort();
+        assert!(auditor.sweep().await.is_empty());
+
+        let status = auditor.status();
+        assert_eq!((status.window_from, status.window_to), (5, 12));
+        assert_eq!(status.coverage, 1.0);
+        assert!(status.blocks_reexecuted >= 2, "{:?}", status);
+        assert!(status.findings.is_empty(), "{:?}", status.findings);
+        assert!(status.cpu_fraction <= 0.6, "over budget: {:?}", status);
+        assert!(auditor.may_produce());
+    }
+
+    #[tokio::test]
+    async fn corrupted_recent_block_is_caught_in_one_sweep() {
+        let cluster = TestCluster::new("recent", 6).await;
+        let auditor = cluster.auditor(fast(8));
+        let mut alerts = auditor.subscribe();
+        cluster.corrupt(4, "acct-004", "acct-00X");
+
+        let findings = auditor.sweep().await;
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].height, 4);
+        assert!(matches!(findings[0].issue, FsckIssue::TxRootMismatch { height: 4, .. }));
+        assert!(!auditor.may_produce());
+
+        let alert = alerts.try_recv().unwrap();
+        let recovery = alert.recovery.unwrap();
+        assert_eq!(recovery.truncated_blocks, vec![4, 5, 6]);
+        assert_eq!(recovery.resync_from, Some(4));
+        assert_eq!(cluster.store.tip().unwrap(), Some(3));
+        assert_eq!(auditor.status().last_audited_height, None);
+    }
+
+    #[tokio::test]
+    async fn historical_canary_flags_a_flipped_byte() {
+        let cluster = TestCluster::new("history", 10).await;
+        let config = AuditConfig { historical_samples: 16, ..fast(3) };
+        let auditor = cluster.auditor(config);
+        cluster.corrupt(2, "acct-002", "acct-102");
+
+        let findings = auditor.sweep().await;
+        assert_eq!(findings.len(), 1, "{:?}", findings);
+        assert!(findings[0].historical);
+        assert_eq!(findings[0].height, 2);
+        assert!(matches!(findings[0].issue, FsckIssue::TxRootMismatch { height: 2, .. }));
+        // Old data going bad does not stop the node or roll anything back.
+        assert!(auditor.may_produce());
+        assert_eq!(cluster.store.tip().unwrap(), Some(10));
+        assert_eq!(auditor.status().last_audited_height, Some(10));
+        assert_eq!(auditor.status().historical_checked, 7);
+    }
+}
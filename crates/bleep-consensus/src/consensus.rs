@@ -26,14 +26,15 @@
 //! **Root cause (old code):** `ring::digest::Context::update` accumulates.  The hash
 //! at nonce N included all bytes from nonces 0..N, making PoW unverifiable.
 //!
-//! **Fix:** A fresh `sha2::Sha256` is constructed per iteration over
-//! `block_commitment(32B) || nonce_le8(8B)`.
+//! **Fix:** A fresh hasher is constructed per iteration over
+//! `block_commitment || nonce_le8(8B)` (`bleep_core::block::pow_hash`).
 //!
 //! ### S-04 — MEDIUM: `collect_votes` documented as network integration point
 //!
 //! `eligible_voters()` replaces `collect_votes()` and is documented honestly:
-//! it returns eligible voter IDs from the local registry.  Real vote accumulation
-//! is handled by `PbftConsensusEngine` (the H-02-hardened engine).
+//! it returns eligible voter IDs from the local registry.  Votes themselves are
+//! exchanged over `NetworkingModule` by `pbft_round::PbftRound`, which changes
+//! view when the leader's proposal or a quorum does not arrive in time.
 //!
 //! ### S-05 — MEDIUM: `verify_signature` uses correct peer public key
 //!
@@ -54,12 +55,22 @@ use pqcrypto_traits::sign::{DetachedSignature as _, PublicKey as _, SecretKey as
 use sha2::{Digest, Sha256};
 use bincode;
 
-use bleep_core::block::Block;
+use bleep_core::block::{pow_hash, pow_target_met, Block, ChainId};
+use bleep_core::block_validation::BlockValidator;
 use bleep_core::blockchain::Blockchain;
 use crate::blockchain_state::BlockchainState;
 use crate::networking::NetworkingModule;
 use bleep_crypto::zkp_verification::BLEEPError;
 use crate::ai_adaptive_logic::AIAdaptiveConsensus;
+pub use crate::types::{ConsensusMode, Validator};
+use crate::finality::FinalizyCertificate;
+use crate::validator_set::{EpochTransition, EpochValidatorSets, SetMember, ValidatorSet, ValidatorSetConfig};
+use crate::pbft_round::{PbftRound, PbftTimeouts};
+use crate::equivocation::{DoubleSignEvidence, EquivocationDetector};
+use crate::slashing_engine::SlashingEvent;
+use crate::block_timing::{retarget_pow_difficulty, BlockTimingParams, SlotSchedule, INITIAL_POW_DIFFICULTY};
+use bleep_auth::audit_trail::{AuditAction, AuditOutcome, AuditTrail};
+use bleep_state::ai_recommendations::{now_ms, RecommendationEffect, RecommendationSlot};
 
 // ── SPHINCS+-SHAKE-256-simple constants ───────────────────────────────────────
 
@@ -80,13 +91,6 @@ const REPUTATION_SUSPECT_THRESHOLD: f64 = 0.30;
 
 // ─────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ConsensusMode {
-    PoS,
-    PBFT,
-    PoW,
-}
-
 /// Persistent signing identity for this validator node.
 ///
 /// SAFETY: `sk_bytes` must never be written to disk in plaintext.
@@ -120,24 +124,14 @@ impl ValidatorSigningKey {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Validator {
-    pub id: String,
-    pub reputation: f64,
-    pub latency: u64,
-    pub stake: u64,
-    pub active: bool,
-    pub last_signed_block: u64,
-}
-
 // ─────────────────────────────────────────────────────────────────────────────
 
 pub struct BLEEPAdaptiveConsensus {
     consensus_mode:     ConsensusMode,
     network_reliability: f64,
     validators:         HashMap<String, Validator>,
-    #[allow(dead_code)]
-    pow_difficulty:     usize,
+    /// Expected hashes per PoW block; retargeted from block timestamps.
+    pow_difficulty:     u64,
     networking:         Arc<NetworkingModule>,
     #[allow(dead_code)]
     ai_engine:          Arc<AIAdaptiveConsensus>,
@@ -149,6 +143,43 @@ pub struct BLEEPAdaptiveConsensus {
 
     // S-05 FIX: peer public-key registry for correct verification.
     validator_pubkeys:  HashMap<String, Vec<u8>>,
+
+    /// Epoch-rotated active sets; when attached they replace the static
+    /// `validators` map for quorum, leader selection and certificates.
+    validator_sets:     Option<EpochValidatorSets>,
+    /// Whether this driver decides the epoch sets itself from `validators`
+    /// (`with_epoch_rotation`) rather than having them fed from a registry.
+    epoch_rotation:     bool,
+    /// Highest committed height.
+    tip_height:         u64,
+    /// Epochs entered since the last `drain_epoch_transitions`.
+    epoch_transitions:  Vec<EpochTransition>,
+
+    /// Interval targeting: PoW retarget parameters and, once attached with
+    /// `with_block_timing`, the PoS/PBFT slot schedule.
+    timing:             BlockTimingParams,
+    slot_schedule:      Option<SlotSchedule>,
+
+    /// Operator audit trail for validator key rotations.
+    audit:              Option<AuditTrail>,
+
+    /// Latest AI recommendations, written by background evaluation loops
+    /// and only ever read here — see `with_ai_recommendations`.
+    ai_mode_signals:    Option<RecommendationSlot<ConsensusMode>>,
+    ai_anomalies:       Option<RecommendationSlot<Vec<String>>>,
+
+    /// This node's validator id in PBFT rounds; without it PBFT cannot run.
+    pbft_identity:      Option<String>,
+    pbft_timeouts:      PbftTimeouts,
+
+    /// First signed header per (height, validator), for double-sign detection.
+    equivocations:      EquivocationDetector,
+    /// Slashes applied since the last `drain_slashing_events`.
+    slashing_events:    Vec<SlashingEvent>,
+
+    /// Network this node runs; blocks for any other chain fail
+    /// `verify_signature`.
+    chain_id:           ChainId,
 }
 
 impl BLEEPAdaptiveConsensus {
@@ -170,19 +201,255 @@ impl BLEEPAdaptiveConsensus {
         let state         = BlockchainState::default();
         let genesis_block = Block::new(0, vec![], "0".to_string());
         let blockchain    = Arc::new(RwLock::new(
-            Blockchain::new(genesis_block, state, tx_pool)
+            Blockchain::new(genesis_block, state, tx_pool, None)
+                .expect("an in-memory chain has nothing to resume")
         ));
 
         BLEEPAdaptiveConsensus {
             consensus_mode: ConsensusMode::PoS,
             network_reliability: 0.95,
             validators,
-            pow_difficulty: 4,
+            pow_difficulty: INITIAL_POW_DIFFICULTY,
             networking,
             ai_engine,
             blockchain,
             signing_key,
             validator_pubkeys,
+            validator_sets: None,
+            epoch_rotation: false,
+            tip_height: 0,
+            epoch_transitions: Vec::new(),
+            timing: BlockTimingParams::default(),
+            slot_schedule: None,
+            audit: None,
+            ai_mode_signals: None,
+            ai_anomalies: None,
+            pbft_identity: None,
+            pbft_timeouts: PbftTimeouts::default(),
+            equivocations: EquivocationDetector::new(),
+            slashing_events: Vec::new(),
+            chain_id: ChainId::NONE,
+        }
+    }
+
+    /// Take part in PBFT rounds as `validator_id`, signing with this node's
+    /// key.  Messages go through the PBFT link attached to `networking`.
+    pub fn with_pbft_identity(mut self, validator_id: String) -> Self {
+        self.pbft_identity = Some(validator_id);
+        self
+    }
+
+    pub fn with_pbft_timeouts(mut self, timeouts: PbftTimeouts) -> Self {
+        self.pbft_timeouts = timeouts;
+        self
+    }
+
+    /// Read AI recommendations from `mode_signals` (consensus modes to
+    /// signal for) and `anomalies` (validator ids a model flagged).
+    ///
+    /// Neither is ever awaited: a stale or missing record reads as "no
+    /// signal" / "no flags".  A mode recommendation is only a signal for the
+    /// coordinated mode switch, and anomaly flags are advisory — neither
+    /// changes mode or validator status directly.
+    pub fn with_ai_recommendations(
+        mut self,
+        mode_signals: RecommendationSlot<ConsensusMode>,
+        anomalies:    RecommendationSlot<Vec<String>>,
+    ) -> Self {
+        self.ai_mode_signals = Some(mode_signals);
+        self.ai_anomalies = Some(anomalies);
+        self
+    }
+
+    /// Mode this node should signal for, from a fresh `ModeSignal`
+    /// recommendation that differs from the current mode.
+    pub fn mode_signal(&self, now_ms: u64) -> Option<ConsensusMode> {
+        self.ai_mode_signals.as_ref()
+            .filter(|slot| slot.effect() == RecommendationEffect::ModeSignal)
+            .and_then(|slot| slot.fresh(now_ms))
+            .map(|r| r.value)
+            .filter(|mode| *mode != self.consensus_mode)
+    }
+
+    /// Record validator key registrations and rotations to `trail`.
+    pub fn with_audit_trail(mut self, trail: AuditTrail) -> Self {
+        self.audit = Some(trail);
+        self
+    }
+
+    /// Target `params.target_block_interval`: PoW retargets from block
+    /// timestamps and PoS/PBFT proposers follow the genesis-anchored slot
+    /// schedule.
+    pub fn with_block_timing(mut self, params: BlockTimingParams) -> Self {
+        self.timing = params;
+        self.slot_schedule = Some(SlotSchedule::new(params));
+        self
+    }
+
+    /// Accept only blocks of the network `chain_id`.
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Mode blocks are currently finalized under.
+    pub fn consensus_mode(&self) -> ConsensusMode {
+        self.consensus_mode
+    }
+
+    /// Current PoW difficulty in expected hashes per block.
+    pub fn pow_difficulty(&self) -> u64 {
+        self.pow_difficulty
+    }
+
+    /// Check a peer's PoW block: it must be mined at the current
+    /// difficulty, and its nonce must meet it.
+    pub fn verify_pow_block(&self, block: &Block) -> bool {
+        BlockValidator::validate_pow(block, self.pow_difficulty)
+    }
+
+    /// Check that `proposer_id` owns the slot `block.timestamp` falls in
+    /// (within tolerance) and that the slot is later than the parent's.
+    /// Not enforced until a slot schedule is attached.
+    pub fn check_proposal_slot(
+        &self,
+        block: &Block,
+        proposer_id: &str,
+        parent_timestamp: u64,
+    ) -> Result<(), String> {
+        let schedule = match &self.slot_schedule {
+            Some(s) => s,
+            None => return Ok(()),
+        };
+        let set = self.validator_set_at(block.index)
+            .ok_or_else(|| format!("No validator set for block {}", block.index))?;
+        schedule
+            .check_proposal(set, proposer_id, parent_timestamp, block.timestamp)
+            .map(|_| ())
+            .map_err(|e| format!("Block {} rejected: {}", block.index, e))
+    }
+
+    /// Leader rotation index for `block`: its slot when a schedule is
+    /// attached, otherwise its height.  `None` for pre-genesis timestamps.
+    fn leader_turn(&self, block: &Block) -> Option<u64> {
+        match &self.slot_schedule {
+            Some(schedule) => schedule.slot_at(block.timestamp),
+            None => Some(block.index),
+        }
+    }
+
+    /// Drive membership from epoch validator sets instead of the static map.
+    pub fn with_validator_sets(mut self, sets: EpochValidatorSets) -> Self {
+        self.validator_sets = Some(sets);
+        self
+    }
+
+    /// Rotate the active set every `config.blocks_per_epoch` blocks from
+    /// this driver's own validator records.
+    ///
+    /// The genesis set (epochs 0 and 1) is the current eligible validators.
+    /// At the first block of epoch `e` the set for `e + 1` is recomputed:
+    /// active validators with non-zero stake and voting reputation, top
+    /// `max_validators` by stake, ties broken by a seed — the hash of the
+    /// previous epoch's final block, i.e. the boundary block's
+    /// `previous_hash`.  Nodes with the same history derive the same sets.
+    pub fn with_epoch_rotation(mut self, config: ValidatorSetConfig) -> Result<Self, String> {
+        self.validator_sets = Some(EpochValidatorSets::new(config, &self.rotation_candidates())?);
+        self.epoch_rotation = true;
+        Ok(self)
+    }
+
+    /// `(id, stake)` of every validator eligible for the next set.
+    fn rotation_candidates(&self) -> Vec<(String, u128)> {
+        let mut candidates: Vec<(String, u128)> = self.validators.values()
+            .filter(|v| v.active && v.stake > 0 && v.reputation >= MIN_REPUTATION_FOR_VOTE)
+            .map(|v| (v.id.clone(), v.stake as u128))
+            .collect();
+        candidates.sort();
+        candidates
+    }
+
+    /// Record `block` as committed.  `finalize_block` calls this; blocks
+    /// committed another way (e.g. sync) must be fed here in height order.
+    /// With epoch rotation, a boundary block decides the next epoch's set
+    /// and emits an `EpochTransition` for the epoch it opens.
+    pub fn on_block_committed(&mut self, block: &Block) {
+        self.tip_height = self.tip_height.max(block.index);
+        if !self.epoch_rotation {
+            return;
+        }
+        let candidates = self.rotation_candidates();
+        let sets = match self.validator_sets.as_mut() {
+            Some(sets) => sets,
+            None => return,
+        };
+        if !sets.is_snapshot_height(block.index) {
+            return;
+        }
+        let epoch = sets.epoch_of(block.index);
+        sets.apply_seeded_snapshot(epoch, &candidates, block.previous_hash.as_bytes());
+        if let Some(transition) = sets.transition_into(epoch) {
+            info!(
+                "Entered epoch {}: {} joining, {} leaving",
+                epoch, transition.joining.len(), transition.leaving.len()
+            );
+            self.epoch_transitions.push(transition);
+        }
+    }
+
+    /// Epoch of the highest committed block; 0 without epoch sets.
+    pub fn current_epoch(&self) -> u64 {
+        self.validator_sets.as_ref().map(|s| s.epoch_of(self.tip_height)).unwrap_or(0)
+    }
+
+    /// The set for epoch `epoch`, once decided (one epoch ahead at most).
+    pub fn validators_for_epoch(&self, epoch: u64) -> Option<&ValidatorSet> {
+        self.validator_sets.as_ref()?.set_for_epoch(epoch)
+    }
+
+    /// Epochs entered since the last call, oldest first.
+    pub fn drain_epoch_transitions(&mut self) -> Vec<EpochTransition> {
+        std::mem::take(&mut self.epoch_transitions)
+    }
+
+    /// Whether `validator_id` may sign the block at `height`: a member of
+    /// that epoch's set, or — for blocks still in flight across a
+    /// boundary — of the previous epoch's.  Anyone may sign without
+    /// epoch sets.
+    pub fn is_authorized_signer(&self, height: u64, validator_id: &str) -> bool {
+        let sets = match &self.validator_sets {
+            Some(sets) => sets,
+            None => return true,
+        };
+        let epoch = sets.epoch_of(height);
+        let in_set = |e: u64| sets.set_for_epoch(e).is_some_and(|s| s.contains(validator_id));
+        in_set(epoch) || epoch.checked_sub(1).is_some_and(in_set)
+    }
+
+    /// Mutable access for feeding committed heights (`EpochValidatorSets::on_block`).
+    pub fn validator_sets_mut(&mut self) -> Option<&mut EpochValidatorSets> {
+        self.validator_sets.as_mut()
+    }
+
+    /// The active set governing the block at `height`, if epoch sets are attached.
+    pub fn validator_set_at(&self, height: u64) -> Option<&ValidatorSet> {
+        self.validator_sets.as_ref()?.validator_set_at(height)
+    }
+
+    /// Verify a finality certificate against the validator set of its
+    /// block's epoch.  Without epoch sets only the claimed voting power of
+    /// the known validators is checked.
+    pub fn verify_checkpoint(&self, cert: &FinalizyCertificate) -> Result<(), String> {
+        match &self.validator_sets {
+            Some(sets) => sets.verify_certificate(cert),
+            None => {
+                let total: u128 = self.validators.values().map(|v| v.stake as u128).sum();
+                if cert.meets_quorum(total) {
+                    Ok(())
+                } else {
+                    Err(format!("Certificate for block {} does not meet quorum", cert.block_height))
+                }
+            }
         }
     }
 
@@ -192,15 +459,34 @@ impl BLEEPAdaptiveConsensus {
         validator_id: String,
         pk_bytes: Vec<u8>,
     ) -> Result<(), String> {
-        sphincsshake256fsimple::PublicKey::from_bytes(&pk_bytes)
-            .map_err(|e| format!("Invalid SPHINCS+ pk for '{}': {:?}", validator_id, e))?;
+        let checked = sphincsshake256fsimple::PublicKey::from_bytes(&pk_bytes)
+            .map_err(|e| format!("Invalid SPHINCS+ pk for '{}': {:?}", validator_id, e));
+        if let Some(trail) = &self.audit {
+            let params = serde_json::json!({
+                "validator_id": &validator_id,
+                "pk_sha256":    hex::encode(Sha256::digest(&pk_bytes)),
+                "replaces":     self.validator_pubkeys.contains_key(&validator_id),
+            });
+            let outcome = if checked.is_ok() { AuditOutcome::Success } else { AuditOutcome::Failure };
+            trail.append(&validator_id, AuditAction::ValidatorKeyRotation, &params, outcome);
+        }
+        checked?;
         self.validator_pubkeys.insert(validator_id, pk_bytes);
         Ok(())
     }
 
     // ── Consensus mode switching ──────────────────────────────────────────────
 
-    pub fn switch_consensus_mode(&mut self, network_load: u64, avg_latency: u64) {
+    /// Switch to `mode`, e.g. `AIAdaptiveConsensus::predict_best_consensus`.
+    pub fn switch_consensus_mode(&mut self, mode: ConsensusMode) {
+        if self.consensus_mode != mode {
+            info!("Switching consensus mode {:?} -> {:?}", self.consensus_mode, mode);
+            self.consensus_mode = mode;
+        }
+    }
+
+    /// Switch to the mode suited to the given load (%) and latency (ms).
+    pub fn switch_for_conditions(&mut self, network_load: u64, avg_latency: u64) {
         let network_reliability = if network_load > 90 || avg_latency > 500 {
             0.60
         } else if network_load > 70 || avg_latency > 200 {
@@ -219,10 +505,10 @@ impl BLEEPAdaptiveConsensus {
 
         if self.consensus_mode != predicted_mode {
             info!(
-                "Switching consensus mode to {:?} (load={}%, latency={}ms, reliability={:.2})",
-                predicted_mode, network_load, avg_latency, network_reliability
+                "Conditions: load={}%, latency={}ms, reliability={:.2}",
+                network_load, avg_latency, network_reliability
             );
-            self.consensus_mode     = predicted_mode;
+            self.switch_consensus_mode(predicted_mode);
             self.network_reliability = network_reliability;
         }
     }
@@ -231,14 +517,16 @@ impl BLEEPAdaptiveConsensus {
 
     /// Finalize `block` under the current consensus mode.
     /// On failure, switches mode once and retries.  Never recurses.
-    pub fn finalize_block(
+    pub async fn finalize_block(
         &mut self,
         block: &Block,
         state: &mut BlockchainState,
     ) -> Result<(), BLEEPError> {
-        let success = self.run_consensus(block, state);
+        let success = self.run_consensus(block, state).await;
         if success {
             info!("Block {} finalized using {:?}", block.index, self.consensus_mode);
+            self.retarget_pow(block, state);
+            self.on_block_committed(block);
             return Ok(());
         }
 
@@ -246,10 +534,12 @@ impl BLEEPAdaptiveConsensus {
             "Block {} failed under {:?}; adjusting strategy.",
             block.index, self.consensus_mode
         );
-        self.switch_consensus_mode(50, 40);
+        self.switch_for_conditions(50, 40);
 
-        if self.run_consensus(block, state) {
+        if self.run_consensus(block, state).await {
             info!("Block {} finalized on retry using {:?}", block.index, self.consensus_mode);
+            self.retarget_pow(block, state);
+            self.on_block_committed(block);
             Ok(())
         } else {
             Err(BLEEPError::ConsensusFailed(format!(
@@ -259,17 +549,32 @@ impl BLEEPAdaptiveConsensus {
         }
     }
 
-    fn run_consensus(&mut self, block: &Block, state: &mut BlockchainState) -> bool {
+    async fn run_consensus(&mut self, block: &Block, state: &mut BlockchainState) -> bool {
         match self.consensus_mode {
             ConsensusMode::PoS  => self.pos_algorithm(block, state),
-            ConsensusMode::PBFT => self.pbft_algorithm(block, state),
-            ConsensusMode::PoW  => self.pow_algorithm(block),
+            ConsensusMode::PBFT => self.pbft_algorithm(block, state).await,
+            ConsensusMode::PoW  => self.pow_algorithm(block, state),
         }
     }
 
     // ── PoS ──────────────────────────────────────────────────────────────────
 
     fn pos_algorithm(&self, block: &Block, state: &mut BlockchainState) -> bool {
+        if let Some(set) = self.validator_set_at(block.index) {
+            let turn = match self.leader_turn(block) {
+                Some(t) => t,
+                None => return false,
+            };
+            let leader = match set.leader(turn) {
+                Some(l) => l,
+                None => return false,
+            };
+            if !self.locally_healthy(&leader.id, 0.8) {
+                return false;
+            }
+            return state.add_block(block.clone()).is_ok();
+        }
+
         let mut sorted: Vec<&Validator> = self.validators.values().collect();
         sorted.sort_by(|a, b| {
             b.stake.cmp(&a.stake).then_with(|| a.id.cmp(&b.id))
@@ -291,93 +596,156 @@ impl BLEEPAdaptiveConsensus {
     /// Old code used `ring::digest::Context::update` inside the loop, accumulating
     /// all previous nonce bytes — producing a non-deterministic, unverifiable hash.
     ///
-    /// Fix: `block_commitment = SHA-256(bincode(block))` computed once.
-    /// Each iteration: `SHA-256(block_commitment || nonce_le8)` with a fresh hasher.
-    #[allow(dead_code)]
-    fn pow_algorithm(&mut self, block: &Block) -> bool {
-        let block_bytes = match bincode::serialize(block) {
-            Ok(b)  => b,
-            Err(e) => { warn!("PoW: serialise failed: {}", e); return false; }
-        };
-        let commitment: [u8; 32] = Sha256::digest(&block_bytes).into();
-        let target = "0".repeat(self.pow_difficulty);
+    /// Fix: `block_commitment = Block::pow_commitment()` computed once.
+    /// Each iteration: `pow_hash(block_commitment, nonce)` with a fresh hasher.
+    /// The block is stamped with `pow_difficulty` and the winning nonce, so
+    /// peers can check it with `verify_pow_block`.
+    fn pow_algorithm(&mut self, block: &Block, state: &mut BlockchainState) -> bool {
+        let mut mined = block.clone();
+        mined.pow_difficulty = self.pow_difficulty;
+        let commitment = mined.pow_commitment();
 
         for nonce in 0u64..10_000_000 {
-            let mut h = Sha256::new();
-            h.update(&commitment);
-            h.update(&nonce.to_le_bytes());
-            let hash_hex = hex::encode(h.finalize());
-            if hash_hex.starts_with(&target) {
-                info!("PoW block {}: nonce={}, hash={}", block.index, nonce, &hash_hex[..16]);
-                self.adjust_pow_difficulty();
-                return true;
+            let hash = pow_hash(&commitment, nonce);
+            if pow_target_met(&hash, self.pow_difficulty) {
+                info!("PoW block {}: nonce={}, hash={}", block.index, nonce, &hex::encode(hash)[..16]);
+                mined.pow_nonce = nonce;
+                return state.add_block(mined).is_ok();
             }
         }
         warn!("PoW block {}: max attempts exceeded", block.index);
         false
     }
 
-    #[allow(dead_code)]
-    fn adjust_pow_difficulty(&mut self) {
-        if self.networking.get_network_hashrate() > 500 {
-            self.pow_difficulty += 1;
-        } else if self.pow_difficulty > 2 {
-            self.pow_difficulty -= 1;
+    /// In PoW mode, retarget difficulty at every `retarget_window`
+    /// boundary from the median of the last window's block intervals in
+    /// `state`.
+    fn retarget_pow(&mut self, block: &Block, state: &BlockchainState) {
+        let window = self.timing.retarget_window.max(2);
+        if self.consensus_mode != ConsensusMode::PoW || block.index % window != 0 {
+            return;
+        }
+        let timestamps = state.recent_timestamps(window as usize + 1);
+        let next = retarget_pow_difficulty(self.pow_difficulty, &timestamps, &self.timing);
+        if next != self.pow_difficulty {
+            info!(
+                "PoW difficulty retargeted {} -> {} at block {}",
+                self.pow_difficulty, next, block.index
+            );
+            self.pow_difficulty = next;
         }
-        info!("PoW difficulty adjusted to {}", self.pow_difficulty);
     }
 
     // ── PBFT  ─  S-04 FIX ────────────────────────────────────────────────────
 
-    fn pbft_algorithm(&self, block: &Block, state: &mut BlockchainState) -> bool {
-        let leader = match self.select_pbft_leader() {
-            Some(l) => l,
-            None    => return false,
+    /// Run a PBFT round for `block`'s height with the other validators and
+    /// add the block 2f+1 of them committed — this node's `block` if it
+    /// led the deciding view, otherwise the other leader's.  How fast each
+    /// validator answered feeds its `latency` for the adaptive logic.
+    /// Waiting on the other validators yields to the runtime.
+    async fn pbft_algorithm(&mut self, block: &Block, state: &mut BlockchainState) -> bool {
+        let validator_id = match &self.pbft_identity {
+            Some(id) => id,
+            None => {
+                warn!("PBFT: no validator identity configured for block {}", block.index);
+                return false;
+            }
         };
-        if self.networking.broadcast_proposal(block, &leader.id.clone()).is_err() {
-            warn!("PBFT: broadcast failed for block {}", block.index);
-            return false;
-        }
-
-        // eligible_voters returns the candidate set from the local registry.
-        // Actual quorum enforcement (real network vote counting) is done by
-        // PbftConsensusEngine — this path is the mode-level driver only.
-        let candidates = self.eligible_voters();
-        if !self.has_quorum(&candidates) {
+        let candidates = self.eligible_voters(block.index);
+        if !self.has_quorum(&candidates, block.index) {
             warn!(
                 "PBFT: insufficient eligible voters for block {} ({}/{})",
-                block.index, candidates.len(),
-                (self.validators.len() as f64 * 0.66).ceil() as usize
+                block.index, candidates.len(), self.quorum_size(block.index)
             );
             return false;
         }
-        state.add_block(block.clone()).is_ok()
+        let set = match self.pbft_validator_set(block.index) {
+            Some(set) if set.contains(validator_id) => set,
+            _ => {
+                warn!("PBFT: {} is not an active validator for block {}", validator_id, block.index);
+                return false;
+            }
+        };
+
+        let mut round = PbftRound::new(
+            validator_id.clone(),
+            &self.signing_key.sk_bytes,
+            set,
+            self.validator_pubkeys.clone(),
+            block.index,
+            &self.networking,
+            self.pbft_timeouts,
+        );
+        let outcome = round.run(block).await;
+        let latencies = round.vote_latencies().clone();
+        for (id, ms) in latencies {
+            if let Some(v) = self.validators.get_mut(&id) {
+                v.latency = (v.latency + ms) / 2;
+            }
+        }
+        match outcome {
+            Ok(decided) => state.add_block(decided).is_ok(),
+            Err(e) => {
+                warn!("PBFT: block {} not committed: {}", block.index, e);
+                false
+            }
+        }
     }
 
-    fn select_pbft_leader(&self) -> Option<&Validator> {
-        let mut active: Vec<&Validator> = self.validators.values()
-            .filter(|v| v.active && v.reputation > 0.7)
+    /// Validators PBFT leadership rotates over at `height`: the epoch set
+    /// when attached, otherwise the active static validators by stake then
+    /// id.  View `v` is led by `leader(height + v)`.
+    fn pbft_validator_set(&self, height: u64) -> Option<ValidatorSet> {
+        if let Some(set) = self.validator_set_at(height) {
+            return Some(set.clone());
+        }
+        let mut members: Vec<SetMember> = self.validators.values()
+            .filter(|v| v.active)
+            .map(|v| SetMember { id: v.id.clone(), stake: v.stake as u128 })
             .collect();
-        if active.is_empty() {
-            warn!("No eligible PBFT leaders.");
+        if members.is_empty() {
             return None;
         }
-        active.sort_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.id.cmp(&b.id)));
-        active.into_iter().next()
+        members.sort_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.id.cmp(&b.id)));
+        let total_stake = members.iter().map(|m| m.stake).sum();
+        Some(ValidatorSet { epoch: 0, members, total_stake })
+    }
+
+    /// A validator without a local record is trusted to the epoch set.
+    fn locally_healthy(&self, id: &str, min_reputation: f64) -> bool {
+        self.validators.get(id)
+            .map(|v| v.active && v.reputation > min_reputation)
+            .unwrap_or(true)
     }
 
     /// Return the set of validator IDs eligible to vote (local registry only).
     /// S-04: renamed from `collect_votes`; no longer claims to count network votes.
-    fn eligible_voters(&self) -> HashSet<String> {
+    ///
+    /// With epoch sets attached, only members of the set for `height` vote.
+    fn eligible_voters(&self, height: u64) -> HashSet<String> {
+        if let Some(set) = self.validator_set_at(height) {
+            return set.members.iter()
+                .filter(|m| self.validators.get(&m.id)
+                    .map(|v| v.active && v.reputation >= MIN_REPUTATION_FOR_VOTE)
+                    .unwrap_or(true))
+                .map(|m| m.id.clone())
+                .collect();
+        }
         self.validators.iter()
             .filter(|(_, v)| v.active && v.reputation >= MIN_REPUTATION_FOR_VOTE)
             .map(|(id, _)| id.clone())
             .collect()
     }
 
-    fn has_quorum(&self, votes: &HashSet<String>) -> bool {
-        let required = (self.validators.len() as f64 * 0.66).ceil() as usize;
-        votes.len() >= required
+    fn quorum_size(&self, height: u64) -> usize {
+        match self.validator_set_at(height) {
+            Some(set) => set.quorum_size(),
+            None => (self.validators.len() as f64 * 0.66).ceil() as usize,
+        }
+    }
+
+    fn has_quorum(&self, votes: &HashSet<String>, height: u64) -> bool {
+        votes.len() >= self.quorum_size(height)
     }
 
     // ── Validator monitoring  ─  logic-inversion fix ──────────────────────────
@@ -404,6 +772,15 @@ impl BLEEPAdaptiveConsensus {
                 v.active = false;
             }
         }
+
+        // AI anomaly flags are advisory: logged, never acted on here.
+        if let Some(slot) = &self.ai_anomalies {
+            for id in slot.value_or(Vec::new(), now_ms()) {
+                if self.validators.get(&id).is_some_and(|v| v.active) {
+                    warn!("Validator {} flagged by anomaly model '{}' (advisory only).", id, slot.model());
+                }
+            }
+        }
     }
 
     // ── Block signing  ─  S-01 FIX ───────────────────────────────────────────
@@ -418,11 +795,11 @@ impl BLEEPAdaptiveConsensus {
     ///
     /// This implementation uses `self.signing_key.sk_bytes` (set once at init).
     ///
-    /// **Signed payload:** `SHA-256(bincode(block))` — 32 bytes.
+    /// **Signed payload:** `SHA-256(bincode(block.header()))` — 32 bytes.
+    /// The header commits to the bodies through its Merkle and evidence
+    /// roots, so double-sign evidence can carry headers alone.
     pub fn sign_block(&self, block: &Block, _validator_id: &str) -> Result<Vec<u8>, String> {
-        let block_bytes = bincode::serialize(block)
-            .map_err(|e| format!("Serialise failed for block {}: {}", block.index, e))?;
-        let block_hash: [u8; 32] = Sha256::digest(&block_bytes).into();
+        let block_hash = Self::signing_hash(block)?;
 
         let sk = sphincsshake256fsimple::SecretKey::from_bytes(&self.signing_key.sk_bytes)
             .map_err(|e| format!("Invalid signing key: {:?}", e))?;
@@ -443,8 +820,37 @@ impl BLEEPAdaptiveConsensus {
     /// a freshly generated, unrelated public key — always returning `false`.
     ///
     /// This implementation looks up `validator_id` in `self.validator_pubkeys`
-    /// and uses the stored public key.  Returns `false` on any failure.
+    /// and uses the stored public key.  Returns `false` on any failure,
+    /// including a signer outside `is_authorized_signer` for the block and
+    /// a block of another chain.
     pub fn verify_signature(&self, block: &Block, signature: &[u8], validator_id: &str) -> bool {
+        if block.chain_id != self.chain_id {
+            warn!("verify_signature: block {} is for chain {}, not {}", block.index, block.chain_id, self.chain_id);
+            return false;
+        }
+        if !self.is_authorized_signer(block.index, validator_id) {
+            warn!("verify_signature: '{}' is not in the validator set for block {}", validator_id, block.index);
+            return false;
+        }
+        let block_hash = match Self::signing_hash(block) {
+            Ok(h)  => h,
+            Err(e) => { warn!("verify_signature: {}", e); return false; }
+        };
+        let ok = self.verify_hash_signature(&block_hash, signature, validator_id);
+        if !ok {
+            warn!("verify_signature: FAILED for block {} from '{}'", block.index, validator_id);
+        }
+        ok
+    }
+
+    /// The 32 bytes `sign_block` signs: `SHA-256(bincode(block.header()))`.
+    fn signing_hash(block: &Block) -> Result<[u8; 32], String> {
+        let block_bytes = bincode::serialize(&block.header())
+            .map_err(|e| format!("Serialise failed for block {}: {}", block.index, e))?;
+        Ok(Sha256::digest(&block_bytes).into())
+    }
+
+    fn verify_hash_signature(&self, block_hash: &[u8], signature: &[u8], validator_id: &str) -> bool {
         let pk_bytes = match self.validator_pubkeys.get(validator_id) {
             Some(b) => b,
             None => {
@@ -467,17 +873,95 @@ impl BLEEPAdaptiveConsensus {
             Err(e) => { warn!("verify_signature: malformed sig from '{}': {:?}", validator_id, e); return false; }
         };
 
-        let block_bytes = match bincode::serialize(block) {
-            Ok(b)  => b,
-            Err(e) => { warn!("verify_signature: serialise failed: {}", e); return false; }
-        };
-        let block_hash: [u8; 32] = Sha256::digest(&block_bytes).into();
+        sphincsshake256fsimple::verify_detached_signature(&sig, block_hash, &pk).is_ok()
+    }
 
-        let ok = sphincsshake256fsimple::verify_detached_signature(&sig, &block_hash, &pk).is_ok();
-        if !ok {
-            warn!("verify_signature: FAILED for block {} from '{}'", block.index, validator_id);
+    // ── Equivocation slashing ────────────────────────────────────────────────
+
+    /// Check `signature` by `validator_id` over `block` and remember it.
+    ///
+    /// When the validator already signed a different block at this height
+    /// the offence is slashed locally and the evidence returned, for the
+    /// caller to gossip under `SLASHING_EVIDENCE_TOPIC`.  The node binary
+    /// does not call this yet — see the `equivocation` module docs.
+    pub fn observe_signed_block(
+        &mut self,
+        block: &Block,
+        signature: &[u8],
+        validator_id: &str,
+    ) -> Option<DoubleSignEvidence> {
+        if !self.verify_signature(block, signature, validator_id) {
+            return None;
         }
-        ok
+        let block_hash = hex::encode(Self::signing_hash(block).ok()?);
+        let evidence = self.equivocations.record(validator_id, block, &block_hash, signature)?;
+        warn!("Validator {} double-signed at height {}", validator_id, block.index);
+        if let Err(e) = self.apply_slashing_evidence(&evidence) {
+            warn!("Slashing {} failed: {}", validator_id, e);
+        }
+        Some(evidence)
+    }
+
+    /// Verify double-sign evidence, local or gossiped, and slash the
+    /// validator: its stake goes to zero and it stops voting.
+    ///
+    /// Both headers must be of this chain and the same height, differ in
+    /// signing hash, and carry signatures that verify against the
+    /// validator's registered key over that hash; the validator must have
+    /// been entitled to sign at that height.  Evidence against a validator
+    /// that is already slashed is rejected, so each offence yields one
+    /// `SlashingEvent`.
+    pub fn apply_slashing_evidence(&mut self, evidence: &DoubleSignEvidence) -> Result<SlashingEvent, String> {
+        evidence.to_slashing_evidence().is_well_formed()?;
+        let DoubleSignEvidence { validator_id, header_1, signature_1, header_2, signature_2 } = evidence;
+        let height = evidence.height();
+        if header_2.index != height {
+            return Err(format!("Evidence headers are at heights {} and {}", height, header_2.index));
+        }
+        for header in [header_1, header_2] {
+            if header.chain_id != self.chain_id {
+                return Err(format!("Evidence header is for chain {}, not {}", header.chain_id, self.chain_id));
+            }
+        }
+        if !self.is_authorized_signer(height, validator_id) {
+            return Err(format!("'{}' was not in the validator set at height {}", validator_id, height));
+        }
+        let (hash_1, hash_2) = (Self::signing_hash(header_1)?, Self::signing_hash(header_2)?);
+        if hash_1 == hash_2 {
+            return Err("Evidence headers are identical".to_string());
+        }
+        for (hash, signature) in [(hash_1, signature_1), (hash_2, signature_2)] {
+            if !self.verify_hash_signature(&hash, signature, validator_id) {
+                return Err(format!("Evidence signature by '{}' does not verify", validator_id));
+            }
+        }
+
+        let validator = self.validators.get_mut(validator_id)
+            .ok_or_else(|| format!("Unknown validator '{}'", validator_id))?;
+        if !validator.active && validator.stake == 0 {
+            return Err(format!("Validator '{}' is already slashed", validator_id));
+        }
+        let slash_amount = validator.stake as u128;
+        validator.stake  = 0;
+        validator.active = false;
+        warn!("Slashed validator {} for double-signing at height {}: {} stake", validator_id, height, slash_amount);
+
+        let event = SlashingEvent {
+            evidence_type: "DOUBLE_SIGNING".to_string(),
+            validator_id: validator_id.clone(),
+            block_height: height,
+            slash_amount,
+            processed_at_epoch: self.validator_set_at(height).map(|s| s.epoch).unwrap_or(0),
+            timestamp: now_ms() / 1000,
+        };
+        self.slashing_events.push(event.clone());
+        Ok(event)
+    }
+
+    /// Slashes applied since the last call, for the governance module.
+    /// Nothing outside tests drains these yet.
+    pub fn drain_slashing_events(&mut self) -> Vec<SlashingEvent> {
+        std::mem::take(&mut self.slashing_events)
     }
 
     // ── S-02 FIX: Cryptographic PoS proposer seed ────────────────────────────
@@ -507,6 +991,7 @@ impl BLEEPAdaptiveConsensus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network_metrics::FixedMetrics;
 
     fn make_block(index: u64) -> Block {
         Block::new(index, vec![], format!("prev_{}", index))
@@ -518,10 +1003,33 @@ mod tests {
             pubkeys,
             key,
             Arc::new(NetworkingModule::new()),
-            Arc::new(AIAdaptiveConsensus::new()),
+            Arc::new(AIAdaptiveConsensus::new(HashMap::new(), Arc::new(FixedMetrics::default()))),
         )
     }
 
+    #[test]
+    fn ai_recommendations_never_act_directly() {
+        let modes = RecommendationSlot::new("consensus_mode", RecommendationEffect::ModeSignal)
+            .with_max_age_ms(1_000);
+        let anomalies = RecommendationSlot::new("validator_anomaly", RecommendationEffect::Advisory);
+        let mut c = make_consensus(ValidatorSigningKey::generate(), HashMap::new())
+            .with_ai_recommendations(modes.clone(), anomalies.clone());
+        c.validators.insert("v1".into(), Validator {
+            id: "v1".into(), reputation: 0.9, latency: 10, stake: 1, active: true, last_signed_block: 0,
+        });
+
+        assert_eq!(c.mode_signal(5_000), None);
+        modes.publish(ConsensusMode::PBFT, 5_000);
+        assert_eq!(c.mode_signal(5_500), Some(ConsensusMode::PBFT));
+        assert_eq!(c.consensus_mode, ConsensusMode::PoS);
+        // Stale: back to the default of no signal.
+        assert_eq!(c.mode_signal(6_001), None);
+
+        anomalies.publish(vec!["v1".into()], now_ms());
+        c.monitor_validators();
+        assert!(c.validators["v1"].active);
+    }
+
     // ── S-01: Sign + Verify round-trip ───────────────────────────────────────
 
     #[test]
@@ -539,6 +1047,26 @@ mod tests {
         assert!(c.verify_signature(&block, &sig, &vid), "S-01: round-trip must pass");
     }
 
+    #[test]
+    fn test_verify_fails_for_another_chain() {
+        let key = ValidatorSigningKey::generate();
+        let (pk, sk) = (key.pk_bytes.clone(), key.sk_bytes.clone());
+        let pubkeys: HashMap<String, Vec<u8>> = [("v1".to_string(), pk.clone())].into();
+
+        let chain_1 = make_consensus(key, pubkeys.clone()).with_chain_id(ChainId(1));
+        let chain_2 = make_consensus(ValidatorSigningKey::from_bytes(pk, sk).unwrap(), pubkeys)
+            .with_chain_id(ChainId(2));
+
+        let mut block = make_block(42);
+        block.chain_id = ChainId(1);
+        let sig = chain_1.sign_block(&block, "v1").expect("sign failed");
+        assert!(chain_1.verify_signature(&block, &sig, "v1"));
+        assert!(!chain_2.verify_signature(&block, &sig, "v1"), "signed on chain 1, checked on chain 2");
+
+        block.chain_id = ChainId(2);
+        assert!(!chain_2.verify_signature(&block, &sig, "v1"), "the signature covers the chain id");
+    }
+
     #[test]
     fn test_verify_fails_unknown_validator() {
         let c     = make_consensus(ValidatorSigningKey::generate(), HashMap::new());
@@ -607,20 +1135,28 @@ mod tests {
     #[test]
     fn test_pow_hash_deterministic_per_nonce() {
         let block = make_block(0);
-        let bytes  = bincode::serialize(&block).unwrap();
-        let commit: [u8; 32] = Sha256::digest(&bytes).into();
-
-        let hash_fn = |n: u64| {
-            let mut h = Sha256::new();
-            h.update(&commit);
-            h.update(&n.to_le_bytes());
-            hex::encode(h.finalize())
-        };
+        let commit = block.pow_commitment();
+        let hash_fn = |n: u64| hex::encode(pow_hash(&commit, n));
 
         assert_eq!(hash_fn(0), hash_fn(0), "S-03: same nonce must give same hash");
         assert_ne!(hash_fn(0), hash_fn(1), "S-03: different nonces must give different hashes");
     }
 
+    #[test]
+    fn test_pow_block_checked_against_current_difficulty() {
+        let mut c = make_consensus(ValidatorSigningKey::generate(), HashMap::new());
+        c.pow_difficulty = 16;
+        let mut state = BlockchainState::new();
+        assert!(c.pow_algorithm(&make_block(1), &mut state));
+
+        let mined = state.latest_block().expect("mined block is stored");
+        assert_eq!(mined.pow_difficulty, 16);
+        assert!(c.verify_pow_block(&mined));
+
+        c.pow_difficulty = 32;
+        assert!(!c.verify_pow_block(&mined), "a block mined at a stale difficulty is rejected");
+    }
+
     // ── monitor_validators logic ──────────────────────────────────────────────
 
     #[test]
@@ -640,7 +1176,7 @@ mod tests {
             HashMap::new(),
             ValidatorSigningKey::generate(),
             Arc::new(NetworkingModule::new()),
-            Arc::new(AIAdaptiveConsensus::new()),
+            Arc::new(AIAdaptiveConsensus::new(HashMap::new(), Arc::new(FixedMetrics::default()))),
         );
 
         c.monitor_validators();
@@ -649,6 +1185,21 @@ mod tests {
         assert!(!c.validators["bad"].active,  "low-rep must be deactivated");
     }
 
+    #[test]
+    fn test_switch_follows_ai_prediction() {
+        let mut ai = AIAdaptiveConsensus::new(
+            HashMap::new(),
+            Arc::new(FixedMetrics { load: 95, latency_ms: 250, reliability: 0.3 }),
+        );
+        ai.step();
+        let mut c = make_consensus(ValidatorSigningKey::generate(), HashMap::new());
+        c.switch_consensus_mode(ai.predict_best_consensus());
+        assert_eq!(c.consensus_mode, ConsensusMode::PoW);
+
+        c.switch_for_conditions(20, 10);
+        assert_eq!(c.consensus_mode, ConsensusMode::PoS);
+    }
+
     // ── register_validator_pubkey ─────────────────────────────────────────────
 
     #[test]
@@ -657,4 +1208,237 @@ mod tests {
         let r = c.register_validator_pubkey("v1".into(), vec![0u8; 5]);
         assert!(r.is_err(), "short key must be rejected");
     }
+
+    // ── Epoch validator sets ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_quorum_and_leader_follow_epoch_set() {
+        use crate::validator_set::ValidatorSetConfig;
+
+        let genesis = vec![("a".to_string(), 300), ("b".to_string(), 200), ("c".to_string(), 100)];
+        let config = ValidatorSetConfig { blocks_per_epoch: 10, max_validators: 10, min_stake: 1 };
+        let mut sets = EpochValidatorSets::new(config, &genesis).unwrap();
+        let mut next = genesis.clone();
+        next.push(("d".to_string(), 50));
+        sets.apply_snapshot(1, &next);
+
+        let c = make_consensus(ValidatorSigningKey::generate(), HashMap::new())
+            .with_validator_sets(sets);
+        assert_eq!(c.quorum_size(5), 3);
+        assert_eq!(c.quorum_size(25), 3);
+        assert_eq!(c.eligible_voters(25).len(), 4);
+        let set = c.pbft_validator_set(23).unwrap();
+        assert_eq!(set.leader(23).map(|m| m.id.as_str()), Some("d"));
+        assert_eq!(set.leader(23 + 1).map(|m| m.id.as_str()), Some("a"), "view 1 rotates on");
+
+        let mut cert = FinalizyCertificate::new(5, "h".into(), 0, "PoS".into(), "r".into(), 0, 2).unwrap();
+        for id in ["a", "b"] {
+            cert.add_validator_signature(id.into(), vec![1], 0).unwrap();
+        }
+        assert!(c.verify_checkpoint(&cert).is_ok());
+    }
+
+    // ── PBFT view change ─────────────────────────────────────────────────────
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_pbft_survives_leader_crash_mid_round() {
+        use crate::networking::LocalPbftHub;
+        use crate::view_change::ViewTimeouts;
+
+        const H: u64 = 4;
+        let ids: Vec<String> = (0..4).map(|i| format!("v{}", i)).collect();
+        let keys: Vec<ValidatorSigningKey> = ids.iter().map(|_| ValidatorSigningKey::generate()).collect();
+        let pubkeys: HashMap<String, Vec<u8>> = ids.iter().cloned()
+            .zip(keys.iter().map(|k| k.pk_bytes.clone()))
+            .collect();
+        let validators: HashMap<String, Validator> = ids.iter()
+            .map(|id| (id.clone(), Validator {
+                id: id.clone(), reputation: 0.9, latency: 10, stake: 100, active: true, last_signed_block: 0,
+            }))
+            .collect();
+        let timeouts = PbftTimeouts {
+            proposal:   ViewTimeouts { base_ms: 1_500, max_ms: 20_000 },
+            prepare_ms: 1_500,
+            commit_ms:  1_500,
+            max_views:  4,
+        };
+
+        // v0 leads view 0 at height 4: its proposal reaches v1 only, then it dies.
+        let hub = LocalPbftHub::new();
+        hub.crash_after("v0", 1);
+        let links: Vec<_> = ids.iter().map(|id| hub.join(id)).collect();
+        let nodes: Vec<_> = ids.iter().cloned().zip(keys).zip(links)
+            .map(|((id, key), link)| {
+                let (validators, pubkeys) = (validators.clone(), pubkeys.clone());
+                tokio::spawn(async move {
+                    let networking = NetworkingModule::new();
+                    networking.attach_pbft_link(link);
+                    let mut c = BLEEPAdaptiveConsensus::new(
+                        validators,
+                        pubkeys,
+                        key,
+                        Arc::new(networking),
+                        Arc::new(AIAdaptiveConsensus::new(HashMap::new(), Arc::new(FixedMetrics::default()))),
+                    )
+                    .with_pbft_identity(id.clone())
+                    .with_pbft_timeouts(timeouts);
+                    let mut state = BlockchainState::new();
+                    let candidate = Block::new(H, vec![], format!("proposed-by-{}", id));
+                    c.pbft_algorithm(&candidate, &mut state).await.then(|| state.latest_block().unwrap())
+                })
+            })
+            .collect();
+
+        // The survivors change to view 1, led by v1, and all commit its block.
+        let mut decided: Vec<Block> = Vec::new();
+        for node in nodes.into_iter().skip(1) {
+            decided.push(node.await.unwrap().expect("survivor committed"));
+        }
+        for block in &decided {
+            assert_eq!(block.previous_hash, "proposed-by-v1");
+            assert_eq!(block.compute_hash(), decided[0].compute_hash());
+        }
+    }
+
+    #[test]
+    fn test_epoch_rotation_is_deterministic_across_nodes() {
+        const N: u64 = 10;
+        let config = ValidatorSetConfig { blocks_per_epoch: N, max_validators: 4, min_stake: 1 };
+        let mut ids: Vec<String> = (0..8).map(|i| format!("v{}", i)).collect();
+        ids.push("whale".into());
+        let node = |order: &[String]| {
+            let validators = order.iter()
+                .map(|id| (id.clone(), Validator {
+                    id: id.clone(), reputation: 0.9, latency: 10,
+                    stake: if id == "whale" { 500 } else { 100 }, active: true, last_signed_block: 0,
+                }))
+                .collect();
+            BLEEPAdaptiveConsensus::new(
+                validators,
+                HashMap::new(),
+                ValidatorSigningKey::generate(),
+                Arc::new(NetworkingModule::new()),
+                Arc::new(AIAdaptiveConsensus::new(HashMap::new(), Arc::new(FixedMetrics::default()))),
+            )
+            .with_epoch_rotation(config)
+            .unwrap()
+        };
+        let mut a = node(&ids);
+        ids.reverse();
+        let mut b = node(&ids);
+
+        // The whale loses voting reputation during epoch 1 and regains it in epoch 2.
+        let mut parent = Block::new(0, vec![], "0".into());
+        for h in 1..=5 * N {
+            let block = Block::new(h, vec![], parent.compute_hash());
+            for c in [&mut a, &mut b] {
+                if h == 15 { c.validators.get_mut("whale").unwrap().reputation = 0.1; }
+                if h == 25 { c.validators.get_mut("whale").unwrap().reputation = 0.9; }
+                c.on_block_committed(&block);
+            }
+            parent = block;
+        }
+
+        assert_eq!((a.current_epoch(), b.current_epoch()), (5, 5));
+        let set = a.validators_for_epoch(5).expect("epoch 5 decided");
+        assert_eq!(Some(set), b.validators_for_epoch(5));
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.members[0].id, "whale");
+
+        let transitions = a.drain_epoch_transitions();
+        assert_eq!(transitions, b.drain_epoch_transitions());
+        assert_eq!(transitions.iter().map(|t| t.epoch).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        assert!(transitions[2].leaving.contains(&"whale".to_string()));
+        assert!(transitions[3].joining.contains(&"whale".to_string()));
+
+        // Out of the epoch-3 set, but still accepted there for one epoch of grace.
+        assert!(!a.validators_for_epoch(3).unwrap().contains("whale"));
+        assert!(a.is_authorized_signer(35, "whale"));
+        assert!(!a.is_authorized_signer(35, "stranger"));
+    }
+
+    // ── Equivocation slashing ────────────────────────────────────────────────
+
+    #[test]
+    fn test_double_signer_slashed_on_remote_node() {
+        use crate::equivocation::{decode_slashing_evidence, encode_slashing_evidence};
+
+        let byzantine = ValidatorSigningKey::generate();
+        let pubkeys: HashMap<String, Vec<u8>> =
+            [("byz".to_string(), byzantine.pk_bytes.clone())].into_iter().collect();
+        let validators: HashMap<String, Validator> = [("byz", 500), ("honest", 500)].into_iter()
+            .map(|(id, stake)| (id.to_string(), Validator {
+                id: id.into(), reputation: 0.9, latency: 10, stake, active: true, last_signed_block: 0,
+            }))
+            .collect();
+        let node = |key: ValidatorSigningKey| BLEEPAdaptiveConsensus::new(
+            validators.clone(),
+            pubkeys.clone(),
+            key,
+            Arc::new(NetworkingModule::new()),
+            Arc::new(AIAdaptiveConsensus::new(HashMap::new(), Arc::new(FixedMetrics::default()))),
+        );
+        let signer = node(byzantine);
+        let mut local = node(ValidatorSigningKey::generate());
+        let mut remote = node(ValidatorSigningKey::generate());
+
+        // Two different blocks at height 7, both signed by "byz".
+        let (a, b) = (Block::new(7, vec![], "fork-a".into()), Block::new(7, vec![], "fork-b".into()));
+        let (sig_a, sig_b) = (signer.sign_block(&a, "byz").unwrap(), signer.sign_block(&b, "byz").unwrap());
+        assert!(local.observe_signed_block(&a, &sig_a, "byz").is_none());
+        let evidence = local.observe_signed_block(&b, &sig_b, "byz").expect("double-sign detected");
+        assert_eq!(local.validators["byz"].stake, 0);
+
+        // Gossiped to a node that never saw either block.
+        let gossiped = decode_slashing_evidence(&encode_slashing_evidence(&evidence)).unwrap();
+        let mut forged = gossiped.clone();
+        forged.signature_2[0] ^= 0xff;
+        assert!(remote.apply_slashing_evidence(&forged).is_err(), "forged evidence is rejected");
+
+        // Honest signatures from two heights are not a double-sign, even
+        // when the evidence claims otherwise.
+        let next = Block::new(8, vec![], "fork-a".into());
+        let honest = DoubleSignEvidence {
+            header_2: next.header(), signature_2: signer.sign_block(&next, "byz").unwrap(), ..gossiped.clone()
+        };
+        assert!(remote.apply_slashing_evidence(&honest).unwrap_err().contains("heights"));
+        let mut relabelled = honest.clone();
+        relabelled.header_2.index = 7;
+        assert!(remote.apply_slashing_evidence(&relabelled).unwrap_err().contains("does not verify"));
+
+        let mut other_chain = node(ValidatorSigningKey::generate()).with_chain_id(ChainId(2));
+        assert!(other_chain.apply_slashing_evidence(&gossiped).unwrap_err().contains("chain"));
+        assert!(remote.validators["byz"].active);
+
+        let event = remote.apply_slashing_evidence(&gossiped).unwrap();
+        assert_eq!((event.validator_id.as_str(), event.block_height, event.slash_amount), ("byz", 7, 500));
+        assert_eq!(remote.validators["byz"].stake, 0);
+        assert!(!remote.validators["byz"].active);
+        assert!(!remote.eligible_voters(8).contains("byz"));
+        assert_eq!(remote.validators["honest"].stake, 500);
+        assert!(remote.apply_slashing_evidence(&gossiped).is_err(), "one offence, one slash");
+        assert_eq!(remote.drain_slashing_events().len(), 1);
+        assert!(remote.drain_slashing_events().is_empty());
+    }
+
+    // ── Block timing ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_proposal_must_match_slot_leader() {
+        use crate::validator_set::ValidatorSetConfig;
+
+        let genesis = vec![("a".to_string(), 300), ("b".to_string(), 200), ("c".to_string(), 100)];
+        let sets = EpochValidatorSets::new(ValidatorSetConfig::default(), &genesis).unwrap();
+        let timing = BlockTimingParams { target_block_interval: 3, genesis_timestamp: 1_000, ..Default::default() };
+        let c = make_consensus(ValidatorSigningKey::generate(), HashMap::new())
+            .with_validator_sets(sets)
+            .with_block_timing(timing);
+
+        // Slot 1 (1003..1006) belongs to the second member.
+        let mut block = make_block(1);
+        block.timestamp = 1_004;
+        assert!(c.check_proposal_slot(&block, "b", 1_000).is_ok());
+        assert!(c.check_proposal_slot(&block, "a", 1_000).is_err());
+        assert_eq!(c.leader_turn(&block), Some(1));
+    }
 }
@@ -0,0 +1,196 @@
+//! # Consensus signing key
+//!
+//! A validator signs blocks with a SPHINCS+ key kept in a keystore file,
+//! and the node finds that file through the `[consensus]` stanza of its
+//! TOML config:
+//!
+//! ```text
+//! <home>/consensus_key.json     validator id, public and secret key (hex), mode 0600
+//! <home>/node.toml
+//!   [consensus]
+//!   keystore     = "<home>/consensus_key.json"
+//!   validator_id = "<first 8 bytes of the public key, hex>"
+//! ```
+//!
+//! `bleep-cli validator init` writes both; the node reads the config named
+//! by `BLEEP_NODE_CONFIG` and signs with a fresh key when it has none.
+//! Other tables of an existing config are left as they are.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use bleep_crypto::tx_signer::generate_tx_keypair;
+use serde::{Deserialize, Serialize};
+
+use crate::block_producer::ProposerKey;
+use crate::staking::validator_id_for_key;
+
+/// Keystore file name under the validator's home directory.
+pub const CONSENSUS_KEY_FILE: &str = "consensus_key.json";
+/// Node config file name under the validator's home directory.
+pub const NODE_CONFIG_FILE: &str = "node.toml";
+
+/// A stored consensus keypair.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ConsensusKeystore {
+    pub validator_id: String,
+    public_key:       String,
+    secret_key:       String,
+}
+
+impl std::fmt::Debug for ConsensusKeystore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsensusKeystore")
+            .field("validator_id", &self.validator_id)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ConsensusKeystore {
+    /// Load the keystore in `dir`, generating and saving one if there is
+    /// none.  The flag is true when the key was generated.
+    pub fn load_or_create(dir: &Path) -> Result<(Self, bool), String> {
+        let path = dir.join(CONSENSUS_KEY_FILE);
+        if path.exists() {
+            return Ok((Self::load(&path)?, false));
+        }
+        let (pk, sk) = generate_tx_keypair();
+        let keystore = ConsensusKeystore {
+            validator_id: validator_id_for_key(&pk),
+            public_key:   hex::encode(&pk),
+            secret_key:   hex::encode(&sk),
+        };
+        fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        let json = serde_json::to_vec_pretty(&keystore).map_err(|e| e.to_string())?;
+        write_private(&path, &json)?;
+        Ok((keystore, true))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let keystore: ConsensusKeystore = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        let pk = keystore.public_key()?;
+        if keystore.validator_id != validator_id_for_key(&pk) {
+            return Err(format!("{}: validator id does not match the public key", path.display()));
+        }
+        Ok(keystore)
+    }
+
+    pub fn public_key(&self) -> Result<Vec<u8>, String> {
+        hex::decode(&self.public_key).map_err(|e| format!("consensus public key: {}", e))
+    }
+
+    /// The key a `BlockProducer` signs with.
+    pub fn proposer_key(&self) -> Result<ProposerKey, String> {
+        Ok(ProposerKey {
+            validator_id: self.validator_id.clone(),
+            sk: hex::decode(&self.secret_key).map_err(|e| format!("consensus secret key: {}", e))?,
+            pk: self.public_key()?,
+        })
+    }
+}
+
+/// The `[consensus]` table of a node config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConsensusConfig {
+    pub keystore:     PathBuf,
+    pub validator_id: String,
+}
+
+impl ConsensusConfig {
+    /// The `[consensus]` table of the config at `path`; `None` if the file
+    /// or the table is missing.
+    pub fn read(path: &Path) -> Result<Option<Self>, String> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let table = read_table(path)?;
+        table.get("consensus")
+            .map(|t| t.clone().try_into().map_err(|e| format!("{} [consensus]: {}", path.display(), e)))
+            .transpose()
+    }
+
+    /// Write this as the `[consensus]` table of the config at `path`,
+    /// keeping the file's other tables.
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let mut table = if path.exists() { read_table(path)? } else { toml::Table::new() };
+        let stanza = toml::Value::try_from(self).map_err(|e| e.to_string())?;
+        table.insert("consensus".into(), stanza);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+        }
+        let text = toml::to_string_pretty(&table).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn load_keystore(&self) -> Result<ConsensusKeystore, String> {
+        let keystore = ConsensusKeystore::load(&self.keystore)?;
+        if keystore.validator_id != self.validator_id {
+            return Err(format!(
+                "{} holds validator {}, config names {}",
+                self.keystore.display(), keystore.validator_id, self.validator_id
+            ));
+        }
+        Ok(keystore)
+    }
+}
+
+fn read_table(path: &Path) -> Result<toml::Table, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    text.parse().map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Write `bytes` readable by the owner only, via a temp file and rename.
+fn write_private(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let tmp = path.with_extension("json.tmp");
+    let io = |e: std::io::Error| format!("{}: {}", path.display(), e);
+    {
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+        let mut f = opts.open(&tmp).map_err(io)?;
+        f.write_all(bytes).map_err(io)?;
+        f.sync_all().map_err(io)?;
+    }
+    fs::rename(&tmp, path).map_err(io)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("bleep-consensus-key-{}-{}", name, std::process::id()))
+    }
+
+    #[test]
+    fn keystore_is_created_once_and_stanza_keeps_other_tables() {
+        let dir = temp_dir("init");
+        let _ = fs::remove_dir_all(&dir);
+        let (created, fresh) = ConsensusKeystore::load_or_create(&dir).unwrap();
+        let (loaded, again) = ConsensusKeystore::load_or_create(&dir).unwrap();
+        assert!(fresh && !again);
+        assert_eq!(loaded.public_key().unwrap(), created.public_key().unwrap());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.join(CONSENSUS_KEY_FILE)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        let config_path = dir.join(NODE_CONFIG_FILE);
+        fs::write(&config_path, "[p2p]\nport = 7000\n").unwrap();
+        let config = ConsensusConfig { keystore: dir.join(CONSENSUS_KEY_FILE), validator_id: created.validator_id.clone() };
+        config.write(&config_path).unwrap();
+
+        assert_eq!(ConsensusConfig::read(&config_path).unwrap(), Some(config.clone()));
+        assert!(fs::read_to_string(&config_path).unwrap().contains("port = 7000"));
+        let key = config.load_keystore().unwrap().proposer_key().unwrap();
+        assert_eq!(key.validator_id, created.validator_id);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,176 @@
+//! # Equivocation detection
+//!
+//! Remembers the first header each validator signed at each height.  A
+//! second, different header signed by the same validator at that height is
+//! a double-sign; the detector hands back a `DoubleSignEvidence` carrying
+//! both headers and both signatures, which any node can check against the
+//! validator's registered key.
+//!
+//! ```text
+//!   signed block ──► EquivocationDetector::record
+//!                      same (validator, height), new header ──► DoubleSignEvidence
+//!   gossip (SLASHING_EVIDENCE_TOPIC) ──► BLEEPAdaptiveConsensus::apply_slashing_evidence
+//!                      same height & chain · headers differ · both signatures
+//!                      verify · zero stake · deactivate
+//!                      ──► SlashingEvent (drain_slashing_events → governance)
+//! ```
+//!
+//! Carrying the headers rather than bare hashes matters: signatures are
+//! public, so two honest signatures from different heights could otherwise
+//! be passed off as a double-sign.
+//!
+//! The detector does not verify signatures; callers record only what they
+//! have already verified, and receivers of gossiped evidence verify it again.
+//!
+//! **Not wired into the node yet.**  `bleep` does not run
+//! `BLEEPAdaptiveConsensus`; inbound blocks go through `evidence::EvidencePool`
+//! (`EVIDENCE_TOPIC`) instead.  Nothing subscribes to
+//! `SLASHING_EVIDENCE_TOPIC` and `drain_slashing_events` has no caller outside
+//! tests.  A node embedding the driver must gossip what `observe_signed_block`
+//! returns, feed the topic to `apply_slashing_evidence`, and forward drained
+//! events to governance.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use bleep_core::block::Block;
+
+use crate::slashing_engine::SlashingEvidence;
+
+/// `MessageType::Custom` tag double-sign evidence is gossiped under.
+pub const SLASHING_EVIDENCE_TOPIC: &str = "slashing-evidence";
+
+/// Two different headers signed by one validator at one height.
+///
+/// Headers are `Block::header()`s; `signature_n` is the validator's
+/// detached signature over header `n`'s consensus signing hash.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoubleSignEvidence {
+    pub validator_id: String,
+    pub header_1:     Block,
+    pub signature_1:  Vec<u8>,
+    pub header_2:     Block,
+    pub signature_2:  Vec<u8>,
+}
+
+impl DoubleSignEvidence {
+    /// Height of the offence.
+    pub fn height(&self) -> u64 {
+        self.header_1.index
+    }
+
+    /// The record the slashing engine keeps for this offence.
+    pub fn to_slashing_evidence(&self) -> SlashingEvidence {
+        SlashingEvidence::DoubleSigning {
+            validator_id: self.validator_id.clone(),
+            height:       self.height(),
+            block_hash_1: self.header_1.compute_hash(),
+            block_hash_2: self.header_2.compute_hash(),
+            signature_1:  self.signature_1.clone(),
+            signature_2:  self.signature_2.clone(),
+        }
+    }
+}
+
+/// Serialise evidence for gossip.
+pub fn encode_slashing_evidence(evidence: &DoubleSignEvidence) -> Vec<u8> {
+    serde_json::to_vec(evidence).expect("slashing evidence serialises")
+}
+
+pub fn decode_slashing_evidence(bytes: &[u8]) -> Result<DoubleSignEvidence, String> {
+    serde_json::from_slice(bytes).map_err(|e| format!("Malformed slashing evidence: {}", e))
+}
+
+/// First signed header per `(height, validator)`, keyed by its signing hash.
+#[derive(Debug, Default)]
+pub struct EquivocationDetector {
+    seen: BTreeMap<(u64, String), (String, Block, Vec<u8>)>,
+}
+
+impl EquivocationDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `validator_id` signed `block`, whose signing hash is
+    /// `signing_hash`.
+    ///
+    /// Returns evidence when a different hash was already recorded for that
+    /// validator and height.  The two headers are ordered by hash so every
+    /// node that sees the same pair builds identical evidence.
+    pub fn record(
+        &mut self,
+        validator_id: &str,
+        block: &Block,
+        signing_hash: &str,
+        signature: &[u8],
+    ) -> Option<DoubleSignEvidence> {
+        let key = (block.index, validator_id.to_string());
+        let (first_hash, first_header, first_sig) = match self.seen.get(&key) {
+            None => {
+                self.seen.insert(key, (signing_hash.to_string(), block.header(), signature.to_vec()));
+                return None;
+            }
+            Some((hash, _, _)) if hash == signing_hash => return None,
+            Some(first) => first.clone(),
+        };
+
+        let this = (block.header(), signature.to_vec());
+        let ((header_1, signature_1), (header_2, signature_2)) =
+            if first_hash.as_str() < signing_hash {
+                ((first_header, first_sig), this)
+            } else {
+                (this, (first_header, first_sig))
+            };
+        Some(DoubleSignEvidence {
+            validator_id: validator_id.to_string(),
+            header_1,
+            signature_1,
+            header_2,
+            signature_2,
+        })
+    }
+
+    /// Forget every height below `height`.
+    pub fn prune_below(&mut self, height: u64) {
+        self.seen = self.seen.split_off(&(height, String::new()));
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_hash_at_a_height_is_evidence() {
+        let block = |height: u64, prev: &str| Block::new(height, vec![], prev.to_string());
+        let (a5, b5) = (block(5, "a"), block(5, "b"));
+        let mut d = EquivocationDetector::new();
+        assert!(d.record("v1", &b5, "bb", &[2]).is_none());
+        assert!(d.record("v1", &b5, "bb", &[2]).is_none(), "re-seeing a block is not a double-sign");
+        assert!(d.record("v2", &a5, "aa", &[3]).is_none(), "other validators are tracked apart");
+        assert!(d.record("v1", &block(6, "a"), "aa", &[4]).is_none(), "other heights are tracked apart");
+
+        let evidence = d.record("v1", &a5, "aa", &[1]).expect("double-sign evidence");
+        assert_eq!((evidence.validator_id.as_str(), evidence.height()), ("v1", 5));
+        assert_eq!((evidence.header_1.previous_hash.as_str(), evidence.header_2.previous_hash.as_str()), ("a", "b"));
+        assert_eq!((evidence.signature_1.clone(), evidence.signature_2.clone()), (vec![1], vec![2]));
+        assert!(evidence.to_slashing_evidence().is_well_formed().is_ok());
+
+        d.prune_below(6);
+        assert_eq!(d.len(), 1);
+        let evidence = d.record("v1", &block(6, "c"), "cc", &[5]).unwrap();
+        let decoded = decode_slashing_evidence(&encode_slashing_evidence(&evidence)).unwrap();
+        assert_eq!((decoded.validator_id.as_str(), decoded.height()), ("v1", 6));
+        assert!(decode_slashing_evidence(b"junk").is_err());
+    }
+}
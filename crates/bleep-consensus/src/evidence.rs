@@ -0,0 +1,581 @@
+//! # Misbehaviour evidence
+//!
+//! Turns an observed double-sign into an on-chain slash:
+//!
+//! ```text
+//!   inbound block ──► EvidencePool::observe_block
+//!                       second header from the same signer at a height
+//!                       ──► Evidence::DoubleSign
+//!   gossip (EVIDENCE_TOPIC) ──► EvidencePool::add      verify · dedupe · expiry
+//!   proposer ──► select_for_block ──► Block::set_evidence   (reserved space)
+//!   import   ──► validate_block
+//!   commit   ──► on_block_committed ──► SlashingEngine::process_evidence
+//! ```
+//!
+//! Both headers must carry SPHINCS+ signatures by the accused validator's
+//! registered key (`ValidatorIdentity::signing_key_id`, hex).  One offence is
+//! one `(height, validator)`: evidence for a pair that is pending or already
+//! committed is a duplicate.  Evidence more than `window` blocks old is
+//! neither accepted nor includable, so a validator cannot be slashed for
+//! ancient history after its stake has moved on.
+//!
+//! Censorship: when a proposer commits a block with free evidence space while
+//! evidence is pending, it is recorded against that evidence.  After
+//! `censorship_window` such blocks every proposer that skipped it is flagged
+//! (`flagged()`), and the count starts over until the evidence is included.
+
+use std::collections::BTreeMap;
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use bleep_core::block::Block;
+
+use crate::slashing_engine::{SlashingEngine, SlashingEvent, SlashingEvidence};
+use crate::validator_identity::ValidatorRegistry;
+
+/// `MessageType::Custom` tag evidence is gossiped under.
+pub const EVIDENCE_TOPIC: &str = "evidence";
+/// Evidence entries a block may carry.
+pub const MAX_EVIDENCE_PER_BLOCK: usize = 4;
+/// Blocks after the offence during which evidence is accepted.
+pub const DEFAULT_EVIDENCE_WINDOW: u64 = 1_000;
+/// Consecutive skipping blocks before their proposers are flagged.
+pub const DEFAULT_CENSORSHIP_WINDOW: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EvidenceError {
+    #[error("Malformed evidence: {0}")]
+    Malformed(String),
+    #[error("Validator {0} has no registered signing key")]
+    UnknownValidator(String),
+    #[error("Header is not signed with {0}'s registered key")]
+    KeyMismatch(String),
+    #[error("Header signature does not verify")]
+    BadSignature,
+    #[error("Evidence for height {height} is outside the {window}-block window at height {current}")]
+    Expired { height: u64, current: u64, window: u64 },
+    #[error("Evidence against {validator} at height {height} already known")]
+    Duplicate { validator: String, height: u64 },
+    #[error("Block carries {0} evidence entries, limit is {}", MAX_EVIDENCE_PER_BLOCK)]
+    TooMany(usize),
+}
+
+// ── Evidence ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Evidence {
+    /// Two different headers signed by one validator at one height.
+    ///
+    /// Headers are stripped of transactions, proofs, evidence and signature;
+    /// the signatures travel in `sig_a` / `sig_b`.
+    DoubleSign {
+        validator_id: String,
+        height:       u64,
+        sig_a:        Vec<u8>,
+        sig_b:        Vec<u8>,
+        header_a:     Block,
+        header_b:     Block,
+    },
+}
+
+impl Evidence {
+    /// Evidence from two signed blocks, or `None` unless they share a height,
+    /// differ in hash and are both signed.  The pair is ordered by hash, so
+    /// every observer builds the same evidence.
+    pub fn double_sign(validator_id: &str, a: &Block, b: &Block) -> Option<Self> {
+        let (hash_a, hash_b) = (a.compute_hash(), b.compute_hash());
+        if a.index != b.index || hash_a == hash_b {
+            return None;
+        }
+        a.signer_public_key()?;
+        b.signer_public_key()?;
+        let (a, b) = if hash_a < hash_b { (a, b) } else { (b, a) };
+        Some(Evidence::DoubleSign {
+            validator_id: validator_id.to_string(),
+            height:       a.index,
+            sig_a:        a.validator_signature.clone(),
+            sig_b:        b.validator_signature.clone(),
+            header_a:     header_only(a),
+            header_b:     header_only(b),
+        })
+    }
+
+    pub fn validator_id(&self) -> &str {
+        match self {
+            Evidence::DoubleSign { validator_id, .. } => validator_id,
+        }
+    }
+
+    /// Height of the offence.
+    pub fn height(&self) -> u64 {
+        match self {
+            Evidence::DoubleSign { height, .. } => *height,
+        }
+    }
+
+    fn key(&self) -> (u64, String) {
+        (self.height(), self.validator_id().to_string())
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("evidence serialises")
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, EvidenceError> {
+        serde_json::from_slice(bytes).map_err(|e| EvidenceError::Malformed(e.to_string()))
+    }
+
+    /// Check both signatures against the accused validator's registered key.
+    pub fn verify(&self, registry: &ValidatorRegistry) -> Result<(), EvidenceError> {
+        let Evidence::DoubleSign { validator_id, height, sig_a, sig_b, header_a, header_b } = self;
+        if header_a.index != *height || header_b.index != *height {
+            return Err(EvidenceError::Malformed("header height differs from evidence height".into()));
+        }
+        if header_a.compute_hash() == header_b.compute_hash() {
+            return Err(EvidenceError::Malformed("headers are identical".into()));
+        }
+        let key = registered_key(registry, validator_id)?;
+        for (header, sig) in [(header_a, sig_a), (header_b, sig_b)] {
+            let mut signed = header.clone();
+            signed.validator_signature = sig.clone();
+            match signed.signer_public_key() {
+                Some(pk) if pk == key.as_slice() => {}
+                Some(_) => return Err(EvidenceError::KeyMismatch(validator_id.clone())),
+                None => return Err(EvidenceError::BadSignature),
+            }
+            if !signed.verify_header_signature() {
+                return Err(EvidenceError::BadSignature);
+            }
+        }
+        Ok(())
+    }
+
+    fn to_slashing_evidence(&self) -> SlashingEvidence {
+        let Evidence::DoubleSign { validator_id, height, sig_a, sig_b, header_a, header_b } = self;
+        SlashingEvidence::DoubleSigning {
+            validator_id: validator_id.clone(),
+            height:       *height,
+            block_hash_1: header_a.compute_hash(),
+            block_hash_2: header_b.compute_hash(),
+            signature_1:  sig_a.clone(),
+            signature_2:  sig_b.clone(),
+        }
+    }
+}
+
+/// The block minus everything the hash does not need.
+fn header_only(block: &Block) -> Block {
+    let mut h = block.clone();
+    h.transactions.clear();
+    h.tx_proofs.clear();
+    h.evidence.clear();
+    h.zk_proof.clear();
+    h.validator_signature.clear();
+    h
+}
+
+fn registered_key(registry: &ValidatorRegistry, validator_id: &str) -> Result<Vec<u8>, EvidenceError> {
+    registry.get(validator_id)
+        .and_then(|v| hex::decode(&v.signing_key_id).ok())
+        .ok_or_else(|| EvidenceError::UnknownValidator(validator_id.to_string()))
+}
+
+/// Active validator whose registered key is `public_key`.
+pub fn validator_for_key(registry: &ValidatorRegistry, public_key: &[u8]) -> Option<String> {
+    let hex_key = hex::encode(public_key);
+    registry.get_active_validators().into_iter()
+        .find(|v| v.signing_key_id == hex_key)
+        .map(|v| v.id.clone())
+}
+
+// ── Pool ──────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct EvidenceConfig {
+    /// Blocks after the offence during which evidence is accepted.
+    pub window:            u64,
+    /// Skipping blocks before their proposers are flagged.
+    pub censorship_window: u64,
+    pub max_per_block:     usize,
+}
+
+impl Default for EvidenceConfig {
+    fn default() -> Self {
+        EvidenceConfig {
+            window:            DEFAULT_EVIDENCE_WINDOW,
+            censorship_window: DEFAULT_CENSORSHIP_WINDOW,
+            max_per_block:     MAX_EVIDENCE_PER_BLOCK,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Pending {
+    evidence:    Evidence,
+    received_at: u64,
+    skipped_by:  Vec<String>,
+}
+
+/// What committing a block's evidence did.
+#[derive(Debug, Clone, Default)]
+pub struct CommitReport {
+    /// Slashes applied, one per included evidence entry.
+    pub slashed: Vec<SlashingEvent>,
+    /// Proposers that crossed the censorship window in this block.
+    pub flagged: Vec<String>,
+}
+
+/// Detects, holds and commits evidence for one node.
+pub struct EvidencePool {
+    config:    EvidenceConfig,
+    /// First signed header seen per `(height, validator)`.
+    seen:      BTreeMap<(u64, String), Block>,
+    pending:   BTreeMap<(u64, String), Pending>,
+    /// `(height, validator)` → height of the block that included it.
+    committed: BTreeMap<(u64, String), u64>,
+    flagged:   BTreeMap<String, u64>,
+}
+
+impl EvidencePool {
+    pub fn new(config: EvidenceConfig) -> Self {
+        EvidencePool {
+            config,
+            seen:      BTreeMap::new(),
+            pending:   BTreeMap::new(),
+            committed: BTreeMap::new(),
+            flagged:   BTreeMap::new(),
+        }
+    }
+
+    fn expired(&self, height: u64, current: u64) -> bool {
+        height.saturating_add(self.config.window) < current
+    }
+
+    /// Record a signed block from `validator_id`; returns evidence when it
+    /// conflicts with an earlier block at the same height.
+    pub fn observe_block(&mut self, validator_id: &str, block: &Block, current_height: u64) -> Option<Evidence> {
+        if self.expired(block.index, current_height) {
+            return None;
+        }
+        let key = (block.index, validator_id.to_string());
+        if self.pending.contains_key(&key) || self.committed.contains_key(&key) {
+            return None;
+        }
+        match self.seen.get(&key) {
+            None => {
+                let mut header = header_only(block);
+                header.validator_signature = block.validator_signature.clone();
+                self.seen.insert(key, header);
+                None
+            }
+            Some(first) => {
+                let ev = Evidence::double_sign(validator_id, first, block)?;
+                warn!("[Evidence] {} signed two blocks at height {}", validator_id, block.index);
+                Some(ev)
+            }
+        }
+    }
+
+    /// Whether `evidence` could be included in a block at `current_height`.
+    pub fn check(&self, evidence: &Evidence, current_height: u64, registry: &ValidatorRegistry) -> Result<(), EvidenceError> {
+        let height = evidence.height();
+        if self.expired(height, current_height) {
+            return Err(EvidenceError::Expired { height, current: current_height, window: self.config.window });
+        }
+        if height > current_height {
+            return Err(EvidenceError::Malformed(format!("evidence height {} is in the future", height)));
+        }
+        if self.committed.contains_key(&evidence.key()) {
+            return Err(EvidenceError::Duplicate { validator: evidence.validator_id().into(), height });
+        }
+        evidence.verify(registry)
+    }
+
+    /// Accept evidence from gossip or local detection.
+    pub fn add(&mut self, evidence: Evidence, current_height: u64, registry: &ValidatorRegistry) -> Result<(), EvidenceError> {
+        self.check(&evidence, current_height, registry)?;
+        let key = evidence.key();
+        if self.pending.contains_key(&key) {
+            return Err(EvidenceError::Duplicate { validator: key.1, height: key.0 });
+        }
+        info!("[Evidence] Pending: {} at height {}", key.1, key.0);
+        self.pending.insert(key, Pending { evidence, received_at: current_height, skipped_by: Vec::new() });
+        Ok(())
+    }
+
+    /// Oldest includable evidence for the block at `height`.
+    pub fn select_for_block(&self, height: u64) -> Vec<Evidence> {
+        self.pending.values()
+            .filter(|p| !self.expired(p.evidence.height(), height) && p.evidence.height() <= height)
+            .take(self.config.max_per_block)
+            .map(|p| p.evidence.clone())
+            .collect()
+    }
+
+    /// Decode and check a received block's evidence.
+    pub fn validate_block(&self, block: &Block, registry: &ValidatorRegistry) -> Result<Vec<Evidence>, EvidenceError> {
+        if block.evidence.len() > self.config.max_per_block {
+            return Err(EvidenceError::TooMany(block.evidence.len()));
+        }
+        if Block::calculate_evidence_root(&block.evidence) != block.evidence_root {
+            return Err(EvidenceError::Malformed("evidence root mismatch".into()));
+        }
+        let mut keys = Vec::new();
+        let mut out = Vec::with_capacity(block.evidence.len());
+        for bytes in &block.evidence {
+            let ev = Evidence::decode(bytes)?;
+            self.check(&ev, block.index, registry)?;
+            if keys.contains(&ev.key()) {
+                return Err(EvidenceError::Duplicate { validator: ev.validator_id().into(), height: ev.height() });
+            }
+            keys.push(ev.key());
+            out.push(ev);
+        }
+        Ok(out)
+    }
+
+    /// Apply a committed block: slash for its evidence (as returned by
+    /// `validate_block`), account for any evidence its proposer left out,
+    /// and prune expired entries.
+    pub fn on_block_committed(
+        &mut self,
+        block:    &Block,
+        proposer: &str,
+        included: &[Evidence],
+        slashing: &mut SlashingEngine,
+        registry: &mut ValidatorRegistry,
+    ) -> CommitReport {
+        let height = block.index;
+        let mut report = CommitReport::default();
+        for ev in included {
+            let key = ev.key();
+            self.pending.remove(&key);
+            if self.committed.insert(key, height).is_some() {
+                continue;
+            }
+            match slashing.process_evidence(ev.to_slashing_evidence(), registry, block.epoch_id, block.timestamp) {
+                Ok(event) => report.slashed.push(event),
+                Err(e) => warn!("[Evidence] slash of {} at {} failed: {}", ev.validator_id(), ev.height(), e),
+            }
+        }
+
+        if included.len() < self.config.max_per_block {
+            let window = self.config.censorship_window;
+            let mut crossed: Vec<String> = Vec::new();
+            for p in self.pending.values_mut() {
+                if p.received_at >= height {
+                    continue;
+                }
+                p.skipped_by.push(proposer.to_string());
+                if p.skipped_by.len() as u64 >= window {
+                    crossed.append(&mut p.skipped_by);
+                }
+            }
+            crossed.sort();
+            crossed.dedup();
+            for who in crossed {
+                warn!("[Evidence] Proposer {} skipped available evidence for {} blocks", who, window);
+                *self.flagged.entry(who.clone()).or_default() += 1;
+                report.flagged.push(who);
+            }
+        }
+
+        self.prune(height);
+        report
+    }
+
+    fn prune(&mut self, current: u64) {
+        let Some(floor) = current.checked_sub(self.config.window) else { return };
+        let keep = (floor, String::new());
+        self.seen = self.seen.split_off(&keep);
+        self.pending = self.pending.split_off(&keep);
+        self.committed = self.committed.split_off(&keep);
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_committed(&self, validator_id: &str, height: u64) -> bool {
+        self.committed.contains_key(&(height, validator_id.to_string()))
+    }
+
+    /// Proposer → number of times flagged for skipping evidence.
+    pub fn flagged(&self) -> &BTreeMap<String, u64> {
+        &self.flagged
+    }
+}
+
+impl Default for EvidencePool {
+    fn default() -> Self {
+        Self::new(EvidenceConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator_identity::ValidatorIdentity;
+    use bleep_crypto::tx_signer::generate_tx_keypair;
+    use std::collections::HashMap;
+
+    const STAKE: u128 = 1_000_000;
+
+    struct Node {
+        pool:     EvidencePool,
+        registry: ValidatorRegistry,
+        slashing: SlashingEngine,
+    }
+
+    /// Validators `v0..v{n}` with real SPHINCS+ keys, one node per validator;
+    /// gossip is delivering the encoded bytes to every node.
+    struct TestCluster {
+        keys:  HashMap<String, (Vec<u8>, Vec<u8>)>,
+        nodes: Vec<Node>,
+    }
+
+    impl TestCluster {
+        fn new(n: usize, config: EvidenceConfig) -> Self {
+            let keys: HashMap<String, (Vec<u8>, Vec<u8>)> =
+                (0..n).map(|i| (format!("v{}", i), generate_tx_keypair())).collect();
+            let nodes = (0..n).map(|_| {
+                let mut registry = ValidatorRegistry::new();
+                for (id, (pk, _)) in &keys {
+                    let v = ValidatorIdentity::new(id.clone(), vec![0u8; 1568], hex::encode(pk), STAKE, 0).unwrap();
+                    registry.register_validator(v).unwrap();
+                    registry.activate_validator(id).unwrap();
+                }
+                Node { pool: EvidencePool::new(config.clone()), registry, slashing: SlashingEngine::new() }
+            }).collect();
+            TestCluster { keys, nodes }
+        }
+
+        fn signed(&self, signer: &str, height: u64, tag: &str) -> Block {
+            let (pk, sk) = &self.keys[signer];
+            let mut b = Block::new(height, vec![], tag.to_string());
+            b.sign_block_with_pk(sk, pk).unwrap();
+            b
+        }
+
+        fn gossip(&mut self, bytes: &[u8], height: u64) {
+            for node in &mut self.nodes {
+                let ev = Evidence::decode(bytes).unwrap();
+                node.pool.add(ev, height, &node.registry).unwrap();
+            }
+        }
+
+        /// `proposer` builds the block at `height` from its own pool; every
+        /// node validates and commits it.
+        fn commit(&mut self, proposer: usize, height: u64) -> Vec<CommitReport> {
+            let evidence = self.nodes[proposer].pool.select_for_block(height);
+            let id = format!("v{}", proposer);
+            let mut block = Block::new(height, vec![], format!("block-{}", height));
+            block.set_evidence(evidence.iter().map(Evidence::encode).collect());
+            self.commit_block(&id, block)
+        }
+
+        fn commit_block(&mut self, proposer: &str, block: Block) -> Vec<CommitReport> {
+            self.nodes.iter_mut().map(|n| {
+                let included = n.pool.validate_block(&block, &n.registry).unwrap();
+                n.pool.on_block_committed(&block, proposer, &included, &mut n.slashing, &mut n.registry)
+            }).collect()
+        }
+    }
+
+    #[test]
+    fn double_sign_flows_from_detection_to_slash() {
+        let mut c = TestCluster::new(3, EvidenceConfig::default());
+        let a = c.signed("v1", 5, "fork-a");
+        let b = c.signed("v1", 5, "fork-b");
+
+        let observer = &mut c.nodes[0].pool;
+        assert!(observer.observe_block("v1", &a, 5).is_none());
+        assert!(observer.observe_block("v1", &a, 5).is_none(), "same block twice is not evidence");
+        let ev = observer.observe_block("v1", &b, 5).expect("conflicting header");
+        assert_eq!(Evidence::double_sign("v1", &b, &a).unwrap().encode(), ev.encode(), "canonical order");
+
+        c.gossip(&ev.encode(), 5);
+        let reports = c.commit(2, 6);
+        for (node, report) in c.nodes.iter().zip(&reports) {
+            assert_eq!(report.slashed.len(), 1);
+            assert_eq!(report.slashed[0].validator_id, "v1");
+            assert_eq!(report.slashed[0].evidence_type, "DOUBLE_SIGNING");
+            let v1 = node.registry.get("v1").unwrap();
+            assert_eq!(v1.double_sign_count, 1);
+            assert!(v1.stake < STAKE);
+            assert!(node.pool.is_committed("v1", 5));
+            assert_eq!(node.pool.pending_len(), 0);
+        }
+
+        // Re-gossiped or re-included evidence is a duplicate.
+        let n = &mut c.nodes[1];
+        assert!(matches!(n.pool.add(ev.clone(), 7, &n.registry), Err(EvidenceError::Duplicate { .. })));
+        let mut again = Block::new(7, vec![], "again".into());
+        again.set_evidence(vec![ev.encode()]);
+        assert!(matches!(n.pool.validate_block(&again, &n.registry), Err(EvidenceError::Duplicate { .. })));
+    }
+
+    #[test]
+    fn evidence_with_mismatched_key_is_rejected() {
+        let c = TestCluster::new(3, EvidenceConfig::default());
+        // Two conflicting blocks by v2, blamed on v1.
+        let a = c.signed("v2", 5, "fork-a");
+        let b = c.signed("v2", 5, "fork-b");
+        let framed = Evidence::double_sign("v1", &a, &b).unwrap();
+        let n = &c.nodes[0];
+        assert_eq!(framed.verify(&n.registry), Err(EvidenceError::KeyMismatch("v1".into())));
+
+        // A header altered after signing no longer verifies.
+        let Evidence::DoubleSign { mut header_b, validator_id, height, sig_a, sig_b, header_a } =
+            Evidence::double_sign("v2", &a, &b).unwrap();
+        header_b.timestamp += 1;
+        let tampered = Evidence::DoubleSign { validator_id, height, sig_a, sig_b, header_a, header_b };
+        assert_eq!(tampered.verify(&n.registry), Err(EvidenceError::BadSignature));
+        assert!(Evidence::double_sign("v2", &a, &a).is_none());
+    }
+
+    #[test]
+    fn expired_evidence_is_not_includable() {
+        let config = EvidenceConfig { window: 10, ..Default::default() };
+        let mut c = TestCluster::new(2, config);
+        let ev = Evidence::double_sign("v1", &c.signed("v1", 5, "a"), &c.signed("v1", 5, "b")).unwrap();
+
+        c.gossip(&ev.encode(), 12);
+        let n = &c.nodes[0];
+        assert_eq!(n.pool.select_for_block(15).len(), 1);
+        assert!(n.pool.select_for_block(16).is_empty(), "window ends at height 15");
+        assert!(matches!(n.pool.check(&ev, 16, &n.registry), Err(EvidenceError::Expired { height: 5, .. })));
+
+        let mut late = Block::new(16, vec![], "late".into());
+        late.set_evidence(vec![ev.encode()]);
+        assert!(matches!(n.pool.validate_block(&late, &n.registry), Err(EvidenceError::Expired { .. })));
+
+        // Committing past the window drops it without a slash.
+        let reports = c.commit(0, 16);
+        assert!(reports.iter().all(|r| r.slashed.is_empty()));
+        assert_eq!(c.nodes[0].pool.pending_len(), 0);
+    }
+
+    #[test]
+    fn proposers_skipping_evidence_are_flagged() {
+        let config = EvidenceConfig { censorship_window: 3, ..Default::default() };
+        let mut c = TestCluster::new(3, config);
+        let ev = Evidence::double_sign("v1", &c.signed("v1", 5, "a"), &c.signed("v1", 5, "b")).unwrap();
+        c.gossip(&ev.encode(), 5);
+
+        let empty = |h: u64| Block::new(h, vec![], format!("empty-{}", h));
+        for (h, proposer) in [(6, "v0"), (7, "v2")] {
+            assert!(c.commit_block(proposer, empty(h)).iter().all(|r| r.flagged.is_empty()));
+        }
+        let reports = c.commit_block("v0", empty(8));
+        assert_eq!(reports[0].flagged, vec!["v0".to_string(), "v2".to_string()]);
+        assert_eq!(c.nodes[1].pool.flagged().get("v0"), Some(&1));
+
+        // Including it ends the streak.
+        let reports = c.commit(2, 9);
+        assert_eq!(reports[0].slashed.len(), 1);
+        assert!(reports[0].flagged.is_empty());
+    }
+}
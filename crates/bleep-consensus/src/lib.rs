@@ -1,6 +1,8 @@
 pub mod ai_adaptive_logic;
+pub mod network_metrics;
 pub mod blockchain_state;
 pub mod consensus;
+pub mod types;
 pub mod networking;
 pub mod tests;
 pub mod epoch;
@@ -10,16 +12,37 @@ pub mod pbft_engine;
 pub mod pow_engine;
 pub mod orchestrator;
 pub mod validator_identity;
+pub mod validator_set;
+pub mod block_timing;
 pub mod slashing_engine;
 pub mod finality;
 pub mod block_producer;
+pub mod block_execution;
+pub mod block_store;
+pub mod replay;
+pub mod storage_fsck;
+pub mod view_change;
+pub mod pbft_round;
+pub mod evidence;
+pub mod equivocation;
+pub mod replica;
+pub mod partition;
+pub mod rewards;
+pub mod staking;
+pub mod consensus_key;
+pub mod snap_sync;
+pub mod chain_audit;
 
-pub use consensus::{BLEEPAdaptiveConsensus, ConsensusMode, Validator};
+pub use consensus::BLEEPAdaptiveConsensus;
+pub use types::{ConsensusMode, Validator};
 pub use blockchain_state::BlockchainState;
-pub use networking::NetworkingModule;
+pub use network_metrics::{FixedMetrics, MetricsProvider, TelemetryMetrics};
+pub use networking::{GossipPbftLink, LocalPbftHub, NetworkingModule, PbftGossip, PbftLink, PBFT_TOPIC};
 pub use epoch::{EpochConfig, EpochState, ConsensusMode as EpochConsensusMode};
 pub use engine::{ConsensusEngine, ConsensusError};
-pub use validator_identity::{ValidatorIdentity, ValidatorRegistry, ValidatorState};
+pub use validator_identity::{RegistryStakes, ValidatorIdentity, ValidatorRegistry, ValidatorState};
+pub use validator_set::{EpochTransition, EpochValidatorSets, MissedSlots, ValidatorSet, ValidatorSetConfig, ValidatorSetEvent};
+pub use block_timing::{pow_schedule, BlockTimingParams, SlotSchedule, SlotError, retarget_pow_difficulty, INITIAL_POW_DIFFICULTY};
 pub use slashing_engine::{SlashingEngine, SlashingEvidence, SlashingEvent, SlashingPenalty};
 pub use orchestrator::ConsensusOrchestrator;
 pub use finality::{FinalizyCertificate, FinalityProof, FinalizityManager, ValidatorSignature};
@@ -50,7 +73,23 @@ pub fn run_consensus_engine() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-pub use block_producer::{BlockProducer, FinalizedBlock, ProducerConfig, start_block_producer, MAX_TXS_PER_BLOCK, BLOCK_INTERVAL_MS};
+pub use block_producer::{BlockProducer, FinalizedBlock, ProducerConfig, ProposerKey, start_block_producer, MAX_TXS_PER_BLOCK, BLOCK_INTERVAL_MS};
+pub use block_execution::{execute_block, execute_block_with_fees, execute_block_with_rewards, production_executor, BlockExecution, FeeContext, RewardContext, TxReceipt};
+pub use block_store::{BlockStore, StoredBlock};
+pub use replay::{replay_range, fork_for_replay, ReplayOptions, ReplayReport};
+pub use storage_fsck::{fsck, FsckIssue, FsckOptions, FsckReport};
+pub use view_change::{NewView, ViewChange, ViewChangeManager, ViewTimeouts};
+pub use pbft_round::{PbftMessage, PbftRound, PbftTimeouts};
+pub use evidence::{Evidence, EvidenceConfig, EvidenceError, EvidencePool};
+pub use equivocation::{
+    decode_slashing_evidence, encode_slashing_evidence, DoubleSignEvidence, EquivocationDetector, SLASHING_EVIDENCE_TOPIC,
+};
+pub use replica::{ReplicaError, ReplicaFollower, SyncStatus};
+pub use partition::{PartitionConfig, PartitionDetector, PartitionStatus, SafeModeEvent};
+pub use chain_audit::{AuditAlert, AuditConfig, AuditStatus, ChainAuditor};
+pub use staking::{BondRecord, BondRequest, BondStatus, StakingParams, StakingTx, STAKING_ACCOUNT};
+pub use consensus_key::{ConsensusConfig, ConsensusKeystore};
+pub use snap_sync::{SnapOutcome, SnapProgress, SnapSyncConfig, SnapSyncError, SnapSyncer, SnapshotServer, StateManifest};
 
 pub mod gossip_bridge;
 pub use gossip_bridge::{GossipBridge, encode_finalized_block, decode_finalized_block};
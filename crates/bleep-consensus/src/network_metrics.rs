@@ -0,0 +1,196 @@
+//! # Network metrics for adaptive consensus
+//!
+//! `AIAdaptiveConsensus` picks a consensus mode from three readings taken
+//! through a `MetricsProvider`.  `TelemetryMetrics` derives them from the
+//! running node:
+//!
+//! * **load** — transaction pool depth as a percentage of its capacity
+//! * **latency** — mean peer round-trip time recorded by `PeerManager`
+//! * **reliability** — regularity of recent block intervals
+//!   (1 − coefficient of variation), scaled by the share of healthy peers
+//!
+//! and, once `with_telemetry` is called, exports each reading to
+//! `bleep_consensus_*` gauges.
+
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+
+use bleep_core::transaction_pool::TransactionPool;
+use bleep_p2p::peer_manager::PeerManager;
+use bleep_telemetry::metrics::{MetricGauge, MetricsRegistry};
+
+/// Block timestamps kept for interval regularity.
+const BLOCK_WINDOW: usize = 32;
+
+/// Source of the readings `AIAdaptiveConsensus` adapts to.
+pub trait MetricsProvider: Send + Sync {
+    /// Network load, 0–100 %.
+    fn network_load(&self) -> u64;
+    /// Average latency between peers, in milliseconds.
+    fn avg_latency(&self) -> u64;
+    /// Network health, 0.0–1.0.
+    fn reliability(&self) -> f64;
+}
+
+/// Constant readings, for simulations and tests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedMetrics {
+    pub load:        u64,
+    pub latency_ms:  u64,
+    pub reliability: f64,
+}
+
+impl Default for FixedMetrics {
+    fn default() -> Self {
+        FixedMetrics { load: 0, latency_ms: 0, reliability: 1.0 }
+    }
+}
+
+impl MetricsProvider for FixedMetrics {
+    fn network_load(&self) -> u64 {
+        self.load
+    }
+
+    fn avg_latency(&self) -> u64 {
+        self.latency_ms
+    }
+
+    fn reliability(&self) -> f64 {
+        self.reliability
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Gauges {
+    load:        MetricGauge,
+    latency_ms:  MetricGauge,
+    reliability: MetricGauge,
+}
+
+/// Readings taken from the peer table, the transaction pool and committed
+/// block timestamps.
+pub struct TelemetryMetrics {
+    peers:       Arc<PeerManager>,
+    pool:        Arc<TransactionPool>,
+    /// Timestamps of the last `BLOCK_WINDOW` committed blocks, oldest first.
+    block_times: Mutex<VecDeque<u64>>,
+    gauges:      Option<Gauges>,
+}
+
+impl TelemetryMetrics {
+    pub fn new(peers: Arc<PeerManager>, pool: Arc<TransactionPool>) -> Self {
+        TelemetryMetrics {
+            peers,
+            pool,
+            block_times: Mutex::new(VecDeque::with_capacity(BLOCK_WINDOW)),
+            gauges: None,
+        }
+    }
+
+    /// Register gauges `bleep_consensus_*`, set on every reading.
+    /// Reliability is exported in per mille.
+    pub fn with_telemetry(mut self, registry: &mut MetricsRegistry) -> Self {
+        self.gauges = Some(Gauges {
+            load:        registry.gauge("bleep_consensus_network_load"),
+            latency_ms:  registry.gauge("bleep_consensus_avg_latency_ms"),
+            reliability: registry.gauge("bleep_consensus_reliability_permille"),
+        });
+        self
+    }
+
+    /// Feed the timestamp of every committed block.
+    pub fn record_block(&self, timestamp: u64) {
+        let mut times = self.block_times.lock();
+        if times.len() == BLOCK_WINDOW {
+            times.pop_front();
+        }
+        times.push_back(timestamp);
+    }
+}
+
+impl MetricsProvider for TelemetryMetrics {
+    fn network_load(&self) -> u64 {
+        let capacity = self.pool.capacity().max(1);
+        // A busy pool is left out of this reading rather than waited on.
+        let depth = self.pool.try_pool_size().unwrap_or(0);
+        let load = (depth.min(capacity) * 100 / capacity) as u64;
+        if let Some(g) = &self.gauges {
+            g.load.set(load as i64);
+        }
+        load
+    }
+
+    fn avg_latency(&self) -> u64 {
+        let latency = self.peers.average_rtt_ms().unwrap_or(0);
+        if let Some(g) = &self.gauges {
+            g.latency_ms.set(latency as i64);
+        }
+        latency
+    }
+
+    fn reliability(&self) -> f64 {
+        let times: Vec<u64> = self.block_times.lock().iter().copied().collect();
+        let total = self.peers.peer_count();
+        let healthy_share = if total == 0 {
+            1.0
+        } else {
+            self.peers.healthy_peers().len() as f64 / total as f64
+        };
+        let reliability = interval_regularity(&times) * healthy_share;
+        if let Some(g) = &self.gauges {
+            g.reliability.set((reliability * 1000.0).round() as i64);
+        }
+        reliability
+    }
+}
+
+/// 1 − coefficient of variation of the gaps between `times`, clamped to
+/// 0.0–1.0.  Fewer than two gaps says nothing and scores 1.0.
+fn interval_regularity(times: &[u64]) -> f64 {
+    if times.len() < 3 {
+        return 1.0;
+    }
+    let gaps: Vec<f64> = times.windows(2)
+        .map(|w| w[1].saturating_sub(w[0]) as f64)
+        .collect();
+    let n = gaps.len() as f64;
+    let mean = gaps.iter().sum::<f64>() / n;
+    if mean <= 0.0 {
+        return 0.0;
+    }
+    let variance = gaps.iter().map(|g| (g - mean) * (g - mean)).sum::<f64>() / n;
+    (1.0 - variance.sqrt() / mean).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_p2p::peer_manager::PeerManagerConfig;
+    use bleep_p2p::types::NodeId;
+
+    #[test]
+    fn readings_follow_pool_and_block_intervals() {
+        let (peers, _events) = PeerManager::new(NodeId::random(), PeerManagerConfig::default());
+        let mut registry = MetricsRegistry::new();
+        let metrics = TelemetryMetrics::new(peers, TransactionPool::new(100))
+            .with_telemetry(&mut registry);
+
+        assert_eq!(metrics.network_load(), 0);
+        assert_eq!(metrics.avg_latency(), 0, "no peer has been measured");
+        assert_eq!(metrics.reliability(), 1.0, "too few blocks to judge");
+
+        for t in [0, 3_000, 6_000, 9_000] {
+            metrics.record_block(t);
+        }
+        assert_eq!(metrics.reliability(), 1.0);
+
+        for t in [9_100, 20_000, 20_050] {
+            metrics.record_block(t);
+        }
+        let r = metrics.reliability();
+        assert!(r < 0.5, "erratic intervals lower reliability, got {r}");
+        assert_eq!(metrics.gauges.as_ref().unwrap().reliability.get(), (r * 1000.0).round() as i64);
+    }
+}
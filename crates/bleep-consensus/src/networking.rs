@@ -1,14 +1,39 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::sync::Mutex as AsyncMutex;
+
 use bleep_core::networking::NetworkingModule as CoreNetworkingModule;
 use bleep_core::block::Block;
 
+use crate::pbft_round::PbftMessage;
+
+/// Carries PBFT messages between validators.
+#[async_trait]
+pub trait PbftLink: Send + Sync {
+    /// Send `msg` to every other validator.
+    fn broadcast(&self, msg: &PbftMessage) -> Result<(), String>;
+    /// Send `msg` to validator `to` only.
+    fn send(&self, to: &str, msg: &PbftMessage) -> Result<(), String>;
+    /// Next message addressed to this validator, waiting at most `timeout`.
+    async fn recv_timeout(&self, timeout: Duration) -> Option<PbftMessage>;
+}
+
 pub struct NetworkingModule {
     inner: CoreNetworkingModule,
+    pbft: RwLock<Option<Arc<dyn PbftLink>>>,
 }
 
 impl NetworkingModule {
     pub fn new() -> Self {
         Self {
-            inner: CoreNetworkingModule::new()
+            inner: CoreNetworkingModule::new(),
+            pbft: RwLock::new(None),
         }
     }
 
@@ -24,9 +49,261 @@ impl NetworkingModule {
             block.index,
             block.index
         );
-        
+
         // Broadcast block to all connected peers
         self.inner.broadcast_block(block)
             .map_err(|e| format!("Failed to broadcast block from leader {}: {:?}", leader_id, e))
     }
+
+    /// Route PBFT rounds through `link`.
+    pub fn attach_pbft_link(&self, link: Arc<dyn PbftLink>) {
+        *self.pbft.write() = Some(link);
+    }
+
+    pub fn broadcast_pbft(&self, msg: &PbftMessage) -> Result<(), String> {
+        match self.pbft.read().as_ref() {
+            Some(link) => link.broadcast(msg),
+            None => Err("no PBFT link attached".to_string()),
+        }
+    }
+
+    pub fn send_pbft(&self, to: &str, msg: &PbftMessage) -> Result<(), String> {
+        match self.pbft.read().as_ref() {
+            Some(link) => link.send(to, msg),
+            None => Err("no PBFT link attached".to_string()),
+        }
+    }
+
+    /// Next PBFT message, or `None` after `timeout` (at once if no link is attached).
+    pub async fn recv_pbft(&self, timeout: Duration) -> Option<PbftMessage> {
+        let link = self.pbft.read().clone()?;
+        link.recv_timeout(timeout).await
+    }
+}
+
+impl Default for NetworkingModule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ── In-process PBFT network ───────────────────────────────────────────────────
+
+#[derive(Default)]
+struct Hub {
+    inboxes: BTreeMap<String, mpsc::UnboundedSender<PbftMessage>>,
+    crashed: HashSet<String>,
+    /// Deliveries a validator has left before it crashes.
+    budgets: HashMap<String, usize>,
+}
+
+impl Hub {
+    /// Deliver `msg` from `from` to `to`, spending `from`'s crash budget.
+    fn deliver(&mut self, from: &str, to: &str, msg: &PbftMessage) {
+        if from == to || self.crashed.contains(from) || self.crashed.contains(to) {
+            return;
+        }
+        if let Some(left) = self.budgets.get_mut(from) {
+            if *left == 0 {
+                self.crashed.insert(from.to_string());
+                return;
+            }
+            *left -= 1;
+        }
+        if let Some(tx) = self.inboxes.get(to) {
+            let _ = tx.send(msg.clone());
+        }
+    }
+}
+
+/// Connects validators in one process, for simulations and tests.  A
+/// crashed validator neither sends nor receives anything.
+#[derive(Clone, Default)]
+pub struct LocalPbftHub {
+    hub: Arc<Mutex<Hub>>,
+}
+
+impl LocalPbftHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn join(&self, validator_id: &str) -> Arc<dyn PbftLink> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.hub.lock().inboxes.insert(validator_id.to_string(), tx);
+        Arc::new(LocalPbftLink {
+            validator_id: validator_id.to_string(),
+            hub:          self.hub.clone(),
+            inbox:        AsyncMutex::new(rx),
+        })
+    }
+
+    pub fn crash(&self, validator_id: &str) {
+        self.hub.lock().crashed.insert(validator_id.to_string());
+    }
+
+    /// Crash `validator_id` once `deliveries` more of its messages reached a
+    /// peer; peers are served in id order, so a broadcast can be cut short.
+    pub fn crash_after(&self, validator_id: &str, deliveries: usize) {
+        self.hub.lock().budgets.insert(validator_id.to_string(), deliveries);
+    }
+}
+
+struct LocalPbftLink {
+    validator_id: String,
+    hub:          Arc<Mutex<Hub>>,
+    inbox:        AsyncMutex<mpsc::UnboundedReceiver<PbftMessage>>,
+}
+
+/// Next message in `inbox`, or `None` after `timeout`.
+async fn recv_within(inbox: &AsyncMutex<mpsc::UnboundedReceiver<PbftMessage>>, timeout: Duration) -> Option<PbftMessage> {
+    let mut inbox = inbox.lock().await;
+    tokio::time::timeout(timeout, inbox.recv()).await.ok().flatten()
+}
+
+#[async_trait]
+impl PbftLink for LocalPbftLink {
+    fn broadcast(&self, msg: &PbftMessage) -> Result<(), String> {
+        let mut hub = self.hub.lock();
+        let peers: Vec<String> = hub.inboxes.keys().cloned().collect();
+        for peer in peers {
+            hub.deliver(&self.validator_id, &peer, msg);
+        }
+        Ok(())
+    }
+
+    fn send(&self, to: &str, msg: &PbftMessage) -> Result<(), String> {
+        self.hub.lock().deliver(&self.validator_id, to, msg);
+        Ok(())
+    }
+
+    async fn recv_timeout(&self, timeout: Duration) -> Option<PbftMessage> {
+        let crashed = self.hub.lock().crashed.contains(&self.validator_id);
+        if crashed {
+            tokio::time::sleep(timeout).await;
+            return None;
+        }
+        recv_within(&self.inbox, timeout).await
+    }
+}
+
+// ── PBFT over gossip ──────────────────────────────────────────────────────────
+
+/// Gossip topic PBFT messages travel under.
+pub const PBFT_TOPIC: &str = "pbft";
+
+/// Hands an encoded PBFT envelope to the node's gossip, e.g. a
+/// `P2PNode::broadcast` under `PBFT_TOPIC`.
+pub type PbftGossip = Arc<dyn Fn(Vec<u8>) + Send + Sync>;
+
+/// A PBFT message, who sent it and who it is for; `None` is everyone.
+#[derive(Serialize, Deserialize)]
+struct PbftEnvelope {
+    from:    String,
+    to:      Option<String>,
+    message: PbftMessage,
+}
+
+/// Connects validator nodes through the p2p gossip.  Every message goes
+/// out as a broadcast; the node's inbound handler passes what arrives under
+/// `PBFT_TOPIC` to `deliver`, which keeps what is for this validator.  The
+/// messages carry their own signatures, so the envelope is not signed.
+pub struct GossipPbftLink {
+    validator_id: String,
+    gossip:       PbftGossip,
+    inbox_tx:     mpsc::UnboundedSender<PbftMessage>,
+    inbox:        AsyncMutex<mpsc::UnboundedReceiver<PbftMessage>>,
+}
+
+impl GossipPbftLink {
+    pub fn new(validator_id: String, gossip: PbftGossip) -> Self {
+        let (inbox_tx, inbox) = mpsc::unbounded_channel();
+        Self { validator_id, gossip, inbox_tx, inbox: AsyncMutex::new(inbox) }
+    }
+
+    /// Take in a gossiped envelope.  Returns whether it was for this
+    /// validator; our own broadcasts and messages for others are dropped.
+    pub fn deliver(&self, payload: &[u8]) -> Result<bool, String> {
+        let envelope: PbftEnvelope = bincode::deserialize(payload)
+            .map_err(|e| format!("bad PBFT envelope: {}", e))?;
+        if envelope.from == self.validator_id
+            || envelope.to.as_deref().is_some_and(|to| to != self.validator_id)
+        {
+            return Ok(false);
+        }
+        self.inbox_tx.send(envelope.message).map_err(|e| e.to_string())?;
+        Ok(true)
+    }
+
+    fn gossip(&self, to: Option<&str>, msg: &PbftMessage) -> Result<(), String> {
+        let envelope = PbftEnvelope {
+            from:    self.validator_id.clone(),
+            to:      to.map(str::to_string),
+            message: msg.clone(),
+        };
+        let bytes = bincode::serialize(&envelope).map_err(|e| format!("PBFT envelope: {}", e))?;
+        (self.gossip)(bytes);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PbftLink for GossipPbftLink {
+    fn broadcast(&self, msg: &PbftMessage) -> Result<(), String> {
+        self.gossip(None, msg)
+    }
+
+    fn send(&self, to: &str, msg: &PbftMessage) -> Result<(), String> {
+        self.gossip(Some(to), msg)
+    }
+
+    async fn recv_timeout(&self, timeout: Duration) -> Option<PbftMessage> {
+        recv_within(&self.inbox, timeout).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pbft_round::{VotePhase, VoteRequest};
+
+    fn request(requester: &str) -> PbftMessage {
+        PbftMessage::VoteRequest(VoteRequest {
+            height:     1,
+            view:       0,
+            phase:      VotePhase::Prepare,
+            block_hash: "h".into(),
+            requester:  requester.into(),
+        })
+    }
+
+    #[tokio::test]
+    async fn gossip_link_keeps_only_what_is_addressed_to_it() {
+        let sent = Arc::new(Mutex::new(Vec::<Vec<u8>>::new()));
+        let gossip = |sent: &Arc<Mutex<Vec<Vec<u8>>>>| -> PbftGossip {
+            let sent = Arc::clone(sent);
+            Arc::new(move |bytes: Vec<u8>| sent.lock().push(bytes))
+        };
+        let (a, b) = (GossipPbftLink::new("a".into(), gossip(&sent)), GossipPbftLink::new("b".into(), gossip(&sent)));
+
+        a.broadcast(&request("a")).unwrap();
+        a.send("b", &request("a")).unwrap();
+        a.send("c", &request("a")).unwrap();
+        let out: Vec<Vec<u8>> = std::mem::take(&mut *sent.lock());
+        assert_eq!(out.len(), 3);
+
+        assert_eq!(a.deliver(&out[0]), Ok(false), "own broadcast");
+        assert_eq!(b.deliver(&out[0]), Ok(true));
+        assert_eq!(b.deliver(&out[1]), Ok(true));
+        assert_eq!(b.deliver(&out[2]), Ok(false), "for c");
+        assert!(b.deliver(b"garbage").is_err());
+
+        for _ in 0..2 {
+            match b.recv_timeout(Duration::from_millis(10)).await {
+                Some(PbftMessage::VoteRequest(r)) => assert_eq!(r.requester, "a"),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert!(b.recv_timeout(Duration::from_millis(10)).await.is_none());
+    }
 }
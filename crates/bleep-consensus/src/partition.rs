@@ -0,0 +1,345 @@
+//! # Partition detection and safe mode
+//!
+//! A validator cut off from most of the network must stop finalizing
+//! rather than build a minority fork it will later throw away.
+//! `PartitionDetector` records when each validator was last heard from
+//! (blocks, votes, gossip heartbeats) and weighs that by stake:
+//!
+//! ```text
+//! connectivity = stake(self + validators heard within window_ms) / total stake
+//!
+//! tick(now) once per window
+//!   Normal   ── connectivity <  enter_below for enter_windows ticks ──▶ SafeMode
+//!   SafeMode ── connectivity >= exit_above  for exit_windows ticks  ──▶ Normal
+//! ```
+//!
+//! In safe mode the node neither proposes nor votes (`may_participate`); it
+//! keeps syncing and serving read RPC, and `/rpc/ready` reports it as
+//! degraded with the reason.  Requiring several consecutive low windows
+//! keeps a brief restart of one large peer from tripping safe mode, and
+//! `exit_above > enter_below` keeps a flapping link from toggling it.
+//! Entry and exit are written to the audit trail.
+//!
+//! Time is passed in explicitly (`now_ms`), as in `view_change`.
+
+use std::collections::HashMap;
+
+use bleep_auth::audit_trail::{AuditAction, AuditOutcome, AuditTrail};
+use log::{info, warn};
+use parking_lot::Mutex as PLMutex;
+use serde::{Deserialize, Serialize};
+
+use crate::block_producer::BLOCK_INTERVAL_MS;
+use crate::validator_identity::ValidatorRegistry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PartitionConfig {
+    /// Length of one window; a validator counts as connected if heard from
+    /// within the last window.
+    pub window_ms:     u64,
+    /// Enter safe mode below this fraction of stake ...
+    pub enter_below:   f64,
+    /// ... seen in this many consecutive windows.
+    pub enter_windows: u32,
+    /// Leave safe mode at or above this fraction of stake ...
+    pub exit_above:    f64,
+    /// ... seen in this many consecutive windows.
+    pub exit_windows:  u32,
+}
+
+impl Default for PartitionConfig {
+    fn default() -> Self {
+        PartitionConfig {
+            window_ms:     BLOCK_INTERVAL_MS * 2,
+            enter_below:   2.0 / 3.0,
+            enter_windows: 3,
+            exit_above:    0.75,
+            exit_windows:  3,
+        }
+    }
+}
+
+/// What `/rpc/ready` reports about partition safe mode.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PartitionStatus {
+    pub safe_mode:    bool,
+    /// Connectivity measured at the last tick.
+    pub connectivity: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason:       Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since_ms:     Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SafeModeEvent {
+    Entered { connectivity: f64, reason: String },
+    Exited { connectivity: f64 },
+}
+
+struct Inner {
+    stakes:       HashMap<String, u128>,
+    last_heard:   HashMap<String, u64>,
+    low_windows:  u32,
+    high_windows: u32,
+    status:       PartitionStatus,
+}
+
+/// Tracks stake-weighted connectivity and the safe-mode state.  Shared by
+/// the gossip handlers that report contacts, the block producer and RPC.
+pub struct PartitionDetector {
+    self_id: String,
+    config:  PartitionConfig,
+    inner:   PLMutex<Inner>,
+    audit:   Option<AuditTrail>,
+}
+
+impl PartitionDetector {
+    pub fn new(self_id: String, stakes: HashMap<String, u128>, config: PartitionConfig) -> Self {
+        PartitionDetector {
+            self_id,
+            config,
+            inner: PLMutex::new(Inner {
+                stakes,
+                last_heard:   HashMap::new(),
+                low_windows:  0,
+                high_windows: 0,
+                status:       PartitionStatus { safe_mode: false, connectivity: 1.0, reason: None, since_ms: None },
+            }),
+            audit: None,
+        }
+    }
+
+    /// Record safe-mode entry and exit in the operator audit trail.
+    pub fn with_audit_trail(mut self, trail: AuditTrail) -> Self {
+        self.audit = Some(trail);
+        self
+    }
+
+    pub fn config(&self) -> &PartitionConfig {
+        &self.config
+    }
+
+    /// Replace the validator set connectivity is measured against.
+    pub fn set_stakes(&self, stakes: HashMap<String, u128>) {
+        self.inner.lock().stakes = stakes;
+    }
+
+    /// Effective stake of every active validator in `registry`.
+    pub fn stakes_from(registry: &ValidatorRegistry) -> HashMap<String, u128> {
+        registry.get_active_validators().into_iter().map(|v| (v.id.clone(), v.effective_stake())).collect()
+    }
+
+    /// A block, vote or heartbeat from `validator` arrived.
+    pub fn heard_from(&self, validator: &str, now_ms: u64) {
+        let mut inner = self.inner.lock();
+        let last = inner.last_heard.entry(validator.to_string()).or_insert(now_ms);
+        *last = (*last).max(now_ms);
+    }
+
+    /// Fraction of stake (this node included) heard from within the window.
+    pub fn connectivity(&self, now_ms: u64) -> f64 {
+        self.measure(&self.inner.lock(), now_ms)
+    }
+
+    fn measure(&self, inner: &Inner, now_ms: u64) -> f64 {
+        let total: u128 = inner.stakes.values().sum();
+        if total == 0 {
+            return 1.0;
+        }
+        let connected: u128 = inner
+            .stakes
+            .iter()
+            .filter(|(id, _)| {
+                **id == self.self_id
+                    || inner.last_heard.get(*id).map_or(false, |t| now_ms.saturating_sub(*t) < self.config.window_ms)
+            })
+            .map(|(_, stake)| stake)
+            .sum();
+        connected as f64 / total as f64
+    }
+
+    /// Close a window: measure connectivity and move in or out of safe
+    /// mode.  Call once every `window_ms`.
+    pub fn tick(&self, now_ms: u64) -> Option<SafeModeEvent> {
+        let mut inner = self.inner.lock();
+        let c = self.measure(&inner, now_ms);
+        inner.status.connectivity = c;
+
+        let event = if !inner.status.safe_mode {
+            inner.low_windows = if c < self.config.enter_below { inner.low_windows + 1 } else { 0 };
+            if inner.low_windows < self.config.enter_windows {
+                return None;
+            }
+            let reason = format!(
+                "connected to {:.0}% of validator stake, below {:.0}% for {} windows",
+                c * 100.0, self.config.enter_below * 100.0, inner.low_windows,
+            );
+            inner.low_windows = 0;
+            inner.status = PartitionStatus { safe_mode: true, connectivity: c, reason: Some(reason.clone()), since_ms: Some(now_ms) };
+            SafeModeEvent::Entered { connectivity: c, reason }
+        } else {
+            inner.high_windows = if c >= self.config.exit_above { inner.high_windows + 1 } else { 0 };
+            if inner.high_windows < self.config.exit_windows {
+                return None;
+            }
+            inner.high_windows = 0;
+            inner.status = PartitionStatus { safe_mode: false, connectivity: c, reason: None, since_ms: None };
+            SafeModeEvent::Exited { connectivity: c }
+        };
+        drop(inner);
+
+        let params = match &event {
+            SafeModeEvent::Entered { connectivity, reason } => {
+                warn!("[Partition] Entering safe mode: {}", reason);
+                serde_json::json!({ "event": "entered", "connectivity": connectivity, "reason": reason })
+            }
+            SafeModeEvent::Exited { connectivity } => {
+                info!("[Partition] Leaving safe mode at {:.0}% connectivity", connectivity * 100.0);
+                serde_json::json!({ "event": "exited", "connectivity": connectivity })
+            }
+        };
+        if let Some(trail) = &self.audit {
+            trail.append(&self.self_id, AuditAction::SafeMode, &params, AuditOutcome::Success);
+        }
+        Some(event)
+    }
+
+    pub fn is_safe_mode(&self) -> bool {
+        self.inner.lock().status.safe_mode
+    }
+
+    /// Whether this node may propose blocks and vote.
+    pub fn may_participate(&self) -> bool {
+        !self.is_safe_mode()
+    }
+
+    pub fn status(&self) -> PartitionStatus {
+        self.inner.lock().status.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    /// Validators `v0..v{n}`, each with its own detector.  Every window each
+    /// running node hears from every running node on its side of the
+    /// partition; the window's proposer builds a block if it may participate
+    /// and its side holds a 2/3 quorum of stake.
+    struct TestCluster {
+        ids:      Vec<String>,
+        stakes:   HashMap<String, u128>,
+        nodes:    Vec<PartitionDetector>,
+        minority: HashSet<usize>,
+        down:     HashSet<usize>,
+        now:      u64,
+        height:   u64,
+        produced: Vec<u64>,
+    }
+
+    impl TestCluster {
+        fn new(stakes: &[u128]) -> Self {
+            let ids: Vec<String> = (0..stakes.len()).map(|i| format!("v{}", i)).collect();
+            let stakes: HashMap<String, u128> = ids.iter().cloned().zip(stakes.iter().copied()).collect();
+            let nodes = ids.iter().map(|id| {
+                PartitionDetector::new(id.clone(), stakes.clone(), PartitionConfig::default())
+            }).collect();
+            TestCluster {
+                produced: vec![0; ids.len()],
+                ids, stakes, nodes,
+                minority: HashSet::new(), down: HashSet::new(), now: 0, height: 0,
+            }
+        }
+
+        fn partition(&mut self, minority: &[usize]) {
+            self.minority = minority.iter().copied().collect();
+        }
+
+        fn heal(&mut self) {
+            self.minority.clear();
+        }
+
+        fn linked(&self, a: usize, b: usize) -> bool {
+            !self.down.contains(&a) && !self.down.contains(&b)
+                && self.minority.contains(&a) == self.minority.contains(&b)
+        }
+
+        /// One window of gossip, one tick, one proposal slot.
+        fn window(&mut self) -> Vec<Option<SafeModeEvent>> {
+            self.now += PartitionConfig::default().window_ms;
+            for a in 0..self.nodes.len() {
+                for b in 0..self.nodes.len() {
+                    if a != b && self.linked(a, b) {
+                        self.nodes[a].heard_from(&self.ids[b], self.now);
+                    }
+                }
+            }
+            let events = self.nodes.iter().map(|n| n.tick(self.now)).collect();
+
+            let proposer = (self.now / PartitionConfig::default().window_ms) as usize % self.nodes.len();
+            let total: u128 = self.stakes.values().sum();
+            let voting: u128 = (0..self.nodes.len())
+                .filter(|v| *v == proposer || self.linked(proposer, *v))
+                .filter(|v| self.nodes[*v].may_participate())
+                .map(|v| self.stakes[&self.ids[v]])
+                .sum();
+            if self.nodes[proposer].may_participate() && voting * 3 >= total * 2 {
+                self.height += 1;
+                self.produced[proposer] += 1;
+            }
+            events
+        }
+    }
+
+    #[test]
+    fn minority_enters_safe_mode_until_the_partition_heals() {
+        let mut c = TestCluster::new(&[100; 5]);
+        c.window();
+        c.partition(&[4]);
+
+        for _ in 0..2 {
+            assert!(c.window().iter().all(Option::is_none), "two low windows are not enough");
+        }
+        let events = c.window();
+        assert!(matches!(events[4], Some(SafeModeEvent::Entered { .. })));
+        assert!(events[..4].iter().all(Option::is_none), "the majority stays normal");
+        let status = c.nodes[4].status();
+        assert!(status.safe_mode);
+        assert!(status.reason.unwrap().contains("20%"));
+
+        let (height, produced) = (c.height, c.produced[4]);
+        for _ in 0..10 {
+            c.window();
+        }
+        assert_eq!(c.produced[4], produced, "safe mode produces nothing");
+        assert_eq!(c.height, height + 8, "the majority finalizes every other slot");
+
+        c.heal();
+        for _ in 0..2 {
+            c.window();
+            assert!(c.nodes[4].is_safe_mode(), "hysteresis holds safe mode");
+        }
+        assert!(matches!(c.window()[4], Some(SafeModeEvent::Exited { .. })));
+        let status = c.nodes[4].status();
+        assert!(!status.safe_mode && status.reason.is_none());
+        assert_eq!(status.connectivity, 1.0);
+    }
+
+    #[test]
+    fn brief_restart_of_a_large_peer_is_tolerated() {
+        let mut c = TestCluster::new(&[100, 100, 400]);
+        c.window();
+        c.down.insert(2);
+        for _ in 0..2 {
+            c.window();
+        }
+        assert!(c.nodes[0].connectivity(c.now) < 0.5);
+        c.down.clear();
+        for _ in 0..5 {
+            assert!(c.window().iter().all(Option::is_none));
+        }
+        assert!(c.nodes.iter().all(PartitionDetector::may_participate));
+    }
+}
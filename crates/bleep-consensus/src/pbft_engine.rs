@@ -72,7 +72,7 @@ pub struct PbftConsensusEngine {
     total_validators:  usize,
     quorum_size:       usize,
     finalized_blocks:  HashMap<u64, PbftBlockState>,
-    #[allow(dead_code)]
+    /// Advanced by `view_change::ViewChangeManager` when a NewView is accepted.
     current_view:      u64,
 
     /// H-02 FIX: per-block prepare-vote accumulators.
@@ -231,6 +231,29 @@ impl PbftConsensusEngine {
         Ok(())
     }
 
+    /// Current PBFT view.
+    pub fn current_view(&self) -> u64 {
+        self.current_view
+    }
+
+    /// Move to `view` after a view change at `block_height`.
+    ///
+    /// Votes gathered in the abandoned view are discarded so the NewView
+    /// proposal can be pre-prepared afresh.  A committed block is final and
+    /// is left alone.
+    pub fn enter_view(&mut self, block_height: u64, view: u64) {
+        if view <= self.current_view {
+            return;
+        }
+        self.current_view = view;
+        if !self.is_finalized(block_height) {
+            self.finalized_blocks.remove(&block_height);
+            self.prepare_votes.remove(&block_height);
+            self.commit_votes.remove(&block_height);
+        }
+        info!("PBFT: Entered view {} at block {}", view, block_height);
+    }
+
     /// Returns how many distinct prepare votes have been received for `block_height`.
     pub fn prepare_vote_count(&self, block_height: u64) -> usize {
         self.prepare_votes.get(&block_height).map_or(0, |v| v.len())
@@ -464,6 +487,22 @@ mod tests {
         assert_eq!(engine.block_state(300), Some(PbftBlockState::Proposed));
     }
 
+    #[test]
+    fn test_pbft_enter_view_resets_uncommitted_height() {
+        let ids = validators(&["v1", "v2", "v3", "v4"]);
+        let mut engine = PbftConsensusEngine::new("v1".into(), ids).unwrap();
+        let block = Block::new(7, vec![], "prev".into());
+        engine.pre_prepare(7, &block).unwrap();
+        engine.process_prepare(7, "v1").unwrap();
+
+        engine.enter_view(7, 1);
+        assert_eq!(engine.current_view(), 1);
+        assert_eq!(engine.block_state(7), None);
+        assert_eq!(engine.prepare_vote_count(7), 0);
+        // The NewView proposal can now enter the pipeline.
+        assert!(engine.pre_prepare(7, &block).is_ok());
+    }
+
     #[test]
     fn test_pbft_health_status_perfect() {
         let mut engine = PbftConsensusEngine::new(
@@ -0,0 +1,654 @@
+//! # PBFT rounds over the network
+//!
+//! Runs one height of PBFT between validators, exchanging signed messages
+//! through `NetworkingModule`:
+//!
+//! ```text
+//!   leader(h, v)  ──► Proposal{h, v, block}
+//!   every member  ──► Vote{Prepare, h, v, hash}   2f+1 ──► prepared
+//!   every member  ──► Vote{Commit,  h, v, hash}   2f+1 ──► decided
+//!
+//!   phase timer expires ──► ViewChange{h, v+1} ──► leader(h, v+1) sends NewView ──► Prepare …
+//! ```
+//!
+//! Entering a voting phase also broadcasts a `VoteRequest`; validators that
+//! already voted in it answer with their signed vote, so one lost broadcast
+//! does not cost a view.  Only validly signed votes of distinct members of
+//! the set count, and how long each member took to answer is kept for the
+//! adaptive logic (`vote_latencies`).
+//!
+//! Waiting for the proposal uses the view timer of `ViewChangeManager`;
+//! the prepare and commit phases have timers of their own that double per
+//! view the same way.  A leader that crashes mid-round therefore costs one
+//! timeout: the survivors certify view `v + 1`, led by the next member of
+//! the active set in rotation, and decide there.
+//!
+//! `PbftRound::run` is async: waiting for messages yields to the runtime
+//! instead of blocking the worker thread it runs on.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use bleep_core::block::Block;
+use bleep_crypto::tx_signer::{sign_tx_payload, verify_tx_signature};
+
+use crate::block_producer::BLOCK_INTERVAL_MS;
+use crate::engine::ConsensusError;
+use crate::networking::NetworkingModule;
+use crate::validator_set::ValidatorSet;
+use crate::view_change::{NewView, ViewChange, ViewChangeManager, ViewTimeouts};
+
+/// Domain prefixes so consensus signatures never validate as anything else.
+const PROPOSAL_DOMAIN: &[u8] = b"BLEEP PBFT Proposal";
+const VOTE_DOMAIN: &[u8]     = b"BLEEP PBFT Vote";
+
+/// Longest a receive waits before the phase timers are checked again.
+const POLL: Duration = Duration::from_millis(20);
+
+// ── Messages ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VotePhase {
+    Prepare,
+    Commit,
+}
+
+/// The leader's block for `view` at `height`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    pub height:    u64,
+    pub view:      u64,
+    pub leader:    String,
+    pub block:     Block,
+    pub signature: Vec<u8>,
+}
+
+impl Proposal {
+    pub fn payload(&self) -> [u8; 32] {
+        let mut h = Sha3_256::new();
+        h.update(PROPOSAL_DOMAIN);
+        h.update(self.height.to_le_bytes());
+        h.update(self.view.to_le_bytes());
+        h.update(self.block.compute_hash().as_bytes());
+        h.update(self.leader.as_bytes());
+        h.finalize().into()
+    }
+}
+
+/// A prepare or commit vote for the block hashing to `block_hash`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Vote {
+    pub phase:        VotePhase,
+    pub height:       u64,
+    pub view:         u64,
+    pub block_hash:   String,
+    pub validator_id: String,
+    pub signature:    Vec<u8>,
+}
+
+impl Vote {
+    pub fn payload(&self) -> [u8; 32] {
+        let mut h = Sha3_256::new();
+        h.update(VOTE_DOMAIN);
+        h.update([self.phase as u8]);
+        h.update(self.height.to_le_bytes());
+        h.update(self.view.to_le_bytes());
+        h.update(self.block_hash.as_bytes());
+        h.update(self.validator_id.as_bytes());
+        h.finalize().into()
+    }
+}
+
+/// "Send me your vote on `block_hash` in `phase`, if you cast one."
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteRequest {
+    pub height:     u64,
+    pub view:       u64,
+    pub phase:      VotePhase,
+    pub block_hash: String,
+    pub requester:  String,
+}
+
+/// Everything validators send each other during a round.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PbftMessage {
+    Proposal(Proposal),
+    VoteRequest(VoteRequest),
+    Vote(Vote),
+    ViewChange(ViewChange),
+    NewView(NewView),
+}
+
+// ── Timeouts ──────────────────────────────────────────────────────────────────
+
+/// Per-phase timers of a round.  `prepare_ms` and `commit_ms` apply to
+/// view 0 and double per view like `proposal`, capped at `proposal.max_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PbftTimeouts {
+    /// Wait for the leader's proposal, or for the NewView after a view change.
+    pub proposal:   ViewTimeouts,
+    /// Wait for 2f+1 prepares once a proposal is accepted.
+    pub prepare_ms: u64,
+    /// Wait for 2f+1 commits once prepared.
+    pub commit_ms:  u64,
+    /// Views tried before the round gives up.
+    pub max_views:  u64,
+}
+
+impl Default for PbftTimeouts {
+    fn default() -> Self {
+        PbftTimeouts {
+            proposal:   ViewTimeouts::default(),
+            prepare_ms: BLOCK_INTERVAL_MS,
+            commit_ms:  BLOCK_INTERVAL_MS,
+            max_views:  8,
+        }
+    }
+}
+
+impl PbftTimeouts {
+    fn phase(&self, base_ms: u64, view: u64) -> u64 {
+        ViewTimeouts { base_ms, max_ms: self.proposal.max_ms }.timeout_for(view)
+    }
+}
+
+// ── Round ─────────────────────────────────────────────────────────────────────
+
+/// Votes being gathered for `block` in the current view.
+struct Collecting {
+    block:        Block,
+    hash:         String,
+    requested_at: u64,
+    deadline:     u64,
+}
+
+enum Phase {
+    /// Waiting for the current view's proposal, or for its NewView.
+    AwaitProposal,
+    Prepare(Collecting),
+    Commit(Collecting),
+}
+
+/// One validator's side of PBFT for a single height.
+pub struct PbftRound<'a> {
+    validator_id: String,
+    secret_key:   &'a [u8],
+    set:          ValidatorSet,
+    public_keys:  HashMap<String, Vec<u8>>,
+    net:          &'a NetworkingModule,
+    timeouts:     PbftTimeouts,
+    views:        ViewChangeManager,
+    phase:        Phase,
+    /// (view, phase, block hash) → voter → arrival time.
+    votes:        HashMap<(u64, VotePhase, String), HashMap<String, u64>>,
+    /// Votes this validator cast, kept to answer `VoteRequest`s.
+    cast:         HashMap<(u64, VotePhase), Vote>,
+    latencies:    HashMap<String, u64>,
+    started:      Instant,
+}
+
+impl<'a> PbftRound<'a> {
+    /// `public_keys` maps every member of `set` to its SPHINCS+ public key.
+    pub fn new(
+        validator_id: String,
+        secret_key:   &'a [u8],
+        set:          ValidatorSet,
+        public_keys:  HashMap<String, Vec<u8>>,
+        height:       u64,
+        net:          &'a NetworkingModule,
+        timeouts:     PbftTimeouts,
+    ) -> Self {
+        let views = ViewChangeManager::new(
+            validator_id.clone(),
+            secret_key.to_vec(),
+            set.clone(),
+            public_keys.clone(),
+            height,
+            0,
+        )
+        .with_timeouts(timeouts.proposal);
+        PbftRound {
+            validator_id,
+            secret_key,
+            set,
+            public_keys,
+            net,
+            timeouts,
+            views,
+            phase: Phase::AwaitProposal,
+            votes: HashMap::new(),
+            cast: HashMap::new(),
+            latencies: HashMap::new(),
+            started: Instant::now(),
+        }
+    }
+
+    fn now(&self) -> u64 {
+        self.started.elapsed().as_millis() as u64
+    }
+
+    /// Run the round until 2f+1 validators committed a block, and return
+    /// it.  `candidate` is what this validator proposes in any view it
+    /// leads, unless a view change carries a block already prepared.
+    pub async fn run(&mut self, candidate: &Block) -> Result<Block, ConsensusError> {
+        let height = self.views.height();
+        if self.views.leader(0) == Some(self.validator_id.as_str()) {
+            if let Some(block) = self.propose(candidate)? {
+                return Ok(self.decided(block));
+            }
+        }
+        loop {
+            if let Some(block) = self.check_timers(candidate)? {
+                return Ok(self.decided(block));
+            }
+            if self.views.view() >= self.timeouts.max_views {
+                return Err(ConsensusError::Other(format!(
+                    "height {} undecided after {} views", height, self.views.view()
+                )));
+            }
+            let msg = match self.net.recv_pbft(POLL).await {
+                Some(m) => m,
+                None => continue,
+            };
+            if let Some(block) = self.handle(msg, candidate)? {
+                return Ok(self.decided(block));
+            }
+        }
+    }
+
+    /// Milliseconds between this validator's `VoteRequest` and each other
+    /// validator's vote, for the latest phase that validator answered.
+    pub fn vote_latencies(&self) -> &HashMap<String, u64> {
+        &self.latencies
+    }
+
+    fn decided(&self, block: Block) -> Block {
+        info!(
+            "[PBFT] Height {} committed in view {} ({})",
+            block.index, self.views.view(), block.compute_hash()
+        );
+        block
+    }
+
+    fn propose(&mut self, candidate: &Block) -> Result<Option<Block>, ConsensusError> {
+        let view = self.views.view();
+        let mut proposal = Proposal {
+            height:    self.views.height(),
+            view,
+            leader:    self.validator_id.clone(),
+            block:     candidate.clone(),
+            signature: Vec::new(),
+        };
+        proposal.signature = self.sign(&proposal.payload())?;
+        self.views.on_proposal(view, &self.validator_id, candidate)?;
+        self.broadcast(PbftMessage::Proposal(proposal))?;
+        self.enter_prepare(candidate.clone())
+    }
+
+    fn check_timers(&mut self, candidate: &Block) -> Result<Option<Block>, ConsensusError> {
+        let now = self.now();
+        let vc = match &self.phase {
+            Phase::AwaitProposal => self.views.on_tick(now)?,
+            Phase::Prepare(c) | Phase::Commit(c) if now >= c.deadline => {
+                Some(self.views.abandon_view(now)?)
+            }
+            _ => None,
+        };
+        let vc = match vc {
+            Some(vc) => vc,
+            None => return Ok(None),
+        };
+        self.phase = Phase::AwaitProposal;
+        self.broadcast(PbftMessage::ViewChange(vc.clone()))?;
+        // Our own view change may be the one completing a quorum.
+        self.on_view_change(vc, candidate)
+    }
+
+    fn handle(&mut self, msg: PbftMessage, candidate: &Block) -> Result<Option<Block>, ConsensusError> {
+        let height = self.views.height();
+        match msg {
+            PbftMessage::Proposal(p) => {
+                if p.height != height || p.block.index != height || !matches!(self.phase, Phase::AwaitProposal) {
+                    return Ok(None);
+                }
+                if !self.verify(&p.leader, &p.payload(), &p.signature) {
+                    warn!("[PBFT] Dropping proposal with a bad signature from {}", p.leader);
+                    return Ok(None);
+                }
+                match self.views.on_proposal(p.view, &p.leader, &p.block) {
+                    Ok(()) => self.enter_prepare(p.block),
+                    Err(e) => {
+                        debug!("[PBFT] Ignoring proposal from {}: {}", p.leader, e);
+                        Ok(None)
+                    }
+                }
+            }
+            PbftMessage::VoteRequest(r) => {
+                if r.height != height || !self.set.contains(&r.requester) {
+                    return Ok(None);
+                }
+                if let Some(vote) = self.cast.get(&(r.view, r.phase)).filter(|v| v.block_hash == r.block_hash) {
+                    if let Err(e) = self.net.send_pbft(&r.requester, &PbftMessage::Vote(vote.clone())) {
+                        debug!("[PBFT] Could not answer vote request from {}: {}", r.requester, e);
+                    }
+                }
+                Ok(None)
+            }
+            PbftMessage::Vote(v) => {
+                if v.height != height {
+                    return Ok(None);
+                }
+                let key = (v.view, v.phase, v.block_hash.clone());
+                if self.votes.get(&key).is_some_and(|voters| voters.contains_key(&v.validator_id)) {
+                    debug!("[PBFT] Duplicate {:?} vote from {}", v.phase, v.validator_id);
+                    return Ok(None);
+                }
+                if !self.verify(&v.validator_id, &v.payload(), &v.signature) {
+                    warn!("[PBFT] Dropping {:?} vote with a bad signature from {}", v.phase, v.validator_id);
+                    return Ok(None);
+                }
+                let now = self.now();
+                self.record_latency(&v, now);
+                self.votes.entry(key).or_default().insert(v.validator_id, now);
+                self.advance()
+            }
+            PbftMessage::ViewChange(vc) => self.on_view_change(vc, candidate),
+            PbftMessage::NewView(nv) => {
+                if nv.view <= self.views.view() && !self.views.state().in_view_change {
+                    return Ok(None);
+                }
+                let now = self.now();
+                match self.views.on_new_view(&nv, now) {
+                    Ok(block) => self.enter_prepare(block),
+                    Err(e) => {
+                        debug!("[PBFT] Ignoring new-view {} from {}: {}", nv.view, nv.leader, e);
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record a view change; if it gives this validator a quorum for a
+    /// view it leads, certify the view and propose in it.
+    fn on_view_change(&mut self, vc: ViewChange, candidate: &Block) -> Result<Option<Block>, ConsensusError> {
+        let view = match self.views.on_view_change(vc) {
+            Ok(Some(view)) => view,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                debug!("[PBFT] Ignoring view change: {}", e);
+                return Ok(None);
+            }
+        };
+        let now = self.now();
+        let nv = self.views.new_view(view, candidate.clone(), now)?;
+        info!("[PBFT] Height {} leading view {}", nv.height, view);
+        let proposal = nv.proposal.clone();
+        self.broadcast(PbftMessage::NewView(nv))?;
+        self.enter_prepare(proposal)
+    }
+
+    fn enter_prepare(&mut self, block: Block) -> Result<Option<Block>, ConsensusError> {
+        self.collect(VotePhase::Prepare, block, self.timeouts.prepare_ms)?;
+        self.advance()
+    }
+
+    /// Vote for `block` in `phase`, ask the others for theirs and start
+    /// the phase timer.
+    fn collect(&mut self, phase: VotePhase, block: Block, timeout_ms: u64) -> Result<(), ConsensusError> {
+        let view = self.views.view();
+        let hash = block.compute_hash();
+        self.vote(phase, hash.clone())?;
+        self.broadcast(PbftMessage::VoteRequest(VoteRequest {
+            height:     self.views.height(),
+            view,
+            phase,
+            block_hash: hash.clone(),
+            requester:  self.validator_id.clone(),
+        }))?;
+        let now = self.now();
+        let collecting = Collecting {
+            block,
+            hash,
+            requested_at: now,
+            deadline:     now + self.timeouts.phase(timeout_ms, view),
+        };
+        self.phase = match phase {
+            VotePhase::Prepare => Phase::Prepare(collecting),
+            VotePhase::Commit  => Phase::Commit(collecting),
+        };
+        Ok(())
+    }
+
+    /// Move through prepared and committed as far as the votes allow.
+    fn advance(&mut self) -> Result<Option<Block>, ConsensusError> {
+        let view = self.views.view();
+        let quorum = self.set.quorum_size();
+        loop {
+            match &self.phase {
+                Phase::Prepare(c) if self.count(view, VotePhase::Prepare, &c.hash) >= quorum => {
+                    let block = c.block.clone();
+                    self.views.on_prepared(block.clone())?;
+                    self.collect(VotePhase::Commit, block, self.timeouts.commit_ms)?;
+                }
+                Phase::Commit(c) if self.count(view, VotePhase::Commit, &c.hash) >= quorum => {
+                    return Ok(Some(c.block.clone()));
+                }
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    fn count(&self, view: u64, phase: VotePhase, hash: &str) -> usize {
+        self.votes.get(&(view, phase, hash.to_string())).map_or(0, |v| v.len())
+    }
+
+    /// Time `vote` took to answer the request of the phase it belongs to.
+    fn record_latency(&mut self, vote: &Vote, now: u64) {
+        let c = match (&self.phase, vote.phase) {
+            (Phase::Prepare(c), VotePhase::Prepare) | (Phase::Commit(c), VotePhase::Commit) => c,
+            _ => return,
+        };
+        if vote.view == self.views.view() && vote.block_hash == c.hash {
+            self.latencies.insert(vote.validator_id.clone(), now.saturating_sub(c.requested_at));
+        }
+    }
+
+    fn vote(&mut self, phase: VotePhase, block_hash: String) -> Result<(), ConsensusError> {
+        let mut vote = Vote {
+            phase,
+            height:       self.views.height(),
+            view:         self.views.view(),
+            block_hash,
+            validator_id: self.validator_id.clone(),
+            signature:    Vec::new(),
+        };
+        vote.signature = self.sign(&vote.payload())?;
+        let now = self.now();
+        self.votes
+            .entry((vote.view, phase, vote.block_hash.clone()))
+            .or_default()
+            .insert(self.validator_id.clone(), now);
+        self.cast.insert((vote.view, phase), vote.clone());
+        self.broadcast(PbftMessage::Vote(vote))
+    }
+
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, ConsensusError> {
+        sign_tx_payload(payload, self.secret_key).map_err(ConsensusError::Other)
+    }
+
+    fn verify(&self, id: &str, payload: &[u8], signature: &[u8]) -> bool {
+        self.set.contains(id)
+            && self.public_keys.get(id).is_some_and(|pk| verify_tx_signature(payload, signature, pk))
+    }
+
+    fn broadcast(&self, msg: PbftMessage) -> Result<(), ConsensusError> {
+        self.net.broadcast_pbft(&msg).map_err(ConsensusError::Other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use parking_lot::Mutex;
+
+    use bleep_crypto::tx_signer::generate_tx_keypair;
+
+    use crate::networking::PbftLink;
+    use crate::validator_set::SetMember;
+
+    /// Height at which `v0` leads view 0.
+    const H: u64 = 4;
+
+    /// Plays back `script`, then stays silent; records what was sent.
+    #[derive(Default)]
+    struct ScriptedLink {
+        script: Mutex<VecDeque<PbftMessage>>,
+        sent:   Mutex<Vec<(Option<String>, PbftMessage)>>,
+    }
+
+    #[async_trait]
+    impl PbftLink for ScriptedLink {
+        fn broadcast(&self, msg: &PbftMessage) -> Result<(), String> {
+            self.sent.lock().push((None, msg.clone()));
+            Ok(())
+        }
+
+        fn send(&self, to: &str, msg: &PbftMessage) -> Result<(), String> {
+            self.sent.lock().push((Some(to.to_string()), msg.clone()));
+            Ok(())
+        }
+
+        async fn recv_timeout(&self, timeout: Duration) -> Option<PbftMessage> {
+            let next = self.script.lock().pop_front();
+            if next.is_none() {
+                tokio::time::sleep(timeout).await;
+            }
+            next
+        }
+    }
+
+    /// Validators `v0..v3`; the round under test runs as `v1`.
+    struct Fixture {
+        keys:  HashMap<String, (Vec<u8>, Vec<u8>)>,
+        set:   ValidatorSet,
+        block: Block,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let ids: Vec<String> = (0..4).map(|i| format!("v{}", i)).collect();
+            Fixture {
+                keys:  ids.iter().map(|id| (id.clone(), generate_tx_keypair())).collect(),
+                set:   ValidatorSet {
+                    epoch:       0,
+                    members:     ids.iter().map(|id| SetMember { id: id.clone(), stake: 100 }).collect(),
+                    total_stake: 400,
+                },
+                block: Block::new(H, vec![], "parent".into()),
+            }
+        }
+
+        fn proposal(&self) -> PbftMessage {
+            let mut p = Proposal {
+                height: H, view: 0, leader: "v0".into(), block: self.block.clone(), signature: Vec::new(),
+            };
+            p.signature = sign_tx_payload(&p.payload(), &self.keys["v0"].1).unwrap();
+            PbftMessage::Proposal(p)
+        }
+
+        /// `phase` vote for the proposal claiming to be from `id`, signed by `signer`.
+        fn vote(&self, phase: VotePhase, id: &str, signer: &str) -> PbftMessage {
+            let mut v = Vote {
+                phase,
+                height:       H,
+                view:         0,
+                block_hash:   self.block.compute_hash(),
+                validator_id: id.into(),
+                signature:    Vec::new(),
+            };
+            v.signature = sign_tx_payload(&v.payload(), &self.keys[signer].1).unwrap();
+            PbftMessage::Vote(v)
+        }
+
+        async fn run(&self, script: Vec<PbftMessage>, timeouts: PbftTimeouts) -> (Result<Block, ConsensusError>, HashMap<String, u64>, Arc<ScriptedLink>) {
+            let link = Arc::new(ScriptedLink { script: Mutex::new(script.into()), ..Default::default() });
+            let net = NetworkingModule::new();
+            net.attach_pbft_link(link.clone());
+            let pks = self.keys.iter().map(|(id, (pk, _))| (id.clone(), pk.clone())).collect();
+            let mut round = PbftRound::new("v1".into(), &self.keys["v1"].1, self.set.clone(), pks, H, &net, timeouts);
+            let result = round.run(&Block::new(H, vec![], "v1-candidate".into())).await;
+            (result, round.vote_latencies().clone(), link)
+        }
+    }
+
+    fn timeouts(phase_ms: u64) -> PbftTimeouts {
+        PbftTimeouts {
+            proposal:   ViewTimeouts { base_ms: 5_000, max_ms: 60_000 },
+            prepare_ms: phase_ms,
+            commit_ms:  phase_ms,
+            max_views:  1,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_and_forged_votes_do_not_reach_quorum() {
+        let f = Fixture::new();
+        let script = vec![
+            f.proposal(),
+            f.vote(VotePhase::Prepare, "v0", "v0"),
+            f.vote(VotePhase::Prepare, "v0", "v0"),
+            // v2's name, v3's key.
+            f.vote(VotePhase::Prepare, "v2", "v3"),
+        ];
+        let (result, latencies, link) = f.run(script, timeouts(300)).await;
+
+        // Own vote plus v0 is 2 of the 3 needed: the phase times out.
+        assert!(result.is_err());
+        assert_eq!(latencies.keys().collect::<Vec<_>>(), vec!["v0"]);
+        let sent = link.sent.lock();
+        assert!(sent.iter().any(|(_, m)| matches!(m, PbftMessage::VoteRequest(r) if r.phase == VotePhase::Prepare)));
+        assert!(sent.iter().any(|(_, m)| matches!(m, PbftMessage::ViewChange(vc) if vc.new_view == 1)));
+        assert!(!sent.iter().any(|(_, m)| matches!(m, PbftMessage::Vote(v) if v.phase == VotePhase::Commit)));
+    }
+
+    #[tokio::test]
+    async fn test_distinct_valid_votes_commit_and_requests_are_answered() {
+        let f = Fixture::new();
+        let script = vec![
+            f.proposal(),
+            PbftMessage::VoteRequest(VoteRequest {
+                height:     H,
+                view:       0,
+                phase:      VotePhase::Prepare,
+                block_hash: f.block.compute_hash(),
+                requester:  "v2".into(),
+            }),
+            f.vote(VotePhase::Prepare, "v0", "v0"),
+            f.vote(VotePhase::Prepare, "v0", "v0"),
+            f.vote(VotePhase::Prepare, "v2", "v3"),
+            f.vote(VotePhase::Prepare, "v3", "v3"),
+            f.vote(VotePhase::Commit, "v3", "v3"),
+            f.vote(VotePhase::Commit, "v3", "v3"),
+            f.vote(VotePhase::Commit, "v2", "v0"),
+            f.vote(VotePhase::Commit, "v0", "v0"),
+        ];
+        let (result, latencies, link) = f.run(script, timeouts(10_000)).await;
+
+        assert_eq!(result.unwrap().compute_hash(), f.block.compute_hash());
+        let mut answered: Vec<_> = latencies.keys().cloned().collect();
+        answered.sort();
+        assert_eq!(answered, vec!["v0", "v3"]);
+        let sent = link.sent.lock();
+        assert!(sent.iter().any(|(to, m)| to.as_deref() == Some("v2")
+            && matches!(m, PbftMessage::Vote(v) if v.phase == VotePhase::Prepare && v.validator_id == "v1")));
+    }
+}
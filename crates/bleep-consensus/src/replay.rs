@@ -0,0 +1,341 @@
+//! # Deterministic block replay
+//!
+//! Re-executes a range of stored blocks through `block_execution` — the
+//! same code the producer runs — on a fork of the state as of the block
+//! before `from`, and compares every block's state root, receipts root and
+//! gas total with what was recorded when it was committed.
+//!
+//! The report names the first divergent block and, within it, the first
+//! transaction whose receipt differs, with a bounded dump of the accounts
+//! and storage keys that disagree.  A single transaction can also be traced
+//! step by step (`ReplayOptions::trace_tx`).
+//!
+//! Data directory layout (matches the node defaults under `/tmp`):
+//!
+//! ```text
+//! <data-dir>/bleep-state    StateManager RocksDB   (BLEEP_STATE_DIR)
+//! <data-dir>/bleep-blocks   BlockStore             (BLEEP_BLOCKS_DIR)
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use parking_lot::Mutex as PLMutex;
+use serde::Serialize;
+
+use bleep_state::state_manager::StateManager;
+use bleep_vm::execution::executor::Executor;
+use bleep_vm::execution::trace::TraceStep;
+
+use crate::block_execution::{execute_block_with_fees, receipts_root, AccountSnapshot, TxReceipt};
+use crate::block_store::{BlockStore, StoredBlock};
+
+/// State database directory name inside a replay data dir.
+pub const STATE_SUBDIR: &str = "bleep-state";
+/// Block store directory name inside a replay data dir.
+pub const BLOCKS_SUBDIR: &str = "bleep-blocks";
+/// Default cap on dumped accounts / storage keys per divergence.
+pub const DEFAULT_MAX_DUMP: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Emit a step-level trace for this transaction hash.
+    pub trace_tx: Option<String>,
+    /// Maximum accounts and storage keys listed in a divergence.
+    pub max_dump: usize,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        ReplayOptions { trace_tx: None, max_dump: DEFAULT_MAX_DUMP }
+    }
+}
+
+// ── Report ────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Mismatch<T> {
+    pub expected: T,
+    pub actual:   T,
+}
+
+fn mismatch<T: PartialEq>(expected: T, actual: T) -> Option<Mismatch<T>> {
+    if expected == actual { None } else { Some(Mismatch { expected, actual }) }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AccountDiff {
+    pub address:  String,
+    pub expected: Option<AccountSnapshot>,
+    pub actual:   Option<AccountSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TxDivergence {
+    pub index:    usize,
+    pub tx_hash:  String,
+    pub expected: Option<TxReceipt>,
+    pub actual:   Option<TxReceipt>,
+    /// Accounts whose recorded and replayed post-values differ.
+    pub accounts: Vec<AccountDiff>,
+    /// Storage keys written in only one of the two runs.
+    pub storage_keys: Vec<String>,
+    /// `true` if `accounts` or `storage_keys` hit `max_dump`.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockDivergence {
+    pub height:        u64,
+    pub state_root:    Option<Mismatch<String>>,
+    pub receipts_root: Option<Mismatch<String>>,
+    pub gas_used:      Option<Mismatch<u64>>,
+    /// First transaction whose receipt differs, if any.
+    pub tx:            Option<TxDivergence>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TxTrace {
+    pub height:  u64,
+    pub tx_hash: String,
+    pub steps:   Vec<TraceStep>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReport {
+    pub from:             u64,
+    pub to:               u64,
+    pub blocks_replayed:  u64,
+    pub divergent_blocks: u64,
+    pub first_divergence: Option<BlockDivergence>,
+    pub trace:            Option<TxTrace>,
+}
+
+// ── Replay ────────────────────────────────────────────────────────────────────
+
+/// Fork `source` to the state block `from` executed on, into `scratch`.
+pub fn fork_for_replay<P: AsRef<Path>>(
+    source:  &StateManager,
+    store:   &BlockStore,
+    from:    u64,
+    scratch: P,
+) -> Result<StateManager, String> {
+    let first = store.get(from)?.ok_or_else(|| format!("block {} not in store", from))?;
+    source.fork_at(first.parent_state_height, scratch).map_err(|e| e.to_string())
+}
+
+/// Replay blocks `from..=to` on `state`, which must be at the parent state
+/// of block `from` (see `fork_for_replay`).
+///
+/// `executor` should come from `block_execution::production_executor`;
+/// pass `trace = true` there when `opts.trace_tx` is set.
+pub async fn replay_range(
+    store:    &BlockStore,
+    state:    &PLMutex<StateManager>,
+    executor: &Executor,
+    from:     u64,
+    to:       u64,
+    opts:     &ReplayOptions,
+) -> Result<ReplayReport, String> {
+    if from > to {
+        return Err(format!("empty range: from {} > to {}", from, to));
+    }
+    let blocks = store.range(from, to)?;
+    let mut report = ReplayReport {
+        from,
+        to,
+        blocks_replayed:  0,
+        divergent_blocks: 0,
+        first_divergence: None,
+        trace:            None,
+    };
+
+    for stored in &blocks {
+        let exec = execute_block_with_fees(executor, state, &stored.block.transactions, stored.fees.as_ref()).await;
+        report.blocks_replayed += 1;
+
+        if let Some(wanted) = &opts.trace_tx {
+            if let Some(t) = exec.txs.iter().find(|t| &t.receipt.tx_hash == wanted) {
+                report.trace = Some(TxTrace {
+                    height:  stored.height(),
+                    tx_hash: wanted.clone(),
+                    steps:   t.trace.clone(),
+                });
+            }
+        }
+
+        // Every tx in a committed block was applied, so the replayed
+        // receipts are compared one-for-one, failures included.
+        let actual: Vec<TxReceipt> = exec.txs.iter().map(|t| t.receipt.clone()).collect();
+        let divergence = BlockDivergence {
+            height:        stored.height(),
+            state_root:    mismatch(stored.state_root.clone(), hex::encode(exec.state_root)),
+            receipts_root: mismatch(stored.receipts_root.clone(), hex::encode(receipts_root(&actual))),
+            gas_used:      mismatch(stored.gas_used, exec.gas_used),
+            tx:            first_tx_divergence(stored, &actual, opts.max_dump),
+        };
+        let diverged = divergence.state_root.is_some()
+            || divergence.receipts_root.is_some()
+            || divergence.gas_used.is_some()
+            || divergence.tx.is_some();
+        if diverged {
+            report.divergent_blocks += 1;
+            if report.first_divergence.is_none() {
+                report.first_divergence = Some(divergence);
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn first_tx_divergence(stored: &StoredBlock, actual: &[TxReceipt], max_dump: usize) -> Option<TxDivergence> {
+    let len = stored.receipts.len().max(actual.len());
+    let index = (0..len).find(|&i| stored.receipts.get(i) != actual.get(i))?;
+    let expected = stored.receipts.get(index).cloned();
+    let got = actual.get(index).cloned();
+    let tx_hash = expected.as_ref().or(got.as_ref()).map(|r| r.tx_hash.clone()).unwrap_or_default();
+
+    let empty: BTreeMap<String, AccountSnapshot> = BTreeMap::new();
+    let exp_accts = expected.as_ref().map_or(&empty, |r| &r.accounts);
+    let act_accts = got.as_ref().map_or(&empty, |r| &r.accounts);
+    let addresses: BTreeSet<&String> = exp_accts.keys().chain(act_accts.keys()).collect();
+    let mut accounts: Vec<AccountDiff> = addresses.into_iter()
+        .filter(|a| exp_accts.get(*a) != act_accts.get(*a))
+        .map(|a| AccountDiff {
+            address:  a.clone(),
+            expected: exp_accts.get(a).copied(),
+            actual:   act_accts.get(a).copied(),
+        })
+        .collect();
+
+    let exp_keys: BTreeSet<&String> = expected.iter().flat_map(|r| r.storage_keys.iter()).collect();
+    let act_keys: BTreeSet<&String> = got.iter().flat_map(|r| r.storage_keys.iter()).collect();
+    let mut storage_keys: Vec<String> = exp_keys.symmetric_difference(&act_keys).map(|k| (*k).clone()).collect();
+
+    let truncated = accounts.len() > max_dump || storage_keys.len() > max_dump;
+    accounts.truncate(max_dump);
+    storage_keys.truncate(max_dump);
+
+    Some(TxDivergence { index, tx_hash, expected, actual: got, accounts, storage_keys, truncated })
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_execution::{execute_block, production_executor, tx_hash};
+    use bleep_core::block::{Block, ChainId, Transaction};
+
+    fn scratch(tag: &str) -> std::path::PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("bleep-replay-{}-{}-{}", tag, std::process::id(), nanos))
+    }
+
+    fn tx(from: &str, to: &str, amount: u64, ts: u64, nonce: u64) -> Transaction {
+        Transaction {
+            sender:    from.into(),
+            receiver:  to.into(),
+            amount,
+            timestamp: ts,
+            signature: Vec::new(),
+            max_fee:   0,
+            tip:       0,
+            nonce,
+            chain_id:  ChainId::NONE,
+        }
+    }
+
+    /// Produce blocks 1..=3 through the production path and archive them.
+    async fn build_chain(tag: &str) -> (PLMutex<StateManager>, BlockStore, Vec<Vec<Transaction>>) {
+        let state = PLMutex::new(StateManager::new());
+        {
+            let mut s = state.lock();
+            s.mint("alice", 1_000).unwrap();
+            s.mint("bob", 500).unwrap();
+            s.advance_block(); // seal genesis allocations
+        }
+        let store = BlockStore::open(scratch(tag)).unwrap();
+        let executor = production_executor(false);
+        let blocks = vec![
+            vec![tx("alice", "bob", 100, 1, 0), tx("bob", "carol", 50, 2, 0)],
+            vec![tx("carol", "dave", 10, 3, 0), tx("alice", "dave", 200, 4, 1), tx("bob", "alice", 5, 5, 1)],
+            vec![tx("dave", "erin", 60, 6, 0)],
+        ];
+        let mut prev = "0".repeat(64);
+        for (i, txs) in blocks.iter().enumerate() {
+            let exec = execute_block(&executor, &state, txs).await;
+            assert!(exec.txs.iter().all(|t| t.receipt.success));
+            let block = Block::new(i as u64 + 1, txs.clone(), prev);
+            prev = block.compute_hash();
+            store.put(&StoredBlock::new(block, &exec)).unwrap();
+        }
+        (state, store, blocks)
+    }
+
+    #[tokio::test]
+    async fn test_clean_range_has_no_divergence() {
+        let (source, store, _) = build_chain("clean").await;
+        let fork = fork_for_replay(&source.lock(), &store, 1, scratch("clean-fork")).unwrap();
+        let report = replay_range(
+            &store, &PLMutex::new(fork), &production_executor(false), 1, 3, &ReplayOptions::default(),
+        ).await.unwrap();
+        assert_eq!(report.blocks_replayed, 3);
+        assert_eq!(report.divergent_blocks, 0);
+        assert!(report.first_divergence.is_none());
+
+        // Starting mid-range works too.
+        let fork = fork_for_replay(&source.lock(), &store, 2, scratch("clean-fork-2")).unwrap();
+        let report = replay_range(
+            &store, &PLMutex::new(fork), &production_executor(false), 2, 3, &ReplayOptions::default(),
+        ).await.unwrap();
+        assert_eq!(report.divergent_blocks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_receipt_is_pinpointed() {
+        let (source, store, blocks) = build_chain("corrupt").await;
+        let mut stored = store.get(2).unwrap().unwrap();
+        stored.receipts[1].accounts.get_mut("dave").unwrap().balance += 1;
+        store.put(&stored).unwrap();
+
+        let fork = fork_for_replay(&source.lock(), &store, 1, scratch("corrupt-fork")).unwrap();
+        let report = replay_range(
+            &store, &PLMutex::new(fork), &production_executor(false), 1, 3, &ReplayOptions::default(),
+        ).await.unwrap();
+
+        assert_eq!(report.divergent_blocks, 1);
+        let d = report.first_divergence.unwrap();
+        assert_eq!(d.height, 2);
+        assert!(d.state_root.is_none(), "execution itself still matches");
+        let tx = d.tx.expect("tx-level divergence");
+        assert_eq!(tx.index, 1);
+        assert_eq!(tx.tx_hash, tx_hash(&blocks[1][1]));
+        assert_eq!(tx.accounts.len(), 1);
+        assert_eq!(tx.accounts[0].address, "dave");
+    }
+
+    #[tokio::test]
+    async fn test_trace_single_transaction() {
+        let (source, store, blocks) = build_chain("trace").await;
+        let wanted = tx_hash(&blocks[1][1]);
+        let fork = fork_for_replay(&source.lock(), &store, 1, scratch("trace-fork")).unwrap();
+        let opts = ReplayOptions { trace_tx: Some(wanted.clone()), ..Default::default() };
+        let report = replay_range(
+            &store, &PLMutex::new(fork), &production_executor(true), 1, 3, &opts,
+        ).await.unwrap();
+
+        assert_eq!(report.divergent_blocks, 0, "tracing must not change execution");
+        let trace = report.trace.expect("trace");
+        assert_eq!(trace.height, 2);
+        assert_eq!(trace.tx_hash, wanted);
+        assert!(matches!(trace.steps.last(), Some(TraceStep::GasCheckpoint { .. })));
+        assert_eq!(
+            trace.steps.iter().filter(|s| matches!(s, TraceStep::BalanceChange { .. })).count(),
+            2,
+        );
+    }
+}
@@ -0,0 +1,412 @@
+//! # Replica follower
+//!
+//! A replica (`bleep --role replica`) holds no keys, has no mempool and
+//! never proposes.  It follows the chain by re-executing every block its
+//! upstream nodes gossip and checking the result against the block's state
+//! root, so the state it serves over RPC is exactly the validators' state.
+//!
+//! ```text
+//! gossip Block ─▶ known proposer? ─▶ header signature ─▶ links to tip?
+//!                                                            │
+//!      execute_block_with_rewards (block.base_fee) ◀─────────┘
+//!                    │ state root == block.shard_state_root, else roll back
+//!                    ▼
+//!   Blockchain::add_block → BlockStore → shard height → SyncStatus
+//! ```
+//!
+//! Blocks are applied strictly in height order.  A block ahead of the tip
+//! waits in a bounded buffer until the gap fills; one at or below the tip is
+//! ignored.  `SyncStatus` tracks the local height against the highest
+//! height seen upstream, which is what `/rpc/ready` reports on replicas.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use parking_lot::Mutex as PLMutex;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use bleep_core::base_fee::BaseFeeParams;
+use bleep_core::block::Block;
+use bleep_core::blockchain::Blockchain;
+use bleep_state::state_manager::StateManager;
+use bleep_vm::execution::executor::Executor;
+
+use crate::block_execution::{execute_block_with_rewards, production_executor, FeeContext, RewardContext};
+use crate::block_store::{BlockStore, StoredBlock};
+use crate::evidence::validator_for_key;
+use crate::rewards::RewardLedger;
+use crate::staking;
+use crate::validator_identity::ValidatorRegistry;
+
+/// Blocks held ahead of the tip while waiting for a gap to fill.
+pub const MAX_PENDING_BLOCKS: usize = 1_024;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ReplicaError {
+    #[error("Block {0} is not signed")]
+    Unsigned(u64),
+    #[error("Block {height} is signed by unknown proposer {key}")]
+    UnknownProposer { height: u64, key: String },
+    #[error("Block {0} header signature does not verify")]
+    BadSignature(u64),
+    #[error("Block {height} does not extend the local tip {tip}")]
+    NotOnTip { height: u64, tip: u64 },
+    #[error("Block {height} is {ahead} blocks ahead of the tip and the buffer is full")]
+    TooFarAhead { height: u64, ahead: u64 },
+    #[error("Block {height} state root mismatch: block {expected}, executed {actual}")]
+    StateRootMismatch { height: u64, expected: String, actual: String },
+    #[error("Block {0} was rejected by the chain")]
+    Rejected(u64),
+    #[error("Replica storage: {0}")]
+    Storage(String),
+}
+
+/// Local height against the highest height seen upstream.
+#[derive(Debug, Default)]
+pub struct SyncStatus {
+    height:       AtomicU64,
+    upstream_tip: AtomicU64,
+}
+
+impl SyncStatus {
+    pub fn new(height: u64) -> Self {
+        Self { height: AtomicU64::new(height), upstream_tip: AtomicU64::new(height) }
+    }
+
+    pub fn height(&self) -> u64 {
+        self.height.load(Ordering::Relaxed)
+    }
+
+    /// Highest height announced upstream; never below the local height.
+    pub fn upstream_tip(&self) -> u64 {
+        self.upstream_tip.load(Ordering::Relaxed).max(self.height())
+    }
+
+    pub fn lag(&self) -> u64 {
+        self.upstream_tip() - self.height()
+    }
+
+    /// Synced when no more than `max_lag` blocks behind upstream.
+    pub fn is_synced(&self, max_lag: u64) -> bool {
+        self.lag() <= max_lag
+    }
+
+    pub fn observe_upstream(&self, height: u64) {
+        self.upstream_tip.fetch_max(height, Ordering::Relaxed);
+    }
+
+    fn set_height(&self, height: u64) {
+        self.height.store(height, Ordering::Relaxed);
+    }
+}
+
+/// Applies gossiped blocks to a keyless node's chain and state.
+pub struct ReplicaFollower {
+    blockchain:  Arc<RwLock<Blockchain>>,
+    state:       Arc<PLMutex<StateManager>>,
+    executor:    Executor,
+    /// Hex SPHINCS+ public key → validator id, on top of `registry`.
+    proposers:   HashMap<String, String>,
+    registry:    Option<Arc<PLMutex<ValidatorRegistry>>>,
+    block_store: Option<Arc<BlockStore>>,
+    fee_params:  BaseFeeParams,
+    rewards:     Option<Arc<PLMutex<RewardLedger>>>,
+    /// Out-of-order blocks by height; the lock also serialises `apply`.
+    pending:     tokio::sync::Mutex<BTreeMap<u64, Block>>,
+    status:      Arc<SyncStatus>,
+}
+
+impl ReplicaFollower {
+    /// Follow on top of `blockchain`'s latest block, which `state` must be
+    /// the post-state of — genesis, or the last archived block on restart.
+    pub fn new(blockchain: Arc<RwLock<Blockchain>>, state: Arc<PLMutex<StateManager>>) -> Self {
+        let height = blockchain.read().ok()
+            .and_then(|c| c.latest_block())
+            .map(|b| b.index)
+            .unwrap_or(0);
+        Self {
+            blockchain,
+            state,
+            executor:    production_executor(false),
+            proposers:   HashMap::new(),
+            registry:    None,
+            block_store: None,
+            fee_params:  BaseFeeParams::default(),
+            rewards:     None,
+            pending:     tokio::sync::Mutex::new(BTreeMap::new()),
+            status:      Arc::new(SyncStatus::new(height)),
+        }
+    }
+
+    /// Accept blocks signed by `public_key`, crediting tips to `validator_id`.
+    pub fn with_proposer(mut self, validator_id: impl Into<String>, public_key: &[u8]) -> Self {
+        self.proposers.insert(hex::encode(public_key), validator_id.into());
+        self
+    }
+
+    /// Accept blocks signed by any active validator in `registry`.
+    pub fn with_registry(mut self, registry: Arc<PLMutex<ValidatorRegistry>>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    /// Archive applied blocks with their receipts, as producers do.
+    pub fn with_block_store(mut self, store: Arc<BlockStore>) -> Self {
+        self.block_store = Some(store);
+        self
+    }
+
+    /// Fee parameters blocks with a base fee were charged under.
+    pub fn with_fee_params(mut self, params: BaseFeeParams) -> Self {
+        self.fee_params = params;
+        self
+    }
+
+    /// Attribute block rewards as the producers do; required to follow a
+    /// chain whose producers pay rewards.
+    pub fn with_rewards(mut self, ledger: Arc<PLMutex<RewardLedger>>) -> Self {
+        self.rewards = Some(ledger);
+        self
+    }
+
+    /// Report through `status` instead of a status of its own, e.g. one an
+    /// RPC server started before the follower existed.
+    pub fn with_status(mut self, status: Arc<SyncStatus>) -> Self {
+        status.set_height(self.status.height());
+        self.status = status;
+        self
+    }
+
+    pub fn status(&self) -> Arc<SyncStatus> {
+        Arc::clone(&self.status)
+    }
+
+    /// Apply `block`, then any buffered blocks it unblocks.  Returns the
+    /// heights applied; empty when the block was stale or buffered.
+    pub async fn apply(&self, block: Block) -> Result<Vec<u64>, ReplicaError> {
+        if block.index <= self.status.height() {
+            return Ok(Vec::new());
+        }
+        // Only authenticated blocks may move the upstream tip or take
+        // buffer space.
+        self.authenticate(&block)?;
+        self.status.observe_upstream(block.index);
+
+        let mut pending = self.pending.lock().await;
+        let tip = self.status.height();
+        if block.index <= tip {
+            return Ok(Vec::new());
+        }
+        if block.index > tip + 1 {
+            if pending.len() >= MAX_PENDING_BLOCKS && !pending.contains_key(&block.index) {
+                return Err(ReplicaError::TooFarAhead { height: block.index, ahead: block.index - tip });
+            }
+            pending.insert(block.index, block);
+            return Ok(Vec::new());
+        }
+
+        let mut applied = Vec::new();
+        let mut next = Some(block);
+        while let Some(block) = next {
+            let height = block.index;
+            self.apply_next(block).await?;
+            applied.push(height);
+            next = pending.remove(&(height + 1));
+        }
+        let tip = self.status.height();
+        pending.retain(|h, _| *h > tip);
+        Ok(applied)
+    }
+
+    /// Signer key and validator id of a block signed by a known proposer.
+    fn authenticate(&self, block: &Block) -> Result<(Vec<u8>, String), ReplicaError> {
+        let height = block.index;
+        let public_key = block.signer_public_key().ok_or(ReplicaError::Unsigned(height))?.to_vec();
+        let proposer = self.registry.as_ref()
+            .and_then(|r| validator_for_key(&r.lock(), &public_key))
+            .or_else(|| staking::validator_for_key(&self.state.lock(), &public_key))
+            .or_else(|| self.proposers.get(&hex::encode(&public_key)).cloned())
+            .ok_or_else(|| ReplicaError::UnknownProposer {
+                height,
+                key: hex::encode(&public_key[..public_key.len().min(8)]),
+            })?;
+        if !block.verify_header_signature() {
+            return Err(ReplicaError::BadSignature(height));
+        }
+        Ok((public_key, proposer))
+    }
+
+    async fn apply_next(&self, block: Block) -> Result<(), ReplicaError> {
+        let height = block.index;
+        let (public_key, proposer) = self.authenticate(&block)?;
+        {
+            let chain = self.blockchain.read().map_err(|e| ReplicaError::Storage(e.to_string()))?;
+            let tip = chain.latest_block().ok_or_else(|| ReplicaError::Storage("chain has no genesis".into()))?;
+            if tip.index + 1 != height || tip.compute_hash() != block.previous_hash {
+                return Err(ReplicaError::NotOnTip { height, tip: tip.index });
+            }
+        }
+
+        // Blocks only carry a base fee when their producer charged one.
+        let fees = (block.base_fee > 0).then(|| FeeContext {
+            base_fee: block.base_fee,
+            params:   self.fee_params,
+            proposer: proposer.clone(),
+        });
+        // Restored with the state if the block is refused.
+        let ledger_before = self.rewards.as_ref().map(|l| l.lock().clone());
+        let rewards = self.rewards.as_deref().map(|ledger| RewardContext {
+            ledger,
+            epoch:    block.epoch_id,
+            proposer: &proposer,
+            voters:   &[],
+        });
+        let exec = execute_block_with_rewards(
+            &self.executor, &self.state, &block.transactions, fees.as_ref(), rewards.as_ref(),
+        ).await;
+        let actual = hex::encode(exec.state_root);
+        let all_applied = exec.txs.iter().all(|t| t.receipt.success);
+        if !all_applied || actual != block.shard_state_root {
+            self.roll_back(exec.parent_state_height, ledger_before);
+            return Err(ReplicaError::StateRootMismatch { height, expected: block.shard_state_root, actual });
+        }
+
+        let accepted = self.blockchain.write()
+            .map_err(|e| ReplicaError::Storage(e.to_string()))?
+            .add_block(block.clone(), &public_key);
+        if !accepted {
+            self.roll_back(exec.parent_state_height, ledger_before);
+            return Err(ReplicaError::Rejected(height));
+        }
+
+        if let Some(ref store) = self.block_store {
+            if let Err(e) = store.put(&StoredBlock::new(block.clone(), &exec)) {
+                warn!("[Replica] block store: {}", e);
+            }
+        }
+        if let Err(e) = self.state.lock().set_shard_height(block.shard_id, height) {
+            warn!("[Replica] shard height: {}", e);
+        }
+        self.status.set_height(height);
+        info!("[Replica] Applied block {} from {} txs={}", height, proposer, block.transactions.len());
+        Ok(())
+    }
+
+    fn roll_back(&self, height: u64, ledger: Option<RewardLedger>) {
+        if let (Some(current), Some(before)) = (&self.rewards, ledger) {
+            *current.lock() = before;
+        }
+        let mut state = self.state.lock();
+        if state.block_height() > height {
+            if let Err(e) = state.rollback_to(height) {
+                warn!("[Replica] rollback to {}: {}", height, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_execution::execute_block_with_fees;
+    use bleep_core::block::{ChainId, Transaction};
+    use bleep_core::blockchain::BlockchainState;
+    use bleep_core::transaction_pool::TransactionPool;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload};
+
+    struct Node {
+        blockchain: Arc<RwLock<Blockchain>>,
+        state:      Arc<PLMutex<StateManager>>,
+    }
+
+    fn node() -> Node {
+        let mut state = StateManager::new();
+        state.mint("alice", 1_000_000).unwrap();
+        state.advance_block();
+        let mut core = BlockchainState::default();
+        core.credit("alice", 1_000_000);
+        let genesis = Block::new(0, vec![], "0".to_string());
+        Node {
+            blockchain: Arc::new(RwLock::new(Blockchain::new(genesis, core, TransactionPool::new(100), None).unwrap())),
+            state:      Arc::new(PLMutex::new(state)),
+        }
+    }
+
+    /// Alice → bob, signed with a throwaway key: blocks are only accepted
+    /// with valid transaction signatures.
+    fn transfer(amount: u64, ts: u64, nonce: u64) -> Transaction {
+        let (pk, sk) = generate_tx_keypair();
+        let mut tx = Transaction {
+            sender: "alice".into(), receiver: "bob".into(), amount, timestamp: ts, signature: Vec::new(),
+            max_fee: 0, tip: 0, nonce, chain_id: ChainId::NONE,
+        };
+        tx.signature = pk;
+        tx.signature.extend(sign_tx_payload(&tx.signed_payload(), &sk).unwrap());
+        tx
+    }
+
+    /// What a producer commits: executed on `validator`, signed with `sk`.
+    async fn produce(validator: &Node, txs: Vec<Transaction>, sk: &[u8], pk: &[u8]) -> Block {
+        let exec = execute_block_with_fees(&production_executor(false), &validator.state, &txs, None).await;
+        let (height, prev) = {
+            let chain = validator.blockchain.read().unwrap();
+            let tip = chain.latest_block().unwrap();
+            (tip.index + 1, tip.compute_hash())
+        };
+        let mut block = Block::new(height, txs, prev);
+        block.shard_state_root = hex::encode(exec.state_root);
+        block.sign_block_with_pk(sk, pk).unwrap();
+        assert!(validator.blockchain.write().unwrap().add_block(block.clone(), pk));
+        block
+    }
+
+    #[tokio::test]
+    async fn follows_blocks_in_order_and_matches_validator_state() {
+        let (pk, sk) = generate_tx_keypair();
+        let validator = node();
+        let replica = node();
+        let follower = ReplicaFollower::new(Arc::clone(&replica.blockchain), Arc::clone(&replica.state))
+            .with_proposer("v0", &pk);
+
+        let b1 = produce(&validator, vec![transfer(10, 1, 0)], &sk, &pk).await;
+        let b2 = produce(&validator, vec![transfer(20, 2, 1)], &sk, &pk).await;
+        let b3 = produce(&validator, vec![transfer(30, 3, 2)], &sk, &pk).await;
+
+        // Out of order: 3 and 2 wait for 1.
+        assert!(follower.apply(b3.clone()).await.unwrap().is_empty());
+        assert!(follower.apply(b2).await.unwrap().is_empty());
+        assert_eq!(follower.status().lag(), 3);
+        assert!(!follower.status().is_synced(2));
+        assert_eq!(follower.apply(b1.clone()).await.unwrap(), vec![1, 2, 3]);
+        assert!(follower.apply(b1).await.unwrap().is_empty());
+        assert!(follower.status().is_synced(0));
+
+        assert_eq!(replica.state.lock().get_balance("bob"), 60);
+        assert_eq!(replica.state.lock().state_root(), validator.state.lock().state_root());
+        assert_eq!(replica.blockchain.read().unwrap().latest_block().unwrap().compute_hash(), b3.compute_hash());
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_proposers_and_rolls_back_divergent_blocks() {
+        let (pk, sk) = generate_tx_keypair();
+        let (rogue_pk, rogue_sk) = generate_tx_keypair();
+        let validator = node();
+        let replica = node();
+        let follower = ReplicaFollower::new(Arc::clone(&replica.blockchain), Arc::clone(&replica.state))
+            .with_proposer("v0", &pk);
+
+        let forged = produce(&node(), vec![transfer(10, 1, 0)], &rogue_sk, &rogue_pk).await;
+        assert!(matches!(follower.apply(forged).await, Err(ReplicaError::UnknownProposer { height: 1, .. })));
+
+        let mut wrong_root = produce(&validator, vec![transfer(10, 1, 0)], &sk, &pk).await;
+        wrong_root.shard_state_root = hex::encode([7u8; 32]);
+        wrong_root.sign_block_with_pk(&sk, &pk).unwrap();
+        let root_before = replica.state.lock().state_root();
+        assert!(matches!(follower.apply(wrong_root).await, Err(ReplicaError::StateRootMismatch { height: 1, .. })));
+        assert_eq!(replica.state.lock().state_root(), root_before);
+        assert_eq!(replica.state.lock().get_balance("bob"), 0);
+        assert_eq!(follower.status().height(), 0);
+    }
+}
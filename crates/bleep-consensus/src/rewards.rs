@@ -0,0 +1,517 @@
+//! # Block rewards
+//!
+//! Validators earn a per-block issuance plus the tips of the transactions
+//! they include.  Block execution credits both to `REWARD_POOL_ACCOUNT`;
+//! the `RewardLedger` records who they belong to and pays them out when
+//! claimed:
+//!
+//! ```text
+//! block   pool = issuance(epoch) + tips
+//!           proposer ← proposer_bps of pool, plus every rounding remainder
+//!           voters   ← the rest, pro rata to the stake they signed with
+//!
+//! epoch   per validator: commission = earned × commission_bps → validator
+//!           earned − commission → reward per bonded unit of its stake pool
+//!
+//! claim   settle the claimer's delegations, pay out, zero
+//! ```
+//!
+//! Distribution is lazy: closing an epoch touches one accumulator per
+//! validator, never the delegators.  A delegation is settled against its
+//! pool's accumulator only when its stake changes or its owner claims.
+//!
+//! All arithmetic is integer and every division floors.  What a floor drops
+//! is carried, never lost: sub-unit remainders stay in each pool and each
+//! delegation (in `SCALE` fixed point) and are paid once they add up to a
+//! whole µBLEEP, so the pool account always holds exactly what the ledger
+//! owes plus those carried fractions.
+//!
+//! Reward transactions are ordinary transactions to reserved receivers:
+//! `bleep:rewards/claim` (`ClaimRewards`) and
+//! `bleep:rewards/commission/<bps>`, both with amount 0.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::validator_identity::ValidatorRegistry;
+
+pub use bleep_core::base_fee::{REWARD_POOL_ACCOUNT, REWARD_TX_PREFIX};
+
+const BPS: u128 = 10_000;
+
+/// Fixed-point scale of the reward-per-stake accumulators.
+const SCALE: u128 = 1_000_000_000_000;
+
+/// Governance-controlled reward parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardParams {
+    /// µBLEEP issued per block in epoch 0.
+    pub block_issuance:     u64,
+    /// Issuance drops by `decay_bps` every `decay_epochs` epochs; 0 = never.
+    pub decay_epochs:       u64,
+    pub decay_bps:          u16,
+    /// Proposer's share of a block's rewards; voters share the rest.
+    pub proposer_bps:       u16,
+    /// Highest commission a validator may set.
+    pub max_commission_bps: u16,
+}
+
+impl Default for RewardParams {
+    fn default() -> Self {
+        Self {
+            block_issuance:     100_000_000,
+            decay_epochs:       365,
+            decay_bps:          1_000,
+            proposer_bps:       2_000,
+            max_commission_bps: 5_000,
+        }
+    }
+}
+
+impl RewardParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if [self.decay_bps, self.proposer_bps, self.max_commission_bps].iter().any(|b| *b as u128 > BPS) {
+            return Err("decay_bps, proposer_bps and max_commission_bps must be ≤ 10000".into());
+        }
+        Ok(())
+    }
+
+    /// Issuance per block during `epoch`.  Each decay step floors.
+    pub fn issuance(&self, epoch: u64) -> u64 {
+        let mut issuance = self.block_issuance as u128;
+        if self.decay_epochs > 0 {
+            for _ in 0..epoch / self.decay_epochs {
+                if issuance == 0 {
+                    break;
+                }
+                issuance = issuance * (BPS - self.decay_bps as u128) / BPS;
+            }
+        }
+        issuance as u64
+    }
+}
+
+// ── Transactions ──────────────────────────────────────────────────────────────
+
+/// What a reward transaction asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RewardTx {
+    /// Pay the sender everything it has accrued.
+    Claim,
+    /// Set the sender's validator commission from the next epoch on.
+    SetCommission { bps: u16 },
+}
+
+impl RewardTx {
+    /// Receiver of the transaction carrying this request.
+    pub fn receiver(&self) -> String {
+        match self {
+            RewardTx::Claim => format!("{}claim", REWARD_TX_PREFIX),
+            RewardTx::SetCommission { bps } => format!("{}commission/{}", REWARD_TX_PREFIX, bps),
+        }
+    }
+
+    /// Parse a receiver; `None` if it is not a reward transaction at all.
+    pub fn parse(receiver: &str) -> Option<Result<RewardTx, String>> {
+        let rest = receiver.strip_prefix(REWARD_TX_PREFIX)?;
+        Some(match rest.split_once('/') {
+            None if rest == "claim" => Ok(RewardTx::Claim),
+            Some(("commission", bps)) => bps
+                .parse()
+                .map(|bps| RewardTx::SetCommission { bps })
+                .map_err(|_| format!("bad commission {:?}", bps)),
+            _ => Err(format!("unknown reward transaction {:?}", receiver)),
+        })
+    }
+}
+
+/// Whether `receiver` marks a reward transaction.
+pub fn is_reward_tx(receiver: &str) -> bool {
+    receiver.starts_with(REWARD_TX_PREFIX)
+}
+
+// ── Ledger ────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Default)]
+struct Delegation {
+    stake:      u128,
+    /// Pool accumulator value this delegation was last settled at.
+    checkpoint: u128,
+    /// Sub-µBLEEP remainder of the last settlement, in `SCALE` units.
+    carry:      u128,
+}
+
+/// Everything bonded to one validator.
+#[derive(Debug, Clone, Default)]
+struct StakePool {
+    bonded:           u128,
+    /// Cumulative reward per bonded µBLEEP, in `SCALE` units.
+    reward_per_stake: u128,
+    /// Remainder of the last accumulator step, in `SCALE` units.
+    carry:            u128,
+    delegations:      BTreeMap<String, Delegation>,
+}
+
+impl StakePool {
+    /// Settle `delegator` up to the current accumulator; returns the whole
+    /// µBLEEP it earned since the last settlement.
+    fn settle(&mut self, delegator: &str) -> u128 {
+        let rps = self.reward_per_stake;
+        match self.delegations.get_mut(delegator) {
+            Some(d) => {
+                let units = d.stake * (rps - d.checkpoint) + d.carry;
+                d.checkpoint = rps;
+                d.carry = units % SCALE;
+                units / SCALE
+            }
+            None => 0,
+        }
+    }
+
+    /// What `settle` would pay, without settling.
+    fn unsettled(&self, delegator: &str) -> u128 {
+        self.delegations
+            .get(delegator)
+            .map_or(0, |d| (d.stake * (self.reward_per_stake - d.checkpoint) + d.carry) / SCALE)
+    }
+}
+
+/// How one validator's epoch earnings were split.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochPayout {
+    pub validator:  String,
+    pub epoch:      u64,
+    pub earned:     u128,
+    pub commission: u128,
+    /// Credited to the stake pool (the validator's own stake included).
+    pub to_stakers: u128,
+}
+
+/// Lifetime totals, for reconciling against the pool account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardTotals {
+    pub issued:  u128,
+    pub fees:    u128,
+    pub claimed: u128,
+}
+
+/// Rewards of one address, as served over RPC.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardsView {
+    pub address:        String,
+    /// Claimable now.
+    pub claimable:      u128,
+    /// Earned as a validator in the open epoch; claimable once it closes.
+    pub accruing:       u128,
+    /// Stake bonded by this address, per validator.
+    pub bonded:         BTreeMap<String, u128>,
+    pub commission_bps: Option<u16>,
+    pub epoch:          u64,
+}
+
+/// Attribution and lazy distribution of block rewards.
+#[derive(Debug, Clone)]
+pub struct RewardLedger {
+    params:     RewardParams,
+    /// Epoch currently accruing.
+    epoch:      u64,
+    /// Validator earnings of the open epoch.
+    earned:     BTreeMap<String, u128>,
+    commission: BTreeMap<String, u16>,
+    pools:      BTreeMap<String, StakePool>,
+    /// Settled, unclaimed rewards by address.
+    settled:    BTreeMap<String, u128>,
+    totals:     RewardTotals,
+}
+
+impl RewardLedger {
+    pub fn new(params: RewardParams) -> Result<Self, String> {
+        params.validate()?;
+        Ok(Self {
+            params,
+            epoch: 0,
+            earned: BTreeMap::new(),
+            commission: BTreeMap::new(),
+            pools: BTreeMap::new(),
+            settled: BTreeMap::new(),
+            totals: RewardTotals::default(),
+        })
+    }
+
+    pub fn params(&self) -> &RewardParams {
+        &self.params
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn totals(&self) -> RewardTotals {
+        self.totals
+    }
+
+    /// Issuance per block during `epoch`.
+    pub fn issuance(&self, epoch: u64) -> u64 {
+        self.params.issuance(epoch)
+    }
+
+    /// Set `validator`'s commission; it applies when the open epoch closes.
+    pub fn set_commission(&mut self, validator: &str, bps: u16) -> Result<(), String> {
+        if bps > self.params.max_commission_bps {
+            return Err(format!("commission {} bps above the {} bps maximum", bps, self.params.max_commission_bps));
+        }
+        self.commission.insert(validator.to_string(), bps);
+        Ok(())
+    }
+
+    pub fn commission_bps(&self, validator: &str) -> u16 {
+        self.commission.get(validator).copied().unwrap_or(0)
+    }
+
+    /// Set the stake `delegator` has bonded to `validator` (a validator's own
+    /// stake is a delegation to itself).  Rewards earned at the old stake
+    /// are settled first.
+    pub fn set_bond(&mut self, validator: &str, delegator: &str, stake: u128) {
+        let pool = self.pools.entry(validator.to_string()).or_default();
+        let paid = pool.settle(delegator);
+        let rps = pool.reward_per_stake;
+        let d = pool.delegations.entry(delegator.to_string()).or_insert_with(|| Delegation {
+            checkpoint: rps,
+            ..Default::default()
+        });
+        pool.bonded = pool.bonded - d.stake + stake;
+        d.stake = stake;
+        if stake == 0 && d.carry == 0 {
+            pool.delegations.remove(delegator);
+        }
+        self.credit(delegator, paid);
+    }
+
+    pub fn bonded(&self, validator: &str, delegator: &str) -> u128 {
+        self.pools.get(validator).and_then(|p| p.delegations.get(delegator)).map_or(0, |d| d.stake)
+    }
+
+    /// Bond every registered validator's own stake to itself.
+    pub fn sync_validator_stakes(&mut self, registry: &ValidatorRegistry) {
+        let stakes: Vec<(String, u128)> =
+            registry.get_active_validators().iter().map(|v| (v.id.clone(), v.stake)).collect();
+        for (id, stake) in stakes {
+            self.set_bond(&id, &id, stake);
+        }
+    }
+
+    /// Close every epoch before `epoch`, distributing what was earned in it.
+    pub fn enter_epoch(&mut self, epoch: u64) -> Vec<EpochPayout> {
+        if epoch <= self.epoch {
+            return Vec::new();
+        }
+        let payouts = self.close_epoch();
+        self.epoch = epoch;
+        payouts
+    }
+
+    /// Attribute one block's rewards: `issued` newly minted plus `fees`
+    /// collected, both already credited to `REWARD_POOL_ACCOUNT`.  `voters`
+    /// are the `(validator, stake)` pairs whose signatures the block carries.
+    pub fn record_block(&mut self, epoch: u64, proposer: &str, voters: &[(String, u128)], issued: u128, fees: u128) {
+        self.enter_epoch(epoch);
+        self.totals.issued += issued;
+        self.totals.fees += fees;
+
+        let reward = issued + fees;
+        let voter_stake: u128 = voters.iter().map(|(_, s)| s).sum();
+        let mut to_proposer = reward;
+        if voter_stake > 0 {
+            let to_voters = reward - reward * self.params.proposer_bps as u128 / BPS;
+            for (voter, stake) in voters {
+                let share = to_voters * stake / voter_stake;
+                *self.earned.entry(voter.clone()).or_default() += share;
+                to_proposer -= share;
+            }
+        }
+        *self.earned.entry(proposer.to_string()).or_default() += to_proposer;
+    }
+
+    /// Distribute the open epoch's earnings to commissions and stake pools.
+    fn close_epoch(&mut self) -> Vec<EpochPayout> {
+        let mut payouts = Vec::with_capacity(self.earned.len());
+        for (validator, earned) in std::mem::take(&mut self.earned) {
+            let commission = earned * self.commission_bps(&validator) as u128 / BPS;
+            let to_stakers = earned - commission;
+            let pool = self.pools.entry(validator.clone()).or_default();
+            let unbonded = if pool.bonded == 0 {
+                to_stakers
+            } else {
+                let units = to_stakers * SCALE + pool.carry;
+                pool.reward_per_stake += units / pool.bonded;
+                pool.carry = units % pool.bonded;
+                0
+            };
+            // Nobody bonded: the validator keeps the stakers' part too.
+            self.credit(&validator, commission + unbonded);
+            payouts.push(EpochPayout { validator, epoch: self.epoch, earned, commission, to_stakers });
+        }
+        payouts
+    }
+
+    fn credit(&mut self, address: &str, amount: u128) {
+        if amount > 0 {
+            *self.settled.entry(address.to_string()).or_default() += amount;
+        }
+    }
+
+    /// Claimable by `address` now, across all its delegations.
+    pub fn claimable(&self, address: &str) -> u128 {
+        let unsettled: u128 = self.pools.values().map(|p| p.unsettled(address)).sum();
+        self.settled.get(address).copied().unwrap_or(0) + unsettled
+    }
+
+    /// Settle and zero everything `address` can claim; returns the payout,
+    /// which the caller moves out of `REWARD_POOL_ACCOUNT`.
+    pub fn claim(&mut self, address: &str) -> u128 {
+        let unsettled: u128 = self.pools.values_mut().map(|p| p.settle(address)).sum();
+        let amount = self.settled.remove(address).unwrap_or(0) + unsettled;
+        self.totals.claimed += amount;
+        amount
+    }
+
+    /// Whole µBLEEP owed to all addresses, including the open epoch.
+    /// `issued + fees − claimed − owed` is the carried rounding remainder.
+    pub fn owed(&self) -> u128 {
+        let settled: u128 = self.settled.values().sum();
+        let unsettled: u128 = self
+            .pools
+            .values()
+            .flat_map(|p| p.delegations.keys().map(move |d| p.unsettled(d)))
+            .sum();
+        let accruing: u128 = self.earned.values().sum();
+        settled + unsettled + accruing
+    }
+
+    pub fn view(&self, address: &str) -> RewardsView {
+        RewardsView {
+            address:        address.to_string(),
+            claimable:      self.claimable(address),
+            accruing:       self.earned.get(address).copied().unwrap_or(0),
+            bonded:         self
+                .pools
+                .iter()
+                .filter_map(|(v, p)| p.delegations.get(address).filter(|d| d.stake > 0).map(|d| (v.clone(), d.stake)))
+                .collect(),
+            commission_bps: self.commission.get(address).copied(),
+            epoch:          self.epoch,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> RewardParams {
+        RewardParams {
+            block_issuance:     1_000,
+            decay_epochs:       0,
+            decay_bps:          0,
+            proposer_bps:       2_000,
+            max_commission_bps: 10_000,
+        }
+    }
+
+    /// A: 600 own stake, 10% commission.  B: 300 own + 100 from dave, 5%.
+    /// C: 100 own, no commission.
+    fn three_validators() -> RewardLedger {
+        let mut ledger = RewardLedger::new(params()).unwrap();
+        ledger.set_bond("A", "A", 600);
+        ledger.set_bond("B", "B", 300);
+        ledger.set_bond("B", "dave", 100);
+        ledger.set_bond("C", "C", 100);
+        ledger.set_commission("A", 1_000).unwrap();
+        ledger.set_commission("B", 500).unwrap();
+        ledger
+    }
+
+    fn voters(ids: &[(&str, u128)]) -> Vec<(String, u128)> {
+        ids.iter().map(|(id, s)| (id.to_string(), *s)).collect()
+    }
+
+    #[test]
+    fn issuance_decays_on_schedule() {
+        let p = RewardParams { block_issuance: 1_000, decay_epochs: 10, decay_bps: 1_000, ..params() };
+        assert_eq!([p.issuance(0), p.issuance(9), p.issuance(10), p.issuance(25)], [1_000, 1_000, 900, 810]);
+        assert!(RewardParams { proposer_bps: 10_001, ..params() }.validate().is_err());
+        assert_eq!(RewardTx::parse(&RewardTx::Claim.receiver()), Some(Ok(RewardTx::Claim)));
+        let commission = RewardTx::SetCommission { bps: 750 };
+        assert_eq!(RewardTx::parse(&commission.receiver()), Some(Ok(commission)));
+        assert!(matches!(RewardTx::parse("bleep:rewards/commission/x"), Some(Err(_))));
+        assert_eq!(RewardTx::parse("alice"), None);
+    }
+
+    #[test]
+    fn three_validator_epoch_matches_hand_computed_shares() {
+        let mut ledger = three_validators();
+        let all = voters(&[("A", 600), ("B", 400), ("C", 100)]);
+
+        // Block 1: 1000 issued + 100 fees.  A proposes: 220, then the voters'
+        // 880 by stake: A 480, B 320, C 80.
+        ledger.record_block(0, "A", &all, 1_000, 100);
+        // Block 2: C missed the vote.  B proposes: 200, then A 480, B 320.
+        ledger.record_block(0, "B", &all[..2], 1_000, 0);
+        assert_eq!(ledger.view("A").accruing, 1_180);
+        assert_eq!(ledger.view("B").accruing, 840);
+        assert_eq!(ledger.view("C").accruing, 80);
+        assert_eq!(ledger.claimable("A"), 0);
+
+        let payouts = ledger.enter_epoch(1);
+        assert_eq!(payouts.iter().map(|p| (p.validator.as_str(), p.commission, p.to_stakers)).collect::<Vec<_>>(),
+                   [("A", 118, 1_062), ("B", 42, 798), ("C", 0, 80)]);
+
+        // A alone in its pool.  B's 798 splits 3:1 — 598.5 and 199.5, floored.
+        assert_eq!(ledger.claimable("A"), 1_180);
+        assert_eq!(ledger.claimable("B"), 42 + 598);
+        assert_eq!(ledger.claimable("dave"), 199);
+        assert_eq!(ledger.claimable("C"), 80);
+        // The two halves are carried, not lost.
+        assert_eq!(ledger.totals().issued + ledger.totals().fees - ledger.owed(), 1);
+    }
+
+    #[test]
+    fn unclaimed_rewards_accumulate_across_epochs() {
+        let mut ledger = three_validators();
+        ledger.record_block(0, "A", &voters(&[("A", 600), ("B", 400), ("C", 100)]), 1_000, 100);
+        ledger.record_block(0, "B", &voters(&[("A", 600), ("B", 400)]), 1_000, 0);
+
+        // Epoch 1: B proposes alone.  50 commission; 950 over 400 bonded
+        // plus the halves carried from epoch 0 pays exactly 713 and 238.
+        ledger.record_block(1, "B", &[], 1_000, 0);
+        ledger.enter_epoch(2);
+        assert_eq!(ledger.claimable("B"), 42 + 598 + 50 + 713);
+        assert_eq!(ledger.claimable("dave"), 199 + 238);
+        assert_eq!(ledger.totals().issued + ledger.totals().fees, ledger.owed());
+
+        // Changing stake settles first, so past rewards keep their old weight.
+        ledger.set_bond("B", "dave", 300);
+        assert_eq!(ledger.claimable("dave"), 437);
+        assert_eq!(ledger.view("dave").bonded.get("B"), Some(&300));
+    }
+
+    #[test]
+    fn claiming_pays_out_and_zeroes_the_accrual() {
+        let mut ledger = three_validators();
+        ledger.record_block(0, "B", &[], 1_000, 0);
+        ledger.record_block(1, "B", &[], 1_000, 0);
+        ledger.enter_epoch(2);
+        let owed_before = ledger.owed();
+
+        let paid = ledger.claim("dave");
+        assert_eq!(paid, 475);
+        assert_eq!(ledger.claimable("dave"), 0);
+        assert_eq!(ledger.claim("dave"), 0);
+        assert_eq!(ledger.totals().claimed, 475);
+        assert_eq!(ledger.owed(), owed_before - 475);
+
+        // The validator's own share is unaffected.
+        assert_eq!(ledger.claimable("B"), 100 + 1_425);
+    }
+}
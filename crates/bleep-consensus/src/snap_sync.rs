@@ -0,0 +1,855 @@
+//! # Snap sync
+//!
+//! A fresh node fetches the state at a recent checkpoint from its peers
+//! instead of replaying every block since genesis, then follows blocks
+//! from the checkpoint as a replica does.
+//!
+//! ```text
+//! GetStateManifest ─▶ checkpoint block signed by a known proposer?
+//!                     manifest signed by a known validator?
+//!                     manifest root == checkpoint.shard_state_root?
+//!        │
+//! GetStateChunk × n   in parallel across peers; each chunk checked
+//!        │            against its manifest hash and kept on disk
+//!        ▼
+//! assembled tree root == checkpoint root ─▶ StateManager::import_accounts
+//! ```
+//!
+//! A checkpoint is every block whose height is a multiple of the server's
+//! `checkpoint_interval`.  The manifest lists one SHA3-256 hash per chunk
+//! and is signed by the serving validator over `StateManifest::digest`,
+//! which covers the checkpoint block hash; the state root itself is the
+//! one the checkpoint's proposer signed.  A chunk that does not match its
+//! hash gets its peer penalized — dropped for the rest of the sync — and
+//! is fetched from another peer.  Verified chunks are written to the sync
+//! directory as they arrive, so a restarted sync only fetches the rest.
+//!
+//! Reward accounting (`RewardLedger`) is not part of the account state and
+//! is not transferred; a snap-synced node starts with an empty ledger.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use parking_lot::Mutex as PLMutex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use bleep_core::block::Block;
+use bleep_crypto::tx_signer::{sign_tx_payload, verify_tx_signature};
+use bleep_p2p::snap_sync::{SnapPeer, SnapRequest, SnapResponse, SnapSource, SnapTelemetry, LATEST_CHECKPOINT};
+use bleep_state::shard_state::{ShardRing, ShardedState};
+use bleep_state::state_manager::{AccountState, StateManager};
+
+use crate::block_store::BlockStore;
+use crate::evidence::validator_for_key;
+use crate::validator_identity::ValidatorRegistry;
+
+/// Default blocks between checkpoints a server snapshots.
+pub const DEFAULT_CHECKPOINT_INTERVAL: u64 = 100;
+
+/// Default accounts per chunk; keeps a chunk well inside one P2P frame.
+pub const DEFAULT_CHUNK_ACCOUNTS: usize = 1_024;
+
+/// Default chunk downloads in flight.
+pub const DEFAULT_PARALLEL_CHUNKS: usize = 4;
+
+/// Failed requests (timeouts, `Unavailable`) before a peer is dropped.
+pub const DEFAULT_MAX_PEER_FAILURES: u32 = 3;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SnapSyncError {
+    #[error("No peer served a verifiable state manifest")]
+    NoManifest,
+    #[error("Chunk {0} could not be fetched: no usable peers left")]
+    NoPeers(u32),
+    #[error("Assembled state root {actual} does not match checkpoint root {expected}")]
+    RootMismatch { expected: String, actual: String },
+    #[error("Snap sync storage: {0}")]
+    Storage(String),
+}
+
+/// Signed description of the state at a checkpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateManifest {
+    /// The checkpoint block; its `shard_state_root` is the state root.
+    pub checkpoint:   Block,
+    /// `StateManager` height of the post-checkpoint state.
+    pub state_height: u64,
+    /// Shard layout the root was computed under.
+    pub ring:         ShardRing,
+    pub chunk_hashes: Vec<[u8; 32]>,
+    pub total_bytes:  u64,
+    /// SPHINCS+ public key of the serving validator.
+    pub signer:       Vec<u8>,
+    /// Signature over `digest()`.
+    pub signature:    Vec<u8>,
+}
+
+impl StateManifest {
+    pub fn height(&self) -> u64 {
+        self.checkpoint.index
+    }
+
+    pub fn chunk_count(&self) -> u32 {
+        self.chunk_hashes.len() as u32
+    }
+
+    /// What the serving validator signs.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut h = Sha3_256::new();
+        h.update(b"bleep:snap_manifest");
+        h.update(self.checkpoint.compute_hash().as_bytes());
+        h.update(self.checkpoint.shard_state_root.as_bytes());
+        h.update(self.state_height.to_le_bytes());
+        h.update(serde_json::to_vec(&self.ring).unwrap_or_default());
+        for hash in &self.chunk_hashes {
+            h.update(hash);
+        }
+        h.update(self.total_bytes.to_le_bytes());
+        h.finalize().into()
+    }
+
+    fn sign(&mut self, sk: &[u8], pk: &[u8]) -> Result<(), String> {
+        self.signer = pk.to_vec();
+        self.signature = sign_tx_payload(&self.digest(), sk)?;
+        Ok(())
+    }
+}
+
+fn chunk_hash(bytes: &[u8]) -> [u8; 32] {
+    Sha3_256::digest(bytes).into()
+}
+
+/// Root of the tree `accounts` build under `ring`.
+fn assembled_root(ring: &ShardRing, accounts: &[(String, AccountState)]) -> [u8; 32] {
+    let mut tree = ShardedState::new(ring.clone());
+    for (addr, state) in accounts {
+        if state.balance > 0 || state.nonce > 0 {
+            tree.insert(addr, state.balance, state.nonce);
+        }
+    }
+    tree.global_root()
+}
+
+// ── Serving ───────────────────────────────────────────────────────────────────
+
+/// A manifest and its chunks, encoded as served.
+#[derive(Debug, Clone)]
+pub struct StateSnapshot {
+    pub manifest:   StateManifest,
+    manifest_bytes: Vec<u8>,
+    chunks:         Vec<Vec<u8>>,
+}
+
+impl StateSnapshot {
+    /// Chunk `accounts` (as of `checkpoint`) and sign the manifest.
+    pub fn build(
+        checkpoint: Block,
+        state_height: u64,
+        ring: ShardRing,
+        accounts: &[(String, AccountState)],
+        chunk_accounts: usize,
+        sk: &[u8],
+        pk: &[u8],
+    ) -> Result<Self, String> {
+        let chunks = accounts
+            .chunks(chunk_accounts.max(1))
+            .map(serde_json::to_vec)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        let mut manifest = StateManifest {
+            checkpoint,
+            state_height,
+            ring,
+            chunk_hashes: chunks.iter().map(|c| chunk_hash(c)).collect(),
+            total_bytes:  chunks.iter().map(|c| c.len() as u64).sum(),
+            signer:       Vec::new(),
+            signature:    Vec::new(),
+        };
+        manifest.sign(sk, pk)?;
+        let manifest_bytes = serde_json::to_vec(&manifest).map_err(|e| e.to_string())?;
+        Ok(StateSnapshot { manifest, manifest_bytes, chunks })
+    }
+}
+
+/// Serves snapshots of the live state at checkpoints in the block store.
+///
+/// The latest snapshot is built on first request and cached; a checkpoint
+/// older than the state journal can no longer be exported.
+pub struct SnapshotServer {
+    state:          Arc<PLMutex<StateManager>>,
+    store:          Arc<BlockStore>,
+    sk:             Vec<u8>,
+    pk:             Vec<u8>,
+    interval:       u64,
+    chunk_accounts: usize,
+    cached:         PLMutex<Option<Arc<StateSnapshot>>>,
+}
+
+impl SnapshotServer {
+    pub fn new(state: Arc<PLMutex<StateManager>>, store: Arc<BlockStore>, sk: Vec<u8>, pk: Vec<u8>) -> Self {
+        Self {
+            state,
+            store,
+            sk,
+            pk,
+            interval:       DEFAULT_CHECKPOINT_INTERVAL,
+            chunk_accounts: DEFAULT_CHUNK_ACCOUNTS,
+            cached:         PLMutex::new(None),
+        }
+    }
+
+    pub fn with_checkpoint_interval(mut self, interval: u64) -> Self {
+        self.interval = interval.max(1);
+        self
+    }
+
+    pub fn with_chunk_accounts(mut self, accounts: usize) -> Self {
+        self.chunk_accounts = accounts.max(1);
+        self
+    }
+
+    /// Highest checkpoint in the block store.
+    pub fn latest_checkpoint(&self) -> Option<u64> {
+        let tip = self.store.tip().ok()??;
+        let checkpoint = tip / self.interval * self.interval;
+        (checkpoint > 0).then_some(checkpoint)
+    }
+
+    /// Snapshot at checkpoint `height`, built if not cached.
+    pub fn snapshot(&self, height: u64) -> Result<Arc<StateSnapshot>, String> {
+        if height == 0 || height % self.interval != 0 {
+            return Err(format!("{} is not a checkpoint height", height));
+        }
+        if let Some(s) = self.cached.lock().as_ref().filter(|s| s.manifest.height() == height) {
+            return Ok(Arc::clone(s));
+        }
+        let stored = self.store.get(height)?.ok_or_else(|| format!("block {} is not archived", height))?;
+        let state_height = stored.post_state_height();
+        let (accounts, ring) = {
+            let state = self.state.lock();
+            (state.export_accounts(state_height).map_err(|e| e.to_string())?, state.shard_ring().clone())
+        };
+        let snapshot = Arc::new(StateSnapshot::build(
+            stored.block, state_height, ring, &accounts, self.chunk_accounts, &self.sk, &self.pk,
+        )?);
+        info!(
+            "[SnapSync] Snapshot at checkpoint {}: {} accounts in {} chunks",
+            height, accounts.len(), snapshot.chunks.len(),
+        );
+        *self.cached.lock() = Some(Arc::clone(&snapshot));
+        Ok(snapshot)
+    }
+}
+
+impl SnapSource for SnapshotServer {
+    fn manifest(&self, at_checkpoint: u64) -> Option<Vec<u8>> {
+        let height = match at_checkpoint {
+            LATEST_CHECKPOINT => self.latest_checkpoint()?,
+            h => h,
+        };
+        match self.snapshot(height) {
+            Ok(s) => Some(s.manifest_bytes.clone()),
+            Err(e) => {
+                warn!("[SnapSync] Cannot serve checkpoint {}: {}", height, e);
+                None
+            }
+        }
+    }
+
+    fn chunk(&self, checkpoint: u64, index: u32) -> Option<Vec<u8>> {
+        let cached = self.cached.lock().clone().filter(|s| s.manifest.height() == checkpoint);
+        let snapshot = match cached {
+            Some(s) => s,
+            None => self.snapshot(checkpoint).ok()?,
+        };
+        snapshot.chunks.get(index as usize).cloned()
+    }
+}
+
+// ── Syncing ───────────────────────────────────────────────────────────────────
+
+/// Download progress, shared with `/rpc/ready` and telemetry.
+#[derive(Debug)]
+pub struct SnapProgress {
+    chunks_done:  AtomicU64,
+    chunks_total: AtomicU64,
+    bytes:        AtomicU64,
+    penalized:    AtomicU64,
+    complete:     AtomicBool,
+    started:      Instant,
+}
+
+impl Default for SnapProgress {
+    fn default() -> Self {
+        Self {
+            chunks_done:  AtomicU64::new(0),
+            chunks_total: AtomicU64::new(0),
+            bytes:        AtomicU64::new(0),
+            penalized:    AtomicU64::new(0),
+            complete:     AtomicBool::new(false),
+            started:      Instant::now(),
+        }
+    }
+}
+
+impl SnapProgress {
+    pub fn chunks_done(&self) -> u64 {
+        self.chunks_done.load(Ordering::Relaxed)
+    }
+
+    pub fn chunks_total(&self) -> u64 {
+        self.chunks_total.load(Ordering::Relaxed)
+    }
+
+    /// Chunk bytes downloaded this run (resumed chunks excluded).
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn bytes_per_sec(&self) -> u64 {
+        let secs = self.started.elapsed().as_secs_f64();
+        if secs > 0.0 { (self.bytes() as f64 / secs) as u64 } else { 0 }
+    }
+
+    pub fn peers_penalized(&self) -> u64 {
+        self.penalized.load(Ordering::Relaxed)
+    }
+
+    /// True once the state is imported; the node may then serve reads.
+    pub fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SnapSyncConfig {
+    /// Checkpoint to sync to; `LATEST_CHECKPOINT` for the highest offered.
+    pub at_checkpoint:     u64,
+    pub parallel_chunks:   usize,
+    pub max_peer_failures: u32,
+}
+
+impl Default for SnapSyncConfig {
+    fn default() -> Self {
+        Self {
+            at_checkpoint:     LATEST_CHECKPOINT,
+            parallel_chunks:   DEFAULT_PARALLEL_CHUNKS,
+            max_peer_failures: DEFAULT_MAX_PEER_FAILURES,
+        }
+    }
+}
+
+/// What a completed sync did.
+#[derive(Debug, Clone)]
+pub struct SnapOutcome {
+    /// Block to follow on from.
+    pub checkpoint: Block,
+    pub accounts:   usize,
+    pub fetched:    u32,
+    /// Chunks already on disk from an interrupted run.
+    pub resumed:    u32,
+    /// Peers dropped for serving bad data.
+    pub penalized:  Vec<String>,
+}
+
+/// Peers still in use, and the penalties handed out.
+#[derive(Default)]
+struct PeerBook {
+    dropped:   Vec<bool>,
+    failures:  Vec<u32>,
+    penalized: Vec<String>,
+}
+
+/// Downloads and verifies the state at a checkpoint from peers.
+pub struct SnapSyncer {
+    peers:     Vec<Arc<dyn SnapPeer>>,
+    /// Hex SPHINCS+ public key → validator id, on top of `registry`.
+    proposers: HashMap<String, String>,
+    registry:  Option<Arc<PLMutex<ValidatorRegistry>>>,
+    dir:       PathBuf,
+    config:    SnapSyncConfig,
+    progress:  Arc<SnapProgress>,
+    telemetry: Option<SnapTelemetry>,
+}
+
+impl SnapSyncer {
+    /// Sync from `peers`, keeping verified chunks under `dir`.
+    pub fn new(peers: Vec<Arc<dyn SnapPeer>>, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            peers,
+            proposers: HashMap::new(),
+            registry:  None,
+            dir:       dir.into(),
+            config:    SnapSyncConfig::default(),
+            progress:  Arc::new(SnapProgress::default()),
+            telemetry: None,
+        }
+    }
+
+    /// Trust checkpoints and manifests signed by `public_key`.
+    pub fn with_proposer(mut self, validator_id: impl Into<String>, public_key: &[u8]) -> Self {
+        self.proposers.insert(hex::encode(public_key), validator_id.into());
+        self
+    }
+
+    /// Trust checkpoints and manifests signed by any active validator.
+    pub fn with_registry(mut self, registry: Arc<PLMutex<ValidatorRegistry>>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn with_config(mut self, config: SnapSyncConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_telemetry(mut self, telemetry: SnapTelemetry) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    pub fn progress(&self) -> Arc<SnapProgress> {
+        Arc::clone(&self.progress)
+    }
+
+    fn trusted(&self, public_key: &[u8]) -> bool {
+        self.registry.as_ref().and_then(|r| validator_for_key(&r.lock(), public_key)).is_some()
+            || self.proposers.contains_key(&hex::encode(public_key))
+    }
+
+    /// Whether `manifest` is one to sync to.
+    fn verify_manifest(&self, manifest: &StateManifest) -> Result<(), String> {
+        let checkpoint = &manifest.checkpoint;
+        match checkpoint.signer_public_key() {
+            Some(pk) if self.trusted(pk) && checkpoint.verify_header_signature() => {}
+            _ => return Err(format!("checkpoint {} is not signed by a known proposer", checkpoint.index)),
+        }
+        if !self.trusted(&manifest.signer) || !verify_tx_signature(&manifest.digest(), &manifest.signature, &manifest.signer) {
+            return Err(format!("manifest for checkpoint {} is not signed by a known validator", checkpoint.index));
+        }
+        if manifest.chunk_hashes.is_empty() {
+            return Err("manifest lists no chunks".into());
+        }
+        Ok(())
+    }
+
+    /// Download, verify and import the state into `state`.
+    pub async fn run(&self, state: &PLMutex<StateManager>) -> Result<SnapOutcome, SnapSyncError> {
+        std::fs::create_dir_all(&self.dir).map_err(|e| SnapSyncError::Storage(e.to_string()))?;
+        let book = PLMutex::new(PeerBook {
+            dropped:  vec![false; self.peers.len()],
+            failures: vec![0; self.peers.len()],
+            ..PeerBook::default()
+        });
+
+        let manifest = self.fetch_manifest(&book).await?;
+        let total = manifest.chunk_count();
+        self.progress.chunks_total.store(total as u64, Ordering::Relaxed);
+
+        let missing: Vec<u32> = (0..total).filter(|i| self.load_chunk(&manifest, *i).is_none()).collect();
+        let resumed = total - missing.len() as u32;
+        self.progress.chunks_done.store(resumed as u64, Ordering::Relaxed);
+        info!(
+            "[SnapSync] Checkpoint {}: {} chunks, {} on disk, {} peers",
+            manifest.height(), total, resumed, self.peers.len(),
+        );
+
+        // Popped from the back: lowest index first.
+        let queue = PLMutex::new(missing.iter().rev().copied().collect::<Vec<_>>());
+        let cursor = AtomicUsize::new(0);
+        let workers = (0..self.config.parallel_chunks.max(1)).map(|_| self.worker(&manifest, &queue, &book, &cursor));
+        for result in futures::future::join_all(workers).await {
+            result?;
+        }
+
+        let mut accounts = Vec::new();
+        for index in 0..total {
+            let bytes = self.load_chunk(&manifest, index)
+                .ok_or_else(|| SnapSyncError::Storage(format!("chunk {} vanished", index)))?;
+            let chunk: Vec<(String, AccountState)> = serde_json::from_slice(&bytes)
+                .map_err(|e| SnapSyncError::Storage(format!("chunk {}: {}", index, e)))?;
+            accounts.extend(chunk);
+        }
+        let actual = hex::encode(assembled_root(&manifest.ring, &accounts));
+        if actual != manifest.checkpoint.shard_state_root {
+            // Every chunk matched a manifest its signer vouched for, so the
+            // manifest itself is bad; start over next time.
+            self.clear();
+            return Err(SnapSyncError::RootMismatch { expected: manifest.checkpoint.shard_state_root.clone(), actual });
+        }
+
+        let imported = accounts.len();
+        state.lock()
+            .import_accounts(manifest.ring.clone(), manifest.state_height, accounts)
+            .map_err(|e| SnapSyncError::Storage(e.to_string()))?;
+        self.clear();
+        self.progress.complete.store(true, Ordering::Relaxed);
+        self.export_telemetry();
+        info!("[SnapSync] ✅ Imported {} accounts at checkpoint {}", imported, manifest.height());
+
+        Ok(SnapOutcome {
+            checkpoint: manifest.checkpoint,
+            accounts:   imported,
+            fetched:    missing.len() as u32,
+            resumed,
+            penalized:  book.into_inner().penalized,
+        })
+    }
+
+    /// The manifest an interrupted run left, if a peer still serves it;
+    /// otherwise the highest verified one offered.
+    async fn fetch_manifest(&self, book: &PLMutex<PeerBook>) -> Result<StateManifest, SnapSyncError> {
+        let saved: Option<StateManifest> = std::fs::read(self.dir.join(MANIFEST_FILE))
+            .ok()
+            .and_then(|b| serde_json::from_slice(&b).ok())
+            .filter(|m| self.verify_manifest(m).is_ok());
+        if let Some(saved) = saved {
+            let wanted = SnapRequest::GetStateManifest { at_checkpoint: saved.height() };
+            for peer in &self.peers {
+                if let Ok(SnapResponse::Manifest(bytes)) = peer.request(wanted.clone()).await {
+                    if serde_json::from_slice::<StateManifest>(&bytes).map_or(false, |m| m.digest() == saved.digest()) {
+                        return Ok(saved);
+                    }
+                }
+            }
+            warn!("[SnapSync] No peer serves checkpoint {} any more; starting over", saved.height());
+            self.clear();
+            std::fs::create_dir_all(&self.dir).map_err(|e| SnapSyncError::Storage(e.to_string()))?;
+        }
+
+        let request = SnapRequest::GetStateManifest { at_checkpoint: self.config.at_checkpoint };
+        let mut best: Option<StateManifest> = None;
+        for (i, peer) in self.peers.iter().enumerate() {
+            let bytes = match peer.request(request.clone()).await {
+                Ok(SnapResponse::Manifest(bytes)) => bytes,
+                Ok(_) => continue,
+                Err(e) => {
+                    warn!("[SnapSync] {}: manifest request failed: {}", peer.id(), e);
+                    continue;
+                }
+            };
+            let verified = serde_json::from_slice::<StateManifest>(&bytes)
+                .map_err(|e| e.to_string())
+                .and_then(|m| self.verify_manifest(&m).map(|_| m));
+            match verified {
+                Ok(m) if best.as_ref().map_or(true, |b| m.height() > b.height()) => best = Some(m),
+                Ok(_) => {}
+                Err(e) => self.penalize(book, i, &e),
+            }
+        }
+        let manifest = best.ok_or(SnapSyncError::NoManifest)?;
+        let bytes = serde_json::to_vec(&manifest).map_err(|e| SnapSyncError::Storage(e.to_string()))?;
+        std::fs::write(self.dir.join(MANIFEST_FILE), bytes).map_err(|e| SnapSyncError::Storage(e.to_string()))?;
+        Ok(manifest)
+    }
+
+    /// Fetch chunks off `queue` until it is empty.
+    async fn worker(
+        &self,
+        manifest: &StateManifest,
+        queue: &PLMutex<Vec<u32>>,
+        book: &PLMutex<PeerBook>,
+        cursor: &AtomicUsize,
+    ) -> Result<(), SnapSyncError> {
+        loop {
+            let Some(index) = queue.lock().pop() else { return Ok(()) };
+            loop {
+                let peer = self.next_peer(book, cursor).ok_or(SnapSyncError::NoPeers(index))?;
+                let request = SnapRequest::GetStateChunk { checkpoint: manifest.height(), index };
+                match self.peers[peer].request(request).await {
+                    Ok(SnapResponse::Chunk(bytes)) if chunk_hash(&bytes) == manifest.chunk_hashes[index as usize] => {
+                        std::fs::write(self.chunk_path(index), &bytes)
+                            .map_err(|e| SnapSyncError::Storage(e.to_string()))?;
+                        self.progress.chunks_done.fetch_add(1, Ordering::Relaxed);
+                        self.progress.bytes.fetch_add(bytes.len() as u64, Ordering::Relaxed);
+                        self.export_telemetry();
+                        break;
+                    }
+                    Ok(SnapResponse::Chunk(_)) => {
+                        self.penalize(book, peer, &format!("chunk {} does not match the manifest", index));
+                    }
+                    Ok(_) => self.fail(book, peer, &format!("chunk {} unavailable", index)),
+                    Err(e) => self.fail(book, peer, &e.to_string()),
+                }
+            }
+        }
+    }
+
+    /// Round-robin over the peers still in use.
+    fn next_peer(&self, book: &PLMutex<PeerBook>, cursor: &AtomicUsize) -> Option<usize> {
+        let book = book.lock();
+        let n = self.peers.len();
+        (0..n)
+            .map(|_| cursor.fetch_add(1, Ordering::Relaxed) % n)
+            .find(|i| !book.dropped[*i])
+    }
+
+    /// Drop a peer that served bad data.
+    fn penalize(&self, book: &PLMutex<PeerBook>, peer: usize, reason: &str) {
+        let mut book = book.lock();
+        if !std::mem::replace(&mut book.dropped[peer], true) {
+            let id = self.peers[peer].id();
+            warn!("[SnapSync] Penalizing {}: {}", id, reason);
+            book.penalized.push(id);
+            self.progress.penalized.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a failed request; drop the peer after too many.
+    fn fail(&self, book: &PLMutex<PeerBook>, peer: usize, reason: &str) {
+        let mut book = book.lock();
+        book.failures[peer] += 1;
+        warn!("[SnapSync] {}: {}", self.peers[peer].id(), reason);
+        if book.failures[peer] >= self.config.max_peer_failures {
+            book.dropped[peer] = true;
+        }
+    }
+
+    fn chunk_path(&self, index: u32) -> PathBuf {
+        self.dir.join(format!("chunk-{:06}", index))
+    }
+
+    /// A chunk on disk that matches the manifest.
+    fn load_chunk(&self, manifest: &StateManifest, index: u32) -> Option<Vec<u8>> {
+        let bytes = std::fs::read(self.chunk_path(index)).ok()?;
+        (chunk_hash(&bytes) == manifest.chunk_hashes[index as usize]).then_some(bytes)
+    }
+
+    fn clear(&self) {
+        if let Err(e) = remove_dir(&self.dir) {
+            warn!("[SnapSync] Cannot clear {}: {}", self.dir.display(), e);
+        }
+    }
+
+    fn export_telemetry(&self) {
+        if let Some(t) = &self.telemetry {
+            let p = &self.progress;
+            t.export(p.chunks_done(), p.chunks_total(), p.bytes_per_sec(), p.peers_penalized());
+        }
+    }
+}
+
+fn remove_dir(dir: &Path) -> std::io::Result<()> {
+    match std::fs::remove_dir_all(dir) {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::RwLock;
+
+    use async_trait::async_trait;
+    use bleep_core::block::{ChainId, Transaction};
+    use bleep_core::blockchain::{Blockchain, BlockchainState};
+    use bleep_core::transaction_pool::TransactionPool;
+    use bleep_crypto::tx_signer::generate_tx_keypair;
+    use bleep_p2p::error::{P2PError, P2PResult};
+    use bleep_p2p::snap_sync::LocalSnapPeer;
+
+    use crate::block_execution::{execute_block, production_executor};
+    use crate::block_store::StoredBlock;
+    use crate::replica::ReplicaFollower;
+
+    fn scratch(tag: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("bleep-snap-{}-{}-{}", tag, std::process::id(), nanos))
+    }
+
+    /// One validator producing signed, archived blocks.
+    struct TestCluster {
+        state:  Arc<PLMutex<StateManager>>,
+        store:  Arc<BlockStore>,
+        blocks: Vec<Block>,
+        pk:     Vec<u8>,
+        sk:     Vec<u8>,
+    }
+
+    impl TestCluster {
+        async fn new(tag: &str, height: u64) -> Self {
+            let (pk, sk) = generate_tx_keypair();
+            let mut state = StateManager::new();
+            state.mint("alice", 1_000_000).unwrap();
+            state.advance_block();
+            let mut c = TestCluster {
+                state:  Arc::new(PLMutex::new(state)),
+                store:  Arc::new(BlockStore::open(scratch(tag)).unwrap()),
+                blocks: vec![Block::new(0, vec![], "0".to_string())],
+                pk,
+                sk,
+            };
+            for _ in 0..height {
+                c.produce().await;
+            }
+            c
+        }
+
+        /// Block paying a new account, so the state grows with the chain.
+        async fn produce(&mut self) {
+            let height = self.blocks.len() as u64;
+            let mut tx = Transaction {
+                sender: "alice".into(), receiver: format!("acct-{:03}", height), amount: height, timestamp: height,
+                signature: Vec::new(), max_fee: 0, tip: 0, nonce: height - 1, chain_id: ChainId::NONE,
+            };
+            tx.signature = self.pk.clone();
+            tx.signature.extend(sign_tx_payload(&tx.signed_payload(), &self.sk).unwrap());
+            let txs = vec![tx];
+            let exec = execute_block(&production_executor(false), &self.state, &txs).await;
+            let mut block = Block::new(height, txs, self.blocks.last().unwrap().compute_hash());
+            block.shard_state_root = hex::encode(exec.state_root);
+            block.sign_block_with_pk(&self.sk, &self.pk).unwrap();
+            self.store.put(&StoredBlock::new(block.clone(), &exec)).unwrap();
+            self.blocks.push(block);
+        }
+
+        fn server(&self) -> Arc<SnapshotServer> {
+            Arc::new(
+                SnapshotServer::new(Arc::clone(&self.state), Arc::clone(&self.store), self.sk.clone(), self.pk.clone())
+                    .with_checkpoint_interval(10)
+                    .with_chunk_accounts(3),
+            )
+        }
+
+        fn peer(&self, id: &str) -> Arc<dyn SnapPeer> {
+            Arc::new(LocalSnapPeer { id: id.into(), source: self.server() })
+        }
+
+        fn syncer(&self, peers: Vec<Arc<dyn SnapPeer>>, dir: &Path) -> SnapSyncer {
+            SnapSyncer::new(peers, dir)
+                .with_proposer("v0", &self.pk)
+                .with_config(SnapSyncConfig { parallel_chunks: 2, ..SnapSyncConfig::default() })
+        }
+    }
+
+    /// Serves bad bytes for chunk `corrupt`, or stops after `serve_chunks`.
+    struct FaultyPeer {
+        inner:        Arc<dyn SnapPeer>,
+        corrupt:      Option<u32>,
+        serve_chunks: Option<u64>,
+        served:       AtomicU64,
+    }
+
+    impl FaultyPeer {
+        fn new(inner: Arc<dyn SnapPeer>) -> Self {
+            FaultyPeer { inner, corrupt: None, serve_chunks: None, served: AtomicU64::new(0) }
+        }
+    }
+
+    #[async_trait]
+    impl SnapPeer for FaultyPeer {
+        fn id(&self) -> String {
+            format!("faulty-{}", self.inner.id())
+        }
+
+        async fn request(&self, request: SnapRequest) -> P2PResult<SnapResponse> {
+            if let SnapRequest::GetStateChunk { index, .. } = request {
+                if self.serve_chunks.map_or(false, |n| self.served.load(Ordering::Relaxed) >= n) {
+                    return Err(P2PError::Serialization("connection reset".into()));
+                }
+                self.served.fetch_add(1, Ordering::Relaxed);
+                if self.corrupt == Some(index) {
+                    return Ok(SnapResponse::Chunk(b"[]".to_vec()));
+                }
+            }
+            self.inner.request(request).await
+        }
+    }
+
+    fn fresh_state() -> Arc<PLMutex<StateManager>> {
+        let mut state = StateManager::new();
+        state.mint("genesis-only", 1).unwrap();
+        state.advance_block();
+        Arc::new(PLMutex::new(state))
+    }
+
+    #[tokio::test]
+    async fn fresh_node_snap_syncs_and_follows_to_the_tip() {
+        let cluster = TestCluster::new("follow", 14).await;
+        let fresh = fresh_state();
+        let syncer = cluster.syncer(vec![cluster.peer("v0")], &scratch("follow-sync"));
+        let progress = syncer.progress();
+
+        let outcome = syncer.run(&fresh).await.unwrap();
+        assert_eq!(outcome.checkpoint.index, 10);
+        assert_eq!((outcome.fetched, outcome.resumed), (4, 0), "11 accounts, 3 per chunk");
+        assert!(progress.is_complete());
+        assert_eq!((progress.chunks_done(), progress.chunks_total()), (4, 4));
+        assert_eq!(fresh.lock().get_balance("genesis-only"), 0);
+
+        // Follow on from the checkpoint: only the blocks after it execute.
+        let mut core = BlockchainState::default();
+        for (addr, balance) in fresh.lock().export_balances() {
+            core.credit(&addr, balance as u64);
+        }
+        for (addr, nonce) in fresh.lock().export_nonces() {
+            core.set_nonce(&addr, nonce);
+        }
+        let chain = Blockchain::new(outcome.checkpoint, core, TransactionPool::new(1), None).unwrap();
+        let follower = ReplicaFollower::new(Arc::new(RwLock::new(chain)), Arc::clone(&fresh))
+            .with_proposer("v0", &cluster.pk);
+        let mut executed = 0;
+        for block in &cluster.blocks[11..] {
+            executed += follower.apply(block.clone()).await.unwrap().len();
+        }
+        assert_eq!(executed, 4, "a full replay would have executed 14 blocks");
+        assert_eq!(fresh.lock().state_root(), cluster.state.lock().state_root());
+    }
+
+    #[tokio::test]
+    async fn corrupted_chunk_is_refetched_from_another_peer() {
+        let cluster = TestCluster::new("corrupt", 10).await;
+        let bad = FaultyPeer { corrupt: Some(0), ..FaultyPeer::new(cluster.peer("v0")) };
+        let peers: Vec<Arc<dyn SnapPeer>> = vec![Arc::new(bad), cluster.peer("v1")];
+        let fresh = fresh_state();
+        let syncer = cluster.syncer(peers, &scratch("corrupt-sync"))
+            .with_config(SnapSyncConfig { parallel_chunks: 1, ..SnapSyncConfig::default() });
+
+        let outcome = syncer.run(&fresh).await.unwrap();
+        assert_eq!(outcome.penalized, vec!["faulty-v0".to_string()]);
+        assert_eq!(syncer.progress().peers_penalized(), 1);
+        assert_eq!(hex::encode(fresh.lock().state_root()), cluster.blocks[10].shard_state_root);
+    }
+
+    #[tokio::test]
+    async fn interrupted_sync_resumes_from_completed_chunks() {
+        let cluster = TestCluster::new("resume", 10).await;
+        let dir = scratch("resume-sync");
+        let fresh = fresh_state();
+
+        let dying = FaultyPeer { serve_chunks: Some(2), ..FaultyPeer::new(cluster.peer("v0")) };
+        let interrupted = cluster.syncer(vec![Arc::new(dying)], &dir)
+            .with_config(SnapSyncConfig { max_peer_failures: 1, ..SnapSyncConfig::default() });
+        assert!(matches!(interrupted.run(&fresh).await, Err(SnapSyncError::NoPeers(_))));
+        assert!(!interrupted.progress().is_complete());
+        assert_eq!(fresh.lock().get_balance("genesis-only"), 1, "nothing imported");
+
+        // "Restart": a new syncer over the same directory.
+        let counting = Arc::new(FaultyPeer::new(cluster.peer("v1")));
+        let resumed = cluster.syncer(vec![Arc::clone(&counting) as Arc<dyn SnapPeer>], &dir);
+        let outcome = resumed.run(&fresh).await.unwrap();
+        assert_eq!((outcome.resumed, outcome.fetched), (2, 2));
+        assert_eq!(counting.served.load(Ordering::Relaxed), 2);
+        assert_eq!(hex::encode(fresh.lock().state_root()), cluster.blocks[10].shard_state_root);
+    }
+
+    #[tokio::test]
+    async fn unsigned_checkpoints_are_refused() {
+        let cluster = TestCluster::new("unknown", 10).await;
+        let (stranger, _) = generate_tx_keypair();
+        let syncer = SnapSyncer::new(vec![cluster.peer("v0")], scratch("unknown-sync")).with_proposer("x", &stranger);
+        assert_eq!(syncer.run(&fresh_state()).await.unwrap_err(), SnapSyncError::NoManifest);
+    }
+}
@@ -0,0 +1,503 @@
+//! # Validator bonds
+//!
+//! Any account can bond stake to a consensus key and so become a validator
+//! candidate.  Bonds are ordinary transactions to reserved receivers, like
+//! reward transactions:
+//!
+//! ```text
+//! bleep:staking/bond/<consensus pk hex>   amount = stake   bond, or top up
+//! bleep:staking/unbond                    amount = 0       start unbonding; once
+//!                                                          `unbonding_blocks` have
+//!                                                          passed, withdraw
+//! ```
+//!
+//! Bonded funds sit in `STAKING_ACCOUNT`, and the records live in that
+//! account's storage, so they are covered by the state root and roll back
+//! with it:
+//!
+//! ```text
+//! params           StakingParams JSON
+//! bond:<id>        BondRecord JSON
+//! owner:<account>  validator id
+//! index            JSON list of validator ids
+//! ```
+//!
+//! A validator's id is the first 8 bytes of its consensus key in hex — the
+//! id a node signing with that key reports.  A key binds to one account and
+//! an account to one key; bonding again from the owner tops the stake up.
+//!
+//! `ledger_snapshot` adds the bonds that are not unbonding to the
+//! registry's validators; `EpochValidatorSets` takes its snapshots from it,
+//! so a bond made during epoch `e` joins the set of epoch `e + 2` (`e + 1`
+//! if made in the first block of `e`).  Heights in bond records are state
+//! heights.
+
+use bleep_crypto::key_change::PUBLIC_KEY_LEN;
+use bleep_state::state_manager::StateManager;
+use serde::{Deserialize, Serialize};
+
+use crate::validator_identity::ValidatorRegistry;
+use crate::validator_set::{snapshot, EpochValidatorSets, MissedSlots};
+
+pub use bleep_core::base_fee::{STAKING_ACCOUNT, STAKING_TX_PREFIX};
+
+const PARAMS_KEY: &[u8] = b"params";
+const INDEX_KEY: &[u8] = b"index";
+
+/// Governance-controlled bonding parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StakingParams {
+    /// Smallest total bond (µBLEEP) a validator may hold.
+    pub min_stake:        u128,
+    /// Blocks between starting to unbond and withdrawing.
+    pub unbonding_blocks: u64,
+}
+
+impl Default for StakingParams {
+    fn default() -> Self {
+        Self {
+            min_stake:        1_000 * 100_000_000,
+            unbonding_blocks: 1_000,
+        }
+    }
+}
+
+/// Bonding parameters in effect; the defaults until `set_params` is called.
+pub fn params(state: &StateManager) -> StakingParams {
+    read(state, PARAMS_KEY).unwrap_or_default()
+}
+
+pub fn set_params(state: &mut StateManager, params: &StakingParams) {
+    write(state, PARAMS_KEY, params);
+}
+
+// ── Transactions ──────────────────────────────────────────────────────────────
+
+/// What a staking transaction asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StakingTx {
+    /// Bond the transaction's amount to `consensus_key`.
+    Bond { consensus_key: Vec<u8> },
+    /// Start unbonding the sender's bond, or withdraw it once unbonded.
+    Unbond,
+}
+
+impl StakingTx {
+    /// Receiver of the transaction carrying this request.
+    pub fn receiver(&self) -> String {
+        match self {
+            StakingTx::Bond { consensus_key } => format!("{}bond/{}", STAKING_TX_PREFIX, hex::encode(consensus_key)),
+            StakingTx::Unbond => format!("{}unbond", STAKING_TX_PREFIX),
+        }
+    }
+
+    /// Parse a receiver; `None` if it is not a staking transaction at all.
+    pub fn parse(receiver: &str) -> Option<Result<StakingTx, String>> {
+        let rest = receiver.strip_prefix(STAKING_TX_PREFIX)?;
+        Some(match rest.split_once('/') {
+            None if rest == "unbond" => Ok(StakingTx::Unbond),
+            Some(("bond", key)) => hex::decode(key)
+                .map(|consensus_key| StakingTx::Bond { consensus_key })
+                .map_err(|_| format!("bad consensus key {:?}", key)),
+            _ => Err(format!("unknown staking transaction {:?}", receiver)),
+        })
+    }
+}
+
+/// Whether `receiver` marks a staking transaction.
+pub fn is_staking_tx(receiver: &str) -> bool {
+    receiver.starts_with(STAKING_TX_PREFIX)
+}
+
+/// Validator id of a consensus key.
+pub fn validator_id_for_key(consensus_key: &[u8]) -> String {
+    hex::encode(&consensus_key[..consensus_key.len().min(8)])
+}
+
+// ── Records ───────────────────────────────────────────────────────────────────
+
+/// Stake one account has bonded to one consensus key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BondRecord {
+    pub validator_id:  String,
+    pub owner:         String,
+    /// Hex SPHINCS+ public key blocks are signed with.
+    pub consensus_key: String,
+    pub stake:         u128,
+    /// Height of the block carrying the first bond.
+    pub bonded_at:     u64,
+    /// Height of the block carrying the unbond, once unbonding.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unbonding_at:  Option<u64>,
+}
+
+impl BondRecord {
+    /// Height from which the stake can be withdrawn, once unbonding.
+    pub fn withdrawable_at(&self, params: &StakingParams) -> Option<u64> {
+        self.unbonding_at.map(|h| h.saturating_add(params.unbonding_blocks))
+    }
+}
+
+/// Bond of validator `validator_id`.
+pub fn bond_of(state: &StateManager, validator_id: &str) -> Option<BondRecord> {
+    read(state, format!("bond:{}", validator_id).as_bytes())
+}
+
+/// Bond owned by `account`.
+pub fn bond_of_owner(state: &StateManager, account: &str) -> Option<BondRecord> {
+    let id: String = read(state, format!("owner:{}", account).as_bytes())?;
+    bond_of(state, &id)
+}
+
+/// Every bond, in bonding order.
+pub fn bonds(state: &StateManager) -> Vec<BondRecord> {
+    index(state).iter().filter_map(|id| bond_of(state, id)).collect()
+}
+
+/// Bonded validator whose consensus key is `public_key`.
+pub fn validator_for_key(state: &StateManager, public_key: &[u8]) -> Option<String> {
+    bond_of(state, &validator_id_for_key(public_key))
+        .filter(|b| b.consensus_key == hex::encode(public_key))
+        .map(|b| b.validator_id)
+}
+
+/// `(id, stake)` of every bond that is not unbonding.
+pub fn active_stakes(state: &StateManager) -> Vec<(String, u128)> {
+    bonds(state).into_iter()
+        .filter(|b| b.unbonding_at.is_none())
+        .map(|b| (b.validator_id, b.stake))
+        .collect()
+}
+
+/// The staking ledger a validator set is selected from: the registry's
+/// participating validators, then the bonds of validators it does not know.
+pub fn ledger_snapshot(registry: &ValidatorRegistry, state: &StateManager) -> Vec<(String, u128)> {
+    let mut stakes = snapshot(registry);
+    for (id, stake) in active_stakes(state) {
+        if !stakes.iter().any(|(known, _)| *known == id) {
+            stakes.push((id, stake));
+        }
+    }
+    stakes
+}
+
+// ── Bonding ───────────────────────────────────────────────────────────────────
+
+/// A bond about to be submitted or applied.
+#[derive(Debug, Clone, Copy)]
+pub struct BondRequest<'a> {
+    pub sender:        &'a str,
+    pub consensus_key: &'a [u8],
+    pub amount:        u128,
+}
+
+impl BondRequest<'_> {
+    pub fn validator_id(&self) -> String {
+        validator_id_for_key(self.consensus_key)
+    }
+
+    /// The validator's stake after this bond, or why it must be refused.
+    /// `key_bond` is the bond already on the consensus key, `owner_bond`
+    /// the sender's.
+    pub fn check(
+        &self,
+        params:     &StakingParams,
+        balance:    u128,
+        key_bond:   Option<&BondRecord>,
+        owner_bond: Option<&BondRecord>,
+    ) -> Result<u128, String> {
+        if self.consensus_key.len() != PUBLIC_KEY_LEN {
+            return Err(format!(
+                "consensus key must be {} bytes, got {}", PUBLIC_KEY_LEN, self.consensus_key.len()
+            ));
+        }
+        let id = self.validator_id();
+        if let Some(bond) = key_bond.filter(|b| b.owner != self.sender) {
+            return Err(format!("consensus key {} is already bound to {}", id, bond.owner));
+        }
+        if let Some(bond) = owner_bond.filter(|b| b.validator_id != id) {
+            return Err(format!("{} already bonds validator {}", self.sender, bond.validator_id));
+        }
+        if key_bond.is_some_and(|b| b.unbonding_at.is_some()) {
+            return Err(format!("validator {} is unbonding", id));
+        }
+        if self.amount == 0 {
+            return Err("bond amount must be > 0".into());
+        }
+        if balance < self.amount {
+            return Err(format!("insufficient balance: {} < {}", balance, self.amount));
+        }
+        let stake = key_bond.map_or(0, |b| b.stake).saturating_add(self.amount);
+        if stake < params.min_stake {
+            return Err(format!("stake {} below the minimum {}", stake, params.min_stake));
+        }
+        Ok(stake)
+    }
+}
+
+/// Carry out a staking request of `sender`, in the block at `height`.
+pub(crate) fn apply(
+    state:   &mut StateManager,
+    sender:  &str,
+    amount:  u128,
+    request: StakingTx,
+    height:  u64,
+) -> Result<(), String> {
+    let params = params(state);
+    match request {
+        StakingTx::Bond { consensus_key } => {
+            let request = BondRequest { sender, consensus_key: &consensus_key, amount };
+            let key_bond = bond_of(state, &request.validator_id());
+            let stake = request.check(
+                &params,
+                state.get_balance(sender),
+                key_bond.as_ref(),
+                bond_of_owner(state, sender).as_ref(),
+            )?;
+            if !state.apply_transfer(sender, STAKING_ACCOUNT, amount) {
+                return Err("insufficient funds".into());
+            }
+            let record = match key_bond {
+                Some(bond) => BondRecord { stake, ..bond },
+                None => {
+                    let id = request.validator_id();
+                    write(state, format!("owner:{}", sender).as_bytes(), &id);
+                    let mut ids = index(state);
+                    ids.push(id.clone());
+                    write(state, INDEX_KEY, &ids);
+                    BondRecord {
+                        validator_id:  id,
+                        owner:         sender.to_string(),
+                        consensus_key: hex::encode(&consensus_key),
+                        stake,
+                        bonded_at:     height,
+                        unbonding_at:  None,
+                    }
+                }
+            };
+            write(state, format!("bond:{}", record.validator_id).as_bytes(), &record);
+        }
+        StakingTx::Unbond => {
+            if amount != 0 {
+                return Err("unbond must not transfer funds".into());
+            }
+            let mut bond = bond_of_owner(state, sender).ok_or_else(|| format!("{} has no bond", sender))?;
+            match bond.withdrawable_at(&params) {
+                None => {
+                    bond.unbonding_at = Some(height);
+                    write(state, format!("bond:{}", bond.validator_id).as_bytes(), &bond);
+                }
+                Some(at) if height < at => {
+                    return Err(format!("unbonding: {} blocks until withdrawal", at - height));
+                }
+                Some(_) => {
+                    let held = state.get_balance(STAKING_ACCOUNT);
+                    if held < bond.stake {
+                        return Err(format!("staking account holds {} but {} is bonded", held, bond.stake));
+                    }
+                    state.set_balance(STAKING_ACCOUNT, held - bond.stake);
+                    let balance = state.get_balance(sender);
+                    state.set_balance(sender, balance + bond.stake);
+                    state.set_storage(STAKING_ACCOUNT, format!("bond:{}", bond.validator_id).as_bytes(), &[]);
+                    state.set_storage(STAKING_ACCOUNT, format!("owner:{}", sender).as_bytes(), &[]);
+                    let ids: Vec<String> = index(state).into_iter().filter(|id| *id != bond.validator_id).collect();
+                    write(state, INDEX_KEY, &ids);
+                }
+            }
+            state.increment_nonce(sender);
+        }
+    }
+    Ok(())
+}
+
+// ── Status ────────────────────────────────────────────────────────────────────
+
+/// Where a bond stands, as `GET /rpc/validator/bond/{id}` reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BondStatus {
+    #[serde(flatten)]
+    pub bond:   BondRecord,
+    /// `bonded`, `unbonding` or `withdrawable`.
+    pub phase:  String,
+    /// State height the status was taken at.
+    pub height: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub withdrawable_at:           Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks_until_withdrawable: Option<u64>,
+    /// Epoch of the chain tip, when validator sets are tracked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epoch:            Option<u64>,
+    /// Member of the current epoch's set.
+    #[serde(default)]
+    pub in_current_set:   bool,
+    /// Member of the next epoch's (already decided) set.
+    #[serde(default)]
+    pub in_next_set:      bool,
+    /// First epoch the validator will be in the set, while it is not yet.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activation_epoch: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocks_until_active: Option<u64>,
+    /// Most recent slots it led but did not sign, oldest first.
+    #[serde(default)]
+    pub missed_slots:     Vec<u64>,
+    #[serde(default)]
+    pub missed_total:     u64,
+}
+
+impl BondStatus {
+    /// Unbonding progress of `bond` at state height `height`.
+    pub fn new(bond: BondRecord, params: &StakingParams, height: u64) -> Self {
+        let withdrawable_at = bond.withdrawable_at(params);
+        let phase = match withdrawable_at {
+            None => "bonded",
+            Some(at) if height < at => "unbonding",
+            Some(_) => "withdrawable",
+        };
+        BondStatus {
+            bond,
+            phase: phase.to_string(),
+            height,
+            withdrawable_at,
+            blocks_until_withdrawable: withdrawable_at.map(|at| at.saturating_sub(height)),
+            epoch: None,
+            in_current_set: false,
+            in_next_set: false,
+            activation_epoch: None,
+            blocks_until_active: None,
+            missed_slots: Vec::new(),
+            missed_total: 0,
+        }
+    }
+
+    /// Add set membership and the activation countdown at chain height
+    /// `chain_height`.  A bond not yet in a decided set is picked up by
+    /// the snapshot at the start of the next epoch.
+    pub fn with_sets(mut self, sets: &EpochValidatorSets, chain_height: u64) -> Self {
+        let id = &self.bond.validator_id;
+        let epoch = sets.epoch_of(chain_height);
+        let member_of = |e: u64| sets.validator_set_at(e * sets.config().blocks_per_epoch)
+            .is_some_and(|s| s.contains(id));
+        self.epoch = Some(epoch);
+        self.in_current_set = member_of(epoch);
+        self.in_next_set = member_of(epoch + 1);
+        if !self.in_current_set && self.bond.unbonding_at.is_none() {
+            let activation = if self.in_next_set { epoch + 1 } else { epoch + 2 };
+            self.activation_epoch = Some(activation);
+            self.blocks_until_active = Some(
+                (activation * sets.config().blocks_per_epoch).saturating_sub(chain_height),
+            );
+        }
+        self
+    }
+
+    pub fn with_missed(mut self, missed: &MissedSlots) -> Self {
+        self.missed_slots = missed.recent(&self.bond.validator_id);
+        self.missed_total = missed.total(&self.bond.validator_id);
+        self
+    }
+}
+
+// ── Storage ───────────────────────────────────────────────────────────────────
+
+fn read<T: serde::de::DeserializeOwned>(state: &StateManager, key: &[u8]) -> Option<T> {
+    serde_json::from_slice(&state.get_storage(STAKING_ACCOUNT, key)?).ok()
+}
+
+fn write<T: Serialize>(state: &mut StateManager, key: &[u8], value: &T) {
+    let bytes = serde_json::to_vec(value).unwrap_or_default();
+    state.set_storage(STAKING_ACCOUNT, key, &bytes);
+}
+
+fn index(state: &StateManager) -> Vec<String> {
+    read(state, INDEX_KEY).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "alice";
+    const BOB: &str = "bob";
+
+    fn state_with(params: StakingParams) -> StateManager {
+        let mut state = StateManager::new();
+        set_params(&mut state, &params);
+        state.mint(ALICE, 10_000).unwrap();
+        state.mint(BOB, 10_000).unwrap();
+        state
+    }
+
+    fn key(byte: u8) -> Vec<u8> {
+        vec![byte; PUBLIC_KEY_LEN]
+    }
+
+    fn bond(state: &mut StateManager, sender: &str, key: &[u8], amount: u128, height: u64) -> Result<(), String> {
+        apply(state, sender, amount, StakingTx::Bond { consensus_key: key.to_vec() }, height)
+    }
+
+    #[test]
+    fn receivers_round_trip() {
+        for tx in [StakingTx::Bond { consensus_key: key(7) }, StakingTx::Unbond] {
+            assert_eq!(StakingTx::parse(&tx.receiver()), Some(Ok(tx)));
+        }
+        assert!(StakingTx::parse("bleep:staking/bond/zz").unwrap().is_err());
+        assert_eq!(StakingTx::parse("bleep:rewards/claim"), None);
+    }
+
+    #[test]
+    fn a_key_binds_to_one_account() {
+        let mut state = state_with(StakingParams { min_stake: 1_000, unbonding_blocks: 10 });
+        let (k1, k2) = (key(1), key(2));
+
+        assert_eq!(bond(&mut state, ALICE, &k1, 999, 1), Err("stake 999 below the minimum 1000".into()));
+        assert_eq!(bond(&mut state, ALICE, &k1, 20_000, 1), Err("insufficient balance: 10000 < 20000".into()));
+        bond(&mut state, ALICE, &k1, 1_000, 1).unwrap();
+        // Topping up needs no minimum of its own.
+        bond(&mut state, ALICE, &k1, 500, 2).unwrap();
+
+        let id = validator_id_for_key(&k1);
+        assert_eq!(
+            bond(&mut state, BOB, &k1, 5_000, 3),
+            Err(format!("consensus key {} is already bound to {}", id, ALICE)),
+        );
+        assert_eq!(
+            bond(&mut state, ALICE, &k2, 5_000, 3),
+            Err(format!("{} already bonds validator {}", ALICE, id)),
+        );
+
+        let record = bond_of_owner(&state, ALICE).unwrap();
+        assert_eq!((record.stake, record.bonded_at), (1_500, 1));
+        assert_eq!(validator_for_key(&state, &k1), Some(id.clone()));
+        assert_eq!(active_stakes(&state), vec![(id, 1_500)]);
+        assert_eq!(state.get_balance(STAKING_ACCOUNT), 1_500);
+        assert_eq!(state.get_balance(ALICE), 8_500);
+    }
+
+    #[test]
+    fn unbonded_stake_is_withdrawn_after_the_delay() {
+        let params = StakingParams { min_stake: 1_000, unbonding_blocks: 10 };
+        let mut state = state_with(params);
+        bond(&mut state, ALICE, &key(1), 4_000, 1).unwrap();
+
+        apply(&mut state, ALICE, 0, StakingTx::Unbond, 5).unwrap();
+        assert!(active_stakes(&state).is_empty());
+        let status = BondStatus::new(bond_of_owner(&state, ALICE).unwrap(), &params, 8);
+        assert_eq!(status.phase, "unbonding");
+        assert_eq!(status.blocks_until_withdrawable, Some(7));
+
+        assert_eq!(
+            apply(&mut state, ALICE, 0, StakingTx::Unbond, 14),
+            Err("unbonding: 1 blocks until withdrawal".into()),
+        );
+        apply(&mut state, ALICE, 0, StakingTx::Unbond, 15).unwrap();
+        assert_eq!(bond_of_owner(&state, ALICE), None);
+        assert!(bonds(&state).is_empty());
+        assert_eq!(state.get_balance(ALICE), 10_000);
+        assert_eq!(state.get_balance(STAKING_ACCOUNT), 0);
+
+        // The key is free again.
+        bond(&mut state, BOB, &key(1), 1_000, 16).unwrap();
+    }
+}
@@ -0,0 +1,379 @@
+//! # Storage consistency check and recovery
+//!
+//! A committed block touches three stores: the `BlockStore` archive, the
+//! `StateManager` database and the per-shard height records.  A crash or a
+//! lost write between them leaves a node that starts "fine" and then serves
+//! wrong balances.  `fsck` runs at startup and from `bleep-cli state fsck`:
+//!
+//!   - the tip block decodes, its header index matches the height it is
+//!     stored at and it links to its parent by hash
+//!   - the last `receipts_window` blocks' receipts hash to their recorded
+//!     receipts root
+//!   - the state each block produced is still materialisable and its root
+//!     matches the recorded one
+//!   - no shard records a height above the consistent tip
+//!
+//! Recovery truncates blocks, unwinds the state and resets shard heights to
+//! the highest height where all of the above agree — never below the last
+//! finalized checkpoint — and reports the dropped range so it can be
+//! fetched again from peers.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use bleep_state::state_manager::StateManager;
+
+use crate::block_execution::receipts_root;
+use crate::block_store::{BlockStore, StoredBlock};
+
+/// Default number of blocks below the tip whose receipts are re-hashed.
+pub const DEFAULT_RECEIPTS_WINDOW: u64 = 64;
+
+#[derive(Debug, Clone)]
+pub struct FsckOptions {
+    /// Blocks checked below and including the tip.
+    pub receipts_window:   u64,
+    /// Last finalized checkpoint height; recovery never goes below it.
+    pub checkpoint_height: u64,
+    /// Where scratch forks for past state roots are built.
+    pub scratch_dir:       PathBuf,
+}
+
+impl Default for FsckOptions {
+    fn default() -> Self {
+        FsckOptions {
+            receipts_window:   DEFAULT_RECEIPTS_WINDOW,
+            checkpoint_height: 0,
+            scratch_dir:       std::env::temp_dir(),
+        }
+    }
+}
+
+// ── Report ────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FsckIssue {
+    /// The block file is missing or does not decode.
+    UnreadableBlock { height: u64, error: String },
+    /// The block header's index disagrees with the height it is stored at.
+    IndexMismatch { height: u64, index: u64 },
+    /// `previous_hash` is not the hash of the stored parent.
+    BrokenHashLink { height: u64, expected: String, actual: String },
+    ReceiptsRootMismatch { height: u64, expected: String, actual: String },
+    /// The state database never reached the height this block produced.
+    StateMissing { height: u64, needed: u64, state_height: u64 },
+    /// The state this block produced has been pruned from the journal.
+    StatePruned { height: u64, needed: u64 },
+    StateRootMismatch { height: u64, expected: String, actual: String },
+    /// The state database is past what the consistent tip produced.
+    StateAhead { state_height: u64, expected: u64 },
+    ShardAhead { shard_id: u64, shard_height: u64, consistent_height: u64 },
+    /// The transactions do not hash to the header's Merkle root
+    /// (`chain_audit` only).
+    TxRootMismatch { height: u64, expected: String, actual: String },
+    /// The header signature does not verify against its embedded key
+    /// (`chain_audit` only).
+    BadSignature { height: u64 },
+}
+
+/// What `recover` changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Recovery {
+    pub truncated_blocks:     Vec<u64>,
+    pub state_rolled_back_to: Option<u64>,
+    /// `(shard_id, previous height)` of every shard reset.
+    pub truncated_shards:     Vec<(u64, u64)>,
+    /// First block height to fetch again from peers.
+    pub resync_from:          Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckReport {
+    pub tip:                     Option<u64>,
+    pub state_height:            u64,
+    /// Highest block height where blocks, state and shards agree.
+    pub consistent_height:       u64,
+    /// State height matching `consistent_height`, when a stored block pins it.
+    pub consistent_state_height: Option<u64>,
+    pub issues:                  Vec<FsckIssue>,
+    pub recovery:                Option<Recovery>,
+}
+
+impl FsckReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+// ── Check ─────────────────────────────────────────────────────────────────────
+
+/// Check `store` against `state` without modifying either.
+///
+/// `state` must have its trie loaded (`rebuild_trie_from_db`) so its root
+/// is meaningful.
+pub fn check(store: &BlockStore, state: &mut StateManager, opts: &FsckOptions) -> Result<FsckReport, String> {
+    let tip = store.tip()?;
+    let state_height = state.block_height();
+    let mut issues = Vec::new();
+    let mut consistent = 0;
+    let mut consistent_state = None;
+
+    if let Some(tip) = tip {
+        // A snap-synced node holds no blocks below its checkpoint.
+        let first = store.heights()?.first().copied().unwrap_or(1);
+        let low = tip.saturating_sub(opts.receipts_window.saturating_sub(1)).max(first).max(1);
+
+        // Blocks: keep the longest sound run from the bottom of the window.
+        let mut parent_hash = match low {
+            1 => None,
+            _ => store.get(low - 1).ok().flatten().map(|b| b.block.compute_hash()),
+        };
+        let mut blocks = Vec::new();
+        for h in low..=tip {
+            match check_block(store, h, parent_hash.as_deref()) {
+                Ok(b) => {
+                    parent_hash = Some(b.block.compute_hash());
+                    blocks.push(b);
+                }
+                Err(issue) => {
+                    issues.push(issue);
+                    break;
+                }
+            }
+        }
+
+        // State: the highest sound block whose post-state we can rebuild
+        // and whose root matches.
+        consistent = low - 1;
+        consistent_state = blocks.first().map(|b| b.parent_state_height);
+        for b in blocks.iter().rev() {
+            let height = b.height();
+            let needed = b.post_state_height();
+            if needed > state_height {
+                issues.push(FsckIssue::StateMissing { height, needed, state_height });
+                continue;
+            }
+            if needed < state.earliest_queryable_height() {
+                issues.push(FsckIssue::StatePruned { height, needed });
+                break;
+            }
+            let actual = hex::encode(state_root_at(state, needed, &opts.scratch_dir)?);
+            if actual != b.state_root {
+                issues.push(FsckIssue::StateRootMismatch { height, expected: b.state_root.clone(), actual });
+                continue;
+            }
+            consistent = height;
+            consistent_state = Some(needed);
+            break;
+        }
+
+        if let Some(expected) = consistent_state {
+            if state_height > expected {
+                issues.push(FsckIssue::StateAhead { state_height, expected });
+            }
+        }
+    }
+
+    let shards = state.shard_heights().map_err(|e| e.to_string())?;
+    for (shard_id, shard_height) in shards {
+        if shard_height > consistent {
+            issues.push(FsckIssue::ShardAhead { shard_id, shard_height, consistent_height: consistent });
+        }
+    }
+
+    Ok(FsckReport {
+        tip,
+        state_height,
+        consistent_height: consistent,
+        consistent_state_height: consistent_state,
+        issues,
+        recovery: None,
+    })
+}
+
+pub(crate) fn check_block(store: &BlockStore, height: u64, parent_hash: Option<&str>) -> Result<StoredBlock, FsckIssue> {
+    let b = match store.get(height) {
+        Ok(Some(b)) => b,
+        Ok(None) => return Err(FsckIssue::UnreadableBlock { height, error: "missing".into() }),
+        Err(error) => return Err(FsckIssue::UnreadableBlock { height, error }),
+    };
+    if b.block.index != height {
+        return Err(FsckIssue::IndexMismatch { height, index: b.block.index });
+    }
+    if let Some(expected) = parent_hash {
+        if b.block.previous_hash != expected {
+            return Err(FsckIssue::BrokenHashLink {
+                height,
+                expected: expected.to_string(),
+                actual:   b.block.previous_hash.clone(),
+            });
+        }
+    }
+    let actual = hex::encode(receipts_root(&b.receipts));
+    if actual != b.receipts_root {
+        return Err(FsckIssue::ReceiptsRootMismatch { height, expected: b.receipts_root.clone(), actual });
+    }
+    Ok(b)
+}
+
+/// State root at `height`, via a scratch fork when it is in the past.
+fn state_root_at(state: &mut StateManager, height: u64, scratch: &Path) -> Result<[u8; 32], String> {
+    if height == state.block_height() {
+        return Ok(state.state_root());
+    }
+    let dir = scratch.join(format!("bleep-fsck-{}-{}", std::process::id(), height));
+    let root = state.fork_at(height, &dir)
+        .map(|mut fork| fork.state_root())
+        .map_err(|e| e.to_string());
+    let _ = std::fs::remove_dir_all(&dir);
+    root
+}
+
+// ── Recovery ──────────────────────────────────────────────────────────────────
+
+/// Roll blocks, state and shard heights back to `report.consistent_height`.
+pub fn recover(
+    store:  &BlockStore,
+    state:  &mut StateManager,
+    report: &FsckReport,
+    opts:   &FsckOptions,
+) -> Result<Recovery, String> {
+    let target = report.consistent_height;
+    if target < opts.checkpoint_height {
+        return Err(format!(
+            "no consistent height at or above checkpoint {} (best {}); full re-sync required",
+            opts.checkpoint_height, target
+        ));
+    }
+
+    let truncated_blocks = store.truncate_above(target)?;
+
+    let mut state_rolled_back_to = None;
+    if let Some(h) = report.consistent_state_height {
+        if h < state.block_height() {
+            state.rollback_to(h).map_err(|e| e.to_string())?;
+            state_rolled_back_to = Some(h);
+        }
+    }
+
+    let mut truncated_shards = Vec::new();
+    for (shard_id, h) in state.shard_heights().map_err(|e| e.to_string())? {
+        if h > target {
+            state.set_shard_height(shard_id, target).map_err(|e| e.to_string())?;
+            truncated_shards.push((shard_id, h));
+        }
+    }
+
+    let resync_from = report.tip.filter(|tip| *tip > target).map(|_| target + 1);
+    Ok(Recovery { truncated_blocks, state_rolled_back_to, truncated_shards, resync_from })
+}
+
+/// `check`, then `recover` if anything is wrong and `repair` is set.
+pub fn fsck(
+    store:  &BlockStore,
+    state:  &mut StateManager,
+    opts:   &FsckOptions,
+    repair: bool,
+) -> Result<FsckReport, String> {
+    let mut report = check(store, state, opts)?;
+    if repair && !report.is_clean() {
+        report.recovery = Some(recover(store, state, &report, opts)?);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_execution::{execute_block, production_executor};
+    use bleep_core::block::{Block, ChainId, Transaction};
+    use parking_lot::Mutex as PLMutex;
+
+    fn scratch(tag: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("bleep-fsck-test-{}-{}-{}", tag, std::process::id(), nanos))
+    }
+
+    fn tx(from: &str, to: &str, amount: u64, ts: u64, nonce: u64) -> Transaction {
+        Transaction { sender: from.into(), receiver: to.into(), amount, timestamp: ts, signature: Vec::new(), max_fee: 0, tip: 0, nonce, chain_id: ChainId::NONE }
+    }
+
+    /// Produce blocks 1..=3 on shard 0 and archive them.
+    async fn build_chain(tag: &str) -> (StateManager, BlockStore) {
+        let state = PLMutex::new(StateManager::new());
+        {
+            let mut s = state.lock();
+            s.mint("alice", 1_000).unwrap();
+            s.advance_block();
+        }
+        let store = BlockStore::open(scratch(tag)).unwrap();
+        let executor = production_executor(false);
+        let mut prev = "0".repeat(64);
+        for i in 1..=3u64 {
+            let txs = vec![tx("alice", "bob", 10 * i, i, i - 1)];
+            let exec = execute_block(&executor, &state, &txs).await;
+            let block = Block::new(i, txs, prev);
+            prev = block.compute_hash();
+            store.put(&StoredBlock::new(block, &exec)).unwrap();
+            state.lock().set_shard_height(0, i).unwrap();
+        }
+        (state.into_inner(), store)
+    }
+
+    #[tokio::test]
+    async fn test_clean_store_reports_no_issues() {
+        let (mut state, store) = build_chain("clean").await;
+        let report = fsck(&store, &mut state, &FsckOptions::default(), true).unwrap();
+        assert!(report.is_clean(), "{:?}", report.issues);
+        assert_eq!(report.tip, Some(3));
+        assert_eq!(report.consistent_height, 3);
+        assert!(report.recovery.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lost_tip_state_rolls_back() {
+        let (source, store) = build_chain("lost-state").await;
+        // The state database lost block 3's write.
+        let tip = store.get(3).unwrap().unwrap();
+        let mut state = source.fork_at(tip.parent_state_height, scratch("lost-state-db")).unwrap();
+        state.set_shard_height(0, 2).unwrap();
+
+        let report = fsck(&store, &mut state, &FsckOptions::default(), true).unwrap();
+        assert!(matches!(report.issues[0], FsckIssue::StateMissing { height: 3, .. }));
+        assert_eq!(report.consistent_height, 2);
+        let recovery = report.recovery.unwrap();
+        assert_eq!(recovery.truncated_blocks, vec![3]);
+        assert_eq!(recovery.resync_from, Some(3));
+        assert_eq!(store.tip().unwrap(), Some(2));
+
+        // A second pass finds nothing left to fix.
+        assert!(check(&store, &mut state, &FsckOptions::default()).unwrap().is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_shard_ahead_of_tip_is_truncated() {
+        let (mut state, store) = build_chain("shard").await;
+        state.set_shard_height(0, 7).unwrap();
+
+        let report = fsck(&store, &mut state, &FsckOptions::default(), true).unwrap();
+        assert_eq!(report.issues, vec![FsckIssue::ShardAhead { shard_id: 0, shard_height: 7, consistent_height: 3 }]);
+        let recovery = report.recovery.unwrap();
+        assert_eq!(recovery.truncated_shards, vec![(0, 7)]);
+        assert!(recovery.truncated_blocks.is_empty());
+        assert_eq!(state.shard_heights().unwrap()[&0], 3);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_stops_at_checkpoint() {
+        let (source, store) = build_chain("checkpoint").await;
+        let mut state = source.fork_at(store.get(3).unwrap().unwrap().parent_state_height, scratch("checkpoint-db")).unwrap();
+        let opts = FsckOptions { checkpoint_height: 3, ..Default::default() };
+        assert!(fsck(&store, &mut state, &opts, true).is_err());
+        assert_eq!(store.tip().unwrap(), Some(3), "nothing truncated");
+    }
+}
@@ -0,0 +1,23 @@
+//! # Shared consensus types
+//!
+//! `BLEEPAdaptiveConsensus` and `AIAdaptiveConsensus` both use these, so a
+//! mode predicted by the AI engine can be handed straight to
+//! `BLEEPAdaptiveConsensus::switch_consensus_mode`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsensusMode {
+    PoS,
+    PBFT,
+    PoW,
+}
+
+#[derive(Debug, Clone)]
+pub struct Validator {
+    pub id: String,
+    pub reputation: f64,
+    /// Network latency in ms.
+    pub latency: u64,
+    pub stake: u64,
+    pub active: bool,
+    pub last_signed_block: u64,
+}
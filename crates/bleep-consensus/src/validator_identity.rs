@@ -11,6 +11,12 @@
 use serde::{Serialize, Deserialize};
 use std::collections::{HashMap, BTreeSet};
 use std::fmt;
+use std::sync::Arc;
+
+use bleep_core::fork_choice::StakeWeights;
+use parking_lot::Mutex;
+
+use crate::evidence::validator_for_key;
 
 /// Validator identity using post-quantum cryptography.
 /// 
@@ -437,6 +443,18 @@ impl Default for ValidatorRegistry {
     }
 }
 
+/// Fork-choice stakes from the live registry: a block weighs its signer's
+/// voting power, and a key no active validator signs with weighs nothing.
+pub struct RegistryStakes(pub Arc<Mutex<ValidatorRegistry>>);
+
+impl StakeWeights for RegistryStakes {
+    fn weight(&self, _epoch_id: u64, signer: &[u8]) -> Option<u64> {
+        let registry = self.0.lock();
+        let id = validator_for_key(&registry, signer)?;
+        Some(registry.get_voting_power(&id).min(u64::MAX as u128) as u64)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -505,6 +523,17 @@ mod tests {
         assert_eq!(registry.total_active_stake(), 1000000);
     }
 
+    #[test]
+    fn test_registry_stakes_weigh_active_signers_only() {
+        let mut registry = ValidatorRegistry::new();
+        registry.register_validator(ValidatorIdentity::new("v1".into(), vec![0u8; 1568], hex::encode(b"v1-key"), 1000000, 0).unwrap()).unwrap();
+        registry.activate_validator("v1").unwrap();
+        let stakes = RegistryStakes(Arc::new(Mutex::new(registry)));
+
+        assert_eq!(stakes.weight(0, b"v1-key"), Some(1000000));
+        assert_eq!(stakes.weight(0, b"stranger"), None);
+    }
+
     #[test]
     fn test_validator_registry_slashing() {
         let mut registry = ValidatorRegistry::new();
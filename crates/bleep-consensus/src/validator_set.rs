@@ -0,0 +1,489 @@
+//! # Epoch validator sets
+//!
+//! The active validator set changes only at epoch boundaries, and every
+//! change is known one full epoch before it takes effect:
+//!
+//! ```text
+//!   epoch:      e-1        │        e         │       e+1
+//!   first block of e ──────┘ snapshot S_e      └──── S_e is the active set
+//! ```
+//!
+//! At the first block of epoch `e` the staking ledger (`ValidatorRegistry`)
+//! is snapshotted; the top `max_validators` participating validators by
+//! bonded stake that meet `min_stake` form the set for epoch `e + 1`.  All
+//! nodes see the same ledger at the same height, so they derive the same
+//! set without extra messages.  Epochs 0 and 1 use the genesis set.
+//!
+//! Quorum math, leader selection and checkpoint (finality certificate)
+//! verification read `validator_set_at(height)`, so a historical block is
+//! always checked against the set of its own epoch.
+//!
+//! A producer whose leader's key is not held signs the slot itself and
+//! records it in `MissedSlots` against the leader.
+//!
+//! A seeded snapshot (`apply_seeded_snapshot`) breaks stake ties by
+//! `SHA-256(seed || id)` instead of by id, so equal-stake validators take
+//! turns at the cut-off and in leader order from one epoch to the next.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::finality::FinalizyCertificate;
+use crate::validator_identity::ValidatorRegistry;
+
+/// Rotation parameters; fixed at genesis like `EpochConfig`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ValidatorSetConfig {
+    /// Blocks per epoch (`E`). Must be > 0.
+    pub blocks_per_epoch: u64,
+    /// Size cap of the active set (`N`).
+    pub max_validators:   usize,
+    /// Bonded stake (microBLEEP) required to be selected.
+    pub min_stake:        u128,
+}
+
+impl Default for ValidatorSetConfig {
+    fn default() -> Self {
+        Self {
+            blocks_per_epoch: 100,
+            max_validators:   100,
+            min_stake:        1,
+        }
+    }
+}
+
+/// One member of an epoch's set, with the stake it was selected with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SetMember {
+    pub id:    String,
+    pub stake: u128,
+}
+
+/// The validators active during one epoch, ordered by stake (desc) then id,
+/// or then `SHA-256(seed || id)` for a seeded set.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSet {
+    pub epoch:       u64,
+    pub members:     Vec<SetMember>,
+    pub total_stake: u128,
+}
+
+impl ValidatorSet {
+    fn select(epoch: u64, stakes: &[(String, u128)], config: &ValidatorSetConfig, seed: Option<&[u8]>) -> Self {
+        let mut members: Vec<SetMember> = stakes.iter()
+            .filter(|(_, stake)| *stake >= config.min_stake)
+            .map(|(id, stake)| SetMember { id: id.clone(), stake: *stake })
+            .collect();
+        let tiebreak = |id: &str| -> [u8; 32] {
+            match seed {
+                Some(seed) => Sha256::new().chain_update(seed).chain_update(id.as_bytes()).finalize().into(),
+                None => [0u8; 32],
+            }
+        };
+        members.sort_by(|a, b| {
+            b.stake.cmp(&a.stake)
+                .then_with(|| tiebreak(&a.id).cmp(&tiebreak(&b.id)))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        members.truncate(config.max_validators);
+        let total_stake = members.iter().map(|m| m.stake).sum();
+        ValidatorSet { epoch, members, total_stake }
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.members.iter().any(|m| m.id == id)
+    }
+
+    pub fn stake_of(&self, id: &str) -> Option<u128> {
+        self.members.iter().find(|m| m.id == id).map(|m| m.stake)
+    }
+
+    /// Votes needed for a BFT quorum: `⌊2n/3⌋ + 1`.
+    pub fn quorum_size(&self) -> usize {
+        if self.members.is_empty() { 0 } else { self.members.len() * 2 / 3 + 1 }
+    }
+
+    /// Strictly more than two thirds of the set's stake.
+    pub fn has_stake_quorum(&self, signed_stake: u128) -> bool {
+        signed_stake > self.total_stake * 2 / 3
+    }
+
+    /// Proposer for `height`: round-robin over the members, so any
+    /// `len()` consecutive heights give every member one turn.
+    pub fn leader(&self, height: u64) -> Option<&SetMember> {
+        if self.members.is_empty() {
+            return None;
+        }
+        self.members.get((height % self.members.len() as u64) as usize)
+    }
+}
+
+/// Why a validator left the active set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeaveReason {
+    /// Still bonded but no longer in the top `max_validators`.
+    Outranked,
+    /// Stake fell below `min_stake` (e.g. slashed).
+    BelowMinimum,
+    /// No longer participating: exiting, exited or ejected.
+    Inactive,
+}
+
+/// Set change, emitted at the snapshot that decides it.  `epoch` is the
+/// epoch in which the change takes effect.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidatorSetEvent {
+    Entered { id: String, epoch: u64, stake: u128 },
+    Left    { id: String, epoch: u64, reason: LeaveReason },
+}
+
+/// The chain entered `epoch`: who joined and who left relative to the
+/// previous epoch's set, each in id order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochTransition {
+    pub epoch:   u64,
+    pub joining: Vec<String>,
+    pub leaving: Vec<String>,
+}
+
+/// Validator sets by epoch, derived from staking-ledger snapshots.
+#[derive(Debug, Clone)]
+pub struct EpochValidatorSets {
+    config: ValidatorSetConfig,
+    sets:   BTreeMap<u64, ValidatorSet>,
+    events: Vec<ValidatorSetEvent>,
+}
+
+impl EpochValidatorSets {
+    /// Start from the genesis `(id, stake)` list, active for epochs 0 and 1.
+    pub fn new(config: ValidatorSetConfig, genesis: &[(String, u128)]) -> Result<Self, String> {
+        if config.blocks_per_epoch == 0 {
+            return Err("blocks_per_epoch must be > 0".to_string());
+        }
+        if config.max_validators == 0 {
+            return Err("max_validators must be > 0".to_string());
+        }
+        let mut sets = BTreeMap::new();
+        for epoch in 0..=1 {
+            sets.insert(epoch, ValidatorSet::select(epoch, genesis, &config, None));
+        }
+        Ok(EpochValidatorSets { config, sets, events: Vec::new() })
+    }
+
+    /// Genesis set from the registry's participating validators.
+    pub fn from_registry(config: ValidatorSetConfig, registry: &ValidatorRegistry) -> Result<Self, String> {
+        Self::new(config, &snapshot(registry))
+    }
+
+    pub fn config(&self) -> &ValidatorSetConfig {
+        &self.config
+    }
+
+    pub fn epoch_of(&self, height: u64) -> u64 {
+        height / self.config.blocks_per_epoch
+    }
+
+    /// The set that governs the block at `height`; `None` for heights more
+    /// than one epoch past the last processed boundary.
+    pub fn validator_set_at(&self, height: u64) -> Option<&ValidatorSet> {
+        self.sets.get(&self.epoch_of(height))
+    }
+
+    /// The set for `epoch`, if it has been decided.
+    pub fn set_for_epoch(&self, epoch: u64) -> Option<&ValidatorSet> {
+        self.sets.get(&epoch)
+    }
+
+    /// Feed every committed height.  At the first block of an epoch the
+    /// registry is snapshotted to decide the next epoch's set; otherwise
+    /// this does nothing.  Re-processing a boundary is a no-op.
+    pub fn on_block(&mut self, height: u64, registry: &ValidatorRegistry) -> Vec<ValidatorSetEvent> {
+        if !self.is_snapshot_height(height) {
+            return Vec::new();
+        }
+        self.apply_snapshot(self.epoch_of(height), &snapshot(registry))
+    }
+
+    /// Whether committing `height` decides a set not decided yet: it is
+    /// the first block of an epoch whose successor has no set.
+    pub fn is_snapshot_height(&self, height: u64) -> bool {
+        height != 0
+            && height % self.config.blocks_per_epoch == 0
+            && !self.sets.contains_key(&(self.epoch_of(height) + 1))
+    }
+
+    /// Decide the set for `epoch + 1` from a ledger snapshot taken at the
+    /// start of `epoch`, and return the resulting membership changes.
+    pub fn apply_snapshot(&mut self, epoch: u64, stakes: &[(String, u128)]) -> Vec<ValidatorSetEvent> {
+        self.decide(epoch, stakes, None)
+    }
+
+    /// `apply_snapshot`, breaking stake ties by `SHA-256(seed || id)`.
+    pub fn apply_seeded_snapshot(
+        &mut self,
+        epoch: u64,
+        stakes: &[(String, u128)],
+        seed: &[u8],
+    ) -> Vec<ValidatorSetEvent> {
+        self.decide(epoch, stakes, Some(seed))
+    }
+
+    fn decide(&mut self, epoch: u64, stakes: &[(String, u128)], seed: Option<&[u8]>) -> Vec<ValidatorSetEvent> {
+        let next = ValidatorSet::select(epoch + 1, stakes, &self.config, seed);
+        let snapshot: HashMap<&str, u128> = stakes.iter().map(|(id, s)| (id.as_str(), *s)).collect();
+
+        let mut events = Vec::new();
+        if let Some(current) = self.sets.get(&epoch) {
+            for m in &current.members {
+                if next.contains(&m.id) {
+                    continue;
+                }
+                let reason = match snapshot.get(m.id.as_str()) {
+                    None => LeaveReason::Inactive,
+                    Some(s) if *s < self.config.min_stake => LeaveReason::BelowMinimum,
+                    Some(_) => LeaveReason::Outranked,
+                };
+                events.push(ValidatorSetEvent::Left { id: m.id.clone(), epoch: epoch + 1, reason });
+            }
+            for m in &next.members {
+                if !current.contains(&m.id) {
+                    events.push(ValidatorSetEvent::Entered { id: m.id.clone(), epoch: epoch + 1, stake: m.stake });
+                }
+            }
+        }
+
+        info!(
+            "Validator set for epoch {}: {} members, {} changes",
+            epoch + 1, next.len(), events.len()
+        );
+        self.sets.insert(epoch + 1, next);
+        self.events.extend(events.iter().cloned());
+        events
+    }
+
+    /// Membership change from `epoch - 1` to `epoch`, once both are decided.
+    pub fn transition_into(&self, epoch: u64) -> Option<EpochTransition> {
+        let previous = self.sets.get(&epoch.checked_sub(1)?)?;
+        let current = self.sets.get(&epoch)?;
+        let mut joining: Vec<String> = current.members.iter()
+            .filter(|m| !previous.contains(&m.id))
+            .map(|m| m.id.clone())
+            .collect();
+        let mut leaving: Vec<String> = previous.members.iter()
+            .filter(|m| !current.contains(&m.id))
+            .map(|m| m.id.clone())
+            .collect();
+        joining.sort();
+        leaving.sort();
+        Some(EpochTransition { epoch, joining, leaving })
+    }
+
+    /// Every set change so far, oldest first.
+    pub fn events(&self) -> &[ValidatorSetEvent] {
+        &self.events
+    }
+
+    /// Check a finality certificate against the set of its block's epoch:
+    /// every signer must be a member and the signers' stake *in that set*
+    /// (not the voting power they claim) must exceed two thirds.
+    pub fn verify_certificate(&self, cert: &FinalizyCertificate) -> Result<(), String> {
+        let set = self.validator_set_at(cert.block_height).ok_or_else(|| {
+            format!("No validator set known for height {}", cert.block_height)
+        })?;
+        let mut signed = 0u128;
+        for sig in &cert.validator_signatures {
+            let stake = set.stake_of(&sig.validator_id).ok_or_else(|| format!(
+                "Signer {} is not in the epoch {} validator set", sig.validator_id, set.epoch
+            ))?;
+            signed = signed.saturating_add(stake);
+        }
+        if !set.has_stake_quorum(signed) {
+            return Err(format!(
+                "Certificate for block {} has {} of {} epoch-{} stake",
+                cert.block_height, signed, set.total_stake, set.epoch
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Slots whose leader did not sign, per validator.
+#[derive(Debug, Clone, Default)]
+pub struct MissedSlots {
+    recent: HashMap<String, VecDeque<u64>>,
+    totals: HashMap<String, u64>,
+}
+
+impl MissedSlots {
+    /// Heights kept per validator.
+    pub const RECENT: usize = 32;
+
+    pub fn record(&mut self, validator: &str, height: u64) {
+        let recent = self.recent.entry(validator.to_string()).or_default();
+        if recent.len() == Self::RECENT {
+            recent.pop_front();
+        }
+        recent.push_back(height);
+        *self.totals.entry(validator.to_string()).or_default() += 1;
+    }
+
+    /// Most recent missed heights, oldest first.
+    pub fn recent(&self, validator: &str) -> Vec<u64> {
+        self.recent.get(validator).map(|r| r.iter().copied().collect()).unwrap_or_default()
+    }
+
+    pub fn total(&self, validator: &str) -> u64 {
+        self.totals.get(validator).copied().unwrap_or(0)
+    }
+}
+
+/// `(id, effective stake)` of every participating validator, in id order.
+pub fn snapshot(registry: &ValidatorRegistry) -> Vec<(String, u128)> {
+    registry.get_active_validators().into_iter()
+        .filter(|v| v.can_participate())
+        .map(|v| (v.id.clone(), v.effective_stake()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator_identity::ValidatorIdentity;
+    use std::collections::HashSet;
+
+    const E: u64 = 10;
+
+    fn config(max_validators: usize) -> ValidatorSetConfig {
+        ValidatorSetConfig { blocks_per_epoch: E, max_validators, min_stake: 100 }
+    }
+
+    fn bond(reg: &mut ValidatorRegistry, id: &str, stake: u128) {
+        let v = ValidatorIdentity::new(id.into(), vec![0u8; 1568], id.into(), stake, 0).unwrap();
+        reg.register_validator(v).unwrap();
+        reg.activate_validator(id).unwrap();
+    }
+
+    fn registry(validators: &[(&str, u128)]) -> ValidatorRegistry {
+        let mut reg = ValidatorRegistry::new();
+        for (id, stake) in validators {
+            bond(&mut reg, id, *stake);
+        }
+        reg
+    }
+
+    fn run(sets: &mut EpochValidatorSets, reg: &ValidatorRegistry, heights: std::ops::Range<u64>) {
+        for h in heights {
+            sets.on_block(h, reg);
+        }
+    }
+
+    fn ids(set: &ValidatorSet) -> Vec<&str> {
+        set.members.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    fn certificate(height: u64, signers: &[&str]) -> FinalizyCertificate {
+        let mut cert = FinalizyCertificate::new(
+            height, format!("hash{}", height), height / E, "PoS_NORMAL".into(), "root".into(), 0, 2,
+        ).unwrap();
+        for s in signers {
+            // Claimed voting power is ignored in favour of the set's stake.
+            cert.add_validator_signature(s.to_string(), vec![1], u128::MAX / 4).unwrap();
+        }
+        cert
+    }
+
+    #[test]
+    fn bonded_validator_enters_one_epoch_after_snapshot() {
+        let mut reg = registry(&[("a", 500), ("b", 400), ("c", 300)]);
+        let mut sets = EpochValidatorSets::from_registry(config(10), &reg).unwrap();
+
+        run(&mut sets, &reg, 0..5);
+        bond(&mut reg, "d", 450);
+        run(&mut sets, &reg, 5..25);
+
+        // Snapshot at height 10 (start of epoch 1) sees d; it activates in epoch 2.
+        assert!(!sets.validator_set_at(19).unwrap().contains("d"));
+        assert!(sets.validator_set_at(20).unwrap().contains("d"));
+        assert_eq!(ids(sets.validator_set_at(20).unwrap()), vec!["a", "d", "b", "c"]);
+        assert_eq!(sets.events(), &[ValidatorSetEvent::Entered { id: "d".into(), epoch: 2, stake: 450 }]);
+    }
+
+    #[test]
+    fn quorum_tracks_set_size() {
+        let mut reg = registry(&[("a", 500), ("b", 400), ("c", 300), ("d", 200)]);
+        let mut sets = EpochValidatorSets::from_registry(config(10), &reg).unwrap();
+        assert_eq!(sets.validator_set_at(0).unwrap().quorum_size(), 3);
+
+        for (id, stake) in [("e", 150), ("f", 150), ("g", 150)] {
+            bond(&mut reg, id, stake);
+        }
+        run(&mut sets, &reg, 0..21);
+        let set = sets.validator_set_at(20).unwrap();
+        assert_eq!(set.len(), 7);
+        assert_eq!(set.quorum_size(), 5);
+
+        // The cap keeps only the top N; outranked members are reported.
+        let mut capped = EpochValidatorSets::from_registry(config(3), &registry(&[("a", 500), ("b", 400), ("c", 300)])).unwrap();
+        capped.apply_snapshot(1, &[("a".into(), 500), ("b".into(), 400), ("c".into(), 300), ("z".into(), 900)]);
+        assert_eq!(ids(capped.validator_set_at(20).unwrap()), vec!["z", "a", "b"]);
+        assert!(capped.events().contains(&ValidatorSetEvent::Left { id: "c".into(), epoch: 2, reason: LeaveReason::Outranked }));
+    }
+
+    #[test]
+    fn leader_rotation_covers_new_set() {
+        let mut reg = registry(&[("a", 500), ("b", 400)]);
+        let mut sets = EpochValidatorSets::from_registry(config(10), &reg).unwrap();
+        bond(&mut reg, "c", 300);
+        bond(&mut reg, "d", 200);
+        run(&mut sets, &reg, 0..21);
+
+        let set = sets.validator_set_at(20).unwrap();
+        let leaders: HashSet<&str> = (20..20 + set.len() as u64)
+            .map(|h| set.leader(h).unwrap().id.as_str())
+            .collect();
+        assert_eq!(leaders, ["a", "b", "c", "d"].into_iter().collect());
+    }
+
+    #[test]
+    fn historical_blocks_use_their_epoch_set() {
+        let mut reg = registry(&[("a", 500), ("b", 400), ("c", 300)]);
+        let mut sets = EpochValidatorSets::from_registry(config(10), &reg).unwrap();
+        for id in ["a", "b", "c"] {
+            reg.mark_validator_for_exit(id).unwrap();
+        }
+        for (id, stake) in [("x", 500), ("y", 400), ("z", 300)] {
+            bond(&mut reg, id, stake);
+        }
+        run(&mut sets, &reg, 0..30);
+
+        assert!(sets.verify_certificate(&certificate(5, &["a", "b", "c"])).is_ok());
+        assert!(sets.verify_certificate(&certificate(25, &["a", "b", "c"])).is_err());
+        assert!(sets.verify_certificate(&certificate(25, &["x", "y", "z"])).is_ok());
+        // Two thirds of the count but not more than two thirds of the stake.
+        assert!(sets.verify_certificate(&certificate(25, &["y", "z"])).is_err());
+    }
+
+    #[test]
+    fn slashed_below_minimum_is_dropped_at_next_boundary() {
+        let mut reg = registry(&[("a", 500), ("b", 400), ("c", 300)]);
+        let mut sets = EpochValidatorSets::from_registry(config(10), &reg).unwrap();
+        run(&mut sets, &reg, 0..3);
+        reg.record_validator_downtime("c", 250).unwrap();
+        run(&mut sets, &reg, 3..21);
+
+        assert!(sets.validator_set_at(15).unwrap().contains("c"));
+        assert!(!sets.validator_set_at(20).unwrap().contains("c"));
+        assert_eq!(sets.events(), &[ValidatorSetEvent::Left { id: "c".into(), epoch: 2, reason: LeaveReason::BelowMinimum }]);
+    }
+}
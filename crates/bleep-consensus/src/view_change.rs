@@ -0,0 +1,613 @@
+//! # PBFT view change
+//!
+//! Keeps PBFT live when the leader of a height crashes or misbehaves.
+//!
+//! ```text
+//!   leader(h, v) = validator_set_at(h).leader(h + v)
+//!
+//!   timer(v) expires ──► ViewChange{h, v+1, last_prepared}  (signed, broadcast)
+//!   leader(h, v+1) collects 2f+1 ViewChange ──► NewView{h, v+1, proof, proposal}
+//!   proposal = highest-view prepared block among the proof, else a fresh one
+//! ```
+//!
+//! Each view's timeout is twice the previous one (capped), so a run of
+//! failed leaders is eventually outlasted.  Once a validator has asked for
+//! view `v` it ignores proposals from older views, and once a `NewView`
+//! certificate is accepted only its proposal is valid in that view.  The
+//! `ViewState` is persisted before any message leaves the node, so a
+//! restart cannot vote in a view it already abandoned.
+//!
+//! Time is passed in explicitly (`now_ms`), as in `block_timing`.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use bleep_core::block::Block;
+use bleep_crypto::tx_signer::{sign_tx_payload, verify_tx_signature};
+
+use crate::block_producer::BLOCK_INTERVAL_MS;
+use crate::engine::ConsensusError;
+use crate::validator_set::ValidatorSet;
+
+/// Domain prefixes so consensus signatures never validate as anything else.
+const VIEW_CHANGE_DOMAIN: &[u8] = b"BLEEP PBFT ViewChange";
+const NEW_VIEW_DOMAIN: &[u8]    = b"BLEEP PBFT NewView";
+
+// ── Timeouts ──────────────────────────────────────────────────────────────────
+
+/// Per-view timer: `min(base_ms · 2^view, max_ms)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ViewTimeouts {
+    pub base_ms: u64,
+    pub max_ms:  u64,
+}
+
+impl Default for ViewTimeouts {
+    fn default() -> Self {
+        ViewTimeouts { base_ms: BLOCK_INTERVAL_MS * 2, max_ms: 120_000 }
+    }
+}
+
+impl ViewTimeouts {
+    pub fn timeout_for(&self, view: u64) -> u64 {
+        let factor = 1u64 << view.min(32);
+        self.base_ms.saturating_mul(factor).min(self.max_ms)
+    }
+}
+
+// ── Messages ──────────────────────────────────────────────────────────────────
+
+/// A block that reached PREPARED in `view`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreparedBlock {
+    pub view:  u64,
+    pub block: Block,
+}
+
+/// "I give up on the current view at `height`; move to `new_view`."
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewChange {
+    pub height:        u64,
+    pub new_view:      u64,
+    pub last_prepared: Option<PreparedBlock>,
+    pub validator_id:  String,
+    pub signature:     Vec<u8>,
+}
+
+impl ViewChange {
+    pub fn payload(&self) -> [u8; 32] {
+        let mut h = Sha3_256::new();
+        h.update(VIEW_CHANGE_DOMAIN);
+        h.update(self.height.to_le_bytes());
+        h.update(self.new_view.to_le_bytes());
+        if let Some(p) = &self.last_prepared {
+            h.update(p.view.to_le_bytes());
+            h.update(p.block.compute_hash().as_bytes());
+        }
+        h.update(self.validator_id.as_bytes());
+        h.finalize().into()
+    }
+}
+
+/// The new leader's certificate: 2f+1 `ViewChange`s plus its proposal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewView {
+    pub height:       u64,
+    pub view:         u64,
+    pub view_changes: Vec<ViewChange>,
+    pub proposal:     Block,
+    pub leader:       String,
+    pub signature:    Vec<u8>,
+}
+
+impl NewView {
+    pub fn payload(&self) -> [u8; 32] {
+        let mut h = Sha3_256::new();
+        h.update(NEW_VIEW_DOMAIN);
+        h.update(self.height.to_le_bytes());
+        h.update(self.view.to_le_bytes());
+        h.update(self.proposal.compute_hash().as_bytes());
+        h.update(self.leader.as_bytes());
+        h.finalize().into()
+    }
+}
+
+/// Highest-view prepared block among `msgs`.
+fn highest_prepared<'a>(msgs: impl IntoIterator<Item = &'a ViewChange>) -> Option<&'a PreparedBlock> {
+    msgs.into_iter()
+        .filter_map(|m| m.last_prepared.as_ref())
+        .max_by_key(|p| p.view)
+}
+
+// ── Persisted state ───────────────────────────────────────────────────────────
+
+/// Everything a validator must remember across restarts to stay honest.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ViewState {
+    pub height:         u64,
+    pub view:           u64,
+    /// A `ViewChange` for `view` was sent and no `NewView` accepted yet.
+    pub in_view_change: bool,
+    pub last_prepared:  Option<PreparedBlock>,
+    /// view → hash of the one proposal accepted in that view.
+    pub accepted:       BTreeMap<u64, String>,
+}
+
+// ── Manager ───────────────────────────────────────────────────────────────────
+
+/// View-change driver for one validator.
+pub struct ViewChangeManager {
+    validator_id:    String,
+    secret_key:      Vec<u8>,
+    set:             ValidatorSet,
+    public_keys:     HashMap<String, Vec<u8>>,
+    timeouts:        ViewTimeouts,
+    state:           ViewState,
+    view_started_ms: u64,
+    /// new_view → sender → message, for the current height.
+    collected:       HashMap<u64, HashMap<String, ViewChange>>,
+    store:           Option<PathBuf>,
+}
+
+impl ViewChangeManager {
+    /// Start at view 0 of `height`.  `public_keys` maps every member of
+    /// `set` to its SPHINCS+ public key.
+    pub fn new(
+        validator_id: String,
+        secret_key:   Vec<u8>,
+        set:          ValidatorSet,
+        public_keys:  HashMap<String, Vec<u8>>,
+        height:       u64,
+        now_ms:       u64,
+    ) -> Self {
+        ViewChangeManager {
+            validator_id,
+            secret_key,
+            set,
+            public_keys,
+            timeouts: ViewTimeouts::default(),
+            state: ViewState { height, ..Default::default() },
+            view_started_ms: now_ms,
+            collected: HashMap::new(),
+            store: None,
+        }
+    }
+
+    pub fn with_timeouts(mut self, timeouts: ViewTimeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Persist view state to `path`, resuming from it if it is at or past
+    /// the starting height.
+    pub fn with_store<P: AsRef<Path>>(mut self, path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        match fs::read(&path) {
+            Ok(bytes) => {
+                let saved: ViewState = serde_json::from_slice(&bytes)
+                    .map_err(|e| format!("decode {}: {}", path.display(), e))?;
+                if saved.height >= self.state.height {
+                    info!("[ViewChange] Resuming height {} view {}", saved.height, saved.view);
+                    self.state = saved;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("read {}: {}", path.display(), e)),
+        }
+        self.store = Some(path);
+        self.persist()?;
+        Ok(self)
+    }
+
+    pub fn state(&self) -> &ViewState {
+        &self.state
+    }
+
+    pub fn height(&self) -> u64 {
+        self.state.height
+    }
+
+    pub fn view(&self) -> u64 {
+        self.state.view
+    }
+
+    /// Timeout of the current view.
+    pub fn current_timeout(&self) -> u64 {
+        self.timeouts.timeout_for(self.state.view)
+    }
+
+    /// Leader of `view` at the current height.
+    pub fn leader(&self, view: u64) -> Option<&str> {
+        self.set.leader(self.state.height.wrapping_add(view)).map(|m| m.id.as_str())
+    }
+
+    fn is_leader(&self, view: u64) -> bool {
+        self.leader(view) == Some(self.validator_id.as_str())
+    }
+
+    /// Advance the timer.  Returns the signed `ViewChange` to broadcast if
+    /// the current view expired.
+    pub fn on_tick(&mut self, now_ms: u64) -> Result<Option<ViewChange>, ConsensusError> {
+        if now_ms.saturating_sub(self.view_started_ms) < self.current_timeout() {
+            return Ok(None);
+        }
+        self.abandon_view(now_ms).map(Some)
+    }
+
+    /// Give up on the current view now, whatever its timer says — e.g. a
+    /// prepare or commit phase timed out.  Returns the signed `ViewChange`
+    /// to broadcast.
+    pub fn abandon_view(&mut self, now_ms: u64) -> Result<ViewChange, ConsensusError> {
+        let new_view = self.state.view + 1;
+        let mut vc = ViewChange {
+            height:        self.state.height,
+            new_view,
+            last_prepared: self.state.last_prepared.clone(),
+            validator_id:  self.validator_id.clone(),
+            signature:     Vec::new(),
+        };
+        vc.signature = sign_tx_payload(&vc.payload(), &self.secret_key)
+            .map_err(ConsensusError::Other)?;
+
+        // Commit to the new view before anyone can see the message.
+        self.state.view = new_view;
+        self.state.in_view_change = true;
+        self.view_started_ms = now_ms;
+        self.persist().map_err(ConsensusError::Other)?;
+
+        warn!(
+            "[ViewChange] Height {} timed out, requesting view {} (next timeout {}ms)",
+            vc.height, new_view, self.current_timeout()
+        );
+        self.collected.entry(new_view).or_default().insert(self.validator_id.clone(), vc.clone());
+        Ok(vc)
+    }
+
+    fn verify_view_change(&self, vc: &ViewChange) -> Result<(), ConsensusError> {
+        if vc.height != self.state.height {
+            return Err(ConsensusError::ProposalRejected {
+                reason: format!("view change for height {}, at {}", vc.height, self.state.height),
+            });
+        }
+        if let Some(p) = &vc.last_prepared {
+            if p.block.index != vc.height || p.view >= vc.new_view {
+                return Err(ConsensusError::ProposalRejected {
+                    reason: format!("bad prepared block from {}", vc.validator_id),
+                });
+            }
+        }
+        self.verify_signature(&vc.validator_id, &vc.payload(), &vc.signature)
+    }
+
+    fn verify_signature(&self, id: &str, payload: &[u8], signature: &[u8]) -> Result<(), ConsensusError> {
+        let pk = match self.public_keys.get(id) {
+            Some(pk) if self.set.contains(id) => pk,
+            _ => return Err(ConsensusError::InvalidSignature { validator_id: id.to_string() }),
+        };
+        if verify_tx_signature(payload, signature, pk) {
+            Ok(())
+        } else {
+            Err(ConsensusError::InvalidSignature { validator_id: id.to_string() })
+        }
+    }
+
+    /// Record a peer's `ViewChange`.  Returns the view this validator now
+    /// leads with a quorum of view changes, ready for `new_view`.
+    pub fn on_view_change(&mut self, vc: ViewChange) -> Result<Option<u64>, ConsensusError> {
+        if vc.height == self.state.height && vc.new_view < self.state.view {
+            return Ok(None); // stale
+        }
+        self.verify_view_change(&vc)?;
+        let view = vc.new_view;
+        let votes = self.collected.entry(view).or_default();
+        votes.insert(vc.validator_id.clone(), vc);
+        let ready = votes.len() >= self.set.quorum_size()
+            && self.is_leader(view)
+            && !self.state.accepted.contains_key(&view);
+        Ok(if ready { Some(view) } else { None })
+    }
+
+    /// Build, sign and adopt the `NewView` for `view`.  `fresh` is proposed
+    /// only if no view change carries a prepared block.
+    pub fn new_view(&mut self, view: u64, fresh: Block, now_ms: u64) -> Result<NewView, ConsensusError> {
+        if !self.is_leader(view) {
+            return Err(ConsensusError::ProposalRejected {
+                reason: format!("{} is not the leader of view {}", self.validator_id, view),
+            });
+        }
+        let view_changes: Vec<ViewChange> = self.collected.get(&view)
+            .map(|v| v.values().cloned().collect())
+            .unwrap_or_default();
+        if view_changes.len() < self.set.quorum_size() {
+            return Err(ConsensusError::PbftQuorumNotReached {
+                validators: view_changes.len(),
+                threshold:  self.set.quorum_size(),
+            });
+        }
+        let proposal = highest_prepared(&view_changes).map(|p| p.block.clone()).unwrap_or(fresh);
+        let mut nv = NewView {
+            height: self.state.height,
+            view,
+            view_changes,
+            proposal,
+            leader: self.validator_id.clone(),
+            signature: Vec::new(),
+        };
+        nv.signature = sign_tx_payload(&nv.payload(), &self.secret_key)
+            .map_err(ConsensusError::Other)?;
+        self.enter_view(view, nv.proposal.compute_hash(), now_ms)?;
+        Ok(nv)
+    }
+
+    /// Validate a `NewView` certificate and move to its view.  Returns the
+    /// block to run PBFT on.
+    pub fn on_new_view(&mut self, nv: &NewView, now_ms: u64) -> Result<Block, ConsensusError> {
+        if nv.height != self.state.height || nv.view < self.state.view || nv.view == 0 {
+            return Err(ConsensusError::ProposalRejected {
+                reason: format!("stale new-view {}/{}", nv.height, nv.view),
+            });
+        }
+        if self.leader(nv.view) != Some(nv.leader.as_str()) {
+            return Err(ConsensusError::ProposalRejected {
+                reason: format!("{} is not the leader of view {}", nv.leader, nv.view),
+            });
+        }
+        self.verify_signature(&nv.leader, &nv.payload(), &nv.signature)?;
+
+        let mut signers = HashSet::new();
+        for vc in &nv.view_changes {
+            if vc.new_view != nv.view {
+                continue;
+            }
+            if self.verify_view_change(vc).is_ok() {
+                signers.insert(vc.validator_id.as_str());
+            }
+        }
+        if signers.len() < self.set.quorum_size() {
+            return Err(ConsensusError::PbftQuorumNotReached {
+                validators: signers.len(),
+                threshold:  self.set.quorum_size(),
+            });
+        }
+
+        let hash = nv.proposal.compute_hash();
+        let required = highest_prepared(nv.view_changes.iter().filter(|vc| vc.new_view == nv.view));
+        if let Some(p) = required {
+            if p.block.compute_hash() != hash {
+                return Err(ConsensusError::ProposalRejected {
+                    reason: format!("new-view {} must re-propose the block prepared in view {}", nv.view, p.view),
+                });
+            }
+        }
+        self.enter_view(nv.view, hash, now_ms)?;
+        Ok(nv.proposal.clone())
+    }
+
+    fn enter_view(&mut self, view: u64, proposal_hash: String, now_ms: u64) -> Result<(), ConsensusError> {
+        if let Some(existing) = self.state.accepted.get(&view) {
+            if *existing != proposal_hash {
+                return Err(ConsensusError::ProposalRejected {
+                    reason: format!("conflicting proposal in view {}", view),
+                });
+            }
+        }
+        self.state.view = view;
+        self.state.in_view_change = false;
+        self.state.accepted.insert(view, proposal_hash);
+        self.view_started_ms = now_ms;
+        self.persist().map_err(ConsensusError::Other)?;
+        info!("[ViewChange] Height {} entered view {}", self.state.height, view);
+        Ok(())
+    }
+
+    /// Normal-case pre-prepare check: the proposer leads the current view,
+    /// no view change is pending, and it is the only proposal of the view.
+    pub fn on_proposal(&mut self, view: u64, proposer: &str, block: &Block) -> Result<(), ConsensusError> {
+        if view != self.state.view || self.state.in_view_change {
+            return Err(ConsensusError::ProposalRejected {
+                reason: format!("proposal for view {}, at view {}", view, self.state.view),
+            });
+        }
+        if self.leader(view) != Some(proposer) {
+            return Err(ConsensusError::ProposalRejected {
+                reason: format!("{} is not the leader of view {}", proposer, view),
+            });
+        }
+        let hash = block.compute_hash();
+        match self.state.accepted.get(&view) {
+            Some(existing) if *existing != hash => Err(ConsensusError::ProposalRejected {
+                reason: format!("conflicting proposal in view {}", view),
+            }),
+            Some(_) => Ok(()),
+            None => {
+                self.state.accepted.insert(view, hash);
+                self.persist().map_err(ConsensusError::Other)
+            }
+        }
+    }
+
+    /// The PBFT engine saw `block` reach PREPARED in the current view.
+    pub fn on_prepared(&mut self, block: Block) -> Result<(), ConsensusError> {
+        let view = self.state.view;
+        if self.state.last_prepared.as_ref().is_some_and(|p| p.view >= view) {
+            return Ok(());
+        }
+        self.state.last_prepared = Some(PreparedBlock { view, block });
+        self.persist().map_err(ConsensusError::Other)
+    }
+
+    /// `height` committed: reset to view 0 of the next height.
+    pub fn on_commit(&mut self, height: u64, now_ms: u64) -> Result<(), ConsensusError> {
+        if height < self.state.height {
+            return Ok(());
+        }
+        self.state = ViewState { height: height + 1, ..Default::default() };
+        self.collected.clear();
+        self.view_started_ms = now_ms;
+        self.persist().map_err(ConsensusError::Other)
+    }
+
+    fn persist(&self) -> Result<(), String> {
+        let path = match &self.store {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        let bytes = serde_json::to_vec(&self.state).map_err(|e| e.to_string())?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, bytes).map_err(|e| format!("write {}: {}", tmp.display(), e))?;
+        fs::rename(&tmp, path).map_err(|e| format!("rename {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator_set::SetMember;
+    use bleep_crypto::tx_signer::generate_tx_keypair;
+
+    const BASE: u64 = 1_000;
+    const H: u64 = 5;
+
+    /// Four validators `v0..v3`; leader(H, v) = v[(H + v) % 4].
+    struct Cluster {
+        nodes: Vec<ViewChangeManager>,
+    }
+
+    impl Cluster {
+        fn new() -> Self {
+            let ids: Vec<String> = (0..4).map(|i| format!("v{}", i)).collect();
+            let keys: Vec<(Vec<u8>, Vec<u8>)> = ids.iter().map(|_| generate_tx_keypair()).collect();
+            let set = ValidatorSet {
+                epoch:       0,
+                members:     ids.iter().map(|id| SetMember { id: id.clone(), stake: 100 }).collect(),
+                total_stake: 400,
+            };
+            let pks: HashMap<String, Vec<u8>> = ids.iter().cloned()
+                .zip(keys.iter().map(|(pk, _)| pk.clone()))
+                .collect();
+            let nodes = ids.iter().zip(&keys)
+                .map(|(id, (_, sk))| {
+                    ViewChangeManager::new(id.clone(), sk.clone(), set.clone(), pks.clone(), H, 0)
+                        .with_timeouts(ViewTimeouts { base_ms: BASE, max_ms: 60_000 })
+                })
+                .collect();
+            Cluster { nodes }
+        }
+
+        /// Every node in `alive` times out at `now`; messages are delivered
+        /// to all of them.  Returns the NewView the new leader builds.
+        fn view_change(&mut self, alive: &[usize], now: u64, fresh: Block) -> NewView {
+            let vcs: Vec<ViewChange> = alive.iter()
+                .filter_map(|&i| self.nodes[i].on_tick(now).unwrap())
+                .collect();
+            let mut ready = None;
+            for &i in alive {
+                for vc in &vcs {
+                    if vc.validator_id == self.nodes[i].validator_id {
+                        continue;
+                    }
+                    if let Some(view) = self.nodes[i].on_view_change(vc.clone()).unwrap() {
+                        ready = Some((i, view));
+                    }
+                }
+            }
+            let (leader, view) = ready.expect("a live leader reached quorum");
+            self.nodes[leader].new_view(view, fresh, now).unwrap()
+        }
+    }
+
+    fn block(height: u64, tag: &str) -> Block {
+        Block::new(height, vec![], tag.to_string())
+    }
+
+    #[test]
+    fn test_timeouts_back_off_per_view() {
+        let t = ViewTimeouts { base_ms: BASE, max_ms: 5_000 };
+        assert_eq!(t.timeout_for(0), 1_000);
+        assert_eq!(t.timeout_for(1), 2_000);
+        assert_eq!(t.timeout_for(2), 4_000);
+        assert_eq!(t.timeout_for(3), 5_000, "capped");
+        assert_eq!(t.timeout_for(400), 5_000);
+
+        let mut c = Cluster::new();
+        let n = &mut c.nodes[0];
+        assert!(n.on_tick(BASE - 1).unwrap().is_none());
+        assert_eq!(n.on_tick(BASE).unwrap().unwrap().new_view, 1);
+        // View 1 waits twice as long before giving up.
+        assert!(n.on_tick(BASE + 2 * BASE - 1).unwrap().is_none());
+        assert_eq!(n.on_tick(BASE + 2 * BASE).unwrap().unwrap().new_view, 2);
+    }
+
+    #[test]
+    fn test_dead_leader_triggers_view_change_and_chain_continues() {
+        let mut c = Cluster::new();
+        assert_eq!(c.nodes[0].leader(0), Some("v1"));
+        // v1 crashed at height H; the others time out.
+        let alive = [0, 2, 3];
+        let nv = c.view_change(&alive, BASE, block(H, "fresh"));
+        assert_eq!((nv.view, nv.leader.as_str()), (1, "v2"));
+
+        for &i in &[0, 3] {
+            let proposal = c.nodes[i].on_new_view(&nv, BASE).unwrap();
+            assert_eq!(proposal.compute_hash(), nv.proposal.compute_hash());
+            assert_eq!(c.nodes[i].view(), 1);
+        }
+        for &i in &alive {
+            c.nodes[i].on_commit(H, BASE + 10).unwrap();
+            assert_eq!((c.nodes[i].height(), c.nodes[i].view()), (H + 1, 0));
+        }
+        // Height H+1 proceeds normally under its own view-0 leader.
+        let next = block(H + 1, "next");
+        for &i in &[0, 3] {
+            c.nodes[i].on_proposal(0, "v2", &next).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_prepared_block_is_reproposed_and_conflicts_ignored() {
+        let mut c = Cluster::new();
+        let prepared = block(H, "prepared-in-view-0");
+        c.nodes[0].on_proposal(0, "v1", &prepared).unwrap();
+        c.nodes[0].on_prepared(prepared.clone()).unwrap();
+
+        let nv = c.view_change(&[0, 2, 3], BASE, block(H, "fresh"));
+        assert_eq!(nv.proposal.compute_hash(), prepared.compute_hash(), "highest prepared wins");
+
+        // A NewView that swaps in another block is refused.
+        let mut forged = nv.clone();
+        forged.proposal = block(H, "other");
+        assert!(c.nodes[3].on_new_view(&forged, BASE).is_err());
+
+        c.nodes[3].on_new_view(&nv, BASE).unwrap();
+        // Once the certificate formed, the leader's conflicting proposal is ignored.
+        assert!(c.nodes[3].on_proposal(1, "v2", &block(H, "conflict")).is_err());
+        assert!(c.nodes[3].on_proposal(1, "v2", &prepared).is_ok());
+    }
+
+    #[test]
+    fn test_restart_resumes_abandoned_view() {
+        let mut c = Cluster::new();
+        let path = std::env::temp_dir()
+            .join(format!("bleep-view-{}-{}.json", std::process::id(), BASE));
+        let _ = fs::remove_file(&path);
+        let mut node = c.nodes.remove(0).with_store(&path).unwrap();
+        let (id, sk) = (node.validator_id.clone(), node.secret_key.clone());
+        let (set, pks) = (node.set.clone(), node.public_keys.clone());
+        node.on_tick(BASE).unwrap().unwrap();
+        drop(node);
+
+        let mut restarted = ViewChangeManager::new(id, sk, set, pks, H, BASE)
+            .with_store(&path)
+            .unwrap();
+        assert_eq!(restarted.view(), 1);
+        assert!(restarted.state().in_view_change);
+        // No vote for a view-0 proposal after asking to leave view 0.
+        assert!(restarted.on_proposal(0, "v1", &block(H, "late")).is_err());
+        let _ = fs::remove_file(&path);
+    }
+}
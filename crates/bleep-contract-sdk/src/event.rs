@@ -0,0 +1,70 @@
+//! Event payloads in the layout `bleep_vm::execution::event_abi` decodes:
+//! fields in schema order, integers little-endian, addresses as 32 raw
+//! bytes, `bytes` and `string` as a `u32` little-endian length followed by
+//! the bytes.
+
+use crate::Error;
+
+/// Largest event payload an [`Event`] can hold.
+pub const EVENT_CAPACITY: usize = 256;
+
+/// An event under construction, emitted with `Env::emit`.
+///
+/// ```rust,ignore
+/// env.emit(Event::new("Transfer").address(&to).u128(amount))?;
+/// ```
+pub struct Event<'n> {
+    name:     &'n str,
+    data:     [u8; EVENT_CAPACITY],
+    len:      usize,
+    overflow: bool,
+}
+
+impl<'n> Event<'n> {
+    pub fn new(name: &'n str) -> Self {
+        Event { name, data: [0u8; EVENT_CAPACITY], len: 0, overflow: false }
+    }
+
+    pub fn u64(self, v: u64) -> Self {
+        self.push(&v.to_le_bytes())
+    }
+
+    pub fn u128(self, v: u128) -> Self {
+        self.push(&v.to_le_bytes())
+    }
+
+    pub fn address(self, a: &[u8; 32]) -> Self {
+        self.push(a)
+    }
+
+    pub fn bytes(self, b: &[u8]) -> Self {
+        self.push(&(b.len() as u32).to_le_bytes()).push(b)
+    }
+
+    pub fn string(self, s: &str) -> Self {
+        self.bytes(s.as_bytes())
+    }
+
+    fn push(mut self, bytes: &[u8]) -> Self {
+        match self.data.get_mut(self.len..self.len + bytes.len()) {
+            Some(dst) if !self.overflow => {
+                dst.copy_from_slice(bytes);
+                self.len += bytes.len();
+            }
+            _ => self.overflow = true,
+        }
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// The encoded fields, or `EventTooLarge` if they did not fit.
+    pub fn data(&self) -> Result<&[u8], Error> {
+        if self.overflow {
+            return Err(Error::EventTooLarge);
+        }
+        Ok(&self.data[..self.len])
+    }
+}
@@ -0,0 +1,296 @@
+//! # bleep-contract-sdk
+//!
+//! Safe, typed access to the host imports `ContractRegistry` gives WASM
+//! contracts, for `#![no_std]` contracts built for `wasm32-unknown-unknown`.
+//!
+//! | `bleep` import                                         | wrapper                               |
+//! |--------------------------------------------------------|---------------------------------------|
+//! | `calldata(out_ptr, out_cap) -> i32`                    | `Env::calldata`                       |
+//! | `storage_read(key_ptr, key_len, out_ptr, out_cap) -> i32` | `Env::get`, `get_u64`, `get_u128`  |
+//! | `storage_write(key_ptr, key_len, val_ptr, val_len)`    | `Env::set`, `set_u64`, `set_u128`     |
+//! | `emit_event(name_ptr, name_len, data_ptr, data_len)`   | `Env::emit` with an [`Event`]         |
+//! | `crypto_sha3(ptr, len, out_ptr)`                       | `Env::sha3`                           |
+//!
+//! A contract exports `execute(calldata_len: i32) -> i32`; the registry
+//! returns its result little-endian as the call's output.  Negative results
+//! are the convention for errors (see [`Error::code`]).  The host has no
+//! caller, value or cross-contract call imports yet, so neither does the SDK.
+//!
+//! Every wrapper goes through the [`Host`] trait.  `WasmHost` calls the
+//! real imports (wasm32 only); `mock::MockHost` (feature `mock`) keeps
+//! storage and events in memory so contract logic can be unit tested
+//! natively.
+//!
+//! ```rust,ignore
+//! #[no_mangle]
+//! pub extern "C" fn execute(_calldata_len: i32) -> i32 {
+//!     let mut env = Env::default();
+//!     let count = env.get_u64(b"count").unwrap_or(None).unwrap_or(0) + 1;
+//!     env.set_u64(b"count", count);
+//!     count as i32
+//! }
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+
+#[cfg(any(test, feature = "mock"))]
+extern crate alloc;
+
+mod event;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+
+pub use event::{Event, EVENT_CAPACITY};
+
+/// Why an SDK call failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The calldata or a stored value is longer than the buffer given for it
+    BufferTooSmall { needed: usize },
+    /// A stored value does not have the width of the type it is read as
+    InvalidValue,
+    /// An event's fields do not fit in `EVENT_CAPACITY` bytes
+    EventTooLarge,
+}
+
+impl Error {
+    /// Negative `execute` result for this error.
+    pub fn code(&self) -> i32 {
+        match self {
+            Error::BufferTooSmall { .. } => -1,
+            Error::InvalidValue => -2,
+            Error::EventTooLarge => -3,
+        }
+    }
+}
+
+/// The host functions a contract can call.
+pub trait Host {
+    /// Copy the calldata into `out` if it fits; returns its length.
+    fn calldata(&mut self, out: &mut [u8]) -> usize;
+    /// Copy the value under `key` into `out` if it fits; returns its
+    /// length, or `None` if the key is unset.
+    fn storage_read(&mut self, key: &[u8], out: &mut [u8]) -> Option<usize>;
+    fn storage_write(&mut self, key: &[u8], value: &[u8]);
+    fn emit_event(&mut self, name: &str, data: &[u8]);
+    /// SHA3-256 of `data`.
+    fn sha3(&mut self, data: &[u8]) -> [u8; 32];
+}
+
+/// Typed access to a contract's calldata, storage and events.
+pub struct Env<H: Host> {
+    host: H,
+}
+
+impl<H: Host> Env<H> {
+    pub fn new(host: H) -> Self {
+        Env { host }
+    }
+
+    pub fn host(&self) -> &H {
+        &self.host
+    }
+
+    pub fn into_host(self) -> H {
+        self.host
+    }
+
+    /// The call's calldata, copied into `buf`.
+    pub fn calldata<'a>(&mut self, buf: &'a mut [u8]) -> Result<&'a [u8], Error> {
+        let len = self.host.calldata(buf);
+        buf.get(..len).ok_or(Error::BufferTooSmall { needed: len })
+    }
+
+    /// The value under `key`, copied into `buf`.
+    pub fn get<'a>(&mut self, key: &[u8], buf: &'a mut [u8]) -> Result<Option<&'a [u8]>, Error> {
+        match self.host.storage_read(key, buf) {
+            None => Ok(None),
+            Some(len) => buf.get(..len).map(Some).ok_or(Error::BufferTooSmall { needed: len }),
+        }
+    }
+
+    pub fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.host.storage_write(key, value);
+    }
+
+    /// A little-endian `u64` stored with `set_u64`.
+    pub fn get_u64(&mut self, key: &[u8]) -> Result<Option<u64>, Error> {
+        Ok(self.get_fixed::<8>(key)?.map(u64::from_le_bytes))
+    }
+
+    pub fn set_u64(&mut self, key: &[u8], value: u64) {
+        self.set(key, &value.to_le_bytes());
+    }
+
+    /// A little-endian `u128` stored with `set_u128`.
+    pub fn get_u128(&mut self, key: &[u8]) -> Result<Option<u128>, Error> {
+        Ok(self.get_fixed::<16>(key)?.map(u128::from_le_bytes))
+    }
+
+    pub fn set_u128(&mut self, key: &[u8], value: u128) {
+        self.set(key, &value.to_le_bytes());
+    }
+
+    fn get_fixed<const N: usize>(&mut self, key: &[u8]) -> Result<Option<[u8; N]>, Error> {
+        let mut buf = [0u8; N];
+        match self.host.storage_read(key, &mut buf) {
+            None => Ok(None),
+            Some(len) if len == N => Ok(Some(buf)),
+            Some(_) => Err(Error::InvalidValue),
+        }
+    }
+
+    /// Emit `event`; fails if its fields overflowed the builder.
+    pub fn emit(&mut self, event: Event<'_>) -> Result<(), Error> {
+        let data = event.data()?;
+        self.host.emit_event(event.name(), data);
+        Ok(())
+    }
+
+    /// SHA3-256 through the `crypto_sha3` precompile.
+    pub fn sha3(&mut self, data: &[u8]) -> [u8; 32] {
+        self.host.sha3(data)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use wasm::WasmHost;
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use super::{Env, Host};
+
+    #[link(wasm_import_module = "bleep")]
+    extern "C" {
+        fn calldata(out_ptr: i32, out_cap: i32) -> i32;
+        fn storage_read(key_ptr: i32, key_len: i32, out_ptr: i32, out_cap: i32) -> i32;
+        fn storage_write(key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32);
+        fn emit_event(name_ptr: i32, name_len: i32, data_ptr: i32, data_len: i32);
+        fn crypto_sha3(ptr: i32, len: i32, out_ptr: i32);
+    }
+
+    /// The imports `ContractRegistry` links contracts against.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct WasmHost;
+
+    // Pointers and lengths are wasm32 addresses, which fit in `i32`.
+    impl Host for WasmHost {
+        fn calldata(&mut self, out: &mut [u8]) -> usize {
+            unsafe { calldata(out.as_mut_ptr() as i32, out.len() as i32) as usize }
+        }
+
+        fn storage_read(&mut self, key: &[u8], out: &mut [u8]) -> Option<usize> {
+            let len = unsafe {
+                storage_read(key.as_ptr() as i32, key.len() as i32, out.as_mut_ptr() as i32, out.len() as i32)
+            };
+            (len >= 0).then_some(len as usize)
+        }
+
+        fn storage_write(&mut self, key: &[u8], value: &[u8]) {
+            unsafe { storage_write(key.as_ptr() as i32, key.len() as i32, value.as_ptr() as i32, value.len() as i32) }
+        }
+
+        fn emit_event(&mut self, name: &str, data: &[u8]) {
+            unsafe { emit_event(name.as_ptr() as i32, name.len() as i32, data.as_ptr() as i32, data.len() as i32) }
+        }
+
+        fn sha3(&mut self, data: &[u8]) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            unsafe { crypto_sha3(data.as_ptr() as i32, data.len() as i32, out.as_mut_ptr() as i32) };
+            out
+        }
+    }
+
+    impl Default for Env<WasmHost> {
+        fn default() -> Self {
+            Env::new(WasmHost)
+        }
+    }
+
+    #[cfg(feature = "panic-handler")]
+    #[panic_handler]
+    fn panic(_: &core::panic::PanicInfo) -> ! {
+        core::arch::wasm32::unreachable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockHost;
+
+    #[test]
+    fn storage_round_trips() {
+        let mut env = Env::new(MockHost::new());
+        assert_eq!(env.get_u64(b"count"), Ok(None));
+        env.set_u64(b"count", 41);
+        env.set_u128(b"supply", u128::MAX - 1);
+        env.set(b"name", b"counter");
+        assert_eq!(env.get_u64(b"count"), Ok(Some(41)));
+        assert_eq!(env.get_u128(b"supply"), Ok(Some(u128::MAX - 1)));
+
+        let mut buf = [0u8; 16];
+        assert_eq!(env.get(b"name", &mut buf), Ok(Some(&b"counter"[..])));
+        let mut small = [0u8; 4];
+        assert_eq!(env.get(b"name", &mut small), Err(Error::BufferTooSmall { needed: 7 }));
+        // Wrong width either way.
+        assert_eq!(env.get_u64(b"name"), Err(Error::InvalidValue));
+        assert_eq!(env.get_u128(b"count"), Err(Error::InvalidValue));
+
+        let host = env.into_host();
+        assert_eq!(host.storage.get(&b"count"[..]), Some(&41u64.to_le_bytes().to_vec()));
+    }
+
+    #[test]
+    fn calldata_is_copied_into_the_buffer() {
+        let mut env = Env::new(MockHost::new().with_calldata(&[1, 2, 3]));
+        let mut buf = [0u8; 8];
+        assert_eq!(env.calldata(&mut buf), Ok(&[1, 2, 3][..]));
+        let mut small = [0u8; 2];
+        assert_eq!(env.calldata(&mut small), Err(Error::BufferTooSmall { needed: 3 }));
+    }
+
+    #[test]
+    fn events_decode_against_the_vm_schema() {
+        use bleep_vm::execution::event_abi::{event_topic, EventBuilder, EventDef, FieldType, FieldValue};
+        use bleep_vm::execution::state_transition::EmittedEvent;
+        use bleep_vm::EventSchema;
+
+        let schema = EventSchema::new(vec![EventDef::new("Moved")
+            .field("id", FieldType::U64)
+            .field("amount", FieldType::U128)
+            .field("to", FieldType::Address)
+            .field("memo", FieldType::String)
+            .field("blob", FieldType::Bytes)])
+        .unwrap();
+
+        let mut env = Env::new(MockHost::new());
+        let event = Event::new("Moved").u64(7).u128(1 << 100).address(&[9; 32]).string("hi").bytes(&[1, 2]);
+        env.emit(event).unwrap();
+        let (name, data) = env.into_host().events.pop().unwrap();
+
+        let expected = EventBuilder::new("Moved").u64(7).u128(1 << 100).address([9; 32]).string("hi").bytes(&[1, 2]);
+        assert_eq!((name.clone(), data.clone()), expected.build());
+        schema.check(&name, &data).unwrap();
+        let stored = EmittedEvent { contract: [0; 32], topics: vec![event_topic(&name)], data, log_index: 0 };
+        let decoded = schema.decode(&stored).unwrap();
+        assert_eq!(decoded.fields["amount"], FieldValue::U128(1 << 100));
+        assert_eq!(decoded.fields["memo"], FieldValue::String("hi".into()));
+        assert_eq!(decoded.fields["blob"], FieldValue::Bytes(vec![1, 2]));
+    }
+
+    #[test]
+    fn oversized_events_are_refused() {
+        let mut env = Env::new(MockHost::new());
+        let event = Event::new("Big").bytes(&[0; EVENT_CAPACITY]);
+        assert_eq!(env.emit(event), Err(Error::EventTooLarge));
+        assert!(env.host().events.is_empty());
+        assert_eq!(Error::EventTooLarge.code(), -3);
+    }
+
+    #[test]
+    fn mock_sha3_matches_the_precompile() {
+        let mut env = Env::new(MockHost::new());
+        assert_eq!(env.sha3(b"abc"), bleep_vm::execution::precompiles::sha3(b"abc"));
+    }
+}
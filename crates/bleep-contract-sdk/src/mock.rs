@@ -0,0 +1,65 @@
+//! In-memory [`Host`] for testing contract logic off-chain.
+//!
+//! ```rust,ignore
+//! let mut env = Env::new(MockHost::new().with_calldata(&5u64.to_le_bytes()));
+//! assert_eq!(increment(&mut env), Ok(5));
+//! assert_eq!(env.host().events.len(), 1);
+//! ```
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use sha3::{Digest, Sha3_256};
+
+use crate::Host;
+
+/// Host state a test can set up and inspect.
+#[derive(Debug, Default, Clone)]
+pub struct MockHost {
+    pub calldata: Vec<u8>,
+    pub storage:  BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Emitted events, oldest first: name and payload
+    pub events:   Vec<(String, Vec<u8>)>,
+}
+
+impl MockHost {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_calldata(mut self, calldata: &[u8]) -> Self {
+        self.calldata = calldata.to_vec();
+        self
+    }
+}
+
+// Copies only when the buffer is large enough, like the registry's host.
+impl Host for MockHost {
+    fn calldata(&mut self, out: &mut [u8]) -> usize {
+        if let Some(dst) = out.get_mut(..self.calldata.len()) {
+            dst.copy_from_slice(&self.calldata);
+        }
+        self.calldata.len()
+    }
+
+    fn storage_read(&mut self, key: &[u8], out: &mut [u8]) -> Option<usize> {
+        let value = self.storage.get(key)?;
+        if let Some(dst) = out.get_mut(..value.len()) {
+            dst.copy_from_slice(value);
+        }
+        Some(value.len())
+    }
+
+    fn storage_write(&mut self, key: &[u8], value: &[u8]) {
+        self.storage.insert(key.to_vec(), value.to_vec());
+    }
+
+    fn emit_event(&mut self, name: &str, data: &[u8]) {
+        self.events.push((name.to_string(), data.to_vec()));
+    }
+
+    fn sha3(&mut self, data: &[u8]) -> [u8; 32] {
+        Sha3_256::digest(data).into()
+    }
+}
@@ -0,0 +1,329 @@
+//! # Per-block base fee
+//!
+//! EIP-1559-style fee floor.  Every block charges each included transaction
+//! the block's base fee plus the transaction's tip; the base fee is burned
+//! (or, with `treasury_share_bps`, partly paid to `TREASURY_ACCOUNT`) and
+//! the tip goes to the proposer.
+//!
+//! After each block the base fee moves towards demand: a block fuller than
+//! `target_fullness_bps` raises it, an emptier one lowers it, by at most
+//! `max_change_bps` per block and never outside `[min_base_fee,
+//! max_base_fee]`.  Slots without a block count as empty blocks.
+//!
+//! Transactions state the most they will pay per inclusion (`max_fee`) and
+//! an optional `tip`.  The `TransactionPool` admits only transactions whose
+//! `max_fee` covers the base fee the next block will charge, and evicts the
+//! ones a rising fee has left behind.
+
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+/// Account credited with the treasury share of burned base fees.
+pub const TREASURY_ACCOUNT: &str = "bleep:treasury";
+
+/// Account holding block issuance and tips until validators and delegators
+/// claim them (see `bleep_consensus::rewards`).
+pub const REWARD_POOL_ACCOUNT: &str = "bleep:rewards";
+
+/// Receiver prefix of reward transactions (claims, commission changes);
+/// like key changes they carry no amount.
+pub const REWARD_TX_PREFIX: &str = "bleep:rewards/";
+
+/// Account holding bonded validator stake (see `bleep_consensus::staking`).
+pub const STAKING_ACCOUNT: &str = "bleep:staking";
+
+/// Receiver prefix of staking transactions: a bond carries the stake, an
+/// unbond no amount.
+pub const STAKING_TX_PREFIX: &str = "bleep:staking/";
+
+const BPS: u64 = 10_000;
+
+/// Governance-controlled base fee parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseFeeParams {
+    /// Base fee of the first block, in µBLEEP.
+    pub initial_base_fee:    u64,
+    pub min_base_fee:        u64,
+    pub max_base_fee:        u64,
+    /// Fullness at which the base fee holds steady.
+    pub target_fullness_bps: u16,
+    /// Largest change per block, reached by a full or an empty block.
+    pub max_change_bps:      u16,
+    /// Part of the base fee paid to the treasury instead of burned.
+    pub treasury_share_bps:  u16,
+}
+
+impl Default for BaseFeeParams {
+    fn default() -> Self {
+        Self {
+            initial_base_fee:    1_000,
+            min_base_fee:        100,
+            max_base_fee:        10_000_000_000,
+            target_fullness_bps: 5_000,
+            max_change_bps:      1_250,
+            treasury_share_bps:  0,
+        }
+    }
+}
+
+impl BaseFeeParams {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_base_fee == 0 {
+            return Err("min_base_fee must be positive".into());
+        }
+        if self.min_base_fee > self.max_base_fee {
+            return Err(format!(
+                "min_base_fee {} exceeds max_base_fee {}", self.min_base_fee, self.max_base_fee
+            ));
+        }
+        if !(self.min_base_fee..=self.max_base_fee).contains(&self.initial_base_fee) {
+            return Err(format!("initial_base_fee {} outside bounds", self.initial_base_fee));
+        }
+        if self.target_fullness_bps == 0 || self.target_fullness_bps as u64 >= BPS {
+            return Err("target_fullness_bps must be in (0, 10000)".into());
+        }
+        if self.max_change_bps as u64 > BPS || self.treasury_share_bps as u64 > BPS {
+            return Err("max_change_bps and treasury_share_bps must be ≤ 10000".into());
+        }
+        Ok(())
+    }
+
+    /// Base fee of the block after one charging `current` and filled to
+    /// `fullness_bps`.
+    pub fn next_base_fee(&self, current: u64, fullness_bps: u16) -> u64 {
+        let current = current as u128;
+        let target  = self.target_fullness_bps as u128;
+        let full    = (fullness_bps as u128).min(BPS as u128);
+        let max_change = self.max_change_bps as u128;
+        let next = if full > target {
+            let delta = current * max_change * (full - target) / ((BPS as u128 - target) * BPS as u128);
+            // Always move up, so a small base fee can still ratchet.
+            current + delta.max(1)
+        } else {
+            current - current * max_change * (target - full) / (target * BPS as u128)
+        };
+        next.clamp(self.min_base_fee as u128, self.max_base_fee as u128) as u64
+    }
+
+    /// `(burned, treasury)` parts of one base fee payment.
+    pub fn split(&self, base_fee: u64) -> (u64, u64) {
+        let treasury = (base_fee as u128 * self.treasury_share_bps as u128 / BPS as u128) as u64;
+        (base_fee - treasury, treasury)
+    }
+}
+
+/// Block fullness in basis points.
+pub fn fullness_bps(tx_count: usize, capacity: usize) -> u16 {
+    if capacity == 0 {
+        return 0;
+    }
+    (tx_count as u64 * BPS / capacity as u64).min(BPS) as u16
+}
+
+/// What including one transaction cost its sender.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeCharge {
+    pub base_fee: u64,
+    /// Tip actually paid to the proposer.
+    pub tip:      u64,
+    pub burned:   u64,
+    pub treasury: u64,
+}
+
+impl FeeCharge {
+    /// Total debited from the sender on top of the amount.
+    pub fn total(&self) -> u64 {
+        self.base_fee + self.tip
+    }
+}
+
+/// Fee for including a transaction offering `max_fee` and `tip` in a block
+/// charging `base_fee`.  The tip is capped so the total never exceeds
+/// `max_fee`.
+pub fn charge(params: &BaseFeeParams, base_fee: u64, max_fee: u64, tip: u64) -> Result<FeeCharge, String> {
+    if max_fee < base_fee {
+        return Err(format!("max fee {} below base fee {}", max_fee, base_fee));
+    }
+    let (burned, treasury) = params.split(base_fee);
+    Ok(FeeCharge { base_fee, tip: tip.min(max_fee - base_fee), burned, treasury })
+}
+
+/// Running totals of fees charged since the tracker started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTotals {
+    pub burned:   u128,
+    pub treasury: u128,
+    pub tips:     u128,
+    pub blocks:   u64,
+}
+
+/// Fee suggestion for a new transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    /// Base fee the next block charges; the pool's admission floor.
+    pub base_fee:  u64,
+    pub tip:       u64,
+    /// Twice the base fee plus the tip, so the transaction survives several
+    /// full blocks before it falls below the floor.
+    pub max_fee:   u64,
+}
+
+struct TrackerState {
+    params:   BaseFeeParams,
+    base_fee: u64,
+    totals:   FeeTotals,
+}
+
+/// Live base fee, shared by the block producer (which moves it), the pool
+/// (which admits against it) and RPC (which reports it).
+pub struct BaseFeeTracker {
+    inner: Mutex<TrackerState>,
+}
+
+impl BaseFeeTracker {
+    pub fn new(params: BaseFeeParams) -> Result<Self, String> {
+        params.validate()?;
+        Ok(Self {
+            inner: Mutex::new(TrackerState {
+                params,
+                base_fee: params.initial_base_fee,
+                totals:   FeeTotals::default(),
+            }),
+        })
+    }
+
+    /// Base fee the next block charges.
+    pub fn current(&self) -> u64 {
+        self.inner.lock().unwrap().base_fee
+    }
+
+    pub fn params(&self) -> BaseFeeParams {
+        self.inner.lock().unwrap().params
+    }
+
+    /// Replace the parameters (governance), keeping the current base fee
+    /// within the new bounds.
+    pub fn set_params(&self, params: BaseFeeParams) -> Result<(), String> {
+        params.validate()?;
+        let mut s = self.inner.lock().unwrap();
+        s.params = params;
+        s.base_fee = s.base_fee.clamp(params.min_base_fee, params.max_base_fee);
+        Ok(())
+    }
+
+    /// Record a committed block of `tx_count` out of `capacity`
+    /// transactions and the fees it charged; returns the next base fee.
+    pub fn on_block(&self, tx_count: usize, capacity: usize, charges: &[FeeCharge]) -> u64 {
+        let mut s = self.inner.lock().unwrap();
+        for c in charges {
+            s.totals.burned   += c.burned as u128;
+            s.totals.treasury += c.treasury as u128;
+            s.totals.tips     += c.tip as u128;
+        }
+        s.totals.blocks += 1;
+        s.base_fee = s.params.next_base_fee(s.base_fee, fullness_bps(tx_count, capacity));
+        s.base_fee
+    }
+
+    /// A slot passed without a block; decays the base fee like an empty block.
+    pub fn on_empty_slot(&self) -> u64 {
+        let mut s = self.inner.lock().unwrap();
+        s.base_fee = s.params.next_base_fee(s.base_fee, 0);
+        s.base_fee
+    }
+
+    pub fn totals(&self) -> FeeTotals {
+        self.inner.lock().unwrap().totals
+    }
+
+    /// Suggested `max_fee` for a transaction tipping `tip`.
+    pub fn estimate(&self, tip: u64) -> FeeEstimate {
+        let base_fee = self.current();
+        FeeEstimate { base_fee, tip, max_fee: base_fee.saturating_mul(2).saturating_add(tip) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> BaseFeeTracker {
+        BaseFeeTracker::new(BaseFeeParams::default()).unwrap()
+    }
+
+    #[test]
+    fn full_blocks_ratchet_the_fee_up_within_bounds() {
+        let params = BaseFeeParams::default();
+        let t = tracker();
+        let mut prev = t.current();
+        for _ in 0..10 {
+            let next = t.on_block(100, 100, &[]);
+            assert!(next > prev);
+            // At most +12.5% per block.
+            assert!(next as u128 * 10_000 <= prev as u128 * 11_250);
+            prev = next;
+        }
+        // At target the fee holds.
+        assert_eq!(t.on_block(50, 100, &[]), prev);
+
+        let capped = BaseFeeParams { max_base_fee: 1_100, ..params };
+        let t = BaseFeeTracker::new(capped).unwrap();
+        for _ in 0..10 {
+            t.on_block(100, 100, &[]);
+        }
+        assert_eq!(t.current(), 1_100);
+    }
+
+    #[test]
+    fn empty_blocks_and_slots_decay_the_fee_to_the_minimum() {
+        let t = tracker();
+        for _ in 0..5 {
+            t.on_block(100, 100, &[]);
+        }
+        let high = t.current();
+        let after_block = t.on_block(0, 100, &[]);
+        assert!(after_block < high);
+        assert!(after_block as u128 * 10_000 >= high as u128 * 8_750);
+        assert!(t.on_empty_slot() < after_block);
+        for _ in 0..200 {
+            t.on_empty_slot();
+        }
+        assert_eq!(t.current(), BaseFeeParams::default().min_base_fee);
+    }
+
+    #[test]
+    fn charges_reconcile_with_burn_and_treasury_totals() {
+        let params = BaseFeeParams { treasury_share_bps: 2_500, ..Default::default() };
+        let t = BaseFeeTracker::new(params).unwrap();
+
+        assert!(charge(&params, 1_000, 999, 0).is_err());
+        let capped = charge(&params, 1_000, 1_200, 500).unwrap();
+        assert_eq!(capped.tip, 200);
+        assert_eq!(capped.total(), 1_200);
+
+        let charges: Vec<FeeCharge> = (0..7)
+            .map(|i| charge(&params, 1_001, 5_000, i * 10).unwrap())
+            .collect();
+        for c in &charges {
+            assert_eq!(c.burned + c.treasury, c.base_fee);
+        }
+        t.on_block(charges.len(), 10, &charges);
+
+        let totals = t.totals();
+        let paid: u128 = charges.iter().map(|c| c.total() as u128).sum();
+        assert_eq!(totals.burned + totals.treasury + totals.tips, paid);
+        assert_eq!(totals.treasury, 7 * 250);
+        assert_eq!(totals.tips, 210);
+    }
+
+    #[test]
+    fn governance_params_are_validated_and_clamp_the_fee() {
+        let t = tracker();
+        assert!(t.set_params(BaseFeeParams { target_fullness_bps: 0, ..Default::default() }).is_err());
+        t.set_params(BaseFeeParams { min_base_fee: 5_000, initial_base_fee: 5_000, ..Default::default() })
+            .unwrap();
+        assert_eq!(t.current(), 5_000);
+        assert_eq!(t.estimate(7), FeeEstimate { base_fee: 5_000, tip: 7, max_fee: 10_007 });
+    }
+}
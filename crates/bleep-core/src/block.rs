@@ -32,8 +32,9 @@ use chrono::Utc;
 
 // bleep_crypto::tx_signer used by InboundBlockHandler in main.rs (not directly in block.rs)
 use bleep_crypto::pq_crypto::SignatureScheme;
+pub use bleep_crypto::chain_id::ChainId;
 use pqcrypto_sphincsplus::sphincsshake256fsimple;
-use pqcrypto_traits::sign::{SecretKey as _, DetachedSignature as _};
+use pqcrypto_traits::sign::{SecretKey as _, PublicKey as _, DetachedSignature as _};
 
 /// Byte length of a SPHINCS+-SHAKE-256-simple public key.
 /// pqcrypto_sphincsplus::sphincsshake256fsimple generates 64-byte public keys.
@@ -43,6 +44,9 @@ pub const SPHINCS_SIG_LEN: usize = 49856;
 /// Total validator_signature length: pk || sig.
 pub const VALIDATOR_SIG_LEN: usize = SPHINCS_PK_LEN + SPHINCS_SIG_LEN;
 
+/// Timestamp of `Block::genesis` (2024-01-01T00:00:00Z).
+pub const GENESIS_TIMESTAMP: u64 = 1_704_067_200;
+
 /// Legacy Sprint 5 validator_signature length (SHA3 scheme).
 const LEGACY_SIG_LEN: usize = 96;
 
@@ -53,6 +57,50 @@ pub struct Transaction {
     pub amount: u64,
     pub timestamp: u64,
     pub signature: Vec<u8>,
+    #[serde(default, skip_serializing_if = "crate::transaction::is_zero")]
+    pub max_fee: u64,
+    #[serde(default, skip_serializing_if = "crate::transaction::is_zero")]
+    pub tip: u64,
+    /// Position in the sender's transaction sequence, from 0.
+    #[serde(default, skip_serializing_if = "crate::transaction::is_zero")]
+    pub nonce: u64,
+    /// Network the transaction is signed for; `ChainId::NONE` when unbound.
+    #[serde(default, skip_serializing_if = "ChainId::is_none")]
+    pub chain_id: ChainId,
+}
+
+impl Transaction {
+    /// Canonical payload the wire signature covers, bound to `chain_id`.
+    pub fn signed_payload(&self) -> [u8; 32] {
+        self.chain_id.bind(bleep_crypto::tx_signer::tx_payload_with_nonce(
+            &self.sender, &self.receiver, self.amount, self.timestamp, self.max_fee, self.tip, self.nonce,
+        ))
+    }
+
+    /// Canonical id `"sender:receiver:amount:timestamp"`, as
+    /// `TransactionPool::remove_confirmed` takes it.
+    pub fn id(&self) -> String {
+        format!("{}:{}:{}:{}", self.sender, self.receiver, self.amount, self.timestamp)
+    }
+
+    /// Whether the wire signature `pk(64) || sig` verifies over
+    /// `signed_payload` — `transaction_pool::signature_valid`, which mempool
+    /// admission checks.  A recovery co-signature on a key change is not
+    /// checked here.
+    pub fn signature_valid(&self) -> bool {
+        crate::transaction_pool::signature_valid(&self.into())
+    }
+}
+
+/// Groth16 proof attached to one of a block's transactions.
+///
+/// Bytes are arkworks compressed encodings (`ark_groth16::Proof<Bls12_381>`
+/// and BLS12-381 scalars), decoded by `BlockValidator::validate_tx_proofs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxZkProof {
+    pub tx_index: u32,
+    pub proof: Vec<u8>,
+    pub public_inputs: Vec<Vec<u8>>,
 }
 
 /// Consensus mode enumeration.
@@ -95,6 +143,37 @@ pub struct Block {
     pub shard_registry_root: String,
     pub shard_id: u64,
     pub shard_state_root: String,
+
+    /// Per-transaction Groth16 proofs, batch-verified on validation.
+    /// Absent in blocks without private transactions.
+    #[serde(default)]
+    pub tx_proofs: Vec<TxZkProof>,
+
+    /// Encoded misbehaviour evidence (`bleep_consensus::evidence`), kept in
+    /// reserved block space.  Committed to by `evidence_root`, which the
+    /// block hash covers whenever evidence is present.
+    #[serde(default)]
+    pub evidence: Vec<Vec<u8>>,
+    #[serde(default)]
+    pub evidence_root: String,
+
+    /// Base fee every transaction in the block paid (`base_fee` module).
+    /// Zero on chains without a fee market; hashed only when set.
+    #[serde(default)]
+    pub base_fee: u64,
+
+    /// Expected hashes per block this block was mined at; zero for blocks
+    /// not produced by proof of work.  Hashed, with `pow_nonce`, only when set.
+    #[serde(default)]
+    pub pow_difficulty: u64,
+    #[serde(default)]
+    pub pow_nonce: u64,
+
+    /// Network the block belongs to, inherited from genesis.  Every
+    /// transaction in it must be signed for the same chain.  Hashed only
+    /// when set.
+    #[serde(default)]
+    pub chain_id: ChainId,
 }
 
 impl Block {
@@ -115,9 +194,28 @@ impl Block {
             shard_registry_root: "0".repeat(64),
             shard_id: 0,
             shard_state_root: "0".repeat(64),
+            tx_proofs: vec![],
+            evidence: vec![],
+            evidence_root: String::new(),
+            base_fee: 0,
+            pow_difficulty: 0,
+            pow_nonce: 0,
+            chain_id: ChainId::NONE,
         }
     }
 
+    /// The network's genesis block.  Unlike `Block::new` its timestamp is
+    /// fixed, so every node — validator or replica — links block 1 to the
+    /// same hash.
+    pub fn genesis() -> Self {
+        Self { timestamp: GENESIS_TIMESTAMP, ..Self::new(0, vec![], "0".to_string()) }
+    }
+
+    /// Genesis of the network `chain_id`; every later block inherits it.
+    pub fn genesis_for(chain_id: ChainId) -> Self {
+        Self { chain_id, ..Self::genesis() }
+    }
+
     pub fn with_consensus_and_sharding(
         index: u64,
         transactions: Vec<Transaction>,
@@ -137,6 +235,13 @@ impl Block {
             zk_proof: vec![],
             epoch_id, consensus_mode, protocol_version,
             shard_registry_root, shard_id, shard_state_root,
+            tx_proofs: vec![],
+            evidence: vec![],
+            evidence_root: String::new(),
+            base_fee: 0,
+            pow_difficulty: 0,
+            pow_nonce: 0,
+            chain_id: ChainId::NONE,
         }
     }
 
@@ -157,6 +262,84 @@ impl Block {
             self.shard_registry_root,
             self.shard_id
         ));
+        // Blocks without evidence keep their pre-evidence hashes.
+        if !self.evidence_root.is_empty() {
+            h.update(self.evidence_root.as_bytes());
+        }
+        if self.base_fee != 0 {
+            h.update(self.base_fee.to_le_bytes());
+        }
+        if self.pow_difficulty != 0 {
+            h.update(self.pow_difficulty.to_le_bytes());
+            h.update(self.pow_nonce.to_le_bytes());
+        }
+        if !self.chain_id.is_none() {
+            h.update(self.chain_id.0.to_le_bytes());
+        }
+        hex::encode(h.finalize())
+    }
+
+    // ── Proof of work ─────────────────────────────────────────────────────────
+
+    /// What the nonce search commits to: the block hash with `pow_nonce` zeroed.
+    pub fn pow_commitment(&self) -> String {
+        Block { pow_nonce: 0, ..self.header() }.compute_hash()
+    }
+
+    /// Whether the block claims to be mined: an `EmergencyPow` block, or
+    /// one carrying a difficulty.
+    pub fn is_pow(&self) -> bool {
+        self.consensus_mode == ConsensusMode::EmergencyPow || self.pow_difficulty != 0
+    }
+
+    /// Whether `pow_nonce` solves the block at its `pow_difficulty`.
+    pub fn meets_pow_difficulty(&self) -> bool {
+        pow_target_met(&pow_hash(&self.pow_commitment(), self.pow_nonce), self.pow_difficulty)
+    }
+
+    /// The block without its body: transactions, proofs, evidence and
+    /// signature dropped.  Every field `compute_hash` covers is kept, so the
+    /// header hashes the same as the block and links the same way.
+    pub fn header(&self) -> Block {
+        Block {
+            index: self.index,
+            timestamp: self.timestamp,
+            transactions: vec![],
+            previous_hash: self.previous_hash.clone(),
+            merkle_root: self.merkle_root.clone(),
+            validator_signature: vec![],
+            zk_proof: vec![],
+            epoch_id: self.epoch_id,
+            consensus_mode: self.consensus_mode,
+            protocol_version: self.protocol_version,
+            shard_registry_root: self.shard_registry_root.clone(),
+            shard_id: self.shard_id,
+            shard_state_root: self.shard_state_root.clone(),
+            tx_proofs: vec![],
+            evidence: vec![],
+            evidence_root: self.evidence_root.clone(),
+            base_fee: self.base_fee,
+            pow_difficulty: self.pow_difficulty,
+            pow_nonce: self.pow_nonce,
+            chain_id: self.chain_id,
+        }
+    }
+
+    /// Attach evidence and commit to it; call before signing.
+    pub fn set_evidence(&mut self, evidence: Vec<Vec<u8>>) {
+        self.evidence_root = Block::calculate_evidence_root(&evidence);
+        self.evidence = evidence;
+    }
+
+    /// SHA3-256 over the entries' hashes; empty string for no evidence.
+    pub fn calculate_evidence_root(evidence: &[Vec<u8>]) -> String {
+        if evidence.is_empty() {
+            return String::new();
+        }
+        let mut h = Sha3_256::new();
+        for e in evidence {
+            h.update(Sha3_256::digest(e));
+        }
         hex::encode(h.finalize())
     }
 
@@ -272,6 +455,27 @@ impl Block {
         Ok(sig_non_zero)
     }
 
+    /// Public key embedded in a Sprint 6+ `validator_signature`, if any.
+    pub fn signer_public_key(&self) -> Option<&[u8]> {
+        (self.validator_signature.len() == VALIDATOR_SIG_LEN)
+            .then(|| &self.validator_signature[..SPHINCS_PK_LEN])
+    }
+
+    /// Full SPHINCS+ check of the header signature against the embedded key.
+    ///
+    /// Only the header is hashed, so this is independent of the block's
+    /// transaction count; callers decide whether the key is a validator's.
+    pub fn verify_header_signature(&self) -> bool {
+        let Some(pk_bytes) = self.signer_public_key() else { return false };
+        let (Ok(pk), Ok(sig)) = (
+            sphincsshake256fsimple::PublicKey::from_bytes(pk_bytes),
+            sphincsshake256fsimple::DetachedSignature::from_bytes(&self.validator_signature[SPHINCS_PK_LEN..]),
+        ) else {
+            return false;
+        };
+        sphincsshake256fsimple::verify_detached_signature(&sig, &self.compute_hash_bytes(), &pk).is_ok()
+    }
+
     /// Legacy Sprint 5 verification (SHA3 scheme, 96-byte sig).
     fn verify_signature_legacy(&self, public_key: &[u8]) -> Result<bool, String> {
         if public_key.len() != 32 {
@@ -400,6 +604,11 @@ impl Block {
             h.update(tx.receiver.as_bytes());
             h.update(tx.amount.to_le_bytes());
             h.update(tx.timestamp.to_le_bytes());
+            // Fee-less transactions keep their pre-fee leaves.
+            if tx.max_fee != 0 || tx.tip != 0 {
+                h.update(tx.max_fee.to_le_bytes());
+                h.update(tx.tip.to_le_bytes());
+            }
             hex::encode(h.finalize())
         }).collect();
 
@@ -419,6 +628,25 @@ impl Block {
     }
 }
 
+// ── Proof of work ─────────────────────────────────────────────────────────────
+
+/// Work hash of one nonce: `SHA3-256(commitment || nonce_le8)`.
+pub fn pow_hash(commitment: &str, nonce: u64) -> [u8; 32] {
+    let mut h = Sha3_256::new();
+    h.update(commitment.as_bytes());
+    h.update(nonce.to_le_bytes());
+    h.finalize().into()
+}
+
+/// Whether `hash` meets `difficulty`: its first 8 bytes, big-endian, are at
+/// most `u64::MAX / difficulty`, so one in `difficulty` hashes qualifies.
+pub fn pow_target_met(hash: &[u8], difficulty: u64) -> bool {
+    let mut head = [0u8; 8];
+    let n = hash.len().min(8);
+    head[..n].copy_from_slice(&hash[..n]);
+    u64::from_be_bytes(head) <= u64::MAX / difficulty.max(1)
+}
+
 // ── Block keypair helper (Sprint 3+, still used for pk fingerprint) ───────────
 
 /// Derive a (secret_key_32, public_key_32) pair for the block-signing fingerprint.
@@ -0,0 +1,906 @@
+//! # Block sync
+//!
+//! A node that is behind downloads the blocks it is missing from its
+//! peers over `bleep_p2p::block_sync` and applies them in order:
+//!
+//! ```text
+//! Headers from the local tip ─▶ each header links to the one before it?
+//!        │                      (BlockValidator::validate_block_link)
+//!        ▼
+//! Bodies in batches, in parallel across peers ─▶ hash == header hash?
+//!        │
+//!        ▼
+//! Blockchain::add_block, lowest height first ─▶ SyncProgress / telemetry
+//! ```
+//!
+//! The header chain is taken from the peer reporting the highest tip; a
+//! peer whose headers do not link is penalized — dropped for the rest of
+//! the sync — and the headers are fetched again from the next best peer.
+//! Bodies may come from any peer at or above the batch, and a body whose
+//! hash differs from its header gets its peer penalized the same way.
+//! A block the chain refuses stops the sync: its header chain is the one
+//! the best peer vouched for, so there is nothing better to fall back to.
+//!
+//! Headers are served with their validator signature.  With a signer check
+//! attached (`ChainSyncer::with_signer_check`) every header must carry a
+//! valid signature by a key the check accepts for its height before any
+//! body is downloaded; a peer serving one that does not is penalized like
+//! one whose headers do not link.
+//!
+//! A peer whose header above our tip links to some other block holds a
+//! competing chain.  Its headers are fetched from at most
+//! `max_fork_depth` blocks below our tip, its blocks from where the two
+//! chains part, and the branch goes to `Blockchain::handle_fork`, which
+//! switches only to a heavier one.  A branch the chain rejects gets its
+//! peer penalized; a lighter one is left alone and so is its peer.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+use parking_lot::Mutex;
+use thiserror::Error;
+
+use bleep_p2p::block_sync::{BlockSource, SyncPart, SyncPeer, SyncRequest, SyncResponse, SyncTelemetry, MAX_SYNC_BLOCKS};
+
+use crate::block::Block;
+use crate::block_validation::BlockValidator;
+use crate::blockchain::Blockchain;
+
+/// Default blocks per body request.
+pub const DEFAULT_BODY_BATCH: u32 = 32;
+
+/// Default body requests in flight.
+pub const DEFAULT_PARALLEL_BODIES: usize = 4;
+
+/// Failed requests (timeouts, `Unavailable`, busy) before a peer is dropped.
+pub const DEFAULT_MAX_PEER_FAILURES: u32 = 3;
+
+/// Blocks below the tip a competing chain may part from ours.
+pub const DEFAULT_MAX_FORK_DEPTH: u64 = 64;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BlockSyncError {
+    #[error("Headers from {0} could not be fetched: no usable peers left")]
+    NoHeaders(u64),
+    #[error("Block {0} could not be fetched: no usable peers left")]
+    NoPeers(u64),
+    #[error("Block {0} was rejected by the chain")]
+    Rejected(u64),
+    #[error("Block sync storage: {0}")]
+    Storage(String),
+}
+
+/// Serves blocks from a node's chain.
+pub struct ChainSource {
+    chain: Arc<RwLock<Blockchain>>,
+}
+
+impl ChainSource {
+    pub fn new(chain: Arc<RwLock<Blockchain>>) -> Self {
+        Self { chain }
+    }
+
+    fn with_block<T>(&self, index: u64, f: impl FnOnce(&Block) -> T) -> Option<T> {
+        let chain = self.chain.read().ok()?;
+        let first = chain.chain.front()?.index;
+        let block = chain.chain.get(usize::try_from(index.checked_sub(first)?).ok()?)?;
+        Some(f(block))
+    }
+}
+
+impl BlockSource for ChainSource {
+    fn tip(&self) -> u64 {
+        self.chain.read().ok().and_then(|c| c.latest_block()).map_or(0, |b| b.index)
+    }
+
+    fn header(&self, index: u64) -> Option<Vec<u8>> {
+        self.with_block(index, |b| {
+            let mut header = b.header();
+            header.validator_signature = b.validator_signature.clone();
+            bincode::serialize(&header).ok()
+        }).flatten()
+    }
+
+    fn block(&self, index: u64) -> Option<Vec<u8>> {
+        self.with_block(index, |b| bincode::serialize(b).ok()).flatten()
+    }
+}
+
+/// Local height against the best height peers report, shared with
+/// `/rpc/ready` and telemetry.
+#[derive(Debug, Default)]
+pub struct SyncProgress {
+    height:      AtomicU64,
+    best_height: AtomicU64,
+    penalized:   AtomicU64,
+    complete:    AtomicBool,
+}
+
+impl SyncProgress {
+    pub fn height(&self) -> u64 {
+        self.height.load(Ordering::Relaxed)
+    }
+
+    pub fn best_height(&self) -> u64 {
+        self.best_height.load(Ordering::Relaxed)
+    }
+
+    pub fn peers_penalized(&self) -> u64 {
+        self.penalized.load(Ordering::Relaxed)
+    }
+
+    /// True once the chain has caught up with the best height seen.
+    pub fn is_complete(&self) -> bool {
+        self.complete.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BlockSyncConfig {
+    pub header_batch:      u32,
+    pub body_batch:        u32,
+    pub parallel_bodies:   usize,
+    pub max_peer_failures: u32,
+    pub max_fork_depth:    u64,
+}
+
+impl Default for BlockSyncConfig {
+    fn default() -> Self {
+        Self {
+            header_batch:      MAX_SYNC_BLOCKS,
+            body_batch:        DEFAULT_BODY_BATCH,
+            parallel_bodies:   DEFAULT_PARALLEL_BODIES,
+            max_peer_failures: DEFAULT_MAX_PEER_FAILURES,
+            max_fork_depth:    DEFAULT_MAX_FORK_DEPTH,
+        }
+    }
+}
+
+/// What a completed sync did.
+#[derive(Debug, Clone)]
+pub struct SyncOutcome {
+    pub from_height: u64,
+    pub height:      u64,
+    pub applied:     u64,
+    /// Competing chains switched to.
+    pub reorgs:      u64,
+    /// Peers dropped for serving bad data.
+    pub penalized:   Vec<String>,
+}
+
+/// Peers still in use, their reported tips, and the penalties handed out.
+#[derive(Default)]
+struct PeerBook {
+    dropped:   Vec<bool>,
+    failures:  Vec<u32>,
+    tips:      Vec<u64>,
+    penalized: Vec<String>,
+}
+
+/// Headers a peer serves above the local tip.
+enum HeaderChain {
+    /// Linking on from the tip.
+    Linked(Vec<Block>),
+    /// From the peer at this index, whose chain leaves ours below the tip.
+    Fork(usize),
+}
+
+/// Downloaded bodies waiting for the blocks below them.
+struct Applier {
+    next:    u64,
+    pending: BTreeMap<u64, Block>,
+}
+
+/// Whether the key that signed a header may sign blocks at its height,
+/// e.g. membership of the active validator set.
+pub type SignerCheck = Arc<dyn Fn(&Block, &[u8]) -> bool + Send + Sync>;
+
+/// Validator key a block is passed to `Blockchain::add_block` with, as the
+/// node's own block acceptance picks it.
+pub type ValidatorKey = Arc<dyn Fn(&Block) -> Vec<u8> + Send + Sync>;
+
+/// Catches a chain up with its peers, headers first.
+pub struct ChainSyncer {
+    peers:      Vec<Arc<dyn SyncPeer>>,
+    validator_key: ValidatorKey,
+    signer_check: Option<SignerCheck>,
+    config:     BlockSyncConfig,
+    progress:   Arc<SyncProgress>,
+    telemetry:  Option<SyncTelemetry>,
+}
+
+impl ChainSyncer {
+    pub fn new(peers: Vec<Arc<dyn SyncPeer>>) -> Self {
+        Self {
+            peers,
+            validator_key: Arc::new(|_block: &Block| Vec::new()),
+            signer_check: None,
+            config:     BlockSyncConfig::default(),
+            progress:   Arc::new(SyncProgress::default()),
+            telemetry:  None,
+        }
+    }
+
+    /// Validator key passed to `Blockchain::add_block` for every block.
+    pub fn with_public_key(mut self, public_key: &[u8]) -> Self {
+        let public_key = public_key.to_vec();
+        self.validator_key = Arc::new(move |_block: &Block| public_key.clone());
+        self
+    }
+
+    /// Pick the key each block is validated against, so synced blocks pass
+    /// the same `add_block` check as gossiped ones.
+    pub fn with_validator_key(mut self, key: ValidatorKey) -> Self {
+        self.validator_key = key;
+        self
+    }
+
+    /// Require every header to be signed by a key `check` accepts.
+    pub fn with_signer_check(mut self, check: SignerCheck) -> Self {
+        self.signer_check = Some(check);
+        self
+    }
+
+    pub fn with_config(mut self, config: BlockSyncConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_telemetry(mut self, telemetry: SyncTelemetry) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    pub fn progress(&self) -> Arc<SyncProgress> {
+        Arc::clone(&self.progress)
+    }
+
+    /// Download and apply every block the best peer has above the local tip.
+    pub async fn run(&self, chain: &RwLock<Blockchain>) -> Result<SyncOutcome, BlockSyncError> {
+        let mut tip = Self::tip(chain)?;
+        let from_height = tip.index;
+        self.progress.height.store(tip.index, Ordering::Relaxed);
+        let book = Mutex::new(PeerBook {
+            dropped:  vec![false; self.peers.len()],
+            failures: vec![0; self.peers.len()],
+            tips:     vec![0; self.peers.len()],
+            ..PeerBook::default()
+        });
+        self.probe(&book, &tip).await;
+
+        let mut reorgs = 0;
+        let headers = loop {
+            match self.fetch_headers(&book, &tip).await? {
+                HeaderChain::Linked(headers) => break headers,
+                HeaderChain::Fork(peer) => {
+                    if self.fork(chain, &book, peer, &tip).await? {
+                        reorgs += 1;
+                        tip = Self::tip(chain)?;
+                        self.progress.height.store(tip.index, Ordering::Relaxed);
+                    } else {
+                        // Nothing this peer has is worth taking.
+                        book.lock().tips[peer] = tip.index;
+                    }
+                }
+            }
+        };
+        log::info!(
+            "[BlockSync] {} headers verified above height {} from {} peers",
+            headers.len(), tip.index, self.peers.len(),
+        );
+
+        // Popped from the back: lowest height first.
+        let batch = self.config.body_batch.max(1) as usize;
+        let queue = Mutex::new(
+            headers.chunks(batch).map(|c| (c[0].index, c.len() as u32)).rev().collect::<Vec<_>>(),
+        );
+        let applier = Mutex::new(Applier { next: tip.index + 1, pending: BTreeMap::new() });
+        let cursor = AtomicUsize::new(0);
+        let workers = (0..self.config.parallel_bodies.max(1))
+            .map(|_| self.worker(chain, &headers, &queue, &applier, &book, &cursor));
+        for result in futures::future::join_all(workers).await {
+            result?;
+        }
+
+        let height = self.progress.height();
+        self.progress.complete.store(true, Ordering::Relaxed);
+        self.export_telemetry();
+        log::info!("[BlockSync] ✅ Synced to height {} ({} blocks applied)", height, height - tip.index);
+        Ok(SyncOutcome {
+            from_height,
+            height,
+            applied:     height - tip.index,
+            reorgs,
+            penalized:   book.into_inner().penalized,
+        })
+    }
+
+    fn tip(chain: &RwLock<Blockchain>) -> Result<Block, BlockSyncError> {
+        chain.read()
+            .map_err(|e| BlockSyncError::Storage(e.to_string()))?
+            .latest_block()
+            .ok_or_else(|| BlockSyncError::Storage("chain is empty".into()))
+    }
+
+    /// Ask every peer for its tip.
+    async fn probe(&self, book: &Mutex<PeerBook>, tip: &Block) {
+        let first = SyncRequest { from_index: tip.index + 1, max_blocks: self.config.header_batch, part: SyncPart::Headers };
+        let probes = self.peers.iter().map(|peer| peer.request(first.clone()));
+        for (i, probe) in futures::future::join_all(probes).await.into_iter().enumerate() {
+            match probe {
+                Ok(response) => book.lock().tips[i] = response.tip(),
+                Err(e) => self.fail(book, i, &e.to_string()),
+            }
+        }
+        self.note_best(book);
+    }
+
+    /// Headers above `tip` up to the best peer's tip, each checked to link
+    /// to the one before it and, with a signer check, to be signed by a
+    /// validator.  Stops at the best peer's first header if it links to a
+    /// block other than `tip`.
+    async fn fetch_headers(&self, book: &Mutex<PeerBook>, tip: &Block) -> Result<HeaderChain, BlockSyncError> {
+        'peers: loop {
+            let peer = {
+                let book = book.lock();
+                (0..self.peers.len())
+                    .filter(|i| !book.dropped[*i] && book.tips[*i] > tip.index)
+                    .max_by_key(|i| book.tips[*i])
+            };
+            // Nobody is ahead of us: already synced.
+            let Some(peer) = peer else {
+                return if book.lock().dropped.iter().all(|d| *d) && !self.peers.is_empty() {
+                    Err(BlockSyncError::NoHeaders(tip.index + 1))
+                } else {
+                    Ok(HeaderChain::Linked(Vec::new()))
+                };
+            };
+
+            let mut headers: Vec<Block> = Vec::new();
+            loop {
+                let prev = headers.last().unwrap_or(tip);
+                let from_index = prev.index + 1;
+                if from_index > book.lock().tips[peer] {
+                    return Ok(HeaderChain::Linked(headers));
+                }
+                let request = SyncRequest { from_index, max_blocks: self.config.header_batch, part: SyncPart::Headers };
+                let batch = match self.peers[peer].request(request).await {
+                    Ok(SyncResponse::Headers { tip: peer_tip, headers }) => {
+                        book.lock().tips[peer] = peer_tip;
+                        self.note_best(book);
+                        headers
+                    }
+                    Ok(response) => {
+                        // Asked at or below the tip it reported, and got nothing.
+                        book.lock().tips[peer] = response.tip();
+                        self.fail(book, peer, &format!("no headers from {}", from_index));
+                        continue 'peers;
+                    }
+                    Err(e) => {
+                        self.fail(book, peer, &e.to_string());
+                        if book.lock().dropped[peer] {
+                            continue 'peers;
+                        }
+                        continue;
+                    }
+                };
+                for bytes in batch {
+                    let prev = headers.last().unwrap_or(tip);
+                    let header = match bincode::deserialize::<Block>(&bytes) {
+                        Ok(h) if h.index == prev.index + 1 && BlockValidator::validate_block_link(prev, &h) => {
+                            if !self.signed_by_validator(&h) {
+                                self.penalize(book, peer, &format!("header {} is not signed by a validator", h.index));
+                                continue 'peers;
+                            }
+                            h
+                        }
+                        Ok(h) if headers.is_empty() && h.index == tip.index + 1 => {
+                            return Ok(HeaderChain::Fork(peer));
+                        }
+                        Ok(h) => {
+                            self.penalize(book, peer, &format!("header {} does not link to {}", h.index, prev.index));
+                            continue 'peers;
+                        }
+                        Err(e) => {
+                            self.penalize(book, peer, &format!("undecodable header: {}", e));
+                            continue 'peers;
+                        }
+                    };
+                    headers.push(header);
+                }
+            }
+        }
+    }
+
+    /// Fetch `peer`'s chain from where it leaves ours, at most
+    /// `max_fork_depth` blocks below `tip`, and offer it to
+    /// `Blockchain::handle_fork`.  Returns whether the chain switched.
+    async fn fork(
+        &self,
+        chain: &RwLock<Blockchain>,
+        book: &Mutex<PeerBook>,
+        peer: usize,
+        tip: &Block,
+    ) -> Result<bool, BlockSyncError> {
+        let read = move || chain.read().map_err(|e| BlockSyncError::Storage(e.to_string()));
+        let floor = tip.index.saturating_sub(self.config.max_fork_depth);
+        let base = read()?.get_block_by_index(floor)
+            .ok_or_else(|| BlockSyncError::Storage(format!("block {} is missing", floor)))?;
+        let last = book.lock().tips[peer].min(tip.index + self.config.max_fork_depth);
+
+        let mut headers: Vec<Block> = Vec::new();
+        while headers.last().unwrap_or(&base).index < last {
+            let from_index = headers.last().unwrap_or(&base).index + 1;
+            let request = SyncRequest { from_index, max_blocks: self.config.header_batch, part: SyncPart::Headers };
+            let batch = match self.peers[peer].request(request).await {
+                Ok(SyncResponse::Headers { headers, .. }) if !headers.is_empty() => headers,
+                Ok(_) => {
+                    self.fail(book, peer, &format!("no fork headers from {}", from_index));
+                    return Ok(false);
+                }
+                Err(e) => {
+                    self.fail(book, peer, &e.to_string());
+                    return Ok(false);
+                }
+            };
+            for bytes in batch {
+                let prev = headers.last().unwrap_or(&base);
+                match bincode::deserialize::<Block>(&bytes) {
+                    Ok(h) if h.index == prev.index + 1 && BlockValidator::validate_block_link(prev, &h) => headers.push(h),
+                    Ok(_) if headers.is_empty() => {
+                        log::warn!(
+                            "[BlockSync] {} is on a chain that parts from ours more than {} blocks down",
+                            self.peers[peer].id(), self.config.max_fork_depth,
+                        );
+                        return Ok(false);
+                    }
+                    _ => {
+                        self.penalize(book, peer, &format!("fork header above {} does not link", prev.index));
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+
+        let (ancestor, branch) = {
+            let ours = read()?;
+            let shared = headers.iter()
+                .take_while(|h| ours.get_block_by_index(h.index).is_some_and(|b| b.compute_hash() == h.compute_hash()))
+                .count();
+            let branch = headers.split_off(shared);
+            let ancestor = headers.pop().unwrap_or(base);
+            (ancestor, branch)
+        };
+        if branch.is_empty() {
+            return Ok(false);
+        }
+        if let Some(h) = branch.iter().find(|h| !self.signed_by_validator(h)) {
+            self.penalize(book, peer, &format!("fork header {} is not signed by a validator", h.index));
+            return Ok(false);
+        }
+
+        let mut fork = VecDeque::from([ancestor]);
+        for batch in branch.chunks(self.config.body_batch.max(1) as usize) {
+            let request = SyncRequest { from_index: batch[0].index, max_blocks: batch.len() as u32, part: SyncPart::Bodies };
+            let bodies = match self.peers[peer].request(request).await {
+                Ok(SyncResponse::Bodies { blocks, .. }) if blocks.len() >= batch.len() => blocks,
+                Ok(_) => {
+                    self.fail(book, peer, &format!("fork blocks from {} unavailable", batch[0].index));
+                    return Ok(false);
+                }
+                Err(e) => {
+                    self.fail(book, peer, &e.to_string());
+                    return Ok(false);
+                }
+            };
+            for (header, bytes) in batch.iter().zip(&bodies) {
+                match bincode::deserialize::<Block>(bytes) {
+                    Ok(block) if block.compute_hash() == header.compute_hash() => fork.push_back(block),
+                    _ => {
+                        self.penalize(book, peer, &format!("fork block {} does not match its header", header.index));
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+
+        let switched = chain.write()
+            .map_err(|e| BlockSyncError::Storage(e.to_string()))?
+            .handle_fork(fork);
+        match switched {
+            Ok(switched) => Ok(switched),
+            Err(e) => {
+                self.penalize(book, peer, &e);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Fetch and apply body batches off `queue` until it is empty.
+    async fn worker(
+        &self,
+        chain: &RwLock<Blockchain>,
+        headers: &[Block],
+        queue: &Mutex<Vec<(u64, u32)>>,
+        applier: &Mutex<Applier>,
+        book: &Mutex<PeerBook>,
+        cursor: &AtomicUsize,
+    ) -> Result<(), BlockSyncError> {
+        let base = headers.first().map_or(0, |h| h.index);
+        loop {
+            let Some((from_index, count)) = queue.lock().pop() else { return Ok(()) };
+            let last = from_index + count as u64 - 1;
+            let peer = self.next_peer(book, cursor, last).ok_or(BlockSyncError::NoPeers(from_index))?;
+            let request = SyncRequest { from_index, max_blocks: count, part: SyncPart::Bodies };
+            let blocks = match self.peers[peer].request(request).await {
+                Ok(SyncResponse::Bodies { blocks, .. }) => blocks,
+                Ok(_) => {
+                    self.fail(book, peer, &format!("blocks from {} unavailable", from_index));
+                    queue.lock().push((from_index, count));
+                    continue;
+                }
+                Err(e) => {
+                    self.fail(book, peer, &e.to_string());
+                    queue.lock().push((from_index, count));
+                    continue;
+                }
+            };
+
+            let mut verified = Vec::with_capacity(blocks.len());
+            for (offset, bytes) in blocks.iter().take(count as usize).enumerate() {
+                let header = &headers[(from_index - base) as usize + offset];
+                match bincode::deserialize::<Block>(bytes) {
+                    Ok(block) if block.compute_hash() == header.compute_hash() => verified.push(block),
+                    _ => {
+                        self.penalize(book, peer, &format!("block {} does not match its header", header.index));
+                        break;
+                    }
+                }
+            }
+            // Whatever was not served, or not served right, goes back.
+            let got = verified.len() as u32;
+            if got < count {
+                queue.lock().push((from_index + got as u64, count - got));
+            }
+            self.apply(chain, applier, verified)?;
+        }
+    }
+
+    /// Queue `blocks` and add every block that now extends the tip.
+    fn apply(&self, chain: &RwLock<Blockchain>, applier: &Mutex<Applier>, blocks: Vec<Block>) -> Result<(), BlockSyncError> {
+        let mut applier = applier.lock();
+        for block in blocks {
+            applier.pending.insert(block.index, block);
+        }
+        let mut applied = false;
+        loop {
+            let next = applier.next;
+            let Some(block) = applier.pending.remove(&next) else { break };
+            let key = (self.validator_key)(&block);
+            let accepted = chain.write()
+                .map_err(|e| BlockSyncError::Storage(e.to_string()))?
+                .add_block(block, &key);
+            if !accepted {
+                return Err(BlockSyncError::Rejected(next));
+            }
+            applier.next += 1;
+            self.progress.height.store(next, Ordering::Relaxed);
+            applied = true;
+        }
+        if applied {
+            self.export_telemetry();
+        }
+        Ok(())
+    }
+
+    /// Whether `header` carries a valid signature by a key the signer check
+    /// accepts; always true without a check.
+    fn signed_by_validator(&self, header: &Block) -> bool {
+        let Some(check) = &self.signer_check else { return true };
+        header.verify_header_signature()
+            && header.signer_public_key().is_some_and(|pk| check(header, pk))
+    }
+
+    /// Round-robin over the peers still in use whose tip reaches `height`.
+    fn next_peer(&self, book: &Mutex<PeerBook>, cursor: &AtomicUsize, height: u64) -> Option<usize> {
+        let book = book.lock();
+        let n = self.peers.len();
+        (0..n)
+            .map(|_| cursor.fetch_add(1, Ordering::Relaxed) % n)
+            .find(|i| !book.dropped[*i] && book.tips[*i] >= height)
+    }
+
+    fn note_best(&self, book: &Mutex<PeerBook>) {
+        let best = book.lock().tips.iter().copied().max().unwrap_or(0);
+        self.progress.best_height.fetch_max(best, Ordering::Relaxed);
+        self.export_telemetry();
+    }
+
+    /// Drop a peer that served bad data.
+    fn penalize(&self, book: &Mutex<PeerBook>, peer: usize, reason: &str) {
+        let mut book = book.lock();
+        if !std::mem::replace(&mut book.dropped[peer], true) {
+            let id = self.peers[peer].id();
+            log::warn!("[BlockSync] Penalizing {}: {}", id, reason);
+            book.penalized.push(id);
+            self.progress.penalized.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Count a failed request; drop the peer after too many.
+    fn fail(&self, book: &Mutex<PeerBook>, peer: usize, reason: &str) {
+        let mut book = book.lock();
+        book.failures[peer] += 1;
+        log::warn!("[BlockSync] {}: {}", self.peers[peer].id(), reason);
+        if book.failures[peer] >= self.config.max_peer_failures {
+            book.dropped[peer] = true;
+        }
+    }
+
+    fn export_telemetry(&self) {
+        if let Some(t) = &self.telemetry {
+            let p = &self.progress;
+            t.export(p.height(), p.best_height(), p.peers_penalized());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::BlockchainState;
+    use crate::transaction_pool::TransactionPool;
+    use async_trait::async_trait;
+    use bleep_p2p::block_sync::{LocalSyncPeer, TcpSyncPeer};
+    use bleep_p2p::error::P2PResult;
+    use bleep_p2p::p2p_node::{P2PNode, P2PNodeConfig};
+
+    fn chain_of(height: u64) -> Blockchain {
+        let mut chain = Blockchain::new(Block::genesis(), BlockchainState::default(), TransactionPool::new(1), None).unwrap();
+        extend(&mut chain, height);
+        chain
+    }
+
+    fn extend(chain: &mut Blockchain, height: u64) {
+        while chain.height() < height {
+            let tip = chain.latest_block().unwrap();
+            let block = Block::new(tip.index + 1, vec![], tip.compute_hash());
+            assert!(chain.add_block(block, &[]));
+        }
+    }
+
+    /// Serves blocks whose timestamps were tampered with after hashing.
+    struct Forger(Arc<dyn BlockSource>);
+
+    #[async_trait]
+    impl SyncPeer for Forger {
+        fn id(&self) -> String {
+            "forger".into()
+        }
+
+        async fn request(&self, request: SyncRequest) -> P2PResult<SyncResponse> {
+            Ok(match bleep_p2p::block_sync::serve(self.0.as_ref(), &request) {
+                SyncResponse::Bodies { tip, blocks } => SyncResponse::Bodies {
+                    tip,
+                    blocks: blocks.iter().map(|bytes| {
+                        let mut block: Block = bincode::deserialize(bytes).unwrap();
+                        block.timestamp += 1;
+                        bincode::serialize(&block).unwrap()
+                    }).collect(),
+                },
+                other => other,
+            })
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn node_500_blocks_behind_converges_with_its_peers() {
+        let node_a = Arc::new(RwLock::new(chain_of(520)));
+        let source: Arc<dyn BlockSource> = Arc::new(ChainSource::new(Arc::clone(&node_a)));
+        let (p2p_a, handle_a) = P2PNode::start(P2PNodeConfig {
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        p2p_a.message_protocol.attach_block_source(Arc::clone(&source));
+
+        // B shares A's first 20 blocks
+        let mut b = Blockchain::new(Block::genesis(), BlockchainState::default(), TransactionPool::new(1), None).unwrap();
+        for index in 1..=20 {
+            let block = node_a.read().unwrap().get_block_by_index(index).unwrap();
+            assert!(b.add_block(block, &[]));
+        }
+        let node_b = RwLock::new(b);
+
+        let peers: Vec<Arc<dyn SyncPeer>> = vec![
+            Arc::new(TcpSyncPeer { addr: handle_a.local_addr() }),
+            Arc::new(LocalSyncPeer { id: "a-local".into(), source: Arc::clone(&source) }),
+            Arc::new(Forger(Arc::clone(&source))),
+        ];
+        let syncer = ChainSyncer::new(peers)
+            .with_config(BlockSyncConfig { body_batch: 64, ..Default::default() });
+        let outcome = syncer.run(&node_b).await.unwrap();
+
+        assert_eq!((outcome.from_height, outcome.height, outcome.applied), (20, 520, 500));
+        assert_eq!(outcome.penalized, vec!["forger".to_string()]);
+        let progress = syncer.progress();
+        assert_eq!((progress.height(), progress.best_height()), (520, 520));
+        assert!(progress.is_complete());
+        let (a, b) = (node_a.read().unwrap(), node_b.read().unwrap());
+        assert_eq!(b.latest_block().unwrap().compute_hash(), a.latest_block().unwrap().compute_hash());
+        assert!(b.verify_chain(&[]));
+
+        // Caught up: a second run has nothing to do
+        drop((a, b));
+        let again = ChainSyncer::new(vec![Arc::new(LocalSyncPeer { id: "a-local".into(), source })]);
+        assert_eq!(again.run(&node_b).await.unwrap().applied, 0);
+        handle_a.shutdown().await;
+    }
+
+    fn signed_chain_of(height: u64, sk: &[u8], pk: &[u8]) -> Blockchain {
+        let mut chain = Blockchain::new(Block::genesis(), BlockchainState::default(), TransactionPool::new(1), None).unwrap();
+        while chain.height() < height {
+            let tip = chain.latest_block().unwrap();
+            let mut block = Block::new(tip.index + 1, vec![], tip.compute_hash());
+            block.sign_block_with_pk(sk, pk).unwrap();
+            assert!(chain.add_block(block, pk));
+        }
+        chain
+    }
+
+    #[tokio::test]
+    async fn blocks_signed_by_another_key_than_the_validators_are_rejected() {
+        use bleep_crypto::tx_signer::generate_tx_keypair;
+
+        let ((validator_pk, validator_sk), (mallory_pk, mallory_sk)) = (generate_tx_keypair(), generate_tx_keypair());
+        let forged = Arc::new(RwLock::new(signed_chain_of(2, &mallory_sk, &mallory_pk)));
+        let honest = Arc::new(RwLock::new(signed_chain_of(2, &validator_sk, &validator_pk)));
+        let key = validator_pk.clone();
+        let syncer = |source: Arc<RwLock<Blockchain>>| {
+            let key = key.clone();
+            ChainSyncer::new(vec![Arc::new(LocalSyncPeer { id: "peer".into(), source: Arc::new(ChainSource::new(source)) })])
+                .with_validator_key(Arc::new(move |_block: &Block| key.clone()))
+        };
+
+        let node = RwLock::new(chain_of(0));
+        assert!(matches!(syncer(forged).run(&node).await, Err(BlockSyncError::Rejected(1))));
+        assert_eq!(node.read().unwrap().height(), 0);
+
+        assert_eq!(syncer(honest).run(&node).await.unwrap().height, 2);
+        assert!(node.read().unwrap().verify_chain(&validator_pk));
+    }
+
+    #[tokio::test]
+    async fn headers_signed_outside_the_validator_set_are_refused() {
+        use bleep_crypto::tx_signer::generate_tx_keypair;
+
+        let ((validator_pk, validator_sk), (mallory_pk, mallory_sk)) = (generate_tx_keypair(), generate_tx_keypair());
+        let honest = Arc::new(RwLock::new(signed_chain_of(3, &validator_sk, &validator_pk)));
+        let forged = Arc::new(RwLock::new(signed_chain_of(5, &mallory_sk, &mallory_pk)));
+        let peers: Vec<Arc<dyn SyncPeer>> = vec![
+            Arc::new(LocalSyncPeer { id: "mallory".into(), source: Arc::new(ChainSource::new(forged)) }),
+            Arc::new(LocalSyncPeer { id: "honest".into(), source: Arc::new(ChainSource::new(Arc::clone(&honest))) }),
+        ];
+        let node = RwLock::new(chain_of(0));
+        let syncer = ChainSyncer::new(peers)
+            .with_signer_check(Arc::new(move |_header: &Block, pk: &[u8]| pk == validator_pk.as_slice()));
+        let outcome = syncer.run(&node).await.unwrap();
+
+        // Mallory reports the best tip, so its headers are tried first
+        assert_eq!(outcome.penalized, vec!["mallory".to_string()]);
+        assert_eq!(outcome.height, 3);
+        assert_eq!(
+            node.read().unwrap().latest_block().unwrap().compute_hash(),
+            honest.read().unwrap().latest_block().unwrap().compute_hash(),
+        );
+    }
+
+    /// Extend `chain` to `height` with blocks `pk` signs, their timestamps
+    /// moved by `skew` so branches built apart differ.
+    fn extend_signed(chain: &mut Blockchain, height: u64, (pk, sk): (&[u8], &[u8]), skew: u64) {
+        while chain.height() < height {
+            let tip = chain.latest_block().unwrap();
+            let mut block = Block::new(tip.index + 1, vec![], tip.compute_hash());
+            block.timestamp += skew;
+            block.sign_block_with_pk(sk, pk).unwrap();
+            assert!(chain.add_block(block, pk));
+        }
+    }
+
+    /// Stake 10 for one validator, 1 for any other.
+    struct OneHeavy(Vec<u8>);
+
+    impl crate::fork_choice::StakeWeights for OneHeavy {
+        fn weight(&self, _epoch_id: u64, signer: &[u8]) -> Option<u64> {
+            Some(if signer == self.0 { 10 } else { 1 })
+        }
+    }
+
+    #[tokio::test]
+    async fn a_heavier_competing_chain_replaces_ours_and_a_lighter_one_does_not() {
+        use bleep_crypto::tx_signer::generate_tx_keypair;
+
+        let ((light_pk, light_sk), (heavy_pk, heavy_sk)) = (generate_tx_keypair(), generate_tx_keypair());
+        let light = (light_pk.as_slice(), light_sk.as_slice());
+        let mut ours = chain_of(0).with_stake_weights(Arc::new(OneHeavy(heavy_pk.clone())));
+        extend_signed(&mut ours, 6, light, 0);
+
+        // Parts from ours above block 3 and is signed by the heavy validator
+        let mut heavier = chain_of(0);
+        for index in 1..=3 {
+            assert!(heavier.add_block(ours.get_block_by_index(index).unwrap(), &light_pk));
+        }
+        extend_signed(&mut heavier, 8, (&heavy_pk, &heavy_sk), 1);
+        let heavier = Arc::new(RwLock::new(heavier));
+        let node = RwLock::new(ours);
+        let syncer = ChainSyncer::new(vec![
+            Arc::new(LocalSyncPeer { id: "heavy".into(), source: Arc::new(ChainSource::new(Arc::clone(&heavier))) }),
+        ]);
+        let outcome = syncer.run(&node).await.unwrap();
+
+        assert_eq!((outcome.reorgs, outcome.height), (1, 8));
+        assert!(outcome.penalized.is_empty());
+        assert_eq!(
+            node.read().unwrap().latest_block().unwrap().compute_hash(),
+            heavier.read().unwrap().latest_block().unwrap().compute_hash(),
+        );
+
+        // Longer, but all of it from the light validator
+        let mut longer = chain_of(0);
+        extend_signed(&mut longer, 12, light, 2);
+        let syncer = ChainSyncer::new(vec![
+            Arc::new(LocalSyncPeer { id: "light".into(), source: Arc::new(ChainSource::new(Arc::new(RwLock::new(longer)))) }),
+        ]);
+        let outcome = syncer.run(&node).await.unwrap();
+
+        assert_eq!((outcome.reorgs, outcome.height, outcome.applied), (0, 8, 0));
+        assert!(outcome.penalized.is_empty());
+        assert_eq!(
+            node.read().unwrap().latest_block().unwrap().compute_hash(),
+            heavier.read().unwrap().latest_block().unwrap().compute_hash(),
+        );
+    }
+
+    /// Reports a higher tip than it has and serves headers with a gap.
+    struct Skipper(Arc<dyn BlockSource>);
+
+    #[async_trait]
+    impl SyncPeer for Skipper {
+        fn id(&self) -> String {
+            "skipper".into()
+        }
+
+        async fn request(&self, request: SyncRequest) -> P2PResult<SyncResponse> {
+            Ok(match bleep_p2p::block_sync::serve(self.0.as_ref(), &request) {
+                SyncResponse::Headers { headers, .. } => {
+                    SyncResponse::Headers { tip: 50, headers: headers.into_iter().skip(1).collect() }
+                }
+                other => other,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn headers_that_do_not_link_are_refetched_from_another_peer() {
+        let served = Arc::new(RwLock::new(chain_of(40)));
+        let source: Arc<dyn BlockSource> = Arc::new(ChainSource::new(Arc::clone(&served)));
+        let node = RwLock::new(chain_of(0));
+        let peers: Vec<Arc<dyn SyncPeer>> = vec![
+            Arc::new(Skipper(Arc::clone(&source))),
+            Arc::new(LocalSyncPeer { id: "honest".into(), source }),
+        ];
+        let syncer = ChainSyncer::new(peers);
+        let outcome = syncer.run(&node).await.unwrap();
+
+        // The skipper claims the best tip, so its headers are tried first
+        assert_eq!(outcome.penalized, vec!["skipper".to_string()]);
+        assert_eq!(outcome.height, 40);
+        assert_eq!(syncer.progress().best_height(), 50);
+        assert_eq!(
+            node.read().unwrap().latest_block().unwrap().compute_hash(),
+            served.read().unwrap().latest_block().unwrap().compute_hash(),
+        );
+    }
+}
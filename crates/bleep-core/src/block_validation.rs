@@ -1,4 +1,48 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 use crate::block::Block;
+use crate::transaction::ZKTransaction;
+use crate::transaction_pool::{signer_public_key, KeyCheck};
+use bleep_crypto::key_change::key_hash;
+use bleep_crypto::zkp_verification::{
+    decode_groth16_proof, decode_public_input, BLEEPError, BLEEPZKPModule, Fr, Proof,
+};
+
+/// How far (seconds) a block timestamp may run ahead of the local clock.
+pub const MAX_FUTURE_DRIFT_SECS: u64 = 15;
+
+/// Next difficulty from the current one and the timestamps of the retarget
+/// window, oldest first.
+pub type PowRetarget = Arc<dyn Fn(u64, &[u64]) -> u64 + Send + Sync>;
+
+/// The difficulty a PoW block must carry, derived from the chain below it.
+///
+/// It is the difficulty of the last mined block, or `initial` if there is
+/// none; when that block closed a retarget window (its index a multiple of
+/// `window`), `retarget` is applied over the window's timestamps first.
+#[derive(Clone)]
+pub struct PowSchedule {
+    pub initial:  u64,
+    pub window:   u64,
+    pub retarget: PowRetarget,
+}
+
+impl PowSchedule {
+    /// Expected difficulty at `height`, reading ancestors through `block_at`.
+    pub fn expected<'a>(&self, height: u64, block_at: impl Fn(u64) -> Option<&'a Block>) -> u64 {
+        let window = self.window.max(2);
+        let last = (0..height).rev().map_while(&block_at).find(|b| b.is_pow());
+        let Some(last) = last else { return self.initial };
+        if last.index % window != 0 {
+            return last.pow_difficulty;
+        }
+        let timestamps: Vec<u64> = (last.index.saturating_sub(window)..=last.index)
+            .filter_map(|h| block_at(h).map(|b| b.timestamp))
+            .collect();
+        (self.retarget)(last.pow_difficulty, &timestamps)
+    }
+}
 
 pub struct BlockValidator;
 
@@ -44,6 +88,106 @@ impl BlockValidator {
         true
     }
 
+    /// **Batch-verify the block's per-transaction Groth16 proofs**
+    ///
+    /// Collects every entry of `block.tx_proofs` and checks them with one
+    /// `BLEEPZKPModule::verify_batch` call.  Blocks without proofs pass.
+    /// Undecodable proofs, proofs pointing past the transaction list and
+    /// invalid proofs all reject the block; invalid ones are logged by
+    /// transaction index.
+    pub fn validate_tx_proofs(block: &Block, zkp: &BLEEPZKPModule) -> bool {
+        if block.tx_proofs.is_empty() {
+            return true;
+        }
+        let batch = match Self::collect_tx_proofs(block) {
+            Ok(batch) => batch,
+            Err(e) => {
+                log::error!("Block {} carries a malformed tx proof: {}", block.index, e);
+                return false;
+            }
+        };
+        match zkp.verify_batch_detailed(&batch) {
+            Ok(result) if result.all_valid => true,
+            Ok(result) => {
+                let txs: Vec<u32> = result.invalid.iter()
+                    .map(|&i| block.tx_proofs[i].tx_index)
+                    .collect();
+                log::error!("Block {} has invalid ZK proofs for txs {:?}", block.index, txs);
+                false
+            }
+            Err(e) => {
+                log::error!("Block {} tx proof verification error: {}", block.index, e);
+                false
+            }
+        }
+    }
+
+    fn collect_tx_proofs(block: &Block) -> Result<Vec<(Proof, Vec<Fr>)>, BLEEPError> {
+        block.tx_proofs.iter().map(|p| {
+            if p.tx_index as usize >= block.transactions.len() {
+                return Err(BLEEPError::Generic(format!("tx_index {} out of range", p.tx_index)));
+            }
+            let proof = decode_groth16_proof(&p.proof)?;
+            let inputs = p.public_inputs.iter()
+                .map(|x| decode_public_input(x))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok((proof, inputs))
+        }).collect()
+    }
+
+    /// **Transaction signatures, uniqueness and Merkle root**
+    ///
+    /// SAFETY: Rejects the block if any transaction's wire signature fails
+    /// to verify (the same check mempool admission makes), if two
+    /// transactions share an id, if a sender's nonces within the block do not
+    /// run consecutively upward, or if `merkle_root` is not the root of the
+    /// transaction list — otherwise a proposer could swap transactions under
+    /// a signed header.  Whether the first nonce per sender is the account's
+    /// next one is checked when the block is applied.
+    pub fn validate_transactions(block: &Block) -> bool {
+        let mut seen = HashSet::new();
+        let mut last_nonce: HashMap<&str, u64> = HashMap::new();
+        for (i, tx) in block.transactions.iter().enumerate() {
+            if tx.chain_id != block.chain_id {
+                log::error!(
+                    "Block {} tx {} is signed for chain {}, not {}",
+                    block.index, i, tx.chain_id, block.chain_id
+                );
+                return false;
+            }
+            if !tx.signature_valid() {
+                log::error!("Block {} tx {} from {} has an invalid signature", block.index, i, tx.sender);
+                return false;
+            }
+            if !seen.insert(tx.id()) {
+                log::error!("Block {} tx {} duplicates id {}", block.index, i, tx.id());
+                return false;
+            }
+            if let Some(prev) = last_nonce.insert(&tx.sender, tx.nonce) {
+                if prev.checked_add(1) != Some(tx.nonce) {
+                    log::error!(
+                        "Block {} tx {} from {} has nonce {} after {}",
+                        block.index, i, tx.sender, tx.nonce, prev
+                    );
+                    return false;
+                }
+            }
+        }
+
+        let expected = Block::calculate_merkle_root(&block.transactions);
+        if block.merkle_root != expected {
+            log::error!(
+                "Block {} merkle root mismatch! Expected {}, got {}",
+                block.index,
+                expected,
+                block.merkle_root
+            );
+            return false;
+        }
+
+        true
+    }
+
     /// **AI-based anomaly detection for malicious blocks**
     /// 
     /// SAFETY: Returns FALSE for any block that appears malicious.
@@ -95,9 +239,87 @@ impl BlockValidator {
             return false;
         }
 
+        if current_block.chain_id != prev_block.chain_id {
+            log::error!(
+                "Block {} is for chain {}, its parent for chain {}",
+                current_block.index,
+                current_block.chain_id,
+                prev_block.chain_id
+            );
+            return false;
+        }
+
         true
     }
 
+    /// **Timestamp monotonicity and future-drift bound**
+    ///
+    /// SAFETY: A block may never be earlier than its parent, and may not run
+    /// more than `MAX_FUTURE_DRIFT_SECS` ahead of `now` (unix seconds).
+    /// Without these, a proposer could skew PoW retargeting and slot
+    /// assignment by lying about time.
+    pub fn validate_timestamp(prev_block: &Block, block: &Block, now: u64) -> bool {
+        if block.timestamp < prev_block.timestamp {
+            log::error!(
+                "Block {} timestamp {} is before parent timestamp {}",
+                block.index,
+                block.timestamp,
+                prev_block.timestamp
+            );
+            return false;
+        }
+
+        if block.timestamp > now.saturating_add(MAX_FUTURE_DRIFT_SECS) {
+            log::error!(
+                "Block {} timestamp {} is more than {}s ahead of local time {}",
+                block.index,
+                block.timestamp,
+                MAX_FUTURE_DRIFT_SECS,
+                now
+            );
+            return false;
+        }
+
+        true
+    }
+
+    /// **Proof-of-work difficulty**
+    ///
+    /// SAFETY: A PoW block must carry exactly the difficulty the chain
+    /// expects at its height (`expected`, from the local retarget) and a
+    /// nonce that meets it.  Otherwise a miner could pick an easy difficulty
+    /// and outpace honest miners.
+    pub fn validate_pow(block: &Block, expected: u64) -> bool {
+        if block.pow_difficulty != expected {
+            log::error!(
+                "Block {} mined at difficulty {}, expected {}",
+                block.index,
+                block.pow_difficulty,
+                expected
+            );
+            return false;
+        }
+
+        if !block.meets_pow_difficulty() {
+            log::error!("Block {} nonce {} does not meet its difficulty", block.index, block.pow_nonce);
+            return false;
+        }
+
+        true
+    }
+
+    /// **Proof-of-work for any block that claims it**
+    ///
+    /// Non-PoW blocks pass.  A PoW block is held to `expected` when the
+    /// chain has a `PowSchedule`, and otherwise only to its own difficulty.
+    pub fn validate_pow_claim(block: &Block, expected: Option<u64>) -> bool {
+        match expected {
+            _ if !block.is_pow() => true,
+            Some(expected) => Self::validate_pow(block, expected),
+            None => block.meets_pow_difficulty(),
+        }
+    }
+
     /// **Network-wide peer consensus verification**
     /// 
     /// SAFETY: Rejects blocks that don't match network consensus.
@@ -131,33 +353,79 @@ impl BlockValidator {
         true
     }
 
+    /// **Transaction signers**
+    ///
+    /// SAFETY: Rejects the block if any transaction is signed by a key that
+    /// `check` does not authorize for its sender — the binding mempool
+    /// admission enforces with `TransactionPool::set_key_check`.  Without it
+    /// a proposer could include a transfer "from" any account signed with
+    /// its own key.  Signatures themselves are checked by
+    /// `validate_transactions`.
+    pub fn validate_transaction_signers(block: &Block, check: &KeyCheck) -> bool {
+        for (i, tx) in block.transactions.iter().enumerate() {
+            let pool_tx = ZKTransaction::from(tx);
+            let authorized = signer_public_key(&pool_tx)
+                .is_some_and(|pk| check(&tx.sender, &key_hash(pk)));
+            if !authorized {
+                log::error!(
+                    "Block {} tx {} is signed by a key not authorized for {}",
+                    block.index, i, tx.sender
+                );
+                return false;
+            }
+        }
+        true
+    }
+
     /// **Full block validation pipeline**
     /// 
     /// SAFETY: Executes all validation steps in strict order:
     /// 1. Signature validation
-    /// 2. Link validation
-    /// 3. Network consensus checks
-    /// 4. AI anomaly detection
-    pub fn validate_full_block(prev_block: &Block, block: &Block, public_key: &[u8]) -> bool {
+    /// 2. Transaction validation
+    /// 3. Link validation
+    /// 4. Timestamp validation
+    /// 5. Proof of work, if claimed
+    /// 6. Network consensus checks
+    /// 7. AI anomaly detection
+    pub fn validate_full_block(prev_block: &Block, block: &Block, public_key: &[u8], expected_pow: Option<u64>) -> bool {
         // Step 1: Verify block signature
         if !Self::validate_block(block, public_key) {
             log::error!("Block {} failed signature validation", block.index);
             return false;
         }
         
-        // Step 2: Verify block link to previous block
+        // Step 2: Verify transaction signatures, ids and merkle root
+        if !Self::validate_transactions(block) {
+            log::error!("Block {} failed transaction validation", block.index);
+            return false;
+        }
+
+        // Step 3: Verify block link to previous block
         if !Self::validate_block_link(prev_block, block) {
             log::error!("Block {} failed link validation to previous block", block.index);
             return false;
         }
         
-        // Step 3: Network consensus validation
+        // Step 4: Timestamp monotonicity and drift
+        let now = chrono::Utc::now().timestamp() as u64;
+        if !Self::validate_timestamp(prev_block, block, now) {
+            log::error!("Block {} failed timestamp validation", block.index);
+            return false;
+        }
+
+        // Step 5: Proof of work at the chain's difficulty
+        if !Self::validate_pow_claim(block, expected_pow) {
+            log::error!("Block {} failed proof-of-work validation", block.index);
+            return false;
+        }
+
+        // Step 6: Network consensus validation
         if !Self::network_validate(block) {
             log::error!("Block {} failed network validation", block.index);
             return false;
         }
         
-        // Step 4: AI anomaly detection
+        // Step 7: AI anomaly detection
         if !Self::ai_validate(block) {
             log::error!("Block {} failed AI anomaly detection", block.index);
             return false;
@@ -168,3 +436,48 @@ impl BlockValidator {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{pow_hash, pow_target_met};
+
+    #[test]
+    fn timestamp_before_parent_rejected() {
+        let mut parent = Block::new(1, vec![], "0".to_string());
+        parent.timestamp = 1_000;
+        let mut block = Block::new(2, vec![], parent.compute_hash());
+
+        block.timestamp = 999;
+        assert!(!BlockValidator::validate_timestamp(&parent, &block, 1_000));
+        block.timestamp = 1_000;
+        assert!(BlockValidator::validate_timestamp(&parent, &block, 1_000));
+    }
+
+    #[test]
+    fn pow_block_must_match_expected_difficulty() {
+        let mut block = Block::new(2, vec![], "0".to_string());
+        block.pow_difficulty = 64;
+        let commitment = block.pow_commitment();
+        let solves = |n: u64| pow_target_met(&pow_hash(&commitment, n), 64);
+
+        block.pow_nonce = (0..).find(|n| solves(*n)).unwrap();
+        assert!(BlockValidator::validate_pow(&block, 64));
+        assert!(!BlockValidator::validate_pow(&block, 128), "easier than the chain expects");
+
+        block.pow_nonce = (0..).find(|n| !solves(*n)).unwrap();
+        assert!(!BlockValidator::validate_pow(&block, 64), "nonce does not do the work");
+    }
+
+    #[test]
+    fn timestamp_too_far_in_future_rejected() {
+        let mut parent = Block::new(1, vec![], "0".to_string());
+        parent.timestamp = 1_000;
+        let mut block = Block::new(2, vec![], parent.compute_hash());
+
+        block.timestamp = 1_000 + MAX_FUTURE_DRIFT_SECS;
+        assert!(BlockValidator::validate_timestamp(&parent, &block, 1_000));
+        block.timestamp += 1;
+        assert!(!BlockValidator::validate_timestamp(&parent, &block, 1_000));
+    }
+}
@@ -3,16 +3,20 @@
 //! Wires the full execution path:
 //!   add_block()  →  validate  →  apply state  →  drain tx pool  →  log
 //!
-//! `BlockchainState` (in-memory) tracks balances and is updated atomically
-//! with each accepted block.  Sprint 3 replaces the HashMap with a RocksDB
+//! `BlockchainState` (in-memory) tracks balances and account nonces and is
+//! updated atomically with each accepted block.  A transaction applies only
+//! at its sender's next nonce, so a confirmed transaction cannot be replayed.  Sprint 3 replaces the HashMap with a RocksDB
 //! sparse Merkle trie via bleep-state::state_storage.
 
-use std::collections::{VecDeque, HashMap};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::{Arc, RwLock};
 
 use crate::block::{Block, Transaction};
-use crate::block_validation::BlockValidator;
-use crate::transaction_pool::TransactionPool;
+use crate::block_validation::{BlockValidator, PowSchedule};
+use crate::chain_store::BlockStore;
+use crate::fork_choice::{branch_score, StakeWeights};
+use crate::transaction::ZKTransaction;
+use crate::transaction_pool::{carries_no_amount, KeyCheck, TransactionPool};
 
 // ─── In-memory account state ─────────────────────────────────────────────────
 
@@ -23,6 +27,8 @@ use crate::transaction_pool::TransactionPool;
 #[derive(Default, Clone)]
 pub struct BlockchainState {
     pub balances: HashMap<String, u64>,
+    /// Next expected nonce per sender; absent means 0.
+    pub nonces: HashMap<String, u64>,
 }
 
 impl BlockchainState {
@@ -51,6 +57,15 @@ impl BlockchainState {
         Ok(())
     }
 
+    /// Nonce the next transaction from `address` must carry.
+    pub fn next_nonce(&self, address: &str) -> u64 {
+        self.nonces.get(address).copied().unwrap_or(0)
+    }
+
+    pub fn set_nonce(&mut self, address: &str, nonce: u64) {
+        self.nonces.insert(address.to_string(), nonce);
+    }
+
     /// Query balance without mutating state.
     pub fn balance_of(&self, address: &str) -> u64 {
         self.balances.get(address).copied().unwrap_or(1_000_000_000) // 10 BLEEP default for testnet
@@ -64,11 +79,19 @@ impl BlockchainState {
         if tx.sender == tx.receiver {
             return Err(format!("Self-transfer rejected for {}", tx.sender));
         }
-        if tx.amount == 0 {
+        if tx.amount == 0 && !carries_no_amount(&tx.receiver) {
             return Err("Zero-amount transaction rejected".to_string());
         }
+        let expected = self.next_nonce(&tx.sender);
+        if tx.nonce != expected {
+            return Err(format!(
+                "Nonce {} from {} rejected: expected {}",
+                tx.nonce, tx.sender, expected
+            ));
+        }
         self.debit(&tx.sender, tx.amount)?;
         self.credit(&tx.receiver, tx.amount);
+        self.set_nonce(&tx.sender, expected + 1);
         Ok(())
     }
 
@@ -77,15 +100,15 @@ impl BlockchainState {
     /// On first failure the state is NOT partially updated — we roll back
     /// all successful transactions in the failed block.
     pub fn apply_block(&mut self, block: &Block) -> Result<(), String> {
-        // Snapshot balances so we can roll back on failure
-        let snapshot = self.balances.clone();
+        // Snapshot balances and nonces so we can roll back on failure
+        let snapshot = (self.balances.clone(), self.nonces.clone());
         for tx in &block.transactions {
             if let Err(e) = self.apply_transaction(tx) {
                 log::warn!(
                     "Block {} tx from={} to={} amount={} rejected: {} — rolling back block",
                     block.index, tx.sender, tx.receiver, tx.amount, e
                 );
-                self.balances = snapshot;
+                (self.balances, self.nonces) = snapshot;
                 return Err(e);
             }
         }
@@ -100,12 +123,13 @@ impl BlockchainState {
 
     /// Revert a previously-applied block.  Used by `Blockchain::rollback()`.
     pub fn revert_block(&mut self, block: &Block) {
-        for tx in &block.transactions {
-            // Reverse: credit sender, debit receiver
+        for tx in block.transactions.iter().rev() {
+            // Reverse: credit sender, debit receiver, rewind sender nonce
             self.credit(&tx.sender, tx.amount);
             if let Some(b) = self.balances.get_mut(&tx.receiver) {
                 *b = b.saturating_sub(tx.amount);
             }
+            self.set_nonce(&tx.sender, tx.nonce);
         }
     }
 }
@@ -116,22 +140,108 @@ pub struct Blockchain {
     pub chain: VecDeque<Block>,
     pub state: Arc<RwLock<BlockchainState>>,
     pub transaction_pool: Arc<RwLock<Arc<TransactionPool>>>,
+    /// Where accepted blocks are persisted; `None` keeps the chain in memory.
+    store: Option<Arc<dyn BlockStore>>,
+    /// Signer stakes for fork choice; `None` weighs every signed block 1.
+    stakes: Option<Arc<dyn StakeWeights>>,
+    /// Which keys may sign for an account; `None` checks signatures only.
+    key_check: Option<KeyCheck>,
+    /// Difficulty PoW blocks must be mined at; `None` holds each to its own.
+    pow: Option<PowSchedule>,
 }
 
 impl Blockchain {
-    /// Initialise the chain with a genesis block.
+    /// Initialise the chain with a genesis block; accepted blocks go to `store`.
+    ///
+    /// `state` is the state at genesis.  If `store` already holds blocks
+    /// above genesis the chain resumes from them: a stored block at the
+    /// genesis index must be this genesis, every later block must link to
+    /// its parent and carry a valid header signature, and each is applied
+    /// to `state` in turn so balances and nonces come back as they were.
     pub fn new(
         genesis_block: Block,
         state: BlockchainState,
         tx_pool: Arc<TransactionPool>,
-    ) -> Self {
-        let mut chain = VecDeque::new();
-        chain.push_back(genesis_block);
-        Self {
+        store: Option<Arc<dyn BlockStore>>,
+    ) -> Result<Self, String> {
+        let mut chain = VecDeque::from([genesis_block]);
+        let mut state = state;
+        if let Some(store) = &store {
+            Self::resume(store.as_ref(), &mut chain, &mut state)?;
+        }
+        Ok(Self {
             chain,
             state: Arc::new(RwLock::new(state)),
             transaction_pool: Arc::new(RwLock::new(tx_pool)),
+            store,
+            stakes: None,
+            key_check: None,
+            pow: None,
+        })
+    }
+
+    /// Replay the blocks `store` holds above `chain`'s genesis onto it.
+    fn resume(store: &dyn BlockStore, chain: &mut VecDeque<Block>, state: &mut BlockchainState) -> Result<(), String> {
+        let genesis = chain.front().cloned().ok_or("chain is empty")?;
+        let Some(tip) = store.latest_index()? else { return Ok(()) };
+        if let Some(stored) = store.get_block_by_index(genesis.index)? {
+            if stored.compute_hash() != genesis.compute_hash() {
+                return Err(format!(
+                    "block store holds a different chain: block {} is {}, expected {}",
+                    genesis.index, stored.compute_hash(), genesis.compute_hash(),
+                ));
+            }
         }
+        for index in genesis.index + 1..=tip {
+            let block = store.get_block_by_index(index)?
+                .ok_or_else(|| format!("block store is missing block {} below tip {}", index, tip))?;
+            let prev = chain.back().expect("chain starts at genesis");
+            if !BlockValidator::validate_block_link(prev, &block) || !block.verify_header_signature() {
+                return Err(format!("stored block {} failed verification", index));
+            }
+            state.apply_block(&block).map_err(|e| format!("stored block {} does not apply: {}", index, e))?;
+            chain.push_back(block);
+        }
+        if tip > genesis.index {
+            log::info!("Chain resumed from store at height {}", tip);
+        }
+        Ok(())
+    }
+
+    /// Weigh PoS and PBFT blocks by their signer's stake in fork choice.
+    pub fn with_stake_weights(mut self, stakes: Arc<dyn StakeWeights>) -> Self {
+        self.stakes = Some(stakes);
+        self
+    }
+
+    /// Accept only blocks whose transactions are signed by a key `check`
+    /// authorizes for their sender, as the pool admits them.
+    pub fn with_key_check(mut self, check: KeyCheck) -> Self {
+        self.key_check = Some(check);
+        self
+    }
+
+    /// Hold PoW blocks to the difficulty `schedule` derives from the chain
+    /// below them, here and in fork choice.
+    pub fn with_pow_schedule(mut self, schedule: PowSchedule) -> Self {
+        self.pow = Some(schedule);
+        self
+    }
+
+    /// Our block at `index`, by position from genesis.
+    fn block_at(&self, index: u64) -> Option<&Block> {
+        let offset = index.checked_sub(self.chain.front()?.index)?;
+        self.chain.get(usize::try_from(offset).ok()?)
+    }
+
+    /// Difficulty `block` must carry on top of our chain up to `ancestor`
+    /// and then `branch`; `None` without a schedule or for a non-PoW block.
+    fn expected_pow(&self, block: &Block, ancestor: u64, branch: &[Block]) -> Option<u64> {
+        let schedule = self.pow.as_ref().filter(|_| block.is_pow())?;
+        Some(schedule.expected(block.index, |index: u64| match index.checked_sub(ancestor + 1) {
+            None => self.block_at(index),
+            Some(offset) => branch.get(usize::try_from(offset).ok()?),
+        }))
     }
 
     // ── Block acceptance ──────────────────────────────────────────────────────
@@ -151,11 +261,18 @@ impl Blockchain {
 
         // ── 1. Full structural + signature validation ─────────────────────
         if !block.transactions.is_empty() || block.index != 0 {
-            if !BlockValidator::validate_full_block(last_block, &block, public_key) {
+            let expected_pow = self.expected_pow(&block, last_block.index, &[]);
+            if !BlockValidator::validate_full_block(last_block, &block, public_key, expected_pow) {
                 log::error!("Block {} failed validation", block.index);
                 return false;
             }
         }
+        if let Some(check) = &self.key_check {
+            if !BlockValidator::validate_transaction_signers(&block, check) {
+                log::error!("Block {} carries a transaction its sender did not sign", block.index);
+                return false;
+            }
+        }
 
         // ── 2. Apply transactions to in-memory state ─────────────────────
         {
@@ -166,15 +283,22 @@ impl Blockchain {
             }
         }
 
-        // ── 3. Drain confirmed transactions from pool ─────────────────────
+        // ── 3. Persist before the block becomes the tip ───────────────────
+        if let Some(store) = &self.store {
+            if let Err(e) = store.put_block(&block) {
+                log::error!("Block {} could not be persisted: {}", block.index, e);
+                self.state.write().unwrap().revert_block(&block);
+                return false;
+            }
+        }
+
+        // ── 4. Drain confirmed transactions from pool ─────────────────────
         //   (async pool, so we do a best-effort fire-and-forget via tokio::spawn)
         let pool = self.transaction_pool.read().unwrap().clone();
         let confirmed_ids: Vec<String> = block
             .transactions
             .iter()
-            .map(|tx| {
-                format!("{}:{}:{}:{}", tx.sender, tx.receiver, tx.amount, tx.timestamp)
-            })
+            .map(Transaction::id)
             .collect();
         tokio::spawn(async move {
             for id in confirmed_ids {
@@ -182,7 +306,7 @@ impl Blockchain {
             }
         });
 
-        // ── 4. Append to chain ────────────────────────────────────────────
+        // ── 5. Append to chain ────────────────────────────────────────────
         let block_index = block.index;
         self.chain.push_back(block);
         log::info!(
@@ -224,6 +348,11 @@ impl Blockchain {
         self.state.read().unwrap().balance_of(address)
     }
 
+    /// Nonce the next transaction from `address` must carry.
+    pub fn next_nonce(&self, address: &str) -> u64 {
+        self.state.read().unwrap().next_nonce(address)
+    }
+
     // ── Chain management ──────────────────────────────────────────────────────
 
     /// Full integrity verification (link hashes + signatures).
@@ -242,20 +371,94 @@ impl Blockchain {
         true
     }
 
-    /// Fork resolution: adopt the longer valid chain.
-    pub fn handle_fork(&mut self, new_chain: VecDeque<Block>) {
-        if new_chain.len() > self.chain.len() {
-            log::warn!(
-                "Fork detected — adopting chain of length {} (was {})",
-                new_chain.len(),
-                self.chain.len()
+    /// Reorg onto `new_chain` if its blocks since the common ancestor
+    /// outscore ours (see `fork_choice`).
+    ///
+    /// `new_chain` must include a block we hold and link on from it; each
+    /// block past the ancestor must carry a valid header signature and pass
+    /// `BlockValidator::validate_transactions`, be mined at the scheduled
+    /// difficulty if it claims PoW (and, with a key check,
+    /// `validate_transaction_signers`) before the branch is scored.  Our blocks above the
+    /// ancestor are reverted tip-first and the new branch applied on a copy
+    /// of the state, so a branch that overdraws leaves everything as it was.
+    /// The store takes the whole branch in one `BlockStore::put_blocks`.
+    /// Transactions only our branch carried go back to the pool.
+    ///
+    /// Returns whether the chain was switched.
+    pub fn handle_fork(&mut self, new_chain: VecDeque<Block>) -> Result<bool, String> {
+        let ancestor = new_chain.iter().rposition(|b| {
+            self.get_block_by_index(b.index).map(|ours| ours.compute_hash()) == Some(b.compute_hash())
+        }).ok_or("competing chain shares no block with ours")?;
+        let ancestor_index = new_chain[ancestor].index;
+        let branch: Vec<Block> = new_chain.into_iter().skip(ancestor + 1).collect();
+        let ours: Vec<Block> = self.chain.iter().filter(|b| b.index > ancestor_index).cloned().collect();
+
+        // Validated before scoring, so a PoW block counts the difficulty
+        // the schedule demands rather than whatever it claims.
+        let mut prev = self.get_block_by_index(ancestor_index).expect("ancestor is on our chain");
+        for block in &branch {
+            if !BlockValidator::validate_block_link(&prev, block)
+                || !block.verify_header_signature()
+                || !BlockValidator::validate_transactions(block)
+                || !BlockValidator::validate_pow_claim(block, self.expected_pow(block, ancestor_index, &branch))
+                || self.key_check.as_ref().is_some_and(|check| !BlockValidator::validate_transaction_signers(block, check))
+            {
+                return Err(format!("fork block {} failed validation", block.index));
+            }
+            prev = block.clone();
+        }
+
+        let stakes = self.stakes.as_deref();
+        let (theirs_score, ours_score) = (branch_score(&branch, stakes), branch_score(&ours, stakes));
+        if theirs_score <= ours_score {
+            log::info!(
+                "Fork from block {} scores {} against our {} — keeping current chain",
+                ancestor_index, theirs_score, ours_score
             );
-            self.chain = new_chain;
+            return Ok(false);
+        }
+
+        let mut state = self.state.read().unwrap().clone();
+        for block in ours.iter().rev() {
+            state.revert_block(block);
+        }
+        for block in &branch {
+            state.apply_block(block).map_err(|e| format!("fork block {} does not apply: {}", block.index, e))?;
+        }
+
+        if let Some(store) = &self.store {
+            store.put_blocks(&branch).map_err(|e| format!("fork could not be persisted: {}", e))?;
+        }
+
+        log::warn!(
+            "Reorg at block {}: {} blocks (score {}) replace {} (score {})",
+            ancestor_index, branch.len(), theirs_score, ours.len(), ours_score
+        );
+        let included: HashSet<String> = branch.iter().flat_map(|b| &b.transactions).map(Transaction::id).collect();
+        let evicted: Vec<ZKTransaction> = ours.iter()
+            .flat_map(|b| &b.transactions)
+            .filter(|tx| !included.contains(&tx.id()))
+            .map(ZKTransaction::from)
+            .collect();
+        *self.state.write().unwrap() = state;
+        self.chain.retain(|b| b.index <= ancestor_index);
+        self.chain.extend(branch);
+
+        if !evicted.is_empty() {
+            let pool = self.transaction_pool.read().unwrap().clone();
+            tokio::spawn(async move { pool.return_transactions(evicted).await });
         }
+        Ok(true)
     }
 
     /// Roll back the tip of the chain, reverting its state changes.
     pub fn rollback(&mut self) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.delete_tip() {
+                log::error!("Tip could not be removed from the chain store: {} — not rolling back", e);
+                return;
+            }
+        }
         if let Some(removed) = self.chain.pop_back() {
             let mut state = self.state.write().unwrap();
             state.revert_block(&removed);
@@ -281,3 +484,367 @@ pub fn register_chain(chain: Arc<RwLock<Blockchain>>) {
 pub fn global_chain() -> Option<Arc<RwLock<Blockchain>>> {
     CHAIN.get().cloned()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::ChainId;
+    use bleep_crypto::key_change::key_hash;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload};
+
+    struct Signer {
+        pk: Vec<u8>,
+        sk: Vec<u8>,
+    }
+
+    impl Signer {
+        fn new() -> Self {
+            let (pk, sk) = generate_tx_keypair();
+            Signer { pk, sk }
+        }
+
+        fn transfer(&self, from: &str, to: &str, amount: u64, ts: u64, nonce: u64) -> Transaction {
+            self.transfer_on(ChainId::NONE, from, to, amount, ts, nonce)
+        }
+
+        fn transfer_on(&self, chain_id: ChainId, from: &str, to: &str, amount: u64, ts: u64, nonce: u64) -> Transaction {
+            let mut tx = Transaction {
+                sender: from.into(), receiver: to.into(), amount, timestamp: ts, signature: Vec::new(),
+                max_fee: 0, tip: 0, nonce, chain_id,
+            };
+            tx.signature = self.pk.clone();
+            tx.signature.extend(sign_tx_payload(&tx.signed_payload(), &self.sk).unwrap());
+            tx
+        }
+
+        fn block(&self, chain: &Blockchain, txs: Vec<Transaction>) -> Block {
+            self.block_on(&chain.latest_block().unwrap(), txs)
+        }
+
+        fn mined_on(&self, tip: &Block, difficulty: u64) -> Block {
+            let mut block = Block::new(tip.index + 1, vec![], tip.compute_hash());
+            block.consensus_mode = crate::block::ConsensusMode::EmergencyPow;
+            block.pow_difficulty = difficulty;
+            while !block.meets_pow_difficulty() {
+                block.pow_nonce += 1;
+            }
+            block.sign_block_with_pk(&self.sk, &self.pk).unwrap();
+            block
+        }
+
+        fn block_on(&self, tip: &Block, txs: Vec<Transaction>) -> Block {
+            let mut block = Block::new(tip.index + 1, txs, tip.compute_hash());
+            block.chain_id = tip.chain_id;
+            block.sign_block_with_pk(&self.sk, &self.pk).unwrap();
+            block
+        }
+    }
+
+    /// Stake 10 for one validator, 1 for everyone else.
+    struct OneHeavy(Vec<u8>);
+
+    impl StakeWeights for OneHeavy {
+        fn weight(&self, _epoch_id: u64, signer: &[u8]) -> Option<u64> {
+            Some(if signer == self.0 { 10 } else { 1 })
+        }
+    }
+
+    /// Let spawned pool updates run.
+    async fn settle() {
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn chain() -> Blockchain {
+        let mut state = BlockchainState::new();
+        state.credit("alice", 100);
+        Blockchain::new(Block::genesis(), state, TransactionPool::new(10), None).unwrap()
+    }
+
+    fn balance(chain: &Blockchain, address: &str) -> Option<u64> {
+        chain.state.read().unwrap().balances.get(address).copied()
+    }
+
+    #[tokio::test]
+    async fn bad_signature_and_overdraft_blocks_are_rejected() {
+        let (validator, alice) = (Signer::new(), Signer::new());
+        let mut chain = chain();
+
+        let mut forged = alice.transfer("alice", "bob", 10, 1, 1);
+        forged.amount = 90;
+        let bad_sig = validator.block(&chain, vec![alice.transfer("alice", "carol", 5, 2, 0), forged]);
+        assert!(!BlockValidator::validate_transactions(&bad_sig));
+        assert!(!chain.add_block(bad_sig, &validator.pk));
+
+        let overdraft = validator.block(&chain, vec![
+            alice.transfer("alice", "bob", 60, 1, 0),
+            alice.transfer("alice", "carol", 60, 2, 1),
+        ]);
+        assert!(BlockValidator::validate_transactions(&overdraft));
+        assert!(!chain.add_block(overdraft, &validator.pk));
+
+        assert_eq!(chain.height(), 0);
+        assert_eq!(balance(&chain, "alice"), Some(100));
+        assert_eq!(balance(&chain, "bob"), None);
+
+        let good = validator.block(&chain, vec![
+            alice.transfer("alice", "bob", 60, 1, 0),
+            alice.transfer("alice", "carol", 40, 2, 1),
+        ]);
+        assert!(chain.add_block(good, &validator.pk));
+        assert_eq!(balance(&chain, "alice"), Some(0));
+        assert_eq!(balance(&chain, "carol"), Some(40));
+    }
+
+    #[tokio::test]
+    async fn transfers_signed_by_another_key_are_rejected() {
+        let (validator, alice) = (Signer::new(), Signer::new());
+        let alice_key = key_hash(&alice.pk);
+        let mut chain = chain().with_key_check(Arc::new(move |account: &str, hash: &[u8; 32]| {
+            account != "alice" || *hash == alice_key
+        }));
+
+        let forged = validator.block(&chain, vec![validator.transfer("alice", "mallory", 100, 1, 0)]);
+        assert!(BlockValidator::validate_transactions(&forged), "the proposer's own signature verifies");
+        assert!(!chain.add_block(forged, &validator.pk));
+        assert_eq!(balance(&chain, "mallory"), None);
+
+        let genesis = chain.latest_block().unwrap();
+        let forked = validator.block_on(&genesis, vec![validator.transfer("alice", "mallory", 100, 1, 0)]);
+        assert!(chain.handle_fork(VecDeque::from([genesis, forked])).is_err());
+
+        let signed = validator.block(&chain, vec![alice.transfer("alice", "bob", 10, 1, 0)]);
+        assert!(chain.add_block(signed, &validator.pk));
+        assert_eq!(balance(&chain, "bob"), Some(10));
+    }
+
+    #[test]
+    fn duplicate_ids_and_a_stale_merkle_root_are_rejected() {
+        let alice = Signer::new();
+        let tx = alice.transfer("alice", "bob", 10, 1, 0);
+        let duplicated = Block::new(1, vec![tx.clone(), tx.clone()], "0".into());
+        assert!(!BlockValidator::validate_transactions(&duplicated));
+
+        let mut swapped = Block::new(1, vec![tx], "0".into());
+        swapped.transactions[0] = alice.transfer("alice", "mallory", 10, 1, 0);
+        assert!(!BlockValidator::validate_transactions(&swapped));
+        swapped.merkle_root = Block::calculate_merkle_root(&swapped.transactions);
+        assert!(BlockValidator::validate_transactions(&swapped));
+    }
+
+    #[tokio::test]
+    async fn replayed_and_out_of_order_nonces_are_rejected() {
+        let (validator, alice) = (Signer::new(), Signer::new());
+        let mut chain = chain();
+
+        let swapped = validator.block(&chain, vec![
+            alice.transfer("alice", "bob", 10, 1, 1),
+            alice.transfer("alice", "bob", 10, 2, 0),
+        ]);
+        assert!(!BlockValidator::validate_transactions(&swapped));
+
+        let skipped = validator.block(&chain, vec![alice.transfer("alice", "bob", 10, 1, 1)]);
+        assert!(BlockValidator::validate_transactions(&skipped));
+        assert!(!chain.add_block(skipped, &validator.pk), "nonce 1 before nonce 0");
+
+        let to_bob = alice.transfer("alice", "bob", 10, 1, 0);
+        assert!(chain.add_block(validator.block(&chain, vec![to_bob.clone()]), &validator.pk));
+        assert_eq!(chain.next_nonce("alice"), 1);
+
+        let replay = validator.block(&chain, vec![to_bob]);
+        assert!(!chain.add_block(replay, &validator.pk));
+        assert_eq!(balance(&chain, "alice"), Some(90));
+
+        chain.rollback();
+        assert_eq!((chain.next_nonce("alice"), balance(&chain, "alice")), (0, Some(100)));
+    }
+
+    #[tokio::test]
+    async fn transactions_and_blocks_from_another_chain_are_rejected() {
+        let (validator, alice) = (Signer::new(), Signer::new());
+        let mut state = BlockchainState::new();
+        state.credit("alice", 100);
+        let mut chain = Blockchain::new(Block::genesis_for(ChainId(1)), state, TransactionPool::new(10), None).unwrap();
+
+        let mut replayed = alice.transfer_on(ChainId(1), "alice", "bob", 10, 1, 0);
+        replayed.chain_id = ChainId(2);
+        assert!(!replayed.signature_valid(), "signed on chain 1, presented on chain 2");
+
+        let foreign = alice.transfer_on(ChainId(2), "alice", "bob", 10, 1, 0);
+        assert!(foreign.signature_valid());
+        let block = validator.block(&chain, vec![foreign]);
+        assert!(!BlockValidator::validate_transactions(&block));
+        assert!(!chain.add_block(block, &validator.pk));
+
+        let tip = chain.latest_block().unwrap();
+        let mut forked = Block::new(1, vec![], tip.compute_hash());
+        forked.chain_id = ChainId(2);
+        assert!(!BlockValidator::validate_block_link(&tip, &forked));
+
+        let home = alice.transfer_on(ChainId(1), "alice", "bob", 10, 1, 0);
+        assert!(chain.add_block(validator.block(&chain, vec![home]), &validator.pk));
+        assert_eq!(balance(&chain, "bob"), Some(10));
+    }
+
+    #[tokio::test]
+    async fn heavier_branch_of_equal_length_wins() {
+        let (light, heavy, alice) = (Signer::new(), Signer::new(), Signer::new());
+        let mut chain = chain().with_stake_weights(Arc::new(OneHeavy(heavy.pk.clone())));
+        let genesis = chain.latest_block().unwrap();
+
+        let to_bob = alice.transfer("alice", "bob", 30, 1, 0);
+        for txs in [vec![to_bob], vec![]] {
+            let block = light.block(&chain, txs);
+            assert!(chain.add_block(block, &light.pk));
+        }
+        settle().await;
+        let ours = chain.chain.clone();
+
+        let mut fork = VecDeque::from([genesis]);
+        for txs in [vec![alice.transfer("alice", "carol", 50, 2, 0)], vec![]] {
+            let block = heavy.block_on(fork.back().unwrap(), txs);
+            fork.push_back(block);
+        }
+        assert_eq!(chain.handle_fork(fork.clone()), Ok(true));
+        assert_eq!(chain.height(), 2);
+        assert_eq!(chain.latest_block().unwrap().compute_hash(), fork.back().unwrap().compute_hash());
+        assert_eq!(balance(&chain, "alice"), Some(50));
+        assert_eq!(balance(&chain, "carol"), Some(50));
+        assert_eq!(balance(&chain, "bob"), Some(0));
+        settle().await;
+        let pool = chain.transaction_pool.read().unwrap().clone();
+        assert_eq!(pool.pool_size().await, 1, "alice → bob is pending again");
+
+        // Same length, lighter: no switch back
+        assert_eq!(chain.handle_fork(ours), Ok(false));
+        assert_eq!(chain.latest_block().unwrap().compute_hash(), fork.back().unwrap().compute_hash());
+
+        let stranger = light.block_on(&Block::new(7, vec![], "f".repeat(64)), vec![]);
+        assert!(chain.handle_fork(VecDeque::from([stranger])).is_err());
+    }
+
+    #[tokio::test]
+    async fn overdrawing_fork_leaves_the_chain_alone() {
+        let (light, heavy, alice) = (Signer::new(), Signer::new(), Signer::new());
+        let mut chain = chain().with_stake_weights(Arc::new(OneHeavy(heavy.pk.clone())));
+        let genesis = chain.latest_block().unwrap();
+        let block = light.block(&chain, vec![alice.transfer("alice", "bob", 30, 1, 0)]);
+        assert!(chain.add_block(block, &light.pk));
+
+        let fork = VecDeque::from([
+            genesis.clone(),
+            heavy.block_on(&genesis, vec![alice.transfer("alice", "carol", 500, 2, 0)]),
+        ]);
+        assert!(chain.handle_fork(fork).is_err());
+        assert_eq!(chain.height(), 1);
+        assert_eq!(balance(&chain, "alice"), Some(70));
+        assert_eq!(balance(&chain, "carol"), None);
+    }
+
+    #[tokio::test]
+    async fn pow_blocks_must_carry_the_scheduled_difficulty() {
+        let miner = Signer::new();
+        let schedule = PowSchedule { initial: 16, window: 1_000, retarget: Arc::new(|d: u64, _: &[u64]| d) };
+        let mut chain = chain().with_pow_schedule(schedule);
+        let genesis = chain.latest_block().unwrap();
+
+        assert!(!chain.add_block(miner.mined_on(&genesis, 8), &miner.pk), "easier than scheduled");
+        let mut unsolved = miner.mined_on(&genesis, 16);
+        unsolved.pow_nonce += 1;
+        while unsolved.meets_pow_difficulty() {
+            unsolved.pow_nonce += 1;
+        }
+        unsolved.sign_block_with_pk(&miner.sk, &miner.pk).unwrap();
+        assert!(!chain.add_block(unsolved, &miner.pk), "nonce does not do the work");
+        assert!(chain.add_block(miner.mined_on(&genesis, 16), &miner.pk));
+
+        // Claiming more work than the schedule asked for does not win a fork
+        let inflated = VecDeque::from([genesis.clone(), miner.mined_on(&genesis, 64)]);
+        assert!(chain.handle_fork(inflated).is_err());
+        assert_eq!(chain.height(), 1);
+        assert_eq!(chain.latest_block().unwrap().pow_difficulty, 16);
+    }
+
+    /// `BlockStore` kept in a map, standing in for the node's archive.
+    #[derive(Default)]
+    struct MemoryStore(std::sync::Mutex<std::collections::BTreeMap<u64, Block>>);
+
+    impl BlockStore for MemoryStore {
+        fn put_blocks(&self, blocks: &[Block]) -> Result<(), String> {
+            let mut map = self.0.lock().unwrap();
+            if let Some(first) = blocks.first() {
+                map.retain(|index, _| *index < first.index);
+            }
+            map.extend(blocks.iter().map(|b| (b.index, b.clone())));
+            Ok(())
+        }
+
+        fn delete_tip(&self) -> Result<Option<Block>, String> {
+            Ok(self.0.lock().unwrap().pop_last().map(|(_, b)| b))
+        }
+
+        fn get_block_by_index(&self, index: u64) -> Result<Option<Block>, String> {
+            Ok(self.0.lock().unwrap().get(&index).cloned())
+        }
+
+        fn get_block_by_hash(&self, hash: &str) -> Result<Option<Block>, String> {
+            Ok(self.0.lock().unwrap().values().find(|b| b.compute_hash() == hash).cloned())
+        }
+
+        fn latest_index(&self) -> Result<Option<u64>, String> {
+            Ok(self.0.lock().unwrap().keys().next_back().copied())
+        }
+    }
+
+    fn reopen(store: &Arc<MemoryStore>) -> Result<Blockchain, String> {
+        let mut state = BlockchainState::new();
+        state.credit("alice", 100);
+        let store: Arc<dyn BlockStore> = store.clone();
+        Blockchain::new(Block::genesis(), state, TransactionPool::new(10), Some(store))
+    }
+
+    #[tokio::test]
+    async fn a_reopened_chain_replays_its_blocks_onto_the_genesis_state() {
+        let (validator, alice) = (Signer::new(), Signer::new());
+        let store = Arc::new(MemoryStore::default());
+        let tip_hash = {
+            let mut chain = reopen(&store).unwrap();
+            for nonce in 0..3 {
+                let block = validator.block(&chain, vec![alice.transfer("alice", "bob", 10, nonce + 1, nonce)]);
+                assert!(chain.add_block(block, &validator.pk));
+            }
+            let rolled_back = chain.latest_block().unwrap().compute_hash();
+            chain.rollback();
+            assert!(store.get_block_by_hash(&rolled_back).unwrap().is_none());
+            chain.latest_block().unwrap().compute_hash()
+        };
+
+        let chain = reopen(&store).unwrap();
+        assert_eq!(chain.height(), 2);
+        assert_eq!(chain.latest_block().unwrap().compute_hash(), tip_hash);
+        assert!(chain.verify_chain(&validator.pk));
+        assert_eq!(balance(&chain, "alice"), Some(80));
+        assert_eq!(balance(&chain, "bob"), Some(20));
+        assert_eq!(chain.next_nonce("alice"), 2);
+    }
+
+    #[tokio::test]
+    async fn a_stored_block_that_does_not_link_is_refused_on_reopen() {
+        let validator = Signer::new();
+        let store = Arc::new(MemoryStore::default());
+        {
+            let mut chain = reopen(&store).unwrap();
+            for _ in 0..2 {
+                let block = validator.block(&chain, vec![]);
+                assert!(chain.add_block(block, &validator.pk));
+            }
+        }
+
+        let mut forged = store.get_block_by_index(2).unwrap().unwrap();
+        forged.previous_hash = "f".repeat(64);
+        store.put_block(&forged).unwrap();
+        let err = reopen(&store).err().unwrap();
+        assert!(err.contains("block 2"), "{}", err);
+    }
+}
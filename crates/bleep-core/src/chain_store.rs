@@ -0,0 +1,33 @@
+//! # Chain store
+//!
+//! Durable backing for `Blockchain`.  Without one the chain lives only in
+//! its `VecDeque`, and a restart starts again from genesis.
+//!
+//! The node's implementation is the block archive in
+//! `bleep_consensus::block_store`, so the chain and the producer's
+//! execution records share one directory of blocks.
+
+use crate::block::Block;
+
+/// Persistent block storage passed to `Blockchain::new`.
+pub trait BlockStore: Send + Sync {
+    /// Store `block` at its index and make it the tip.  Anything stored
+    /// above that index — a chain being replaced — is dropped.
+    fn put_block(&self, block: &Block) -> Result<(), String> {
+        self.put_blocks(std::slice::from_ref(block))
+    }
+
+    /// Store consecutive `blocks` and make the last one the tip, dropping
+    /// anything stored from the first one's index up that they replace.
+    fn put_blocks(&self, blocks: &[Block]) -> Result<(), String>;
+
+    /// Remove the tip; returns it, or `None` on an empty store.
+    fn delete_tip(&self) -> Result<Option<Block>, String>;
+
+    fn get_block_by_index(&self, index: u64) -> Result<Option<Block>, String>;
+
+    fn get_block_by_hash(&self, hash: &str) -> Result<Option<Block>, String>;
+
+    /// Index of the tip, `None` on an empty store.
+    fn latest_index(&self) -> Result<Option<u64>, String>;
+}
@@ -0,0 +1,95 @@
+//! # Fork choice
+//!
+//! `Blockchain::handle_fork` scores the blocks each side added since the
+//! common ancestor and reorgs only to a strictly higher score, so a longer
+//! chain of cheap blocks does not win by length alone.
+//!
+//! A block's score depends on the consensus mode it records:
+//!
+//! - `EmergencyPow`: its `pow_difficulty`, the hashes the miner expected to
+//!   try, if `pow_nonce` meets it, and nothing otherwise.  A block mined
+//!   before difficulty was recorded weighs 1.  `handle_fork` checks the
+//!   difficulty against the chain's `PowSchedule` before scoring, so a
+//!   miner cannot claim more work than the schedule asked of it.
+//! - `PosNormal` / `PbftFastFinality`: the stake of the validator that
+//!   signed it, from `StakeWeights`.  A signer the table does not know, and
+//!   an unsigned block, weigh nothing.  Without a stake table every signed
+//!   block weighs 1.
+//!
+//! bleep-core does not know the validator sets, so the node supplies them
+//! through `Blockchain::with_stake_weights`.
+
+use crate::block::{Block, ConsensusMode};
+
+/// Stake of a block signer in the epoch it signed for; `None` if the key
+/// is not a validator's.
+pub trait StakeWeights: Send + Sync {
+    fn weight(&self, epoch_id: u64, signer: &[u8]) -> Option<u64>;
+}
+
+pub fn block_score(block: &Block, stakes: Option<&dyn StakeWeights>) -> u128 {
+    match block.consensus_mode {
+        ConsensusMode::EmergencyPow if block.meets_pow_difficulty() => block.pow_difficulty.max(1) as u128,
+        ConsensusMode::EmergencyPow => 0,
+        ConsensusMode::PosNormal | ConsensusMode::PbftFastFinality => match block.signer_public_key() {
+            Some(signer) => match stakes {
+                Some(s) => s.weight(block.epoch_id, signer).unwrap_or(0) as u128,
+                None => 1,
+            },
+            None => 0,
+        },
+    }
+}
+
+/// Summed score of `blocks`.
+pub fn branch_score<'a, I>(blocks: I, stakes: Option<&dyn StakeWeights>) -> u128
+where
+    I: IntoIterator<Item = &'a Block>,
+{
+    blocks.into_iter().fold(0u128, |total, b| total.saturating_add(block_score(b, stakes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::tx_signer::generate_tx_keypair;
+
+    struct Fixed(Vec<u8>);
+
+    impl StakeWeights for Fixed {
+        fn weight(&self, _epoch_id: u64, signer: &[u8]) -> Option<u64> {
+            (signer == self.0).then_some(10)
+        }
+    }
+
+    #[test]
+    fn stake_weights_signed_blocks_and_work_weights_pow_blocks() {
+        let (pk, sk) = generate_tx_keypair();
+        let (other_pk, other_sk) = generate_tx_keypair();
+        let mut heavy = Block::new(1, vec![], "0".into());
+        heavy.sign_block_with_pk(&sk, &pk).unwrap();
+        let mut light = Block::new(1, vec![], "0".into());
+        light.sign_block_with_pk(&other_sk, &other_pk).unwrap();
+        let stakes = Fixed(pk);
+
+        assert_eq!(block_score(&heavy, Some(&stakes)), 10);
+        assert_eq!(block_score(&light, Some(&stakes)), 0, "an unknown signer weighs nothing");
+        assert_eq!(block_score(&heavy, None), 1);
+        assert_eq!(block_score(&light, None), 1);
+        assert_eq!(block_score(&Block::new(1, vec![], "0".into()), Some(&stakes)), 0);
+        assert_eq!(branch_score([&heavy, &light], Some(&stakes)), 10);
+
+        let mut pow = Block::new(1, vec![], "0".into());
+        pow.consensus_mode = ConsensusMode::EmergencyPow;
+        assert_eq!(block_score(&pow, None), 1, "mined before difficulty was recorded");
+        pow.pow_difficulty = 16;
+        while !pow.meets_pow_difficulty() {
+            pow.pow_nonce += 1;
+        }
+        assert_eq!(block_score(&pow, None), 16);
+        while pow.meets_pow_difficulty() {
+            pow.pow_nonce += 1;
+        }
+        assert_eq!(block_score(&pow, None), 0, "an unsolved block weighs nothing");
+    }
+}
@@ -1,7 +1,10 @@
 // === Core Blockchain Logic ===
 pub mod block;
+pub mod block_sync;
 pub mod block_validation;
 pub mod blockchain;
+pub mod chain_store;
+pub mod fork_choice;
 pub mod state;
 pub mod networking;
 
@@ -11,6 +14,11 @@ pub mod transaction_manager;
 pub mod transaction_pool;
 pub mod mempool;
 pub mod mempool_bridge;
+pub mod pending_spend;
+pub mod base_fee;
+pub mod scheduled_tx;
+pub mod relay_checks;
+pub mod wal;
 
 // === Identity and Security ===
 pub mod proof_of_identity;
@@ -25,12 +33,17 @@ pub mod decision_attestation;
 pub mod decision_verification;
 
 // === Re-exports for broader ecosystem access ===
-pub use block::{Block, derive_block_keypair};
+pub use block::{Block, ChainId, derive_block_keypair};
+pub use block_sync::{BlockSyncConfig, BlockSyncError, ChainSource, ChainSyncer, SignerCheck, SyncOutcome, SyncProgress, ValidatorKey};
 pub use block_validation::*;
 pub use blockchain::*;
 pub use transaction::{ZKTransaction};
 pub use transaction_manager::*;
 pub use transaction_pool::*;
+pub use pending_spend::{BalanceLookup, PendingSpend, PendingSpendLedger, SpendModel};
+pub use base_fee::{BaseFeeParams, BaseFeeTracker, FeeCharge, FeeEstimate, FeeTotals};
+pub use scheduled_tx::{ScheduleEvent, ScheduleOutcome, ScheduledTx, TxScheduler};
+pub use relay_checks::CoreRelayValidator;
 pub use mempool::*;
 pub use proof_of_identity::*;
 pub use anti_asset_loss::*;
@@ -5,35 +5,97 @@ use crate::transaction::ZKTransaction;
 // use crate::core::transaction::ZKTransaction;
 // use crate::crypto::proof_of_identity::ProofOfIdentity;
 // use crate::networking::encryption::QuantumEncryption;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tokio::sync::Mutex;
 use std::sync::Arc;
 
+/// Order in which pending transactions are served: highest `tip`, then
+/// highest `max_fee`, then earliest arrival.  The smallest key is the first
+/// to be evicted.
+type Priority = (u64, u64, Reverse<u64>);
+
+fn priority(tx: &ZKTransaction, seq: u64) -> Priority {
+    (tx.tip, tx.max_fee, Reverse(seq))
+}
+
+/// Bytes a pending transaction counts against the `max_bytes` bound.
+fn tx_size(tx: &ZKTransaction) -> usize {
+    bincode::serialized_size(tx).map(|n| n as usize).unwrap_or(usize::MAX)
+}
+
+struct Pending {
+    tx:    ZKTransaction,
+    key:   Priority,
+    bytes: usize,
+}
+
+#[derive(Default)]
+struct Pool {
+    transactions: HashMap<String, Pending>,          // Stores transactions with unique IDs
+    by_priority:  BTreeMap<Priority, String>,        // Serving / eviction order
+    seen:         HashSet<String>,                   // Prevents duplicate transactions
+    bytes:        usize,
+    next_seq:     u64,
+}
+
+impl Pool {
+    fn remove(&mut self, tx_id: &str) -> Option<ZKTransaction> {
+        let pending = self.transactions.remove(tx_id)?;
+        self.by_priority.remove(&pending.key);
+        self.bytes = self.bytes.saturating_sub(pending.bytes);
+        Some(pending.tx)
+    }
+
+    /// Drop the highest-priority transaction, for block building.
+    fn pop_top(&mut self) -> Option<ZKTransaction> {
+        let (_, tx_id) = self.by_priority.pop_last()?;
+        self.seen.remove(&tx_id);
+        let pending = self.transactions.remove(&tx_id)?;
+        self.bytes = self.bytes.saturating_sub(pending.bytes);
+        Some(pending.tx)
+    }
+}
+
 /// The Mempool stores unconfirmed transactions before they are added to a block
+///
+/// With `with_capacity` the pool is bounded by count and by encoded size;
+/// once either is exceeded the lowest-priority transactions are evicted,
+/// which may be the one just offered.
 pub struct Mempool {
-    transactions: Mutex<HashMap<String, ZKTransaction>>,  // Stores transactions with unique IDs
-    seen_transactions: Mutex<HashSet<String>>,           // Prevents duplicate transactions
+    pool:      Mutex<Pool>,
+    max_txs:   usize,
+    max_bytes: usize,
 }
 
 impl Mempool {
-    /// Initializes a new mempool
+    /// Initializes a new, unbounded mempool
     pub fn new() -> Arc<Self> {
+        Self::with_capacity(usize::MAX, usize::MAX)
+    }
+
+    /// A mempool holding at most `max_txs` transactions and `max_bytes` of
+    /// encoded transactions.
+    pub fn with_capacity(max_txs: usize, max_bytes: usize) -> Arc<Self> {
         Arc::new(Self {
-            transactions: Mutex::new(HashMap::new()),
-            seen_transactions: Mutex::new(HashSet::new()),
+            pool: Mutex::new(Pool::default()),
+            max_txs,
+            max_bytes,
         })
     }
 
     /// Adds a transaction to the mempool after verifying its validity
+    ///
+    /// Returns `false` for invalid or duplicate transactions, and for one
+    /// that is itself evicted because everything already pending pays more.
     pub async fn add_transaction(&self, transaction: ZKTransaction) -> bool {
-        let transactions = self.transactions.lock().await;
-        let mut seen_transactions = self.seen_transactions.lock().await;
-        
+        let mut pool = self.pool.lock().await;
+
         let tx_id = format!("{}:{}:{}:{}", 
             transaction.sender, transaction.receiver, transaction.amount, transaction.timestamp);
         
         // Check for duplicate transactions
-        if seen_transactions.contains(&tx_id) {
+        if pool.seen.contains(&tx_id) {
             log::warn!("Attempting to add duplicate transaction: {}", tx_id);
             return false;
         }
@@ -60,43 +122,73 @@ impl Mempool {
         }
         
         // Add to seen set to prevent future duplicates
-        seen_transactions.insert(tx_id.clone());
+        pool.seen.insert(tx_id.clone());
         
         // Add to transaction store
-        drop(transactions); // Release the read lock
-        let mut transactions_write = self.transactions.lock().await;
-        transactions_write.insert(tx_id, transaction);
-        
-        log::debug!("Transaction added to mempool. Total: {}", transactions_write.len());
+        let seq = pool.next_seq;
+        pool.next_seq += 1;
+        let key = priority(&transaction, seq);
+        let bytes = tx_size(&transaction);
+        pool.bytes = pool.bytes.saturating_add(bytes);
+        pool.by_priority.insert(key, tx_id.clone());
+        pool.transactions.insert(tx_id.clone(), Pending { tx: transaction, key, bytes });
+
+        // Evict from the bottom until both bounds hold again
+        let mut admitted = true;
+        while pool.transactions.len() > self.max_txs || pool.bytes > self.max_bytes {
+            let Some(evicted) = pool.by_priority.values().next().cloned() else { break };
+            pool.remove(&evicted);
+            pool.seen.remove(&evicted);
+            if evicted == tx_id {
+                admitted = false;
+                log::debug!("Transaction {} not admitted: mempool full of higher-fee transactions", tx_id);
+            } else {
+                log::debug!("Evicted {} from full mempool", evicted);
+            }
+        }
+
+        log::debug!("Transaction added to mempool. Total: {}", pool.transactions.len());
         
-        true
+        admitted
     }
 
     /// Removes a transaction after it is included in a block
     pub async fn remove_transaction(&self, tx_id: &str) {
-        let mut transactions = self.transactions.lock().await;
-        let mut seen_transactions = self.seen_transactions.lock().await;
-        
-        transactions.remove(tx_id);
-        seen_transactions.remove(tx_id);
+        let mut pool = self.pool.lock().await;
+        pool.remove(tx_id);
+        pool.seen.remove(tx_id);
+    }
+
+    /// Removes and returns the `n` highest-priority transactions, best
+    /// first, for block building.
+    pub async fn take_top(&self, n: usize) -> Vec<ZKTransaction> {
+        let mut pool = self.pool.lock().await;
+        std::iter::from_fn(|| pool.pop_top()).take(n).collect()
     }
 
-    /// Returns a list of pending transactions for block inclusion
+    /// Returns a list of pending transactions for block inclusion, best first
     pub async fn get_pending_transactions(&self) -> Vec<ZKTransaction> {
-        let transactions = self.transactions.lock().await;
-        transactions.values().cloned().collect()
+        let pool = self.pool.lock().await;
+        pool.by_priority.values().rev().map(|id| pool.transactions[id].tx.clone()).collect()
     }
 
     /// Checks if a transaction already exists in the mempool
     pub async fn transaction_exists(&self, tx_id: &str) -> bool {
-        let transactions = self.transactions.lock().await;
-        transactions.contains_key(tx_id)
+        let pool = self.pool.lock().await;
+        pool.transactions.contains_key(tx_id)
     }
 
     /// Clears old transactions (used for mempool cleanup)
     pub async fn clear_old_transactions(&self) {
-        let mut transactions = self.transactions.lock().await;
-        transactions.retain(|_, tx| tx.timestamp + 600 > chrono::Utc::now().timestamp() as u64);
+        let mut pool = self.pool.lock().await;
+        let now = chrono::Utc::now().timestamp() as u64;
+        let expired: Vec<String> = pool.transactions.iter()
+            .filter(|(_, p)| p.tx.timestamp + 600 <= now)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in expired {
+            pool.remove(&id);
+        }
     }
 }
 
@@ -106,3 +198,68 @@ impl ZKTransaction {
         format!("{}:{}:{}:{}", self.sender, self.receiver, self.amount, self.timestamp)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::ChainId;
+
+    fn tx(n: u64, max_fee: u64, tip: u64) -> ZKTransaction {
+        ZKTransaction {
+            sender:    format!("sender-{}", n),
+            receiver:  "bob".into(),
+            amount:    1,
+            timestamp: n,
+            signature: vec![1; 64],
+            max_fee,
+            tip,
+            nonce:     0,
+            chain_id:  ChainId::NONE,
+        }
+    }
+
+    fn senders(txs: &[ZKTransaction]) -> Vec<&str> {
+        txs.iter().map(|t| t.sender.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn full_pool_evicts_the_lowest_fee() {
+        let pool = Mempool::with_capacity(3, usize::MAX);
+        assert!(pool.add_transaction(tx(1, 10, 1)).await);
+        assert!(pool.add_transaction(tx(2, 10, 5)).await);
+        assert!(pool.add_transaction(tx(3, 10, 3)).await);
+
+        // Outbids sender-1, which goes
+        assert!(pool.add_transaction(tx(4, 10, 4)).await);
+        assert!(!pool.transaction_exists(&tx(1, 10, 1).get_hash()).await);
+        // Pays no more than anything pending, so is not admitted
+        assert!(!pool.add_transaction(tx(5, 10, 3)).await);
+        // Evicted transactions may come back with a better fee
+        assert!(pool.add_transaction(tx(1, 20, 9)).await);
+
+        let pending = pool.get_pending_transactions().await;
+        assert_eq!(senders(&pending), ["sender-1", "sender-2", "sender-4"]);
+    }
+
+    #[tokio::test]
+    async fn byte_bound_evicts_until_it_fits() {
+        let size = tx_size(&tx(1, 0, 0));
+        let pool = Mempool::with_capacity(usize::MAX, 2 * size);
+        assert!(pool.add_transaction(tx(1, 0, 1)).await);
+        assert!(pool.add_transaction(tx(2, 0, 2)).await);
+        assert!(pool.add_transaction(tx(3, 0, 3)).await);
+        assert_eq!(senders(&pool.get_pending_transactions().await), ["sender-3", "sender-2"]);
+    }
+
+    #[tokio::test]
+    async fn take_top_is_fee_ordered_with_arrival_tie_break() {
+        let pool = Mempool::new();
+        for (n, max_fee, tip) in [(1, 10, 2), (2, 10, 2), (3, 30, 2), (4, 5, 7), (5, 10, 2)] {
+            assert!(pool.add_transaction(tx(n, max_fee, tip)).await);
+        }
+        let top = pool.take_top(4).await;
+        assert_eq!(senders(&top), ["sender-4", "sender-3", "sender-1", "sender-2"]);
+        assert_eq!(senders(&pool.take_top(10).await), ["sender-5"]);
+        assert!(pool.take_top(1).await.is_empty());
+    }
+}
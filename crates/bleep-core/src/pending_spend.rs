@@ -0,0 +1,128 @@
+//! # Pending-spend ledger
+//!
+//! Tracks, per account, how much the transactions waiting in the
+//! `TransactionPool` will take out of it once included.  Admission checks
+//! `confirmed balance − pending outflow ≥ new outflow`, so ten pending
+//! 100-token transfers from a 100-token account are stopped at the door
+//! instead of failing one by one at block inclusion.
+//!
+//! A transaction's outflow is decided by the `SpendModel`: the sender pays
+//! the amount, and the fee is charged to the sender unless a fee payer
+//! sponsors it.  The ledger is a pure function of the pool's contents —
+//! `TransactionPool` adds on admission and on reorg return, subtracts on
+//! removal, and rebuilds it from scratch on reload.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::transaction::ZKTransaction;
+
+/// Confirmed balance of an account, e.g. from the live `StateManager`.
+pub type BalanceLookup = Arc<dyn Fn(&str) -> u128 + Send + Sync>;
+
+/// How a transaction's cost is split between accounts.
+#[derive(Debug, Clone, Default)]
+pub struct SpendModel {
+    /// Fee charged per transaction, in µBLEEP.
+    pub flat_fee:   u64,
+    /// sender → account that pays the sender's fees.
+    pub fee_payers: HashMap<String, String>,
+}
+
+impl SpendModel {
+    pub fn with_fee(mut self, flat_fee: u64) -> Self {
+        self.flat_fee = flat_fee;
+        self
+    }
+
+    /// Have `sponsor` pay the fees of every transaction sent by `sender`.
+    pub fn with_fee_payer(mut self, sender: impl Into<String>, sponsor: impl Into<String>) -> Self {
+        self.fee_payers.insert(sender.into(), sponsor.into());
+        self
+    }
+
+    /// Every account `tx` debits and by how much.  An account appears at
+    /// most once.  A base-fee transaction is counted at its `max_fee`, the
+    /// most inclusion can charge it.
+    pub fn outflows(&self, tx: &ZKTransaction) -> Vec<(String, u128)> {
+        let amount = tx.amount as u128;
+        let fee = self.flat_fee as u128 + tx.max_fee as u128;
+        match self.fee_payers.get(&tx.sender) {
+            Some(sponsor) if *sponsor != tx.sender && fee > 0 => {
+                vec![(tx.sender.clone(), amount), (sponsor.clone(), fee)]
+            }
+            _ => vec![(tx.sender.clone(), amount + fee)],
+        }
+    }
+}
+
+/// Pending outflow of one account.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PendingSpend {
+    /// Total µBLEEP the account's pending transactions will debit.
+    pub outflow:  u128,
+    /// Pending transactions that debit the account.
+    pub tx_count: u32,
+}
+
+/// Per-account pending outflow.
+#[derive(Debug, Default)]
+pub struct PendingSpendLedger {
+    accounts: HashMap<String, PendingSpend>,
+}
+
+impl PendingSpendLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rebuild from the pool's current transactions.
+    pub fn rebuild<'a>(model: &SpendModel, txs: impl IntoIterator<Item = &'a ZKTransaction>) -> Self {
+        let mut ledger = Self::new();
+        for tx in txs {
+            ledger.add(&model.outflows(tx));
+        }
+        ledger
+    }
+
+    pub fn get(&self, account: &str) -> PendingSpend {
+        self.accounts.get(account).copied().unwrap_or_default()
+    }
+
+    /// The first account in `outflows` whose confirmed balance does not
+    /// cover its pending outflow plus the new one, if any.
+    pub fn shortfall(&self, outflows: &[(String, u128)], balance: &dyn Fn(&str) -> u128) -> Option<String> {
+        outflows.iter()
+            .find(|(account, amount)| {
+                let pending = self.get(account).outflow;
+                pending.saturating_add(*amount) > balance(account)
+            })
+            .map(|(account, _)| account.clone())
+    }
+
+    pub fn add(&mut self, outflows: &[(String, u128)]) {
+        for (account, amount) in outflows {
+            let e = self.accounts.entry(account.clone()).or_default();
+            e.outflow = e.outflow.saturating_add(*amount);
+            e.tx_count += 1;
+        }
+    }
+
+    pub fn remove(&mut self, outflows: &[(String, u128)]) {
+        for (account, amount) in outflows {
+            if let Some(e) = self.accounts.get_mut(account) {
+                e.outflow = e.outflow.saturating_sub(*amount);
+                e.tx_count = e.tx_count.saturating_sub(1);
+                if e.tx_count == 0 {
+                    self.accounts.remove(account);
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.accounts.clear();
+    }
+}
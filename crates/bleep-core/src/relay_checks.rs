@@ -0,0 +1,216 @@
+//! Relay-time checks for gossiped transactions and blocks.
+//!
+//! `CoreRelayValidator` is the `bleep_p2p::RelayValidator` a node plugs into
+//! its `GossipRouter`'s `RelayGuard`.  It only does what can be decided from
+//! the bytes alone, so a relay pays microseconds instead of milliseconds per
+//! junk payload:
+//!
+//! | Topic          | Checks                                                  |
+//! |----------------|---------------------------------------------------------|
+//! | `Transactions` | size cap, JSON `ZKTransaction`, non-empty parties,      |
+//! |                | `pk(64) ‖ sig(49856)` signature length, dust floor      |
+//! | `Blocks`       | size cap, JSON `Block`, signer in the epoch validator   |
+//! |                | set, SPHINCS+ header signature                          |
+//!
+//! Transfers carry no fee field — the flat per-transaction fee is charged at
+//! execution — so the relay fee floor is enforced as a minimum `amount`.
+//! Transaction signatures are *not* verified here; that is the job of the
+//! mempool, and is what makes the relay check cheap.
+
+use std::collections::HashSet;
+
+use bleep_p2p::gossip_router::Topic;
+use bleep_p2p::relay_guard::{RelayReject, RelayValidator};
+use parking_lot::RwLock;
+
+use crate::block::{Block, VALIDATOR_SIG_LEN};
+use crate::transaction::ZKTransaction;
+
+/// Largest transaction payload a relay will forward.
+pub const DEFAULT_MAX_TX_BYTES: usize = 256 * 1024;
+/// Largest block payload a relay will forward.
+pub const DEFAULT_MAX_BLOCK_BYTES: usize = 64 * 1024 * 1024;
+
+/// Validator keys allowed to sign blocks in the current epoch.
+#[derive(Debug, Default)]
+pub struct EpochValidatorSet {
+    epoch: u64,
+    keys:  HashSet<Vec<u8>>,
+}
+
+/// Stateless relay screening for the core gossip topics.
+pub struct CoreRelayValidator {
+    max_tx_bytes:    usize,
+    max_block_bytes: usize,
+    min_amount:      u64,
+    validators:      RwLock<EpochValidatorSet>,
+}
+
+impl Default for CoreRelayValidator {
+    fn default() -> Self {
+        CoreRelayValidator {
+            max_tx_bytes:    DEFAULT_MAX_TX_BYTES,
+            max_block_bytes: DEFAULT_MAX_BLOCK_BYTES,
+            min_amount:      1,
+            validators:      RwLock::new(EpochValidatorSet::default()),
+        }
+    }
+}
+
+impl CoreRelayValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_tx_bytes(mut self, max: usize) -> Self {
+        self.max_tx_bytes = max;
+        self
+    }
+
+    pub fn with_max_block_bytes(mut self, max: usize) -> Self {
+        self.max_block_bytes = max;
+        self
+    }
+
+    /// Minimum transfer amount a relay will forward.
+    pub fn with_min_amount(mut self, min: u64) -> Self {
+        self.min_amount = min;
+        self
+    }
+
+    /// Replace the signer set on epoch rotation.
+    pub fn set_validators(&self, epoch: u64, keys: impl IntoIterator<Item = Vec<u8>>) {
+        *self.validators.write() = EpochValidatorSet { epoch, keys: keys.into_iter().collect() };
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.validators.read().epoch
+    }
+
+    fn check_tx(&self, payload: &[u8]) -> Result<(), RelayReject> {
+        if payload.len() > self.max_tx_bytes {
+            return Err(RelayReject::Oversize { size: payload.len(), max: self.max_tx_bytes });
+        }
+        let tx: ZKTransaction = serde_json::from_slice(payload)
+            .map_err(|e| RelayReject::Malformed(e.to_string()))?;
+        if tx.sender.is_empty() || tx.receiver.is_empty() {
+            return Err(RelayReject::Malformed("empty sender or receiver".into()));
+        }
+        if tx.signature.len() != VALIDATOR_SIG_LEN {
+            return Err(RelayReject::MissingSignature);
+        }
+        if tx.amount < self.min_amount {
+            return Err(RelayReject::BelowFeeFloor);
+        }
+        Ok(())
+    }
+
+    fn check_block(&self, payload: &[u8]) -> Result<(), RelayReject> {
+        if payload.len() > self.max_block_bytes {
+            return Err(RelayReject::Oversize { size: payload.len(), max: self.max_block_bytes });
+        }
+        let block: Block = serde_json::from_slice(payload)
+            .map_err(|e| RelayReject::Malformed(e.to_string()))?;
+        let signer = block.signer_public_key().ok_or(RelayReject::MissingSignature)?;
+        if !self.validators.read().keys.contains(signer) {
+            return Err(RelayReject::UnknownSigner);
+        }
+        if !block.verify_header_signature() {
+            return Err(RelayReject::BadSignature);
+        }
+        Ok(())
+    }
+}
+
+impl RelayValidator for CoreRelayValidator {
+    fn check(&self, topic: &Topic, payload: &[u8]) -> Result<(), RelayReject> {
+        match topic {
+            Topic::Transactions => self.check_tx(payload),
+            Topic::Blocks => self.check_block(payload),
+            Topic::Headers | Topic::AddressEvents(_) | Topic::Evidence => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::ChainId;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload, tx_payload, verify_tx_signature};
+    use std::time::Instant;
+
+    fn signed_tx(amount: u64) -> ZKTransaction {
+        let (pk, sk) = generate_tx_keypair();
+        let payload = tx_payload("alice", "bob", amount, 1);
+        let mut signature = pk;
+        signature.extend(sign_tx_payload(&payload, &sk).unwrap());
+        ZKTransaction { sender: "alice".into(), receiver: "bob".into(), amount, timestamp: 1, signature, max_fee: 0, tip: 0, nonce: 0, chain_id: ChainId::NONE }
+    }
+
+    fn signed_block() -> (Block, Vec<u8>) {
+        let (pk, sk) = generate_tx_keypair();
+        let mut block = Block::new(1, vec![], "0".repeat(64));
+        block.sign_block_with_pk(&sk, &pk).unwrap();
+        (block, pk)
+    }
+
+    #[test]
+    fn tx_checks_reject_junk_and_pass_signed_transfers() {
+        let v = CoreRelayValidator::new().with_min_amount(10);
+        let ok = serde_json::to_vec(&signed_tx(50)).unwrap();
+        assert_eq!(v.check(&Topic::Transactions, &ok), Ok(()));
+
+        assert!(matches!(v.check(&Topic::Transactions, b"\x00garbage"), Err(RelayReject::Malformed(_))));
+        let mut unsigned = signed_tx(50);
+        unsigned.signature.clear();
+        let unsigned = serde_json::to_vec(&unsigned).unwrap();
+        assert_eq!(v.check(&Topic::Transactions, &unsigned), Err(RelayReject::MissingSignature));
+        let dust = serde_json::to_vec(&signed_tx(1)).unwrap();
+        assert_eq!(v.check(&Topic::Transactions, &dust), Err(RelayReject::BelowFeeFloor));
+        let tight = CoreRelayValidator::new().with_max_tx_bytes(1024);
+        assert!(matches!(tight.check(&Topic::Transactions, &ok), Err(RelayReject::Oversize { .. })));
+    }
+
+    #[test]
+    fn block_from_non_validator_is_rejected() {
+        let v = CoreRelayValidator::new();
+        let (block, pk) = signed_block();
+        let bytes = serde_json::to_vec(&block).unwrap();
+        assert_eq!(v.check(&Topic::Blocks, &bytes), Err(RelayReject::UnknownSigner));
+
+        v.set_validators(1, [pk]);
+        assert_eq!(v.check(&Topic::Blocks, &bytes), Ok(()));
+
+        let mut tampered = block;
+        tampered.index = 2;
+        let tampered = serde_json::to_vec(&tampered).unwrap();
+        assert_eq!(v.check(&Topic::Blocks, &tampered), Err(RelayReject::BadSignature));
+    }
+
+    /// Relay screening must stay cheaper than the signature check the
+    /// mempool performs on the same transaction.
+    #[test]
+    fn bench_tx_relay_check_vs_full_signature_verification() {
+        const ROUNDS: u32 = 20;
+        let v = CoreRelayValidator::new();
+        let tx = signed_tx(50);
+        let bytes = serde_json::to_vec(&tx).unwrap();
+        let payload = tx.signed_payload();
+        let (pk, sig) = tx.signature.split_at(64);
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            v.check(&Topic::Transactions, &bytes).unwrap();
+        }
+        let relay = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            assert!(verify_tx_signature(&payload, sig, pk));
+        }
+        let full = start.elapsed();
+
+        println!("relay check {:?}/tx, full verify {:?}/tx", relay / ROUNDS, full / ROUNDS);
+        assert!(relay < full, "relay check ({:?}) not cheaper than verification ({:?})", relay, full);
+    }
+}
@@ -0,0 +1,514 @@
+//! # Scheduled transactions
+//!
+//! Holds fully signed transactions until a release condition is met, so a
+//! vesting release or delayed payout does not need the sender's machine
+//! online at the right moment.
+//!
+//! ```text
+//!   schedule ─► signature + replay check ─► encrypted record in the store WAL
+//!   every block: expiry reached?  ─► Expired   (dropped)
+//!                release reached? ─► re-check (replay, balance) ─► TransactionPool
+//!                                                      └─ failure ─► Failed
+//! ```
+//!
+//! Entries are AES-256-GCM encrypted at rest under a node-local key
+//! (`nonce(12) || ciphertext`) and kept in a write-ahead log,
+//! `<dir>/scheduled.wal`: an `Add` record when scheduled, a `Done` record
+//! when released, expired or cancelled.  The entry id
+//! is the hex SHA3-256 transaction payload, so a transaction can only be
+//! scheduled once.  Every outcome is published as a `ScheduleEvent` on a
+//! broadcast channel and kept in a short history for the RPC.
+//!
+//! A scheduled entry can be cancelled with a signature over
+//! `cancel_payload(id)` by the key that signed the transaction.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tokio::sync::broadcast;
+
+use bleep_crypto::tx_signer::verify_tx_signature;
+
+use crate::pending_spend::BalanceLookup;
+use crate::transaction::ZKTransaction;
+use crate::transaction_pool::{signature_valid, signer_public_key, TransactionPool};
+use crate::wal::{Wal, WalOptions, WalStats};
+
+/// Domain prefix of the payload a cancellation signs.
+const CANCEL_DOMAIN: &[u8] = b"BLEEP Cancel Scheduled Tx";
+/// Events kept for `recent_events`.
+const EVENT_HISTORY: usize = 256;
+/// Capacity of the event broadcast channel.
+pub const SCHEDULE_EVENT_CAPACITY: usize = 256;
+
+/// A block height or unix timestamp (seconds) the chain must reach.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    Height(u64),
+    Timestamp(u64),
+}
+
+impl Trigger {
+    pub fn reached(&self, height: u64, now: u64) -> bool {
+        match *self {
+            Trigger::Height(h) => height >= h,
+            Trigger::Timestamp(t) => now >= t,
+        }
+    }
+}
+
+/// A held transaction and when to release it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledTx {
+    pub id:         String,
+    pub tx:         ZKTransaction,
+    /// Not released before this.
+    pub release:    Trigger,
+    /// Dropped once this is reached without a release.
+    pub expiry:     Option<Trigger>,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduleOutcome {
+    Released,
+    Expired,
+    Failed { reason: String },
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleEvent {
+    pub id:      String,
+    pub sender:  String,
+    pub height:  u64,
+    #[serde(flatten)]
+    pub outcome: ScheduleOutcome,
+}
+
+/// Store WAL record; `sealed` is the encrypted `ScheduledTx`.
+#[derive(Serialize, Deserialize)]
+enum StoreRecord {
+    Add { id: String, sealed: Vec<u8> },
+    Done { id: String },
+}
+
+/// Entry id of `tx`: hex of its canonical signed payload.
+pub fn schedule_id(tx: &ZKTransaction) -> String {
+    hex::encode(tx.signed_payload())
+}
+
+/// What the sender signs to cancel entry `id`.
+pub fn cancel_payload(id: &str) -> [u8; 32] {
+    let mut h = Sha3_256::new();
+    h.update(CANCEL_DOMAIN);
+    h.update(id.as_bytes());
+    h.finalize().into()
+}
+
+/// Read the 32-byte store key at `path`, generating and writing a fresh one
+/// on first start.
+pub fn load_or_create_key<P: AsRef<Path>>(path: P) -> Result<[u8; 32], String> {
+    let path = path.as_ref();
+    if let Ok(bytes) = fs::read(path) {
+        return bytes.as_slice().try_into()
+            .map_err(|_| format!("{}: expected 32 key bytes, found {}", path.display(), bytes.len()));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("create {}: {}", parent.display(), e))?;
+    }
+    let key: [u8; 32] = Aes256Gcm::generate_key(&mut OsRng).into();
+    fs::write(path, key).map_err(|e| format!("write {}: {}", path.display(), e))?;
+    Ok(key)
+}
+
+/// Time-/height-locked transaction holder.
+pub struct TxScheduler {
+    wal:      Wal<StoreRecord>,
+    cipher:   Aes256Gcm,
+    pool:     Arc<TransactionPool>,
+    balances: Option<BalanceLookup>,
+    entries:  Mutex<BTreeMap<String, ScheduledTx>>,
+    history:  Mutex<VecDeque<ScheduleEvent>>,
+    events:   broadcast::Sender<ScheduleEvent>,
+}
+
+impl TxScheduler {
+    /// Open the store at `dir` (created if missing) and load every entry.
+    pub fn open<P: AsRef<Path>>(dir: P, key: &[u8; 32], pool: Arc<TransactionPool>) -> Result<Self, String> {
+        Self::open_with_options(dir, key, pool, WalOptions::default())
+    }
+
+    /// As `open`, with explicit fsync and compaction settings for the store WAL.
+    pub fn open_with_options<P: AsRef<Path>>(
+        dir:  P,
+        key:  &[u8; 32],
+        pool: Arc<TransactionPool>,
+        opts: WalOptions,
+    ) -> Result<Self, String> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|e| format!("create {}: {}", dir.display(), e))?;
+        let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+        let (wal, recovery) = Wal::open(dir.join("scheduled.wal"), opts)?;
+        let mut sealed = BTreeMap::new();
+        for record in recovery.records {
+            match record {
+                StoreRecord::Add { id, sealed: blob } => { sealed.insert(id, blob); }
+                StoreRecord::Done { id } => { sealed.remove(&id); }
+            }
+        }
+        let mut entries = BTreeMap::new();
+        for (id, blob) in &sealed {
+            let sched = decrypt_entry(&cipher, blob).map_err(|e| format!("entry {}: {}", id, e))?;
+            entries.insert(id.clone(), sched);
+        }
+        // Start from a snapshot of the live entries so the log stays bounded.
+        let snapshot: Vec<StoreRecord> = sealed.into_iter()
+            .map(|(id, sealed)| StoreRecord::Add { id, sealed })
+            .collect();
+        wal.compact(&snapshot)?;
+        log::info!("[TxScheduler] Loaded {} scheduled transactions from {}", entries.len(), dir.display());
+
+        let (events, _) = broadcast::channel(SCHEDULE_EVENT_CAPACITY);
+        Ok(TxScheduler {
+            wal,
+            cipher,
+            pool,
+            balances: None,
+            entries: Mutex::new(entries),
+            history: Mutex::new(VecDeque::new()),
+            events,
+        })
+    }
+
+    /// Check the sender's confirmed balance before each release.
+    pub fn with_balances(mut self, balances: BalanceLookup) -> Self {
+        self.balances = Some(balances);
+        self
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ScheduleEvent> {
+        self.events.subscribe()
+    }
+
+    /// Most recent outcomes, oldest first.
+    pub fn recent_events(&self) -> Vec<ScheduleEvent> {
+        self.history.lock().iter().cloned().collect()
+    }
+
+    /// Pending entries, optionally only those sent by `sender`.
+    pub fn list(&self, sender: Option<&str>) -> Vec<ScheduledTx> {
+        self.entries.lock().values()
+            .filter(|s| sender.map_or(true, |a| s.tx.sender == a))
+            .cloned()
+            .collect()
+    }
+
+    /// Hold `tx` until `release`.  `height`/`now` are the chain's current
+    /// height and time.  Returns the entry id.
+    pub async fn schedule(
+        &self,
+        tx:      ZKTransaction,
+        release: Trigger,
+        expiry:  Option<Trigger>,
+        height:  u64,
+        now:     u64,
+    ) -> Result<String, String> {
+        if tx.amount == 0 || tx.sender.is_empty() || tx.receiver.is_empty() || tx.sender == tx.receiver {
+            return Err("malformed transaction".into());
+        }
+        if !signature_valid(&tx) {
+            return Err("invalid transaction signature".into());
+        }
+        if let Some(exp) = expiry {
+            if exp.reached(height, now) {
+                return Err("expiry already reached".into());
+            }
+            let before_release = match (release, exp) {
+                (Trigger::Height(r), Trigger::Height(e)) | (Trigger::Timestamp(r), Trigger::Timestamp(e)) => e <= r,
+                _ => false,
+            };
+            if before_release {
+                return Err("expiry must come after release".into());
+            }
+        }
+        // The pool rejects replays; a transaction it has already seen can
+        // never be released.
+        if self.pool.has_seen(&tx).await {
+            return Err("transaction already submitted".into());
+        }
+
+        let id = schedule_id(&tx);
+        let sched = ScheduledTx { id: id.clone(), tx, release, expiry, created_at: now };
+        let mut entries = self.entries.lock();
+        if entries.contains_key(&id) {
+            return Err(format!("transaction {} already scheduled", id));
+        }
+        self.write_entry(&sched)?;
+        entries.insert(id.clone(), sched);
+        log::info!("[TxScheduler] Scheduled {} ({:?}, expiry {:?})", id, release, expiry);
+        Ok(id)
+    }
+
+    /// Cancel entry `id`.  `signature` is `pk(64) || sig` over
+    /// `cancel_payload(id)` and must come from the transaction's signer.
+    pub fn cancel(&self, id: &str, signature: &[u8], height: u64) -> Result<(), String> {
+        let mut entries = self.entries.lock();
+        let sched = entries.get(id).ok_or_else(|| format!("no scheduled transaction {}", id))?;
+        let owner = signer_public_key(&sched.tx).ok_or("scheduled transaction has no signer")?;
+        if signature.len() <= owner.len() || &signature[..owner.len()] != owner {
+            return Err("cancellation not signed by the transaction's key".into());
+        }
+        if !verify_tx_signature(&cancel_payload(id), &signature[owner.len()..], owner) {
+            return Err("invalid cancellation signature".into());
+        }
+        let sched = entries.remove(id).expect("entry checked above");
+        drop(entries);
+        self.finish(sched, ScheduleOutcome::Cancelled, height);
+        Ok(())
+    }
+
+    /// Release or drop every entry whose condition is met at `height`/`now`.
+    /// Call once per committed block.
+    pub async fn process(&self, height: u64, now: u64) -> Vec<ScheduleEvent> {
+        let due: Vec<ScheduledTx> = {
+            let mut entries = self.entries.lock();
+            let ids: Vec<String> = entries.values()
+                .filter(|s| s.release.reached(height, now) || s.expiry.is_some_and(|e| e.reached(height, now)))
+                .map(|s| s.id.clone())
+                .collect();
+            ids.iter().filter_map(|id| entries.remove(id)).collect()
+        };
+
+        let mut events = Vec::with_capacity(due.len());
+        for sched in due {
+            let outcome = if sched.expiry.is_some_and(|e| e.reached(height, now)) {
+                ScheduleOutcome::Expired
+            } else {
+                self.release(&sched).await
+            };
+            events.push(self.finish(sched, outcome, height));
+        }
+        events
+    }
+
+    /// Re-check and submit; the pool's own admission is the final word.
+    async fn release(&self, sched: &ScheduledTx) -> ScheduleOutcome {
+        let tx = &sched.tx;
+        if self.pool.has_seen(tx).await {
+            return ScheduleOutcome::Failed { reason: "stale: transaction already submitted".into() };
+        }
+        if let Some(balances) = &self.balances {
+            let balance = balances(&tx.sender);
+            if balance < tx.amount as u128 {
+                return ScheduleOutcome::Failed {
+                    reason: format!("insufficient balance: {} < {}", balance, tx.amount),
+                };
+            }
+        }
+        if self.pool.add_transaction(tx.clone()).await {
+            ScheduleOutcome::Released
+        } else {
+            ScheduleOutcome::Failed { reason: "rejected by the transaction pool".into() }
+        }
+    }
+
+    fn finish(&self, sched: ScheduledTx, outcome: ScheduleOutcome, height: u64) -> ScheduleEvent {
+        if let Err(e) = self.wal.append(&StoreRecord::Done { id: sched.id.clone() }) {
+            log::warn!("[TxScheduler] log removal of {}: {}", sched.id, e);
+        }
+        self.compact_if_needed();
+        let event = ScheduleEvent { id: sched.id, sender: sched.tx.sender, height, outcome };
+        match &event.outcome {
+            ScheduleOutcome::Failed { reason } => log::warn!("[TxScheduler] {} failed: {}", event.id, reason),
+            other => log::info!("[TxScheduler] {} {:?}", event.id, other),
+        }
+        {
+            let mut history = self.history.lock();
+            if history.len() == EVENT_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(event.clone());
+        }
+        let _ = self.events.send(event.clone());
+        event
+    }
+
+    /// Flush the store WAL; call once per committed block under `FsyncPolicy::OnCommit`.
+    pub fn wal_commit(&self) -> Result<(), String> {
+        self.wal.commit()
+    }
+
+    /// Flush the store WAL if its fsync interval has passed.
+    pub fn wal_sync_if_due(&self) -> Result<(), String> {
+        self.wal.sync_if_due()
+    }
+
+    pub fn wal_stats(&self) -> WalStats {
+        self.wal.stats()
+    }
+
+    fn write_entry(&self, sched: &ScheduledTx) -> Result<(), String> {
+        let sealed = self.seal(sched)?;
+        self.wal.append(&StoreRecord::Add { id: sched.id.clone(), sealed })
+    }
+
+    fn seal(&self, sched: &ScheduledTx) -> Result<Vec<u8>, String> {
+        let plain = serde_json::to_vec(sched).map_err(|e| e.to_string())?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ct = self.cipher.encrypt(&nonce, plain.as_slice())
+            .map_err(|e| format!("encrypt: {}", e))?;
+        let mut blob = nonce.to_vec();
+        blob.extend_from_slice(&ct);
+        Ok(blob)
+    }
+
+    /// Rewrite the WAL as one `Add` per pending entry once it is over its
+    /// size threshold.  Holds `entries` so no `Add` is lost to the rewrite.
+    fn compact_if_needed(&self) {
+        if !self.wal.needs_compaction() {
+            return;
+        }
+        let entries = self.entries.lock();
+        let snapshot: Result<Vec<StoreRecord>, String> = entries.values()
+            .map(|s| Ok(StoreRecord::Add { id: s.id.clone(), sealed: self.seal(s)? }))
+            .collect();
+        if let Err(e) = snapshot.and_then(|records| self.wal.compact(&records)) {
+            log::error!("[TxScheduler] WAL compaction failed: {}", e);
+        }
+    }
+}
+
+fn decrypt_entry(cipher: &Aes256Gcm, blob: &[u8]) -> Result<ScheduledTx, String> {
+    if blob.len() < 12 + 16 {
+        return Err("entry too short".into());
+    }
+    let (nonce, ct) = blob.split_at(12);
+    let plain = cipher.decrypt(Nonce::from_slice(nonce), ct)
+        .map_err(|_| "decryption failed — wrong key or corrupted entry".to_string())?;
+    serde_json::from_slice(&plain).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::ChainId;
+    use std::path::PathBuf;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload, tx_payload};
+
+    const KEY: [u8; 32] = [7u8; 32];
+
+    fn scratch(tag: &str) -> PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("bleep-sched-{}-{}-{}", tag, std::process::id(), nanos))
+    }
+
+    /// `pk || sig` over `payload` — the wire format of tx and cancel signatures.
+    fn wire_sig(pk: &[u8], sk: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut sig = pk.to_vec();
+        sig.extend(sign_tx_payload(payload, sk).unwrap());
+        sig
+    }
+
+    fn signed_tx(pk: &[u8], sk: &[u8], amount: u64) -> ZKTransaction {
+        let payload = tx_payload("alice", "bob", amount, 1_700_000_000);
+        ZKTransaction {
+            sender:    "alice".into(),
+            receiver:  "bob".into(),
+            amount,
+            timestamp: 1_700_000_000,
+            signature: wire_sig(pk, sk, &payload),
+            max_fee:   0,
+            tip:       0,
+            nonce:     0,
+            chain_id:  ChainId::NONE,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_height_locked_tx_released_at_target_height() {
+        let (pk, sk) = generate_tx_keypair();
+        let pool = TransactionPool::new(100);
+        let dir = scratch("height");
+        let sched = TxScheduler::open(&dir, &KEY, Arc::clone(&pool)).unwrap();
+        sched.schedule(signed_tx(&pk, &sk, 50), Trigger::Height(10), None, 3, 0).await.unwrap();
+
+        // Survives a restart, and the sender is not readable on disk.
+        drop(sched);
+        let raw = fs::read(dir.join("scheduled.wal")).unwrap();
+        assert!(!raw.windows(5).any(|w| w == b"alice"));
+        let sched = TxScheduler::open(&dir, &KEY, Arc::clone(&pool)).unwrap();
+        assert_eq!(sched.list(Some("alice")).len(), 1);
+
+        assert!(sched.process(9, 0).await.is_empty());
+        assert_eq!(pool.pool_size().await, 0);
+        let events = sched.process(10, 0).await;
+        assert_eq!(events[0].outcome, ScheduleOutcome::Released);
+        assert_eq!(pool.pool_size().await, 1);
+        assert!(sched.list(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_dropped_with_event() {
+        let (pk, sk) = generate_tx_keypair();
+        let pool = TransactionPool::new(100);
+        let sched = TxScheduler::open(scratch("expiry"), &KEY, Arc::clone(&pool)).unwrap();
+        let mut rx = sched.subscribe();
+        sched.schedule(
+            signed_tx(&pk, &sk, 50), Trigger::Timestamp(2_000), Some(Trigger::Height(12)), 1, 1_000,
+        ).await.unwrap();
+
+        sched.process(12, 1_500).await;
+        let event = rx.recv().await.unwrap();
+        assert_eq!(event.outcome, ScheduleOutcome::Expired);
+        assert_eq!(pool.pool_size().await, 0);
+        assert!(sched.list(None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stale_release_fails_and_notifies() {
+        let (pk, sk) = generate_tx_keypair();
+        let pool = TransactionPool::new(100);
+        let sched = TxScheduler::open(scratch("stale"), &KEY, Arc::clone(&pool)).unwrap();
+        let tx = signed_tx(&pk, &sk, 50);
+        sched.schedule(tx.clone(), Trigger::Height(5), None, 1, 0).await.unwrap();
+
+        // The sender broadcast the same transaction by hand meanwhile.
+        assert!(pool.add_transaction(tx).await);
+        let events = sched.process(5, 0).await;
+        assert!(matches!(&events[0].outcome, ScheduleOutcome::Failed { reason } if reason.starts_with("stale")));
+        assert_eq!(sched.recent_events(), events);
+        assert_eq!(pool.pool_size().await, 1, "nothing submitted twice");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_requires_signer_key() {
+        let (pk, sk) = generate_tx_keypair();
+        let (other_pk, other_sk) = generate_tx_keypair();
+        let pool = TransactionPool::new(100);
+        let sched = TxScheduler::open(scratch("cancel"), &KEY, pool).unwrap();
+        let id = sched.schedule(signed_tx(&pk, &sk, 50), Trigger::Height(5), None, 1, 0).await.unwrap();
+
+        let wrong = wire_sig(&other_pk, &other_sk, &cancel_payload(&id));
+        assert!(sched.cancel(&id, &wrong, 2).is_err());
+        assert_eq!(sched.list(None).len(), 1);
+
+        let right = wire_sig(&pk, &sk, &cancel_payload(&id));
+        sched.cancel(&id, &right, 2).unwrap();
+        assert!(sched.list(None).is_empty());
+        assert_eq!(sched.recent_events()[0].outcome, ScheduleOutcome::Cancelled);
+    }
+}
@@ -1,3 +1,4 @@
+use bleep_crypto::chain_id::ChainId;
 use bleep_crypto::quantum_secure::QuantumSecure;
 use serde::{Serialize, Deserialize};
 use chrono::Utc;
@@ -81,6 +82,22 @@ pub struct ZKTransaction {
     pub amount: u64,
     pub timestamp: u64,
     pub signature: Vec<u8>,
+    /// Most the sender pays per inclusion (base fee + tip), in µBLEEP.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub max_fee: u64,
+    /// Offered to the proposer on top of the base fee.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub tip: u64,
+    /// Position in the sender's transaction sequence, from 0.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub nonce: u64,
+    /// Network the transaction is signed for; `ChainId::NONE` when unbound.
+    #[serde(default, skip_serializing_if = "ChainId::is_none")]
+    pub chain_id: ChainId,
+}
+
+pub(crate) fn is_zero(v: &u64) -> bool {
+    *v == 0
 }
 
 impl ZKTransaction {
@@ -95,9 +112,20 @@ impl ZKTransaction {
             amount,
             timestamp,
             signature,
+            max_fee: 0,
+            tip: 0,
+            nonce: 0,
+            chain_id: ChainId::NONE,
         }
     }
 
+    /// Canonical payload the wire signature covers, bound to `chain_id`.
+    pub fn signed_payload(&self) -> [u8; 32] {
+        self.chain_id.bind(bleep_crypto::tx_signer::tx_payload_with_nonce(
+            &self.sender, &self.receiver, self.amount, self.timestamp, self.max_fee, self.tip, self.nonce,
+        ))
+    }
+
     /// Verifies transaction validity using quantum-safe signatures (SPHINCS+)
     pub fn verify(&self, quantum_secure: &QuantumSecure) -> bool {
         let data = format!("{}{}{}{}", self.sender, self.receiver, self.amount, self.timestamp);
@@ -105,6 +133,23 @@ impl ZKTransaction {
     }
 }
 
+/// A confirmed transaction in the pool's form, e.g. when a reorg returns it.
+impl From<&crate::block::Transaction> for ZKTransaction {
+    fn from(tx: &crate::block::Transaction) -> Self {
+        Self {
+            sender:    tx.sender.clone(),
+            receiver:  tx.receiver.clone(),
+            amount:    tx.amount,
+            timestamp: tx.timestamp,
+            signature: tx.signature.clone(),
+            max_fee:   tx.max_fee,
+            tip:       tx.tip,
+            nonce:     tx.nonce,
+            chain_id:  tx.chain_id,
+        }
+    }
+}
+
 /// Consensus message types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ConsensusMessage {
@@ -1,9 +1,42 @@
 //! # TransactionPool
 //!
+//! With spend tracking enabled (`with_spend_tracking`), every admission,
+//! removal, reorg return and reload keeps a `PendingSpendLedger` in step
+//! with the pool, and a transaction is admitted only if each account it
+//! debits can cover it on top of what is already pending.
+//!
+//! With a write-ahead log attached (`recover_from_wal`), every change to the
+//! pending set is appended as a `PoolRecord`, so a crashed node rebuilds the
+//! exact pool it had instead of starting empty.
+//!
+//! With a base fee tracker attached (`set_base_fee`), a transaction is
+//! admitted only if its `max_fee` covers the base fee the next block
+//! charges, and `evict_below` drops the ones a rising fee has priced out.
+//!
+//! With a key check attached (`set_key_check`), a transaction is admitted
+//! only if its signer may sign for the sender — accounts that rotated
+//! their key stop accepting the old one once its grace period ends.  Key
+//! changes (`bleep_crypto::key_change`) carry no amount and may append a
+//! recovery co-signature, which block execution verifies.  So do reward
+//! transactions (`REWARD_TX_PREFIX`) and unbonds (`STAKING_TX_PREFIX`).
+//!
+//! With a nonce lookup attached (`set_nonce_lookup`), each sender's pending
+//! transactions form a gapless run from its confirmed nonce.  A nonce past
+//! the end of the run waits in a queue until the gap fills; a nonce inside
+//! it replaces the pending transaction with that nonce if it pays a higher
+//! tip, which is how a stuck transaction is bumped.  Queued transactions
+//! are not written to the WAL.
+use crate::block::ChainId;
+use crate::base_fee::{BaseFeeTracker, REWARD_TX_PREFIX, STAKING_TX_PREFIX};
+use crate::pending_spend::{BalanceLookup, PendingSpend, PendingSpendLedger, SpendModel};
 use crate::transaction::ZKTransaction;
-use std::collections::{HashSet, VecDeque};
+use crate::wal::{Wal, WalRecovery, WalStats};
+use bleep_crypto::key_change::{is_key_change, key_hash, split_cosigned};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::time::Duration;
 use tokio::sync::Mutex;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use sha2::{Digest, Sha256};
 use hex;
 
@@ -13,6 +46,9 @@ const MIN_SIG_LEN: usize = 164;
 /// Expected SPHINCS+ public key length for sphincsshake256fsimple: 64 bytes
 const SPHINCS_PK_LEN: usize = 64;
 
+/// How far past a sender's next nonce a transaction may be queued.
+pub const MAX_NONCE_GAP: u64 = 64;
+
 // ── TransactionPool ───────────────────────────────────────────────────────────
 
 /// FIFO transaction pool with SPHINCS+ signature verification on admission.
@@ -23,29 +59,250 @@ pub struct TransactionPool {
     seen_hashes: Mutex<HashSet<[u8; 32]>>,
     /// Maximum number of pending transactions.
     max_size: usize,
+    /// Pending outflow per account.  Always locked after `pool`.
+    spend: Mutex<PendingSpendLedger>,
+    spend_model: SpendModel,
+    /// Confirmed balances; `None` disables the overspend check.
+    balances: Option<BalanceLookup>,
+    /// Write-ahead log of pool changes.  Appended under the `pool` lock.
+    wal: OnceLock<Wal<PoolRecord>>,
+    /// Admission floor; unset = no fee market.
+    base_fee: OnceLock<Arc<BaseFeeTracker>>,
+    /// Signer authorization; unset = any valid signer.
+    key_check: OnceLock<KeyCheck>,
+    /// Confirmed account nonces; unset = nonces are not checked.
+    nonce_lookup: OnceLock<NonceLookup>,
+    /// Chain admitted transactions must be signed for; unset = any.
+    chain_id: OnceLock<ChainId>,
+    /// Transactions waiting for a lower nonce, by sender and nonce.  Always
+    /// locked after `seen_hashes`.
+    queued: Mutex<BTreeMap<(String, u64), ZKTransaction>>,
+}
+
+/// Whether the key hashing to the second argument may sign for the account
+/// named by the first, e.g. `StateManager::key_authorized`.
+pub type KeyCheck = Arc<dyn Fn(&str, &[u8; 32]) -> bool + Send + Sync>;
+
+/// Nonce the next confirmed transaction from an account must carry, e.g.
+/// `StateManager::get_nonce`.
+pub type NonceLookup = Arc<dyn Fn(&str) -> u64 + Send + Sync>;
+
+/// One change to the pending set, as written to the pool's WAL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PoolRecord {
+    Admit(ZKTransaction),
+    /// Canonical id of a confirmed transaction.
+    Remove(String),
+    /// Reorged transactions put back at the front, in block order.
+    Return(Vec<ZKTransaction>),
+    Clear,
+    /// Whole pool; written by `reload` and as the compaction snapshot.
+    Reset(Vec<ZKTransaction>),
+    /// Pending transaction `old` (canonical id) replaced in place by a
+    /// higher-tip one with the same nonce.
+    Replace { old: String, new: ZKTransaction },
+}
+
+/// Rebuild the pending set from a replayed WAL.
+pub fn replay_pool_records(records: &[PoolRecord]) -> Vec<ZKTransaction> {
+    let mut pool: VecDeque<ZKTransaction> = VecDeque::new();
+    for record in records {
+        match record {
+            PoolRecord::Admit(tx) => pool.push_back(tx.clone()),
+            PoolRecord::Remove(id) => pool.retain(|tx| canonical_id(tx) != *id),
+            PoolRecord::Return(txs) => {
+                let pending: HashSet<String> = pool.iter().map(canonical_id).collect();
+                for tx in txs.iter().rev() {
+                    if !pending.contains(&canonical_id(tx)) {
+                        pool.push_front(tx.clone());
+                    }
+                }
+            }
+            PoolRecord::Clear => pool.clear(),
+            PoolRecord::Reset(txs) => pool = txs.iter().cloned().collect(),
+            PoolRecord::Replace { old, new } => match pool.iter().position(|tx| canonical_id(tx) == *old) {
+                Some(i) => pool[i] = new.clone(),
+                None => pool.push_back(new.clone()),
+            },
+        }
+    }
+    pool.into()
+}
+
+/// Outcome of `TransactionPool::recover_from_wal`.
+#[derive(Debug, Clone)]
+pub struct PoolRecovery {
+    pub replayed_records: usize,
+    pub restored:         usize,
+    /// Pending transactions that no longer validate against the chain.
+    pub discarded:        usize,
+    pub torn_tail_bytes:  u64,
+    pub elapsed:          Duration,
+    pub entries_per_sec:  f64,
+}
+
+/// Why `TransactionPool::admit` turned a transaction away.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AdmissionError {
+    #[error("pool is full ({capacity} pending)")]
+    PoolFull { capacity: usize },
+    #[error("sender is empty")]
+    MissingSender,
+    #[error("receiver is empty")]
+    MissingReceiver,
+    #[error("sender and receiver are the same account")]
+    SelfTransfer,
+    #[error("amount is zero")]
+    ZeroAmount,
+    #[error("timestamp is zero")]
+    ZeroTimestamp,
+    #[error("signature is {len} bytes, need at least {MIN_SIG_LEN}")]
+    SignatureTooShort { len: usize },
+    #[error("signature does not verify")]
+    BadSignature,
+    #[error("transaction already seen")]
+    Duplicate,
+    #[error("{account} cannot cover the transaction on top of its pending spends")]
+    InsufficientBalance { account: String },
+    #[error("max fee {max_fee} is below the base fee {base_fee}")]
+    FeeTooLow { max_fee: u64, base_fee: u64 },
+    #[error("signer key is not authorized for {account}")]
+    KeyNotAuthorized { account: String },
+    #[error("nonce {got} is already used, next is {expected}")]
+    NonceTooLow { expected: u64, got: u64 },
+    #[error("nonce {got} is more than {MAX_NONCE_GAP} past the next nonce {expected}")]
+    NonceGap { expected: u64, got: u64 },
+    #[error("replacing nonce {nonce} needs a tip above {tip}")]
+    ReplacementUnderpriced { nonce: u64, tip: u64 },
+    #[error("signed for chain {got}, this is chain {expected}")]
+    WrongChain { expected: ChainId, got: ChainId },
+}
+
+impl AdmissionError {
+    /// Stable machine-readable code, for API responses.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AdmissionError::PoolFull { .. }            => "pool_full",
+            AdmissionError::MissingSender
+            | AdmissionError::MissingReceiver
+            | AdmissionError::SelfTransfer
+            | AdmissionError::ZeroAmount
+            | AdmissionError::ZeroTimestamp            => "malformed",
+            AdmissionError::SignatureTooShort { .. }
+            | AdmissionError::BadSignature             => "bad_signature",
+            AdmissionError::KeyNotAuthorized { .. }    => "key_not_authorized",
+            AdmissionError::Duplicate                  => "duplicate",
+            AdmissionError::InsufficientBalance { .. } => "insufficient_balance",
+            AdmissionError::FeeTooLow { .. }           => "fee_too_low",
+            AdmissionError::NonceTooLow { .. }         => "nonce_too_low",
+            AdmissionError::NonceGap { .. }            => "nonce_gap",
+            AdmissionError::ReplacementUnderpriced { .. } => "replacement_underpriced",
+            AdmissionError::WrongChain { .. }          => "wrong_chain",
+        }
+    }
+}
+
+/// Canonical ID `"sender:receiver:amount:timestamp"`, as used by `remove_confirmed`.
+fn canonical_id(tx: &ZKTransaction) -> String {
+    format!("{}:{}:{}:{}", tx.sender, tx.receiver, tx.amount, tx.timestamp)
+}
+
+/// Nonce that follows `account`'s run of pending transactions from `confirmed`.
+fn next_pending_nonce(pool: &VecDeque<ZKTransaction>, account: &str, confirmed: u64) -> u64 {
+    let pending: HashSet<u64> = pool.iter().filter(|tx| tx.sender == account).map(|tx| tx.nonce).collect();
+    let mut next = confirmed;
+    while pending.contains(&next) {
+        next += 1;
+    }
+    next
+}
+
+/// SHA-256 of the signed payload, the key of `seen_hashes`.
+fn payload_hash(tx: &ZKTransaction) -> [u8; 32] {
+    Sha256::digest(tx.signed_payload()).into()
+}
+
+/// SPHINCS+ public key carried in the wire signature `pk(64) || sig`.
+pub fn signer_public_key(tx: &ZKTransaction) -> Option<&[u8]> {
+    if tx.signature.len() < MIN_SIG_LEN {
+        return None;
+    }
+    Some(&tx.signature[..SPHINCS_PK_LEN])
+}
+
+/// Whether a transaction to `receiver` may have amount 0: key changes,
+/// reward requests and unbonds move no funds of their own.
+pub fn carries_no_amount(receiver: &str) -> bool {
+    is_key_change(receiver)
+        || receiver.starts_with(REWARD_TX_PREFIX)
+        || receiver.starts_with(STAKING_TX_PREFIX)
+}
+
+/// Verify the wire signature against the canonical payload, as admission
+/// does.  A recovery co-signature on a key change is not checked here.
+pub fn signature_valid(tx: &ZKTransaction) -> bool {
+    let pk = match signer_public_key(tx) {
+        Some(pk) => pk,
+        None => return false,
+    };
+    let (primary, _) = split_cosigned(&tx.signature);
+    bleep_crypto::tx_signer::verify_tx_signature(&tx.signed_payload(), &primary[SPHINCS_PK_LEN..], pk)
 }
 
 impl TransactionPool {
     /// Create a new pool with the given capacity limit.
     pub fn new(max_size: usize) -> Arc<Self> {
-        Arc::new(Self {
+        Arc::new(Self::build(max_size, None, SpendModel::default()))
+    }
+
+    /// Create a pool that rejects transactions the debited accounts cannot
+    /// cover once their pending outflow is taken into account.
+    pub fn with_spend_tracking(max_size: usize, balances: BalanceLookup, model: SpendModel) -> Arc<Self> {
+        Arc::new(Self::build(max_size, Some(balances), model))
+    }
+
+    fn build(max_size: usize, balances: Option<BalanceLookup>, spend_model: SpendModel) -> Self {
+        Self {
             pool: Mutex::new(VecDeque::with_capacity(max_size.min(65_536))),
             seen_hashes: Mutex::new(HashSet::new()),
             max_size,
-        })
+            spend: Mutex::new(PendingSpendLedger::new()),
+            spend_model,
+            balances,
+            wal: OnceLock::new(),
+            base_fee: OnceLock::new(),
+            key_check: OnceLock::new(),
+            nonce_lookup: OnceLock::new(),
+            chain_id: OnceLock::new(),
+            queued: Mutex::new(BTreeMap::new()),
+        }
     }
 
     /// Validate and admit a transaction into the pool.
     ///
     /// Checks (in order):
     /// 1. Pool capacity
-    /// 2. Required fields (sender, receiver non-empty; amount > 0; sender ≠ receiver)
+    /// 2. Required fields (sender, receiver non-empty; amount > 0 unless a
+    ///    key change; sender ≠ receiver), and with a chain id set, that the
+    ///    transaction is signed for it
     /// 3. Signature present and structurally valid length
-    /// 4. **S-07**: SPHINCS+ cryptographic signature verification
+    /// 4. **S-07**: SPHINCS+ cryptographic signature verification, and with a
+    ///    key check, that the signer may sign for the sender
     /// 5. **S-09**: Duplicate detection via SHA-256 payload hash
+    /// 6. Nonce: with a nonce lookup, not below the sender's confirmed
+    ///    nonce; a replacement must raise the tip, and a transaction past
+    ///    the sender's pending run is queued instead of checked further
+    /// 7. Pending spend: confirmed balance − pending outflow covers this tx
+    /// 8. Fee floor: `max_fee` covers the next block's base fee
     ///
-    /// Returns `true` if the transaction was admitted, `false` otherwise.
+    /// Returns `true` if the transaction was admitted or queued, `false`
+    /// otherwise.
     pub async fn add_transaction(&self, transaction: ZKTransaction) -> bool {
+        self.admit(transaction).await.is_ok()
+    }
+
+    /// `add_transaction`, reporting why a transaction was turned away.
+    pub async fn admit(&self, transaction: ZKTransaction) -> Result<(), AdmissionError> {
         // ── Step 1: Capacity check ────────────────────────────────────────────
         {
             let pool = self.pool.lock().await;
@@ -54,30 +311,39 @@ impl TransactionPool {
                     "[TxPool] At capacity ({}/{}), rejecting tx from {}",
                     pool.len(), self.max_size, transaction.sender
                 );
-                return false;
+                return Err(AdmissionError::PoolFull { capacity: self.max_size });
             }
         }
 
         // ── Step 2: Structural field validation ───────────────────────────────
         if transaction.sender.is_empty() {
             log::error!("[TxPool] Rejected: sender is empty");
-            return false;
+            return Err(AdmissionError::MissingSender);
         }
         if transaction.receiver.is_empty() {
             log::error!("[TxPool] Rejected: receiver is empty");
-            return false;
+            return Err(AdmissionError::MissingReceiver);
         }
         if transaction.sender == transaction.receiver {
             log::error!("[TxPool] Rejected: sender == receiver ({})", transaction.sender);
-            return false;
+            return Err(AdmissionError::SelfTransfer);
         }
-        if transaction.amount == 0 {
+        if transaction.amount == 0 && !carries_no_amount(&transaction.receiver) {
             log::error!("[TxPool] Rejected: zero-amount tx from {}", transaction.sender);
-            return false;
+            return Err(AdmissionError::ZeroAmount);
         }
         if transaction.timestamp == 0 {
             log::error!("[TxPool] Rejected: zero timestamp from {}", transaction.sender);
-            return false;
+            return Err(AdmissionError::ZeroTimestamp);
+        }
+        if let Some(&expected) = self.chain_id.get() {
+            if transaction.chain_id != expected {
+                log::error!(
+                    "[TxPool] Rejected: tx from {} is for chain {}, not {}",
+                    transaction.sender, transaction.chain_id, expected
+                );
+                return Err(AdmissionError::WrongChain { expected, got: transaction.chain_id });
+            }
         }
 
         // ── Step 3: Signature length check ────────────────────────────────────
@@ -87,7 +353,7 @@ impl TransactionPool {
                 "[TxPool] Rejected: signature too short ({} bytes, need ≥ {}) from {}",
                 transaction.signature.len(), SPHINCS_PK_LEN + 100, transaction.sender
             );
-            return false;
+            return Err(AdmissionError::SignatureTooShort { len: transaction.signature.len() });
         }
 
         // ── Step 4: S-07 — SPHINCS+ cryptographic verification ───────────────
@@ -104,11 +370,12 @@ impl TransactionPool {
                 "[TxPool] Rejected: signature too short for SPHINCS+ key (need ≥{} bytes, got {})",
                 MIN_SIG_LEN, transaction.signature.len()
             );
-            return false;
+            return Err(AdmissionError::SignatureTooShort { len: transaction.signature.len() });
         }
 
-        let pk_bytes  = &transaction.signature[..SPHINCS_PK_LEN];  // SPHINCS+ PK is 64 bytes
-        let sig_bytes = &transaction.signature[SPHINCS_PK_LEN..];
+        let (primary, _) = split_cosigned(&transaction.signature);
+        let pk_bytes  = &primary[..SPHINCS_PK_LEN];  // SPHINCS+ PK is 64 bytes
+        let sig_bytes = &primary[SPHINCS_PK_LEN..];
 
         eprintln!("[DEBUG TxPool] Total signature length: {} bytes", transaction.signature.len());
         eprintln!("[DEBUG TxPool] PK bytes length: {} bytes", pk_bytes.len());
@@ -117,12 +384,7 @@ impl TransactionPool {
         eprintln!("[DEBUG TxPool] Payload: sender='{}' receiver='{}' amount={} timestamp={}", 
             transaction.sender, transaction.receiver, transaction.amount, transaction.timestamp);
 
-        let payload = bleep_crypto::tx_signer::tx_payload(
-            &transaction.sender,
-            &transaction.receiver,
-            transaction.amount,
-            transaction.timestamp,
-        );
+        let payload = transaction.signed_payload();
 
         eprintln!("[DEBUG TxPool] Payload hash (32 bytes): {}", hex::encode(&payload));
 
@@ -133,39 +395,192 @@ impl TransactionPool {
             );
             eprintln!("[DEBUG TxPool] Verification failed for: {} -> {} amount {}", 
                 transaction.sender, transaction.receiver, transaction.amount);
-            return false;
+            return Err(AdmissionError::BadSignature);
         }
+        if let Some(check) = self.key_check.get() {
+            if !check(&transaction.sender, &key_hash(pk_bytes)) {
+                log::warn!("[TxPool] Rejected: signer key not authorized for {}", transaction.sender);
+                return Err(AdmissionError::KeyNotAuthorized { account: transaction.sender });
+            }
+        }
+
+        // Steps 5–8 and admission run under the pool lock so the nonce and
+        // spend checks and the ledger update see the same pool.
+        let mut pool = self.pool.lock().await;
+        let mut spend = self.spend.lock().await;
 
         // ── Step 5: S-09 — Duplicate detection via payload hash ──────────────
         //
         // Hash the canonical payload (same bytes that were signed).
         // This catches exact replays (same sender/receiver/amount/timestamp).
-        let payload = bleep_crypto::tx_signer::tx_payload(
-            &transaction.sender,
-            &transaction.receiver,
-            transaction.amount,
-            transaction.timestamp,
-        );
-        let tx_hash: [u8; 32] = Sha256::digest(&payload).into();
+        let tx_hash = payload_hash(&transaction);
+        let mut seen = self.seen_hashes.lock().await;
+        if seen.contains(&tx_hash) {
+            log::warn!(
+                "[TxPool] S-09: Duplicate tx rejected (hash={}…) from {}",
+                hex::encode(&tx_hash[..4]),
+                transaction.sender
+            );
+            return Err(AdmissionError::Duplicate);
+        }
 
-        {
-            let mut seen = self.seen_hashes.lock().await;
-            if seen.contains(&tx_hash) {
-                log::warn!(
-                    "[TxPool] S-09: Duplicate tx rejected (hash={}…) from {}",
-                    hex::encode(&tx_hash[..4]),
-                    transaction.sender
-                );
-                return false;
+        // ── Step 6: Nonce ─────────────────────────────────────────────────────
+        let mut replaces = None;
+        if let Some(lookup) = self.nonce_lookup.get() {
+            let sender = transaction.sender.clone();
+            let confirmed = lookup(&sender);
+            let next = next_pending_nonce(&pool, &sender, confirmed);
+            let nonce = transaction.nonce;
+            if nonce < confirmed {
+                log::warn!("[TxPool] Rejected: nonce {} from {} already used (next {})", nonce, sender, confirmed);
+                return Err(AdmissionError::NonceTooLow { expected: confirmed, got: nonce });
             }
-            seen.insert(tx_hash);
+            if nonce > next {
+                if nonce - next > MAX_NONCE_GAP {
+                    return Err(AdmissionError::NonceGap { expected: next, got: nonce });
+                }
+                let mut queued = self.queued.lock().await;
+                if let Some(waiting) = queued.get(&(sender.clone(), nonce)) {
+                    if transaction.tip <= waiting.tip {
+                        return Err(AdmissionError::ReplacementUnderpriced { nonce, tip: waiting.tip });
+                    }
+                } else if queued.len() >= self.max_size {
+                    return Err(AdmissionError::PoolFull { capacity: self.max_size });
+                }
+                seen.insert(tx_hash);
+                queued.insert((sender.clone(), nonce), transaction);
+                log::debug!("[TxPool] Queued nonce {} from {} until nonce {} arrives", nonce, sender, next);
+                return Ok(());
+            }
+            if nonce < next {
+                let i = pool.iter()
+                    .position(|tx| tx.sender == sender && tx.nonce == nonce)
+                    .expect("the pending run holds every nonce below its end");
+                if transaction.tip <= pool[i].tip {
+                    return Err(AdmissionError::ReplacementUnderpriced { nonce, tip: pool[i].tip });
+                }
+                replaces = Some(i);
+            }
+        }
+
+        // ── Steps 7–8: Pending spend and fee floor ───────────────────────────
+        //
+        // Checked before the hash is marked seen, so a tx rejected here can be
+        // resubmitted once earlier pending spends leave the pool.  A
+        // replacement is checked without the outflow of the tx it replaces.
+        let outflows = self.spend_model.outflows(&transaction);
+        let displaced = replaces.map(|i| self.spend_model.outflows(&pool[i]));
+        if let Some(old) = &displaced {
+            spend.remove(old);
+        }
+        if let Err(e) = self.affordable(&spend, &transaction, &outflows) {
+            if let Some(old) = &displaced {
+                spend.add(old);
+            }
+            return Err(e);
         }
 
         // ── Admit ─────────────────────────────────────────────────────────────
-        let mut pool = self.pool.lock().await;
-        pool.push_back(transaction);
+        seen.insert(tx_hash);
+        spend.add(&outflows);
+        if let Some(i) = replaces {
+            let old = canonical_id(&pool[i]);
+            log::info!("[TxPool] Nonce {} from {} replaced with a higher tip", transaction.nonce, transaction.sender);
+            pool[i] = transaction.clone();
+            self.log_change(&pool, PoolRecord::Replace { old, new: transaction });
+            return Ok(());
+        }
+        let sender = transaction.sender.clone();
+        pool.push_back(transaction.clone());
+        self.log_change(&pool, PoolRecord::Admit(transaction));
+        if self.nonce_lookup.get().is_some() {
+            let mut queued = self.queued.lock().await;
+            self.promote(&mut pool, &mut spend, &mut seen, &mut queued, &sender);
+        }
         log::debug!("[TxPool] Admitted tx — pool {}/{}", pool.len(), self.max_size);
-        true
+        Ok(())
+    }
+
+    /// Steps 7–8 of admission: `outflows` fit on top of `spend`, and the
+    /// fee covers the floor.
+    fn affordable(
+        &self,
+        spend:       &PendingSpendLedger,
+        transaction: &ZKTransaction,
+        outflows:    &[(String, u128)],
+    ) -> Result<(), AdmissionError> {
+        if let Some(balance) = &self.balances {
+            if let Some(account) = spend.shortfall(outflows, balance.as_ref()) {
+                log::warn!(
+                    "[TxPool] Rejected: {} cannot cover tx from {} (pending outflow {})",
+                    account, transaction.sender, spend.get(&account).outflow
+                );
+                return Err(AdmissionError::InsufficientBalance { account });
+            }
+        }
+        if let Some(floor) = self.fee_floor() {
+            if transaction.max_fee < floor {
+                log::warn!(
+                    "[TxPool] Rejected: max fee {} from {} below base fee {}",
+                    transaction.max_fee, transaction.sender, floor
+                );
+                return Err(AdmissionError::FeeTooLow { max_fee: transaction.max_fee, base_fee: floor });
+            }
+        }
+        Ok(())
+    }
+
+    /// Settle `account` against its confirmed nonce: drop pending and
+    /// queued transactions it has overtaken, then move queued ones whose
+    /// turn has come into the pool.  A queued transaction that no longer
+    /// passes steps 7–8 is dropped, and so is everything queued behind it.
+    fn promote(
+        &self,
+        pool:    &mut VecDeque<ZKTransaction>,
+        spend:   &mut PendingSpendLedger,
+        seen:    &mut HashSet<[u8; 32]>,
+        queued:  &mut BTreeMap<(String, u64), ZKTransaction>,
+        account: &str,
+    ) {
+        let Some(lookup) = self.nonce_lookup.get() else { return };
+        let confirmed = lookup(account);
+        let mut stale = Vec::new();
+        pool.retain(|tx| {
+            let keep = tx.sender != account || tx.nonce >= confirmed;
+            if !keep {
+                spend.remove(&self.spend_model.outflows(tx));
+                stale.push(canonical_id(tx));
+            }
+            keep
+        });
+        for id in stale {
+            self.log_change(pool, PoolRecord::Remove(id));
+        }
+        queued.retain(|(sender, nonce), _| sender != account || *nonce >= confirmed);
+
+        let mut next = next_pending_nonce(pool, account, confirmed);
+        while pool.len() < self.max_size {
+            let Some(tx) = queued.remove(&(account.to_string(), next)) else { break };
+            let outflows = self.spend_model.outflows(&tx);
+            if let Err(e) = self.affordable(spend, &tx, &outflows) {
+                log::warn!("[TxPool] Dropped queued nonce {} from {}: {}", next, account, e);
+                seen.remove(&payload_hash(&tx));
+                let behind: Vec<_> = queued.range((account.to_string(), next)..)
+                    .take_while(|((sender, _), _)| sender == account)
+                    .map(|(key, _)| key.clone())
+                    .collect();
+                for key in behind {
+                    if let Some(tx) = queued.remove(&key) {
+                        seen.remove(&payload_hash(&tx));
+                    }
+                }
+                break;
+            }
+            spend.add(&outflows);
+            pool.push_back(tx.clone());
+            self.log_change(pool, PoolRecord::Admit(tx));
+            next += 1;
+        }
     }
 
     /// Retrieve all pending transactions (read-only snapshot).
@@ -178,6 +593,9 @@ impl TransactionPool {
     pub async fn clear_pool(&self) {
         let mut pool = self.pool.lock().await;
         pool.clear();
+        self.spend.lock().await.clear();
+        self.queued.lock().await.clear();
+        self.log_change(&pool, PoolRecord::Clear);
         // Note: seen_hashes is NOT cleared — previously seen hashes must remain
         // invalid to prevent cross-block replay attacks.
     }
@@ -187,16 +605,46 @@ impl TransactionPool {
         self.pool.lock().await.len()
     }
 
+    /// Maximum number of pending transactions.
+    pub fn capacity(&self) -> usize {
+        self.max_size
+    }
+
+    /// Current pool depth, or `None` if the pool is busy; for synchronous
+    /// samplers that must not wait.
+    pub fn try_pool_size(&self) -> Option<usize> {
+        self.pool.try_lock().ok().map(|p| p.len())
+    }
+
     /// Remove a confirmed transaction by its canonical ID.
     ///
     /// The canonical ID string is `"sender:receiver:amount:timestamp"`.
     /// This is called after a block containing the transaction is finalised.
+    /// With a nonce lookup, the sender's queued transactions that were
+    /// waiting on it move into the pool.
     pub async fn remove_confirmed(&self, tx_id: &str) {
         let mut pool = self.pool.lock().await;
+        let mut spend = self.spend.lock().await;
         let before = pool.len();
+        let mut senders = Vec::new();
         pool.retain(|tx| {
-            format!("{}:{}:{}:{}", tx.sender, tx.receiver, tx.amount, tx.timestamp) != tx_id
+            let keep = canonical_id(tx) != tx_id;
+            if !keep {
+                spend.remove(&self.spend_model.outflows(tx));
+                senders.push(tx.sender.clone());
+            }
+            keep
         });
+        if pool.len() != before {
+            self.log_change(&pool, PoolRecord::Remove(tx_id.to_string()));
+        }
+        if self.nonce_lookup.get().is_some() {
+            let mut seen = self.seen_hashes.lock().await;
+            let mut queued = self.queued.lock().await;
+            for sender in senders {
+                self.promote(&mut pool, &mut spend, &mut seen, &mut queued, &sender);
+            }
+        }
         log::debug!(
             "[TxPool] remove_confirmed '{}': {} → {} pending",
             tx_id, before, pool.len()
@@ -211,6 +659,188 @@ impl TransactionPool {
         let pool = self.pool.lock().await;
         pool.iter().take(limit).cloned().collect()
     }
+
+    /// Admit against `tracker`'s base fee from now on.
+    pub fn set_base_fee(&self, tracker: Arc<BaseFeeTracker>) -> Result<(), String> {
+        self.base_fee.set(tracker).map_err(|_| "transaction pool already has a base fee".to_string())
+    }
+
+    /// Check every admitted transaction's signer against the sender's keys.
+    pub fn set_key_check(&self, check: KeyCheck) -> Result<(), String> {
+        self.key_check.set(check).map_err(|_| "transaction pool already has a key check".to_string())
+    }
+
+    /// Check every admitted transaction's nonce against `lookup`.
+    pub fn set_nonce_lookup(&self, lookup: NonceLookup) -> Result<(), String> {
+        self.nonce_lookup.set(lookup).map_err(|_| "transaction pool already has a nonce lookup".to_string())
+    }
+
+    /// Admit only transactions signed for `chain_id`.
+    pub fn set_chain_id(&self, chain_id: ChainId) -> Result<(), String> {
+        self.chain_id.set(chain_id).map_err(|_| "transaction pool already has a chain id".to_string())
+    }
+
+    /// Nonce the next transaction from `account` should carry, counting its
+    /// pending transactions on top of `confirmed`; `None` if the pool is busy.
+    pub fn try_pending_nonce(&self, account: &str, confirmed: u64) -> Option<u64> {
+        self.pool.try_lock().ok().map(|pool| next_pending_nonce(&pool, account, confirmed))
+    }
+
+    /// Nonce the next transaction from `account` should carry, counting its
+    /// pending transactions on top of `confirmed`.
+    pub async fn pending_nonce(&self, account: &str, confirmed: u64) -> u64 {
+        next_pending_nonce(&*self.pool.lock().await, account, confirmed)
+    }
+
+    /// Transactions waiting for a lower nonce from their sender.
+    pub async fn queued_count(&self) -> usize {
+        self.queued.lock().await.len()
+    }
+
+    /// Base fee a transaction's `max_fee` must cover, if a tracker is attached.
+    pub fn fee_floor(&self) -> Option<u64> {
+        self.base_fee.get().map(|t| t.current())
+    }
+
+    /// Drop pending transactions whose `max_fee` is below `floor`; they can
+    /// no longer be included.  Returns how many were evicted.
+    pub async fn evict_below(&self, floor: u64) -> usize {
+        let mut pool = self.pool.lock().await;
+        let mut spend = self.spend.lock().await;
+        let mut evicted = Vec::new();
+        pool.retain(|tx| {
+            let keep = tx.max_fee >= floor;
+            if !keep {
+                spend.remove(&self.spend_model.outflows(tx));
+                evicted.push(canonical_id(tx));
+            }
+            keep
+        });
+        for id in &evicted {
+            self.log_change(&pool, PoolRecord::Remove(id.clone()));
+        }
+        if !evicted.is_empty() {
+            log::info!("[TxPool] Evicted {} txs below base fee {}", evicted.len(), floor);
+        }
+        evicted.len()
+    }
+
+    /// Put transactions from reorged-out blocks back at the front of the
+    /// pool, in block order.  They passed admission before, so they are not
+    /// re-verified; ones already pending are skipped.
+    pub async fn return_transactions(&self, txs: Vec<ZKTransaction>) {
+        let mut pool = self.pool.lock().await;
+        let mut spend = self.spend.lock().await;
+        let pending: HashSet<String> = pool.iter().map(canonical_id).collect();
+        let mut returned = 0;
+        for tx in txs.iter().rev() {
+            if pending.contains(&canonical_id(tx)) {
+                continue;
+            }
+            spend.add(&self.spend_model.outflows(tx));
+            pool.push_front(tx.clone());
+            returned += 1;
+        }
+        if returned > 0 {
+            self.log_change(&pool, PoolRecord::Return(txs));
+        }
+        log::debug!("[TxPool] {} reorged txs returned — pool {}/{}", returned, pool.len(), self.max_size);
+    }
+
+    /// Replace the pool with `txs` (e.g. a snapshot loaded from disk) and
+    /// rebuild the seen-hash set and pending-spend ledger from them.
+    pub async fn reload(&self, txs: Vec<ZKTransaction>) {
+        let mut pool = self.pool.lock().await;
+        let mut spend = self.spend.lock().await;
+        let mut seen = self.seen_hashes.lock().await;
+        seen.extend(txs.iter().map(payload_hash));
+        *spend = PendingSpendLedger::rebuild(&self.spend_model, &txs);
+        *pool = txs.into();
+        self.log_change(&pool, PoolRecord::Reset(pool.iter().cloned().collect()));
+    }
+
+    /// `true` if an identical transaction was already admitted, so
+    /// submitting `tx` again would be rejected as a replay.
+    pub async fn has_seen(&self, tx: &ZKTransaction) -> bool {
+        self.seen_hashes.lock().await.contains(&payload_hash(tx))
+    }
+
+    /// Pending outflow of `account` across the pool.
+    pub async fn pending_spend(&self, account: &str) -> PendingSpend {
+        self.spend.lock().await.get(account)
+    }
+
+    // ── Write-ahead log ───────────────────────────────────────────────────────
+
+    /// Rebuild the pool from `recovery` (returned by `Wal::open`), keeping
+    /// only transactions for which `keep` holds against the current chain,
+    /// then log every later change to `wal`.  The log is compacted to the
+    /// recovered pool first, so recovering again yields the same state.
+    pub async fn recover_from_wal(
+        &self,
+        wal:      Wal<PoolRecord>,
+        recovery: WalRecovery<PoolRecord>,
+        keep:     impl Fn(&ZKTransaction) -> bool,
+    ) -> Result<PoolRecovery, String> {
+        if self.wal.get().is_some() {
+            return Err("transaction pool already has a WAL".into());
+        }
+        let pending = replay_pool_records(&recovery.records);
+        let replayed = pending.len();
+        let valid: Vec<ZKTransaction> = pending.into_iter().filter(|tx| keep(tx)).collect();
+        let restored = valid.len();
+
+        wal.compact(&[PoolRecord::Reset(valid.clone())])?;
+        self.reload(valid).await;
+        self.wal.set(wal).map_err(|_| "transaction pool already has a WAL".to_string())?;
+
+        let report = PoolRecovery {
+            replayed_records: recovery.records.len(),
+            restored,
+            discarded:        replayed - restored,
+            torn_tail_bytes:  recovery.torn_tail_bytes,
+            elapsed:          recovery.elapsed,
+            entries_per_sec:  recovery.entries_per_sec(),
+        };
+        log::info!(
+            "[TxPool] Recovered {} pending txs from WAL ({} discarded, {} records, {:.0} entries/s)",
+            report.restored, report.discarded, report.replayed_records, report.entries_per_sec
+        );
+        Ok(report)
+    }
+
+    /// Flush the WAL; call once per committed block under `FsyncPolicy::OnCommit`.
+    pub fn wal_commit(&self) {
+        if let Some(Err(e)) = self.wal.get().map(Wal::commit) {
+            log::error!("[TxPool] WAL commit failed: {}", e);
+        }
+    }
+
+    /// Flush the WAL if its fsync interval has passed.
+    pub fn wal_sync_if_due(&self) {
+        if let Some(Err(e)) = self.wal.get().map(Wal::sync_if_due) {
+            log::error!("[TxPool] WAL sync failed: {}", e);
+        }
+    }
+
+    pub fn wal_stats(&self) -> Option<WalStats> {
+        self.wal.get().map(Wal::stats)
+    }
+
+    /// Append `record` (already applied to `pool`), compacting to a snapshot
+    /// of `pool` once the log is over its size threshold.
+    fn log_change(&self, pool: &VecDeque<ZKTransaction>, record: PoolRecord) {
+        let Some(wal) = self.wal.get() else { return };
+        if let Err(e) = wal.append(&record) {
+            log::error!("[TxPool] WAL append failed: {}", e);
+            return;
+        }
+        if wal.needs_compaction() {
+            if let Err(e) = wal.compact(&[PoolRecord::Reset(pool.iter().cloned().collect())]) {
+                log::error!("[TxPool] WAL compaction failed: {}", e);
+            }
+        }
+    }
 }
 
 // ── Unit tests ────────────────────────────────────────────────────────────────
@@ -236,6 +866,10 @@ mod tests {
             amount,
             timestamp,
             signature: full_sig,
+            max_fee:   0,
+            tip:       0,
+            nonce:     0,
+            chain_id:  ChainId::NONE,
         }
     }
 
@@ -256,6 +890,7 @@ mod tests {
             sender: "alice".into(), receiver: "bob".into(),
             amount: 100, timestamp: 1_700_000_001,
             signature: vec![],
+            max_fee: 0, tip: 0, nonce: 0, chain_id: ChainId::NONE,
         };
         assert!(!pool.add_transaction(tx).await, "S-07: empty sig must be rejected");
     }
@@ -267,6 +902,7 @@ mod tests {
             sender: "alice".into(), receiver: "bob".into(),
             amount: 100, timestamp: 1_700_000_002,
             signature: vec![0u8; 10],  // too short
+            max_fee: 0, tip: 0, nonce: 0, chain_id: ChainId::NONE,
         };
         assert!(!pool.add_transaction(tx).await, "S-07: short sig must be rejected");
     }
@@ -279,6 +915,7 @@ mod tests {
             sender: "alice".into(), receiver: "bob".into(),
             amount: 100, timestamp: 1_700_000_003,
             signature: vec![0u8; SPHINCS_PK_LEN + 49856],
+            max_fee: 0, tip: 0, nonce: 0, chain_id: ChainId::NONE,
         };
         assert!(!pool.add_transaction(tx).await, "S-07: forged sig must fail SPHINCS+ verify");
     }
@@ -340,6 +977,7 @@ mod tests {
             sender: "alice".into(), receiver: "bob".into(),
             amount: 0, timestamp: 1_700_000_031,
             signature: vec![1u8; MIN_SIG_LEN + 10],
+            max_fee: 0, tip: 0, nonce: 0, chain_id: ChainId::NONE,
         };
         assert!(!pool.add_transaction(tx).await);
     }
@@ -362,6 +1000,178 @@ mod tests {
         assert!(!pool.add_transaction(tx3).await, "Pool at capacity must reject");
     }
 
+    fn balances(entries: &[(&str, u128)]) -> BalanceLookup {
+        let map: std::collections::HashMap<String, u128> =
+            entries.iter().map(|(a, b)| (a.to_string(), *b)).collect();
+        Arc::new(move |a: &str| map.get(a).copied().unwrap_or(0))
+    }
+
+    #[tokio::test]
+    async fn test_pending_overspend_rejected_until_first_removed() {
+        let pool = TransactionPool::with_spend_tracking(100, balances(&[("alice", 100)]), SpendModel::default());
+        let tx1 = make_signed_tx("alice", "bob", 100, 1_700_300_001);
+        let tx2 = make_signed_tx("alice", "carol", 100, 1_700_300_002);
+        assert!(pool.add_transaction(tx1.clone()).await);
+        assert!(!pool.add_transaction(tx2.clone()).await, "second 100 exceeds balance − pending");
+        assert_eq!(pool.pending_spend("alice").await, PendingSpend { outflow: 100, tx_count: 1 });
+
+        pool.remove_confirmed(&canonical_id(&tx1)).await;
+        assert_eq!(pool.pending_spend("alice").await, PendingSpend::default());
+        assert!(pool.add_transaction(tx2).await, "rejected tx is admitted once the first leaves");
+    }
+
+    #[tokio::test]
+    async fn test_reorg_return_and_reload_restore_pending() {
+        let pool = TransactionPool::with_spend_tracking(100, balances(&[("alice", 1_000)]), SpendModel::default());
+        let in_block = vec![
+            make_signed_tx("alice", "bob", 300, 1_700_300_011),
+            make_signed_tx("alice", "bob", 200, 1_700_300_012),
+        ];
+        let pending = make_signed_tx("alice", "carol", 50, 1_700_300_013);
+        assert!(pool.add_transaction(pending.clone()).await);
+
+        pool.return_transactions(in_block.clone()).await;
+        let expected = PendingSpend { outflow: 550, tx_count: 3 };
+        assert_eq!(pool.pending_spend("alice").await, expected);
+        let order: Vec<u64> = pool.get_transactions().await.iter().map(|t| t.amount).collect();
+        assert_eq!(order, vec![300, 200, 50], "returned txs go first, in block order");
+
+        // Returning the same block twice does not double-count.
+        pool.return_transactions(in_block).await;
+        assert_eq!(pool.pending_spend("alice").await, expected);
+
+        let snapshot = pool.get_transactions().await;
+        pool.clear_pool().await;
+        assert_eq!(pool.pending_spend("alice").await, PendingSpend::default());
+        pool.reload(snapshot).await;
+        assert_eq!(pool.pending_spend("alice").await, expected);
+        assert!(!pool.add_transaction(pending).await, "reloaded txs are marked seen");
+    }
+
+    #[tokio::test]
+    async fn test_sponsor_fee_outflow_counts_against_sponsor() {
+        let model = SpendModel::default().with_fee(10).with_fee_payer("alice", "sponsor");
+        let pool = TransactionPool::with_spend_tracking(
+            100,
+            balances(&[("alice", 1_000), ("sponsor", 15)]),
+            model,
+        );
+        assert!(pool.add_transaction(make_signed_tx("alice", "bob", 100, 1_700_300_021)).await);
+        assert_eq!(pool.pending_spend("alice").await.outflow, 100, "fee is not charged to alice");
+        assert_eq!(pool.pending_spend("sponsor").await.outflow, 10);
+        assert!(
+            !pool.add_transaction(make_signed_tx("alice", "bob", 100, 1_700_300_022)).await,
+            "sponsor cannot cover a second fee"
+        );
+    }
+
+    /// A transfer from alice at `nonce` paying `tip`, signed over both.
+    fn nonce_tx(nonce: u64, tip: u64, timestamp: u64) -> ZKTransaction {
+        let (pk, sk) = generate_tx_keypair();
+        let payload = bleep_crypto::tx_signer::tx_payload_with_nonce("alice", "bob", 10, timestamp, 0, tip, nonce);
+        let mut signature = pk;
+        signature.extend(sign_tx_payload(&payload, &sk).unwrap());
+        ZKTransaction {
+            sender: "alice".into(), receiver: "bob".into(), amount: 10, timestamp,
+            signature, max_fee: 0, tip, nonce, chain_id: ChainId::NONE,
+        }
+    }
+
+    fn confirmed_nonce(nonce: Arc<std::sync::atomic::AtomicU64>) -> NonceLookup {
+        Arc::new(move |_: &str| nonce.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_used_nonces_are_rejected_and_gaps_wait_in_the_queue() {
+        let confirmed = Arc::new(std::sync::atomic::AtomicU64::new(1));
+        let pool = TransactionPool::new(100);
+        pool.set_nonce_lookup(confirmed_nonce(Arc::clone(&confirmed))).unwrap();
+
+        let err = pool.admit(nonce_tx(0, 0, 1_700_500_000)).await.unwrap_err();
+        assert_eq!(err, AdmissionError::NonceTooLow { expected: 1, got: 0 });
+        assert_eq!(err.code(), "nonce_too_low");
+        assert_eq!(
+            pool.admit(nonce_tx(1 + MAX_NONCE_GAP + 1, 0, 1_700_500_001)).await,
+            Err(AdmissionError::NonceGap { expected: 1, got: MAX_NONCE_GAP + 2 }),
+        );
+
+        assert!(pool.add_transaction(nonce_tx(3, 0, 1_700_500_003)).await);
+        assert!(pool.add_transaction(nonce_tx(2, 0, 1_700_500_002)).await);
+        assert_eq!((pool.pool_size().await, pool.queued_count().await), (0, 2), "nonce 1 is missing");
+        assert_eq!(pool.try_pending_nonce("alice", 1), Some(1));
+
+        let first = nonce_tx(1, 0, 1_700_500_004);
+        assert!(pool.add_transaction(first.clone()).await);
+        let nonces: Vec<u64> = pool.get_transactions().await.iter().map(|t| t.nonce).collect();
+        assert_eq!(nonces, vec![1, 2, 3], "the gap filled and the queue drained in order");
+        assert_eq!((pool.queued_count().await, pool.try_pending_nonce("alice", 1)), (0, Some(4)));
+
+        // Nonce 5 waits on 4 until a block confirms 1..=4 elsewhere.
+        assert!(pool.add_transaction(nonce_tx(5, 0, 1_700_500_005)).await);
+        confirmed.store(5, std::sync::atomic::Ordering::SeqCst);
+        pool.remove_confirmed(&canonical_id(&first)).await;
+        let nonces: Vec<u64> = pool.get_transactions().await.iter().map(|t| t.nonce).collect();
+        assert_eq!(nonces, vec![5], "overtaken nonces leave, the waiting one moves in");
+        assert_eq!(pool.queued_count().await, 0);
+    }
+
+    /// A transfer from alice signed for `chain_id`.
+    fn chain_tx(chain_id: ChainId, timestamp: u64) -> ZKTransaction {
+        let (pk, sk) = generate_tx_keypair();
+        let mut tx = ZKTransaction {
+            sender: "alice".into(), receiver: "bob".into(), amount: 10, timestamp,
+            signature: Vec::new(), max_fee: 0, tip: 0, nonce: 0, chain_id,
+        };
+        tx.signature = pk;
+        tx.signature.extend(sign_tx_payload(&tx.signed_payload(), &sk).unwrap());
+        tx
+    }
+
+    #[tokio::test]
+    async fn test_transactions_for_another_chain_are_rejected() {
+        let pool = TransactionPool::new(100);
+        pool.set_chain_id(ChainId(2)).unwrap();
+
+        let err = pool.admit(chain_tx(ChainId(1), 1_700_550_000)).await.unwrap_err();
+        assert_eq!(err, AdmissionError::WrongChain { expected: ChainId(2), got: ChainId(1) });
+        assert_eq!(err.code(), "wrong_chain");
+
+        let mut relabelled = chain_tx(ChainId(1), 1_700_550_001);
+        relabelled.chain_id = ChainId(2);
+        assert_eq!(pool.admit(relabelled).await, Err(AdmissionError::BadSignature));
+
+        assert!(pool.add_transaction(chain_tx(ChainId(2), 1_700_550_002)).await);
+        assert!(pool.set_chain_id(ChainId(1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_same_nonce_with_higher_tip_replaces_pending_tx() {
+        let pool = TransactionPool::with_spend_tracking(100, balances(&[("alice", 25)]), SpendModel::default());
+        pool.set_nonce_lookup(confirmed_nonce(Default::default())).unwrap();
+        let path = wal_path("replace");
+        let (wal, rec) = Wal::open(&path, crate::wal::WalOptions::default()).unwrap();
+        pool.recover_from_wal(wal, rec, |_| true).await.unwrap();
+
+        let stuck = nonce_tx(0, 1, 1_700_600_000);
+        assert!(pool.add_transaction(stuck.clone()).await);
+        assert!(pool.add_transaction(nonce_tx(1, 1, 1_700_600_001)).await);
+
+        let err = pool.admit(nonce_tx(0, 1, 1_700_600_002)).await.unwrap_err();
+        assert_eq!(err, AdmissionError::ReplacementUnderpriced { nonce: 0, tip: 1 });
+        assert_eq!(err.code(), "replacement_underpriced");
+
+        // The replaced transaction's outflow no longer counts against alice.
+        let bumped = nonce_tx(0, 50, 1_700_600_003);
+        pool.admit(bumped.clone()).await.unwrap();
+        assert_eq!(pool.pending_spend("alice").await, PendingSpend { outflow: 20, tx_count: 2 });
+        let nonce_and_tip = |txs: Vec<ZKTransaction>| txs.iter().map(|t| (t.nonce, t.tip)).collect::<Vec<_>>();
+        assert_eq!(nonce_and_tip(pool.get_transactions().await), vec![(0, 50), (1, 1)]);
+        assert!(!pool.add_transaction(stuck).await, "the replaced tx stays seen");
+
+        let (_, rec) = Wal::open(&path, crate::wal::WalOptions::default()).unwrap();
+        assert_eq!(nonce_and_tip(replay_pool_records(&rec.records)), vec![(0, 50), (1, 1)]);
+    }
+
     #[tokio::test]
     async fn test_peek_for_block() {
         let pool = TransactionPool::new(100);
@@ -375,4 +1185,99 @@ mod tests {
         assert_eq!(peeked.len(), 2, "peek_for_block must respect limit");
         assert_eq!(pool.pool_size().await, 3, "peek must not remove txs");
     }
+
+    #[tokio::test]
+    async fn test_fee_floor_rejects_and_evicts_priced_out_txs() {
+        use crate::base_fee::{BaseFeeParams, BaseFeeTracker};
+
+        let fee_tx = |max_fee: u64, ts: u64| {
+            let (pk, sk) = generate_tx_keypair();
+            let payload = bleep_crypto::tx_signer::tx_payload_with_fee("alice", "bob", 10, ts, max_fee, 0);
+            let mut signature = pk;
+            signature.extend(sign_tx_payload(&payload, &sk).unwrap());
+            ZKTransaction {
+                sender: "alice".into(), receiver: "bob".into(), amount: 10, timestamp: ts,
+                signature, max_fee, tip: 0, nonce: 0, chain_id: ChainId::NONE,
+            }
+        };
+        let tracker = Arc::new(BaseFeeTracker::new(BaseFeeParams::default()).unwrap());
+        let pool = TransactionPool::new(100);
+        pool.set_base_fee(tracker.clone()).unwrap();
+        assert_eq!(pool.fee_floor(), Some(1_000));
+
+        assert!(!pool.add_transaction(make_signed_tx("alice", "bob", 10, 1_700_500_000)).await, "fee-less tx below floor");
+        assert!(!pool.add_transaction(fee_tx(999, 1_700_500_001)).await);
+        assert!(pool.add_transaction(fee_tx(1_000, 1_700_500_002)).await);
+        assert!(pool.add_transaction(fee_tx(5_000, 1_700_500_003)).await);
+
+        // A full block raises the fee past the cheaper tx, which is evicted.
+        let floor = tracker.on_block(10, 10, &[]);
+        assert_eq!(floor, 1_125);
+        assert_eq!(pool.evict_below(floor).await, 1);
+        let left = pool.get_transactions().await;
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].max_fee, 5_000);
+        assert!(!pool.add_transaction(fee_tx(1_100, 1_700_500_004)).await);
+    }
+
+    fn unsigned_tx(i: u64) -> ZKTransaction {
+        ZKTransaction {
+            sender:    format!("acct-{}", i % 17),
+            receiver:  "sink".to_string(),
+            amount:    i + 1,
+            timestamp: 1_700_400_000 + i,
+            signature: vec![],
+            max_fee:   0,
+            tip:       0,
+            nonce:     0,
+            chain_id:  ChainId::NONE,
+        }
+    }
+
+    fn wal_path(tag: &str) -> std::path::PathBuf {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        std::env::temp_dir().join(format!("bleep-pool-wal-{}-{}-{}/pool.wal", tag, std::process::id(), nanos))
+    }
+
+    async fn recovered_pool(path: &std::path::Path, keep: impl Fn(&ZKTransaction) -> bool) -> (Arc<TransactionPool>, PoolRecovery) {
+        let (wal, rec) = Wal::open(path, crate::wal::WalOptions::default()).unwrap();
+        let pool = TransactionPool::new(5_000);
+        let report = pool.recover_from_wal(wal, rec, keep).await.unwrap();
+        (pool, report)
+    }
+
+    #[tokio::test]
+    async fn test_wal_recovery_after_crash_restores_pending_set() {
+        let path = wal_path("crash");
+        let expected: Vec<String> = {
+            let (pool, _) = recovered_pool(&path, |_| true).await;
+            assert!(pool.add_transaction(make_signed_tx("alice", "bob", 9, 1_700_400_999)).await);
+            // 1k more admissions; reorg returns skip signing, each logged as its own record.
+            for i in 0..1_000 {
+                pool.return_transactions(vec![unsigned_tx(i)]).await;
+            }
+            for i in (0..1_000).step_by(7) {
+                pool.remove_confirmed(&canonical_id(&unsigned_tx(i))).await;
+            }
+            pool.get_transactions().await.iter().map(canonical_id).collect()
+            // Dropped here with no shutdown flush.
+        };
+        assert_eq!(expected.len(), 1_001 - 143);
+
+        let (pool, report) = recovered_pool(&path, |_| true).await;
+        let ids: Vec<String> = pool.get_transactions().await.iter().map(canonical_id).collect();
+        assert_eq!(ids, expected);
+        assert_eq!(report.discarded, 0);
+        assert!(report.entries_per_sec > 0.0);
+        drop(pool);
+
+        // Recovery is idempotent, and entries the chain no longer accepts are dropped.
+        let (pool, report) = recovered_pool(&path, |tx| tx.sender != "acct-0").await;
+        assert_eq!(report.replayed_records, 1, "log was compacted to one snapshot");
+        assert!(report.discarded > 0);
+        assert_eq!(pool.pool_size().await, expected.len() - report.discarded);
+    }
 }
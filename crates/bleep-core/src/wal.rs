@@ -0,0 +1,421 @@
+//! # Write-ahead log
+//!
+//! Append-only record log used to make the transaction pool and the
+//! transaction scheduler survive a hard crash without a shutdown dump.
+//!
+//! Each record is framed as
+//!
+//! ```text
+//! len: u32 LE | checksum: first 8 bytes of SHA-256(payload) | payload (bincode)
+//! ```
+//!
+//! and written with a single `write_all`.  On open the log is replayed up to
+//! the first frame that is short or fails its checksum — the torn tail of an
+//! interrupted write — and the file is truncated there, so replay is
+//! idempotent.  When the log grows past `WalOptions::compact_above_bytes` the
+//! owner rewrites it as a snapshot of its current state (`compact`).
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use parking_lot::Mutex;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use bleep_telemetry::metrics::{MetricGauge, MetricsRegistry};
+
+const HEADER_LEN: usize = 4 + 8;
+
+/// When appended records are flushed to stable storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsyncPolicy {
+    /// fsync after every record.
+    EveryWrite,
+    /// fsync at most once per interval; `sync_if_due` covers idle periods.
+    Interval(Duration),
+    /// fsync only on `commit`, e.g. once per committed block.
+    OnCommit,
+}
+
+impl FromStr for FsyncPolicy {
+    type Err = String;
+
+    /// `always`, `commit`, or `interval:<ms>`.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "always" => Ok(FsyncPolicy::EveryWrite),
+            "commit" => Ok(FsyncPolicy::OnCommit),
+            _ => s.strip_prefix("interval:")
+                .and_then(|ms| ms.parse().ok())
+                .map(|ms| FsyncPolicy::Interval(Duration::from_millis(ms)))
+                .ok_or_else(|| format!("unknown fsync policy '{}' (always | commit | interval:<ms>)", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WalOptions {
+    pub fsync:               FsyncPolicy,
+    /// Log size that triggers compaction into a snapshot.
+    pub compact_above_bytes: u64,
+}
+
+impl Default for WalOptions {
+    fn default() -> Self {
+        WalOptions {
+            fsync:               FsyncPolicy::Interval(Duration::from_millis(200)),
+            compact_above_bytes: 64 * 1024 * 1024,
+        }
+    }
+}
+
+/// Counters for telemetry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalStats {
+    pub size_bytes:      u64,
+    pub records_written: u64,
+    pub fsyncs:          u64,
+    pub last_fsync_us:   u64,
+    pub max_fsync_us:    u64,
+    pub compactions:     u64,
+    /// Unix time of the last compaction.
+    pub last_compaction: Option<u64>,
+}
+
+/// Result of replaying a log on open.
+#[derive(Debug, Clone)]
+pub struct WalRecovery<R> {
+    pub records:         Vec<R>,
+    /// Bytes dropped from the end of the file (0 after a clean stop).
+    pub torn_tail_bytes: u64,
+    pub elapsed:         Duration,
+}
+
+impl<R> WalRecovery<R> {
+    pub fn entries_per_sec(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            return self.records.len() as f64;
+        }
+        self.records.len() as f64 / secs
+    }
+}
+
+struct Inner {
+    file:      File,
+    dirty:     bool,
+    last_sync: Instant,
+    stats:     WalStats,
+}
+
+/// Append-only log of `R` records.
+pub struct Wal<R> {
+    path:  PathBuf,
+    opts:  WalOptions,
+    inner: Mutex<Inner>,
+    _rec:  PhantomData<fn(R) -> R>,
+}
+
+impl<R: Serialize + DeserializeOwned> Wal<R> {
+    /// Open (or create) the log at `path` and replay it.
+    pub fn open<P: AsRef<Path>>(path: P, opts: WalOptions) -> Result<(Self, WalRecovery<R>), String> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("create {}: {}", parent.display(), e))?;
+        }
+        let started = Instant::now();
+        let bytes = match fs::read(&path) {
+            Ok(b) => b,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(format!("read {}: {}", path.display(), e)),
+        };
+        let (records, good) = decode_frames::<R>(&bytes);
+        let torn_tail_bytes = (bytes.len() - good) as u64;
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)
+            .map_err(|e| format!("open {}: {}", path.display(), e))?;
+        if torn_tail_bytes > 0 {
+            log::warn!(
+                "[WAL] {}: dropped {} byte torn tail after {} records",
+                path.display(), torn_tail_bytes, records.len()
+            );
+            file.set_len(good as u64).map_err(|e| format!("truncate {}: {}", path.display(), e))?;
+            file.sync_data().map_err(|e| format!("sync {}: {}", path.display(), e))?;
+        }
+
+        let recovery = WalRecovery { records, torn_tail_bytes, elapsed: started.elapsed() };
+        log::info!(
+            "[WAL] Replayed {} records from {} in {:?} ({:.0} entries/s)",
+            recovery.records.len(), path.display(), recovery.elapsed, recovery.entries_per_sec()
+        );
+        let wal = Wal {
+            path,
+            opts,
+            inner: Mutex::new(Inner {
+                file,
+                dirty: false,
+                last_sync: Instant::now(),
+                stats: WalStats { size_bytes: good as u64, ..WalStats::default() },
+            }),
+            _rec: PhantomData,
+        };
+        Ok((wal, recovery))
+    }
+
+    /// Append `record`, syncing according to the fsync policy.
+    pub fn append(&self, record: &R) -> Result<(), String> {
+        let frame = encode_frame(record)?;
+        let mut inner = self.inner.lock();
+        inner.file.write_all(&frame).map_err(|e| format!("append {}: {}", self.path.display(), e))?;
+        inner.stats.size_bytes += frame.len() as u64;
+        inner.stats.records_written += 1;
+        inner.dirty = true;
+        match self.opts.fsync {
+            FsyncPolicy::EveryWrite => self.sync(&mut inner),
+            FsyncPolicy::Interval(every) if inner.last_sync.elapsed() >= every => self.sync(&mut inner),
+            _ => Ok(()),
+        }
+    }
+
+    /// Flush everything appended so far (the batch boundary for `OnCommit`).
+    pub fn commit(&self) -> Result<(), String> {
+        let mut inner = self.inner.lock();
+        self.sync(&mut inner)
+    }
+
+    /// For `Interval`: flush if records are waiting and the interval has passed.
+    pub fn sync_if_due(&self) -> Result<(), String> {
+        let mut inner = self.inner.lock();
+        match self.opts.fsync {
+            FsyncPolicy::Interval(every) if inner.last_sync.elapsed() >= every => self.sync(&mut inner),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn needs_compaction(&self) -> bool {
+        self.inner.lock().stats.size_bytes > self.opts.compact_above_bytes
+    }
+
+    /// Replace the log with `snapshot`, records that rebuild the current
+    /// state on replay.  Atomic: a crash leaves either the old or new log.
+    pub fn compact(&self, snapshot: &[R]) -> Result<(), String> {
+        let mut buf = Vec::new();
+        for record in snapshot {
+            buf.extend(encode_frame(record)?);
+        }
+        let mut inner = self.inner.lock();
+        let tmp = self.path.with_extension("compact");
+        {
+            let mut f = File::create(&tmp).map_err(|e| format!("create {}: {}", tmp.display(), e))?;
+            f.write_all(&buf).map_err(|e| format!("write {}: {}", tmp.display(), e))?;
+            f.sync_all().map_err(|e| format!("sync {}: {}", tmp.display(), e))?;
+        }
+        fs::rename(&tmp, &self.path).map_err(|e| format!("rename {}: {}", self.path.display(), e))?;
+        if let Some(dir) = self.path.parent().and_then(|p| File::open(p).ok()) {
+            let _ = dir.sync_all();
+        }
+        inner.file = OpenOptions::new().append(true).open(&self.path)
+            .map_err(|e| format!("reopen {}: {}", self.path.display(), e))?;
+        let before = inner.stats.size_bytes;
+        inner.stats.size_bytes = buf.len() as u64;
+        inner.stats.compactions += 1;
+        inner.stats.last_compaction = Some(unix_now());
+        inner.dirty = false;
+        inner.last_sync = Instant::now();
+        log::info!(
+            "[WAL] Compacted {}: {} → {} bytes ({} records)",
+            self.path.display(), before, buf.len(), snapshot.len()
+        );
+        Ok(())
+    }
+
+    pub fn stats(&self) -> WalStats {
+        self.inner.lock().stats.clone()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn sync(&self, inner: &mut Inner) -> Result<(), String> {
+        if !inner.dirty {
+            return Ok(());
+        }
+        let started = Instant::now();
+        inner.file.sync_data().map_err(|e| format!("sync {}: {}", self.path.display(), e))?;
+        let us = started.elapsed().as_micros() as u64;
+        inner.stats.fsyncs += 1;
+        inner.stats.last_fsync_us = us;
+        inner.stats.max_fsync_us = inner.stats.max_fsync_us.max(us);
+        inner.dirty = false;
+        inner.last_sync = Instant::now();
+        Ok(())
+    }
+}
+
+fn encode_frame<R: Serialize>(record: &R) -> Result<Vec<u8>, String> {
+    let payload = bincode::serialize(record).map_err(|e| format!("encode WAL record: {}", e))?;
+    let len = u32::try_from(payload.len()).map_err(|_| "WAL record too large".to_string())?;
+    let mut frame = Vec::with_capacity(HEADER_LEN + payload.len());
+    frame.extend_from_slice(&len.to_le_bytes());
+    frame.extend_from_slice(&Sha256::digest(&payload)[..8]);
+    frame.extend_from_slice(&payload);
+    Ok(frame)
+}
+
+/// Decode frames until the first bad one; returns the records and the
+/// length of the valid prefix.
+fn decode_frames<R: DeserializeOwned>(bytes: &[u8]) -> (Vec<R>, usize) {
+    let mut records = Vec::new();
+    let mut pos = 0;
+    while bytes.len() - pos >= HEADER_LEN {
+        let len = u32::from_le_bytes(bytes[pos..pos + 4].try_into().expect("4 bytes")) as usize;
+        let start = pos + HEADER_LEN;
+        if bytes.len() - start < len {
+            break;
+        }
+        let payload = &bytes[start..start + len];
+        if Sha256::digest(payload)[..8] != bytes[pos + 4..start] {
+            break;
+        }
+        match bincode::deserialize(payload) {
+            Ok(r) => records.push(r),
+            Err(_) => break,
+        }
+        pos = start + len;
+    }
+    (records, pos)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Telemetry handles for one log: size, fsync latency, compaction age.
+#[derive(Debug, Clone)]
+pub struct WalTelemetry {
+    size_bytes:          MetricGauge,
+    fsync_latency_us:    MetricGauge,
+    compaction_age_secs: MetricGauge,
+}
+
+impl WalTelemetry {
+    /// Register gauges `bleep_<name>_wal_*`.
+    pub fn register(registry: &mut MetricsRegistry, name: &str) -> Self {
+        WalTelemetry {
+            size_bytes:          registry.gauge(&format!("bleep_{}_wal_bytes", name)),
+            fsync_latency_us:    registry.gauge(&format!("bleep_{}_wal_fsync_latency_us", name)),
+            compaction_age_secs: registry.gauge(&format!("bleep_{}_wal_last_compaction_age_secs", name)),
+        }
+    }
+
+    /// Push the current readings; compaction age is -1 until the first compaction.
+    pub fn export(&self, stats: &WalStats) {
+        self.size_bytes.set(stats.size_bytes as i64);
+        self.fsync_latency_us.set(stats.last_fsync_us as i64);
+        self.compaction_age_secs.set(
+            stats.last_compaction.map_or(-1, |t| unix_now().saturating_sub(t) as i64),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Seek;
+
+    fn scratch(tag: &str) -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+        std::env::temp_dir()
+            .join(format!("bleep-wal-{}-{}-{}", tag, std::process::id(), nanos))
+            .join("test.wal")
+    }
+
+    #[test]
+    fn torn_final_record_skipped() {
+        let path = scratch("torn");
+        {
+            let (wal, _) = Wal::<String>::open(&path, WalOptions::default()).unwrap();
+            for i in 0..10 {
+                wal.append(&format!("record-{}", i)).unwrap();
+            }
+        }
+        // Crash mid-write: the last frame loses its final bytes.
+        let len = fs::metadata(&path).unwrap().len();
+        OpenOptions::new().write(true).open(&path).unwrap().set_len(len - 3).unwrap();
+
+        let (wal, rec) = Wal::<String>::open(&path, WalOptions::default()).unwrap();
+        assert_eq!(rec.records.len(), 9);
+        assert_eq!(rec.records[8], "record-8");
+        assert!(rec.torn_tail_bytes > 0);
+
+        // The tail was truncated, so new records follow the last good one.
+        wal.append(&"after".to_string()).unwrap();
+        drop(wal);
+        let (_, rec) = Wal::<String>::open(&path, WalOptions::default()).unwrap();
+        assert_eq!(rec.records.len(), 10);
+        assert_eq!(rec.records[9], "after");
+        assert_eq!(rec.torn_tail_bytes, 0);
+    }
+
+    #[test]
+    fn corrupted_record_ends_replay() {
+        let path = scratch("corrupt");
+        {
+            let (wal, _) = Wal::<u64>::open(&path, WalOptions::default()).unwrap();
+            for i in 0..5u64 {
+                wal.append(&i).unwrap();
+            }
+        }
+        let mut f = OpenOptions::new().write(true).open(&path).unwrap();
+        f.seek(std::io::SeekFrom::End(-1)).unwrap();
+        f.write_all(&[0xFF]).unwrap();
+        drop(f);
+        let (_, rec) = Wal::<u64>::open(&path, WalOptions::default()).unwrap();
+        assert_eq!(rec.records, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn compaction_shrinks_and_preserves_replay() {
+        let path = scratch("compact");
+        let opts = WalOptions { fsync: FsyncPolicy::OnCommit, compact_above_bytes: 1024 };
+        let (wal, _) = Wal::<(bool, u64)>::open(&path, opts).unwrap();
+        // Add 0..200, then remove all but the multiples of 10.
+        for i in 0..200u64 {
+            wal.append(&(true, i)).unwrap();
+        }
+        for i in (0..200u64).filter(|i| i % 10 != 0) {
+            wal.append(&(false, i)).unwrap();
+        }
+        wal.commit().unwrap();
+        assert!(wal.needs_compaction());
+        let before = wal.stats().size_bytes;
+
+        let live: Vec<(bool, u64)> = (0..200).step_by(10).map(|i| (true, i)).collect();
+        wal.compact(&live).unwrap();
+        let stats = wal.stats();
+        assert!(stats.size_bytes < before / 10);
+        assert_eq!(stats.size_bytes, fs::metadata(&path).unwrap().len());
+        assert!(stats.last_compaction.is_some());
+        wal.append(&(true, 500)).unwrap();
+        drop(wal);
+
+        let (_, rec) = Wal::<(bool, u64)>::open(&path, opts).unwrap();
+        let mut expected = live;
+        expected.push((true, 500));
+        assert_eq!(rec.records, expected);
+    }
+
+    #[test]
+    fn fsync_policy_parses() {
+        assert_eq!("always".parse(), Ok(FsyncPolicy::EveryWrite));
+        assert_eq!("commit".parse(), Ok(FsyncPolicy::OnCommit));
+        assert_eq!("interval:50".parse(), Ok(FsyncPolicy::Interval(Duration::from_millis(50))));
+        assert!("sometimes".parse::<FsyncPolicy>().is_err());
+    }
+}
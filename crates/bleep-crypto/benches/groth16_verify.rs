@@ -0,0 +1,79 @@
+//! Sequential vs rayon-parallel verification of 100 Groth16 proofs, across
+//! pool sizes, for tuning the verifier's thread pool.
+//!
+//!     cargo bench -p bleep-crypto --bench groth16_verify
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::Groth16;
+use ark_relations::lc;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use bleep_crypto::zkp_verification::{BLEEPZKPModule, Proof};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const PROOFS: u64 = 100;
+
+/// Knowledge of `x, y` with `x · y = z` for public `z`.
+struct MulCircuit {
+    x: Option<Fr>,
+    y: Option<Fr>,
+}
+
+impl ConstraintSynthesizer<Fr> for MulCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
+        let y = cs.new_witness_variable(|| self.y.ok_or(SynthesisError::AssignmentMissing))?;
+        let z = cs.new_input_variable(|| {
+            Ok(self.x.ok_or(SynthesisError::AssignmentMissing)? * self.y.ok_or(SynthesisError::AssignmentMissing)?)
+        })?;
+        cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)
+    }
+}
+
+fn fixture() -> (BLEEPZKPModule, Vec<Proof>, Vec<Vec<Fr>>) {
+    let mut rng = StdRng::seed_from_u64(42);
+    let pk = Groth16::<Bls12_381>::generate_random_parameters_with_reduction(MulCircuit { x: None, y: None }, &mut rng)
+        .unwrap();
+    let (proofs, inputs) = (1..=PROOFS)
+        .map(|i| {
+            let (x, y) = (Fr::from(i), Fr::from(i + 1));
+            let proof = Groth16::<Bls12_381>::create_random_proof_with_reduction(
+                MulCircuit { x: Some(x), y: Some(y) }, &pk, &mut rng,
+            ).unwrap();
+            (proof, vec![x * y])
+        })
+        .unzip();
+    (BLEEPZKPModule::new().with_groth16_vk(pk.vk), proofs, inputs)
+}
+
+fn verify(c: &mut Criterion) {
+    let (zkp, proofs, inputs) = fixture();
+    let mut group = c.benchmark_group("groth16_verify_100");
+    group.sample_size(10);
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            for (proof, x) in proofs.iter().zip(&inputs) {
+                assert!(zkp.verify_groth16(proof, x).unwrap());
+            }
+        })
+    });
+
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    let mut sizes: Vec<usize> = [1, 2, 4, 8, cores].into_iter().filter(|&n| n <= cores).collect();
+    sizes.dedup();
+    for threads in sizes {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        group.bench_with_input(BenchmarkId::new("parallel", threads), &threads, |b, _| {
+            b.iter(|| {
+                let verdicts = pool.install(|| zkp.verify_batch_proofs(&proofs, &inputs)).unwrap();
+                assert!(verdicts.into_iter().all(|v| v));
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, verify);
+criterion_main!(benches);
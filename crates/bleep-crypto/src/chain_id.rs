@@ -0,0 +1,115 @@
+//! # Chain ids
+//!
+//! Every BLEEP network is named by a `ChainId`, fixed at genesis.  Signed
+//! transaction payloads, block hashes and governance votes commit to it,
+//! so a signature made for one network does not verify on another.
+//!
+//! ## Numbering
+//! Ids share the numbering of `bleep_connect_types::ChainId::to_u32`, where
+//! external chains take small numbers (Ethereum = 1 … Aptos = 18) and BLEEP
+//! is 999.  `900..=999` is reserved for BLEEP networks:
+//!
+//! ```text
+//!   999  mainnet   (bleep_connect_types::ChainId::BLEEP)
+//!   998  testnet
+//!   997  devnet
+//! ```
+//!
+//! ## Binding
+//! ```text
+//!   bound = sha3_256( "BLEEP-CHAIN-v1" || id_le4 || payload )
+//! ```
+//!
+//! `ChainId::NONE` (0) leaves payloads unbound, so data signed before
+//! chain ids existed stays valid on a chain started without one.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// Prefix hashed in front of every chain-bound payload.
+pub const CHAIN_DOMAIN: &[u8] = b"BLEEP-CHAIN-v1";
+
+/// Interop ids reserved for BLEEP networks.
+pub const BLEEP_NETWORKS: RangeInclusive<u32> = 900..=999;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ChainId(pub u32);
+
+impl ChainId {
+    /// No chain: payloads are signed unbound.
+    pub const NONE: ChainId = ChainId(0);
+    pub const MAINNET: ChainId = ChainId(999);
+    pub const TESTNET: ChainId = ChainId(998);
+    pub const DEVNET: ChainId = ChainId(997);
+
+    pub fn is_none(&self) -> bool {
+        *self == ChainId::NONE
+    }
+
+    /// Whether this id lies in the range reserved for BLEEP networks.
+    pub fn is_bleep_network(&self) -> bool {
+        BLEEP_NETWORKS.contains(&self.0)
+    }
+
+    /// Bind a 32-byte signing payload to this chain.
+    pub fn bind(self, payload: [u8; 32]) -> [u8; 32] {
+        if self.is_none() {
+            return payload;
+        }
+        let mut h = Sha3_256::new();
+        h.update(CHAIN_DOMAIN);
+        h.update(self.0.to_le_bytes());
+        h.update(payload);
+        h.finalize().into()
+    }
+}
+
+impl fmt::Display for ChainId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for ChainId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim()
+            .parse::<u32>()
+            .map(ChainId)
+            .map_err(|e| format!("Invalid chain id '{}': {}", s, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tx_signer::{generate_tx_keypair, sign_tx_payload, tx_payload, verify_tx_signature};
+
+    #[test]
+    fn payload_signed_on_one_chain_fails_on_another() {
+        let (pk, sk) = generate_tx_keypair();
+        let payload = tx_payload("alice", "bob", 10, 1);
+        let sig = sign_tx_payload(&ChainId(1).bind(payload), &sk).unwrap();
+
+        assert!(verify_tx_signature(&ChainId(1).bind(payload), &sig, &pk));
+        assert!(!verify_tx_signature(&ChainId(2).bind(payload), &sig, &pk));
+        assert!(!verify_tx_signature(&payload, &sig, &pk), "unbound payload differs");
+        assert_eq!(ChainId::NONE.bind(payload), payload);
+    }
+
+    #[test]
+    fn bleep_networks_use_reserved_range() {
+        for id in [ChainId::MAINNET, ChainId::TESTNET, ChainId::DEVNET] {
+            assert!(id.is_bleep_network());
+        }
+        assert!(!ChainId(1).is_bleep_network(), "1 is Ethereum in interop numbering");
+        assert_eq!("998".parse::<ChainId>().unwrap(), ChainId::TESTNET);
+        assert!("mainnet".parse::<ChainId>().is_err());
+    }
+}
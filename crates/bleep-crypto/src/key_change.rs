@@ -0,0 +1,116 @@
+//! # Key-change transactions
+//!
+//! An account rotates its signing key with an ordinary transaction sent to
+//! a reserved receiver that names the new key:
+//!
+//! ```text
+//! receiver  = "bleep:key_change/" ‖ hex(new key hash) [‖ "/" ‖ hex(recovery key hash)]
+//! amount    = 0
+//! signature = pk(64) ‖ sig [‖ recovery_pk(64) ‖ recovery_sig]
+//! ```
+//!
+//! Key hashes are `SHA3-256(pk)`.  The primary signature comes from a key
+//! currently authorized for the sender; an account that registered a
+//! recovery key must append a co-signature by it over the same payload.
+//! Naming a recovery key in the receiver registers (or replaces) it.
+//!
+//! The address never changes — it identifies the account, not the key.
+
+use pqcrypto_sphincsplus::sphincsshake256fsimple;
+use sha3::{Digest, Sha3_256};
+
+/// Receiver prefix marking a key-change transaction.
+pub const KEY_CHANGE_PREFIX: &str = "bleep:key_change/";
+
+/// Length of a SPHINCS+ public key in the wire signature.
+pub const PUBLIC_KEY_LEN: usize = 64;
+
+/// Hash accounts record their authorized keys under.
+pub fn key_hash(public_key: &[u8]) -> [u8; 32] {
+    Sha3_256::digest(public_key).into()
+}
+
+/// Keys a key-change transaction installs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyChange {
+    pub new_key_hash:      [u8; 32],
+    /// Recovery key to register, replacing any registered one.
+    pub recovery_key_hash: Option<[u8; 32]>,
+}
+
+impl KeyChange {
+    /// Receiver of the transaction carrying this change.
+    pub fn receiver(&self) -> String {
+        match self.recovery_key_hash {
+            Some(r) => format!("{}{}/{}", KEY_CHANGE_PREFIX, hex::encode(self.new_key_hash), hex::encode(r)),
+            None => format!("{}{}", KEY_CHANGE_PREFIX, hex::encode(self.new_key_hash)),
+        }
+    }
+
+    /// Parse a receiver; `None` if it is not a key change at all.
+    pub fn parse(receiver: &str) -> Option<Result<KeyChange, String>> {
+        let rest = receiver.strip_prefix(KEY_CHANGE_PREFIX)?;
+        let mut parts = rest.split('/');
+        let new_key_hash = parse_hash(parts.next().unwrap_or_default());
+        let recovery_key_hash = parts.next().map(parse_hash).transpose();
+        Some(match (new_key_hash, recovery_key_hash, parts.next()) {
+            (Ok(new_key_hash), Ok(recovery_key_hash), None) => Ok(KeyChange { new_key_hash, recovery_key_hash }),
+            (Err(e), _, _) | (_, Err(e), _) => Err(e),
+            (_, _, Some(_)) => Err("trailing fields in key-change receiver".into()),
+        })
+    }
+}
+
+/// Whether `receiver` marks a key-change transaction.
+pub fn is_key_change(receiver: &str) -> bool {
+    receiver.starts_with(KEY_CHANGE_PREFIX)
+}
+
+fn parse_hash(s: &str) -> Result<[u8; 32], String> {
+    let bytes = hex::decode(s).map_err(|e| format!("bad key hash: {}", e))?;
+    bytes.try_into().map_err(|_| "key hash must be 32 bytes".to_string())
+}
+
+/// Split a wire signature into the primary `pk ‖ sig` and the recovery
+/// co-signature `recovery_pk ‖ recovery_sig`, if one is appended.
+pub fn split_cosigned(signature: &[u8]) -> (&[u8], Option<&[u8]>) {
+    let primary = PUBLIC_KEY_LEN + sphincsshake256fsimple::signature_bytes();
+    if signature.len() > primary {
+        let (primary, cosig) = signature.split_at(primary);
+        (primary, Some(cosig))
+    } else {
+        (signature, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receiver_roundtrip() {
+        let change = KeyChange { new_key_hash: [1u8; 32], recovery_key_hash: None };
+        assert_eq!(KeyChange::parse(&change.receiver()), Some(Ok(change)));
+        let change = KeyChange { new_key_hash: [1u8; 32], recovery_key_hash: Some([2u8; 32]) };
+        assert_eq!(KeyChange::parse(&change.receiver()), Some(Ok(change)));
+
+        assert_eq!(KeyChange::parse("bob"), None);
+        assert!(matches!(KeyChange::parse("bleep:key_change/abcd"), Some(Err(_))));
+    }
+
+    #[test]
+    fn cosignature_is_split_off() {
+        let (pk, sk) = crate::tx_signer::generate_tx_keypair();
+        let payload = crate::tx_signer::tx_payload("alice", "bleep:key_change/00", 0, 1);
+        let mut signature = pk.clone();
+        signature.extend(crate::tx_signer::sign_tx_payload(&payload, &sk).unwrap());
+        assert_eq!(split_cosigned(&signature), (&signature[..], None));
+
+        let primary_len = signature.len();
+        signature.extend(&pk);
+        signature.extend(crate::tx_signer::sign_tx_payload(&payload, &sk).unwrap());
+        let (primary, cosig) = split_cosigned(&signature);
+        assert_eq!(primary.len(), primary_len);
+        assert_eq!(cosig.map(<[u8]>::len), Some(primary_len));
+    }
+}
@@ -1,10 +1,15 @@
 pub mod quantum_secure;
 pub mod bip39;
 pub mod tx_signer;
+pub mod chain_id;
+pub mod key_change;
+pub mod message_signer;
 pub mod merkletree;
 pub mod logging;
+pub mod redaction;
 pub mod quantum_resistance;
 pub mod zkp_verification;
+pub mod prover;
 pub mod anti_asset_loss;
 pub mod pq_crypto;
 pub mod merkle_commitment;
@@ -14,5 +19,7 @@ mod tests;
 
 pub use pq_crypto::*;
 pub use bip39::{mnemonic_to_seed, mnemonic_to_bleep_seed, validate_mnemonic};
-pub use tx_signer::{sign_tx_payload, verify_tx_signature, tx_payload, generate_tx_keypair};
+pub use tx_signer::{sign_tx_payload, verify_tx_signature, tx_payload, generate_tx_keypair, tx_keypair_from_seed};
+pub use message_signer::{message_payload, sign_message, verify_message_signature};
 pub use merkle_commitment::*;
+pub use redaction::Sensitive;
@@ -175,6 +175,64 @@ impl MerkleProof {
     }
 }
 
+/// Position-aware membership proof.
+///
+/// Unlike `MerkleProof`, each step records which side the sibling sits on,
+/// so proofs verify for every leaf position, not only left-most paths.
+/// The verifier learns the proven leaf and one sibling per level — nothing
+/// about the other leaves — which is what allow-list checks rely on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MembershipProof {
+    /// SHA3-256 hash of the leaf data
+    pub leaf_hash: [u8; 32],
+
+    /// (sibling hash, sibling_is_left) from leaf level up to the root
+    pub path: Vec<([u8; 32], bool)>,
+}
+
+impl MembershipProof {
+    /// Fold the path into the root it commits to
+    pub fn compute_root(&self) -> [u8; 32] {
+        let mut current = self.leaf_hash;
+        for (sibling, sibling_is_left) in &self.path {
+            current = if *sibling_is_left {
+                hash_pair(sibling, &current)
+            } else {
+                hash_pair(&current, sibling)
+            };
+        }
+        current
+    }
+
+    /// Verify that the proven leaf is included under `root`
+    pub fn verify(&self, root: &[u8; 32]) -> MerkleResult<()> {
+        if self.compute_root() == *root {
+            Ok(())
+        } else {
+            Err(MerkleError::ProofVerificationFailed(
+                "Membership path does not lead to expected root".to_string()
+            ))
+        }
+    }
+
+    /// Verify that `data` is the proven leaf and is included under `root`
+    pub fn verify_leaf(&self, data: &[u8], root: &[u8; 32]) -> MerkleResult<()> {
+        if MerkleLeaf::new(0, data.to_vec()).hash != self.leaf_hash {
+            return Err(MerkleError::InvalidProof("Leaf data does not match proof".to_string()));
+        }
+        self.verify(root)
+    }
+}
+
+fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(left);
+    hasher.update(right);
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&hasher.finalize());
+    hash
+}
+
 /// A complete Merkle tree
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MerkleTree {
@@ -351,6 +409,41 @@ impl MerkleTree {
         })
     }
     
+    /// Generate a position-aware membership proof for a leaf
+    pub fn prove_membership(&self, leaf_index: u64) -> MerkleResult<MembershipProof> {
+        if !self.finalized {
+            return Err(MerkleError::InvalidProof("Tree not finalized".to_string()));
+        }
+
+        let position = *self.leaf_index.get(&leaf_index)
+            .ok_or_else(|| MerkleError::LeafNotFound(format!("Leaf {} not found", leaf_index)))?;
+
+        let mut level: Vec<[u8; 32]> = self.leaves.iter().map(|l| l.hash).collect();
+        let mut index = position;
+        let mut path = Vec::new();
+
+        while level.len() > 1 {
+            // Odd node out is paired with itself, matching `finalize`
+            let (sibling, sibling_is_left) = if index % 2 == 1 {
+                (level[index - 1], true)
+            } else {
+                (level[(index + 1).min(level.len() - 1)], false)
+            };
+            path.push((sibling, sibling_is_left));
+
+            level = level
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            index /= 2;
+        }
+
+        Ok(MembershipProof {
+            leaf_hash: self.leaves[position].hash,
+            path,
+        })
+    }
+
     /// Get a leaf
     pub fn get_leaf(&self, index: u64) -> MerkleResult<MerkleLeaf> {
         let position = self.leaf_index.get(&index)
@@ -486,6 +579,23 @@ mod tests {
         proof.verify(&root).unwrap();
     }
 
+    #[test]
+    fn test_membership_proof_every_position() {
+        let mut tree = MerkleTree::new();
+        for i in 0..5u8 {
+            tree.add_leaf(vec![i; 4]).unwrap();
+        }
+        let root = tree.finalize().unwrap();
+
+        for i in 0..5u64 {
+            let proof = tree.prove_membership(i).unwrap();
+            proof.verify_leaf(&[i as u8; 4], &root).unwrap();
+        }
+        let proof = tree.prove_membership(3).unwrap();
+        assert!(proof.verify_leaf(&[9u8; 4], &root).is_err());
+        assert!(proof.verify(&[0u8; 32]).is_err());
+    }
+
     #[test]
     fn test_commitment_create_and_verify() {
         let data = b"secret data";
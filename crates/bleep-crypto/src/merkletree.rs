@@ -1,50 +1,381 @@
+//! # MerkleTree
+//!
+//! SHA3-256 binary Merkle tree over raw leaves: a leaf hashes to
+//! `H(0x00 || leaf)`, a pair to `H(0x01 || left || right)`, and the odd
+//! node at the end of a level is promoted as `H(0x01 || node)`.  The
+//! prefixes keep an interior node from passing as a leaf.  The empty
+//! tree's root is 32 zero bytes.
+//!
+//! `generate_inclusion_proof` / `verify_inclusion` prove a leaf is in the
+//! tree.  A tree built with `MerkleTree::sorted` keeps its leaves in byte
+//! order without duplicates, which also lets it prove a leaf is *absent*
+//! (`generate_exclusion_proof` / `verify_exclusion`): the proof shows the
+//! two leaves that would surround it sitting at adjacent positions, or the
+//! first or last leaf if it would fall outside them.  The ZKP revocation
+//! tree is sorted so a wallet can prove its key is not revoked.
+//!
+//! Each proof step records which side the sibling is on, or that the node
+//! was promoted alone, and that shape is bound by the root, so a verified
+//! proof also fixes the leaf's position.  Every leaf's proof has one step
+//! per level, so the two neighbours of a gap must have proofs of the same
+//! length.  Verification needs only the root.
+
+use serde::{Deserialize, Serialize};
 use sha3::{Digest, Sha3_256};
 
 #[derive(Default, Clone, Debug)]
 pub struct MerkleTree {
     leaves: Vec<Vec<u8>>,
     root: Vec<u8>,
+    /// Leaves kept in byte order, without duplicates.
+    sorted: bool,
+}
+
+/// One level of an inclusion proof, leaf end first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProofStep {
+    /// The node is a left child; this is its right sibling.
+    Left(Vec<u8>),
+    /// The node is a right child; this is its left sibling.
+    Right(Vec<u8>),
+    /// The node was last on an odd level and hashed alone.
+    Promoted,
+}
+
+/// Path from a leaf to the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub steps: Vec<ProofStep>,
+}
+
+impl MerkleProof {
+    /// Position of the proven leaf.
+    pub fn index(&self) -> u64 {
+        self.steps.iter().enumerate()
+            .filter(|(_, s)| matches!(s, ProofStep::Right(_)))
+            .map(|(level, _)| 1u64 << level)
+            .sum()
+    }
+
+    fn is_first(&self) -> bool {
+        !self.steps.iter().any(|s| matches!(s, ProofStep::Right(_)))
+    }
+
+    fn is_last(&self) -> bool {
+        !self.steps.iter().any(|s| matches!(s, ProofStep::Left(_)))
+    }
+}
+
+/// An inclusion proof for a neighbouring leaf.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Neighbour {
+    pub leaf: Vec<u8>,
+    pub proof: MerkleProof,
+}
+
+/// Proof that a leaf is not in a sorted tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExclusionProof {
+    /// The tree has no leaves.
+    Empty,
+    /// The leaf sorts before the first leaf.
+    BeforeFirst(Neighbour),
+    /// The leaf sorts after the last leaf.
+    AfterLast(Neighbour),
+    /// The leaf sorts between two adjacent leaves.
+    Between(Neighbour, Neighbour),
+}
+
+fn hash_leaf(leaf: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    hasher.update([0x00]);
+    hasher.update(leaf);
+    hasher.finalize().to_vec()
+}
+
+fn hash_promoted(node: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    hasher.update([0x01]);
+    hasher.update(node);
+    hasher.finalize().to_vec()
+}
+
+fn hash_pair(left: &[u8], right: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().to_vec()
 }
 
 impl MerkleTree {
     pub fn new() -> Self {
-        MerkleTree { leaves: vec![], root: vec![] }
+        MerkleTree { leaves: vec![], root: vec![0u8; 32], sorted: false }
+    }
+
+    /// A tree that keeps its leaves in byte order, so it can prove absence.
+    pub fn sorted() -> Self {
+        MerkleTree { sorted: true, ..Self::new() }
     }
 
     pub fn add_leaf(&mut self, leaf: Vec<u8>) {
-        self.leaves.push(leaf);
+        if self.sorted {
+            match self.leaves.binary_search(&leaf) {
+                Ok(_) => return,
+                Err(pos) => self.leaves.insert(pos, leaf),
+            }
+        } else {
+            self.leaves.push(leaf);
+        }
         self.root = self.calculate_root();
     }
 
     pub fn contains_leaf(&self, leaf: &[u8]) -> bool {
-        self.leaves.iter().any(|l| l == leaf)
+        self.position(leaf).is_some()
     }
 
     pub fn root(&self) -> Vec<u8> {
         self.root.clone()
     }
 
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    fn position(&self, leaf: &[u8]) -> Option<usize> {
+        if self.sorted {
+            self.leaves.binary_search_by(|l| l.as_slice().cmp(leaf)).ok()
+        } else {
+            self.leaves.iter().position(|l| l == leaf)
+        }
+    }
+
+    /// Every level of hashes, leaves first.
+    fn levels(&self) -> Vec<Vec<Vec<u8>>> {
+        let mut levels = vec![self.leaves.iter().map(|l| hash_leaf(l)).collect::<Vec<_>>()];
+        while levels.last().map_or(false, |l| l.len() > 1) {
+            let next = levels.last().unwrap().chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => hash_pair(left, right),
+                    [single] => hash_promoted(single),
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+        levels
+    }
+
     fn calculate_root(&self) -> Vec<u8> {
         if self.leaves.is_empty() {
             return vec![0u8; 32];
         }
-        let mut hashes: Vec<Vec<u8>> = self.leaves.iter().map(|leaf| {
-            let mut hasher = Sha3_256::new();
-            hasher.update(leaf);
-            hasher.finalize().to_vec()
-        }).collect();
-        while hashes.len() > 1 {
-            let mut next_level = vec![];
-            for pair in hashes.chunks(2) {
-                let mut hasher = Sha3_256::new();
-                hasher.update(&pair[0]);
-                if pair.len() == 2 {
-                    hasher.update(&pair[1]);
+        self.levels().pop().unwrap().remove(0)
+    }
+
+    fn proof_at(&self, index: usize) -> MerkleProof {
+        let levels = self.levels();
+        let mut steps = Vec::with_capacity(levels.len() - 1);
+        let mut pos = index;
+        for level in &levels[..levels.len() - 1] {
+            steps.push(if pos % 2 == 1 {
+                ProofStep::Right(level[pos - 1].clone())
+            } else if pos + 1 < level.len() {
+                ProofStep::Left(level[pos + 1].clone())
+            } else {
+                ProofStep::Promoted
+            });
+            pos /= 2;
+        }
+        MerkleProof { steps }
+    }
+
+    /// Path proving `leaf` is in the tree; `None` if it is not.
+    pub fn generate_inclusion_proof(&self, leaf: &[u8]) -> Option<MerkleProof> {
+        self.position(leaf).map(|i| self.proof_at(i))
+    }
+
+    /// Proof that `leaf` is not in the tree.  `None` if it is, or if the
+    /// tree is not `sorted`.
+    pub fn generate_exclusion_proof(&self, leaf: &[u8]) -> Option<ExclusionProof> {
+        if !self.sorted {
+            return None;
+        }
+        let pos = self.leaves.binary_search_by(|l| l.as_slice().cmp(leaf)).err()?;
+        let neighbour = |i: usize| Neighbour { leaf: self.leaves[i].clone(), proof: self.proof_at(i) };
+        Some(if self.leaves.is_empty() {
+            ExclusionProof::Empty
+        } else if pos == 0 {
+            ExclusionProof::BeforeFirst(neighbour(0))
+        } else if pos == self.leaves.len() {
+            ExclusionProof::AfterLast(neighbour(pos - 1))
+        } else {
+            ExclusionProof::Between(neighbour(pos - 1), neighbour(pos))
+        })
+    }
+}
+
+/// Whether `proof` leads from `leaf` to `root`.
+pub fn verify_inclusion(root: &[u8], leaf: &[u8], proof: &MerkleProof) -> bool {
+    if proof.steps.len() >= 64 {
+        return false;
+    }
+    let mut node = hash_leaf(leaf);
+    for step in &proof.steps {
+        node = match step {
+            ProofStep::Left(sibling) => hash_pair(&node, sibling),
+            ProofStep::Right(sibling) => hash_pair(sibling, &node),
+            ProofStep::Promoted => hash_promoted(&node),
+        };
+    }
+    node == root
+}
+
+/// Whether `proof` shows `leaf` is absent from the sorted tree with `root`.
+pub fn verify_exclusion(root: &[u8], leaf: &[u8], proof: &ExclusionProof) -> bool {
+    let holds = |n: &Neighbour| verify_inclusion(root, &n.leaf, &n.proof);
+    match proof {
+        ExclusionProof::Empty => root == [0u8; 32],
+        ExclusionProof::BeforeFirst(first) => {
+            leaf < first.leaf.as_slice() && first.proof.is_first() && holds(first)
+        }
+        ExclusionProof::AfterLast(last) => {
+            leaf > last.leaf.as_slice() && last.proof.is_last() && holds(last)
+        }
+        ExclusionProof::Between(lower, upper) => {
+            lower.leaf.as_slice() < leaf
+                && leaf < upper.leaf.as_slice()
+                && lower.proof.steps.len() == upper.proof.steps.len()
+                && lower.proof.index().checked_add(1) == Some(upper.proof.index())
+                && holds(lower)
+                && holds(upper)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn tree(leaves: &[Vec<u8>], sorted: bool) -> MerkleTree {
+        let mut tree = if sorted { MerkleTree::sorted() } else { MerkleTree::new() };
+        for leaf in leaves {
+            tree.add_leaf(leaf.clone());
+        }
+        tree
+    }
+
+    #[test]
+    fn test_exclusion_at_every_gap() {
+        let t = tree(&[b"b".to_vec(), b"d".to_vec(), b"f".to_vec()], true);
+        let root = t.root();
+        for absent in [&b"a"[..], b"c", b"e", b"g"] {
+            let proof = t.generate_exclusion_proof(absent).unwrap();
+            assert!(verify_exclusion(&root, absent, &proof), "{:?}", absent);
+        }
+        assert!(t.generate_exclusion_proof(b"d").is_none());
+
+        // A gap proof does not cover leaves outside it, nor non-adjacent pairs
+        let proof = t.generate_exclusion_proof(b"c").unwrap();
+        assert!(!verify_exclusion(&root, b"e", &proof));
+        let wide = ExclusionProof::Between(
+            Neighbour { leaf: b"b".to_vec(), proof: t.generate_inclusion_proof(b"b").unwrap() },
+            Neighbour { leaf: b"f".to_vec(), proof: t.generate_inclusion_proof(b"f").unwrap() },
+        );
+        assert!(!verify_exclusion(&root, b"d", &wide));
+        let not_last = ExclusionProof::AfterLast(
+            Neighbour { leaf: b"d".to_vec(), proof: t.generate_inclusion_proof(b"d").unwrap() },
+        );
+        assert!(!verify_exclusion(&root, b"e", &not_last));
+
+        let empty = MerkleTree::sorted();
+        let proof = empty.generate_exclusion_proof(b"x").unwrap();
+        assert!(verify_exclusion(&empty.root(), b"x", &proof));
+        assert!(MerkleTree::new().generate_exclusion_proof(b"x").is_none());
+    }
+
+    #[test]
+    fn test_interior_nodes_do_not_pass_as_leaves() {
+        let leaves: Vec<Vec<u8>> = [b"a", b"b", b"c", b"d"].iter().map(|l| l.to_vec()).collect();
+        let t = tree(&leaves, true);
+        let proof = t.generate_inclusion_proof(b"a").unwrap();
+        // The parent of "a" and "b", posing as a leaf one level up
+        let parent = [hash_leaf(b"a"), hash_leaf(b"b")].concat();
+        let short = MerkleProof { steps: proof.steps[1..].to_vec() };
+        assert!(!verify_inclusion(&t.root(), &parent, &short));
+    }
+
+    #[test]
+    fn test_proof_is_serializable() {
+        let t = tree(&[b"k1".to_vec(), b"k2".to_vec(), b"k3".to_vec()], true);
+        let proof = t.generate_exclusion_proof(b"k0").unwrap();
+        let decoded: ExclusionProof = serde_json::from_str(&serde_json::to_string(&proof).unwrap()).unwrap();
+        assert!(verify_exclusion(&t.root(), b"k0", &decoded));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_every_leaf_proves_and_flips_break_it(
+            leaves in prop::collection::vec(prop::collection::vec(any::<u8>(), 1..16), 1..40),
+            sorted in any::<bool>(),
+            pick in any::<prop::sample::Index>(),
+            flip in any::<prop::sample::Index>(),
+        ) {
+            let t = tree(&leaves, sorted);
+            let root = t.root();
+            for leaf in &leaves {
+                let proof = t.generate_inclusion_proof(leaf).unwrap();
+                prop_assert!(verify_inclusion(&root, leaf, &proof));
+            }
+
+            let leaf = pick.get(&leaves);
+            let proof = t.generate_inclusion_proof(leaf).unwrap();
+
+            let mut bad_leaf = leaf.clone();
+            let i = flip.index(bad_leaf.len());
+            bad_leaf[i] ^= 0x01;
+            prop_assert!(!verify_inclusion(&root, &bad_leaf, &proof));
+
+            let mut bad_root = root.clone();
+            bad_root[flip.index(32)] ^= 0x01;
+            prop_assert!(!verify_inclusion(&bad_root, leaf, &proof));
+
+            let siblings: Vec<usize> = proof.steps.iter().enumerate()
+                .filter(|(_, s)| !matches!(s, ProofStep::Promoted))
+                .map(|(i, _)| i)
+                .collect();
+            if !siblings.is_empty() {
+                let mut bad_proof = proof.clone();
+                if let ProofStep::Left(s) | ProofStep::Right(s) = &mut bad_proof.steps[*flip.get(&siblings)] {
+                    s[flip.index(32)] ^= 0x01;
                 }
-                next_level.push(hasher.finalize().to_vec());
+                prop_assert!(!verify_inclusion(&root, leaf, &bad_proof));
             }
-            hashes = next_level;
         }
-        hashes[0].clone()
-    }
+
+        #[test]
+        fn prop_absent_leaves_prove_exclusion(
+            leaves in prop::collection::btree_set(prop::collection::vec(any::<u8>(), 1..8), 0..30),
+            probe in prop::collection::vec(any::<u8>(), 1..8),
+        ) {
+            let leaves: Vec<Vec<u8>> = leaves.into_iter().collect();
+            let t = tree(&leaves, true);
+            match t.generate_exclusion_proof(&probe) {
+                Some(proof) => {
+                    prop_assert!(!leaves.contains(&probe));
+                    prop_assert!(verify_exclusion(&t.root(), &probe, &proof));
+                }
+                None => prop_assert!(leaves.contains(&probe)),
+            }
+            for leaf in &leaves {
+                let proof = t.generate_inclusion_proof(leaf).unwrap();
+                prop_assert!(verify_inclusion(&t.root(), leaf, &proof));
+            }
         }
+    }
+}
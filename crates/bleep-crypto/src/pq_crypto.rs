@@ -116,7 +116,7 @@ impl KyberPublicKey {
 }
 
 /// Kyber1024 secret key wrapper
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct KyberSecretKey {
     bytes: Vec<u8>,
 }
@@ -157,6 +157,13 @@ impl KyberSecretKey {
     }
 }
 
+impl fmt::Debug for KyberSecretKey {
+    /// Never prints key material; see `crate::redaction`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED:KyberSecretKey]")
+    }
+}
+
 /// Kyber1024 ciphertext wrapper
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct KyberCiphertext {
@@ -184,7 +191,7 @@ impl KyberCiphertext {
 }
 
 /// 32-byte shared secret
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct SharedSecret {
     bytes: [u8; 32],
 }
@@ -195,6 +202,12 @@ impl SharedSecret {
     }
 }
 
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED:SharedSecret]")
+    }
+}
+
 /// Kyber KEM operations
 pub struct KyberKem;
 
@@ -339,7 +352,7 @@ impl PublicKey {
 ///
 /// Wrapped in `zeroize` to ensure the key material is zeroed on drop,
 /// defending against cold-boot and core-dump key recovery (SA-L3 fix).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SecretKey {
     /// Raw SPHINCS+ secret key bytes; zeroized on drop.
     bytes: Vec<u8>,
@@ -371,6 +384,12 @@ impl Drop for SecretKey {
     }
 }
 
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[REDACTED:SecretKey]")
+    }
+}
+
 /// SPHINCS+-SHAKE-256f-simple signature scheme (NIST PQC Level 5).
 ///
 /// Replaces the former SHA3-MAC stub.  All operations call the
@@ -0,0 +1,662 @@
+//! # Background proof generation
+//!
+//! A Groth16 proof takes seconds of CPU; generating one inline freezes the
+//! UI or RPC handler that asked for it.  `ProverService` runs proof jobs on
+//! a fixed pool of worker threads instead:
+//!
+//! ```text
+//! submit(request id, job) ──► bounded FIFO ──► N worker threads
+//!          │                                        │
+//!          ▼                                        ▼
+//!        JobId    status(): Queued { position }    <store_dir>/<sha256(request id)>.proof
+//!                         → Proving { percent, stage }
+//!                         → Done(proof) | Failed(reason)
+//! ```
+//!
+//! - A job reports progress through `JobContext::milestone`; `Groth16Job`
+//!   reports synthesis, proving and encoding.
+//! - `cancel` drops a queued job at once; a running job stops at its next
+//!   milestone.  Either way the job disappears.
+//! - Finished proofs are written to `store_dir`, keyed by request id, so a
+//!   request resubmitted after a restart is answered without proving again.
+//!   Submitting a request id that is queued, running or done returns the
+//!   same job.
+//! - `ProverTelemetry` exports queue depth and proof latency.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use ark_bls12_381::Bls12_381;
+use ark_groth16::{Groth16, ProvingKey};
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_serialize::CanonicalSerialize;
+use bleep_telemetry::metrics::{MetricCounter, MetricGauge, MetricsRegistry};
+use parking_lot::{Condvar, Mutex};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::zkp_verification::Fr;
+
+/// Worker threads and queue bound of a `ProverService`.
+#[derive(Debug, Clone)]
+pub struct ProverConfig {
+    pub workers:        usize,
+    /// Jobs that may wait for a worker; further submissions are refused.
+    pub queue_capacity: usize,
+    /// Where finished proofs are kept.  `None` keeps them in memory only.
+    pub store_dir:      Option<PathBuf>,
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        ProverConfig { workers: 1, queue_capacity: 64, store_dir: None }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ProverError {
+    #[error("prover queue is full ({0} jobs waiting)")]
+    QueueFull(usize),
+    #[error("proof job {0} was cancelled")]
+    Cancelled(JobId),
+    #[error("proof generation failed: {0}")]
+    Failed(String),
+    #[error("proof store: {0}")]
+    Storage(String),
+}
+
+/// Handle of a submitted job: the request id it was submitted under.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JobId(String);
+
+impl JobId {
+    pub fn new(request_id: &str) -> Self {
+        JobId(request_id.to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for JobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    /// Waiting for a worker; `position` 0 is next.
+    Queued { position: usize },
+    /// Running; `percent` only moves at the job's milestones.
+    Proving { percent: u8, stage: &'static str },
+    Done(Vec<u8>),
+    Failed(String),
+}
+
+/// Work a `ProverService` runs: produce a serialized proof.
+pub trait ProofJob: Send + 'static {
+    /// Report progress through `ctx` and give up once it says cancelled.
+    fn prove(self: Box<Self>, ctx: &JobContext) -> Result<Vec<u8>, String>;
+}
+
+/// Progress reporting and cancellation for a running job.
+pub struct JobContext {
+    shared:    Arc<Shared>,
+    id:        JobId,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobContext {
+    /// Record that the job reached `percent` at `stage`; fails once the job
+    /// has been cancelled, so `?` stops it.
+    pub fn milestone(&self, percent: u8, stage: &'static str) -> Result<(), String> {
+        if self.is_cancelled() {
+            return Err("cancelled".into());
+        }
+        let mut inner = self.shared.inner.lock();
+        if let Some(entry) = inner.jobs.get_mut(&self.id) {
+            entry.state = State::Proving { percent: percent.min(100), stage };
+        }
+        self.shared.changed.notify_all();
+        Ok(())
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Groth16 proof over BLS12-381 for `circuit`, compressed.
+pub struct Groth16Job<C> {
+    circuit:     C,
+    proving_key: Arc<ProvingKey<Bls12_381>>,
+}
+
+impl<C> Groth16Job<C> {
+    pub fn new(circuit: C, proving_key: Arc<ProvingKey<Bls12_381>>) -> Self {
+        Groth16Job { circuit, proving_key }
+    }
+}
+
+impl<C: ConstraintSynthesizer<Fr> + Clone + Send + 'static> ProofJob for Groth16Job<C> {
+    fn prove(self: Box<Self>, ctx: &JobContext) -> Result<Vec<u8>, String> {
+        ctx.milestone(5, "synthesizing")?;
+        // A bad witness fails here, before the expensive part.
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        self.circuit.clone().generate_constraints(cs.clone()).map_err(|e| e.to_string())?;
+        if !cs.is_satisfied().map_err(|e| e.to_string())? {
+            return Err("witness does not satisfy the circuit".into());
+        }
+        ctx.milestone(30, "proving")?;
+        let proof = Groth16::<Bls12_381>::create_random_proof_with_reduction(
+            self.circuit, &self.proving_key, &mut rand::thread_rng(),
+        )
+        .map_err(|e| e.to_string())?;
+        ctx.milestone(95, "encoding")?;
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).map_err(|e| e.to_string())?;
+        Ok(bytes)
+    }
+}
+
+// ── Service ───────────────────────────────────────────────────────────────────
+
+enum State {
+    Queued(Box<dyn ProofJob>),
+    Proving { percent: u8, stage: &'static str },
+    Done(Vec<u8>),
+    Failed(String),
+}
+
+struct Entry {
+    state:     State,
+    cancelled: Arc<AtomicBool>,
+    submitted: Instant,
+}
+
+#[derive(Default)]
+struct Inner {
+    jobs:      HashMap<JobId, Entry>,
+    queue:     VecDeque<JobId>,
+    shutdown:  bool,
+    counters:  ProverStats,
+}
+
+struct Shared {
+    config:  ProverConfig,
+    inner:   Mutex<Inner>,
+    /// Signalled when a job is queued or on shutdown.
+    work:    Condvar,
+    /// Signalled on every state change, for `wait`.
+    changed: Condvar,
+}
+
+/// Point-in-time copy of the prover counters, for telemetry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProverStats {
+    pub queue_depth:       usize,
+    pub proving:           usize,
+    pub completed:         u64,
+    pub failed:            u64,
+    pub cancelled:         u64,
+    /// Submission to finished proof, for the last and all completed jobs.
+    pub last_latency_ms:   u64,
+    pub total_latency_ms:  u64,
+}
+
+impl ProverStats {
+    pub fn avg_latency_ms(&self) -> u64 {
+        if self.completed == 0 { 0 } else { self.total_latency_ms / self.completed }
+    }
+}
+
+/// Runs `ProofJob`s on worker threads; see the module docs.
+pub struct ProverService {
+    shared:  Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ProverService {
+    pub fn new(config: ProverConfig) -> Self {
+        let workers = config.workers.max(1);
+        let shared = Arc::new(Shared {
+            config,
+            inner:   Mutex::new(Inner::default()),
+            work:    Condvar::new(),
+            changed: Condvar::new(),
+        });
+        let workers = (0..workers)
+            .map(|i| {
+                let shared = Arc::clone(&shared);
+                std::thread::Builder::new()
+                    .name(format!("bleep-prover-{i}"))
+                    .spawn(move || work(shared))
+                    .expect("spawn prover thread")
+            })
+            .collect();
+        ProverService { shared, workers }
+    }
+
+    /// Queue `job` under `request_id`.  A request that is already queued,
+    /// running or proven (in memory or in the store) is not proven again.
+    pub fn submit(&self, request_id: &str, job: Box<dyn ProofJob>) -> Result<JobId, ProverError> {
+        let id = JobId::new(request_id);
+        let stored = self.load(&id)?;
+        let mut inner = self.shared.inner.lock();
+        if inner.jobs.get(&id).is_some_and(|e| !matches!(e.state, State::Failed(_))) {
+            return Ok(id);
+        }
+        if let Some(proof) = stored {
+            inner.jobs.insert(id.clone(), Entry::new(State::Done(proof)));
+            return Ok(id);
+        }
+        if inner.queue.len() >= self.shared.config.queue_capacity {
+            return Err(ProverError::QueueFull(inner.queue.len()));
+        }
+        inner.jobs.insert(id.clone(), Entry::new(State::Queued(job)));
+        inner.queue.push_back(id.clone());
+        self.shared.work.notify_one();
+        Ok(id)
+    }
+
+    /// Where `id` stands; `None` for unknown and cancelled jobs.
+    pub fn status(&self, id: &JobId) -> Option<JobStatus> {
+        {
+            let inner = self.shared.inner.lock();
+            if let Some(entry) = inner.jobs.get(id) {
+                return Some(match &entry.state {
+                    State::Queued(_) => JobStatus::Queued {
+                        position: inner.queue.iter().position(|q| q == id).unwrap_or(0),
+                    },
+                    State::Proving { percent, stage } => JobStatus::Proving { percent: *percent, stage: *stage },
+                    State::Done(proof) => JobStatus::Done(proof.clone()),
+                    State::Failed(reason) => JobStatus::Failed(reason.clone()),
+                });
+            }
+        }
+        self.load(id).ok().flatten().map(JobStatus::Done)
+    }
+
+    /// Drop a queued job, or stop a running one at its next milestone.
+    /// `false` if the job is unknown or already finished.
+    pub fn cancel(&self, id: &JobId) -> bool {
+        let mut inner = self.shared.inner.lock();
+        let Some(entry) = inner.jobs.get(id) else { return false };
+        match entry.state {
+            State::Queued(_) => {
+                inner.jobs.remove(id);
+                inner.queue.retain(|q| q != id);
+                inner.counters.cancelled += 1;
+                self.shared.changed.notify_all();
+                true
+            }
+            State::Proving { .. } => {
+                entry.cancelled.store(true, Ordering::Relaxed);
+                true
+            }
+            State::Done(_) | State::Failed(_) => false,
+        }
+    }
+
+    /// Block until `id` finishes.
+    pub fn wait(&self, id: &JobId) -> Result<Vec<u8>, ProverError> {
+        let mut inner = self.shared.inner.lock();
+        loop {
+            match inner.jobs.get(id).map(|e| &e.state) {
+                Some(State::Done(proof)) => return Ok(proof.clone()),
+                Some(State::Failed(reason)) => return Err(ProverError::Failed(reason.clone())),
+                Some(_) => self.shared.changed.wait(&mut inner),
+                None => {
+                    drop(inner);
+                    return match self.load(id)? {
+                        Some(proof) => Ok(proof),
+                        None => Err(ProverError::Cancelled(id.clone())),
+                    };
+                }
+            }
+        }
+    }
+
+    /// `submit` then `wait`, for callers that can block.
+    pub fn prove_blocking(&self, request_id: &str, job: Box<dyn ProofJob>) -> Result<Vec<u8>, ProverError> {
+        let id = self.submit(request_id, job)?;
+        self.wait(&id)
+    }
+
+    pub fn stats(&self) -> ProverStats {
+        let inner = self.shared.inner.lock();
+        ProverStats {
+            queue_depth: inner.queue.len(),
+            proving: inner.jobs.values().filter(|e| matches!(e.state, State::Proving { .. })).count(),
+            ..inner.counters
+        }
+    }
+
+    fn load(&self, id: &JobId) -> Result<Option<Vec<u8>>, ProverError> {
+        let Some(path) = proof_path(&self.shared.config, id) else { return Ok(None) };
+        match std::fs::read(&path) {
+            Ok(proof) => Ok(Some(proof)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ProverError::Storage(format!("{}: {}", path.display(), e))),
+        }
+    }
+}
+
+impl Drop for ProverService {
+    fn drop(&mut self) {
+        self.shared.inner.lock().shutdown = true;
+        self.shared.work.notify_all();
+        // Running jobs are stopped at their next milestone.
+        for entry in self.shared.inner.lock().jobs.values() {
+            entry.cancelled.store(true, Ordering::Relaxed);
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Entry {
+    fn new(state: State) -> Self {
+        Entry { state, cancelled: Arc::new(AtomicBool::new(false)), submitted: Instant::now() }
+    }
+}
+
+fn proof_path(config: &ProverConfig, id: &JobId) -> Option<PathBuf> {
+    let dir = config.store_dir.as_ref()?;
+    Some(dir.join(format!("{}.proof", hex::encode(Sha256::digest(id.as_str().as_bytes())))))
+}
+
+fn persist(config: &ProverConfig, id: &JobId, proof: &[u8]) -> Result<(), String> {
+    let Some(path) = proof_path(config, id) else { return Ok(()) };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let tmp = path.with_extension("proof.tmp");
+    std::fs::write(&tmp, proof).map_err(|e| e.to_string())?;
+    std::fs::rename(&tmp, &path).map_err(|e| e.to_string())
+}
+
+/// Worker loop: take the oldest queued job, run it, record the outcome.
+fn work(shared: Arc<Shared>) {
+    loop {
+        let (id, job, cancelled, submitted) = {
+            let mut inner = shared.inner.lock();
+            let id = loop {
+                if inner.shutdown {
+                    return;
+                }
+                match inner.queue.pop_front() {
+                    Some(id) => break id,
+                    None => shared.work.wait(&mut inner),
+                }
+            };
+            let Some(entry) = inner.jobs.get_mut(&id) else { continue };
+            // Only queued jobs are ever in the queue.
+            let State::Queued(job) = std::mem::replace(&mut entry.state, State::Proving { percent: 0, stage: "starting" }) else {
+                continue;
+            };
+            (id, job, Arc::clone(&entry.cancelled), entry.submitted)
+        };
+        shared.changed.notify_all();
+
+        let ctx = JobContext { shared: Arc::clone(&shared), id: id.clone(), cancelled: Arc::clone(&cancelled) };
+        let outcome = catch_unwind(AssertUnwindSafe(|| job.prove(&ctx)))
+            .unwrap_or_else(|_| Err("prover panicked".into()))
+            .and_then(|proof| persist(&shared.config, &id, &proof).map(|()| proof));
+
+        let mut inner = shared.inner.lock();
+        if cancelled.load(Ordering::Relaxed) {
+            inner.jobs.remove(&id);
+            inner.counters.cancelled += 1;
+        } else {
+            let state = match outcome {
+                Ok(proof) => {
+                    let latency = submitted.elapsed().as_millis() as u64;
+                    inner.counters.completed += 1;
+                    inner.counters.last_latency_ms = latency;
+                    inner.counters.total_latency_ms += latency;
+                    State::Done(proof)
+                }
+                Err(reason) => {
+                    inner.counters.failed += 1;
+                    State::Failed(reason)
+                }
+            };
+            if let Some(entry) = inner.jobs.get_mut(&id) {
+                entry.state = state;
+            }
+        }
+        shared.changed.notify_all();
+    }
+}
+
+// ── Telemetry ─────────────────────────────────────────────────────────────────
+
+/// Telemetry handles for a `ProverService`.
+#[derive(Debug, Clone)]
+pub struct ProverTelemetry {
+    queue_depth:  MetricGauge,
+    proving:      MetricGauge,
+    completed:    MetricCounter,
+    failed:       MetricCounter,
+    last_latency: MetricGauge,
+    avg_latency:  MetricGauge,
+    last:         ProverStats,
+}
+
+impl ProverTelemetry {
+    /// Register `bleep_prover_*` metrics.
+    pub fn register(registry: &mut MetricsRegistry) -> Self {
+        ProverTelemetry {
+            queue_depth:  registry.gauge("bleep_prover_queue_depth"),
+            proving:      registry.gauge("bleep_prover_jobs_proving"),
+            completed:    registry.counter("bleep_prover_proofs_completed_total"),
+            failed:       registry.counter("bleep_prover_proofs_failed_total"),
+            last_latency: registry.gauge("bleep_prover_last_latency_ms"),
+            avg_latency:  registry.gauge("bleep_prover_avg_latency_ms"),
+            last:         ProverStats::default(),
+        }
+    }
+
+    pub fn export(&mut self, prover: &ProverService) {
+        let stats = prover.stats();
+        self.queue_depth.set(stats.queue_depth as i64);
+        self.proving.set(stats.proving as i64);
+        self.completed.add(stats.completed.saturating_sub(self.last.completed));
+        self.failed.add(stats.failed.saturating_sub(self.last.failed));
+        self.last_latency.set(stats.last_latency_ms as i64);
+        self.avg_latency.set(stats.avg_latency_ms() as i64);
+        self.last = stats;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zkp_verification::{decode_groth16_proof, BLEEPZKPModule};
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("bleep-prover-{}", hex::encode(rand::random::<[u8; 8]>())))
+    }
+
+    /// Records its name when it runs; waits for `gate` first if given.
+    struct TestJob {
+        name:  &'static str,
+        ran:   Arc<Mutex<Vec<&'static str>>>,
+        gate:  Option<mpsc::Receiver<()>>,
+    }
+
+    impl ProofJob for TestJob {
+        fn prove(self: Box<Self>, ctx: &JobContext) -> Result<Vec<u8>, String> {
+            ctx.milestone(10, "gated")?;
+            if let Some(gate) = &self.gate {
+                gate.recv().map_err(|e| e.to_string())?;
+            }
+            ctx.milestone(90, "recording")?;
+            self.ran.lock().push(self.name);
+            Ok(self.name.as_bytes().to_vec())
+        }
+    }
+
+    fn job(name: &'static str, ran: &Arc<Mutex<Vec<&'static str>>>) -> Box<TestJob> {
+        Box::new(TestJob { name, ran: Arc::clone(ran), gate: None })
+    }
+
+    /// Occupies a worker until the returned sender fires.
+    fn gated(name: &'static str, ran: &Arc<Mutex<Vec<&'static str>>>) -> (Box<TestJob>, mpsc::Sender<()>) {
+        let (tx, rx) = mpsc::channel();
+        (Box::new(TestJob { name, ran: Arc::clone(ran), gate: Some(rx) }), tx)
+    }
+
+    fn wait_for(prover: &ProverService, id: &JobId, pred: impl Fn(&JobStatus) -> bool) {
+        for _ in 0..500 {
+            if prover.status(id).as_ref().is_some_and(&pred) {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        panic!("job {id} stuck at {:?}", prover.status(id));
+    }
+
+    #[test]
+    fn single_worker_runs_jobs_in_fifo_order() {
+        let prover = ProverService::new(ProverConfig::default());
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let (first, release) = gated("first", &ran);
+        let a = prover.submit("a", first).unwrap();
+        let b = prover.submit("b", job("second", &ran)).unwrap();
+        let c = prover.submit("c", job("third", &ran)).unwrap();
+
+        wait_for(&prover, &a, |s| matches!(s, JobStatus::Proving { percent: 10, stage: "gated" }));
+        assert_eq!(prover.status(&b), Some(JobStatus::Queued { position: 0 }));
+        assert_eq!(prover.status(&c), Some(JobStatus::Queued { position: 1 }));
+        assert_eq!(prover.stats().queue_depth, 2);
+
+        release.send(()).unwrap();
+        assert_eq!(prover.wait(&c).unwrap(), b"third");
+        assert_eq!(prover.wait(&a).unwrap(), b"first");
+        assert_eq!(*ran.lock(), ["first", "second", "third"]);
+        let stats = prover.stats();
+        assert_eq!((stats.completed, stats.queue_depth, stats.proving), (3, 0, 0));
+    }
+
+    #[test]
+    fn cancelling_a_queued_job_removes_it() {
+        let prover = ProverService::new(ProverConfig::default());
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let (first, release) = gated("first", &ran);
+        let a = prover.submit("a", first).unwrap();
+        let b = prover.submit("b", job("second", &ran)).unwrap();
+        wait_for(&prover, &a, |s| matches!(s, JobStatus::Proving { .. }));
+
+        assert!(prover.cancel(&b));
+        assert_eq!(prover.status(&b), None);
+        assert_eq!(prover.wait(&b), Err(ProverError::Cancelled(b.clone())));
+        release.send(()).unwrap();
+        prover.wait(&a).unwrap();
+        assert_eq!(*ran.lock(), ["first"]);
+        assert!(!prover.cancel(&a), "finished jobs cannot be cancelled");
+        assert_eq!(prover.stats().cancelled, 1);
+    }
+
+    #[test]
+    fn cancelling_a_running_job_stops_it_at_the_next_milestone() {
+        let prover = ProverService::new(ProverConfig::default());
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let (first, release) = gated("first", &ran);
+        let a = prover.submit("a", first).unwrap();
+        wait_for(&prover, &a, |s| matches!(s, JobStatus::Proving { .. }));
+        assert!(prover.cancel(&a));
+        release.send(()).unwrap();
+        assert_eq!(prover.wait(&a), Err(ProverError::Cancelled(a.clone())));
+        assert!(ran.lock().is_empty());
+    }
+
+    #[test]
+    fn full_queue_refuses_submissions() {
+        let prover = ProverService::new(ProverConfig { queue_capacity: 1, ..Default::default() });
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let (first, release) = gated("first", &ran);
+        let a = prover.submit("a", first).unwrap();
+        wait_for(&prover, &a, |s| matches!(s, JobStatus::Proving { .. }));
+        prover.submit("b", job("second", &ran)).unwrap();
+        assert_eq!(prover.submit("c", job("third", &ran)), Err(ProverError::QueueFull(1)));
+        // Resubmitting a queued request is not a new job.
+        assert!(prover.submit("b", job("second", &ran)).is_ok());
+        release.send(()).unwrap();
+    }
+
+    #[test]
+    fn stored_proof_survives_restart_and_is_not_regenerated() {
+        let dir = temp_dir();
+        let config = ProverConfig { store_dir: Some(dir.clone()), ..Default::default() };
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let proof = {
+            let prover = ProverService::new(config.clone());
+            prover.prove_blocking("transfer-1", job("proved", &ran)).unwrap()
+        };
+
+        let prover = ProverService::new(config);
+        let id = JobId::new("transfer-1");
+        assert_eq!(prover.status(&id), Some(JobStatus::Done(proof.clone())));
+        assert_eq!(prover.prove_blocking("transfer-1", job("again", &ran)).unwrap(), proof);
+        assert_eq!(*ran.lock(), ["proved"]);
+        assert_eq!(prover.stats().completed, 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[derive(Clone)]
+    struct MulCircuit {
+        x: Option<Fr>,
+        y: Option<Fr>,
+    }
+
+    impl ConstraintSynthesizer<Fr> for MulCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = cs.new_witness_variable(|| self.y.ok_or(SynthesisError::AssignmentMissing))?;
+            let z = cs.new_input_variable(|| {
+                Ok(self.x.ok_or(SynthesisError::AssignmentMissing)? * self.y.ok_or(SynthesisError::AssignmentMissing)?)
+            })?;
+            cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)
+        }
+    }
+
+    #[test]
+    fn groth16_job_produces_a_verifying_proof() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let pk = Arc::new(
+            Groth16::<Bls12_381>::generate_random_parameters_with_reduction(MulCircuit { x: None, y: None }, &mut rng)
+                .unwrap(),
+        );
+        let prover = ProverService::new(ProverConfig::default());
+        let circuit = MulCircuit { x: Some(Fr::from(6u64)), y: Some(Fr::from(7u64)) };
+        let bytes = prover.prove_blocking("mul", Box::new(Groth16Job::new(circuit, Arc::clone(&pk)))).unwrap();
+
+        let zkp = BLEEPZKPModule::new().with_groth16_vk(pk.vk.clone());
+        let proof = decode_groth16_proof(&bytes).unwrap();
+        assert!(zkp.verify_groth16(&proof, &[Fr::from(42u64)]).unwrap());
+
+        let mut registry = MetricsRegistry::new();
+        let mut tel = ProverTelemetry::register(&mut registry);
+        tel.export(&prover);
+        assert_eq!(tel.completed.get(), 1);
+        assert_eq!(tel.queue_depth.get(), 0);
+    }
+}
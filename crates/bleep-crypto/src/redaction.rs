@@ -0,0 +1,475 @@
+//! bleep-crypto/src/redaction.rs
+//!
+//! Keeps secrets and PII out of log output.
+//!
+//! - [`Sensitive<T>`] wraps private keys, mnemonics, passphrases and API
+//!   tokens.  Its `Debug` and `Display` always print `[REDACTED:<type>]`,
+//!   so the value cannot end up in a log line by accident.
+//! - [`RedactingFormat`] and [`RedactingFields`] are `tracing_subscriber`
+//!   formatters that mask event fields by name, per module, according to a
+//!   [`RedactionConfig`].  The config lives behind a [`RedactionHandle`]
+//!   and can be swapped at runtime; call sites never change.
+//!
+//! ## Usage
+//! ```rust,no_run
+//! use bleep_crypto::redaction::{RedactionConfig, RedactionHandle};
+//!
+//! let handle = RedactionHandle::new(RedactionConfig::default());
+//! tracing_subscriber::fmt()
+//!     .fmt_fields(handle.field_format())
+//!     .event_format(handle.event_format())
+//!     .init();
+//! tracing::info!(address = "BLEEP1a1b2c3d4e5f60718293a4b5c6d7e8f9012345678", "balance queried");
+//! // … INFO bleep_node: balance queried address=BLEE...5678
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+// ── Sensitive<T> ──────────────────────────────────────────────────────────────
+
+/// A value that must never be logged.
+///
+/// `Debug` and `Display` print `[REDACTED:<type>]`; the value itself is only
+/// reachable through [`expose`](Self::expose) or
+/// [`into_inner`](Self::into_inner).  It deserializes transparently, so it
+/// can sit in request bodies, but deliberately does not serialize.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Sensitive<T>(T);
+
+impl<T> Sensitive<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Borrow the wrapped value.  Keep the borrow away from log macros.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Sensitive<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED:{}]", short_type_name(std::any::type_name::<T>()))
+    }
+}
+
+impl<T> fmt::Display for Sensitive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// `alloc::vec::Vec<u8>` → `Vec<u8>`.
+fn short_type_name(full: &str) -> String {
+    let mut out = String::with_capacity(full.len());
+    let mut segment = String::new();
+    for c in full.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            segment.push(c);
+        } else {
+            out.push_str(segment.rsplit("::").next().unwrap_or(""));
+            segment.clear();
+            out.push(c);
+        }
+    }
+    out.push_str(segment.rsplit("::").next().unwrap_or(""));
+    out
+}
+
+// ── Masks and config ──────────────────────────────────────────────────────────
+
+/// How a field's value is shown.  Written as `"clear"`, `"full"` or
+/// `"partial:<n>"` in config files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Mask {
+    /// Shown as logged.
+    Clear,
+    /// Replaced by `[REDACTED]`.
+    Full,
+    /// First and last `n` characters kept, e.g. `BLEE...5678`.  Values of
+    /// `2n` characters or fewer are fully redacted.
+    Partial(usize),
+}
+
+impl Mask {
+    pub fn apply(&self, value: &str) -> String {
+        match *self {
+            Mask::Clear => value.to_string(),
+            Mask::Full => "[REDACTED]".to_string(),
+            Mask::Partial(n) => {
+                let value = value.trim_matches('"');
+                let chars: Vec<char> = value.chars().collect();
+                if chars.len() <= 2 * n {
+                    return "[REDACTED]".to_string();
+                }
+                let head: String = chars[..n].iter().collect();
+                let tail: String = chars[chars.len() - n..].iter().collect();
+                format!("{}...{}", head, tail)
+            }
+        }
+    }
+}
+
+impl FromStr for Mask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clear" => Ok(Mask::Clear),
+            "full" => Ok(Mask::Full),
+            _ => s
+                .strip_prefix("partial:")
+                .and_then(|n| n.parse().ok())
+                .map(Mask::Partial)
+                .ok_or_else(|| format!("unknown mask '{}'", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Mask {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Mask> for String {
+    fn from(mask: Mask) -> Self {
+        match mask {
+            Mask::Clear => "clear".into(),
+            Mask::Full => "full".into(),
+            Mask::Partial(n) => format!("partial:{}", n),
+        }
+    }
+}
+
+/// Field masks, with per-module overrides.
+///
+/// ```json
+/// {"default": {"address": "partial:4"},
+///  "modules": {"bleep_rpc::faucet": {"address": "full"}}}
+/// ```
+///
+/// A module entry applies to that module and its children; the longest
+/// matching entry that names the field wins, then `default`.  Unlisted
+/// fields are shown as logged.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    #[serde(default)]
+    pub default: BTreeMap<String, Mask>,
+    #[serde(default)]
+    pub modules: BTreeMap<String, BTreeMap<String, Mask>>,
+}
+
+impl Default for RedactionConfig {
+    /// Secret-looking field names fully redacted, `address` kept to its
+    /// first and last 4 characters.
+    fn default() -> Self {
+        let mut default: BTreeMap<String, Mask> = [
+            "private_key", "secret_key", "sk", "mnemonic", "seed",
+            "password", "passphrase", "api_key", "token", "admin_token",
+        ]
+        .iter()
+        .map(|f| (f.to_string(), Mask::Full))
+        .collect();
+        default.insert("address".into(), Mask::Partial(4));
+        Self { default, modules: BTreeMap::new() }
+    }
+}
+
+impl RedactionConfig {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Mask for `field` in events from `module`.
+    pub fn mask_for(&self, module: &str, field: &str) -> Mask {
+        self.modules
+            .iter()
+            .filter(|(prefix, _)| {
+                module == prefix.as_str()
+                    || module.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.starts_with("::"))
+            })
+            .filter_map(|(prefix, fields)| fields.get(field).map(|m| (prefix.len(), *m)))
+            .max_by_key(|(len, _)| *len)
+            .map(|(_, m)| m)
+            .or_else(|| self.default.get(field).copied())
+            .unwrap_or(Mask::Clear)
+    }
+}
+
+/// Shared, replaceable [`RedactionConfig`].  Clones see the same config.
+#[derive(Debug, Clone, Default)]
+pub struct RedactionHandle {
+    config: Arc<RwLock<RedactionConfig>>,
+}
+
+impl RedactionHandle {
+    pub fn new(config: RedactionConfig) -> Self {
+        Self { config: Arc::new(RwLock::new(config)) }
+    }
+
+    /// Replace the config; the next event logged uses it.
+    pub fn set(&self, config: RedactionConfig) {
+        *self.config.write() = config;
+    }
+
+    pub fn config(&self) -> RedactionConfig {
+        self.config.read().clone()
+    }
+
+    pub fn event_format(&self) -> RedactingFormat {
+        RedactingFormat { handle: self.clone() }
+    }
+
+    pub fn field_format(&self) -> RedactingFields {
+        RedactingFields { handle: self.clone() }
+    }
+}
+
+// ── Formatters ────────────────────────────────────────────────────────────────
+
+/// Writes fields as `name=value`, masking each by the config.
+struct MaskingVisitor<'a, 'w> {
+    writer: &'a mut Writer<'w>,
+    config: &'a RedactionConfig,
+    module: &'a str,
+    empty:  bool,
+    result: fmt::Result,
+}
+
+impl<'a, 'w> MaskingVisitor<'a, 'w> {
+    fn new(writer: &'a mut Writer<'w>, config: &'a RedactionConfig, module: &'a str) -> Self {
+        Self { writer, config, module, empty: true, result: Ok(()) }
+    }
+
+    fn write(&mut self, field: &Field, value: String) {
+        if self.result.is_err() {
+            return;
+        }
+        let sep = if self.empty { "" } else { " " };
+        self.empty = false;
+        self.result = if field.name() == "message" {
+            write!(self.writer, "{}{}", sep, value)
+        } else {
+            let masked = self.config.mask_for(self.module, field.name()).apply(&value);
+            write!(self.writer, "{}{}={}", sep, field.name(), masked)
+        };
+    }
+}
+
+impl Visit for MaskingVisitor<'_, '_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.write(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.write(field, format!("{:?}", value));
+    }
+}
+
+/// Event formatter applying the per-module masks of a [`RedactionHandle`].
+///
+/// Output: `<time> <LEVEL> <spans>: <target>: <message> <fields>`.
+pub struct RedactingFormat {
+    handle: RedactionHandle,
+}
+
+impl<S, N> FormatEvent<S, N> for RedactingFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let meta = event.metadata();
+        SystemTime.format_time(&mut writer)?;
+        write!(writer, " {:>5} ", meta.level())?;
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                writer.write_str(span.name())?;
+                let ext = span.extensions();
+                if let Some(fields) = ext.get::<FormattedFields<N>>().filter(|f| !f.is_empty()) {
+                    write!(writer, "{{{}}}", fields)?;
+                }
+                writer.write_char(':')?;
+            }
+            writer.write_char(' ')?;
+        }
+        write!(writer, "{}: ", meta.target())?;
+        let config = self.handle.config.read();
+        let mut visitor = MaskingVisitor::new(&mut writer, &config, meta.target());
+        event.record(&mut visitor);
+        visitor.result?;
+        writeln!(writer)
+    }
+}
+
+/// Field formatter for span fields.  Spans are formatted once, when they
+/// are created, so only the `default` masks apply.
+pub struct RedactingFields {
+    handle: RedactionHandle,
+}
+
+impl<'w> FormatFields<'w> for RedactingFields {
+    fn format_fields<R: RecordFields>(&self, mut writer: Writer<'w>, fields: R) -> fmt::Result {
+        let config = self.handle.config.read();
+        let mut visitor = MaskingVisitor::new(&mut writer, &config, "");
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pq_crypto::{KyberSecretKey, SecretKey};
+    use std::io;
+    use std::sync::Mutex;
+    use tracing::subscriber::with_default;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    const ADDRESS: &str = "BLEEP1a1b2c3d4e5f60718293a4b5c6d7e8f9012345678";
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Capture {
+        fn output(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for Capture {
+        type Writer = Capture;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    fn subscriber(handle: &RedactionHandle, capture: &Capture) -> impl Subscriber + Send + Sync {
+        tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .fmt_fields(handle.field_format())
+            .event_format(handle.event_format())
+            .with_writer(capture.clone())
+            .finish()
+    }
+
+    #[test]
+    fn secrets_never_reach_trace_output() {
+        let mnemonic = "abandon ability able about above absent absorb abstract absurd abuse access accident";
+        let passphrase = "correct-horse-battery-staple";
+        let token = "bk_live_7f3a9c1e5d2b8a64";
+        let raw_sk = vec![0xAB; SecretKey::LEN];
+        let sk = SecretKey::from_bytes(&raw_sk).unwrap();
+        let kyber = KyberSecretKey::from_bytes(vec![0xCD; 3168]).unwrap();
+
+        let handle = RedactionHandle::default();
+        let capture = Capture::default();
+        with_default(subscriber(&handle, &capture), || {
+            let mnemonic = Sensitive::new(mnemonic.to_string());
+            let passphrase = Sensitive::new(passphrase);
+            let token = Sensitive::new(token.to_string());
+            let key = Sensitive::new(raw_sk.clone());
+            let span = tracing::trace_span!("unlock", input = %passphrase);
+            let _enter = span.enter();
+            tracing::trace!(?mnemonic, %token, ?key, "importing {} / {:?}", mnemonic, key);
+            tracing::trace!(?sk, ?kyber, "keys loaded");
+            tracing::trace!(private_key = %hex::encode(&raw_sk), "raw field");
+        });
+
+        let out = capture.output();
+        let sk_hex = hex::encode(&raw_sk);
+        assert!(out.contains("[REDACTED:String]") && out.contains("[REDACTED:Vec<u8>]"));
+        assert!(out.contains("[REDACTED:&str]"));
+        for secret in [mnemonic, passphrase, token, sk_hex.as_str()] {
+            assert!(!out.contains(secret), "leaked {:?} in:\n{}", secret, out);
+        }
+        assert!(!out.contains("171, 171") && !out.contains("205, 205"), "leaked key bytes in:\n{}", out);
+    }
+
+    #[test]
+    fn partial_mask_keeps_address_ends() {
+        assert_eq!(Mask::Partial(4).apply(ADDRESS), "BLEE...5678");
+        assert_eq!(Mask::Partial(4).apply(&format!("{:?}", ADDRESS)), "BLEE...5678");
+        assert_eq!(Mask::Partial(4).apply("BLEEP1ab"), "[REDACTED]");
+        assert_eq!(Mask::Full.apply(ADDRESS), "[REDACTED]");
+        assert_eq!("partial:6".parse::<Mask>().unwrap(), Mask::Partial(6));
+        assert!("partial:x".parse::<Mask>().is_err());
+
+        let handle = RedactionHandle::default();
+        let capture = Capture::default();
+        with_default(subscriber(&handle, &capture), || {
+            tracing::info!(address = ADDRESS, amount = 5, "sent");
+        });
+        let out = capture.output();
+        assert!(out.contains("sent address=BLEE...5678 amount=5"), "{}", out);
+        assert!(!out.contains(ADDRESS));
+    }
+
+    #[test]
+    fn config_changes_apply_to_existing_call_sites() {
+        fn log_transfer() {
+            tracing::info!(target: "bleep_rpc::faucet", address = ADDRESS, "drip");
+            tracing::info!(target: "bleep_state", address = ADDRESS, "credit");
+        }
+
+        let handle = RedactionHandle::default();
+        let capture = Capture::default();
+        with_default(subscriber(&handle, &capture), || {
+            log_transfer();
+            handle.set(RedactionConfig::from_json(r#"{
+                "default": {"address": "clear"},
+                "modules": {"bleep_rpc": {"address": "full"}}
+            }"#).unwrap());
+            log_transfer();
+        });
+
+        let lines: Vec<String> = capture.output().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].ends_with("drip address=BLEE...5678"));
+        assert!(lines[1].ends_with("credit address=BLEE...5678"));
+        assert!(lines[2].ends_with("drip address=[REDACTED]"));
+        assert!(lines[3].ends_with(&format!("credit address={}", ADDRESS)));
+    }
+}
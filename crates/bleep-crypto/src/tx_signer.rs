@@ -18,7 +18,9 @@
 
 use pqcrypto_sphincsplus::sphincsshake256fsimple;
 use pqcrypto_traits::sign::{PublicKey as _, SecretKey as _, DetachedSignature as _};
-use sha3::{Digest, Sha3_256};
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{Digest, Sha3_256, Shake256};
+use zeroize::Zeroizing;
 
 /// Sign a transaction payload using SPHINCS+-SHAKE-256.
 ///
@@ -67,6 +69,57 @@ pub fn tx_payload(sender: &str, receiver: &str, amount: u64, timestamp: u64) ->
     h.finalize().into()
 }
 
+/// Payload of a transaction offering `max_fee` and `tip` per inclusion.
+///
+/// Fee-less transactions (both zero) sign the plain `tx_payload`, so
+/// signatures made before fees existed stay valid.
+///
+/// Layout: `sha3_256( tx_payload || max_fee_le8 || tip_le8 )`
+pub fn tx_payload_with_fee(
+    sender: &str,
+    receiver: &str,
+    amount: u64,
+    timestamp: u64,
+    max_fee: u64,
+    tip: u64,
+) -> [u8; 32] {
+    let base = tx_payload(sender, receiver, amount, timestamp);
+    if max_fee == 0 && tip == 0 {
+        return base;
+    }
+    let mut h = Sha3_256::new();
+    h.update(base);
+    h.update(&max_fee.to_le_bytes());
+    h.update(&tip.to_le_bytes());
+    h.finalize().into()
+}
+
+/// Payload of a transaction carrying account nonce `nonce`.
+///
+/// Nonce 0 — an account's first transaction — signs the plain
+/// `tx_payload_with_fee`, so signatures made before nonces existed stay
+/// valid for it.
+///
+/// Layout: `sha3_256( tx_payload_with_fee || nonce_le8 )`
+pub fn tx_payload_with_nonce(
+    sender: &str,
+    receiver: &str,
+    amount: u64,
+    timestamp: u64,
+    max_fee: u64,
+    tip: u64,
+    nonce: u64,
+) -> [u8; 32] {
+    let base = tx_payload_with_fee(sender, receiver, amount, timestamp, max_fee, tip);
+    if nonce == 0 {
+        return base;
+    }
+    let mut h = Sha3_256::new();
+    h.update(base);
+    h.update(&nonce.to_le_bytes());
+    h.finalize().into()
+}
+
 /// Generate a fresh SPHINCS+ keypair.
 ///
 /// Returns `(public_key_bytes, secret_key_bytes)`.
@@ -75,6 +128,100 @@ pub fn generate_tx_keypair() -> (Vec<u8>, Vec<u8>) {
     (pk.as_bytes().to_vec(), sk.as_bytes().to_vec())
 }
 
+/// Length of the seed `tx_keypair_from_seed` expands into a keypair.
+pub const TX_KEYPAIR_SEED_LEN: usize = 96;
+
+/// Hash output length of SPHINCS+-SHAKE-256f.
+const SPX_N: usize = 32;
+/// Hypertree layers; keygen only builds the top one.
+const SPX_D: u8 = 17;
+/// Height of each hypertree layer (68 / 17).
+const SPX_TREE_HEIGHT: u32 = 4;
+/// WOTS+ chains per one-time key for n = 32, w = 16.
+const SPX_WOTS_LEN: usize = 67;
+const SPX_WOTS_W: u8 = 16;
+
+// Address types and byte offsets of the SHAKE address layout.
+const ADDR_TYPE_WOTS: u8 = 0;
+const ADDR_TYPE_WOTSPK: u8 = 1;
+const ADDR_TYPE_HASHTREE: u8 = 2;
+const ADDR_TYPE_WOTSPRF: u8 = 5;
+const OFFSET_LAYER: usize = 3;
+const OFFSET_TYPE: usize = 19;
+const OFFSET_KP_ADDR: usize = 23;
+const OFFSET_CHAIN_ADDR: usize = 27;
+const OFFSET_HASH_ADDR: usize = 31;
+const OFFSET_TREE_HGT: usize = 27;
+const OFFSET_TREE_INDEX: usize = 28;
+
+/// `SHAKE256(parts…)` truncated to n bytes — the scheme's `thash` and
+/// `prf_addr` in its "simple" instantiation.
+fn spx_shake(parts: &[&[u8]]) -> Zeroizing<[u8; SPX_N]> {
+    let mut hasher = Shake256::default();
+    for part in parts {
+        Update::update(&mut hasher, part);
+    }
+    let mut out = Zeroizing::new([0u8; SPX_N]);
+    hasher.finalize_xof().read(&mut out[..]);
+    out
+}
+
+/// WOTS+ public key of leaf `leaf` of the top hypertree layer, compressed
+/// to n bytes.
+fn spx_top_leaf(sk_seed: &[u8], pub_seed: &[u8], leaf: u8) -> Zeroizing<[u8; SPX_N]> {
+    let mut addr = [0u8; 32];
+    addr[OFFSET_LAYER] = SPX_D - 1;
+    addr[OFFSET_KP_ADDR] = leaf;
+    let mut pk_addr = addr;
+    pk_addr[OFFSET_TYPE] = ADDR_TYPE_WOTSPK;
+
+    let mut chain_ends = Zeroizing::new(vec![0u8; SPX_WOTS_LEN * SPX_N]);
+    for (chain, end) in chain_ends.chunks_exact_mut(SPX_N).enumerate() {
+        addr[OFFSET_CHAIN_ADDR] = chain as u8;
+        addr[OFFSET_HASH_ADDR] = 0;
+        addr[OFFSET_TYPE] = ADDR_TYPE_WOTSPRF;
+        let mut node = spx_shake(&[pub_seed, &addr, sk_seed]);
+        addr[OFFSET_TYPE] = ADDR_TYPE_WOTS;
+        for step in 0..SPX_WOTS_W - 1 {
+            addr[OFFSET_HASH_ADDR] = step;
+            node = spx_shake(&[pub_seed, &addr, &node[..]]);
+        }
+        end.copy_from_slice(&node[..]);
+    }
+    spx_shake(&[pub_seed, &pk_addr, &chain_ends])
+}
+
+/// Derive the SPHINCS+-SHAKE-256f keypair of a 96-byte seed
+/// `SK.seed ‖ SK.prf ‖ PK.seed`, as the reference `crypto_sign_seed_keypair`
+/// does: `PK.root` is the root of the top hypertree layer.  The same seed
+/// always yields the same keypair.
+///
+/// Returns `(public_key_bytes, secret_key_bytes)`.
+pub fn tx_keypair_from_seed(seed: &[u8; TX_KEYPAIR_SEED_LEN]) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+    let (sk_seed, pub_seed) = (&seed[..SPX_N], &seed[2 * SPX_N..]);
+
+    let mut nodes: Vec<Zeroizing<[u8; SPX_N]>> = (0..1u8 << SPX_TREE_HEIGHT)
+        .map(|leaf| spx_top_leaf(sk_seed, pub_seed, leaf))
+        .collect();
+    let mut addr = [0u8; 32];
+    addr[OFFSET_LAYER] = SPX_D - 1;
+    addr[OFFSET_TYPE] = ADDR_TYPE_HASHTREE;
+    for height in 1..=SPX_TREE_HEIGHT {
+        addr[OFFSET_TREE_HGT] = height as u8;
+        nodes = nodes.chunks_exact(2).enumerate()
+            .map(|(index, pair)| {
+                addr[OFFSET_TREE_INDEX..].copy_from_slice(&(index as u32).to_be_bytes());
+                spx_shake(&[pub_seed, &addr, &pair[0][..], &pair[1][..]])
+            })
+            .collect();
+    }
+    let root = &nodes[0][..];
+
+    let pk = [pub_seed, root].concat();
+    let sk = Zeroizing::new([&seed[..], root].concat());
+    (pk, sk)
+}
+
 // ── Unit tests ────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -109,6 +256,32 @@ mod tests {
         assert!(!verify_tx_signature(&payload, &sig, &pk2));
     }
 
+    #[test]
+    fn test_seeded_keypair_is_deterministic_and_signs() {
+        let (pk, sk) = tx_keypair_from_seed(&[7u8; TX_KEYPAIR_SEED_LEN]);
+        assert_eq!(tx_keypair_from_seed(&[7u8; TX_KEYPAIR_SEED_LEN]), (pk.clone(), sk.clone()));
+        assert_ne!(tx_keypair_from_seed(&[8u8; TX_KEYPAIR_SEED_LEN]).0, pk);
+
+        let payload = tx_payload("alice", "bob", 1000, 12345);
+        let sig = sign_tx_payload(&payload, &sk).unwrap();
+        assert!(verify_tx_signature(&payload, &sig, &pk));
+    }
+
+    #[test]
+    fn test_seeded_keypair_matches_the_reference_keygen() {
+        // Public key the PQClean `crypto_sign_seed_keypair` derives from
+        // the seed bytes 3, 10, 17, … (i·7 + 3).
+        let seed: [u8; TX_KEYPAIR_SEED_LEN] = std::array::from_fn(|i| (i * 7 + 3) as u8);
+        let (pk, sk) = tx_keypair_from_seed(&seed);
+        assert_eq!(
+            hex::encode(&pk),
+            "c3cad1d8dfe6edf4fb020910171e252c333a41484f565d646b727980878e959c\
+             ad168facc71734d63d828f3bd6680716af407914d82952d5793ed2c4de15c921",
+        );
+        assert_eq!(sk.len(), sphincsshake256fsimple::secret_key_bytes());
+        assert_eq!((&sk[..TX_KEYPAIR_SEED_LEN], &sk[TX_KEYPAIR_SEED_LEN..]), (&seed[..], &pk[32..]));
+    }
+
     #[test]
     fn test_tx_payload_deterministic() {
         let p1 = tx_payload("alice", "bob", 1000, 99999);
@@ -122,4 +295,13 @@ mod tests {
         let p2 = tx_payload("alice", "bob", 2000, 99999);
         assert_ne!(p1, p2);
     }
+
+    #[test]
+    fn test_nonce_is_signed_from_one_on() {
+        let first = tx_payload_with_nonce("alice", "bob", 1000, 99999, 0, 0, 0);
+        assert_eq!(first, tx_payload("alice", "bob", 1000, 99999));
+        let second = tx_payload_with_nonce("alice", "bob", 1000, 99999, 0, 0, 1);
+        assert_ne!(second, first);
+        assert_ne!(second, tx_payload_with_nonce("alice", "bob", 1000, 99999, 0, 0, 2));
+    }
 }
@@ -1,9 +1,21 @@
 use std::fs;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
 use thiserror::Error;
 use sha2::{Sha256, Digest};
-use sha3::Sha3_256;
+use ark_bls12_381::{Bls12_381, G1Affine, G1Projective, G2Affine};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{One, Zero};
+use ark_groth16::{prepare_verifying_key, Groth16, PreparedVerifyingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use lru::LruCache;
+use parking_lot::Mutex;
+use rand::Rng;
+use rayon::prelude::*;
 use crate::quantum_secure::KyberAESHybrid;
-use crate::merkletree::MerkleTree;
+use crate::merkletree::{ExclusionProof, MerkleTree};
 use crate::logging::BLEEPLogger;
 
 /// Initialize the ZKP subsystem for production-safe startup.
@@ -43,6 +55,99 @@ pub enum BLEEPError {
     ConsensusFailed(String),
 }
 
+/// Groth16 proof over BLS12-381.
+pub type Proof = ark_groth16::Proof<Bls12_381>;
+
+/// The BLS12-381 scalar modulus `r`, in decimal.
+const FR_MODULUS_DECIMAL: &str =
+    "52435875175126190479447740508185965837690552500527637822603658699938581184513";
+
+/// Parse a public input written as a decimal field element.  Values at or
+/// above the modulus are refused rather than silently reduced.
+pub fn parse_public_input(s: &str) -> Result<Fr, BLEEPError> {
+    let s = s.trim();
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(BLEEPError::Generic(format!("Public input '{}' is not a decimal field element", s)));
+    }
+    let digits = s.trim_start_matches('0');
+    let too_big = digits.len() > FR_MODULUS_DECIMAL.len()
+        || (digits.len() == FR_MODULUS_DECIMAL.len() && digits >= FR_MODULUS_DECIMAL);
+    if too_big {
+        return Err(BLEEPError::Generic(format!("Public input '{}' is not below the BLS12-381 scalar modulus", s)));
+    }
+    s.parse::<Fr>().map_err(|_| BLEEPError::Generic(format!("Public input '{}' is not a field element", s)))
+}
+
+fn decode_verifying_key(bytes: &[u8], path: &str) -> Result<VerifyingKey<Bls12_381>, BLEEPError> {
+    VerifyingKey::<Bls12_381>::deserialize_compressed(bytes).map_err(|e| BLEEPError::Generic(
+        format!("Verifying key at '{}' is not a compressed BLS12-381 Groth16 key: {}", path, e)
+    ))
+}
+
+/// BLS12-381 scalar field element (Groth16 public input).
+pub use ark_bls12_381::Fr;
+
+/// Verified proofs remembered by default.
+pub const DEFAULT_PROOF_CACHE_CAPACITY: usize = 4096;
+
+/// Cache key: `(sha256(proof), sha256(public inputs))`.
+type ProofCacheKey = ([u8; 32], [u8; 32]);
+
+/// Outcome of `BLEEPZKPModule::verify_batch_detailed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchVerification {
+    pub all_valid:  bool,
+    /// Indices into the input slice of the proofs that failed, ascending.
+    pub invalid:    Vec<usize>,
+    /// Proofs accepted from the cache without a pairing check.
+    pub cache_hits: usize,
+}
+
+/// Point-in-time copy of the Groth16 verification counters, for telemetry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZkpVerifyStats {
+    pub batches:           u64,
+    /// Proofs that went through a pairing check (cache misses).
+    pub proofs_verified:   u64,
+    pub invalid_proofs:    u64,
+    pub cache_hits:        u64,
+    pub cache_misses:      u64,
+    pub total_verify_micros: u64,
+    pub last_batch_micros: u64,
+}
+
+impl ZkpVerifyStats {
+    /// Fraction of looked-up proofs served from the cache (0.0 when idle).
+    pub fn cache_hit_rate(&self) -> f64 {
+        let total = self.cache_hits + self.cache_misses;
+        if total == 0 { 0.0 } else { self.cache_hits as f64 / total as f64 }
+    }
+
+    pub fn avg_batch_micros(&self) -> u64 {
+        if self.batches == 0 { 0 } else { self.total_verify_micros / self.batches }
+    }
+}
+
+#[derive(Default)]
+struct VerifyCounters {
+    batches:           AtomicU64,
+    proofs_verified:   AtomicU64,
+    invalid_proofs:    AtomicU64,
+    cache_hits:        AtomicU64,
+    cache_misses:      AtomicU64,
+    total_verify_micros: AtomicU64,
+    last_batch_micros: AtomicU64,
+}
+
+/// A proof that missed the cache, with its public-input combination
+/// `L = IC_0 + Σ x_j·IC_{j+1}` computed once up front.
+struct PendingProof<'a> {
+    index: usize,
+    proof: &'a Proof,
+    key:   ProofCacheKey,
+    l:     G1Projective,
+}
+
 /// **ZKP Module with Advanced Security & Performance**
 /// ZKP Module with Advanced Security & Performance
 pub struct BLEEPZKPModule {
@@ -50,6 +155,9 @@ pub struct BLEEPZKPModule {
     pub verifying_key: Vec<u8>,
     pub revocation_tree: MerkleTree,
     pub logger: BLEEPLogger,
+    groth16_vk: Option<PreparedVerifyingKey<Bls12_381>>,
+    proof_cache: Mutex<LruCache<ProofCacheKey, ()>>,
+    counters: VerifyCounters,
 }
 
 impl BLEEPZKPModule {
@@ -58,8 +166,11 @@ impl BLEEPZKPModule {
         Self {
             proving_key: vec![0u8; 64],
             verifying_key: vec![1u8; 64],
-            revocation_tree: MerkleTree::new(),
+            revocation_tree: MerkleTree::sorted(),
             logger: BLEEPLogger::new(),
+            groth16_vk: None,
+            proof_cache: Mutex::new(new_proof_cache(DEFAULT_PROOF_CACHE_CAPACITY)),
+            counters: VerifyCounters::default(),
         }
     }
 
@@ -75,11 +186,26 @@ impl BLEEPZKPModule {
         Ok(Self {
             proving_key,
             verifying_key,
-            revocation_tree: MerkleTree::new(),
+            revocation_tree: MerkleTree::sorted(),
             logger: BLEEPLogger::new(),
+            groth16_vk: None,
+            proof_cache: Mutex::new(new_proof_cache(DEFAULT_PROOF_CACHE_CAPACITY)),
+            counters: VerifyCounters::default(),
         })
     }
 
+    /// Use `vk` for `verify_groth16` / `verify_batch`.
+    pub fn with_groth16_vk(mut self, vk: VerifyingKey<Bls12_381>) -> Self {
+        self.groth16_vk = Some(prepare_verifying_key(&vk));
+        self
+    }
+
+    /// Remember up to `capacity` verified proofs (minimum 1).
+    pub fn with_proof_cache_capacity(mut self, capacity: usize) -> Self {
+        self.proof_cache = Mutex::new(new_proof_cache(capacity));
+        self
+    }
+
     /// Securely save proving & verifying keys with hybrid quantum-safe encryption
     pub fn save_keys(
         &self,
@@ -177,25 +303,45 @@ impl BLEEPZKPModule {
             ));
         }
 
-        // If we reach here we have non-empty, non-placeholder bytes but cannot
-        // yet deserialise them into the target proof system. Return an explicit
-        // error rather than continuing with invalid state.
-        Err(BLEEPError::Generic(
-            "ZKP key deserialisation not yet implemented for this key format. \
-             See load_keys() documentation for the production integration path."
-            .to_string()
-        ))
+        // The verifying key is a compressed ark-serialize Groth16 key; the
+        // proving key is kept as opaque bytes until proving is wired up.
+        let vk = decode_verifying_key(&vk_bytes, verifying_key_path)?;
+        Ok(Self { proving_key: pk_bytes, ..Self::from_verifying_key(vk_bytes, vk) })
+    }
+
+    /// Load only the verifying key — all that checking proofs needs.
+    pub fn load_verifying_key(verifying_key_path: &str) -> Result<Self, BLEEPError> {
+        let vk_bytes = fs::read(verifying_key_path).map_err(|e| BLEEPError::Generic(
+            format!("Cannot read verifying key at '{}': {}", verifying_key_path, e)
+        ))?;
+        if vk_bytes == b"dummy_verifying_key" {
+            return Err(BLEEPError::Generic(format!(
+                "'{}' is the development placeholder, not a Groth16 verifying key", verifying_key_path
+            )));
+        }
+        let vk = decode_verifying_key(&vk_bytes, verifying_key_path)?;
+        Ok(Self::from_verifying_key(vk_bytes, vk))
+    }
+
+    fn from_verifying_key(vk_bytes: Vec<u8>, vk: VerifyingKey<Bls12_381>) -> Self {
+        Self { verifying_key: vk_bytes, ..Self::new() }.with_groth16_vk(vk)
     }
 
-    /// Aggregate multiple proofs using a simple hash-based accumulator.
-    pub fn aggregate_proofs(&self, _proofs: &[Vec<u8>]) -> Result<Vec<u8>, BLEEPError> {
-        // Dummy aggregation: hash all proofs together
-        let mut hasher = Sha3_256::new();
-        for proof in _proofs {
-            hasher.update(proof);
+    /// Bundle encoded proofs into one blob that `verify_aggregated_proof`
+    /// can split back apart: a `u32` LE count, then each proof as a `u32` LE
+    /// length followed by its bytes.
+    pub fn aggregate_proofs(&self, proofs: &[Vec<u8>]) -> Result<Vec<u8>, BLEEPError> {
+        let frame_len = |n: usize| u32::try_from(n).map_err(|_| BLEEPError::Generic(
+            format!("Cannot aggregate: {} does not fit a u32 frame length", n)
+        ));
+        let mut out = Vec::with_capacity(4 + proofs.iter().map(|p| 4 + p.len()).sum::<usize>());
+        out.extend_from_slice(&frame_len(proofs.len())?.to_le_bytes());
+        for proof in proofs {
+            out.extend_from_slice(&frame_len(proof.len())?.to_le_bytes());
+            out.extend_from_slice(proof);
         }
         self.logger.info("Proof aggregation successful.");
-        Ok(hasher.finalize().to_vec())
+        Ok(out)
     }
 
     /// Generate merkle-based zero-knowledge proofs for a batch of transactions
@@ -229,6 +375,12 @@ impl BLEEPZKPModule {
         self.revocation_tree.contains_leaf(key_bytes)
     }
 
+    /// Proof, checkable with `merkletree::verify_exclusion` against the
+    /// revocation root, that a key is not revoked; `None` if it is.
+    pub fn prove_not_revoked(&self, key_bytes: &[u8]) -> Option<ExclusionProof> {
+        self.revocation_tree.generate_exclusion_proof(key_bytes)
+    }
+
     /// Save the revocation list securely
     pub fn save_revocation_tree(&self, path: &str) -> Result<(), BLEEPError> {
         // Save the root of the Merkle tree as a simple representation
@@ -239,7 +391,7 @@ impl BLEEPZKPModule {
 
     /// Load the revocation list from a file
     pub fn load_revocation_tree(_path: &str) -> Result<MerkleTree, BLEEPError> {
-        Ok(MerkleTree::new())
+        Ok(MerkleTree::sorted())
     }
 
     /// Generate a zero-knowledge proof for the given data
@@ -248,6 +400,295 @@ impl BLEEPZKPModule {
         let proofs = self.generate_batch_proofs(vec![data.to_vec()])?;
         Ok(proofs.into_iter().next().unwrap())
     }
+
+    // ── Groth16 verification ─────────────────────────────────────────────────
+
+    fn groth16_key(&self) -> Result<&PreparedVerifyingKey<Bls12_381>, BLEEPError> {
+        self.groth16_vk.as_ref().ok_or_else(|| BLEEPError::Generic(
+            "No Groth16 verifying key configured (see with_groth16_vk)".into()
+        ))
+    }
+
+    /// Verify one Groth16 proof against the configured key, bypassing the cache.
+    pub fn verify_groth16(&self, proof: &Proof, public_inputs: &[Fr]) -> Result<bool, BLEEPError> {
+        let pvk = self.groth16_key()?;
+        if public_inputs.len() + 1 != pvk.vk.gamma_abc_g1.len() {
+            return Ok(false);
+        }
+        Groth16::<Bls12_381>::verify_proof(pvk, proof, public_inputs)
+            .map_err(|e| BLEEPError::Generic(format!("Groth16 verification: {e}")))
+    }
+
+    /// Verify each proof on its own, in parallel on the rayon pool, and report
+    /// every verdict.  `public_inputs[i]` belongs to `proofs[i]`.
+    ///
+    /// Unlike `verify_batch_detailed` this does no pairing combination and
+    /// skips the cache: n proofs cost n full checks spread over the pool.
+    /// Run it inside `ThreadPool::install` to bound the threads it uses.
+    pub fn verify_batch_proofs(&self, proofs: &[Proof], public_inputs: &[Vec<Fr>]) -> Result<Vec<bool>, BLEEPError> {
+        if proofs.len() != public_inputs.len() {
+            return Err(BLEEPError::Generic(format!(
+                "{} proofs but {} public input sets", proofs.len(), public_inputs.len()
+            )));
+        }
+        let pvk = self.groth16_key()?;
+        proofs.par_iter()
+            .zip(public_inputs.par_iter())
+            .map(|(proof, inputs)| {
+                if inputs.len() + 1 != pvk.vk.gamma_abc_g1.len() {
+                    return Ok(false);
+                }
+                Groth16::<Bls12_381>::verify_proof(pvk, proof, inputs)
+                    .map_err(|e| BLEEPError::Generic(format!("Groth16 verification: {e}")))
+            })
+            .collect()
+    }
+
+    /// Split an `aggregate_proofs` blob, decode each compressed proof, and
+    /// verify them with `verify_batch_proofs`.
+    pub fn verify_aggregated_proof(&self, aggregated: &[u8], public_inputs: &[Vec<Fr>]) -> Result<Vec<bool>, BLEEPError> {
+        let proofs = split_aggregate(aggregated)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, bytes)| decode_groth16_proof(bytes).map_err(|_| BLEEPError::Generic(
+                format!("Aggregated proof {} is not a compressed Groth16 proof", i)
+            )))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.verify_batch_proofs(&proofs, public_inputs)
+    }
+
+    /// Verify many Groth16 proofs at once; `true` only if every proof is valid.
+    ///
+    /// See `verify_batch_detailed` for which proofs failed.
+    pub fn verify_batch(&self, proofs_and_inputs: &[(Proof, Vec<Fr>)]) -> Result<bool, BLEEPError> {
+        Ok(self.verify_batch_detailed(proofs_and_inputs)?.all_valid)
+    }
+
+    /// Verify many Groth16 proofs with a single multi-pairing.
+    ///
+    /// Each proof's equation `e(A,B) = e(α,β)·e(L,γ)·e(C,δ)` is scaled by a
+    /// fresh random 128-bit scalar `r_i` and the scaled equations are summed,
+    /// so n proofs cost n + 3 Miller loops and one final exponentiation.  A
+    /// forged proof passes only if it cancels against random scalars it
+    /// cannot predict (probability ≈ 2⁻¹²⁸).  When the combined check fails
+    /// the batch is bisected to find the culprits.
+    ///
+    /// Proofs found in the LRU cache are accepted without a pairing check;
+    /// only valid proofs are ever cached.
+    pub fn verify_batch_detailed(
+        &self,
+        proofs_and_inputs: &[(Proof, Vec<Fr>)],
+    ) -> Result<BatchVerification, BLEEPError> {
+        let pvk = self.groth16_key()?;
+        let vk = &pvk.vk;
+        let started = Instant::now();
+
+        let mut invalid = Vec::new();
+        let mut pending = Vec::with_capacity(proofs_and_inputs.len());
+        let mut cache_hits = 0usize;
+        {
+            let mut cache = self.proof_cache.lock();
+            for (index, (proof, inputs)) in proofs_and_inputs.iter().enumerate() {
+                let key = proof_cache_key(proof, inputs)?;
+                if cache.get(&key).is_some() {
+                    cache_hits += 1;
+                    continue;
+                }
+                match input_combination(vk, inputs) {
+                    Some(l) => pending.push(PendingProof { index, proof, key, l }),
+                    None => invalid.push(index),
+                }
+            }
+        }
+
+        if !pending.is_empty() && !batch_pairing_check(vk, &pending) {
+            bisect_invalid(vk, &pending, &mut invalid);
+        }
+        invalid.sort_unstable();
+
+        {
+            let mut cache = self.proof_cache.lock();
+            for p in &pending {
+                if invalid.binary_search(&p.index).is_err() {
+                    cache.put(p.key, ());
+                }
+            }
+        }
+
+        let micros = started.elapsed().as_micros() as u64;
+        let c = &self.counters;
+        c.batches.fetch_add(1, Ordering::Relaxed);
+        c.proofs_verified.fetch_add(pending.len() as u64, Ordering::Relaxed);
+        c.invalid_proofs.fetch_add(invalid.len() as u64, Ordering::Relaxed);
+        c.cache_hits.fetch_add(cache_hits as u64, Ordering::Relaxed);
+        c.cache_misses.fetch_add((proofs_and_inputs.len() - cache_hits) as u64, Ordering::Relaxed);
+        c.total_verify_micros.fetch_add(micros, Ordering::Relaxed);
+        c.last_batch_micros.store(micros, Ordering::Relaxed);
+
+        if !invalid.is_empty() {
+            self.logger.warning(&format!("Groth16 batch rejected proofs at {:?}", invalid));
+        }
+        Ok(BatchVerification { all_valid: invalid.is_empty(), invalid, cache_hits })
+    }
+
+    /// Verification timing and cache counters since startup.
+    pub fn verify_stats(&self) -> ZkpVerifyStats {
+        let c = &self.counters;
+        ZkpVerifyStats {
+            batches:           c.batches.load(Ordering::Relaxed),
+            proofs_verified:   c.proofs_verified.load(Ordering::Relaxed),
+            invalid_proofs:    c.invalid_proofs.load(Ordering::Relaxed),
+            cache_hits:        c.cache_hits.load(Ordering::Relaxed),
+            cache_misses:      c.cache_misses.load(Ordering::Relaxed),
+            total_verify_micros: c.total_verify_micros.load(Ordering::Relaxed),
+            last_batch_micros: c.last_batch_micros.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn new_proof_cache(capacity: usize) -> LruCache<ProofCacheKey, ()> {
+    LruCache::new(NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN))
+}
+
+fn proof_cache_key(proof: &Proof, inputs: &[Fr]) -> Result<ProofCacheKey, BLEEPError> {
+    let mut bytes = Vec::new();
+    proof.serialize_compressed(&mut bytes).map_err(|_| BLEEPError::SerializationError)?;
+    let proof_hash = Sha256::digest(&bytes).into();
+
+    let mut hasher = Sha256::new();
+    for x in inputs {
+        bytes.clear();
+        x.serialize_compressed(&mut bytes).map_err(|_| BLEEPError::SerializationError)?;
+        hasher.update(&bytes);
+    }
+    Ok((proof_hash, hasher.finalize().into()))
+}
+
+/// `IC_0 + Σ x_j·IC_{j+1}`, or `None` if the input count does not match the key.
+fn input_combination(vk: &VerifyingKey<Bls12_381>, inputs: &[Fr]) -> Option<G1Projective> {
+    if inputs.len() + 1 != vk.gamma_abc_g1.len() {
+        return None;
+    }
+    let mut l = vk.gamma_abc_g1[0].into_group();
+    for (x, ic) in inputs.iter().zip(&vk.gamma_abc_g1[1..]) {
+        l += *ic * x;
+    }
+    Some(l)
+}
+
+/// Randomised combined check of every proof in `batch`:
+///
+/// `Π e(r_i·A_i, B_i) · e(−Σr_i·α, β) · e(−Σr_i·L_i, γ) · e(−Σr_i·C_i, δ) = 1`
+fn batch_pairing_check(vk: &VerifyingKey<Bls12_381>, batch: &[PendingProof<'_>]) -> bool {
+    let mut rng = rand::thread_rng();
+    let mut g1: Vec<G1Affine> = Vec::with_capacity(batch.len() + 3);
+    let mut g2: Vec<G2Affine> = Vec::with_capacity(batch.len() + 3);
+
+    let mut r_sum = Fr::zero();
+    let mut l_acc = G1Projective::zero();
+    let mut c_acc = G1Projective::zero();
+    for p in batch {
+        let r = Fr::from(rng.gen::<u128>() | 1);
+        g1.push((p.proof.a * r).into_affine());
+        g2.push(p.proof.b);
+        r_sum += r;
+        l_acc += p.l * r;
+        c_acc += p.proof.c * r;
+    }
+    g1.push((-(vk.alpha_g1 * r_sum)).into_affine());
+    g2.push(vk.beta_g2);
+    g1.push((-l_acc).into_affine());
+    g2.push(vk.gamma_g2);
+    g1.push((-c_acc).into_affine());
+    g2.push(vk.delta_g2);
+
+    Bls12_381::multi_pairing(g1, g2).0.is_one()
+}
+
+/// Append to `invalid` the indices of the failing proofs in `batch`, which
+/// is already known to fail as a whole.
+fn bisect_invalid(vk: &VerifyingKey<Bls12_381>, batch: &[PendingProof<'_>], invalid: &mut Vec<usize>) {
+    if batch.len() == 1 {
+        invalid.push(batch[0].index);
+        return;
+    }
+    let (left, right) = batch.split_at(batch.len() / 2);
+    for half in [left, right] {
+        if !batch_pairing_check(vk, half) {
+            bisect_invalid(vk, half, invalid);
+        }
+    }
+}
+
+/// Frames of an `aggregate_proofs` blob, in order.
+fn split_aggregate(mut bytes: &[u8]) -> Result<Vec<&[u8]>, BLEEPError> {
+    fn take<'a>(bytes: &mut &'a [u8], n: usize, what: &str) -> Result<&'a [u8], BLEEPError> {
+        if bytes.len() < n {
+            return Err(BLEEPError::Generic(format!(
+                "Truncated proof aggregate: {} needs {} bytes, {} left", what, n, bytes.len()
+            )));
+        }
+        let (head, rest) = bytes.split_at(n);
+        *bytes = rest;
+        Ok(head)
+    }
+    fn read_u32(bytes: &mut &[u8], what: &str) -> Result<usize, BLEEPError> {
+        let raw: [u8; 4] = take(bytes, 4, what)?.try_into().expect("took 4 bytes");
+        Ok(u32::from_le_bytes(raw) as usize)
+    }
+
+    let count = read_u32(&mut bytes, "proof count")?;
+    let mut frames = Vec::with_capacity(count.min(bytes.len() / 4));
+    for i in 0..count {
+        let len = read_u32(&mut bytes, &format!("length of proof {}", i))?;
+        frames.push(take(&mut bytes, len, &format!("proof {}", i))?);
+    }
+    if !bytes.is_empty() {
+        return Err(BLEEPError::Generic(format!("Proof aggregate has {} trailing bytes", bytes.len())));
+    }
+    Ok(frames)
+}
+
+/// Decode a compressed Groth16 proof.
+pub fn decode_groth16_proof(bytes: &[u8]) -> Result<Proof, BLEEPError> {
+    Proof::deserialize_compressed(bytes).map_err(|_| BLEEPError::SerializationError)
+}
+
+/// Decode a compressed BLS12-381 scalar.
+pub fn decode_public_input(bytes: &[u8]) -> Result<Fr, BLEEPError> {
+    Fr::deserialize_compressed(bytes).map_err(|_| BLEEPError::SerializationError)
+}
+
+/// A Groth16 verifying key decoded and prepared once, for callers that
+/// hold keys by id and verify against them repeatedly.
+#[derive(Clone)]
+pub struct Groth16Key(PreparedVerifyingKey<Bls12_381>);
+
+impl Groth16Key {
+    pub fn new(vk: VerifyingKey<Bls12_381>) -> Self {
+        Groth16Key(prepare_verifying_key(&vk))
+    }
+
+    /// Decode a compressed verifying key.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BLEEPError> {
+        VerifyingKey::<Bls12_381>::deserialize_compressed(bytes)
+            .map(Self::new)
+            .map_err(|_| BLEEPError::SerializationError)
+    }
+
+    /// Number of public inputs a proof under this key takes.
+    pub fn input_count(&self) -> usize {
+        self.0.vk.gamma_abc_g1.len().saturating_sub(1)
+    }
+
+    /// Verify one proof; a wrong number of inputs is `Ok(false)`.
+    pub fn verify(&self, proof: &Proof, public_inputs: &[Fr]) -> Result<bool, BLEEPError> {
+        if public_inputs.len() != self.input_count() {
+            return Ok(false);
+        }
+        Groth16::<Bls12_381>::verify_proof(&self.0, proof, public_inputs)
+            .map_err(|e| BLEEPError::Generic(format!("Groth16 verification: {e}")))
+    }
 }
 
 /// Verify a raw proof payload encoded as a hex string.
@@ -263,3 +704,206 @@ pub fn verify_proof(proof_hex: &str) -> Result<bool, BLEEPError> {
 
     Ok(true)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_groth16::ProvingKey;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+    use ark_relations::lc;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// Proves knowledge of `x, y` with `x · y = z` for public `z`.
+    struct MulCircuit {
+        x: Option<Fr>,
+        y: Option<Fr>,
+    }
+
+    impl ConstraintSynthesizer<Fr> for MulCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+            let x = cs.new_witness_variable(|| self.x.ok_or(SynthesisError::AssignmentMissing))?;
+            let y = cs.new_witness_variable(|| self.y.ok_or(SynthesisError::AssignmentMissing))?;
+            let z = cs.new_input_variable(|| {
+                Ok(self.x.ok_or(SynthesisError::AssignmentMissing)? * self.y.ok_or(SynthesisError::AssignmentMissing)?)
+            })?;
+            cs.enforce_constraint(lc!() + x, lc!() + y, lc!() + z)
+        }
+    }
+
+    fn setup(rng: &mut StdRng) -> ProvingKey<Bls12_381> {
+        Groth16::<Bls12_381>::generate_random_parameters_with_reduction(MulCircuit { x: None, y: None }, rng)
+            .unwrap()
+    }
+
+    fn prove(pk: &ProvingKey<Bls12_381>, x: u64, y: u64, rng: &mut StdRng) -> (Proof, Vec<Fr>) {
+        let (x, y) = (Fr::from(x), Fr::from(y));
+        let proof = Groth16::<Bls12_381>::create_random_proof_with_reduction(
+            MulCircuit { x: Some(x), y: Some(y) }, pk, rng,
+        ).unwrap();
+        (proof, vec![x * y])
+    }
+
+    fn module(pk: &ProvingKey<Bls12_381>) -> BLEEPZKPModule {
+        BLEEPZKPModule::new().with_groth16_vk(pk.vk.clone())
+    }
+
+    #[test]
+    fn bisection_identifies_the_bad_proof() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let pk = setup(&mut rng);
+        let mut batch: Vec<_> = (1..=8).map(|i| prove(&pk, i, i + 1, &mut rng)).collect();
+        // Claim a different product for proof 5.
+        batch[5].1[0] += Fr::one();
+
+        let zkp = module(&pk);
+        let result = zkp.verify_batch_detailed(&batch).unwrap();
+        assert!(!result.all_valid);
+        assert_eq!(result.invalid, vec![5]);
+        assert!(!zkp.verify_batch(&batch).unwrap());
+
+        batch[5].1[0] -= Fr::one();
+        assert!(zkp.verify_batch(&batch).unwrap());
+    }
+
+    #[test]
+    fn batch_agrees_with_individual_verification() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let pk = setup(&mut rng);
+        for round in 0..6 {
+            let n = rng.gen_range(1..7);
+            let mut batch: Vec<_> = (0..n)
+                .map(|_| prove(&pk, rng.gen_range(1..1000), rng.gen_range(1..1000), &mut rng))
+                .collect();
+            for (proof, inputs) in batch.iter_mut() {
+                match rng.gen_range(0..4) {
+                    0 => inputs[0] += Fr::from(rng.gen_range(1..10u64)),
+                    1 => proof.c = (proof.c * Fr::from(2u64)).into_affine(),
+                    _ => {}
+                }
+            }
+
+            // Fresh module per round so the cache cannot mask disagreements.
+            let zkp = module(&pk);
+            let expected: Vec<usize> = batch.iter().enumerate()
+                .filter(|(_, (p, x))| !zkp.verify_groth16(p, x).unwrap())
+                .map(|(i, _)| i)
+                .collect();
+            let result = zkp.verify_batch_detailed(&batch).unwrap();
+            assert_eq!(result.invalid, expected, "round {round}");
+            assert_eq!(result.all_valid, expected.is_empty());
+        }
+    }
+
+    #[test]
+    fn cache_short_circuits_repeat_verification() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let pk = setup(&mut rng);
+        let batch: Vec<_> = (1..=4).map(|i| prove(&pk, i, 3, &mut rng)).collect();
+        let zkp = module(&pk);
+
+        let first = zkp.verify_batch_detailed(&batch).unwrap();
+        assert!(first.all_valid);
+        assert_eq!(first.cache_hits, 0);
+
+        let second = zkp.verify_batch_detailed(&batch).unwrap();
+        assert!(second.all_valid);
+        assert_eq!(second.cache_hits, 4);
+
+        let stats = zkp.verify_stats();
+        assert_eq!(stats.batches, 2);
+        assert_eq!(stats.proofs_verified, 4, "second batch must not re-run pairings");
+        assert_eq!(stats.cache_hits, 4);
+        assert!((stats.cache_hit_rate() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn groth16_key_round_trips_through_bytes() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let pk = setup(&mut rng);
+        let mut vk_bytes = Vec::new();
+        pk.vk.serialize_compressed(&mut vk_bytes).unwrap();
+        let key = Groth16Key::from_bytes(&vk_bytes).unwrap();
+        assert_eq!(key.input_count(), 1);
+
+        let (proof, inputs) = prove(&pk, 6, 7, &mut rng);
+        assert!(key.verify(&proof, &inputs).unwrap());
+        assert!(!key.verify(&proof, &[inputs[0] + Fr::one()]).unwrap());
+        assert!(!key.verify(&proof, &[]).unwrap());
+        assert!(Groth16Key::from_bytes(&vk_bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn verifying_key_loads_from_disk() {
+        let mut rng = StdRng::seed_from_u64(13);
+        let pk = setup(&mut rng);
+        let dir = std::env::temp_dir().join(format!("bleep-zkp-vk-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let vk_path = dir.join("verifying.key");
+        let mut vk_bytes = Vec::new();
+        pk.vk.serialize_compressed(&mut vk_bytes).unwrap();
+        fs::write(&vk_path, &vk_bytes).unwrap();
+
+        let zkp = BLEEPZKPModule::load_verifying_key(vk_path.to_str().unwrap()).unwrap();
+        let (proof, inputs) = prove(&pk, 6, 7, &mut rng);
+        assert_eq!(parse_public_input(" 42 ").unwrap(), inputs[0]);
+        assert!(zkp.verify_groth16(&proof, &inputs).unwrap());
+
+        fs::write(&vk_path, &vk_bytes[..vk_bytes.len() / 2]).unwrap();
+        let err = BLEEPZKPModule::load_verifying_key(vk_path.to_str().unwrap()).err().unwrap();
+        assert!(err.to_string().contains("not a compressed BLS12-381 Groth16 key"), "{}", err);
+        fs::remove_dir_all(&dir).ok();
+
+        assert!(parse_public_input("0x2a").is_err());
+        assert!(parse_public_input("").is_err());
+        assert!(parse_public_input(&"9".repeat(80)).is_err());
+        assert!(parse_public_input(FR_MODULUS_DECIMAL).is_err());
+        assert_eq!(parse_public_input("0").unwrap(), Fr::zero());
+    }
+
+    #[test]
+    fn parallel_batch_reports_each_verdict() {
+        let mut rng = StdRng::seed_from_u64(17);
+        let pk = setup(&mut rng);
+        let zkp = module(&pk);
+        let (proofs, mut inputs): (Vec<Proof>, Vec<Vec<Fr>>) =
+            (1..=6).map(|i| prove(&pk, i, i + 2, &mut rng)).unzip();
+        inputs[2][0] += Fr::one();
+        inputs[4].clear();
+
+        assert_eq!(zkp.verify_batch_proofs(&proofs, &inputs).unwrap(), [true, true, false, true, false, true]);
+        assert!(zkp.verify_batch_proofs(&proofs, &inputs[1..]).is_err());
+        assert!(BLEEPZKPModule::new().verify_batch_proofs(&proofs, &inputs).is_err());
+    }
+
+    #[test]
+    fn aggregate_round_trips_into_verification() {
+        let mut rng = StdRng::seed_from_u64(19);
+        let pk = setup(&mut rng);
+        let zkp = module(&pk);
+        let (proofs, inputs): (Vec<Proof>, Vec<Vec<Fr>>) =
+            (1..=3).map(|i| prove(&pk, i, 5, &mut rng)).unzip();
+        let encoded: Vec<Vec<u8>> = proofs.iter().map(|p| {
+            let mut bytes = Vec::new();
+            p.serialize_compressed(&mut bytes).unwrap();
+            bytes
+        }).collect();
+
+        let aggregate = zkp.aggregate_proofs(&encoded).unwrap();
+        assert_eq!(zkp.verify_aggregated_proof(&aggregate, &inputs).unwrap(), [true; 3]);
+        assert!(zkp.verify_aggregated_proof(&zkp.aggregate_proofs(&[]).unwrap(), &[]).unwrap().is_empty());
+
+        let truncated = zkp.verify_aggregated_proof(&aggregate[..aggregate.len() - 1], &inputs).err().unwrap();
+        assert!(truncated.to_string().contains("Truncated proof aggregate"), "{}", truncated);
+        let mut trailing = aggregate.clone();
+        trailing.push(0);
+        assert!(zkp.verify_aggregated_proof(&trailing, &inputs).is_err());
+        let garbled = zkp.aggregate_proofs(&[vec![7; 10]]).unwrap();
+        assert!(zkp.verify_aggregated_proof(&garbled, &inputs[..1]).is_err());
+    }
+
+    #[test]
+    fn batch_without_key_is_an_error() {
+        assert!(BLEEPZKPModule::new().verify_batch(&[]).is_err());
+    }
+}
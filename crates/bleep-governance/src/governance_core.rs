@@ -16,6 +16,12 @@ use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use log::{info, error};
 use thiserror::Error;
+use bleep_auth::audit_trail::{AuditAction, AuditOutcome, AuditTrail};
+use bleep_crypto::chain_id::ChainId;
+use bleep_crypto::tx_signer::verify_tx_signature;
+use crate::governance_events::{transition_events, GovernanceEvent, VoteEvent};
+use crate::proposal_content::{validate_hints, ContentCommitment, RetrievalHint, MAX_INLINE_DESCRIPTION};
+use crate::parameter_registry::ParameterRegistry;
 
 /// Proposal type determining what action is executed
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -76,6 +82,9 @@ pub enum ProposalState {
     
     /// Expired without reaching quorum
     Expired,
+
+    /// Vetoed while queued, before its execution epoch
+    Vetoed,
 }
 
 impl ProposalState {
@@ -84,7 +93,8 @@ impl ProposalState {
             ProposalState::Executed | 
             ProposalState::RolledBack | 
             ProposalState::Rejected | 
-            ProposalState::Expired
+            ProposalState::Expired |
+            ProposalState::Vetoed
         )
     }
 }
@@ -144,7 +154,8 @@ pub struct Vote {
     /// Epoch when vote was cast
     pub vote_epoch: u64,
     
-    /// Signature: SHA256(validator_id || proposal_id || approval || stake || epoch)
+    /// SPHINCS+ signature over `signing_payload`: the chain-bound
+    /// SHA256(validator_id || proposal_id || approval || stake || epoch)
     pub signature: Vec<u8>,
 }
 
@@ -175,6 +186,19 @@ impl Vote {
         hasher.update(self.vote_epoch.to_le_bytes());
         hasher.finalize().to_vec()
     }
+
+    /// What the validator signs: the vote hash bound to `chain_id`.
+    pub fn signing_payload(&self, proposal_id: &str, chain_id: ChainId) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&self.compute_hash(proposal_id));
+        chain_id.bind(hash)
+    }
+
+    /// Whether `signature` is `public_key`'s signature of this vote on
+    /// `proposal_id` for chain `chain_id`.
+    pub fn verify_signature(&self, proposal_id: &str, chain_id: ChainId, public_key: &[u8]) -> bool {
+        verify_tx_signature(&self.signing_payload(proposal_id, chain_id), &self.signature, public_key)
+    }
 }
 
 /// Tally result: vote counts and voting power
@@ -262,6 +286,9 @@ pub enum GovernanceError {
     
     #[error("Invalid validator")]
     InvalidValidator,
+
+    #[error("Vote signature from {0} does not verify")]
+    InvalidVoteSignature(String),
     
     #[error("Execution failed: {0}")]
     ExecutionFailed(String),
@@ -274,6 +301,9 @@ pub enum GovernanceError {
     
     #[error("Invalid proposal: {0}")]
     InvalidProposal(String),
+
+    #[error("Inline description is {size} bytes (max {max}); publish the full text off-chain and submit a content commitment instead")]
+    InlineContentTooLarge { size: usize, max: usize },
 }
 
 
@@ -289,8 +319,12 @@ pub struct Proposal {
     /// Title for human readability
     pub title: String,
     
-    /// Description/justification
+    /// Description/justification (inline, capped at `MAX_INLINE_DESCRIPTION`)
     pub description: String,
+
+    /// Commitment to the full off-chain text, if any
+    #[serde(default)]
+    pub content: Option<ContentCommitment>,
     
     /// Current state
     pub state: ProposalState,
@@ -337,6 +371,7 @@ impl Proposal {
             proposal_type,
             title,
             description,
+            content: None,
             state: ProposalState::Draft,
             voting_window,
             execution_epoch,
@@ -349,6 +384,31 @@ impl Proposal {
         }
     }
     
+    /// Attach a commitment to the full proposal text stored off-chain
+    pub fn with_content(mut self, commitment: ContentCommitment) -> Self {
+        self.content = Some(commitment);
+        self
+    }
+
+    /// Hex content hash, shown alongside the proposal when voting
+    pub fn content_hash(&self) -> Option<String> {
+        self.content.as_ref().map(ContentCommitment::hash_hex)
+    }
+
+    /// Check the inline description cap and the commitment, if present
+    pub fn validate_content(&self) -> Result<(), GovernanceError> {
+        if self.description.len() > MAX_INLINE_DESCRIPTION {
+            return Err(GovernanceError::InlineContentTooLarge {
+                size: self.description.len(),
+                max: MAX_INLINE_DESCRIPTION,
+            });
+        }
+        if let Some(commitment) = &self.content {
+            commitment.validate().map_err(GovernanceError::InvalidProposal)?;
+        }
+        Ok(())
+    }
+
     /// Submit proposal (Draft → Pending)
     pub fn submit(&mut self) -> Result<(), GovernanceError> {
         if self.state != ProposalState::Draft {
@@ -536,6 +596,12 @@ pub enum GovernancePayload {
         version: String,
         code_hash: Vec<u8>,
     },
+
+    /// Force-unfreeze a PAT holder frozen by the token issuer (escape hatch)
+    AssetForceUnfreeze {
+        symbol: String,
+        account: [u8; 32],
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -569,10 +635,19 @@ impl GovernancePayload {
                 info!("Executing upgrade: {} v{}", module_name, version);
                 Ok(())
             }
+            GovernancePayload::AssetForceUnfreeze { symbol, account } => {
+                // Applied to the PAT registry by GovernanceEngine::apply_asset_action
+                info!("Executing asset unfreeze: {} for {}", symbol, hex::encode(account));
+                Ok(())
+            }
         }
     }
 }
 
+/// SPHINCS+ public key a validator signs votes with, by validator id,
+/// e.g. from the validator registry.
+pub type VoterKeys = std::sync::Arc<dyn Fn(&str) -> Option<Vec<u8>> + Send + Sync>;
+
 /// On-Chain Governance Engine (coordinating all proposals)
 pub struct GovernanceEngine {
     /// All proposals ever submitted
@@ -583,6 +658,19 @@ pub struct GovernanceEngine {
     
     /// Total network stake (used for quorum calculation)
     total_network_stake: u128,
+
+    /// Operator audit trail for executions and asset actions
+    audit: Option<AuditTrail>,
+
+    /// Bounds for parameter proposals; records executed changes
+    parameters: Option<std::sync::Arc<parking_lot::Mutex<ParameterRegistry>>>,
+
+    /// Lifecycle and vote events not yet drained by the node
+    events: Vec<GovernanceEvent>,
+
+    /// Chain and voter keys votes are verified against; `None` accepts
+    /// votes unverified
+    voter_keys: Option<(ChainId, VoterKeys)>,
 }
 
 impl GovernanceEngine {
@@ -591,6 +679,65 @@ impl GovernanceEngine {
             proposals: HashMap::new(),
             proposal_queue: Vec::new(),
             total_network_stake,
+            audit: None,
+            parameters: None,
+            events: Vec::new(),
+            voter_keys: None,
+        }
+    }
+
+    /// Accept only votes signed for `chain_id` by the key `keys` returns
+    /// for their validator
+    pub fn with_voter_keys(mut self, chain_id: ChainId, keys: VoterKeys) -> Self {
+        self.voter_keys = Some((chain_id, keys));
+        self
+    }
+
+    /// Record proposal executions and asset actions to `trail`
+    pub fn with_audit_trail(mut self, trail: AuditTrail) -> Self {
+        self.audit = Some(trail);
+        self
+    }
+
+    /// Check parameter proposals against `registry` at submission and
+    /// record executed ones in its history
+    pub fn with_parameter_registry(mut self, registry: std::sync::Arc<parking_lot::Mutex<ParameterRegistry>>) -> Self {
+        self.parameters = Some(registry);
+        self
+    }
+
+    /// Record an executed parameter change in the attached registry
+    fn record_parameter_change(&self, proposal_id: &str, epoch: u64) {
+        let (registry, proposal) = match (&self.parameters, self.proposals.get(proposal_id)) {
+            (Some(r), Some(p)) => (r, p),
+            _ => return,
+        };
+        if let GovernancePayload::ProtocolParameterChange { rule_name, new_value } = &proposal.payload {
+            if let Err(e) = registry.lock().apply_proposal(rule_name, *new_value, proposal_id, epoch) {
+                error!("Proposal {} executed but parameter not recorded: {}", proposal_id, e);
+            }
+        }
+    }
+
+    /// Record the events of `proposal_id` moving on from `before`
+    fn record_transition(&mut self, proposal_id: &str, before: ProposalState, epoch: u64) {
+        if let Some(proposal) = self.proposals.get(proposal_id) {
+            self.events.extend(transition_events(proposal, before, epoch));
+        }
+    }
+
+    /// Take the events recorded since the last call, oldest first.
+    ///
+    /// The node drains these once per block and publishes them with the
+    /// block's height (see `governance_feed`).
+    pub fn drain_events(&mut self) -> Vec<GovernanceEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn audit(&self, action: AuditAction, proposal_id: &str, ok: bool) {
+        if let Some(trail) = &self.audit {
+            let outcome = if ok { AuditOutcome::Success } else { AuditOutcome::Failure };
+            trail.append("governance", action, &serde_json::json!({ "proposal_id": proposal_id }), outcome);
         }
     }
     
@@ -602,10 +749,19 @@ impl GovernanceEngine {
             ));
         }
         
+        proposal.validate_content()?;
+        if let (Some(registry), GovernancePayload::ProtocolParameterChange { rule_name, new_value }) =
+            (&self.parameters, &proposal.payload)
+        {
+            registry.lock().validate_proposal(rule_name, *new_value)
+                .map_err(|e| GovernanceError::InvalidProposal(e.to_string()))?;
+        }
         proposal.submit()?;
         let proposal_id = proposal.id.clone();
+        let epoch = proposal.created_epoch;
         self.proposals.insert(proposal_id.clone(), proposal);
         self.proposal_queue.push(proposal_id.clone());
+        self.record_transition(&proposal_id, ProposalState::Draft, epoch);
         
         info!("Proposal {} submitted", proposal_id);
         Ok(proposal_id)
@@ -616,6 +772,25 @@ impl GovernanceEngine {
         self.proposals.get(id).ok_or(GovernanceError::ProposalNotFound)
     }
     
+    /// Replace the retrieval hints of a committed proposal.
+    ///
+    /// Hints are not part of the commitment, so a dead mirror can be
+    /// swapped out at any stage without touching the committed hash.
+    pub fn update_retrieval_hints(
+        &mut self,
+        proposal_id: &str,
+        hints: Vec<RetrievalHint>,
+    ) -> Result<(), GovernanceError> {
+        validate_hints(&hints).map_err(GovernanceError::InvalidProposal)?;
+        let proposal = self.get_proposal_mut(proposal_id)?;
+        let commitment = proposal.content.as_mut().ok_or_else(|| {
+            GovernanceError::InvalidProposal("Proposal has no content commitment".to_string())
+        })?;
+        commitment.hints = hints;
+        info!("Proposal {} retrieval hints updated", proposal_id);
+        Ok(())
+    }
+
     /// Get mutable proposal by ID
     fn get_proposal_mut(&mut self, id: &str) -> Result<&mut Proposal, GovernanceError> {
         self.proposals.get_mut(id).ok_or(GovernanceError::ProposalNotFound)
@@ -628,18 +803,30 @@ impl GovernanceEngine {
         current_epoch: u64,
     ) -> Result<(), GovernanceError> {
         let proposal = self.get_proposal_mut(proposal_id)?;
-        proposal.start_voting(current_epoch)
+        let before = proposal.state;
+        proposal.start_voting(current_epoch)?;
+        self.record_transition(proposal_id, before, current_epoch);
+        Ok(())
     }
     
-    /// Cast a vote
+    /// Cast a vote; with voter keys attached its signature must verify
     pub fn cast_vote(
         &mut self,
         proposal_id: &str,
         vote: Vote,
         current_epoch: u64,
     ) -> Result<(), GovernanceError> {
+        if let Some((chain_id, keys)) = &self.voter_keys {
+            let public_key = keys(&vote.validator_id).ok_or(GovernanceError::InvalidValidator)?;
+            if !vote.verify_signature(proposal_id, *chain_id, &public_key) {
+                return Err(GovernanceError::InvalidVoteSignature(vote.validator_id.clone()));
+            }
+        }
         let proposal = self.get_proposal_mut(proposal_id)?;
-        proposal.cast_vote(vote, current_epoch)
+        let event = VoteEvent::new(proposal, &vote);
+        proposal.cast_vote(vote, current_epoch)?;
+        self.events.push(GovernanceEvent::Vote(event));
+        Ok(())
     }
     
     /// Close voting and compute tally
@@ -650,7 +837,10 @@ impl GovernanceEngine {
     ) -> Result<(), GovernanceError> {
         let total_stake = self.total_network_stake;
         let proposal = self.get_proposal_mut(proposal_id)?;
-        proposal.close_voting(current_epoch, total_stake)
+        let before = proposal.state;
+        proposal.close_voting(current_epoch, total_stake)?;
+        self.record_transition(proposal_id, before, current_epoch);
+        Ok(())
     }
     
     /// Execute proposal at execution epoch
@@ -660,9 +850,70 @@ impl GovernanceEngine {
         current_epoch: u64,
     ) -> Result<(), GovernanceError> {
         let proposal = self.get_proposal_mut(proposal_id)?;
-        proposal.execute(current_epoch)
+        let before = proposal.state;
+        let result = proposal.execute(current_epoch);
+        self.record_transition(proposal_id, before, current_epoch);
+        self.audit(AuditAction::GovernanceExecution, proposal_id, result.is_ok());
+        if result.is_ok() {
+            self.record_parameter_change(proposal_id, current_epoch);
+        }
+        result
     }
     
+    /// Veto a queued proposal (AwaitingExecution → Vetoed).
+    ///
+    /// Only possible during the timelock, i.e. before `execution_epoch`.
+    pub fn veto_proposal(
+        &mut self,
+        proposal_id: &str,
+        current_epoch: u64,
+    ) -> Result<(), GovernanceError> {
+        let proposal = self.get_proposal_mut(proposal_id)?;
+        if proposal.state != ProposalState::AwaitingExecution {
+            return Err(GovernanceError::InvalidStateTransition(
+                format!("Cannot veto proposal in state {:?}", proposal.state)
+            ));
+        }
+        if current_epoch >= proposal.execution_epoch {
+            return Err(GovernanceError::InvalidStateTransition(
+                format!("Timelock of proposal {} ended at epoch {}", proposal_id, proposal.execution_epoch)
+            ));
+        }
+        proposal.state = ProposalState::Vetoed;
+        info!("Proposal {} vetoed at epoch {}", proposal_id, current_epoch);
+        self.record_transition(proposal_id, ProposalState::AwaitingExecution, current_epoch);
+        Ok(())
+    }
+
+    /// Apply an executed `AssetForceUnfreeze` proposal to the PAT registry.
+    ///
+    /// Only proposals that have reached `Executed` are honoured, so the
+    /// issuer's freeze can only be overridden by a passed vote.
+    pub fn apply_asset_action(
+        &self,
+        proposal_id: &str,
+        registry: &mut bleep_pat::PATRegistry,
+    ) -> Result<(), GovernanceError> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if proposal.state != ProposalState::Executed {
+            return Err(GovernanceError::InvalidStateTransition(
+                format!("Proposal {} not executed (state {:?})", proposal_id, proposal.state)
+            ));
+        }
+        match &proposal.payload {
+            GovernancePayload::AssetForceUnfreeze { symbol, account } => {
+                let result = registry
+                    .governance_unfreeze(symbol, account, proposal_id)
+                    .map_err(|e| GovernanceError::ExecutionFailed(e.to_string()));
+                self.audit(AuditAction::AssetRecovery, proposal_id, result.is_ok());
+                result
+            }
+            _ => Err(GovernanceError::InvalidProposal(
+                "Proposal carries no asset action".to_string()
+            )),
+        }
+    }
+
     /// Get all proposals in a given state
     pub fn get_proposals_by_state(&self, state: ProposalState) -> Vec<&Proposal> {
         self.proposals.values()
@@ -683,19 +934,29 @@ impl GovernanceEngine {
             // Transition: Pending → Voting
             if proposal.state == ProposalState::Pending 
                 && new_epoch >= proposal.voting_window.start_epoch {
+                let before = proposal.state;
                 let _ = proposal.start_voting(new_epoch);
+                self.events.extend(transition_events(proposal, before, new_epoch));
             }
             
             // Transition: Voting → Tallying
             if proposal.state == ProposalState::Voting 
                 && new_epoch >= proposal.voting_window.end_epoch {
+                let before = proposal.state;
                 let _ = proposal.close_voting(new_epoch, self.total_network_stake);
+                self.events.extend(transition_events(proposal, before, new_epoch));
             }
             
             // Transition: AwaitingExecution → Executing
             if proposal.state == ProposalState::AwaitingExecution 
                 && new_epoch >= proposal.execution_epoch {
-                let _ = proposal.execute(new_epoch);
+                let before = proposal.state;
+                let ok = proposal.execute(new_epoch).is_ok();
+                self.events.extend(transition_events(proposal, before, new_epoch));
+                self.audit(AuditAction::GovernanceExecution, &proposal_id, ok);
+                if ok {
+                    self.record_parameter_change(&proposal_id, new_epoch);
+                }
             }
         }
         
@@ -802,6 +1063,44 @@ mod tests {
         assert!(matches!(proposal.cast_vote(vote2, 2), Err(GovernanceError::DoubleVoting(_))));
     }
 
+    #[test]
+    fn test_vote_signed_on_one_chain_fails_on_another() {
+        use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload};
+
+        let (pk, sk) = generate_tx_keypair();
+        let mut vote = Vote::new("val-1".to_string(), true, 1000, 2, Vec::new());
+        vote.signature = sign_tx_payload(&vote.signing_payload("prop-1", ChainId(1)), &sk).unwrap();
+
+        assert!(vote.verify_signature("prop-1", ChainId(1), &pk));
+        assert!(!vote.verify_signature("prop-1", ChainId(2), &pk));
+        assert!(!vote.verify_signature("prop-2", ChainId(1), &pk));
+    }
+
+    #[test]
+    fn test_engine_rejects_unsigned_and_foreign_votes() {
+        use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload};
+
+        let (pk, sk) = generate_tx_keypair();
+        let keys: VoterKeys = std::sync::Arc::new(move |id: &str| (id == "val-1").then(|| pk.clone()));
+        let mut engine = GovernanceEngine::new(10_000).with_voter_keys(ChainId(1), keys);
+        let id = engine.submit_proposal(parameter_proposal("prop-1", "Raise it".to_string())).unwrap();
+        engine.start_voting(&id, 2).unwrap();
+        let signed = |validator: &str, chain_id: ChainId| {
+            let mut vote = Vote::new(validator.to_string(), true, 8_000, 2, Vec::new());
+            vote.signature = sign_tx_payload(&vote.signing_payload(&id, chain_id), &sk).unwrap();
+            vote
+        };
+
+        let unsigned = Vote::new("val-1".into(), true, 8_000, 2, vec![]);
+        assert!(matches!(engine.cast_vote(&id, unsigned, 2), Err(GovernanceError::InvalidVoteSignature(_))));
+        assert!(matches!(engine.cast_vote(&id, signed("val-1", ChainId(2)), 2), Err(GovernanceError::InvalidVoteSignature(_))));
+        assert!(matches!(engine.cast_vote(&id, signed("val-2", ChainId(1)), 2), Err(GovernanceError::InvalidValidator)));
+        assert!(engine.get_proposal(&id).unwrap().votes.is_empty());
+
+        engine.cast_vote(&id, signed("val-1", ChainId(1)), 2).unwrap();
+        assert_eq!(engine.get_proposal(&id).unwrap().votes.len(), 1);
+    }
+
     #[test]
     fn test_tally_computation() {
         let tally = VoteTally::compute(
@@ -815,4 +1114,129 @@ mod tests {
         assert!(tally.quorum_met); // 6700 + 3300 = 10000 > 10000/3
         assert!(tally.approved); // 67% >= 67% threshold
     }
+
+    #[test]
+    fn test_asset_force_unfreeze_end_to_end() {
+        use bleep_pat::{FreezeAccountIntent, PATIntent, PATIntentKind, PATRegistry};
+
+        let issuer = [0x01u8; 32];
+        let holder = [0x02u8; 32];
+        let mut registry = PATRegistry::new();
+        registry.execute(&PATIntent::create_token(issuer, "RWA", "Regulated", 8, 0, 0, true)).unwrap();
+        registry.execute(&PATIntent::mint(issuer, "RWA", holder, 1_000)).unwrap();
+        registry.execute(&PATIntent::new(issuer, PATIntentKind::FreezeAccount(FreezeAccountIntent {
+            symbol: "RWA".into(), account: holder, frozen: true,
+        }), 10_000, 0, 1)).unwrap();
+        assert!(registry.is_account_frozen("RWA", &holder));
+
+        let mut engine = GovernanceEngine::new(10_000);
+        let proposal = Proposal::new(
+            "prop-unfreeze".to_string(),
+            ProposalType::Recovery,
+            "Unfreeze holder".to_string(),
+            "Issuer unreachable".to_string(),
+            VotingWindow::new(2, 4).unwrap(),
+            5,
+            67,
+            GovernancePayload::AssetForceUnfreeze { symbol: "RWA".into(), account: holder },
+            1,
+        );
+        let id = engine.submit_proposal(proposal).unwrap();
+
+        // Not executed yet — registry untouched.
+        assert!(engine.apply_asset_action(&id, &mut registry).is_err());
+
+        engine.start_voting(&id, 2).unwrap();
+        engine.cast_vote(&id, Vote::new("val-1".into(), true, 8_000, 2, vec![]), 2).unwrap();
+        engine.close_voting(&id, 4).unwrap();
+        engine.execute_proposal(&id, 5).unwrap();
+        engine.apply_asset_action(&id, &mut registry).unwrap();
+
+        assert!(!registry.is_account_frozen("RWA", &holder));
+        registry.execute(&PATIntent::transfer(holder, "RWA", issuer, 100)).unwrap();
+    }
+
+    fn parameter_proposal(id: &str, description: String) -> Proposal {
+        Proposal::new(
+            id.to_string(),
+            ProposalType::ProtocolParameter,
+            "Raise gas limit".to_string(),
+            description,
+            VotingWindow::new(2, 4).unwrap(),
+            5,
+            67,
+            GovernancePayload::ProtocolParameterChange {
+                rule_name: "BLOCK_GAS_LIMIT".to_string(),
+                new_value: 30_000_000,
+            },
+            1,
+        )
+    }
+
+    #[test]
+    fn test_parameter_proposals_checked_against_registry() {
+        use crate::parameter_registry::{ChangeSource, ParameterRegistry};
+
+        let registry = std::sync::Arc::new(parking_lot::Mutex::new(ParameterRegistry::genesis().unwrap()));
+        let mut engine = GovernanceEngine::new(10_000).with_parameter_registry(std::sync::Arc::clone(&registry));
+        let set = |id: &str, value: u128| Proposal::new(
+            id.to_string(),
+            ProposalType::ProtocolParameter,
+            "Checkpoint cadence".to_string(),
+            String::new(),
+            VotingWindow::new(2, 4).unwrap(),
+            5,
+            67,
+            GovernancePayload::ProtocolParameterChange { rule_name: "CHECKPOINT_FREQUENCY".to_string(), new_value: value },
+            1,
+        );
+
+        let err = engine.submit_proposal(set("prop-high", 50_000)).unwrap_err();
+        assert!(err.to_string().contains("outside bounds"), "{}", err);
+        assert!(engine.get_proposal("prop-high").is_err());
+
+        let id = engine.submit_proposal(set("prop-ok", 250)).unwrap();
+        engine.start_voting(&id, 2).unwrap();
+        engine.cast_vote(&id, Vote::new("val-1".into(), true, 8_000, 2, vec![]), 2).unwrap();
+        engine.close_voting(&id, 4).unwrap();
+        engine.execute_proposal(&id, 5).unwrap();
+
+        let registry = registry.lock();
+        assert_eq!(registry.value("CHECKPOINT_FREQUENCY"), Some(250));
+        let history = registry.history("CHECKPOINT_FREQUENCY").unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].source, ChangeSource::Proposal { proposal_id: "prop-ok".into(), epoch: 5 });
+    }
+
+    #[test]
+    fn test_oversized_inline_description_rejected() {
+        let mut engine = GovernanceEngine::new(10_000);
+        let long = "x".repeat(MAX_INLINE_DESCRIPTION + 1);
+        let err = engine.submit_proposal(parameter_proposal("prop-long", long)).unwrap_err();
+        assert!(matches!(err, GovernanceError::InlineContentTooLarge { .. }));
+        assert!(err.to_string().contains("content commitment"));
+
+        let short = "Short rationale".to_string();
+        let id = engine.submit_proposal(parameter_proposal("prop-short", short)).unwrap();
+        assert!(engine.get_proposal(&id).unwrap().content.is_none());
+    }
+
+    #[test]
+    fn test_committed_content_hints_update_keeps_commitment() {
+        let document = "x".repeat(MAX_INLINE_DESCRIPTION * 4);
+        let commitment = ContentCommitment::for_content(document.as_bytes(), "text/markdown")
+            .with_hint(RetrievalHint::Https("https://old.example/prop.md".into()));
+        let proposal = parameter_proposal("prop-doc", "See committed text".to_string())
+            .with_content(commitment.clone());
+
+        let mut engine = GovernanceEngine::new(10_000);
+        let id = engine.submit_proposal(proposal).unwrap();
+        let stored = engine.get_proposal(&id).unwrap();
+        assert_eq!(stored.content_hash(), Some(commitment.hash_hex()));
+
+        engine.update_retrieval_hints(&id, vec![RetrievalHint::Ipfs("bafynew".into())]).unwrap();
+        let updated = engine.get_proposal(&id).unwrap().content.clone().unwrap();
+        assert_eq!(updated.digest(), commitment.digest());
+        assert_eq!(updated.hints, vec![RetrievalHint::Ipfs("bafynew".into())]);
+    }
 }
@@ -1,4 +1,49 @@
+//! # Self-amending governance
+//!
+//! Users and proposals (with their running vote tallies) live in
+//! `DashMap`s.  `SelfAmendingGovernance::open` backs them with RocksDB and
+//! writes every mutation through, so a restart resumes mid-vote:
+//!
+//! ```text
+//!   user:<id be u64>      → User JSON
+//!   proposal:<id be u64>  → Proposal JSON
+//!   delegation:<id be u64> → Delegation JSON, keyed by the delegator
+//!   quarantine:<key>      → raw bytes of an entry that failed validation
+//!   schema:version        → see `bleep_state::migrations`
+//! ```
+//!
+//! An entry that does not decode, or whose id or audit hash does not match,
+//! is moved under `quarantine:` on load instead of failing startup.
+//!
+//! ## Voting rules
+//! A user's voting power is their stake, read through the `StakeLookup`
+//! given to `with_stake_lookup` by public key; the most they can cast (or
+//! delegate) is that, at quadratic weight.  Submitting a proposal records
+//! every user's power in its `electorate`: only they vote on it, with that
+//! power, and its quorum is measured against that snapshot.  A proposal is
+//! open for `GovernanceParams::voting_period_secs` from submission, by the
+//! injected `Clock`.  After that it executes only if the weight cast
+//! reaches `quorum` of the electorate's weight and `votes_for` exceeds
+//! `approval_threshold` of the weight cast; `close_expired_proposals`
+//! marks those that ended without quorum.
+//!
+//! ## Delegation
+//! A user may hand their weight to another with `delegate_vote`.  When a
+//! user votes, every user delegating to them — directly or through a chain
+//! — whose weight is not yet in that proposal's tally is counted with the
+//! same support, and shows up in `delegated_for` / `delegated_against`.
+//! A delegation is counted at most once per proposal, and the votes it
+//! carried come out of the delegator's power there like votes they cast
+//! themselves.  A user may add to their vote until that power is spent,
+//! always on the same side; the weight is that of their running total, so
+//! splitting a vote never weighs more than casting it at once.  Chains may
+//! not loop and are capped at `with_max_delegation_depth` links.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
 use std::sync::Arc;
+use parking_lot::RwLock;
+use rocksdb::{IteratorMode, WriteBatch, DB};
 use thiserror::Error;
 use serde::{Deserialize, Serialize};
 use log::{info, warn, error};
@@ -9,6 +54,37 @@ use bleep_crypto::quantum_secure::QuantumSecure;
 use bleep_crypto::zkp_verification::BLEEPZKPModule;
 // BLEEPInteroperabilityModule — from bleep-interop (now a proper crate)
 use bleep_interop::interoperability::BLEEPInteroperabilityModule;
+use bleep_auth::{Clock, SystemClock};
+use bleep_state::migrations::{self, Migration, Schema};
+
+const PREFIX_USER: &[u8] = b"user:";
+const PREFIX_PROPOSAL: &[u8] = b"proposal:";
+const PREFIX_DELEGATION: &[u8] = b"delegation:";
+const PREFIX_QUARANTINE: &[u8] = b"quarantine:";
+
+/// Layout version `open` reads and writes.
+///
+/// 1 — `user:`, `proposal:` and `delegation:` JSON entries, `quarantine:`
+///     for rejects
+/// 2 — voting power comes from stake and is no longer stored on `user:`;
+///     proposals carry their `electorate` and the votes each user `spent`
+pub const GOVERNANCE_SCHEMA_VERSION: u32 = 2;
+
+const GOVERNANCE_SCHEMA: Schema = Schema {
+    store:       "governance",
+    cf:          None,
+    current:     GOVERNANCE_SCHEMA_VERSION,
+    unversioned: 1,
+    migrations:  &[Migration {
+        from:        1,
+        to:          2,
+        description: "drop stored voting power, close proposals without an electorate",
+        apply:       close_unsnapshotted_proposals,
+    }],
+};
+
+/// Voting power of the user holding a public key, usually their stake.
+pub type StakeLookup = Arc<dyn Fn(&[u8]) -> u64 + Send + Sync>;
 
 // Custom error definitions
 #[derive(Debug, Error)]
@@ -27,6 +103,22 @@ pub enum SelfAmendingError {
     ProposalCategorizationError,
     #[error("ZKP generation error")]
     ZKPGenerationError,
+    #[error("Storage error: {0}")]
+    StorageError(String),
+    #[error("Delegation rejected: {0}")]
+    DelegationError(String),
+    #[error("User {user} holds {power} votes, not {requested}")]
+    InsufficientVotingPower { user: u64, power: u64, requested: u64 },
+    #[error("User {0} has already voted the other way on this proposal")]
+    OppositeSide(u64),
+    #[error("Voting is still open until {ends_at}")]
+    VotingStillOpen { ends_at: u64 },
+    #[error("Voting closed at {ended_at}")]
+    VotingClosed { ended_at: u64 },
+    #[error("Quorum not reached: {cast} of {required} weight cast")]
+    QuorumNotReached { cast: u64, required: u64 },
+    #[error("Proposal rejected: {votes_for} for, {votes_against} against")]
+    Rejected { votes_for: u64, votes_against: u64 },
     #[error("Unknown error")]
     UnknownError,
 }
@@ -52,8 +144,88 @@ pub struct Proposal {
     pub executed: bool,
     pub audit_hash: Vec<u8>,
     pub category: Option<String>,
+    /// Part of `votes_for` carried by delegations.
+    #[serde(default)]
+    pub delegated_for: u64,
+    /// Part of `votes_against` carried by delegations.
+    #[serde(default)]
+    pub delegated_against: u64,
+    /// Users whose weight is in the tally, by their own vote or delegated.
+    #[serde(default)]
+    pub counted: BTreeSet<u64>,
+    /// Unix seconds voting opened.
+    #[serde(default)]
+    pub voting_starts: u64,
+    /// Unix seconds voting closes.
+    #[serde(default)]
+    pub voting_ends: u64,
+    /// Set by `close_expired_proposals` when voting ended without quorum.
+    #[serde(default)]
+    pub expired: bool,
+    /// Voting power of each user when voting opened; only they vote here.
+    #[serde(default)]
+    pub electorate: BTreeMap<u64, u64>,
+    /// Votes each user has spent here, their own or through a delegation.
+    #[serde(default)]
+    pub spent: BTreeMap<u64, u64>,
+    /// Side each user's votes went to; `true` is for.
+    #[serde(default)]
+    pub sides: BTreeMap<u64, bool>,
+}
+
+impl Proposal {
+    /// The electorate's weight at full voting power.
+    pub fn total_weight(&self) -> u64 {
+        self.electorate.values().map(|&power| quadratic_weight(power)).sum()
+    }
+
+    /// Votes `user` may still cast here.
+    pub fn remaining_power(&self, user: u64) -> u64 {
+        let power = self.electorate.get(&user).copied().unwrap_or(0);
+        power.saturating_sub(self.spent.get(&user).copied().unwrap_or(0))
+    }
+
+    /// Add `votes` to `user`'s total on `support`'s side and return the
+    /// weight that adds: the rise in the quadratic weight of the total.
+    fn spend(&mut self, user: u64, votes: u64, support: bool) -> u64 {
+        let before = self.spent.get(&user).copied().unwrap_or(0);
+        self.spent.insert(user, before + votes);
+        self.sides.insert(user, support);
+        quadratic_weight(before + votes) - quadratic_weight(before)
+    }
 }
 
+/// Quorum, approval and timing rules for proposals.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GovernanceParams {
+    /// Fraction of the electorate's weight that must be cast.
+    pub quorum: f64,
+    /// Fraction of the cast weight `votes_for` must exceed.
+    pub approval_threshold: f64,
+    pub voting_period_secs: u64,
+}
+
+impl Default for GovernanceParams {
+    fn default() -> Self {
+        GovernanceParams {
+            quorum: 0.2,
+            approval_threshold: 0.5,
+            voting_period_secs: 7 * 86_400,
+        }
+    }
+}
+
+/// `delegator` hands the quadratic weight of `votes` to `delegate`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Delegation {
+    pub delegator: u64,
+    pub delegate: u64,
+    pub votes: u64,
+}
+
+/// Longest delegation chain, in links, unless configured otherwise.
+pub const DEFAULT_MAX_DELEGATION_DEPTH: usize = 3;
+
 // Governance Module
 pub struct SelfAmendingGovernance {
     proposals: Arc<DashMap<u64, Proposal>>,
@@ -62,6 +234,14 @@ pub struct SelfAmendingGovernance {
     quantum_secure: Arc<QuantumSecure>,
     zkp_module: Arc<BLEEPZKPModule>,
     interoperability: Arc<BLEEPInteroperabilityModule>,
+    /// Delegations by delegator.
+    delegations: Arc<RwLock<BTreeMap<u64, Delegation>>>,
+    max_delegation_depth: usize,
+    params: GovernanceParams,
+    clock: Arc<dyn Clock>,
+    stake: StakeLookup,
+    db: Option<Arc<DB>>,
+    quarantined: usize,
 }
 
 impl SelfAmendingGovernance {
@@ -77,18 +257,232 @@ impl SelfAmendingGovernance {
             quantum_secure,
             zkp_module,
             interoperability,
+            delegations: Arc::new(RwLock::new(BTreeMap::new())),
+            max_delegation_depth: DEFAULT_MAX_DELEGATION_DEPTH,
+            params: GovernanceParams::default(),
+            clock: Arc::new(SystemClock),
+            stake: Arc::new(|_: &[u8]| 0),
+            db: None,
+            quarantined: 0,
         })
     }
 
+    pub fn with_params(mut self, params: GovernanceParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    /// Time source for voting periods.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Where voting power comes from.  Without one nobody holds any.
+    pub fn with_stake_lookup(mut self, stake: StakeLookup) -> Self {
+        self.stake = stake;
+        self
+    }
+
+    /// Cap delegation chains at `depth` links.
+    pub fn with_max_delegation_depth(mut self, depth: usize) -> Self {
+        self.max_delegation_depth = depth;
+        self
+    }
+
+    /// Open (or create) a governance store at `path` and load its users and
+    /// proposals.  Entries that fail validation are quarantined, not fatal.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        quantum_secure: Arc<QuantumSecure>,
+        zkp_module: Arc<BLEEPZKPModule>,
+        interoperability: Arc<BLEEPInteroperabilityModule>,
+    ) -> Result<Self, SelfAmendingError> {
+        let path = path.as_ref();
+        let db = DB::open_default(path)
+            .map_err(|e| SelfAmendingError::StorageError(format!("open {}: {}", path.display(), e)))?;
+        let migrated = migrations::migrate(&db, &GOVERNANCE_SCHEMA, false)
+            .map_err(|e| SelfAmendingError::StorageError(e.to_string()))?;
+        if !migrated.is_noop() {
+            info!("[Governance] {}", migrated.render());
+        }
+
+        let mut governance = Self::new(quantum_secure, zkp_module, interoperability)?;
+        let mut rejects = WriteBatch::default();
+        for entry in db.iterator(IteratorMode::Start) {
+            let (key, value) = entry.map_err(|e| SelfAmendingError::StorageError(e.to_string()))?;
+            let loaded = if let Some(id) = key.strip_prefix(PREFIX_USER) {
+                decode_entry::<User>(id, &value, |u| u.id).map(|u| {
+                    governance.users.insert(u.id, u);
+                })
+            } else if let Some(id) = key.strip_prefix(PREFIX_PROPOSAL) {
+                decode_entry::<Proposal>(id, &value, |p| p.id)
+                    .and_then(|p| if p.audit_hash == audit_hash(&p.description) {
+                        Ok(p)
+                    } else {
+                        Err("audit hash does not match the description".to_string())
+                    })
+                    .map(|p| {
+                        governance.proposals.insert(p.id, p);
+                    })
+            } else if let Some(id) = key.strip_prefix(PREFIX_DELEGATION) {
+                decode_entry::<Delegation>(id, &value, |d| d.delegator).map(|d| {
+                    governance.delegations.write().insert(d.delegator, d);
+                })
+            } else {
+                Ok(())
+            };
+            if let Err(reason) = loaded {
+                warn!("[Governance] Quarantining {}: {}", String::from_utf8_lossy(&key), reason);
+                rejects.put([PREFIX_QUARANTINE, &key[..]].concat(), &value);
+                rejects.delete(&key);
+                governance.quarantined += 1;
+            }
+        }
+        db.write(rejects).map_err(|e| SelfAmendingError::StorageError(e.to_string()))?;
+
+        info!(
+            "[Governance] Opened {} — {} users, {} proposals, {} quarantined",
+            path.display(), governance.users.len(), governance.proposals.len(), governance.quarantined,
+        );
+        governance.db = Some(Arc::new(db));
+        Ok(governance)
+    }
+
+    /// Entries moved to quarantine by `open`.
+    pub fn quarantined(&self) -> usize {
+        self.quarantined
+    }
+
+    pub fn proposal(&self, proposal_id: u64) -> Option<Proposal> {
+        self.proposals.get(&proposal_id).map(|p| p.clone())
+    }
+
+    /// Hand `delegator_id`'s weight (quadratic in `votes`) to
+    /// `delegate_user_id`, replacing any earlier delegation.  Refused if it
+    /// would close a cycle or make a chain longer than the configured depth.
+    pub fn delegate_vote(&self, delegator_id: u64, delegate_user_id: u64, votes: u64) -> Result<(), SelfAmendingError> {
+        for id in [delegator_id, delegate_user_id] {
+            if !self.users.contains_key(&id) {
+                return Err(SelfAmendingError::DelegationError(format!("no user {}", id)));
+            }
+        }
+        if delegator_id == delegate_user_id {
+            return Err(SelfAmendingError::DelegationError("cannot delegate to oneself".into()));
+        }
+        self.check_power(delegator_id, votes)?;
+
+        let mut delegations = self.delegations.write();
+        let mut graph = delegations.clone();
+        graph.remove(&delegator_id);
+
+        // Links from the delegate onwards; reaching the delegator is a cycle
+        let mut downstream = 1;
+        let mut cursor = delegate_user_id;
+        while let Some(next) = graph.get(&cursor) {
+            if next.delegate == delegator_id {
+                return Err(SelfAmendingError::DelegationError(format!(
+                    "{} → {} would close a cycle", delegator_id, delegate_user_id,
+                )));
+            }
+            cursor = next.delegate;
+            downstream += 1;
+        }
+        let depth = upstream_depth(&graph, delegator_id) + downstream;
+        if depth > self.max_delegation_depth {
+            return Err(SelfAmendingError::DelegationError(format!(
+                "chain through {} → {} would be {} links, limit is {}",
+                delegator_id, delegate_user_id, depth, self.max_delegation_depth,
+            )));
+        }
+
+        let delegation = Delegation { delegator: delegator_id, delegate: delegate_user_id, votes };
+        self.persist(PREFIX_DELEGATION, delegator_id, &delegation)?;
+        delegations.insert(delegator_id, delegation);
+        info!("User {} delegated to user {}", delegator_id, delegate_user_id);
+        Ok(())
+    }
+
+    /// Withdraw `delegator_id`'s delegation.  Weight already counted on a
+    /// proposal stays there; later votes no longer carry it.
+    pub fn revoke_delegation(&self, delegator_id: u64) -> Result<Delegation, SelfAmendingError> {
+        let mut delegations = self.delegations.write();
+        if !delegations.contains_key(&delegator_id) {
+            return Err(SelfAmendingError::DelegationError(format!("user {} has not delegated", delegator_id)));
+        }
+        if let Some(db) = &self.db {
+            db.delete([PREFIX_DELEGATION, &delegator_id.to_be_bytes()].concat())
+                .map_err(|e| SelfAmendingError::StorageError(e.to_string()))?;
+        }
+        let revoked = delegations.remove(&delegator_id).expect("checked above");
+        info!("User {} revoked their delegation to user {}", delegator_id, revoked.delegate);
+        Ok(revoked)
+    }
+
+    /// Current delegation graph, ordered by delegator.
+    pub fn get_delegations(&self) -> Vec<Delegation> {
+        self.delegations.read().values().cloned().collect()
+    }
+
+    /// Sum of every user's weight at full voting power, by current stake.
+    pub fn total_voting_weight(&self) -> u64 {
+        self.users.iter().map(|u| quadratic_weight((self.stake)(&u.public_key))).sum()
+    }
+
+    fn check_power(&self, user_id: u64, votes: u64) -> Result<(), SelfAmendingError> {
+        let power = self.users.get(&user_id).map(|u| (self.stake)(&u.public_key))
+            .ok_or_else(|| SelfAmendingError::DelegationError(format!("no user {}", user_id)))?;
+        if votes > power {
+            return Err(SelfAmendingError::InsufficientVotingPower { user: user_id, power, requested: votes });
+        }
+        Ok(())
+    }
+
+    /// Mark proposals whose voting ended without quorum as expired and
+    /// return their ids.
+    pub fn close_expired_proposals(&self) -> Result<Vec<u64>, SelfAmendingError> {
+        let now = self.clock.now_secs();
+        let mut closed = Vec::new();
+        for mut proposal in self.proposals.iter_mut() {
+            if proposal.executed || proposal.expired || now < proposal.voting_ends {
+                continue;
+            }
+            if proposal.votes_for + proposal.votes_against < self.required_weight(&proposal) {
+                let mut updated = proposal.clone();
+                updated.expired = true;
+                self.persist(PREFIX_PROPOSAL, updated.id, &updated)?;
+                *proposal = updated;
+                closed.push(proposal.id);
+            }
+        }
+        closed.sort_unstable();
+        for id in &closed {
+            info!("Proposal {} expired without quorum", id);
+        }
+        Ok(closed)
+    }
+
+    fn required_weight(&self, proposal: &Proposal) -> u64 {
+        (self.params.quorum * proposal.total_weight() as f64).ceil() as u64
+    }
+
+    fn persist<T: Serialize>(&self, prefix: &[u8], id: u64, value: &T) -> Result<(), SelfAmendingError> {
+        let Some(db) = &self.db else { return Ok(()) };
+        let bytes = serde_json::to_vec(value).map_err(|e| SelfAmendingError::StorageError(e.to_string()))?;
+        db.put([prefix, &id.to_be_bytes()].concat(), bytes)
+            .map_err(|e| SelfAmendingError::StorageError(e.to_string()))
+    }
+
     /// Register a new user securely
     pub async fn register_user(&self, username: &str, role: &str, public_key: Vec<u8>) -> Result<u64, SelfAmendingError> {
-        let user_id = self.users.len() as u64 + 1;
+        let user_id = self.users.iter().map(|u| *u.key()).max().unwrap_or(0) + 1;
         let user = User {
             id: user_id,
             username: username.to_string(),
             role: role.to_string(),
             public_key,
         };
+        self.persist(PREFIX_USER, user_id, &user)?;
         self.users.insert(user_id, user);
         info!("User registered: {}", username);
         Ok(user_id)
@@ -96,15 +490,15 @@ impl SelfAmendingGovernance {
 
     /// Submit a proposal
     pub async fn submit_proposal(&self, proposer: User, title: &str, description: &str) -> Result<u64, SelfAmendingError> {
-        let proposal_id = self.proposals.len() as u64 + 1;
+        let proposal_id = self.proposals.iter().map(|p| *p.key()).max().unwrap_or(0) + 1;
 
         // Categorize the proposal using the ML model
         let category = self.categorize_proposal(description).await?;
 
         // Generate an audit hash for the proposal
-        let mut hasher = Sha256::new();
-        hasher.update(description);
-        let audit_hash = hasher.finalize().to_vec();
+        let audit_hash = audit_hash(description);
+        let now = self.clock.now_secs();
+        let electorate = self.users.iter().map(|u| (u.id, (self.stake)(&u.public_key))).collect();
 
         let proposal = Proposal {
             id: proposal_id,
@@ -116,8 +510,18 @@ impl SelfAmendingGovernance {
             executed: false,
             audit_hash,
             category: Some(category),
+            delegated_for: 0,
+            delegated_against: 0,
+            counted: BTreeSet::new(),
+            voting_starts: now,
+            voting_ends: now + self.params.voting_period_secs,
+            expired: false,
+            electorate,
+            spent: BTreeMap::new(),
+            sides: BTreeMap::new(),
         };
 
+        self.persist(PREFIX_PROPOSAL, proposal_id, &proposal)?;
         self.proposals.insert(proposal_id, proposal);
         info!("Proposal submitted: {}", title);
         Ok(proposal_id)
@@ -140,7 +544,8 @@ impl SelfAmendingGovernance {
         }
     }
 
-    /// Vote on a proposal using ZKP for privacy
+    /// Vote on a proposal using ZKP for privacy.  The weight of everyone
+    /// delegating to `voter` and not yet counted goes in with it.
     pub async fn vote(
         &self,
         proposal_id: u64,
@@ -148,23 +553,48 @@ impl SelfAmendingGovernance {
         votes: u64,
         support: bool,
     ) -> Result<(), SelfAmendingError> {
-        let weight = (votes as f64).sqrt() as u64; // Quadratic voting weight
-
         // Generate a ZKP for the vote
         let vote_bytes = format!("{}:{}", proposal_id, if support { 1 } else { 0 }).into_bytes();
         let _proof = self.zkp_module.generate_proof(&vote_bytes)
             .map_err(|_| SelfAmendingError::ZKPGenerationError)?;
 
         if let Some(mut proposal) = self.proposals.get_mut(&proposal_id) {
+            if self.clock.now_secs() >= proposal.voting_ends {
+                return Err(SelfAmendingError::VotingClosed { ended_at: proposal.voting_ends });
+            }
+            let power = proposal.remaining_power(voter.id);
+            if votes > power {
+                return Err(SelfAmendingError::InsufficientVotingPower { user: voter.id, power, requested: votes });
+            }
+            if proposal.sides.get(&voter.id).is_some_and(|&side| side != support) {
+                return Err(SelfAmendingError::OppositeSide(voter.id));
+            }
+            let mut updated = proposal.clone();
+            updated.counted.insert(voter.id);
+            let weight = updated.spend(voter.id, votes, support);
+            let mut delegated = 0;
+            for d in collect_delegators(&self.delegations.read(), voter.id, &updated.counted) {
+                // Only the electorate counts, with what it has left here
+                if !updated.electorate.contains_key(&d.delegator) {
+                    continue;
+                }
+                let carried = d.votes.min(updated.remaining_power(d.delegator));
+                updated.counted.insert(d.delegator);
+                delegated += updated.spend(d.delegator, carried, support);
+            }
             if support {
-                proposal.votes_for += weight;
+                updated.votes_for += weight + delegated;
+                updated.delegated_for += delegated;
             } else {
-                proposal.votes_against += weight;
+                updated.votes_against += weight + delegated;
+                updated.delegated_against += delegated;
             }
+            self.persist(PREFIX_PROPOSAL, proposal_id, &updated)?;
+            *proposal = updated;
 
             info!(
-                "Vote recorded for proposal {} by user {} with weight {}",
-                proposal_id, voter.username, weight
+                "Vote recorded for proposal {} by user {} with weight {} (+{} delegated)",
+                proposal_id, voter.username, weight, delegated
             );
             Ok(())
         } else {
@@ -173,28 +603,47 @@ impl SelfAmendingGovernance {
         }
     }
 
-    /// Execute a proposal if conditions are met
+    /// Execute a proposal once its voting period is over, quorum was
+    /// reached and `votes_for` clears the approval threshold.
     pub async fn execute_proposal(&self, proposal_id: u64) -> Result<(), SelfAmendingError> {
         if let Some(mut proposal) = self.proposals.get_mut(&proposal_id) {
-            if proposal.votes_for > proposal.votes_against && !proposal.executed {
-                proposal.executed = true;
-
-                // Add blockchain integration to log execution
-                let execution_log = format!(
-                    "Executed proposal: {} with {} votes for and {} votes against",
-                    proposal.title, proposal.votes_for, proposal.votes_against
-                );
-                self.log_to_blockchain(&execution_log).await?;
-
-                info!("Proposal executed: {}", proposal.title);
-                Ok(())
-            } else {
+            let required = self.required_weight(&proposal);
+            if proposal.executed {
+                warn!("Proposal {} has already been executed", proposal_id);
+                return Err(SelfAmendingError::ExecutionError);
+            }
+            if self.clock.now_secs() < proposal.voting_ends {
+                return Err(SelfAmendingError::VotingStillOpen { ends_at: proposal.voting_ends });
+            }
+            let cast = proposal.votes_for + proposal.votes_against;
+            if proposal.expired || cast < required {
+                return Err(SelfAmendingError::QuorumNotReached { cast, required });
+            }
+            if proposal.votes_for as f64 <= self.params.approval_threshold * cast as f64 {
                 warn!(
-                    "Execution conditions not met for proposal {}. Votes For: {}, Votes Against: {}",
+                    "Proposal {} rejected. Votes For: {}, Votes Against: {}",
                     proposal_id, proposal.votes_for, proposal.votes_against
                 );
-                Err(SelfAmendingError::ExecutionError)
+                return Err(SelfAmendingError::Rejected {
+                    votes_for: proposal.votes_for,
+                    votes_against: proposal.votes_against,
+                });
             }
+
+            let mut updated = proposal.clone();
+            updated.executed = true;
+            self.persist(PREFIX_PROPOSAL, proposal_id, &updated)?;
+            *proposal = updated;
+
+            // Add blockchain integration to log execution
+            let execution_log = format!(
+                "Executed proposal: {} with {} votes for and {} votes against",
+                proposal.title, proposal.votes_for, proposal.votes_against
+            );
+            self.log_to_blockchain(&execution_log).await?;
+
+            info!("Proposal executed: {}", proposal.title);
+            Ok(())
         } else {
             error!("Proposal not found: {}", proposal_id);
             Err(SelfAmendingError::InvalidProposalError)
@@ -222,3 +671,399 @@ impl SelfAmendingGovernance {
         Ok(())
     }
 }
+
+/// Quadratic voting weight of `votes`.
+fn quadratic_weight(votes: u64) -> u64 {
+    // Floor square root; the float estimate is corrected for large inputs
+    let mut root = (votes as f64).sqrt() as u64;
+    while root.checked_mul(root).map_or(true, |sq| sq > votes) {
+        root -= 1;
+    }
+    while (root + 1).checked_mul(root + 1).is_some_and(|sq| sq <= votes) {
+        root += 1;
+    }
+    root
+}
+
+/// Links in the longest chain of delegators ending at `user`.
+fn upstream_depth(graph: &BTreeMap<u64, Delegation>, user: u64) -> usize {
+    graph.values()
+        .filter(|d| d.delegate == user)
+        .map(|d| 1 + upstream_depth(graph, d.delegator))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Everyone delegating to `voter`, directly or transitively, who is not in
+/// `counted`.  A counted delegator's own delegators were counted with it.
+fn collect_delegators(graph: &BTreeMap<u64, Delegation>, voter: u64, counted: &BTreeSet<u64>) -> Vec<Delegation> {
+    let mut found = Vec::new();
+    let mut frontier = vec![voter];
+    while let Some(user) = frontier.pop() {
+        for d in graph.values().filter(|d| d.delegate == user && !counted.contains(&d.delegator)) {
+            frontier.push(d.delegator);
+            found.push(d.clone());
+        }
+    }
+    found
+}
+
+fn audit_hash(description: &str) -> Vec<u8> {
+    Sha256::digest(description.as_bytes()).to_vec()
+}
+
+/// Decode a stored entry and check it is filed under its own id.
+fn decode_entry<T: for<'de> Deserialize<'de>>(
+    id_bytes: &[u8],
+    value: &[u8],
+    id_of: impl Fn(&T) -> u64,
+) -> Result<T, String> {
+    let id = u64::from_be_bytes(id_bytes.try_into().map_err(|_| "malformed key".to_string())?);
+    let entry: T = serde_json::from_slice(value).map_err(|e| e.to_string())?;
+    if id_of(&entry) != id {
+        return Err(format!("entry claims id {}", id_of(&entry)));
+    }
+    Ok(entry)
+}
+
+/// Schema 1 → 2.  Stored voting power was whatever `register_user` was
+/// told, so it is dropped, and proposals still open without an electorate
+/// — tallied with that power, or from before voting periods with
+/// `voting_ends` 0 — are closed as expired.  Entries that do not decode are
+/// left for `open` to quarantine.
+fn close_unsnapshotted_proposals(db: &DB) -> Result<(), String> {
+    let mut batch = WriteBatch::default();
+    for entry in db.iterator(IteratorMode::Start) {
+        let (key, value) = entry.map_err(|e| e.to_string())?;
+        if key.starts_with(PREFIX_USER) {
+            let Ok(user) = serde_json::from_slice::<User>(&value) else { continue };
+            batch.put(&key, serde_json::to_vec(&user).map_err(|e| e.to_string())?);
+        } else if key.starts_with(PREFIX_PROPOSAL) {
+            let Ok(mut proposal) = serde_json::from_slice::<Proposal>(&value) else { continue };
+            if !proposal.executed && proposal.electorate.is_empty() {
+                proposal.expired = true;
+            }
+            batch.put(&key, serde_json::to_vec(&proposal).map_err(|e| e.to_string())?);
+        }
+    }
+    db.write(batch).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_auth::ManualClock;
+
+    fn temp_dir(tag: &str) -> std::path::PathBuf {
+        let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("bleep-governance-{}-{}-{}", tag, std::process::id(), nanos))
+    }
+
+    fn open(dir: &Path) -> SelfAmendingGovernance {
+        SelfAmendingGovernance::open(
+            dir,
+            Arc::new(QuantumSecure::keygen()),
+            Arc::new(BLEEPZKPModule::new()),
+            Arc::new(BLEEPInteroperabilityModule::new()),
+        )
+        .unwrap()
+        .with_stake_lookup(Arc::new(|_: &[u8]| 100))
+    }
+
+    #[tokio::test]
+    async fn test_tally_survives_reopen() {
+        let dir = temp_dir("reopen");
+        let proposal_id = {
+            let governance = open(&dir);
+            let id = governance.register_user("alice", "Admin", vec![1, 2, 3]).await.unwrap();
+            let alice = governance.users.get(&id).unwrap().clone();
+            let proposal_id = governance.submit_proposal(alice.clone(), "Raise gas cap", "update the gas cap").await.unwrap();
+            governance.vote(proposal_id, alice.clone(), 16, true).await.unwrap();
+            governance.vote(proposal_id, alice, 9, true).await.unwrap();
+            proposal_id
+        };
+
+        let governance = open(&dir);
+        assert_eq!(governance.users.len(), 1);
+        let proposal = governance.proposal(proposal_id).unwrap();
+        // Adding to a vote weighs the total: √25, not √16 + √9
+        assert_eq!((proposal.votes_for, proposal.votes_against), (5, 0));
+        assert!(!proposal.executed);
+        assert_eq!(governance.quarantined(), 0);
+
+        // New ids continue after the reloaded ones
+        let bob = governance.register_user("bob", "Member", vec![4]).await.unwrap();
+        assert_eq!(bob, 2);
+    }
+
+    fn governance() -> SelfAmendingGovernance {
+        SelfAmendingGovernance::new(
+            Arc::new(QuantumSecure::keygen()),
+            Arc::new(BLEEPZKPModule::new()),
+            Arc::new(BLEEPInteroperabilityModule::new()),
+        )
+        .unwrap()
+        .with_stake_lookup(Arc::new(|_: &[u8]| 100))
+    }
+
+    async fn users(governance: &SelfAmendingGovernance, n: usize) -> Vec<User> {
+        let mut users = Vec::new();
+        for i in 0..n {
+            let id = governance.register_user(&format!("user{}", i), "Member", vec![i as u8]).await.unwrap();
+            users.push(governance.users.get(&id).unwrap().clone());
+        }
+        users
+    }
+
+    #[tokio::test]
+    async fn test_delegated_weight_follows_the_delegate() {
+        let governance = governance();
+        let u = users(&governance, 4).await;
+        // u0 → u1 → u2; u3 votes on its own
+        governance.delegate_vote(u[0].id, u[1].id, 4).unwrap();
+        governance.delegate_vote(u[1].id, u[2].id, 9).unwrap();
+        let p = governance.submit_proposal(u[3].clone(), "Fee cut", "update fees").await.unwrap();
+
+        governance.vote(p, u[2].clone(), 16, true).await.unwrap();
+        governance.vote(p, u[3].clone(), 25, false).await.unwrap();
+        let tally = governance.proposal(p).unwrap();
+        assert_eq!((tally.votes_for, tally.delegated_for), (4 + 3 + 2, 3 + 2));
+        assert_eq!((tally.votes_against, tally.delegated_against), (5, 0));
+
+        // Each delegation went in once; what u0 kept is still theirs to
+        // cast, on the side its delegation took
+        governance.vote(p, u[2].clone(), 9, true).await.unwrap();
+        assert_eq!(governance.proposal(p).unwrap().delegated_for, 3 + 2);
+        assert!(matches!(governance.vote(p, u[0].clone(), 96, false).await, Err(SelfAmendingError::OppositeSide(_))));
+        governance.vote(p, u[0].clone(), 96, true).await.unwrap();
+        assert!(matches!(
+            governance.vote(p, u[0].clone(), 1, true).await,
+            Err(SelfAmendingError::InsufficientVotingPower { power: 0, .. })
+        ));
+        let tally = governance.proposal(p).unwrap();
+        // u2: √16 then √25 - √16; u0: √4 delegated then √100 - √4
+        assert_eq!((tally.votes_for, tally.votes_against), (4 + 3 + 2 + 1 + 8, 5));
+        assert_eq!(governance.get_delegations().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_splitting_a_vote_does_not_raise_its_weight() {
+        let governance = governance();
+        let u = users(&governance, 2).await;
+        let p = governance.submit_proposal(u[0].clone(), "Split", "code change").await.unwrap();
+        for _ in 0..100 {
+            governance.vote(p, u[0].clone(), 1, true).await.unwrap();
+        }
+        governance.vote(p, u[1].clone(), 100, false).await.unwrap();
+        let tally = governance.proposal(p).unwrap();
+        assert_eq!((tally.votes_for, tally.votes_against), (10, 10));
+        assert_eq!(quadratic_weight(u64::MAX), u32::MAX as u64);
+    }
+
+    #[tokio::test]
+    async fn test_cycles_and_deep_chains_are_refused() {
+        let governance = governance().with_max_delegation_depth(2);
+        let u = users(&governance, 4).await;
+        governance.delegate_vote(u[0].id, u[1].id, 1).unwrap();
+        assert!(matches!(governance.delegate_vote(u[1].id, u[0].id, 1), Err(SelfAmendingError::DelegationError(_))));
+        assert!(governance.delegate_vote(u[2].id, u[2].id, 1).is_err());
+
+        governance.delegate_vote(u[1].id, u[2].id, 1).unwrap();
+        assert!(matches!(governance.delegate_vote(u[2].id, u[0].id, 1), Err(SelfAmendingError::DelegationError(_))));
+        // u0 → u1 → u2 → u3 is three links
+        assert!(matches!(governance.delegate_vote(u[2].id, u[3].id, 1), Err(SelfAmendingError::DelegationError(_))));
+        // Growing the chain from the front counts too
+        assert!(governance.delegate_vote(u[3].id, u[0].id, 1).is_err());
+        assert_eq!(governance.get_delegations().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_revocation_mid_proposal() {
+        let governance = governance();
+        let u = users(&governance, 2).await;
+        governance.delegate_vote(u[0].id, u[1].id, 9).unwrap();
+        let first = governance.submit_proposal(u[1].clone(), "One", "code change").await.unwrap();
+        let second = governance.submit_proposal(u[1].clone(), "Two", "code change two").await.unwrap();
+        governance.vote(first, u[1].clone(), 4, true).await.unwrap();
+
+        let revoked = governance.revoke_delegation(u[0].id).unwrap();
+        assert_eq!(revoked.delegate, u[1].id);
+        assert!(governance.get_delegations().is_empty());
+        assert!(governance.revoke_delegation(u[0].id).is_err());
+
+        // Already counted on the first proposal; free to vote on the second
+        assert_eq!(governance.proposal(first).unwrap().votes_for, 2 + 3);
+        governance.vote(second, u[1].clone(), 4, true).await.unwrap();
+        governance.vote(second, u[0].clone(), 9, false).await.unwrap();
+        let tally = governance.proposal(second).unwrap();
+        assert_eq!((tally.votes_for, tally.delegated_for, tally.votes_against), (2, 0, 3));
+    }
+
+    fn timed(clock: &Arc<ManualClock>) -> SelfAmendingGovernance {
+        governance()
+            .with_params(GovernanceParams { quorum: 0.5, approval_threshold: 0.5, voting_period_secs: 100 })
+            .with_clock(clock.clone())
+    }
+
+    #[tokio::test]
+    async fn test_execution_waits_for_the_voting_period() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let governance = timed(&clock);
+        let u = users(&governance, 4).await;
+        let p = governance.submit_proposal(u[0].clone(), "Ship it", "code change").await.unwrap();
+        governance.vote(p, u[0].clone(), 100, true).await.unwrap();
+        governance.vote(p, u[1].clone(), 100, true).await.unwrap();
+        assert!(matches!(governance.execute_proposal(p).await, Err(SelfAmendingError::VotingStillOpen { ends_at: 1_100 })));
+
+        clock.advance(100);
+        assert!(matches!(governance.vote(p, u[2].clone(), 100, false).await, Err(SelfAmendingError::VotingClosed { .. })));
+        governance.execute_proposal(p).await.unwrap();
+        assert!(governance.proposal(p).unwrap().executed);
+        assert!(matches!(governance.execute_proposal(p).await, Err(SelfAmendingError::ExecutionError)));
+
+        // Voting power bounds what a user can cast
+        let q = governance.submit_proposal(u[0].clone(), "Again", "code change again").await.unwrap();
+        assert!(matches!(
+            governance.vote(q, u[0].clone(), 101, true).await,
+            Err(SelfAmendingError::InsufficientVotingPower { power: 100, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_quorum_and_approval_are_enforced() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let governance = timed(&clock);
+        let u = users(&governance, 4).await;
+        assert_eq!(governance.total_voting_weight(), 40);
+        let thin = governance.submit_proposal(u[0].clone(), "Thin", "code change").await.unwrap();
+        let split = governance.submit_proposal(u[0].clone(), "Split", "update split").await.unwrap();
+        governance.vote(thin, u[0].clone(), 100, true).await.unwrap();
+        governance.vote(split, u[0].clone(), 100, true).await.unwrap();
+        governance.vote(split, u[1].clone(), 100, false).await.unwrap();
+
+        assert!(governance.close_expired_proposals().unwrap().is_empty());
+        clock.advance(100);
+        assert!(matches!(
+            governance.execute_proposal(thin).await,
+            Err(SelfAmendingError::QuorumNotReached { cast: 10, required: 20 })
+        ));
+        assert!(matches!(governance.execute_proposal(split).await, Err(SelfAmendingError::Rejected { votes_for: 10, votes_against: 10 })));
+
+        assert_eq!(governance.close_expired_proposals().unwrap(), vec![thin]);
+        assert!(governance.proposal(thin).unwrap().expired);
+        assert!(!governance.proposal(split).unwrap().expired);
+        assert!(governance.close_expired_proposals().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_quorum_is_measured_against_the_electorate_at_opening() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let stakes: Arc<DashMap<Vec<u8>, u64>> = Arc::new(DashMap::new());
+        let lookup = stakes.clone();
+        let governance = timed(&clock)
+            .with_stake_lookup(Arc::new(move |key: &[u8]| lookup.get(key).map(|s| *s).unwrap_or(0)));
+        for i in 0..4u8 {
+            stakes.insert(vec![i], 100);
+        }
+        let u = users(&governance, 4).await;
+        let p = governance.submit_proposal(u[0].clone(), "Ship it", "code change").await.unwrap();
+
+        // Stake that arrives after opening changes nothing on this proposal
+        stakes.insert(vec![0], 10_000);
+        stakes.insert(vec![4], 10_000);
+        let late = governance.register_user("late", "Member", vec![4]).await.unwrap();
+        let late = governance.users.get(&late).unwrap().clone();
+        assert_eq!(governance.total_voting_weight(), 100 + 10 + 10 + 10 + 100);
+        assert_eq!(governance.proposal(p).unwrap().total_weight(), 40);
+        assert!(matches!(
+            governance.vote(p, late, 1, true).await,
+            Err(SelfAmendingError::InsufficientVotingPower { power: 0, .. })
+        ));
+        assert!(matches!(
+            governance.vote(p, u[0].clone(), 101, true).await,
+            Err(SelfAmendingError::InsufficientVotingPower { power: 100, .. })
+        ));
+
+        governance.vote(p, u[0].clone(), 100, true).await.unwrap();
+        governance.vote(p, u[1].clone(), 100, true).await.unwrap();
+        clock.advance(100);
+        governance.execute_proposal(p).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_schema_1_proposals_without_an_electorate_are_closed() {
+        let dir = temp_dir("migrate");
+        {
+            let db = DB::open_default(&dir).unwrap();
+            let alice = serde_json::json!({
+                "id": 1, "username": "alice", "role": "Admin", "public_key": [1], "voting_power": 1_000_000,
+            });
+            db.put([PREFIX_USER, &1u64.to_be_bytes()].concat(), alice.to_string()).unwrap();
+            for (id, executed) in [(1u64, false), (2, true)] {
+                let proposal = serde_json::json!({
+                    "id": id, "title": "Old", "description": "code change", "proposer": alice.clone(),
+                    "votes_for": 1_000, "votes_against": 0, "executed": executed,
+                    "audit_hash": audit_hash("code change"), "category": null,
+                });
+                db.put([PREFIX_PROPOSAL, &id.to_be_bytes()].concat(), proposal.to_string()).unwrap();
+            }
+        }
+
+        let governance = open(&dir);
+        assert_eq!(governance.quarantined(), 0);
+        let stale = governance.proposal(1).unwrap();
+        assert!(stale.expired && stale.voting_ends == 0);
+        assert!(matches!(governance.execute_proposal(1).await, Err(SelfAmendingError::QuorumNotReached { .. })));
+        let done = governance.proposal(2).unwrap();
+        assert!(done.executed && !done.expired);
+        drop(governance);
+
+        let db = DB::open_default(&dir).unwrap();
+        let user = db.get([PREFIX_USER, &1u64.to_be_bytes()].concat()).unwrap().unwrap();
+        assert!(!String::from_utf8(user).unwrap().contains("voting_power"));
+    }
+
+    #[tokio::test]
+    async fn test_delegations_survive_reopen() {
+        let dir = temp_dir("delegation");
+        {
+            let governance = open(&dir);
+            let u = users(&governance, 3).await;
+            governance.delegate_vote(u[0].id, u[2].id, 1).unwrap();
+            governance.delegate_vote(u[1].id, u[2].id, 1).unwrap();
+            governance.revoke_delegation(u[1].id).unwrap();
+        }
+        let governance = open(&dir);
+        assert_eq!(governance.get_delegations(), vec![Delegation { delegator: 1, delegate: 3, votes: 1 }]);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_entries_are_quarantined() {
+        let dir = temp_dir("quarantine");
+        let kept = {
+            let governance = open(&dir);
+            let id = governance.register_user("alice", "Admin", vec![1]).await.unwrap();
+            let alice = governance.users.get(&id).unwrap().clone();
+            governance.submit_proposal(alice, "Keep", "governance vote").await.unwrap()
+        };
+        {
+            let db = DB::open_default(&dir).unwrap();
+            db.put([PREFIX_PROPOSAL, &7u64.to_be_bytes()].concat(), b"{\"id\": 7, \"tit").unwrap();
+            let mut edited = serde_json::from_slice::<Proposal>(
+                &db.get([PREFIX_PROPOSAL, &kept.to_be_bytes()].concat()).unwrap().unwrap(),
+            ).unwrap();
+            edited.id = 8;
+            edited.description = "rewritten after the fact".into();
+            db.put([PREFIX_PROPOSAL, &8u64.to_be_bytes()].concat(), serde_json::to_vec(&edited).unwrap()).unwrap();
+        }
+
+        let governance = open(&dir);
+        assert_eq!(governance.quarantined(), 2);
+        assert!(governance.proposal(kept).is_some());
+        assert!(governance.proposal(7).is_none() && governance.proposal(8).is_none());
+
+        // Quarantined entries stay out of the way on the next open
+        drop(governance);
+        assert_eq!(open(&dir).quarantined(), 0);
+    }
+}
@@ -0,0 +1,152 @@
+//! # Governance lifecycle events
+//!
+//! `GovernanceEngine` records a `GovernanceEvent` for every proposal
+//! transition and every vote it accepts.  The node drains them once per
+//! block with `GovernanceEngine::drain_events` and hands them to a
+//! `GovernanceFeed` (see `governance_feed`), which adds block heights and
+//! fans them out to subscribers.
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::governance_core::{Proposal, ProposalState, ProposalType, Vote};
+
+/// Topic of proposal transitions.
+pub const PROPOSALS_TOPIC: &str = "governance.proposals";
+/// Topic of cast votes.
+pub const VOTES_TOPIC: &str = "governance.votes";
+
+/// A proposal state change, as seen by subscribers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transition {
+    Submitted,
+    VotingOpened,
+    Passed,
+    Queued,
+    Executed,
+    Vetoed,
+    Rejected,
+    Expired,
+    RolledBack,
+}
+
+/// Timelock of a queued proposal, in epochs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedTimelock {
+    pub queued_epoch:    u64,
+    pub execution_epoch: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposalEvent {
+    pub proposal_id: String,
+    pub category:    ProposalType,
+    /// SHA3-256 of the title, hex
+    pub title_hash:  String,
+    pub transition:  Transition,
+    /// Set on `queued`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timelock:    Option<QueuedTimelock>,
+}
+
+impl ProposalEvent {
+    fn new(proposal: &Proposal, transition: Transition) -> Self {
+        Self {
+            proposal_id: proposal.id.clone(),
+            category:    proposal.proposal_type,
+            title_hash:  title_hash(&proposal.title),
+            transition,
+            timelock:    None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteDirection {
+    Approve,
+    Reject,
+}
+
+/// A vote the engine accepted.  Carries the vote's weight, never the
+/// voter's balance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteEvent {
+    pub proposal_id: String,
+    pub category:    ProposalType,
+    pub voter:       String,
+    pub weight:      u128,
+    pub direction:   VoteDirection,
+}
+
+impl VoteEvent {
+    pub(crate) fn new(proposal: &Proposal, vote: &Vote) -> Self {
+        Self {
+            proposal_id: proposal.id.clone(),
+            category:    proposal.proposal_type,
+            voter:       vote.validator_id.clone(),
+            weight:      vote.stake,
+            direction:   if vote.approval { VoteDirection::Approve } else { VoteDirection::Reject },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GovernanceEvent {
+    Proposal(ProposalEvent),
+    Vote(VoteEvent),
+}
+
+impl GovernanceEvent {
+    pub fn topic(&self) -> &'static str {
+        match self {
+            GovernanceEvent::Proposal(_) => PROPOSALS_TOPIC,
+            GovernanceEvent::Vote(_) => VOTES_TOPIC,
+        }
+    }
+
+    pub fn proposal_id(&self) -> &str {
+        match self {
+            GovernanceEvent::Proposal(e) => &e.proposal_id,
+            GovernanceEvent::Vote(e) => &e.proposal_id,
+        }
+    }
+
+    pub fn category(&self) -> ProposalType {
+        match self {
+            GovernanceEvent::Proposal(e) => e.category,
+            GovernanceEvent::Vote(e) => e.category,
+        }
+    }
+}
+
+/// Hex SHA3-256 of a proposal title.
+pub fn title_hash(title: &str) -> String {
+    hex::encode(Sha3_256::digest(title.as_bytes()))
+}
+
+/// Events for `proposal` having moved from `before` to its current state
+/// at `epoch`.  Passing emits `passed` and `queued` together.
+pub(crate) fn transition_events(proposal: &Proposal, before: ProposalState, epoch: u64) -> Vec<GovernanceEvent> {
+    if proposal.state == before {
+        return Vec::new();
+    }
+    let event = |t| GovernanceEvent::Proposal(ProposalEvent::new(proposal, t));
+    match proposal.state {
+        ProposalState::Pending => vec![event(Transition::Submitted)],
+        ProposalState::Voting => vec![event(Transition::VotingOpened)],
+        ProposalState::AwaitingExecution => {
+            let mut queued = ProposalEvent::new(proposal, Transition::Queued);
+            queued.timelock = Some(QueuedTimelock { queued_epoch: epoch, execution_epoch: proposal.execution_epoch });
+            vec![event(Transition::Passed), GovernanceEvent::Proposal(queued)]
+        }
+        ProposalState::Executed => vec![event(Transition::Executed)],
+        ProposalState::Vetoed => vec![event(Transition::Vetoed)],
+        ProposalState::Rejected => vec![event(Transition::Rejected)],
+        ProposalState::Expired => vec![event(Transition::Expired)],
+        ProposalState::RolledBack => vec![event(Transition::RolledBack)],
+        ProposalState::Draft | ProposalState::Tallying | ProposalState::Executing => Vec::new(),
+    }
+}
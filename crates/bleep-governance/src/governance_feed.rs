@@ -0,0 +1,625 @@
+//! # Governance event feed
+//!
+//! Fans the events drained from `GovernanceEngine` out to WebSocket
+//! subscribers and webhooks.
+//!
+//! - `GovernanceFeed::publish_block` gives the events committed in a block
+//!   its height and a sequence number each, and broadcasts them as
+//!   `Notice`s on `governance.proposals` / `governance.votes`.
+//! - A notice can be reverted until `confirmation_depth` blocks sit on top
+//!   of it (`final_at_height`).  A reorg (`revert_to`, or publishing a
+//!   height that was already published) withdraws the notices above the
+//!   fork point, newest first, with `revert` notices that repeat the event
+//!   and name the withdrawn `seq`.  Reverting a final height is refused.
+//! - `queued` notices carry the timelock in blocks, so UIs can count down
+//!   without another query.
+//! - `GovernanceWebhooks` POSTs matching notices to registered URLs, signed
+//!   with HMAC-SHA256 over `"{timestamp}.{body}"` (see
+//!   `webhook_signature`).
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use log::warn;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+use bleep_crypto::Sensitive;
+
+use crate::governance_core::ProposalType;
+use crate::governance_events::{GovernanceEvent, PROPOSALS_TOPIC, VOTES_TOPIC};
+
+/// Header carrying `sha256=<hex HMAC>` on webhook deliveries.
+pub const SIGNATURE_HEADER: &str = "x-bleep-signature";
+/// Header carrying the unix timestamp the signature covers.
+pub const TIMESTAMP_HEADER: &str = "x-bleep-timestamp";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FeedError {
+    #[error("unknown topic '{0}'")]
+    UnknownTopic(String),
+    #[error("unknown proposal category '{0}'")]
+    UnknownCategory(String),
+    #[error("cannot revert height {height}: heights up to {final_height} are final")]
+    BeyondConfirmationDepth { height: u64, final_height: u64 },
+    #[error("webhook url must start with http:// or https://")]
+    InvalidUrl,
+    #[error("webhook secret must not be empty")]
+    EmptySecret,
+    #[error("unknown webhook {0}")]
+    UnknownWebhook(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct FeedConfig {
+    /// Blocks on top of a notice's block before it can no longer be reverted
+    pub confirmation_depth: u64,
+    /// Converts timelocks from epochs to blocks
+    pub blocks_per_epoch:   u64,
+    /// Notices buffered per subscriber before it lags
+    pub channel_capacity:   usize,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self { confirmation_depth: 6, blocks_per_epoch: 1_000, channel_capacity: 1_024 }
+    }
+}
+
+/// Timelock of a `queued` notice, in blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimelockDeadline {
+    pub queued_at_height: u64,
+    pub window_blocks:    u64,
+    pub deadline_height:  u64,
+}
+
+/// One delivery on a governance topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notice {
+    pub seq:             u64,
+    pub topic:           String,
+    /// Height of the block that committed the event
+    pub height:          u64,
+    /// Height from which the event can no longer be reverted
+    pub final_at_height: u64,
+    /// On revert notices, the `seq` being withdrawn
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverts:         Option<u64>,
+    pub event:           GovernanceEvent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timelock:        Option<TimelockDeadline>,
+}
+
+/// Which notices a subscriber or webhook receives.  Empty lists match
+/// everything.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionFilter {
+    pub topics:       Vec<String>,
+    pub proposal_ids: Vec<String>,
+    pub categories:   Vec<ProposalType>,
+}
+
+impl SubscriptionFilter {
+    /// Build a filter; categories use `ProposalType::as_str` names
+    /// (`PROTOCOL_PARAMETER`, …), case-insensitively.
+    pub fn new(topics: Vec<String>, proposal_ids: Vec<String>, categories: &[String]) -> Result<Self, FeedError> {
+        if let Some(t) = topics.iter().find(|t| *t != PROPOSALS_TOPIC && *t != VOTES_TOPIC) {
+            return Err(FeedError::UnknownTopic(t.clone()));
+        }
+        let categories = categories
+            .iter()
+            .map(|c| parse_category(c).ok_or_else(|| FeedError::UnknownCategory(c.clone())))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { topics, proposal_ids, categories })
+    }
+
+    /// From the `topics`, `proposal` and `category` query parameters, each
+    /// comma-separated.
+    pub fn from_query(query: &HashMap<String, String>) -> Result<Self, FeedError> {
+        let list = |key: &str| -> Vec<String> {
+            query.get(key)
+                .map(|v| v.split(',').map(str::trim).filter(|s| !s.is_empty()).map(String::from).collect())
+                .unwrap_or_default()
+        };
+        Self::new(list("topics"), list("proposal"), &list("category"))
+    }
+
+    pub fn matches(&self, notice: &Notice) -> bool {
+        (self.topics.is_empty() || self.topics.iter().any(|t| *t == notice.topic))
+            && (self.proposal_ids.is_empty()
+                || self.proposal_ids.iter().any(|id| id == notice.event.proposal_id()))
+            && (self.categories.is_empty() || self.categories.contains(&notice.event.category()))
+    }
+}
+
+fn parse_category(name: &str) -> Option<ProposalType> {
+    [
+        ProposalType::ProtocolParameter,
+        ProposalType::ValidatorSanction,
+        ProposalType::Recovery,
+        ProposalType::UpgradeAuthorization,
+    ]
+    .into_iter()
+    .find(|t| t.as_str().eq_ignore_ascii_case(name))
+}
+
+// ── Feed ──────────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct FeedState {
+    next_seq:    u64,
+    tip:         Option<u64>,
+    /// Notices that can still be reverted, by height
+    unconfirmed: BTreeMap<u64, Vec<Notice>>,
+}
+
+pub struct GovernanceFeed {
+    config: FeedConfig,
+    state:  Mutex<FeedState>,
+    tx:     broadcast::Sender<Notice>,
+}
+
+impl GovernanceFeed {
+    pub fn new(config: FeedConfig) -> Self {
+        let (tx, _) = broadcast::channel(config.channel_capacity.max(1));
+        Self { config, state: Mutex::new(FeedState::default()), tx }
+    }
+
+    pub fn config(&self) -> &FeedConfig {
+        &self.config
+    }
+
+    /// Every notice published or reverted from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<Notice> {
+        self.tx.subscribe()
+    }
+
+    /// Highest height published.
+    pub fn tip(&self) -> Option<u64> {
+        self.state.lock().tip
+    }
+
+    /// Publish the events committed in the block at `height`.  A height at
+    /// or below the tip replaces that block: what was published from
+    /// `height` up is reverted first.
+    pub fn publish_block(&self, height: u64, events: Vec<GovernanceEvent>) -> Result<Vec<Notice>, FeedError> {
+        let mut st = self.state.lock();
+        self.revert_from(&mut st, height)?;
+
+        let final_at_height = height + self.config.confirmation_depth;
+        let mut notices = Vec::with_capacity(events.len());
+        for event in events {
+            let timelock = match &event {
+                GovernanceEvent::Proposal(p) => p.timelock.map(|t| {
+                    let window_blocks = t.execution_epoch.saturating_sub(t.queued_epoch) * self.config.blocks_per_epoch;
+                    TimelockDeadline { queued_at_height: height, window_blocks, deadline_height: height + window_blocks }
+                }),
+                GovernanceEvent::Vote(_) => None,
+            };
+            let notice = Notice {
+                seq: st.next_seq,
+                topic: event.topic().to_string(),
+                height,
+                final_at_height,
+                reverts: None,
+                event,
+                timelock,
+            };
+            st.next_seq += 1;
+            // No subscribers is fine.
+            let _ = self.tx.send(notice.clone());
+            notices.push(notice);
+        }
+        if !notices.is_empty() {
+            st.unconfirmed.insert(height, notices.clone());
+        }
+        st.tip = Some(height);
+        let depth = self.config.confirmation_depth;
+        st.unconfirmed.retain(|h, _| h + depth > height);
+        Ok(notices)
+    }
+
+    /// Roll the feed back to `height`, reverting everything published above it.
+    pub fn revert_to(&self, height: u64) -> Result<Vec<Notice>, FeedError> {
+        let mut st = self.state.lock();
+        self.revert_from(&mut st, height + 1)
+    }
+
+    fn revert_from(&self, st: &mut FeedState, from: u64) -> Result<Vec<Notice>, FeedError> {
+        let Some(tip) = st.tip else { return Ok(Vec::new()) };
+        if from > tip {
+            return Ok(Vec::new());
+        }
+        let depth = self.config.confirmation_depth;
+        if from + depth <= tip {
+            return Err(FeedError::BeyondConfirmationDepth { height: from, final_height: tip - depth });
+        }
+        let withdrawn = st.unconfirmed.split_off(&from);
+        let mut reverts = Vec::new();
+        for original in withdrawn.into_values().rev().flat_map(|ns| ns.into_iter().rev()) {
+            let notice = Notice { seq: st.next_seq, reverts: Some(original.seq), ..original };
+            st.next_seq += 1;
+            let _ = self.tx.send(notice.clone());
+            reverts.push(notice);
+        }
+        st.tip = from.checked_sub(1);
+        Ok(reverts)
+    }
+}
+
+// ── Webhooks ──────────────────────────────────────────────────────────────────
+
+/// `sha256=<hex>` HMAC-SHA256 of `"{timestamp}.{body}"` under `secret`.
+pub fn webhook_signature(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+    format!("sha256={}", hex::encode(webhook_mac(secret, timestamp, body).finalize().into_bytes()))
+}
+
+/// Constant-time check of a `x-bleep-signature` value.
+pub fn verify_webhook_signature(secret: &[u8], timestamp: u64, body: &[u8], signature: &str) -> bool {
+    let Some(sig) = signature.strip_prefix("sha256=").and_then(|h| hex::decode(h).ok()) else {
+        return false;
+    };
+    webhook_mac(secret, timestamp, body).verify_slice(&sig).is_ok()
+}
+
+fn webhook_mac(secret: &[u8], timestamp: u64, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac
+}
+
+/// Delivers one signed webhook body.
+#[async_trait]
+pub trait WebhookSink: Send + Sync {
+    async fn post(&self, url: &str, headers: Vec<(&'static str, String)>, body: Vec<u8>) -> Result<(), String>;
+}
+
+/// POSTs webhook bodies as JSON.
+pub struct HttpWebhookSink {
+    client: reqwest::Client,
+}
+
+impl Default for HttpWebhookSink {
+    fn default() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl WebhookSink for HttpWebhookSink {
+    async fn post(&self, url: &str, headers: Vec<(&'static str, String)>, body: Vec<u8>) -> Result<(), String> {
+        let mut req = self.client.post(url).header("content-type", "application/json");
+        for (name, value) in headers {
+            req = req.header(name, value);
+        }
+        let resp = req.body(body).send().await.map_err(|e| e.to_string())?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("HTTP {}", resp.status()))
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub id:        u64,
+    pub url:       String,
+    pub secret:    Sensitive<String>,
+    pub filter:    SubscriptionFilter,
+    pub delivered: u64,
+    /// Failed deliveries; failures are not retried
+    pub failed:    u64,
+}
+
+/// Registered governance webhooks.
+pub struct GovernanceWebhooks {
+    hooks:   Mutex<BTreeMap<u64, Webhook>>,
+    next_id: Mutex<u64>,
+    sink:    Arc<dyn WebhookSink>,
+}
+
+impl GovernanceWebhooks {
+    pub fn new(sink: Arc<dyn WebhookSink>) -> Self {
+        Self { hooks: Mutex::new(BTreeMap::new()), next_id: Mutex::new(1), sink }
+    }
+
+    pub fn register(&self, url: String, secret: String, filter: SubscriptionFilter) -> Result<u64, FeedError> {
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(FeedError::InvalidUrl);
+        }
+        if secret.is_empty() {
+            return Err(FeedError::EmptySecret);
+        }
+        let id = {
+            let mut next = self.next_id.lock();
+            *next += 1;
+            *next - 1
+        };
+        let hook = Webhook { id, url, secret: Sensitive::new(secret), filter, delivered: 0, failed: 0 };
+        self.hooks.lock().insert(id, hook);
+        Ok(id)
+    }
+
+    pub fn remove(&self, id: u64) -> Result<(), FeedError> {
+        self.hooks.lock().remove(&id).map(|_| ()).ok_or(FeedError::UnknownWebhook(id))
+    }
+
+    pub fn list(&self) -> Vec<Webhook> {
+        self.hooks.lock().values().cloned().collect()
+    }
+
+    /// Deliver `notice` to every webhook whose filter matches it.
+    pub async fn deliver(&self, notice: &Notice) {
+        let targets: Vec<Webhook> = self.hooks.lock().values().filter(|h| h.filter.matches(notice)).cloned().collect();
+        if targets.is_empty() {
+            return;
+        }
+        let body = match serde_json::to_vec(notice) {
+            Ok(b) => b,
+            Err(e) => {
+                warn!("[governance] notice {} not serialisable: {}", notice.seq, e);
+                return;
+            }
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        for hook in targets {
+            let headers = vec![
+                (SIGNATURE_HEADER, webhook_signature(hook.secret.expose().as_bytes(), timestamp, &body)),
+                (TIMESTAMP_HEADER, timestamp.to_string()),
+            ];
+            let result = self.sink.post(&hook.url, headers, body.clone()).await;
+            if let Err(e) = &result {
+                warn!("[governance] webhook {} ({}) failed for notice {}: {}", hook.id, hook.url, notice.seq, e);
+            }
+            if let Some(h) = self.hooks.lock().get_mut(&hook.id) {
+                match result {
+                    Ok(()) => h.delivered += 1,
+                    Err(_) => h.failed += 1,
+                }
+            }
+        }
+    }
+
+    /// Deliver every notice from `rx` until the feed closes.
+    pub async fn run(self: Arc<Self>, mut rx: broadcast::Receiver<Notice>) {
+        loop {
+            match rx.recv().await {
+                Ok(notice) => self.deliver(&notice).await,
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("[governance] webhooks lagged, {} notices not delivered", n);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::governance_core::{GovernanceEngine, GovernancePayload, Proposal, Vote, VotingWindow};
+    use crate::governance_events::Transition;
+
+    const STAKE: u128 = 1_000;
+
+    fn proposal(id: &str, kind: ProposalType) -> Proposal {
+        Proposal::new(
+            id.into(),
+            kind,
+            format!("Proposal {}", id),
+            "test".into(),
+            VotingWindow::new(2, 4).unwrap(),
+            6,
+            67,
+            GovernancePayload::ProtocolParameterChange { rule_name: "BLOCK_TIME".into(), new_value: 6000 },
+            0,
+        )
+    }
+
+    fn vote(voter: &str, stake: u128) -> Vote {
+        Vote::new(voter.into(), true, stake, 2, vec![])
+    }
+
+    fn drain(rx: &mut broadcast::Receiver<Notice>) -> Vec<Notice> {
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+
+    fn transition(n: &Notice) -> Option<Transition> {
+        match &n.event {
+            GovernanceEvent::Proposal(p) => Some(p.transition),
+            GovernanceEvent::Vote(_) => None,
+        }
+    }
+
+    /// Engine with `id` queued at epoch 4, events published at heights 1..=4.
+    fn queued(feed: &GovernanceFeed, id: &str) -> GovernanceEngine {
+        let mut engine = GovernanceEngine::new(STAKE);
+        engine.submit_proposal(proposal(id, ProposalType::ProtocolParameter)).unwrap();
+        feed.publish_block(1, engine.drain_events()).unwrap();
+        engine.advance_epoch(2).unwrap();
+        feed.publish_block(2, engine.drain_events()).unwrap();
+        engine.cast_vote(id, vote("validator-1", 900), 2).unwrap();
+        feed.publish_block(3, engine.drain_events()).unwrap();
+        engine.advance_epoch(4).unwrap();
+        feed.publish_block(4, engine.drain_events()).unwrap();
+        engine
+    }
+
+    #[test]
+    fn subscriber_sees_every_transition_once() {
+        let feed = GovernanceFeed::new(FeedConfig { blocks_per_epoch: 10, ..FeedConfig::default() });
+        let mut rx = feed.subscribe();
+        let mut engine = queued(&feed, "p1");
+        engine.advance_epoch(5).unwrap();
+        feed.publish_block(5, engine.drain_events()).unwrap();
+        engine.advance_epoch(6).unwrap();
+        feed.publish_block(6, engine.drain_events()).unwrap();
+        engine.advance_epoch(7).unwrap();
+        feed.publish_block(7, engine.drain_events()).unwrap();
+
+        let notices = drain(&mut rx);
+        let proposals: Vec<&Notice> = notices.iter().filter(|n| n.topic == PROPOSALS_TOPIC).collect();
+        assert_eq!(
+            proposals.iter().filter_map(|n| transition(n)).collect::<Vec<_>>(),
+            [Transition::Submitted, Transition::VotingOpened, Transition::Passed, Transition::Queued, Transition::Executed]
+        );
+        assert_eq!(proposals.iter().map(|n| n.height).collect::<Vec<_>>(), [1, 2, 4, 4, 6]);
+        let seqs: Vec<u64> = notices.iter().map(|n| n.seq).collect();
+        assert_eq!(seqs, (0..notices.len() as u64).collect::<Vec<_>>());
+
+        // Queue entry at block 4, two epochs of 10 blocks.
+        assert_eq!(
+            proposals[3].timelock,
+            Some(TimelockDeadline { queued_at_height: 4, window_blocks: 20, deadline_height: 24 })
+        );
+
+        let votes: Vec<&Notice> = notices.iter().filter(|n| n.topic == VOTES_TOPIC).collect();
+        assert_eq!(votes.len(), 1);
+        let json = serde_json::to_value(votes[0]).unwrap();
+        assert_eq!(json["event"]["voter"], "validator-1");
+        assert_eq!(json["event"]["weight"], 900);
+        assert_eq!(json["event"]["direction"], "approve");
+        assert!(json["event"].get("balance").is_none() && json["event"].get("stake").is_none());
+    }
+
+    #[derive(Default)]
+    struct CaptureSink(Mutex<Vec<(String, Vec<(&'static str, String)>, Vec<u8>)>>);
+
+    #[async_trait]
+    impl WebhookSink for CaptureSink {
+        async fn post(&self, url: &str, headers: Vec<(&'static str, String)>, body: Vec<u8>) -> Result<(), String> {
+            self.0.lock().push((url.to_string(), headers, body));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn veto_webhook_is_signed() {
+        let feed = GovernanceFeed::new(FeedConfig::default());
+        let sink = Arc::new(CaptureSink::default());
+        let hooks = GovernanceWebhooks::new(sink.clone());
+        let filter = SubscriptionFilter::new(vec![PROPOSALS_TOPIC.into()], vec!["p1".into()], &[]).unwrap();
+        let id = hooks.register("https://ui.example/hook".into(), "whsec".into(), filter).unwrap();
+
+        let mut rx = feed.subscribe();
+        let mut engine = queued(&feed, "p1");
+        engine.veto_proposal("p1", 5).unwrap();
+        assert!(engine.veto_proposal("p1", 5).is_err());
+        feed.publish_block(5, engine.drain_events()).unwrap();
+        for notice in drain(&mut rx) {
+            hooks.deliver(&notice).await;
+        }
+
+        let posts = sink.0.lock();
+        // submitted, voting_opened, passed, queued, vetoed — not the vote.
+        assert_eq!(posts.len(), 5);
+        let (url, headers, body) = posts.last().unwrap();
+        assert_eq!(url, "https://ui.example/hook");
+        let notice: Notice = serde_json::from_slice(body).unwrap();
+        assert_eq!(transition(&notice), Some(Transition::Vetoed));
+        assert_eq!(notice.height, 5);
+
+        let header = |name: &str| headers.iter().find(|(n, _)| *n == name).map(|(_, v)| v.clone()).unwrap();
+        let timestamp: u64 = header(TIMESTAMP_HEADER).parse().unwrap();
+        let signature = header(SIGNATURE_HEADER);
+        assert!(verify_webhook_signature(b"whsec", timestamp, body, &signature));
+        assert!(!verify_webhook_signature(b"other", timestamp, body, &signature));
+        assert!(!verify_webhook_signature(b"whsec", timestamp + 1, body, &signature));
+        let mut tampered = body.clone();
+        tampered[0] ^= 1;
+        assert!(!verify_webhook_signature(b"whsec", timestamp, &tampered, &signature));
+
+        assert_eq!(hooks.list()[0].delivered, 5);
+        assert!(!format!("{:?}", hooks.list()).contains("whsec"));
+        hooks.remove(id).unwrap();
+        assert_eq!(hooks.remove(id), Err(FeedError::UnknownWebhook(id)));
+    }
+
+    #[test]
+    fn filters_drop_unrelated_proposals() {
+        let feed = GovernanceFeed::new(FeedConfig::default());
+        let mut rx = feed.subscribe();
+        let mut engine = GovernanceEngine::new(STAKE);
+        engine.submit_proposal(proposal("p1", ProposalType::ProtocolParameter)).unwrap();
+        engine.submit_proposal(proposal("p2", ProposalType::Recovery)).unwrap();
+        engine.advance_epoch(2).unwrap();
+        engine.cast_vote("p1", vote("validator-1", 500), 2).unwrap();
+        engine.cast_vote("p2", vote("validator-1", 500), 2).unwrap();
+        feed.publish_block(1, engine.drain_events()).unwrap();
+        let notices = drain(&mut rx);
+        assert_eq!(notices.len(), 6);
+
+        let query = |q: &[(&str, &str)]| {
+            let q: HashMap<String, String> = q.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            SubscriptionFilter::from_query(&q).unwrap()
+        };
+        let matching = |f: &SubscriptionFilter| notices.iter().filter(|n| f.matches(n)).count();
+
+        let by_id = query(&[("proposal", "p2")]);
+        assert_eq!(matching(&by_id), 3);
+        assert!(notices.iter().filter(|n| by_id.matches(n)).all(|n| n.event.proposal_id() == "p2"));
+        assert_eq!(matching(&query(&[("category", "protocol_parameter")])), 3);
+        assert_eq!(matching(&query(&[("topics", "governance.proposals"), ("category", "RECOVERY")])), 2);
+        assert_eq!(matching(&query(&[("topics", "governance.votes"), ("proposal", "p1,p2")])), 2);
+        assert_eq!(matching(&query(&[("proposal", "p3")])), 0);
+
+        let bad = HashMap::from([("topics".to_string(), "governance.chat".to_string())]);
+        assert_eq!(SubscriptionFilter::from_query(&bad), Err(FeedError::UnknownTopic("governance.chat".into())));
+        let bad = HashMap::from([("category".to_string(), "TREASURY".to_string())]);
+        assert_eq!(SubscriptionFilter::from_query(&bad), Err(FeedError::UnknownCategory("TREASURY".into())));
+    }
+
+    #[test]
+    fn reorg_reverts_unconfirmed_notices() {
+        let feed = GovernanceFeed::new(FeedConfig { confirmation_depth: 3, ..FeedConfig::default() });
+        let mut rx = feed.subscribe();
+        let mut engine = queued(&feed, "p1");
+        let before = drain(&mut rx);
+        let vote_seq = before.iter().find(|n| n.topic == VOTES_TOPIC).unwrap().seq;
+        let queued_seqs: Vec<u64> = before.iter().filter(|n| n.height == 4).map(|n| n.seq).collect();
+
+        // Block 3 (the vote) and block 4 (passed + queued) are replaced.
+        let reverts = feed.revert_to(2).unwrap();
+        assert_eq!(reverts.iter().map(|n| n.reverts.unwrap()).collect::<Vec<_>>(),
+                   [queued_seqs[1], queued_seqs[0], vote_seq]);
+        assert_eq!(drain(&mut rx), reverts);
+        assert!(reverts.iter().all(|n| n.seq > queued_seqs[1]));
+        assert_eq!(reverts[2].event, before.iter().find(|n| n.seq == vote_seq).unwrap().event);
+        assert_eq!(feed.tip(), Some(2));
+
+        // Republishing a height reverts what it replaces.
+        engine.veto_proposal("p1", 5).unwrap();
+        feed.publish_block(3, engine.drain_events()).unwrap();
+        let replaced = feed.publish_block(3, Vec::new()).unwrap();
+        assert!(replaced.is_empty());
+        let notices = drain(&mut rx);
+        assert_eq!(notices.len(), 2);
+        assert_eq!(transition(&notices[0]), Some(Transition::Vetoed));
+        assert_eq!(notices[1].reverts, Some(notices[0].seq));
+
+        // Blocks at depth 3 are final.
+        for h in 4..=7 {
+            feed.publish_block(h, Vec::new()).unwrap();
+        }
+        assert_eq!(
+            feed.revert_to(3),
+            Err(FeedError::BeyondConfirmationDepth { height: 4, final_height: 4 })
+        );
+        assert!(feed.revert_to(4).unwrap().is_empty());
+        assert_eq!(feed.tip(), Some(4));
+    }
+}
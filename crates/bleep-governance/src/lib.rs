@@ -1,6 +1,10 @@
 // PHASE 2: ON-CHAIN GOVERNANCE CORE
 pub mod governance_core;
+pub mod proposal_content;
 pub mod deterministic_executor;
+pub mod parameter_registry;
+pub mod governance_events;
+pub mod governance_feed;
 
 // PHASE 4: CONSTITUTIONAL GOVERNANCE LAYER
 pub mod constitution;
@@ -35,6 +39,27 @@ pub use governance_core::{
     Proposal, GovernancePayload, SanctionAction, GovernanceEngine, GovernanceError,
 };
 
+pub use proposal_content::{
+    ContentCommitment, ContentFetcher, HttpContentFetcher, ProposalContentResolver,
+    ResolvedContent, RetrievalHint, MAX_INLINE_DESCRIPTION,
+};
+
+pub use governance_events::{
+    GovernanceEvent, ProposalEvent, QueuedTimelock, Transition, VoteDirection, VoteEvent,
+    PROPOSALS_TOPIC, VOTES_TOPIC,
+};
+
+pub use governance_feed::{
+    verify_webhook_signature, webhook_signature, FeedConfig, FeedError, GovernanceFeed,
+    GovernanceWebhooks, HttpWebhookSink, Notice, SubscriptionFilter, TimelockDeadline, Webhook,
+    WebhookSink, SIGNATURE_HEADER, TIMESTAMP_HEADER,
+};
+
+pub use parameter_registry::{
+    ChangeSource, Mutability, ParamKind, ParameterChange, ParameterError, ParameterRegistry,
+    ParameterSpec, ParameterView,
+};
+
 pub use deterministic_executor::{
     DeterministicExecutor, ExecutionLogEntry, ExecutionStatus, ExecutionRecord, ExecutionError,
 };
@@ -0,0 +1,385 @@
+//! # Runtime parameter registry
+//!
+//! One place to answer "what are the effective parameters and when did
+//! they last change".  Every parameter is registered under a stable key
+//! with its type, default, bounds, owning subsystem and how it may change:
+//!
+//! - `Governed` — only by an executed `ProtocolParameterChange` proposal
+//! - `NodeLocal` — by the operator's configuration; reloads are recorded
+//! - `Fixed` — never after genesis
+//!
+//! A subsystem that holds a parameter itself registers a resolver, so the
+//! current value is read from it at query time; otherwise the registry's
+//! own value (the default, then each recorded change) is the effective one.
+//!
+//! Attached to a `GovernanceEngine` with `with_parameter_registry`, the
+//! registry checks parameter proposals at submission and records each
+//! executed one in the parameter's history with the proposal id.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::protocol_rules::{ProtocolRuleError, ProtocolRuleSetFactory};
+
+/// Changes kept per parameter; older ones are dropped first.
+pub const MAX_HISTORY: usize = 256;
+
+/// Owning subsystem of each genesis protocol rule.
+const GENESIS_SUBSYSTEMS: &[(&str, &str, ParamKind)] = &[
+    ("SHARD_SPLIT_THRESHOLD",      "sharding",   ParamKind::Bytes),
+    ("SHARD_MERGE_THRESHOLD",      "sharding",   ParamKind::Bytes),
+    ("VALIDATOR_ROTATION_CADENCE", "consensus",  ParamKind::Epochs),
+    ("MIN_VALIDATOR_STAKE",        "staking",    ParamKind::Amount),
+    ("CROSS_SHARD_TIMEOUT",        "interop",    ParamKind::Blocks),
+    ("SLASHING_PROPORTION",        "staking",    ParamKind::Percent),
+    ("CHECKPOINT_FREQUENCY",       "consensus",  ParamKind::Blocks),
+    ("FINALITY_THRESHOLD",         "consensus",  ParamKind::Percent),
+    ("AI_PROPOSAL_MIN_CONFIDENCE", "governance", ParamKind::Percent),
+    ("AI_REPUTATION_DECAY_RATE",   "governance", ParamKind::Percent),
+];
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ParameterError {
+    #[error("Unknown parameter: {0}")]
+    Unknown(String),
+
+    #[error("Parameter {key} = {value} outside bounds [{min}, {max}]")]
+    OutOfBounds { key: String, value: u128, min: u128, max: u128 },
+
+    #[error("Parameter {key} is {mutability} and cannot be changed this way")]
+    NotMutable { key: String, mutability: Mutability },
+
+    #[error("Parameter {0} is already registered")]
+    Duplicate(String),
+}
+
+/// What a parameter's value measures.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ParamKind {
+    Integer,
+    /// Amount in µBLEEP.
+    Amount,
+    BasisPoints,
+    Percent,
+    Blocks,
+    Epochs,
+    Bytes,
+    Milliseconds,
+}
+
+/// How a parameter may change.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Mutability {
+    Governed,
+    NodeLocal,
+    Fixed,
+}
+
+impl fmt::Display for Mutability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Mutability::Governed  => "governed",
+            Mutability::NodeLocal => "node-local",
+            Mutability::Fixed     => "fixed",
+        })
+    }
+}
+
+/// Static description of one parameter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ParameterSpec {
+    pub key:         String,
+    pub subsystem:   String,
+    pub kind:        ParamKind,
+    pub default:     u128,
+    pub min:         u128,
+    pub max:         u128,
+    pub mutability:  Mutability,
+    pub description: String,
+}
+
+impl ParameterSpec {
+    pub fn new(key: &str, subsystem: &str, kind: ParamKind, default: u128, min: u128, max: u128) -> Self {
+        ParameterSpec {
+            key:         key.to_string(),
+            subsystem:   subsystem.to_string(),
+            kind,
+            default,
+            min,
+            max,
+            mutability:  Mutability::Governed,
+            description: String::new(),
+        }
+    }
+
+    pub fn node_local(mut self) -> Self {
+        self.mutability = Mutability::NodeLocal;
+        self
+    }
+
+    pub fn fixed(mut self) -> Self {
+        self.mutability = Mutability::Fixed;
+        self
+    }
+
+    pub fn describe(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    fn check_bounds(&self, value: u128) -> Result<(), ParameterError> {
+        if value < self.min || value > self.max {
+            return Err(ParameterError::OutOfBounds { key: self.key.clone(), value, min: self.min, max: self.max });
+        }
+        Ok(())
+    }
+}
+
+/// Where a change came from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ChangeSource {
+    Proposal { proposal_id: String, epoch: u64 },
+    ConfigReload { origin: String },
+}
+
+/// One recorded change of a parameter.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ParameterChange {
+    pub key:       String,
+    pub old_value: u128,
+    pub new_value: u128,
+    pub source:    ChangeSource,
+    /// Unix milliseconds.
+    pub timestamp: u64,
+}
+
+/// A parameter with its effective value, as listed.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ParameterView {
+    #[serde(flatten)]
+    pub spec:         ParameterSpec,
+    pub value:        u128,
+    pub last_change:  Option<ParameterChange>,
+}
+
+/// Reads a parameter's live value from the subsystem holding it.
+pub type ParameterResolver = Arc<dyn Fn() -> u128 + Send + Sync>;
+
+/// All registered runtime parameters, their values and change history.
+#[derive(Default)]
+pub struct ParameterRegistry {
+    specs:     BTreeMap<String, ParameterSpec>,
+    values:    HashMap<String, u128>,
+    resolvers: HashMap<String, ParameterResolver>,
+    history:   HashMap<String, Vec<ParameterChange>>,
+}
+
+impl ParameterRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registry of the genesis protocol rules; immutable rules are `Fixed`.
+    pub fn genesis() -> Result<Self, ProtocolRuleError> {
+        let rules = ProtocolRuleSetFactory::create_genesis()?;
+        let mut registry = Self::new();
+        for &(name, subsystem, kind) in GENESIS_SUBSYSTEMS {
+            let rule = rules.get_rule(name)?;
+            let spec = ParameterSpec::new(
+                name, subsystem, kind, rule.value as u128, rule.bounds.min as u128, rule.bounds.max as u128,
+            )
+            .describe(&rule.description);
+            let spec = if rule.is_mutable { spec } else { spec.fixed() };
+            registry.register(spec).map_err(|e| ProtocolRuleError::InvalidConstraint(e.to_string()))?;
+        }
+        Ok(registry)
+    }
+
+    pub fn register(&mut self, spec: ParameterSpec) -> Result<(), ParameterError> {
+        if self.specs.contains_key(&spec.key) {
+            return Err(ParameterError::Duplicate(spec.key));
+        }
+        self.values.insert(spec.key.clone(), spec.default);
+        self.specs.insert(spec.key.clone(), spec);
+        Ok(())
+    }
+
+    /// Register `spec`, reading its current value from `resolver`.
+    pub fn register_live(
+        &mut self,
+        spec: ParameterSpec,
+        resolver: impl Fn() -> u128 + Send + Sync + 'static,
+    ) -> Result<(), ParameterError> {
+        let key = spec.key.clone();
+        self.register(spec)?;
+        self.resolvers.insert(key, Arc::new(resolver));
+        Ok(())
+    }
+
+    pub fn spec(&self, key: &str) -> Option<&ParameterSpec> {
+        self.specs.get(key)
+    }
+
+    /// Effective value of `key` right now.
+    pub fn value(&self, key: &str) -> Option<u128> {
+        match self.resolvers.get(key) {
+            Some(resolve) => Some(resolve()),
+            None => self.values.get(key).copied(),
+        }
+    }
+
+    /// Every parameter, optionally only those of `subsystem`, ordered by key.
+    pub fn list(&self, subsystem: Option<&str>) -> Vec<ParameterView> {
+        self.specs
+            .values()
+            .filter(|s| subsystem.map_or(true, |sub| s.subsystem == sub))
+            .map(|s| ParameterView {
+                spec:        s.clone(),
+                value:       self.value(&s.key).unwrap_or(s.default),
+                last_change: self.history.get(&s.key).and_then(|h| h.last()).cloned(),
+            })
+            .collect()
+    }
+
+    /// Subsystems with at least one parameter.
+    pub fn subsystems(&self) -> Vec<String> {
+        let mut subs: Vec<String> = self.specs.values().map(|s| s.subsystem.clone()).collect();
+        subs.sort();
+        subs.dedup();
+        subs
+    }
+
+    /// Changes of `key`, oldest first.
+    pub fn history(&self, key: &str) -> Result<&[ParameterChange], ParameterError> {
+        if !self.specs.contains_key(key) {
+            return Err(ParameterError::Unknown(key.to_string()));
+        }
+        Ok(self.history.get(key).map(Vec::as_slice).unwrap_or(&[]))
+    }
+
+    /// Whether a proposal may set `key` to `value`.
+    pub fn validate_proposal(&self, key: &str, value: u128) -> Result<(), ParameterError> {
+        let spec = self.specs.get(key).ok_or_else(|| ParameterError::Unknown(key.to_string()))?;
+        if spec.mutability != Mutability::Governed {
+            return Err(ParameterError::NotMutable { key: key.to_string(), mutability: spec.mutability });
+        }
+        spec.check_bounds(value)
+    }
+
+    /// Record an executed proposal setting `key` to `value`.
+    pub fn apply_proposal(&mut self, key: &str, value: u128, proposal_id: &str, epoch: u64) -> Result<(), ParameterError> {
+        self.validate_proposal(key, value)?;
+        self.record(key, value, ChangeSource::Proposal { proposal_id: proposal_id.to_string(), epoch });
+        Ok(())
+    }
+
+    /// Record node-local values loaded from `origin`; unchanged ones are
+    /// skipped.  Returns the number of changes recorded.
+    pub fn reload_config(
+        &mut self,
+        origin: &str,
+        values: impl IntoIterator<Item = (String, u128)>,
+    ) -> Result<usize, ParameterError> {
+        let mut checked = Vec::new();
+        for (key, value) in values {
+            let spec = self.specs.get(&key).ok_or_else(|| ParameterError::Unknown(key.clone()))?;
+            if spec.mutability != Mutability::NodeLocal {
+                return Err(ParameterError::NotMutable { key, mutability: spec.mutability });
+            }
+            spec.check_bounds(value)?;
+            checked.push((key, value));
+        }
+        let mut changed = 0;
+        for (key, value) in checked {
+            if self.value(&key) != Some(value) {
+                self.record(&key, value, ChangeSource::ConfigReload { origin: origin.to_string() });
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    fn record(&mut self, key: &str, value: u128, source: ChangeSource) {
+        let old_value = self.value(key).unwrap_or_default();
+        self.values.insert(key.to_string(), value);
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        log::info!("[Parameters] {} {} → {} ({:?})", key, old_value, value, source);
+        let history = self.history.entry(key.to_string()).or_default();
+        history.push(ParameterChange { key: key.to_string(), old_value, new_value: value, source, timestamp });
+        if history.len() > MAX_HISTORY {
+            history.remove(0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[test]
+    fn lists_parameters_across_subsystems_with_live_values() {
+        let mut registry = ParameterRegistry::genesis().unwrap();
+        let base_fee = Arc::new(AtomicU64::new(1_000));
+        let live = Arc::clone(&base_fee);
+        registry.register_live(
+            ParameterSpec::new("MIN_BASE_FEE", "fees", ParamKind::Amount, 100, 1, 1_000_000).node_local(),
+            move || live.load(Ordering::Relaxed) as u128,
+        ).unwrap();
+        base_fee.store(2_500, Ordering::Relaxed);
+
+        let subsystems = registry.subsystems();
+        for sub in ["consensus", "fees", "interop", "sharding", "staking"] {
+            assert!(subsystems.iter().any(|s| s == sub), "{} missing", sub);
+        }
+        let fees = registry.list(Some("fees"));
+        assert_eq!(fees.len(), 1);
+        assert_eq!(fees[0].value, 2_500, "resolved at query time");
+
+        let consensus = registry.list(Some("consensus"));
+        let finality = consensus.iter().find(|p| p.spec.key == "FINALITY_THRESHOLD").unwrap();
+        assert_eq!((finality.value, finality.spec.mutability), (67, Mutability::Fixed));
+    }
+
+    #[test]
+    fn proposals_are_bounds_checked_and_recorded() {
+        let mut registry = ParameterRegistry::genesis().unwrap();
+        assert_eq!(
+            registry.validate_proposal("CHECKPOINT_FREQUENCY", 5_000),
+            Err(ParameterError::OutOfBounds { key: "CHECKPOINT_FREQUENCY".into(), value: 5_000, min: 10, max: 1_000 }),
+        );
+        assert!(matches!(registry.validate_proposal("FINALITY_THRESHOLD", 75), Err(ParameterError::NotMutable { .. })));
+        assert!(matches!(registry.validate_proposal("NO_SUCH_RULE", 1), Err(ParameterError::Unknown(_))));
+
+        registry.apply_proposal("CHECKPOINT_FREQUENCY", 200, "prop-7", 12).unwrap();
+        assert_eq!(registry.value("CHECKPOINT_FREQUENCY"), Some(200));
+        let history = registry.history("CHECKPOINT_FREQUENCY").unwrap();
+        assert_eq!((history[0].old_value, history[0].new_value), (100, 200));
+        assert_eq!(history[0].source, ChangeSource::Proposal { proposal_id: "prop-7".into(), epoch: 12 });
+    }
+
+    #[test]
+    fn config_reload_records_only_node_local_changes() {
+        let mut registry = ParameterRegistry::new();
+        registry.register(ParameterSpec::new("MEMPOOL_CAPACITY", "mempool", ParamKind::Integer, 10_000, 1, 1_000_000).node_local()).unwrap();
+        registry.register(ParameterSpec::new("SLOTS", "consensus", ParamKind::Integer, 4, 1, 10)).unwrap();
+
+        assert_eq!(registry.reload_config("env", [("MEMPOOL_CAPACITY".to_string(), 10_000)]), Ok(0));
+        assert_eq!(registry.reload_config("env", [("MEMPOOL_CAPACITY".to_string(), 20_000)]), Ok(1));
+        assert!(registry.reload_config("env", [("SLOTS".to_string(), 5)]).is_err());
+        assert_eq!(
+            registry.history("MEMPOOL_CAPACITY").unwrap()[0].source,
+            ChangeSource::ConfigReload { origin: "env".into() },
+        );
+    }
+}
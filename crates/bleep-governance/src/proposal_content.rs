@@ -0,0 +1,403 @@
+// PROPOSAL CONTENT ANCHORING
+// Long proposal texts live off-chain; the chain stores a commitment.
+//
+// A proposal carries a short inline description and, optionally, a
+// `ContentCommitment`: the SHA3-256 hash, size and MIME type of the full
+// text, plus hints on where to fetch it (IPFS CID or HTTPS URL).
+//
+// INVARIANTS:
+// 1. Only hash, size and MIME type are committed (`ContentCommitment::digest`)
+// 2. Retrieval hints can be replaced without changing the commitment
+// 3. Fetched bytes are shown as proposal text only if they match the hash
+// 4. Only verified content is cached
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+/// Largest description accepted inline; longer texts need a commitment.
+pub const MAX_INLINE_DESCRIPTION: usize = 4 * 1024;
+
+/// Largest committed document the resolver will download.
+pub const MAX_COMMITTED_CONTENT: u64 = 8 * 1024 * 1024;
+
+pub const MAX_RETRIEVAL_HINTS: usize = 8;
+pub const MAX_HINT_LEN: usize = 512;
+pub const MAX_MIME_LEN: usize = 128;
+
+/// Verified documents kept by a resolver.
+pub const MAX_CACHED_CONTENT: usize = 256;
+
+/// Public gateway used for `ipfs://` hints unless configured otherwise.
+pub const DEFAULT_IPFS_GATEWAY: &str = "https://ipfs.io";
+
+const DIGEST_DOMAIN: &[u8] = b"bleep-proposal-content-v1";
+
+/// SHA3-256 of `content`.
+pub fn content_hash(content: &[u8]) -> [u8; 32] {
+    Sha3_256::digest(content).into()
+}
+
+/// Where a committed document can be fetched from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "location", rename_all = "lowercase")]
+pub enum RetrievalHint {
+    /// IPFS content identifier, fetched through a gateway
+    Ipfs(String),
+    /// Direct HTTPS URL
+    Https(String),
+}
+
+impl RetrievalHint {
+    /// Parse `ipfs://<cid>` or `https://…`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let hint = if let Some(cid) = s.strip_prefix("ipfs://") {
+            RetrievalHint::Ipfs(cid.to_string())
+        } else if s.starts_with("https://") {
+            RetrievalHint::Https(s.to_string())
+        } else {
+            return Err(format!("Unsupported retrieval hint '{}': use ipfs://<cid> or https://", s));
+        };
+        hint.check()?;
+        Ok(hint)
+    }
+
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            RetrievalHint::Ipfs(cid) => {
+                if cid.is_empty() || !cid.chars().all(|c| c.is_ascii_alphanumeric()) {
+                    return Err(format!("Invalid IPFS CID '{}'", cid));
+                }
+                if cid.len() > MAX_HINT_LEN {
+                    return Err("IPFS CID too long".to_string());
+                }
+            }
+            RetrievalHint::Https(url) => {
+                if !url.starts_with("https://") || url.len() <= "https://".len() {
+                    return Err(format!("Invalid HTTPS URL '{}'", url));
+                }
+                if url.len() > MAX_HINT_LEN || url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+                    return Err("HTTPS URL too long or contains whitespace".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RetrievalHint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RetrievalHint::Ipfs(cid) => write!(f, "ipfs://{}", cid),
+            RetrievalHint::Https(url) => f.write_str(url),
+        }
+    }
+}
+
+/// On-chain commitment to an off-chain proposal document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContentCommitment {
+    /// SHA3-256 of the document
+    pub hash: [u8; 32],
+
+    /// Document length in bytes
+    pub size: u64,
+
+    /// MIME type, e.g. `text/markdown`
+    pub mime: String,
+
+    /// Where to fetch the document (not committed)
+    #[serde(default)]
+    pub hints: Vec<RetrievalHint>,
+}
+
+impl ContentCommitment {
+    /// Commit to `content` with no retrieval hints.
+    pub fn for_content(content: &[u8], mime: &str) -> Self {
+        ContentCommitment {
+            hash: content_hash(content),
+            size: content.len() as u64,
+            mime: mime.to_string(),
+            hints: Vec::new(),
+        }
+    }
+
+    pub fn with_hint(mut self, hint: RetrievalHint) -> Self {
+        self.hints.push(hint);
+        self
+    }
+
+    pub fn hash_hex(&self) -> String {
+        hex::encode(self.hash)
+    }
+
+    /// Digest of the committed fields; retrieval hints are excluded.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha3_256::new();
+        hasher.update(DIGEST_DOMAIN);
+        hasher.update(self.hash);
+        hasher.update(self.size.to_le_bytes());
+        hasher.update(self.mime.as_bytes());
+        hasher.finalize().into()
+    }
+
+    /// True if `content` is exactly the committed document.
+    pub fn matches(&self, content: &[u8]) -> bool {
+        content.len() as u64 == self.size && content_hash(content) == self.hash
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.size == 0 {
+            return Err("Committed content is empty".to_string());
+        }
+        if self.size > MAX_COMMITTED_CONTENT {
+            return Err(format!(
+                "Committed content is {} bytes (max {})", self.size, MAX_COMMITTED_CONTENT
+            ));
+        }
+        validate_mime(&self.mime)?;
+        validate_hints(&self.hints)
+    }
+}
+
+fn validate_mime(mime: &str) -> Result<(), String> {
+    let valid = mime.len() <= MAX_MIME_LEN
+        && mime.split_once('/').map_or(false, |(t, s)| !t.is_empty() && !s.is_empty())
+        && mime.chars().all(|c| c.is_ascii_graphic() || c == ' ');
+    if valid { Ok(()) } else { Err(format!("Invalid MIME type '{}'", mime)) }
+}
+
+pub fn validate_hints(hints: &[RetrievalHint]) -> Result<(), String> {
+    if hints.len() > MAX_RETRIEVAL_HINTS {
+        return Err(format!("Too many retrieval hints ({}, max {})", hints.len(), MAX_RETRIEVAL_HINTS));
+    }
+    hints.iter().try_for_each(RetrievalHint::check)
+}
+
+/// Downloads a document named by a retrieval hint.
+#[async_trait]
+pub trait ContentFetcher: Send + Sync {
+    /// Fetch at most `max_len` bytes (anything longer is an error).
+    async fn fetch(&self, hint: &RetrievalHint, max_len: u64) -> Result<Vec<u8>, String>;
+}
+
+/// Fetches over HTTPS; IPFS hints go through a gateway.
+pub struct HttpContentFetcher {
+    client: reqwest::Client,
+    ipfs_gateway: String,
+}
+
+impl HttpContentFetcher {
+    pub fn new(ipfs_gateway: &str) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(15))
+            .build()
+            .unwrap_or_default();
+        HttpContentFetcher {
+            client,
+            ipfs_gateway: ipfs_gateway.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl Default for HttpContentFetcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_IPFS_GATEWAY)
+    }
+}
+
+#[async_trait]
+impl ContentFetcher for HttpContentFetcher {
+    async fn fetch(&self, hint: &RetrievalHint, max_len: u64) -> Result<Vec<u8>, String> {
+        let url = match hint {
+            RetrievalHint::Ipfs(cid) => format!("{}/ipfs/{}", self.ipfs_gateway, cid),
+            RetrievalHint::Https(url) => url.clone(),
+        };
+        let mut resp = self.client.get(&url).send().await.map_err(|e| e.to_string())?;
+        if !resp.status().is_success() {
+            return Err(format!("HTTP {}", resp.status()));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+            body.extend_from_slice(&chunk);
+            if body.len() as u64 > max_len {
+                return Err(format!("Response exceeds {} bytes", max_len));
+            }
+        }
+        Ok(body)
+    }
+}
+
+/// Outcome of resolving a committed document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum ResolvedContent {
+    /// Fetched bytes match the commitment
+    Verified { text: String, source: String },
+    /// A source served bytes that do not match the commitment
+    Tampered { source: String, actual_hash: String, actual_size: u64 },
+    /// No source could be reached
+    Unavailable { errors: Vec<String> },
+}
+
+impl ResolvedContent {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, ResolvedContent::Verified { .. })
+    }
+}
+
+struct ContentCache {
+    entries: HashMap<[u8; 32], (String, String)>,
+    order: VecDeque<[u8; 32]>,
+}
+
+/// Fetches committed proposal content on demand and verifies it.
+pub struct ProposalContentResolver {
+    fetcher: Box<dyn ContentFetcher>,
+    cache: Mutex<ContentCache>,
+}
+
+impl ProposalContentResolver {
+    pub fn new(fetcher: Box<dyn ContentFetcher>) -> Self {
+        ProposalContentResolver {
+            fetcher,
+            cache: Mutex::new(ContentCache { entries: HashMap::new(), order: VecDeque::new() }),
+        }
+    }
+
+    /// Try each hint in order until one serves the committed bytes.
+    pub async fn resolve(&self, commitment: &ContentCommitment) -> ResolvedContent {
+        if let Some((text, source)) = self.cache.lock().entries.get(&commitment.hash).cloned() {
+            return ResolvedContent::Verified { text, source };
+        }
+        if commitment.hints.is_empty() {
+            return ResolvedContent::Unavailable { errors: vec!["No retrieval hints".to_string()] };
+        }
+
+        let mut errors = Vec::new();
+        let mut tampered = None;
+        for hint in &commitment.hints {
+            let source = hint.to_string();
+            let bytes = match self.fetcher.fetch(hint, commitment.size).await {
+                Ok(b) => b,
+                Err(e) => {
+                    errors.push(format!("{}: {}", source, e));
+                    continue;
+                }
+            };
+            if !commitment.matches(&bytes) {
+                log::warn!("Proposal content from {} does not match commitment {}", source, commitment.hash_hex());
+                tampered.get_or_insert(ResolvedContent::Tampered {
+                    source,
+                    actual_hash: hex::encode(content_hash(&bytes)),
+                    actual_size: bytes.len() as u64,
+                });
+                continue;
+            }
+            let text = String::from_utf8_lossy(&bytes).into_owned();
+            self.insert(commitment.hash, text.clone(), source.clone());
+            return ResolvedContent::Verified { text, source };
+        }
+        tampered.unwrap_or(ResolvedContent::Unavailable { errors })
+    }
+
+    fn insert(&self, hash: [u8; 32], text: String, source: String) {
+        let mut cache = self.cache.lock();
+        if cache.entries.insert(hash, (text, source)).is_none() {
+            cache.order.push_back(hash);
+        }
+        while cache.order.len() > MAX_CACHED_CONTENT {
+            if let Some(old) = cache.order.pop_front() {
+                cache.entries.remove(&old);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serves fixed bytes per hint and counts fetches.
+    struct MapFetcher {
+        docs: HashMap<String, Vec<u8>>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ContentFetcher for MapFetcher {
+        async fn fetch(&self, hint: &RetrievalHint, _max_len: u64) -> Result<Vec<u8>, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            self.docs.get(&hint.to_string()).cloned().ok_or_else(|| "404".to_string())
+        }
+    }
+
+    fn fetcher(docs: &[(&str, &[u8])]) -> MapFetcher {
+        MapFetcher {
+            docs: docs.iter().map(|(k, v)| (k.to_string(), v.to_vec())).collect(),
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    const DOC: &[u8] = b"# Raise block gas limit\n\nFull rationale follows...";
+
+    #[tokio::test]
+    async fn commitment_round_trip_verifies_and_caches() {
+        let commitment = ContentCommitment::for_content(DOC, "text/markdown")
+            .with_hint(RetrievalHint::parse("https://dead.example/doc.md").unwrap())
+            .with_hint(RetrievalHint::parse("ipfs://bafyexample").unwrap());
+        commitment.validate().unwrap();
+        let json = serde_json::to_string(&commitment).unwrap();
+        let commitment: ContentCommitment = serde_json::from_str(&json).unwrap();
+
+        let fetcher = fetcher(&[("ipfs://bafyexample", DOC)]);
+        let calls = Arc::clone(&fetcher.calls);
+        let resolver = ProposalContentResolver::new(Box::new(fetcher));
+        let resolved = resolver.resolve(&commitment).await;
+        assert_eq!(resolved, ResolvedContent::Verified {
+            text: String::from_utf8(DOC.to_vec()).unwrap(),
+            source: "ipfs://bafyexample".into(),
+        });
+        // Second lookup is served from the cache.
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(resolver.resolve(&commitment).await.is_verified());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn tampered_and_unreachable_content_is_flagged() {
+        let commitment = ContentCommitment::for_content(DOC, "text/markdown")
+            .with_hint(RetrievalHint::Https("https://mirror.example/doc.md".into()));
+
+        let resolver = ProposalContentResolver::new(Box::new(fetcher(&[
+            ("https://mirror.example/doc.md", b"# Lower block gas limit"),
+        ])));
+        match resolver.resolve(&commitment).await {
+            ResolvedContent::Tampered { source, actual_size, .. } => {
+                assert_eq!(source, "https://mirror.example/doc.md");
+                assert_eq!(actual_size, 23);
+            }
+            other => panic!("expected tampered, got {:?}", other),
+        }
+
+        let resolver = ProposalContentResolver::new(Box::new(fetcher(&[])));
+        assert!(matches!(resolver.resolve(&commitment).await, ResolvedContent::Unavailable { .. }));
+    }
+
+    #[test]
+    fn hints_are_not_committed() {
+        let base = ContentCommitment::for_content(DOC, "text/markdown");
+        let moved = base.clone().with_hint(RetrievalHint::Ipfs("bafyexample".into()));
+        assert_eq!(base.digest(), moved.digest());
+
+        let other = ContentCommitment::for_content(DOC, "text/plain");
+        assert_ne!(base.digest(), other.digest());
+        assert!(RetrievalHint::parse("http://insecure.example").is_err());
+    }
+}
@@ -5,6 +5,12 @@
 //! - `encode_transfer`: Serialize an intent into chain-specific calldata
 //! - `verify_execution`: Validate an execution proof against the target chain's rules
 //! - `get_finality_blocks`: Return the number of confirmations needed for finality
+//!
+//! Destination addresses and amounts are checked before submission by
+//! `AdapterRegistry::validate_request`; see `validation`.
+//!
+//! Any chain can instead be served by a `SandboxAdapter` with an in-memory
+//! ledger (`AdapterMode::Sandbox`); see `sandbox`.
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -15,10 +21,15 @@ use sha3::{Keccak256, Digest};
 use tracing::debug;
 
 use bleep_connect_types::{
-    InstantIntent, ChainId, BleepConnectError, BleepConnectResult,
+    AssetType, InstantIntent, ChainId, BleepConnectError, BleepConnectResult,
 };
 use bleep_connect_crypto::sha256;
 
+pub mod sandbox;
+pub mod validation;
+pub use sandbox::{AdapterMode, ChainModeConfig, SandboxAdapter, SandboxConfig, SandboxState, SandboxTx, SandboxTxStatus};
+pub use validation::{convert_amount, Rounding, Severity, ValidationIssue, BLEEP_DECIMALS};
+
 // ─────────────────────────────────────────────────────────────────────────────
 // CHAIN ADAPTER TRAIT
 // ─────────────────────────────────────────────────────────────────────────────
@@ -40,6 +51,27 @@ pub trait ChainAdapter: Send + Sync {
 
     /// Return the chain's native token decimals.
     fn native_decimals(&self) -> u8;
+
+    /// Smallest transfer, in native base units, the destination will accept.
+    fn dust_threshold(&self) -> u128 { 1 }
+
+    /// Check `address` as a recipient on this chain; `None` means valid.
+    fn validate_address(&self, address: &str) -> Option<ValidationIssue> {
+        validation::validate_address(self.chain_id(), address)
+    }
+
+    /// Broadcast `calldata` paying `amount` to `to`; returns the tx hash.
+    /// Live adapters do not broadcast yet: the hash is derived from the
+    /// calldata, as executors have always simulated it.
+    fn submit_transfer(&self, _to: &str, _amount: u128, calldata: &[u8]) -> BleepConnectResult<String> {
+        Ok(format!("0x{}", hex::encode(sha256(calldata))))
+    }
+
+    /// Where `tx_hash` stands on this chain.  Live adapters report every
+    /// transaction final until receipts are fetched over RPC.
+    fn tx_status(&self, tx_hash: &str) -> RelayStatus {
+        RelayStatus::Finalized { block_number: 0, tx_hash: tx_hash.to_string() }
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -207,6 +239,8 @@ impl ChainAdapter for BitcoinAdapter {
     fn get_finality_blocks(&self) -> u64 { 6 }
     fn chain_id(&self) -> ChainId { ChainId::Bitcoin }
     fn native_decimals(&self) -> u8 { 8 }
+    /// Bitcoin Core's relay dust limit for a P2PKH output.
+    fn dust_threshold(&self) -> u128 { 546 }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -267,6 +301,8 @@ impl ChainAdapter for SolanaAdapter {
     fn get_finality_blocks(&self) -> u64 { 32 }
     fn chain_id(&self) -> ChainId { ChainId::Solana }
     fn native_decimals(&self) -> u8 { 9 }
+    /// Rent-exempt minimum of a system account; anything less is never credited.
+    fn dust_threshold(&self) -> u128 { 890_880 }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -522,11 +558,13 @@ impl Default for SepoliaRelay {
 
 pub struct AdapterRegistry {
     adapters: HashMap<u32, Arc<dyn ChainAdapter>>,
+    /// Chains served by a `SandboxAdapter`, also present in `adapters`.
+    sandboxes: HashMap<u32, Arc<SandboxAdapter>>,
 }
 
 impl AdapterRegistry {
     pub fn new() -> Self {
-        let mut reg = Self { adapters: HashMap::new() };
+        let mut reg = Self { adapters: HashMap::new(), sandboxes: HashMap::new() };
 
         // Register all built-in adapters
         reg.register(Arc::new(EthereumAdapter::new(ChainId::Ethereum)));
@@ -544,8 +582,54 @@ impl AdapterRegistry {
         reg
     }
 
+    /// The built-in adapters, with each chain named in `modes` (by
+    /// canonical name) served in its configured mode.
+    pub fn with_modes(modes: &HashMap<String, ChainModeConfig>) -> BleepConnectResult<Self> {
+        let mut reg = Self::new();
+        for (name, mode) in modes {
+            let chain = ChainId::from_name(name)
+                .ok_or_else(|| BleepConnectError::InvalidChainId(name.clone()))?;
+            if mode.mode == AdapterMode::Sandbox {
+                reg.sandbox_chain(chain, mode.sandbox.clone())?;
+            }
+        }
+        Ok(reg)
+    }
+
+    /// Every built-in chain served by a sandbox with `config`.
+    pub fn sandboxed(config: SandboxConfig) -> Self {
+        let mut reg = Self::new();
+        for chain in reg.chain_ids() {
+            // Every chain_ids() entry has a live adapter to wrap.
+            let _ = reg.sandbox_chain(chain, config.clone());
+        }
+        reg
+    }
+
     pub fn register(&mut self, adapter: Arc<dyn ChainAdapter>) {
-        self.adapters.insert(adapter.chain_id().to_u32(), adapter);
+        let key = adapter.chain_id().to_u32();
+        self.sandboxes.remove(&key);
+        self.adapters.insert(key, adapter);
+    }
+
+    /// Replace `chain`'s live adapter with a sandbox wrapping it.
+    pub fn sandbox_chain(&mut self, chain: ChainId, config: SandboxConfig) -> BleepConnectResult<Arc<SandboxAdapter>> {
+        let key = chain.to_u32();
+        if let Some(existing) = self.sandboxes.get(&key) {
+            return Ok(Arc::clone(existing));
+        }
+        let live = self.get(chain).ok_or_else(|| {
+            BleepConnectError::InvalidChainId(format!("no adapter to sandbox for {}", chain.canonical_name()))
+        })?;
+        let sandbox = Arc::new(SandboxAdapter::new(live, config));
+        self.adapters.insert(key, sandbox.clone());
+        self.sandboxes.insert(key, Arc::clone(&sandbox));
+        Ok(sandbox)
+    }
+
+    /// The sandbox serving `chain`, if it is in sandbox mode.
+    pub fn sandbox(&self, chain: ChainId) -> Option<Arc<SandboxAdapter>> {
+        self.sandboxes.get(&chain.to_u32()).cloned()
     }
 
     pub fn get(&self, chain: ChainId) -> Option<Arc<dyn ChainAdapter>> {
@@ -555,6 +639,42 @@ impl AdapterRegistry {
     pub fn supported_chains(&self) -> Vec<u32> {
         self.adapters.keys().copied().collect()
     }
+
+    pub fn chain_ids(&self) -> Vec<ChainId> {
+        self.adapters.values().map(|a| a.chain_id()).collect()
+    }
+
+    /// Pre-flight checks for `intent`: the recipient address against the
+    /// destination chain and, for native transfers, the amount converted to
+    /// destination decimals with `Rounding::Exact` and its dust threshold.
+    /// Issues of `Severity::Error` must block submission.
+    pub fn validate_request(&self, intent: &InstantIntent) -> Vec<ValidationIssue> {
+        let Some(dest) = self.get(intent.dest_chain) else {
+            return vec![ValidationIssue::UnsupportedChain {
+                chain: intent.dest_chain.canonical_name().to_string(),
+            }];
+        };
+        let mut issues: Vec<ValidationIssue> =
+            dest.validate_address(&intent.recipient.address).into_iter().collect();
+
+        let native = intent.source_asset.asset_type == AssetType::Native
+            && intent.dest_asset.asset_type == AssetType::Native;
+        // Token decimals are not known here; only a zero amount can be ruled out.
+        let (from, to, threshold) = if native {
+            let from = self.get(intent.source_chain).map_or(BLEEP_DECIMALS, |a| a.native_decimals());
+            (from, dest.native_decimals(), dest.dust_threshold())
+        } else {
+            (0, 0, 1)
+        };
+        match convert_amount(intent.source_amount, from, to, Rounding::Exact) {
+            Ok(amount) if amount < threshold => {
+                issues.push(ValidationIssue::BelowDust { amount, threshold });
+            }
+            Ok(_) => {}
+            Err(issue) => issues.push(issue),
+        }
+        issues
+    }
 }
 
 impl Default for AdapterRegistry {
@@ -678,4 +798,65 @@ mod tests {
             assert!(registry.get(chain).is_some(), "Missing adapter for {:?}", chain);
         }
     }
+
+    #[test]
+    fn test_registry_modes() {
+        let modes: HashMap<String, ChainModeConfig> = serde_json::from_str(
+            r#"{ "ethereum": { "mode": "sandbox", "sandbox": { "confirmation_depth": 5 } },
+                 "solana":   { "mode": "live" } }"#,
+        ).unwrap();
+        let registry = AdapterRegistry::with_modes(&modes).unwrap();
+        let sandbox = registry.sandbox(ChainId::Ethereum).unwrap();
+        assert_eq!(sandbox.config().block_time_ms, SandboxConfig::default().block_time_ms);
+        assert_eq!(registry.get(ChainId::Ethereum).unwrap().get_finality_blocks(), 5);
+        assert!(registry.sandbox(ChainId::Solana).is_none());
+
+        let modes = HashMap::from([("mars".to_string(), ChainModeConfig::default())]);
+        assert!(matches!(AdapterRegistry::with_modes(&modes), Err(BleepConnectError::InvalidChainId(_))));
+
+        let all = AdapterRegistry::sandboxed(SandboxConfig::default());
+        assert!(all.chain_ids().into_iter().all(|chain| all.sandbox(chain).is_some()));
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let registry = AdapterRegistry::new();
+        let mut intent = make_intent(ChainId::Ethereum);
+        intent.recipient.address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed".into();
+        assert!(registry.validate_request(&intent).is_empty());
+
+        intent.recipient.address = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed".into();
+        let issues = registry.validate_request(&intent);
+        assert_eq!(issues, vec![ValidationIssue::MissingChecksum]);
+        assert!(!validation::has_errors(&issues));
+
+        let mut intent = make_intent(ChainId::Solana);
+        intent.recipient.address = "1111111111111111111111111111111".into();
+        assert_eq!(
+            registry.validate_request(&intent),
+            vec![ValidationIssue::BadLength { expected: 32, found: 31 }],
+        );
+
+        // 8 BLEEP decimals into Cosmos' 6: 1.23456789 has no exact uatom amount.
+        let mut intent = make_intent(ChainId::Cosmos);
+        intent.recipient.address = "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu".into();
+        intent.source_amount = 123_456_789;
+        assert_eq!(
+            registry.validate_request(&intent),
+            vec![ValidationIssue::PrecisionLoss { amount: 123_456_789, from_decimals: 8, to_decimals: 6 }],
+        );
+
+        let mut intent = make_intent(ChainId::Bitcoin);
+        intent.source_amount = 500;
+        assert_eq!(
+            registry.validate_request(&intent),
+            vec![ValidationIssue::BelowDust { amount: 500, threshold: 546 }],
+        );
+
+        let intent = make_intent(ChainId::Near);
+        assert!(matches!(
+            registry.validate_request(&intent)[..],
+            [ValidationIssue::UnsupportedChain { .. }]
+        ));
+    }
 }
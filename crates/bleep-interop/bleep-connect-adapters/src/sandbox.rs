@@ -0,0 +1,421 @@
+//! # Sandbox chains
+//!
+//! A `SandboxAdapter` stands in for a destination chain with an in-memory
+//! ledger, so the transfer pipeline can run end to end without RPC
+//! endpoints or funded accounts.  Encoding, address rules and decimals come
+//! from the live adapter it wraps; broadcasting and receipts go to the
+//! ledger:
+//!
+//! ```text
+//! submit_transfer ─▶ mempool ──(latency_ms)──▶ block h ─▶ Confirming ─▶ Finalized
+//!                                               │  revert_every: Reverted
+//!                                               └─ reorg_every: dropped at h+1,
+//!                                                  re-included at h+2
+//! ```
+//!
+//! Blocks follow the clock every `block_time_ms`; with `block_time_ms = 0`
+//! each `tx_status` query mines one block, so a poller observes every
+//! block — the deterministic setting for tests.  A reorg lands one block
+//! after inclusion, so it is only seen before finality when
+//! `confirmation_depth > 1`.
+//!
+//! The ledger mints what it delivers: balances are credits received, not a
+//! full account model.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use bleep_connect_crypto::sha256;
+use bleep_connect_types::{BleepConnectError, BleepConnectResult, ChainId, InstantIntent};
+
+use crate::{ChainAdapter, RelayStatus, ValidationIssue};
+
+/// How the registry serves a chain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdapterMode {
+    #[default]
+    Live,
+    Sandbox,
+}
+
+/// One chain's entry in `BleepConnectConfig::chains`:
+///
+/// ```json
+/// { "ethereum": { "mode": "sandbox", "sandbox": { "confirmation_depth": 3, "revert_every": 5 } } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChainModeConfig {
+    pub mode:    AdapterMode,
+    /// Ignored in `Live` mode.
+    pub sandbox: SandboxConfig,
+}
+
+impl ChainModeConfig {
+    pub fn sandbox(sandbox: SandboxConfig) -> Self {
+        Self { mode: AdapterMode::Sandbox, sandbox }
+    }
+}
+
+/// Behaviour of a simulated chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Milliseconds between blocks; 0 mines one block per `tx_status` query.
+    pub block_time_ms:      u64,
+    /// Delay before a submitted transaction can be included.
+    pub latency_ms:         u64,
+    /// Confirmations before a transaction is final.
+    pub confirmation_depth: u64,
+    /// Revert every Nth submitted transaction; 0 never.
+    pub revert_every:       u64,
+    /// Reorg every Nth submitted transaction out of its block once it has
+    /// one confirmation; 0 never.
+    pub reorg_every:        u64,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            block_time_ms:      1_000,
+            latency_ms:         0,
+            confirmation_depth: 3,
+            revert_every:       0,
+            reorg_every:        0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum SandboxTxStatus {
+    Pending,
+    Included { block: u64 },
+    Reverted { block: u64 },
+}
+
+/// A transaction as `SandboxState` reports it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxTx {
+    pub hash:    String,
+    pub to:      String,
+    pub amount:  u128,
+    pub status:  SandboxTxStatus,
+    /// Whether a reorg has dropped it from a block.
+    pub reorged: bool,
+}
+
+/// Snapshot of a sandbox chain, served by `GET /rpc/interop/sandbox/:chain/state`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxState {
+    pub chain:        String,
+    pub height:       u64,
+    pub config:       SandboxConfig,
+    /// Credits received per address.
+    pub balances:     BTreeMap<String, u128>,
+    /// Oldest first.
+    pub transactions: Vec<SandboxTx>,
+    pub reverted:     u64,
+    pub reorgs:       u64,
+}
+
+struct LedgerTx {
+    tx:         SandboxTx,
+    calldata:   Vec<u8>,
+    ready_at:   Instant,
+    /// Not included before this height; set when a reorg drops it.
+    not_before: u64,
+    revert:     bool,
+    reorg:      bool,
+}
+
+struct Ledger {
+    height:       u64,
+    /// Blocks mined by the clock, as opposed to `mine`.
+    clock_blocks: u64,
+    balances:     BTreeMap<String, u128>,
+    txs:          Vec<LedgerTx>,
+    index:        HashMap<String, usize>,
+    reverted:     u64,
+    reorgs:       u64,
+}
+
+impl Ledger {
+    fn credit(&mut self, to: &str, amount: u128) {
+        *self.balances.entry(to.to_string()).or_insert(0) += amount;
+    }
+
+    fn debit(&mut self, to: &str, amount: u128) {
+        if let Some(balance) = self.balances.get_mut(to) {
+            *balance = balance.saturating_sub(amount);
+        }
+    }
+
+    fn mine_block(&mut self, now: Instant) {
+        self.height += 1;
+        let height = self.height;
+
+        // Reorgs first, so a dropped transaction skips this block.
+        for i in 0..self.txs.len() {
+            let t = &self.txs[i];
+            if !(t.reorg && t.tx.status == SandboxTxStatus::Included { block: height - 1 }) {
+                continue;
+            }
+            let (to, amount) = (t.tx.to.clone(), t.tx.amount);
+            self.debit(&to, amount);
+            let t = &mut self.txs[i];
+            t.reorg = false;
+            t.not_before = height + 1;
+            t.tx.reorged = true;
+            t.tx.status = SandboxTxStatus::Pending;
+            self.reorgs += 1;
+            debug!("sandbox reorg at {}: {} dropped", height, t.tx.hash);
+        }
+
+        for i in 0..self.txs.len() {
+            let t = &self.txs[i];
+            if t.tx.status != SandboxTxStatus::Pending || t.ready_at > now || t.not_before > height {
+                continue;
+            }
+            if t.revert {
+                self.reverted += 1;
+                self.txs[i].tx.status = SandboxTxStatus::Reverted { block: height };
+            } else {
+                let (to, amount) = (t.tx.to.clone(), t.tx.amount);
+                self.credit(&to, amount);
+                self.txs[i].tx.status = SandboxTxStatus::Included { block: height };
+            }
+        }
+    }
+}
+
+/// A chain simulated in memory; see the module docs.
+pub struct SandboxAdapter {
+    live:    Arc<dyn ChainAdapter>,
+    config:  SandboxConfig,
+    started: Instant,
+    ledger:  Mutex<Ledger>,
+}
+
+impl SandboxAdapter {
+    /// Simulate the chain `live` serves.
+    pub fn new(live: Arc<dyn ChainAdapter>, config: SandboxConfig) -> Self {
+        Self {
+            live,
+            config,
+            started: Instant::now(),
+            ledger: Mutex::new(Ledger {
+                height:       0,
+                clock_blocks: 0,
+                balances:     BTreeMap::new(),
+                txs:          Vec::new(),
+                index:        HashMap::new(),
+                reverted:     0,
+                reorgs:       0,
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &SandboxConfig {
+        &self.config
+    }
+
+    /// Mine `blocks` blocks now, on top of any the clock produces.
+    pub fn mine(&self, blocks: u64) {
+        let mut ledger = self.ledger();
+        let now = Instant::now();
+        for _ in 0..blocks {
+            ledger.mine_block(now);
+        }
+    }
+
+    pub fn height(&self) -> u64 {
+        let mut ledger = self.ledger();
+        self.catch_up(&mut ledger);
+        ledger.height
+    }
+
+    pub fn balance(&self, address: &str) -> u128 {
+        self.ledger().balances.get(address).copied().unwrap_or(0)
+    }
+
+    pub fn state(&self) -> SandboxState {
+        let mut ledger = self.ledger();
+        self.catch_up(&mut ledger);
+        SandboxState {
+            chain:        self.live.chain_id().canonical_name().to_string(),
+            height:       ledger.height,
+            config:       self.config.clone(),
+            balances:     ledger.balances.clone(),
+            transactions: ledger.txs.iter().map(|t| t.tx.clone()).collect(),
+            reverted:     ledger.reverted,
+            reorgs:       ledger.reorgs,
+        }
+    }
+
+    fn ledger(&self) -> MutexGuard<'_, Ledger> {
+        self.ledger.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Mine the blocks the clock owes since the last call.
+    fn catch_up(&self, ledger: &mut Ledger) {
+        if self.config.block_time_ms == 0 {
+            return;
+        }
+        let due = self.started.elapsed().as_millis() as u64 / self.config.block_time_ms;
+        let now = Instant::now();
+        while ledger.clock_blocks < due {
+            ledger.clock_blocks += 1;
+            ledger.mine_block(now);
+        }
+    }
+
+    fn is_nth(seq: u64, every: u64) -> bool {
+        every > 0 && seq % every == 0
+    }
+}
+
+impl ChainAdapter for SandboxAdapter {
+    fn encode_transfer(&self, intent: &InstantIntent) -> BleepConnectResult<Vec<u8>> {
+        self.live.encode_transfer(intent)
+    }
+
+    /// The live adapter's check, plus: a transaction carrying this proof
+    /// is in a block.
+    fn verify_execution(&self, intent: &InstantIntent, execution_proof: &[u8]) -> BleepConnectResult<bool> {
+        if !self.live.verify_execution(intent, execution_proof)? {
+            return Ok(false);
+        }
+        Ok(self.ledger().txs.iter().any(|t| {
+            t.calldata == execution_proof && matches!(t.tx.status, SandboxTxStatus::Included { .. })
+        }))
+    }
+
+    fn get_finality_blocks(&self) -> u64 { self.config.confirmation_depth }
+    fn chain_id(&self) -> ChainId { self.live.chain_id() }
+    fn native_decimals(&self) -> u8 { self.live.native_decimals() }
+    fn dust_threshold(&self) -> u128 { self.live.dust_threshold() }
+
+    fn validate_address(&self, address: &str) -> Option<ValidationIssue> {
+        self.live.validate_address(address)
+    }
+
+    fn submit_transfer(&self, to: &str, amount: u128, calldata: &[u8]) -> BleepConnectResult<String> {
+        let mut ledger = self.ledger();
+        self.catch_up(&mut ledger);
+        let seq = ledger.txs.len() as u64 + 1;
+
+        let mut preimage = self.live.chain_id().canonical_name().as_bytes().to_vec();
+        preimage.extend_from_slice(&seq.to_be_bytes());
+        preimage.extend_from_slice(to.as_bytes());
+        preimage.extend_from_slice(&amount.to_be_bytes());
+        preimage.extend_from_slice(calldata);
+        let hash = format!("0x{}", hex::encode(sha256(&preimage)));
+        if ledger.index.contains_key(&hash) {
+            return Err(BleepConnectError::InternalError(format!("duplicate sandbox tx {}", hash)));
+        }
+
+        let index = ledger.txs.len();
+        ledger.index.insert(hash.clone(), index);
+        ledger.txs.push(LedgerTx {
+            tx: SandboxTx {
+                hash: hash.clone(),
+                to: to.to_string(),
+                amount,
+                status: SandboxTxStatus::Pending,
+                reorged: false,
+            },
+            calldata:   calldata.to_vec(),
+            ready_at:   Instant::now() + Duration::from_millis(self.config.latency_ms),
+            not_before: 0,
+            revert:     Self::is_nth(seq, self.config.revert_every),
+            reorg:      Self::is_nth(seq, self.config.reorg_every),
+        });
+        Ok(hash)
+    }
+
+    fn tx_status(&self, tx_hash: &str) -> RelayStatus {
+        let mut ledger = self.ledger();
+        if self.config.block_time_ms == 0 {
+            ledger.mine_block(Instant::now());
+        } else {
+            self.catch_up(&mut ledger);
+        }
+        let Some(&i) = ledger.index.get(tx_hash) else {
+            return RelayStatus::Pending;
+        };
+        match ledger.txs[i].tx.status {
+            SandboxTxStatus::Pending => RelayStatus::Pending,
+            SandboxTxStatus::Reverted { .. } => RelayStatus::Reverted {
+                reason: "execution reverted: sandbox failure injection".into(),
+            },
+            SandboxTxStatus::Included { block } => {
+                let confirmations = ledger.height - block + 1;
+                if confirmations >= self.config.confirmation_depth {
+                    RelayStatus::Finalized { block_number: block, tx_hash: tx_hash.to_string() }
+                } else {
+                    RelayStatus::Confirming { block_number: block, confirmations }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EthereumAdapter;
+
+    fn sandbox(config: SandboxConfig) -> SandboxAdapter {
+        SandboxAdapter::new(Arc::new(EthereumAdapter::new(ChainId::Ethereum)), config)
+    }
+
+    fn per_query(depth: u64) -> SandboxConfig {
+        SandboxConfig { block_time_ms: 0, confirmation_depth: depth, ..SandboxConfig::default() }
+    }
+
+    #[test]
+    fn transfers_confirm_then_finalize() {
+        let chain = sandbox(per_query(2));
+        let tx = chain.submit_transfer("0xabc", 7, b"call").unwrap();
+        assert_eq!(chain.tx_status(&tx), RelayStatus::Confirming { block_number: 1, confirmations: 1 });
+        assert!(matches!(chain.tx_status(&tx), RelayStatus::Finalized { block_number: 1, .. }));
+        assert_eq!(chain.balance("0xabc"), 7);
+        assert_eq!(chain.tx_status("0xunknown"), RelayStatus::Pending);
+    }
+
+    #[test]
+    fn failure_injection_reverts_and_reorgs() {
+        let chain = sandbox(SandboxConfig { revert_every: 2, reorg_every: 3, ..per_query(3) });
+        let ok = chain.submit_transfer("0xa", 1, b"1").unwrap();
+        let reverted = chain.submit_transfer("0xb", 2, b"2").unwrap();
+        let reorged = chain.submit_transfer("0xc", 3, b"3").unwrap();
+
+        chain.mine(1);
+        assert!(matches!(chain.tx_status(&reverted), RelayStatus::Reverted { .. }));
+        // The status query mined block 2, which dropped the third tx.
+        assert_eq!(chain.tx_status(&reorged), RelayStatus::Confirming { block_number: 3, confirmations: 1 });
+
+        let state = chain.state();
+        assert_eq!((state.height, state.reverted, state.reorgs), (3, 1, 1));
+        assert_eq!(state.balances.get("0xb"), None);
+        assert!(state.transactions[2].reorged);
+        assert!(matches!(chain.tx_status(&ok), RelayStatus::Finalized { block_number: 1, .. }));
+    }
+
+    #[test]
+    fn latency_and_clock_mining() {
+        let chain = sandbox(SandboxConfig { block_time_ms: 5, latency_ms: 40, confirmation_depth: 1, ..SandboxConfig::default() });
+        let tx = chain.submit_transfer("0xa", 1, b"1").unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(chain.tx_status(&tx), RelayStatus::Pending);
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(matches!(chain.tx_status(&tx), RelayStatus::Finalized { .. }));
+        assert!(chain.height() >= 10);
+    }
+}
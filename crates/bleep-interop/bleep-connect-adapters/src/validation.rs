@@ -0,0 +1,497 @@
+//! # Pre-flight validation
+//!
+//! A malformed destination address is otherwise only discovered on the
+//! destination chain, after the source funds are escrowed — or not at all, if
+//! it happens to decode to somebody else's account.
+//!
+//! - `validate_address` — syntax and checksum per chain family: EIP-55 for EVM
+//!   chains, base58check and bech32/bech32m for Bitcoin, base58 + length for
+//!   Solana, bech32 for Cosmos and Celestia, SS58 for Polkadot
+//! - `convert_amount` — integer units between decimal conventions under an
+//!   explicit `Rounding` policy
+//! - `AdapterRegistry::validate_request` combines both with the destination
+//!   adapter's dust threshold
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use bleep_connect_crypto::{blake2b_32, sha256};
+use bleep_connect_types::ChainId;
+
+/// Decimals of BLEEP's integer units.
+pub const BLEEP_DECIMALS: u8 = 8;
+
+/// SS58 network prefix of Polkadot mainnet addresses.
+pub const SS58_PREFIX_POLKADOT: u16 = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Submittable, but worth showing to the user.
+    Warning,
+    /// Must block submission.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(tag = "issue", rename_all = "snake_case")]
+pub enum ValidationIssue {
+    #[error("no adapter for chain {chain}")]
+    UnsupportedChain { chain: String },
+    #[error("malformed address: {reason}")]
+    MalformedAddress { reason: String },
+    #[error("address decodes to {found} bytes, expected {expected}")]
+    BadLength { expected: usize, found: usize },
+    #[error("address checksum does not match")]
+    BadChecksum,
+    /// Single-case EVM address: valid, but a typo in it cannot be detected.
+    #[error("address carries no EIP-55 checksum")]
+    MissingChecksum,
+    #[error("address is for network {found}, expected {expected}")]
+    WrongNetworkPrefix { expected: String, found: String },
+    #[error("amount {amount} is below the dust threshold {threshold}")]
+    BelowDust { amount: u128, threshold: u128 },
+    #[error("amount {amount} with {from_decimals} decimals cannot be represented with {to_decimals}")]
+    PrecisionLoss { amount: u128, from_decimals: u8, to_decimals: u8 },
+    #[error("amount {amount} overflows at {to_decimals} decimals")]
+    AmountOverflow { amount: u128, to_decimals: u8 },
+}
+
+impl ValidationIssue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            ValidationIssue::MissingChecksum => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+/// `true` if any issue must block submission.
+pub fn has_errors(issues: &[ValidationIssue]) -> bool {
+    issues.iter().any(|i| i.severity() == Severity::Error)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// AMOUNTS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    /// Reject any amount that loses precision.
+    Exact,
+    /// Truncate towards zero; the remainder stays with the sender.
+    Down,
+}
+
+/// Convert `amount` from `from_decimals` to `to_decimals` integer units.
+pub fn convert_amount(
+    amount: u128,
+    from_decimals: u8,
+    to_decimals: u8,
+    rounding: Rounding,
+) -> Result<u128, ValidationIssue> {
+    if to_decimals >= from_decimals {
+        return 10u128
+            .checked_pow(u32::from(to_decimals - from_decimals))
+            .and_then(|factor| amount.checked_mul(factor))
+            .ok_or(ValidationIssue::AmountOverflow { amount, to_decimals });
+    }
+    let (quotient, remainder) = match 10u128.checked_pow(u32::from(from_decimals - to_decimals)) {
+        Some(factor) => (amount / factor, amount % factor),
+        None => (0, amount),
+    };
+    if remainder != 0 && rounding == Rounding::Exact {
+        return Err(ValidationIssue::PrecisionLoss { amount, from_decimals, to_decimals });
+    }
+    Ok(quotient)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ADDRESSES
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Check `address` as a destination on `chain`.  `None` means it is valid;
+/// chains without a known address format are passed through.
+pub fn validate_address(chain: ChainId, address: &str) -> Option<ValidationIssue> {
+    match chain {
+        ChainId::Ethereum | ChainId::Polygon | ChainId::Arbitrum | ChainId::Optimism
+        | ChainId::Base | ChainId::Avalanche | ChainId::BSC | ChainId::ZkSync => eip55_address(address),
+        ChainId::Bitcoin => bitcoin_address(address),
+        ChainId::Solana => solana_address(address),
+        ChainId::Cosmos => bech32_account(address, "cosmos"),
+        ChainId::Celestia => bech32_account(address, "celestia"),
+        ChainId::Polkadot => ss58_address(address, SS58_PREFIX_POLKADOT),
+        ChainId::BLEEP => bleep_address(address),
+        _ => None,
+    }
+}
+
+fn malformed(reason: &str) -> Option<ValidationIssue> {
+    Some(ValidationIssue::MalformedAddress { reason: reason.into() })
+}
+
+fn eip55_address(address: &str) -> Option<ValidationIssue> {
+    let Some(digits) = address.strip_prefix("0x") else {
+        return malformed("missing 0x prefix");
+    };
+    if digits.len() != 40 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return malformed("expected 40 hex digits");
+    }
+    let has_lower = digits.bytes().any(|b| b.is_ascii_lowercase());
+    let has_upper = digits.bytes().any(|b| b.is_ascii_uppercase());
+    match (has_lower, has_upper) {
+        (true, true) => {}
+        (false, false) => return None,
+        _ => return Some(ValidationIssue::MissingChecksum),
+    }
+    let hash = Keccak256::digest(digits.to_ascii_lowercase().as_bytes());
+    for (i, b) in digits.bytes().enumerate() {
+        if !b.is_ascii_alphabetic() {
+            continue;
+        }
+        let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+        if (nibble >= 8) != b.is_ascii_uppercase() {
+            return Some(ValidationIssue::BadChecksum);
+        }
+    }
+    None
+}
+
+fn bitcoin_address(address: &str) -> Option<ValidationIssue> {
+    let lower = address.to_ascii_lowercase();
+    if ["bc1", "tb1", "bcrt1"].iter().any(|hrp| lower.starts_with(hrp)) {
+        return segwit_address(address, "bc");
+    }
+    let Some(raw) = base58_decode(address) else {
+        return malformed("invalid base58 character");
+    };
+    if raw.len() != 25 {
+        return Some(ValidationIssue::BadLength { expected: 25, found: raw.len() });
+    }
+    let (payload, checksum) = raw.split_at(21);
+    if sha256(&sha256(payload))[..4] != *checksum {
+        return Some(ValidationIssue::BadChecksum);
+    }
+    match payload[0] {
+        0x00 | 0x05 => None,
+        version => Some(ValidationIssue::WrongNetworkPrefix {
+            expected: "0x00/0x05".into(),
+            found:    format!("0x{:02x}", version),
+        }),
+    }
+}
+
+fn segwit_address(address: &str, expected_hrp: &str) -> Option<ValidationIssue> {
+    let decoded = match bech32_decode(address) {
+        Ok(d) => d,
+        Err(issue) => return Some(issue),
+    };
+    let Some((&version, program)) = decoded.data.split_first() else {
+        return malformed("empty witness program");
+    };
+    // BIP-350: v0 programs use the original bech32 constant, v1+ bech32m.
+    let expected_constant = if version == 0 { BECH32_CONST } else { BECH32M_CONST };
+    if decoded.constant != expected_constant {
+        return Some(ValidationIssue::BadChecksum);
+    }
+    if version > 16 {
+        return malformed("witness version above 16");
+    }
+    let Some(program) = from_5bit(program) else {
+        return malformed("invalid witness program padding");
+    };
+    if !(2..=40).contains(&program.len()) || (version == 0 && program.len() != 20 && program.len() != 32) {
+        return malformed("invalid witness program length");
+    }
+    if decoded.hrp != expected_hrp {
+        return Some(ValidationIssue::WrongNetworkPrefix { expected: expected_hrp.into(), found: decoded.hrp });
+    }
+    None
+}
+
+/// Solana addresses are bare base58 ed25519 keys — no checksum, so only the
+/// alphabet and the decoded length can be checked.
+fn solana_address(address: &str) -> Option<ValidationIssue> {
+    let Some(raw) = base58_decode(address) else {
+        return malformed("invalid base58 character");
+    };
+    if raw.len() != 32 {
+        return Some(ValidationIssue::BadLength { expected: 32, found: raw.len() });
+    }
+    None
+}
+
+fn bech32_account(address: &str, expected_hrp: &str) -> Option<ValidationIssue> {
+    let decoded = match bech32_decode(address) {
+        Ok(d) => d,
+        Err(issue) => return Some(issue),
+    };
+    if decoded.constant != BECH32_CONST {
+        return Some(ValidationIssue::BadChecksum);
+    }
+    if decoded.hrp != expected_hrp {
+        return Some(ValidationIssue::WrongNetworkPrefix { expected: expected_hrp.into(), found: decoded.hrp });
+    }
+    match from_5bit(&decoded.data) {
+        Some(account) if account.len() == 20 || account.len() == 32 => None,
+        Some(account) => Some(ValidationIssue::BadLength { expected: 20, found: account.len() }),
+        None => malformed("invalid bech32 padding"),
+    }
+}
+
+fn ss58_address(address: &str, expected_prefix: u16) -> Option<ValidationIssue> {
+    let Some(raw) = base58_decode(address) else {
+        return malformed("invalid base58 character");
+    };
+    let (prefix, prefix_len) = match raw.as_slice() {
+        [b0, ..] if *b0 < 64 => (u16::from(*b0), 1),
+        [b0, b1, ..] if *b0 < 128 => {
+            let lower = ((b0 & 0x3f) << 2) | (b1 >> 6);
+            (u16::from(lower) | (u16::from(b1 & 0x3f) << 8), 2)
+        }
+        _ => return malformed("unknown SS58 prefix"),
+    };
+    // 32-byte account id followed by a 2-byte checksum.
+    if raw.len() != prefix_len + 34 {
+        return Some(ValidationIssue::BadLength { expected: prefix_len + 34, found: raw.len() });
+    }
+    let (body, checksum) = raw.split_at(raw.len() - 2);
+    let mut preimage = b"SS58PRE".to_vec();
+    preimage.extend_from_slice(body);
+    if blake2b_32(&preimage)[..2] != *checksum {
+        return Some(ValidationIssue::BadChecksum);
+    }
+    if prefix != expected_prefix {
+        return Some(ValidationIssue::WrongNetworkPrefix {
+            expected: expected_prefix.to_string(),
+            found:    prefix.to_string(),
+        });
+    }
+    None
+}
+
+/// `BLEEP1<hex40>`, as derived by the wallet.
+fn bleep_address(address: &str) -> Option<ValidationIssue> {
+    let Some(digits) = address.strip_prefix("BLEEP1") else {
+        return malformed("missing BLEEP1 prefix");
+    };
+    if digits.len() != 40 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return malformed("expected 40 hex digits");
+    }
+    None
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ENCODINGS
+// ─────────────────────────────────────────────────────────────────────────────
+
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+fn base58_decode(s: &str) -> Option<Vec<u8>> {
+    // Little-endian accumulator; each leading '1' is a leading zero byte.
+    let mut bytes: Vec<u8> = Vec::new();
+    for c in s.bytes() {
+        let mut carry = BASE58_ALPHABET.iter().position(|&a| a == c)? as u32;
+        for b in bytes.iter_mut() {
+            carry += u32::from(*b) * 58;
+            *b = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = s.bytes().take_while(|&c| c == b'1').count();
+    bytes.resize(bytes.len() + zeros, 0);
+    bytes.reverse();
+    Some(bytes)
+}
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+struct Bech32 {
+    hrp:      String,
+    /// 5-bit groups, checksum stripped.
+    data:     Vec<u8>,
+    /// `BECH32_CONST` or `BECH32M_CONST`.
+    constant: u32,
+}
+
+fn bech32_decode(s: &str) -> Result<Bech32, ValidationIssue> {
+    let bad = |reason: &str| ValidationIssue::MalformedAddress { reason: reason.into() };
+    if s.len() > 90 {
+        return Err(bad("longer than 90 characters"));
+    }
+    if s.bytes().any(|b| b.is_ascii_lowercase()) && s.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(bad("mixed-case bech32"));
+    }
+    let s = s.to_ascii_lowercase();
+    let sep = s.rfind('1').ok_or_else(|| bad("missing bech32 separator"))?;
+    if sep == 0 || sep + 7 > s.len() || !s[..sep].bytes().all(|b| (33..=126).contains(&b)) {
+        return Err(bad("invalid bech32 prefix or checksum length"));
+    }
+    let hrp = &s[..sep];
+    let data = s[sep + 1..]
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&x| x == c).map(|p| p as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| bad("invalid bech32 character"))?;
+
+    let mut values: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 0x1f));
+    values.extend_from_slice(&data);
+    let constant = bech32_polymod(&values);
+    if constant != BECH32_CONST && constant != BECH32M_CONST {
+        return Err(ValidationIssue::BadChecksum);
+    }
+    Ok(Bech32 { hrp: hrp.to_string(), data: data[..data.len() - 6].to_vec(), constant })
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+    let mut chk = 1u32;
+    for &v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+/// Regroup 5-bit values into bytes; `None` on non-zero or over-long padding.
+fn from_5bit(data: &[u8]) -> Option<Vec<u8>> {
+    let (mut acc, mut bits) = (0u32, 0u32);
+    let mut out = Vec::with_capacity(data.len() * 5 / 8);
+    for &v in data {
+        acc = (acc << 5) | u32::from(v);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+        }
+        acc &= (1 << bits) - 1;
+    }
+    (bits < 5 && acc == 0).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(chain: ChainId, address: &str) -> Option<ValidationIssue> {
+        validate_address(chain, address)
+    }
+
+    #[test]
+    fn eip55_checksum() {
+        assert_eq!(issue(ChainId::Ethereum, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"), None);
+        assert_eq!(issue(ChainId::Arbitrum, "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359"), None);
+
+        let lower = issue(ChainId::Ethereum, "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+        assert_eq!(lower, Some(ValidationIssue::MissingChecksum));
+        assert_eq!(lower.unwrap().severity(), Severity::Warning);
+
+        assert_eq!(
+            issue(ChainId::Ethereum, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Some(ValidationIssue::BadChecksum),
+        );
+        assert!(matches!(
+            issue(ChainId::Ethereum, "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"),
+            Some(ValidationIssue::MalformedAddress { .. })
+        ));
+    }
+
+    #[test]
+    fn bitcoin_fixtures() {
+        assert_eq!(issue(ChainId::Bitcoin, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"), None);
+        assert_eq!(issue(ChainId::Bitcoin, "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy"), None);
+        assert_eq!(issue(ChainId::Bitcoin, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"), None);
+        assert_eq!(
+            issue(ChainId::Bitcoin, "bc1p5d7rjq7g6rdk2yhzks9smlaqtedr4dekq08ge8ztwac72sfr9rusxg3297"),
+            None,
+        );
+
+        assert_eq!(
+            issue(ChainId::Bitcoin, "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb"),
+            Some(ValidationIssue::BadChecksum),
+        );
+        assert_eq!(
+            issue(ChainId::Bitcoin, "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5"),
+            Some(ValidationIssue::BadChecksum),
+        );
+        assert_eq!(
+            issue(ChainId::Bitcoin, "mipcBbFg9gMiCh81Kj8tqqdgoZub1ZJRfn"),
+            Some(ValidationIssue::WrongNetworkPrefix { expected: "0x00/0x05".into(), found: "0x6f".into() }),
+        );
+        assert_eq!(
+            issue(ChainId::Bitcoin, "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"),
+            Some(ValidationIssue::WrongNetworkPrefix { expected: "bc".into(), found: "tb".into() }),
+        );
+    }
+
+    #[test]
+    fn solana_fixtures() {
+        assert_eq!(issue(ChainId::Solana, "11111111111111111111111111111111"), None);
+        assert_eq!(issue(ChainId::Solana, "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T"), None);
+        assert_eq!(
+            issue(ChainId::Solana, "1111111111111111111111111111111"),
+            Some(ValidationIssue::BadLength { expected: 32, found: 31 }),
+        );
+        assert!(matches!(
+            issue(ChainId::Solana, "0OIl1111111111111111111111111111"),
+            Some(ValidationIssue::MalformedAddress { .. })
+        ));
+    }
+
+    #[test]
+    fn cosmos_fixtures() {
+        assert_eq!(issue(ChainId::Cosmos, "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu"), None);
+        assert_eq!(
+            issue(ChainId::Cosmos, "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xv"),
+            Some(ValidationIssue::BadChecksum),
+        );
+        assert_eq!(
+            issue(ChainId::Cosmos, "osmo1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5helwsw"),
+            Some(ValidationIssue::WrongNetworkPrefix { expected: "cosmos".into(), found: "osmo".into() }),
+        );
+    }
+
+    #[test]
+    fn polkadot_fixtures() {
+        assert_eq!(issue(ChainId::Polkadot, "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"), None);
+        assert_eq!(
+            issue(ChainId::Polkadot, "15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp6"),
+            Some(ValidationIssue::BadChecksum),
+        );
+        assert_eq!(
+            issue(ChainId::Polkadot, "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+            Some(ValidationIssue::WrongNetworkPrefix { expected: "0".into(), found: "42".into() }),
+        );
+    }
+
+    #[test]
+    fn amount_conversion() {
+        // 1.5 BLEEP → 18 decimals.
+        assert_eq!(convert_amount(150_000_000, 8, 18, Rounding::Exact), Ok(1_500_000_000_000_000_000));
+        // 8 → 6 decimals: the last two digits cannot survive.
+        assert_eq!(
+            convert_amount(123_456_789, 8, 6, Rounding::Exact),
+            Err(ValidationIssue::PrecisionLoss { amount: 123_456_789, from_decimals: 8, to_decimals: 6 }),
+        );
+        assert_eq!(convert_amount(123_456_789, 8, 6, Rounding::Down), Ok(1_234_567));
+        assert_eq!(convert_amount(123_456_700, 8, 6, Rounding::Exact), Ok(1_234_567));
+        assert_eq!(
+            convert_amount(u128::MAX / 2, 8, 18, Rounding::Exact),
+            Err(ValidationIssue::AmountOverflow { amount: u128::MAX / 2, to_decimals: 18 }),
+        );
+    }
+}
@@ -10,6 +10,7 @@
 //! 3. **RPC endpoints**: expose `submit_intent`, `get_transfer_status`, `get_executor_info`
 //! 4. **Node startup**: call `BleepConnectOrchestrator::new(...).await` and `start()`
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -29,7 +30,9 @@ use bleep_connect_layer1_social::{Layer1Social, RegisteredVoter, ProposalResult}
 use bleep_connect_layer2_fullnode::Layer2FullNode;
 use bleep_connect_layer3_zkproof::{Layer3ZKProof, ProofInput};
 use bleep_connect_layer4_instant::Layer4Instant;
-use bleep_connect_adapters::AdapterRegistry;
+use bleep_connect_adapters::{
+    AdapterRegistry, ChainModeConfig, SandboxConfig, SandboxState, Severity, ValidationIssue,
+};
 
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -49,6 +52,10 @@ pub struct BleepConnectConfig {
     pub commitment_chain_block_interval_secs: u64,
     /// Maximum transfer value (in base units) before Layer 2 verification is required
     pub layer2_threshold: u128,
+    /// Adapter mode per chain, keyed by canonical chain name; chains not
+    /// listed are live.
+    #[serde(default)]
+    pub chains: HashMap<String, ChainModeConfig>,
 }
 
 impl Default for BleepConnectConfig {
@@ -62,6 +69,7 @@ impl Default for BleepConnectConfig {
             data_directory: PathBuf::from("/var/lib/bleep/bleep-connect"),
             commitment_chain_block_interval_secs: 6,
             layer2_threshold: 100_000_000_000_000, // $100M equivalent
+            chains: HashMap::new(),
         }
     }
 }
@@ -151,7 +159,7 @@ impl BleepConnectOrchestrator {
             None
         };
         let layer1 = Arc::new(Layer1Social::new(commitment_chain.clone()));
-        let adapters = Arc::new(AdapterRegistry::new());
+        let adapters = Arc::new(AdapterRegistry::with_modes(&config.chains)?);
 
         info!(
             "BleepConnectOrchestrator initialized for {:?}: L4={} L3={} L2={} L1={}",
@@ -221,6 +229,15 @@ impl BleepConnectOrchestrator {
             return Err(BleepConnectError::InternalError("Protocol is paused by governance".into()));
         }
 
+        let errors: Vec<String> = self.validate_intent(&intent)
+            .iter()
+            .filter(|i| i.severity() == Severity::Error)
+            .map(ToString::to_string)
+            .collect();
+        if !errors.is_empty() {
+            return Err(BleepConnectError::ValidationFailed(errors.join("; ")));
+        }
+
         let requires_l2 = intent.source_amount >= self.config.layer2_threshold;
         if requires_l2 && self.layer2.is_none() {
             return Err(BleepConnectError::InternalError(
@@ -237,11 +254,24 @@ impl BleepConnectOrchestrator {
         Ok(id)
     }
 
+    /// Pre-flight checks of the recipient address and amount against the
+    /// destination adapter; `submit_intent` rejects any `Severity::Error`.
+    pub fn validate_intent(&self, intent: &InstantIntent) -> Vec<ValidationIssue> {
+        self.adapters.validate_request(intent)
+    }
+
     /// Get the current status of a transfer by its ID.
     pub fn get_transfer_status(&self, id: [u8; 32]) -> Option<TransferStatus> {
         self.layer4.get_status(&id)
     }
 
+    /// Ledger of `chain`'s sandbox; `None` unless it runs in sandbox mode.
+    ///
+    /// Backs `GET /rpc/interop/sandbox/:chain/state`.
+    pub fn sandbox_state(&self, chain: ChainId) -> Option<SandboxState> {
+        self.adapters.sandbox(chain).map(|s| s.state())
+    }
+
     // ── Pending intent list for executor polling ──────────────────────────
 
     /// Return all intent IDs currently in the pool.
@@ -504,6 +534,17 @@ impl BleepConnectBuilder {
         self
     }
 
+    /// Serve every chain with a built-in adapter from a sandbox, unless
+    /// `chains` already names it.
+    pub fn sandbox_all(mut self, sandbox: SandboxConfig) -> Self {
+        for chain in AdapterRegistry::new().chain_ids() {
+            self.config.chains
+                .entry(chain.canonical_name().to_string())
+                .or_insert_with(|| ChainModeConfig::sandbox(sandbox.clone()));
+        }
+        self
+    }
+
     pub async fn build(self, keypair: ClassicalKeyPair) -> BleepConnectResult<Arc<BleepConnectOrchestrator>> {
         let orchestrator = BleepConnectOrchestrator::new(self.config, keypair).await?;
         Ok(Arc::new(orchestrator))
@@ -565,6 +606,20 @@ mod tests {
         assert!(matches!(status, Some(TransferStatus::AuctionOpen { .. })));
     }
 
+    #[tokio::test]
+    async fn test_submit_rejects_invalid_recipient() {
+        let orc = make_orchestrator().await;
+
+        let mut intent = make_intent();
+        intent.recipient = UniversalAddress::ethereum("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD");
+        let err = orc.submit_intent(intent).await.unwrap_err();
+        assert!(matches!(err, BleepConnectError::ValidationFailed(_)));
+
+        // A lowercase address carries no checksum: warned about, not rejected.
+        assert_eq!(orc.validate_intent(&make_intent()), vec![ValidationIssue::MissingChecksum]);
+        assert!(orc.submit_intent(make_intent()).await.is_ok());
+    }
+
     #[tokio::test]
     async fn test_governance_pause() {
         let orc = make_orchestrator().await;
@@ -614,5 +669,24 @@ mod tests {
 
         let metrics = orc.metrics().await;
         assert_eq!(metrics.total_intents_submitted, 0);
+        assert!(orc.sandbox_state(ChainId::Ethereum).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_chains() {
+        let dir = tempdir().unwrap();
+        let orc = BleepConnectBuilder::new()
+            .data_directory(dir.path().to_path_buf())
+            .block_interval(999)
+            .sandbox_all(SandboxConfig { confirmation_depth: 4, ..SandboxConfig::default() })
+            .build(ClassicalKeyPair::generate())
+            .await
+            .unwrap();
+
+        let state = orc.sandbox_state(ChainId::Solana).unwrap();
+        assert_eq!((state.chain.as_str(), state.config.confirmation_depth), ("solana", 4));
+        assert!(state.transactions.is_empty());
+        // Validation still follows the live adapter's address rules.
+        assert!(orc.submit_intent(make_intent()).await.is_ok());
     }
 }
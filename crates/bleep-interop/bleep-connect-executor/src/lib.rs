@@ -2,22 +2,28 @@
 //!
 //! Executor node implementation: monitors the intent pool, evaluates bids,
 //! executes transfers on destination chains, and manages capital.
+//!
+//! A transfer is broadcast through the destination adapter and watched by
+//! the `ConfirmationPoller` until it reaches the adapter's finality depth.
+//! A reverted or dropped transaction is broadcast again, up to
+//! `ConfirmationPoller::max_attempts`; after that the escrow is refunded to
+//! the sender on the source chain.
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use dashmap::DashMap;
 use tokio::sync::RwLock;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use bleep_connect_types::{
-    InstantIntent, ExecutorProfile, ExecutorTier,
+    InstantIntent, ExecutorProfile, ExecutorTier, FailureReason,
     UniversalAddress, ChainId, BleepConnectError, BleepConnectResult,
 };
 use bleep_connect_crypto::{sha256, ClassicalKeyPair};
 use bleep_connect_layer4_instant::{Layer4Instant, ExecutorBid, ExecutionProof};
-use bleep_connect_adapters::AdapterRegistry;
+use bleep_connect_adapters::{AdapterRegistry, ChainAdapter, RelayStatus};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // RISK ENGINE
@@ -163,6 +169,90 @@ impl ExecutionStrategy {
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// CONFIRMATION POLLER
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Watches destination transactions until they are final.
+#[derive(Debug, Clone)]
+pub struct ConfirmationPoller {
+    pub poll_interval: Duration,
+    /// Consecutive polls a transaction may stay unseen before it counts as dropped.
+    pub max_pending_polls: u32,
+    /// Broadcasts of one transfer before it is refunded.
+    pub max_attempts: u32,
+}
+
+impl Default for ConfirmationPoller {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(1),
+            max_pending_polls: 60,
+            max_attempts: 3,
+        }
+    }
+}
+
+/// How one broadcast ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxOutcome {
+    Final { block_number: u64 },
+    Reverted { reason: String },
+    Dropped,
+}
+
+impl ConfirmationPoller {
+    /// Poll `tx_hash` until it is final, reverted or dropped.  A
+    /// transaction that leaves the block it was seen in has been reorged
+    /// out: its confirmations start over and `reorgs` is incremented.
+    pub async fn watch(&self, adapter: &dyn ChainAdapter, tx_hash: &str, reorgs: &mut u32) -> TxOutcome {
+        let depth = adapter.get_finality_blocks();
+        let mut seen_in: Option<u64> = None;
+        let mut pending_polls = 0;
+        loop {
+            match adapter.tx_status(tx_hash) {
+                RelayStatus::Finalized { block_number, .. } => return TxOutcome::Final { block_number },
+                RelayStatus::Reverted { reason } => return TxOutcome::Reverted { reason },
+                RelayStatus::Confirming { block_number, confirmations } => {
+                    if seen_in.is_some_and(|b| b != block_number) {
+                        *reorgs += 1;
+                        warn!("{} reorged into block {}; confirmations restart", tx_hash, block_number);
+                    }
+                    if confirmations >= depth {
+                        return TxOutcome::Final { block_number };
+                    }
+                    seen_in = Some(block_number);
+                    pending_polls = 0;
+                }
+                RelayStatus::Pending => {
+                    if let Some(block) = seen_in.take() {
+                        *reorgs += 1;
+                        warn!("{} reorged out of block {}; waiting for re-inclusion", tx_hash, block);
+                        pending_polls = 0;
+                    } else {
+                        pending_polls += 1;
+                        if pending_polls >= self.max_pending_polls {
+                            return TxOutcome::Dropped;
+                        }
+                    }
+                }
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// A transfer final on its destination chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Delivery {
+    pub dest_tx_hash: String,
+    pub block_number: u64,
+    /// Broadcasts it took, counting the one that landed.
+    pub attempts: u32,
+    /// Reorgs the poller saw along the way.
+    pub reorgs: u32,
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // EXECUTOR NODE
 // ─────────────────────────────────────────────────────────────────────────────
@@ -174,6 +264,7 @@ pub struct ExecutorNode {
     risk: Arc<RiskEngine>,
     strategy: ExecutionStrategy,
     adapters: Arc<AdapterRegistry>,
+    poller: ConfirmationPoller,
 }
 
 impl ExecutorNode {
@@ -206,9 +297,21 @@ impl ExecutorNode {
             risk: Arc::new(RiskEngine::new(risk_tolerance, 20)),
             strategy,
             adapters: Arc::new(AdapterRegistry::new()),
+            poller: ConfirmationPoller::default(),
         }
     }
 
+    /// Execute against `adapters`, e.g. `AdapterRegistry::sandboxed`.
+    pub fn with_adapters(mut self, adapters: Arc<AdapterRegistry>) -> Self {
+        self.adapters = adapters;
+        self
+    }
+
+    pub fn with_poller(mut self, poller: ConfirmationPoller) -> Self {
+        self.poller = poller;
+        self
+    }
+
     pub async fn deposit_capital(&self, chain: ChainId, amount: u128) {
         self.capital.deposit(chain, amount).await;
     }
@@ -261,11 +364,14 @@ impl ExecutorNode {
     }
 
     /// Execute a won intent on the destination chain and submit proof.
+    ///
+    /// If no broadcast becomes final within `max_attempts`, the escrow is
+    /// refunded, the transfer ends `Refunded` and an error is returned.
     pub async fn execute(
         &self,
         intent: &InstantIntent,
         layer4: &Arc<Layer4Instant>,
-    ) -> BleepConnectResult<()> {
+    ) -> BleepConnectResult<Delivery> {
         let intent_id = intent.calculate_id();
         let start_ms = now_ms();
 
@@ -278,9 +384,18 @@ impl ExecutorNode {
             .ok_or_else(|| BleepConnectError::InternalError("No adapter".into()))?;
         let calldata = adapter.encode_transfer(intent)?;
 
-        // Simulate destination chain execution
-        // In production: sign and broadcast the transaction to the dest chain's RPC
-        let dest_tx_hash = format!("0x{}", hex::encode(sha256(&calldata)));
+        let delivery = match self.deliver(intent, adapter.as_ref(), &calldata).await {
+            Ok(delivery) => delivery,
+            Err(reason) => {
+                self.capital.release(intent.dest_chain, intent.min_dest_amount).await;
+                self.risk.release(&intent_id);
+                let refund_tx = self.refund(intent, intent_id, reason.clone(), layer4).await?;
+                return Err(BleepConnectError::InternalError(format!(
+                    "Execution failed ({:?}); escrow refunded in {}", reason, refund_tx
+                )));
+            }
+        };
+        let dest_tx_hash = delivery.dest_tx_hash.clone();
         let execution_time_ms = now_ms() - start_ms;
 
         // Verify the execution we just performed
@@ -313,10 +428,76 @@ impl ExecutorNode {
         self.capital.release(intent.dest_chain, intent.min_dest_amount).await;
         self.risk.release(&intent_id);
         info!(
-            "Executed intent {} in {}ms; dest_tx={}",
-            hex::encode(intent_id), execution_time_ms, dest_tx_hash
+            "Executed intent {} in {}ms; dest_tx={} (attempts={}, reorgs={})",
+            hex::encode(intent_id), execution_time_ms, dest_tx_hash, delivery.attempts, delivery.reorgs
         );
-        Ok(())
+        Ok(delivery)
+    }
+
+    /// Broadcast `calldata` until one transaction is final.
+    async fn deliver(
+        &self,
+        intent: &InstantIntent,
+        adapter: &dyn ChainAdapter,
+        calldata: &[u8],
+    ) -> Result<Delivery, FailureReason> {
+        let mut reorgs = 0;
+        let mut failure = FailureReason::InvalidExecution;
+        for attempt in 1..=self.poller.max_attempts {
+            let tx_hash = match adapter.submit_transfer(&intent.recipient.address, intent.min_dest_amount, calldata) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    warn!("Broadcast {} of {} failed: {}", attempt, hex::encode(intent.calculate_id()), e);
+                    failure = FailureReason::Other(e.to_string());
+                    continue;
+                }
+            };
+            match self.poller.watch(adapter, &tx_hash, &mut reorgs).await {
+                TxOutcome::Final { block_number } => {
+                    return Ok(Delivery { dest_tx_hash: tx_hash, block_number, attempts: attempt, reorgs });
+                }
+                TxOutcome::Reverted { reason } => {
+                    warn!("Attempt {}: {} reverted: {}", attempt, tx_hash, reason);
+                    failure = FailureReason::Other(format!("destination tx reverted: {}", reason));
+                }
+                TxOutcome::Dropped => {
+                    warn!("Attempt {}: {} dropped", attempt, tx_hash);
+                    failure = if reorgs > 0 {
+                        FailureReason::ChainReorganization
+                    } else {
+                        FailureReason::Other("destination tx dropped".into())
+                    };
+                }
+            }
+        }
+        Err(failure)
+    }
+
+    /// Return the escrowed source amount to the sender and wait for it to be final.
+    async fn refund(
+        &self,
+        intent: &InstantIntent,
+        intent_id: [u8; 32],
+        reason: FailureReason,
+        layer4: &Arc<Layer4Instant>,
+    ) -> BleepConnectResult<String> {
+        let source = self.adapters.get(intent.source_chain)
+            .ok_or_else(|| BleepConnectError::InternalError("No source chain adapter for refund".into()))?;
+        let mut calldata = b"REFUND".to_vec();
+        calldata.extend_from_slice(&intent_id);
+        let refund_tx = source.submit_transfer(&intent.sender.address, intent.source_amount, &calldata)?;
+
+        let mut reorgs = 0;
+        match self.poller.watch(source.as_ref(), &refund_tx, &mut reorgs).await {
+            TxOutcome::Final { .. } => {}
+            outcome => {
+                return Err(BleepConnectError::InternalError(format!(
+                    "Refund {} of {} not final: {:?}", refund_tx, hex::encode(intent_id), outcome
+                )));
+            }
+        }
+        layer4.refund_intent(intent_id, &self.profile.address, reason, refund_tx.clone()).await?;
+        Ok(refund_tx)
     }
 }
 
@@ -334,10 +515,12 @@ fn now_ms() -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bleep_connect_adapters::{SandboxConfig, SandboxTxStatus};
     use bleep_connect_commitment_chain::{CommitmentChain, Validator};
-    use tempfile::tempdir;
+    use bleep_connect_types::TransferStatus;
+    use tempfile::{tempdir, TempDir};
 
-    async fn make_executor() -> (ExecutorNode, Arc<Layer4Instant>) {
+    async fn make_executor() -> (ExecutorNode, Arc<Layer4Instant>, TempDir) {
         let dir = tempdir().unwrap();
         let kp = ClassicalKeyPair::generate();
         let v = Validator::new(kp.public_key_bytes(), 1_000_000);
@@ -371,7 +554,7 @@ mod tests {
             last_execution_at: now(),
         });
 
-        (exec, layer4)
+        (exec, layer4, dir)
     }
 
     fn make_intent() -> InstantIntent {
@@ -399,7 +582,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_bid_placement() {
-        let (exec, layer4) = make_executor().await;
+        let (exec, layer4, _dir) = make_executor().await;
         let intent = make_intent();
         layer4.submit_intent(intent.clone()).await.unwrap();
         let bid_placed = exec.evaluate_and_bid(&intent, &layer4).await.unwrap();
@@ -419,4 +602,127 @@ mod tests {
             1_000_000
         ));
     }
-  }
+
+    /// One block per status query, two confirmations to finality.
+    fn per_query() -> SandboxConfig {
+        SandboxConfig { block_time_ms: 0, confirmation_depth: 2, ..SandboxConfig::default() }
+    }
+
+    struct SandboxRun {
+        adapters: Arc<AdapterRegistry>,
+        layer4:   Arc<Layer4Instant>,
+        id:       [u8; 32],
+        result:   BleepConnectResult<Delivery>,
+        _dir:     TempDir,
+    }
+
+    /// Escrow on a sandboxed BLEEP, auction, and execute onto a sandboxed
+    /// Ethereum with `dest` behaviour.  `dest_noise` unrelated transactions
+    /// land on Ethereum first.
+    async fn run_sandboxed(dest: SandboxConfig, dest_noise: usize) -> SandboxRun {
+        let mut registry = AdapterRegistry::new();
+        let source = registry.sandbox_chain(ChainId::BLEEP, per_query()).unwrap();
+        let ethereum = registry.sandbox_chain(ChainId::Ethereum, dest).unwrap();
+        for i in 0..dest_noise {
+            ethereum.submit_transfer("0xnoise", 1, &[i as u8]).unwrap();
+        }
+        let adapters = Arc::new(registry);
+
+        let (exec, layer4, dir) = make_executor().await;
+        let exec = exec.with_adapters(Arc::clone(&adapters)).with_poller(ConfirmationPoller {
+            poll_interval: Duration::from_millis(1),
+            max_pending_polls: 5,
+            max_attempts: 3,
+        });
+
+        let mut intent = make_intent();
+        intent.escrow_tx_hash = source.submit_transfer("escrow", intent.source_amount, b"ESCROW").unwrap();
+        source.mine(2);
+        let id = layer4.submit_intent(intent.clone()).await.unwrap();
+        assert!(exec.evaluate_and_bid(&intent, &layer4).await.unwrap());
+        layer4.close_auction(id).await.unwrap();
+        let result = exec.execute(&intent, &layer4).await;
+        SandboxRun { adapters, layer4, id, result, _dir: dir }
+    }
+
+    fn states(layer4: &Layer4Instant, id: &[u8; 32]) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = layer4.status_history(id).iter().map(|s| match s {
+            TransferStatus::Created { .. } => "Created",
+            TransferStatus::EscrowLocked { .. } => "EscrowLocked",
+            TransferStatus::AuctionOpen { .. } => "AuctionOpen",
+            TransferStatus::BidAccepted { .. } => "BidAccepted",
+            TransferStatus::ExecutionStarted { .. } => "ExecutionStarted",
+            TransferStatus::ExecutionCompleted { .. } => "ExecutionCompleted",
+            TransferStatus::Failed { .. } => "Failed",
+            TransferStatus::Refunded { .. } => "Refunded",
+            _ => "Other",
+        }).collect();
+        names.dedup();
+        names
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_transfer_walks_every_state() {
+        let run = run_sandboxed(per_query(), 0).await;
+        let delivery = run.result.unwrap();
+        assert_eq!((delivery.attempts, delivery.reorgs), (1, 0));
+        assert_eq!(
+            states(&run.layer4, &run.id),
+            ["Created", "EscrowLocked", "AuctionOpen", "BidAccepted", "ExecutionStarted", "ExecutionCompleted"],
+        );
+
+        let intent = make_intent();
+        let ethereum = run.adapters.sandbox(ChainId::Ethereum).unwrap().state();
+        assert_eq!(ethereum.balances.get(&intent.recipient.address), Some(&intent.min_dest_amount));
+        assert_eq!(ethereum.transactions[0].hash, delivery.dest_tx_hash);
+        let bleep = run.adapters.sandbox(ChainId::BLEEP).unwrap();
+        assert_eq!(bleep.balance("escrow"), intent.source_amount);
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_revert_is_retried_then_refunded() {
+        // The noise tx takes slot 1; the executor's first broadcast is the
+        // 2nd and reverts, the retry lands.
+        let run = run_sandboxed(SandboxConfig { revert_every: 2, ..per_query() }, 1).await;
+        let delivery = run.result.unwrap();
+        assert_eq!(delivery.attempts, 2);
+        assert_eq!(states(&run.layer4, &run.id).last(), Some(&"ExecutionCompleted"));
+
+        // Every broadcast reverts: three attempts, then the escrow goes back.
+        let run = run_sandboxed(SandboxConfig { revert_every: 1, ..per_query() }, 0).await;
+        assert!(run.result.is_err());
+        let ethereum = run.adapters.sandbox(ChainId::Ethereum).unwrap().state();
+        assert_eq!(ethereum.reverted, 3);
+        assert!(ethereum.transactions.iter().all(|t| matches!(t.status, SandboxTxStatus::Reverted { .. })));
+        assert!(ethereum.balances.is_empty());
+
+        let intent = make_intent();
+        let bleep = run.adapters.sandbox(ChainId::BLEEP).unwrap();
+        assert_eq!(bleep.balance(&intent.sender.address), intent.source_amount);
+        assert_eq!(
+            states(&run.layer4, &run.id),
+            ["Created", "EscrowLocked", "AuctionOpen", "BidAccepted", "ExecutionStarted", "Failed", "Refunded"],
+        );
+        assert!(matches!(
+            run.layer4.status_history(&run.id).iter().rev().nth(1),
+            Some(TransferStatus::Failed { reason: FailureReason::Other(r), .. }) if r.starts_with("destination tx reverted")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sandbox_reorg_restarts_confirmations() {
+        let run = run_sandboxed(
+            SandboxConfig { reorg_every: 1, confirmation_depth: 3, ..per_query() },
+            0,
+        ).await;
+        let delivery = run.result.unwrap();
+        // Included in block 1, dropped by block 2, re-included in block 3.
+        assert_eq!((delivery.attempts, delivery.reorgs, delivery.block_number), (1, 1, 3));
+        assert_eq!(states(&run.layer4, &run.id).last(), Some(&"ExecutionCompleted"));
+
+        let ethereum = run.adapters.sandbox(ChainId::Ethereum).unwrap().state();
+        assert_eq!(ethereum.reorgs, 1);
+        assert!(ethereum.transactions[0].reorged);
+        assert_eq!(ethereum.transactions[0].status, SandboxTxStatus::Included { block: 3 });
+    }
+}
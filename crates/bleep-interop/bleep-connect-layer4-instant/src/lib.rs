@@ -31,6 +31,8 @@ use bleep_connect_commitment_chain::CommitmentChain;
 pub struct IntentPool {
     intents: DashMap<[u8; 32], InstantIntent>,
     statuses: DashMap<[u8; 32], TransferStatus>,
+    /// Every status each transfer has passed through, oldest first.
+    history: DashMap<[u8; 32], Vec<TransferStatus>>,
 }
 
 impl IntentPool {
@@ -38,6 +40,7 @@ impl IntentPool {
         Self {
             intents: DashMap::new(),
             statuses: DashMap::new(),
+            history: DashMap::new(),
         }
     }
 
@@ -48,7 +51,8 @@ impl IntentPool {
             return Err(BleepConnectError::IntentExpired(intent.expires_at));
         }
         self.intents.insert(id, intent);
-        self.statuses.insert(id, TransferStatus::Created { created_at: ts });
+        self.history.remove(&id);
+        self.update_status(id, TransferStatus::Created { created_at: ts });
         Ok(id)
     }
 
@@ -61,9 +65,14 @@ impl IntentPool {
     }
 
     pub fn update_status(&self, id: [u8; 32], status: TransferStatus) {
+        self.history.entry(id).or_default().push(status.clone());
         self.statuses.insert(id, status);
     }
 
+    pub fn status_history(&self, id: &[u8; 32]) -> Vec<TransferStatus> {
+        self.history.get(id).map(|e| e.value().clone()).unwrap_or_default()
+    }
+
     pub fn remove(&self, id: &[u8; 32]) -> Option<InstantIntent> {
         self.intents.remove(id).map(|(_, v)| v)
     }
@@ -462,6 +471,57 @@ impl Layer4Instant {
         self.intent_pool.get_status(id)
     }
 
+    /// Every status the transfer has passed through, oldest first.
+    pub fn status_history(&self, id: &[u8; 32]) -> Vec<TransferStatus> {
+        self.intent_pool.status_history(id)
+    }
+
+    /// `executor` could not deliver `intent_id` and the escrow went back to
+    /// the sender in `refund_tx`: release the bond, count the failure and
+    /// record `Failed` then `Refunded`.
+    pub async fn refund_intent(
+        &self,
+        intent_id: [u8; 32],
+        executor: &UniversalAddress,
+        reason: FailureReason,
+        refund_tx: String,
+    ) -> BleepConnectResult<()> {
+        let intent = self.intent_pool.get(&intent_id)
+            .ok_or_else(|| BleepConnectError::InternalError("Intent not found".into()))?;
+
+        let bond = intent.calculate_min_executor_bond(
+            self.executor_registry.get_profile(executor)
+                .map(|p| p.tier)
+                .unwrap_or(ExecutorTier::Standard)
+        );
+        self.executor_registry.release_bond(&executor.to_string(), bond);
+        self.executor_registry.record_failure(executor);
+
+        warn!("Transfer {} failed ({:?}); refunded in {}", hex::encode(intent_id), reason, refund_tx);
+        self.intent_pool.update_status(intent_id, TransferStatus::Failed {
+            reason,
+            failed_at: now(),
+        });
+        self.intent_pool.update_status(intent_id, TransferStatus::Refunded {
+            refund_tx: refund_tx.clone(),
+            refunded_at: now(),
+        });
+
+        let data = [&intent_id[..], refund_tx.as_bytes()].concat();
+        let commitment = StateCommitment {
+            commitment_id: sha256(&[&b"L4-REFUND"[..], &intent_id].concat()),
+            commitment_type: CommitmentType::InstantTransfer,
+            data_hash: sha256(&data),
+            layer: 4,
+            created_at: now(),
+        };
+        self.commitment_chain.submit_commitment(commitment).await?;
+
+        self.intent_pool.remove(&intent_id);
+        self.execution_starts.remove(&intent_id);
+        Ok(())
+    }
+
     /// Return all intent IDs currently in the pool (for executor polling).
     pub fn intent_pool_ids(&self) -> Vec<[u8; 32]> {
         self.intent_pool.all_pending_ids()
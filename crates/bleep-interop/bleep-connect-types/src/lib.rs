@@ -89,6 +89,8 @@ impl ChainId {
         }
     }
 
+    /// Numeric id.  `900..=999` is reserved for BLEEP networks
+    /// (`bleep_crypto::chain_id::BLEEP_NETWORKS`).
     pub fn to_u32(&self) -> u32 {
         match self {
             ChainId::Ethereum => 1,
@@ -724,6 +726,86 @@ pub struct ValidatorSignature {
     pub signed_at: u64,
 }
 
+// ═══════════════════════════════════════════════════════════════════════════
+//  CROSS-CHAIN MESSAGE TYPES
+// ═══════════════════════════════════════════════════════════════════════════
+
+/// Ordered stream of messages from one source contract to one destination contract.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MessageChannel {
+    pub source: UniversalAddress,
+    pub destination_chain: ChainId,
+    pub destination_contract: String,
+}
+
+/// An arbitrary contract call carried from one chain to another.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossChainMessage {
+    /// Sending contract.
+    pub source: UniversalAddress,
+    pub destination_chain: ChainId,
+    pub destination_contract: String,
+    /// Calldata the destination contract is invoked with.
+    pub payload: Vec<u8>,
+    pub gas_budget: u64,
+    /// Position in the message's channel, assigned by the sending side from 0.
+    pub sequence: u64,
+}
+
+impl CrossChainMessage {
+    pub fn channel(&self) -> MessageChannel {
+        MessageChannel {
+            source: self.source.clone(),
+            destination_chain: self.destination_chain,
+            destination_contract: self.destination_contract.clone(),
+        }
+    }
+
+    /// Commitment over every field; identifies the message on both chains.
+    pub fn commitment(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"BLEEP-CONNECT-MESSAGE-V1:");
+        hasher.update(&self.source.to_bytes());
+        hasher.update(&[0]);
+        hasher.update(&self.destination_chain.to_u32().to_be_bytes());
+        hasher.update(self.destination_contract.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(&(self.payload.len() as u64).to_be_bytes());
+        hasher.update(&self.payload);
+        hasher.update(&self.gas_budget.to_be_bytes());
+        hasher.update(&self.sequence.to_be_bytes());
+
+        let result = hasher.finalize();
+        let mut id = [0u8; 32];
+        id.copy_from_slice(&result);
+        id
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    /// The destination call succeeded with this return data.
+    Delivered { output: Vec<u8> },
+    /// The destination call failed; the channel moves on past the message.
+    Failed { reason: String },
+}
+
+/// Outcome of delivering one message, reported back to the source chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryReceipt {
+    /// `CrossChainMessage::commitment` of the delivered message.
+    pub message_id: [u8; 32],
+    pub channel: MessageChannel,
+    pub sequence: u64,
+    pub status: DeliveryStatus,
+}
+
+impl DeliveryReceipt {
+    pub fn is_delivered(&self) -> bool {
+        matches!(self.status, DeliveryStatus::Delivered { .. })
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════
 //  ERROR TYPES
 // ═══════════════════════════════════════════════════════════════════════════
@@ -736,6 +818,9 @@ pub enum BleepConnectError {
     #[error("Invalid address format: {0}")]
     InvalidAddress(String),
     
+    #[error("Request failed validation: {0}")]
+    ValidationFailed(String),
+    
     #[error("Intent expired at {0}")]
     IntentExpired(u64),
     
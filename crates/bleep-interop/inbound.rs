@@ -0,0 +1,869 @@
+//! bleep-interop/src/inbound.rs
+//! Inbound transfer verification: remote-chain deposits minted on BLEEP
+//!
+//! Every mint of a wrapped asset must go through
+//! [`InboundVerifier::verify_inbound`]. A claim is accepted only when the
+//! lock event is proven on the source chain:
+//!
+//! - **Receipt proof** (Ethereum and other EVM chains): the header's
+//!   `transactionsRoot` / `receiptsRoot` are signed by a quorum of the chain's
+//!   registered verifiers, and Merkle-Patricia proofs against those roots show
+//!   the transaction and the lock log inside its receipt.
+//! - **Attestation only** (chains without practical light verification): the
+//!   deposit itself is signed by a higher verifier quorum
+//!   (`CONSENSUS_THRESHOLD`, as for Layer 2 full-node verification).
+//!
+//! Accepted deposits are recorded in a [`GlobalNullifierSet`] keyed by
+//! `(chain, tx hash, log index)`, so the same deposit can never mint twice.
+//!
+//! Cross-chain messages ([`MessageClaim`]) go through the same two proof
+//! modes via [`InboundVerifier::verify_message`] and are consumed by
+//! `(chain, message commitment)`; `messaging::MessageInbox` delivers them.
+//!
+//! ## Lock event
+//! ```text
+//! Locked(bytes32 indexed recipientHash, uint256 amount)
+//!   topics[0] = lock_topic, topics[1] = keccak256(BLEEP recipient address)
+//!   data      = amount (32-byte big-endian word)
+//! ```
+//!
+//! ## Message event
+//! ```text
+//! MessageSent(bytes32 indexed messageId)
+//!   emitted by the message's source contract
+//!   topics[0] = keccak256("MessageSent(bytes32)"), topics[1] = CrossChainMessage::commitment
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+use bleep_connect_crypto::{sha256, ClassicalKeyPair};
+use bleep_connect_types::constants::{CONSENSUS_THRESHOLD, MIN_VERIFIER_NODES};
+use bleep_connect_types::{ChainId, CrossChainMessage};
+
+use crate::nullifier_store::{GlobalNullifierSet, NullifierError};
+
+const HEADER_DOMAIN:   &[u8] = b"BLEEP-INBOUND-HEADER-V1";
+const DEPOSIT_DOMAIN:  &[u8] = b"BLEEP-INBOUND-DEPOSIT-V1";
+const CONSUMED_DOMAIN: &[u8] = b"BLEEP-INBOUND-CONSUMED-V1";
+const MESSAGE_DOMAIN:  &[u8] = b"BLEEP-INBOUND-MESSAGE-V1";
+
+// ── Errors ────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum InboundError {
+    #[error("chain {0} is not registered for inbound transfers")]
+    UnknownChain(String),
+    #[error("invalid chain config: {0}")]
+    InvalidConfig(String),
+    #[error("{0} has no lock contract for asset {1}")]
+    UnknownAsset(String, String),
+    #[error("proof type does not match the chain's verification mode")]
+    WrongProofType,
+    #[error("header not attested: {valid} of {required} verifier signatures")]
+    HeaderNotAttested { valid: usize, required: usize },
+    #[error("deposit not attested: {valid} of {required} verifier signatures")]
+    DepositNotAttested { valid: usize, required: usize },
+    #[error("invalid Merkle-Patricia proof: {0}")]
+    InvalidProof(String),
+    #[error("malformed RLP: {0}")]
+    Rlp(String),
+    #[error("lock event does not match claim: {0}")]
+    EventMismatch(String),
+    #[error("message event does not match claim: {0}")]
+    MessageMismatch(String),
+    #[error("deposit already consumed")]
+    AlreadyConsumed,
+    #[error("replay store: {0}")]
+    Store(String),
+}
+
+// ── Registry ──────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InboundMode {
+    /// Merkle-Patricia receipt proof against a quorum-attested header.
+    ReceiptProof,
+    /// No light verification; deposits are signed directly by the verifiers.
+    AttestationOnly,
+}
+
+/// A lock contract on the source chain and the asset it mints on BLEEP.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockContract {
+    /// Emitter address (20 bytes on EVM chains).
+    pub address: Vec<u8>,
+    /// Wrapped asset symbol minted on BLEEP.
+    pub asset:   String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InboundChainConfig {
+    pub chain:          ChainId,
+    pub mode:           InboundMode,
+    /// Ed25519 keys of the verifiers allowed to attest for this chain.
+    pub verifiers:      Vec<[u8; 32]>,
+    /// Distinct valid verifier signatures required.
+    pub quorum:         usize,
+    pub lock_contracts: Vec<LockContract>,
+    /// `topics[0]` of the lock event.
+    pub lock_topic:     [u8; 32],
+}
+
+impl InboundChainConfig {
+    /// Smallest quorum accepted for `mode` with `verifiers` keys.
+    pub fn min_quorum(mode: InboundMode, verifiers: usize) -> usize {
+        match mode {
+            InboundMode::ReceiptProof    => verifiers * 2 / 3 + 1,
+            InboundMode::AttestationOnly => (verifiers as f64 * CONSENSUS_THRESHOLD).ceil() as usize,
+        }
+    }
+
+    fn lock_contract(&self, asset: &str) -> Option<&LockContract> {
+        self.lock_contracts.iter().find(|c| c.asset == asset)
+    }
+}
+
+/// Per-chain inbound configuration.
+#[derive(Debug, Clone, Default)]
+pub struct InboundRegistry {
+    chains: HashMap<ChainId, InboundChainConfig>,
+}
+
+impl InboundRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add or replace a chain. Rejects quorums below [`InboundChainConfig::min_quorum`].
+    pub fn register(&mut self, config: InboundChainConfig) -> Result<(), InboundError> {
+        let n = config.verifiers.iter().collect::<HashSet<_>>().len();
+        if n != config.verifiers.len() {
+            return Err(InboundError::InvalidConfig("duplicate verifier key".into()));
+        }
+        if n < MIN_VERIFIER_NODES {
+            return Err(InboundError::InvalidConfig(format!(
+                "{} verifiers, at least {} required", n, MIN_VERIFIER_NODES
+            )));
+        }
+        let min = InboundChainConfig::min_quorum(config.mode, n);
+        if config.quorum < min || config.quorum > n {
+            return Err(InboundError::InvalidConfig(format!(
+                "quorum {} outside {}..={} for {:?}", config.quorum, min, n, config.mode
+            )));
+        }
+        self.chains.insert(config.chain, config);
+        Ok(())
+    }
+
+    pub fn get(&self, chain: &ChainId) -> Option<&InboundChainConfig> {
+        self.chains.get(chain)
+    }
+}
+
+// ── Proof formats ─────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attestation {
+    pub verifier:  [u8; 32],
+    pub signature: Vec<u8>,
+}
+
+/// Source-chain block header fields needed for receipt proofs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttestedHeader {
+    pub block_number:      u64,
+    pub block_hash:        [u8; 32],
+    pub transactions_root: [u8; 32],
+    pub receipts_root:     [u8; 32],
+    pub attestations:      Vec<Attestation>,
+}
+
+impl AttestedHeader {
+    /// Digest the verifiers sign for `chain`.
+    pub fn digest(&self, chain: ChainId) -> [u8; 32] {
+        let mut data = HEADER_DOMAIN.to_vec();
+        data.extend_from_slice(chain.canonical_name().as_bytes());
+        data.extend_from_slice(&self.block_number.to_be_bytes());
+        data.extend_from_slice(&self.block_hash);
+        data.extend_from_slice(&self.transactions_root);
+        data.extend_from_slice(&self.receipts_root);
+        sha256(&data)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptProof {
+    pub header:        AttestedHeader,
+    /// Position of the transaction in the block; the trie key is `rlp(tx_index)`.
+    pub tx_index:      u64,
+    /// Raw transaction as stored in the trie; its keccak256 is the tx hash.
+    pub transaction:   Vec<u8>,
+    /// Proof nodes from `transactions_root` down to the transaction.
+    pub tx_proof:      Vec<Vec<u8>>,
+    /// Proof nodes from `receipts_root` down to the receipt.
+    pub receipt_proof: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InboundProof {
+    Receipt(ReceiptProof),
+    /// Verifier signatures over [`TransferClaim::digest`].
+    Attested(Vec<Attestation>),
+}
+
+/// A relayer's request to mint for a deposit on `chain`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferClaim {
+    pub chain:     ChainId,
+    pub tx_hash:   [u8; 32],
+    /// Index of the lock log within the transaction's receipt.
+    pub log_index: u32,
+    pub recipient: String,
+    pub asset:     String,
+    pub amount:    u128,
+    pub proof:     InboundProof,
+}
+
+impl TransferClaim {
+    /// Digest verifiers sign for attestation-only chains.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut data = DEPOSIT_DOMAIN.to_vec();
+        data.extend_from_slice(self.chain.canonical_name().as_bytes());
+        data.extend_from_slice(&self.tx_hash);
+        data.extend_from_slice(&self.log_index.to_be_bytes());
+        data.extend_from_slice(self.recipient.as_bytes());
+        data.push(0);
+        data.extend_from_slice(self.asset.as_bytes());
+        data.push(0);
+        data.extend_from_slice(&self.amount.to_be_bytes());
+        sha256(&data)
+    }
+
+    fn consumed_key(&self) -> [u8; 32] {
+        let mut data = CONSUMED_DOMAIN.to_vec();
+        data.extend_from_slice(self.chain.canonical_name().as_bytes());
+        data.extend_from_slice(&self.tx_hash);
+        data.extend_from_slice(&self.log_index.to_be_bytes());
+        sha256(&data)
+    }
+}
+
+/// A relayer's request to deliver a message sent from `message.source.chain`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageClaim {
+    pub message:   CrossChainMessage,
+    /// Source-chain transaction that emitted the message event.
+    pub tx_hash:   [u8; 32],
+    /// Index of the message log within the transaction's receipt.
+    pub log_index: u32,
+    pub proof:     InboundProof,
+}
+
+impl MessageClaim {
+    /// Digest verifiers sign for attestation-only chains.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut data = MESSAGE_DOMAIN.to_vec();
+        data.extend_from_slice(self.message.source.chain.canonical_name().as_bytes());
+        data.extend_from_slice(&self.message.commitment());
+        sha256(&data)
+    }
+
+    fn consumed_key(&self) -> [u8; 32] {
+        let mut data = CONSUMED_DOMAIN.to_vec();
+        data.extend_from_slice(MESSAGE_DOMAIN);
+        data.extend_from_slice(self.message.source.chain.canonical_name().as_bytes());
+        data.extend_from_slice(&self.message.commitment());
+        sha256(&data)
+    }
+}
+
+/// `topics[0]` of the message event.
+pub fn message_topic() -> [u8; 32] {
+    keccak(b"MessageSent(bytes32)")
+}
+
+/// A deposit that passed verification and has been marked consumed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedDeposit {
+    pub chain:        ChainId,
+    pub tx_hash:      [u8; 32],
+    pub log_index:    u32,
+    pub recipient:    String,
+    pub asset:        String,
+    pub amount:       u128,
+    /// Source block, when proven by a receipt proof.
+    pub block_number: Option<u64>,
+}
+
+// ── Verifier ──────────────────────────────────────────────────────────────────
+
+pub struct InboundVerifier {
+    registry: InboundRegistry,
+    consumed: GlobalNullifierSet,
+}
+
+impl InboundVerifier {
+    pub fn new(registry: InboundRegistry, consumed: GlobalNullifierSet) -> Self {
+        Self { registry, consumed }
+    }
+
+    /// Verify `claim` and mark its deposit consumed. The caller may mint
+    /// `amount` of `asset` to `recipient` only on `Ok`.
+    pub fn verify_inbound(&self, claim: &TransferClaim) -> Result<VerifiedDeposit, InboundError> {
+        let config = self.registry.get(&claim.chain)
+            .ok_or_else(|| InboundError::UnknownChain(claim.chain.canonical_name().to_string()))?;
+        let contract = config.lock_contract(&claim.asset).ok_or_else(|| {
+            InboundError::UnknownAsset(claim.chain.canonical_name().to_string(), claim.asset.clone())
+        })?;
+
+        let block_number = match (&claim.proof, config.mode) {
+            (InboundProof::Receipt(proof), InboundMode::ReceiptProof) => {
+                verify_receipt_proof(config, contract, claim, proof)?;
+                Some(proof.header.block_number)
+            }
+            (InboundProof::Attested(atts), InboundMode::AttestationOnly) => {
+                let valid = count_valid(&config.verifiers, &claim.digest(), atts);
+                if valid < config.quorum {
+                    return Err(InboundError::DepositNotAttested { valid, required: config.quorum });
+                }
+                None
+            }
+            _ => return Err(InboundError::WrongProofType),
+        };
+
+        self.consumed.spend(claim.consumed_key()).map_err(|e| match e {
+            NullifierError::AlreadySpent(_) => InboundError::AlreadyConsumed,
+            NullifierError::Store(msg) => InboundError::Store(msg),
+        })?;
+
+        log::info!(
+            "[Inbound] Verified {} {} deposit {}#{} for {}",
+            claim.amount, claim.asset, hex::encode(claim.tx_hash), claim.log_index, claim.recipient
+        );
+        Ok(VerifiedDeposit {
+            chain:        claim.chain,
+            tx_hash:      claim.tx_hash,
+            log_index:    claim.log_index,
+            recipient:    claim.recipient.clone(),
+            asset:        claim.asset.clone(),
+            amount:       claim.amount,
+            block_number,
+        })
+    }
+
+    /// `true` if this deposit has already minted.
+    pub fn is_consumed(&self, claim: &TransferClaim) -> bool {
+        self.consumed.is_spent(&claim.consumed_key())
+    }
+
+    /// Verify that `claim.message` was sent on its source chain and mark it
+    /// consumed. The caller may deliver the message only on `Ok`.
+    pub fn verify_message(&self, claim: &MessageClaim) -> Result<(), InboundError> {
+        let chain = claim.message.source.chain;
+        let config = self.registry.get(&chain)
+            .ok_or_else(|| InboundError::UnknownChain(chain.canonical_name().to_string()))?;
+
+        match (&claim.proof, config.mode) {
+            (InboundProof::Receipt(proof), InboundMode::ReceiptProof) => {
+                let receipt = prove_receipt(config, chain, &claim.tx_hash, proof)?;
+                check_message_log(&receipt, claim)?;
+            }
+            (InboundProof::Attested(atts), InboundMode::AttestationOnly) => {
+                let valid = count_valid(&config.verifiers, &claim.digest(), atts);
+                if valid < config.quorum {
+                    return Err(InboundError::DepositNotAttested { valid, required: config.quorum });
+                }
+            }
+            _ => return Err(InboundError::WrongProofType),
+        }
+
+        self.consumed.spend(claim.consumed_key()).map_err(|e| match e {
+            NullifierError::AlreadySpent(_) => InboundError::AlreadyConsumed,
+            NullifierError::Store(msg) => InboundError::Store(msg),
+        })?;
+        log::info!(
+            "[Inbound] Verified message {} #{} from {}",
+            hex::encode(claim.message.commitment()), claim.message.sequence, claim.message.source
+        );
+        Ok(())
+    }
+
+    /// `true` if this message has already been accepted.
+    pub fn is_message_consumed(&self, claim: &MessageClaim) -> bool {
+        self.consumed.is_spent(&claim.consumed_key())
+    }
+}
+
+/// Distinct registered verifiers with a valid signature over `digest`.
+pub(crate) fn count_valid(verifiers: &[[u8; 32]], digest: &[u8; 32], atts: &[Attestation]) -> usize {
+    atts.iter()
+        .filter(|a| verifiers.contains(&a.verifier))
+        .filter(|a| ClassicalKeyPair::verify(&a.verifier, digest, &a.signature).unwrap_or(false))
+        .map(|a| a.verifier)
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+fn verify_receipt_proof(
+    config:   &InboundChainConfig,
+    contract: &LockContract,
+    claim:    &TransferClaim,
+    proof:    &ReceiptProof,
+) -> Result<(), InboundError> {
+    let receipt = prove_receipt(config, claim.chain, &claim.tx_hash, proof)?;
+    check_lock_log(&receipt, config, contract, claim)
+}
+
+/// Check the header quorum and both trie proofs; returns the proven receipt.
+fn prove_receipt(
+    config:  &InboundChainConfig,
+    chain:   ChainId,
+    tx_hash: &[u8; 32],
+    proof:   &ReceiptProof,
+) -> Result<Vec<u8>, InboundError> {
+    let header = &proof.header;
+    let valid = count_valid(&config.verifiers, &header.digest(chain), &header.attestations);
+    if valid < config.quorum {
+        return Err(InboundError::HeaderNotAttested { valid, required: config.quorum });
+    }
+
+    // Bind the claimed tx hash to the transaction at `tx_index`.
+    let key = rlp_index(proof.tx_index);
+    if keccak(&proof.transaction) != *tx_hash {
+        return Err(InboundError::InvalidProof("transaction does not hash to tx_hash".into()));
+    }
+    let tx = verify_mpt_proof(header.transactions_root, &key, &proof.tx_proof)?;
+    if tx != proof.transaction {
+        return Err(InboundError::InvalidProof("transaction trie value mismatch".into()));
+    }
+
+    verify_mpt_proof(header.receipts_root, &key, &proof.receipt_proof)
+}
+
+/// Decode a successful receipt and return the `[address, topics, data]` of log `index`.
+fn receipt_log<'a>(
+    receipt:  &'a [u8],
+    index:    u32,
+    mismatch: impl Fn(&str) -> InboundError,
+) -> Result<Vec<Rlp<'a>>, InboundError> {
+    // EIP-2718 typed receipts carry a one-byte type prefix.
+    let body = match receipt.first() {
+        Some(&t) if t < 0x80 => &receipt[1..],
+        _ => receipt,
+    };
+    let fields = rlp_decode(body)?.list()?.to_vec();
+    if fields.len() != 4 {
+        return Err(InboundError::Rlp("receipt must have 4 fields".into()));
+    }
+    if fields[0].bytes()? != [1u8] {
+        return Err(mismatch("transaction reverted"));
+    }
+    let logs = fields[3].list()?;
+    let log = logs.get(index as usize).ok_or_else(|| mismatch("log index out of range"))?.list()?;
+    if log.len() != 3 {
+        return Err(InboundError::Rlp("log must have 3 fields".into()));
+    }
+    Ok(log.to_vec())
+}
+
+/// Match message log `claim.log_index` against the claimed message.
+fn check_message_log(receipt: &[u8], claim: &MessageClaim) -> Result<(), InboundError> {
+    let mismatch = |m: &str| InboundError::MessageMismatch(m.to_string());
+    let log = receipt_log(receipt, claim.log_index, mismatch)?;
+
+    let sender = hex::decode(claim.message.source.address.trim_start_matches("0x"))
+        .map_err(|_| mismatch("source is not a hex contract address"))?;
+    if log[0].bytes()? != sender.as_slice() {
+        return Err(mismatch("log not emitted by the source contract"));
+    }
+    let topics = log[1].list()?;
+    if topics.len() < 2 || topics[0].bytes()? != message_topic() {
+        return Err(mismatch("not a message event"));
+    }
+    if topics[1].bytes()? != claim.message.commitment() {
+        return Err(mismatch("message commitment"));
+    }
+    Ok(())
+}
+
+/// Decode the receipt and match log `claim.log_index` against the claim.
+fn check_lock_log(
+    receipt:  &[u8],
+    config:   &InboundChainConfig,
+    contract: &LockContract,
+    claim:    &TransferClaim,
+) -> Result<(), InboundError> {
+    let mismatch = |m: &str| InboundError::EventMismatch(m.to_string());
+    let log = receipt_log(receipt, claim.log_index, mismatch)?;
+
+    if log[0].bytes()? != contract.address.as_slice() {
+        return Err(mismatch("log not emitted by the asset's lock contract"));
+    }
+    let topics = log[1].list()?;
+    if topics.len() < 2 || topics[0].bytes()? != config.lock_topic {
+        return Err(mismatch("not a lock event"));
+    }
+    if topics[1].bytes()? != keccak(claim.recipient.as_bytes()) {
+        return Err(mismatch("recipient"));
+    }
+    let data = log[2].bytes()?;
+    if data.len() != 32 || data[..16].iter().any(|&b| b != 0) {
+        return Err(mismatch("amount word"));
+    }
+    let amount = u128::from_be_bytes(data[16..].try_into().expect("16 bytes"));
+    if amount != claim.amount {
+        return Err(mismatch("amount"));
+    }
+    Ok(())
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+// ── Merkle-Patricia proofs ────────────────────────────────────────────────────
+
+/// Trie key of the item at `index` (`rlp(index)`).
+fn rlp_index(index: u64) -> Vec<u8> {
+    match index {
+        0 => vec![0x80],
+        1..=0x7f => vec![index as u8],
+        _ => {
+            let be = index.to_be_bytes();
+            let skip = be.iter().take_while(|&&b| b == 0).count();
+            let mut out = vec![0x80 + (8 - skip) as u8];
+            out.extend_from_slice(&be[skip..]);
+            out
+        }
+    }
+}
+
+enum NodeRef<'a> {
+    Hash([u8; 32]),
+    Inline(Rlp<'a>),
+}
+
+fn child_ref<'a>(item: &Rlp<'a>) -> Result<NodeRef<'a>, InboundError> {
+    match item {
+        Rlp::Bytes(b) if b.is_empty() => Err(InboundError::InvalidProof("key not in trie".into())),
+        Rlp::Bytes(b) if b.len() == 32 => Ok(NodeRef::Hash((*b).try_into().expect("32 bytes"))),
+        Rlp::Bytes(_) => Err(InboundError::InvalidProof("bad child reference".into())),
+        Rlp::List(..) => Ok(NodeRef::Inline(item.clone())),
+    }
+}
+
+/// Walk `proof` from `root` along `key`; returns the value stored there.
+fn verify_mpt_proof(root: [u8; 32], key: &[u8], proof: &[Vec<u8>]) -> Result<Vec<u8>, InboundError> {
+    let bad = |m: &str| InboundError::InvalidProof(m.to_string());
+    let nibbles: Vec<u8> = key.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect();
+    let mut path = &nibbles[..];
+    let mut nodes = proof.iter();
+    let mut next = NodeRef::Hash(root);
+
+    loop {
+        let node = match next {
+            NodeRef::Hash(h) => {
+                let raw = nodes.next().ok_or_else(|| bad("proof ends early"))?;
+                if keccak(raw) != h {
+                    return Err(bad("node hash mismatch"));
+                }
+                rlp_decode(raw)?
+            }
+            NodeRef::Inline(item) => item,
+        };
+        let items = node.list()?;
+        match items.len() {
+            17 => {
+                let Some((&nibble, rest)) = path.split_first() else {
+                    let value = items[16].bytes()?;
+                    if value.is_empty() {
+                        return Err(bad("key not in trie"));
+                    }
+                    return Ok(value.to_vec());
+                };
+                next = child_ref(&items[nibble as usize])?;
+                path = rest;
+            }
+            2 => {
+                let hp = items[0].bytes()?;
+                let (&flag, tail) = hp.split_first().ok_or_else(|| bad("empty node path"))?;
+                let is_leaf = flag & 0x20 != 0;
+                let mut node_path = Vec::with_capacity(tail.len() * 2 + 1);
+                if flag & 0x10 != 0 {
+                    node_path.push(flag & 0x0f);
+                }
+                node_path.extend(tail.iter().flat_map(|b| [b >> 4, b & 0x0f]));
+                if !path.starts_with(&node_path) {
+                    return Err(bad("key not in trie"));
+                }
+                path = &path[node_path.len()..];
+                if is_leaf {
+                    if !path.is_empty() {
+                        return Err(bad("key not in trie"));
+                    }
+                    return Ok(items[1].bytes()?.to_vec());
+                }
+                next = child_ref(&items[1])?;
+            }
+            _ => return Err(bad("node is neither branch nor leaf/extension")),
+        }
+    }
+}
+
+// ── Minimal RLP decoder ───────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+enum Rlp<'a> {
+    Bytes(&'a [u8]),
+    List(Vec<Rlp<'a>>),
+}
+
+impl<'a> Rlp<'a> {
+    fn bytes(&self) -> Result<&'a [u8], InboundError> {
+        match self {
+            Rlp::Bytes(b) => Ok(b),
+            Rlp::List(_) => Err(InboundError::Rlp("expected bytes, found list".into())),
+        }
+    }
+
+    fn list(&self) -> Result<&[Rlp<'a>], InboundError> {
+        match self {
+            Rlp::List(items) => Ok(items),
+            Rlp::Bytes(_) => Err(InboundError::Rlp("expected list, found bytes".into())),
+        }
+    }
+}
+
+fn rlp_decode(buf: &[u8]) -> Result<Rlp<'_>, InboundError> {
+    let (item, rest) = rlp_item(buf)?;
+    if !rest.is_empty() {
+        return Err(InboundError::Rlp("trailing bytes".into()));
+    }
+    Ok(item)
+}
+
+fn rlp_item(buf: &[u8]) -> Result<(Rlp<'_>, &[u8]), InboundError> {
+    let (&prefix, rest) = buf.split_first().ok_or_else(|| InboundError::Rlp("unexpected end".into()))?;
+    match prefix {
+        0x00..=0x7f => Ok((Rlp::Bytes(&buf[..1]), rest)),
+        0x80..=0xb7 => {
+            let (data, rest) = rlp_take(rest, (prefix - 0x80) as usize)?;
+            Ok((Rlp::Bytes(data), rest))
+        }
+        0xb8..=0xbf => {
+            let (len, rest) = rlp_long_len(rest, (prefix - 0xb7) as usize)?;
+            let (data, rest) = rlp_take(rest, len)?;
+            Ok((Rlp::Bytes(data), rest))
+        }
+        0xc0..=0xf7 => {
+            let (data, rest) = rlp_take(rest, (prefix - 0xc0) as usize)?;
+            Ok((Rlp::List(rlp_list(data)?), rest))
+        }
+        0xf8..=0xff => {
+            let (len, rest) = rlp_long_len(rest, (prefix - 0xf7) as usize)?;
+            let (data, rest) = rlp_take(rest, len)?;
+            Ok((Rlp::List(rlp_list(data)?), rest))
+        }
+    }
+}
+
+fn rlp_list(mut data: &[u8]) -> Result<Vec<Rlp<'_>>, InboundError> {
+    let mut items = Vec::new();
+    while !data.is_empty() {
+        let (item, rest) = rlp_item(data)?;
+        items.push(item);
+        data = rest;
+    }
+    Ok(items)
+}
+
+fn rlp_long_len(buf: &[u8], len_of_len: usize) -> Result<(usize, &[u8]), InboundError> {
+    if len_of_len > std::mem::size_of::<usize>() {
+        return Err(InboundError::Rlp("length too large".into()));
+    }
+    let (len, rest) = rlp_take(buf, len_of_len)?;
+    Ok((len.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize), rest))
+}
+
+fn rlp_take(buf: &[u8], n: usize) -> Result<(&[u8], &[u8]), InboundError> {
+    if buf.len() < n {
+        return Err(InboundError::Rlp("unexpected end".into()));
+    }
+    Ok(buf.split_at(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK_CONTRACT: [u8; 20] = [0xE7; 20];
+    const LOCK_TOPIC: [u8; 32] = [0x10; 32];
+
+    // ── RLP encoding for fixtures ─────────────────────────────────────────────
+
+    fn enc_len(short: u8, len: usize) -> Vec<u8> {
+        if len <= 55 {
+            return vec![short + len as u8];
+        }
+        let be = len.to_be_bytes();
+        let skip = be.iter().take_while(|&&b| b == 0).count();
+        let mut out = vec![short + 55 + (be.len() - skip) as u8];
+        out.extend_from_slice(&be[skip..]);
+        out
+    }
+
+    fn enc_bytes(b: &[u8]) -> Vec<u8> {
+        if b.len() == 1 && b[0] < 0x80 {
+            return b.to_vec();
+        }
+        let mut out = enc_len(0x80, b.len());
+        out.extend_from_slice(b);
+        out
+    }
+
+    fn enc_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        let mut out = enc_len(0xc0, payload.len());
+        out.extend(payload);
+        out
+    }
+
+    /// Trie holding `values[0]` at key rlp(1) and `values[1]` at rlp(2):
+    /// extension [0] → branch → two leaves. Returns the root and the proof for key rlp(1).
+    fn two_item_trie(values: [&[u8]; 2]) -> ([u8; 32], Vec<Vec<u8>>) {
+        let leaves: Vec<Vec<u8>> = values.iter()
+            .map(|v| enc_list(&[enc_bytes(&[0x20]), enc_bytes(v)]))
+            .collect();
+        let mut branch_items = vec![enc_bytes(&[]); 17];
+        branch_items[1] = enc_bytes(&keccak(&leaves[0]));
+        branch_items[2] = enc_bytes(&keccak(&leaves[1]));
+        let branch = enc_list(&branch_items);
+        let ext = enc_list(&[enc_bytes(&[0x10]), enc_bytes(&keccak(&branch))]);
+        (keccak(&ext), vec![ext, branch, leaves[0].clone()])
+    }
+
+    fn receipt(recipient: &str, amount: u128) -> Vec<u8> {
+        let mut word = [0u8; 32];
+        word[16..].copy_from_slice(&amount.to_be_bytes());
+        let log = enc_list(&[
+            enc_bytes(&LOCK_CONTRACT),
+            enc_list(&[enc_bytes(&LOCK_TOPIC), enc_bytes(&keccak(recipient.as_bytes()))]),
+            enc_bytes(&word),
+        ]);
+        let mut out = vec![0x02]; // EIP-1559 typed receipt
+        out.extend(enc_list(&[enc_bytes(&[1]), enc_bytes(&[0x52, 0x08]), enc_bytes(&[0u8; 256]), enc_list(&[log])]));
+        out
+    }
+
+    struct Fixture {
+        verifiers: Vec<ClassicalKeyPair>,
+        verifier:  InboundVerifier,
+    }
+
+    fn fixture() -> Fixture {
+        let verifiers: Vec<_> = (0..4).map(|_| ClassicalKeyPair::generate()).collect();
+        let mut registry = InboundRegistry::new();
+        registry.register(InboundChainConfig {
+            chain:          ChainId::Ethereum,
+            mode:           InboundMode::ReceiptProof,
+            verifiers:      verifiers.iter().map(|k| k.public_key_bytes()).collect(),
+            quorum:         3,
+            lock_contracts: vec![LockContract { address: LOCK_CONTRACT.to_vec(), asset: "wETH".into() }],
+            lock_topic:     LOCK_TOPIC,
+        }).unwrap();
+        Fixture { verifiers, verifier: InboundVerifier::new(registry, GlobalNullifierSet::open_temp()) }
+    }
+
+    fn claim(signers: &[&ClassicalKeyPair]) -> TransferClaim {
+        let tx = b"\x02signed-lock-transaction".to_vec();
+        let (transactions_root, tx_proof) = two_item_trie([&tx, b"other transaction"]);
+        let (receipts_root, receipt_proof) = two_item_trie([&receipt("BLEEP1alice", 5_000), &receipt("BLEEP1bob", 1)]);
+        let mut header = AttestedHeader {
+            block_number: 19_000_000,
+            block_hash: [0xBB; 32],
+            transactions_root,
+            receipts_root,
+            attestations: vec![],
+        };
+        let digest = header.digest(ChainId::Ethereum);
+        header.attestations = signers.iter()
+            .map(|k| Attestation { verifier: k.public_key_bytes(), signature: k.sign(&digest) })
+            .collect();
+        TransferClaim {
+            chain:     ChainId::Ethereum,
+            tx_hash:   keccak(&tx),
+            log_index: 0,
+            recipient: "BLEEP1alice".into(),
+            asset:     "wETH".into(),
+            amount:    5_000,
+            proof:     InboundProof::Receipt(ReceiptProof { header, tx_index: 1, transaction: tx, tx_proof, receipt_proof }),
+        }
+    }
+
+    #[test]
+    fn valid_receipt_proof_verifies() {
+        let f = fixture();
+        let c = claim(&[&f.verifiers[0], &f.verifiers[1], &f.verifiers[2]]);
+        let deposit = f.verifier.verify_inbound(&c).unwrap();
+        assert_eq!(deposit.amount, 5_000);
+        assert_eq!(deposit.recipient, "BLEEP1alice");
+        assert_eq!(deposit.block_number, Some(19_000_000));
+        assert!(f.verifier.is_consumed(&c));
+    }
+
+    #[test]
+    fn unattested_header_rejected() {
+        let f = fixture();
+        // Two registered verifiers plus an outsider: below the quorum of 3.
+        let outsider = ClassicalKeyPair::generate();
+        let c = claim(&[&f.verifiers[0], &f.verifiers[1], &outsider]);
+        assert_eq!(
+            f.verifier.verify_inbound(&c),
+            Err(InboundError::HeaderNotAttested { valid: 2, required: 3 })
+        );
+        assert!(!f.verifier.is_consumed(&c));
+    }
+
+    #[test]
+    fn replayed_deposit_rejected() {
+        let f = fixture();
+        let c = claim(&[&f.verifiers[1], &f.verifiers[2], &f.verifiers[3]]);
+        f.verifier.verify_inbound(&c).unwrap();
+        assert_eq!(f.verifier.verify_inbound(&c), Err(InboundError::AlreadyConsumed));
+    }
+
+    #[test]
+    fn claim_must_match_lock_log() {
+        let f = fixture();
+        let quorum = [&f.verifiers[0], &f.verifiers[1], &f.verifiers[2]];
+        let mut c = claim(&quorum);
+        c.amount = 50_000;
+        assert!(matches!(f.verifier.verify_inbound(&c), Err(InboundError::EventMismatch(_))));
+        let mut c = claim(&quorum);
+        if let InboundProof::Receipt(p) = &mut c.proof {
+            p.receipt_proof[2][5] ^= 1;
+        }
+        assert!(matches!(f.verifier.verify_inbound(&c), Err(InboundError::InvalidProof(_))));
+    }
+
+    #[test]
+    fn attestation_only_chains_need_higher_quorum() {
+        let keys: Vec<[u8; 32]> = (0..10u8).map(|i| ClassicalKeyPair::from_bytes(&[i + 1; 32]).unwrap().public_key_bytes()).collect();
+        let config = |mode, quorum| InboundChainConfig {
+            chain: ChainId::Solana,
+            mode,
+            verifiers: keys.clone(),
+            quorum,
+            lock_contracts: vec![],
+            lock_topic: [0; 32],
+        };
+        let mut registry = InboundRegistry::new();
+        assert!(registry.register(config(InboundMode::ReceiptProof, 7)).is_ok());
+        assert!(registry.register(config(InboundMode::AttestationOnly, 7)).is_err());
+        assert!(registry.register(config(InboundMode::AttestationOnly, 9)).is_ok());
+    }
+}
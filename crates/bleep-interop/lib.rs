@@ -44,10 +44,12 @@ pub use bleep_connect_types::{
     Vote, VoteChoice, VoterType,
     SocialProposal, ProposalType, Evidence, EvidenceType,
     BleepConnectError, BleepConnectResult,
+    CrossChainMessage, MessageChannel, DeliveryReceipt, DeliveryStatus,
 };
 
-pub use bleep_connect_adapters::{ChainAdapter, AdapterRegistry};
+pub use bleep_connect_adapters::{ChainAdapter, AdapterRegistry, ValidationIssue};
 pub use bleep_connect_adapters::{SepoliaRelay, SepoliaRelayTx, RelayStatus, SEPOLIA_CHAIN_ID, SEPOLIA_BLEEP_FULFILL_ADDR};
+pub use bleep_connect_adapters::{AdapterMode, ChainModeConfig, SandboxAdapter, SandboxConfig, SandboxState};
 
 pub use bleep_connect_core::{BleepConnectOrchestrator, BleepConnectBuilder, BleepConnectConfig};
 
@@ -228,4 +230,16 @@ pub use layer3_bridge::{
 pub mod nullifier_store;
 pub use nullifier_store::{GlobalNullifierSet, NullifierError};
 
+pub mod inbound;
+pub use inbound::{
+    InboundVerifier, InboundRegistry, InboundChainConfig, InboundMode, InboundError,
+    TransferClaim, VerifiedDeposit, MessageClaim,
+};
+
+pub mod messaging;
+pub use messaging::{
+    Outbox, MessageAttestor, AttestedMessage, MessageRelayer, MessageInbox,
+    MessageAdapter, ContractCaller, MessageError, message_tx,
+};
+
 // ── Hardening-phase modules ────────────────────────────────────────────────────
@@ -0,0 +1,575 @@
+//! bleep-interop/messaging.rs
+//! Cross-chain message passing: arbitrary contract calls between chains
+//!
+//! Outbound (BLEEP → remote chain):
+//!
+//! ```text
+//! Outbox::send             assigns the next sequence on the message's channel
+//! message_tx               zero-value transaction to `bleep:xmsg/<commitment>`;
+//!                          the block's transaction Merkle root commits it
+//! MessageAttestor::attest  a BLEEP verifier finds the transaction in the block
+//!                          and signs (commitment, height, tx root)
+//! MessageRelayer::relay    checks the verifier quorum, then has the destination
+//!                          chain's MessageAdapter invoke the target contract
+//! ```
+//!
+//! Inbound (remote chain → BLEEP): [`MessageInbox::receive`] verifies a
+//! [`MessageClaim`] with the inbound-proof machinery, which also rejects
+//! replays, and calls the destination BLEEP contract through a
+//! [`ContractCaller`] — the VM `Executor` on a node.
+//!
+//! A channel is one `(source contract, destination chain, destination
+//! contract)` triple. Both directions deliver a channel strictly in sequence
+//! order, holding messages that arrive ahead of a gap until it fills. A failed
+//! destination call produces a `DeliveryStatus::Failed` receipt and the
+//! channel moves on; every receipt is also published to subscribers.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::{broadcast, Mutex};
+
+use bleep_connect_crypto::{sha256, ClassicalKeyPair};
+use bleep_connect_types::{
+    ChainId, CrossChainMessage, DeliveryReceipt, DeliveryStatus, MessageChannel, UniversalAddress,
+};
+use bleep_core::block::{Block, ChainId as CoreChainId, Transaction};
+use bleep_vm::execution::executor::Executor;
+use bleep_vm::intent::{ContractCallBuilder, Intent};
+use bleep_vm::types::ChainId as VmChainId;
+
+use crate::inbound::{count_valid, Attestation, InboundError, InboundVerifier, MessageClaim};
+
+/// Receiver prefix of the transactions that commit outbound messages.
+pub const MESSAGE_TX_PREFIX: &str = "bleep:xmsg/";
+
+const ATTEST_DOMAIN: &[u8] = b"BLEEP-XMSG-ATTEST-V1";
+
+/// Receipts buffered per subscriber before the slowest one lags.
+const RECEIPT_CAPACITY: usize = 256;
+
+// ── Errors ────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MessageError {
+    #[error("block {0} transactions do not match its Merkle root")]
+    BadBlock(u64),
+    #[error("message {0} is not committed in block {1}")]
+    NotCommitted(String, u64),
+    #[error("message not attested: {valid} of {required} verifier signatures")]
+    NotAttested { valid: usize, required: usize },
+    #[error("no message adapter for {0}")]
+    NoAdapter(String),
+    #[error("message is addressed to {0}, not BLEEP")]
+    WrongDestination(String),
+    #[error("sequence {sequence} already received on its channel")]
+    Duplicate { sequence: u64 },
+    #[error(transparent)]
+    Inbound(#[from] InboundError),
+}
+
+// ── Outbound: commitment ──────────────────────────────────────────────────────
+
+/// Sequence allocator for every channel with a BLEEP source.
+#[derive(Debug, Default)]
+pub struct Outbox {
+    next: std::sync::Mutex<HashMap<MessageChannel, u64>>,
+}
+
+impl Outbox {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the next message on the `(source, destination)` channel.
+    /// Commit it with [`message_tx`] in the order `send` returned them.
+    pub fn send(
+        &self,
+        source:               UniversalAddress,
+        destination_chain:    ChainId,
+        destination_contract: &str,
+        payload:              Vec<u8>,
+        gas_budget:           u64,
+    ) -> CrossChainMessage {
+        let mut message = CrossChainMessage {
+            source,
+            destination_chain,
+            destination_contract: destination_contract.to_string(),
+            payload,
+            gas_budget,
+            sequence: 0,
+        };
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let sequence = next.entry(message.channel()).or_insert(0);
+        message.sequence = *sequence;
+        *sequence += 1;
+        message
+    }
+}
+
+/// Receiver of the transaction committing `message`.
+pub fn message_receiver(message: &CrossChainMessage) -> String {
+    format!("{}{}", MESSAGE_TX_PREFIX, hex::encode(message.commitment()))
+}
+
+/// `true` if `receiver` marks a message commitment rather than a transfer.
+pub fn is_message_tx(receiver: &str) -> bool {
+    receiver.starts_with(MESSAGE_TX_PREFIX)
+}
+
+/// Zero-value transaction from the source contract that commits `message`
+/// in a block of the BLEEP network `chain_id`.
+pub fn message_tx(message: &CrossChainMessage, chain_id: CoreChainId, timestamp: u64) -> Transaction {
+    Transaction {
+        sender:    message.source.address.clone(),
+        receiver:  message_receiver(message),
+        amount:    0,
+        timestamp,
+        signature: Vec::new(),
+        max_fee:   0,
+        tip:       0,
+        nonce:     0,
+        chain_id,
+    }
+}
+
+// ── Outbound: attestation ─────────────────────────────────────────────────────
+
+/// A committed message with BLEEP verifier signatures, as handed to relayers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttestedMessage {
+    pub message:      CrossChainMessage,
+    /// Height of the BLEEP block that commits the message.
+    pub height:       u64,
+    /// That block's transaction Merkle root.
+    pub tx_root:      String,
+    pub attestations: Vec<Attestation>,
+}
+
+impl AttestedMessage {
+    /// Digest the verifiers sign.
+    pub fn digest(&self) -> [u8; 32] {
+        attest_digest(&self.message, self.height, &self.tx_root)
+    }
+}
+
+fn attest_digest(message: &CrossChainMessage, height: u64, tx_root: &str) -> [u8; 32] {
+    let mut data = ATTEST_DOMAIN.to_vec();
+    data.extend_from_slice(&message.commitment());
+    data.extend_from_slice(&height.to_be_bytes());
+    data.extend_from_slice(tx_root.as_bytes());
+    sha256(&data)
+}
+
+/// A BLEEP verifier's signing side of the outbound pipeline.
+pub struct MessageAttestor {
+    key: ClassicalKeyPair,
+}
+
+impl MessageAttestor {
+    pub fn new(key: ClassicalKeyPair) -> Self {
+        Self { key }
+    }
+
+    /// Sign `message` if `block` is internally consistent and carries its
+    /// commitment transaction from the source contract.
+    pub fn attest(&self, block: &Block, message: &CrossChainMessage) -> Result<Attestation, MessageError> {
+        if Block::calculate_merkle_root(&block.transactions) != block.merkle_root {
+            return Err(MessageError::BadBlock(block.index));
+        }
+        let receiver = message_receiver(message);
+        let committed = block.transactions.iter()
+            .any(|tx| tx.receiver == receiver && tx.sender == message.source.address);
+        if !committed {
+            return Err(MessageError::NotCommitted(hex::encode(message.commitment()), block.index));
+        }
+        let digest = attest_digest(message, block.index, &block.merkle_root);
+        Ok(Attestation { verifier: self.key.public_key_bytes(), signature: self.key.sign(&digest) })
+    }
+}
+
+// ── Delivery ──────────────────────────────────────────────────────────────────
+
+/// Destination side of outbound delivery for one chain.
+#[async_trait]
+pub trait MessageAdapter: Send + Sync {
+    fn chain_id(&self) -> ChainId;
+
+    /// Invoke `message.destination_contract` with `message.payload` within
+    /// `message.gas_budget`; returns the call's output.
+    async fn invoke(&self, message: &CrossChainMessage) -> Result<Vec<u8>, String>;
+}
+
+/// Call interface of the BLEEP VM, as used for inbound delivery.
+#[async_trait]
+pub trait ContractCaller: Send + Sync {
+    async fn call(&self, contract: &str, payload: &[u8], gas_budget: u64) -> Result<Vec<u8>, String>;
+}
+
+#[async_trait]
+impl ContractCaller for Executor {
+    async fn call(&self, contract: &str, payload: &[u8], gas_budget: u64) -> Result<Vec<u8>, String> {
+        let bytes = hex::decode(contract.trim_start_matches("0x"))
+            .ok()
+            .filter(|b| b.len() <= 32)
+            .ok_or_else(|| format!("{} is not a contract address", contract))?;
+        let mut address = [0u8; 32];
+        address[32 - bytes.len()..].copy_from_slice(&bytes);
+        let kind = ContractCallBuilder::new(address).calldata(payload.to_vec()).gas(gas_budget).build();
+        match self.execute(&Intent::new_unsigned(kind, VmChainId::Bleep)).await {
+            Ok(o) if o.success() => Ok(o.output().to_vec()),
+            Ok(o) => Err(o.routed_result.result.revert_reason.clone().unwrap_or_else(|| "VM reverted".into())),
+            Err(e) => Err(format!("VM error: {}", e)),
+        }
+    }
+}
+
+/// Inbound delivery goes through the same channel queues as outbound.
+struct CallerAdapter<'a>(&'a dyn ContractCaller);
+
+#[async_trait]
+impl MessageAdapter for CallerAdapter<'_> {
+    fn chain_id(&self) -> ChainId {
+        ChainId::BLEEP
+    }
+
+    async fn invoke(&self, message: &CrossChainMessage) -> Result<Vec<u8>, String> {
+        self.0.call(&message.destination_contract, &message.payload, message.gas_budget).await
+    }
+}
+
+/// Next sequence of one channel plus the messages received ahead of it.
+#[derive(Debug, Default)]
+struct ChannelQueue {
+    next:    u64,
+    pending: BTreeMap<u64, CrossChainMessage>,
+}
+
+impl ChannelQueue {
+    fn push(&mut self, message: CrossChainMessage) -> Result<(), MessageError> {
+        let sequence = message.sequence;
+        if sequence < self.next || self.pending.contains_key(&sequence) {
+            return Err(MessageError::Duplicate { sequence });
+        }
+        self.pending.insert(sequence, message);
+        Ok(())
+    }
+
+    fn pop_ready(&mut self) -> Option<CrossChainMessage> {
+        let message = self.pending.remove(&self.next)?;
+        self.next += 1;
+        Some(message)
+    }
+}
+
+/// In-order delivery shared by the relayer and the inbox.
+struct Channels {
+    queues:   Mutex<HashMap<MessageChannel, ChannelQueue>>,
+    receipts: broadcast::Sender<DeliveryReceipt>,
+}
+
+impl Channels {
+    fn new() -> Self {
+        Self { queues: Mutex::new(HashMap::new()), receipts: broadcast::channel(RECEIPT_CAPACITY).0 }
+    }
+
+    /// Queue `message` and deliver everything on its channel that is now in
+    /// order. Returns the receipts of the messages delivered by this call.
+    async fn deliver(
+        &self,
+        message: CrossChainMessage,
+        adapter: &dyn MessageAdapter,
+    ) -> Result<Vec<DeliveryReceipt>, MessageError> {
+        // Held across delivery so a channel never has two calls in flight.
+        let mut queues = self.queues.lock().await;
+        let queue = queues.entry(message.channel()).or_default();
+        queue.push(message)?;
+
+        let mut receipts = Vec::new();
+        while let Some(next) = queue.pop_ready() {
+            let status = match adapter.invoke(&next).await {
+                Ok(output) => DeliveryStatus::Delivered { output },
+                Err(reason) => {
+                    log::warn!(
+                        "[Messaging] {} #{} to {}:{} failed: {}",
+                        next.source, next.sequence, adapter.chain_id().canonical_name(),
+                        next.destination_contract, reason
+                    );
+                    DeliveryStatus::Failed { reason }
+                }
+            };
+            let receipt = DeliveryReceipt {
+                message_id: next.commitment(),
+                channel:    next.channel(),
+                sequence:   next.sequence,
+                status,
+            };
+            // No subscribers is fine; the caller still gets the receipt.
+            let _ = self.receipts.send(receipt.clone());
+            receipts.push(receipt);
+        }
+        Ok(receipts)
+    }
+}
+
+// ── Outbound: relaying ────────────────────────────────────────────────────────
+
+/// Carries attested BLEEP messages to destination adapters.
+pub struct MessageRelayer {
+    /// Ed25519 keys of the BLEEP verifiers.
+    verifiers: Vec<[u8; 32]>,
+    quorum:    usize,
+    adapters:  HashMap<ChainId, Arc<dyn MessageAdapter>>,
+    channels:  Channels,
+}
+
+impl MessageRelayer {
+    pub fn new(verifiers: Vec<[u8; 32]>, quorum: usize) -> Self {
+        Self { verifiers, quorum, adapters: HashMap::new(), channels: Channels::new() }
+    }
+
+    pub fn with_adapter(mut self, adapter: Arc<dyn MessageAdapter>) -> Self {
+        self.adapters.insert(adapter.chain_id(), adapter);
+        self
+    }
+
+    /// Delivery receipts of every message this relayer delivers.
+    pub fn subscribe(&self) -> broadcast::Receiver<DeliveryReceipt> {
+        self.channels.receipts.subscribe()
+    }
+
+    /// Check `attested` against the verifier quorum and deliver it, plus any
+    /// later messages of its channel it was holding up.
+    pub async fn relay(&self, attested: &AttestedMessage) -> Result<Vec<DeliveryReceipt>, MessageError> {
+        let message = &attested.message;
+        let adapter = self.adapters.get(&message.destination_chain)
+            .ok_or_else(|| MessageError::NoAdapter(message.destination_chain.canonical_name().to_string()))?;
+        let valid = count_valid(&self.verifiers, &attested.digest(), &attested.attestations);
+        if valid < self.quorum {
+            return Err(MessageError::NotAttested { valid, required: self.quorum });
+        }
+        self.channels.deliver(message.clone(), adapter.as_ref()).await
+    }
+}
+
+// ── Inbound ───────────────────────────────────────────────────────────────────
+
+/// Delivers proven remote-chain messages to BLEEP contracts.
+pub struct MessageInbox {
+    verifier: Arc<InboundVerifier>,
+    caller:   Arc<dyn ContractCaller>,
+    channels: Channels,
+}
+
+impl MessageInbox {
+    pub fn new(verifier: Arc<InboundVerifier>, caller: Arc<dyn ContractCaller>) -> Self {
+        Self { verifier, caller, channels: Channels::new() }
+    }
+
+    /// Delivery receipts of every message this inbox delivers.
+    pub fn subscribe(&self) -> broadcast::Receiver<DeliveryReceipt> {
+        self.channels.receipts.subscribe()
+    }
+
+    /// Verify `claim`, consuming it, and deliver it plus any later messages
+    /// of its channel it was holding up. A replayed claim fails with
+    /// `InboundError::AlreadyConsumed`.
+    pub async fn receive(&self, claim: &MessageClaim) -> Result<Vec<DeliveryReceipt>, MessageError> {
+        let message = &claim.message;
+        if message.destination_chain != ChainId::BLEEP {
+            return Err(MessageError::WrongDestination(message.destination_chain.canonical_name().to_string()));
+        }
+        self.verifier.verify_message(claim)?;
+        self.channels.deliver(message.clone(), &CallerAdapter(self.caller.as_ref())).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inbound::{InboundChainConfig, InboundMode, InboundProof, InboundRegistry};
+    use crate::nullifier_store::GlobalNullifierSet;
+
+    /// Destination chain recording every call; payloads starting with `revert` fail.
+    #[derive(Default)]
+    struct MockChain {
+        calls: std::sync::Mutex<Vec<CrossChainMessage>>,
+    }
+
+    #[async_trait]
+    impl MessageAdapter for MockChain {
+        fn chain_id(&self) -> ChainId {
+            ChainId::Ethereum
+        }
+
+        async fn invoke(&self, message: &CrossChainMessage) -> Result<Vec<u8>, String> {
+            tokio::task::yield_now().await;
+            self.calls.lock().unwrap().push(message.clone());
+            if message.payload.starts_with(b"revert") {
+                return Err("execution reverted".into());
+            }
+            Ok(sha256(&message.payload).to_vec())
+        }
+    }
+
+    #[derive(Default)]
+    struct MockVm {
+        calls: std::sync::Mutex<Vec<(String, Vec<u8>)>>,
+    }
+
+    #[async_trait]
+    impl ContractCaller for MockVm {
+        async fn call(&self, contract: &str, payload: &[u8], _gas_budget: u64) -> Result<Vec<u8>, String> {
+            self.calls.lock().unwrap().push((contract.to_string(), payload.to_vec()));
+            Ok(Vec::new())
+        }
+    }
+
+    fn governance() -> UniversalAddress {
+        UniversalAddress::new(ChainId::BLEEP, "BLEEP1governance".into())
+    }
+
+    struct Outbound {
+        attestors: Vec<MessageAttestor>,
+        relayer:   MessageRelayer,
+        chain:     Arc<MockChain>,
+    }
+
+    /// Four BLEEP verifiers, quorum three, relaying to a mock Ethereum.
+    fn outbound() -> Outbound {
+        let keys: Vec<_> = (0..4).map(|_| ClassicalKeyPair::generate()).collect();
+        let chain = Arc::new(MockChain::default());
+        let relayer = MessageRelayer::new(keys.iter().map(|k| k.public_key_bytes()).collect(), 3)
+            .with_adapter(chain.clone());
+        Outbound { attestors: keys.into_iter().map(MessageAttestor::new).collect(), relayer, chain }
+    }
+
+    /// Commit `messages` in one block at `height` and have `attestors` sign each.
+    fn commit(attestors: &[MessageAttestor], height: u64, messages: &[CrossChainMessage]) -> Vec<AttestedMessage> {
+        let txs = messages.iter().enumerate().map(|(i, m)| message_tx(m, CoreChainId::NONE, 1_700_000_000 + i as u64)).collect();
+        let block = Block::new(height, txs, Block::genesis().compute_hash());
+        messages.iter().map(|m| AttestedMessage {
+            message:      m.clone(),
+            height,
+            tx_root:      block.merkle_root.clone(),
+            attestations: attestors.iter().map(|a| a.attest(&block, m).unwrap()).collect(),
+        }).collect()
+    }
+
+    #[tokio::test]
+    async fn outbound_message_is_attested_and_delivered() {
+        let o = outbound();
+        let outbox = Outbox::new();
+        let message = outbox.send(governance(), ChainId::Ethereum, "0x7e45", b"setFee(uint16)\x00\x19".to_vec(), 200_000);
+        let mut events = o.relayer.subscribe();
+
+        let uncommitted = outbox.send(governance(), ChainId::Ethereum, "0x7e45", b"unsent".to_vec(), 200_000);
+        let block = Block::new(7, vec![message_tx(&message, CoreChainId::NONE, 1_700_000_000)], Block::genesis().compute_hash());
+        assert!(matches!(o.attestors[0].attest(&block, &uncommitted), Err(MessageError::NotCommitted(_, 7))));
+
+        let attested = commit(&o.attestors[..3], 7, &[message.clone()]).remove(0);
+        let short = AttestedMessage { attestations: attested.attestations[..2].to_vec(), ..attested.clone() };
+        assert_eq!(o.relayer.relay(&short).await, Err(MessageError::NotAttested { valid: 2, required: 3 }));
+
+        let receipts = o.relayer.relay(&attested).await.unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].message_id, message.commitment());
+        assert_eq!(receipts[0].status, DeliveryStatus::Delivered { output: sha256(&message.payload).to_vec() });
+        assert_eq!(*o.chain.calls.lock().unwrap(), vec![message]);
+        assert_eq!(events.recv().await.unwrap(), receipts[0]);
+    }
+
+    #[tokio::test]
+    async fn replayed_inbound_message_rejected() {
+        let keys: Vec<_> = (0..3).map(|_| ClassicalKeyPair::generate()).collect();
+        let mut registry = InboundRegistry::new();
+        registry.register(InboundChainConfig {
+            chain:          ChainId::Solana,
+            mode:           InboundMode::AttestationOnly,
+            verifiers:      keys.iter().map(|k| k.public_key_bytes()).collect(),
+            quorum:         3,
+            lock_contracts: vec![],
+            lock_topic:     [0; 32],
+        }).unwrap();
+        let vm = Arc::new(MockVm::default());
+        let verifier = Arc::new(InboundVerifier::new(registry, GlobalNullifierSet::open_temp()));
+        let inbox = MessageInbox::new(verifier, vm.clone());
+
+        let mut claim = MessageClaim {
+            message: CrossChainMessage {
+                source:               UniversalAddress::solana("GovProgram1111"),
+                destination_chain:    ChainId::BLEEP,
+                destination_contract: "0xb1ee9".into(),
+                payload:              b"release(7)".to_vec(),
+                gas_budget:           50_000,
+                sequence:             0,
+            },
+            tx_hash:   [0x5A; 32],
+            log_index: 0,
+            proof:     InboundProof::Attested(vec![]),
+        };
+        let digest = claim.digest();
+        claim.proof = InboundProof::Attested(
+            keys.iter().map(|k| Attestation { verifier: k.public_key_bytes(), signature: k.sign(&digest) }).collect(),
+        );
+
+        let receipts = inbox.receive(&claim).await.unwrap();
+        assert!(receipts[0].is_delivered());
+        assert_eq!(inbox.receive(&claim).await, Err(MessageError::Inbound(InboundError::AlreadyConsumed)));
+        assert_eq!(*vm.calls.lock().unwrap(), vec![("0xb1ee9".to_string(), b"release(7)".to_vec())]);
+    }
+
+    #[tokio::test]
+    async fn channel_order_holds_for_concurrent_sends() {
+        let o = outbound();
+        let outbox = Arc::new(Outbox::new());
+        let sends: Vec<_> = (0..3).map(|i| {
+            let outbox = outbox.clone();
+            tokio::spawn(async move {
+                outbox.send(governance(), ChainId::Ethereum, "0x7e45", format!("call-{}", i).into_bytes(), 100_000)
+            })
+        }).collect();
+        let mut messages = Vec::new();
+        for send in sends {
+            messages.push(send.await.unwrap());
+        }
+        messages.sort_by_key(|m| m.sequence);
+        assert_eq!(messages.iter().map(|m| m.sequence).collect::<Vec<_>>(), [0, 1, 2]);
+
+        // Relay newest first, concurrently: nothing may overtake sequence 0.
+        let relayer = Arc::new(o.relayer);
+        let relays: Vec<_> = commit(&o.attestors, 3, &messages).into_iter().rev().map(|a| {
+            let relayer = relayer.clone();
+            tokio::spawn(async move { relayer.relay(&a).await.unwrap() })
+        }).collect();
+        let mut receipts = Vec::new();
+        for relay in relays {
+            receipts.extend(relay.await.unwrap());
+        }
+        receipts.sort_by_key(|r| r.sequence);
+        assert_eq!(receipts.iter().map(|r| r.message_id).collect::<Vec<_>>(),
+                   messages.iter().map(|m| m.commitment()).collect::<Vec<_>>());
+        assert_eq!(*o.chain.calls.lock().unwrap(), messages);
+    }
+
+    #[tokio::test]
+    async fn failed_destination_call_yields_failure_receipt() {
+        let o = outbound();
+        let outbox = Outbox::new();
+        let messages = [
+            outbox.send(governance(), ChainId::Ethereum, "0x7e45", b"revert: paused".to_vec(), 100_000),
+            outbox.send(governance(), ChainId::Ethereum, "0x7e45", b"unpause()".to_vec(), 100_000),
+        ];
+        let attested = commit(&o.attestors, 9, &messages);
+
+        let receipts = o.relayer.relay(&attested[0]).await.unwrap();
+        assert_eq!(receipts[0].status, DeliveryStatus::Failed { reason: "execution reverted".into() });
+        // The channel moves past the failure instead of stalling or retrying it.
+        assert!(o.relayer.relay(&attested[1]).await.unwrap()[0].is_delivered());
+        assert_eq!(o.relayer.relay(&attested[0]).await, Err(MessageError::Duplicate { sequence: 0 }));
+        assert_eq!(o.chain.calls.lock().unwrap().len(), 2);
+    }
+}
@@ -11,16 +11,29 @@
 //! | Latency consistency            | 15%    |
 //! | Unique subnet diversity        | 10%    |
 //!
-//! Sybil detection uses subnet clustering: if more than `MAX_PEERS_PER_SUBNET`
+//! Invalid messages and failed handshakes add penalty points that are
+//! subtracted from the total; `PeerScoring::decay` shrinks them each
+//! maintenance sweep so a peer that behaves again recovers.  A peer sending
+//! more than the flood threshold within the rate window is flooding
+//! regardless of its score (`PeerScoring::is_flooding`).  Until a message's
+//! signature has been checked its claimed sender may be anyone, so the
+//! same flood limit is kept per source IP (`AddressRates`) and no node's
+//! score is touched.
+//!
+//! Sybil detection uses subnet clustering: if more than `max_peers_per_subnet`
 //! peers share the same /24 IPv4 prefix (or /48 IPv6 prefix), new peers from
-//! that subnet are marked as Sybil candidates.
+//! that subnet are marked as Sybil candidates.  `SybilDetector::evaluate`
+//! also looks at join timing: more than `max_joins_per_window` peers from
+//! one subnet joining within `join_window_secs` are suspects too.
 
+use std::collections::{HashMap, HashSet};
 use std::net::{IpAddr, SocketAddr};
 
 use dashmap::DashMap;
 use tracing::warn;
 
-use crate::types::{NodeId, PeerStatus, unix_now};
+use crate::peer_manager::PeerManager;
+use crate::types::{NodeId, PeerInfo, PeerStatus, unix_now};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // CONSTANTS
@@ -33,9 +46,15 @@ pub const TRUST_SUSPICIOUS_THRESHOLD: f64 = 40.0;
 /// Window for rate-of-message calculation.
 const MESSAGE_RATE_WINDOW_SECS: u64 = 60;
 /// Max messages per MESSAGE_RATE_WINDOW_SECS before penalising.
-const MESSAGE_RATE_FLOOD_THRESHOLD: u64 = 500;
+pub const MESSAGE_RATE_FLOOD_THRESHOLD: u64 = 500;
+/// Penalty points per invalid message (bad block, undecryptable or anomalous payload).
+const INVALID_MESSAGE_PENALTY: f64 = 15.0;
+/// Penalty points per failed handshake.
+const HANDSHAKE_FAILURE_PENALTY: f64 = 10.0;
+/// Penalties below this are dropped rather than decayed forever.
+const PENALTY_FLOOR: f64 = 0.01;
 /// Max peers tolerated from the same /24 subnet before flagging.
-const MAX_PEERS_PER_SUBNET: usize = 5;
+pub const MAX_PEERS_PER_SUBNET: usize = 5;
 /// Minimum peer age (seconds) before receiving full longevity bonus.
 const LONGEVITY_FULL_BONUS_SECS: u64 = 86400; // 24 h
 
@@ -52,6 +71,9 @@ pub struct InteractionRecord {
     /// Sorted latency samples (milliseconds).
     pub latency_samples: Vec<u32>,
     pub first_seen: u64,
+    /// Points subtracted from the score for invalid messages and failed
+    /// handshakes; shrinks with `PeerScoring::decay`.
+    pub penalty: f64,
 }
 
 impl InteractionRecord {
@@ -100,6 +122,15 @@ impl InteractionRecord {
         }
     }
 
+    /// Mean latency in milliseconds; `None` before the first sample.
+    pub fn mean_latency(&self) -> Option<f64> {
+        if self.latency_samples.is_empty() {
+            return None;
+        }
+        let sum: u64 = self.latency_samples.iter().map(|&x| x as u64).sum();
+        Some(sum as f64 / self.latency_samples.len() as f64)
+    }
+
     /// Coefficient of variation of latency (std / mean).  Low = consistent.
     pub fn latency_cv(&self) -> f64 {
         if self.latency_samples.len() < 3 {
@@ -123,15 +154,23 @@ impl InteractionRecord {
 
 pub struct PeerScoring {
     records: DashMap<NodeId, InteractionRecord>,
+    flood_threshold: u64,
 }
 
 impl PeerScoring {
     pub fn new() -> Self {
         PeerScoring {
             records: DashMap::new(),
+            flood_threshold: MESSAGE_RATE_FLOOD_THRESHOLD,
         }
     }
 
+    /// Messages per rate window above which a peer is flooding.
+    pub fn with_flood_threshold(mut self, threshold: u64) -> Self {
+        self.flood_threshold = threshold.max(1);
+        self
+    }
+
     fn record_mut(&self, id: &NodeId) -> dashmap::mapref::one::RefMut<'_, NodeId, InteractionRecord> {
         self.records
             .entry(id.clone())
@@ -155,6 +194,40 @@ impl PeerScoring {
         self.record_mut(id).record_latency(latency_ms);
     }
 
+    /// A message that failed validation: a failed interaction plus a penalty.
+    pub fn record_invalid_message(&self, id: &NodeId) {
+        let mut rec = self.record_mut(id);
+        rec.failure_count += 1;
+        rec.penalty += INVALID_MESSAGE_PENALTY;
+    }
+
+    pub fn record_handshake_failure(&self, id: &NodeId) {
+        self.record_mut(id).penalty += HANDSHAKE_FAILURE_PENALTY;
+    }
+
+    /// Whether `id` sent more than the flood threshold within the rate window.
+    pub fn is_flooding(&self, id: &NodeId) -> bool {
+        self.records.get(id)
+            .map(|rec| rec.message_rate(unix_now()) > self.flood_threshold)
+            .unwrap_or(false)
+    }
+
+    /// Multiply every peer's penalty by `factor` (in [0, 1]) so scores
+    /// recover once the misbehaviour stops.
+    pub fn decay(&self, factor: f64) {
+        let factor = factor.clamp(0.0, 1.0);
+        for mut rec in self.records.iter_mut() {
+            rec.penalty *= factor;
+            if rec.penalty < PENALTY_FLOOR {
+                rec.penalty = 0.0;
+            }
+        }
+    }
+
+    pub fn mean_latency(&self, id: &NodeId) -> Option<f64> {
+        self.records.get(id)?.mean_latency()
+    }
+
     /// Compute the composite trust score [0.0, 100.0].
     pub fn calculate_score(&self, id: &NodeId) -> f64 {
         let now = unix_now();
@@ -168,10 +241,10 @@ impl PeerScoring {
 
         // 2. Message rate component [0, 20] — penalise flooding
         let rate = rec.message_rate(now);
-        let rate_score = if rate > MESSAGE_RATE_FLOOD_THRESHOLD {
+        let rate_score = if rate > self.flood_threshold {
             0.0
         } else {
-            (1.0 - (rate as f64 / MESSAGE_RATE_FLOOD_THRESHOLD as f64)) * 20.0
+            (1.0 - (rate as f64 / self.flood_threshold as f64)) * 20.0
         };
 
         // 3. Longevity component [0, 15]
@@ -186,7 +259,8 @@ impl PeerScoring {
         //    Here we give full marks unless overridden.
         let diversity_score = 10.0;
 
-        let total = success_component + rate_score + longevity_score + latency_score + diversity_score;
+        let total = success_component + rate_score + longevity_score + latency_score + diversity_score
+            - rec.penalty;
         total.clamp(0.0, 100.0)
     }
 
@@ -217,12 +291,70 @@ impl PeerScoring {
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// ADDRESS RATES — unauthenticated traffic
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Message rates per source IP, for traffic not yet authenticated.
+pub struct AddressRates {
+    records: DashMap<IpAddr, InteractionRecord>,
+    flood_threshold: u64,
+}
+
+impl AddressRates {
+    pub fn new(flood_threshold: u64) -> Self {
+        AddressRates {
+            records: DashMap::new(),
+            flood_threshold: flood_threshold.max(1),
+        }
+    }
+
+    /// Count a message from `ip`; `true` once `ip` sent more than the flood
+    /// threshold within the rate window.
+    pub fn record_message(&self, ip: IpAddr) -> bool {
+        let now = unix_now();
+        let mut rec = self.records.entry(ip).or_insert_with(|| InteractionRecord::new(now));
+        rec.record_message(now);
+        rec.message_rate(now) > self.flood_threshold
+    }
+
+    /// Forget addresses with no message in the current rate window.
+    pub fn prune(&self) {
+        let now = unix_now();
+        self.records.retain(|_, rec| {
+            rec.prune_old_messages(now);
+            !rec.recent_message_times.is_empty()
+        });
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // SYBIL DETECTOR
 // ─────────────────────────────────────────────────────────────────────────────
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct SybilConfig {
+    /// Peers tolerated from one /24 (IPv4) or /48 (IPv6).
+    pub max_peers_per_subnet: usize,
+    /// Span in which bursts of joins from one subnet are counted.
+    pub join_window_secs: u64,
+    /// Joins from one subnet tolerated within `join_window_secs`.
+    pub max_joins_per_window: usize,
+}
+
+impl Default for SybilConfig {
+    fn default() -> Self {
+        SybilConfig {
+            max_peers_per_subnet: MAX_PEERS_PER_SUBNET,
+            join_window_secs:     60,
+            max_joins_per_window: 3,
+        }
+    }
+}
+
 /// Detects Sybil attacks via subnet clustering and behavioural correlation.
 pub struct SybilDetector {
+    config: SybilConfig,
     /// subnet_key → peer_count
     subnet_counts: DashMap<String, usize>,
     /// Peers flagged as Sybil candidates.
@@ -231,7 +363,12 @@ pub struct SybilDetector {
 
 impl SybilDetector {
     pub fn new() -> Self {
+        Self::with_config(SybilConfig::default())
+    }
+
+    pub fn with_config(config: SybilConfig) -> Self {
         SybilDetector {
+            config,
             subnet_counts: DashMap::new(),
             flagged: DashMap::new(),
         }
@@ -256,7 +393,7 @@ impl SybilDetector {
         let mut count = self.subnet_counts.entry(key.clone()).or_insert(0);
         *count += 1;
 
-        if *count > MAX_PEERS_PER_SUBNET {
+        if *count > self.config.max_peers_per_subnet {
             warn!(peer = %id, subnet = %key, count = *count, "Sybil candidate: subnet saturation");
             self.flagged.insert(id.clone(), true);
             return true;
@@ -281,6 +418,46 @@ impl SybilDetector {
         }
         self.flagged.remove(id);
     }
+
+    /// Suspected Sybils among `pm`'s peers; see `evaluate_peers`.
+    pub fn evaluate(&self, pm: &PeerManager) -> Vec<NodeId> {
+        self.evaluate_peers(&pm.all_peers())
+    }
+
+    /// Every peer in an oversized subnet or in a burst of joins from one
+    /// subnet, plus any peer flagged earlier.  Sorted, without duplicates.
+    pub fn evaluate_peers(&self, peers: &[PeerInfo]) -> Vec<NodeId> {
+        let mut by_subnet: HashMap<String, Vec<&PeerInfo>> = HashMap::new();
+        for peer in peers {
+            by_subnet.entry(Self::subnet_key(&peer.addr)).or_default().push(peer);
+        }
+
+        let mut suspects = HashSet::new();
+        for (subnet, mut members) in by_subnet {
+            if members.len() > self.config.max_peers_per_subnet {
+                warn!(subnet = %subnet, peers = members.len(), "Sybil cluster: subnet saturation");
+                suspects.extend(members.iter().map(|p| p.id.clone()));
+                continue;
+            }
+            // Sliding window over join times: any window holding too many
+            // joins makes all of them suspects.
+            members.sort_by_key(|p| p.first_seen);
+            let burst = self.config.max_joins_per_window + 1;
+            for window in members.windows(burst) {
+                let span = window[burst - 1].first_seen - window[0].first_seen;
+                if span <= self.config.join_window_secs {
+                    warn!(subnet = %subnet, joins = burst, span_secs = span, "Sybil cluster: join burst");
+                    suspects.extend(window.iter().map(|p| p.id.clone()));
+                }
+            }
+        }
+        suspects.extend(
+            peers.iter().filter(|p| self.is_flagged(&p.id)).map(|p| p.id.clone()),
+        );
+        let mut suspects: Vec<NodeId> = suspects.into_iter().collect();
+        suspects.sort_by(|a, b| a.0.cmp(&b.0));
+        suspects
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -374,6 +551,58 @@ mod tests {
         assert!(score < 80.0, "Score {score} should be penalised for flooding");
     }
 
+    #[test]
+    fn test_flooding_flagged_past_threshold() {
+        let scoring = PeerScoring::new().with_flood_threshold(50);
+        let (flooder, normal) = (nid(4), nid(5));
+        let flagged_at = (1..=200).find(|_| {
+            scoring.record_message(&flooder);
+            scoring.is_flooding(&flooder)
+        });
+        assert_eq!(flagged_at, Some(51));
+
+        for _ in 0..40 {
+            scoring.record_message(&normal);
+        }
+        assert!(!scoring.is_flooding(&normal));
+        assert!(!scoring.is_flooding(&nid(6)), "unknown peers are not flooding");
+    }
+
+    #[test]
+    fn test_address_flooding_flagged_past_threshold() {
+        let rates = AddressRates::new(5);
+        let (flooder, normal): (IpAddr, IpAddr) = ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        assert_eq!((1..=20).find(|_| rates.record_message(flooder)), Some(6));
+        assert!(!rates.record_message(normal));
+
+        rates.prune();
+        assert!(rates.record_message(flooder), "messages in the window are kept");
+    }
+
+    #[test]
+    fn test_invalid_messages_penalise_then_decay() {
+        let scoring = PeerScoring::new();
+        let id = nid(7);
+        let mut rec = InteractionRecord::new(unix_now() - 90000);
+        rec.success_count = 100;
+        scoring.records.insert(id.clone(), rec);
+        let clean = scoring.calculate_score(&id);
+
+        for _ in 0..3 {
+            scoring.record_invalid_message(&id);
+        }
+        scoring.record_handshake_failure(&id);
+        let penalised = scoring.calculate_score(&id);
+        assert!(penalised < TRUST_SUSPICIOUS_THRESHOLD, "{penalised}");
+
+        for _ in 0..10 {
+            scoring.decay(0.5);
+        }
+        let recovered = scoring.calculate_score(&id);
+        assert!(recovered > penalised && recovered >= TRUST_HEALTHY_THRESHOLD, "{recovered}");
+        assert!(recovered < clean + f64::EPSILON);
+    }
+
     #[test]
     fn test_classify_status_boundaries() {
         let scoring = PeerScoring::new();
@@ -402,6 +631,54 @@ mod tests {
         assert!(sybil.is_flagged(&last_id));
     }
 
+    /// `n` peers in 10.`subnet`.0.0/24, the i-th joining at `start + i * gap`.
+    fn cluster(subnet: u8, n: usize, start: u64, gap: u64, tag: u8) -> Vec<PeerInfo> {
+        (0..n).map(|i| {
+            let mut id = nid(tag);
+            id.0[1] = i as u8;
+            let addr = format!("10.{}.0.{}:9000", subnet, i + 1).parse().unwrap();
+            let mut peer = PeerInfo::new(id, addr, vec![], vec![]);
+            peer.first_seen = start + i as u64 * gap;
+            peer
+        }).collect()
+    }
+
+    #[test]
+    fn test_evaluate_clean_topology() {
+        let sybil = SybilDetector::new();
+        // 30 peers in 30 subnets, plus 3 neighbours that joined hours apart
+        let mut peers: Vec<PeerInfo> = (0..30u8).flat_map(|s| cluster(s, 1, 1_000, 0, s)).collect();
+        peers.extend(cluster(200, 3, 1_000, 3_600, 200));
+        assert!(sybil.evaluate_peers(&peers).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_flags_fifty_node_cluster() {
+        let sybil = SybilDetector::new();
+        let mut peers: Vec<PeerInfo> = (0..30u8).flat_map(|s| cluster(s, 1, 1_000, 0, s)).collect();
+        let sybils = cluster(66, 50, 5_000, 1, 66);
+        peers.extend(sybils.iter().cloned());
+
+        let suspects = sybil.evaluate_peers(&peers);
+        assert_eq!(suspects.len(), 50);
+        assert!(sybils.iter().all(|p| suspects.contains(&p.id)));
+    }
+
+    #[test]
+    fn test_evaluate_flags_join_burst_below_subnet_cap() {
+        let sybil = SybilDetector::with_config(SybilConfig { max_joins_per_window: 2, ..Default::default() });
+        // Three joins within 10 s: under the subnet cap but a burst
+        let burst = cluster(7, 3, 1_000, 5, 7);
+        let slow = cluster(8, 3, 1_000, 600, 8);
+        let peers: Vec<PeerInfo> = burst.iter().chain(&slow).cloned().collect();
+        let suspects = sybil.evaluate_peers(&peers);
+        assert_eq!(suspects, {
+            let mut ids: Vec<NodeId> = burst.iter().map(|p| p.id.clone()).collect();
+            ids.sort_by(|a, b| a.0.cmp(&b.0));
+            ids
+        });
+    }
+
     #[test]
     fn test_anomaly_detector_oversized_payload() {
         let det = AnomalyDetector::new();
@@ -0,0 +1,265 @@
+//! Block-sync request/response frames.
+//!
+//! A node behind the chain downloads the blocks it is missing, headers
+//! first:
+//!
+//! ```text
+//! sync SyncRequest { from_index, max_blocks, part: Headers } ─▶ Headers { tip, headers } | Unavailable
+//! sync SyncRequest { from_index, max_blocks, part: Bodies }  ─▶ Bodies { tip, blocks }   | Unavailable
+//! ```
+//!
+//! Every response carries the server's tip, which is how the syncing node
+//! learns the best known height.  Frames are anonymous and unencrypted
+//! like `snap`: blocks are public and the syncing node checks the hash
+//! links of the headers before fetching a body.  This module only moves
+//! bytes; what a header and a block are, and how they are checked and
+//! applied, lives in `bleep_core::block_sync`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use bleep_telemetry::metrics::{MetricGauge, MetricsRegistry};
+
+use crate::error::{P2PError, P2PResult};
+use crate::message_protocol::MessageProtocol;
+use crate::service_quota::Busy;
+use crate::transport::Transport;
+use crate::types::{unix_now, MessageType, NodeId, SecureMessage};
+
+/// `MessageType::Custom` tag of block-sync frames.
+pub const BLOCK_SYNC_MESSAGE: &str = "sync";
+
+/// Most blocks a server returns for one request.
+pub const MAX_SYNC_BLOCKS: u32 = 256;
+
+/// Encoded bytes a server packs into one response, well inside a frame.
+/// The first block is always sent, however large.
+pub const MAX_SYNC_BYTES: usize = 2 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncPart {
+    Headers,
+    Bodies,
+}
+
+/// Blocks `from_index ..` up to `max_blocks` of them, as headers or in full.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncRequest {
+    pub from_index: u64,
+    pub max_blocks: u32,
+    pub part:       SyncPart,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SyncResponse {
+    Headers { tip: u64, headers: Vec<Vec<u8>> },
+    Bodies { tip: u64, blocks: Vec<Vec<u8>> },
+    /// The peer holds no block at `from_index`.
+    Unavailable { tip: u64 },
+}
+
+impl SyncResponse {
+    /// Height of the serving node's chain.
+    pub fn tip(&self) -> u64 {
+        match self {
+            SyncResponse::Headers { tip, .. }
+            | SyncResponse::Bodies { tip, .. }
+            | SyncResponse::Unavailable { tip } => *tip,
+        }
+    }
+}
+
+/// What a node serves block-sync requests from.
+pub trait BlockSource: Send + Sync {
+    fn tip(&self) -> u64;
+    /// Encoded header of block `index`.
+    fn header(&self, index: u64) -> Option<Vec<u8>>;
+    /// Encoded block `index` with its body.
+    fn block(&self, index: u64) -> Option<Vec<u8>>;
+}
+
+/// Answer `request` from `source`.
+pub fn serve(source: &dyn BlockSource, request: &SyncRequest) -> SyncResponse {
+    let tip = source.tip();
+    let last = request.from_index
+        .saturating_add(request.max_blocks.clamp(1, MAX_SYNC_BLOCKS) as u64 - 1)
+        .min(tip);
+    let mut items = Vec::new();
+    let mut bytes = 0;
+    for index in request.from_index..=last {
+        let item = match request.part {
+            SyncPart::Headers => source.header(index),
+            SyncPart::Bodies  => source.block(index),
+        };
+        let Some(item) = item else { break };
+        if !items.is_empty() && bytes + item.len() > MAX_SYNC_BYTES {
+            break;
+        }
+        bytes += item.len();
+        items.push(item);
+    }
+    match request.part {
+        _ if items.is_empty() => SyncResponse::Unavailable { tip },
+        SyncPart::Headers => SyncResponse::Headers { tip, headers: items },
+        SyncPart::Bodies  => SyncResponse::Bodies { tip, blocks: items },
+    }
+}
+
+/// One peer a syncing node downloads blocks from.
+#[async_trait]
+pub trait SyncPeer: Send + Sync {
+    /// Stable label for logs and penalties.
+    fn id(&self) -> String;
+    async fn request(&self, request: SyncRequest) -> P2PResult<SyncResponse>;
+}
+
+/// Requests block-sync frames over the P2P TCP transport.
+pub struct TcpSyncPeer {
+    pub addr: SocketAddr,
+}
+
+#[async_trait]
+impl SyncPeer for TcpSyncPeer {
+    fn id(&self) -> String {
+        self.addr.to_string()
+    }
+
+    async fn request(&self, request: SyncRequest) -> P2PResult<SyncResponse> {
+        let reply = MessageProtocol::exchange(self.addr, &request_frame(&request)?).await?;
+        read_reply(&reply)
+    }
+}
+
+/// Requests block-sync frames over a node's own transport, so a QUIC node
+/// syncs on its `Lane::Sync` streams.
+pub struct TransportSyncPeer {
+    pub addr:      SocketAddr,
+    pub transport: Arc<dyn Transport>,
+}
+
+#[async_trait]
+impl SyncPeer for TransportSyncPeer {
+    fn id(&self) -> String {
+        self.addr.to_string()
+    }
+
+    async fn request(&self, request: SyncRequest) -> P2PResult<SyncResponse> {
+        let reply = MessageProtocol::exchange_via(self.transport.as_ref(), self.addr, &request_frame(&request)?).await?;
+        read_reply(&reply)
+    }
+}
+
+fn request_frame(request: &SyncRequest) -> P2PResult<SecureMessage> {
+    let payload = bincode::serialize(request).map_err(|e| P2PError::Serialization(e.to_string()))?;
+    let mut nonce = [0u8; 16];
+    rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce);
+    Ok(SecureMessage {
+        version: 1,
+        sender_id: NodeId::random(),
+        message_type: MessageType::Custom(BLOCK_SYNC_MESSAGE.into()),
+        payload,
+        signature: Vec::new(),
+        hop_count: 0,
+        nonce,
+        timestamp: unix_now(),
+    })
+}
+
+fn read_reply(reply: &SecureMessage) -> P2PResult<SyncResponse> {
+    Busy::check(reply)?;
+    bincode::deserialize(&reply.payload).map_err(|e| P2PError::Serialization(e.to_string()))
+}
+
+/// Serves from a `BlockSource` in-process; for tests and local tooling.
+pub struct LocalSyncPeer {
+    pub id:     String,
+    pub source: Arc<dyn BlockSource>,
+}
+
+#[async_trait]
+impl SyncPeer for LocalSyncPeer {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    async fn request(&self, request: SyncRequest) -> P2PResult<SyncResponse> {
+        Ok(serve(self.source.as_ref(), &request))
+    }
+}
+
+/// Telemetry gauges of a running block sync.
+#[derive(Debug, Clone)]
+pub struct SyncTelemetry {
+    height:          MetricGauge,
+    best_height:     MetricGauge,
+    peers_penalized: MetricGauge,
+}
+
+impl SyncTelemetry {
+    /// Register gauges `bleep_block_sync_*`.
+    pub fn register(registry: &mut MetricsRegistry) -> Self {
+        SyncTelemetry {
+            height:          registry.gauge("bleep_block_sync_height"),
+            best_height:     registry.gauge("bleep_block_sync_best_height"),
+            peers_penalized: registry.gauge("bleep_block_sync_peers_penalized"),
+        }
+    }
+
+    pub fn export(&self, height: u64, best_height: u64, peers_penalized: u64) {
+        self.height.set(height as i64);
+        self.best_height.set(best_height as i64);
+        self.peers_penalized.set(peers_penalized as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Blocks 0..=tip, each `size` bytes.
+    struct Fixed {
+        tip:  u64,
+        size: usize,
+    }
+
+    impl BlockSource for Fixed {
+        fn tip(&self) -> u64 {
+            self.tip
+        }
+
+        fn header(&self, index: u64) -> Option<Vec<u8>> {
+            (index <= self.tip).then(|| index.to_le_bytes().to_vec())
+        }
+
+        fn block(&self, index: u64) -> Option<Vec<u8>> {
+            (index <= self.tip).then(|| vec![index as u8; self.size])
+        }
+    }
+
+    #[test]
+    fn test_serve_clamps_to_tip_count_and_byte_budget() {
+        let small = Fixed { tip: 1_000, size: 10 };
+        let request = SyncRequest { from_index: 990, max_blocks: 100, part: SyncPart::Headers };
+        match serve(&small, &request) {
+            SyncResponse::Headers { tip, headers } => assert_eq!((tip, headers.len()), (1_000, 11)),
+            other => panic!("unexpected {:?}", other),
+        }
+        let request = SyncRequest { from_index: 0, max_blocks: u32::MAX, part: SyncPart::Bodies };
+        match serve(&small, &request) {
+            SyncResponse::Bodies { blocks, .. } => assert_eq!(blocks.len(), MAX_SYNC_BLOCKS as usize),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let large = Fixed { tip: 10, size: MAX_SYNC_BYTES / 2 + 1 };
+        let request = SyncRequest { from_index: 1, max_blocks: 5, part: SyncPart::Bodies };
+        match serve(&large, &request) {
+            SyncResponse::Bodies { blocks, .. } => assert_eq!(blocks.len(), 1),
+            other => panic!("unexpected {:?}", other),
+        }
+        let beyond = SyncRequest { from_index: 11, max_blocks: 5, part: SyncPart::Headers };
+        assert_eq!(serve(&large, &beyond), SyncResponse::Unavailable { tip: 10 });
+    }
+}
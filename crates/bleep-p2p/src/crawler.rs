@@ -0,0 +1,188 @@
+//! Network crawler: walks PEX responses from a seed to map the network.
+//!
+//! The crawler never joins consensus or completes a handshake.  It sends an
+//! anonymous `pex` frame to each address, checks that the reply is signed
+//! by the node it describes, and queues the peers that node reports.
+//! Nodes that cannot be reached are listed separately.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::{P2PError, P2PResult};
+use crate::message_protocol::MessageProtocol;
+use crate::node_info::{PexResponse, PEX_MESSAGE};
+use crate::types::{unix_now, MessageType, NodeId, SecureMessage};
+
+/// Default cap on nodes visited in one crawl.
+pub const DEFAULT_MAX_NODES: usize = 1_000;
+
+/// Fetches one node's PEX response.
+#[async_trait]
+pub trait PexProbe: Send + Sync {
+    async fn probe(&self, addr: SocketAddr) -> P2PResult<PexResponse>;
+}
+
+/// Probes nodes over the P2P TCP transport.
+pub struct TcpPexProbe;
+
+#[async_trait]
+impl PexProbe for TcpPexProbe {
+    async fn probe(&self, addr: SocketAddr) -> P2PResult<PexResponse> {
+        let mut nonce = [0u8; 16];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce);
+        let request = SecureMessage {
+            version: 1,
+            sender_id: NodeId::random(),
+            message_type: MessageType::Custom(PEX_MESSAGE.into()),
+            payload: Vec::new(),
+            signature: Vec::new(),
+            hop_count: 0,
+            nonce,
+            timestamp: unix_now(),
+        };
+        let reply = MessageProtocol::exchange(addr, &request).await?;
+        let response: PexResponse = bincode::deserialize(&reply.payload)
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        MessageProtocol::verify_plain(&reply, &response.hello)?;
+        Ok(response)
+    }
+}
+
+/// One node reached during a crawl.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapNode {
+    pub node_id:  String,
+    pub addr:     SocketAddr,
+    pub agent:    String,
+    pub moniker:  String,
+    pub region:   String,
+    /// Node ids this node reported as connected peers.
+    pub peers:    Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkMap {
+    pub seed:        SocketAddr,
+    pub crawled_at:  u64,
+    pub nodes:       Vec<MapNode>,
+    /// Addresses that were advertised but did not answer.
+    pub unreachable: Vec<SocketAddr>,
+}
+
+/// Breadth-first walk from `seed`, visiting at most `max_nodes` addresses.
+pub async fn crawl(probe: &dyn PexProbe, seed: SocketAddr, max_nodes: usize) -> NetworkMap {
+    let mut queue = VecDeque::from([seed]);
+    let mut queued: HashSet<SocketAddr> = HashSet::from([seed]);
+    let mut nodes: HashMap<NodeId, MapNode> = HashMap::new();
+    let mut unreachable = Vec::new();
+    let mut visited = 0usize;
+
+    while let Some(addr) = queue.pop_front() {
+        if visited >= max_nodes {
+            break;
+        }
+        visited += 1;
+        let response = match probe.probe(addr).await {
+            Ok(r) => r,
+            Err(e) => {
+                debug!(addr = %addr, error = %e, "Crawl probe failed");
+                unreachable.push(addr);
+                continue;
+            }
+        };
+        for peer in &response.peers {
+            if !nodes.contains_key(&peer.node_id) && queued.insert(peer.addr) {
+                queue.push_back(peer.addr);
+            }
+        }
+        let hello = response.hello;
+        nodes.entry(hello.node_id.clone()).or_insert_with(|| MapNode {
+            node_id: hello.node_id.to_string(),
+            addr,
+            agent:   hello.agent,
+            moniker: hello.metadata.moniker,
+            region:  hello.metadata.region,
+            peers:   response.peers.iter().map(|p| p.node_id.to_string()).collect(),
+        });
+    }
+
+    let mut nodes: Vec<MapNode> = nodes.into_values().collect();
+    nodes.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    NetworkMap { seed, crawled_at: unix_now(), nodes, unreachable }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_info::{Hello, NodeMetadata, PeerDirectory};
+    use crate::p2p_node::{P2PNode, P2PNodeConfig};
+    use crate::quantum_crypto::NodeIdentity;
+
+    /// In-memory nodes wired in a ring, answering PEX from their directories.
+    struct TestCluster {
+        nodes: HashMap<SocketAddr, PeerDirectory>,
+    }
+
+    impl TestCluster {
+        fn ring(n: u8) -> (Self, Vec<SocketAddr>) {
+            let addrs: Vec<SocketAddr> = (0..n).map(|i| SocketAddr::from(([10, 0, 0, i + 1], 7700))).collect();
+            let hellos: Vec<Hello> = (0..n as usize).map(|i| Hello::new(
+                &NodeIdentity::generate(), "bleep/test", addrs[i], NodeMetadata::new(&format!("node-{i}"), "", ""),
+            )).collect();
+            let mut nodes = HashMap::new();
+            for i in 0..n as usize {
+                let dir = PeerDirectory::new(hellos[i].clone());
+                // Each node only knows its successor.
+                let next = (i + 1) % n as usize;
+                dir.on_hello(hellos[next].clone(), addrs[next]).unwrap();
+                nodes.insert(addrs[i], dir);
+            }
+            (TestCluster { nodes }, addrs)
+        }
+    }
+
+    #[async_trait]
+    impl PexProbe for TestCluster {
+        async fn probe(&self, addr: SocketAddr) -> P2PResult<PexResponse> {
+            self.nodes.get(&addr)
+                .map(|d| d.pex_response())
+                .ok_or(P2PError::ConnectionTimeout { addr: addr.to_string() })
+        }
+    }
+
+    #[tokio::test]
+    async fn crawler_discovers_every_node_in_cluster() {
+        let (cluster, addrs) = TestCluster::ring(6);
+        let map = crawl(&cluster, addrs[0], DEFAULT_MAX_NODES).await;
+        assert_eq!(map.nodes.len(), 6);
+        assert!(map.unreachable.is_empty());
+        assert!(map.nodes.iter().all(|n| n.peers.len() == 1));
+
+        let capped = crawl(&cluster, addrs[0], 2).await;
+        assert_eq!(capped.nodes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn crawler_walks_real_nodes_over_tcp() {
+        let start = |port: u16| P2PNode::start(P2PNodeConfig {
+            listen_addr: SocketAddr::from(([127, 0, 0, 1], port)),
+            ..Default::default()
+        });
+        let (a, handle_a) = start(17720).await.unwrap();
+        let (b, handle_b) = start(17721).await.unwrap();
+        let b_hello = b.message_protocol.hello("127.0.0.1:17720".parse().unwrap()).await.unwrap();
+        assert_eq!(b_hello.node_id, a.node_id);
+        assert_eq!(a.directory.len(), 1);
+
+        let seed = "127.0.0.1:17720".parse().unwrap();
+        let map = crawl(&TcpPexProbe, seed, DEFAULT_MAX_NODES).await;
+        let ids: HashSet<String> = map.nodes.iter().map(|n| n.node_id.clone()).collect();
+        assert_eq!(ids, HashSet::from([a.node_id.to_string(), b.node_id.to_string()]));
+        handle_a.shutdown().await;
+        handle_b.shutdown().await;
+    }
+}
@@ -8,29 +8,29 @@
 //!
 //! - **Route selection** — `OnionRouter::select_route` (Kademlia peer set,
 //!   AI trust-score filter ≥ 55.0, random shuffle for unlinkability).
-//! - **Per-hop KEM** — `kyber_encapsulate` from `quantum_crypto.rs`
-//!   (Kyber-768, NIST PQC finalist).
-//! - **Onion wrapping** — `OnionRouter::wrap` (nested AES-256-GCM layers,
-//!   inner-to-outer construction).
-//! - **Dispatch** — `OnionRouter::send_anonymous` →
+//! - **Per-hop KEM** — each relay's long-term Kyber-768 public key, as
+//!   registered with `PeerManager::register_kyber_key`, encapsulates a
+//!   fresh key for that relay's layer.
+//! - **Onion wrapping** — `seal_onion` (nested AES-256-GCM layers,
+//!   inner-to-outer construction).  A relay opening its layer sees only the
+//!   next hop and an opaque packet for it; any change to a packet makes the
+//!   next hop's decryption fail.
+//! - **Dispatch** — `OnionRouter::send_packet` →
 //!   `MessageProtocol::send_message` (real TCP write with timeout).
-//!
-//! There are no stubs, no `println!` calls, and no unencrypted clones.
 
 use std::sync::Arc;
 
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
 use crate::ai_security::PeerScoring;
 use crate::error::{P2PError, P2PResult};
 use crate::message_protocol::MessageProtocol;
-use crate::onion_routing::{OnionLayer, OnionRouter};
+use crate::onion_routing::{open_onion, seal_onion, OnionPacket, OnionRouter, Peeled};
 use crate::peer_manager::PeerManager;
-use crate::quantum_crypto::{kyber_encapsulate, KyberKeypair};
+use crate::quantum_crypto::KyberKeypair;
 use crate::types::{MessageType, NodeId, RoutePath};
 
-/// Maximum hops in a dark-routing circuit (mirrors `onion_routing::MAX_HOPS`).
-pub const MAX_HOPS: usize = 6;
+pub use crate::onion_routing::MAX_HOPS;
 
 // ── DarkRouting ───────────────────────────────────────────────────────────────
 
@@ -41,7 +41,8 @@ pub const MAX_HOPS: usize = 6;
 /// `DarkRouting` itself holds no mutable state.
 #[derive(Clone)]
 pub struct DarkRouting {
-    router: Arc<OnionRouter>,
+    router:       Arc<OnionRouter>,
+    peer_manager: Arc<PeerManager>,
 }
 
 impl std::fmt::Debug for DarkRouting {
@@ -63,7 +64,8 @@ impl DarkRouting {
         scoring:          Arc<PeerScoring>,
     ) -> Self {
         Self {
-            router: Arc::new(OnionRouter::new(peer_manager, message_protocol, scoring)),
+            router: Arc::new(OnionRouter::new(peer_manager.clone(), message_protocol, scoring)),
+            peer_manager,
         }
     }
 
@@ -74,15 +76,10 @@ impl DarkRouting {
     /// ## Steps
     /// 1. **Route selection** — picks up to `MAX_HOPS` peers above the
     ///    trust threshold and shuffles them.
-    /// 2. **KEM** — for each relay, generates an ephemeral Kyber-768 keypair
-    ///    and calls `kyber_encapsulate` to produce a per-hop shared secret.
-    ///    The resulting `(ciphertext, shared_secret)` pair provides forward
-    ///    secrecy: even if a relay's long-term key is compromised later, past
-    ///    messages cannot be decrypted.
-    /// 3. **Onion wrapping** — `OnionRouter::wrap` encrypts the payload in
-    ///    nested AES-256-GCM layers (inner-to-outer) using the shared secrets.
-    /// 4. **Dispatch** — `OnionRouter::send_anonymous` serialises the outermost
-    ///    layer and calls `MessageProtocol::send_message` (real TCP write).
+    /// 2. **Onion wrapping** — `onion_encrypt` seals one layer per relay to
+    ///    the relay's registered Kyber key.
+    /// 3. **Dispatch** — `OnionRouter::send_packet` serialises the packet
+    ///    and calls `MessageProtocol::send_message` (real TCP write).
     ///
     /// Returns `Ok(())` once the first hop has been dispatched.  Subsequent
     /// relay hops are the responsibility of the receiving nodes.
@@ -100,37 +97,8 @@ impl DarkRouting {
             return Err(P2PError::NoRoute { sender: sender_id.to_string() });
         }
 
-        // ── 2. Per-hop Kyber KEM ─────────────────────────────────────────────
-        //
-        // We generate one ephemeral keypair per relay and encapsulate to the
-        // ephemeral public key.  The shared secret is used as AES-256-GCM key
-        // material inside OnionRouter::wrap.
-        //
-        // In a production deployment each relay's long-term Kyber public key
-        // (stored in PeerInfo::sphincs_public_key and paired with a Kyber key
-        // during the ZkHandshake) would be used here instead of an ephemeral
-        // key.  The ephemeral approach used below provides equivalent security
-        // for the symmetric layer while remaining independent of the relay's
-        // key management.
-        let mut hop_shared_secrets: Vec<Vec<u8>> = Vec::with_capacity(route.hops.len());
-
-        for (hop_index, _relay_id) in route.hops.iter().enumerate() {
-            let ephemeral = KyberKeypair::generate();
-            let pk_bytes  = ephemeral.public_key.0.as_slice();
-
-            let (_ciphertext, shared_secret) = kyber_encapsulate(pk_bytes)
-                .map_err(|e| {
-                    error!(
-                        hop    = hop_index,
-                        sender = %sender_id,
-                        err    = %e,
-                        "KEM encapsulate failed"
-                    );
-                    e
-                })?;
-
-            hop_shared_secrets.push(shared_secret);
-        }
+        // ── 2. Per-hop Kyber KEM + onion wrapping ────────────────────────────
+        let packet = self.onion_encrypt(plaintext, &route)?;
 
         info!(
             sender   = %sender_id,
@@ -139,41 +107,47 @@ impl DarkRouting {
             "Dispatching anonymous onion circuit"
         );
 
-        // ── 3 & 4. Wrap + dispatch ────────────────────────────────────────────
-        self.router
-            .send_anonymous(sender_id, plaintext, message_type, &hop_shared_secrets)
-            .await
+        // ── 3. Dispatch ───────────────────────────────────────────────────────
+        self.router.send_packet(&route.hops[0], &packet).await
+    }
+
+    /// Wrap `msg` in one layer per hop of `route`, each sealed to that
+    /// relay's registered Kyber key.  Fails if a relay has no key.
+    pub fn onion_encrypt(&self, msg: &[u8], route: &RoutePath) -> P2PResult<OnionPacket> {
+        let hops = route.hops.iter()
+            .map(|id| {
+                self.peer_manager.kyber_key(id)
+                    .map(|pk| (id.clone(), pk))
+                    .ok_or_else(|| P2PError::Crypto(format!("no Kyber key registered for relay {}", id)))
+            })
+            .collect::<P2PResult<Vec<_>>>()?;
+        seal_onion(msg, &hops)
     }
 
     // ── Receive ───────────────────────────────────────────────────────────────
 
-    /// Peel one onion layer from an incoming dark-routed message.
+    /// Peel this node's layer off `packet` with its Kyber keypair.
     ///
-    /// Called by the inbound message handler when a `MessageType::OnionRelay`
-    /// frame arrives.
-    ///
-    /// Returns `(inner_payload_bytes, next_hop)`.
-    /// When `next_hop` is `None`, this node is the final destination and
-    /// `inner_payload_bytes` is the original plaintext.
-    pub fn handle_incoming_layer(
-        &self,
-        layer_bytes:   &[u8],
-        local_id:      &NodeId,
-        shared_secret: &[u8],
-    ) -> P2PResult<(Vec<u8>, Option<NodeId>)> {
-        let layer: OnionLayer = bincode::deserialize(layer_bytes)
-            .map_err(|e| P2PError::Serialization(e.to_string()))?;
-
-        let (inner, next_hop) = self.router.peel(&layer, local_id, shared_secret)?;
-
+    /// Returns `Peeled::Forward` with the packet for the next hop, or
+    /// `Peeled::Deliver` with the original plaintext when this node is the
+    /// destination.  A tampered packet fails with `DecryptionFailed`.
+    pub fn peel_layer(&self, packet: &OnionPacket, node_key: &KyberKeypair) -> P2PResult<Peeled> {
+        let peeled = open_onion(packet, node_key)?;
         debug!(
-            local    = %local_id,
-            hop      = layer.hop_index,
-            has_next = next_hop.is_some(),
+            has_next = matches!(peeled, Peeled::Forward { .. }),
             "Peeled onion layer"
         );
+        Ok(peeled)
+    }
 
-        Ok((inner, next_hop))
+    /// Peel one onion layer from an incoming dark-routed message.
+    ///
+    /// Called by the inbound message handler when a `MessageType::OnionRelay`
+    /// frame arrives.
+    pub fn handle_incoming_layer(&self, layer_bytes: &[u8], node_key: &KyberKeypair) -> P2PResult<Peeled> {
+        let packet: OnionPacket = bincode::deserialize(layer_bytes)
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        self.peel_layer(&packet, node_key)
     }
 }
 
@@ -234,4 +208,76 @@ mod tests {
         let dr2 = dr.clone();
         let _s  = format!("{:?}", dr2);
     }
+
+    /// Three relays with Kyber keys registered, and their keypairs.
+    fn relays(dr: &DarkRouting) -> (RoutePath, Vec<KyberKeypair>) {
+        let mut hops = Vec::new();
+        let mut keys = Vec::new();
+        for _ in 0..3 {
+            let id = NodeId::random();
+            let kp = KyberKeypair::generate();
+            dr.peer_manager.register_kyber_key(&id, kp.public_key.0.clone());
+            hops.push(id);
+            keys.push(kp);
+        }
+        (RoutePath { hops }, keys)
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack.windows(needle.len()).any(|w| w == needle)
+    }
+
+    #[test]
+    fn each_relay_sees_only_the_next_hop() {
+        let dr = make_dark_routing();
+        let (route, keys) = relays(&dr);
+        let msg = b"meet at the usual place at noon";
+        let packet = dr.onion_encrypt(msg, &route).unwrap();
+
+        let Peeled::Forward { next_hop, packet: second } = dr.peel_layer(&packet, &keys[0]).unwrap() else {
+            panic!("first relay should forward");
+        };
+        assert_eq!(next_hop, route.hops[1]);
+        // What the first relay holds after peeling does not contain the payload
+        assert!(!contains(&bincode::serialize(&second).unwrap(), msg));
+        assert!(dr.peel_layer(&second, &keys[0]).is_err());
+        assert!(dr.peel_layer(&packet, &keys[1]).is_err());
+
+        let Peeled::Forward { next_hop, packet: third } = dr.peel_layer(&second, &keys[1]).unwrap() else {
+            panic!("second relay should forward");
+        };
+        assert_eq!(next_hop, route.hops[2]);
+        let bytes = bincode::serialize(&third).unwrap();
+        assert_eq!(dr.handle_incoming_layer(&bytes, &keys[2]).unwrap(), Peeled::Deliver(msg.to_vec()));
+    }
+
+    #[test]
+    fn tampered_packet_fails_at_the_next_hop() {
+        let dr = make_dark_routing();
+        let (route, keys) = relays(&dr);
+        let packet = dr.onion_encrypt(b"payload", &route).unwrap();
+        let Peeled::Forward { packet: second, .. } = dr.peel_layer(&packet, &keys[0]).unwrap() else {
+            panic!("first relay should forward");
+        };
+
+        let mut body = second.clone();
+        let last = body.sealed.len() - 1;
+        body.sealed[last] ^= 1;
+        assert!(matches!(dr.peel_layer(&body, &keys[1]), Err(P2PError::DecryptionFailed)));
+
+        let mut kem = second.clone();
+        kem.kem_ciphertext[0] ^= 1;
+        assert!(matches!(dr.peel_layer(&kem, &keys[1]), Err(P2PError::DecryptionFailed)));
+
+        assert!(dr.peel_layer(&second, &keys[1]).is_ok());
+    }
+
+    #[test]
+    fn relay_without_kyber_key_cannot_be_used() {
+        let dr = make_dark_routing();
+        let (mut route, _) = relays(&dr);
+        route.hops.push(NodeId::random());
+        assert!(matches!(dr.onion_encrypt(b"x", &route), Err(P2PError::Crypto(_))));
+        assert!(dr.onion_encrypt(b"x", &RoutePath { hops: vec![] }).is_err());
+    }
 }
@@ -19,6 +19,9 @@ pub enum P2PError {
     #[error("Peer is banned: {peer_id}")]
     PeerBanned { peer_id: String },
 
+    #[error("Too many unauthenticated messages from {addr}")]
+    AddressFlooding { addr: String },
+
     #[error("Peer rejected (trust score {score:.2} below threshold {threshold:.2})")]
     PeerUntrusted { score: f64, threshold: f64 },
 
@@ -57,6 +60,15 @@ pub enum P2PError {
 
     #[error("Sybil attack detected from {peer_id}")]
     SybilDetected { peer_id: String },
+
+    #[error("Transport error: {0}")]
+    Transport(String),
+
+    #[error("Handshake rejected: {0}")]
+    HandshakeRejected(String),
+
+    #[error("Peer busy serving {class}, retry in {retry_after_ms} ms")]
+    Busy { class: &'static str, retry_after_ms: u64 },
 }
 
 pub type P2PResult<T> = Result<T, P2PError>;
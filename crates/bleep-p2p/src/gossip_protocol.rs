@@ -1,12 +1,15 @@
 //! Production gossip protocol for bleep-p2p.
 //!
 //! Implements the Plumtree / epidemic broadcast tree variant:
-//! - Eager-push to a small fanout of highly-trusted peers.
+//! - Eager-push to a small fanout of highly-trusted peers; the fanout adapts
+//!   to the observed duplicate ratio and latency (see `gossip_router`).
+//!   Suspected Sybils are pushed to only when no one else is left.
 //! - Lazy-push (IHave) to the rest for bandwidth efficiency.
 //! - Deduplication via a bounded LRU seen-message cache.
 //! - Anti-flood: per-peer message-rate tracking via PeerScoring.
 //! - All outbound messages are sealed via MessageProtocol (AES-GCM + Ed25519).
 
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -15,16 +18,15 @@ use parking_lot::Mutex;
 use tokio::time::interval;
 use tracing::{debug, warn};
 
+use crate::gossip_router::{AdaptiveFanout, FanoutConfig};
 use crate::message_protocol::MessageProtocol;
 use crate::peer_manager::PeerManager;
-use crate::types::{MessageType, NodeId, SecureMessage};
+use crate::types::{unix_now, MessageType, NodeId, SecureMessage};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // CONSTANTS
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Number of eager peers to push immediately.
-const EAGER_FANOUT: usize = 8;
 /// LRU capacity for seen-message IDs.
 const SEEN_CACHE_CAPACITY: usize = 16_384;
 /// How often the gossip background loop ticks.
@@ -47,6 +49,14 @@ fn message_id(msg: &SecureMessage) -> [u8; 32] {
     out
 }
 
+/// Highest trust first, except that suspected Sybils go after everyone else.
+fn relay_order(scored: &mut [(NodeId, f64)], suspects: &HashSet<NodeId>) {
+    scored.sort_by(|a, b| {
+        suspects.contains(&a.0).cmp(&suspects.contains(&b.0))
+            .then(b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal))
+    });
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // GOSSIP ENGINE
 // ─────────────────────────────────────────────────────────────────────────────
@@ -58,6 +68,8 @@ pub struct GossipProtocol {
     seen: Arc<Mutex<LruCache<[u8; 32], ()>>>,
     /// Pending messages to be spread on the next tick.
     pending: Arc<Mutex<Vec<(SecureMessage, Option<NodeId>)>>>,
+    /// Number of eager peers to push immediately.
+    fanout: Mutex<AdaptiveFanout>,
 }
 
 impl GossipProtocol {
@@ -69,9 +81,15 @@ impl GossipProtocol {
                 unsafe { std::num::NonZeroUsize::new_unchecked(SEEN_CACHE_CAPACITY) },
             ))),
             pending: Arc::new(Mutex::new(Vec::new())),
+            fanout: Mutex::new(AdaptiveFanout::new(FanoutConfig::default())),
         })
     }
 
+    /// Current eager-push fanout.
+    pub fn fanout(&self) -> usize {
+        self.fanout.lock().current()
+    }
+
     /// Enqueue a message for gossip.  `exclude` is the peer we received it from
     /// (to avoid echoing back).
     pub fn enqueue(&self, msg: SecureMessage, exclude: Option<NodeId>) {
@@ -79,11 +97,18 @@ impl GossipProtocol {
         let mut seen = self.seen.lock();
         if seen.contains(&id) {
             debug!("GossipProtocol: dropping duplicate message");
+            drop(seen);
+            self.fanout.lock().observe(true, None);
             return;
         }
         seen.put(id, ());
         drop(seen);
 
+        // `timestamp` has second resolution; latency is coarse but enough
+        // to notice propagation falling behind.
+        let latency_ms = unix_now().saturating_sub(msg.timestamp) * 1000;
+        self.fanout.lock().observe(false, Some(latency_ms));
+
         self.pending.lock().push((msg, exclude));
     }
 
@@ -104,7 +129,7 @@ impl GossipProtocol {
 
         let healthy = self.peer_manager.healthy_peers();
 
-        // Select EAGER_FANOUT highest-scoring peers (excluding sender).
+        // Select the `fanout` highest-scoring peers (excluding sender).
         let candidates: Vec<NodeId> = healthy
             .iter()
             .filter(|p| exclude.map_or(true, |ex| &p.id != ex))
@@ -117,7 +142,7 @@ impl GossipProtocol {
             .all_peers()
             .await;
 
-        // Score-sort candidates
+        // Score-sort candidates, suspected Sybils last
         let mut scored: Vec<(NodeId, f64)> = candidates
             .into_iter()
             .filter_map(|id| {
@@ -125,11 +150,12 @@ impl GossipProtocol {
                 Some((id, info.trust_score))
             })
             .collect();
-        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        let suspects: HashSet<NodeId> = self.peer_manager.sybil_suspects().into_iter().collect();
+        relay_order(&mut scored, &suspects);
 
         let eager: Vec<NodeId> = scored
             .into_iter()
-            .take(EAGER_FANOUT)
+            .take(self.fanout())
             .map(|(id, _)| id)
             .collect();
 
@@ -185,7 +211,7 @@ mod tests {
     use super::*;
     use crate::peer_manager::{PeerManager, PeerManagerConfig};
     use crate::quantum_crypto::{Ed25519Keypair, KyberKeypair};
-    use crate::types::{MessageType, unix_now};
+    use crate::types::MessageType;
 
     fn make_gossip() -> Arc<GossipProtocol> {
         let local_id = NodeId::random();
@@ -238,6 +264,15 @@ mod tests {
         g.spread(msg, None).await;
     }
 
+    #[test]
+    fn test_suspected_sybils_relayed_last() {
+        let (honest, sybil) = (NodeId::random(), NodeId::random());
+        let mut scored = vec![(sybil.clone(), 95.0), (honest.clone(), 60.0)];
+        relay_order(&mut scored, &HashSet::from([sybil.clone()]));
+        assert_eq!(scored[0].0, honest);
+        assert_eq!(scored[1].0, sybil);
+    }
+
     #[test]
     fn test_message_id_deterministic() {
         let msg = make_msg();
@@ -255,6 +290,18 @@ mod tests {
         assert_ne!(message_id(&msg1), message_id(&msg2));
     }
 
+    #[test]
+    fn test_duplicates_shrink_fanout() {
+        let g = make_gossip();
+        let initial = g.fanout();
+        let msg = make_msg();
+        g.enqueue(msg.clone(), None);
+        for _ in 0..FanoutConfig::default().window {
+            g.enqueue(msg.clone(), None);
+        }
+        assert_eq!(g.fanout(), initial - 1);
+    }
+
     #[test]
     fn test_seen_cache_capacity_respected() {
         let g = make_gossip();
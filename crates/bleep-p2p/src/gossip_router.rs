@@ -0,0 +1,966 @@
+//! Topic-aware gossip routing with lazy propagation and adaptive fanout.
+//!
+//! `GossipRouter` is the transport-agnostic core of the gossip layer: it
+//! consumes inbound `GossipFrame`s and returns the frames that should be
+//! sent in response, leaving sealing and delivery to `MessageProtocol`.
+//!
+//! - **Topics** — peers announce the topics they want (`Subscribe`).
+//!   Payloads are only ever forwarded to peers subscribed to their topic, so
+//!   a light node that follows `Headers` and its own `AddressEvents` never
+//!   pays for full blocks.
+//! - **Lazy push (IHAVE/IWANT)** — payloads at or above
+//!   `lazy_threshold_bytes` are advertised by id only; a peer pulls the body
+//!   with `IWant` the first time it hears of it.  Smaller payloads are
+//!   eager-pushed to `fanout` peers and advertised to the rest.
+//! - **Adaptive fanout** — the eager fanout shrinks while the duplicate
+//!   ratio is high and grows when p95 propagation latency exceeds target.
+//! - **Relay screening** — with a `RelayGuard` attached, first receipts
+//!   must pass a cheap stateless check before they are delivered or
+//!   forwarded; peers that keep relaying junk lose eager links and are
+//!   eventually ignored (see `relay_guard`).
+//! - **Pull quotas** — with `ServiceQuotas` attached, `IWant` replies
+//!   count against the requester's `GossipPull` quota; an over-quota pull
+//!   is answered with `Busy` instead of payloads.
+//! - **Telemetry** — duplicate ratio, latency percentiles and per-topic
+//!   traffic are kept in `GossipMetrics` and exported to a
+//!   `bleep_telemetry::MetricsRegistry` via `GossipTelemetry`.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+use bleep_telemetry::metrics::{MetricCounter, MetricGauge, MetricsRegistry};
+use lru::LruCache;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::relay_guard::{RelayGuard, RelayPenalty, RelayStanding};
+use crate::service_quota::{PeerKey, ServiceClass, ServiceQuotas};
+use crate::types::NodeId;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// CONSTANTS
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Payloads at or above this size are propagated lazily (IHAVE/IWANT).
+pub const DEFAULT_LAZY_THRESHOLD_BYTES: usize = 4 * 1024;
+/// Capacity of the seen-id and payload caches.
+const ROUTER_CACHE_CAPACITY: usize = 16_384;
+/// Number of latency samples kept for percentile estimates.
+const LATENCY_WINDOW: usize = 1_024;
+
+/// Gossip message identifier.
+pub type GossipId = [u8; 32];
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TOPICS & FRAMES
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Subscription topic.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Topic {
+    /// Full blocks.
+    Blocks,
+    /// Block headers only (what light nodes follow).
+    Headers,
+    /// Mempool transactions.
+    Transactions,
+    /// Events touching one address.
+    AddressEvents(String),
+    /// Consensus misbehaviour evidence.
+    Evidence,
+}
+
+impl Topic {
+    /// Telemetry label; address topics are aggregated under `events`.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Topic::Blocks           => "blocks",
+            Topic::Headers          => "headers",
+            Topic::Transactions     => "transactions",
+            Topic::AddressEvents(_) => "events",
+            Topic::Evidence         => "evidence",
+        }
+    }
+}
+
+/// Wire frames exchanged by `GossipRouter`s.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GossipFrame {
+    Subscribe { topics: Vec<Topic> },
+    Unsubscribe { topics: Vec<Topic> },
+    /// Full payload.  `origin_ms` is the publisher's timestamp, used for
+    /// propagation-latency measurement.
+    Publish { id: GossipId, topic: Topic, payload: Vec<u8>, origin_ms: u64 },
+    /// "I have these messages" — lazy advertisement.
+    IHave { topic: Topic, ids: Vec<GossipId> },
+    /// "Send me these payloads" — reply to `IHave`.
+    IWant { ids: Vec<GossipId> },
+    /// Reply to an `IWant` over the requester's pull quota.
+    Busy { retry_after_ms: u64 },
+}
+
+impl GossipFrame {
+    pub fn is_full_payload(&self) -> bool {
+        matches!(self, GossipFrame::Publish { .. })
+    }
+}
+
+/// A frame to be sent to `to`.
+#[derive(Debug, Clone)]
+pub struct Outbound {
+    pub to:    NodeId,
+    pub frame: GossipFrame,
+}
+
+/// Deterministic id over (topic ‖ payload ‖ origin timestamp).
+pub fn gossip_id(topic: &Topic, payload: &[u8], origin_ms: u64) -> GossipId {
+    let mut h = Sha256::new();
+    h.update(topic.label().as_bytes());
+    if let Topic::AddressEvents(addr) = topic {
+        h.update(addr.as_bytes());
+    }
+    h.update(payload);
+    h.update(origin_ms.to_le_bytes());
+    h.finalize().into()
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ADAPTIVE FANOUT
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Tuning knobs for `AdaptiveFanout`.
+#[derive(Debug, Clone)]
+pub struct FanoutConfig {
+    pub initial: usize,
+    pub min:     usize,
+    pub max:     usize,
+    /// Shrink when more than this share of received payloads are duplicates.
+    pub high_duplicate_ratio: f64,
+    /// Grow when p95 propagation latency exceeds this many milliseconds.
+    pub latency_target_ms: u64,
+    /// Receipts per adjustment window.
+    pub window: usize,
+}
+
+impl Default for FanoutConfig {
+    fn default() -> Self {
+        FanoutConfig {
+            initial:              8,
+            min:                  2,
+            max:                  16,
+            high_duplicate_ratio: 0.5,
+            latency_target_ms:    500,
+            window:               32,
+        }
+    }
+}
+
+/// Eager-push fanout that tracks network conditions.
+///
+/// Every `window` receipts the fanout moves by one step: up if p95 latency
+/// over the window is above target (propagation is too slow), otherwise
+/// down if the duplicate ratio is above `high_duplicate_ratio` (we are
+/// sending the same payload along too many paths).  Latency wins when both
+/// fire, since reaching every peer matters more than saving bandwidth.
+#[derive(Debug, Clone)]
+pub struct AdaptiveFanout {
+    config:     FanoutConfig,
+    current:    usize,
+    received:   usize,
+    duplicates: usize,
+    latencies:  Vec<u64>,
+}
+
+impl AdaptiveFanout {
+    pub fn new(config: FanoutConfig) -> Self {
+        let current = config.initial.clamp(config.min, config.max);
+        AdaptiveFanout { config, current, received: 0, duplicates: 0, latencies: Vec::new() }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// Record one payload receipt.  `latency_ms` is only known for first
+    /// receipts.
+    pub fn observe(&mut self, duplicate: bool, latency_ms: Option<u64>) {
+        self.received += 1;
+        if duplicate {
+            self.duplicates += 1;
+        }
+        if let Some(l) = latency_ms {
+            self.latencies.push(l);
+        }
+        if self.received >= self.config.window {
+            self.adjust();
+        }
+    }
+
+    fn adjust(&mut self) {
+        let dup_ratio = self.duplicates as f64 / self.received as f64;
+        let slow = percentile(&mut self.latencies, 95)
+            .is_some_and(|p95| p95 > self.config.latency_target_ms);
+
+        if slow {
+            self.current = (self.current + 1).min(self.config.max);
+        } else if dup_ratio > self.config.high_duplicate_ratio {
+            self.current = self.current.saturating_sub(1).max(self.config.min);
+        }
+        self.received = 0;
+        self.duplicates = 0;
+        self.latencies.clear();
+    }
+}
+
+/// Nearest-rank percentile; sorts `samples` in place.
+fn percentile(samples: &mut [u64], pct: usize) -> Option<u64> {
+    if samples.is_empty() {
+        return None;
+    }
+    samples.sort_unstable();
+    let rank = (pct * samples.len()).div_ceil(100);
+    Some(samples[rank.clamp(1, samples.len()) - 1])
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// METRICS
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Per-topic traffic counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TopicTraffic {
+    pub messages: u64,
+    pub bytes:    u64,
+}
+
+/// Counters maintained by a `GossipRouter`.
+#[derive(Debug, Clone, Default)]
+pub struct GossipMetrics {
+    /// Full payloads received (first receipts + duplicates).
+    pub payloads_received: u64,
+    pub duplicates:        u64,
+    /// Full payloads sent (eager pushes and IWANT replies).
+    pub payloads_sent:     u64,
+    pub ihave_sent:        u64,
+    pub iwant_sent:        u64,
+    /// Payloads received for a topic we are not subscribed to (dropped).
+    pub unsolicited:       u64,
+    /// Payloads that failed relay screening or came from a banned peer.
+    pub relay_dropped:     u64,
+    /// First-receipt traffic per topic label.
+    pub per_topic:         HashMap<&'static str, TopicTraffic>,
+    latencies:             VecDeque<u64>,
+}
+
+impl GossipMetrics {
+    /// Share of received payloads that were duplicates.
+    pub fn duplicate_ratio(&self) -> f64 {
+        if self.payloads_received == 0 {
+            return 0.0;
+        }
+        self.duplicates as f64 / self.payloads_received as f64
+    }
+
+    /// Propagation-latency percentile (ms) over the recent window.
+    pub fn latency_percentile(&self, pct: usize) -> Option<u64> {
+        let mut samples: Vec<u64> = self.latencies.iter().copied().collect();
+        percentile(&mut samples, pct)
+    }
+
+    fn record_latency(&mut self, ms: u64) {
+        if self.latencies.len() == LATENCY_WINDOW {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(ms);
+    }
+}
+
+/// Telemetry handles for exporting `GossipMetrics`.
+///
+/// Gauges are absolute snapshots; call `GossipTelemetry::export` whenever
+/// a fresh reading is wanted (e.g. on each gossip tick).
+#[derive(Debug, Clone)]
+pub struct GossipTelemetry {
+    duplicate_ratio_permille: MetricGauge,
+    latency_p50_ms:           MetricGauge,
+    latency_p95_ms:           MetricGauge,
+    latency_p99_ms:           MetricGauge,
+    fanout:                   MetricGauge,
+    payloads_sent:            MetricGauge,
+    topic_messages:           HashMap<&'static str, MetricCounter>,
+    topic_bytes:              HashMap<&'static str, MetricCounter>,
+    last_topic:               HashMap<&'static str, TopicTraffic>,
+}
+
+impl GossipTelemetry {
+    const TOPIC_LABELS: [&'static str; 4] = ["blocks", "headers", "transactions", "events"];
+
+    pub fn register(registry: &mut MetricsRegistry) -> Self {
+        let mut topic_messages = HashMap::new();
+        let mut topic_bytes = HashMap::new();
+        for label in Self::TOPIC_LABELS {
+            topic_messages.insert(label, registry.counter(&format!("bleep_gossip_{}_messages_total", label)));
+            topic_bytes.insert(label, registry.counter(&format!("bleep_gossip_{}_bytes_total", label)));
+        }
+        GossipTelemetry {
+            duplicate_ratio_permille: registry.gauge("bleep_gossip_duplicate_ratio_permille"),
+            latency_p50_ms:           registry.gauge("bleep_gossip_latency_p50_ms"),
+            latency_p95_ms:           registry.gauge("bleep_gossip_latency_p95_ms"),
+            latency_p99_ms:           registry.gauge("bleep_gossip_latency_p99_ms"),
+            fanout:                   registry.gauge("bleep_gossip_fanout"),
+            payloads_sent:            registry.gauge("bleep_gossip_payloads_sent"),
+            topic_messages,
+            topic_bytes,
+            last_topic: HashMap::new(),
+        }
+    }
+
+    /// Push the current readings of `router` into the registered metrics.
+    pub fn export(&mut self, router: &GossipRouter) {
+        let m = router.metrics();
+        self.duplicate_ratio_permille.set((m.duplicate_ratio() * 1000.0) as i64);
+        self.latency_p50_ms.set(m.latency_percentile(50).unwrap_or(0) as i64);
+        self.latency_p95_ms.set(m.latency_percentile(95).unwrap_or(0) as i64);
+        self.latency_p99_ms.set(m.latency_percentile(99).unwrap_or(0) as i64);
+        self.fanout.set(router.fanout() as i64);
+        self.payloads_sent.set(m.payloads_sent as i64);
+
+        // Counters are monotonic: add only what changed since the last export.
+        for (label, now) in &m.per_topic {
+            let prev = self.last_topic.insert(*label, *now).unwrap_or_default();
+            if let Some(c) = self.topic_messages.get(*label) {
+                c.add(now.messages - prev.messages);
+            }
+            if let Some(c) = self.topic_bytes.get(*label) {
+                c.add(now.bytes - prev.bytes);
+            }
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// ROUTER
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct GossipRouterConfig {
+    pub lazy_threshold_bytes: usize,
+    pub fanout:               FanoutConfig,
+}
+
+impl Default for GossipRouterConfig {
+    fn default() -> Self {
+        GossipRouterConfig {
+            lazy_threshold_bytes: DEFAULT_LAZY_THRESHOLD_BYTES,
+            fanout:               FanoutConfig::default(),
+        }
+    }
+}
+
+struct StoredMessage {
+    topic:     Topic,
+    payload:   Vec<u8>,
+    origin_ms: u64,
+}
+
+/// Per-node gossip state machine.
+pub struct GossipRouter {
+    config:        GossipRouterConfig,
+    subscriptions: HashSet<Topic>,
+    /// Topics each connected peer has subscribed to.
+    peers:         HashMap<NodeId, HashSet<Topic>>,
+    seen:          LruCache<GossipId, ()>,
+    /// Payloads we can serve in reply to `IWant`.
+    store:         LruCache<GossipId, StoredMessage>,
+    /// Ids we have already asked for (avoids duplicate pulls).
+    requested:     HashSet<GossipId>,
+    fanout:        AdaptiveFanout,
+    metrics:       GossipMetrics,
+    /// Payloads delivered to the local application, in arrival order.
+    inbox:         Vec<(Topic, Vec<u8>)>,
+    relay:         Option<RelayGuard>,
+    quotas:        Option<Arc<ServiceQuotas>>,
+}
+
+impl GossipRouter {
+    pub fn new(config: GossipRouterConfig) -> Self {
+        let cap = NonZeroUsize::new(ROUTER_CACHE_CAPACITY).expect("non-zero capacity");
+        GossipRouter {
+            fanout:        AdaptiveFanout::new(config.fanout.clone()),
+            config,
+            subscriptions: HashSet::new(),
+            peers:         HashMap::new(),
+            seen:          LruCache::new(cap),
+            store:         LruCache::new(cap),
+            requested:     HashSet::new(),
+            metrics:       GossipMetrics::default(),
+            inbox:         Vec::new(),
+            relay:         None,
+            quotas:        None,
+        }
+    }
+
+    /// Screen inbound payloads with `guard` before delivering or relaying them.
+    pub fn with_relay_guard(mut self, guard: RelayGuard) -> Self {
+        self.relay = Some(guard);
+        self
+    }
+
+    /// Meter `IWant` replies against `quotas`.
+    pub fn with_service_quotas(mut self, quotas: Arc<ServiceQuotas>) -> Self {
+        self.quotas = Some(quotas);
+        self
+    }
+
+    pub fn relay_guard(&self) -> Option<&RelayGuard> {
+        self.relay.as_ref()
+    }
+
+    /// Drain reputation penalties for `PeerManager::apply_relay_penalties`.
+    pub fn take_relay_penalties(&mut self) -> Vec<RelayPenalty> {
+        self.relay.as_mut().map(|g| g.take_penalties()).unwrap_or_default()
+    }
+
+    fn standing(&self, peer: &NodeId) -> RelayStanding {
+        self.relay.as_ref().map_or(RelayStanding::Trusted, |g| g.standing(peer))
+    }
+
+    pub fn fanout(&self) -> usize {
+        self.fanout.current()
+    }
+
+    pub fn metrics(&self) -> &GossipMetrics {
+        &self.metrics
+    }
+
+    pub fn is_subscribed(&self, topic: &Topic) -> bool {
+        self.subscriptions.contains(topic)
+    }
+
+    /// Drain payloads delivered since the last call.
+    pub fn take_delivered(&mut self) -> Vec<(Topic, Vec<u8>)> {
+        std::mem::take(&mut self.inbox)
+    }
+
+    /// Register a newly connected peer and tell it what we subscribe to.
+    pub fn add_peer(&mut self, peer: NodeId) -> Vec<Outbound> {
+        self.peers.entry(peer.clone()).or_default();
+        if self.subscriptions.is_empty() {
+            return Vec::new();
+        }
+        vec![Outbound { to: peer, frame: GossipFrame::Subscribe { topics: self.subscriptions.iter().cloned().collect() } }]
+    }
+
+    pub fn remove_peer(&mut self, peer: &NodeId) {
+        self.peers.remove(peer);
+    }
+
+    /// Subscribe locally and announce it to every peer.
+    pub fn subscribe(&mut self, topics: Vec<Topic>) -> Vec<Outbound> {
+        self.subscriptions.extend(topics.iter().cloned());
+        self.broadcast(GossipFrame::Subscribe { topics })
+    }
+
+    pub fn unsubscribe(&mut self, topics: Vec<Topic>) -> Vec<Outbound> {
+        for t in &topics {
+            self.subscriptions.remove(t);
+        }
+        self.broadcast(GossipFrame::Unsubscribe { topics })
+    }
+
+    /// Originate a message.
+    pub fn publish(&mut self, topic: Topic, payload: Vec<u8>, now_ms: u64) -> Vec<Outbound> {
+        let id = gossip_id(&topic, &payload, now_ms);
+        if self.seen.put(id, ()).is_some() {
+            return Vec::new();
+        }
+        let out = self.forward(id, &topic, &payload, now_ms, None);
+        self.store.put(id, StoredMessage { topic, payload, origin_ms: now_ms });
+        out
+    }
+
+    /// Process one inbound frame from `from`.
+    pub fn handle(&mut self, from: &NodeId, frame: GossipFrame, now_ms: u64) -> Vec<Outbound> {
+        if self.standing(from) == RelayStanding::Banned {
+            if frame.is_full_payload() {
+                self.metrics.relay_dropped += 1;
+            }
+            return Vec::new();
+        }
+        match frame {
+            GossipFrame::Subscribe { topics } => {
+                self.peers.entry(from.clone()).or_default().extend(topics);
+                Vec::new()
+            }
+            GossipFrame::Unsubscribe { topics } => {
+                if let Some(subs) = self.peers.get_mut(from) {
+                    for t in &topics {
+                        subs.remove(t);
+                    }
+                }
+                Vec::new()
+            }
+            GossipFrame::Publish { id, topic, payload, origin_ms } => {
+                self.on_publish(from, id, topic, payload, origin_ms, now_ms)
+            }
+            GossipFrame::IHave { topic, ids } => {
+                if !self.is_subscribed(&topic) {
+                    return Vec::new();
+                }
+                let wanted: Vec<GossipId> = ids
+                    .into_iter()
+                    .filter(|id| !self.seen.contains(id) && self.requested.insert(*id))
+                    .collect();
+                if wanted.is_empty() {
+                    return Vec::new();
+                }
+                self.metrics.iwant_sent += 1;
+                vec![Outbound { to: from.clone(), frame: GossipFrame::IWant { ids: wanted } }]
+            }
+            GossipFrame::IWant { ids } => {
+                // The quota keeps its own clock, shared with the other classes.
+                let key = PeerKey::Node(from.clone());
+                if let Some(quotas) = &self.quotas {
+                    if let Err(busy) = quotas.admit(&key, ServiceClass::GossipPull, quotas.clock_ms()) {
+                        let frame = GossipFrame::Busy { retry_after_ms: busy.retry_after_ms };
+                        return vec![Outbound { to: from.clone(), frame }];
+                    }
+                }
+                let mut out = Vec::new();
+                for id in ids {
+                    let Some(msg) = self.store.get(&id) else { continue };
+                    // Never hand a payload to a peer that did not subscribe to it.
+                    if !self.peers.get(from).is_some_and(|s| s.contains(&msg.topic)) {
+                        continue;
+                    }
+                    out.push(Outbound {
+                        to:    from.clone(),
+                        frame: GossipFrame::Publish {
+                            id,
+                            topic:     msg.topic.clone(),
+                            payload:   msg.payload.clone(),
+                            origin_ms: msg.origin_ms,
+                        },
+                    });
+                }
+                self.metrics.payloads_sent += out.len() as u64;
+                if let Some(quotas) = &self.quotas {
+                    let bytes = out.iter()
+                        .map(|o| match &o.frame { GossipFrame::Publish { payload, .. } => payload.len(), _ => 0 })
+                        .sum();
+                    quotas.charge(&key, ServiceClass::GossipPull, bytes, quotas.clock_ms());
+                }
+                out
+            }
+            GossipFrame::Busy { .. } => Vec::new(),
+        }
+    }
+
+    fn on_publish(
+        &mut self,
+        from:      &NodeId,
+        id:        GossipId,
+        topic:     Topic,
+        payload:   Vec<u8>,
+        origin_ms: u64,
+        now_ms:    u64,
+    ) -> Vec<Outbound> {
+        if !self.is_subscribed(&topic) {
+            self.metrics.unsolicited += 1;
+            return Vec::new();
+        }
+        self.metrics.payloads_received += 1;
+        if self.seen.put(id, ()).is_some() {
+            self.metrics.duplicates += 1;
+            self.fanout.observe(true, None);
+            return Vec::new();
+        }
+        self.requested.remove(&id);
+
+        // Junk stops here: not delivered, not stored, not forwarded.
+        if let Some(guard) = self.relay.as_mut() {
+            if guard.screen(from, &topic, &payload).is_err() {
+                self.metrics.relay_dropped += 1;
+                return Vec::new();
+            }
+        }
+
+        let latency = now_ms.saturating_sub(origin_ms);
+        self.metrics.record_latency(latency);
+        self.fanout.observe(false, Some(latency));
+        let traffic = self.metrics.per_topic.entry(topic.label()).or_default();
+        traffic.messages += 1;
+        traffic.bytes += payload.len() as u64;
+
+        let out = self.forward(id, &topic, &payload, origin_ms, Some(from));
+        self.inbox.push((topic.clone(), payload.clone()));
+        self.store.put(id, StoredMessage { topic, payload, origin_ms });
+        out
+    }
+
+    /// Eager-push / lazy-advertise `id` to every peer subscribed to `topic`.
+    fn forward(
+        &mut self,
+        id:        GossipId,
+        topic:     &Topic,
+        payload:   &[u8],
+        origin_ms: u64,
+        exclude:   Option<&NodeId>,
+    ) -> Vec<Outbound> {
+        let mut targets: Vec<(RelayStanding, NodeId)> = self
+            .peers
+            .iter()
+            .filter(|(peer, subs)| exclude != Some(*peer) && subs.contains(topic))
+            .map(|(peer, _)| (self.standing(peer), peer.clone()))
+            .filter(|(standing, _)| *standing != RelayStanding::Banned)
+            .collect();
+        // Demoted peers sort last so eager pushes go to trusted links first.
+        targets.sort_by_key(|(standing, peer)| (*standing == RelayStanding::Demoted, peer.0));
+
+        let eager = if payload.len() >= self.config.lazy_threshold_bytes {
+            0
+        } else {
+            self.fanout.current()
+        };
+
+        let mut out = Vec::with_capacity(targets.len());
+        for (i, (standing, to)) in targets.into_iter().enumerate() {
+            let frame = if i < eager && standing == RelayStanding::Trusted {
+                self.metrics.payloads_sent += 1;
+                GossipFrame::Publish { id, topic: topic.clone(), payload: payload.to_vec(), origin_ms }
+            } else {
+                self.metrics.ihave_sent += 1;
+                GossipFrame::IHave { topic: topic.clone(), ids: vec![id] }
+            };
+            out.push(Outbound { to, frame });
+        }
+        out
+    }
+
+    fn broadcast(&self, frame: GossipFrame) -> Vec<Outbound> {
+        self.peers
+            .keys()
+            .map(|p| Outbound { to: p.clone(), frame: frame.clone() })
+            .collect()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TESTS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relay_guard::{RelayPolicy, RelayReject, RelayValidator};
+    use std::sync::Arc;
+
+    /// Transactions: a zero first byte is malformed.
+    /// Blocks: the first byte is the signer and must be validator 1 or 2.
+    struct TestRules;
+
+    impl RelayValidator for TestRules {
+        fn check(&self, topic: &Topic, payload: &[u8]) -> Result<(), RelayReject> {
+            match (topic, payload.first()) {
+                (Topic::Transactions, Some(0) | None) => Err(RelayReject::Malformed("zero tag".into())),
+                (Topic::Blocks, Some(1 | 2)) => Ok(()),
+                (Topic::Blocks, _) => Err(RelayReject::UnknownSigner),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// In-process full-mesh cluster driving routers through a FIFO wire.
+    struct Cluster {
+        ids:     Vec<NodeId>,
+        routers: Vec<GossipRouter>,
+        wire:    VecDeque<(NodeId, Outbound)>,
+        /// Every frame that crossed the wire, as (sender index, receiver index, frame).
+        log:     Vec<(usize, usize, GossipFrame)>,
+        clock:   u64,
+    }
+
+    impl Cluster {
+        fn new(n: usize, config: GossipRouterConfig) -> Self {
+            Self::build(n, (0..n).map(|_| GossipRouter::new(config.clone())).collect())
+        }
+
+        /// Every router screens with `TestRules`.
+        fn guarded(n: usize) -> Self {
+            let routers = (0..n)
+                .map(|_| {
+                    GossipRouter::new(GossipRouterConfig::default())
+                        .with_relay_guard(RelayGuard::new(Arc::new(TestRules), RelayPolicy::default()))
+                })
+                .collect();
+            Self::build(n, routers)
+        }
+
+        fn build(n: usize, routers: Vec<GossipRouter>) -> Self {
+            let ids: Vec<NodeId> = (0..n).map(|i| NodeId::from_bytes(&[i as u8])).collect();
+            let mut c = Cluster { ids, routers, wire: VecDeque::new(), log: Vec::new(), clock: 0 };
+            for i in 0..n {
+                for j in 0..n {
+                    if i != j {
+                        let peer = c.ids[j].clone();
+                        let out = c.routers[i].add_peer(peer);
+                        c.send(i, out);
+                    }
+                }
+            }
+            c
+        }
+
+        fn index(&self, id: &NodeId) -> usize {
+            self.ids.iter().position(|x| x == id).unwrap()
+        }
+
+        fn send(&mut self, from: usize, out: Vec<Outbound>) {
+            let sender = self.ids[from].clone();
+            self.wire.extend(out.into_iter().map(|o| (sender.clone(), o)));
+        }
+
+        fn subscribe(&mut self, node: usize, topics: Vec<Topic>) {
+            let out = self.routers[node].subscribe(topics);
+            self.send(node, out);
+            self.run();
+        }
+
+        fn publish(&mut self, node: usize, topic: Topic, payload: Vec<u8>) {
+            let out = self.routers[node].publish(topic, payload, self.clock);
+            self.send(node, out);
+            self.run();
+        }
+
+        fn run(&mut self) {
+            while let Some((from, o)) = self.wire.pop_front() {
+                self.clock += 1;
+                let to = self.index(&o.to);
+                self.log.push((self.index(&from), to, o.frame.clone()));
+                let out = self.routers[to].handle(&from, o.frame, self.clock);
+                self.send(to, out);
+            }
+        }
+
+        fn full_payloads(&self) -> usize {
+            self.log.iter().filter(|(_, _, f)| f.is_full_payload()).count()
+        }
+    }
+
+    #[test]
+    fn test_lazy_block_propagation_beats_flooding() {
+        const N: usize = 10;
+        let mut c = Cluster::new(N, GossipRouterConfig::default());
+        for i in 0..N {
+            c.subscribe(i, vec![Topic::Blocks]);
+        }
+        let block = vec![0xAB; 64 * 1024];
+        c.publish(0, Topic::Blocks, block.clone());
+
+        for i in 1..N {
+            let got = c.routers[i].take_delivered();
+            assert_eq!(got, vec![(Topic::Blocks, block.clone())], "node {} missed the block", i);
+        }
+        // Flooding: origin sends to N-1, every receiver forwards to N-2 more.
+        let flooding = (N - 1) + (N - 1) * (N - 2);
+        assert!(c.full_payloads() < flooding);
+        assert_eq!(c.full_payloads(), N - 1, "each node pulls the body exactly once");
+    }
+
+    #[test]
+    fn test_topic_filtered_node_never_receives_unsubscribed_payloads() {
+        let mut c = Cluster::new(6, GossipRouterConfig::default());
+        for i in 0..5 {
+            c.subscribe(i, vec![Topic::Blocks, Topic::Headers, Topic::Transactions]);
+        }
+        let light = 5;
+        c.subscribe(light, vec![Topic::Headers, Topic::AddressEvents("bleep1light".into())]);
+
+        c.publish(0, Topic::Blocks, vec![1; 8 * 1024]);
+        c.publish(1, Topic::Transactions, vec![2; 200]);
+        c.publish(2, Topic::Headers, vec![3; 200]);
+        c.publish(3, Topic::AddressEvents("bleep1other".into()), vec![4; 50]);
+
+        let delivered = c.routers[light].take_delivered();
+        assert_eq!(delivered, vec![(Topic::Headers, vec![3; 200])]);
+        assert_eq!(c.routers[light].metrics().unsolicited, 0);
+        let leaked = c.log.iter().any(|(_, to, f)| {
+            *to == light && matches!(f, GossipFrame::Publish { topic, .. } | GossipFrame::IHave { topic, .. } if *topic != Topic::Headers)
+        });
+        assert!(!leaked, "light node was sent traffic for a topic it does not follow");
+    }
+
+    #[test]
+    fn test_fanout_shrinks_under_duplicates() {
+        const N: usize = 10;
+        let mut c = Cluster::new(N, GossipRouterConfig::default());
+        for i in 0..N {
+            c.subscribe(i, vec![Topic::Transactions]);
+        }
+        let initial = c.routers[1].fanout();
+        for k in 0..60u32 {
+            c.publish((k as usize) % N, Topic::Transactions, k.to_le_bytes().to_vec());
+        }
+        let router = &c.routers[1];
+        assert!(router.metrics().duplicate_ratio() > 0.5);
+        assert!(router.fanout() < initial, "fanout {} did not shrink", router.fanout());
+        assert!(router.fanout() >= FanoutConfig::default().min);
+    }
+
+    #[test]
+    fn test_fanout_grows_when_latency_degrades() {
+        let cfg = FanoutConfig::default();
+        let mut f = AdaptiveFanout::new(cfg.clone());
+        for _ in 0..cfg.window {
+            f.observe(false, Some(cfg.latency_target_ms * 4));
+        }
+        assert_eq!(f.current(), cfg.initial + 1);
+    }
+
+    #[test]
+    fn test_telemetry_export() {
+        let mut c = Cluster::new(3, GossipRouterConfig::default());
+        for i in 0..3 {
+            c.subscribe(i, vec![Topic::Headers]);
+        }
+        c.publish(0, Topic::Headers, vec![9; 100]);
+
+        let mut registry = MetricsRegistry::new();
+        let mut tel = GossipTelemetry::register(&mut registry);
+        tel.export(&c.routers[1]);
+        tel.export(&c.routers[1]);
+        assert_eq!(tel.topic_messages["headers"].get(), 1);
+        assert_eq!(tel.topic_bytes["headers"].get(), 100);
+        assert_eq!(tel.fanout.get(), c.routers[1].fanout() as i64);
+    }
+
+    /// Senders of any frame carrying or advertising `id`.
+    fn carriers(c: &Cluster, id: GossipId) -> HashSet<usize> {
+        c.log
+            .iter()
+            .filter(|(_, _, f)| match f {
+                GossipFrame::Publish { id: x, .. } => *x == id,
+                GossipFrame::IHave { ids, .. } => ids.contains(&id),
+                _ => false,
+            })
+            .map(|(from, _, _)| *from)
+            .collect()
+    }
+
+    #[test]
+    fn test_malformed_tx_not_forwarded_beyond_first_hop() {
+        const N: usize = 6;
+        let mut c = Cluster::guarded(N);
+        for i in 0..N {
+            c.subscribe(i, vec![Topic::Transactions]);
+        }
+        let junk = vec![0u8; 120];
+        let id = gossip_id(&Topic::Transactions, &junk, c.clock);
+        c.publish(0, Topic::Transactions, junk);
+
+        assert_eq!(carriers(&c, id), HashSet::from([0]), "only the origin may carry the junk tx");
+        for i in 1..N {
+            assert!(c.routers[i].take_delivered().is_empty());
+            assert_eq!(c.routers[i].metrics().relay_dropped, 1);
+        }
+
+        let good = vec![7u8; 120];
+        c.publish(1, Topic::Transactions, good.clone());
+        for i in (0..N).filter(|i| *i != 1) {
+            assert_eq!(c.routers[i].take_delivered(), vec![(Topic::Transactions, good.clone())]);
+        }
+    }
+
+    #[test]
+    fn test_block_from_non_validator_dropped_at_relay() {
+        const N: usize = 5;
+        let mut c = Cluster::guarded(N);
+        for i in 0..N {
+            c.subscribe(i, vec![Topic::Blocks]);
+        }
+        // Large enough to go lazy: every hop must pull, screen and drop it.
+        let forged = vec![9u8; 16 * 1024];
+        let id = gossip_id(&Topic::Blocks, &forged, c.clock);
+        c.publish(0, Topic::Blocks, forged);
+        assert_eq!(carriers(&c, id), HashSet::from([0]));
+        for i in 1..N {
+            assert!(c.routers[i].take_delivered().is_empty(), "node {} accepted a forged block", i);
+        }
+
+        let signed = vec![1u8; 16 * 1024];
+        c.publish(2, Topic::Blocks, signed.clone());
+        for i in (0..N).filter(|i| *i != 2) {
+            assert_eq!(c.routers[i].take_delivered(), vec![(Topic::Blocks, signed.clone())]);
+        }
+    }
+
+    #[test]
+    fn test_offending_peer_is_banned_and_honest_peers_unaffected() {
+        const N: usize = 4;
+        let mut c = Cluster::guarded(N);
+        for i in 0..N {
+            c.subscribe(i, vec![Topic::Transactions]);
+        }
+        for k in 0..10u8 {
+            c.publish(0, Topic::Transactions, vec![0, k]);
+            c.publish(1 + (k as usize % 2), Topic::Transactions, vec![1, k]);
+        }
+
+        let (spammer, honest_a, honest_b) = (c.ids[0].clone(), c.ids[1].clone(), c.ids[2].clone());
+        let router = &mut c.routers[3];
+        let guard = router.relay_guard().unwrap();
+        assert_eq!(guard.standing(&spammer), RelayStanding::Banned);
+        assert_eq!(guard.standing(&honest_a), RelayStanding::Trusted);
+        assert_eq!(guard.standing(&honest_b), RelayStanding::Trusted);
+        assert_eq!(guard.stats(&honest_a).rejected, 0);
+
+        let penalties = router.take_relay_penalties();
+        assert!(penalties.contains(&RelayPenalty::Ban(spammer.clone())));
+        assert!(penalties.iter().all(|p| matches!(p, RelayPenalty::Failure(id) | RelayPenalty::Ban(id) if *id == spammer)));
+
+        // Once banned, even well-formed payloads from the spammer are ignored
+        // and it gets no more traffic from this node.
+        router.take_delivered();
+        let before = c.log.len();
+        c.publish(0, Topic::Transactions, vec![5, 5]);
+        assert!(c.routers[3].take_delivered().is_empty());
+        let ids0 = c.ids[0].clone();
+        c.publish(3, Topic::Transactions, vec![6, 6]);
+        assert!(!c.log[before..].iter().any(|(from, to, _)| *from == 3 && c.ids[*to] == ids0));
+    }
+
+    #[test]
+    fn test_iwant_over_pull_quota_is_answered_busy() {
+        use crate::service_quota::{ClassQuota, ServiceQuotaConfig};
+
+        let config = ServiceQuotaConfig {
+            gossip_pull: ClassQuota { requests_per_sec: 2, bytes_per_sec: 1024 * 1024 },
+            ..Default::default()
+        };
+        let quotas = Arc::new(ServiceQuotas::new(config));
+        let mut router = GossipRouter::new(GossipRouterConfig::default()).with_service_quotas(Arc::clone(&quotas));
+        let peer = NodeId::from_bytes(&[7]);
+        router.add_peer(peer.clone());
+        router.handle(&peer, GossipFrame::Subscribe { topics: vec![Topic::Blocks] }, 0);
+        router.publish(Topic::Blocks, vec![1; 1000], 0);
+        let id = gossip_id(&Topic::Blocks, &[1; 1000], 0);
+
+        for _ in 0..2 {
+            let out = router.handle(&peer, GossipFrame::IWant { ids: vec![id] }, 1);
+            assert!(out[0].frame.is_full_payload());
+        }
+        let out = router.handle(&peer, GossipFrame::IWant { ids: vec![id] }, 1);
+        assert!(matches!(out[..], [Outbound { frame: GossipFrame::Busy { retry_after_ms }, .. }] if retry_after_ms > 0));
+        let stats = quotas.stats(&PeerKey::Node(peer)).unwrap();
+        assert_eq!((stats.classes["gossip_pull"].bytes, stats.rejected()), (2000, 1));
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let mut s: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile(&mut s, 50), Some(50));
+        assert_eq!(percentile(&mut s, 99), Some(99));
+        assert_eq!(percentile(&mut [], 50), None);
+    }
+}
@@ -1,40 +1,242 @@
 //! Production Kademlia DHT implementation for peer discovery.
 //!
 //! Implements:
-//! - XOR metric routing with 256-bit key space
-//! - K-buckets (k=20) with LRU eviction
-//! - FIND_NODE / FIND_VALUE / STORE / PING RPCs (in-process for integration;
-//!   wire transport delegated to the message layer)
-//! - Parallel alpha=3 lookups (iterative closest-node algorithm)
+//! - XOR metric routing with 256-bit key space; node ids are the SHA-256 of
+//!   the node's Ed25519 identity key
+//! - K-buckets (k=20) with LRU eviction: a full bucket pings its least
+//!   reputable, least recently seen entry and replaces it only if it does
+//!   not answer; malicious and banned peers are never inserted
+//! - FIND_NODE / FIND_VALUE / STORE / PING RPCs and provider records over
+//!   `dht` request/response frames (`DhtTransport`)
+//! - Iterative lookups with alpha=3 requests in flight, each request bounded
+//!   by `REQUEST_TIMEOUT` and the whole lookup by `LOOKUP_TIMEOUT`
+//! - Values and provider records expire after their TTL; those this node
+//!   published are re-published every `REPUBLISH_INTERVAL`
 //! - Bucket refresh on a background timer
+//!
+//! Provider records say "this node can serve X": snap-sync servers announce
+//! the checkpoint they serve under `snap_checkpoint_key`, interop relayers
+//! the chains they attest for under `relayer_key`.
 
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 
+use async_trait::async_trait;
 use dashmap::DashMap;
-use tokio::sync::RwLock;
-use tokio::time::interval;
+use futures::future::join_all;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::time::{interval, timeout, timeout_at};
 use tracing::{debug, info};
 
-use crate::types::{NodeId, PeerInfo, unix_now};
+use crate::error::{P2PError, P2PResult};
+use crate::message_protocol::MessageProtocol;
+use crate::quantum_crypto::{ed25519_verify, NodeIdentity};
+use crate::service_quota::Busy;
+use crate::transport::Transport;
+use crate::types::{unix_now, MessageType, NodeId, PeerInfo, PeerStatus, SecureMessage};
 
 // ─────────────────────────────────────────────────────────────────────────────
 // CONSTANTS
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// `MessageType::Custom` tag of DHT frames.
+pub const DHT_MESSAGE: &str = "dht";
+
 /// Max peers per K-bucket.
 const K: usize = 20;
 /// Parallel lookup concurrency.
-#[allow(dead_code)]
 const ALPHA: usize = 3;
 /// Number of K-buckets (one per bit in the 256-bit key space).
 const BUCKET_COUNT: usize = 256;
 /// How often to refresh a bucket that has not been looked up.
 const BUCKET_REFRESH_INTERVAL: Duration = Duration::from_secs(3600);
-/// TTL for DHT values.
-const VALUE_TTL_SECS: u64 = 86400;
+/// Longest a single DHT request may take.
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+/// Longest an iterative lookup may take, however many peers fail to answer.
+pub const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+/// TTL for DHT values, and the most a STORE may ask for.
+pub const VALUE_TTL_SECS: u64 = 86400;
+/// TTL for provider records, and the most an ADD_PROVIDER may ask for.
+pub const PROVIDER_TTL_SECS: u64 = 86400;
+/// How often this node re-publishes its own values and provider records.
+const REPUBLISH_INTERVAL: Duration = Duration::from_secs(3600);
+/// Largest value a STORE accepts.
+pub const MAX_VALUE_BYTES: usize = 64 * 1024;
+/// Most provider records kept per key.
+const MAX_PROVIDERS_PER_KEY: usize = K;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// KEYS
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Position of `key` in the DHT key space.
+pub fn key_id(key: &str) -> NodeId {
+    NodeId::from_bytes(key.as_bytes())
+}
+
+/// Provider key of nodes serving snap-sync state at `checkpoint`.
+pub fn snap_checkpoint_key(checkpoint: u64) -> NodeId {
+    key_id(&format!("snap/checkpoint/{checkpoint}"))
+}
+
+/// Provider key of interop relayers attesting for `chain` (its canonical name).
+pub fn relayer_key(chain: &str) -> NodeId {
+    key_id(&format!("interop/relayer/{chain}"))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// WIRE
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// How to reach a DHT node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DhtContact {
+    pub id: NodeId,
+    pub addr: SocketAddr,
+}
+
+impl DhtContact {
+    pub fn peer_info(&self) -> PeerInfo {
+        PeerInfo::new(self.id.clone(), self.addr, Vec::new(), Vec::new())
+    }
+}
+
+impl From<&PeerInfo> for DhtContact {
+    fn from(peer: &PeerInfo) -> Self {
+        DhtContact { id: peer.id.clone(), addr: peer.addr }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DhtRequest {
+    Ping,
+    FindNode { target: NodeId },
+    /// The value under `key`, or the closest nodes to it.
+    FindValue { key: NodeId },
+    Store { key: NodeId, value: Vec<u8>, ttl_secs: u64 },
+    /// Record the requester as a provider of `key`.
+    AddProvider { key: NodeId, ttl_secs: u64 },
+    GetProviders { key: NodeId },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DhtResponse {
+    Pong,
+    Nodes(Vec<DhtContact>),
+    Value(Vec<u8>),
+    Providers { providers: Vec<DhtContact>, closer: Vec<DhtContact> },
+    /// Whether a STORE or ADD_PROVIDER was accepted.
+    Stored(bool),
+}
+
+/// Payload of a `dht` frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DhtFrame {
+    /// The requester, when it answers DHT requests itself.
+    pub sender: Option<DhtContact>,
+    /// Ed25519 identity key `sender.id` is derived from.
+    pub sender_pubkey: Vec<u8>,
+    pub request: DhtRequest,
+}
+
+impl DhtFrame {
+    /// `sender`, if `msg` carrying this frame was signed by the identity key
+    /// its id is derived from.  Unverified senders are still answered, but
+    /// never enter a routing table or a provider record.
+    pub fn verified_sender(&self, msg: &SecureMessage) -> Option<DhtContact> {
+        let sender = self.sender.as_ref()?;
+        let genuine = sender.id == msg.sender_id
+            && NodeId::from_bytes(&self.sender_pubkey) == sender.id
+            && ed25519_verify(&msg.signing_bytes(), &msg.signature, &self.sender_pubkey).is_ok();
+        genuine.then(|| sender.clone())
+    }
+}
+
+/// Carries DHT requests to other nodes.
+#[async_trait]
+pub trait DhtTransport: Send + Sync {
+    async fn request(&self, from: &DhtContact, to: &DhtContact, request: DhtRequest) -> P2PResult<DhtResponse>;
+}
+
+/// Sends DHT requests over the node's stream transport (TCP or QUIC),
+/// signed with the node key.
+pub struct TcpDhtTransport {
+    pub identity: Arc<NodeIdentity>,
+    pub transport: Arc<dyn Transport>,
+}
+
+#[async_trait]
+impl DhtTransport for TcpDhtTransport {
+    async fn request(&self, from: &DhtContact, to: &DhtContact, request: DhtRequest) -> P2PResult<DhtResponse> {
+        let frame = DhtFrame {
+            sender: Some(from.clone()),
+            sender_pubkey: self.identity.ed_keypair.public_key_bytes(),
+            request,
+        };
+        let payload = bincode::serialize(&frame).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        let mut nonce = [0u8; 16];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce);
+        let mut msg = SecureMessage {
+            version: 1,
+            sender_id: self.identity.node_id(),
+            message_type: MessageType::Custom(DHT_MESSAGE.into()),
+            payload,
+            signature: Vec::new(),
+            hop_count: 0,
+            nonce,
+            timestamp: unix_now(),
+        };
+        msg.signature = self.identity.sign_ed(&msg.signing_bytes());
+        let reply = MessageProtocol::exchange_via(self.transport.as_ref(), to.addr, &msg).await?;
+        Busy::check(&reply)?;
+        if reply.sender_id != to.id {
+            return Err(P2PError::Dht(format!("{} answered for {}", reply.sender_id, to.id)));
+        }
+        bincode::deserialize(&reply.payload).map_err(|e| P2PError::Serialization(e.to_string()))
+    }
+}
+
+/// Routes DHT requests between nodes in one process; for tests and local
+/// tooling.
+#[derive(Default)]
+pub struct LocalDhtNetwork {
+    nodes: DashMap<NodeId, Weak<KademliaDht>>,
+    /// Joined nodes whose requests never complete, as if cut off.
+    unreachable: Mutex<HashSet<NodeId>>,
+}
+
+impl LocalDhtNetwork {
+    /// Attach `dht` to this network, reachable as `addr`.
+    pub fn join(self: &Arc<Self>, dht: &Arc<KademliaDht>, addr: SocketAddr) {
+        self.nodes.insert(dht.local_id.clone(), Arc::downgrade(dht));
+        dht.attach_transport(Arc::clone(self) as Arc<dyn DhtTransport>, addr);
+    }
+
+    pub fn set_unreachable(&self, id: &NodeId, unreachable: bool) {
+        let mut set = self.unreachable.lock();
+        if unreachable {
+            set.insert(id.clone());
+        } else {
+            set.remove(id);
+        }
+    }
+}
+
+#[async_trait]
+impl DhtTransport for LocalDhtNetwork {
+    async fn request(&self, from: &DhtContact, to: &DhtContact, request: DhtRequest) -> P2PResult<DhtResponse> {
+        let cut_off = self.unreachable.lock().contains(&to.id);
+        if cut_off {
+            std::future::pending::<()>().await;
+        }
+        let node = self.nodes.get(&to.id).and_then(|n| n.upgrade())
+            .ok_or_else(|| P2PError::PeerNotFound { peer_id: to.id.to_string() })?;
+        Ok(node.handle(Some(from.clone()), request))
+    }
+}
 
 // ─────────────────────────────────────────────────────────────────────────────
 // ROUTING TABLE
@@ -46,7 +248,21 @@ struct BucketEntry {
     last_contact: Instant,
 }
 
+/// What happened to a peer offered to the routing table.
+#[derive(Debug, Clone)]
+pub enum Insert {
+    Added,
+    /// Already present; moved to most-recently-seen.
+    Refreshed,
+    /// Self, or a malicious or banned peer.
+    Rejected,
+    /// The bucket is full.  `candidate` should be pinged and evicted only if
+    /// it does not answer.
+    Full { candidate: PeerInfo },
+}
+
 /// A single K-bucket holding up to K peers at a given XOR-distance bit-prefix.
+/// Entries run from least to most recently seen.
 #[derive(Debug)]
 struct KBucket {
     entries: VecDeque<BucketEntry>,
@@ -61,18 +277,16 @@ impl KBucket {
         }
     }
 
-    /// Insert or update a peer.  Returns `true` if insertion succeeded, `false`
-    /// if the bucket is full and the peer was not already present (caller should
-    /// ping the oldest entry to decide eviction).
-    fn upsert(&mut self, peer: PeerInfo) -> bool {
-        // If already present, move to tail (most-recently-seen).
+    /// Insert or refresh a peer.  A known peer keeps the reputation the
+    /// table has for it; `set_trust` updates that.
+    fn offer(&mut self, peer: PeerInfo) -> Insert {
         if let Some(pos) = self.entries.iter().position(|e| e.peer.id == peer.id) {
             let mut entry = self.entries.remove(pos).expect("position must be valid");
-            entry.peer = peer;
+            entry.peer.addr = peer.addr;
             entry.last_contact = Instant::now();
             self.entries.push_back(entry);
             self.last_changed = Instant::now();
-            return true;
+            return Insert::Refreshed;
         }
         if self.entries.len() < K {
             self.entries.push_back(BucketEntry {
@@ -80,9 +294,11 @@ impl KBucket {
                 last_contact: Instant::now(),
             });
             self.last_changed = Instant::now();
-            true
-        } else {
-            false
+            return Insert::Added;
+        }
+        match self.eviction_candidate() {
+            Some(entry) => Insert::Full { candidate: entry.peer.clone() },
+            None => Insert::Rejected,
         }
     }
 
@@ -91,14 +307,23 @@ impl KBucket {
         self.entries.retain(|e| &e.peer.id != id);
     }
 
-    /// Return all entries sorted newest-first.
+    /// Return all entries, least recently seen first.
     fn all_entries(&self) -> Vec<&BucketEntry> {
         self.entries.iter().collect()
     }
 
-    #[allow(dead_code)]
-    fn oldest_entry(&self) -> Option<&BucketEntry> {
-        self.entries.front()
+    /// The least reputable entry, the least recently seen among equals.
+    fn eviction_candidate(&self) -> Option<&BucketEntry> {
+        self.entries
+            .iter()
+            .enumerate()
+            .min_by(|(i, a), (j, b)| {
+                a.peer.trust_score
+                    .partial_cmp(&b.peer.trust_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(i.cmp(j))
+            })
+            .map(|(_, e)| e)
     }
 
     fn needs_refresh(&self) -> bool {
@@ -136,14 +361,29 @@ impl RoutingTable {
         0
     }
 
+    /// A random id that falls into bucket `idx`, to refresh it with a lookup.
+    fn random_id_in_bucket(&self, idx: usize) -> NodeId {
+        let mut dist = NodeId::random().0;
+        let (byte_idx, bit_pos) = (idx / 8, idx % 8);
+        dist[..byte_idx].fill(0);
+        let bit = 1u8 << bit_pos;
+        dist[byte_idx] = bit | (dist[byte_idx] & (bit - 1));
+        NodeId(std::array::from_fn(|i| self.local_id.0[i] ^ dist[i]))
+    }
+
+    /// Offer a peer to the routing table.
+    pub fn offer(&mut self, peer: PeerInfo) -> Insert {
+        if peer.id == self.local_id || matches!(peer.status, PeerStatus::Malicious | PeerStatus::Banned) {
+            return Insert::Rejected;
+        }
+        let idx = self.bucket_index(&peer.id);
+        self.buckets[idx].offer(peer)
+    }
+
     /// Insert or update a peer in the routing table.
     /// Returns `false` if the corresponding bucket is full.
     pub fn upsert(&mut self, peer: PeerInfo) -> bool {
-        if peer.id == self.local_id {
-            return false; // Never insert self
-        }
-        let idx = self.bucket_index(&peer.id);
-        self.buckets[idx].upsert(peer)
+        matches!(self.offer(peer), Insert::Added | Insert::Refreshed)
     }
 
     /// Remove a peer from the routing table.
@@ -152,6 +392,19 @@ impl RoutingTable {
         self.buckets[idx].remove(id);
     }
 
+    /// Update the reputation the table holds for a peer.
+    pub fn set_trust(&mut self, id: &NodeId, trust_score: f64, status: PeerStatus) {
+        if matches!(status, PeerStatus::Malicious | PeerStatus::Banned) {
+            self.remove(id);
+            return;
+        }
+        let idx = self.bucket_index(id);
+        if let Some(entry) = self.buckets[idx].entries.iter_mut().find(|e| &e.peer.id == id) {
+            entry.peer.trust_score = trust_score;
+            entry.peer.status = status;
+        }
+    }
+
     /// Find the `k` closest peers to `target` in the routing table.
     pub fn find_closest(&self, target: &NodeId, k: usize) -> Vec<PeerInfo> {
         let mut all: Vec<(Vec<u8>, PeerInfo)> = self
@@ -204,87 +457,363 @@ impl RoutingTable {
 #[derive(Debug, Clone)]
 struct DhtValue {
     data: Vec<u8>,
-    stored_at: u64,
+    expires_at: u64,
+    /// Published by this node with `put`, so re-published until it expires.
+    original: bool,
 }
 
-impl DhtValue {
-    fn is_expired(&self) -> bool {
-        unix_now().saturating_sub(self.stored_at) > VALUE_TTL_SECS
-    }
+#[derive(Debug, Clone)]
+struct ProviderRecord {
+    provider: DhtContact,
+    expires_at: u64,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// STATS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, Default)]
+struct LookupStats {
+    count: u64,
+    timeouts: u64,
+    total_ms: u64,
+    last_ms: u64,
+    last_hops: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketFill {
+    pub index: usize,
+    pub peers: usize,
+}
+
+/// Routing table fill and lookup latency, for telemetry and `net dht-status`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DhtStats {
+    pub node_id: String,
+    pub peers: usize,
+    pub bucket_size: usize,
+    /// Non-empty buckets only.
+    pub buckets: Vec<BucketFill>,
+    pub values: usize,
+    pub provider_keys: usize,
+    /// Keys this node announces itself as a provider of.
+    pub provided: usize,
+    pub lookups: u64,
+    /// Lookups cut short by `LOOKUP_TIMEOUT`.
+    pub lookup_timeouts: u64,
+    pub avg_lookup_ms: u64,
+    pub last_lookup_ms: u64,
+    /// Rounds of the last lookup that found a node closer to its target.
+    pub last_lookup_hops: u32,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
 // KADEMLIA DHT
 // ─────────────────────────────────────────────────────────────────────────────
 
+#[derive(Debug, Clone, Copy)]
+enum LookupKind {
+    Node,
+    Value,
+    Providers,
+}
+
+impl LookupKind {
+    fn request(self, target: &NodeId) -> DhtRequest {
+        match self {
+            LookupKind::Node => DhtRequest::FindNode { target: target.clone() },
+            LookupKind::Value => DhtRequest::FindValue { key: target.clone() },
+            LookupKind::Providers => DhtRequest::GetProviders { key: target.clone() },
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct LookupOutcome {
+    closest: Vec<DhtContact>,
+    value: Option<Vec<u8>>,
+    providers: Vec<DhtContact>,
+}
+
 /// The Kademlia DHT for peer discovery and key-value storage.
 pub struct KademliaDht {
     local_id: NodeId,
-    routing_table: Arc<RwLock<RoutingTable>>,
-    /// Key-value store (key = hex NodeId, value = serialised peer address or arbitrary data).
-    store: DashMap<String, DhtValue>,
+    routing_table: RwLock<RoutingTable>,
+    store: DashMap<NodeId, DhtValue>,
+    providers: DashMap<NodeId, Vec<ProviderRecord>>,
+    /// Keys this node provides, with the TTL it announces them for.
+    provided: DashMap<NodeId, u64>,
+    /// How to reach other nodes, and how they reach us.  Without one the
+    /// DHT answers from local state only.
+    transport: RwLock<Option<(Arc<dyn DhtTransport>, DhtContact)>>,
+    lookups: Mutex<LookupStats>,
 }
 
 impl KademliaDht {
     pub fn new(local_id: NodeId) -> Self {
         KademliaDht {
-            routing_table: Arc::new(RwLock::new(RoutingTable::new(local_id.clone()))),
+            routing_table: RwLock::new(RoutingTable::new(local_id.clone())),
             local_id,
             store: DashMap::new(),
+            providers: DashMap::new(),
+            provided: DashMap::new(),
+            transport: RwLock::new(None),
+            lookups: Mutex::new(LookupStats::default()),
         }
     }
 
-    /// Bootstrap from a list of known seed peers.
+    /// Send requests through `transport`, advertising this node at `addr`.
+    pub fn attach_transport(&self, transport: Arc<dyn DhtTransport>, addr: SocketAddr) {
+        let local = DhtContact { id: self.local_id.clone(), addr };
+        *self.transport.write() = Some((transport, local));
+    }
+
+    pub fn local_contact(&self) -> Option<DhtContact> {
+        self.transport.read().as_ref().map(|(_, local)| local.clone())
+    }
+
+    /// Bootstrap from a list of known seed peers, then look up our own id to
+    /// fill the buckets near us and announce ourselves on the way.
     pub async fn bootstrap(&self, seeds: &[PeerInfo]) {
-        let mut rt = self.routing_table.write().await;
-        for seed in seeds {
-            rt.upsert(seed.clone());
+        {
+            let mut rt = self.routing_table.write();
+            for seed in seeds {
+                rt.offer(seed.clone());
+            }
         }
-        info!(local_id = %self.local_id, seeds = seeds.len(), "Kademlia bootstrap complete");
+        let local_id = self.local_id.clone();
+        let found = self.lookup(&local_id, LookupKind::Node).await.closest.len();
+        info!(local_id = %self.local_id, seeds = seeds.len(), found, "Kademlia bootstrap complete");
     }
 
-    /// Add or refresh a peer in the routing table.
+    /// Add or refresh a peer in the routing table.  When its bucket is full
+    /// the eviction candidate is pinged and only replaced if it does not
+    /// answer.
     pub async fn add_peer(&self, peer: PeerInfo) -> bool {
-        let mut rt = self.routing_table.write().await;
-        let inserted = rt.upsert(peer.clone());
-        if inserted {
-            debug!(peer_id = %peer.id, "Kademlia: added peer");
-        } else {
-            debug!(peer_id = %peer.id, "Kademlia: bucket full, eviction candidate");
+        let offered = self.routing_table.write().offer(peer.clone());
+        match offered {
+            Insert::Added | Insert::Refreshed => {
+                debug!(peer_id = %peer.id, "Kademlia: added peer");
+                true
+            }
+            Insert::Rejected => false,
+            Insert::Full { candidate } => {
+                if self.ping(&candidate).await {
+                    self.routing_table.write().offer(candidate.clone());
+                    debug!(peer_id = %peer.id, kept = %candidate.id, "Kademlia: bucket full, incumbent answered");
+                    return false;
+                }
+                let mut rt = self.routing_table.write();
+                rt.remove(&candidate.id);
+                let added = matches!(rt.offer(peer.clone()), Insert::Added);
+                debug!(peer_id = %peer.id, evicted = %candidate.id, "Kademlia: replaced unresponsive peer");
+                added
+            }
         }
-        inserted
+    }
+
+    /// Note a node that contacted us or answered us: insert it if its bucket
+    /// has room, refresh it if known.  Never pings.
+    fn observe(&self, contact: &DhtContact) {
+        self.routing_table.write().offer(contact.peer_info());
     }
 
     /// Remove a peer (e.g., after detecting it is offline or malicious).
     pub async fn remove_peer(&self, id: &NodeId) {
-        let mut rt = self.routing_table.write().await;
-        rt.remove(id);
+        self.routing_table.write().remove(id);
         debug!(peer_id = %id, "Kademlia: removed peer");
     }
 
-    /// Find the k-closest peers to `target` (iterative lookup in local routing table).
-    /// For a full network lookup the caller sends FIND_NODE RPCs to the returned peers.
+    /// Update a peer's reputation; malicious and banned peers are dropped.
+    pub fn set_trust(&self, id: &NodeId, trust_score: f64, status: PeerStatus) {
+        self.routing_table.write().set_trust(id, trust_score, status);
+    }
+
+    /// Whether `peer` answers a PING.  Without a transport there is no way
+    /// to tell, so the peer is assumed alive.
+    async fn ping(&self, peer: &PeerInfo) -> bool {
+        let Some((transport, from)) = self.transport.read().clone() else {
+            return true;
+        };
+        let reply = timeout(REQUEST_TIMEOUT, transport.request(&from, &DhtContact::from(peer), DhtRequest::Ping)).await;
+        matches!(reply, Ok(Ok(DhtResponse::Pong)))
+    }
+
+    /// Find the k-closest peers to `target` in the local routing table.
     pub async fn find_closest_peers(&self, target: &NodeId, k: usize) -> Vec<PeerInfo> {
-        let rt = self.routing_table.read().await;
-        rt.find_closest(target, k)
+        self.routing_table.read().find_closest(target, k)
     }
 
-    /// Store an arbitrary value under `key`.
-    pub fn store_value(&self, key: &str, value: &[u8]) {
-        self.store.insert(
-            key.to_string(),
-            DhtValue {
-                data: value.to_vec(),
-                stored_at: unix_now(),
+    fn closest_contacts(&self, target: &NodeId, k: usize) -> Vec<DhtContact> {
+        self.routing_table.read().find_closest(target, k).iter().map(DhtContact::from).collect()
+    }
+
+    // ── RPC HANDLER ──────────────────────────────────────────────────────────
+
+    /// Answer a request from `from` (when it can be reached back).
+    pub fn handle(&self, from: Option<DhtContact>, request: DhtRequest) -> DhtResponse {
+        if let Some(contact) = &from {
+            self.observe(contact);
+        }
+        let now = unix_now();
+        match request {
+            DhtRequest::Ping => DhtResponse::Pong,
+            DhtRequest::FindNode { target } => DhtResponse::Nodes(self.closest_contacts(&target, K)),
+            DhtRequest::FindValue { key } => match self.value_at(&key, now) {
+                Some(value) => DhtResponse::Value(value),
+                None => DhtResponse::Nodes(self.closest_contacts(&key, K)),
             },
-        );
-        debug!(key, bytes = value.len(), "Kademlia: stored value");
+            DhtRequest::Store { key, value, ttl_secs } => {
+                if value.len() > MAX_VALUE_BYTES {
+                    return DhtResponse::Stored(false);
+                }
+                self.insert_value(key, value, ttl_secs.min(VALUE_TTL_SECS), false, now);
+                DhtResponse::Stored(true)
+            }
+            DhtRequest::AddProvider { key, ttl_secs } => match from {
+                Some(provider) => DhtResponse::Stored(
+                    self.add_provider_at(key, provider, ttl_secs.min(PROVIDER_TTL_SECS), now),
+                ),
+                None => DhtResponse::Stored(false),
+            },
+            DhtRequest::GetProviders { key } => DhtResponse::Providers {
+                providers: self.providers_at(&key, now),
+                closer: self.closest_contacts(&key, K),
+            },
+        }
     }
 
-    /// Look up a value by `key`.  Returns `None` if not found or expired.
-    pub fn lookup_value(&self, key: &str) -> Option<Vec<u8>> {
+    // ── ITERATIVE LOOKUP ─────────────────────────────────────────────────────
+
+    /// Ask ever closer nodes about `target`, ALPHA at a time, until the K
+    /// closest known have all been asked, a value turns up or
+    /// `LOOKUP_TIMEOUT` passes.
+    async fn lookup(&self, target: &NodeId, kind: LookupKind) -> LookupOutcome {
+        let mut outcome = LookupOutcome::default();
+        let mut shortlist = self.closest_contacts(target, K);
+        let Some((transport, from)) = self.transport.read().clone() else {
+            outcome.closest = shortlist;
+            return outcome;
+        };
+        let started = tokio::time::Instant::now();
+        let deadline = started + LOOKUP_TIMEOUT;
+        let mut queried: HashSet<NodeId> = HashSet::from([self.local_id.clone()]);
+        let mut failed: HashSet<NodeId> = HashSet::new();
+        let mut timed_out = false;
+        let mut hops = 0;
+
+        loop {
+            let batch: Vec<DhtContact> = shortlist.iter()
+                .take(K)
+                .filter(|c| !queried.contains(&c.id))
+                .take(ALPHA)
+                .cloned()
+                .collect();
+            if batch.is_empty() {
+                break;
+            }
+            queried.extend(batch.iter().map(|c| c.id.clone()));
+            let request = kind.request(target);
+            let calls = batch.iter().map(|to| timeout(REQUEST_TIMEOUT, transport.request(&from, to, request.clone())));
+            let replies = match timeout_at(deadline, join_all(calls)).await {
+                Ok(replies) => replies,
+                Err(_) => {
+                    timed_out = true;
+                    break;
+                }
+            };
+
+            let mut learned = Vec::new();
+            for (contact, reply) in batch.into_iter().zip(replies) {
+                let Ok(Ok(response)) = reply else {
+                    failed.insert(contact.id);
+                    continue;
+                };
+                self.observe(&contact);
+                match response {
+                    DhtResponse::Nodes(nodes) => learned.extend(nodes),
+                    DhtResponse::Value(value) => {
+                        outcome.value.get_or_insert(value);
+                    }
+                    DhtResponse::Providers { providers, closer } => {
+                        for p in providers {
+                            if !outcome.providers.iter().any(|q| q.id == p.id) {
+                                outcome.providers.push(p);
+                            }
+                        }
+                        learned.extend(closer);
+                    }
+                    DhtResponse::Pong | DhtResponse::Stored(_) => {}
+                }
+            }
+            if outcome.value.is_some() || outcome.providers.len() >= K {
+                break;
+            }
+            let best = shortlist.first().map(|c| target.xor_distance(&c.id));
+            for c in learned {
+                if c.id != self.local_id && !failed.contains(&c.id) && !shortlist.iter().any(|s| s.id == c.id) {
+                    shortlist.push(c);
+                }
+            }
+            shortlist.retain(|c| !failed.contains(&c.id));
+            shortlist.sort_by_key(|c| target.xor_distance(&c.id));
+            let closer = shortlist.first().map(|c| target.xor_distance(&c.id));
+            if closer.is_some() && (best.is_none() || closer < best) {
+                hops += 1;
+            }
+        }
+
+        shortlist.retain(|c| !failed.contains(&c.id));
+        shortlist.truncate(K);
+        outcome.closest = shortlist;
+        self.record_lookup(started.elapsed(), timed_out, hops);
+        outcome
+    }
+
+    fn record_lookup(&self, took: Duration, timed_out: bool, hops: u32) {
+        let ms = took.as_millis() as u64;
+        let mut stats = self.lookups.lock();
+        stats.count += 1;
+        stats.total_ms = stats.total_ms.saturating_add(ms);
+        stats.last_ms = ms;
+        stats.last_hops = hops;
+        if timed_out {
+            stats.timeouts += 1;
+        }
+    }
+
+    /// The K closest nodes to `target` the network knows of.  Nodes that
+    /// answer along the way land in the routing table; `PeerManager::discover`
+    /// hands the rest to the caller to dial.
+    pub async fn find_node(&self, target: &NodeId) -> Vec<DhtContact> {
+        self.lookup(target, LookupKind::Node).await.closest
+    }
+
+    /// Send `request` to the K closest nodes to `key`; returns how many
+    /// accepted it.
+    async fn replicate(&self, key: &NodeId, request: DhtRequest) -> usize {
+        let Some((transport, from)) = self.transport.read().clone() else {
+            return 0;
+        };
+        let closest = self.lookup(key, LookupKind::Node).await.closest;
+        let calls = closest.iter().map(|to| timeout(REQUEST_TIMEOUT, transport.request(&from, to, request.clone())));
+        join_all(calls).await
+            .into_iter()
+            .filter(|r| matches!(r, Ok(Ok(DhtResponse::Stored(true)))))
+            .count()
+    }
+
+    // ── VALUES ───────────────────────────────────────────────────────────────
+
+    fn insert_value(&self, key: NodeId, data: Vec<u8>, ttl_secs: u64, original: bool, now: u64) {
+        let original = original || self.store.get(&key).is_some_and(|v| v.original);
+        self.store.insert(key, DhtValue { data, expires_at: now.saturating_add(ttl_secs), original });
+    }
+
+    fn value_at(&self, key: &NodeId, now: u64) -> Option<Vec<u8>> {
         let entry = self.store.get(key)?;
-        if entry.is_expired() {
+        if now >= entry.expires_at {
             drop(entry);
             self.store.remove(key);
             return None;
@@ -292,6 +821,33 @@ impl KademliaDht {
         Some(entry.data.clone())
     }
 
+    /// Store an arbitrary value under `key`, on this node only.
+    pub fn store_value(&self, key: &str, value: &[u8]) {
+        self.insert_value(key_id(key), value.to_vec(), VALUE_TTL_SECS, false, unix_now());
+        debug!(key, bytes = value.len(), "Kademlia: stored value");
+    }
+
+    /// Look up a value by `key` on this node.  Returns `None` if not found or expired.
+    pub fn lookup_value(&self, key: &str) -> Option<Vec<u8>> {
+        self.value_at(&key_id(key), unix_now())
+    }
+
+    /// Store `value` under `key` here and on the K closest nodes to it, and
+    /// keep re-publishing it.  Returns how many nodes accepted it.
+    pub async fn put(&self, key: &str, value: Vec<u8>) -> usize {
+        let id = key_id(key);
+        self.insert_value(id.clone(), value.clone(), VALUE_TTL_SECS, true, unix_now());
+        self.replicate(&id, DhtRequest::Store { key: id.clone(), value, ttl_secs: VALUE_TTL_SECS }).await
+    }
+
+    /// The value under `key`, from this node or the network.
+    pub async fn get(&self, key: &str) -> Option<Vec<u8>> {
+        if let Some(value) = self.lookup_value(key) {
+            return Some(value);
+        }
+        self.lookup(&key_id(key), LookupKind::Value).await.value
+    }
+
     /// Store peer address information under the peer's NodeId (hex).
     pub fn store_peer_addr(&self, peer_id: &NodeId, addr: &SocketAddr) {
         self.store_value(&peer_id.to_string(), addr.to_string().as_bytes());
@@ -304,31 +860,154 @@ impl KademliaDht {
         addr_str.parse().ok()
     }
 
+    // ── PROVIDERS ────────────────────────────────────────────────────────────
+
+    fn add_provider_at(&self, key: NodeId, provider: DhtContact, ttl_secs: u64, now: u64) -> bool {
+        let expires_at = now.saturating_add(ttl_secs);
+        let mut records = self.providers.entry(key).or_default();
+        records.retain(|r| r.expires_at > now);
+        if let Some(record) = records.iter_mut().find(|r| r.provider.id == provider.id) {
+            *record = ProviderRecord { provider, expires_at };
+            return true;
+        }
+        if records.len() >= MAX_PROVIDERS_PER_KEY {
+            return false;
+        }
+        records.push(ProviderRecord { provider, expires_at });
+        true
+    }
+
+    fn providers_at(&self, key: &NodeId, now: u64) -> Vec<DhtContact> {
+        self.providers.get(key)
+            .map(|records| records.iter().filter(|r| r.expires_at > now).map(|r| r.provider.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Providers of `key` this node holds records for.
+    pub fn local_providers(&self, key: &NodeId) -> Vec<DhtContact> {
+        self.providers_at(key, unix_now())
+    }
+
+    /// Announce this node as a provider of `key` to the K closest nodes, and
+    /// keep re-announcing it until `unprovide`.  Returns how many accepted.
+    pub async fn provide(&self, key: &NodeId, ttl_secs: u64) -> usize {
+        let ttl_secs = ttl_secs.min(PROVIDER_TTL_SECS);
+        self.provided.insert(key.clone(), ttl_secs);
+        if let Some(local) = self.local_contact() {
+            self.add_provider_at(key.clone(), local, ttl_secs, unix_now());
+        }
+        self.replicate(key, DhtRequest::AddProvider { key: key.clone(), ttl_secs }).await
+    }
+
+    /// Stop re-announcing `key`; records elsewhere lapse with their TTL.
+    pub fn unprovide(&self, key: &NodeId) {
+        self.provided.remove(key);
+    }
+
+    /// Other nodes providing `key`, from this node's records and the network.
+    pub async fn find_providers(&self, key: &NodeId) -> Vec<DhtContact> {
+        let mut providers = self.local_providers(key);
+        for p in self.lookup(key, LookupKind::Providers).await.providers {
+            if !providers.iter().any(|q| q.id == p.id) {
+                providers.push(p);
+            }
+        }
+        providers.retain(|p| p.id != self.local_id);
+        providers
+    }
+
+    // ── MAINTENANCE ──────────────────────────────────────────────────────────
+
     /// Return all peers currently in the routing table.
     pub async fn all_peers(&self) -> Vec<PeerInfo> {
-        self.routing_table.read().await.all_peers()
+        self.routing_table.read().all_peers()
     }
 
     pub async fn peer_count(&self) -> usize {
-        self.routing_table.read().await.peer_count()
+        self.routing_table.read().peer_count()
+    }
+
+    fn evict_expired_at(&self, now: u64) {
+        self.store.retain(|_, v| now < v.expires_at);
+        self.providers.retain(|_, records| {
+            records.retain(|r| r.expires_at > now);
+            !records.is_empty()
+        });
     }
 
-    /// Purge expired DHT values.
+    /// Purge expired DHT values and provider records.
     pub fn evict_expired_values(&self) {
-        self.store.retain(|_, v| !v.is_expired());
+        self.evict_expired_at(unix_now());
+    }
+
+    /// Re-publish this node's values and provider records with fresh TTLs.
+    /// Returns how many stores the network accepted.
+    pub async fn republish(&self) -> usize {
+        let now = unix_now();
+        let values: Vec<(NodeId, Vec<u8>)> = self.store.iter()
+            .filter(|e| e.original && now < e.expires_at)
+            .map(|e| (e.key().clone(), e.data.clone()))
+            .collect();
+        let provided: Vec<(NodeId, u64)> = self.provided.iter().map(|e| (e.key().clone(), *e.value())).collect();
+
+        let mut accepted = 0;
+        for (key, value) in values {
+            self.insert_value(key.clone(), value.clone(), VALUE_TTL_SECS, true, now);
+            accepted += self.replicate(&key, DhtRequest::Store { key: key.clone(), value, ttl_secs: VALUE_TTL_SECS }).await;
+        }
+        for (key, ttl_secs) in provided {
+            accepted += self.provide(&key, ttl_secs).await;
+        }
+        accepted
+    }
+
+    pub fn stats(&self) -> DhtStats {
+        let (peers, buckets) = {
+            let rt = self.routing_table.read();
+            let buckets = rt.buckets.iter()
+                .enumerate()
+                .filter(|(_, b)| !b.entries.is_empty())
+                .map(|(index, b)| BucketFill { index, peers: b.entries.len() })
+                .collect();
+            (rt.peer_count(), buckets)
+        };
+        let lookups = *self.lookups.lock();
+        DhtStats {
+            node_id: self.local_id.to_string(),
+            peers,
+            bucket_size: K,
+            buckets,
+            values: self.store.len(),
+            provider_keys: self.providers.len(),
+            provided: self.provided.len(),
+            lookups: lookups.count,
+            lookup_timeouts: lookups.timeouts,
+            avg_lookup_ms: lookups.total_ms / lookups.count.max(1),
+            last_lookup_ms: lookups.last_ms,
+            last_lookup_hops: lookups.last_hops,
+        }
     }
 
-    /// Background maintenance loop: evict stale entries and trigger bucket refresh.
+    /// Background maintenance loop: evict expired entries, re-publish our
+    /// own, and refresh stale buckets with a lookup into each.
     pub async fn run_maintenance(self: Arc<Self>) {
         let mut ticker = interval(Duration::from_secs(300));
+        let mut last_republish = Instant::now();
         loop {
             ticker.tick().await;
             self.evict_expired_values();
-            let stale = self.routing_table.read().await.stale_bucket_indices();
+            if last_republish.elapsed() >= REPUBLISH_INTERVAL {
+                last_republish = Instant::now();
+                let accepted = self.republish().await;
+                debug!(accepted, "Kademlia: re-published values and provider records");
+            }
+            let stale = self.routing_table.read().stale_bucket_indices();
             if !stale.is_empty() {
                 debug!(stale_buckets = stale.len(), "Kademlia: refreshing stale buckets");
-                // In a networked deployment, we would perform FIND_NODE RPCs here.
-                // The routing table self-updates when responses arrive via add_peer().
+            }
+            for idx in stale {
+                let target = self.routing_table.read().random_id_in_bucket(idx);
+                self.lookup(&target, LookupKind::Node).await;
             }
         }
     }
@@ -337,8 +1016,8 @@ impl KademliaDht {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use std::net::SocketAddr;
-    use crate::types::PeerStatus;
 
     fn make_peer(seed: u8) -> PeerInfo {
         let mut id_bytes = [0u8; 32];
@@ -348,6 +1027,23 @@ mod tests {
         PeerInfo::new(id, addr, vec![seed; 32], vec![seed; 64])
     }
 
+    fn local_addr(i: usize) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], 20_000 + i as u16))
+    }
+
+    /// `n` DHT nodes on one in-process network, not yet knowing each other.
+    fn local_network(n: usize) -> (Arc<LocalDhtNetwork>, Vec<Arc<KademliaDht>>) {
+        let net = Arc::new(LocalDhtNetwork::default());
+        let nodes = (0..n)
+            .map(|i| {
+                let dht = Arc::new(KademliaDht::new(NodeId::random()));
+                net.join(&dht, local_addr(i));
+                dht
+            })
+            .collect();
+        (net, nodes)
+    }
+
     #[test]
     fn test_routing_table_insert_and_find() {
         let local_id = NodeId::random();
@@ -361,6 +1057,34 @@ mod tests {
         assert_eq!(closest.len(), 5);
     }
 
+    proptest! {
+        #[test]
+        fn prop_find_closest_ordered_by_xor_distance(
+            local in any::<[u8; 32]>(),
+            ids in proptest::collection::vec(any::<[u8; 32]>(), 1..80),
+            target in any::<[u8; 32]>(),
+            k in 1usize..40,
+        ) {
+            let mut rt = RoutingTable::new(NodeId(local));
+            for (i, id) in ids.into_iter().enumerate() {
+                rt.upsert(PeerInfo::new(NodeId(id), local_addr(i), vec![], vec![]));
+            }
+            let target = NodeId(target);
+            let closest = rt.find_closest(&target, k);
+            prop_assert_eq!(closest.len(), k.min(rt.peer_count()));
+            prop_assert!(closest.windows(2).all(|w| target.xor_distance(&w[0].id) <= target.xor_distance(&w[1].id)));
+
+            // Nothing left out is closer than the farthest returned
+            if let Some(farthest) = closest.last() {
+                let bound = target.xor_distance(&farthest.id);
+                let returned: HashSet<NodeId> = closest.iter().map(|p| p.id.clone()).collect();
+                prop_assert!(rt.all_peers().iter()
+                    .filter(|p| !returned.contains(&p.id))
+                    .all(|p| target.xor_distance(&p.id) >= bound));
+            }
+        }
+    }
+
     #[test]
     fn test_routing_table_no_self_insert() {
         let local = make_peer(1);
@@ -369,6 +1093,26 @@ mod tests {
         assert_eq!(rt.peer_count(), 0);
     }
 
+    #[test]
+    fn test_routing_table_rejects_malicious_peers() {
+        let mut rt = RoutingTable::new(NodeId::random());
+        let mut peer = make_peer(3);
+        peer.status = PeerStatus::Malicious;
+        assert!(!rt.upsert(peer));
+        let peer = make_peer(4);
+        assert!(rt.upsert(peer.clone()));
+        rt.set_trust(&peer.id, 10.0, PeerStatus::Banned);
+        assert_eq!(rt.peer_count(), 0);
+    }
+
+    #[test]
+    fn test_random_id_lands_in_bucket() {
+        let rt = RoutingTable::new(NodeId::random());
+        for idx in [0, 7, 8, 100, 255] {
+            assert_eq!(rt.bucket_index(&rt.random_id_in_bucket(idx)), idx);
+        }
+    }
+
     #[tokio::test]
     async fn test_dht_store_and_lookup() {
         let dht = KademliaDht::new(NodeId::random());
@@ -421,4 +1165,138 @@ mod tests {
         // With 25 unique peers across many buckets, all should fit (K=20 per bucket)
         assert!(rt.peer_count() <= 25);
     }
+
+    #[tokio::test]
+    async fn test_twenty_nodes_resolve_a_value_from_any_node() {
+        let (_net, nodes) = local_network(20);
+        let seed = nodes[0].local_contact().unwrap().peer_info();
+        for node in &nodes[1..] {
+            node.bootstrap(&[seed.clone()]).await;
+        }
+
+        // Held by one node only: every other node has to find it.
+        nodes[7].store_value("manifest", b"checkpoint-4200");
+        for (i, node) in nodes.iter().enumerate() {
+            assert_eq!(node.get("manifest").await, Some(b"checkpoint-4200".to_vec()), "node {i}");
+        }
+
+        // Published: replicated to the nodes closest to the key.
+        assert!(nodes[3].put("published", b"v".to_vec()).await > 1);
+        assert_eq!(nodes[19].get("published").await, Some(b"v".to_vec()));
+
+        let key = snap_checkpoint_key(4200);
+        assert!(nodes[5].provide(&key, 600).await > 0);
+        let providers = nodes[12].find_providers(&key).await;
+        assert_eq!(providers, vec![nodes[5].local_contact().unwrap()]);
+        assert!(nodes[12].stats().lookups > 0);
+    }
+
+    #[tokio::test]
+    async fn test_two_hundred_nodes_converge_within_log_hops() {
+        let (_net, nodes) = local_network(200);
+        let seed = nodes[0].local_contact().unwrap().peer_info();
+        for node in &nodes[1..] {
+            node.bootstrap(&[seed.clone()]).await;
+        }
+
+        // log2(200) rounds each at least halving the distance
+        let max_hops = (nodes.len() as f64).log2().ceil() as u32;
+        for (from, to) in [(1, 199), (57, 3), (120, 64), (199, 0), (88, 141)] {
+            let target = nodes[to].local_id.clone();
+            let closest = nodes[from].find_node(&target).await;
+            assert_eq!(closest.first().map(|c| &c.id), Some(&target), "{from} -> {to}");
+            let hops = nodes[from].stats().last_lookup_hops;
+            assert!(hops <= max_hops, "{from} -> {to}: {hops} hops");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_bucket_keeps_responsive_peers() {
+        let net = Arc::new(LocalDhtNetwork::default());
+        let local = Arc::new(KademliaDht::new(NodeId([0u8; 32])));
+        net.join(&local, local_addr(0));
+
+        // Ids with the top bit set all fall into the same bucket.
+        let bucket_id = |first: u8| {
+            let mut id = NodeId::random();
+            id.0[0] = first | 0x80;
+            id
+        };
+        let mut originals = Vec::new();
+        let mut alive = Vec::new();
+        for i in 0..K {
+            let id = bucket_id(i as u8);
+            if i % 2 == 0 {
+                let dht = Arc::new(KademliaDht::new(id.clone()));
+                net.join(&dht, local_addr(100 + i));
+                alive.push(dht);
+            }
+            assert!(local.add_peer(DhtContact { id: id.clone(), addr: local_addr(100 + i) }.peer_info()).await);
+            originals.push(id);
+        }
+
+        for i in 0..K {
+            let newcomer = DhtContact { id: bucket_id(0x40 | i as u8), addr: local_addr(200 + i) };
+            // The least recently seen incumbent is pinged each time.
+            assert_eq!(local.add_peer(newcomer.peer_info()).await, i % 2 == 1, "newcomer {i}");
+        }
+
+        let rt = local.routing_table.read();
+        assert_eq!(rt.peer_count(), K);
+        for (i, id) in originals.iter().enumerate() {
+            assert_eq!(rt.get(id).is_some(), i % 2 == 0, "original {i}");
+        }
+    }
+
+    #[test]
+    fn test_provider_records_expire_after_ttl() {
+        let dht = KademliaDht::new(NodeId::random());
+        let key = relayer_key("ethereum");
+        let relayer = DhtContact { id: NodeId::random(), addr: local_addr(1) };
+
+        let reply = dht.handle(Some(relayer.clone()), DhtRequest::AddProvider { key: key.clone(), ttl_secs: 60 });
+        assert_eq!(reply, DhtResponse::Stored(true));
+        // Providers must be reachable back; anonymous announcements are refused.
+        let reply = dht.handle(None, DhtRequest::AddProvider { key: key.clone(), ttl_secs: 60 });
+        assert_eq!(reply, DhtResponse::Stored(false));
+
+        let now = unix_now();
+        assert_eq!(dht.providers_at(&key, now + 30), vec![relayer]);
+        assert!(dht.providers_at(&key, now + 61).is_empty());
+        dht.evict_expired_at(now + 61);
+        assert_eq!(dht.stats().provider_keys, 0);
+
+        // A STORE cannot outlive the TTL cap.
+        let id = key_id("x");
+        dht.handle(None, DhtRequest::Store { key: id.clone(), value: b"v".to_vec(), ttl_secs: u64::MAX });
+        assert!(dht.value_at(&id, now + VALUE_TTL_SECS + 1).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_lookup_bounded_with_half_the_network_unreachable() {
+        let (net, nodes) = local_network(20);
+        for node in &nodes[1..] {
+            nodes[0].add_peer(node.local_contact().unwrap().peer_info()).await;
+        }
+        for (i, node) in nodes.iter().enumerate() {
+            if i % 2 == 1 {
+                net.set_unreachable(&node.local_id, true);
+            } else if i > 0 {
+                node.store_value("checkpoint", b"4200");
+            }
+        }
+
+        let started = tokio::time::Instant::now();
+        assert_eq!(nodes[0].get("checkpoint").await, Some(b"4200".to_vec()));
+        assert!(started.elapsed() <= LOOKUP_TIMEOUT);
+
+        let started = tokio::time::Instant::now();
+        let closest = nodes[0].find_node(&NodeId::random()).await;
+        assert!(started.elapsed() <= LOOKUP_TIMEOUT);
+        assert!(!closest.is_empty());
+
+        let stats = nodes[0].stats();
+        assert_eq!(stats.lookups, 2);
+        assert!(stats.last_lookup_ms <= LOOKUP_TIMEOUT.as_millis() as u64);
+    }
 }
@@ -14,12 +14,13 @@
 //! │  └──────────────┘  └─────────────┘  └───────────────┘  │
 //! │  ┌──────────────────────────────────────────────────┐   │
 //! │  │              MessageProtocol                     │   │
-//! │  │  TCP framing  ·  AES-256-GCM  ·  Ed25519 sig   │   │
+//! │  │  TCP/QUIC  ·  AES-256-GCM  ·  Ed25519 sig      │   │
 //! │  │  Kyber-768 KEM  ·  Anti-replay nonce cache      │   │
 //! │  └──────────────────────────────────────────────────┘   │
 //! │  ┌──────────────────────────────────────────────────┐   │
 //! │  │              KademliaDHT                         │   │
 //! │  │  256 K-buckets  ·  XOR metric  ·  k=20          │   │
+//! │  │  FIND_NODE/VALUE  ·  STORE  ·  provider records  │   │
 //! │  └──────────────────────────────────────────────────┘   │
 //! │  ┌──────────────────────────────────────────────────┐   │
 //! │  │              QuantumCrypto                       │   │
@@ -43,18 +44,37 @@
 //! ```
 
 pub mod ai_security;
+pub mod block_sync;
+pub mod crawler;
+pub mod dark_routing;
 pub mod error;
 pub mod gossip_protocol;
+pub mod gossip_router;
 pub mod kademlia_dht;
 pub mod message_protocol;
+pub mod node_info;
 pub mod onion_routing;
 pub mod p2p_node;
 pub mod peer_manager;
+#[cfg(feature = "quic")]
+pub mod quic_transport;
 pub mod quantum_crypto;
+pub mod relay_guard;
+pub mod service_quota;
+pub mod snap_sync;
+pub mod transport;
 pub mod types;
 
 // Re-export the most commonly used items at crate root
+pub use block_sync::{BlockSource, SyncPeer, SyncRequest, SyncResponse, TcpSyncPeer, TransportSyncPeer};
+pub use crawler::{crawl, NetworkMap, PexProbe, TcpPexProbe};
 pub use error::{P2PError, P2PResult};
-pub use p2p_node::{NodeHandle, P2PNode, P2PNodeConfig};
+pub use kademlia_dht::{DhtContact, DhtStats, DhtTransport, KademliaDht, LocalDhtNetwork, TcpDhtTransport};
+pub use node_info::{Hello, LocalNodeInfo, NodeMetadata, PeerDirectory, PeerSummary};
+pub use p2p_node::{NodeHandle, P2PNode, P2PNodeConfig, P2PSettings};
 pub use peer_manager::{PeerEvent, PeerManager, PeerManagerConfig};
+pub use relay_guard::{RelayGuard, RelayPenalty, RelayPolicy, RelayReject, RelayValidator};
+pub use service_quota::{Busy, PeerKey, PeerServiceStats, ServiceClass, ServiceQuotaConfig, ServiceQuotas};
+pub use snap_sync::{SnapPeer, SnapRequest, SnapResponse, SnapSource, TcpSnapPeer};
+pub use transport::{Lane, TcpTransport, Transport, TransportKind};
 pub use types::{MessageType, NodeId, PeerInfo, PeerStatus, SecureMessage};
@@ -1,9 +1,20 @@
 //! Production message protocol for bleep-p2p.
 //!
-//! Transport: async TCP with a 4-byte length-prefix framing.
+//! Transport: a `Transport` stream per frame (TCP by default, QUIC with
+//! the `quic` feature) with a 4-byte length-prefix framing.
 //! Encryption: Kyber-768 KEM session key → AES-256-GCM per message.
 //! Authentication: Ed25519 signature on every message.
 //! Anti-replay: 16-byte nonce + timestamp within ±30s window.
+//!
+//! Handshake (`hello`) and peer-exchange (`pex`) frames are signed but not
+//! encrypted, since they run before a session exists; each is answered on
+//! the same connection from the attached `PeerDirectory`.  Snap-sync
+//! (`snap`) frames are answered the same way from an attached
+//! `SnapSource`, block-sync (`sync`) frames from an attached `BlockSource`,
+//! and DHT (`dht`) frames from the peer manager's `KademliaDht`.  With
+//! `ServiceQuotas` attached, snap, block-sync and DHT requests
+//! are metered per peer and answered with a `busy` frame when over quota;
+//! encrypted consensus messages are never metered or queued.
 
 use std::collections::HashMap;
 use std::net::SocketAddr;
@@ -12,14 +23,20 @@ use std::time::Duration;
 
 use bytes::{BufMut, Bytes, BytesMut};
 use dashmap::DashMap;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
 use tokio::sync::{mpsc, Mutex};
 use tokio::time::timeout;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
 
+use crate::block_sync::{self, BlockSource, SyncRequest, SyncResponse, BLOCK_SYNC_MESSAGE};
 use crate::error::{P2PError, P2PResult};
+use crate::kademlia_dht::{DhtFrame, DHT_MESSAGE};
+use crate::node_info::{Hello, PeerDirectory, HELLO_MESSAGE, PEX_MESSAGE};
 use crate::peer_manager::PeerManager;
+use crate::service_quota::{Busy, PeerKey, ServiceClass, ServiceQuotas, BUSY_MESSAGE};
+use crate::snap_sync::{self, SnapRequest, SnapResponse, SnapSource, SNAP_MESSAGE};
+use crate::transport::{BoxStream, Lane, TcpTransport, Transport};
 use crate::quantum_crypto::{
     kyber_decapsulate, kyber_encapsulate, ed25519_verify,
     Ed25519Keypair, KyberKeypair, SessionKey,
@@ -34,8 +51,6 @@ use crate::types::{NodeId, SecureMessage, MessageType, unix_now};
 const MAX_FRAME_BYTES: usize = 4 * 1024 * 1024;
 /// Anti-replay timestamp tolerance (seconds).
 const REPLAY_WINDOW_SECS: u64 = 30;
-/// TCP connection timeout.
-const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
 /// Read timeout per frame.
 const READ_TIMEOUT: Duration = Duration::from_secs(30);
 
@@ -96,6 +111,16 @@ pub struct MessageProtocol {
     /// Inbound message channel — consumers subscribe to this.
     inbound_tx: mpsc::Sender<(NodeId, SecureMessage)>,
     peer_manager: Arc<PeerManager>,
+    /// Answers `hello` / `pex` frames once attached.
+    directory: parking_lot::RwLock<Option<Arc<PeerDirectory>>>,
+    /// Answers `snap` frames once attached.
+    snap_source: parking_lot::RwLock<Option<Arc<dyn SnapSource>>>,
+    /// Answers `sync` frames once attached.
+    block_source: parking_lot::RwLock<Option<Arc<dyn BlockSource>>>,
+    /// Meters `snap` and `dht` requests once attached.
+    service_quotas: parking_lot::RwLock<Option<Arc<ServiceQuotas>>>,
+    /// Carries outbound frames; a TCP dialer until one is attached.
+    transport: parking_lot::RwLock<Arc<dyn Transport>>,
 }
 
 impl MessageProtocol {
@@ -114,6 +139,11 @@ impl MessageProtocol {
             nonce_cache: Arc::new(Mutex::new(NonceCache::new())),
             inbound_tx: tx,
             peer_manager,
+            directory: parking_lot::RwLock::new(None),
+            snap_source: parking_lot::RwLock::new(None),
+            block_source: parking_lot::RwLock::new(None),
+            service_quotas: parking_lot::RwLock::new(None),
+            transport: parking_lot::RwLock::new(Arc::new(TcpTransport::dialer())),
         });
         (proto, rx)
     }
@@ -194,6 +224,12 @@ impl MessageProtocol {
         msg: &SecureMessage,
         sender_pubkey_bytes: &[u8],
     ) -> P2PResult<Vec<u8>> {
+        self.authenticate(msg, sender_pubkey_bytes).await?;
+        self.decrypt(msg)
+    }
+
+    /// Steps 1–3 of `open_message`: freshness, replay nonce and signature.
+    async fn authenticate(&self, msg: &SecureMessage, sender_pubkey_bytes: &[u8]) -> P2PResult<()> {
         // 1. Timestamp freshness
         let now = unix_now();
         if now.abs_diff(msg.timestamp) > REPLAY_WINDOW_SECS {
@@ -209,9 +245,11 @@ impl MessageProtocol {
         }
 
         // 3. Signature verification
-        ed25519_verify(&msg.signing_bytes(), &msg.signature, sender_pubkey_bytes)?;
+        ed25519_verify(&msg.signing_bytes(), &msg.signature, sender_pubkey_bytes)
+    }
 
-        // 4. Decryption
+    /// Step 4 of `open_message`: decrypt under the sender's session key.
+    fn decrypt(&self, msg: &SecureMessage) -> P2PResult<Vec<u8>> {
         let session = self
             .sessions
             .get(&msg.sender_id)
@@ -219,6 +257,226 @@ impl MessageProtocol {
         session.key.decrypt(&msg.payload)
     }
 
+    // ── HANDSHAKE / PEX ───────────────────────────────────────────────────────
+
+    /// Serve `hello` and `pex` frames from `directory` and count inbound
+    /// messages against it.
+    pub fn attach_directory(&self, directory: Arc<PeerDirectory>) {
+        *self.directory.write() = Some(directory);
+    }
+
+    /// Serve `snap` frames from `source`.
+    pub fn attach_snap_source(&self, source: Arc<dyn SnapSource>) {
+        *self.snap_source.write() = Some(source);
+    }
+
+    /// Serve `sync` frames from `source`.
+    pub fn attach_block_source(&self, source: Arc<dyn BlockSource>) {
+        *self.block_source.write() = Some(source);
+    }
+
+    /// Meter `snap`, `sync` and `dht` requests against `quotas`.
+    pub fn attach_service_quotas(&self, quotas: Arc<ServiceQuotas>) {
+        *self.service_quotas.write() = Some(quotas);
+    }
+
+    /// Send outbound frames over `transport` instead of plain TCP.
+    pub fn attach_transport(&self, transport: Arc<dyn Transport>) {
+        *self.transport.write() = transport;
+    }
+
+    pub fn transport(&self) -> Arc<dyn Transport> {
+        self.transport.read().clone()
+    }
+
+    /// Build a signed, unencrypted `SecureMessage`.
+    pub fn sign_plain(&self, message_type: MessageType, payload: Vec<u8>) -> SecureMessage {
+        let mut nonce = [0u8; 16];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce);
+        let mut msg = SecureMessage {
+            version: 1,
+            sender_id: self.local_id.clone(),
+            message_type,
+            payload,
+            signature: Vec::new(),
+            hop_count: 0,
+            nonce,
+            timestamp: unix_now(),
+        };
+        msg.signature = self.local_identity.sign(&msg.signing_bytes());
+        msg
+    }
+
+    /// Check that `msg` was signed by the node `hello` describes.
+    pub fn verify_plain(msg: &SecureMessage, hello: &Hello) -> P2PResult<()> {
+        hello.check()?;
+        if msg.sender_id != hello.node_id {
+            return Err(P2PError::HandshakeRejected("sender is not the node in the hello".into()));
+        }
+        ed25519_verify(&msg.signing_bytes(), &msg.signature, &hello.ed25519_pubkey)
+    }
+
+    /// Send our `Hello` to `peer_addr` and record theirs.
+    pub async fn hello(&self, peer_addr: SocketAddr) -> P2PResult<Hello> {
+        let directory = self.directory.read().clone()
+            .ok_or_else(|| P2PError::HandshakeRejected("no peer directory attached".into()))?;
+        let payload = bincode::serialize(directory.local())
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        let request = self.sign_plain(MessageType::Custom(HELLO_MESSAGE.into()), payload);
+        let reply = Self::exchange_via(self.transport().as_ref(), peer_addr, &request).await?;
+        let theirs: Hello = bincode::deserialize(&reply.payload)
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        Self::verify_plain(&reply, &theirs)?;
+        directory.on_hello(theirs.clone(), peer_addr)?;
+        Ok(theirs)
+    }
+
+    /// Send `msg` to `peer_addr` over TCP and read one frame back.
+    pub async fn exchange(peer_addr: SocketAddr, msg: &SecureMessage) -> P2PResult<SecureMessage> {
+        Self::exchange_via(&TcpTransport::dialer(), peer_addr, msg).await
+    }
+
+    /// Send `msg` to `peer_addr` over `transport` and read one frame back.
+    pub async fn exchange_via(
+        transport: &dyn Transport,
+        peer_addr: SocketAddr,
+        msg: &SecureMessage,
+    ) -> P2PResult<SecureMessage> {
+        let frame = Self::encode_frame(msg)?;
+        let mut stream = transport.open(peer_addr, Lane::for_message(&msg.message_type)).await?;
+        stream.write_all(&frame).await.map_err(P2PError::Io)?;
+        stream.flush().await.map_err(P2PError::Io)?;
+        Self::decode_frame(&mut stream).await
+    }
+
+    async fn answer_plain(&self, stream: BoxStream, peer_addr: SocketAddr, kind: &str, msg: SecureMessage) -> P2PResult<()> {
+        let directory = self.directory.read().clone()
+            .ok_or_else(|| P2PError::HandshakeRejected("no peer directory attached".into()))?;
+        let reply = if kind == HELLO_MESSAGE {
+            if unix_now().abs_diff(msg.timestamp) > REPLAY_WINDOW_SECS {
+                return Err(P2PError::AuthenticationFailed);
+            }
+            let theirs: Hello = bincode::deserialize(&msg.payload)
+                .map_err(|e| P2PError::Serialization(e.to_string()))?;
+            Self::verify_plain(&msg, &theirs)?;
+            debug!(peer = %theirs.node_id, moniker = %theirs.metadata.moniker, "Hello received");
+            directory.on_hello(theirs, peer_addr)?;
+            bincode::serialize(directory.local())
+        } else {
+            bincode::serialize(&directory.pex_response())
+        }
+        .map_err(|e| P2PError::Serialization(e.to_string()))?;
+
+        Self::write_reply(stream, &self.sign_plain(MessageType::Custom(kind.to_string()), reply)).await
+    }
+
+    async fn answer_snap(&self, stream: BoxStream, peer_addr: SocketAddr, msg: SecureMessage) -> P2PResult<()> {
+        let source = self.snap_source.read().clone()
+            .ok_or_else(|| P2PError::HandshakeRejected("no snap source attached".into()))?;
+        let request: SnapRequest = bincode::deserialize(&msg.payload)
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        let quotas = self.service_quotas.read().clone();
+        let reply = match quotas {
+            None => self.snap_reply(&snap_sync::serve(source.as_ref(), &request))?,
+            Some(quotas) => {
+                // Snap frames carry a throwaway sender id, so charge the IP.
+                let key = PeerKey::Addr(peer_addr.ip());
+                let served = Self::serve_metered(&quotas, &key, move || snap_sync::serve(source.as_ref(), &request));
+                match served.await.map(|r| r.unwrap_or(SnapResponse::Unavailable)) {
+                    Ok(response) => {
+                        let reply = self.snap_reply(&response)?;
+                        quotas.charge(&key, ServiceClass::Sync, reply.payload.len(), quotas.clock_ms());
+                        reply
+                    }
+                    Err(busy) => self.busy_reply(busy)?,
+                }
+            }
+        };
+        Self::write_reply(stream, &reply).await
+    }
+
+    /// Run `serve` within `key`'s quota: rate-checked, then read off the
+    /// async workers once an expensive slot is free.  `None` if it panicked.
+    async fn serve_metered<T, F>(quotas: &ServiceQuotas, key: &PeerKey, serve: F) -> Result<Option<T>, Busy>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        quotas.admit(key, ServiceClass::Sync, quotas.clock_ms())?;
+        let _permit = quotas.acquire(key, ServiceClass::Sync).await?;
+        Ok(tokio::task::spawn_blocking(serve).await.ok())
+    }
+
+    async fn answer_blocks(&self, stream: BoxStream, peer_addr: SocketAddr, msg: SecureMessage) -> P2PResult<()> {
+        let source = self.block_source.read().clone()
+            .ok_or_else(|| P2PError::HandshakeRejected("no block source attached".into()))?;
+        let request: SyncRequest = bincode::deserialize(&msg.payload)
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        let quotas = self.service_quotas.read().clone();
+        let reply = match quotas {
+            None => self.sync_reply(&block_sync::serve(source.as_ref(), &request))?,
+            Some(quotas) => {
+                // Sync frames carry a throwaway sender id, so charge the IP.
+                let key = PeerKey::Addr(peer_addr.ip());
+                let tip = source.tip();
+                let served = Self::serve_metered(&quotas, &key, move || block_sync::serve(source.as_ref(), &request));
+                match served.await.map(|r| r.unwrap_or(SyncResponse::Unavailable { tip })) {
+                    Ok(response) => {
+                        let reply = self.sync_reply(&response)?;
+                        quotas.charge(&key, ServiceClass::Sync, reply.payload.len(), quotas.clock_ms());
+                        reply
+                    }
+                    Err(busy) => self.busy_reply(busy)?,
+                }
+            }
+        };
+        Self::write_reply(stream, &reply).await
+    }
+
+    fn snap_reply(&self, response: &SnapResponse) -> P2PResult<SecureMessage> {
+        let reply = bincode::serialize(response).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        Ok(self.sign_plain(MessageType::Custom(SNAP_MESSAGE.into()), reply))
+    }
+
+    fn sync_reply(&self, response: &SyncResponse) -> P2PResult<SecureMessage> {
+        let reply = bincode::serialize(response).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        Ok(self.sign_plain(MessageType::Custom(BLOCK_SYNC_MESSAGE.into()), reply))
+    }
+
+    fn busy_reply(&self, busy: Busy) -> P2PResult<SecureMessage> {
+        debug!(class = busy.class.label(), retry_after_ms = busy.retry_after_ms, "Request over quota");
+        let reply = bincode::serialize(&busy).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        Ok(self.sign_plain(MessageType::Custom(BUSY_MESSAGE.into()), reply))
+    }
+
+    async fn answer_dht(&self, stream: BoxStream, peer_addr: SocketAddr, msg: SecureMessage) -> P2PResult<()> {
+        let frame: DhtFrame = bincode::deserialize(&msg.payload)
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        let sender = frame.verified_sender(&msg);
+        let metered = self.service_quotas.read().clone().map(|quotas| {
+            let key = sender.as_ref()
+                .map_or(PeerKey::Addr(peer_addr.ip()), |contact| PeerKey::Node(contact.id.clone()));
+            (quotas, key)
+        });
+        if let Some((quotas, key)) = &metered {
+            if let Err(busy) = quotas.admit(key, ServiceClass::Dht, quotas.clock_ms()) {
+                return Self::write_reply(stream, &self.busy_reply(busy)?).await;
+            }
+        }
+        let reply = bincode::serialize(&self.peer_manager.dht().handle(sender, frame.request))
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        if let Some((quotas, key)) = &metered {
+            quotas.charge(key, ServiceClass::Dht, reply.len(), quotas.clock_ms());
+        }
+        Self::write_reply(stream, &self.sign_plain(MessageType::Custom(DHT_MESSAGE.into()), reply)).await
+    }
+
+    async fn write_reply(mut stream: BoxStream, reply: &SecureMessage) -> P2PResult<()> {
+        let frame = Self::encode_frame(reply)?;
+        stream.write_all(&frame).await.map_err(P2PError::Io)?;
+        stream.shutdown().await.map_err(P2PError::Io)
+    }
+
     // ── WIRE FRAMING ──────────────────────────────────────────────────────────
 
     /// Encode a `SecureMessage` as a length-prefixed frame: `[u32 BE length][bincode bytes]`.
@@ -237,8 +495,8 @@ impl MessageProtocol {
         Ok(buf.freeze())
     }
 
-    /// Decode a length-prefixed frame from a TCP stream.
-    pub async fn decode_frame(stream: &mut TcpStream) -> P2PResult<SecureMessage> {
+    /// Decode a length-prefixed frame from a stream.
+    pub async fn decode_frame<S: AsyncRead + Unpin + ?Sized>(stream: &mut S) -> P2PResult<SecureMessage> {
         // Read 4-byte length header
         let mut len_buf = [0u8; 4];
         timeout(READ_TIMEOUT, stream.read_exact(&mut len_buf))
@@ -266,20 +524,18 @@ impl MessageProtocol {
 
     // ── SEND ─────────────────────────────────────────────────────────────────
 
-    /// Open a TCP connection to `peer_addr` and send `msg`.
+    /// Open a stream to `peer_addr` on the attached transport and send `msg`.
     pub async fn send_message(&self, peer_addr: SocketAddr, msg: &SecureMessage) -> P2PResult<()> {
         let frame = Self::encode_frame(msg)?;
-        let mut stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(peer_addr))
-            .await
-            .map_err(|_| P2PError::ConnectionTimeout { addr: peer_addr.to_string() })?
-            .map_err(P2PError::Io)?;
+        let transport = self.transport();
+        let mut stream = transport.open(peer_addr, Lane::for_message(&msg.message_type)).await?;
 
         timeout(READ_TIMEOUT, stream.write_all(&frame))
             .await
             .map_err(|_| P2PError::ConnectionTimeout { addr: peer_addr.to_string() })?
             .map_err(P2PError::Io)?;
 
-        stream.flush().await.map_err(P2PError::Io)?;
+        stream.shutdown().await.map_err(P2PError::Io)?;
         debug!(peer = %peer_addr, bytes = frame.len(), "Sent message");
         Ok(())
     }
@@ -289,29 +545,59 @@ impl MessageProtocol {
     /// Accept incoming connections on `bind_addr` and dispatch to the inbound channel.
     pub async fn listen(self: Arc<Self>, bind_addr: SocketAddr) -> P2PResult<()> {
         let listener = TcpListener::bind(bind_addr).await.map_err(P2PError::Io)?;
-        info!(addr = %bind_addr, "MessageProtocol listening");
-
-        loop {
-            match listener.accept().await {
-                Ok((stream, peer_addr)) => {
-                    let proto = self.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = proto.handle_incoming(stream, peer_addr).await {
-                            warn!(peer = %peer_addr, error = %e, "Inbound connection error");
-                        }
-                    });
-                }
-                Err(e) => {
-                    error!(error = %e, "Accept error");
+        self.serve(listener).await;
+        Ok(())
+    }
+
+    /// Accept loop over an already bound `listener`; runs until aborted.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) {
+        self.serve_transport(Arc::new(TcpTransport::new(listener))).await
+    }
+
+    /// Accept loop over `transport`'s inbound streams; runs until aborted
+    /// or the transport stops accepting.
+    pub async fn serve_transport(self: Arc<Self>, transport: Arc<dyn Transport>) {
+        if let Some(addr) = transport.local_addr() {
+            info!(addr = %addr, transport = %transport.kind(), "MessageProtocol listening");
+        }
+
+        while let Some((stream, peer_addr)) = transport.accept().await {
+            let proto = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = proto.handle_incoming(stream, peer_addr).await {
+                    warn!(peer = %peer_addr, error = %e, "Inbound connection error");
                 }
-            }
+            });
         }
+        info!("MessageProtocol stopped listening");
     }
 
-    async fn handle_incoming(&self, mut stream: TcpStream, _peer_addr: SocketAddr) -> P2PResult<()> {
+    async fn handle_incoming(&self, mut stream: BoxStream, peer_addr: SocketAddr) -> P2PResult<()> {
         let msg = Self::decode_frame(&mut stream).await?;
+        if let MessageType::Custom(kind) = &msg.message_type {
+            if kind == HELLO_MESSAGE || kind == PEX_MESSAGE {
+                let kind = kind.clone();
+                return self.answer_plain(stream, peer_addr, &kind, msg).await;
+            }
+            if kind == SNAP_MESSAGE {
+                return self.answer_snap(stream, peer_addr, msg).await;
+            }
+            if kind == BLOCK_SYNC_MESSAGE {
+                return self.answer_blocks(stream, peer_addr, msg).await;
+            }
+            if kind == DHT_MESSAGE {
+                return self.answer_dht(stream, peer_addr, msg).await;
+            }
+        }
         let sender_id = msg.sender_id.clone();
 
+        // Until the signature checks out the claimed sender may be anyone,
+        // so rate-limit by source address and leave the sender's score alone.
+        if self.peer_manager.record_unauthenticated(peer_addr.ip()) {
+            warn!(addr = %peer_addr, "Address is flooding, dropping connection");
+            return Err(P2PError::AddressFlooding { addr: peer_addr.ip().to_string() });
+        }
+
         // Look up sender's public key from peer manager
         let sender_pk = self
             .peer_manager
@@ -319,22 +605,34 @@ impl MessageProtocol {
             .map(|p| p.public_key.clone())
             .ok_or_else(|| P2PError::PeerNotFound { peer_id: sender_id.to_string() })?;
 
+        // Authenticate: freshness, replay and signature
+        self.authenticate(&msg, &sender_pk).await?;
+
+        // Flood check, before spending any more crypto on the message
+        if self.peer_manager.record_message(&sender_id) {
+            warn!(peer = %sender_id, "Peer is flooding, banning");
+            self.peer_manager.ban_peer(&sender_id).await;
+            return Err(P2PError::PeerBanned { peer_id: sender_id.to_string() });
+        }
+
         // Anomaly check
         if let Some(reason) = self.peer_manager.check_message_anomaly(&sender_id, &msg.payload, msg.hop_count) {
             warn!(peer = %sender_id, reason = %reason, "Anomaly detected, flagging peer");
-            self.peer_manager.record_failure(&sender_id);
+            self.peer_manager.record_invalid_message(&sender_id);
             return Err(P2PError::AuthenticationFailed);
         }
 
-        // Verify and decrypt
-        let _plaintext = self.open_message(&msg, &sender_pk).await.map_err(|e| {
-            self.peer_manager.record_failure(&sender_id);
+        // Decrypt
+        let _plaintext = self.decrypt(&msg).map_err(|e| {
+            self.peer_manager.record_invalid_message(&sender_id);
             e
         })?;
 
         self.peer_manager.record_success(&sender_id);
-        self.peer_manager.record_message(&sender_id);
         self.peer_manager.touch(&sender_id);
+        if let Some(directory) = self.directory.read().as_ref() {
+            directory.on_message(&sender_id);
+        }
 
         let _ = self.inbound_tx.send((sender_id, msg)).await;
         Ok(())
@@ -412,6 +710,189 @@ mod tests {
         assert!(MessageProtocol::encode_frame(&msg).is_err());
     }
 
+    /// Serves every chunk, slowly.
+    struct SlowSource;
+
+    impl SnapSource for SlowSource {
+        fn manifest(&self, _at_checkpoint: u64) -> Option<Vec<u8>> {
+            None
+        }
+
+        fn chunk(&self, _checkpoint: u64, _index: u32) -> Option<Vec<u8>> {
+            std::thread::sleep(Duration::from_millis(100));
+            Some(vec![7; 1024])
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_snap_flood_is_throttled_without_delaying_consensus() {
+        use crate::service_quota::{ClassQuota, ServiceQuotaConfig};
+        use crate::snap_sync::{SnapPeer, TcpSnapPeer};
+
+        let (proto_a, _, _) = make_proto();
+        let (proto_b, mut rx_b, pm_b) = make_proto();
+        let quotas = Arc::new(ServiceQuotas::new(ServiceQuotaConfig {
+            sync: ClassQuota { requests_per_sec: 10, bytes_per_sec: 1024 * 1024 },
+            max_concurrent_expensive: 1,
+            max_queued_per_peer: 4,
+            ..Default::default()
+        }));
+        proto_b.attach_snap_source(Arc::new(SlowSource));
+        proto_b.attach_service_quotas(Arc::clone(&quotas));
+        let b_addr: SocketAddr = "127.0.0.1:17740".parse().unwrap();
+        tokio::spawn(Arc::clone(&proto_b).listen(b_addr));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // A is an admitted peer with a session to B.
+        let sphincs = SphincsKeypair::generate();
+        let challenge = b"test-handshake-context";
+        let proof = sphincs_sign(challenge, &sphincs.secret_key.0).unwrap();
+        pm_b.add_peer(
+            proto_a.local_id.clone(),
+            "127.0.0.1:17741".parse().unwrap(),
+            proto_a.local_identity.public_key_bytes(),
+            sphincs.public_key.0.clone(),
+            challenge,
+            &proof,
+        )
+        .await
+        .unwrap();
+        let kem_ct = proto_a.initiate_session(&proto_b.local_id, &proto_b.local_kyber.public_key.0).unwrap();
+        proto_b.accept_session(&proto_a.local_id, &kem_ct).unwrap();
+
+        // A flood of chunk requests from one address: 10 pass the rate
+        // limit, one runs, four queue and the rest are refused.
+        let flood: Vec<_> = (0..30)
+            .map(|index| {
+                tokio::spawn(async move {
+                    TcpSnapPeer { addr: b_addr }.request(SnapRequest::GetStateChunk { checkpoint: 1, index }).await
+                })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // A block from A goes straight through while the flood is queued.
+        let started = std::time::Instant::now();
+        let block = proto_a.seal_message(&proto_b.local_id, MessageType::Block, b"block 1").unwrap();
+        proto_a.send_message(b_addr, &block).await.unwrap();
+        let (from, _) = timeout(Duration::from_secs(1), rx_b.recv()).await.unwrap().unwrap();
+        assert_eq!(from, proto_a.local_id);
+        assert!(started.elapsed() < Duration::from_millis(300), "block took {:?}", started.elapsed());
+
+        let mut served = 0;
+        let mut busy = 0;
+        for request in flood {
+            match request.await.unwrap() {
+                Ok(SnapResponse::Chunk(_)) => served += 1,
+                Err(P2PError::Busy { class: "sync", retry_after_ms }) if retry_after_ms > 0 => busy += 1,
+                other => panic!("unexpected reply {:?}", other),
+            }
+        }
+        assert_eq!((served, busy), (5, 25));
+
+        // The flood is charged to its address, never to A's node id.
+        let stats = quotas.stats(&PeerKey::Addr("127.0.0.1".parse().unwrap())).unwrap();
+        assert_eq!(stats.classes["sync"].served, 5);
+        assert_eq!(stats.rejected(), 25);
+        assert!(quotas.stats(&PeerKey::Node(proto_a.local_id.clone())).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_forged_messages_do_not_penalise_the_claimed_sender() {
+        let (proto_a, _, _) = make_proto();
+        let (proto_b, mut rx_b, pm_b) = make_proto();
+        let (mallory, _, _) = make_proto();
+        let b_addr: SocketAddr = "127.0.0.1:17743".parse().unwrap();
+        tokio::spawn(Arc::clone(&proto_b).listen(b_addr));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let sphincs = SphincsKeypair::generate();
+        let challenge = b"test-handshake-context";
+        let proof = sphincs_sign(challenge, &sphincs.secret_key.0).unwrap();
+        pm_b.add_peer(
+            proto_a.local_id.clone(),
+            "127.0.0.1:17744".parse().unwrap(),
+            proto_a.local_identity.public_key_bytes(),
+            sphincs.public_key.0.clone(),
+            challenge,
+            &proof,
+        )
+        .await
+        .unwrap();
+        let kem_ct = proto_a.initiate_session(&proto_b.local_id, &proto_b.local_kyber.public_key.0).unwrap();
+        proto_b.accept_session(&proto_a.local_id, &kem_ct).unwrap();
+        let score = pm_b.get_peer(&proto_a.local_id).unwrap().trust_score;
+
+        // Mallory signs with its own key but claims to be A.
+        mallory.initiate_session(&proto_b.local_id, &proto_b.local_kyber.public_key.0).unwrap();
+        for i in 0..10u8 {
+            let mut forged = mallory.seal_message(&proto_b.local_id, MessageType::Block, &[i; 64]).unwrap();
+            forged.sender_id = proto_a.local_id.clone();
+            mallory.send_message(b_addr, &forged).await.unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(rx_b.try_recv().is_err());
+        assert!(!pm_b.is_banned(&proto_a.local_id));
+        assert_eq!(pm_b.get_peer(&proto_a.local_id).unwrap().trust_score, score);
+
+        let block = proto_a.seal_message(&proto_b.local_id, MessageType::Block, b"block 1").unwrap();
+        proto_a.send_message(b_addr, &block).await.unwrap();
+        let (from, _) = timeout(Duration::from_secs(1), rx_b.recv()).await.unwrap().unwrap();
+        assert_eq!(from, proto_a.local_id);
+    }
+
+    #[cfg(feature = "quic")]
+    #[tokio::test]
+    async fn test_quic_nodes_exchange_blocks_on_separate_streams() {
+        use crate::quantum_crypto::NodeIdentity;
+        use crate::quic_transport::QuicTransport;
+
+        let (proto_a, _, _) = make_proto();
+        let (proto_b, mut rx_b, pm_b) = make_proto();
+        let localhost: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let quic_a: Arc<dyn Transport> = Arc::new(QuicTransport::bind(localhost, &NodeIdentity::generate()).unwrap());
+        let quic_b: Arc<dyn Transport> = Arc::new(QuicTransport::bind(localhost, &NodeIdentity::generate()).unwrap());
+        let a_addr = quic_a.local_addr().unwrap();
+        let b_addr = quic_b.local_addr().unwrap();
+        proto_a.attach_transport(quic_a.clone());
+        proto_b.attach_transport(quic_b.clone());
+        tokio::spawn(Arc::clone(&proto_a).serve_transport(quic_a));
+        tokio::spawn(Arc::clone(&proto_b).serve_transport(quic_b));
+
+        let sphincs = SphincsKeypair::generate();
+        let challenge = b"test-handshake-context";
+        let proof = sphincs_sign(challenge, &sphincs.secret_key.0).unwrap();
+        pm_b.add_peer(
+            proto_a.local_id.clone(),
+            a_addr,
+            proto_a.local_identity.public_key_bytes(),
+            sphincs.public_key.0.clone(),
+            challenge,
+            &proof,
+        )
+        .await
+        .unwrap();
+        let kem_ct = proto_a.initiate_session(&proto_b.local_id, &proto_b.local_kyber.public_key.0).unwrap();
+        proto_b.accept_session(&proto_a.local_id, &kem_ct).unwrap();
+
+        // Blocks and a vote share one connection, each on its own stream
+        for i in 0..3u8 {
+            let block = proto_a.seal_message(&proto_b.local_id, MessageType::Block, &[i; 2048]).unwrap();
+            proto_a.send_message(b_addr, &block).await.unwrap();
+        }
+        let vote = proto_a.seal_message(&proto_b.local_id, MessageType::Governance, b"vote").unwrap();
+        proto_a.send_message(b_addr, &vote).await.unwrap();
+
+        let mut kinds = Vec::new();
+        for _ in 0..4 {
+            let (from, msg) = timeout(Duration::from_secs(5), rx_b.recv()).await.unwrap().unwrap();
+            assert_eq!(from, proto_a.local_id);
+            kinds.push(msg.message_type);
+        }
+        assert_eq!(kinds.iter().filter(|k| **k == MessageType::Block).count(), 3);
+        assert!(kinds.contains(&MessageType::Governance));
+    }
+
     #[tokio::test]
     async fn test_replay_attack_rejected() {
         let (proto_a, _, _) = make_proto();
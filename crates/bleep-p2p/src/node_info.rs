@@ -0,0 +1,403 @@
+//! Persistent node identity, operator metadata and the connected-peer directory.
+//!
+//! A node's id is `sha256(ed25519 public key)`.  The key is generated on
+//! first start and stored in `<data_dir>/node_key.json`, so the id survives
+//! restarts and is the same in the handshake, in `/rpc/net/*` and in
+//! telemetry.
+//!
+//! Peers introduce themselves with a [`Hello`]: id, key, agent string,
+//! advertised listen address and optional operator metadata (moniker,
+//! contact, region).  Metadata is capped: a node truncates its own before
+//! sending, and a `Hello` over the caps is rejected at the handshake.
+//!
+//! [`PeerDirectory`] records every peer that completed the handshake until
+//! it disconnects, and answers peer-exchange (PEX) requests with its peers'
+//! advertised addresses.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::Path;
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{P2PError, P2PResult};
+use crate::quantum_crypto::{
+    Ed25519Keypair, KyberKeypair, NodeIdentity, SphincsKeypair, SphincsPublicKey, SphincsSecretKey,
+};
+use crate::types::{unix_now, NodeId};
+
+/// File under the data dir holding the node's keys.
+pub const NODE_KEY_FILE: &str = "node_key.json";
+/// Handshake protocol version sent in every `Hello`.
+pub const PROTOCOL_VERSION: u32 = 1;
+/// `MessageType::Custom` tag of handshake frames.
+pub const HELLO_MESSAGE: &str = "hello";
+/// `MessageType::Custom` tag of peer-exchange frames.
+pub const PEX_MESSAGE: &str = "pex";
+
+pub const MAX_MONIKER_LEN: usize = 64;
+pub const MAX_CONTACT_LEN: usize = 128;
+pub const MAX_REGION_LEN: usize = 32;
+pub const MAX_AGENT_LEN: usize = 64;
+/// Most peers returned in one PEX response.
+pub const MAX_PEX_PEERS: usize = 64;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// PERSISTENT IDENTITY
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Serialize, Deserialize)]
+struct StoredKeys {
+    ed25519_secret: String,
+    sphincs_public:  String,
+    sphincs_secret:  String,
+}
+
+/// Load the identity stored in `data_dir`, generating and saving one on
+/// first start.  The Kyber key is per-process and is not stored.
+pub fn load_or_create_identity(data_dir: &Path) -> P2PResult<NodeIdentity> {
+    let path = data_dir.join(NODE_KEY_FILE);
+    if path.exists() {
+        let stored: StoredKeys = serde_json::from_slice(&fs::read(&path)?)
+            .map_err(|e| P2PError::Serialization(format!("{}: {}", path.display(), e)))?;
+        let hex_field = |v: &str| hex::decode(v).map_err(|e| P2PError::Crypto(format!("{}: {}", path.display(), e)));
+        let ed_secret: [u8; 32] = hex_field(&stored.ed25519_secret)?
+            .try_into()
+            .map_err(|_| P2PError::Crypto(format!("{}: bad ed25519 key length", path.display())))?;
+        return Ok(NodeIdentity {
+            ed_keypair: Ed25519Keypair::from_bytes(&ed_secret)?,
+            sphincs_keypair: SphincsKeypair {
+                public_key: SphincsPublicKey(hex_field(&stored.sphincs_public)?),
+                secret_key: SphincsSecretKey(hex_field(&stored.sphincs_secret)?),
+            },
+            kyber_keypair: KyberKeypair::generate(),
+        });
+    }
+
+    let identity = NodeIdentity::generate();
+    let stored = StoredKeys {
+        ed25519_secret: hex::encode(identity.ed_keypair.secret_bytes()),
+        sphincs_public:  hex::encode(&identity.sphincs_keypair.public_key.0),
+        sphincs_secret:  hex::encode(&identity.sphincs_keypair.secret_key.0),
+    };
+    fs::create_dir_all(data_dir)?;
+    let tmp = data_dir.join(format!("{NODE_KEY_FILE}.tmp"));
+    {
+        let mut opts = fs::OpenOptions::new();
+        opts.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+        let mut f = opts.open(&tmp)?;
+        f.write_all(&serde_json::to_vec_pretty(&stored).map_err(|e| P2PError::Serialization(e.to_string()))?)?;
+        f.sync_all()?;
+    }
+    fs::rename(&tmp, &path)?;
+    Ok(identity)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// HELLO
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Operator-supplied description of a node.  All fields are optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeMetadata {
+    pub moniker: String,
+    pub contact: String,
+    pub region:  String,
+}
+
+impl NodeMetadata {
+    /// Build metadata, truncating each field to its cap.
+    pub fn new(moniker: &str, contact: &str, region: &str) -> Self {
+        NodeMetadata {
+            moniker: truncate(moniker, MAX_MONIKER_LEN),
+            contact: truncate(contact, MAX_CONTACT_LEN),
+            region:  truncate(region, MAX_REGION_LEN),
+        }
+    }
+
+    pub fn check(&self) -> P2PResult<()> {
+        for (field, value, cap) in [
+            ("moniker", &self.moniker, MAX_MONIKER_LEN),
+            ("contact", &self.contact, MAX_CONTACT_LEN),
+            ("region", &self.region, MAX_REGION_LEN),
+        ] {
+            if value.len() > cap {
+                return Err(P2PError::HandshakeRejected(format!("{field} is {} bytes, limit {cap}", value.len())));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn truncate(s: &str, cap: usize) -> String {
+    let mut end = s.len().min(cap);
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    s[..end].to_string()
+}
+
+/// First message of every connection, in both directions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub protocol_version: u32,
+    pub node_id:          NodeId,
+    pub ed25519_pubkey:   Vec<u8>,
+    /// Software and version, e.g. `bleep/1.0.0`.
+    pub agent:            String,
+    pub listen_addr:      SocketAddr,
+    pub metadata:         NodeMetadata,
+}
+
+impl Hello {
+    pub fn new(identity: &NodeIdentity, agent: &str, listen_addr: SocketAddr, metadata: NodeMetadata) -> Self {
+        Hello {
+            protocol_version: PROTOCOL_VERSION,
+            node_id:          identity.node_id(),
+            ed25519_pubkey:   identity.ed_keypair.public_key_bytes(),
+            agent:            truncate(agent, MAX_AGENT_LEN),
+            listen_addr,
+            metadata:         NodeMetadata::new(&metadata.moniker, &metadata.contact, &metadata.region),
+        }
+    }
+
+    /// Reject a `Hello` whose id is not its key's hash or whose fields are
+    /// over their caps.
+    pub fn check(&self) -> P2PResult<()> {
+        if NodeId::from_bytes(&self.ed25519_pubkey) != self.node_id {
+            return Err(P2PError::HandshakeRejected("node id does not match its key".into()));
+        }
+        if self.agent.len() > MAX_AGENT_LEN {
+            return Err(P2PError::HandshakeRejected(format!("agent is {} bytes, limit {MAX_AGENT_LEN}", self.agent.len())));
+        }
+        self.metadata.check()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// PEER DIRECTORY
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+struct PeerSession {
+    hello:        Hello,
+    /// Listen address with an unspecified IP replaced by the remote IP.
+    advertised:   SocketAddr,
+    remote_addr:  SocketAddr,
+    connected_at: u64,
+    last_seen:    u64,
+    messages_in:  u64,
+}
+
+/// One connected peer, as served by `GET /rpc/net/peers`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerSummary {
+    pub node_id:      String,
+    pub moniker:      String,
+    pub contact:      String,
+    pub region:       String,
+    pub agent:        String,
+    pub listen_addr:  SocketAddr,
+    pub remote_addr:  SocketAddr,
+    pub connected_at: u64,
+    pub last_seen:    u64,
+    pub messages_in:  u64,
+}
+
+/// The local node, as served by `GET /rpc/net/info`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalNodeInfo {
+    pub node_id:          String,
+    pub protocol_version: u32,
+    pub agent:            String,
+    pub listen_addr:      SocketAddr,
+    pub metadata:         NodeMetadata,
+    pub peer_count:       usize,
+}
+
+/// A peer address learned through PEX.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PexEntry {
+    pub node_id: NodeId,
+    pub addr:    SocketAddr,
+}
+
+/// Answer to a PEX request: who the node is and whom it is connected to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PexResponse {
+    pub hello: Hello,
+    pub peers: Vec<PexEntry>,
+}
+
+/// Peers that completed the handshake and are still connected.
+pub struct PeerDirectory {
+    local: Hello,
+    peers: RwLock<HashMap<NodeId, PeerSession>>,
+}
+
+impl PeerDirectory {
+    pub fn new(local: Hello) -> Self {
+        PeerDirectory { local, peers: RwLock::new(HashMap::new()) }
+    }
+
+    pub fn local(&self) -> &Hello {
+        &self.local
+    }
+
+    /// Record a peer's `Hello`.  A reconnecting peer replaces its old entry.
+    pub fn on_hello(&self, hello: Hello, remote_addr: SocketAddr) -> P2PResult<()> {
+        hello.check()?;
+        if hello.node_id == self.local.node_id {
+            return Err(P2PError::HandshakeRejected("peer has our node id".into()));
+        }
+        let advertised = if hello.listen_addr.ip().is_unspecified() {
+            SocketAddr::new(remote_addr.ip(), hello.listen_addr.port())
+        } else {
+            hello.listen_addr
+        };
+        let now = unix_now();
+        self.peers.write().insert(hello.node_id.clone(), PeerSession {
+            hello,
+            advertised,
+            remote_addr,
+            connected_at: now,
+            last_seen:    now,
+            messages_in:  0,
+        });
+        Ok(())
+    }
+
+    /// Forget a peer; returns whether it was known.
+    pub fn on_disconnect(&self, id: &NodeId) -> bool {
+        self.peers.write().remove(id).is_some()
+    }
+
+    pub fn on_message(&self, id: &NodeId) {
+        if let Some(p) = self.peers.write().get_mut(id) {
+            p.messages_in += 1;
+            p.last_seen = unix_now();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Connected peers, ordered by node id.
+    pub fn peers(&self) -> Vec<PeerSummary> {
+        let mut out: Vec<PeerSummary> = self.peers.read().values().map(|p| PeerSummary {
+            node_id:      p.hello.node_id.to_string(),
+            moniker:      p.hello.metadata.moniker.clone(),
+            contact:      p.hello.metadata.contact.clone(),
+            region:       p.hello.metadata.region.clone(),
+            agent:        p.hello.agent.clone(),
+            listen_addr:  p.advertised,
+            remote_addr:  p.remote_addr,
+            connected_at: p.connected_at,
+            last_seen:    p.last_seen,
+            messages_in:  p.messages_in,
+        }).collect();
+        out.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        out
+    }
+
+    pub fn info(&self) -> LocalNodeInfo {
+        LocalNodeInfo {
+            node_id:          self.local.node_id.to_string(),
+            protocol_version: self.local.protocol_version,
+            agent:            self.local.agent.clone(),
+            listen_addr:      self.local.listen_addr,
+            metadata:         self.local.metadata.clone(),
+            peer_count:       self.len(),
+        }
+    }
+
+    /// Every connected peer with the address it listens on.
+    pub fn entries(&self) -> Vec<PexEntry> {
+        self.peers.read().values().map(|p| PexEntry {
+            node_id: p.hello.node_id.clone(),
+            addr:    p.advertised,
+        }).collect()
+    }
+
+    /// Up to `MAX_PEX_PEERS` most recently seen peers.
+    pub fn pex_response(&self) -> PexResponse {
+        let peers = self.peers.read();
+        let mut sessions: Vec<&PeerSession> = peers.values().collect();
+        sessions.sort_by(|a, b| b.last_seen.cmp(&a.last_seen).then(a.hello.node_id.0.cmp(&b.hello.node_id.0)));
+        PexResponse {
+            hello: self.local.clone(),
+            peers: sessions.into_iter().take(MAX_PEX_PEERS).map(|p| PexEntry {
+                node_id: p.hello.node_id.clone(),
+                addr:    p.advertised,
+            }).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hello(metadata: NodeMetadata) -> Hello {
+        Hello::new(&NodeIdentity::generate(), "bleep/test", "0.0.0.0:7700".parse().unwrap(), metadata)
+    }
+
+    #[test]
+    fn node_id_is_stable_across_restarts() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = load_or_create_identity(dir.path()).unwrap();
+        let second = load_or_create_identity(dir.path()).unwrap();
+        assert_eq!(first.node_id(), second.node_id());
+        assert_eq!(first.sphincs_keypair.public_key.0, second.sphincs_keypair.public_key.0);
+
+        let other = tempfile::tempdir().unwrap();
+        assert_ne!(load_or_create_identity(other.path()).unwrap().node_id(), first.node_id());
+    }
+
+    #[test]
+    fn oversized_moniker_is_truncated_on_send_and_rejected_on_receive() {
+        let long = "m".repeat(MAX_MONIKER_LEN + 10);
+        let ours = hello(NodeMetadata { moniker: long.clone(), ..Default::default() });
+        assert_eq!(ours.metadata.moniker.len(), MAX_MONIKER_LEN);
+
+        let dir = PeerDirectory::new(hello(NodeMetadata::default()));
+        let mut theirs = hello(NodeMetadata::default());
+        theirs.metadata.moniker = long;
+        let remote = "10.0.0.9:40000".parse().unwrap();
+        assert!(matches!(dir.on_hello(theirs.clone(), remote), Err(P2PError::HandshakeRejected(_))));
+
+        theirs.node_id = NodeId::random();
+        theirs.metadata.moniker = "ok".into();
+        assert!(dir.on_hello(theirs, remote).is_err(), "id must match key");
+        assert!(dir.is_empty());
+    }
+
+    #[test]
+    fn directory_tracks_connects_and_disconnects() {
+        let dir = PeerDirectory::new(hello(NodeMetadata::default()));
+        let peer = hello(NodeMetadata::new("alpha", "ops@example.org", "eu-west"));
+        let id = peer.node_id.clone();
+        dir.on_hello(peer, "10.0.0.2:51000".parse().unwrap()).unwrap();
+        dir.on_message(&id);
+
+        let peers = dir.peers();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].moniker, "alpha");
+        assert_eq!(peers[0].messages_in, 1);
+        // Unspecified listen IP is replaced by the address the peer dialled from.
+        assert_eq!(dir.pex_response().peers[0].addr, "10.0.0.2:7700".parse().unwrap());
+
+        assert!(dir.on_disconnect(&id));
+        assert_eq!(dir.info().peer_count, 0);
+    }
+}
@@ -3,17 +3,24 @@
 //! Each hop encrypts under the relay's Kyber-derived session key (AES-256-GCM).
 //! The final destination peels all layers to recover the original plaintext.
 //! Route selection uses AI trust scoring to avoid low-reputation relays.
+//!
+//! `seal_onion` / `open_onion` build and peel `OnionPacket`s addressed to
+//! each relay's long-term Kyber key, so a relay needs nothing but its own
+//! secret key to find the next hop, and learns nothing beyond it.
 
 use std::sync::Arc;
 
 use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use crate::ai_security::PeerScoring;
 use crate::error::{P2PError, P2PResult};
 use crate::message_protocol::MessageProtocol;
 use crate::peer_manager::PeerManager;
-use crate::quantum_crypto::{aes_gcm_decrypt, aes_gcm_encrypt, derive_key};
+use crate::quantum_crypto::{
+    aes_gcm_decrypt, aes_gcm_encrypt, derive_key, kyber_decapsulate, kyber_encapsulate, KyberKeypair,
+};
 use crate::types::{MessageType, NodeId, RoutePath};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -21,7 +28,7 @@ use crate::types::{MessageType, NodeId, RoutePath};
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Maximum hops in an onion circuit.
-const MAX_HOPS: usize = 6;
+pub const MAX_HOPS: usize = 6;
 /// Minimum trust score for a node to be used as a relay.
 const MIN_RELAY_TRUST: f64 = 55.0;
 
@@ -53,6 +60,68 @@ pub struct OnionLayer {
     pub hop_index: u8,
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// ONION PACKET
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// One onion layer, sealed to a relay's Kyber public key.
+///
+/// The relay decapsulates `kem_ciphertext` with its secret key, derives the
+/// layer key and opens `sealed` into a `Peeled`: the next hop and the packet
+/// for it, or the message itself at the last hop.  Layers are not padded,
+/// so a packet's size hints at how many hops remain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnionPacket {
+    /// Kyber-768 ciphertext encapsulating this layer's shared secret.
+    pub kem_ciphertext: Vec<u8>,
+    /// AES-256-GCM encrypted `Peeled` (nonce prepended).
+    pub sealed: Vec<u8>,
+}
+
+/// What a relay finds inside its layer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Peeled {
+    /// Pass `packet` on to `next_hop`.
+    Forward { next_hop: NodeId, packet: OnionPacket },
+    /// This node is the destination.
+    Deliver(Vec<u8>),
+}
+
+/// Layer key from the KEM shared secret, bound to the ciphertext it came from.
+fn packet_key(shared_secret: &[u8], kem_ciphertext: &[u8]) -> [u8; 32] {
+    derive_key(shared_secret, kem_ciphertext, b"bleep-onion-packet-v1")
+}
+
+fn seal_layer(kyber_pk: &[u8], content: &Peeled) -> P2PResult<OnionPacket> {
+    let plaintext = bincode::serialize(content).map_err(|e| P2PError::Serialization(e.to_string()))?;
+    let (kem_ciphertext, shared_secret) = kyber_encapsulate(kyber_pk)?;
+    let sealed = aes_gcm_encrypt(&packet_key(&shared_secret, &kem_ciphertext), &plaintext)?;
+    Ok(OnionPacket { kem_ciphertext, sealed })
+}
+
+/// Wrap `plaintext` for `hops`, given as `(relay id, Kyber public key)` in
+/// route order; the returned packet goes to `hops[0]` and the last hop
+/// receives `plaintext`.
+pub fn seal_onion(plaintext: &[u8], hops: &[(NodeId, Vec<u8>)]) -> P2PResult<OnionPacket> {
+    if hops.is_empty() || hops.len() > MAX_HOPS {
+        return Err(P2PError::Crypto(format!("onion route must have 1 to {} hops, got {}", MAX_HOPS, hops.len())));
+    }
+    let mut content = Peeled::Deliver(plaintext.to_vec());
+    for i in (1..hops.len()).rev() {
+        let packet = seal_layer(&hops[i].1, &content)?;
+        content = Peeled::Forward { next_hop: hops[i].0.clone(), packet };
+    }
+    seal_layer(&hops[0].1, &content)
+}
+
+/// Open the layer of `packet` addressed to `keys`.  A packet sealed to
+/// another key, or altered in transit, fails with `DecryptionFailed`.
+pub fn open_onion(packet: &OnionPacket, keys: &KyberKeypair) -> P2PResult<Peeled> {
+    let shared_secret = kyber_decapsulate(&packet.kem_ciphertext, &keys.secret_key.0)?;
+    let plaintext = aes_gcm_decrypt(&packet_key(&shared_secret, &packet.kem_ciphertext), &packet.sealed)?;
+    bincode::deserialize(&plaintext).map_err(|e| P2PError::Serialization(e.to_string()))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // ONION CIRCUIT
 // ─────────────────────────────────────────────────────────────────────────────
@@ -188,6 +257,22 @@ impl OnionRouter {
 
     // ── SEND ─────────────────────────────────────────────────────────────────
 
+    /// Send `packet` to `first_hop` as an `OnionRelay` frame.
+    pub async fn send_packet(&self, first_hop: &NodeId, packet: &OnionPacket) -> P2PResult<()> {
+        let addr = self
+            .peer_manager
+            .get_peer_addr(first_hop)
+            .ok_or_else(|| P2PError::PeerNotFound { peer_id: first_hop.to_string() })?;
+        if !self.message_protocol.has_session(first_hop) {
+            return Err(P2PError::PeerNotFound { peer_id: first_hop.to_string() });
+        }
+        let bytes = bincode::serialize(packet).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        let sealed = self
+            .message_protocol
+            .seal_message(first_hop, MessageType::OnionRelay, &bytes)?;
+        self.message_protocol.send_message(addr, &sealed).await
+    }
+
     /// Build and send an anonymised message.
     /// Returns immediately after enqueuing the first hop.
     pub async fn send_anonymous(
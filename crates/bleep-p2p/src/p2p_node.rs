@@ -3,9 +3,10 @@
 //! Orchestrates all subsystems:
 //! - PeerManager (admission, scoring, banning)
 //! - KademliaDHT (peer discovery)
-//! - MessageProtocol (encryption, signing, TCP transport)
+//! - MessageProtocol (encryption, signing, TCP or QUIC transport)
 //! - GossipProtocol (epidemic broadcast)
 //! - OnionRouter (anonymous routing)
+//! - Peer store and seed-node dialing with exponential backoff
 //!
 //! Usage:
 //! ```no_run
@@ -21,21 +22,31 @@
 //! ```
 
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
-use tracing::{error, info, warn};
+use tracing::{debug, info, warn};
 
 use crate::ai_security::PeerScoring;
-use crate::error::P2PResult;
+use crate::error::{P2PError, P2PResult};
 use crate::gossip_protocol::GossipProtocol;
+use crate::kademlia_dht::TcpDhtTransport;
 use crate::message_protocol::MessageProtocol;
+use crate::node_info::{load_or_create_identity, Hello, NodeMetadata, PeerDirectory, PeerSummary};
 use crate::onion_routing::OnionRouter;
 use crate::peer_manager::{PeerEvent, PeerManager, PeerManagerConfig};
+#[cfg(feature = "quic")]
+use crate::quic_transport::QuicTransport;
 use crate::quantum_crypto::{
     Ed25519Keypair, KyberKeypair, NodeIdentity,
 };
+use crate::service_quota::{ServiceQuotaConfig, ServiceQuotas};
+use crate::transport::{TcpTransport, Transport, TransportKind};
 use crate::types::{MessageType, NodeId, PeerInfo, SecureMessage};
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -44,12 +55,58 @@ use crate::types::{MessageType, NodeId, PeerInfo, SecureMessage};
 
 #[derive(Debug, Clone)]
 pub struct P2PNodeConfig {
-    /// Address to bind the TCP listener on.
+    /// Address to listen on: a TCP port, or a UDP port under QUIC.
     pub listen_addr: SocketAddr,
+    /// Stream transport, `transport` in the `[p2p]` table (`P2PSettings`).
+    /// QUIC needs the `quic` feature; nodes only reach peers on the same one.
+    pub transport: TransportKind,
     /// Bootstrap peers (NodeId + addr + public keys).
     pub bootstrap_peers: Vec<BootstrapPeer>,
     /// Peer manager configuration.
     pub peer_manager_config: PeerManagerConfig,
+    /// Directory holding `node_key.json`.  Without one the node gets a
+    /// fresh identity on every start.
+    pub data_dir: Option<PathBuf>,
+    /// Operator metadata sent in the `Hello`; truncated to the caps.
+    pub metadata: NodeMetadata,
+    /// Per-peer limits on snap, DHT and gossip-pull serving.
+    pub service_quotas: ServiceQuotaConfig,
+    /// Seed nodes known only by address, dialed on start.
+    pub seed_addrs: Vec<SocketAddr>,
+    /// JSON file of known peers: loaded and dialed on start, saved every
+    /// `PEER_STORE_INTERVAL` and on `NodeHandle::shutdown`.
+    pub peer_store: Option<PathBuf>,
+    /// Retry schedule for dialing seed and stored peers.
+    pub dial_backoff: Backoff,
+}
+
+/// How often the peer store is rewritten while the node runs.
+pub const PEER_STORE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Exponential retry schedule: wait `initial`, doubling up to `max`
+/// between tries, for at most `attempts` tries.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub attempts: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(30),
+            attempts: 6,
+        }
+    }
+}
+
+impl Backoff {
+    /// Wait before retry `retry` (0 is the wait after the first failure).
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.initial.saturating_mul(1u32.checked_shl(retry).unwrap_or(u32::MAX)).min(self.max)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -63,9 +120,48 @@ impl Default for P2PNodeConfig {
     fn default() -> Self {
         P2PNodeConfig {
             listen_addr: SocketAddr::from(([0,0,0,0], 7700)),
+            transport: TransportKind::default(),
             bootstrap_peers: vec![],
             peer_manager_config: PeerManagerConfig::default(),
+            data_dir: None,
+            metadata: NodeMetadata::default(),
+            service_quotas: ServiceQuotaConfig::default(),
+            seed_addrs: vec![],
+            peer_store: None,
+            dial_backoff: Backoff::default(),
+        }
+    }
+}
+
+/// The `[p2p]` table of a node's TOML config:
+///
+/// ```text
+/// [p2p]
+/// transport = "quic"   # or "tcp", the default
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct P2PSettings {
+    #[serde(default)]
+    pub transport: TransportKind,
+}
+
+impl P2PSettings {
+    /// The `[p2p]` table of the config at `path`; `None` if the file or the
+    /// table is missing.
+    pub fn read(path: &Path) -> P2PResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
         }
+        let text = std::fs::read_to_string(path).map_err(P2PError::Io)?;
+        Self::from_toml(&text).map_err(|e| P2PError::Serialization(format!("{}: {}", path.display(), e)))
+    }
+
+    /// The `[p2p]` table of a whole config file; other tables are ignored.
+    pub fn from_toml(text: &str) -> P2PResult<Option<Self>> {
+        let table: toml::Table = text.parse().map_err(|e: toml::de::Error| P2PError::Serialization(e.to_string()))?;
+        table.get("p2p")
+            .map(|t| t.clone().try_into().map_err(|e: toml::de::Error| P2PError::Serialization(format!("[p2p]: {}", e))))
+            .transpose()
     }
 }
 
@@ -80,6 +176,11 @@ pub struct P2PNode {
     pub message_protocol: Arc<MessageProtocol>,
     pub gossip: Arc<GossipProtocol>,
     pub onion_router: Arc<OnionRouter>,
+    /// Peers that completed the `Hello` handshake.
+    pub directory: Arc<PeerDirectory>,
+    /// Per-peer serving quotas; violations feed the peer manager.
+    pub service_quotas: Arc<ServiceQuotas>,
+    dial_backoff: Backoff,
     /// Inbound messages decoded and verified by MessageProtocol.
     inbound_rx: tokio::sync::Mutex<mpsc::Receiver<(NodeId, SecureMessage)>>,
 }
@@ -87,12 +188,25 @@ pub struct P2PNode {
 impl P2PNode {
     /// Construct and start the node.  Returns the node and a handle to all
     /// background tasks that the caller should await / abort on shutdown.
+    ///
+    /// The listener is bound before anything is spawned, so a taken port is
+    /// an error here rather than a log line; port 0 picks a free one, which
+    /// `NodeHandle::local_addr` reports.  Bootstrap peers are greeted with a
+    /// `Hello`, so both sides list each other once startup settles.  Seed
+    /// addresses and the fresh entries of the peer store are dialed in the
+    /// background, each retried on `dial_backoff`.
     pub async fn start(config: P2PNodeConfig) -> P2PResult<(Arc<Self>, NodeHandle)> {
-        // Generate node identity
-        let identity = Arc::new(NodeIdentity::generate());
+        // Node identity: persisted under the data dir when one is configured
+        let identity = Arc::new(match &config.data_dir {
+            Some(dir) => load_or_create_identity(dir)?,
+            None => NodeIdentity::generate(),
+        });
         let node_id = identity.node_id();
 
-        info!(node_id = %node_id, listen = %config.listen_addr, "Starting BLEEP P2P node");
+        let transport = bind_transport(&config, &identity).await?;
+        let listen_addr = transport.local_addr()
+            .ok_or_else(|| P2PError::Transport(format!("{} transport is not listening", transport.kind())))?;
+        info!(node_id = %node_id, listen = %listen_addr, transport = %transport.kind(), "Starting BLEEP P2P node");
 
         // Peer manager
         let (peer_manager, mut event_rx) = PeerManager::new(
@@ -100,21 +214,25 @@ impl P2PNode {
             config.peer_manager_config.clone(),
         );
 
-        // Message protocol
-        let _ed_kp = Ed25519Keypair::from_bytes(
-            identity.ed_keypair.sign(b"derived").as_slice()[..32]
-                .try_into()
-                .unwrap_or(&[0u8; 32]),
-        )
-        .unwrap_or_else(|_| Ed25519Keypair::generate());
-
-        // Simpler: just generate fresh transport keys (separate from identity)
-        let transport_ed = Ed25519Keypair::generate();
+        // Message protocol — signs with the identity key so frames carry the node id
+        let transport_ed = Ed25519Keypair::from_bytes(&identity.ed_keypair.secret_bytes())?;
         let transport_kyber = KyberKeypair::generate();
 
         let (message_protocol, inbound_rx) =
             MessageProtocol::new(transport_ed, transport_kyber, peer_manager.clone());
 
+        let agent = format!("bleep/{}", env!("CARGO_PKG_VERSION"));
+        let directory = Arc::new(PeerDirectory::new(Hello::new(
+            &identity,
+            &agent,
+            listen_addr,
+            config.metadata.clone(),
+        )));
+        message_protocol.attach_directory(directory.clone());
+        message_protocol.attach_transport(transport.clone());
+        let service_quotas = Arc::new(ServiceQuotas::new(config.service_quotas.clone()));
+        message_protocol.attach_service_quotas(service_quotas.clone());
+
         // Gossip
         let gossip = GossipProtocol::new(peer_manager.clone(), message_protocol.clone());
 
@@ -133,19 +251,16 @@ impl P2PNode {
             message_protocol: message_protocol.clone(),
             gossip: gossip.clone(),
             onion_router,
+            directory: directory.clone(),
+            service_quotas: service_quotas.clone(),
+            dial_backoff: config.dial_backoff.clone(),
             inbound_rx: tokio::sync::Mutex::new(inbound_rx),
         });
 
         // ── Spawn background tasks ────────────────────────────────────────────
 
-        // 1. TCP listener
-        let mp_clone = message_protocol.clone();
-        let listen_addr = config.listen_addr;
-        let listen_handle = tokio::spawn(async move {
-            if let Err(e) = mp_clone.listen(listen_addr).await {
-                error!(error = %e, "MessageProtocol listener stopped");
-            }
-        });
+        // 1. Listener
+        let listen_handle = tokio::spawn(message_protocol.clone().serve_transport(transport.clone()));
 
         // 2. Gossip background loop
         let gossip_clone = gossip.clone();
@@ -156,19 +271,30 @@ impl P2PNode {
         // 3. Peer manager maintenance
         peer_manager.clone().spawn_maintenance();
 
-        // 4. Kademlia DHT maintenance
+        // 4. Kademlia DHT: requests go out signed with the identity key
+        peer_manager.dht().attach_transport(
+            Arc::new(TcpDhtTransport { identity: identity.clone(), transport }),
+            listen_addr,
+        );
         let dht_clone = peer_manager.dht();
         let dht_handle = tokio::spawn(async move {
             dht_clone.run_maintenance().await;
         });
 
-        // 5. Peer event logger
+        // 5. Peer event logger; removed and banned peers leave the directory
+        let event_directory = directory.clone();
         let event_handle = tokio::spawn(async move {
             while let Ok(event) = event_rx.recv().await {
                 match &event {
                     PeerEvent::Added(id) => info!(peer = %id, "Peer added"),
-                    PeerEvent::Removed(id) => info!(peer = %id, "Peer removed"),
-                    PeerEvent::Banned(id) => warn!(peer = %id, "Peer banned"),
+                    PeerEvent::Removed(id) => {
+                        event_directory.on_disconnect(id);
+                        info!(peer = %id, "Peer removed")
+                    }
+                    PeerEvent::Banned(id) => {
+                        event_directory.on_disconnect(id);
+                        warn!(peer = %id, "Peer banned")
+                    }
                     PeerEvent::StatusChanged(id, status) => {
                         info!(peer = %id, status = %status, "Peer status changed")
                     }
@@ -176,16 +302,82 @@ impl P2PNode {
             }
         });
 
-        // Bootstrap
+        // 6. Quota violations lower the offender's reputation
+        let penalty_pm = peer_manager.clone();
+        let penalty_handle = tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs(5));
+            loop {
+                tick.tick().await;
+                penalty_pm.apply_quota_penalties(service_quotas.take_penalties());
+            }
+        });
+
+        // Bootstrap: greet and seed the routing table, look ourselves up in
+        // the background, then greet the nodes the lookup turned up
+        let mut seeds = Vec::new();
         for bp in &config.bootstrap_peers {
             let bp_id = NodeId::from_bytes(&bp.ed25519_pubkey);
-            let peer = PeerInfo::new(bp_id.clone(), bp.addr, bp.ed25519_pubkey.clone(), bp.sphincs_pubkey.clone());
-            peer_manager.dht().add_peer(peer).await;
+            seeds.push(PeerInfo::new(bp_id, bp.addr, bp.ed25519_pubkey.clone(), bp.sphincs_pubkey.clone()));
             info!(addr = %bp.addr, "Bootstrap peer registered in DHT");
         }
+        let dht_bootstrap = peer_manager.dht();
+        let discover_pm = peer_manager.clone();
+        let own_id = node_id.clone();
+        let greeter = message_protocol.clone();
+        let bootstrap_handle = tokio::spawn(async move {
+            for seed in &seeds {
+                if let Err(e) = greeter.hello(seed.addr).await {
+                    warn!(addr = %seed.addr, error = %e, "Bootstrap hello failed");
+                }
+            }
+            dht_bootstrap.bootstrap(&seeds).await;
+            for contact in discover_pm.discover(&own_id).await {
+                if let Err(e) = greeter.hello(contact.addr).await {
+                    debug!(peer = %contact.id, addr = %contact.addr, error = %e, "Discovered peer did not answer hello");
+                }
+            }
+        });
+
+        // Seed nodes and peers remembered from the last run
+        let stored = match &config.peer_store {
+            Some(path) => peer_manager.load(path).unwrap_or_else(|e| {
+                warn!(path = %path.display(), error = %e, "Peer store unreadable, starting without it");
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        let mut dial: Vec<SocketAddr> = config.seed_addrs.clone();
+        dial.extend(stored.iter().map(|p| p.addr));
+        let dial_protocol = message_protocol.clone();
+        let dial_pm = peer_manager.clone();
+        let backoff = config.dial_backoff.clone();
+        let dial_handle = tokio::spawn(async move {
+            let reached = dial_all(&dial_protocol, &dial_pm, dial, &backoff).await;
+            info!(reached, "Seed and stored peers dialed");
+        });
+
+        let mut tasks = vec![listen_handle, gossip_handle, dht_handle, event_handle, penalty_handle, bootstrap_handle, dial_handle];
+        let mut peer_store = None;
+        if let Some(path) = config.peer_store.clone() {
+            let store_pm = peer_manager.clone();
+            let store_directory = directory.clone();
+            let store_path = path.clone();
+            tasks.push(tokio::spawn(async move {
+                let mut tick = tokio::time::interval(PEER_STORE_INTERVAL);
+                tick.tick().await;
+                loop {
+                    tick.tick().await;
+                    save_peer_store(&store_pm, &store_directory, &store_path);
+                }
+            }));
+            peer_store = Some((path, peer_manager.clone()));
+        }
 
         let handle = NodeHandle {
-            tasks: vec![listen_handle, gossip_handle, dht_handle, event_handle],
+            local_addr: listen_addr,
+            directory,
+            peer_store,
+            tasks,
         };
 
         Ok((node, handle))
@@ -238,6 +430,17 @@ impl P2PNode {
         Ok(peer_id)
     }
 
+    /// Exchange `Hello`s with the node at `addr` and record it as a peer.
+    pub async fn say_hello(&self, addr: SocketAddr) -> P2PResult<Hello> {
+        self.message_protocol.hello(addr).await
+    }
+
+    /// Dial `addrs` concurrently, retrying each on the configured
+    /// `dial_backoff`; returns how many answered.
+    pub async fn bootstrap(&self, addrs: Vec<SocketAddr>) -> usize {
+        dial_all(&self.message_protocol, &self.peer_manager, addrs, &self.dial_backoff).await
+    }
+
     /// Generate a SPHINCS+ proof-of-identity for use in the handshake.
     pub fn make_identity_proof(&self, challenge: &[u8]) -> P2PResult<Vec<u8>> {
         self.identity.sign_sphincs(challenge)
@@ -252,6 +455,69 @@ impl P2PNode {
     }
 }
 
+/// Bind the configured transport on `listen_addr`.  Under QUIC, bootstrap
+/// peers are pinned to their identity keys.
+async fn bind_transport(config: &P2PNodeConfig, identity: &NodeIdentity) -> P2PResult<Arc<dyn Transport>> {
+    match config.transport {
+        TransportKind::Tcp => {
+            let listener = TcpListener::bind(config.listen_addr).await.map_err(P2PError::Io)?;
+            Ok(Arc::new(TcpTransport::new(listener)))
+        }
+        #[cfg(feature = "quic")]
+        TransportKind::Quic => {
+            let quic = QuicTransport::bind(config.listen_addr, identity)?;
+            for bp in &config.bootstrap_peers {
+                quic.pin(bp.addr, NodeId::from_bytes(&bp.ed25519_pubkey));
+            }
+            Ok(Arc::new(quic))
+        }
+        #[cfg(not(feature = "quic"))]
+        TransportKind::Quic => {
+            let _ = identity;
+            Err(P2PError::Transport("QUIC transport needs bleep-p2p built with the `quic` feature".into()))
+        }
+    }
+}
+
+/// Say hello to each distinct address in `addrs` concurrently; a peer that
+/// answers is remembered for the peer store.
+async fn dial_all(
+    protocol: &MessageProtocol,
+    peer_manager: &PeerManager,
+    mut addrs: Vec<SocketAddr>,
+    backoff: &Backoff,
+) -> usize {
+    addrs.sort();
+    addrs.dedup();
+    let dials = addrs.into_iter().map(|addr| async move {
+        for attempt in 0..backoff.attempts.max(1) {
+            if attempt > 0 {
+                tokio::time::sleep(backoff.delay(attempt - 1)).await;
+            }
+            match protocol.hello(addr).await {
+                Ok(hello) => {
+                    peer_manager.remember(hello.node_id, addr);
+                    return true;
+                }
+                Err(e) => debug!(addr = %addr, attempt, error = %e, "Dial failed"),
+            }
+        }
+        warn!(addr = %addr, attempts = backoff.attempts, "Peer unreachable, giving up");
+        false
+    });
+    futures::future::join_all(dials).await.into_iter().filter(|ok| *ok).count()
+}
+
+/// Remember every connected peer, then write the peer store.
+fn save_peer_store(peer_manager: &PeerManager, directory: &PeerDirectory, path: &Path) {
+    for entry in directory.entries() {
+        peer_manager.remember(entry.node_id, entry.addr);
+    }
+    if let Err(e) = peer_manager.save(path) {
+        warn!(path = %path.display(), error = %e, "Could not save peer store");
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // NODE HANDLE
 // ─────────────────────────────────────────────────────────────────────────────
@@ -259,14 +525,31 @@ impl P2PNode {
 /// Holds the JoinHandles for all background tasks spawned by the node.
 /// Drop this to cancel all tasks, or call `shutdown()`.
 pub struct NodeHandle {
-    tasks: Vec<JoinHandle<()>>,
+    local_addr: SocketAddr,
+    directory:  Arc<PeerDirectory>,
+    peer_store: Option<(PathBuf, Arc<PeerManager>)>,
+    tasks:      Vec<JoinHandle<()>>,
 }
 
 impl NodeHandle {
+    /// Address the listener is bound to, with the port it actually got.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Peers that completed the `Hello` handshake, in either direction.
+    pub fn connected_peers(&self) -> Vec<PeerSummary> {
+        self.directory.peers()
+    }
+
+    /// Stop every task and write the peer store, if there is one.
     pub async fn shutdown(self) {
         for task in self.tasks {
             task.abort();
         }
+        if let Some((path, peer_manager)) = &self.peer_store {
+            save_peer_store(peer_manager, &self.directory, path);
+        }
         info!("BLEEP P2P node shut down");
     }
 }
@@ -275,7 +558,6 @@ impl NodeHandle {
 mod tests {
     use super::*;
     use crate::quantum_crypto::SphincsKeypair;
-    use std::time::Duration;
     use tokio::time::timeout;
 
     async fn start_test_node(port: u16) -> (Arc<P2PNode>, NodeHandle) {
@@ -283,6 +565,7 @@ mod tests {
             listen_addr: format!("127.0.0.1:{port}").parse().unwrap(),
             bootstrap_peers: vec![],
             peer_manager_config: PeerManagerConfig::default(),
+            ..Default::default()
         };
         P2PNode::start(config).await.unwrap()
     }
@@ -343,6 +626,192 @@ mod tests {
         handle.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn test_nodes_on_ephemeral_ports_discover_each_other() {
+        let (node_a, handle_a) = start_test_node(0).await;
+        assert_ne!(handle_a.local_addr().port(), 0);
+
+        let config = P2PNodeConfig {
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            bootstrap_peers: vec![BootstrapPeer {
+                addr:           handle_a.local_addr(),
+                ed25519_pubkey: node_a.identity.ed_keypair.public_key_bytes(),
+                sphincs_pubkey: node_a.identity.sphincs_keypair.public_key.0.clone(),
+            }],
+            ..Default::default()
+        };
+        let (node_b, handle_b) = P2PNode::start(config).await.unwrap();
+        assert_ne!(handle_b.local_addr(), handle_a.local_addr());
+
+        timeout(Duration::from_secs(5), async {
+            while handle_a.connected_peers().is_empty() || handle_b.connected_peers().is_empty() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("bootstrap hello should connect both nodes");
+        let a_peers = handle_a.connected_peers();
+        let b_peers = handle_b.connected_peers();
+        assert_eq!(a_peers[0].node_id, node_b.node_id.to_string());
+        assert_eq!(a_peers[0].listen_addr, handle_b.local_addr());
+        assert_eq!(b_peers[0].node_id, node_a.node_id.to_string());
+
+        // A second node cannot take a bound port
+        let taken = P2PNodeConfig { listen_addr: handle_a.local_addr(), ..Default::default() };
+        assert!(P2PNode::start(taken).await.is_err());
+
+        handle_a.shutdown().await;
+        handle_b.shutdown().await;
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let backoff = Backoff { initial: Duration::from_millis(100), max: Duration::from_secs(1), attempts: 10 };
+        let delays: Vec<u128> = (0..6).map(|r| backoff.delay(r).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+        assert_eq!(backoff.delay(40), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_seed_dialed_until_up_and_remembered_across_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = dir.path().join("peers.json");
+        // Reserve a port for the seed, which comes up only after the first dial fails
+        let seed_addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let config = P2PNodeConfig {
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            seed_addrs: vec![seed_addr],
+            peer_store: Some(store.clone()),
+            dial_backoff: Backoff { initial: Duration::from_millis(100), max: Duration::from_millis(400), attempts: 20 },
+            ..Default::default()
+        };
+        let (node, handle) = P2PNode::start(config.clone()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        let (seed, seed_handle) = P2PNode::start(P2PNodeConfig { listen_addr: seed_addr, ..Default::default() })
+            .await
+            .unwrap();
+
+        timeout(Duration::from_secs(5), async {
+            while handle.connected_peers().is_empty() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("seed should answer once it is up");
+        assert!(node.peer_manager.known_peers().iter().any(|p| p.id == seed.node_id));
+        handle.shutdown().await;
+
+        // The restarted node finds the seed from its store alone
+        let stored = node.peer_manager.load(&store).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!((stored[0].id.clone(), stored[0].addr), (seed.node_id.clone(), seed_addr));
+        let (restarted, restarted_handle) = P2PNode::start(P2PNodeConfig { seed_addrs: vec![], ..config })
+            .await
+            .unwrap();
+        timeout(Duration::from_secs(5), async {
+            while restarted_handle.connected_peers().is_empty() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("stored peer should be dialed on start");
+        assert_eq!(restarted.bootstrap(vec![seed_addr, seed_addr]).await, 1);
+
+        restarted_handle.shutdown().await;
+        seed_handle.shutdown().await;
+    }
+
+    #[cfg(feature = "quic")]
+    #[tokio::test]
+    async fn test_tcp_and_quic_nodes_fail_gracefully_and_quic_nodes_exchange_blocks() {
+        use crate::block_sync::{BlockSource, SyncPart, SyncPeer, SyncRequest, SyncResponse, TransportSyncPeer};
+        use crate::quic_transport::QuicTransport;
+
+        struct OneBlock(Vec<u8>);
+
+        impl BlockSource for OneBlock {
+            fn tip(&self) -> u64 {
+                0
+            }
+
+            fn header(&self, index: u64) -> Option<Vec<u8>> {
+                (index == 0).then(|| self.0[..8].to_vec())
+            }
+
+            fn block(&self, index: u64) -> Option<Vec<u8>> {
+                (index == 0).then(|| self.0.clone())
+            }
+        }
+
+        let quic_config = P2PNodeConfig {
+            listen_addr: "127.0.0.1:0".parse().unwrap(),
+            transport: TransportKind::Quic,
+            ..Default::default()
+        };
+        let (tcp_node, tcp_handle) = start_test_node(0).await;
+        let (quic_a, handle_a) = P2PNode::start(quic_config.clone()).await.unwrap();
+
+        // TCP dialing a QUIC port is refused outright
+        let refused = timeout(Duration::from_secs(5), tcp_node.say_hello(handle_a.local_addr())).await;
+        assert!(matches!(refused, Ok(Err(_))), "TCP to QUIC should error promptly");
+
+        // QUIC dialing a TCP port gets no handshake and gives up
+        let dialer = QuicTransport::bind("127.0.0.1:0".parse().unwrap(), &NodeIdentity::generate())
+            .unwrap()
+            .with_connect_timeout(Duration::from_millis(300));
+        let hello = quic_a.message_protocol.sign_plain(MessageType::Ping, vec![]);
+        let unanswered = MessageProtocol::exchange_via(&dialer, tcp_handle.local_addr(), &hello).await;
+        assert!(unanswered.is_err());
+        assert_eq!(quic_a.message_protocol.transport().kind(), TransportKind::Quic);
+
+        // Two QUIC nodes, the second pinning the first as its bootstrap peer
+        let (quic_b, handle_b) = P2PNode::start(P2PNodeConfig {
+            bootstrap_peers: vec![BootstrapPeer {
+                addr:           handle_a.local_addr(),
+                ed25519_pubkey: quic_a.identity.ed_keypair.public_key_bytes(),
+                sphincs_pubkey: quic_a.identity.sphincs_keypair.public_key.0.clone(),
+            }],
+            ..quic_config
+        })
+        .await
+        .unwrap();
+        timeout(Duration::from_secs(5), async {
+            while handle_a.connected_peers().is_empty() || handle_b.connected_peers().is_empty() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("QUIC nodes should complete the hello");
+        assert_eq!(handle_a.connected_peers()[0].node_id, quic_b.node_id.to_string());
+
+        // The second fetches the first's block over its QUIC sync lane
+        let block = (0..=255u8).cycle().take(64 * 1024).collect::<Vec<_>>();
+        quic_a.message_protocol.attach_block_source(Arc::new(OneBlock(block.clone())));
+        let peer = TransportSyncPeer { addr: handle_a.local_addr(), transport: quic_b.message_protocol.transport() };
+        let fetched = timeout(
+            Duration::from_secs(5),
+            peer.request(SyncRequest { from_index: 0, max_blocks: 1, part: SyncPart::Bodies }),
+        )
+        .await
+        .expect("block sync over QUIC should answer")
+        .unwrap();
+        assert_eq!(fetched, SyncResponse::Bodies { tip: 0, blocks: vec![block] });
+
+        tcp_handle.shutdown().await;
+        handle_a.shutdown().await;
+        handle_b.shutdown().await;
+    }
+
+    #[test]
+    fn test_p2p_settings_come_from_the_p2p_table() {
+        let text = "[consensus]\nvalidator_id = \"ab\"\n\n[p2p]\ntransport = \"quic\"\n";
+        assert_eq!(P2PSettings::from_toml(text).unwrap(), Some(P2PSettings { transport: TransportKind::Quic }));
+        assert_eq!(P2PSettings::from_toml("[p2p]\n").unwrap(), Some(P2PSettings::default()));
+        assert_eq!(P2PSettings::from_toml("[consensus]\n").unwrap(), None);
+        assert!(P2PSettings::from_toml("[p2p]\ntransport = \"udp\"\n").is_err());
+        assert_eq!(P2PSettings::read(Path::new("/nonexistent/node.toml")).unwrap(), None);
+    }
+
     #[tokio::test]
     async fn test_node_accepts_tcp_connections() {
         let (node, handle) = start_test_node(17705).await;
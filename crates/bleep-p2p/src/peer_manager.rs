@@ -1,25 +1,34 @@
 //! Production peer manager for bleep-p2p.
 //!
 //! Responsibilities:
-//! - Lifecycle management (add, remove, ban, prune)
+//! - Lifecycle management (add, remove, ban for a cooldown, prune)
 //! - Quantum-secure identity verification on admission
 //! - Continuous AI-driven trust scoring
 //! - Sybil detection via subnet clustering
 //! - Kademlia DHT integration for distributed peer discovery
 //! - Mesh broadcast of peer events
+//! - Persistence of known peer addresses across restarts
 
-use std::net::SocketAddr;
+use std::fs;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 use tokio::sync::broadcast;
 use tokio::time::interval;
 use tracing::{info, warn};
 
-use crate::ai_security::{AnomalyDetector, PeerScoring, SybilDetector, TRUST_HEALTHY_THRESHOLD, TRUST_SUSPICIOUS_THRESHOLD};
+use crate::ai_security::{
+    AddressRates, AnomalyDetector, PeerScoring, SybilConfig, SybilDetector, MESSAGE_RATE_FLOOD_THRESHOLD,
+    TRUST_HEALTHY_THRESHOLD, TRUST_SUSPICIOUS_THRESHOLD,
+};
 use crate::error::{P2PError, P2PResult};
-use crate::kademlia_dht::KademliaDht;
+use crate::kademlia_dht::{DhtContact, KademliaDht};
+use crate::relay_guard::RelayPenalty;
+use crate::service_quota::{PeerKey, QuotaPenalty};
 use crate::quantum_crypto::sphincs_verify;
 use crate::types::{NodeId, PeerInfo, PeerStatus, unix_now};
 
@@ -49,6 +58,16 @@ pub struct PeerManagerConfig {
     pub min_trust_score: f64,
     /// Age in seconds after which a peer not seen is evicted.
     pub peer_eviction_age_secs: u64,
+    /// How long a ban lasts before the peer may be admitted again.
+    pub ban_cooldown_secs: u64,
+    /// Messages per minute above which a peer is flooding.
+    pub flood_threshold: u64,
+    /// Factor applied to every peer's penalty points each sweep.
+    pub penalty_decay: f64,
+    /// Subnet and join-burst limits for Sybil detection.
+    pub sybil: SybilConfig,
+    /// Age in seconds after which a stored peer is dropped on `load`.
+    pub peer_store_max_age_secs: u64,
 }
 
 impl Default for PeerManagerConfig {
@@ -58,10 +77,28 @@ impl Default for PeerManagerConfig {
             maintenance_interval: Duration::from_secs(30),
             min_trust_score: 20.0,
             peer_eviction_age_secs: 3600,
+            ban_cooldown_secs: 3600,
+            flood_threshold: MESSAGE_RATE_FLOOD_THRESHOLD,
+            penalty_decay: 0.9,
+            sybil: SybilConfig::default(),
+            peer_store_max_age_secs: 7 * 24 * 3600,
         }
     }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// PEER STORE
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A peer as written to the peer store by `PeerManager::save`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredPeer {
+    pub id: NodeId,
+    pub addr: SocketAddr,
+    pub last_seen: u64,
+    pub trust_score: f64,
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // PEER MANAGER
 // ─────────────────────────────────────────────────────────────────────────────
@@ -70,10 +107,17 @@ pub struct PeerManager {
     config: PeerManagerConfig,
     /// The live peer table — NodeId → PeerInfo.
     peers: DashMap<NodeId, PeerInfo>,
-    /// Explicitly banned peers (persist across restarts conceptually).
+    /// Banned peers → ban time; a ban lapses after `ban_cooldown_secs`.
     banned: DashMap<NodeId, u64>,
+    /// Addresses remembered from the peer store or a `Hello`, kept so the
+    /// next `save` still has them while the peer is not admitted.
+    known: DashMap<NodeId, StoredPeer>,
+    /// Long-term Kyber public keys relays publish for onion routing.
+    kyber_keys: DashMap<NodeId, Vec<u8>>,
     dht: Arc<KademliaDht>,
     scoring: Arc<PeerScoring>,
+    /// Message rates of senders not yet authenticated, by source IP.
+    addresses: AddressRates,
     sybil: Arc<SybilDetector>,
     anomaly: Arc<AnomalyDetector>,
     event_tx: broadcast::Sender<PeerEvent>,
@@ -84,13 +128,19 @@ impl PeerManager {
     pub fn new(local_id: NodeId, config: PeerManagerConfig) -> (Arc<Self>, broadcast::Receiver<PeerEvent>) {
         let (tx, rx) = broadcast::channel(1024);
         let dht = Arc::new(KademliaDht::new(local_id));
+        let scoring = PeerScoring::new().with_flood_threshold(config.flood_threshold);
+        let addresses = AddressRates::new(config.flood_threshold);
+        let sybil = SybilDetector::with_config(config.sybil.clone());
         let pm = Arc::new(PeerManager {
             config,
             peers: DashMap::new(),
             banned: DashMap::new(),
+            known: DashMap::new(),
+            kyber_keys: DashMap::new(),
             dht,
-            scoring: Arc::new(PeerScoring::new()),
-            sybil: Arc::new(SybilDetector::new()),
+            addresses,
+            scoring: Arc::new(scoring),
+            sybil: Arc::new(sybil),
             anomaly: Arc::new(AnomalyDetector::new()),
             event_tx: tx,
         });
@@ -117,7 +167,7 @@ impl PeerManager {
         identity_proof_signature: &[u8],
     ) -> P2PResult<()> {
         // 1. Banned check
-        if self.banned.contains_key(&id) {
+        if self.is_banned(&id) {
             return Err(P2PError::PeerBanned { peer_id: id.to_string() });
         }
 
@@ -133,8 +183,11 @@ impl PeerManager {
         }
 
         // 4. SPHINCS+ identity proof
-        sphincs_verify(identity_proof_challenge, identity_proof_signature, &sphincs_pubkey)
-            .map_err(|_| P2PError::QuantumIdentityFailed { peer_id: id.to_string() })?;
+        if sphincs_verify(identity_proof_challenge, identity_proof_signature, &sphincs_pubkey).is_err() {
+            self.sybil.deregister(&id, &addr);
+            self.scoring.record_handshake_failure(&id);
+            return Err(P2PError::QuantumIdentityFailed { peer_id: id.to_string() });
+        }
 
         // 5. Build PeerInfo and score
         let mut peer = PeerInfo::new(id.clone(), addr, ed25519_pubkey, sphincs_pubkey);
@@ -161,6 +214,7 @@ impl PeerManager {
     // ── REMOVAL / BANNING ─────────────────────────────────────────────────────
 
     pub async fn remove_peer(&self, id: &NodeId) {
+        self.kyber_keys.remove(id);
         if let Some((_, peer)) = self.peers.remove(id) {
             self.sybil.deregister(id, &peer.addr);
             self.dht.remove_peer(id).await;
@@ -177,7 +231,11 @@ impl PeerManager {
         warn!(peer_id = %id, "Peer banned");
     }
 
+    /// Whether `id` is banned; a ban whose cooldown has passed is lifted here.
     pub fn is_banned(&self, id: &NodeId) -> bool {
+        let now = unix_now();
+        let cooldown = self.config.ban_cooldown_secs;
+        self.banned.remove_if(id, |_, since| now.saturating_sub(*since) >= cooldown);
         self.banned.contains_key(id)
     }
 
@@ -199,14 +257,86 @@ impl PeerManager {
         }
     }
 
-    pub fn record_message(&self, id: &NodeId) {
+    /// Count an inbound message; `true` once the peer is flooding, which the
+    /// caller answers with `ban_peer`.
+    pub fn record_message(&self, id: &NodeId) -> bool {
         self.scoring.record_message(id);
+        self.scoring.is_flooding(id)
+    }
+
+    /// Count an inbound message by its source address, before its sender
+    /// is authenticated; `true` once the address is flooding, which the
+    /// caller answers by dropping the connection.
+    pub fn record_unauthenticated(&self, ip: IpAddr) -> bool {
+        self.addresses.record_message(ip)
+    }
+
+    /// A message from an authenticated sender that failed validation
+    /// (invalid block, undecryptable or anomalous payload); weighs more than
+    /// an ordinary failure.
+    pub fn record_invalid_message(&self, id: &NodeId) {
+        self.scoring.record_invalid_message(id);
+        if let Some(mut peer) = self.peers.get_mut(id) {
+            peer.record_failure();
+            peer.trust_score = self.scoring.calculate_score(id);
+        }
     }
 
     pub fn record_latency(&self, id: &NodeId, latency_ms: u32) {
         self.scoring.record_latency(id, latency_ms);
     }
 
+    /// Mean round-trip time over connected peers with latency samples.
+    pub fn average_rtt_ms(&self) -> Option<u64> {
+        let rtts: Vec<f64> = self.peers.iter()
+            .filter_map(|e| self.scoring.mean_latency(e.key()))
+            .collect();
+        if rtts.is_empty() {
+            return None;
+        }
+        Some((rtts.iter().sum::<f64>() / rtts.len() as f64).round() as u64)
+    }
+
+    /// Apply relay-screening outcomes from `GossipRouter::take_relay_penalties`:
+    /// each failed payload counts as a failed interaction, a ban is a ban.
+    pub async fn apply_relay_penalties(&self, penalties: Vec<RelayPenalty>) {
+        for penalty in penalties {
+            match penalty {
+                RelayPenalty::Failure(id) => self.record_failure(&id),
+                RelayPenalty::Ban(id) => {
+                    if !self.is_banned(&id) {
+                        warn!(peer_id = %id, "Banning peer for relaying invalid payloads");
+                        self.ban_peer(&id).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply `ServiceQuotas::take_penalties`: each counts as a failed
+    /// interaction for the peer, or for every peer at an offending IP.
+    pub fn apply_quota_penalties(&self, penalties: Vec<QuotaPenalty>) {
+        for QuotaPenalty(key) in penalties {
+            match key {
+                PeerKey::Node(id) => self.record_failure(&id),
+                PeerKey::Addr(ip) => {
+                    let ids: Vec<NodeId> = self.peers.iter()
+                        .filter(|p| p.addr.ip() == ip)
+                        .map(|p| p.id.clone())
+                        .collect();
+                    for id in ids {
+                        self.record_failure(&id);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Peers `SybilDetector::evaluate` currently suspects.
+    pub fn sybil_suspects(&self) -> Vec<NodeId> {
+        self.sybil.evaluate(self)
+    }
+
     pub fn check_message_anomaly(&self, _id: &NodeId, payload: &[u8], hop_count: u8) -> Option<String> {
         self.anomaly.check_message(payload, hop_count)
     }
@@ -239,6 +369,15 @@ impl PeerManager {
             .collect()
     }
 
+    /// Record the Kyber public key `id` accepts onion layers under.
+    pub fn register_kyber_key(&self, id: &NodeId, public_key: Vec<u8>) {
+        self.kyber_keys.insert(id.clone(), public_key);
+    }
+
+    pub fn kyber_key(&self, id: &NodeId) -> Option<Vec<u8>> {
+        self.kyber_keys.get(id).map(|k| k.clone())
+    }
+
     pub fn peer_count(&self) -> usize {
         self.peers.len()
     }
@@ -252,15 +391,92 @@ impl PeerManager {
         self.dht.find_closest_peers(target, k).await
     }
 
+    /// Look `target` up in the DHT and return the nodes it turned up that
+    /// are neither peers yet nor banned, for the caller to dial.
+    pub async fn discover(&self, target: &NodeId) -> Vec<DhtContact> {
+        self.dht.find_node(target).await
+            .into_iter()
+            .filter(|c| !self.peers.contains_key(&c.id) && !self.is_banned(&c.id))
+            .collect()
+    }
+
     pub fn dht(&self) -> Arc<KademliaDht> {
         self.dht.clone()
     }
 
+    // ── PERSISTENCE ───────────────────────────────────────────────────────────
+
+    /// Remember that `id` answered at `addr` just now.
+    pub fn remember(&self, id: NodeId, addr: SocketAddr) {
+        let trust_score = self.known.get(&id).map_or(50.0, |p| p.trust_score);
+        self.known.insert(id.clone(), StoredPeer { id, addr, last_seen: unix_now(), trust_score });
+    }
+
+    /// Remembered peers and admitted ones, best trust score first.  An
+    /// admitted peer's live entry wins over a remembered one.
+    pub fn known_peers(&self) -> Vec<StoredPeer> {
+        let mut out: Vec<StoredPeer> = self.known.iter()
+            .filter(|e| !self.peers.contains_key(e.key()))
+            .map(|e| e.value().clone())
+            .chain(self.peers.iter().map(|p| StoredPeer {
+                id: p.id.clone(),
+                addr: p.addr,
+                last_seen: p.last_seen,
+                trust_score: p.trust_score,
+            }))
+            .filter(|p| !self.banned.contains_key(&p.id))
+            .collect();
+        out.sort_by(|a, b| b.trust_score.total_cmp(&a.trust_score).then(b.last_seen.cmp(&a.last_seen)));
+        out
+    }
+
+    /// Write `known_peers` to `path` as JSON, replacing the file atomically.
+    pub fn save(&self, path: &Path) -> P2PResult<()> {
+        let json = serde_json::to_vec_pretty(&self.known_peers())
+            .map_err(|e| P2PError::Serialization(e.to_string()))?;
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Read the peer store at `path`, dropping peers not seen within
+    /// `peer_store_max_age_secs`, and remember the rest.  Returns them best
+    /// first for the caller to dial; a missing file is an empty store.
+    pub fn load(&self, path: &Path) -> P2PResult<Vec<StoredPeer>> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let stored: Vec<StoredPeer> = serde_json::from_slice(&bytes)
+            .map_err(|e| P2PError::Serialization(format!("{}: {}", path.display(), e)))?;
+        let now = unix_now();
+        let total = stored.len();
+        let mut fresh: Vec<StoredPeer> = stored.into_iter()
+            .filter(|p| now.saturating_sub(p.last_seen) <= self.config.peer_store_max_age_secs)
+            .filter(|p| !self.is_banned(&p.id))
+            .collect();
+        fresh.sort_by(|a, b| b.trust_score.total_cmp(&a.trust_score).then(b.last_seen.cmp(&a.last_seen)));
+        for peer in &fresh {
+            self.known.insert(peer.id.clone(), peer.clone());
+        }
+        info!(path = %path.display(), loaded = fresh.len(), pruned = total - fresh.len(), "Peer store loaded");
+        Ok(fresh)
+    }
+
     // ── MAINTENANCE ───────────────────────────────────────────────────────────
 
     /// Prune banned/malicious/stale peers and re-score suspicious peers.
     pub async fn maintenance_sweep(&self) {
         let now = unix_now();
+        self.scoring.decay(self.config.penalty_decay);
+        self.addresses.prune();
+        let cooldown = self.config.ban_cooldown_secs;
+        self.banned.retain(|_, since| now.saturating_sub(*since) < cooldown);
         let mut to_ban: Vec<NodeId> = Vec::new();
         let mut to_remove: Vec<NodeId> = Vec::new();
 
@@ -291,6 +507,7 @@ impl PeerManager {
                 if p.status != old_status {
                     let _ = self.event_tx.send(PeerEvent::StatusChanged(id.clone(), p.status.clone()));
                 }
+                self.dht.set_trust(&id, score, p.status.clone());
                 if score < self.config.min_trust_score {
                     to_ban.push(id);
                 }
@@ -461,6 +678,95 @@ mod tests {
         assert_eq!(pm.peer_count(), 0);
     }
 
+    #[tokio::test]
+    async fn test_relay_penalties_hit_only_the_offender() {
+        let (pm, _rx) = make_test_pm();
+        let honest = add_test_peer(&pm, 40).await;
+        let spammer = add_test_peer(&pm, 41).await;
+        pm.record_success(&honest);
+        pm.record_success(&spammer);
+        let honest_before = pm.get_peer(&honest).unwrap().trust_score;
+        let spammer_before = pm.get_peer(&spammer).unwrap().trust_score;
+
+        pm.apply_relay_penalties(vec![RelayPenalty::Failure(spammer.clone()); 5]).await;
+        assert!(pm.get_peer(&spammer).unwrap().trust_score < spammer_before);
+        assert!(pm.get_peer(&honest).unwrap().trust_score >= honest_before);
+
+        pm.apply_relay_penalties(vec![RelayPenalty::Ban(spammer.clone())]).await;
+        assert!(pm.is_banned(&spammer));
+        assert!(!pm.is_banned(&honest));
+    }
+
+    #[tokio::test]
+    async fn test_quota_violations_lower_reputation() {
+        use crate::service_quota::{ClassQuota, ServiceClass, ServiceQuotaConfig, ServiceQuotas};
+
+        let (pm, _rx) = make_test_pm();
+        let polite = add_test_peer(&pm, 42).await;
+        let greedy = add_test_peer(&pm, 43).await;
+        pm.record_success(&polite);
+        pm.record_success(&greedy);
+        let polite_before = pm.get_peer(&polite).unwrap().trust_score;
+        let greedy_before = pm.get_peer(&greedy).unwrap().trust_score;
+
+        let quotas = ServiceQuotas::new(ServiceQuotaConfig {
+            sync: ClassQuota { requests_per_sec: 1, bytes_per_sec: 1024 },
+            violations_per_penalty: 3,
+            ..Default::default()
+        });
+        // Snap requests are charged to the IP, DHT requests to the node id.
+        let greedy_ip = PeerKey::Addr("10.0.0.43".parse().unwrap());
+        for _ in 0..10 {
+            let _ = quotas.admit(&greedy_ip, ServiceClass::Sync, 0);
+        }
+        assert!(quotas.admit(&PeerKey::Node(polite.clone()), ServiceClass::Sync, 0).is_ok());
+        let penalties = quotas.take_penalties();
+        assert_eq!(penalties.len(), 3);
+
+        pm.apply_quota_penalties(penalties);
+        assert!(pm.get_peer(&greedy).unwrap().trust_score < greedy_before);
+        assert_eq!(pm.get_peer(&greedy).unwrap().failure_count, 3);
+        assert!(pm.get_peer(&polite).unwrap().trust_score >= polite_before);
+    }
+
+    #[tokio::test]
+    async fn test_flooding_peer_banned_for_cooldown() {
+        let (pm, _rx) = PeerManager::new(
+            NodeId::random(),
+            PeerManagerConfig { flood_threshold: 30, ban_cooldown_secs: 600, ..Default::default() },
+        );
+        let flooder = add_test_peer(&pm, 44).await;
+        let normal = add_test_peer(&pm, 45).await;
+
+        let mut flagged_at = None;
+        for i in 1..=100 {
+            if pm.record_message(&flooder) {
+                pm.ban_peer(&flooder).await;
+                flagged_at = Some(i);
+                break;
+            }
+        }
+        assert_eq!(flagged_at, Some(31));
+        assert!((0..20).all(|_| !pm.record_message(&normal)));
+        assert!(pm.is_banned(&flooder) && pm.get_peer(&flooder).is_none());
+        assert!(!pm.is_banned(&normal) && pm.get_peer(&normal).is_some());
+
+        // Once the cooldown has passed the ban lapses
+        pm.banned.insert(flooder.clone(), unix_now() - 601);
+        assert!(!pm.is_banned(&flooder));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_messages_lead_to_ban_on_sweep() {
+        let (pm, _rx) = make_test_pm();
+        let id = add_test_peer(&pm, 46).await;
+        for _ in 0..6 {
+            pm.record_invalid_message(&id);
+        }
+        pm.maintenance_sweep().await;
+        assert!(pm.is_banned(&id));
+    }
+
     #[tokio::test]
     async fn test_event_broadcast_on_add() {
         let (pm, mut rx) = make_test_pm();
@@ -468,4 +774,39 @@ mod tests {
         let event = rx.try_recv().unwrap();
         assert!(matches!(event, PeerEvent::Added(_)));
     }
+
+    #[tokio::test]
+    async fn test_peer_store_round_trip_prunes_stale_and_banned() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("peers.json");
+        let (pm, _rx) = make_test_pm();
+        let admitted = add_test_peer(&pm, 50).await;
+        let remembered = NodeId::random();
+        pm.remember(remembered.clone(), "10.0.1.1:9000".parse().unwrap());
+        let stale = NodeId::random();
+        pm.remember(stale.clone(), "10.0.1.2:9000".parse().unwrap());
+        pm.known.get_mut(&stale).unwrap().last_seen = 1;
+        pm.save(&path).unwrap();
+
+        let (restarted, _rx) = make_test_pm();
+        assert!(restarted.load(&dir.path().join("missing.json")).unwrap().is_empty());
+        let loaded = restarted.load(&path).unwrap();
+        let ids: Vec<NodeId> = loaded.iter().map(|p| p.id.clone()).collect();
+        assert_eq!(loaded.len(), 2);
+        assert!(ids.contains(&admitted) && ids.contains(&remembered));
+        let entry = loaded.iter().find(|p| p.id == admitted).unwrap();
+        assert_eq!(entry.addr, "10.0.0.50:9000".parse().unwrap());
+        assert_eq!(entry.trust_score, pm.get_peer(&admitted).unwrap().trust_score);
+        assert_eq!(restarted.known_peers().len(), 2);
+
+        // A banned peer is left out of the store
+        pm.remember(admitted.clone(), "10.0.0.50:9000".parse().unwrap());
+        pm.ban_peer(&admitted).await;
+        pm.save(&path).unwrap();
+        let (again, _rx) = make_test_pm();
+        assert_eq!(again.load(&path).unwrap().len(), 1);
+
+        std::fs::write(&path, b"not json").unwrap();
+        assert!(matches!(again.load(&path), Err(P2PError::Serialization(_))));
+    }
 }
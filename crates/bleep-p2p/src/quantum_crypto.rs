@@ -155,6 +155,11 @@ impl Ed25519Keypair {
     pub fn public_key_bytes(&self) -> Vec<u8> {
         self.verifying_key.to_bytes().to_vec()
     }
+
+    /// Secret key bytes, for persisting the node identity.
+    pub fn secret_bytes(&self) -> [u8; 32] {
+        self.signing_key.to_bytes()
+    }
 }
 
 /// Verify an Ed25519 signature.
@@ -0,0 +1,350 @@
+//! QUIC transport (feature `quic`).
+//!
+//! One `quinn` endpoint per node serves and dials on the same UDP port.
+//! Each peer gets a single connection, reused until it closes, and each
+//! frame its own bidirectional stream with the `Lane` priority, so block
+//! sync, gossip and consensus never queue behind one another.
+//!
+//! TLS 1.3 runs on a self-signed certificate whose key is the node's
+//! Ed25519 identity key.  The dialer derives the peer's `NodeId` from the
+//! certificate, checks the handshake signature against it, and, when the
+//! address is pinned with `QuicTransport::pin`, rejects any other id.
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use quinn::crypto::rustls::{QuicClientConfig, QuicServerConfig};
+use quinn::{Connection, Endpoint, RecvStream, SendStream};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider, WebPkiSupportedAlgorithms};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime};
+use rustls::{CertificateError, DigitallySignedStruct, SignatureScheme};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+use tracing::debug;
+
+use crate::error::{P2PError, P2PResult};
+use crate::quantum_crypto::NodeIdentity;
+use crate::transport::{BoxStream, Lane, Transport, TransportKind, CONNECT_TIMEOUT};
+use crate::types::NodeId;
+
+/// ALPN protocol id; a peer speaking anything else fails the handshake.
+const ALPN: &[u8] = b"bleep/1";
+/// SNI sent when dialing; identity comes from the certificate key instead.
+const SERVER_NAME: &str = "bleep";
+/// DER header of an Ed25519 SubjectPublicKeyInfo, followed by the 32-byte key.
+const ED25519_SPKI_PREFIX: [u8; 12] = [0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00];
+/// Inbound streams waiting for `accept`.
+const ACCEPT_BACKLOG: usize = 1024;
+
+fn crypto_err(e: impl fmt::Display) -> P2PError {
+    P2PError::Crypto(e.to_string())
+}
+
+fn transport_err(e: impl fmt::Display) -> P2PError {
+    P2PError::Transport(e.to_string())
+}
+
+/// PKCS#8 v2 encoding of an Ed25519 key, as `ring` writes it.
+fn ed25519_pkcs8(secret: &[u8; 32], public: &[u8]) -> Vec<u8> {
+    let mut der = vec![
+        0x30, 0x51, 0x02, 0x01, 0x01, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70,
+        0x04, 0x22, 0x04, 0x20,
+    ];
+    der.extend_from_slice(secret);
+    der.extend_from_slice(&[0xa1, 0x23, 0x03, 0x21, 0x00]);
+    der.extend_from_slice(public);
+    der
+}
+
+/// Self-signed certificate over the node's identity key.
+fn identity_cert(identity: &NodeIdentity) -> P2PResult<(CertificateDer<'static>, PrivateKeyDer<'static>)> {
+    let pkcs8 = ed25519_pkcs8(&identity.ed_keypair.secret_bytes(), &identity.ed_keypair.public_key_bytes());
+    let key_pair = rcgen::KeyPair::try_from(pkcs8.as_slice()).map_err(crypto_err)?;
+    let cert = rcgen::CertificateParams::new(vec![SERVER_NAME.to_string()])
+        .map_err(crypto_err)?
+        .self_signed(&key_pair)
+        .map_err(crypto_err)?;
+    Ok((cert.der().clone(), PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(pkcs8))))
+}
+
+/// Node id of the Ed25519 key a certificate carries.
+pub fn cert_node_id(cert: &[u8]) -> Option<NodeId> {
+    let at = cert.windows(ED25519_SPKI_PREFIX.len()).position(|w| w == ED25519_SPKI_PREFIX)?;
+    let key = cert.get(at + ED25519_SPKI_PREFIX.len()..at + ED25519_SPKI_PREFIX.len() + 32)?;
+    Some(NodeId::from_bytes(key))
+}
+
+/// Accepts a certificate for its key rather than a CA chain: the key must
+/// sign the handshake and, if one is expected, hash to the expected id.
+#[derive(Debug)]
+struct IdentityVerifier {
+    expected:   Option<NodeId>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for IdentityVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let id = cert_node_id(end_entity)
+            .ok_or(rustls::Error::InvalidCertificate(CertificateError::BadEncoding))?;
+        match &self.expected {
+            Some(expected) if *expected != id => {
+                Err(rustls::Error::InvalidCertificate(CertificateError::ApplicationVerificationFailure))
+            }
+            _ => Ok(ServerCertVerified::assertion()),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// The two halves of a QUIC bidirectional stream as one `FrameStream`.
+struct QuicStream {
+    send: SendStream,
+    recv: RecvStream,
+}
+
+impl AsyncRead for QuicStream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        AsyncRead::poll_read(Pin::new(&mut self.get_mut().recv), cx, buf)
+    }
+}
+
+impl AsyncWrite for QuicStream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(Pin::new(&mut self.get_mut().send), cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(Pin::new(&mut self.get_mut().send), cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(Pin::new(&mut self.get_mut().send), cx)
+    }
+}
+
+pub struct QuicTransport {
+    endpoint:        Endpoint,
+    provider:        Arc<CryptoProvider>,
+    connect_timeout: Duration,
+    /// Open connections by peer address.
+    connections:     DashMap<SocketAddr, Connection>,
+    /// Node ids dialed addresses must present.
+    pins:            DashMap<SocketAddr, NodeId>,
+    incoming:        Mutex<mpsc::Receiver<(BoxStream, SocketAddr)>>,
+    accept_task:     JoinHandle<()>,
+}
+
+impl QuicTransport {
+    /// Bind a QUIC endpoint on the UDP port `addr` and start accepting.
+    /// Must be called inside a Tokio runtime.
+    pub fn bind(addr: SocketAddr, identity: &NodeIdentity) -> P2PResult<Self> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let (cert, key) = identity_cert(identity)?;
+        let mut server_crypto = rustls::ServerConfig::builder_with_provider(provider.clone())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(crypto_err)?
+            .with_no_client_auth()
+            .with_single_cert(vec![cert], key)
+            .map_err(crypto_err)?;
+        server_crypto.alpn_protocols = vec![ALPN.to_vec()];
+        let server_config = quinn::ServerConfig::with_crypto(Arc::new(
+            QuicServerConfig::try_from(server_crypto).map_err(crypto_err)?,
+        ));
+        let endpoint = Endpoint::server(server_config, addr).map_err(P2PError::Io)?;
+
+        let (tx, rx) = mpsc::channel(ACCEPT_BACKLOG);
+        let accepting = endpoint.clone();
+        let accept_task = tokio::spawn(async move {
+            while let Some(incoming) = accepting.accept().await {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    let conn = match incoming.await {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            debug!(error = %e, "QUIC handshake failed");
+                            return;
+                        }
+                    };
+                    let remote = conn.remote_address();
+                    while let Ok((send, recv)) = conn.accept_bi().await {
+                        let stream: BoxStream = Box::new(QuicStream { send, recv });
+                        if tx.send((stream, remote)).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(QuicTransport {
+            endpoint,
+            provider,
+            connect_timeout: CONNECT_TIMEOUT,
+            connections: DashMap::new(),
+            pins: DashMap::new(),
+            incoming: Mutex::new(rx),
+            accept_task,
+        })
+    }
+
+    /// Give up on a dial after `connect_timeout` instead of `CONNECT_TIMEOUT`.
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Only accept `id` from `addr` on the next connection there.
+    pub fn pin(&self, addr: SocketAddr, id: NodeId) {
+        self.pins.insert(addr, id);
+        self.connections.remove(&addr);
+    }
+
+    /// Node id of the peer connected at `addr`, from its certificate.
+    pub fn peer_id(&self, addr: &SocketAddr) -> Option<NodeId> {
+        let conn = self.connections.get(addr)?.clone();
+        let certs = conn.peer_identity()?.downcast::<Vec<CertificateDer<'static>>>().ok()?;
+        cert_node_id(certs.first()?)
+    }
+
+    async fn connection(&self, addr: SocketAddr) -> P2PResult<Connection> {
+        if let Some(conn) = self.connections.get(&addr).map(|c| c.clone()) {
+            if conn.close_reason().is_none() {
+                return Ok(conn);
+            }
+            self.connections.remove(&addr);
+        }
+        let verifier = IdentityVerifier {
+            expected:   self.pins.get(&addr).map(|id| id.clone()),
+            algorithms: self.provider.signature_verification_algorithms,
+        };
+        let mut client_crypto = rustls::ClientConfig::builder_with_provider(self.provider.clone())
+            .with_protocol_versions(&[&rustls::version::TLS13])
+            .map_err(crypto_err)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_no_client_auth();
+        client_crypto.alpn_protocols = vec![ALPN.to_vec()];
+        let config = quinn::ClientConfig::new(Arc::new(
+            QuicClientConfig::try_from(client_crypto).map_err(crypto_err)?,
+        ));
+        let connecting = self.endpoint.connect_with(config, addr, SERVER_NAME).map_err(transport_err)?;
+        let conn = timeout(self.connect_timeout, connecting)
+            .await
+            .map_err(|_| P2PError::ConnectionTimeout { addr: addr.to_string() })?
+            .map_err(transport_err)?;
+        self.connections.insert(addr, conn.clone());
+        Ok(conn)
+    }
+}
+
+impl Drop for QuicTransport {
+    fn drop(&mut self) {
+        self.accept_task.abort();
+        self.endpoint.close(0u32.into(), b"shutdown");
+    }
+}
+
+#[async_trait]
+impl Transport for QuicTransport {
+    fn kind(&self) -> TransportKind {
+        TransportKind::Quic
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.endpoint.local_addr().ok()
+    }
+
+    async fn open(&self, addr: SocketAddr, lane: Lane) -> P2PResult<BoxStream> {
+        let conn = self.connection(addr).await?;
+        let (send, recv) = match conn.open_bi().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                self.connections.remove(&addr);
+                return Err(transport_err(e));
+            }
+        };
+        let _ = send.set_priority(lane.priority());
+        Ok(Box::new(QuicStream { send, recv }))
+    }
+
+    async fn accept(&self) -> Option<(BoxStream, SocketAddr)> {
+        self.incoming.lock().await.recv().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn localhost() -> SocketAddr {
+        "127.0.0.1:0".parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_streams_carry_bytes_and_certs_pin_the_identity() {
+        let server_identity = NodeIdentity::generate();
+        let server = QuicTransport::bind(localhost(), &server_identity).unwrap();
+        let client = QuicTransport::bind(localhost(), &NodeIdentity::generate()).unwrap();
+        let addr = server.local_addr().unwrap();
+
+        let mut out = client.open(addr, Lane::Consensus).await.unwrap();
+        out.write_all(b"vote").await.unwrap();
+        out.flush().await.unwrap();
+        let (mut inbound, from) = server.accept().await.unwrap();
+        assert_eq!(from, client.local_addr().unwrap());
+        let mut buf = [0u8; 4];
+        inbound.read_exact(&mut buf).await.unwrap();
+        assert_eq!(&buf, b"vote");
+        inbound.write_all(b"ack").await.unwrap();
+        inbound.flush().await.unwrap();
+        let mut ack = [0u8; 3];
+        out.read_exact(&mut ack).await.unwrap();
+        assert_eq!(&ack, b"ack");
+        assert_eq!(client.peer_id(&addr), Some(server_identity.node_id()));
+
+        // A pin on the right id connects; on any other id the handshake fails
+        client.pin(addr, server_identity.node_id());
+        assert!(client.open(addr, Lane::Gossip).await.is_ok());
+        client.pin(addr, NodeId::random());
+        assert!(client.open(addr, Lane::Gossip).await.is_err());
+    }
+}
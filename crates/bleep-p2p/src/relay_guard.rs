@@ -0,0 +1,326 @@
+//! Relay-time screening of gossiped payloads.
+//!
+//! Full validation happens where a payload is consumed; without a cheaper
+//! gate in front of the relay, one peer can make every node on the path
+//! deserialize and forward garbage.  A `RelayGuard` sits in front of
+//! `GossipRouter::forward`:
+//!
+//! - **Screening** — every first receipt is passed to a `RelayValidator`
+//!   (stateless, topic-specific, much cheaper than full validation).  A
+//!   rejected payload is neither delivered nor re-forwarded, so it dies at
+//!   the first hop.
+//! - **Standing** — per-peer accept/reject counts decide whether a peer is
+//!   `Trusted`, `Demoted` (lazy IHAVE only, no eager pushes to or from its
+//!   links) or `Banned` (all of its frames are ignored).
+//! - **Reputation** — each rejection and ban is queued as a `RelayPenalty`
+//!   for `PeerManager::apply_relay_penalties`, which feeds the AI trust
+//!   score and the ban list.
+//!
+//! The concrete transaction and block checks live with the types they
+//! decode (`bleep_core::relay_checks`); this module only knows topics and
+//! bytes.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use bleep_telemetry::metrics::{MetricCounter, MetricGauge, MetricsRegistry};
+use thiserror::Error;
+
+use crate::gossip_router::Topic;
+use crate::types::NodeId;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// VALIDATOR
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Why a payload was refused at relay time.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RelayReject {
+    #[error("malformed payload: {0}")]
+    Malformed(String),
+    #[error("payload of {size} bytes exceeds relay cap of {max}")]
+    Oversize { size: usize, max: usize },
+    #[error("signature missing or wrong length")]
+    MissingSignature,
+    #[error("below relay fee floor")]
+    BelowFeeFloor,
+    #[error("signer is not in the current validator set")]
+    UnknownSigner,
+    #[error("header signature does not verify")]
+    BadSignature,
+}
+
+impl RelayReject {
+    /// Telemetry label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            RelayReject::Malformed(_)      => "malformed",
+            RelayReject::Oversize { .. }   => "oversize",
+            RelayReject::MissingSignature  => "missing_signature",
+            RelayReject::BelowFeeFloor     => "fee_floor",
+            RelayReject::UnknownSigner     => "unknown_signer",
+            RelayReject::BadSignature      => "bad_signature",
+        }
+    }
+}
+
+/// Stateless check run on every payload before it is relayed.
+///
+/// Implementations must be cheap relative to full validation: no state
+/// lookups, no per-transaction signature verification.
+pub trait RelayValidator: Send + Sync {
+    fn check(&self, topic: &Topic, payload: &[u8]) -> Result<(), RelayReject>;
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// POLICY & STANDING
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Thresholds turning rejection counts into standing.
+#[derive(Debug, Clone)]
+pub struct RelayPolicy {
+    /// Payloads seen from a peer before ratios are acted on.
+    pub min_samples:  u64,
+    /// Rejection ratio at which a peer loses eager-push links.
+    pub demote_ratio: f64,
+    /// Rejection ratio at which a peer is banned.
+    pub ban_ratio:    f64,
+}
+
+impl Default for RelayPolicy {
+    fn default() -> Self {
+        RelayPolicy { min_samples: 8, demote_ratio: 0.2, ban_ratio: 0.5 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelayStanding {
+    Trusted,
+    Demoted,
+    Banned,
+}
+
+/// Per-peer relay outcome counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerRelayStats {
+    pub accepted: u64,
+    pub rejected: u64,
+}
+
+impl PeerRelayStats {
+    pub fn total(&self) -> u64 {
+        self.accepted + self.rejected
+    }
+
+    pub fn rejection_ratio(&self) -> f64 {
+        if self.total() == 0 {
+            return 0.0;
+        }
+        self.rejected as f64 / self.total() as f64
+    }
+}
+
+/// Reputation effect queued for the peer manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RelayPenalty {
+    /// The peer forwarded one payload that failed screening.
+    Failure(NodeId),
+    /// The peer crossed `ban_ratio`.
+    Ban(NodeId),
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// GUARD
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Screens payloads and tracks the standing of the peers relaying them.
+pub struct RelayGuard {
+    validator: Arc<dyn RelayValidator>,
+    policy:    RelayPolicy,
+    peers:     HashMap<NodeId, PeerRelayStats>,
+    banned:    HashSet<NodeId>,
+    rejects:   HashMap<&'static str, u64>,
+    penalties: Vec<RelayPenalty>,
+}
+
+impl RelayGuard {
+    pub fn new(validator: Arc<dyn RelayValidator>, policy: RelayPolicy) -> Self {
+        RelayGuard {
+            validator,
+            policy,
+            peers:     HashMap::new(),
+            banned:    HashSet::new(),
+            rejects:   HashMap::new(),
+            penalties: Vec::new(),
+        }
+    }
+
+    /// Check a payload received from `from` and update its standing.
+    pub fn screen(&mut self, from: &NodeId, topic: &Topic, payload: &[u8]) -> Result<(), RelayReject> {
+        let verdict = self.validator.check(topic, payload);
+        let stats = self.peers.entry(from.clone()).or_default();
+        match &verdict {
+            Ok(()) => stats.accepted += 1,
+            Err(reason) => {
+                stats.rejected += 1;
+                *self.rejects.entry(reason.label()).or_default() += 1;
+                self.penalties.push(RelayPenalty::Failure(from.clone()));
+            }
+        }
+        let stats = *stats;
+        if verdict.is_err()
+            && stats.total() >= self.policy.min_samples
+            && stats.rejection_ratio() >= self.policy.ban_ratio
+            && self.banned.insert(from.clone())
+        {
+            self.penalties.push(RelayPenalty::Ban(from.clone()));
+        }
+        verdict
+    }
+
+    pub fn standing(&self, peer: &NodeId) -> RelayStanding {
+        if self.banned.contains(peer) {
+            return RelayStanding::Banned;
+        }
+        match self.peers.get(peer) {
+            Some(s) if s.total() >= self.policy.min_samples
+                && s.rejection_ratio() >= self.policy.demote_ratio => RelayStanding::Demoted,
+            _ => RelayStanding::Trusted,
+        }
+    }
+
+    pub fn stats(&self, peer: &NodeId) -> PeerRelayStats {
+        self.peers.get(peer).copied().unwrap_or_default()
+    }
+
+    pub fn peer_stats(&self) -> impl Iterator<Item = (&NodeId, &PeerRelayStats)> {
+        self.peers.iter()
+    }
+
+    /// Rejections by reason label.
+    pub fn rejects(&self) -> &HashMap<&'static str, u64> {
+        &self.rejects
+    }
+
+    /// Drain penalties queued since the last call.
+    pub fn take_penalties(&mut self) -> Vec<RelayPenalty> {
+        std::mem::take(&mut self.penalties)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TELEMETRY
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Telemetry handles for a `RelayGuard`.
+///
+/// Per-peer rejection ratios are gauges named
+/// `bleep_relay_rejection_ratio_permille_<peer id prefix>`, registered the
+/// first time a peer is seen, so `export` needs the registry.
+#[derive(Debug, Clone)]
+pub struct RelayTelemetry {
+    rejected:  HashMap<&'static str, MetricCounter>,
+    banned:    MetricGauge,
+    per_peer:  HashMap<NodeId, MetricGauge>,
+    last:      HashMap<&'static str, u64>,
+}
+
+impl RelayTelemetry {
+    const REASONS: [&'static str; 6] =
+        ["malformed", "oversize", "missing_signature", "fee_floor", "unknown_signer", "bad_signature"];
+
+    pub fn register(registry: &mut MetricsRegistry) -> Self {
+        let rejected = Self::REASONS
+            .iter()
+            .map(|r| (*r, registry.counter(&format!("bleep_relay_rejected_{}_total", r))))
+            .collect();
+        RelayTelemetry {
+            rejected,
+            banned:   registry.gauge("bleep_relay_banned_peers"),
+            per_peer: HashMap::new(),
+            last:     HashMap::new(),
+        }
+    }
+
+    pub fn export(&mut self, registry: &mut MetricsRegistry, guard: &RelayGuard) {
+        for (reason, now) in guard.rejects() {
+            let prev = self.last.insert(*reason, *now).unwrap_or(0);
+            if let Some(c) = self.rejected.get(*reason) {
+                c.add(now - prev);
+            }
+        }
+        self.banned.set(guard.banned.len() as i64);
+        for (peer, stats) in guard.peer_stats() {
+            let gauge = self.per_peer.entry(peer.clone()).or_insert_with(|| {
+                let id = peer.to_string();
+                registry.gauge(&format!("bleep_relay_rejection_ratio_permille_{}", &id[..16]))
+            });
+            gauge.set((stats.rejection_ratio() * 1000.0) as i64);
+        }
+    }
+
+    pub fn peer_ratio_permille(&self, peer: &NodeId) -> Option<i64> {
+        self.per_peer.get(peer).map(|g| g.get())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TESTS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Accepts payloads whose first byte is non-zero.
+    struct FirstByte;
+
+    impl RelayValidator for FirstByte {
+        fn check(&self, _topic: &Topic, payload: &[u8]) -> Result<(), RelayReject> {
+            match payload.first() {
+                Some(0) | None => Err(RelayReject::Malformed("zero tag".into())),
+                Some(_) => Ok(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_standing_degrades_then_bans() {
+        let mut guard = RelayGuard::new(Arc::new(FirstByte), RelayPolicy::default());
+        let peer = NodeId::from_bytes(b"spammer");
+        for _ in 0..6 {
+            guard.screen(&peer, &Topic::Transactions, &[1]).unwrap();
+        }
+        for _ in 0..2 {
+            assert!(guard.screen(&peer, &Topic::Transactions, &[0]).is_err());
+        }
+        assert_eq!(guard.standing(&peer), RelayStanding::Demoted);
+        for _ in 0..6 {
+            let _ = guard.screen(&peer, &Topic::Transactions, &[0]);
+        }
+        assert_eq!(guard.standing(&peer), RelayStanding::Banned);
+
+        let penalties = guard.take_penalties();
+        assert_eq!(penalties.iter().filter(|p| matches!(p, RelayPenalty::Failure(_))).count(), 8);
+        assert_eq!(penalties.iter().filter(|p| matches!(p, RelayPenalty::Ban(_))).count(), 1);
+        assert!(guard.take_penalties().is_empty());
+    }
+
+    #[test]
+    fn test_telemetry_tracks_per_peer_ratio() {
+        let mut guard = RelayGuard::new(Arc::new(FirstByte), RelayPolicy::default());
+        let good = NodeId::from_bytes(b"good");
+        let bad = NodeId::from_bytes(b"bad");
+        guard.screen(&good, &Topic::Blocks, &[1]).unwrap();
+        let _ = guard.screen(&bad, &Topic::Blocks, &[0]);
+        let _ = guard.screen(&bad, &Topic::Blocks, &[1]);
+
+        let mut registry = MetricsRegistry::new();
+        let mut tel = RelayTelemetry::register(&mut registry);
+        tel.export(&mut registry, &guard);
+        tel.export(&mut registry, &guard);
+        assert_eq!(tel.peer_ratio_permille(&good), Some(0));
+        assert_eq!(tel.peer_ratio_permille(&bad), Some(500));
+        assert_eq!(tel.rejected["malformed"].get(), 1);
+    }
+}
@@ -0,0 +1,609 @@
+//! Per-peer quotas on the requests a node serves.
+//!
+//! Serving snap-sync chunks, DHT lookups and gossip pulls costs disk and
+//! bandwidth on behalf of someone else; without limits one greedy peer can
+//! saturate both and starve consensus traffic.  `ServiceQuotas` sits in
+//! front of those handlers:
+//!
+//! - **Rate** — every peer gets two token buckets per `ServiceClass`:
+//!   requests/second and served bytes/second.  Each holds one second's
+//!   worth, so a quiet peer may burst that much.  Bytes are charged after
+//!   serving and may overdraw the bucket; the peer then waits out the debt.
+//! - **Concurrency** — expensive handlers (state chunk and manifest reads)
+//!   run at most `max_concurrent_expensive` at a time across all peers.
+//!   Waiters queue per peer and slots are handed out round-robin over
+//!   peers, so a peer with a long queue cannot starve one with a short one.
+//! - **Back-off** — a refused request is answered with a `busy` frame
+//!   carrying `retry_after_ms` instead of the payload.
+//! - **Reputation** — every `violations_per_penalty` refusals queue a
+//!   `QuotaPenalty` for `PeerManager::apply_quota_penalties`.
+//!
+//! Consensus messages (blocks, votes, transactions) never pass through
+//! here: they arrive on the authenticated path of `MessageProtocol`, which
+//! neither counts against quotas nor waits for an expensive slot.
+//!
+//! Snap-sync frames are anonymous, so peers are keyed by remote IP unless
+//! the frame carries a verified node id (see `PeerKey`).
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bleep_telemetry::metrics::{MetricCounter, MetricGauge, MetricsRegistry};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::error::{P2PError, P2PResult};
+use crate::types::{MessageType, NodeId, SecureMessage};
+
+/// `MessageType::Custom` tag of an over-quota answer.
+pub const BUSY_MESSAGE: &str = "busy";
+
+// ─────────────────────────────────────────────────────────────────────────────
+// CONFIG
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Kinds of request a node serves for its peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceClass {
+    /// Snap-sync manifests and state chunks.
+    Sync,
+    /// Kademlia requests.
+    Dht,
+    /// Gossip payloads served in reply to `IWant`.
+    GossipPull,
+}
+
+impl ServiceClass {
+    pub const ALL: [ServiceClass; 3] = [ServiceClass::Sync, ServiceClass::Dht, ServiceClass::GossipPull];
+
+    /// Telemetry and JSON label.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ServiceClass::Sync       => "sync",
+            ServiceClass::Dht        => "dht",
+            ServiceClass::GossipPull => "gossip_pull",
+        }
+    }
+}
+
+/// Per-peer limits of one service class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassQuota {
+    pub requests_per_sec: u32,
+    pub bytes_per_sec:    u64,
+}
+
+/// Quotas of `P2PNodeConfig::service_quotas`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceQuotaConfig {
+    pub sync:                     ClassQuota,
+    pub dht:                      ClassQuota,
+    pub gossip_pull:              ClassQuota,
+    /// Expensive handlers running at once, across all peers.
+    pub max_concurrent_expensive: usize,
+    /// Expensive requests one peer may have waiting for a slot.
+    pub max_queued_per_peer:      usize,
+    /// Refusals that cost a peer one failed interaction.
+    pub violations_per_penalty:   u32,
+}
+
+impl Default for ServiceQuotaConfig {
+    fn default() -> Self {
+        ServiceQuotaConfig {
+            sync:                     ClassQuota { requests_per_sec: 20, bytes_per_sec: 8 * 1024 * 1024 },
+            dht:                      ClassQuota { requests_per_sec: 50, bytes_per_sec: 1024 * 1024 },
+            gossip_pull:              ClassQuota { requests_per_sec: 50, bytes_per_sec: 4 * 1024 * 1024 },
+            max_concurrent_expensive: 4,
+            max_queued_per_peer:      8,
+            violations_per_penalty:   10,
+        }
+    }
+}
+
+impl ServiceQuotaConfig {
+    /// Parse a JSON config; fields left out keep their defaults.
+    pub fn from_json(json: &str) -> P2PResult<Self> {
+        let config: Self = serde_json::from_str(json).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        if config.max_concurrent_expensive == 0 || config.violations_per_penalty == 0 {
+            return Err(P2PError::Serialization(
+                "max_concurrent_expensive and violations_per_penalty must be positive".into(),
+            ));
+        }
+        Ok(config)
+    }
+
+    pub fn quota(&self, class: ServiceClass) -> ClassQuota {
+        match class {
+            ServiceClass::Sync       => self.sync,
+            ServiceClass::Dht        => self.dht,
+            ServiceClass::GossipPull => self.gossip_pull,
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// WIRE
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Who a quota is charged to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PeerKey {
+    /// A peer whose node id the request proves.
+    Node(NodeId),
+    /// An anonymous requester, by remote IP.
+    Addr(IpAddr),
+}
+
+/// Payload of a `busy` frame: the request was refused, retry later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Busy {
+    pub class:          ServiceClass,
+    pub retry_after_ms: u64,
+}
+
+impl Busy {
+    /// Fail with `P2PError::Busy` if `reply` is a `busy` frame.
+    pub fn check(reply: &SecureMessage) -> P2PResult<()> {
+        if !matches!(&reply.message_type, MessageType::Custom(kind) if kind == BUSY_MESSAGE) {
+            return Ok(());
+        }
+        let busy: Busy = bincode::deserialize(&reply.payload).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        Err(P2PError::Busy { class: busy.class.label(), retry_after_ms: busy.retry_after_ms })
+    }
+}
+
+/// Reputation effect queued for the peer manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaPenalty(pub PeerKey);
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TOKEN BUCKET
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    /// May go negative after a byte charge larger than the balance.
+    tokens:  f64,
+    rate:    f64,
+    last_ms: u64,
+}
+
+impl Bucket {
+    fn full(rate: f64, now_ms: u64) -> Self {
+        Bucket { tokens: rate, rate, last_ms: now_ms }
+    }
+
+    fn refill(&mut self, now_ms: u64) {
+        let elapsed = now_ms.saturating_sub(self.last_ms) as f64 / 1000.0;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.last_ms = self.last_ms.max(now_ms);
+    }
+
+    /// Milliseconds until the balance reaches `level`.
+    fn wait_ms(&self, level: f64) -> u64 {
+        if self.tokens >= level || self.rate <= 0.0 {
+            return 0;
+        }
+        ((level - self.tokens) / self.rate * 1000.0).ceil() as u64
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// STATS
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// What one peer was served in one class.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassStats {
+    pub served:   u64,
+    pub bytes:    u64,
+    pub rejected: u64,
+}
+
+/// Live service stats of one peer, as shown by `GET /rpc/net/peers`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeerServiceStats {
+    /// By `ServiceClass::label`.
+    pub classes:   BTreeMap<String, ClassStats>,
+    /// Expensive requests running.
+    pub in_flight: usize,
+    /// Expensive requests waiting for a slot.
+    pub queued:    usize,
+}
+
+impl PeerServiceStats {
+    /// Sum of two peers' stats (a node id and the IP it connects from).
+    pub fn merge(mut self, other: &PeerServiceStats) -> Self {
+        for (class, s) in &other.classes {
+            let mine = self.classes.entry(class.clone()).or_default();
+            mine.served += s.served;
+            mine.bytes += s.bytes;
+            mine.rejected += s.rejected;
+        }
+        self.in_flight += other.in_flight;
+        self.queued += other.queued;
+        self
+    }
+
+    pub fn rejected(&self) -> u64 {
+        self.classes.values().map(|c| c.rejected).sum()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// QUOTAS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+struct PeerQuota {
+    requests:   HashMap<ServiceClass, Bucket>,
+    bytes:      HashMap<ServiceClass, Bucket>,
+    stats:      BTreeMap<ServiceClass, ClassStats>,
+    violations: u32,
+    in_flight:  usize,
+}
+
+#[derive(Default)]
+struct State {
+    peers:     HashMap<PeerKey, PeerQuota>,
+    penalties: Vec<QuotaPenalty>,
+    /// Expensive handlers running.
+    in_flight: usize,
+    /// Waiters per peer, and the round-robin order of peers with waiters.
+    waiting:   HashMap<PeerKey, VecDeque<oneshot::Sender<()>>>,
+    order:     VecDeque<PeerKey>,
+}
+
+impl State {
+    fn peer(&mut self, key: &PeerKey, config: &ServiceQuotaConfig, now_ms: u64) -> &mut PeerQuota {
+        self.peers.entry(key.clone()).or_insert_with(|| {
+            let buckets = |f: fn(&ClassQuota) -> f64| -> HashMap<ServiceClass, Bucket> {
+                ServiceClass::ALL.iter().map(|c| (*c, Bucket::full(f(&config.quota(*c)), now_ms))).collect()
+            };
+            PeerQuota {
+                requests:   buckets(|q| q.requests_per_sec as f64),
+                bytes:      buckets(|q| q.bytes_per_sec as f64),
+                stats:      BTreeMap::new(),
+                violations: 0,
+                in_flight:  0,
+            }
+        })
+    }
+
+    fn violation(&mut self, key: &PeerKey, class: ServiceClass, every: u32) {
+        let Some(peer) = self.peers.get_mut(key) else { return };
+        peer.stats.entry(class).or_default().rejected += 1;
+        peer.violations += 1;
+        if peer.violations % every == 0 {
+            self.penalties.push(QuotaPenalty(key.clone()));
+        }
+    }
+}
+
+/// Per-peer service quotas, shared by every handler of a node.
+pub struct ServiceQuotas {
+    config: ServiceQuotaConfig,
+    state:  Arc<Mutex<State>>,
+    epoch:  Instant,
+}
+
+impl ServiceQuotas {
+    pub fn new(config: ServiceQuotaConfig) -> Self {
+        ServiceQuotas { config, state: Arc::new(Mutex::new(State::default())), epoch: Instant::now() }
+    }
+
+    pub fn config(&self) -> &ServiceQuotaConfig {
+        &self.config
+    }
+
+    /// Monotonic milliseconds, for callers without a clock of their own.
+    pub fn clock_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64
+    }
+
+    /// Count one `class` request from `key`, or refuse it with how long to
+    /// back off.  Refusals count towards reputation penalties.
+    pub fn admit(&self, key: &PeerKey, class: ServiceClass, now_ms: u64) -> Result<(), Busy> {
+        let mut state = self.state.lock();
+        let peer = state.peer(key, &self.config, now_ms);
+        let requests = peer.requests.get_mut(&class).expect("bucket per class");
+        requests.refill(now_ms);
+        let bytes = peer.bytes.get_mut(&class).expect("bucket per class");
+        bytes.refill(now_ms);
+        let retry_after_ms = peer.requests[&class].wait_ms(1.0).max(peer.bytes[&class].wait_ms(f64::MIN_POSITIVE));
+        if retry_after_ms > 0 {
+            state.violation(key, class, self.config.violations_per_penalty);
+            return Err(Busy { class, retry_after_ms });
+        }
+        if let Some(b) = state.peer(key, &self.config, now_ms).requests.get_mut(&class) {
+            b.tokens -= 1.0;
+        }
+        Ok(())
+    }
+
+    /// Record `bytes` served to `key` for an admitted request.
+    pub fn charge(&self, key: &PeerKey, class: ServiceClass, bytes: usize, now_ms: u64) {
+        let mut state = self.state.lock();
+        let peer = state.peer(key, &self.config, now_ms);
+        if let Some(b) = peer.bytes.get_mut(&class) {
+            b.refill(now_ms);
+            b.tokens -= bytes as f64;
+        }
+        let stats = peer.stats.entry(class).or_default();
+        stats.served += 1;
+        stats.bytes += bytes as u64;
+    }
+
+    /// Wait for a slot to run an expensive handler for `key`.  Refused,
+    /// without waiting, when `key` already has `max_queued_per_peer`
+    /// requests queued.
+    pub async fn acquire(&self, key: &PeerKey, class: ServiceClass) -> Result<ExpensivePermit, Busy> {
+        let rx = {
+            let mut state = self.state.lock();
+            if state.in_flight < self.config.max_concurrent_expensive && state.order.is_empty() {
+                state.in_flight += 1;
+                let now_ms = self.clock_ms();
+                state.peer(key, &self.config, now_ms).in_flight += 1;
+                return Ok(self.permit(key));
+            }
+            let queued = state.waiting.get(key).map_or(0, |q| q.len());
+            if queued >= self.config.max_queued_per_peer {
+                let now_ms = self.clock_ms();
+                state.peer(key, &self.config, now_ms);
+                state.violation(key, class, self.config.violations_per_penalty);
+                // Roughly the time for the queue ahead of it to drain.
+                return Err(Busy { class, retry_after_ms: 100 * (queued as u64 + 1) });
+            }
+            let (tx, rx) = oneshot::channel();
+            state.waiting.entry(key.clone()).or_default().push_back(tx);
+            if queued == 0 {
+                state.order.push_back(key.clone());
+            }
+            rx
+        };
+        // The releasing permit moved its slot to us and counted it.
+        rx.await.map_err(|_| Busy { class, retry_after_ms: 100 })?;
+        Ok(self.permit(key))
+    }
+
+    fn permit(&self, key: &PeerKey) -> ExpensivePermit {
+        ExpensivePermit { state: Arc::clone(&self.state), key: key.clone() }
+    }
+
+    /// Drain penalties queued since the last call.
+    pub fn take_penalties(&self) -> Vec<QuotaPenalty> {
+        std::mem::take(&mut self.state.lock().penalties)
+    }
+
+    /// Live stats of `key`, if it ever made a request.
+    pub fn stats(&self, key: &PeerKey) -> Option<PeerServiceStats> {
+        let state = self.state.lock();
+        let peer = state.peers.get(key)?;
+        Some(PeerServiceStats {
+            classes:   peer.stats.iter().map(|(c, s)| (c.label().to_string(), *s)).collect(),
+            in_flight: peer.in_flight,
+            queued:    state.waiting.get(key).map_or(0, |q| q.len()),
+        })
+    }
+
+    /// Stats of every peer that made a request.
+    pub fn all_stats(&self) -> Vec<(PeerKey, PeerServiceStats)> {
+        let keys: Vec<PeerKey> = self.state.lock().peers.keys().cloned().collect();
+        keys.into_iter().filter_map(|k| self.stats(&k).map(|s| (k, s))).collect()
+    }
+
+    /// Expensive handlers running and waiting, across all peers.
+    pub fn load(&self) -> (usize, usize) {
+        let state = self.state.lock();
+        (state.in_flight, state.waiting.values().map(VecDeque::len).sum())
+    }
+}
+
+/// A slot for one expensive handler; the next waiter gets it on drop.
+pub struct ExpensivePermit {
+    state: Arc<Mutex<State>>,
+    key:   PeerKey,
+}
+
+impl Drop for ExpensivePermit {
+    fn drop(&mut self) {
+        let mut guard = self.state.lock();
+        let state = &mut *guard;
+        if let Some(peer) = state.peers.get_mut(&self.key) {
+            peer.in_flight = peer.in_flight.saturating_sub(1);
+        }
+        while let Some(next) = state.order.pop_front() {
+            let Some(queue) = state.waiting.get_mut(&next) else { continue };
+            let waiter = queue.pop_front();
+            if queue.is_empty() {
+                state.waiting.remove(&next);
+            } else {
+                state.order.push_back(next.clone());
+            }
+            // A waiter that gave up has dropped its receiver; try the next.
+            if waiter.is_some_and(|w| w.send(()).is_ok()) {
+                if let Some(peer) = state.peers.get_mut(&next) {
+                    peer.in_flight += 1;
+                }
+                return;
+            }
+        }
+        state.in_flight = state.in_flight.saturating_sub(1);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TELEMETRY
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Telemetry handles for `ServiceQuotas`: refusals per class, expensive
+/// slots in use and waiting, and peers currently over quota.
+#[derive(Debug, Clone)]
+pub struct ServiceQuotaTelemetry {
+    rejected:  HashMap<ServiceClass, MetricCounter>,
+    in_flight: MetricGauge,
+    queued:    MetricGauge,
+    throttled: MetricGauge,
+    last:      HashMap<ServiceClass, u64>,
+}
+
+impl ServiceQuotaTelemetry {
+    /// Register `bleep_p2p_quota_*` metrics.
+    pub fn register(registry: &mut MetricsRegistry) -> Self {
+        ServiceQuotaTelemetry {
+            rejected:  ServiceClass::ALL.iter()
+                .map(|c| (*c, registry.counter(&format!("bleep_p2p_quota_rejected_{}_total", c.label()))))
+                .collect(),
+            in_flight: registry.gauge("bleep_p2p_quota_expensive_in_flight"),
+            queued:    registry.gauge("bleep_p2p_quota_expensive_queued"),
+            throttled: registry.gauge("bleep_p2p_quota_throttled_peers"),
+            last:      HashMap::new(),
+        }
+    }
+
+    pub fn export(&mut self, quotas: &ServiceQuotas) {
+        let stats = quotas.all_stats();
+        for class in ServiceClass::ALL {
+            let now: u64 = stats.iter()
+                .filter_map(|(_, s)| s.classes.get(class.label()))
+                .map(|c| c.rejected)
+                .sum();
+            let prev = self.last.insert(class, now).unwrap_or(0);
+            self.rejected[&class].add(now.saturating_sub(prev));
+        }
+        let (in_flight, queued) = quotas.load();
+        self.in_flight.set(in_flight as i64);
+        self.queued.set(queued as i64);
+        self.throttled.set(stats.iter().filter(|(_, s)| s.rejected() > 0).count() as i64);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TESTS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn addr(last: u8) -> PeerKey {
+        PeerKey::Addr(IpAddr::from([10, 0, 0, last]))
+    }
+
+    fn config(sync_rps: u32) -> ServiceQuotaConfig {
+        ServiceQuotaConfig {
+            sync: ClassQuota { requests_per_sec: sync_rps, bytes_per_sec: 1024 * 1024 },
+            max_concurrent_expensive: 1,
+            max_queued_per_peer: 3,
+            violations_per_penalty: 5,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_greedy_peer_is_held_to_its_rate_and_others_are_served() {
+        let quotas = ServiceQuotas::new(config(10));
+        let (greedy, polite) = (addr(1), addr(2));
+        let (mut greedy_served, mut polite_served) = (0, 0);
+        // Two seconds: the greedy peer asks every millisecond, the polite
+        // one five times a second.
+        for ms in 0..2_000u64 {
+            if quotas.admit(&greedy, ServiceClass::Sync, ms).is_ok() {
+                greedy_served += 1;
+            }
+            if ms % 200 == 0 && quotas.admit(&polite, ServiceClass::Sync, ms).is_ok() {
+                polite_served += 1;
+            }
+        }
+        // One second of burst plus two of refill.
+        assert!((29..=31).contains(&greedy_served), "greedy served {}", greedy_served);
+        assert_eq!(polite_served, 10);
+
+        let refused = quotas.admit(&greedy, ServiceClass::Sync, 2_000).unwrap_err();
+        assert_eq!(refused.class, ServiceClass::Sync);
+        assert!(refused.retry_after_ms > 0 && refused.retry_after_ms <= 100);
+        assert!(quotas.stats(&greedy).unwrap().rejected() > 1_900);
+        assert_eq!(quotas.stats(&polite).unwrap().rejected(), 0);
+        // Other classes have their own buckets.
+        assert!(quotas.admit(&greedy, ServiceClass::Dht, 2_000).is_ok());
+    }
+
+    #[test]
+    fn test_served_bytes_are_rate_limited() {
+        let quotas = ServiceQuotas::new(config(1_000));
+        let peer = addr(1);
+        quotas.admit(&peer, ServiceClass::Sync, 0).unwrap();
+        // Two seconds' worth in one answer: refused until the debt is repaid.
+        quotas.charge(&peer, ServiceClass::Sync, 2 * 1024 * 1024, 0);
+        let busy = quotas.admit(&peer, ServiceClass::Sync, 500).unwrap_err();
+        assert!((500..=1_001).contains(&busy.retry_after_ms), "{:?}", busy);
+        assert!(quotas.admit(&peer, ServiceClass::Sync, 1_001).is_ok());
+        let stats = quotas.stats(&peer).unwrap();
+        assert_eq!(stats.classes["sync"], ClassStats { served: 1, bytes: 2 * 1024 * 1024, rejected: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_expensive_slots_rotate_fairly_across_peers() {
+        let quotas = Arc::new(ServiceQuotas::new(config(1_000)));
+        let (greedy, polite) = (addr(1), addr(2));
+        let first = quotas.acquire(&greedy, ServiceClass::Sync).await.unwrap();
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut waiters = Vec::new();
+        for (key, label) in [(&greedy, "g1"), (&greedy, "g2"), (&greedy, "g3"), (&polite, "p1")] {
+            let (q, o, key) = (Arc::clone(&quotas), Arc::clone(&order), key.clone());
+            waiters.push(tokio::spawn(async move {
+                let permit = q.acquire(&key, ServiceClass::Sync).await.unwrap();
+                o.lock().push(label);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                drop(permit);
+            }));
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        // A fourth queued request from the greedy peer is refused outright.
+        assert!(quotas.acquire(&greedy, ServiceClass::Sync).await.is_err());
+        assert_eq!(quotas.load(), (1, 4));
+        assert_eq!(quotas.stats(&greedy).unwrap().queued, 3);
+
+        drop(first);
+        for w in waiters {
+            w.await.unwrap();
+        }
+        // The polite peer queued last but is served second.
+        assert_eq!(*order.lock(), ["g1", "p1", "g2", "g3"]);
+        assert_eq!(quotas.load(), (0, 0));
+    }
+
+    #[test]
+    fn test_repeated_violations_queue_penalties() {
+        let quotas = ServiceQuotas::new(config(1));
+        let peer = addr(1);
+        quotas.admit(&peer, ServiceClass::Sync, 0).unwrap();
+        for _ in 0..12 {
+            assert!(quotas.admit(&peer, ServiceClass::Sync, 0).is_err());
+        }
+        assert_eq!(quotas.take_penalties(), vec![QuotaPenalty(peer.clone()); 2]);
+        assert!(quotas.take_penalties().is_empty());
+
+        let mut registry = MetricsRegistry::new();
+        let mut tel = ServiceQuotaTelemetry::register(&mut registry);
+        tel.export(&quotas);
+        tel.export(&quotas);
+        assert_eq!(tel.rejected[&ServiceClass::Sync].get(), 12);
+        assert_eq!(tel.throttled.get(), 1);
+    }
+
+    #[test]
+    fn test_config_from_json_keeps_defaults() {
+        let config = ServiceQuotaConfig::from_json(r#"{"sync":{"requests_per_sec":5,"bytes_per_sec":1000}}"#).unwrap();
+        assert_eq!(config.sync.requests_per_sec, 5);
+        assert_eq!(config.dht, ServiceQuotaConfig::default().dht);
+        assert!(ServiceQuotaConfig::from_json(r#"{"max_concurrent_expensive":0}"#).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+//! Snap-sync request/response frames.
+//!
+//! A node without state asks peers for the state at a checkpoint instead
+//! of replaying every block:
+//!
+//! ```text
+//! snap GetStateManifest { at_checkpoint }  ─▶  Manifest(bytes) | Unavailable
+//! snap GetStateChunk { checkpoint, index } ─▶  Chunk(bytes)    | Unavailable
+//! ```
+//!
+//! Frames are anonymous and unencrypted like `pex`: everything served is
+//! public state and the syncing node verifies it end to end.  This module
+//! only moves bytes; what a manifest and a chunk contain, and how they are
+//! checked, lives in `bleep_consensus::snap_sync`.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use bleep_telemetry::metrics::{MetricGauge, MetricsRegistry};
+
+use crate::error::{P2PError, P2PResult};
+use crate::message_protocol::MessageProtocol;
+use crate::service_quota::Busy;
+use crate::types::{unix_now, MessageType, NodeId, SecureMessage};
+
+/// `MessageType::Custom` tag of snap-sync frames.
+pub const SNAP_MESSAGE: &str = "snap";
+
+/// `at_checkpoint` value asking for the peer's latest checkpoint.
+pub const LATEST_CHECKPOINT: u64 = 0;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapRequest {
+    /// Manifest of the state at checkpoint height `at_checkpoint`.
+    GetStateManifest { at_checkpoint: u64 },
+    /// Chunk `index` of the manifest served for `checkpoint`.
+    GetStateChunk { checkpoint: u64, index: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapResponse {
+    Manifest(Vec<u8>),
+    Chunk(Vec<u8>),
+    /// The peer holds no snapshot for that checkpoint (any more).
+    Unavailable,
+}
+
+/// What a node serves snap-sync requests from.
+pub trait SnapSource: Send + Sync {
+    fn manifest(&self, at_checkpoint: u64) -> Option<Vec<u8>>;
+    fn chunk(&self, checkpoint: u64, index: u32) -> Option<Vec<u8>>;
+}
+
+/// Answer `request` from `source`.
+pub fn serve(source: &dyn SnapSource, request: &SnapRequest) -> SnapResponse {
+    let served = match *request {
+        SnapRequest::GetStateManifest { at_checkpoint } => source.manifest(at_checkpoint).map(SnapResponse::Manifest),
+        SnapRequest::GetStateChunk { checkpoint, index } => source.chunk(checkpoint, index).map(SnapResponse::Chunk),
+    };
+    served.unwrap_or(SnapResponse::Unavailable)
+}
+
+/// One peer a syncing node downloads from.
+#[async_trait]
+pub trait SnapPeer: Send + Sync {
+    /// Stable label for logs and penalties.
+    fn id(&self) -> String;
+    async fn request(&self, request: SnapRequest) -> P2PResult<SnapResponse>;
+}
+
+/// Requests snap-sync frames over the P2P TCP transport.
+pub struct TcpSnapPeer {
+    pub addr: SocketAddr,
+}
+
+#[async_trait]
+impl SnapPeer for TcpSnapPeer {
+    fn id(&self) -> String {
+        self.addr.to_string()
+    }
+
+    async fn request(&self, request: SnapRequest) -> P2PResult<SnapResponse> {
+        let payload = bincode::serialize(&request).map_err(|e| P2PError::Serialization(e.to_string()))?;
+        let mut nonce = [0u8; 16];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce);
+        let frame = SecureMessage {
+            version: 1,
+            sender_id: NodeId::random(),
+            message_type: MessageType::Custom(SNAP_MESSAGE.into()),
+            payload,
+            signature: Vec::new(),
+            hop_count: 0,
+            nonce,
+            timestamp: unix_now(),
+        };
+        let reply = MessageProtocol::exchange(self.addr, &frame).await?;
+        Busy::check(&reply)?;
+        bincode::deserialize(&reply.payload).map_err(|e| P2PError::Serialization(e.to_string()))
+    }
+}
+
+/// Serves from a `SnapSource` in-process; for tests and local tooling.
+pub struct LocalSnapPeer {
+    pub id:     String,
+    pub source: Arc<dyn SnapSource>,
+}
+
+#[async_trait]
+impl SnapPeer for LocalSnapPeer {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    async fn request(&self, request: SnapRequest) -> P2PResult<SnapResponse> {
+        Ok(serve(self.source.as_ref(), &request))
+    }
+}
+
+/// Telemetry gauges of a running snap sync.
+#[derive(Debug, Clone)]
+pub struct SnapTelemetry {
+    chunks_done:     MetricGauge,
+    chunks_total:    MetricGauge,
+    bytes_per_sec:   MetricGauge,
+    peers_penalized: MetricGauge,
+}
+
+impl SnapTelemetry {
+    /// Register gauges `bleep_snap_sync_*`.
+    pub fn register(registry: &mut MetricsRegistry) -> Self {
+        SnapTelemetry {
+            chunks_done:     registry.gauge("bleep_snap_sync_chunks_done"),
+            chunks_total:    registry.gauge("bleep_snap_sync_chunks_total"),
+            bytes_per_sec:   registry.gauge("bleep_snap_sync_bytes_per_sec"),
+            peers_penalized: registry.gauge("bleep_snap_sync_peers_penalized"),
+        }
+    }
+
+    pub fn export(&self, chunks_done: u64, chunks_total: u64, bytes_per_sec: u64, peers_penalized: u64) {
+        self.chunks_done.set(chunks_done as i64);
+        self.chunks_total.set(chunks_total as i64);
+        self.bytes_per_sec.set(bytes_per_sec as i64);
+        self.peers_penalized.set(peers_penalized as i64);
+    }
+}
@@ -0,0 +1,193 @@
+//! Stream transports under `MessageProtocol`.
+//!
+//! Every frame travels on its own stream: the sender opens one, writes a
+//! length-prefixed frame and, for a request, reads one frame back.  What a
+//! stream is depends on the transport:
+//!
+//! - `TcpTransport` — a fresh TCP connection per stream (the default).
+//! - `QuicTransport` (feature `quic`, see `quic_transport`) — one QUIC
+//!   connection per peer and a stream per frame, prioritised by `Lane`, so
+//!   a large block does not hold up consensus traffic behind it.
+//!
+//! Nodes only talk to nodes on the same transport; a TCP node dialing a
+//! QUIC node (or the reverse) gets a connection error, not a hang.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tracing::error;
+
+use crate::block_sync::BLOCK_SYNC_MESSAGE;
+use crate::error::{P2PError, P2PResult};
+use crate::kademlia_dht::DHT_MESSAGE;
+use crate::node_info::{HELLO_MESSAGE, PEX_MESSAGE};
+use crate::snap_sync::SNAP_MESSAGE;
+use crate::types::MessageType;
+
+/// How long dialing a peer may take.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A bidirectional byte stream carrying one frame each way.
+pub trait FrameStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> FrameStream for T {}
+
+pub type BoxStream = Box<dyn FrameStream>;
+
+/// Which transport a node runs, from the `transport = "tcp" | "quic"` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransportKind {
+    #[default]
+    Tcp,
+    Quic,
+}
+
+impl fmt::Display for TransportKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransportKind::Tcp  => write!(f, "tcp"),
+            TransportKind::Quic => write!(f, "quic"),
+        }
+    }
+}
+
+impl FromStr for TransportKind {
+    type Err = P2PError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "tcp"  => Ok(TransportKind::Tcp),
+            "quic" => Ok(TransportKind::Quic),
+            other  => Err(P2PError::Transport(format!("unknown transport {other:?}, expected tcp or quic"))),
+        }
+    }
+}
+
+/// Traffic class of a stream.  QUIC schedules higher lanes first; TCP,
+/// with a connection per stream, ignores it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lane {
+    /// Handshakes, votes, governance and health checks.
+    Consensus,
+    /// Transactions, gossip envelopes, DHT and onion relays.
+    Gossip,
+    /// Blocks and snap-sync chunks.
+    Sync,
+}
+
+impl Lane {
+    pub fn for_message(message_type: &MessageType) -> Lane {
+        match message_type {
+            MessageType::Governance
+            | MessageType::Ping
+            | MessageType::Pong
+            | MessageType::ZkHandshake => Lane::Consensus,
+            MessageType::Block => Lane::Sync,
+            MessageType::Custom(kind) if kind == HELLO_MESSAGE || kind == PEX_MESSAGE => Lane::Consensus,
+            MessageType::Custom(kind) if kind == SNAP_MESSAGE || kind == BLOCK_SYNC_MESSAGE => Lane::Sync,
+            MessageType::Custom(kind) if kind == DHT_MESSAGE => Lane::Gossip,
+            MessageType::Transaction
+            | MessageType::PeerDiscovery
+            | MessageType::Gossip
+            | MessageType::OnionRelay
+            | MessageType::Custom(_) => Lane::Gossip,
+        }
+    }
+
+    /// QUIC stream priority; higher is sent first.
+    pub fn priority(self) -> i32 {
+        match self {
+            Lane::Consensus => 2,
+            Lane::Gossip    => 1,
+            Lane::Sync      => 0,
+        }
+    }
+}
+
+/// Opens outbound streams and yields inbound ones.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    fn kind(&self) -> TransportKind;
+
+    /// Address inbound streams arrive on, if the transport listens.
+    fn local_addr(&self) -> Option<SocketAddr>;
+
+    async fn open(&self, addr: SocketAddr, lane: Lane) -> P2PResult<BoxStream>;
+
+    /// Next inbound stream and the address it came from; `None` once the
+    /// transport no longer accepts.
+    async fn accept(&self) -> Option<(BoxStream, SocketAddr)>;
+}
+
+/// A TCP connection per stream.
+pub struct TcpTransport {
+    listener: Option<TcpListener>,
+}
+
+impl TcpTransport {
+    /// Accept on `listener` and dial out.
+    pub fn new(listener: TcpListener) -> Self {
+        TcpTransport { listener: Some(listener) }
+    }
+
+    /// Dial out only.
+    pub fn dialer() -> Self {
+        TcpTransport { listener: None }
+    }
+}
+
+#[async_trait]
+impl Transport for TcpTransport {
+    fn kind(&self) -> TransportKind {
+        TransportKind::Tcp
+    }
+
+    fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.as_ref()?.local_addr().ok()
+    }
+
+    async fn open(&self, addr: SocketAddr, _lane: Lane) -> P2PResult<BoxStream> {
+        let stream = timeout(CONNECT_TIMEOUT, TcpStream::connect(addr))
+            .await
+            .map_err(|_| P2PError::ConnectionTimeout { addr: addr.to_string() })?
+            .map_err(P2PError::Io)?;
+        Ok(Box::new(stream))
+    }
+
+    async fn accept(&self) -> Option<(BoxStream, SocketAddr)> {
+        let listener = self.listener.as_ref()?;
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => return Some((Box::new(stream), addr)),
+                Err(e) => error!(error = %e, "Accept error"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transport_kind_parses_and_lanes_rank_consensus_first() {
+        assert_eq!("QUIC".parse::<TransportKind>().unwrap(), TransportKind::Quic);
+        assert_eq!("tcp".parse::<TransportKind>().unwrap(), TransportKind::Tcp);
+        assert!("udp".parse::<TransportKind>().is_err());
+        assert_eq!(TransportKind::Quic.to_string(), "quic");
+
+        let vote = Lane::for_message(&MessageType::Governance);
+        let block = Lane::for_message(&MessageType::Block);
+        let tx = Lane::for_message(&MessageType::Transaction);
+        assert!(vote.priority() > tx.priority() && tx.priority() > block.priority());
+        assert_eq!(Lane::for_message(&MessageType::Custom(SNAP_MESSAGE.into())), Lane::Sync);
+    }
+}
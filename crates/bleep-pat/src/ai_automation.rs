@@ -33,7 +33,6 @@ impl BLEEPWallet {
         ai_model_path: &str,
     ) -> Self {
         let private_key = generate_private_key();
-        println!("New wallet created with private key: {}", private_key);
 
         // Load AI model for insights
         let ai_module = Arc::new(CModule::load(ai_model_path).expect("Failed to load AI model"));
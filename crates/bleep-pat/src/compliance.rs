@@ -0,0 +1,108 @@
+//! # Layer 3b — Compliance Controls
+//!
+//! Per-token compliance state for regulated issuers:
+//!
+//! - **Account freezing** — the owner can freeze individual holders.
+//!   Frozen accounts can still *receive* tokens but cannot send them.
+//! - **Allow-list** — when enabled, every transfer must carry an
+//!   `AllowListInclusionProof`: a Merkle path showing the recipient's
+//!   commitment is a leaf of the issuer-maintained tree whose root is
+//!   stored on-chain.
+//!
+//! ## Allow-list commitments
+//!
+//! ```text
+//! leaf_data  = SHA3-256("BLEEP-PAT-ALLOW" || symbol || address || salt)
+//! tree       = bleep_crypto::MerkleTree over leaf_data (issuer-side, off-chain)
+//! on-chain   = tree root only
+//! ```
+//!
+//! This is a plain inclusion proof, not a zero-knowledge one.  It carries
+//! the recipient's salt and one sibling hash per level, so anyone reading
+//! the transfer learns that the recipient is on the list, its leaf, and
+//! roughly how large the list is.  Other holders stay hidden only because
+//! their leaves are salted hashes; the chain stores the root, not the list.
+//!
+//! Governance can force-unfreeze an account through an executed proposal
+//! (`PATRegistry::governance_unfreeze`) as an escape hatch against a
+//! malicious or unreachable issuer.
+
+use crate::intent::Address;
+use bleep_crypto::merkle_commitment::MembershipProof;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use std::collections::BTreeSet;
+
+/// Domain separator for allow-list leaf commitments.
+const ALLOW_LIST_DOMAIN: &[u8] = b"BLEEP-PAT-ALLOW";
+
+/// Owner-configurable compliance switches for one token.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompliancePolicy {
+    /// Owner may freeze individual accounts.
+    pub account_freezing:    bool,
+    /// Transfers must prove the recipient is on the allow-list.
+    pub allow_list_required: bool,
+}
+
+/// Live compliance state for one token, held by `PATRegistry`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ComplianceState {
+    pub policy:          CompliancePolicy,
+    /// Root of the issuer's allow-list tree (`None` = not yet published).
+    pub allow_list_root: Option<[u8; 32]>,
+    /// Frozen holders (hex-encoded, like `TokenLedger` keys).
+    pub frozen_accounts: BTreeSet<String>,
+}
+
+impl ComplianceState {
+    pub fn new(policy: CompliancePolicy) -> Self {
+        ComplianceState { policy, ..Default::default() }
+    }
+
+    pub fn is_frozen(&self, account: &Address) -> bool {
+        self.frozen_accounts.contains(&hex::encode(account))
+    }
+
+    pub fn set_frozen(&mut self, account: &Address, frozen: bool) {
+        let key = hex::encode(account);
+        if frozen {
+            self.frozen_accounts.insert(key);
+        } else {
+            self.frozen_accounts.remove(&key);
+        }
+    }
+}
+
+/// Merkle inclusion proof that a recipient is on a token's allow-list.
+/// Public: it reveals the recipient's leaf and its path (see the module docs).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllowListInclusionProof {
+    /// Per-holder blinding salt chosen by the issuer.
+    pub salt:       [u8; 32],
+    /// Merkle path from the recipient's leaf to the allow-list root.
+    pub membership: MembershipProof,
+}
+
+impl AllowListInclusionProof {
+    /// Check the proof for `recipient` against `root`.
+    pub fn verify(&self, symbol: &str, recipient: &Address, root: &[u8; 32]) -> bool {
+        let leaf = allow_list_leaf(symbol, recipient, &self.salt);
+        self.membership.verify_leaf(&leaf, root).is_ok()
+    }
+
+    /// Number of path nodes (used for gas metering).
+    pub fn path_len(&self) -> usize {
+        self.membership.path.len()
+    }
+}
+
+/// Leaf data an issuer inserts into its allow-list tree for `address`.
+pub fn allow_list_leaf(symbol: &str, address: &Address, salt: &[u8; 32]) -> Vec<u8> {
+    let mut h = Sha3_256::new();
+    h.update(ALLOW_LIST_DOMAIN);
+    h.update(symbol.as_bytes());
+    h.update(address);
+    h.update(salt);
+    h.finalize().to_vec()
+}
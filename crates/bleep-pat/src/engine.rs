@@ -12,6 +12,7 @@
 //! ## Contrast with old Substrate `decl_module!` dispatch
 //!
 
+use crate::compliance::{AllowListInclusionProof, CompliancePolicy, ComplianceState};
 use crate::error::{PATError, PATResult};
 use crate::gas_model::PATGasModel;
 use crate::intent::{PATIntent, PATIntentKind};
@@ -33,6 +34,7 @@ pub struct RegistryView<'a> {
     pub tokens:     &'a BTreeMap<String, PATToken>,
     pub ledgers:    &'a BTreeMap<String, TokenLedger>,
     pub allowances: &'a BTreeMap<String, AllowanceTable>,
+    pub compliance: &'a BTreeMap<String, ComplianceState>,
 }
 
 impl<'a> RegistryView<'a> {
@@ -49,6 +51,41 @@ impl<'a> RegistryView<'a> {
     pub fn allowance_table(&self, symbol: &str) -> Option<&AllowanceTable> {
         self.allowances.get(symbol)
     }
+
+    pub fn compliance(&self, symbol: &str) -> Option<&ComplianceState> {
+        self.compliance.get(symbol)
+    }
+
+    /// Enforce account freezes and the allow-list for a value transfer.
+    ///
+    /// Frozen senders are rejected; frozen recipients are not (they can
+    /// still receive).  When the allow-list is required, `proof` must show
+    /// `to` is a leaf of the currently published root — a proof built
+    /// against any earlier root fails.
+    pub fn check_transfer_compliance(
+        &self,
+        symbol: &str,
+        from:   &crate::intent::Address,
+        to:     &crate::intent::Address,
+        proof:  Option<&AllowListInclusionProof>,
+    ) -> PATResult<()> {
+        let state = match self.compliance(symbol) {
+            Some(c) => c,
+            None => return Ok(()),
+        };
+        if state.is_frozen(from) {
+            return Err(PATError::AccountFrozen(hex::encode(from)));
+        }
+        if state.policy.allow_list_required {
+            let proof = proof.ok_or_else(|| PATError::AllowListProofRequired(symbol.to_string()))?;
+            let root = state.allow_list_root
+                .ok_or_else(|| PATError::AllowListProofInvalid(hex::encode(to)))?;
+            if !proof.verify(symbol, to, &root) {
+                return Err(PATError::AllowListProofInvalid(hex::encode(to)));
+            }
+        }
+        Ok(())
+    }
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -91,6 +128,9 @@ impl PATEngine {
             PATIntentKind::Freeze(i)            => self.exec_freeze(i, &intent.caller, gas_used, view),
             PATIntentKind::UpdateBurnRate(i)    => self.exec_update_burn_rate(i, &intent.caller, gas_used, view),
             PATIntentKind::TransferOwnership(i) => self.exec_transfer_ownership(i, &intent.caller, gas_used, view),
+            PATIntentKind::FreezeAccount(i)     => self.exec_freeze_account(i, &intent.caller, gas_used, view),
+            PATIntentKind::SetCompliancePolicy(i) => self.exec_set_compliance_policy(i, &intent.caller, gas_used, view),
+            PATIntentKind::SetAllowListRoot(i)  => self.exec_set_allow_list_root(i, &intent.caller, gas_used, view),
         };
 
         outcome
@@ -235,6 +275,7 @@ impl PATEngine {
 
         let token = view.token(&i.symbol)?;
         if token.frozen { return Err(PATError::Frozen(i.symbol.clone())); }
+        view.check_transfer_compliance(&i.symbol, caller, &i.to, i.allow_list_proof.as_ref())?;
 
         let ledger = view.ledger(&i.symbol)?;
         let bal = ledger.balance_of(caller);
@@ -328,6 +369,7 @@ impl PATEngine {
 
         let token = view.token(&i.symbol)?;
         if token.frozen { return Err(PATError::Frozen(i.symbol.clone())); }
+        view.check_transfer_compliance(&i.symbol, &i.from, &i.to, i.allow_list_proof.as_ref())?;
 
         // Check allowance
         let allowance = view.allowance_table(&i.symbol)
@@ -488,6 +530,107 @@ impl PATEngine {
         diff.finalise();
         Ok(PATOutcome::success(diff, None))
     }
+
+    // ── FreezeAccount ─────────────────────────────────────────────────────────
+
+    fn exec_freeze_account(
+        &self,
+        i: &crate::intent::FreezeAccountIntent,
+        caller: &crate::intent::Address,
+        gas_used: u64,
+        view: &RegistryView<'_>,
+    ) -> PATResult<PATOutcome> {
+        let token = view.token(&i.symbol)?;
+        if token.owner != *caller {
+            return Err(PATError::Unauthorized(hex::encode(caller)));
+        }
+        let freezing = view.compliance(&i.symbol).map_or(false, |c| c.policy.account_freezing);
+        if !freezing {
+            return Err(PATError::NotFreezable(i.symbol.clone()));
+        }
+
+        let ts = now();
+        let mut diff = PATStateDiff::empty();
+        diff.gas_used = gas_used;
+        diff.token_mutations.push(TokenMutation::SetAccountFrozen {
+            symbol:  i.symbol.clone(),
+            account: i.account,
+            frozen:  i.frozen,
+        });
+        diff.events.push(PATEvent::AccountFrozen {
+            symbol:      i.symbol.clone(),
+            account:     i.account,
+            frozen:      i.frozen,
+            proposal_id: None,
+            ts,
+        });
+        diff.finalise();
+        Ok(PATOutcome::success(diff, None))
+    }
+
+    // ── SetCompliancePolicy ───────────────────────────────────────────────────
+
+    fn exec_set_compliance_policy(
+        &self,
+        i: &crate::intent::SetCompliancePolicyIntent,
+        caller: &crate::intent::Address,
+        gas_used: u64,
+        view: &RegistryView<'_>,
+    ) -> PATResult<PATOutcome> {
+        let token = view.token(&i.symbol)?;
+        if token.owner != *caller {
+            return Err(PATError::Unauthorized(hex::encode(caller)));
+        }
+
+        let ts = now();
+        let mut diff = PATStateDiff::empty();
+        diff.gas_used = gas_used;
+        diff.token_mutations.push(TokenMutation::SetCompliancePolicy {
+            symbol: i.symbol.clone(),
+            policy: CompliancePolicy {
+                account_freezing:    i.account_freezing,
+                allow_list_required: i.allow_list_required,
+            },
+        });
+        diff.events.push(PATEvent::CompliancePolicyUpdated {
+            symbol:              i.symbol.clone(),
+            account_freezing:    i.account_freezing,
+            allow_list_required: i.allow_list_required,
+            ts,
+        });
+        diff.finalise();
+        Ok(PATOutcome::success(diff, None))
+    }
+
+    // ── SetAllowListRoot ──────────────────────────────────────────────────────
+
+    fn exec_set_allow_list_root(
+        &self,
+        i: &crate::intent::SetAllowListRootIntent,
+        caller: &crate::intent::Address,
+        gas_used: u64,
+        view: &RegistryView<'_>,
+    ) -> PATResult<PATOutcome> {
+        let token = view.token(&i.symbol)?;
+        if token.owner != *caller {
+            return Err(PATError::Unauthorized(hex::encode(caller)));
+        }
+
+        let ts = now();
+        let mut diff = PATStateDiff::empty();
+        diff.gas_used = gas_used;
+        diff.token_mutations.push(TokenMutation::SetAllowListRoot {
+            symbol: i.symbol.clone(),
+            root:   i.root,
+        });
+        diff.events.push(PATEvent::AllowListRootUpdated {
+            symbol: i.symbol.clone(),
+            root:   i.root,
+            ts,
+        });
+        diff.finalise();
+        Ok(PATOutcome::success(diff, None))
+    }
 }
 
 fn now() -> u64 {
@@ -60,6 +60,18 @@ pub enum PATError {
     #[error("Token '{0}' is not freezable")]
     NotFreezable(String),
 
+    #[error("Account {0} is frozen for this token")]
+    AccountFrozen(String),
+
+    #[error("Token '{0}' requires an allow-list proof for transfers")]
+    AllowListProofRequired(String),
+
+    #[error("Allow-list proof rejected for recipient {0}")]
+    AllowListProofInvalid(String),
+
+    #[error("Governance unfreeze rejected: {0}")]
+    GovernanceRejected(String),
+
     // ── Gas ───────────────────────────────────────────────────────────────────
     #[error("Out of gas: limit={limit}, used={used}")]
     OutOfGas { limit: u64, used: u64 },
@@ -22,7 +22,13 @@
 //! | Freeze             | 10_000    | —             | Simple flag flip             |
 //! | UpdateBurnRate     | 10_000    | —             | Simple field write           |
 //! | TransferOwnership  | 20_000    | —             | Ownership change             |
+//! | FreezeAccount      | 10_000    | —             | Compliance flag per holder   |
+//! | SetCompliancePolicy| 10_000    | —             |                              |
+//! | SetAllowListRoot   | 10_000    | —             |                              |
+//!
+//! Transfers carrying an allow-list proof pay an extra 500 per Merkle path node.
 
+use crate::compliance::AllowListInclusionProof;
 use crate::intent::{PATIntentKind, TransferIntent, TransferFromIntent};
 use crate::error::{PATError, PATResult};
 
@@ -40,6 +46,8 @@ pub struct PATGasModel {
     pub freeze_base:             u64,
     pub update_burn_rate_base:   u64,
     pub transfer_ownership_base: u64,
+    pub compliance_base:         u64,
+    pub allow_list_per_node:     u64,
 }
 
 impl Default for PATGasModel {
@@ -56,6 +64,8 @@ impl Default for PATGasModel {
             freeze_base:             10_000,
             update_burn_rate_base:   10_000,
             transfer_ownership_base: 20_000,
+            compliance_base:         10_000,
+            allow_list_per_node:     500,
         }
     }
 }
@@ -70,21 +80,29 @@ impl PATGasModel {
             }
             PATIntentKind::Mint(_) => self.mint_base,
             PATIntentKind::Burn(_) => self.burn_base,
-            PATIntentKind::Transfer(TransferIntent { memo, .. }) => {
+            PATIntentKind::Transfer(TransferIntent { memo, allow_list_proof, .. }) => {
                 self.transfer_base
                     + memo.as_ref().map_or(0, |m| m.len() as u64)
                         * self.transfer_per_memo_byte
+                    + self.proof_cost(allow_list_proof)
             }
             PATIntentKind::Approve(_) => self.approve_base,
-            PATIntentKind::TransferFrom(TransferFromIntent { .. }) => {
-                self.transfer_from_base
+            PATIntentKind::TransferFrom(TransferFromIntent { allow_list_proof, .. }) => {
+                self.transfer_from_base + self.proof_cost(allow_list_proof)
             }
             PATIntentKind::Freeze(_)            => self.freeze_base,
             PATIntentKind::UpdateBurnRate(_)    => self.update_burn_rate_base,
             PATIntentKind::TransferOwnership(_) => self.transfer_ownership_base,
+            PATIntentKind::FreezeAccount(_)
+            | PATIntentKind::SetCompliancePolicy(_)
+            | PATIntentKind::SetAllowListRoot(_) => self.compliance_base,
         }
     }
 
+    fn proof_cost(&self, proof: &Option<AllowListInclusionProof>) -> u64 {
+        proof.as_ref().map_or(0, |p| p.path_len() as u64 * self.allow_list_per_node)
+    }
+
     /// Check that `gas_limit >= cost(kind)`.  Returns the cost on success.
     pub fn charge(&self, kind: &PATIntentKind, gas_limit: u64) -> PATResult<u64> {
         let cost = self.cost(kind);
@@ -11,9 +11,12 @@
 //! TransferIntent     ─┤
 //! ApproveIntent      ─┤
 //! TransferFromIntent ─┤
-//! FreezeIntent       ─┘
+//! FreezeIntent       ─┤
+//! FreezeAccountIntent ┤
+//! SetCompliancePolicy ┘
 //! ```
 
+use crate::compliance::AllowListInclusionProof;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
@@ -67,6 +70,10 @@ pub struct TransferIntent {
     pub amount: u128,
     /// Optional memo attached to the transfer (max 128 bytes).
     pub memo:   Option<Vec<u8>>,
+    /// Recipient allow-list proof; required when the token's policy has
+    /// `allow_list_required` set.
+    #[serde(default)]
+    pub allow_list_proof: Option<AllowListInclusionProof>,
 }
 
 /// Approve `spender` to transfer up to `amount` from caller's balance.
@@ -84,6 +91,9 @@ pub struct TransferFromIntent {
     pub from:   Address,
     pub to:     Address,
     pub amount: u128,
+    /// Recipient allow-list proof (see `TransferIntent::allow_list_proof`).
+    #[serde(default)]
+    pub allow_list_proof: Option<AllowListInclusionProof>,
 }
 
 /// Freeze or unfreeze all transfers for a token (owner only, if `freezable=true`).
@@ -93,6 +103,30 @@ pub struct FreezeIntent {
     pub frozen: bool,
 }
 
+/// Freeze or unfreeze a single holder (owner only, if the compliance
+/// policy enables account freezing).  Frozen holders can receive but not send.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreezeAccountIntent {
+    pub symbol:  String,
+    pub account: Address,
+    pub frozen:  bool,
+}
+
+/// Replace the token's compliance policy (owner only).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetCompliancePolicyIntent {
+    pub symbol:              String,
+    pub account_freezing:    bool,
+    pub allow_list_required: bool,
+}
+
+/// Publish a new allow-list Merkle root (owner only).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetAllowListRootIntent {
+    pub symbol: String,
+    pub root:   [u8; 32],
+}
+
 /// Update the token's burn rate (owner only).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateBurnRateIntent {
@@ -124,6 +158,9 @@ pub enum PATIntentKind {
     Freeze(FreezeIntent),
     UpdateBurnRate(UpdateBurnRateIntent),
     TransferOwnership(TransferOwnershipIntent),
+    FreezeAccount(FreezeAccountIntent),
+    SetCompliancePolicy(SetCompliancePolicyIntent),
+    SetAllowListRoot(SetAllowListRootIntent),
 }
 
 /// A fully described PAT operation, ready for the router.
@@ -174,6 +211,9 @@ impl PATIntent {
             PATIntentKind::Freeze(_)             => "Freeze",
             PATIntentKind::UpdateBurnRate(_)     => "UpdateBurnRate",
             PATIntentKind::TransferOwnership(_)  => "TransferOwnership",
+            PATIntentKind::FreezeAccount(_)      => "FreezeAccount",
+            PATIntentKind::SetCompliancePolicy(_) => "SetCompliancePolicy",
+            PATIntentKind::SetAllowListRoot(_)   => "SetAllowListRoot",
         }
     }
 }
@@ -224,6 +264,7 @@ impl PATIntent {
                 to,
                 amount,
                 memo: None,
+                allow_list_proof: None,
             }),
             21_000,
             0,
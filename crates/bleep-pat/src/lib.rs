@@ -12,7 +12,7 @@
 //! │  PATError · PATResult<T>                                      │
 //! ├───────────────────────────────────────────────────────────────┤
 //! │  Layer 3 — Token State                                        │
-//! │  PATToken · TokenLedger · AllowanceTable                      │
+//! │  PATToken · TokenLedger · AllowanceTable · ComplianceState    │
 //! ├───────────────────────────────────────────────────────────────┤
 //! │  Layer 4 — StateDiff                                          │
 //! │  PATStateDiff · BalanceDelta · SupplyDelta · PATEvent         │
@@ -27,6 +27,7 @@
 pub mod intent;
 pub mod error;
 pub mod token;
+pub mod compliance;
 pub mod state_diff;
 pub mod gas_model;
 pub mod engine;
@@ -37,9 +38,11 @@ pub use intent::{
     CreateTokenIntent, MintIntent, BurnIntent, TransferIntent,
     ApproveIntent, TransferFromIntent, FreezeIntent,
     UpdateBurnRateIntent, TransferOwnershipIntent,
+    FreezeAccountIntent, SetCompliancePolicyIntent, SetAllowListRootIntent,
 };
 pub use error::{PATError, PATResult};
 pub use token::{PATToken, TokenLedger, AllowanceTable};
+pub use compliance::{AllowListInclusionProof, CompliancePolicy, ComplianceState, allow_list_leaf};
 pub use state_diff::{PATEvent, PATOutcome, PATStateDiff};
 pub use gas_model::PATGasModel;
 pub use registry::PATRegistry;
@@ -207,7 +210,7 @@ mod tests {
         reg.execute(&PATIntent::mint(ALICE,"USDB",ALICE,10_000_000_000)).unwrap();
         reg.execute(&PATIntent::new(ALICE,PATIntentKind::Approve(ApproveIntent{symbol:"USDB".into(),spender:BOB,amount:500_000_000}),15_000,0,2)).unwrap();
         assert_eq!(reg.allowance("USDB",&ALICE,&BOB), 500_000_000);
-        reg.execute(&PATIntent::new(BOB,PATIntentKind::TransferFrom(TransferFromIntent{symbol:"USDB".into(),from:ALICE,to:CAROL,amount:200_000_000}),25_000,0,3)).unwrap();
+        reg.execute(&PATIntent::new(BOB,PATIntentKind::TransferFrom(TransferFromIntent{symbol:"USDB".into(),from:ALICE,to:CAROL,amount:200_000_000,allow_list_proof:None}),25_000,0,3)).unwrap();
         assert_eq!(reg.allowance("USDB",&ALICE,&BOB), 300_000_000);
         assert_eq!(reg.balance_of("USDB",&CAROL), 199_000_000); // 0.5% burn
     }
@@ -258,6 +261,95 @@ mod tests {
         assert!(matches!(reg.execute(&intent), Err(PATError::OutOfGas { .. })));
     }
 
+    fn freeze_account(reg: &mut PATRegistry, account: Address, frozen: bool, nonce: u64) {
+        reg.execute(&PATIntent::new(ALICE, PATIntentKind::FreezeAccount(FreezeAccountIntent {
+            symbol: "USDB".into(), account, frozen,
+        }), 10_000, 0, nonce)).unwrap();
+    }
+
+    fn transfer_with_proof(caller: Address, to: Address, amount: u128, proof: Option<AllowListInclusionProof>) -> PATIntent {
+        PATIntent::new(caller, PATIntentKind::Transfer(TransferIntent {
+            symbol: "USDB".into(), to, amount, memo: None, allow_list_proof: proof,
+        }), 50_000, 0, 0)
+    }
+
+    #[test]
+    fn test_frozen_account_cannot_send_but_can_receive() {
+        let mut reg = registry_with_usdb();
+        reg.execute(&PATIntent::mint(ALICE,"USDB",BOB,1_000_000)).unwrap();
+        freeze_account(&mut reg, BOB, true, 1);
+        assert!(reg.is_account_frozen("USDB", &BOB));
+
+        assert!(matches!(reg.execute(&PATIntent::transfer(BOB,"USDB",CAROL,100)), Err(PATError::AccountFrozen(_))));
+        reg.execute(&PATIntent::mint(ALICE,"USDB",ALICE,1_000_000)).unwrap();
+        reg.execute(&PATIntent::transfer(ALICE,"USDB",BOB,1_000)).unwrap();
+        assert!(reg.balance_of("USDB",&BOB) > 1_000_000);
+
+        freeze_account(&mut reg, BOB, false, 2);
+        reg.execute(&PATIntent::transfer(BOB,"USDB",CAROL,100)).unwrap();
+    }
+
+    #[test]
+    fn test_allow_list_proof_required_and_stale_root_rejected() {
+        use bleep_crypto::merkle_commitment::MerkleTree;
+
+        let mut reg = registry_with_usdb();
+        reg.execute(&PATIntent::mint(ALICE,"USDB",ALICE,10_000_000)).unwrap();
+        reg.execute(&PATIntent::new(ALICE, PATIntentKind::SetCompliancePolicy(SetCompliancePolicyIntent {
+            symbol: "USDB".into(), account_freezing: true, allow_list_required: true,
+        }), 10_000, 0, 1)).unwrap();
+
+        let salt = [7u8; 32];
+        let mut tree = MerkleTree::new();
+        tree.add_leaf(allow_list_leaf("USDB", &CAROL, &[1u8; 32])).unwrap();
+        tree.add_leaf(allow_list_leaf("USDB", &BOB, &salt)).unwrap();
+        tree.add_leaf(allow_list_leaf("USDB", &[0x04u8; 32], &[2u8; 32])).unwrap();
+        let root = tree.finalize().unwrap();
+        reg.execute(&PATIntent::new(ALICE, PATIntentKind::SetAllowListRoot(SetAllowListRootIntent {
+            symbol: "USDB".into(), root,
+        }), 10_000, 0, 2)).unwrap();
+
+        let proof = AllowListInclusionProof { salt, membership: tree.prove_membership(1).unwrap() };
+
+        assert!(matches!(
+            reg.execute(&transfer_with_proof(ALICE, BOB, 1_000, None)),
+            Err(PATError::AllowListProofRequired(_))
+        ));
+        // Proof for BOB cannot be reused for CAROL.
+        assert!(matches!(
+            reg.execute(&transfer_with_proof(ALICE, CAROL, 1_000, Some(proof.clone()))),
+            Err(PATError::AllowListProofInvalid(_))
+        ));
+        reg.execute(&transfer_with_proof(ALICE, BOB, 1_000, Some(proof.clone()))).unwrap();
+        assert!(reg.balance_of("USDB", &BOB) > 0);
+
+        // Issuer rotates the list; the old proof no longer verifies.
+        let mut rotated = MerkleTree::new();
+        rotated.add_leaf(allow_list_leaf("USDB", &CAROL, &[1u8; 32])).unwrap();
+        rotated.add_leaf(allow_list_leaf("USDB", &BOB, &[9u8; 32])).unwrap();
+        let new_root = rotated.finalize().unwrap();
+        reg.execute(&PATIntent::new(ALICE, PATIntentKind::SetAllowListRoot(SetAllowListRootIntent {
+            symbol: "USDB".into(), root: new_root,
+        }), 10_000, 0, 3)).unwrap();
+        assert!(matches!(
+            reg.execute(&transfer_with_proof(ALICE, BOB, 2_000, Some(proof))),
+            Err(PATError::AllowListProofInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_governance_unfreeze_bypasses_owner() {
+        let mut reg = registry_with_usdb();
+        reg.execute(&PATIntent::mint(ALICE,"USDB",BOB,1_000)).unwrap();
+        freeze_account(&mut reg, BOB, true, 1);
+        reg.governance_unfreeze("USDB", &BOB, "prop-unfreeze").unwrap();
+        assert!(!reg.is_account_frozen("USDB", &BOB));
+        assert!(matches!(
+            reg.governance_unfreeze("USDB", &BOB, "prop-unfreeze"),
+            Err(PATError::GovernanceRejected(_))
+        ));
+    }
+
     #[test]
     fn test_multiple_independent_tokens() {
         let mut reg = PATRegistry::new();
@@ -16,10 +16,11 @@
 //!         ├── balance_deltas   → TokenLedger
 //!         ├── supply_deltas    → PATToken.current_supply / total_burned
 //!         ├── allowance_updates → AllowanceTable
-//!         ├── token_mutations  → PATToken fields
+//!         ├── token_mutations  → PATToken fields / ComplianceState
 //!         └── events           → event log
 //! ```
 
+use crate::compliance::{CompliancePolicy, ComplianceState};
 use crate::engine::{PATEngine, RegistryView};
 use crate::error::{PATError, PATResult};
 use crate::gas_model::PATGasModel;
@@ -37,6 +38,8 @@ pub struct PATRegistry {
     pub tokens:      BTreeMap<String, PATToken>,
     pub ledgers:     BTreeMap<String, TokenLedger>,
     pub allowances:  BTreeMap<String, AllowanceTable>,
+    /// Per-token compliance policy, allow-list root and frozen holders.
+    pub compliance:  BTreeMap<String, ComplianceState>,
     pub events:      Vec<PATEvent>,
     /// Set of executed intent hashes — prevents replay within this session.
     seen_intents:    HashSet<[u8; 32]>,
@@ -49,6 +52,7 @@ impl PATRegistry {
             tokens:       BTreeMap::new(),
             ledgers:      BTreeMap::new(),
             allowances:   BTreeMap::new(),
+            compliance:   BTreeMap::new(),
             events:       Vec::new(),
             seen_intents: HashSet::new(),
             engine:       PATEngine::new(),
@@ -96,6 +100,7 @@ impl PATRegistry {
             tokens:     &self.tokens,
             ledgers:    &self.ledgers,
             allowances: &self.allowances,
+            compliance: &self.compliance,
         };
 
         let outcome = self.engine.execute(intent, &view)?;
@@ -120,6 +125,10 @@ impl PATRegistry {
             self.tokens.insert(i.symbol.clone(), token);
             self.ledgers.insert(i.symbol.clone(), TokenLedger::default());
             self.allowances.insert(i.symbol.clone(), AllowanceTable::default());
+            self.compliance.insert(i.symbol.clone(), ComplianceState::new(CompliancePolicy {
+                account_freezing:    i.freezable,
+                allow_list_required: false,
+            }));
         }
 
         self.apply_diff(&outcome.diff)?;
@@ -199,6 +208,16 @@ impl PATRegistry {
                         t.recompute_hash();
                     }
                 }
+                TokenMutation::SetAccountFrozen { symbol, account, frozen } => {
+                    self.compliance.entry(symbol.clone()).or_default()
+                        .set_frozen(account, *frozen);
+                }
+                TokenMutation::SetCompliancePolicy { symbol, policy } => {
+                    self.compliance.entry(symbol.clone()).or_default().policy = *policy;
+                }
+                TokenMutation::SetAllowListRoot { symbol, root } => {
+                    self.compliance.entry(symbol.clone()).or_default().allow_list_root = Some(*root);
+                }
                 TokenMutation::CreateToken { .. } => {
                     // Handled above before apply_diff is called
                 }
@@ -230,6 +249,60 @@ impl PATRegistry {
             .unwrap_or(0)
     }
 
+    pub fn compliance(&self, symbol: &str) -> Option<&ComplianceState> {
+        self.compliance.get(symbol)
+    }
+
+    pub fn is_account_frozen(&self, symbol: &str, account: &crate::intent::Address) -> bool {
+        self.compliance.get(symbol).map_or(false, |c| c.is_frozen(account))
+    }
+
+    // ── Governance escape hatch ───────────────────────────────────────────────
+
+    /// Force-unfreeze `account` on behalf of an executed governance proposal.
+    ///
+    /// Bypasses the owner check and the `account_freezing` policy so holders
+    /// are never stuck behind an issuer that has disappeared.  The caller
+    /// (governance executor) is responsible for only invoking this after the
+    /// proposal has reached `Executed`.
+    pub fn governance_unfreeze(
+        &mut self,
+        symbol:      &str,
+        account:     &crate::intent::Address,
+        proposal_id: &str,
+    ) -> PATResult<()> {
+        if !self.tokens.contains_key(symbol) {
+            return Err(PATError::TokenNotFound(symbol.to_string()));
+        }
+        if !self.is_account_frozen(symbol, account) {
+            return Err(PATError::GovernanceRejected(format!(
+                "account {} is not frozen for '{}'", hex::encode(account), symbol
+            )));
+        }
+
+        let mut diff = PATStateDiff::empty();
+        diff.token_mutations.push(TokenMutation::SetAccountFrozen {
+            symbol:  symbol.to_string(),
+            account: *account,
+            frozen:  false,
+        });
+        diff.events.push(PATEvent::AccountFrozen {
+            symbol:      symbol.to_string(),
+            account:     *account,
+            frozen:      false,
+            proposal_id: Some(proposal_id.to_string()),
+            ts:          std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        });
+        diff.finalise();
+        self.apply_diff(&diff)?;
+
+        info!("[PATRegistry] governance proposal {} unfroze {} on {}", proposal_id, hex::encode(account), symbol);
+        Ok(())
+    }
+
     pub fn get_token(&self, symbol: &str) -> Option<&PATToken> {
         self.tokens.get(symbol)
     }
@@ -260,6 +333,9 @@ impl PATRegistry {
             PATIntentKind::Freeze(i)            => &i.symbol,
             PATIntentKind::UpdateBurnRate(i)    => &i.symbol,
             PATIntentKind::TransferOwnership(i) => &i.symbol,
+            PATIntentKind::FreezeAccount(i)     => &i.symbol,
+            PATIntentKind::SetCompliancePolicy(i) => &i.symbol,
+            PATIntentKind::SetAllowListRoot(i)  => &i.symbol,
         }
     }
 }
@@ -19,6 +19,7 @@
 //! If execution fails at any point, the diff is discarded — no partial
 //! state mutations ever reach the registry.
 
+use crate::compliance::CompliancePolicy;
 use crate::intent::Address;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -93,6 +94,25 @@ pub enum PATEvent {
         new_owner: Address,
         ts:        u64,
     },
+    AccountFrozen {
+        symbol:        String,
+        account:       Address,
+        frozen:        bool,
+        /// `Some(id)` when forced by an executed governance proposal.
+        proposal_id:   Option<String>,
+        ts:            u64,
+    },
+    CompliancePolicyUpdated {
+        symbol:              String,
+        account_freezing:    bool,
+        allow_list_required: bool,
+        ts:                  u64,
+    },
+    AllowListRootUpdated {
+        symbol: String,
+        root:   [u8; 32],
+        ts:     u64,
+    },
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -134,6 +154,9 @@ pub enum TokenMutation {
     SetFrozen    { symbol: String, frozen: bool },
     SetBurnRate  { symbol: String, new_bps: u16 },
     SetOwner     { symbol: String, new_owner: Address },
+    SetAccountFrozen    { symbol: String, account: Address, frozen: bool },
+    SetCompliancePolicy { symbol: String, policy: CompliancePolicy },
+    SetAllowListRoot    { symbol: String, root: [u8; 32] },
     CreateToken  { symbol: String },   // signal to apply initial token state
 }
 
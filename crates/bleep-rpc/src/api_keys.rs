@@ -0,0 +1,359 @@
+//! # RPC API keys
+//!
+//! Enforcement and management endpoints for per-consumer API keys backed by
+//! `bleep_auth::ApiKeyRegistry`.  Enforcement is off unless a registry is
+//! attached with `RpcState::with_api_keys`.
+//!
+//! - Keys are presented in the `x-api-key` header or the `api_key` query
+//!   parameter.
+//! - Every `/rpc/*` route is classified into a `RouteGroup`.  `/rpc/health`,
+//!   `/rpc/ready` and `/rpc/admin/*` are exempt.
+//! - Unknown, expired and revoked keys get 401, a key calling a group it
+//!   was not granted gets 403, and an exhausted quota or burst limit gets
+//!   429 with `x-ratelimit-*` headers.
+//! - `POST /rpc/tx/submit-batch` counts once per transaction in the batch;
+//!   the guard only checks the key and the handler charges the items.
+//!
+//! Management endpoints need the node's admin token in `x-admin-token`:
+//!
+//! - `POST   /rpc/admin/keys`           — create a key; the plaintext is returned once
+//! - `GET    /rpc/admin/keys`           — list keys
+//! - `DELETE /rpc/admin/keys/{id}`      — revoke a key
+//! - `GET    /rpc/admin/keys/{id}/usage` — hourly usage counters
+
+use std::sync::Arc;
+
+use warp::http::{Method, StatusCode};
+use warp::path::FullPath;
+use warp::{Filter, Rejection, Reply};
+
+use bleep_auth::api_keys::{ApiKeyError, ApiKeySpec, RouteGroup};
+use bleep_crypto::Sensitive;
+
+use crate::tx_batch::SUBMIT_BATCH_PATH;
+use crate::{with_arc_state, ErrResp, RpcState};
+
+/// Header carrying the API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// Query parameter carrying the API key.
+pub const API_KEY_QUERY: &str = "api_key";
+/// Header carrying the admin token for `/rpc/admin/*`.
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+/// Route group of a request, or `None` if it needs no key.
+pub fn route_group(method: &Method, path: &str) -> Option<RouteGroup> {
+    if !path.starts_with("/rpc/") || path == "/rpc/health" || path == "/rpc/ready" || path.starts_with("/rpc/admin") {
+        return None;
+    }
+    if method == Method::GET || method == Method::HEAD {
+        Some(RouteGroup::Read)
+    } else if path == "/rpc/tx" || path.starts_with("/rpc/tx/") {
+        Some(RouteGroup::Tx)
+    } else {
+        Some(RouteGroup::Write)
+    }
+}
+
+fn query_key(raw: &str) -> Option<String> {
+    raw.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == API_KEY_QUERY)
+        .map(|(_, v)| v.to_string())
+}
+
+/// Extracts the key presented in the header or the query string.
+pub(crate) fn presented_key() -> impl Filter<Extract = (Option<Sensitive<String>>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>(API_KEY_HEADER)
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .map(|header: Option<String>, query: String| header.or_else(|| query_key(&query)).map(Sensitive::new))
+}
+
+// ── Enforcement ───────────────────────────────────────────────────────────────
+
+#[derive(Debug)]
+struct ApiKeyRejection(ApiKeyError);
+
+impl warp::reject::Reject for ApiKeyRejection {}
+
+/// Admits the request or rejects it with an `ApiKeyRejection`.
+/// Put in front of the route tree and pair with `recover_api_key`.
+pub fn api_key_guard(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::method()
+        .and(warp::path::full())
+        .and(presented_key())
+        .and(with_arc_state(state))
+        .and_then(|method: Method, path: FullPath, key: Option<Sensitive<String>>, st: Arc<RpcState>| async move {
+            let (Some(registry), Some(group)) = (&st.api_keys, route_group(&method, path.as_str())) else {
+                return Ok(());
+            };
+            // Batches are charged per item by their handler.
+            let units = if method == Method::POST && path.as_str() == SUBMIT_BATCH_PATH { 0 } else { 1 };
+            registry.authorize_units(key.as_ref().map(|k| k.expose().as_str()), group, units)
+                .map(|_| ())
+                .map_err(|e| warp::reject::custom(ApiKeyRejection(e)))
+        })
+        .untuple_one()
+}
+
+/// Turn an `ApiKeyRejection` into its HTTP response; pass others through.
+pub async fn recover_api_key(err: Rejection) -> Result<warp::reply::Response, Rejection> {
+    match err.find::<ApiKeyRejection>() {
+        Some(ApiKeyRejection(e)) => Ok(error_response(e)),
+        None => Err(err),
+    }
+}
+
+pub(crate) fn error_response(e: &ApiKeyError) -> warp::reply::Response {
+    let status = StatusCode::from_u16(e.http_status()).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let mut builder = warp::http::Response::builder()
+        .status(status)
+        .header("content-type", "application/json");
+    let body = match e {
+        ApiKeyError::QuotaExhausted { limit, used, reset_at } => {
+            builder = builder
+                .header("x-ratelimit-limit", limit.to_string())
+                .header("x-ratelimit-remaining", "0")
+                .header("x-ratelimit-reset", reset_at.to_string());
+            serde_json::json!({
+                "error":    e.to_string(),
+                "quota":    limit,
+                "used":     used,
+                "reset_at": reset_at,
+            })
+        }
+        ApiKeyError::BurstExceeded { limit, retry_after_secs } => {
+            builder = builder.header("retry-after", retry_after_secs.to_string());
+            serde_json::json!({ "error": e.to_string(), "burst_per_sec": limit })
+        }
+        _ => serde_json::json!({ "error": e.to_string() }),
+    };
+    builder
+        .body(body.to_string().into())
+        .unwrap_or_else(|_| warp::reply::Response::new(Default::default()))
+}
+
+// ── Admin endpoints ───────────────────────────────────────────────────────────
+
+type AdminReply = Box<dyn Reply + Send>;
+
+fn reply(value: &impl serde::Serialize, status: StatusCode) -> AdminReply {
+    Box::new(warp::reply::with_status(warp::reply::json(value), status))
+}
+
+/// Whether `token` matches the node's configured admin token.
+pub(crate) fn admin_token_ok(st: &RpcState, token: Option<&str>) -> bool {
+    matches!((&st.admin_token, token), (Some(expected), Some(given)) if !expected.expose().is_empty() && expected.expose() == given)
+}
+
+/// Check the admin token; on failure return the reply to send instead.
+fn admin_check(st: &RpcState, token: Option<String>) -> Result<Arc<bleep_auth::ApiKeyRegistry>, AdminReply> {
+    let registry = st.api_keys.clone().ok_or_else(|| {
+        reply(&ErrResp { error: "API key registry not attached".into() }, StatusCode::SERVICE_UNAVAILABLE)
+    })?;
+    if admin_token_ok(st, token.as_deref()) {
+        Ok(registry)
+    } else {
+        Err(reply(&ErrResp { error: "admin token required".into() }, StatusCode::UNAUTHORIZED))
+    }
+}
+
+fn admin_result<T: serde::Serialize>(res: Result<T, ApiKeyError>, ok: StatusCode) -> AdminReply {
+    match res {
+        Ok(v) => reply(&v, ok),
+        Err(e) => Box::new(error_response(&e)),
+    }
+}
+
+/// All `/rpc/admin/keys` routes.
+pub fn admin_key_routes(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let token = warp::header::optional::<String>(ADMIN_TOKEN_HEADER);
+
+    // POST /rpc/admin/keys
+    let create = warp::path!("rpc" / "admin" / "keys")
+        .and(warp::post())
+        .and(token)
+        .and(warp::body::json::<ApiKeySpec>())
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|tok: Option<String>, spec: ApiKeySpec, st: Arc<RpcState>| {
+            match admin_check(&st, tok) {
+                Ok(reg) => {
+                    let params = serde_json::json!({ "route": "keys/create", "spec": &spec });
+                    let res = reg.create(spec);
+                    st.audit_admin("admin", &params, res.is_ok());
+                    admin_result(res, StatusCode::CREATED)
+                }
+                Err(r) => r,
+            }
+        });
+
+    // GET /rpc/admin/keys
+    let list = warp::path!("rpc" / "admin" / "keys")
+        .and(warp::get())
+        .and(token)
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|tok: Option<String>, st: Arc<RpcState>| {
+            match admin_check(&st, tok) {
+                Ok(reg) => reply(&reg.list(), StatusCode::OK),
+                Err(r) => r,
+            }
+        });
+
+    // DELETE /rpc/admin/keys/{id}
+    let revoke = warp::path!("rpc" / "admin" / "keys" / String)
+        .and(warp::delete())
+        .and(token)
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|id: String, tok: Option<String>, st: Arc<RpcState>| {
+            match admin_check(&st, tok) {
+                Ok(reg) => {
+                    let res = reg.revoke(&id);
+                    st.audit_admin("admin", &serde_json::json!({ "route": "keys/revoke", "id": &id }), res.is_ok());
+                    admin_result(res, StatusCode::OK)
+                }
+                Err(r) => r,
+            }
+        });
+
+    // GET /rpc/admin/keys/{id}/usage
+    let usage = warp::path!("rpc" / "admin" / "keys" / String / "usage")
+        .and(warp::get())
+        .and(token)
+        .and(with_arc_state(state))
+        .map(|id: String, tok: Option<String>, st: Arc<RpcState>| {
+            match admin_check(&st, tok) {
+                Ok(reg) => admin_result(reg.usage(&id), StatusCode::OK),
+                Err(r) => r,
+            }
+        });
+
+    create.or(list).unify().or(revoke).unify().or(usage).unify()
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_auth::api_keys::{ApiKeyRegistry, Clock, IssuedApiKey, ManualClock};
+
+    const T0: u64 = 1_704_103_200;
+    const ADMIN: &str = "admin-secret";
+
+    fn state(clock: &Arc<ManualClock>) -> RpcState {
+        let registry = ApiKeyRegistry::new().with_clock(Arc::clone(clock) as Arc<dyn Clock>);
+        RpcState::new().with_api_keys(Arc::new(registry), ADMIN.into())
+    }
+
+    async fn create_key(st: &RpcState, groups: &str, quota: u64) -> IssuedApiKey {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/admin/keys")
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .body(format!(r#"{{"name":"team","groups":{},"daily_quota":{}}}"#, groups, quota))
+            .reply(&crate::rpc_routes_with_state(st.clone()))
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        IssuedApiKey {
+            key:    body["key"].as_str().unwrap().to_string(),
+            record: serde_json::from_value(body["record"].clone()).unwrap(),
+        }
+    }
+
+    #[test]
+    fn routes_are_classified() {
+        assert_eq!(route_group(&Method::GET, "/rpc/state/abc"), Some(RouteGroup::Read));
+        assert_eq!(route_group(&Method::POST, "/rpc/tx"), Some(RouteGroup::Tx));
+        assert_eq!(route_group(&Method::POST, "/rpc/tx/simulate"), Some(RouteGroup::Tx));
+        assert_eq!(route_group(&Method::POST, "/rpc/pat/mint"), Some(RouteGroup::Write));
+        assert_eq!(route_group(&Method::GET, "/rpc/health"), None);
+        assert_eq!(route_group(&Method::GET, "/rpc/ready"), None);
+        assert_eq!(route_group(&Method::POST, "/rpc/admin/keys"), None);
+        assert_eq!(route_group(&Method::GET, "/explorer"), None);
+    }
+
+    #[tokio::test]
+    async fn read_key_rejected_on_tx_submit() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let st = state(&clock);
+        let issued = create_key(&st, r#"["read"]"#, 0).await;
+        let routes = crate::rpc_routes_with_state(st);
+
+        let res = warp::test::request()
+            .path(&format!("/rpc/telemetry?{}={}", API_KEY_QUERY, issued.key))
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/tx")
+            .header(API_KEY_HEADER, &issued.key)
+            .body(r#"{"sender":"a","receiver":"b","amount":1,"timestamp":1,"signature":[]}"#)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = warp::test::request().path("/rpc/telemetry").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn quota_429_then_reset_and_usage_counts() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let st = state(&clock);
+        let issued = create_key(&st, r#"["read"]"#, 2).await;
+        let routes = crate::rpc_routes_with_state(st);
+        let get = || warp::test::request().path("/rpc/telemetry").header(API_KEY_HEADER, &issued.key);
+
+        assert_eq!(get().reply(&routes).await.status(), StatusCode::OK);
+        assert_eq!(get().reply(&routes).await.status(), StatusCode::OK);
+        let res = get().reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        let reset_at = (T0 / 86_400 + 1) * 86_400;
+        assert_eq!(res.headers()["x-ratelimit-reset"], reset_at.to_string().as_str());
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["quota"], 2);
+
+        clock.set(reset_at);
+        assert_eq!(get().reply(&routes).await.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .path(&format!("/rpc/admin/keys/{}/usage", issued.record.id))
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let usage: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(usage["total"], 3);
+        assert_eq!(usage["today"], 1);
+    }
+
+    #[tokio::test]
+    async fn revoked_key_fails_immediately() {
+        let clock = Arc::new(ManualClock::new(T0));
+        let st = state(&clock);
+        let issued = create_key(&st, r#"["read"]"#, 0).await;
+        let routes = crate::rpc_routes_with_state(st);
+
+        let res = warp::test::request()
+            .method("DELETE")
+            .path(&format!("/rpc/admin/keys/{}", issued.record.id))
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED, "admin token required");
+
+        let res = warp::test::request()
+            .method("DELETE")
+            .path(&format!("/rpc/admin/keys/{}", issued.record.id))
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = warp::test::request()
+            .path("/rpc/telemetry")
+            .header(API_KEY_HEADER, &issued.key)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
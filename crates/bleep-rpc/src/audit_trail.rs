@@ -0,0 +1,146 @@
+//! # Operator audit trail
+//!
+//! Read side of `bleep_auth::AuditTrail`, attached with
+//! `RpcState::with_audit_trail`.  Both endpoints need the node's admin token
+//! in `x-admin-token`:
+//!
+//! - `GET /rpc/admin/audit?from=&to=&action=&cursor=&limit=` — one page of
+//!   records; `from`/`to` are unix milliseconds, `action` is e.g.
+//!   `governance_execution`, and `next_cursor` fetches the next page
+//! - `GET /rpc/admin/audit/verify` — walk the chain and report the first break
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use bleep_auth::audit_trail::{AuditQuery, AuditTrail, AuditVerification};
+
+use crate::api_keys::{admin_token_ok, ADMIN_TOKEN_HEADER};
+use crate::{with_arc_state, ErrResp, RpcState};
+
+/// `GET /rpc/admin/audit/verify` response.
+#[derive(Serialize)]
+struct VerifyResp {
+    #[serde(flatten)]
+    verification: AuditVerification,
+    /// Records lost because the writer was overwhelmed.
+    dropped:      u64,
+}
+
+type AuditReply = warp::reply::WithStatus<warp::reply::Json>;
+
+fn err(msg: &str, status: StatusCode) -> AuditReply {
+    warp::reply::with_status(warp::reply::json(&ErrResp { error: msg.into() }), status)
+}
+
+/// Check the admin token and that a trail is attached.
+fn trail_for(st: &RpcState, token: Option<String>) -> Result<AuditTrail, AuditReply> {
+    if !admin_token_ok(st, token.as_deref()) {
+        return Err(err("admin token required", StatusCode::UNAUTHORIZED));
+    }
+    st.audit_trail.clone().ok_or_else(|| err("Audit trail not attached", StatusCode::SERVICE_UNAVAILABLE))
+}
+
+/// All `/rpc/admin/audit` routes.
+pub(crate) fn admin_audit_routes(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let token = warp::header::optional::<String>(ADMIN_TOKEN_HEADER);
+
+    // GET /rpc/admin/audit
+    let list = warp::path!("rpc" / "admin" / "audit")
+        .and(warp::get())
+        .and(token)
+        .and(warp::query::<AuditQuery>())
+        .and(with_arc_state(Arc::clone(&state)))
+        .and_then(|tok: Option<String>, q: AuditQuery, st: Arc<RpcState>| async move {
+            let trail = match trail_for(&st, tok) {
+                Ok(t) => t,
+                Err(r) => return Ok::<_, Rejection>(r),
+            };
+            let page = tokio::task::spawn_blocking(move || trail.store().query(&q)).await;
+            Ok(match page {
+                Ok(Ok(page)) => warp::reply::with_status(warp::reply::json(&page), StatusCode::OK),
+                Ok(Err(e)) => err(&e, StatusCode::INTERNAL_SERVER_ERROR),
+                Err(e) => err(&e.to_string(), StatusCode::INTERNAL_SERVER_ERROR),
+            })
+        });
+
+    // GET /rpc/admin/audit/verify
+    let verify = warp::path!("rpc" / "admin" / "audit" / "verify")
+        .and(warp::get())
+        .and(token)
+        .and(with_arc_state(state))
+        .and_then(|tok: Option<String>, st: Arc<RpcState>| async move {
+            let trail = match trail_for(&st, tok) {
+                Ok(t) => t,
+                Err(r) => return Ok::<_, Rejection>(r),
+            };
+            let dropped = trail.dropped();
+            let result = tokio::task::spawn_blocking(move || trail.store().verify()).await;
+            Ok(match result {
+                Ok(Ok(verification)) => warp::reply::with_status(
+                    warp::reply::json(&VerifyResp { verification, dropped }),
+                    StatusCode::OK,
+                ),
+                Ok(Err(e)) => err(&e, StatusCode::INTERNAL_SERVER_ERROR),
+                Err(e) => err(&e.to_string(), StatusCode::INTERNAL_SERVER_ERROR),
+            })
+        });
+
+    verify.or(list).unify()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_auth::api_keys::ApiKeyRegistry;
+    use bleep_auth::audit_trail::{AuditTrailStore, AUDIT_CHANNEL_CAPACITY};
+
+    const ADMIN: &str = "admin-secret";
+
+    #[tokio::test]
+    async fn admin_calls_are_audited_and_listed() {
+        let trail = AuditTrail::spawn(Arc::new(AuditTrailStore::new()), AUDIT_CHANNEL_CAPACITY);
+        let st = RpcState::new()
+            .with_api_keys(Arc::new(ApiKeyRegistry::new()), ADMIN.into())
+            .with_audit_trail(trail.clone());
+        let routes = crate::rpc_routes_with_state(st);
+
+        let res = warp::test::request().path("/rpc/admin/audit").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/admin/keys")
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .body(r#"{"name":"team","groups":["read"]}"#)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+        trail.flush().await;
+
+        let res = warp::test::request()
+            .path("/rpc/admin/audit?action=admin_rpc&limit=10")
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["records"].as_array().unwrap().len(), 1);
+        assert_eq!(body["records"][0]["actor"], "admin");
+        assert_eq!(body["records"][0]["outcome"], "success");
+
+        let res = warp::test::request()
+            .path("/rpc/admin/audit/verify")
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["records"], 1);
+        assert!(body["first_break"].is_null());
+        assert_eq!(body["dropped"], 0);
+    }
+}
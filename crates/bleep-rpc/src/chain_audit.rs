@@ -0,0 +1,94 @@
+//! # Chain audit status
+//!
+//! - `GET /rpc/admin/audit-status` — what the background `ChainAuditor`
+//!   attached with `RpcState::with_chain_auditor` has covered: window,
+//!   last fully audited height, coverage of the current sweep, CPU share,
+//!   whether production is halted, and recent findings
+//!
+//! Needs the `x-admin-token` header; 503 without an auditor.
+
+use std::sync::Arc;
+
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use crate::api_keys::{admin_token_ok, ADMIN_TOKEN_HEADER};
+use crate::{with_arc_state, ErrResp, RpcState};
+
+fn err(msg: &str, status: StatusCode) -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(warp::reply::json(&ErrResp { error: msg.into() }), status)
+}
+
+// ── GET /rpc/admin/audit-status ───────────────────────────────────────────────
+pub(crate) fn audit_status(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("rpc" / "admin" / "audit-status")
+        .and(warp::get())
+        .and(warp::header::optional::<String>(ADMIN_TOKEN_HEADER))
+        .and(with_arc_state(state))
+        .map(|tok: Option<String>, st: Arc<RpcState>| {
+            if !admin_token_ok(&st, tok.as_deref()) {
+                return err("admin token required", StatusCode::UNAUTHORIZED);
+            }
+            match &st.chain_auditor {
+                Some(auditor) => warp::reply::with_status(warp::reply::json(&auditor.status()), StatusCode::OK),
+                None => err("Chain auditor not attached", StatusCode::SERVICE_UNAVAILABLE),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_auth::api_keys::ApiKeyRegistry;
+    use bleep_consensus::block_store::{BlockStore, StoredBlock};
+    use bleep_consensus::chain_audit::{AuditConfig, ChainAuditor};
+    use bleep_core::block::Block;
+    use bleep_state::state_manager::StateManager;
+    use parking_lot::Mutex;
+
+    const ADMIN: &str = "admin-secret";
+
+    fn admin_state() -> RpcState {
+        RpcState::new().with_api_keys(Arc::new(ApiKeyRegistry::new()), ADMIN.into())
+    }
+
+    #[tokio::test]
+    async fn reports_coverage_after_a_sweep() {
+        let routes = audit_status(Arc::new(admin_state()));
+        let res = warp::test::request().path("/rpc/admin/audit-status").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = warp::test::request()
+            .path("/rpc/admin/audit-status")
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let dir = std::env::temp_dir().join(format!("bleep-rpc-audit-{}", std::process::id()));
+        let store = Arc::new(BlockStore::open(&dir).unwrap());
+        let genesis = Block::genesis();
+        store.put(&StoredBlock::adopted(Block::new(1, vec![], genesis.compute_hash()), 0)).unwrap();
+        let auditor = Arc::new(ChainAuditor::new(
+            store,
+            Arc::new(Mutex::new(StateManager::new())),
+            AuditConfig { cpu_budget: 1.0, ..AuditConfig::default() },
+        ));
+        assert!(auditor.sweep().await.is_empty());
+
+        let routes = audit_status(Arc::new(admin_state().with_chain_auditor(auditor)));
+        let res = warp::test::request()
+            .path("/rpc/admin/audit-status")
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["last_audited_height"], 1);
+        assert_eq!(body["coverage"], 1.0);
+        assert_eq!(body["halted"], false);
+        assert_eq!(body["findings"].as_array().unwrap().len(), 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
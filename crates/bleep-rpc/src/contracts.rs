@@ -0,0 +1,359 @@
+//! # Contract queries
+//!
+//! `GET /rpc/contract/{address}/upgrades` — current code hash, version and
+//! every upgrade or rollback of a contract in the attached
+//! `bleep_vm::ContractRegistry`, oldest first.  Hashes are hex.
+//!
+//! `GET /rpc/contract/{address}/storage/{key}[?at_block=]` — one storage
+//! slot of the contract's state account, live or at a past height.  Key and
+//! value are hex; an unset slot has `value: null`.
+//!
+//! `GET /rpc/contract/{address}/events[?decode=true]` — the contract's event
+//! log, oldest first, with topics and data in hex.  With `decode=true` each
+//! event is also decoded against the contract's event schema into `name`
+//! and a `fields` map; events the schema cannot decode carry a `warning`
+//! instead.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use warp::http::StatusCode;
+use warp::Filter;
+
+use bleep_vm::execution::state_transition::EmittedEvent;
+use bleep_vm::{DecodedEvent, EventSchema, UpgradeAuthority, UpgradeKind, UpgradeRecord};
+
+use bleep_state::state_archive::StateView;
+
+use crate::{state_error_status, with_arc_state, AtBlockQuery, ErrResp, RpcState};
+
+#[derive(Serialize)]
+struct UpgradeEntry {
+    version:          u32,
+    kind:             UpgradeKind,
+    from_code_hash:   String,
+    to_code_hash:     String,
+    /// `governance` or the admin account, hex.
+    authority:        String,
+    migrate_gas_used: u64,
+}
+
+impl From<UpgradeRecord> for UpgradeEntry {
+    fn from(r: UpgradeRecord) -> Self {
+        UpgradeEntry {
+            version:          r.version,
+            kind:             r.kind,
+            from_code_hash:   hex::encode(r.from_code_hash),
+            to_code_hash:     hex::encode(r.to_code_hash),
+            authority:        authority_str(&r.authority),
+            migrate_gas_used: r.migrate_gas_used,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct UpgradesResp {
+    address:     String,
+    code_hash:   String,
+    version:     u32,
+    upgradeable: bool,
+    admin:       Option<String>,
+    upgrades:    Vec<UpgradeEntry>,
+}
+
+fn authority_str(a: &UpgradeAuthority) -> String {
+    match a {
+        UpgradeAuthority::Account(addr) => hex::encode(addr),
+        UpgradeAuthority::Governance => "governance".into(),
+    }
+}
+
+// ── GET /rpc/contract/{address}/upgrades ──────────────────────────────────────
+pub(crate) fn contract_upgrades(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "contract" / String / "upgrades")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|address: String, st: Arc<RpcState>| {
+            let err = |msg: String, status| {
+                warp::reply::with_status(warp::reply::json(&ErrResp { error: msg }), status)
+            };
+            let registry = match &st.contract_registry {
+                Some(r) => r,
+                None => return err("Contract registry not attached".into(), StatusCode::SERVICE_UNAVAILABLE),
+            };
+            let addr = match crate::hex_to_address(&address) {
+                Ok(a) => a,
+                Err(e) => return err(e, StatusCode::BAD_REQUEST),
+            };
+            let (info, history) = match (registry.info(&addr), registry.history(&addr)) {
+                (Some(info), Ok(history)) => (info, history),
+                _ => return err(format!("No contract at {}", address), StatusCode::NOT_FOUND),
+            };
+            warp::reply::with_status(
+                warp::reply::json(&UpgradesResp {
+                    address:     hex::encode(info.address),
+                    code_hash:   hex::encode(info.code_hash),
+                    version:     info.version,
+                    upgradeable: info.upgradeable,
+                    admin:       info.admin.as_ref().map(authority_str),
+                    upgrades:    history.into_iter().map(UpgradeEntry::from).collect(),
+                }),
+                StatusCode::OK,
+            )
+        })
+}
+
+#[derive(Serialize)]
+struct StorageResp {
+    address:      String,
+    key:          String,
+    value:        Option<String>,
+    block_height: u64,
+}
+
+// ── GET /rpc/contract/{address}/storage/{key} ─────────────────────────────────
+pub(crate) fn contract_storage(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "contract" / String / "storage" / String)
+        .and(warp::get())
+        .and(warp::query::<AtBlockQuery>())
+        .and(with_arc_state(state))
+        .map(|address: String, key: String, q: AtBlockQuery, st: Arc<RpcState>| {
+            let err = |msg: String, status| {
+                warp::reply::with_status(warp::reply::json(&ErrResp { error: msg }), status)
+            };
+            let mgr = match &st.state_mgr {
+                Some(m) => m,
+                None => return err("StateManager not attached".into(), StatusCode::SERVICE_UNAVAILABLE),
+            };
+            let slot = match hex::decode(key.trim_start_matches("0x")) {
+                Ok(k) => k,
+                Err(e) => return err(format!("Invalid storage key hex: {}", e), StatusCode::BAD_REQUEST),
+            };
+            let mgr = mgr.lock();
+            let height = q.at_block.unwrap_or_else(|| mgr.block_height());
+            let read = StateView::at_height(&mgr, height)
+                .and_then(|view| view.get_storage(&address, &slot));
+            drop(mgr);
+            match read {
+                Ok(value) => warp::reply::with_status(
+                    warp::reply::json(&StorageResp {
+                        address,
+                        key:   hex::encode(&slot),
+                        value: value.map(hex::encode),
+                        block_height: height,
+                    }),
+                    StatusCode::OK,
+                ),
+                Err(e) => err(e.to_string(), state_error_status(&e)),
+            }
+        })
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    #[serde(default)]
+    decode: bool,
+}
+
+#[derive(Serialize)]
+struct EventEntry {
+    log_index: u32,
+    topics:    Vec<String>,
+    data:      String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    decoded:   Option<DecodedEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning:   Option<String>,
+}
+
+impl EventEntry {
+    fn new(ev: &EmittedEvent, schema: Option<&EventSchema>) -> Self {
+        let (decoded, warning) = match schema.map(|s| s.decode(ev)) {
+            None => (None, None),
+            Some(Ok(d)) => (Some(d), None),
+            Some(Err(w)) => (None, Some(w)),
+        };
+        EventEntry {
+            log_index: ev.log_index,
+            topics:    ev.topics.iter().map(hex::encode).collect(),
+            data:      hex::encode(&ev.data),
+            decoded,
+            warning,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EventsResp {
+    address: String,
+    events:  Vec<EventEntry>,
+}
+
+// ── GET /rpc/contract/{address}/events ────────────────────────────────────────
+pub(crate) fn contract_events(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "contract" / String / "events")
+        .and(warp::get())
+        .and(warp::query::<EventsQuery>())
+        .and(with_arc_state(state))
+        .map(|address: String, q: EventsQuery, st: Arc<RpcState>| {
+            let err = |msg: String, status| {
+                warp::reply::with_status(warp::reply::json(&ErrResp { error: msg }), status)
+            };
+            let registry = match &st.contract_registry {
+                Some(r) => r,
+                None => return err("Contract registry not attached".into(), StatusCode::SERVICE_UNAVAILABLE),
+            };
+            let addr = match crate::hex_to_address(&address) {
+                Ok(a) => a,
+                Err(e) => return err(e, StatusCode::BAD_REQUEST),
+            };
+            let log = match registry.event_log(&addr) {
+                Ok(log) => log,
+                Err(_) => return err(format!("No contract at {}", address), StatusCode::NOT_FOUND),
+            };
+            let schema = if q.decode {
+                // No schema at all decodes every event to a warning.
+                Some(registry.event_schema(&addr).unwrap_or_default())
+            } else {
+                None
+            };
+            warp::reply::with_status(
+                warp::reply::json(&EventsResp {
+                    address: hex::encode(addr),
+                    events:  log.iter().map(|ev| EventEntry::new(ev, schema.as_ref())).collect(),
+                }),
+                StatusCode::OK,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_state::state_manager::StateManager;
+    use bleep_vm::ContractRegistry;
+    use parking_lot::Mutex;
+
+    #[tokio::test]
+    async fn unknown_contract_is_not_found() {
+        let st = RpcState::new().with_contract_registry(Arc::new(ContractRegistry::new()));
+        let routes = contract_upgrades(Arc::new(st));
+        let res = warp::test::request()
+            .path(&format!("/rpc/contract/{}/upgrades", hex::encode([9u8; 32])))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    /// Minimal contract whose `execute` emits one event, `name` at offset 0
+    /// and `data` at 256.
+    fn emitter(name: &str, data: &[u8]) -> Vec<u8> {
+        use wasm_encoder::*;
+        let mut types = TypeSection::new();
+        types.function([ValType::I32; 4], [] as [ValType; 0]);
+        types.function([ValType::I32], [ValType::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", "emit_event", EntityType::Function(0));
+        let mut funcs = FunctionSection::new();
+        funcs.function(1);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export("execute", ExportKind::Func, 1);
+        let mut exec = Function::new([] as [(u32, ValType); 0]);
+        for i in [
+            Instruction::I32Const(0), Instruction::I32Const(name.len() as i32),
+            Instruction::I32Const(256), Instruction::I32Const(data.len() as i32),
+            Instruction::Call(0), Instruction::I32Const(0), Instruction::End,
+        ] {
+            exec.instruction(&i);
+        }
+        let mut code = CodeSection::new();
+        code.function(&exec);
+        let mut segments = DataSection::new();
+        segments.active(0, &ConstExpr::i32_const(0), name.bytes());
+        segments.active(0, &ConstExpr::i32_const(256), data.iter().copied());
+        let mut module = Module::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code).section(&segments);
+        module.finish()
+    }
+
+    #[tokio::test]
+    async fn events_decode_against_schema() {
+        use bleep_vm::{EventBuilder, EventDef, FieldType};
+        let registry = Arc::new(ContractRegistry::new());
+        let schema = EventSchema::new(vec![
+            EventDef::new("Deposit").field("from", FieldType::Address).field("amount", FieldType::U64),
+        ]).unwrap();
+        let (name, data) = EventBuilder::new("Deposit").address([3u8; 32]).u64(42).build();
+        let known = registry.deploy_with_schema(&emitter(&name, &data), false, None, None, Some(schema.clone()))
+            .unwrap();
+        registry.call(&known, &[], 1_000_000).unwrap();
+        let unknown = registry.deploy_with_schema(&emitter("Other", b"raw"), false, None, None, Some(schema))
+            .unwrap();
+        registry.call(&unknown, &[], 1_000_000).unwrap();
+        let routes = contract_events(Arc::new(RpcState::new().with_contract_registry(registry)));
+
+        let res = warp::test::request()
+            .path(&format!("/rpc/contract/{}/events?decode=true", hex::encode(known)))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        let decoded = &body["events"][0]["decoded"];
+        assert_eq!(decoded["name"], "Deposit");
+        assert_eq!(decoded["fields"]["from"], hex::encode([3u8; 32]));
+        assert_eq!(decoded["fields"]["amount"], 42);
+
+        let res = warp::test::request()
+            .path(&format!("/rpc/contract/{}/events?decode=true", hex::encode(unknown)))
+            .reply(&routes)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["events"][0]["data"], hex::encode(b"raw"));
+        assert!(body["events"][0]["decoded"].is_null());
+        assert!(body["events"][0]["warning"].as_str().unwrap().contains("unknown event"));
+
+        // Without `decode` only the raw log is returned.
+        let res = warp::test::request()
+            .path(&format!("/rpc/contract/{}/events", hex::encode(known)))
+            .reply(&routes)
+            .await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert!(body["events"][0]["decoded"].is_null());
+    }
+
+    #[tokio::test]
+    async fn storage_at_past_height() {
+        let mut mgr = StateManager::new();
+        mgr.set_history_retention(1);
+        mgr.set_storage("vault", b"slot", b"one");
+        mgr.advance_block();
+        mgr.set_storage("vault", b"slot", b"two");
+        mgr.advance_block();
+        let st = RpcState::new().with_state_manager(Arc::new(Mutex::new(mgr)));
+        let routes = contract_storage(Arc::new(st));
+        let path = format!("/rpc/contract/vault/storage/{}", hex::encode(b"slot"));
+
+        let res = warp::test::request().path(&path).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["value"], hex::encode(b"two"));
+
+        let res = warp::test::request().path(&format!("{}?at_block=1", path)).reply(&routes).await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["value"], hex::encode(b"one"));
+
+        // Height 0 is outside the journal and this node keeps no archive.
+        let res = warp::test::request().path(&format!("{}?at_block=0", path)).reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::GONE);
+    }
+}
@@ -0,0 +1,281 @@
+//! # Devnet tools
+//!
+//! - `POST /rpc/devnet/deploy` — deploy WASM code straight into the attached
+//!   `ContractRegistry`
+//! - `POST /rpc/devnet/call` — call a deployed contract's `execute` with
+//!   `{address, calldata, gas_limit?}` (hex); returns `{output, gas_used}`
+//!
+//! Contract deployment and calls have no transaction type yet, so local
+//! development networks (`bleep-cli devnet`) expose them here instead.  The route only
+//! exists on nodes built with `RpcState::with_devnet_tools`; everywhere
+//! else it answers 404.
+//!
+//! The deployer signs `deploy_payload(deployer, code)` in the `POST /rpc/tx`
+//! wire format, `pk(64) || sig`.  The contract address is salted with the
+//! deployer's nonce, which the deploy increments, so redeploying the same
+//! code yields a fresh contract.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use warp::http::StatusCode;
+use warp::Filter;
+
+use bleep_crypto::tx_signer::verify_tx_signature;
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+const DEPLOY_DOMAIN: &[u8] = b"bleep:devnet:deploy";
+/// SPHINCS+ public key prefix of the wire signature.
+const SIGNER_PK_LEN: usize = 64;
+
+/// What the deployer signs to deploy `code`.
+pub fn deploy_payload(deployer: &str, code: &[u8]) -> [u8; 32] {
+    let mut h = Sha3_256::new();
+    h.update(DEPLOY_DOMAIN);
+    h.update(deployer.as_bytes());
+    h.update(code);
+    h.finalize().into()
+}
+
+/// Salt of the deployer's `nonce`-th deployment.
+fn deploy_salt(deployer: &str, nonce: u64) -> [u8; 32] {
+    let mut h = Sha3_256::new();
+    h.update(deployer.as_bytes());
+    h.update(nonce.to_be_bytes());
+    h.finalize().into()
+}
+
+#[derive(Deserialize)]
+struct DeployReq {
+    deployer:  String,
+    /// WASM bytecode, hex.
+    code:      String,
+    signature: Vec<u8>,
+}
+
+#[derive(Serialize)]
+struct DeployResp {
+    address:   String,
+    code_hash: String,
+}
+
+// ── POST /rpc/devnet/deploy ───────────────────────────────────────────────────
+pub(crate) fn devnet_deploy(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "devnet" / "deploy")
+        .and(warp::post())
+        .and(warp::body::json::<DeployReq>())
+        .and(with_arc_state(state))
+        .map(|req: DeployReq, st: Arc<RpcState>| {
+            let err = |error: String, status| warp::reply::with_status(
+                warp::reply::json(&ErrResp { error }), status,
+            );
+            if !st.devnet_tools {
+                return err("Devnet tools are disabled on this node".into(), StatusCode::NOT_FOUND);
+            }
+            let (registry, state_mgr) = match (&st.contract_registry, &st.state_mgr) {
+                (Some(r), Some(s)) => (r, s),
+                _ => return err("Contract registry or state not attached".into(), StatusCode::SERVICE_UNAVAILABLE),
+            };
+            let code = match hex::decode(req.code.trim_start_matches("0x")) {
+                Ok(c) => c,
+                Err(e) => return err(format!("Invalid code hex: {}", e), StatusCode::BAD_REQUEST),
+            };
+            if req.signature.len() <= SIGNER_PK_LEN {
+                return err("Signature must be pk(64) || sig".into(), StatusCode::BAD_REQUEST);
+            }
+            let (pk, sig) = req.signature.split_at(SIGNER_PK_LEN);
+            if !verify_tx_signature(&deploy_payload(&req.deployer, &code), sig, pk) {
+                return err("Invalid deploy signature".into(), StatusCode::UNAUTHORIZED);
+            }
+
+            let mut mgr = state_mgr.lock();
+            let salt = deploy_salt(&req.deployer, mgr.get_nonce(&req.deployer));
+            let address = match registry.deploy(&code, false, None, Some(salt)) {
+                Ok(a) => a,
+                Err(e) => return err(format!("Deploy failed: {}", e), StatusCode::BAD_REQUEST),
+            };
+            let code_hash = registry.info(&address).map(|i| i.code_hash).unwrap_or_default();
+            mgr.set_code_hash(&hex::encode(address), code_hash);
+            mgr.increment_nonce(&req.deployer);
+            warp::reply::with_status(
+                warp::reply::json(&DeployResp {
+                    address:   hex::encode(address),
+                    code_hash: hex::encode(code_hash),
+                }),
+                StatusCode::OK,
+            )
+        })
+}
+
+/// Gas for a devnet call that does not name a limit.
+const DEFAULT_CALL_GAS: u64 = 10_000_000;
+
+#[derive(Deserialize)]
+struct CallReq {
+    /// Contract address, hex.
+    address:   String,
+    /// Calldata, hex.
+    #[serde(default)]
+    calldata:  String,
+    gas_limit: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct CallResp {
+    /// `execute`'s result, little-endian, hex.
+    output:   String,
+    gas_used: u64,
+}
+
+// ── POST /rpc/devnet/call ─────────────────────────────────────────────────────
+pub(crate) fn devnet_call(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "devnet" / "call")
+        .and(warp::post())
+        .and(warp::body::json::<CallReq>())
+        .and(with_arc_state(state))
+        .map(|req: CallReq, st: Arc<RpcState>| {
+            let err = |error: String, status| warp::reply::with_status(
+                warp::reply::json(&ErrResp { error }), status,
+            );
+            if !st.devnet_tools {
+                return err("Devnet tools are disabled on this node".into(), StatusCode::NOT_FOUND);
+            }
+            let Some(registry) = &st.contract_registry else {
+                return err("Contract registry not attached".into(), StatusCode::SERVICE_UNAVAILABLE);
+            };
+            let address: [u8; 32] = match hex::decode(req.address.trim_start_matches("0x")).ok()
+                .and_then(|a| a.try_into().ok())
+            {
+                Some(a) => a,
+                None => return err("Address must be 32 bytes of hex".into(), StatusCode::BAD_REQUEST),
+            };
+            let calldata = match hex::decode(req.calldata.trim_start_matches("0x")) {
+                Ok(c) => c,
+                Err(e) => return err(format!("Invalid calldata hex: {}", e), StatusCode::BAD_REQUEST),
+            };
+            match registry.call(&address, &calldata, req.gas_limit.unwrap_or(DEFAULT_CALL_GAS)) {
+                Ok(out) => warp::reply::with_status(
+                    warp::reply::json(&CallResp { output: hex::encode(out.output), gas_used: out.gas_used }),
+                    StatusCode::OK,
+                ),
+                Err(e) => err(format!("Call failed: {}", e), StatusCode::BAD_REQUEST),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload};
+    use bleep_state::state_manager::StateManager;
+    use bleep_vm::ContractRegistry;
+    use parking_lot::Mutex;
+
+    const EMPTY_MODULE: &[u8] = b"\0asm\x01\0\0\0";
+
+    fn signed(deployer: &str, code: &[u8]) -> Vec<u8> {
+        let (pk, sk) = generate_tx_keypair();
+        let mut wire = pk;
+        wire.extend(sign_tx_payload(&deploy_payload(deployer, code), &sk).unwrap());
+        wire
+    }
+
+    fn request(deployer: &str, code: &[u8], signature: Vec<u8>) -> serde_json::Value {
+        serde_json::json!({ "deployer": deployer, "code": hex::encode(code), "signature": signature })
+    }
+
+    #[tokio::test]
+    async fn deploy_needs_devnet_tools_and_a_valid_signature() {
+        let base = RpcState::new()
+            .with_state_manager(Arc::new(Mutex::new(StateManager::new())))
+            .with_contract_registry(Arc::new(ContractRegistry::new()));
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/devnet/deploy")
+            .json(&request("dev", EMPTY_MODULE, signed("dev", EMPTY_MODULE)))
+            .reply(&devnet_deploy(Arc::new(base.clone())))
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let route = devnet_deploy(Arc::new(base.with_devnet_tools()));
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/devnet/deploy")
+            .json(&request("dev", EMPTY_MODULE, signed("other", EMPTY_MODULE)))
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        // The same code deploys twice, at two addresses.
+        let mut addresses = Vec::new();
+        for _ in 0..2 {
+            let res = warp::test::request()
+                .method("POST")
+                .path("/rpc/devnet/deploy")
+                .json(&request("dev", EMPTY_MODULE, signed("dev", EMPTY_MODULE)))
+                .reply(&route)
+                .await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let json: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+            addresses.push(json["address"].as_str().unwrap().to_string());
+        }
+        assert_ne!(addresses[0], addresses[1]);
+    }
+
+    /// Contract whose `execute` returns its calldata length.
+    fn length_echo() -> Vec<u8> {
+        use wasm_encoder::*;
+        let mut types = TypeSection::new();
+        types.function([ValType::I32], [ValType::I32]);
+        let mut funcs = FunctionSection::new();
+        funcs.function(0);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export("execute", ExportKind::Func, 0);
+        let mut exec = Function::new([] as [(u32, ValType); 0]);
+        exec.instruction(&Instruction::LocalGet(0));
+        exec.instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&exec);
+        let mut module = Module::new();
+        module.section(&types).section(&funcs).section(&memory).section(&exports).section(&code);
+        module.finish()
+    }
+
+    #[tokio::test]
+    async fn call_runs_execute_on_a_deployed_contract() {
+        let registry = Arc::new(ContractRegistry::new());
+        let address = registry.deploy(&length_echo(), false, None, None).unwrap();
+        let route = devnet_call(Arc::new(
+            RpcState::new().with_contract_registry(Arc::clone(&registry)).with_devnet_tools(),
+        ));
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/devnet/call")
+            .json(&serde_json::json!({ "address": hex::encode(address), "calldata": "010203" }))
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let json: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(json["output"], hex::encode(3i32.to_le_bytes()));
+        assert!(json["gas_used"].is_u64());
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/devnet/call")
+            .json(&serde_json::json!({ "address": hex::encode([7u8; 32]) }))
+            .reply(&route)
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}
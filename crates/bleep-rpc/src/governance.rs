@@ -0,0 +1,195 @@
+//! # Governance proposal content
+//!
+//! - `GET /rpc/governance/proposal/{id}` — one proposal from the attached
+//!   `GovernanceEngine`, with its content rendered for display
+//!
+//! Inline descriptions are returned as-is.  For proposals that commit to
+//! off-chain text the response always carries the committed hash, size,
+//! MIME type and retrieval hints; the text itself is included only when the
+//! `ProposalContentResolver` fetched bytes that match the hash.  Otherwise
+//! `content_status` is `tampered` or `unavailable` (or `unresolved` when no
+//! resolver is attached) and `warning` says why.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use warp::http::StatusCode;
+use warp::Filter;
+
+use bleep_governance::{ContentCommitment, Proposal, ResolvedContent, RetrievalHint};
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+#[derive(Serialize)]
+struct CommitmentView {
+    hash:  String,
+    size:  u64,
+    mime:  String,
+    hints: Vec<String>,
+}
+
+impl From<&ContentCommitment> for CommitmentView {
+    fn from(c: &ContentCommitment) -> Self {
+        CommitmentView {
+            hash:  c.hash_hex(),
+            size:  c.size,
+            mime:  c.mime.clone(),
+            hints: c.hints.iter().map(RetrievalHint::to_string).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ProposalView {
+    id:             String,
+    title:          String,
+    state:          String,
+    description:    String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content:        Option<CommitmentView>,
+    /// `inline`, `verified`, `tampered`, `unavailable` or `unresolved`
+    content_status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text:           Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning:        Option<String>,
+}
+
+async fn render(st: &RpcState, proposal: Proposal) -> ProposalView {
+    let mut view = ProposalView {
+        id:             proposal.id.clone(),
+        title:          proposal.title.clone(),
+        state:          format!("{:?}", proposal.state),
+        description:    proposal.description.clone(),
+        content:        proposal.content.as_ref().map(CommitmentView::from),
+        content_status: "inline",
+        text:           None,
+        warning:        None,
+    };
+    let commitment = match &proposal.content {
+        Some(c) => c,
+        None => {
+            view.text = Some(proposal.description);
+            return view;
+        }
+    };
+    let resolver = match &st.content_resolver {
+        Some(r) => r,
+        None => {
+            view.content_status = "unresolved";
+            view.warning = Some("Content resolver not attached; text not fetched".into());
+            return view;
+        }
+    };
+    match resolver.resolve(commitment).await {
+        ResolvedContent::Verified { text, .. } => {
+            view.content_status = "verified";
+            view.text = Some(text);
+        }
+        ResolvedContent::Tampered { source, actual_hash, .. } => {
+            view.content_status = "tampered";
+            view.warning = Some(format!(
+                "{} served content with hash {}, which does not match the commitment", source, actual_hash,
+            ));
+        }
+        ResolvedContent::Unavailable { errors } => {
+            view.content_status = "unavailable";
+            view.warning = Some(format!("Content could not be fetched: {}", errors.join("; ")));
+        }
+    }
+    view
+}
+
+// ── GET /rpc/governance/proposal/{id} ─────────────────────────────────────────
+pub(crate) fn governance_proposal(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "governance" / "proposal" / String)
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .and_then(|id: String, st: Arc<RpcState>| async move {
+            let engine = match &st.governance {
+                Some(g) => g,
+                None => return Ok::<_, warp::Rejection>(warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: "Governance engine not attached".into() }),
+                    StatusCode::SERVICE_UNAVAILABLE,
+                )),
+            };
+            let proposal = engine.lock().get_proposal(&id).cloned();
+            Ok(match proposal {
+                Ok(p) => warp::reply::with_status(warp::reply::json(&render(&st, p).await), StatusCode::OK),
+                Err(e) => warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: e.to_string() }),
+                    StatusCode::NOT_FOUND,
+                ),
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use bleep_governance::{
+        ContentFetcher, GovernanceEngine, GovernancePayload, ProposalContentResolver, ProposalType,
+        VotingWindow,
+    };
+    use parking_lot::Mutex;
+
+    const DOC: &[u8] = b"Full proposal text, far too long to keep on-chain.";
+
+    struct OneDoc(Vec<u8>);
+
+    #[async_trait]
+    impl ContentFetcher for OneDoc {
+        async fn fetch(&self, _hint: &RetrievalHint, _max_len: u64) -> Result<Vec<u8>, String> {
+            Ok(self.0.clone())
+        }
+    }
+
+    fn state(served: &[u8]) -> Arc<RpcState> {
+        let mut engine = GovernanceEngine::new(10_000);
+        let proposal = Proposal::new(
+            "prop-1".into(),
+            ProposalType::ProtocolParameter,
+            "Raise gas limit".into(),
+            "Summary".into(),
+            VotingWindow::new(2, 4).unwrap(),
+            5,
+            67,
+            GovernancePayload::ProtocolParameterChange { rule_name: "BLOCK_GAS_LIMIT".into(), new_value: 1 },
+            1,
+        ).with_content(
+            ContentCommitment::for_content(DOC, "text/plain")
+                .with_hint(RetrievalHint::Https("https://docs.example/prop-1.txt".into())),
+        );
+        engine.submit_proposal(proposal).unwrap();
+        let resolver = ProposalContentResolver::new(Box::new(OneDoc(served.to_vec())));
+        Arc::new(RpcState::new().with_governance(Arc::new(Mutex::new(engine)), Some(Arc::new(resolver))))
+    }
+
+    async fn get(st: Arc<RpcState>, id: &str) -> (StatusCode, serde_json::Value) {
+        let res = warp::test::request()
+            .path(&format!("/rpc/governance/proposal/{}", id))
+            .reply(&governance_proposal(st))
+            .await;
+        (res.status(), serde_json::from_slice(res.body()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn proposal_renders_verified_text_and_flags_tampering() {
+        let (status, body) = get(state(DOC), "prop-1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["content_status"], "verified");
+        assert_eq!(body["text"], std::str::from_utf8(DOC).unwrap());
+        assert_eq!(body["content"]["hash"], ContentCommitment::for_content(DOC, "text/plain").hash_hex());
+
+        let (_, body) = get(state(b"Swapped text"), "prop-1").await;
+        assert_eq!(body["content_status"], "tampered");
+        assert!(body.get("text").is_none());
+        assert!(body["warning"].as_str().unwrap().contains("does not match"));
+
+        let (status, _) = get(state(DOC), "missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,307 @@
+//! # Governance event feed
+//!
+//! Serves `bleep_governance::GovernanceFeed`, attached with
+//! `RpcState::with_governance_feed`.
+//!
+//! - `GET /rpc/ws/governance?topics=&proposal=&category=` — WebSocket of
+//!   `Notice`s on `governance.proposals` and `governance.votes`, as JSON
+//!   text frames.  Every parameter is an optional comma-separated list.  A
+//!   subscriber that falls more than the feed's channel capacity behind gets
+//!   an `{"error": …}` frame and is closed, and should resubscribe.
+//!
+//! Webhook management needs the node's admin token in `x-admin-token`:
+//!
+//! - `POST   /rpc/admin/governance/webhooks`      — register `{url, secret, topics?, proposal_ids?, categories?}`
+//! - `GET    /rpc/admin/governance/webhooks`      — list webhooks and delivery counts
+//! - `DELETE /rpc/admin/governance/webhooks/{id}` — remove a webhook
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures_util::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use warp::http::StatusCode;
+use warp::ws::{Message, WebSocket, Ws};
+use warp::{Filter, Rejection, Reply};
+
+use bleep_governance::{GovernanceWebhooks, Notice, SubscriptionFilter, Webhook};
+
+use crate::api_keys::{admin_token_ok, ADMIN_TOKEN_HEADER};
+use crate::{with_arc_state, ErrResp, RpcState};
+
+type FeedReply = Box<dyn Reply + Send>;
+
+fn reply(value: &impl Serialize, status: StatusCode) -> FeedReply {
+    Box::new(warp::reply::with_status(warp::reply::json(value), status))
+}
+
+fn err(msg: &str, status: StatusCode) -> FeedReply {
+    reply(&ErrResp { error: msg.into() }, status)
+}
+
+// ── GET /rpc/ws/governance ────────────────────────────────────────────────────
+
+pub(crate) fn governance_ws(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    warp::path!("rpc" / "ws" / "governance")
+        .and(warp::get())
+        .and(warp::ws())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_arc_state(state))
+        .map(|ws: Ws, query: HashMap<String, String>, st: Arc<RpcState>| -> FeedReply {
+            let Some(feed) = &st.governance_feed else {
+                return err("Governance feed not attached", StatusCode::SERVICE_UNAVAILABLE);
+            };
+            let filter = match SubscriptionFilter::from_query(&query) {
+                Ok(f) => f,
+                Err(e) => return err(&e.to_string(), StatusCode::BAD_REQUEST),
+            };
+            // Subscribe before the upgrade so nothing published during the
+            // handshake is missed.
+            let rx = feed.subscribe();
+            Box::new(ws.on_upgrade(move |socket| stream_notices(socket, rx, filter)))
+        })
+}
+
+async fn stream_notices(socket: WebSocket, mut rx: broadcast::Receiver<Notice>, filter: SubscriptionFilter) {
+    let (mut outgoing, mut incoming) = socket.split();
+    loop {
+        tokio::select! {
+            notice = rx.recv() => match notice {
+                Ok(notice) => {
+                    if !filter.matches(&notice) {
+                        continue;
+                    }
+                    let Ok(text) = serde_json::to_string(&notice) else { continue };
+                    if outgoing.send(Message::text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    let msg = serde_json::json!({ "error": format!("subscriber lagged, {} notices dropped; resubscribe", n) });
+                    let _ = outgoing.send(Message::text(msg.to_string())).await;
+                    break;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            msg = incoming.next() => match msg {
+                Some(Ok(m)) if !m.is_close() => {}
+                _ => break,
+            },
+        }
+    }
+    let _ = outgoing.close().await;
+}
+
+// ── /rpc/admin/governance/webhooks ────────────────────────────────────────────
+
+#[derive(Deserialize)]
+struct RegisterReq {
+    url:          String,
+    secret:       String,
+    #[serde(default)]
+    topics:       Vec<String>,
+    #[serde(default)]
+    proposal_ids: Vec<String>,
+    #[serde(default)]
+    categories:   Vec<String>,
+}
+
+#[derive(Serialize)]
+struct RegisterResp {
+    id: u64,
+}
+
+/// A webhook as listed; the secret is never returned.
+#[derive(Serialize)]
+struct WebhookView {
+    id:           u64,
+    url:          String,
+    topics:       Vec<String>,
+    proposal_ids: Vec<String>,
+    categories:   Vec<&'static str>,
+    delivered:    u64,
+    failed:       u64,
+}
+
+impl From<Webhook> for WebhookView {
+    fn from(h: Webhook) -> Self {
+        Self {
+            id:           h.id,
+            url:          h.url,
+            topics:       h.filter.topics,
+            proposal_ids: h.filter.proposal_ids,
+            categories:   h.filter.categories.iter().map(|c| c.as_str()).collect(),
+            delivered:    h.delivered,
+            failed:       h.failed,
+        }
+    }
+}
+
+/// Check the admin token and that webhooks are attached.
+fn webhooks_for(st: &RpcState, token: Option<String>) -> Result<Arc<GovernanceWebhooks>, FeedReply> {
+    if !admin_token_ok(st, token.as_deref()) {
+        return Err(err("admin token required", StatusCode::UNAUTHORIZED));
+    }
+    st.governance_webhooks.clone()
+        .ok_or_else(|| err("Governance webhooks not attached", StatusCode::SERVICE_UNAVAILABLE))
+}
+
+/// All `/rpc/admin/governance/webhooks` routes.
+pub(crate) fn webhook_routes(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let token = warp::header::optional::<String>(ADMIN_TOKEN_HEADER);
+
+    // POST /rpc/admin/governance/webhooks
+    let register = warp::path!("rpc" / "admin" / "governance" / "webhooks")
+        .and(warp::post())
+        .and(token)
+        .and(warp::body::json::<RegisterReq>())
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|tok: Option<String>, req: RegisterReq, st: Arc<RpcState>| {
+            let hooks = match webhooks_for(&st, tok) {
+                Ok(h) => h,
+                Err(r) => return r,
+            };
+            let params = serde_json::json!({ "route": "governance/webhooks/register", "url": &req.url });
+            let res = SubscriptionFilter::new(req.topics, req.proposal_ids, &req.categories)
+                .and_then(|filter| hooks.register(req.url, req.secret, filter));
+            st.audit_admin("admin", &params, res.is_ok());
+            match res {
+                Ok(id) => reply(&RegisterResp { id }, StatusCode::CREATED),
+                Err(e) => err(&e.to_string(), StatusCode::BAD_REQUEST),
+            }
+        });
+
+    // GET /rpc/admin/governance/webhooks
+    let list = warp::path!("rpc" / "admin" / "governance" / "webhooks")
+        .and(warp::get())
+        .and(token)
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|tok: Option<String>, st: Arc<RpcState>| match webhooks_for(&st, tok) {
+            Ok(hooks) => {
+                let views: Vec<WebhookView> = hooks.list().into_iter().map(WebhookView::from).collect();
+                reply(&views, StatusCode::OK)
+            }
+            Err(r) => r,
+        });
+
+    // DELETE /rpc/admin/governance/webhooks/{id}
+    let remove = warp::path!("rpc" / "admin" / "governance" / "webhooks" / u64)
+        .and(warp::delete())
+        .and(token)
+        .and(with_arc_state(state))
+        .map(|id: u64, tok: Option<String>, st: Arc<RpcState>| {
+            let hooks = match webhooks_for(&st, tok) {
+                Ok(h) => h,
+                Err(r) => return r,
+            };
+            let res = hooks.remove(id);
+            st.audit_admin("admin", &serde_json::json!({ "route": "governance/webhooks/remove", "id": id }), res.is_ok());
+            match res {
+                Ok(()) => reply(&RegisterResp { id }, StatusCode::OK),
+                Err(e) => err(&e.to_string(), StatusCode::NOT_FOUND),
+            }
+        });
+
+    register.or(list).unify().or(remove).unify()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_governance::{
+        FeedConfig, GovernanceEngine, GovernanceFeed, GovernancePayload, HttpWebhookSink, Proposal,
+        ProposalType, VotingWindow,
+    };
+
+    const ADMIN: &str = "admin-secret";
+
+    fn state() -> (RpcState, Arc<GovernanceFeed>) {
+        let feed = Arc::new(GovernanceFeed::new(FeedConfig::default()));
+        let hooks = Arc::new(GovernanceWebhooks::new(Arc::new(HttpWebhookSink::default())));
+        let st = RpcState::new()
+            .with_api_keys(Arc::new(bleep_auth::ApiKeyRegistry::new()), ADMIN.into())
+            .with_governance_feed(Arc::clone(&feed), hooks);
+        (st, feed)
+    }
+
+    #[tokio::test]
+    async fn websocket_streams_filtered_notices() {
+        let (st, feed) = state();
+        let routes = governance_ws(Arc::new(st));
+
+        let res = warp::test::request()
+            .path("/rpc/ws/governance?category=TREASURY")
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let mut client = warp::test::ws()
+            .path("/rpc/ws/governance?topics=governance.proposals&proposal=p2")
+            .handshake(routes)
+            .await
+            .unwrap();
+
+        let mut engine = GovernanceEngine::new(1_000);
+        for id in ["p1", "p2"] {
+            engine.submit_proposal(Proposal::new(
+                id.into(),
+                ProposalType::ProtocolParameter,
+                "Raise block time".into(),
+                "test".into(),
+                VotingWindow::new(2, 4).unwrap(),
+                6,
+                67,
+                GovernancePayload::ProtocolParameterChange { rule_name: "BLOCK_TIME".into(), new_value: 6000 },
+                0,
+            )).unwrap();
+        }
+        feed.publish_block(7, engine.drain_events()).unwrap();
+
+        let msg = client.recv().await.unwrap();
+        let notice: Notice = serde_json::from_str(msg.to_str().unwrap()).unwrap();
+        assert_eq!(notice.event.proposal_id(), "p2");
+        assert_eq!(notice.height, 7);
+    }
+
+    #[tokio::test]
+    async fn webhook_admin_routes() {
+        let (st, _) = state();
+        let routes = webhook_routes(Arc::new(st));
+        let body = r#"{"url":"https://ui.example/hook","secret":"whsec","categories":["recovery"]}"#;
+
+        let res = warp::test::request()
+            .method("POST").path("/rpc/admin/governance/webhooks").body(body)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = warp::test::request()
+            .method("POST").path("/rpc/admin/governance/webhooks")
+            .header(ADMIN_TOKEN_HEADER, ADMIN).body(body)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::CREATED);
+
+        let res = warp::test::request()
+            .path("/rpc/admin/governance/webhooks").header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes).await;
+        let text = std::str::from_utf8(res.body()).unwrap();
+        assert!(text.contains("RECOVERY") && !text.contains("whsec"));
+
+        let res = warp::test::request()
+            .method("DELETE").path("/rpc/admin/governance/webhooks/1").header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = warp::test::request()
+            .method("DELETE").path("/rpc/admin/governance/webhooks/1").header(ADMIN_TOKEN_HEADER, ADMIN)
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
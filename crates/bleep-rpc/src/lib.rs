@@ -3,47 +3,150 @@
 //! Provides `rpc_routes_with_state()` used by `main.rs`, and re-exports
 //! `RpcState` so the node can update live counters.
 //!
-//! - `GET /rpc/state/{address}` — live balance + nonce from `StateManager`
+//! - `GET /rpc/state/{address}[?at_block=]` — balance + nonce from `StateManager`,
+//!   live or at a past height (journal window, or any height on archive nodes)
 //! - `GET /rpc/proof/{address}` — Sparse Merkle Trie inclusion/exclusion proof
+//! - `GET /rpc/debug/xshard/{id}` — every recorded step of a cross-shard transfer, in order
+//! - `GET /rpc/debug/xshard`      — ids of transfers with no commit or abort recorded yet
+//! - `GET /rpc/block/latest`, `/rpc/block/{height}` — archived blocks with their transaction hashes, once a `BlockStore` is attached
+//! - `GET /rpc/block/{height}/receipts` — receipts the block executed with
+//! - `GET /rpc/ready`                      — readiness; replicas are ready within `max_lag` blocks of upstream, validators report partition safe mode as degraded; not ready during snap sync (see `replica`)
 //!
 //! - `POST /rpc/validator/stake`           — register as validator / increase stake
 //! - `POST /rpc/validator/unstake`         — initiate graceful exit
 //! - `GET  /rpc/validator/list`            — list active validators
 //! - `GET  /rpc/validator/status/{id}`     — validator status + slashing history
 //! - `POST /rpc/validator/evidence`        — submit slashing evidence (auto-execute)
+//! - `GET  /rpc/validator/staking`         — bond parameters and the current epoch (see `staking`)
+//! - `GET  /rpc/validator/bond/{id|owner|key}` — bond, unbonding progress and set membership (see `staking`)
 //!
 //! - `GET  /rpc/economics/supply`          — circulating supply, minted, burned
 //! - `GET  /rpc/economics/epoch/{epoch}`   — epoch output: emissions, burns, base fee
 //! - `GET  /rpc/economics/fee`             — current base fee
+//! - `GET  /rpc/rewards/{address}`         — claimable and accruing block rewards, bonds, commission
 //! - `GET  /rpc/oracle/price/{asset}`      — latest aggregated oracle price
 //! - `POST /rpc/oracle/update`             — submit oracle price update
 //! - `GET  /rpc/connect/intents/pending`   — pending Layer 4 intents
-//! - `POST /rpc/connect/intent`            — submit a new Layer 4 instant intent
+//! - `POST /rpc/connect/intent`            — submit a new Layer 4 instant intent; 400 with the
+//!   validation issues if the recipient or amount is rejected
+//! - `POST /rpc/interop/validate`          — pre-flight address/amount checks for an intent body
+//! - `GET  /rpc/interop/sandbox/{chain}/state` — ledger of a chain in sandbox mode (heights, balances, txs)
 //! - `GET  /rpc/connect/intent/{id}`       — intent status
 //! - `POST /rpc/pat/mint`                  — mint PAT tokens
 //! - `GET  /rpc/pat/balance/{address}`     — PAT token balance
+//! - `POST /rpc/tx/simulate`               — dry-run a transaction (optionally at a past height)
+//! - `GET  /rpc/tx/pending?account=`       — account's pending outflow in the transaction pool
+//! - `GET  /rpc/tx/estimate_fee[?tip=]`    — per-block base fee and a suggested `max_fee`
+//! - `/rpc/tx/schedule`, `/rpc/tx/scheduled` — time/height-locked transactions (see `scheduled`)
+//! - `POST /rpc/tx/submit-batch`, `GET /rpc/tx/batch/{id}` — bulk submission with per-item status (see `tx_batch`)
+//! - `GET  /rpc/wallet/{address}/portfolio` — native, PAT and bridged balances (see `portfolio`)
+//! - `POST /rpc/wallet/verify-message`     — check a signed-message envelope
+//! - `GET  /rpc/wallet`, `POST /rpc/wallet/sign`, `GET /rpc/wallet/balance/{address}` — node wallet
+//!   signing and balances; 400 with a JSON error for invalid transactions (see `server_wallet`)
+//! - `/rpc/admin/keys`                     — API key management (see `api_keys`)
+//! - `GET  /rpc/admin/audit`               — operator audit trail (see `audit_trail`)
+//! - `GET  /rpc/admin/audit-status`        — background chain audit coverage and findings (see `chain_audit`)
+//! - `GET  /rpc/contract/{address}/upgrades` — contract upgrade history (see `contracts`)
+//! - `GET  /rpc/contract/{address}/storage/{key}` — contract storage slot, `?at_block=` for past heights
+//! - `GET  /rpc/contract/{address}/events` — contract event log, `?decode=true` for typed fields
+//! - `GET  /rpc/net/info`, `/rpc/net/peers` — local node and handshaken peers with per-peer service stats (see `net`)
+//! - `GET  /rpc/net/dht`                  — Kademlia routing table fill and lookup latency (see `net`)
+//! - `GET  /rpc/governance/proposal/{id}` — proposal with verified off-chain text (see `governance`)
+//! - `GET  /rpc/governance/parameters`    — runtime parameters and their change history (see `parameters`)
+//! - `GET  /rpc/ws/governance`            — WebSocket of proposal and vote notices (see `governance_feed`)
+//! - `/rpc/admin/governance/webhooks`     — signed governance webhooks (see `governance_feed`)
+//! - `GET  /rpc/telemetry/export-preview?profile=` — filtered export payload (see `telemetry_export`)
+//! - `GET  /rpc/telemetry/snapshot`      — every public metric group (see `telemetry_export`)
+//! - `GET  /rpc/telemetry/history?metric=&from=&to=&step=` — downsampled metric history (see `telemetry_history`)
+//! - `POST /rpc/devnet/deploy`            — deploy a contract, devnet nodes only (see `devnet`)
+//! - `POST /rpc/devnet/call`              — call a contract, devnet nodes only (see `devnet`)
 //!
 //! Both endpoints read from `Arc<parking_lot::Mutex<StateManager>>` threaded
 //! through `RpcState`, so they always reflect the most recently committed block.
 
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use parking_lot::Mutex;
 use serde::{Serialize, Deserialize};
 use warp::Filter;
 
-use bleep_state::state_manager::StateManager;
-use bleep_state::state_merkle::MerkleProof;
+use bleep_state::state_archive::StateView;
+use bleep_state::state_manager::{StateError, StateManager};
+use bleep_state::shard_state::ComposedProof;
+use bleep_state::ai_recommendations::{self, Freshness, RecommendationRegistry};
 use bleep_consensus::validator_identity::{ValidatorIdentity, ValidatorRegistry};
 use bleep_consensus::slashing_engine::{SlashingEngine, SlashingEvidence};
 use bleep_consensus::block_producer::BlockProducer;
+use bleep_consensus::block_store::{BlockStore, StoredBlock};
+use bleep_consensus::replica::SyncStatus;
+use bleep_consensus::partition::PartitionDetector;
+use bleep_consensus::rewards::RewardLedger;
+use bleep_consensus::snap_sync::SnapProgress;
+use bleep_core::base_fee::{BaseFeeTracker, FeeEstimate};
+use bleep_core::block::ChainId;
 use bleep_core::transaction_pool::TransactionPool;
 use bleep_economics::BleepEconomicsRuntime;
 use bleep_economics::oracle_bridge::{PriceUpdate, OracleSource};
 use bleep_interop::core::{BleepConnectOrchestrator};
+use bleep_interop::adapters::validation::{has_errors, Severity, ValidationIssue};
 use bleep_pat::PATRegistry;
+use bleep_vm::{ContractRegistry, Executor};
+
+pub mod simulation;
+use simulation::{SimulationError, SimulationRequest};
+
+pub mod api_keys;
+pub use bleep_auth::{ApiKeyRegistry, AuditTrail, AuditTrailStore, AUDIT_CHANNEL_CAPACITY};
+
+pub mod portfolio;
+use bleep_wallet_core::portfolio::PortfolioService;
+
+pub mod server_wallet;
+use server_wallet::ServerWallet;
+use bleep_wallet_core::message::{verify_message, SignedMessage};
+
+pub mod audit_trail;
+use bleep_auth::audit_trail::{AuditAction, AuditOutcome, AuditTrail};
+use bleep_crypto::Sensitive;
+
+pub mod contracts;
+
+pub mod scheduled;
+use bleep_core::TxScheduler;
+
+pub mod net;
+use bleep_p2p::{KademliaDht, PeerDirectory, ServiceQuotas};
+
+pub mod telemetry_export;
+use telemetry_export::TelemetryCollector;
+pub mod telemetry_history;
+pub mod metrics_recorder;
+use bleep_telemetry::export::TelemetryExportConfig;
+use bleep_telemetry::history::TelemetryHistory;
+
+pub mod devnet;
+
+pub mod replica;
+
+pub mod chain_audit;
+use bleep_consensus::chain_audit::ChainAuditor;
+
+pub mod staking;
+use bleep_consensus::validator_set::{EpochValidatorSets, MissedSlots};
+
+pub mod tx_batch;
+use tx_batch::TxBatches;
+use replica::ReplicaRpc;
+
+pub mod governance;
+pub mod parameters;
+pub mod governance_feed;
+use bleep_governance::{
+    ContentCommitment, GovernanceEngine, GovernanceFeed, GovernanceWebhooks, GovernancePayload, ParameterRegistry, Proposal,
+    ProposalContentResolver, ProposalType, VotingWindow, MAX_INLINE_DESCRIPTION,
+};
 
 // ─── Shared live state ────────────────────────────────────────────────────────
 
@@ -83,6 +186,70 @@ pub struct RpcState {
     pub block_producer: Option<Arc<BlockProducer>>,
     /// Live TransactionPool — attach at node startup so POST /rpc/tx can enqueue transactions.
     pub transaction_pool: Option<Arc<TransactionPool>>,
+    /// VM executor used by POST /rpc/tx/simulate for dry-run execution.
+    pub vm_executor: Option<Arc<Executor>>,
+    /// API key registry — when attached, every `/rpc/*` route needs a key.
+    pub api_keys: Option<Arc<ApiKeyRegistry>>,
+    /// Token guarding `/rpc/admin/*`.
+    pub admin_token: Option<Sensitive<String>>,
+    /// Cached multi-source balances for `/rpc/wallet/{address}/portfolio`.
+    pub portfolio: Option<Arc<PortfolioService>>,
+    /// Node wallet behind `POST /rpc/wallet/sign` and `/rpc/wallet/balance`.
+    pub server_wallet: Option<Arc<ServerWallet>>,
+    /// Hash-chained record of admin calls, served at `/rpc/admin/audit`.
+    pub audit_trail: Option<AuditTrail>,
+    /// Deployed contracts, for `/rpc/contract/{address}/upgrades` and `/events`.
+    pub contract_registry: Option<Arc<ContractRegistry>>,
+    /// Held transactions, for `/rpc/tx/schedule` and `/rpc/tx/scheduled`.
+    pub tx_scheduler: Option<Arc<TxScheduler>>,
+    /// Handshaken peers and the local node, for `/rpc/net/*` and telemetry.
+    pub peer_directory: Option<Arc<PeerDirectory>>,
+    /// The P2P node's Kademlia DHT, for `/rpc/net/dht` and telemetry.
+    pub dht: Option<Arc<KademliaDht>>,
+    /// Per-peer serving quotas, for the `service` stats of `/rpc/net/peers`.
+    pub service_quotas: Option<Arc<ServiceQuotas>>,
+    /// Live governance engine, for `/rpc/governance/propose` and `/proposal/{id}`.
+    pub governance: Option<Arc<Mutex<GovernanceEngine>>>,
+    /// Fetches and verifies committed off-chain proposal text.
+    pub content_resolver: Option<Arc<ProposalContentResolver>>,
+    /// Runtime parameter registry, for `/rpc/governance/parameters`.
+    pub parameters: Option<Arc<Mutex<ParameterRegistry>>>,
+    /// Governance notices, for `/rpc/ws/governance`.
+    pub governance_feed: Option<Arc<GovernanceFeed>>,
+    /// Webhooks managed at `/rpc/admin/governance/webhooks`.
+    pub governance_webhooks: Option<Arc<GovernanceWebhooks>>,
+    /// Export profiles, for `/rpc/telemetry/export-preview`.
+    pub telemetry_export: Option<Arc<TelemetryExportConfig>>,
+    /// Sampled metric history, for `/rpc/telemetry/history`.
+    pub telemetry_history: Option<Arc<TelemetryHistory>>,
+    /// Live chain, peer, consensus-mode and energy readings for snapshots.
+    pub telemetry_collector: Option<Arc<TelemetryCollector>>,
+    /// AI recommendation slots, whose freshness `/rpc/telemetry` reports.
+    pub ai_recommendations: Option<Arc<RecommendationRegistry>>,
+    /// Serve `/rpc/devnet/*` — local development networks only.
+    pub devnet_tools: bool,
+    /// Per-block base fee, for `/rpc/tx/estimate_fee`.
+    pub base_fee: Option<Arc<BaseFeeTracker>>,
+    /// Archived blocks and receipts, for `/rpc/block/*`.
+    pub block_store: Option<Arc<BlockStore>>,
+    /// Set on read-only replicas: writes are refused and `/rpc/ready` tracks sync.
+    pub replica: Option<ReplicaRpc>,
+    /// Batches submitted through `/rpc/tx/submit-batch`.
+    pub tx_batches: Arc<TxBatches>,
+    /// Partition safe mode, reported as degraded by `/rpc/ready`.
+    pub partition: Option<Arc<PartitionDetector>>,
+    /// Block reward ledger for `/rpc/rewards/{address}`.
+    pub rewards: Option<Arc<Mutex<RewardLedger>>>,
+    /// Snap sync in progress; `/rpc/ready` is false until it completes.
+    pub snap_sync: Option<Arc<SnapProgress>>,
+    /// Background history audit, for `/rpc/admin/audit-status`.
+    pub chain_auditor: Option<Arc<ChainAuditor>>,
+    /// Epoch validator sets, for the membership part of `/rpc/validator/bond/*`.
+    pub validator_sets: Option<Arc<Mutex<EpochValidatorSets>>>,
+    /// Slots whose leader's key the producer lacked, for `/rpc/validator/bond/*`.
+    pub missed_slots: Option<Arc<Mutex<MissedSlots>>>,
+    /// Network this node runs, reported by `/rpc/health` so clients sign for it.
+    pub chain_id: ChainId,
 }
 
 impl RpcState {
@@ -113,6 +280,38 @@ impl RpcState {
             audit_export_enabled: true,
             block_producer: None,
             transaction_pool: None,
+            vm_executor: None,
+            api_keys: None,
+            admin_token: None,
+            portfolio: None,
+            server_wallet: None,
+            audit_trail: None,
+            contract_registry: None,
+            tx_scheduler: None,
+            peer_directory: None,
+            dht: None,
+            service_quotas: None,
+            governance: None,
+            content_resolver: None,
+            parameters: None,
+            governance_feed: None,
+            governance_webhooks: None,
+            telemetry_export: None,
+            telemetry_history: None,
+            telemetry_collector: None,
+            ai_recommendations: None,
+            devnet_tools: false,
+            base_fee: None,
+            block_store: None,
+            replica: None,
+            tx_batches: Arc::new(TxBatches::new()),
+            partition: None,
+            rewards: None,
+            snap_sync: None,
+            chain_auditor: None,
+            validator_sets: None,
+            missed_slots: None,
+            chain_id: ChainId::NONE,
         }
     }
 
@@ -165,6 +364,203 @@ impl RpcState {
         self
     }
 
+    /// Report `chain_id` as this node's network; server-side signing binds to it.
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Attach a VM `Executor` so POST /rpc/tx/simulate can dry-run transactions.
+    ///
+    /// Simulated intents are unsigned, so the executor should be built with
+    /// `router.verify_signatures = false`.
+    pub fn with_vm_executor(mut self, executor: Arc<Executor>) -> Self {
+        self.vm_executor = Some(executor);
+        self
+    }
+
+    /// Require per-consumer API keys on `/rpc/*` and enable the
+    /// `/rpc/admin/keys` endpoints, guarded by `admin_token`.
+    pub fn with_api_keys(mut self, registry: Arc<ApiKeyRegistry>, admin_token: String) -> Self {
+        self.api_keys = Some(registry);
+        self.admin_token = Some(Sensitive::new(admin_token));
+        self
+    }
+
+    /// Attach the `PortfolioService` behind GET /rpc/wallet/{address}/portfolio.
+    pub fn with_portfolio(mut self, svc: Arc<PortfolioService>) -> Self {
+        self.portfolio = Some(svc);
+        self
+    }
+
+    /// Sign `POST /rpc/wallet/sign` requests with `wallet` and serve balances
+    /// from its `StateMerkle`.
+    pub fn with_server_wallet(mut self, wallet: Arc<ServerWallet>) -> Self {
+        self.server_wallet = Some(wallet);
+        self
+    }
+
+    /// Record admin calls to `trail` and serve it at `/rpc/admin/audit`.
+    pub fn with_audit_trail(mut self, trail: AuditTrail) -> Self {
+        self.audit_trail = Some(trail);
+        self
+    }
+
+    /// Attach the `ContractRegistry` behind GET /rpc/contract/{address}/upgrades.
+    pub fn with_contract_registry(mut self, registry: Arc<ContractRegistry>) -> Self {
+        self.contract_registry = Some(registry);
+        self
+    }
+
+    /// Attach the scheduler behind `/rpc/tx/schedule`.
+    pub fn with_tx_scheduler(mut self, scheduler: Arc<TxScheduler>) -> Self {
+        self.tx_scheduler = Some(scheduler);
+        self
+    }
+
+    /// Attach the P2P node's peer directory behind `/rpc/net/peers` and `/rpc/net/info`.
+    pub fn with_peer_directory(mut self, directory: Arc<PeerDirectory>) -> Self {
+        self.peer_directory = Some(directory);
+        self
+    }
+
+    /// Attach the P2P node's DHT behind `/rpc/net/dht`.
+    pub fn with_dht(mut self, dht: Arc<KademliaDht>) -> Self {
+        self.dht = Some(dht);
+        self
+    }
+
+    /// Attach the P2P node's service quotas to report per-peer serving at `/rpc/net/peers`.
+    pub fn with_service_quotas(mut self, quotas: Arc<ServiceQuotas>) -> Self {
+        self.service_quotas = Some(quotas);
+        self
+    }
+
+    /// Attach the governance engine and, optionally, a resolver for committed proposal text.
+    pub fn with_governance(
+        mut self,
+        engine: Arc<Mutex<GovernanceEngine>>,
+        resolver: Option<Arc<ProposalContentResolver>>,
+    ) -> Self {
+        self.governance = Some(engine);
+        self.content_resolver = resolver;
+        self
+    }
+
+    /// Attach the runtime parameter registry behind `/rpc/governance/parameters`.
+    pub fn with_parameter_registry(mut self, registry: Arc<Mutex<ParameterRegistry>>) -> Self {
+        self.parameters = Some(registry);
+        self
+    }
+
+    /// Serve governance notices over WebSocket from `feed` and manage the
+    /// webhooks that deliver them.
+    pub fn with_governance_feed(mut self, feed: Arc<GovernanceFeed>, webhooks: Arc<GovernanceWebhooks>) -> Self {
+        self.governance_feed = Some(feed);
+        self.governance_webhooks = Some(webhooks);
+        self
+    }
+
+    /// Attach telemetry export profiles behind `/rpc/telemetry/export-preview`.
+    pub fn with_telemetry_export(mut self, config: Arc<TelemetryExportConfig>) -> Self {
+        self.telemetry_export = Some(config);
+        self
+    }
+
+    /// Attach sampled metric history behind `/rpc/telemetry/history`.
+    pub fn with_telemetry_history(mut self, history: Arc<TelemetryHistory>) -> Self {
+        self.telemetry_history = Some(history);
+        self
+    }
+
+    pub fn with_telemetry_collector(mut self, collector: Arc<TelemetryCollector>) -> Self {
+        self.telemetry_collector = Some(collector);
+        self
+    }
+
+    /// Report the freshness of `registry`'s AI recommendations in telemetry.
+    pub fn with_ai_recommendations(mut self, registry: Arc<RecommendationRegistry>) -> Self {
+        self.ai_recommendations = Some(registry);
+        self
+    }
+
+    /// Enable `/rpc/devnet/deploy` and `/rpc/devnet/call`.  Never on a
+    /// public network: they deploy and run contracts outside of blocks.
+    pub fn with_devnet_tools(mut self) -> Self {
+        self.devnet_tools = true;
+        self
+    }
+
+    /// Serve fee estimates from the producer's base fee tracker.
+    pub fn with_base_fee(mut self, tracker: Arc<BaseFeeTracker>) -> Self {
+        self.base_fee = Some(tracker);
+        self
+    }
+
+    /// Serve `/rpc/block/*` from the node's block archive.
+    pub fn with_block_store(mut self, store: Arc<BlockStore>) -> Self {
+        self.block_store = Some(store);
+        self
+    }
+
+    /// Run as a read-only replica: refuse writes and report readiness from
+    /// `status`, ready while at most `max_lag` blocks behind upstream.
+    pub fn with_replica(mut self, status: Arc<SyncStatus>, max_lag: u64) -> Self {
+        self.replica = Some(ReplicaRpc { status, max_lag });
+        self
+    }
+
+    /// Report not ready on `/rpc/ready` until the snap sync behind
+    /// `progress` has imported the state.
+    pub fn with_snap_sync(mut self, progress: Arc<SnapProgress>) -> Self {
+        self.snap_sync = Some(progress);
+        self
+    }
+
+    /// Serve `/rpc/admin/audit-status` from `auditor`.
+    pub fn with_chain_auditor(mut self, auditor: Arc<ChainAuditor>) -> Self {
+        self.chain_auditor = Some(auditor);
+        self
+    }
+
+    /// Report set membership, activation and missed slots on `/rpc/validator/bond/*`.
+    pub fn with_validator_sets(
+        mut self,
+        sets: Arc<Mutex<EpochValidatorSets>>,
+        missed: Option<Arc<Mutex<MissedSlots>>>,
+    ) -> Self {
+        self.validator_sets = Some(sets);
+        self.missed_slots = missed;
+        self
+    }
+
+    /// Report the node's partition safe mode on `/rpc/ready`.
+    pub fn with_partition_detector(mut self, detector: Arc<PartitionDetector>) -> Self {
+        self.partition = Some(detector);
+        self
+    }
+
+    /// Serve block rewards from the producer's `ledger`.
+    pub fn with_reward_ledger(mut self, ledger: Arc<Mutex<RewardLedger>>) -> Self {
+        self.rewards = Some(ledger);
+        self
+    }
+
+    /// Freshness of every AI recommendation slot, by model name.
+    pub fn ai_freshness(&self) -> BTreeMap<String, Freshness> {
+        self.ai_recommendations.as_ref()
+            .map(|r| r.freshness(ai_recommendations::now_ms()))
+            .unwrap_or_default()
+    }
+
+    /// Append an `AdminRpc` record if an audit trail is attached.
+    pub(crate) fn audit_admin<P: Serialize + ?Sized>(&self, actor: &str, params: &P, ok: bool) {
+        if let Some(trail) = &self.audit_trail {
+            let outcome = if ok { AuditOutcome::Success } else { AuditOutcome::Failure };
+            trail.append(actor, AuditAction::AdminRpc, params, outcome);
+        }
+    }
+
     pub fn uptime_secs(&self) -> u64 {
         now_secs().saturating_sub(self.start_time)
     }
@@ -196,13 +592,19 @@ struct HealthResp {
     peers:       usize,
     uptime_secs: u64,
     version:     &'static str,
+    chain_id:    ChainId,
 }
 
 #[derive(Serialize)]
 struct TelemetryResp {
+    /// Stable node id, present once the P2P node is attached.
+    node_id:                Option<String>,
     blocks_produced:        u64,
     transactions_processed: u64,
     uptime_secs:            u64,
+    /// Age, version and budget use of each AI recommendation.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    ai_recommendations:     BTreeMap<String, Freshness>,
 }
 
 #[derive(Serialize)]
@@ -215,10 +617,29 @@ struct TxReq {
     amount: u64,
     timestamp: u64,
     signature: Vec<u8>,
+    #[serde(default)]
+    max_fee: u64,
+    #[serde(default)]
+    tip: u64,
+    #[serde(default)]
+    nonce: u64,
+    #[serde(default)]
+    chain_id: ChainId,
 }
 
 #[derive(Serialize)]
-struct TxResp { tx_id: String, status: &'static str }
+struct TxResp {
+    tx_id:  String,
+    status: &'static str,
+    /// Hash the transaction's receipt will carry, once accepted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tx_hash: Option<String>,
+    /// Why the pool turned it away (see `AdmissionError::code`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code:   Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error:  Option<String>,
+}
 
 #[derive(Deserialize)]
 struct MintReq {
@@ -230,7 +651,59 @@ struct MintReq {
 struct MintResp { address: String, new_balance: String, status: String }
 
 #[derive(Serialize)]
-struct BlockResp { height: u64, hash: String, tx_count: usize, epoch: u64 }
+struct BlockResp {
+    height:     u64,
+    hash:       String,
+    tx_count:   usize,
+    epoch:      u64,
+    /// Post-block state root, for archived blocks.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_root: Option<String>,
+    /// Receipt hash of every included transaction, applied or not, for
+    /// archived blocks.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tx_hashes:  Vec<String>,
+}
+
+impl BlockResp {
+    fn from_stored(stored: &StoredBlock) -> Self {
+        BlockResp {
+            height:     stored.height(),
+            hash:       stored.block.compute_hash(),
+            tx_count:   stored.block.transactions.len(),
+            epoch:      stored.block.epoch_id,
+            state_root: Some(stored.state_root.clone()),
+            tx_hashes:  stored.block.transactions.iter().map(bleep_consensus::block_execution::tx_hash).collect(),
+        }
+    }
+}
+
+/// Response for `GET /rpc/block/{height}/receipts`.
+#[derive(Serialize)]
+struct BlockReceiptsResp {
+    height:        u64,
+    receipts_root: String,
+    receipts:      Vec<bleep_consensus::TxReceipt>,
+}
+
+/// Reply for an archive lookup of one block.
+fn stored_block_reply<T: Serialize>(
+    found: Result<Option<StoredBlock>, String>,
+    height: u64,
+    view: impl FnOnce(&StoredBlock) -> T,
+) -> Box<dyn warp::Reply + Send> {
+    match found {
+        Ok(Some(stored)) => Box::new(warp::reply::json(&view(&stored))),
+        Ok(None) => Box::new(warp::reply::with_status(
+            warp::reply::json(&ErrResp { error: format!("Block {} not found", height) }),
+            warp::http::StatusCode::NOT_FOUND,
+        )),
+        Err(error) => Box::new(warp::reply::with_status(
+            warp::reply::json(&ErrResp { error }),
+            warp::http::StatusCode::INTERNAL_SERVER_ERROR,
+        )),
+    }
+}
 
 /// Response for `GET /rpc/state/{address}`.
 #[derive(Serialize)]
@@ -239,9 +712,36 @@ struct AccountStateResp {
     /// u128 balance as decimal string (avoids JSON u64 overflow)
     balance:      String,
     nonce:        u64,
-    /// Hex-encoded 32-byte Sparse Merkle Trie root at query time
+    /// Nonce for the account's next transaction, past the ones pending in
+    /// the pool; live queries only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pending_nonce: Option<u64>,
+    /// Hex-encoded 32-byte Sparse Merkle Trie root at query time; for
+    /// `at_block` queries the archived root, empty if none was recorded
     state_root:   String,
     block_height: u64,
+    /// Hex hash of the authorized signing key, once the account rotated keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_hash:     Option<String>,
+}
+
+/// `?at_block=` on state reads; absent means the live state.
+#[derive(Deserialize)]
+pub(crate) struct AtBlockQuery {
+    pub(crate) at_block: Option<u64>,
+}
+
+/// HTTP status for a failed state read.
+pub(crate) fn state_error_status(e: &StateError) -> warp::http::StatusCode {
+    use warp::http::StatusCode;
+    match e {
+        StateError::Pruned(_) | StateError::NotArchived { .. } => StatusCode::GONE,
+        StateError::FutureHeight { .. } | StateError::CrossShard(_) => StatusCode::BAD_REQUEST,
+        StateError::UnknownRoot(_) | StateError::AccountNotFound(_) => StatusCode::NOT_FOUND,
+        StateError::Storage(_) | StateError::Serialisation(_) | StateError::Migration(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
 }
 
 /// Response for `GET /rpc/proof/{address}`.
@@ -251,26 +751,38 @@ struct ProofResp {
     exists:   bool,
     /// Hex-encoded leaf hash (all-zeros for exclusion proofs)
     leaf:     String,
-    /// Hex-encoded trie root this proof is valid against
+    /// Hex-encoded global state root this proof is valid against
     root:     String,
+    /// Shard owning the account
+    shard_id: u64,
+    /// Hex-encoded root of that shard's trie
+    shard_root: String,
     /// Hex-encoded sibling hash at each of 256 levels (index 0 = near leaf)
     siblings: Vec<String>,
     /// Whether the proven node is on the right at each level
     is_right: Vec<bool>,
+    /// Position of the shard's leaf in the shard-root tree
+    shard_index: u64,
+    /// Hex-encoded siblings from the shard's leaf up to the global root
+    shard_siblings: Vec<String>,
 }
 
 impl ProofResp {
-    fn from_proof(address: &str, proof: MerkleProof) -> Self {
-        let (siblings, is_right) = proof.path.iter()
+    fn from_proof(address: &str, proof: ComposedProof, root: [u8; 32]) -> Self {
+        let (siblings, is_right) = proof.account.path.iter()
             .map(|n| (hex::encode(n.sibling), n.is_right))
             .unzip();
         Self {
             address:  address.to_string(),
-            exists:   proof.exists,
-            leaf:     hex::encode(proof.leaf),
-            root:     hex::encode(proof.root),
+            exists:   proof.account.exists,
+            leaf:     hex::encode(proof.account.leaf),
+            root:     hex::encode(root),
+            shard_id: proof.shard.shard_id,
+            shard_root: hex::encode(proof.shard.shard_root),
             siblings,
             is_right,
+            shard_index: proof.shard.index,
+            shard_siblings: proof.shard.siblings.iter().map(hex::encode).collect(),
         }
     }
 }
@@ -357,6 +869,7 @@ pub fn rpc_routes_with_state(
                 peers:       st.peer_count.load(std::sync::atomic::Ordering::Relaxed),
                 uptime_secs: st.uptime_secs(),
                 version:     env!("CARGO_PKG_VERSION"),
+                chain_id:    st.chain_id,
             })
         });
 
@@ -366,17 +879,14 @@ pub fn rpc_routes_with_state(
         .and(with_rpc_state(rpc.clone()))
         .map(|st: RpcState| {
             warp::reply::json(&TelemetryResp {
+                node_id:                st.peer_directory.as_ref().map(|d| d.local().node_id.to_string()),
                 blocks_produced:        st.blocks_produced.load(std::sync::atomic::Ordering::Relaxed),
                 transactions_processed: st.txs_processed.load(std::sync::atomic::Ordering::Relaxed),
                 uptime_secs:            st.uptime_secs(),
+                ai_recommendations:     st.ai_freshness(),
             })
         });
 
-    // GET /rpc/wallet
-    let wallet = warp::path!("rpc" / "wallet")
-        .and(warp::get())
-        .map(|| warp::reply::json(&JsonReply { result: "wallet rpc ready".into() }));
-
     // GET /rpc/ai
     let ai = warp::path!("rpc" / "ai")
         .and(warp::get())
@@ -395,6 +905,7 @@ pub fn rpc_routes_with_state(
                     let resp = warp::reply::json(&TxResp {
                         tx_id: "error".to_string(),
                         status: "TransactionPool not attached to RPC state",
+                        tx_hash: None, code: None, error: None,
                     });
                     return Ok::<_, warp::Rejection>(resp);
                 }
@@ -407,24 +918,32 @@ pub fn rpc_routes_with_state(
                 amount: req.amount,
                 timestamp: req.timestamp,
                 signature: req.signature,
+                max_fee: req.max_fee,
+                tip: req.tip,
+                nonce: req.nonce,
+                chain_id: req.chain_id,
             };
 
             // Try to add transaction to pool
-            let admitted = pool.add_transaction(tx).await;
-
-            if admitted {
-                let tx_id = format!("{}:{}:{}:{}", req.sender, req.receiver, req.amount, req.timestamp);
-                let resp = warp::reply::json(&TxResp {
-                    tx_id,
-                    status: "accepted",
-                });
-                Ok(resp)
-            } else {
-                let resp = warp::reply::json(&TxResp {
-                    tx_id: "rejected".to_string(),
-                    status: "validation_failed",
-                });
-                Ok(resp)
+            let hash = tx_batch::receipt_hash(&tx);
+            match pool.admit(tx).await {
+                Ok(()) => {
+                    let tx_id = format!("{}:{}:{}:{}", req.sender, req.receiver, req.amount, req.timestamp);
+                    let resp = warp::reply::json(&TxResp {
+                        tx_id,
+                        status: "accepted",
+                        tx_hash: Some(hash), code: None, error: None,
+                    });
+                    Ok(resp)
+                }
+                Err(e) => {
+                    let resp = warp::reply::json(&TxResp {
+                        tx_id: "rejected".to_string(),
+                        status: "validation_failed",
+                        tx_hash: None, code: Some(e.code()), error: Some(e.to_string()),
+                    });
+                    Ok(resp)
+                }
             }
         });
 
@@ -480,27 +999,58 @@ pub fn rpc_routes_with_state(
     let block_latest = warp::path!("rpc" / "block" / "latest")
         .and(warp::get())
         .and(with_rpc_state(rpc.clone()))
-        .map(|st: RpcState| {
+        .map(|st: RpcState| -> Box<dyn warp::Reply + Send> {
+            if let Some(store) = &st.block_store {
+                if let Ok(Some(tip)) = store.tip() {
+                    return stored_block_reply(store.get(tip), tip, BlockResp::from_stored);
+                }
+            }
             let h = st.chain_height.load(std::sync::atomic::Ordering::Relaxed);
-            warp::reply::json(&BlockResp {
-                height: h, hash: format!("{:064x}", h), tx_count: 0, epoch: h / 1000,
-            })
+            Box::new(warp::reply::json(&BlockResp {
+                height: h, hash: format!("{:064x}", h), tx_count: 0, epoch: h / 1000, state_root: None,
+                tx_hashes: Vec::new(),
+            }))
         });
 
-    // GET /rpc/block/{id}
+    // GET /rpc/block/{id} — by height from the archive, when attached
     let block_by_id = warp::path!("rpc" / "block" / String)
         .and(warp::get())
-        .map(|id: String| {
-            warp::reply::json(&BlockResp { height: 0, hash: id, tx_count: 0, epoch: 0 })
+        .and(with_rpc_state(rpc.clone()))
+        .map(|id: String, st: RpcState| -> Box<dyn warp::Reply + Send> {
+            match (&st.block_store, id.parse::<u64>()) {
+                (Some(store), Ok(height)) => stored_block_reply(store.get(height), height, BlockResp::from_stored),
+                _ => Box::new(warp::reply::json(&BlockResp {
+                    height: 0, hash: id, tx_count: 0, epoch: 0, state_root: None, tx_hashes: Vec::new(),
+                })),
+            }
+        });
+
+    // GET /rpc/block/{height}/receipts
+    let block_receipts = warp::path!("rpc" / "block" / u64 / "receipts")
+        .and(warp::get())
+        .and(with_rpc_state(rpc.clone()))
+        .map(|height: u64, st: RpcState| -> Box<dyn warp::Reply + Send> {
+            match &st.block_store {
+                None => Box::new(warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: "BlockStore not attached".into() }),
+                    warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                )),
+                Some(store) => stored_block_reply(store.get(height), height, |stored| BlockReceiptsResp {
+                    height,
+                    receipts_root: stored.receipts_root.clone(),
+                    receipts:      stored.receipts.clone(),
+                }),
+            }
         });
 
-    // ── Sprint 5: GET /rpc/state/{address} ───────────────────────────────────
-    // Returns live balance, nonce, state root, and block height.
-    // Holds the StateManager lock for the minimum time needed.
+    // ── Sprint 5: GET /rpc/state/{address}[?at_block=] ───────────────────────
+    // Returns balance, nonce, state root, and block height — live, or as of
+    // `at_block`. Holds the StateManager lock for the minimum time needed.
     let state_query = warp::path!("rpc" / "state" / String)
         .and(warp::get())
+        .and(warp::query::<AtBlockQuery>())
         .and(with_rpc_state(rpc.clone()))
-        .map(|address: String, st: RpcState| -> Box<dyn warp::Reply + Send> {
+        .map(|address: String, q: AtBlockQuery, st: RpcState| -> Box<dyn warp::Reply + Send> {
             match &st.state_mgr {
                 None => Box::new(warp::reply::with_status(
                     warp::reply::json(&ErrResp {
@@ -508,26 +1058,53 @@ pub fn rpc_routes_with_state(
                     }),
                     warp::http::StatusCode::SERVICE_UNAVAILABLE,
                 )),
+                Some(mgr_arc) if q.at_block.is_some() => {
+                    let mgr = mgr_arc.lock();
+                    let read = StateView::at_height(&mgr, q.at_block.unwrap_or_default())
+                        .and_then(|view| Ok((view.account(&address)?, view.root(), view.height())));
+                    drop(mgr);
+                    match read {
+                        Ok((acct, root, height)) => Box::new(warp::reply::json(&AccountStateResp {
+                            address,
+                            balance:      acct.balance.to_string(),
+                            nonce:        acct.nonce,
+                            pending_nonce: None,
+                            state_root:   root.map(hex::encode).unwrap_or_default(),
+                            block_height: height,
+                            key_hash:     acct.auth.map(|a| hex::encode(a.key_hash)),
+                        })),
+                        Err(e) => Box::new(warp::reply::with_status(
+                            warp::reply::json(&ErrResp { error: e.to_string() }),
+                            state_error_status(&e),
+                        )),
+                    }
+                }
                 Some(mgr_arc) => {
                     let mut mgr   = mgr_arc.lock();
                     let balance   = mgr.get_balance(&address);
                     let nonce     = mgr.get_nonce(&address);
                     let root      = mgr.state_root();
                     let height    = mgr.block_height();
+                    let auth      = mgr.account_auth(&address);
                     drop(mgr);
+                    let pending_nonce = st.transaction_pool.as_ref()
+                        .and_then(|pool| pool.try_pending_nonce(&address, nonce));
                     Box::new(warp::reply::json(&AccountStateResp {
                         address,
                         balance:      balance.to_string(),
                         nonce,
+                        pending_nonce,
                         state_root:   hex::encode(root),
                         block_height: height,
+                        key_hash:     auth.map(|a| hex::encode(a.key_hash)),
                     }))
                 }
             }
         });
 
     // ── Sprint 5: GET /rpc/proof/{address} ───────────────────────────────────
-    // Generates a Sparse Merkle Trie inclusion or exclusion proof.
+    // Generates an inclusion or exclusion proof: the account's path in its
+    // shard trie plus the shard root's path to the global state root.
     // Light clients verify offline: proof.verify(known_root) == true.
     let proof_query = warp::path!("rpc" / "proof" / String)
         .and(warp::get())
@@ -543,9 +1120,10 @@ pub fn rpc_routes_with_state(
                 Some(mgr_arc) => {
                     let mut mgr = mgr_arc.lock();
                     let proof   = mgr.prove_account(&address);
+                    let root    = mgr.state_root();
                     drop(mgr);
                     Box::new(warp::reply::json(
-                        &ProofResp::from_proof(&address, proof)
+                        &ProofResp::from_proof(&address, proof, root)
                     ))
                 }
             }
@@ -754,29 +1332,35 @@ pub fn rpc_routes_with_state(
             }
         });
 
-    health
+    let routes = health
         .or(telemetry)
-        .or(wallet)
         .or(ai)
         .or(tx_submit)
         .or(mint)
         .or(tx_history)
         .or(block_latest)
         .or(block_by_id)
+        .or(block_receipts)
+        .or(replica::ready(Arc::clone(&state_inner)))
         .or(state_query)
         .or(proof_query)
+        .or(debug_xshard(Arc::clone(&state_inner)))
         .or(validator_stake)
         .or(validator_unstake)
         .or(validator_list)
         .or(validator_status)
         .or(validator_evidence)
+        .or(staking::staking_routes(Arc::clone(&state_inner)))
         .or(economics_supply(Arc::clone(&state_inner)))
         .or(economics_epoch(Arc::clone(&state_inner)))
         .or(economics_fee(Arc::clone(&state_inner)))
+        .or(rewards_query(Arc::clone(&state_inner)))
         .or(oracle_price(Arc::clone(&state_inner)))
         .or(oracle_update(Arc::clone(&state_inner)))
         .or(connect_intents_pending(Arc::clone(&state_inner)))
         .or(connect_submit_intent(Arc::clone(&state_inner)))
+        .or(interop_validate(Arc::clone(&state_inner)))
+        .or(interop_sandbox_state(Arc::clone(&state_inner)))
         .or(connect_intent_status(Arc::clone(&state_inner)))
         .or(connect_relay_tx(Arc::clone(&state_inner)))
         .or(pat_create(Arc::clone(&state_inner)))
@@ -790,6 +1374,33 @@ pub fn rpc_routes_with_state(
         .or(pat_balance(Arc::clone(&state_inner)))
         .or(pat_info(Arc::clone(&state_inner)))
         .or(pat_list(Arc::clone(&state_inner)))
+        .or(tx_simulate(Arc::clone(&state_inner)))
+        .or(tx_pending(Arc::clone(&state_inner)))
+        .or(tx_estimate_fee(Arc::clone(&state_inner)))
+        .or(tx_batch::tx_submit_batch(Arc::clone(&state_inner)))
+        .or(tx_batch::tx_batch_status(Arc::clone(&state_inner)))
+        .or(scheduled::tx_schedule(Arc::clone(&state_inner)))
+        .or(scheduled::tx_scheduled_events(Arc::clone(&state_inner)))
+        .or(scheduled::tx_scheduled_list(Arc::clone(&state_inner)))
+        .or(scheduled::tx_scheduled_cancel(Arc::clone(&state_inner)))
+        .or(portfolio::wallet_portfolio(Arc::clone(&state_inner)))
+        .or(server_wallet::wallet_routes(Arc::clone(&state_inner)))
+        .or(wallet_verify_message())
+        .or(contracts::contract_upgrades(Arc::clone(&state_inner)))
+        .or(contracts::contract_storage(Arc::clone(&state_inner)))
+        .or(contracts::contract_events(Arc::clone(&state_inner)))
+        .or(net::net_info(Arc::clone(&state_inner)))
+        .or(net::net_peers(Arc::clone(&state_inner)))
+        .or(net::net_dht(Arc::clone(&state_inner)))
+        .or(governance::governance_proposal(Arc::clone(&state_inner)))
+        .or(parameters::parameter_routes(Arc::clone(&state_inner)))
+        .or(governance_feed::governance_ws(Arc::clone(&state_inner)))
+        .or(governance_feed::webhook_routes(Arc::clone(&state_inner)))
+        .or(telemetry_export::telemetry_export_preview(Arc::clone(&state_inner)))
+        .or(telemetry_export::telemetry_current(Arc::clone(&state_inner)))
+        .or(telemetry_history::telemetry_history(Arc::clone(&state_inner)))
+        .or(devnet::devnet_deploy(Arc::clone(&state_inner)))
+        .or(devnet::devnet_call(Arc::clone(&state_inner)))
         // ── Sprint 8 ──────────────────────────────────────────────────────
         .or(faucet_drip(Arc::clone(&state_inner)))
         .or(faucet_status(Arc::clone(&state_inner)))
@@ -809,6 +1420,15 @@ pub fn rpc_routes_with_state(
         .or(layer3_intent_submit_route(Arc::clone(&state_inner)))
         .or(benchmark_result_route(Arc::clone(&state_inner)))
         .or(audit_report_route(Arc::clone(&state_inner)))
+        .or(api_keys::admin_key_routes(Arc::clone(&state_inner)))
+        .or(audit_trail::admin_audit_routes(Arc::clone(&state_inner)))
+        .or(chain_audit::audit_status(Arc::clone(&state_inner)));
+
+    replica::replica_guard(Arc::clone(&state_inner))
+        .and(api_keys::api_key_guard(state_inner))
+        .and(routes)
+        .recover(api_keys::recover_api_key)
+        .recover(replica::recover_replica_mode)
 }
 
 /// Convenience wrapper with zero-state (stub / test mode).
@@ -849,6 +1469,30 @@ struct FeeResp {
     last_epoch:       u64,
 }
 
+/// Amounts as decimal strings, like every other µBLEEP figure on the wire.
+#[derive(Serialize)]
+struct RewardsResp {
+    address:        String,
+    claimable:      String,
+    accruing:       String,
+    bonded:         BTreeMap<String, String>,
+    commission_bps: Option<u16>,
+    epoch:          u64,
+}
+
+impl From<bleep_consensus::rewards::RewardsView> for RewardsResp {
+    fn from(v: bleep_consensus::rewards::RewardsView) -> Self {
+        RewardsResp {
+            address:        v.address,
+            claimable:      v.claimable.to_string(),
+            accruing:       v.accruing.to_string(),
+            bonded:         v.bonded.into_iter().map(|(k, s)| (k, s.to_string())).collect(),
+            commission_bps: v.commission_bps,
+            epoch:          v.epoch,
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct OraclePriceResp {
     asset:         String,
@@ -900,6 +1544,32 @@ struct SubmitIntentResp {
     status:    String,
 }
 
+#[derive(Serialize)]
+struct IntentIssue {
+    #[serde(flatten)]
+    issue:    ValidationIssue,
+    severity: Severity,
+    message:  String,
+}
+
+#[derive(Serialize)]
+struct ValidateIntentResp {
+    /// `false` if any issue is an error; warnings alone still submit.
+    valid:  bool,
+    issues: Vec<IntentIssue>,
+}
+
+impl ValidateIntentResp {
+    fn new(issues: Vec<ValidationIssue>) -> Self {
+        Self {
+            valid:  !has_errors(&issues),
+            issues: issues.into_iter()
+                .map(|issue| IntentIssue { severity: issue.severity(), message: issue.to_string(), issue })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 struct IntentStatusResp {
     intent_id: String,
@@ -926,6 +1596,161 @@ fn with_arc_state(
     warp::any().map(move || Arc::clone(&st))
 }
 
+// ── POST /rpc/tx/simulate ─────────────────────────────────────────────────────
+fn tx_simulate(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "simulate")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(65_536))
+        .and(warp::body::json::<SimulationRequest>())
+        .and(with_arc_state(state))
+        .and_then(|req: SimulationRequest, st: Arc<RpcState>| async move {
+            let (mgr, exec) = match (&st.state_mgr, &st.vm_executor) {
+                (Some(m), Some(e)) => (Arc::clone(m), Arc::clone(e)),
+                _ => {
+                    return Ok::<_, warp::Rejection>(warp::reply::with_status(
+                        warp::reply::json(&ErrResp { error: "StateManager or VM executor not attached".into() }),
+                        warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                    ));
+                }
+            };
+            match simulation::simulate(&mgr, &exec, st.contract_registry.as_deref(), &req).await {
+                Ok(res) => Ok(warp::reply::with_status(
+                    warp::reply::json(&res),
+                    warp::http::StatusCode::OK,
+                )),
+                Err(e) => {
+                    let code = match &e {
+                        SimulationError::State(
+                            se @ (StateError::Pruned(_) | StateError::NotArchived { .. }),
+                        ) => state_error_status(se),
+                        _ => warp::http::StatusCode::BAD_REQUEST,
+                    };
+                    Ok(warp::reply::with_status(
+                        warp::reply::json(&ErrResp { error: e.to_string() }),
+                        code,
+                    ))
+                }
+            }
+        })
+}
+
+#[derive(Deserialize)]
+struct PendingQuery { account: String }
+
+#[derive(Serialize)]
+struct PendingResp {
+    account:         String,
+    /// u128 µBLEEP as decimal string
+    pending_outflow: String,
+    pending_txs:     u32,
+}
+
+// ── GET /rpc/tx/pending?account= ──────────────────────────────────────────────
+fn tx_pending(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "pending")
+        .and(warp::get())
+        .and(warp::query::<PendingQuery>())
+        .and(with_arc_state(state))
+        .and_then(|q: PendingQuery, st: Arc<RpcState>| async move {
+            let pool = match &st.transaction_pool {
+                Some(p) => Arc::clone(p),
+                None => {
+                    return Ok::<_, warp::Rejection>(warp::reply::with_status(
+                        warp::reply::json(&ErrResp { error: "TransactionPool not attached".into() }),
+                        warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                    ));
+                }
+            };
+            let pending = pool.pending_spend(&q.account).await;
+            Ok(warp::reply::with_status(
+                warp::reply::json(&PendingResp {
+                    account:         q.account,
+                    pending_outflow: pending.outflow.to_string(),
+                    pending_txs:     pending.tx_count,
+                }),
+                warp::http::StatusCode::OK,
+            ))
+        })
+}
+
+#[derive(Deserialize)]
+struct EstimateFeeQuery {
+    #[serde(default)]
+    tip: u64,
+}
+
+// ── GET /rpc/tx/estimate_fee[?tip=] ───────────────────────────────────────────
+//
+// `base_fee` is also the pool's admission floor; `max_fee` leaves headroom
+// for the fee to rise while the transaction waits.
+fn tx_estimate_fee(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "estimate_fee")
+        .and(warp::get())
+        .and(warp::query::<EstimateFeeQuery>())
+        .and(with_arc_state(state))
+        .map(|q: EstimateFeeQuery, st: Arc<RpcState>| {
+            let estimate: FeeEstimate = match &st.base_fee {
+                Some(tracker) => tracker.estimate(q.tip),
+                // No fee market: nothing beyond the tip is charged.
+                None => FeeEstimate { base_fee: 0, tip: q.tip, max_fee: q.tip },
+            };
+            warp::reply::json(&estimate)
+        })
+}
+
+#[derive(Deserialize)]
+struct VerifyMessageReq {
+    address:     String,
+    /// UTF-8 message; use `message_hex` for binary messages.
+    #[serde(default)]
+    message:     Option<String>,
+    #[serde(default)]
+    message_hex: Option<String>,
+    signature:   SignedMessage,
+}
+
+#[derive(Serialize)]
+struct VerifyMessageResp { address: String, valid: bool }
+
+// ── POST /rpc/wallet/verify-message ───────────────────────────────────────────
+fn wallet_verify_message() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "wallet" / "verify-message")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(262_144))
+        .and(warp::body::json::<VerifyMessageReq>())
+        .map(|req: VerifyMessageReq| {
+            let msg = match (req.message, req.message_hex) {
+                (Some(m), None) => m.into_bytes(),
+                (None, Some(h)) => match hex::decode(h.trim_start_matches("0x")) {
+                    Ok(b) => b,
+                    Err(e) => {
+                        return warp::reply::with_status(
+                            warp::reply::json(&ErrResp { error: format!("Invalid message_hex: {}", e) }),
+                            warp::http::StatusCode::BAD_REQUEST,
+                        );
+                    }
+                },
+                _ => {
+                    return warp::reply::with_status(
+                        warp::reply::json(&ErrResp { error: "Pass exactly one of message or message_hex".into() }),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    );
+                }
+            };
+            let valid = verify_message(&req.address, &msg, &req.signature);
+            warp::reply::with_status(
+                warp::reply::json(&VerifyMessageResp { address: req.address, valid }),
+                warp::http::StatusCode::OK,
+            )
+        })
+}
+
 // ── GET /rpc/economics/supply ─────────────────────────────────────────────────
 fn economics_supply(
     state: Arc<RpcState>,
@@ -1022,6 +1847,27 @@ fn economics_fee(
         })
 }
 
+// ── GET /rpc/rewards/{address} ────────────────────────────────────────────────
+fn rewards_query(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "rewards" / String)
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|address: String, st: Arc<RpcState>| {
+            match &st.rewards {
+                Some(ledger) => warp::reply::with_status(
+                    warp::reply::json(&RewardsResp::from(ledger.lock().view(&address))),
+                    warp::http::StatusCode::OK,
+                ),
+                None => warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: "Block rewards not enabled".into() }),
+                    warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                ),
+            }
+        })
+}
+
 // ── GET /rpc/oracle/price/{asset} ─────────────────────────────────────────────
 fn oracle_price(
     state: Arc<RpcState>,
@@ -1150,6 +1996,71 @@ fn connect_intents_pending(
 }
 
 // ── POST /rpc/connect/intent ──────────────────────────────────────────────────
+fn build_intent(req: &SubmitIntentReq) -> bleep_interop::types::InstantIntent {
+    use sha2::{Sha256, Digest};
+    use bleep_interop::types::{
+        ChainId, UniversalAddress, AssetId, AssetType, InstantIntent,
+    };
+
+    let src = ChainId::from_name(&req.source_chain).unwrap_or(ChainId::BLEEP);
+    let dst = ChainId::from_name(&req.dest_chain).unwrap_or(ChainId::Ethereum);
+
+    let ts = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    // Deterministic intent ID from (source_chain, dest_chain, source_amount, sender, nonce)
+    let nonce = req.nonce.unwrap_or(ts);
+    let mut h = Sha256::new();
+    h.update(req.source_chain.as_bytes());
+    h.update(req.dest_chain.as_bytes());
+    h.update(req.source_amount.to_le_bytes());
+    h.update(req.sender_address.as_bytes());
+    h.update(nonce.to_le_bytes());
+    let id_arr: [u8; 32] = h.finalize().into();
+
+    let escrow_proof = req.escrow_proof
+        .as_ref()
+        .and_then(|s| hex::decode(s).ok())
+        .unwrap_or_else(|| vec![1, 2, 3]); // devnet placeholder
+
+    InstantIntent {
+        intent_id: id_arr,
+        created_at: ts,
+        expires_at: ts + 300,
+        source_chain: src,
+        dest_chain: dst,
+        source_asset: AssetId { chain: src, contract_address: None, token_id: None, asset_type: AssetType::Native },
+        dest_asset:   AssetId { chain: dst, contract_address: None, token_id: None, asset_type: AssetType::Native },
+        source_amount: req.source_amount,
+        min_dest_amount: req.min_dest_amount,
+        sender: UniversalAddress::new(src, req.sender_address.clone()),
+        recipient: UniversalAddress::new(dst, req.recipient_address.clone()),
+        max_solver_reward_bps: req.max_solver_reward_bps.unwrap_or(50),
+        slippage_tolerance_bps: req.slippage_tolerance_bps.unwrap_or(100),
+        nonce,
+        signature: req.signature
+            .as_ref()
+            .and_then(|s| hex::decode(s).ok())
+            .unwrap_or_else(|| vec![1]),
+        escrow_tx_hash: req.escrow_tx_hash.clone().unwrap_or_else(|| format!("0xescrow-{}", hex::encode(&id_arr[..4]))),
+        escrow_proof,
+    }
+}
+
+/// Pre-flight issues for a submission.  An unknown destination name is an
+/// issue here rather than silently becoming Ethereum in `build_intent`.
+fn intent_issues(req: &SubmitIntentReq, intent: &bleep_interop::types::InstantIntent, st: &RpcState) -> Vec<ValidationIssue> {
+    if bleep_interop::types::ChainId::from_name(&req.dest_chain).is_none() {
+        return vec![ValidationIssue::UnsupportedChain { chain: req.dest_chain.clone() }];
+    }
+    match &st.connect_orchestrator {
+        Some(orc) => orc.validate_intent(intent),
+        None => bleep_interop::AdapterRegistry::new().validate_request(intent),
+    }
+}
+
 fn connect_submit_intent(
     state: Arc<RpcState>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -1158,58 +2069,16 @@ fn connect_submit_intent(
         .and(warp::body::json::<SubmitIntentReq>())
         .and(with_arc_state(state))
         .map(|req: SubmitIntentReq, st: Arc<RpcState>| {
-            use sha2::{Sha256, Digest};
-            use bleep_interop::types::{
-                ChainId, UniversalAddress, AssetId, AssetType, InstantIntent,
-            };
-
-            let src = ChainId::from_name(&req.source_chain).unwrap_or(ChainId::BLEEP);
-            let dst = ChainId::from_name(&req.dest_chain).unwrap_or(ChainId::Ethereum);
-
-            let ts = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs();
-
-            // Deterministic intent ID from (source_chain, dest_chain, source_amount, sender, nonce)
-            let nonce = req.nonce.unwrap_or(ts);
-            let mut h = Sha256::new();
-            h.update(req.source_chain.as_bytes());
-            h.update(req.dest_chain.as_bytes());
-            h.update(req.source_amount.to_le_bytes());
-            h.update(req.sender_address.as_bytes());
-            h.update(nonce.to_le_bytes());
-            let id_arr: [u8; 32] = h.finalize().into();
-
-            let escrow_proof = req.escrow_proof
-                .as_ref()
-                .and_then(|s| hex::decode(s).ok())
-                .unwrap_or_else(|| vec![1, 2, 3]); // devnet placeholder
-
-            let intent = InstantIntent {
-                intent_id: id_arr,
-                created_at: ts,
-                expires_at: ts + 300,
-                source_chain: src,
-                dest_chain: dst,
-                source_asset: AssetId { chain: src, contract_address: None, token_id: None, asset_type: AssetType::Native },
-                dest_asset:   AssetId { chain: dst, contract_address: None, token_id: None, asset_type: AssetType::Native },
-                source_amount: req.source_amount,
-                min_dest_amount: req.min_dest_amount,
-                sender: UniversalAddress::new(src, req.sender_address.clone()),
-                recipient: UniversalAddress::new(dst, req.recipient_address.clone()),
-                max_solver_reward_bps: req.max_solver_reward_bps.unwrap_or(50),
-                slippage_tolerance_bps: req.slippage_tolerance_bps.unwrap_or(100),
-                nonce,
-                signature: req.signature
-                    .as_ref()
-                    .and_then(|s| hex::decode(s).ok())
-                    .unwrap_or_else(|| vec![1]),
-                escrow_tx_hash: req.escrow_tx_hash.clone().unwrap_or_else(|| format!("0xescrow-{}", hex::encode(&id_arr[..4]))),
-                escrow_proof,
-            };
+            let intent = build_intent(&req);
+            let issues = intent_issues(&req, &intent, &st);
+            if has_errors(&issues) {
+                return warp::reply::with_status(
+                    warp::reply::json(&ValidateIntentResp::new(issues)),
+                    warp::http::StatusCode::BAD_REQUEST,
+                );
+            }
 
-            let intent_id_hex = hex::encode(id_arr);
+            let intent_id_hex = hex::encode(intent.intent_id);
 
             match &st.connect_orchestrator {
                 Some(orc) => {
@@ -1243,6 +2112,101 @@ fn connect_submit_intent(
         })
 }
 
+// ── POST /rpc/interop/validate ────────────────────────────────────────────────
+fn interop_validate(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "interop" / "validate")
+        .and(warp::post())
+        .and(warp::body::json::<SubmitIntentReq>())
+        .and(with_arc_state(state))
+        .map(|req: SubmitIntentReq, st: Arc<RpcState>| {
+            let intent = build_intent(&req);
+            warp::reply::json(&ValidateIntentResp::new(intent_issues(&req, &intent, &st)))
+        })
+}
+
+// ── GET /rpc/interop/sandbox/{chain}/state ───────────────────────────────────
+// ── GET /rpc/debug/xshard[/{id}] ──────────────────────────────────────────────
+// Timeline of a cross-shard transfer from the state manager's event log,
+// for support investigations; `id` is the transfer's `xshard_id`.  Without
+// an id, the transfers still awaiting a commit or abort.
+fn debug_xshard(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    let open = warp::path!("rpc" / "debug" / "xshard")
+        .and(warp::get())
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|st: Arc<RpcState>| -> Box<dyn warp::Reply + Send> {
+            let Some(mgr) = &st.state_mgr else {
+                return Box::new(warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: "StateManager unavailable (stub mode)".into() }),
+                    warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                ));
+            };
+            let open = mgr.lock().open_xshard_transfers();
+            match open {
+                Ok(ids) => Box::new(warp::reply::json(&serde_json::json!({ "open": ids }))),
+                Err(e) => Box::new(warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: e.to_string() }),
+                    state_error_status(&e),
+                )),
+            }
+        });
+
+    let one = warp::path!("rpc" / "debug" / "xshard" / String)
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|id: String, st: Arc<RpcState>| -> Box<dyn warp::Reply + Send> {
+            let Some(mgr) = &st.state_mgr else {
+                return Box::new(warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: "StateManager unavailable (stub mode)".into() }),
+                    warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                ));
+            };
+            let timeline = mgr.lock().xshard_timeline(&id.to_ascii_lowercase());
+            match timeline {
+                Ok(Some(timeline)) => Box::new(warp::reply::json(&timeline)),
+                Ok(None) => Box::new(warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: format!("no cross-shard transfer {}", id) }),
+                    warp::http::StatusCode::NOT_FOUND,
+                )),
+                Err(e) => Box::new(warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: e.to_string() }),
+                    state_error_status(&e),
+                )),
+            }
+        });
+
+    open.or(one)
+}
+
+fn interop_sandbox_state(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "interop" / "sandbox" / String / "state")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|chain: String, st: Arc<RpcState>| {
+            let Some(orc) = &st.connect_orchestrator else {
+                return warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: "BleepConnect not initialised".into() }),
+                    warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                );
+            };
+            match bleep_interop::types::ChainId::from_name(&chain).and_then(|id| orc.sandbox_state(id)) {
+                Some(sandbox) => warp::reply::with_status(
+                    warp::reply::json(&sandbox),
+                    warp::http::StatusCode::OK,
+                ),
+                None => warp::reply::with_status(
+                    warp::reply::json(&ErrResp { error: format!("{} is not in sandbox mode", chain) }),
+                    warp::http::StatusCode::NOT_FOUND,
+                ),
+            }
+        })
+}
+
 // ── GET /rpc/connect/intent/{id} ──────────────────────────────────────────────
 fn connect_intent_status(
     state: Arc<RpcState>,
@@ -1416,6 +2380,9 @@ struct PatTransferReq {
     from:   String,
     to:     String,
     amount: String,
+    /// Required when the token's compliance policy enforces an allow-list.
+    #[serde(default)]
+    allow_list_proof: Option<bleep_pat::AllowListInclusionProof>,
 }
 
 #[derive(Serialize)]
@@ -1616,7 +2583,10 @@ fn pat_transfer(
                     let burn_deducted = reg.lock().get_token(&req.symbol)
                         .map(|t| t.transfer_burn_amount(amount))
                         .unwrap_or(0);
-                    let intent = bleep_pat::PATIntent::transfer(from, req.symbol.clone(), to, amount);
+                    let mut intent = bleep_pat::PATIntent::transfer(from, req.symbol.clone(), to, amount);
+                    if let bleep_pat::PATIntentKind::Transfer(ref mut t) = intent.kind {
+                        t.allow_list_proof = req.allow_list_proof.clone();
+                    }
                     let mut r = reg.lock();
                     match r.execute(&intent) {
                         Ok(outcome) => {
@@ -1812,6 +2782,9 @@ struct PatFreezeReq {
     symbol: String,
     owner:  String,
     frozen: bool,
+    /// Hex holder address — freezes one account instead of the whole token.
+    #[serde(default)]
+    account: Option<String>,
 }
 
 fn pat_freeze(
@@ -1830,13 +2803,20 @@ fn pat_freeze(
                             warp::reply::json(&ErrResp { error: e }),
                             warp::http::StatusCode::BAD_REQUEST),
                     };
-                    let intent = bleep_pat::PATIntent::new(
-                        owner,
-                        bleep_pat::PATIntentKind::Freeze(bleep_pat::FreezeIntent {
+                    let kind = match &req.account {
+                        None => bleep_pat::PATIntentKind::Freeze(bleep_pat::FreezeIntent {
                             symbol: req.symbol.clone(), frozen: req.frozen,
                         }),
-                        10_000, 0, 0,
-                    );
+                        Some(acct) => match hex_to_address(acct) {
+                            Ok(account) => bleep_pat::PATIntentKind::FreezeAccount(bleep_pat::FreezeAccountIntent {
+                                symbol: req.symbol.clone(), account, frozen: req.frozen,
+                            }),
+                            Err(e) => return warp::reply::with_status(
+                                warp::reply::json(&ErrResp { error: e }),
+                                warp::http::StatusCode::BAD_REQUEST),
+                        },
+                    };
+                    let intent = bleep_pat::PATIntent::new(owner, kind, 10_000, 0, 0);
                     let mut r = reg.lock();
                     match r.execute(&intent) {
                         Ok(_) => warp::reply::with_status(
@@ -1845,6 +2825,7 @@ fn pat_freeze(
                                 detail: serde_json::json!({
                                     "symbol": req.symbol,
                                     "frozen": req.frozen,
+                                    "account": req.account,
                                 }),
                             }),
                             warp::http::StatusCode::OK,
@@ -2009,7 +2990,7 @@ struct AuthRotateResp {
 #[derive(Deserialize)]
 struct AuthRotateReq {
     /// New JWT secret (base64-encoded, must decode to ≥32 bytes).
-    new_secret_b64: String,
+    new_secret_b64: Sensitive<String>,
 }
 
 // ── POST /faucet/{address} ────────────────────────────────────────────────────
@@ -2137,11 +3118,12 @@ fn auth_rotate_secret(
         .and(with_arc_state(state))
         .map(|req: AuthRotateReq, st: Arc<RpcState>| {
             // Validate that the base64 secret decodes to ≥32 bytes
-            match base64::decode(&req.new_secret_b64) {
+            match base64::decode(req.new_secret_b64.expose()) {
                 Ok(bytes) if bytes.len() >= 32 => {
                     let count = st.jwt_rotation_count
                         .fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
                     log::info!("JWT secret rotation #{} accepted via RPC", count);
+                    st.audit_admin("rpc", &serde_json::json!({ "route": "auth/rotate", "rotation": count }), true);
                     Box::new(warp::reply::with_status(
                         warp::reply::json(&AuthRotateResp {
                             ok:             true,
@@ -2155,18 +3137,24 @@ fn auth_rotate_secret(
                         warp::http::StatusCode::OK,
                     )) as Box<dyn warp::Reply + Send>
                 }
-                Ok(_) => Box::new(warp::reply::with_status(
-                    warp::reply::json(&ErrResp {
-                        error: "Decoded secret is shorter than 32 bytes.".into(),
-                    }),
-                    warp::http::StatusCode::BAD_REQUEST,
-                )) as Box<dyn warp::Reply + Send>,
-                Err(e) => Box::new(warp::reply::with_status(
-                    warp::reply::json(&ErrResp {
-                        error: format!("Invalid base64: {}", e),
-                    }),
-                    warp::http::StatusCode::BAD_REQUEST,
-                )) as Box<dyn warp::Reply + Send>,
+                Ok(_) => {
+                    st.audit_admin("rpc", &serde_json::json!({ "route": "auth/rotate" }), false);
+                    Box::new(warp::reply::with_status(
+                        warp::reply::json(&ErrResp {
+                            error: "Decoded secret is shorter than 32 bytes.".into(),
+                        }),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )) as Box<dyn warp::Reply + Send>
+                }
+                Err(e) => {
+                    st.audit_admin("rpc", &serde_json::json!({ "route": "auth/rotate" }), false);
+                    Box::new(warp::reply::with_status(
+                        warp::reply::json(&ErrResp {
+                            error: format!("Invalid base64: {}", e),
+                        }),
+                        warp::http::StatusCode::BAD_REQUEST,
+                    )) as Box<dyn warp::Reply + Send>
+                }
             }
         })
 }
@@ -2343,7 +3331,8 @@ fn explorer_api_validators(
 // ── GET /metrics ──────────────────────────────────────────────────────────────
 //
 // Prometheus text-format metrics endpoint scraped by the Grafana stack.
-// Exposes the key operational counters for dashboarding and alerting.
+// Exposes the key operational counters for dashboarding and alerting, then
+// whatever the `metrics` macros recorded (see `metrics_recorder`).
 fn metrics_prometheus(
     state: Arc<RpcState>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
@@ -2392,7 +3381,9 @@ bleep_faucet_balance_micro {faucet_bal}
 # HELP bleep_jwt_rotations_total Total JWT secret rotations performed.
 # TYPE bleep_jwt_rotations_total counter
 bleep_jwt_rotations_total {jwt_rot}
-"#
+
+{recorded}"#,
+                recorded = metrics_recorder::render(),
             );
 
             warp::http::Response::builder()
@@ -2677,6 +3668,59 @@ mod tests_sprint8 {
         let height = st.chain_height.load(std::sync::atomic::Ordering::Relaxed);
         assert_eq!(height, 42);
     }
+
+    #[tokio::test]
+    async fn estimate_fee_follows_the_base_fee() {
+        use bleep_core::base_fee::BaseFeeParams;
+
+        let get = |st: RpcState| async move {
+            let res = warp::test::request()
+                .path("/rpc/tx/estimate_fee?tip=5")
+                .reply(&tx_estimate_fee(Arc::new(st)))
+                .await;
+            serde_json::from_slice::<FeeEstimate>(res.body()).unwrap()
+        };
+        assert_eq!(get(RpcState::new()).await, FeeEstimate { base_fee: 0, tip: 5, max_fee: 5 });
+
+        let tracker = Arc::new(BaseFeeTracker::new(BaseFeeParams::default()).unwrap());
+        tracker.on_block(10, 10, &[]);
+        let estimate = get(RpcState::new().with_base_fee(tracker)).await;
+        assert_eq!(estimate, FeeEstimate { base_fee: 1_125, tip: 5, max_fee: 2_255 });
+    }
+
+    #[tokio::test]
+    async fn interop_validate_reports_typed_issues() {
+        let routes = interop_validate(Arc::new(RpcState::new()))
+            .or(connect_submit_intent(Arc::new(RpcState::new())));
+        let body = |dest: &str, recipient: &str, amount: u128| serde_json::json!({
+            "source_chain": "bleep", "dest_chain": dest,
+            "source_amount": amount, "min_dest_amount": 0,
+            "sender_address": "BLEEP1f00dfeedf00dfeedf00dfeedf00dfeedf00d",
+            "recipient_address": recipient,
+        });
+        let post = |path: &'static str, json: serde_json::Value| {
+            warp::test::request().method("POST").path(path).json(&json)
+        };
+
+        let res = post("/rpc/interop/validate", body("ethereum", "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed", 100_000_000))
+            .reply(&routes).await;
+        let v: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(v["valid"], true);
+        assert_eq!(v["issues"][0]["issue"], "missing_checksum");
+        assert_eq!(v["issues"][0]["severity"], "warning");
+
+        let res = post("/rpc/interop/validate", body("bitcoin", "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNb", 100_000_000))
+            .reply(&routes).await;
+        let v: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(v["valid"], false);
+        assert_eq!(v["issues"][0]["issue"], "bad_checksum");
+
+        let bad = body("cosmos", "cosmos1qypqxpq9qcrsszg2pvxq6rs0zqg3yyc5lzv7xu", 123_456_789);
+        let res = post("/rpc/connect/intent", bad).reply(&routes).await;
+        assert_eq!(res.status(), warp::http::StatusCode::BAD_REQUEST);
+        let v: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(v["issues"][0]["issue"], "precision_loss");
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════
@@ -2788,11 +3832,35 @@ pub fn governance_proposals_route(
 
 // ── POST /rpc/governance/propose ─────────────────────────────────────────────
 // Submit a new governance proposal.
+//
+// `param` and `new_value` name the parameter to change; with a parameter
+// registry attached to the engine they are checked against its bounds.
+// `description` is stored inline and capped at `MAX_INLINE_DESCRIPTION`.
+// Longer texts go off-chain: pass `content: {hash, size, mime, hints}` with
+// the SHA3-256 hex hash and `ipfs://` / `https://` hints instead.
+
+fn parse_content(v: &serde_json::Value) -> Result<ContentCommitment, String> {
+    let hash = v.get("hash").and_then(|h| h.as_str()).ok_or("content.hash missing")?;
+    let hash: [u8; 32] = hex::decode(hash.trim_start_matches("0x"))
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or("content.hash must be 32 bytes of hex")?;
+    let size = v.get("size").and_then(|s| s.as_u64()).ok_or("content.size missing")?;
+    let mime = v.get("mime").and_then(|m| m.as_str()).ok_or("content.mime missing")?;
+    let hints = v.get("hints").and_then(|h| h.as_array()).cloned().unwrap_or_default()
+        .iter()
+        .map(|h| h.as_str().ok_or_else(|| "content.hints must be strings".to_string())
+            .and_then(bleep_governance::RetrievalHint::parse))
+        .collect::<Result<Vec<_>, String>>()?;
+    let commitment = ContentCommitment { hash, size, mime: mime.to_string(), hints };
+    commitment.validate()?;
+    Ok(commitment)
+}
 
 pub fn governance_propose_route(
     state: Arc<RpcState>,
 ) -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
-    let _st = Arc::clone(&state);
+    let st = Arc::clone(&state);
     warp::path!("rpc" / "governance" / "propose")
         .and(warp::post())
         .and(warp::body::content_length_limit(65_536))
@@ -2801,6 +3869,8 @@ pub fn governance_propose_route(
             let title       = body.get("title").and_then(|v| v.as_str()).unwrap_or("(untitled)");
             let description = body.get("description").and_then(|v| v.as_str()).unwrap_or("");
             let deposit     = body.get("deposit").and_then(|v| v.as_u64()).unwrap_or(0);
+            let param       = body.get("param").and_then(|v| v.as_str()).unwrap_or("rpc_proposal");
+            let new_value   = body.get("new_value").and_then(|v| v.as_u64()).unwrap_or(0);
             if deposit < 10_000_000_000_000 {
                 return warp::reply::json(&serde_json::json!({
                     "error": "insufficient_deposit",
@@ -2808,11 +3878,50 @@ pub fn governance_propose_route(
                     "provided": deposit
                 }));
             }
+            if description.len() > MAX_INLINE_DESCRIPTION {
+                return warp::reply::json(&serde_json::json!({
+                    "error": "description_too_large",
+                    "size": description.len(),
+                    "max": MAX_INLINE_DESCRIPTION,
+                    "hint": "publish the full text off-chain and pass `content` {hash, size, mime, hints} instead"
+                }));
+            }
+            let content = match body.get("content").map(parse_content).transpose() {
+                Ok(c) => c,
+                Err(e) => return warp::reply::json(&serde_json::json!({
+                    "error": "invalid_content",
+                    "detail": e
+                })),
+            };
+            let content_hash = content.as_ref().map(ContentCommitment::hash_hex);
             let pid: u64 = (title.len() as u64).wrapping_mul(0x9e3779b9).wrapping_add(deposit);
+            if let Some(engine) = &st.governance {
+                let mut proposal = Proposal::new(
+                    pid.to_string(),
+                    ProposalType::ProtocolParameter,
+                    title.to_string(),
+                    description.to_string(),
+                    VotingWindow { start_epoch: 0, end_epoch: 10, min_duration: 10 },
+                    11,
+                    67,
+                    GovernancePayload::ProtocolParameterChange { rule_name: param.into(), new_value: new_value as u128 },
+                    0,
+                );
+                proposal.content = content;
+                if let Err(e) = engine.lock().submit_proposal(proposal) {
+                    return warp::reply::json(&serde_json::json!({
+                        "error": "proposal_rejected",
+                        "detail": e.to_string()
+                    }));
+                }
+            }
             warp::reply::json(&serde_json::json!({
                 "proposal_id":       pid,
                 "title":             title,
                 "description":       description,
+                "content_hash":      content_hash,
+                "param":             param,
+                "new_value":         new_value,
                 "deposit":           deposit,
                 "state":             "Active",
                 "voting_end_block":  "current_block + 1000",
@@ -0,0 +1,89 @@
+//! # Metrics recorder
+//!
+//! Crates record through the `metrics` macros (`wasm_executions`,
+//! `wasm_execution_seconds`, …), which are no-ops until a recorder is
+//! installed.  With the `prometheus` feature, `install_recorder` installs
+//! the Prometheus exporter's recorder process-wide and `GET /metrics`
+//! appends `render()` to the node gauges.  Without it the exporter is not
+//! compiled in and `render()` is empty.
+
+#[cfg(feature = "prometheus")]
+mod recorder {
+    use std::sync::OnceLock;
+
+    use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+    pub(super) static HANDLE: OnceLock<Result<PrometheusHandle, String>> = OnceLock::new();
+
+    /// Install the process-wide recorder.  Safe to call more than once;
+    /// every call returns the first call's outcome.
+    pub fn install_recorder() -> Result<PrometheusHandle, String> {
+        HANDLE
+            .get_or_init(|| PrometheusBuilder::new().install_recorder().map_err(|e| e.to_string()))
+            .clone()
+    }
+}
+
+#[cfg(feature = "prometheus")]
+pub use recorder::install_recorder;
+
+/// Everything recorded so far, in Prometheus text format; empty when no
+/// recorder is installed.
+pub fn render() -> String {
+    #[cfg(feature = "prometheus")]
+    if let Some(Ok(handle)) = recorder::HANDLE.get() {
+        return handle.render();
+    }
+    String::new()
+}
+
+#[cfg(all(test, feature = "prometheus"))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use bleep_vm::engines::wasm_engine::WasmRuntime;
+    use bleep_vm::types::GasSchedule;
+    use wasm_encoder::{
+        CodeSection, ExportKind, ExportSection, Function, FunctionSection, Instruction, Module,
+        TypeSection, ValType,
+    };
+
+    use crate::{metrics_prometheus, RpcState};
+
+    /// `call_contract() -> i32` returning 42.
+    fn answer_wasm() -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.function([] as [ValType; 0], [ValType::I32]);
+        let mut funcs = FunctionSection::new();
+        funcs.function(0);
+        let mut exports = ExportSection::new();
+        exports.export("call_contract", ExportKind::Func, 0);
+        let mut body = Function::new([]);
+        body.instruction(&Instruction::I32Const(42)).instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut module = Module::new();
+        module.section(&types).section(&funcs).section(&exports).section(&code);
+        module.finish()
+    }
+
+    #[tokio::test]
+    async fn scrape_includes_vm_counters() {
+        install_recorder().unwrap();
+        let out = WasmRuntime::new()
+            .execute(&answer_wasm(), 1_000_000, Arc::new(GasSchedule::default()), &[], None)
+            .await
+            .unwrap();
+        assert!(out.success);
+
+        let res = warp::test::request()
+            .path("/metrics")
+            .reply(&metrics_prometheus(Arc::new(RpcState::new())))
+            .await;
+        assert_eq!(res.status(), 200);
+        let body = String::from_utf8(res.body().to_vec()).unwrap();
+        assert!(body.contains("bleep_chain_height 0"), "{}", body);
+        assert!(body.contains("wasm_executions"), "{}", body);
+    }
+}
@@ -0,0 +1,214 @@
+//! # Network introspection
+//!
+//! - `GET /rpc/net/info`  — this node's id, agent, listen address, operator
+//!   metadata and peer count
+//! - `GET /rpc/net/peers` — peers that completed the `Hello` handshake, with
+//!   id, moniker, agent, addresses and liveness (`connected_at`,
+//!   `last_seen`, `messages_in`) and, with `ServiceQuotas` attached, what
+//!   each was served (`service`: per-class requests, bytes and refusals,
+//!   expensive requests running and queued)
+//!
+//! - `GET /rpc/net/dht`   — Kademlia DHT stats: routing table bucket fill,
+//!   stored values and provider records, lookup latency
+//!
+//! The first two read the `bleep_p2p::PeerDirectory` attached with
+//! `RpcState::with_peer_directory`, the last the DHT attached with
+//! `RpcState::with_dht`.  Snap-sync requests are anonymous and charged to
+//! the remote IP, so a peer's `service` includes those of its address.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use warp::http::StatusCode;
+use warp::Filter;
+
+use bleep_p2p::{NodeId, PeerDirectory, PeerKey, PeerServiceStats, PeerSummary, ServiceQuotas};
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+#[derive(Serialize)]
+struct PeersResp {
+    count: usize,
+    peers: Vec<PeerEntry>,
+}
+
+#[derive(Serialize)]
+struct PeerEntry {
+    #[serde(flatten)]
+    peer:    PeerSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    service: Option<PeerServiceStats>,
+}
+
+/// What `peer` was served, under its node id and its address.
+fn service_stats(quotas: &ServiceQuotas, peer: &PeerSummary) -> PeerServiceStats {
+    let by_addr = quotas.stats(&PeerKey::Addr(peer.remote_addr.ip())).unwrap_or_default();
+    let by_node = hex::decode(&peer.node_id).ok()
+        .and_then(|b| <[u8; 32]>::try_from(b).ok())
+        .and_then(|id| quotas.stats(&PeerKey::Node(NodeId(id))))
+        .unwrap_or_default();
+    by_node.merge(&by_addr)
+}
+
+fn not_attached() -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(
+        warp::reply::json(&ErrResp { error: "P2P node not attached".into() }),
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+}
+
+fn directory(st: &RpcState) -> Option<&Arc<PeerDirectory>> {
+    st.peer_directory.as_ref()
+}
+
+// ── GET /rpc/net/info ─────────────────────────────────────────────────────────
+pub(crate) fn net_info(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "net" / "info")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|st: Arc<RpcState>| match directory(&st) {
+            Some(d) => warp::reply::with_status(warp::reply::json(&d.info()), StatusCode::OK),
+            None => not_attached(),
+        })
+}
+
+// ── GET /rpc/net/dht ──────────────────────────────────────────────────────────
+pub(crate) fn net_dht(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "net" / "dht")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|st: Arc<RpcState>| match &st.dht {
+            Some(dht) => warp::reply::with_status(warp::reply::json(&dht.stats()), StatusCode::OK),
+            None => not_attached(),
+        })
+}
+
+// ── GET /rpc/net/peers ────────────────────────────────────────────────────────
+pub(crate) fn net_peers(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "net" / "peers")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|st: Arc<RpcState>| match directory(&st) {
+            Some(d) => {
+                let peers: Vec<PeerEntry> = d.peers().into_iter()
+                    .map(|peer| PeerEntry {
+                        service: st.service_quotas.as_ref().map(|q| service_stats(q, &peer)),
+                        peer,
+                    })
+                    .collect();
+                warp::reply::with_status(
+                    warp::reply::json(&PeersResp { count: peers.len(), peers }),
+                    StatusCode::OK,
+                )
+            }
+            None => not_attached(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_p2p::node_info::{Hello, NodeMetadata};
+    use bleep_p2p::quantum_crypto::NodeIdentity;
+    use bleep_p2p::{KademliaDht, NodeId, PeerInfo};
+
+    fn hello(moniker: &str, port: u16) -> Hello {
+        Hello::new(
+            &NodeIdentity::generate(),
+            "bleep/test",
+            ([127, 0, 0, 1], port).into(),
+            NodeMetadata::new(moniker, "", "local"),
+        )
+    }
+
+    async fn peers(routes: &(impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone + 'static)) -> serde_json::Value {
+        let res = warp::test::request().path("/rpc/net/peers").reply(routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        serde_json::from_slice(res.body()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn peers_endpoint_follows_connects_and_disconnects() {
+        let dir = Arc::new(PeerDirectory::new(hello("local", 7700)));
+        let st = Arc::new(RpcState::new().with_peer_directory(Arc::clone(&dir)));
+        let routes = net_peers(Arc::clone(&st)).or(net_info(st));
+
+        assert_eq!(peers(&routes).await["count"], 0);
+
+        let alpha = hello("alpha", 7701);
+        let alpha_id = alpha.node_id.clone();
+        dir.on_hello(alpha, "127.0.0.1:50001".parse().unwrap()).unwrap();
+        dir.on_hello(hello("beta", 7702), "127.0.0.1:50002".parse().unwrap()).unwrap();
+        let body = peers(&routes).await;
+        assert_eq!(body["count"], 2);
+        let monikers: Vec<&str> = body["peers"].as_array().unwrap().iter()
+            .map(|p| p["moniker"].as_str().unwrap()).collect();
+        assert!(monikers.contains(&"alpha") && monikers.contains(&"beta"));
+
+        dir.on_disconnect(&alpha_id);
+        let body = peers(&routes).await;
+        assert_eq!(body["count"], 1);
+        assert_eq!(body["peers"][0]["moniker"], "beta");
+
+        let res = warp::test::request().path("/rpc/net/info").reply(&routes).await;
+        let info: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(info["metadata"]["moniker"], "local");
+        assert_eq!(info["peer_count"], 1);
+    }
+
+    #[tokio::test]
+    async fn peers_endpoint_shows_service_quota_stats() {
+        use bleep_p2p::{ServiceClass, ServiceQuotaConfig};
+
+        let dir = Arc::new(PeerDirectory::new(hello("local", 7700)));
+        let quotas = Arc::new(ServiceQuotas::new(ServiceQuotaConfig::default()));
+        let st = Arc::new(RpcState::new().with_peer_directory(Arc::clone(&dir)).with_service_quotas(Arc::clone(&quotas)));
+        let routes = net_peers(st);
+
+        let alpha = hello("alpha", 7701);
+        let alpha_id = alpha.node_id.clone();
+        dir.on_hello(alpha, "10.0.0.1:50001".parse().unwrap()).unwrap();
+        let body = peers(&routes).await;
+        assert_eq!(body["peers"][0]["service"]["classes"], serde_json::json!({}));
+
+        // Snap requests from its address, DHT requests under its id.
+        let by_addr = PeerKey::Addr("10.0.0.1".parse().unwrap());
+        quotas.admit(&by_addr, ServiceClass::Sync, 0).unwrap();
+        quotas.charge(&by_addr, ServiceClass::Sync, 4096, 0);
+        let by_node = PeerKey::Node(alpha_id);
+        quotas.admit(&by_node, ServiceClass::Dht, 0).unwrap();
+        quotas.charge(&by_node, ServiceClass::Dht, 100, 0);
+
+        let body = peers(&routes).await;
+        let service = &body["peers"][0]["service"];
+        assert_eq!(body["peers"][0]["moniker"], "alpha");
+        assert_eq!(service["classes"]["sync"]["bytes"], 4096);
+        assert_eq!(service["classes"]["dht"]["served"], 1);
+        assert_eq!(service["in_flight"], 0);
+    }
+
+    #[tokio::test]
+    async fn dht_endpoint_reports_bucket_fill() {
+        let routes = net_dht(Arc::new(RpcState::new()));
+        let res = warp::test::request().path("/rpc/net/dht").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let dht = Arc::new(KademliaDht::new(NodeId::random()));
+        for port in 1..=3 {
+            dht.add_peer(PeerInfo::new(NodeId::random(), ([127, 0, 0, 1], port).into(), vec![], vec![])).await;
+        }
+        let routes = net_dht(Arc::new(RpcState::new().with_dht(dht)));
+        let res = warp::test::request().path("/rpc/net/dht").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let stats: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(stats["peers"], 3);
+        let filled: u64 = stats["buckets"].as_array().unwrap().iter().map(|b| b["peers"].as_u64().unwrap()).sum();
+        assert_eq!(filled, 3);
+    }
+}
@@ -0,0 +1,133 @@
+//! # Runtime parameters
+//!
+//! Read side of `bleep_governance::ParameterRegistry`, attached with
+//! `RpcState::with_parameter_registry`:
+//!
+//! - `GET /rpc/governance/parameters?subsystem=` — every registered
+//!   parameter with its bounds, mutability, current value and last change
+//! - `GET /rpc/governance/parameters/{key}/history` — the parameter's
+//!   changes, oldest first, each naming the proposal or config reload
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use bleep_governance::{ParameterChange, ParameterView};
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+#[derive(Debug, Default, Deserialize)]
+struct ParametersQuery {
+    subsystem: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ParametersResp {
+    parameters: Vec<ParameterView>,
+    subsystems: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct HistoryResp {
+    key:     String,
+    value:   u128,
+    changes: Vec<ParameterChange>,
+}
+
+type ParamReply = warp::reply::WithStatus<warp::reply::Json>;
+
+fn err(msg: &str, status: StatusCode) -> ParamReply {
+    warp::reply::with_status(warp::reply::json(&ErrResp { error: msg.into() }), status)
+}
+
+const NOT_ATTACHED: &str = "Parameter registry not attached";
+
+/// All `/rpc/governance/parameters` routes.
+pub(crate) fn parameter_routes(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    // GET /rpc/governance/parameters
+    let list = warp::path!("rpc" / "governance" / "parameters")
+        .and(warp::get())
+        .and(warp::query::<ParametersQuery>())
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|q: ParametersQuery, st: Arc<RpcState>| {
+            let registry = match &st.parameters {
+                Some(r) => r.lock(),
+                None => return err(NOT_ATTACHED, StatusCode::SERVICE_UNAVAILABLE),
+            };
+            let resp = ParametersResp {
+                parameters: registry.list(q.subsystem.as_deref()),
+                subsystems: registry.subsystems(),
+            };
+            warp::reply::with_status(warp::reply::json(&resp), StatusCode::OK)
+        });
+
+    // GET /rpc/governance/parameters/{key}/history
+    let history = warp::path!("rpc" / "governance" / "parameters" / String / "history")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|key: String, st: Arc<RpcState>| {
+            let registry = match &st.parameters {
+                Some(r) => r.lock(),
+                None => return err(NOT_ATTACHED, StatusCode::SERVICE_UNAVAILABLE),
+            };
+            match registry.history(&key) {
+                Ok(changes) => {
+                    let resp = HistoryResp {
+                        value:   registry.value(&key).unwrap_or_default(),
+                        changes: changes.to_vec(),
+                        key,
+                    };
+                    warp::reply::with_status(warp::reply::json(&resp), StatusCode::OK)
+                }
+                Err(e) => err(&e.to_string(), StatusCode::NOT_FOUND),
+            }
+        });
+
+    list.or(history)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_governance::{ParamKind, ParameterRegistry, ParameterSpec};
+    use parking_lot::Mutex;
+
+    fn routes() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+        let mut registry = ParameterRegistry::genesis().unwrap();
+        registry.register_live(
+            ParameterSpec::new("MIN_BASE_FEE", "fees", ParamKind::Amount, 100, 1, 1_000_000).node_local(),
+            || 1_234,
+        ).unwrap();
+        registry.apply_proposal("CHECKPOINT_FREQUENCY", 300, "prop-9", 4).unwrap();
+        parameter_routes(Arc::new(RpcState::new().with_parameter_registry(Arc::new(Mutex::new(registry)))))
+    }
+
+    async fn get(path: &str) -> (StatusCode, serde_json::Value) {
+        let res = warp::test::request().path(path).reply(&routes()).await;
+        (res.status(), serde_json::from_slice(res.body()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn lists_filters_and_reports_history() {
+        let (status, body) = get("/rpc/governance/parameters").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["subsystems"].as_array().unwrap().len() >= 3);
+
+        let (_, body) = get("/rpc/governance/parameters?subsystem=fees").await;
+        let fees = body["parameters"].as_array().unwrap();
+        assert_eq!(fees.len(), 1);
+        assert_eq!((fees[0]["value"].as_u64(), fees[0]["mutability"].as_str()), (Some(1_234), Some("node_local")));
+
+        let (status, body) = get("/rpc/governance/parameters/CHECKPOINT_FREQUENCY/history").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["value"], 300);
+        assert_eq!(body["changes"][0]["source"]["proposal_id"], "prop-9");
+
+        let (status, _) = get("/rpc/governance/parameters/NOPE/history").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,147 @@
+//! # Wallet portfolio
+//!
+//! Backs `GET /rpc/wallet/{address}/portfolio` with a
+//! `bleep_wallet_core::portfolio::PortfolioService`, and provides the
+//! balance sources the node can offer directly:
+//!
+//! - `StateBalanceSource` — native balance held in one shard's `StateManager`
+//! - `PatBalanceSource`   — every PAT token balance in the `PATRegistry`
+//!
+//! Bridged-asset sources come from chain adapters and are registered with
+//! `PortfolioService::with_source` by whoever owns the adapter.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use warp::Filter;
+
+use bleep_pat::PATRegistry;
+use bleep_state::state_manager::StateManager;
+use bleep_wallet_core::portfolio::{Asset, AssetBalance, BalanceSource, PortfolioService};
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+/// Native BLEEP balance in one shard.
+pub struct StateBalanceSource {
+    name:  String,
+    state: Arc<Mutex<StateManager>>,
+}
+
+impl StateBalanceSource {
+    /// `shard` names the source, e.g. `shard-0`.
+    pub fn new(shard: impl Into<String>, state: Arc<Mutex<StateManager>>) -> Self {
+        Self { name: shard.into(), state }
+    }
+}
+
+#[async_trait]
+impl BalanceSource for StateBalanceSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn balances(&self, address: &str) -> Result<Vec<AssetBalance>, String> {
+        let amount = self.state.lock().get_balance(address);
+        Ok(vec![AssetBalance { asset: Asset::Native, amount }])
+    }
+}
+
+/// Non-zero balances of every PAT token.
+pub struct PatBalanceSource {
+    registry: Arc<Mutex<PATRegistry>>,
+}
+
+impl PatBalanceSource {
+    pub fn new(registry: Arc<Mutex<PATRegistry>>) -> Self {
+        Self { registry }
+    }
+}
+
+#[async_trait]
+impl BalanceSource for PatBalanceSource {
+    fn name(&self) -> &str {
+        "pat"
+    }
+
+    async fn balances(&self, address: &str) -> Result<Vec<AssetBalance>, String> {
+        // PAT accounts are 32-byte hex; an address that is not hex cannot
+        // hold PAT tokens.
+        let addr = match crate::hex_to_address(address) {
+            Ok(a) => a,
+            Err(_) => return Ok(Vec::new()),
+        };
+        let reg = self.registry.lock();
+        Ok(reg.list_tokens().into_iter()
+            .map(|t| AssetBalance {
+                asset:  Asset::Pat { symbol: t.symbol.clone() },
+                amount: reg.balance_of(&t.symbol, &addr),
+            })
+            .filter(|b| b.amount > 0)
+            .collect())
+    }
+}
+
+// ── GET /rpc/wallet/{address}/portfolio ───────────────────────────────────────
+pub(crate) fn wallet_portfolio(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "wallet" / String / "portfolio")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .and_then(|address: String, st: Arc<RpcState>| async move {
+            let svc: Arc<PortfolioService> = match &st.portfolio {
+                Some(svc) => Arc::clone(svc),
+                None => {
+                    return Ok::<_, warp::Rejection>(warp::reply::with_status(
+                        warp::reply::json(&ErrResp { error: "Portfolio service not attached".into() }),
+                        warp::http::StatusCode::SERVICE_UNAVAILABLE,
+                    ));
+                }
+            };
+            let portfolio = svc.portfolio(&address).await;
+            Ok(warp::reply::with_status(
+                warp::reply::json(&portfolio),
+                warp::http::StatusCode::OK,
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_pat::PATIntent;
+
+    #[tokio::test]
+    async fn route_aggregates_state_and_pat() {
+        let state = Arc::new(Mutex::new(StateManager::new()));
+        let addr = hex::encode([7u8; 32]);
+        state.lock().set_balance(&addr, 500);
+
+        let owner = [7u8; 32];
+        let registry = Arc::new(Mutex::new(PATRegistry::new()));
+        {
+            let mut reg = registry.lock();
+            reg.execute(&PATIntent::create_token(owner, "GLD", "Gold", 0, 0, 0, false)).unwrap();
+            reg.execute(&PATIntent::mint(owner, "GLD", owner, 40)).unwrap();
+        }
+
+        let svc = PortfolioService::new(Default::default())
+            .with_source(Arc::new(StateBalanceSource::new("shard-0", state)))
+            .with_source(Arc::new(PatBalanceSource::new(registry)));
+        let st = Arc::new(RpcState::new().with_portfolio(Arc::new(svc)));
+
+        let resp = warp::test::request()
+            .method("GET")
+            .path(&format!("/rpc/wallet/{}/portfolio", addr))
+            .reply(&wallet_portfolio(st))
+            .await;
+        assert_eq!(resp.status(), 200);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["totals"][0]["kind"], "native");
+        assert_eq!(body["totals"][0]["amount"], "500");
+        assert_eq!(body["totals"][1]["symbol"], "GLD");
+        assert_eq!(body["totals"][1]["amount"], "40");
+        assert_eq!(body["stale"], false);
+    }
+}
@@ -0,0 +1,274 @@
+//! # Read-only replicas
+//!
+//! A node started with `--role replica` follows the chain without keys, a
+//! mempool or a proposer, and serves the read-only subset of the API:
+//! blocks, state, receipts, events, proofs and telemetry.  With
+//! `RpcState::with_replica` attached:
+//!
+//! - Every request that would submit a transaction, change node state or
+//!   reach `/rpc/admin/*` is rejected with 403 and
+//!   `{"error": "...", "code": "replica_mode"}`.  `GET`/`HEAD` requests and
+//!   the side-effect-free `POST`s in `READ_ONLY_POSTS` pass.
+//! - `GET /rpc/ready` answers 200 only while the replica is within
+//!   `max_lag` blocks of the highest height seen upstream, 503 otherwise,
+//!   so load balancers stop routing reads to a replica that fell behind.
+//!
+//! Without a replica attached `/rpc/ready` always reports ready.  A
+//! validator in partition safe mode (`RpcState::with_partition_detector`)
+//! still serves reads, so it stays ready but is reported `degraded` with
+//! the reason.  A node still snap-syncing (`RpcState::with_snap_sync`) is
+//! not ready whatever its role, and reports its download progress.
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use warp::http::{Method, StatusCode};
+use warp::path::FullPath;
+use warp::{Filter, Rejection};
+
+use bleep_consensus::replica::SyncStatus;
+use bleep_consensus::snap_sync::SnapProgress;
+
+use crate::{with_arc_state, RpcState};
+
+/// Default `max_lag`: blocks a replica may trail upstream and stay ready.
+pub const DEFAULT_MAX_LAG: u64 = 5;
+
+/// `POST` routes that only read, and so stay open on replicas.
+pub const READ_ONLY_POSTS: &[&str] = &["/rpc/tx/simulate", "/rpc/wallet/verify-message"];
+
+/// Sync progress a replica's `/rpc/ready` reports.
+#[derive(Clone)]
+pub struct ReplicaRpc {
+    pub status:  Arc<SyncStatus>,
+    pub max_lag: u64,
+}
+
+/// Whether a replica serves `method path`.
+pub fn replica_allows(method: &Method, path: &str) -> bool {
+    if path.starts_with("/rpc/admin") {
+        return false;
+    }
+    method == Method::GET || method == Method::HEAD || READ_ONLY_POSTS.contains(&path)
+}
+
+#[derive(Debug)]
+struct ReplicaModeRejection {
+    method: Method,
+    path:   String,
+}
+
+impl warp::reject::Reject for ReplicaModeRejection {}
+
+#[derive(Serialize)]
+struct ReplicaModeResp {
+    error: String,
+    code:  &'static str,
+}
+
+/// Rejects writes on replicas with a `ReplicaModeRejection`.
+/// Put in front of the route tree and pair with `recover_replica_mode`.
+pub fn replica_guard(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::method()
+        .and(warp::path::full())
+        .and(with_arc_state(state))
+        .and_then(|method: Method, path: FullPath, st: Arc<RpcState>| async move {
+            if st.replica.is_none() || replica_allows(&method, path.as_str()) {
+                return Ok(());
+            }
+            Err(warp::reject::custom(ReplicaModeRejection { method, path: path.as_str().to_string() }))
+        })
+        .untuple_one()
+}
+
+/// Turn a `ReplicaModeRejection` into its 403 response; pass others through.
+pub async fn recover_replica_mode(err: Rejection) -> Result<warp::reply::Response, Rejection> {
+    use warp::Reply;
+    match err.find::<ReplicaModeRejection>() {
+        Some(r) => Ok(warp::reply::with_status(
+            warp::reply::json(&ReplicaModeResp {
+                error: format!("replica mode: {} {} is not served by read-only replicas", r.method, r.path),
+                code:  "replica_mode",
+            }),
+            StatusCode::FORBIDDEN,
+        ).into_response()),
+        None => Err(err),
+    }
+}
+
+#[derive(Serialize)]
+struct ReadyResp {
+    ready:        bool,
+    role:         &'static str,
+    height:       u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream_tip: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lag:          Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_lag:      Option<u64>,
+    degraded:     bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason:       Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snap_sync:    Option<SnapSyncResp>,
+}
+
+#[derive(Serialize)]
+struct SnapSyncResp {
+    chunks_done:   u64,
+    chunks_total:  u64,
+    bytes_per_sec: u64,
+}
+
+impl From<&SnapProgress> for SnapSyncResp {
+    fn from(p: &SnapProgress) -> Self {
+        SnapSyncResp { chunks_done: p.chunks_done(), chunks_total: p.chunks_total(), bytes_per_sec: p.bytes_per_sec() }
+    }
+}
+
+// ── GET /rpc/ready ────────────────────────────────────────────────────────────
+pub(crate) fn ready(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "ready")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|st: Arc<RpcState>| {
+            let mut resp = match &st.replica {
+                Some(r) => ReadyResp {
+                    ready:        r.status.is_synced(r.max_lag),
+                    role:         "replica",
+                    height:       r.status.height(),
+                    upstream_tip: Some(r.status.upstream_tip()),
+                    lag:          Some(r.status.lag()),
+                    max_lag:      Some(r.max_lag),
+                    degraded:     false,
+                    reason:       None,
+                    snap_sync:    None,
+                },
+                None => {
+                    let partition = st.partition.as_ref().map(|p| p.status());
+                    ReadyResp {
+                        ready:        true,
+                        role:         "validator",
+                        height:       st.chain_height.load(std::sync::atomic::Ordering::Relaxed),
+                        upstream_tip: None,
+                        lag:          None,
+                        max_lag:      None,
+                        degraded:     partition.as_ref().map_or(false, |p| p.safe_mode),
+                        reason:       partition.and_then(|p| p.reason),
+                        snap_sync:    None,
+                    }
+                }
+            };
+            if let Some(p) = st.snap_sync.as_ref().filter(|p| !p.is_complete()) {
+                resp.ready = false;
+                resp.snap_sync = Some(SnapSyncResp::from(p.as_ref()));
+            }
+            let status = if resp.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+            warp::reply::with_status(warp::reply::json(&resp), status)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_consensus::partition::{PartitionConfig, PartitionDetector};
+
+    #[test]
+    fn replicas_serve_reads_only() {
+        assert!(replica_allows(&Method::GET, "/rpc/state/abc"));
+        assert!(replica_allows(&Method::GET, "/rpc/block/3/receipts"));
+        assert!(replica_allows(&Method::POST, "/rpc/tx/simulate"));
+        assert!(!replica_allows(&Method::POST, "/rpc/tx"));
+        assert!(!replica_allows(&Method::POST, "/rpc/tx/schedule"));
+        assert!(!replica_allows(&Method::POST, "/faucet/abc"));
+        assert!(!replica_allows(&Method::GET, "/rpc/admin/keys"));
+    }
+
+    #[tokio::test]
+    async fn writes_get_the_replica_error_and_ready_tracks_lag() {
+        let status = Arc::new(SyncStatus::new(10));
+        let routes = crate::rpc_routes_with_state(
+            RpcState::new().with_replica(Arc::clone(&status), 2),
+        );
+
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/tx")
+            .json(&serde_json::json!({
+                "sender": "a", "receiver": "b", "amount": 1, "timestamp": 1, "signature": []
+            }))
+            .reply(&routes)
+            .await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["code"], "replica_mode");
+
+        let res = warp::test::request().path("/rpc/ready").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        status.observe_upstream(13);
+        let res = warp::test::request().path("/rpc/ready").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!((body["role"].as_str(), body["lag"].as_u64()), (Some("replica"), Some(3)));
+
+        // Validators are always ready.
+        let res = warp::test::request()
+            .path("/rpc/ready")
+            .reply(&crate::rpc_routes_with_state(RpcState::new()))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn not_ready_while_snap_syncing() {
+        let routes = crate::rpc_routes_with_state(
+            RpcState::new().with_snap_sync(Arc::new(SnapProgress::default())),
+        );
+        let res = warp::test::request().path("/rpc/ready").reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["snap_sync"]["chunks_total"], 0);
+    }
+
+    async fn ready_body(st: &RpcState) -> serde_json::Value {
+        let res = warp::test::request()
+            .path("/rpc/ready")
+            .reply(&crate::rpc_routes_with_state(st.clone()))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK, "safe mode still serves reads");
+        serde_json::from_slice(res.body()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn ready_reports_partition_safe_mode_as_degraded() {
+        let config = PartitionConfig { enter_windows: 1, exit_windows: 2, ..PartitionConfig::default() };
+        let stakes = [("self".to_string(), 1u128), ("peer".to_string(), 3u128)].into_iter().collect();
+        let detector = Arc::new(PartitionDetector::new("self".into(), stakes, config));
+        let st = RpcState::new().with_partition_detector(Arc::clone(&detector));
+
+        detector.heard_from("peer", 0);
+        detector.tick(0);
+        let body = ready_body(&st).await;
+        assert_eq!(body["degraded"], false);
+        assert!(body.get("reason").is_none());
+
+        detector.tick(config.window_ms);
+        let body = ready_body(&st).await;
+        assert_eq!(body["degraded"], true);
+        assert!(body["reason"].as_str().unwrap().contains("25%"));
+
+        let healed = 2 * config.window_ms;
+        detector.heard_from("peer", healed);
+        detector.tick(healed);
+        assert_eq!(ready_body(&st).await["degraded"], true, "one good window is not enough");
+        detector.heard_from("peer", healed + 1);
+        detector.tick(healed + 1);
+        assert_eq!(ready_body(&st).await["degraded"], false);
+    }
+}
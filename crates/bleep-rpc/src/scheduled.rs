@@ -0,0 +1,230 @@
+//! # Scheduled transactions
+//!
+//! Front end of `bleep_core::TxScheduler`:
+//!
+//! - `POST /rpc/tx/schedule`                 — hold a signed tx until a height or time
+//! - `GET  /rpc/tx/scheduled?sender=`        — pending entries
+//! - `POST /rpc/tx/scheduled/{id}/cancel`    — cancel with a signature over `cancel_payload(id)`
+//! - `GET  /rpc/tx/scheduled/events`         — recent released / expired / failed / cancelled
+//!
+//! Signatures use the `POST /rpc/tx` wire format, `pk(64) || sig`.
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use warp::http::StatusCode;
+use warp::Filter;
+
+use bleep_core::block::ChainId;
+use bleep_core::scheduled_tx::Trigger;
+use bleep_core::transaction::ZKTransaction;
+use bleep_core::{ScheduledTx, TxScheduler};
+
+use crate::{now_secs, with_arc_state, ErrResp, RpcState};
+
+#[derive(Deserialize)]
+struct ScheduleReq {
+    sender:            String,
+    receiver:          String,
+    amount:            u64,
+    timestamp:         u64,
+    signature:         Vec<u8>,
+    #[serde(default)]
+    max_fee:           u64,
+    #[serde(default)]
+    tip:               u64,
+    #[serde(default)]
+    nonce:             u64,
+    #[serde(default)]
+    chain_id:          ChainId,
+    /// Exactly one of the two release conditions.
+    #[serde(default)]
+    not_before_height: Option<u64>,
+    #[serde(default)]
+    not_before_time:   Option<u64>,
+    #[serde(default)]
+    expiry_height:     Option<u64>,
+    #[serde(default)]
+    expiry_time:       Option<u64>,
+}
+
+#[derive(Serialize)]
+struct ScheduleResp { id: String }
+
+#[derive(Deserialize)]
+struct SenderQuery {
+    #[serde(default)]
+    sender: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ScheduledEntry {
+    id:         String,
+    sender:     String,
+    receiver:   String,
+    amount:     u64,
+    release:    Trigger,
+    expiry:     Option<Trigger>,
+    created_at: u64,
+}
+
+impl From<ScheduledTx> for ScheduledEntry {
+    fn from(s: ScheduledTx) -> Self {
+        ScheduledEntry {
+            id:         s.id,
+            sender:     s.tx.sender,
+            receiver:   s.tx.receiver,
+            amount:     s.tx.amount,
+            release:    s.release,
+            expiry:     s.expiry,
+            created_at: s.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CancelReq { signature: Vec<u8> }
+
+fn err(msg: String, status: StatusCode) -> warp::reply::WithStatus<warp::reply::Json> {
+    warp::reply::with_status(warp::reply::json(&ErrResp { error: msg }), status)
+}
+
+fn scheduler(st: &RpcState) -> Result<Arc<TxScheduler>, warp::reply::WithStatus<warp::reply::Json>> {
+    st.tx_scheduler.clone()
+        .ok_or_else(|| err("Transaction scheduler not attached".into(), StatusCode::SERVICE_UNAVAILABLE))
+}
+
+fn triggers(req: &ScheduleReq) -> Result<(Trigger, Option<Trigger>), String> {
+    let release = match (req.not_before_height, req.not_before_time) {
+        (Some(h), None) => Trigger::Height(h),
+        (None, Some(t)) => Trigger::Timestamp(t),
+        _ => return Err("set exactly one of not_before_height / not_before_time".into()),
+    };
+    let expiry = match (req.expiry_height, req.expiry_time) {
+        (None, None) => None,
+        (Some(h), None) => Some(Trigger::Height(h)),
+        (None, Some(t)) => Some(Trigger::Timestamp(t)),
+        _ => return Err("set at most one of expiry_height / expiry_time".into()),
+    };
+    Ok((release, expiry))
+}
+
+// ── POST /rpc/tx/schedule ─────────────────────────────────────────────────────
+pub(crate) fn tx_schedule(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "schedule")
+        .and(warp::post())
+        .and(warp::body::json::<ScheduleReq>())
+        .and(with_arc_state(state))
+        .and_then(|req: ScheduleReq, st: Arc<RpcState>| async move {
+            let sched = match scheduler(&st) {
+                Ok(s) => s,
+                Err(resp) => return Ok::<_, warp::Rejection>(resp),
+            };
+            let (release, expiry) = match triggers(&req) {
+                Ok(t) => t,
+                Err(e) => return Ok(err(e, StatusCode::BAD_REQUEST)),
+            };
+            let tx = ZKTransaction {
+                sender:    req.sender,
+                receiver:  req.receiver,
+                amount:    req.amount,
+                timestamp: req.timestamp,
+                signature: req.signature,
+                max_fee:   req.max_fee,
+                tip:       req.tip,
+                nonce:     req.nonce,
+                chain_id:  req.chain_id,
+            };
+            let height = st.chain_height.load(Ordering::Relaxed);
+            Ok(match sched.schedule(tx, release, expiry, height, now_secs()).await {
+                Ok(id) => warp::reply::with_status(warp::reply::json(&ScheduleResp { id }), StatusCode::OK),
+                Err(e) => err(e, StatusCode::BAD_REQUEST),
+            })
+        })
+}
+
+// ── GET /rpc/tx/scheduled?sender= ─────────────────────────────────────────────
+pub(crate) fn tx_scheduled_list(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "scheduled")
+        .and(warp::get())
+        .and(warp::query::<SenderQuery>())
+        .and(with_arc_state(state))
+        .map(|q: SenderQuery, st: Arc<RpcState>| {
+            let sched = match scheduler(&st) {
+                Ok(s) => s,
+                Err(resp) => return resp,
+            };
+            let entries: Vec<ScheduledEntry> = sched.list(q.sender.as_deref())
+                .into_iter()
+                .map(ScheduledEntry::from)
+                .collect();
+            warp::reply::with_status(warp::reply::json(&entries), StatusCode::OK)
+        })
+}
+
+// ── POST /rpc/tx/scheduled/{id}/cancel ────────────────────────────────────────
+pub(crate) fn tx_scheduled_cancel(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "scheduled" / String / "cancel")
+        .and(warp::post())
+        .and(warp::body::json::<CancelReq>())
+        .and(with_arc_state(state))
+        .map(|id: String, req: CancelReq, st: Arc<RpcState>| {
+            let sched = match scheduler(&st) {
+                Ok(s) => s,
+                Err(resp) => return resp,
+            };
+            let height = st.chain_height.load(Ordering::Relaxed);
+            match sched.cancel(&id, &req.signature, height) {
+                Ok(()) => warp::reply::with_status(
+                    warp::reply::json(&serde_json::json!({ "id": id, "cancelled": true })),
+                    StatusCode::OK,
+                ),
+                Err(e) if e.starts_with("no scheduled") => err(e, StatusCode::NOT_FOUND),
+                Err(e) => err(e, StatusCode::FORBIDDEN),
+            }
+        })
+}
+
+// ── GET /rpc/tx/scheduled/events ──────────────────────────────────────────────
+pub(crate) fn tx_scheduled_events(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "scheduled" / "events")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|st: Arc<RpcState>| match scheduler(&st) {
+            Ok(s) => warp::reply::with_status(warp::reply::json(&s.recent_events()), StatusCode::OK),
+            Err(resp) => resp,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_core::transaction_pool::TransactionPool;
+
+    #[tokio::test]
+    async fn schedule_needs_one_release_condition() {
+        let dir = std::env::temp_dir().join(format!("bleep-rpc-sched-{}", std::process::id()));
+        let sched = TxScheduler::open(&dir, &[1u8; 32], TransactionPool::new(10)).unwrap();
+        let st = RpcState::new().with_tx_scheduler(Arc::new(sched));
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/tx/schedule")
+            .json(&serde_json::json!({
+                "sender": "alice", "receiver": "bob", "amount": 5, "timestamp": 1,
+                "signature": [], "not_before_height": 10, "not_before_time": 99,
+            }))
+            .reply(&tx_schedule(Arc::new(st)))
+            .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
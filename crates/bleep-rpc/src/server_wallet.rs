@@ -0,0 +1,358 @@
+//! # Server-side wallet
+//!
+//! Signing with a wallet the node holds, for operators whose tooling talks
+//! to their own RPC rather than keeping keys itself:
+//!
+//! - `GET /rpc/wallet` — whether signing is on, and from which address
+//! - `POST /rpc/wallet/sign` — sign a `bleep_wallet_core` `Transaction` from
+//!   the node wallet; returns its `tx_id`, the signature and the signed
+//!   transaction.  A transaction without an `id` gets the SHA3-256 of its
+//!   signing bytes, and one without a `chain_id` is signed for the wallet's
+//!   network.  Every signed transaction is recorded in the wallet's
+//!   `StateMerkle`.
+//! - `GET /rpc/wallet/balance/{address}` — balance, next nonce and latest
+//!   transaction of `address` in that `StateMerkle`, with a proof against
+//!   its root
+//!
+//! Both routes are off unless the node attaches a wallet with
+//! `RpcState::with_server_wallet`; until then they answer 503.
+//! The sign route spends the node's funds, so it also needs the admin token
+//! in `x-admin-token` and answers 401 without it — and always when no admin
+//! token is configured.  Malformed or invalid transactions get a 400 with a
+//! JSON `error` body.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::Serialize;
+use sha3::{Digest, Sha3_256};
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use bleep_crypto::chain_id::ChainId;
+use bleep_wallet_core::state_merkle::StateProof;
+use bleep_wallet_core::wallet_core::{Transaction, Wallet, WalletError};
+
+use crate::api_keys::{admin_token_ok, ADMIN_TOKEN_HEADER};
+use crate::{with_arc_state, ErrResp, RpcState};
+
+/// The wallet the node signs with.
+pub struct ServerWallet {
+    wallet: Wallet,
+}
+
+impl std::fmt::Debug for ServerWallet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ServerWallet").field("address", &self.wallet.address()).finish_non_exhaustive()
+    }
+}
+
+impl ServerWallet {
+    pub fn new(wallet: Wallet) -> Self {
+        ServerWallet { wallet }
+    }
+
+    /// Open the keystore `Wallet::save_keystore` wrote at `path` and sign
+    /// for `chain_id`.
+    pub fn open(path: &Path, passphrase: &str, chain_id: ChainId) -> Result<Self, String> {
+        let wallet = Wallet::load_keystore(path, passphrase)
+            .map_err(|e| format!("keystore {}: {}", path.display(), e))?;
+        Ok(Self::new(wallet.with_chain_id(chain_id)))
+    }
+
+    pub fn address(&self) -> &str {
+        self.wallet.address()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SignResp {
+    tx_id:     String,
+    /// Hex SPHINCS+ signature over `Transaction::signing_bytes`.
+    signature: String,
+    tx:        Transaction,
+}
+
+#[derive(Debug, Serialize)]
+struct StatusResp<'a> {
+    signing: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    address: Option<&'a str>,
+}
+
+#[derive(Debug, Serialize)]
+struct BalanceResp {
+    address: String,
+    balance: f64,
+    /// Nonce of the next transaction from `address`.
+    nonce:   u64,
+    /// Hex `StateMerkle` root.
+    root:    String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest:  Option<Transaction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof:   Option<StateProof>,
+}
+
+/// Why a wallet request failed.
+#[derive(Debug)]
+enum WalletRpcError {
+    InvalidTransaction(String),
+    Unauthorized,
+    Unavailable(&'static str),
+    Signing(String),
+}
+
+impl WalletRpcError {
+    fn reply(self) -> warp::reply::WithStatus<warp::reply::Json> {
+        let (status, error) = match self {
+            WalletRpcError::InvalidTransaction(e) => (StatusCode::BAD_REQUEST, format!("invalid transaction: {}", e)),
+            WalletRpcError::Unauthorized => (StatusCode::UNAUTHORIZED, "admin token required".to_string()),
+            WalletRpcError::Unavailable(e) => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()),
+            WalletRpcError::Signing(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("signing failed: {}", e)),
+        };
+        warp::reply::with_status(warp::reply::json(&ErrResp { error }), status)
+    }
+}
+
+impl From<WalletError> for WalletRpcError {
+    fn from(e: WalletError) -> Self {
+        match e {
+            WalletError::InvalidTransaction => {
+                WalletRpcError::InvalidTransaction("not for this wallet's address and chain".into())
+            }
+            WalletError::Serialization(e) => WalletRpcError::InvalidTransaction(e),
+            e => WalletRpcError::Signing(e.to_string()),
+        }
+    }
+}
+
+fn sign(server: &ServerWallet, body: &[u8]) -> Result<SignResp, WalletRpcError> {
+    let wallet = &server.wallet;
+    let mut tx: Transaction = serde_json::from_slice(body)
+        .map_err(|e| WalletRpcError::InvalidTransaction(e.to_string()))?;
+    if tx.from != wallet.address() {
+        return Err(WalletRpcError::InvalidTransaction(format!(
+            "from {} is not the node wallet {}", tx.from, wallet.address(),
+        )));
+    }
+    if tx.to.trim().is_empty() {
+        return Err(WalletRpcError::InvalidTransaction("missing recipient".into()));
+    }
+    if tx.to == tx.from {
+        return Err(WalletRpcError::InvalidTransaction("recipient is the sender".into()));
+    }
+    if !(tx.amount.is_finite() && tx.amount > 0.0) {
+        return Err(WalletRpcError::InvalidTransaction(format!("amount {} is not positive", tx.amount)));
+    }
+    if !(tx.fee.is_finite() && tx.fee >= 0.0) {
+        return Err(WalletRpcError::InvalidTransaction(format!("fee {} is negative", tx.fee)));
+    }
+
+    if tx.chain_id.is_none() {
+        tx.chain_id = wallet.chain_id;
+    }
+    if tx.id.is_empty() {
+        tx.id = hex::encode(Sha3_256::digest(tx.signing_bytes()?));
+    }
+    tx.signature.clear();
+    wallet.sign_transaction(&mut tx)?;
+    wallet.store_transaction(tx.clone());
+    Ok(SignResp { tx_id: tx.id.clone(), signature: hex::encode(&tx.signature), tx })
+}
+
+fn balance(server: &ServerWallet, address: String) -> BalanceResp {
+    let state = server.wallet.state_merkle().lock().unwrap_or_else(|e| e.into_inner());
+    let (latest, proof) = match state.get_with_proof(&address) {
+        Some((tx, proof)) => (Some(tx.clone()), Some(proof)),
+        None => (None, None),
+    };
+    BalanceResp {
+        balance: state.balance(&address),
+        nonce:   latest.as_ref().map_or(0, |tx| tx.nonce + 1),
+        root:    hex::encode(state.root()),
+        latest,
+        proof,
+        address,
+    }
+}
+
+/// `GET /rpc/wallet`, `POST /rpc/wallet/sign` and `GET /rpc/wallet/balance/{address}`.
+pub(crate) fn wallet_routes(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    let status_route = warp::path!("rpc" / "wallet")
+        .and(warp::get())
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|st: Arc<RpcState>| {
+            let address = st.server_wallet.as_deref().map(ServerWallet::address);
+            warp::reply::json(&StatusResp { signing: address.is_some(), address })
+        });
+
+    let sign_route = warp::path!("rpc" / "wallet" / "sign")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(16 * 1024))
+        .and(warp::body::bytes())
+        .and(warp::header::optional::<String>(ADMIN_TOKEN_HEADER))
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|body: bytes::Bytes, tok: Option<String>, st: Arc<RpcState>| {
+            let result = match &st.server_wallet {
+                Some(_) if !admin_token_ok(&st, tok.as_deref()) => Err(WalletRpcError::Unauthorized),
+                Some(wallet) => sign(wallet, &body),
+                None => Err(WalletRpcError::Unavailable("no server wallet attached to this node")),
+            };
+            match result {
+                Ok(resp) => warp::reply::with_status(warp::reply::json(&resp), StatusCode::OK),
+                Err(e) => e.reply(),
+            }
+        });
+
+    let balance_route = warp::path!("rpc" / "wallet" / "balance" / String)
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|address: String, st: Arc<RpcState>| {
+            match &st.server_wallet {
+                Some(wallet) => warp::reply::with_status(warp::reply::json(&balance(wallet, address)), StatusCode::OK),
+                None => WalletRpcError::Unavailable("no server wallet attached to this node").reply(),
+            }
+        });
+
+    status_route.or(sign_route).or(balance_route)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_auth::api_keys::ApiKeyRegistry;
+    use bleep_crypto::tx_signer::verify_tx_signature;
+    use bleep_wallet_core::wallet_core::{P2PNode, StateMerkle};
+    use std::sync::Mutex;
+
+    const ADMIN: &str = "admin-secret";
+
+    /// State with `wallet` attached and an admin token configured.
+    fn signing_state(wallet: ServerWallet) -> RpcState {
+        RpcState::new()
+            .with_api_keys(Arc::new(ApiKeyRegistry::new()), ADMIN.into())
+            .with_server_wallet(Arc::new(wallet))
+    }
+
+    fn node_wallet(state: Arc<Mutex<StateMerkle>>) -> Wallet {
+        Wallet::new(Arc::new(P2PNode::new()), state).unwrap()
+    }
+
+    fn transfer(from: &str, extra: &str) -> String {
+        format!(r#"{{"id":"","from":"{}","to":"BLEEP1bob","amount":25.0,"fee":0.5,"signature":[]{}}}"#, from, extra)
+    }
+
+    async fn post(st: &Arc<RpcState>, body: &str) -> (StatusCode, serde_json::Value) {
+        let res = warp::test::request()
+            .method("POST")
+            .path("/rpc/wallet/sign")
+            .header(ADMIN_TOKEN_HEADER, ADMIN)
+            .body(body.to_string())
+            .reply(&wallet_routes(Arc::clone(st)))
+            .await;
+        (res.status(), serde_json::from_slice(res.body()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn sign_returns_a_transaction_the_wallet_key_verifies() {
+        let wallet = node_wallet(Arc::new(Mutex::new(StateMerkle::new())));
+        let (from, public_key) = (wallet.address().to_string(), wallet.public_key.clone());
+        let st = Arc::new(signing_state(ServerWallet::new(wallet)));
+
+        let res = warp::test::request().path("/rpc/wallet").reply(&wallet_routes(Arc::clone(&st))).await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!((body["signing"].as_bool(), body["address"].as_str()), (Some(true), Some(from.as_str())));
+
+        let (status, body) = post(&st, &transfer(&from, r#","nonce":3"#)).await;
+        assert_eq!(status, StatusCode::OK, "{}", body);
+        let tx: Transaction = serde_json::from_value(body["tx"].clone()).unwrap();
+        assert_eq!((tx.from.as_str(), tx.nonce), (from.as_str(), 3));
+        assert_eq!(body["tx_id"].as_str(), Some(tx.id.as_str()));
+        assert_eq!(tx.id.len(), 64);
+        assert_eq!(body["signature"].as_str(), Some(hex::encode(&tx.signature).as_str()));
+        assert!(verify_tx_signature(&tx.signing_bytes().unwrap(), &tx.signature, &public_key));
+    }
+
+    #[tokio::test]
+    async fn invalid_transactions_get_a_json_400() {
+        let wallet = node_wallet(Arc::new(Mutex::new(StateMerkle::new())));
+        let from = wallet.address().to_string();
+        let st = Arc::new(signing_state(ServerWallet::new(wallet.with_chain_id(ChainId(7)))));
+        for body in [
+            transfer("BLEEP1mallory", ""),
+            transfer(&from, "").replace("25.0", "0.0"),
+            transfer(&from, "").replace("0.5", "-1.0"),
+            transfer(&from, "").replace("BLEEP1bob", ""),
+            transfer(&from, "").replace("BLEEP1bob", &from),
+            transfer(&from, r#","chain_id":8"#),
+            format!(r#"{{"from":"{}","to":"BLEEP1bob"}}"#, from),
+            "not json".to_string(),
+        ] {
+            let (status, resp) = post(&st, &body).await;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+            assert!(resp["error"].as_str().unwrap().starts_with("invalid transaction"), "{}", resp);
+        }
+
+        let (status, resp) = post(&Arc::new(RpcState::new()), &transfer(&from, "")).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(resp["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn sign_needs_the_admin_token() {
+        let wallet = || ServerWallet::new(node_wallet(Arc::new(Mutex::new(StateMerkle::new()))));
+        let sign = |st: &Arc<RpcState>| {
+            let from = st.server_wallet.as_deref().unwrap().address().to_string();
+            warp::test::request().method("POST").path("/rpc/wallet/sign").body(transfer(&from, ""))
+        };
+
+        // No admin token configured: the wallet never signs.
+        let open = Arc::new(RpcState::new().with_server_wallet(Arc::new(wallet())));
+        let res = sign(&open).header(ADMIN_TOKEN_HEADER, "").reply(&wallet_routes(Arc::clone(&open))).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let st = Arc::new(signing_state(wallet()));
+        let res = sign(&st).reply(&wallet_routes(Arc::clone(&st))).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = sign(&st).header(ADMIN_TOKEN_HEADER, "guess").reply(&wallet_routes(Arc::clone(&st))).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = sign(&st).header(ADMIN_TOKEN_HEADER, ADMIN).reply(&wallet_routes(Arc::clone(&st))).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn balance_reads_the_wallet_state_merkle() {
+        let state = Arc::new(Mutex::new(StateMerkle::new()));
+        let wallet = node_wallet(Arc::clone(&state));
+        let from = wallet.address().to_string();
+        state.lock().unwrap().credit(&from, 100.0);
+        let st = Arc::new(signing_state(ServerWallet::new(wallet)));
+        let (status, _) = post(&st, &transfer(&from, r#","nonce":4"#)).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let balance = |address: &str| {
+            let filter = wallet_routes(Arc::clone(&st));
+            let path = format!("/rpc/wallet/balance/{}", address);
+            async move {
+                let res = warp::test::request().path(&path).reply(&filter).await;
+                assert_eq!(res.status(), StatusCode::OK);
+                serde_json::from_slice::<serde_json::Value>(res.body()).unwrap()
+            }
+        };
+        let body = balance(&from).await;
+        assert_eq!((body["balance"].as_f64(), body["nonce"].as_u64()), (Some(100.0 - (25.0 + 0.5)), Some(5)));
+        let (tx, proof): (Transaction, StateProof) = (
+            serde_json::from_value(body["latest"].clone()).unwrap(),
+            serde_json::from_value(body["proof"].clone()).unwrap(),
+        );
+        assert!(proof.verify(&state.lock().unwrap().root(), &tx));
+        assert_eq!(body["root"].as_str(), Some(hex::encode(state.lock().unwrap().root()).as_str()));
+
+        let body = balance("BLEEP1bob").await;
+        assert_eq!((body["balance"].as_f64(), body["nonce"].as_u64()), (Some(25.0), Some(0)));
+        assert!(body.get("latest").is_none());
+    }
+}
@@ -0,0 +1,542 @@
+//! # Transaction simulation
+//!
+//! Backs `POST /rpc/tx/simulate`: dry-runs a transfer or contract call
+//! against a throwaway `StateOverlay` and reports what *would* happen,
+//! without touching the live `StateManager` or the mempool.
+//!
+//! Pipeline:
+//!   1. Open an overlay at the requested height (live state by default;
+//!      heights past the journal window are read from the state archive
+//!      on archive nodes and otherwise fail with
+//!      `SimulationError::State(Pruned)`).
+//!   2. Check the sender nonce (if supplied) and move `amount` through the
+//!      overlay — this is where insufficient-balance failures surface.
+//!   3. Transfers run through `Executor::simulate` for gas metering.
+//!      Contract calls run `ContractRegistry::dry_run` on the code hash and
+//!      storage the overlay holds for the contract's state account, so a
+//!      call at a past height sees that height's code and storage.  The
+//!      storage it would write and the events it would emit are reported;
+//!      neither reaches the registry or the state.
+//!
+//! Signatures are not verified: simulation is a pre-flight check, so both
+//! signed and unsigned requests are accepted and executed as unsigned intents.
+
+use std::collections::BTreeMap;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use bleep_state::state_manager::{StateError, StateManager};
+use bleep_state::state_overlay::{AccountChange, StateOverlay};
+use bleep_vm::execution::state_transition::EmittedEvent;
+use bleep_vm::{
+    ChainId, ContractCallIntent, ContractRegistry, Executor, Intent, IntentKind, TargetVm, TransferIntent,
+};
+
+/// Default gas limit for simulated contract calls when none is given.
+pub const DEFAULT_SIMULATION_GAS_LIMIT: u64 = 1_000_000;
+
+// ── Request / response types ─────────────────────────────────────────────────
+
+/// Body of `POST /rpc/tx/simulate`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationRequest {
+    pub sender:       String,
+    pub receiver:     String,
+    #[serde(default)]
+    pub amount:       u128,
+    /// Expected sender nonce; checked against state when present.
+    #[serde(default)]
+    pub nonce:        Option<u64>,
+    /// Hex signature. Accepted for wire compatibility with `POST /rpc/tx`
+    /// but not verified.
+    #[serde(default)]
+    pub signature:    Option<String>,
+    /// Simulate against the state at this height instead of the tip.
+    #[serde(default)]
+    pub block_height: Option<u64>,
+    /// Hex contract address (≤ 32 bytes). Present = contract call.
+    #[serde(default)]
+    pub contract:     Option<String>,
+    /// Hex calldata for contract calls.
+    #[serde(default)]
+    pub calldata:     Option<String>,
+    #[serde(default)]
+    pub gas_limit:    Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulatedEvent {
+    pub contract:  String,
+    pub topics:    Vec<String>,
+    pub data:      String,
+    pub log_index: u32,
+}
+
+impl From<&EmittedEvent> for SimulatedEvent {
+    fn from(ev: &EmittedEvent) -> Self {
+        SimulatedEvent {
+            contract:  hex::encode(ev.contract),
+            topics:    ev.topics.iter().map(hex::encode).collect(),
+            data:      hex::encode(&ev.data),
+            log_index: ev.log_index,
+        }
+    }
+}
+
+/// One contract storage slot a simulated call would change.  Key and
+/// values are hex; `None` is an unset slot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageChange {
+    pub contract: String,
+    pub key:      String,
+    pub before:   Option<String>,
+    pub after:    Option<String>,
+}
+
+/// Response of `POST /rpc/tx/simulate`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimulationResult {
+    pub success:               bool,
+    pub gas_used:              u64,
+    /// Hex-encoded return data.
+    pub output:                String,
+    pub events:                Vec<SimulatedEvent>,
+    pub state_changes_summary: Vec<AccountChange>,
+    /// Contract storage a successful call would write, in key order.
+    pub storage_changes:       Vec<StorageChange>,
+    pub failure_reason:        Option<String>,
+    /// Height the simulation read state from.
+    pub block_height:          u64,
+}
+
+impl SimulationResult {
+    fn failed(block_height: u64, reason: String) -> Self {
+        Self {
+            success:               false,
+            gas_used:              0,
+            output:                String::new(),
+            events:                Vec::new(),
+            state_changes_summary: Vec::new(),
+            storage_changes:       Vec::new(),
+            failure_reason:        Some(reason),
+            block_height,
+        }
+    }
+}
+
+/// Reasons a simulation could not be run at all (as opposed to a simulated
+/// transaction that fails, which is reported in `SimulationResult`).
+#[derive(Debug)]
+pub enum SimulationError {
+    /// Malformed request (bad hex, empty addresses), or a contract call
+    /// on a node without a `ContractRegistry`.
+    InvalidRequest(String),
+    /// Requested height is pruned or in the future.
+    State(StateError),
+}
+
+impl std::fmt::Display for SimulationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimulationError::InvalidRequest(msg) => write!(f, "invalid request: {}", msg),
+            SimulationError::State(e)            => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<StateError> for SimulationError {
+    fn from(e: StateError) -> Self { SimulationError::State(e) }
+}
+
+// ── Simulation ───────────────────────────────────────────────────────────────
+
+/// Map a string account address onto the VM's 32-byte account space.
+pub fn account_key(address: &str) -> [u8; 32] {
+    Sha256::digest(address.as_bytes()).into()
+}
+
+/// Build the VM intent a request would execute as.
+pub fn build_intent(req: &SimulationRequest) -> Result<Intent, SimulationError> {
+    if req.sender.is_empty() || req.receiver.is_empty() {
+        return Err(SimulationError::InvalidRequest("sender and receiver are required".into()));
+    }
+    let kind = match &req.contract {
+        None => IntentKind::Transfer(TransferIntent {
+            from:   account_key(&req.sender),
+            to:     account_key(&req.receiver),
+            amount: req.amount,
+            memo:   None,
+        }),
+        Some(contract_hex) => {
+            let raw = hex::decode(contract_hex.trim_start_matches("0x"))
+                .map_err(|e| SimulationError::InvalidRequest(format!("contract: {}", e)))?;
+            if raw.len() > 32 {
+                return Err(SimulationError::InvalidRequest("contract address longer than 32 bytes".into()));
+            }
+            let mut contract = [0u8; 32];
+            contract[32 - raw.len()..].copy_from_slice(&raw);
+            let calldata = match &req.calldata {
+                Some(c) => hex::decode(c.trim_start_matches("0x"))
+                    .map_err(|e| SimulationError::InvalidRequest(format!("calldata: {}", e)))?,
+                None => Vec::new(),
+            };
+            IntentKind::ContractCall(ContractCallIntent {
+                target_vm: TargetVm::Wasm,
+                contract,
+                calldata,
+                gas_limit: req.gas_limit.unwrap_or(DEFAULT_SIMULATION_GAS_LIMIT),
+                value:     req.amount,
+                hints:     Default::default(),
+            })
+        }
+    };
+    Ok(Intent::new_unsigned(kind, ChainId::Bleep))
+}
+
+/// Code hash and storage of a contract's state account.
+struct ContractState {
+    code_hash: [u8; 32],
+    storage:   BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+/// Outcome of the state checks run before the VM.
+struct Precheck {
+    height:   u64,
+    changes:  Vec<AccountChange>,
+    failure:  Option<String>,
+    /// The called contract as of `height`; `None` for transfers.
+    contract: Option<ContractState>,
+}
+
+fn precheck(mgr: &StateManager, req: &SimulationRequest, intent: &Intent) -> Result<Precheck, SimulationError> {
+    let mut overlay = match req.block_height {
+        Some(h) => StateOverlay::at_height(mgr, h)?,
+        None    => StateOverlay::new(mgr),
+    };
+    let height = overlay.height();
+
+    if let Some(expected) = req.nonce {
+        let current = overlay.nonce(&req.sender)?;
+        if expected != current {
+            return Ok(Precheck {
+                height,
+                changes:  Vec::new(),
+                failure:  Some(format!("nonce mismatch: account at {}, tx has {}", current, expected)),
+                contract: None,
+            });
+        }
+    }
+
+    let mut contract = None;
+    if let IntentKind::ContractCall(call) = &intent.kind {
+        let address = hex::encode(call.contract);
+        let account = overlay.account(&address)?;
+        let Some(code_hash) = account.code_hash else {
+            return Ok(Precheck {
+                height,
+                changes:  Vec::new(),
+                failure:  Some(format!("no contract at {} at height {}", address, height)),
+                contract: None,
+            });
+        };
+        let storage = account.storage.iter()
+            .filter_map(|(k, v)| Some((hex::decode(k).ok()?, hex::decode(v).ok()?)))
+            .collect();
+        contract = Some(ContractState { code_hash, storage });
+    }
+
+    // Contract calls carry `amount` as call value; zero is allowed there.
+    let failure = if req.contract.is_some() && req.amount == 0 {
+        None
+    } else {
+        overlay.transfer(&req.sender, &req.receiver, req.amount)?.err()
+    };
+    Ok(Precheck { height, changes: overlay.changes(), failure, contract })
+}
+
+/// Slots that differ between `before` and `after`.
+fn storage_changes(
+    contract: &[u8; 32],
+    before:   &BTreeMap<Vec<u8>, Vec<u8>>,
+    after:    &BTreeMap<Vec<u8>, Vec<u8>>,
+) -> Vec<StorageChange> {
+    let keys: std::collections::BTreeSet<&Vec<u8>> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|k| before.get(*k) != after.get(*k))
+        .map(|k| StorageChange {
+            contract: hex::encode(contract),
+            key:      hex::encode(k),
+            before:   before.get(k).map(hex::encode),
+            after:    after.get(k).map(hex::encode),
+        })
+        .collect()
+}
+
+/// Dry-run `req` against `state`: transfers through `executor`, contract
+/// calls through `registry`.
+///
+/// The state lock is only held for the overlay checks, never across the
+/// VM call.  Nothing is written to `state` or `registry`.
+pub async fn simulate(
+    state:    &Mutex<StateManager>,
+    executor: &Executor,
+    registry: Option<&ContractRegistry>,
+    req:      &SimulationRequest,
+) -> Result<SimulationResult, SimulationError> {
+    let intent = build_intent(req)?;
+    if matches!(intent.kind, IntentKind::ContractCall(_)) && registry.is_none() {
+        return Err(SimulationError::InvalidRequest("this node has no contract registry to call".into()));
+    }
+    let pre = {
+        let mgr = state.lock();
+        precheck(&mgr, req, &intent)?
+    };
+    if let Some(reason) = pre.failure {
+        return Ok(SimulationResult::failed(pre.height, reason));
+    }
+
+    if let (IntentKind::ContractCall(call), Some(registry), Some(contract)) = (&intent.kind, registry, &pre.contract) {
+        let run = match registry.dry_run(
+            &call.contract, &contract.code_hash, &call.calldata, call.gas_limit, contract.storage.clone(),
+        ) {
+            Ok(run) => run,
+            Err(e) => return Ok(SimulationResult::failed(pre.height, e.to_string())),
+        };
+        return Ok(SimulationResult {
+            success:               true,
+            gas_used:              run.gas_used,
+            output:                hex::encode(&run.output),
+            events:                run.events.iter().map(SimulatedEvent::from).collect(),
+            storage_changes:       storage_changes(&call.contract, &contract.storage, &run.storage),
+            state_changes_summary: pre.changes,
+            failure_reason:        None,
+            block_height:          pre.height,
+        });
+    }
+
+    let outcome = match executor.simulate(&intent).await {
+        Ok(o) => o,
+        Err(e) => return Ok(SimulationResult::failed(pre.height, e.to_string())),
+    };
+    let events = outcome.state_diff().events.iter().map(SimulatedEvent::from).collect();
+    let success = outcome.success();
+
+    Ok(SimulationResult {
+        success,
+        gas_used:              outcome.bleep_gas,
+        output:                hex::encode(outcome.output()),
+        events,
+        state_changes_summary: if success { pre.changes } else { Vec::new() },
+        storage_changes:       Vec::new(),
+        failure_reason:        if success { None } else {
+            Some(outcome.routed_result.result.revert_reason.clone()
+                .unwrap_or_else(|| "execution reverted".into()))
+        },
+        block_height:          pre.height,
+    })
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_vm::ExecutorConfig;
+
+    fn test_executor() -> Executor {
+        let mut cfg = ExecutorConfig::default();
+        cfg.router.verify_signatures  = false;
+        cfg.router.sandbox_validation = false;
+        Executor::production(cfg)
+    }
+
+    fn transfer(sender: &str, receiver: &str, amount: u128) -> SimulationRequest {
+        SimulationRequest {
+            sender:   sender.into(),
+            receiver: receiver.into(),
+            amount,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn insufficient_balance_leaves_state_untouched() {
+        let mgr = Mutex::new(StateManager::new());
+        mgr.lock().mint("alice", 10).unwrap();
+        let exec = test_executor();
+
+        let res = simulate(&mgr, &exec, None, &transfer("alice", "bob", 50)).await.unwrap();
+        assert!(!res.success);
+        assert!(res.failure_reason.unwrap().contains("insufficient balance"));
+        assert!(res.state_changes_summary.is_empty());
+
+        let mgr = mgr.lock();
+        assert_eq!(mgr.get_balance("alice"), 10);
+        assert_eq!(mgr.get_balance("bob"), 0);
+        assert_eq!(mgr.get_nonce("alice"), 0);
+    }
+
+    #[tokio::test]
+    async fn simulated_gas_matches_execution() {
+        let mgr = Mutex::new(StateManager::new());
+        mgr.lock().mint("alice", 1_000).unwrap();
+        let exec = test_executor();
+        let req = transfer("alice", "bob", 400);
+
+        let sim = simulate(&mgr, &exec, None, &req).await.unwrap();
+        assert!(sim.success);
+        assert_eq!(sim.state_changes_summary.len(), 2);
+
+        let real = exec.execute(&build_intent(&req).unwrap()).await.unwrap();
+        assert_eq!(sim.gas_used, real.bleep_gas);
+        // Simulation did not move funds.
+        assert_eq!(mgr.lock().get_balance("alice"), 1_000);
+    }
+
+    #[tokio::test]
+    async fn historical_simulation_and_pruned_height() {
+        let mgr = Mutex::new(StateManager::new());
+        {
+            let mut m = mgr.lock();
+            m.set_history_retention(2);
+            m.mint("alice", 100).unwrap();
+            m.advance_block();                 // height 1, alice = 100
+            m.apply_transfer("alice", "bob", 100);
+            for _ in 0..3 { m.advance_block(); } // height 4, journal keeps 2..3
+        }
+        let exec = test_executor();
+
+        let mut req = transfer("alice", "carol", 60);
+        req.block_height = Some(2);
+        let res = simulate(&mgr, &exec, None, &req).await.unwrap();
+        assert!(!res.success, "alice is empty by height 2");
+
+        req.block_height = Some(0);
+        let err = simulate(&mgr, &exec, None, &req).await.unwrap_err();
+        assert!(matches!(err, SimulationError::State(StateError::Pruned(0))));
+    }
+
+    /// `execute` reads slot "k", writes "k" = "v", emits `Ping` carrying
+    /// the byte it read, and returns that byte.  Memory: "k" at 0, "v" at
+    /// 1, "Ping" at 8, read buffer at 16.
+    fn swapper() -> Vec<u8> {
+        use wasm_encoder::*;
+        let mut types = TypeSection::new();
+        types.function([ValType::I32; 4], [ValType::I32]);
+        types.function([ValType::I32; 4], [] as [ValType; 0]);
+        types.function([ValType::I32], [ValType::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", "storage_read", EntityType::Function(0));
+        imports.import("bleep", "storage_write", EntityType::Function(1));
+        imports.import("bleep", "emit_event", EntityType::Function(1));
+        let mut funcs = FunctionSection::new();
+        funcs.function(2);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export("execute", ExportKind::Func, 3);
+        let mut exec = Function::new([] as [(u32, ValType); 0]);
+        for i in [
+            Instruction::I32Const(0), Instruction::I32Const(1),
+            Instruction::I32Const(16), Instruction::I32Const(8),
+            Instruction::Call(0), Instruction::Drop,
+            Instruction::I32Const(0), Instruction::I32Const(1),
+            Instruction::I32Const(1), Instruction::I32Const(1),
+            Instruction::Call(1),
+            Instruction::I32Const(8), Instruction::I32Const(4),
+            Instruction::I32Const(16), Instruction::I32Const(1),
+            Instruction::Call(2),
+            Instruction::I32Const(16),
+            Instruction::I32Load8U(MemArg { offset: 0, align: 0, memory_index: 0 }),
+            Instruction::End,
+        ] {
+            exec.instruction(&i);
+        }
+        let mut code = CodeSection::new();
+        code.function(&exec);
+        let mut segments = DataSection::new();
+        segments.active(0, &ConstExpr::i32_const(0), b"kv".iter().copied());
+        segments.active(0, &ConstExpr::i32_const(8), b"Ping".iter().copied());
+        let mut module = Module::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code).section(&segments);
+        module.finish()
+    }
+
+    /// `mgr` with `swapper` deployed in `registry` and its state account
+    /// holding "k" = `value`.
+    fn deploy_swapper(mgr: &Mutex<StateManager>, registry: &ContractRegistry, value: &[u8]) -> [u8; 32] {
+        let address = registry.deploy(&swapper(), false, None, None).unwrap();
+        let mut m = mgr.lock();
+        m.set_code_hash(&hex::encode(address), registry.info(&address).unwrap().code_hash);
+        m.set_storage(&hex::encode(address), b"k", value);
+        address
+    }
+
+    fn call(address: &[u8; 32]) -> SimulationRequest {
+        SimulationRequest {
+            contract: Some(hex::encode(address)),
+            ..transfer("alice", &hex::encode(address), 0)
+        }
+    }
+
+    #[tokio::test]
+    async fn contract_call_reports_storage_and_events_without_keeping_them() {
+        let mgr = Mutex::new(StateManager::new());
+        let registry = ContractRegistry::new();
+        let exec = test_executor();
+        let address = deploy_swapper(&mgr, &registry, b"a");
+
+        let res = simulate(&mgr, &exec, Some(&registry), &call(&address)).await.unwrap();
+        assert!(res.success, "{:?}", res.failure_reason);
+        assert_eq!(res.output, hex::encode((b'a' as i32).to_le_bytes()));
+        assert_eq!(res.storage_changes, vec![StorageChange {
+            contract: hex::encode(address),
+            key:      hex::encode(b"k"),
+            before:   Some(hex::encode(b"a")),
+            after:    Some(hex::encode(b"v")),
+        }]);
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].topics, vec![hex::encode(bleep_vm::execution::event_abi::event_topic("Ping"))]);
+        assert_eq!(res.events[0].data, hex::encode(b"a"));
+
+        // Neither the state nor the registry saw the call
+        assert_eq!(mgr.lock().get_storage(&hex::encode(address), b"k"), Some(b"a".to_vec()));
+        assert_eq!(registry.storage_get(&address, b"k"), None);
+        assert!(registry.event_log(&address).unwrap().is_empty());
+        assert!(registry.take_events().is_empty());
+
+        let res = simulate(&mgr, &exec, Some(&registry), &call(&[7u8; 32])).await.unwrap();
+        assert!(res.failure_reason.unwrap().contains("no contract"));
+        let err = simulate(&mgr, &exec, None, &call(&address)).await.unwrap_err();
+        assert!(matches!(err, SimulationError::InvalidRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn historical_contract_call_reads_that_heights_storage() {
+        let mgr = Mutex::new(StateManager::new());
+        let registry = ContractRegistry::new();
+        let exec = test_executor();
+        let address = deploy_swapper(&mgr, &registry, b"a");
+        {
+            let mut m = mgr.lock();
+            m.advance_block();                              // height 1, k = "a"
+            m.set_storage(&hex::encode(address), b"k", b"b");
+            m.advance_block();                              // height 2, k = "b"
+        }
+
+        let live = simulate(&mgr, &exec, Some(&registry), &call(&address)).await.unwrap();
+        assert_eq!(live.output, hex::encode((b'b' as i32).to_le_bytes()));
+
+        let mut req = call(&address);
+        req.block_height = Some(1);
+        let past = simulate(&mgr, &exec, Some(&registry), &req).await.unwrap();
+        assert_eq!(past.block_height, 1);
+        assert_eq!(past.output, hex::encode((b'a' as i32).to_le_bytes()));
+        assert_eq!(past.storage_changes[0].before, Some(hex::encode(b"a")));
+        assert_eq!(mgr.lock().get_storage(&hex::encode(address), b"k"), Some(b"b".to_vec()));
+    }
+}
@@ -0,0 +1,164 @@
+//! # Validator bonds
+//!
+//! Read side of the on-chain staking ledger (`bleep_consensus::staking`):
+//!
+//! - `GET /rpc/validator/staking` — bond parameters, state and chain
+//!   heights, and the current epoch when validator sets are attached
+//! - `GET /rpc/validator/bond/{id|owner|consensus key hex}` — the bond with
+//!   its unbonding progress; with `RpcState::with_validator_sets`, also set
+//!   membership, the activation countdown and the slots its leader missed
+
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use serde::Serialize;
+use warp::http::StatusCode;
+use warp::{Filter, Rejection, Reply};
+
+use bleep_consensus::staking::{self, BondRecord, BondStatus};
+use bleep_state::state_manager::StateManager;
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+#[derive(Serialize)]
+struct StakingResp {
+    min_stake:        u128,
+    unbonding_blocks: u64,
+    state_height:     u64,
+    chain_height:     u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    epoch:            Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blocks_per_epoch: Option<u64>,
+}
+
+type StakingReply = warp::reply::WithStatus<warp::reply::Json>;
+
+fn err(msg: &str, status: StatusCode) -> StakingReply {
+    warp::reply::with_status(warp::reply::json(&ErrResp { error: msg.into() }), status)
+}
+
+const NO_STATE: &str = "StateManager unavailable (stub mode)";
+
+/// Bond named by a validator id, its owner's account, or its consensus key.
+fn find_bond(state: &StateManager, who: &str) -> Option<BondRecord> {
+    staking::bond_of(state, who)
+        .or_else(|| staking::bond_of_owner(state, who))
+        .or_else(|| {
+            let key = hex::decode(who).ok()?;
+            staking::bond_of(state, &staking::validator_for_key(state, &key)?)
+        })
+}
+
+/// All `/rpc/validator/staking` and `/rpc/validator/bond` routes.
+pub(crate) fn staking_routes(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    // GET /rpc/validator/staking
+    let params = warp::path!("rpc" / "validator" / "staking")
+        .and(warp::get())
+        .and(with_arc_state(Arc::clone(&state)))
+        .map(|st: Arc<RpcState>| {
+            let Some(mgr) = &st.state_mgr else {
+                return err(NO_STATE, StatusCode::SERVICE_UNAVAILABLE);
+            };
+            let (params, state_height) = {
+                let state = mgr.lock();
+                (staking::params(&state), state.block_height())
+            };
+            let chain_height = st.chain_height.load(Ordering::Relaxed);
+            let sets = st.validator_sets.as_ref().map(|s| s.lock());
+            let resp = StakingResp {
+                min_stake:        params.min_stake,
+                unbonding_blocks: params.unbonding_blocks,
+                state_height,
+                chain_height,
+                epoch:            sets.as_ref().map(|s| s.epoch_of(chain_height)),
+                blocks_per_epoch: sets.as_ref().map(|s| s.config().blocks_per_epoch),
+            };
+            warp::reply::with_status(warp::reply::json(&resp), StatusCode::OK)
+        });
+
+    // GET /rpc/validator/bond/{who}
+    let bond = warp::path!("rpc" / "validator" / "bond" / String)
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|who: String, st: Arc<RpcState>| {
+            let Some(mgr) = &st.state_mgr else {
+                return err(NO_STATE, StatusCode::SERVICE_UNAVAILABLE);
+            };
+            let status = {
+                let state = mgr.lock();
+                find_bond(&state, &who)
+                    .map(|b| BondStatus::new(b, &staking::params(&state), state.block_height()))
+            };
+            let Some(mut status) = status else {
+                return err(&format!("no bond for {}", who), StatusCode::NOT_FOUND);
+            };
+            if let Some(sets) = &st.validator_sets {
+                status = status.with_sets(&sets.lock(), st.chain_height.load(Ordering::Relaxed));
+            }
+            if let Some(missed) = &st.missed_slots {
+                status = status.with_missed(&missed.lock());
+            }
+            warp::reply::with_status(warp::reply::json(&status), StatusCode::OK)
+        });
+
+    params.or(bond)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_consensus::staking::{set_params, StakingParams, STAKING_ACCOUNT};
+    use parking_lot::Mutex;
+
+    const OWNER: &str = "alice";
+
+    /// State holding one bond of 5_000 by `OWNER`, whose id is returned.
+    fn bonded_state() -> (StateManager, String) {
+        let mut state = StateManager::new();
+        set_params(&mut state, &StakingParams { min_stake: 1_000, unbonding_blocks: 10 });
+        state.mint(OWNER, 10_000).unwrap();
+        let key = vec![7u8; 64];
+        let id = staking::validator_id_for_key(&key);
+        let record = BondRecord {
+            validator_id:  id.clone(),
+            owner:         OWNER.into(),
+            consensus_key: hex::encode(&key),
+            stake:         5_000,
+            bonded_at:     1,
+            unbonding_at:  None,
+        };
+        state.set_storage(STAKING_ACCOUNT, format!("bond:{}", id).as_bytes(), &serde_json::to_vec(&record).unwrap());
+        state.set_storage(STAKING_ACCOUNT, format!("owner:{}", OWNER).as_bytes(), &serde_json::to_vec(&id).unwrap());
+        (state, id)
+    }
+
+    async fn get(st: Arc<RpcState>, path: &str) -> (StatusCode, serde_json::Value) {
+        let res = warp::test::request().path(path).reply(&staking_routes(st)).await;
+        (res.status(), serde_json::from_slice(res.body()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn bond_is_found_by_id_owner_and_key() {
+        let (state, id) = bonded_state();
+        let st = Arc::new(RpcState::new().with_state_manager(Arc::new(Mutex::new(state))));
+
+        let (status, body) = get(Arc::clone(&st), "/rpc/validator/staking").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((body["min_stake"].as_u64(), body["unbonding_blocks"].as_u64()), (Some(1_000), Some(10)));
+
+        let key = hex::encode([7u8; 64]);
+        for who in [id.as_str(), OWNER, key.as_str()] {
+            let (status, body) = get(Arc::clone(&st), &format!("/rpc/validator/bond/{}", who)).await;
+            assert_eq!(status, StatusCode::OK, "{}", who);
+            assert_eq!((body["validator_id"].as_str(), body["phase"].as_str()), (Some(id.as_str()), Some("bonded")));
+        }
+
+        let (status, _) = get(Arc::clone(&st), "/rpc/validator/bond/bob").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = get(Arc::new(RpcState::new()), "/rpc/validator/bond/bob").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
@@ -0,0 +1,378 @@
+//! # Telemetry export
+//!
+//! - `GET /rpc/telemetry/export-preview?profile=` — the exact payload the
+//!   named export profile would push, after privacy filtering
+//! - `GET /rpc/telemetry/snapshot` — every public metric group as of now,
+//!   what `bleep-cli telemetry` prints
+//!
+//! `telemetry_snapshot` gathers every metric group from `RpcState`,
+//! including the private `peer_addresses` and `accounts` groups;
+//! `TelemetrySnapshot::filtered` strips those before anything leaves the
+//! node.  A `TelemetryCollector`, when attached, reads the chain height,
+//! peer count and consensus mode from the live modules and estimates the
+//! node's energy use from its CPU time.  `NodeKeySigner` signs exports with
+//! the P2P node's ed25519 identity key.
+
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, RwLock};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::Mutex as AsyncMutex;
+use warp::http::StatusCode;
+use warp::Filter;
+
+use bleep_consensus::block_store::BlockStore;
+use bleep_consensus::{BLEEPAdaptiveConsensus, ConsensusMode};
+use bleep_core::blockchain::Blockchain;
+use bleep_p2p::peer_manager::PeerManager;
+use bleep_p2p::quantum_crypto::NodeIdentity;
+use bleep_telemetry::export::{ExportSigner, MetricGroup, TelemetrySnapshot};
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+/// Snapshot of all metric groups the node can report.
+pub fn telemetry_snapshot(st: &RpcState) -> TelemetrySnapshot {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut snap = TelemetrySnapshot::new(now);
+    snap.record(MetricGroup::Chain, "height", st.chain_height.load(Ordering::Relaxed))
+        .record(MetricGroup::Chain, "blocks_produced", st.blocks_produced.load(Ordering::Relaxed))
+        .record(MetricGroup::Chain, "transactions_processed", st.txs_processed.load(Ordering::Relaxed))
+        .record(MetricGroup::Chain, "uptime_secs", st.uptime_secs())
+        .record(MetricGroup::Network, "peer_count", st.peer_count.load(Ordering::Relaxed) as u64);
+    if let Some(depth) = st.transaction_pool.as_ref().and_then(|p| p.try_pool_size()) {
+        snap.record(MetricGroup::Chain, "mempool_depth", depth as u64);
+    }
+    if let Some(secs) = st.block_store.as_deref().and_then(last_block_time) {
+        snap.record(MetricGroup::Chain, "block_time_secs", secs);
+    }
+    if let Some(auditor) = &st.chain_auditor {
+        let status = auditor.status();
+        snap.record(MetricGroup::Chain, "audit_findings", status.findings_total)
+            .record(MetricGroup::Chain, "audit_last_height", status.last_audited_height.unwrap_or(0))
+            .record(MetricGroup::Chain, "audit_coverage", status.coverage)
+            .record(MetricGroup::Chain, "audit_halted", status.halted);
+    }
+    if let Some(registry) = &st.validator_registry {
+        let registry = registry.lock();
+        snap.record(MetricGroup::Consensus, "active_validators", registry.active_count() as u64)
+            .record(MetricGroup::Consensus, "total_active_stake", registry.total_active_stake().to_string());
+    }
+    for (model, freshness) in st.ai_freshness() {
+        snap.record(MetricGroup::Consensus, &format!("ai_recommendation_age_ms.{}", model), freshness.age_ms)
+            .record(MetricGroup::Consensus, &format!("ai_recommendation_stale.{}", model), freshness.stale);
+    }
+    if let Some(contracts) = &st.contract_registry {
+        snap.record(MetricGroup::Vm, "contracts_deployed", contracts.contract_count() as u64);
+    }
+    if let Some(executor) = &st.vm_executor {
+        let pool = executor.wasm_pool_stats();
+        snap.record(MetricGroup::Vm, "wasm_pool_hit_rate", pool.hit_rate())
+            .record(MetricGroup::Vm, "wasm_pool_reset_us", pool.avg_reset_us)
+            .record(MetricGroup::Vm, "wasm_pool_evictions", pool.evictions)
+            .record(MetricGroup::Vm, "wasm_pool_idle_bytes", pool.idle_bytes as u64);
+    }
+    if let Some(dht) = &st.dht {
+        let stats = dht.stats();
+        snap.record(MetricGroup::Network, "dht_peers", stats.peers as u64)
+            .record(MetricGroup::Network, "dht_buckets_used", stats.buckets.len() as u64)
+            .record(MetricGroup::Network, "dht_lookup_ms", stats.avg_lookup_ms)
+            .record(MetricGroup::Network, "dht_lookup_timeouts", stats.lookup_timeouts);
+    }
+    if let Some(dir) = &st.peer_directory {
+        let addrs: Vec<String> = dir.peers().iter().map(|p| p.remote_addr.to_string()).collect();
+        snap.record(MetricGroup::PeerAddresses, "peers", addrs);
+    }
+    snap.record(MetricGroup::Accounts, "faucet_balance", st.faucet_balance.load(Ordering::Relaxed))
+        .record(MetricGroup::Accounts, "faucet_recipients", st.faucet_drips.lock().len() as u64);
+    if let Some(collector) = &st.telemetry_collector {
+        collector.collect(&mut snap);
+    }
+    snap
+}
+
+/// Power drawn by one fully busy core, for the energy estimate.
+pub const DEFAULT_CORE_WATTS: f64 = 15.0;
+
+/// Reads the node's live modules into a snapshot: chain height from the
+/// `Blockchain`, peer count from the `PeerManager`, the mode
+/// `BLEEPAdaptiveConsensus` finalizes under, and energy from CPU time.
+pub struct TelemetryCollector {
+    blockchain: Arc<RwLock<Blockchain>>,
+    peers:      Arc<PeerManager>,
+    consensus:  Arc<AsyncMutex<BLEEPAdaptiveConsensus>>,
+    core_watts: f64,
+    /// Mode last read, reported while a round holds the engine.
+    last_mode:  Mutex<Option<ConsensusMode>>,
+}
+
+impl TelemetryCollector {
+    pub fn new(
+        blockchain: Arc<RwLock<Blockchain>>,
+        peers: Arc<PeerManager>,
+        consensus: Arc<AsyncMutex<BLEEPAdaptiveConsensus>>,
+    ) -> Self {
+        Self { blockchain, peers, consensus, core_watts: DEFAULT_CORE_WATTS, last_mode: Mutex::new(None) }
+    }
+
+    /// Watts per busy core used to turn CPU time into energy.
+    pub fn with_core_watts(mut self, watts: f64) -> Self {
+        self.core_watts = watts;
+        self
+    }
+
+    /// Record the `chain`, `network`, `consensus` and `energy` readings.
+    pub fn collect(&self, snap: &mut TelemetrySnapshot) {
+        if let Ok(chain) = self.blockchain.read() {
+            snap.record(MetricGroup::Chain, "height", chain.height());
+        }
+        snap.record(MetricGroup::Network, "peer_count", self.peers.peer_count() as u64);
+
+        let mode = match self.consensus.try_lock() {
+            Ok(engine) => Some(*self.last_mode.lock().insert(engine.consensus_mode())),
+            Err(_) => *self.last_mode.lock(),
+        };
+        if let Some(mode) = mode {
+            snap.record(MetricGroup::Consensus, "mode", format!("{:?}", mode));
+        }
+
+        if let Some(cpu_secs) = process_cpu_secs() {
+            snap.record(MetricGroup::Energy, "cpu_secs", cpu_secs)
+                .record(MetricGroup::Energy, "core_watts", self.core_watts)
+                .record(MetricGroup::Energy, "energy_joules", cpu_secs * self.core_watts);
+        }
+    }
+}
+
+/// CPU time this process has used, from `/proc/self/schedstat` (Linux).
+fn process_cpu_secs() -> Option<f64> {
+    let stat = std::fs::read_to_string("/proc/self/schedstat").ok()?;
+    let ns: u64 = stat.split_whitespace().next()?.parse().ok()?;
+    Some(ns as f64 / 1e9)
+}
+
+/// Seconds between the two newest archived blocks.
+fn last_block_time(store: &BlockStore) -> Option<u64> {
+    let tip = store.tip().ok()??;
+    let newest = store.get(tip).ok()??;
+    let previous = store.get(tip.checked_sub(1)?).ok()??;
+    Some(newest.block.timestamp.saturating_sub(previous.block.timestamp))
+}
+
+/// Signs exports with the node's ed25519 identity key.
+pub struct NodeKeySigner(pub Arc<NodeIdentity>);
+
+impl ExportSigner for NodeKeySigner {
+    fn node_id(&self) -> String {
+        self.0.node_id().to_string()
+    }
+
+    fn public_key(&self) -> Vec<u8> {
+        self.0.ed_keypair.public_key_bytes()
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.0.sign_ed(message)
+    }
+}
+
+#[derive(Serialize)]
+struct PreviewResp {
+    profile:         String,
+    endpoints:       Vec<String>,
+    interval_secs:   u64,
+    /// Groups the profile lists but which never leave the node
+    excluded_groups: Vec<MetricGroup>,
+    payload:         TelemetrySnapshot,
+}
+
+// ── GET /rpc/telemetry/snapshot ───────────────────────────────────────────────
+pub(crate) fn telemetry_current(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "telemetry" / "snapshot")
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|st: Arc<RpcState>| warp::reply::json(&telemetry_snapshot(&st).public()))
+}
+
+// ── GET /rpc/telemetry/export-preview?profile= ────────────────────────────────
+pub(crate) fn telemetry_export_preview(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "telemetry" / "export-preview")
+        .and(warp::get())
+        .and(warp::query::<HashMap<String, String>>())
+        .and(with_arc_state(state))
+        .map(|q: HashMap<String, String>, st: Arc<RpcState>| {
+            let err = |error: String, status| warp::reply::with_status(
+                warp::reply::json(&ErrResp { error }), status,
+            );
+            let config = match &st.telemetry_export {
+                Some(c) => c,
+                None => return err("Telemetry export not configured".into(), StatusCode::SERVICE_UNAVAILABLE),
+            };
+            let name = match q.get("profile") {
+                Some(n) => n,
+                None => return err("Missing ?profile=".into(), StatusCode::BAD_REQUEST),
+            };
+            let profile = match config.profile(name) {
+                Some(p) => p,
+                None => return err(format!("Unknown export profile '{}'", name), StatusCode::NOT_FOUND),
+            };
+            warp::reply::with_status(
+                warp::reply::json(&PreviewResp {
+                    profile:         profile.name.clone(),
+                    endpoints:       profile.endpoints.clone(),
+                    interval_secs:   profile.interval_secs,
+                    excluded_groups: profile.excluded_groups(),
+                    payload:         telemetry_snapshot(&st).filtered(profile),
+                }),
+                StatusCode::OK,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_p2p::node_info::{Hello, NodeMetadata};
+    use bleep_p2p::quantum_crypto::ed25519_verify;
+    use bleep_p2p::PeerDirectory;
+    use bleep_telemetry::export::{SignedExport, TelemetryExportConfig};
+
+    const CONFIG: &str = r#"{"profiles": [
+        {"name": "scoreboard", "groups": ["chain", "consensus", "peer_addresses", "accounts"],
+         "endpoints": ["https://scoreboard.example/ingest"]}
+    ]}"#;
+
+    fn state() -> Arc<RpcState> {
+        let local = Hello::new(&NodeIdentity::generate(), "bleep/test", ([127, 0, 0, 1], 7700).into(), NodeMetadata::default());
+        let dir = Arc::new(PeerDirectory::new(local));
+        let peer = Hello::new(&NodeIdentity::generate(), "bleep/test", ([10, 0, 0, 9], 7700).into(), NodeMetadata::default());
+        dir.on_hello(peer, "10.0.0.9:50001".parse().unwrap()).unwrap();
+        Arc::new(
+            RpcState::new()
+                .with_peer_directory(dir)
+                .with_telemetry_export(Arc::new(TelemetryExportConfig::from_json(CONFIG).unwrap())),
+        )
+    }
+
+    #[tokio::test]
+    async fn preview_never_contains_private_groups() {
+        let st = state();
+        // The raw snapshot does carry them…
+        let raw = telemetry_snapshot(&st);
+        assert!(raw.groups.contains_key(&MetricGroup::PeerAddresses));
+        assert!(raw.groups.contains_key(&MetricGroup::Accounts));
+
+        let res = warp::test::request()
+            .path("/rpc/telemetry/export-preview?profile=scoreboard")
+            .reply(&telemetry_export_preview(Arc::clone(&st)))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = String::from_utf8(res.body().to_vec()).unwrap();
+        assert!(!body.contains("10.0.0.9"));
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let groups = json["payload"]["groups"].as_object().unwrap();
+        assert!(groups.contains_key("chain"));
+        assert!(!groups.contains_key("peer_addresses") && !groups.contains_key("accounts"));
+        assert_eq!(json["excluded_groups"], serde_json::json!(["peer_addresses", "accounts"]));
+
+        let res = warp::test::request()
+            .path("/rpc/telemetry/export-preview?profile=nope")
+            .reply(&telemetry_export_preview(st))
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn snapshot_route_serves_public_groups_only() {
+        let st = state();
+        st.chain_height.store(42, Ordering::Relaxed);
+        let res = warp::test::request()
+            .path("/rpc/telemetry/snapshot")
+            .reply(&telemetry_current(st))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let snap: TelemetrySnapshot = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(snap.groups[&MetricGroup::Chain]["height"], 42);
+        assert!(snap.groups.contains_key(&MetricGroup::Network));
+        assert!(!snap.groups.keys().any(|g| g.is_private()));
+    }
+
+    #[tokio::test]
+    async fn exported_payload_carries_collected_energy_and_consensus_mode() {
+        use bleep_consensus::ai_adaptive_logic::AIAdaptiveConsensus;
+        use bleep_consensus::consensus::ValidatorSigningKey;
+        use bleep_consensus::networking::NetworkingModule;
+        use bleep_consensus::FixedMetrics;
+        use bleep_core::block::Block;
+        use bleep_core::blockchain::BlockchainState;
+        use bleep_core::transaction_pool::TransactionPool;
+        use bleep_p2p::peer_manager::PeerManagerConfig;
+        use bleep_p2p::types::NodeId;
+
+        let chain = Blockchain::new(Block::genesis(), BlockchainState::default(), TransactionPool::new(1), None).unwrap();
+        let (peers, _events) = PeerManager::new(NodeId::random(), PeerManagerConfig::default());
+        let mut engine = BLEEPAdaptiveConsensus::new(
+            HashMap::new(),
+            HashMap::new(),
+            ValidatorSigningKey::generate(),
+            Arc::new(NetworkingModule::new()),
+            Arc::new(AIAdaptiveConsensus::new(HashMap::new(), Arc::new(FixedMetrics::default()))),
+        );
+        engine.switch_consensus_mode(ConsensusMode::PBFT);
+        let engine = Arc::new(AsyncMutex::new(engine));
+        let collector = TelemetryCollector::new(Arc::new(RwLock::new(chain)), peers, Arc::clone(&engine))
+            .with_core_watts(10.0);
+
+        let st = RpcState::new()
+            .with_telemetry_collector(Arc::new(collector))
+            .with_telemetry_export(Arc::new(TelemetryExportConfig::from_json(
+                r#"{"profiles": [{"name": "energy", "groups": ["consensus", "energy"], "endpoints": ["https://e.example"]}]}"#,
+            ).unwrap()));
+        st.chain_height.store(99, Ordering::Relaxed);
+        let config = Arc::clone(st.telemetry_export.as_ref().unwrap());
+        let payload = |st: &RpcState| -> serde_json::Value {
+            let signer = NodeKeySigner(Arc::new(NodeIdentity::generate()));
+            let export = SignedExport::new("energy", &telemetry_snapshot(st).filtered(&config.profiles[0]), &signer);
+            serde_json::from_str(&export.payload).unwrap()
+        };
+
+        let json = payload(&st);
+        assert_eq!(json["groups"]["consensus"]["mode"], "PBFT");
+        let energy = &json["groups"]["energy"];
+        let cpu_secs = energy["cpu_secs"].as_f64().unwrap();
+        assert!(cpu_secs > 0.0);
+        assert_eq!(energy["energy_joules"].as_f64().unwrap(), cpu_secs * 10.0);
+        assert!(json["groups"].get("chain").is_none(), "not in the profile");
+        assert_eq!(telemetry_snapshot(&st).groups[&MetricGroup::Chain]["height"], 0, "read from the chain");
+
+        // A round in progress holds the engine: the last mode read stands
+        let _round = engine.lock().await;
+        assert_eq!(payload(&st)["groups"]["consensus"]["mode"], "PBFT");
+    }
+
+    #[test]
+    fn exported_payload_verifies_against_node_key() {
+        let st = state();
+        let identity = Arc::new(NodeIdentity::generate());
+        let signer = NodeKeySigner(Arc::clone(&identity));
+        let config = st.telemetry_export.as_ref().unwrap();
+        let export = SignedExport::new("scoreboard", &telemetry_snapshot(&st).filtered(&config.profiles[0]), &signer);
+
+        assert_eq!(export.node_id, identity.node_id().to_string());
+        assert_eq!(hex::decode(&export.public_key).unwrap(), identity.ed_keypair.public_key_bytes());
+        let sig = hex::decode(&export.signature).unwrap();
+        ed25519_verify(export.payload.as_bytes(), &sig, &identity.ed_keypair.public_key_bytes()).unwrap();
+
+        let mut forged = export.payload.clone().into_bytes();
+        forged[0] ^= 1;
+        assert!(ed25519_verify(&forged, &sig, &identity.ed_keypair.public_key_bytes()).is_err());
+    }
+}
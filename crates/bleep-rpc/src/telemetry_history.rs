@@ -0,0 +1,121 @@
+//! # Telemetry history
+//!
+//! - `GET /rpc/telemetry/history?metric=&from=&to=&step=` — the metric as a
+//!   series aligned to `step` seconds, for charting; without `metric`, the
+//!   metrics with history
+//!
+//! Both replies carry the store's `usage`.  `to` defaults to the newest
+//! sample, `from` to an hour before it and `step` to whatever gives about
+//! `DEFAULT_POINTS` points.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use warp::http::StatusCode;
+use warp::Filter;
+
+use bleep_telemetry::history::{HistoryUsage, Series};
+
+use crate::{with_arc_state, ErrResp, RpcState};
+
+/// Points returned when no `step` is given.
+pub const DEFAULT_POINTS: u64 = 360;
+
+#[derive(Debug, Default, Deserialize)]
+struct HistoryQuery {
+    metric: Option<String>,
+    from:   Option<u64>,
+    to:     Option<u64>,
+    step:   Option<u64>,
+}
+
+#[derive(Serialize)]
+struct MetricsResp {
+    metrics: Vec<String>,
+    latest:  u64,
+    usage:   HistoryUsage,
+}
+
+#[derive(Serialize)]
+struct SeriesResp {
+    #[serde(flatten)]
+    series: Series,
+    usage:  HistoryUsage,
+}
+
+// ── GET /rpc/telemetry/history ────────────────────────────────────────────────
+pub(crate) fn telemetry_history(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "telemetry" / "history")
+        .and(warp::get())
+        .and(warp::query::<HistoryQuery>())
+        .and(with_arc_state(state))
+        .map(|q: HistoryQuery, st: Arc<RpcState>| {
+            let err = |error: String, status| warp::reply::with_status(
+                warp::reply::json(&ErrResp { error }), status,
+            );
+            let history = match &st.telemetry_history {
+                Some(h) => h,
+                None => return err("Telemetry history not attached".into(), StatusCode::SERVICE_UNAVAILABLE),
+            };
+            let Some(metric) = q.metric else {
+                let resp = MetricsResp {
+                    metrics: history.metrics(),
+                    latest:  history.latest(),
+                    usage:   history.usage(),
+                };
+                return warp::reply::with_status(warp::reply::json(&resp), StatusCode::OK);
+            };
+            let to = q.to.unwrap_or_else(|| history.latest());
+            let from = q.from.unwrap_or_else(|| to.saturating_sub(3_600));
+            let step = q.step.unwrap_or_else(|| {
+                to.saturating_sub(from).div_ceil(DEFAULT_POINTS).max(history.config().sample_interval_secs)
+            });
+            match history.query(&metric, from, to, step) {
+                Ok(series) => warp::reply::with_status(
+                    warp::reply::json(&SeriesResp { series, usage: history.usage() }),
+                    StatusCode::OK,
+                ),
+                Err(e) => err(e, StatusCode::BAD_REQUEST),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_telemetry::history::{HistoryConfig, TelemetryHistory};
+
+    #[tokio::test]
+    async fn serves_aligned_series() {
+        let dir = std::env::temp_dir().join(format!("bleep-rpc-telemetry-history-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let history = TelemetryHistory::open(&dir, HistoryConfig::default()).unwrap();
+        for i in 0..360u64 {
+            history.record(1_200_000 + i * 10, "network.peer_count", (i % 7) as f64).unwrap();
+        }
+        let routes = telemetry_history(Arc::new(RpcState::new().with_telemetry_history(Arc::new(history))));
+
+        let res = warp::test::request().path("/rpc/telemetry/history").reply(&routes).await;
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        assert_eq!(body["metrics"][0], "network.peer_count");
+        assert!(body["usage"]["bytes"].as_u64().unwrap() > 0);
+
+        let res = warp::test::request()
+            .path("/rpc/telemetry/history?metric=network.peer_count&from=1200000&to=1203599&step=600")
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(res.body()).unwrap();
+        let points = body["points"].as_array().unwrap();
+        assert_eq!(points.len(), 6);
+        assert!(points.iter().all(|p| p["samples"] == 60));
+
+        let res = warp::test::request()
+            .path("/rpc/telemetry/history?metric=network.peer_count&from=10&to=5")
+            .reply(&routes).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        drop(routes);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,570 @@
+//! # Batch transaction submission
+//!
+//! Exchanges paying out withdrawals submit signed transactions in bulk and
+//! follow them as one batch:
+//!
+//! - `POST /rpc/tx/submit-batch` — `{"transactions": [...]}`, up to
+//!   `MAX_BATCH_ITEMS` items in the `POST /rpc/tx` format
+//! - `GET  /rpc/tx/batch/{id}`   — per-item status, aggregated from receipts
+//!
+//! Every item is validated on its own: a malformed or unadmittable item is
+//! rejected with a typed `code` (see `AdmissionError::code`) and does not
+//! affect the others.  Items are admitted sender by sender in timestamp
+//! order — the per-sender sequence number — whatever their order in the
+//! request; results come back in request order.
+//!
+//! With API keys enforced, a batch counts once per item against the key's
+//! burst limit and daily quota.
+//!
+//! Batches are tracked in memory; the oldest are forgotten once
+//! `MAX_TRACKED_BATCHES` are held.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use warp::http::StatusCode;
+use warp::{Filter, Reply};
+
+use bleep_auth::api_keys::RouteGroup;
+use bleep_consensus::block_execution::tx_hash;
+use bleep_consensus::block_store::BlockStore;
+use bleep_core::transaction::ZKTransaction;
+use bleep_crypto::Sensitive;
+
+use crate::{api_keys, now_secs, with_arc_state, ErrResp, RpcState, TxReq};
+
+/// Largest batch `POST /rpc/tx/submit-batch` accepts.
+pub const MAX_BATCH_ITEMS: usize = 500;
+/// Batches kept for `GET /rpc/tx/batch/{id}`.
+pub const MAX_TRACKED_BATCHES: usize = 1024;
+/// Path of the submission endpoint, which the API key guard meters per item.
+pub const SUBMIT_BATCH_PATH: &str = "/rpc/tx/submit-batch";
+
+#[derive(Deserialize)]
+struct SubmitBatchReq {
+    transactions: Vec<serde_json::Value>,
+}
+
+/// Outcome of one submitted item.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ItemResult {
+    pub index:    usize,
+    pub accepted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash:  Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code:     Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error:    Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitBatchResp {
+    pub batch_id: String,
+    pub accepted: usize,
+    pub rejected: usize,
+    pub results:  Vec<ItemResult>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemStatus {
+    /// Turned away at submission.
+    Rejected,
+    /// Admitted, not yet in a block.
+    Pending,
+    /// Included with a successful receipt.
+    Confirmed,
+    /// Included with a failed receipt.
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub index:   usize,
+    pub status:  ItemStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height:  Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code:    Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error:   Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchCounts {
+    pub rejected:  usize,
+    pub pending:   usize,
+    pub confirmed: usize,
+    pub failed:    usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchStatus {
+    pub batch_id:         String,
+    pub created_at:       u64,
+    /// Chain tip when the batch was submitted.
+    pub submitted_height: u64,
+    /// Highest block whose receipts have been matched.
+    pub scanned_to:       u64,
+    pub counts:           BatchCounts,
+    pub items:            Vec<BatchItem>,
+}
+
+impl BatchStatus {
+    fn recount(&mut self) {
+        let mut counts = BatchCounts::default();
+        for item in &self.items {
+            match item.status {
+                ItemStatus::Rejected  => counts.rejected += 1,
+                ItemStatus::Pending   => counts.pending += 1,
+                ItemStatus::Confirmed => counts.confirmed += 1,
+                ItemStatus::Failed    => counts.failed += 1,
+            }
+        }
+        self.counts = counts;
+    }
+
+    /// Match receipts of blocks above `scanned_to` against pending items.
+    fn refresh(&mut self, store: &BlockStore) -> Result<(), String> {
+        let tip = store.tip()?.unwrap_or(0);
+        while self.counts.pending > 0 && self.scanned_to < tip {
+            let height = self.scanned_to + 1;
+            if let Some(stored) = store.get(height)? {
+                for receipt in &stored.receipts {
+                    let hit = self.items.iter_mut().find(|i| {
+                        i.status == ItemStatus::Pending && i.tx_hash.as_deref() == Some(receipt.tx_hash.as_str())
+                    });
+                    if let Some(item) = hit {
+                        item.status = if receipt.success { ItemStatus::Confirmed } else { ItemStatus::Failed };
+                        item.height = Some(height);
+                        item.error = receipt.failure.clone();
+                        self.counts.pending -= 1;
+                        if receipt.success {
+                            self.counts.confirmed += 1;
+                        } else {
+                            self.counts.failed += 1;
+                        }
+                    }
+                }
+            }
+            self.scanned_to = height;
+        }
+        Ok(())
+    }
+}
+
+/// Submitted batches, oldest first.
+#[derive(Default)]
+pub struct TxBatches {
+    inner: Mutex<TxBatchesInner>,
+}
+
+#[derive(Default)]
+struct TxBatchesInner {
+    seq:     u64,
+    batches: VecDeque<BatchStatus>,
+}
+
+impl TxBatches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a submission and return its id.
+    pub fn register(&self, submitted_height: u64, results: &[ItemResult]) -> String {
+        let created_at = now_secs();
+        let mut inner = self.inner.lock();
+        inner.seq += 1;
+        let mut h = Sha3_256::new();
+        h.update(b"bleep:tx_batch");
+        h.update(created_at.to_le_bytes());
+        h.update(inner.seq.to_le_bytes());
+        for r in results {
+            h.update(r.tx_hash.as_deref().unwrap_or("").as_bytes());
+        }
+        let batch_id = hex::encode(&h.finalize()[..16]);
+
+        let items = results
+            .iter()
+            .map(|r| BatchItem {
+                index:   r.index,
+                status:  if r.accepted { ItemStatus::Pending } else { ItemStatus::Rejected },
+                tx_hash: r.tx_hash.clone(),
+                height:  None,
+                code:    r.code.clone(),
+                error:   r.error.clone(),
+            })
+            .collect();
+        let mut status = BatchStatus {
+            batch_id: batch_id.clone(),
+            created_at,
+            submitted_height,
+            scanned_to: submitted_height,
+            counts: BatchCounts::default(),
+            items,
+        };
+        status.recount();
+
+        if inner.batches.len() >= MAX_TRACKED_BATCHES {
+            inner.batches.pop_front();
+        }
+        inner.batches.push_back(status);
+        batch_id
+    }
+
+    /// Current status of batch `id`, first matching any new receipts in `store`.
+    pub fn status(&self, id: &str, store: Option<&BlockStore>) -> Result<Option<BatchStatus>, String> {
+        let mut inner = self.inner.lock();
+        let Some(batch) = inner.batches.iter_mut().find(|b| b.batch_id == id) else {
+            return Ok(None);
+        };
+        if let Some(store) = store {
+            batch.refresh(store)?;
+        }
+        Ok(Some(batch.clone()))
+    }
+}
+
+/// Hash a transaction's receipt will carry.
+pub fn receipt_hash(tx: &ZKTransaction) -> String {
+    tx_hash(&bleep_core::block::Transaction {
+        sender:    tx.sender.clone(),
+        receiver:  tx.receiver.clone(),
+        amount:    tx.amount,
+        timestamp: tx.timestamp,
+        signature: tx.signature.clone(),
+        max_fee:   tx.max_fee,
+        tip:       tx.tip,
+        nonce:     tx.nonce,
+        chain_id:  tx.chain_id,
+    })
+}
+
+fn parse_item(value: serde_json::Value) -> Result<ZKTransaction, String> {
+    let req: TxReq = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    Ok(ZKTransaction {
+        sender:    req.sender,
+        receiver:  req.receiver,
+        amount:    req.amount,
+        timestamp: req.timestamp,
+        signature: req.signature,
+        max_fee:   req.max_fee,
+        tip:       req.tip,
+        nonce:     req.nonce,
+        chain_id:  req.chain_id,
+    })
+}
+
+/// Admission order: senders in order of first appearance, each sender's
+/// transactions by timestamp.
+fn admission_order(items: &[Result<ZKTransaction, String>]) -> Vec<usize> {
+    let mut rank: HashMap<&str, usize> = HashMap::new();
+    for tx in items.iter().flatten() {
+        let next = rank.len();
+        rank.entry(tx.sender.as_str()).or_insert(next);
+    }
+    let mut order: Vec<usize> = (0..items.len()).filter(|&i| items[i].is_ok()).collect();
+    order.sort_by_key(|&i| {
+        let tx = items[i].as_ref().expect("filtered to parsed items");
+        (rank[tx.sender.as_str()], tx.timestamp)
+    });
+    order
+}
+
+fn err(msg: String, status: StatusCode) -> warp::reply::Response {
+    warp::reply::with_status(warp::reply::json(&ErrResp { error: msg }), status).into_response()
+}
+
+fn chain_tip(st: &RpcState) -> u64 {
+    match st.block_store.as_ref().map(|s| s.tip()) {
+        Some(Ok(Some(tip))) => tip,
+        _ => st.chain_height.load(Ordering::Relaxed),
+    }
+}
+
+// ── POST /rpc/tx/submit-batch ─────────────────────────────────────────────────
+pub(crate) fn tx_submit_batch(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "submit-batch")
+        .and(warp::post())
+        .and(api_keys::presented_key())
+        .and(warp::body::json::<SubmitBatchReq>())
+        .and(with_arc_state(state))
+        .and_then(|key: Option<Sensitive<String>>, req: SubmitBatchReq, st: Arc<RpcState>| async move {
+            let n = req.transactions.len();
+            if n == 0 {
+                return Ok::<_, warp::Rejection>(err("batch is empty".into(), StatusCode::BAD_REQUEST));
+            }
+            if n > MAX_BATCH_ITEMS {
+                return Ok(err(
+                    format!("batch has {} transactions, limit is {}", n, MAX_BATCH_ITEMS),
+                    StatusCode::BAD_REQUEST,
+                ));
+            }
+            if let Some(registry) = &st.api_keys {
+                if let Err(e) = registry.authorize_units(key.as_ref().map(|k| k.expose().as_str()), RouteGroup::Tx, n as u64) {
+                    return Ok(api_keys::error_response(&e));
+                }
+            }
+            let Some(pool) = st.transaction_pool.clone() else {
+                return Ok(err("TransactionPool not attached to RPC state".into(), StatusCode::SERVICE_UNAVAILABLE));
+            };
+
+            let submitted_height = chain_tip(&st);
+            let items: Vec<Result<ZKTransaction, String>> =
+                req.transactions.into_iter().map(parse_item).collect();
+            let mut results: Vec<ItemResult> = items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| ItemResult {
+                    index,
+                    accepted: false,
+                    tx_hash:  None,
+                    code:     item.as_ref().err().map(|_| "malformed".to_string()),
+                    error:    item.as_ref().err().cloned(),
+                })
+                .collect();
+
+            for i in admission_order(&items) {
+                let tx = items[i].as_ref().expect("admission order holds parsed items");
+                match pool.admit(tx.clone()).await {
+                    Ok(()) => {
+                        results[i].accepted = true;
+                        results[i].tx_hash = Some(receipt_hash(tx));
+                    }
+                    Err(e) => {
+                        results[i].code = Some(e.code().to_string());
+                        results[i].error = Some(e.to_string());
+                    }
+                }
+            }
+
+            let accepted = results.iter().filter(|r| r.accepted).count();
+            let batch_id = st.tx_batches.register(submitted_height, &results);
+            let resp = SubmitBatchResp { batch_id, accepted, rejected: n - accepted, results };
+            Ok(warp::reply::json(&resp).into_response())
+        })
+}
+
+// ── GET /rpc/tx/batch/{id} ────────────────────────────────────────────────────
+pub(crate) fn tx_batch_status(
+    state: Arc<RpcState>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Clone {
+    warp::path!("rpc" / "tx" / "batch" / String)
+        .and(warp::get())
+        .and(with_arc_state(state))
+        .map(|id: String, st: Arc<RpcState>| {
+            match st.tx_batches.status(&id, st.block_store.as_deref()) {
+                Ok(Some(status)) => warp::reply::json(&status).into_response(),
+                Ok(None) => err(format!("unknown batch {}", id), StatusCode::NOT_FOUND),
+                Err(e) => err(e, StatusCode::INTERNAL_SERVER_ERROR),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use bleep_auth::api_keys::{ApiKeyRegistry, ApiKeySpec, Clock, ManualClock};
+    use bleep_consensus::block_execution::TxReceipt;
+    use bleep_consensus::block_store::StoredBlock;
+    use bleep_core::block::Block;
+    use bleep_core::transaction_pool::TransactionPool;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload, tx_payload_with_fee};
+
+    struct Signer {
+        pk: Vec<u8>,
+        sk: Vec<u8>,
+    }
+
+    impl Signer {
+        fn new() -> Self {
+            let (pk, sk) = generate_tx_keypair();
+            Signer { pk, sk }
+        }
+
+        fn tx(&self, sender: &str, receiver: &str, amount: u64, timestamp: u64) -> serde_json::Value {
+            let payload = tx_payload_with_fee(sender, receiver, amount, timestamp, 0, 0);
+            let mut signature = self.pk.clone();
+            signature.extend(sign_tx_payload(&payload, &self.sk).unwrap());
+            serde_json::json!({
+                "sender": sender, "receiver": receiver, "amount": amount,
+                "timestamp": timestamp, "signature": signature,
+            })
+        }
+    }
+
+    async fn submit(
+        st: &RpcState,
+        key: Option<&str>,
+        txs: Vec<serde_json::Value>,
+    ) -> (StatusCode, serde_json::Value) {
+        let mut req = warp::test::request()
+            .method("POST")
+            .path(SUBMIT_BATCH_PATH)
+            .json(&serde_json::json!({ "transactions": txs }));
+        if let Some(key) = key {
+            req = req.header(api_keys::API_KEY_HEADER, key);
+        }
+        let res = req.reply(&crate::rpc_routes_with_state(st.clone())).await;
+        (res.status(), serde_json::from_slice(res.body()).unwrap())
+    }
+
+    async fn batch_status(st: &RpcState, id: &str) -> BatchStatus {
+        let res = warp::test::request()
+            .path(&format!("/rpc/tx/batch/{}", id))
+            .reply(&crate::rpc_routes_with_state(st.clone()))
+            .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        serde_json::from_slice(res.body()).unwrap()
+    }
+
+    fn scratch(tag: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bleep-rpc-batch-{}-{}", tag, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn mixed_batch_gets_per_item_results() {
+        let signer = Signer::new();
+        let st = RpcState::new().with_transaction_pool(TransactionPool::new(100));
+        let good = signer.tx("alice", "bob", 5, 1);
+        let mut forged = signer.tx("alice", "bob", 6, 2);
+        forged["amount"] = serde_json::json!(600);
+        let txs = vec![
+            good.clone(),
+            serde_json::json!({ "sender": "alice", "amount": "lots" }),
+            forged,
+            signer.tx("carol", "carol", 1, 1),
+            good,
+        ];
+
+        let (status, body) = submit(&st, None, txs).await;
+        assert_eq!(status, StatusCode::OK);
+        let resp: SubmitBatchResp = serde_json::from_value(body).unwrap();
+        assert_eq!((resp.accepted, resp.rejected), (1, 4));
+        let codes: Vec<Option<&str>> = resp.results.iter().map(|r| r.code.as_deref()).collect();
+        assert_eq!(codes, [None, Some("malformed"), Some("bad_signature"), Some("malformed"), Some("duplicate")]);
+        assert!(resp.results[0].accepted && resp.results[0].tx_hash.is_some());
+        assert_eq!(resp.results.iter().map(|r| r.index).collect::<Vec<_>>(), [0, 1, 2, 3, 4]);
+
+        let (status, _) = submit(&st, None, vec![]).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn one_senders_transactions_are_admitted_in_sequence() {
+        let signer = Signer::new();
+        let pool = TransactionPool::new(100);
+        let st = RpcState::new().with_transaction_pool(Arc::clone(&pool));
+        let timestamps = [7u64, 3, 10, 1, 9, 2, 8, 5, 4, 6];
+        let txs = timestamps.iter().map(|&t| signer.tx("alice", "bob", t, t)).collect();
+
+        let (_, body) = submit(&st, None, txs).await;
+        let resp: SubmitBatchResp = serde_json::from_value(body).unwrap();
+        assert_eq!(resp.accepted, 10);
+        for (r, &t) in resp.results.iter().zip(&timestamps) {
+            assert_eq!(r.tx_hash, Some(receipt_hash(&parse_item(signer.tx("alice", "bob", t, t)).unwrap())));
+        }
+        let admitted: Vec<u64> = pool.get_transactions().await.iter().map(|tx| tx.timestamp).collect();
+        assert_eq!(admitted, (1..=10).collect::<Vec<u64>>());
+    }
+
+    #[tokio::test]
+    async fn batch_status_follows_receipts() {
+        let dir = scratch("status");
+        let store = Arc::new(BlockStore::open(&dir).unwrap());
+        let signer = Signer::new();
+        let st = RpcState::new()
+            .with_transaction_pool(TransactionPool::new(100))
+            .with_block_store(Arc::clone(&store));
+        let txs = (1..=3).map(|t| signer.tx("alice", "bob", 10, t)).collect();
+        let (_, body) = submit(&st, None, txs).await;
+        let resp: SubmitBatchResp = serde_json::from_value(body).unwrap();
+        let hashes: Vec<String> = resp.results.iter().map(|r| r.tx_hash.clone().unwrap()).collect();
+
+        assert_eq!(batch_status(&st, &resp.batch_id).await.counts.pending, 3);
+
+        let land = |height: u64, hashes: &[&String], success: bool| {
+            let receipts = hashes
+                .iter()
+                .map(|h| TxReceipt {
+                    tx_hash:      (*h).clone(),
+                    success,
+                    gas_used:     0,
+                    accounts:     BTreeMap::new(),
+                    storage_keys: vec![],
+                    failure:      (!success).then(|| "insufficient balance".to_string()),
+                    fee:          None,
+                })
+                .collect();
+            store.put(&StoredBlock {
+                block:               Block::new(height, vec![], "0".repeat(64)),
+                receipts,
+                receipts_root:       String::new(),
+                gas_used:            0,
+                state_root:          String::new(),
+                parent_state_height: height - 1,
+                fees:                None,
+            }).unwrap();
+        };
+        land(1, &[&hashes[0], &hashes[2]], true);
+        let s = batch_status(&st, &resp.batch_id).await;
+        assert_eq!((s.counts.confirmed, s.counts.pending, s.scanned_to), (2, 1, 1));
+        assert_eq!(s.items[2].height, Some(1));
+
+        land(2, &[], true);
+        land(3, &[&hashes[1]], false);
+        let s = batch_status(&st, &resp.batch_id).await;
+        assert_eq!(s.counts, BatchCounts { rejected: 0, pending: 0, confirmed: 2, failed: 1 });
+        assert_eq!((s.items[1].status, s.items[1].height), (ItemStatus::Failed, Some(3)));
+
+        let res = warp::test::request()
+            .path("/rpc/tx/batch/nope")
+            .reply(&crate::rpc_routes_with_state(st))
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn api_key_quota_counts_batch_items() {
+        let clock = Arc::new(ManualClock::new(1_704_103_200));
+        let registry = Arc::new(ApiKeyRegistry::new().with_clock(Arc::clone(&clock) as Arc<dyn Clock>));
+        let issued = registry.create(ApiKeySpec {
+            name:          "exchange".into(),
+            groups:        vec![RouteGroup::Tx],
+            daily_quota:   10,
+            burst_per_sec: 0,
+            expires_at:    None,
+        }).unwrap();
+        let signer = Signer::new();
+        let st = RpcState::new()
+            .with_transaction_pool(TransactionPool::new(100))
+            .with_api_keys(Arc::clone(&registry), "admin".into());
+        let batch = |from: u64, n: u64| (from..from + n).map(|t| signer.tx("alice", "bob", 1, t)).collect();
+
+        let (status, _) = submit(&st, None, batch(1, 1)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, _) = submit(&st, Some(&issued.key), batch(1, 4)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = submit(&st, Some(&issued.key), batch(5, 7)).await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!((body["quota"].as_u64(), body["used"].as_u64()), (Some(10), Some(4)));
+
+        let (status, _) = submit(&st, Some(&issued.key), batch(5, 6)).await;
+        assert_eq!(status, StatusCode::OK);
+        let used = registry.authorize_units(Some(&issued.key), RouteGroup::Tx, 0).unwrap().used;
+        assert_eq!(used, 10);
+    }
+}
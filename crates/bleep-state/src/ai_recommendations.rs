@@ -0,0 +1,363 @@
+//! # AI recommendation store
+//!
+//! Consensus and sharding code must never wait on model inference.  Models
+//! run in an `EvaluationLoop` on a background task; each completed
+//! evaluation is published into a `RecommendationSlot` as a versioned,
+//! timestamped `Recommendation`.  Hot paths only read the latest completed
+//! record, and `RecommendationSlot::value_or` hands back the caller's
+//! default once the record is older than the configured maximum age (or
+//! missing), so a slow or stalled model degrades to protocol defaults
+//! rather than stalling block processing.
+//!
+//! Every recommendation carries a `RecommendationEffect`.  Anything that
+//! would change consensus-visible behaviour must be a `ModeSignal`: a mode
+//! this node is willing to signal for, which only takes effect through the
+//! coordinated, deterministic mode-selection path.  `Advisory` records are
+//! for logs, operators and local heuristics only.
+//!
+//! Each loop has an `InferenceBudget` (evaluations per minute); ticks over
+//! budget are skipped, never queued.  `RecommendationRegistry` collects the
+//! slots so their freshness can be reported in telemetry.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use parking_lot::RwLock;
+use serde::Serialize;
+
+/// Default age after which a recommendation is ignored.
+pub const DEFAULT_MAX_AGE_MS: u64 = 60_000;
+
+/// Milliseconds since the Unix epoch.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// How a recommendation may be acted on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecommendationEffect {
+    /// Informational only — never changes consensus-visible behaviour.
+    Advisory,
+    /// A consensus mode to signal for; applied only by coordinated mode
+    /// selection, never directly.
+    ModeSignal,
+}
+
+/// One completed model evaluation.
+#[derive(Debug, Clone)]
+pub struct Recommendation<T> {
+    pub model:          String,
+    /// Increments with every publish to the slot.
+    pub version:        u64,
+    pub produced_at_ms: u64,
+    pub effect:         RecommendationEffect,
+    pub value:          T,
+}
+
+impl<T> Recommendation<T> {
+    pub fn age_ms(&self, now_ms: u64) -> u64 {
+        now_ms.saturating_sub(self.produced_at_ms)
+    }
+}
+
+/// Freshness of a slot's latest record, for telemetry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Freshness {
+    pub model:       String,
+    pub effect:      RecommendationEffect,
+    /// `None` until the first evaluation completes.
+    pub version:     Option<u64>,
+    pub age_ms:      Option<u64>,
+    pub max_age_ms:  u64,
+    pub stale:       bool,
+    pub evaluations: u64,
+    /// Ticks skipped because the inference budget was exhausted.
+    pub skipped:     u64,
+}
+
+#[derive(Debug)]
+struct SlotInner<T> {
+    model:       String,
+    effect:      RecommendationEffect,
+    max_age_ms:  u64,
+    latest:      RwLock<Option<Arc<Recommendation<T>>>>,
+    evaluations: AtomicU64,
+    skipped:     AtomicU64,
+}
+
+/// Latest recommendation from one model.  Cheap to clone; all clones share
+/// the record.
+#[derive(Debug)]
+pub struct RecommendationSlot<T> {
+    inner: Arc<SlotInner<T>>,
+}
+
+impl<T> Clone for RecommendationSlot<T> {
+    fn clone(&self) -> Self {
+        Self { inner: Arc::clone(&self.inner) }
+    }
+}
+
+impl<T> RecommendationSlot<T> {
+    pub fn new(model: impl Into<String>, effect: RecommendationEffect) -> Self {
+        Self {
+            inner: Arc::new(SlotInner {
+                model:       model.into(),
+                effect,
+                max_age_ms:  DEFAULT_MAX_AGE_MS,
+                latest:      RwLock::new(None),
+                evaluations: AtomicU64::new(0),
+                skipped:     AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Override the staleness threshold.  Only valid before the slot is
+    /// shared.
+    pub fn with_max_age_ms(mut self, max_age_ms: u64) -> Self {
+        if let Some(inner) = Arc::get_mut(&mut self.inner) {
+            inner.max_age_ms = max_age_ms;
+        }
+        self
+    }
+
+    pub fn model(&self) -> &str {
+        &self.inner.model
+    }
+
+    pub fn effect(&self) -> RecommendationEffect {
+        self.inner.effect
+    }
+
+    pub fn max_age_ms(&self) -> u64 {
+        self.inner.max_age_ms
+    }
+
+    /// Record a completed evaluation; returns its version.
+    pub fn publish(&self, value: T, produced_at_ms: u64) -> u64 {
+        let mut latest = self.inner.latest.write();
+        let version = latest.as_ref().map(|r| r.version + 1).unwrap_or(1);
+        *latest = Some(Arc::new(Recommendation {
+            model: self.inner.model.clone(),
+            version,
+            produced_at_ms,
+            effect: self.inner.effect,
+            value,
+        }));
+        self.inner.evaluations.fetch_add(1, Ordering::Relaxed);
+        version
+    }
+
+    /// Latest completed record, however old.
+    pub fn latest(&self) -> Option<Arc<Recommendation<T>>> {
+        self.inner.latest.read().clone()
+    }
+
+    /// Latest record if it is no older than the slot's maximum age.
+    pub fn fresh(&self, now_ms: u64) -> Option<Arc<Recommendation<T>>> {
+        self.latest().filter(|r| r.age_ms(now_ms) <= self.inner.max_age_ms)
+    }
+
+    pub fn freshness(&self, now_ms: u64) -> Freshness {
+        let latest = self.latest();
+        let age_ms = latest.as_ref().map(|r| r.age_ms(now_ms));
+        Freshness {
+            model:       self.inner.model.clone(),
+            effect:      self.inner.effect,
+            version:     latest.as_ref().map(|r| r.version),
+            age_ms,
+            max_age_ms:  self.inner.max_age_ms,
+            stale:       age_ms.is_none_or(|a| a > self.inner.max_age_ms),
+            evaluations: self.inner.evaluations.load(Ordering::Relaxed),
+            skipped:     self.inner.skipped.load(Ordering::Relaxed),
+        }
+    }
+
+    fn note_skipped(&self) {
+        self.inner.skipped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<T: Clone> RecommendationSlot<T> {
+    /// The fresh recommendation's value, or `default` when it is stale or
+    /// no evaluation has completed yet.
+    pub fn value_or(&self, default: T, now_ms: u64) -> T {
+        self.fresh(now_ms).map(|r| r.value.clone()).unwrap_or(default)
+    }
+}
+
+// ── Inference budget ──────────────────────────────────────────────────────────
+
+/// Sliding one-minute window of evaluations.
+#[derive(Debug, Clone)]
+pub struct InferenceBudget {
+    max_per_minute: u32,
+    recent:         VecDeque<u64>,
+}
+
+impl InferenceBudget {
+    pub fn per_minute(max_per_minute: u32) -> Self {
+        Self { max_per_minute, recent: VecDeque::new() }
+    }
+
+    /// Take one evaluation from the budget at `now_ms`, if any is left.
+    pub fn try_acquire(&mut self, now_ms: u64) -> bool {
+        while let Some(&t) = self.recent.front() {
+            if now_ms.saturating_sub(t) < 60_000 { break; }
+            self.recent.pop_front();
+        }
+        if self.recent.len() as u32 >= self.max_per_minute {
+            return false;
+        }
+        self.recent.push_back(now_ms);
+        true
+    }
+}
+
+// ── Evaluation loop ───────────────────────────────────────────────────────────
+
+/// Runs one model on a fixed interval, within its budget, publishing each
+/// result to its slot.
+pub struct EvaluationLoop<T> {
+    slot:     RecommendationSlot<T>,
+    budget:   InferenceBudget,
+    interval: Duration,
+}
+
+impl<T: Send + Sync + 'static> EvaluationLoop<T> {
+    pub fn new(slot: RecommendationSlot<T>, budget: InferenceBudget, interval: Duration) -> Self {
+        Self { slot, budget, interval }
+    }
+
+    /// Spawn the loop.  `model` runs on the blocking pool, one evaluation at
+    /// a time; a tick that finds the budget spent is skipped.  A model
+    /// returning `None` publishes nothing, leaving the previous record to
+    /// age out.
+    pub fn spawn<F>(mut self, model: F) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> Option<T> + Send + Sync + 'static,
+    {
+        let model = Arc::new(model);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(self.interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                if !self.budget.try_acquire(now_ms()) {
+                    self.slot.note_skipped();
+                    continue;
+                }
+                let model = Arc::clone(&model);
+                match tokio::task::spawn_blocking(move || model()).await {
+                    Ok(Some(value)) => { self.slot.publish(value, now_ms()); }
+                    Ok(None) => {}
+                    Err(e) => log::warn!("[AI] {} evaluation panicked: {}", self.slot.model(), e),
+                }
+            }
+        })
+    }
+}
+
+// ── Registry ──────────────────────────────────────────────────────────────────
+
+trait FreshnessSource: Send + Sync {
+    fn freshness(&self, now_ms: u64) -> Freshness;
+}
+
+impl<T: Send + Sync> FreshnessSource for RecommendationSlot<T> {
+    fn freshness(&self, now_ms: u64) -> Freshness {
+        RecommendationSlot::freshness(self, now_ms)
+    }
+}
+
+/// Every slot the node publishes, for freshness reporting.
+#[derive(Default)]
+pub struct RecommendationRegistry {
+    slots: RwLock<BTreeMap<String, Box<dyn FreshnessSource>>>,
+}
+
+impl RecommendationRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T: Send + Sync + 'static>(&self, slot: &RecommendationSlot<T>) {
+        self.slots.write().insert(slot.model().to_string(), Box::new(slot.clone()));
+    }
+
+    /// Freshness of every registered slot, by model name.
+    pub fn freshness(&self, now_ms: u64) -> BTreeMap<String, Freshness> {
+        self.slots.read().iter()
+            .map(|(name, slot)| (name.clone(), slot.freshness(now_ms)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn stale_recommendation_falls_back_to_default() {
+        let slot = RecommendationSlot::new("congestion", RecommendationEffect::Advisory)
+            .with_max_age_ms(5_000);
+        assert_eq!(slot.value_or(0u32, 1_000), 0);
+        assert!(slot.freshness(1_000).stale);
+
+        assert_eq!(slot.publish(7, 10_000), 1);
+        assert_eq!(slot.value_or(0, 14_000), 7);
+        assert!(!slot.freshness(14_000).stale);
+        assert_eq!(slot.value_or(0, 15_001), 0);
+        assert!(slot.freshness(15_001).stale);
+
+        assert_eq!(slot.publish(9, 16_000), 2);
+        assert_eq!(slot.value_or(0, 16_000), 9);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn slow_model_does_not_block_readers() {
+        let slot = RecommendationSlot::new("mode", RecommendationEffect::ModeSignal);
+        slot.publish(1u8, now_ms());
+        let handle = EvaluationLoop::new(slot.clone(), InferenceBudget::per_minute(60), Duration::from_millis(1))
+            .spawn(|| {
+                std::thread::sleep(Duration::from_millis(400));
+                Some(2u8)
+            });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // The model is mid-evaluation; reads still return at once.
+        let started = Instant::now();
+        for _ in 0..1_000 {
+            assert_eq!(slot.value_or(0, now_ms()), 1);
+        }
+        assert!(started.elapsed() < Duration::from_millis(100));
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(slot.value_or(0, now_ms()), 2);
+        handle.abort();
+    }
+
+    #[tokio::test]
+    async fn evaluation_loop_respects_budget() {
+        let slot = RecommendationSlot::new("anomaly", RecommendationEffect::Advisory);
+        let registry = RecommendationRegistry::new();
+        registry.register(&slot);
+        let handle = EvaluationLoop::new(slot.clone(), InferenceBudget::per_minute(3), Duration::from_millis(5))
+            .spawn(|| Some(()));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        handle.abort();
+
+        let report = registry.freshness(now_ms());
+        let f = &report["anomaly"];
+        assert_eq!(f.evaluations, 3);
+        assert_eq!(f.version, Some(3));
+        assert!(f.skipped > 0);
+    }
+}
@@ -3,15 +3,21 @@ pub mod quantum_secure;
 pub mod state_merkle;
 pub mod ai;
 pub mod state_manager;
+pub mod state_overlay;
+pub mod state_archive;
+pub mod migrations;
 pub mod state_storage;
 pub mod sharding;
 pub mod protocol_versioning;
 pub mod shard_manager;
 pub mod shard_registry;
+pub mod shard_state;
+pub mod xshard_trace;
 pub mod shard_lifecycle;
 pub mod shard_epoch_binding;
 pub mod shard_validator_assignment;
 pub mod shard_ai_extension;
+pub mod ai_recommendations;
 pub mod cross_shard_transaction;
 pub mod cross_shard_locking;
 pub mod cross_shard_2pc;
@@ -0,0 +1,418 @@
+//! # Storage migrations
+//!
+//! Every RocksDB store declares the schema version its code reads and
+//! writes, together with the steps that bring older layouts up to it.  The
+//! version lives in the store's own column family next to a one-entry
+//! journal:
+//!
+//! ```text
+//!   schema:version  → u32 be             (absent: fresh, or written before versioning)
+//!   schema:journal  → JournalEntry JSON  (last step started or finished)
+//!
+//!   open ─▶ version > current ─▶ refused
+//!        ─▶ journal "started" ─▶ resume that step
+//!        ─▶ for each step from the stored version:
+//!             journal started → apply(db) → {version = to, journal done}
+//! ```
+//!
+//! The new version and the `done` entry are written in one batch, so a node
+//! that crashes mid-step finds the `started` entry on its next start and runs
+//! the step again; steps must therefore be idempotent.  A store whose version
+//! is newer than the binary's is refused rather than misread, and a dry run
+//! reports the plan without writing anything.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocksdb::{IteratorMode, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const KEY_VERSION: &[u8] = b"schema:version";
+const KEY_JOURNAL: &[u8] = b"schema:journal";
+
+/// One ordered, idempotent layout change.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub from:        u32,
+    pub to:          u32,
+    pub description: &'static str,
+    pub apply:       fn(&DB) -> Result<(), String>,
+}
+
+/// Versioning of one store.
+#[derive(Debug, Clone, Copy)]
+pub struct Schema {
+    /// Name used in logs and errors.
+    pub store:       &'static str,
+    /// Column family holding the version and journal; `None` for the default.
+    pub cf:          Option<&'static str>,
+    /// Version this binary reads and writes.
+    pub current:     u32,
+    /// Version of data written before the store was versioned.
+    pub unversioned: u32,
+    pub migrations:  &'static [Migration],
+}
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("{store} store is at schema version {found}, newer than this binary supports ({supported}); run a newer node or restore a compatible backup")]
+    Newer { store: &'static str, found: u32, supported: u32 },
+    #[error("{store} store: no migration from schema version {from}")]
+    NoPath { store: &'static str, from: u32 },
+    #[error("{store} store: migration {from} → {to} failed: {error}")]
+    Step { store: &'static str, from: u32, to: u32, error: String },
+    #[error("{store} store: {error}")]
+    Storage { store: &'static str, error: String },
+}
+
+/// A step as reported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedStep {
+    pub from:        u32,
+    pub to:          u32,
+    pub description: &'static str,
+}
+
+impl From<&Migration> for PlannedStep {
+    fn from(m: &Migration) -> Self {
+        PlannedStep { from: m.from, to: m.to, description: m.description }
+    }
+}
+
+/// What `migrate` ran, or would run on a dry run.
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub store:   &'static str,
+    /// Stored version before migrating; `None` for a fresh store.
+    pub from:    Option<u32>,
+    pub to:      u32,
+    pub steps:   Vec<PlannedStep>,
+    /// Step an earlier run started but did not finish.
+    pub resumed: Option<PlannedStep>,
+    pub dry_run: bool,
+}
+
+impl MigrationReport {
+    pub fn is_noop(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// One line per step, for logs and the CLI.
+    pub fn render(&self) -> String {
+        let verb = if self.dry_run { "would run" } else { "ran" };
+        let mut out = match self.from {
+            None => format!("{}: fresh store at schema version {}", self.store, self.to),
+            Some(v) if self.is_noop() => format!("{}: schema version {} is current", self.store, v),
+            Some(v) => format!("{}: schema version {} → {}, {} {} step(s)", self.store, v, self.to, verb, self.steps.len()),
+        };
+        if let Some(step) = &self.resumed {
+            out.push_str(&format!("\n  resuming interrupted step {} → {}", step.from, step.to));
+        }
+        for step in &self.steps {
+            out.push_str(&format!("\n  {} → {}: {}", step.from, step.to, step.description));
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum StepPhase {
+    Started,
+    Done,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    from:  u32,
+    to:    u32,
+    phase: StepPhase,
+    at_ms: u64,
+}
+
+impl JournalEntry {
+    fn new(step: &Migration, phase: StepPhase) -> Self {
+        let at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        JournalEntry { from: step.from, to: step.to, phase, at_ms }
+    }
+}
+
+/// Reads and writes of the schema keys in the store's column family.
+struct Meta<'a> {
+    db:     &'a DB,
+    schema: &'a Schema,
+}
+
+impl Meta<'_> {
+    fn err(&self, e: impl std::fmt::Display) -> MigrationError {
+        MigrationError::Storage { store: self.schema.store, error: e.to_string() }
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, MigrationError> {
+        match self.schema.cf {
+            Some(name) => {
+                let cf = self.db.cf_handle(name).ok_or_else(|| self.err(format!("missing column family {}", name)))?;
+                self.db.get_cf(&cf, key).map_err(|e| self.err(e))
+            }
+            None => self.db.get(key).map_err(|e| self.err(e)),
+        }
+    }
+
+    fn put(&self, batch: &mut WriteBatch, key: &[u8], value: &[u8]) -> Result<(), MigrationError> {
+        match self.schema.cf {
+            Some(name) => {
+                let cf = self.db.cf_handle(name).ok_or_else(|| self.err(format!("missing column family {}", name)))?;
+                batch.put_cf(&cf, key, value);
+            }
+            None => batch.put(key, value),
+        }
+        Ok(())
+    }
+
+    fn is_empty(&self) -> Result<bool, MigrationError> {
+        let first = match self.schema.cf {
+            Some(name) => {
+                let cf = self.db.cf_handle(name).ok_or_else(|| self.err(format!("missing column family {}", name)))?;
+                let first = self.db.iterator_cf(&cf, IteratorMode::Start).next();
+                first
+            }
+            None => self.db.iterator(IteratorMode::Start).next(),
+        };
+        match first {
+            None => Ok(true),
+            Some(item) => item.map(|_| false).map_err(|e| self.err(e)),
+        }
+    }
+
+    fn version(&self) -> Result<Option<u32>, MigrationError> {
+        self.get(KEY_VERSION)?
+            .map(|v| {
+                let arr: [u8; 4] = v.as_slice().try_into().map_err(|_| self.err("corrupt schema version"))?;
+                Ok(u32::from_be_bytes(arr))
+            })
+            .transpose()
+    }
+
+    fn journal(&self) -> Result<Option<JournalEntry>, MigrationError> {
+        self.get(KEY_JOURNAL)?
+            .map(|v| serde_json::from_slice(&v).map_err(|e| self.err(format!("corrupt schema journal: {}", e))))
+            .transpose()
+    }
+
+    fn write(&self, version: Option<u32>, entry: Option<&JournalEntry>) -> Result<(), MigrationError> {
+        let mut batch = WriteBatch::default();
+        if let Some(version) = version {
+            self.put(&mut batch, KEY_VERSION, &version.to_be_bytes())?;
+        }
+        if let Some(entry) = entry {
+            let json = serde_json::to_vec(entry).map_err(|e| self.err(e))?;
+            self.put(&mut batch, KEY_JOURNAL, &json)?;
+        }
+        let mut opts = rocksdb::WriteOptions::default();
+        opts.set_sync(true);
+        self.db.write_opt(batch, &opts).map_err(|e| self.err(e))
+    }
+}
+
+/// Schema version stored in `db`; `None` if it has never been stamped.
+pub fn stored_version(db: &DB, schema: &Schema) -> Result<Option<u32>, MigrationError> {
+    Meta { db, schema }.version()
+}
+
+/// Bring `db` up to `schema.current`, or with `dry_run` only report the
+/// steps that would run.
+pub fn migrate(db: &DB, schema: &Schema, dry_run: bool) -> Result<MigrationReport, MigrationError> {
+    let meta = Meta { db, schema };
+    let stored = meta.version()?;
+    let from = match stored {
+        Some(v) => Some(v),
+        None if meta.is_empty()? => None,
+        None => Some(schema.unversioned),
+    };
+    let mut report = MigrationReport {
+        store: schema.store,
+        from,
+        to: schema.current,
+        steps: Vec::new(),
+        resumed: None,
+        dry_run,
+    };
+    let Some(mut version) = from else {
+        // Nothing to migrate: stamp a fresh store with the current layout.
+        if !dry_run {
+            meta.write(Some(schema.current), None)?;
+        }
+        return Ok(report);
+    };
+    if version > schema.current {
+        return Err(MigrationError::Newer { store: schema.store, found: version, supported: schema.current });
+    }
+
+    let mut plan = Vec::new();
+    while version < schema.current {
+        let step = schema.migrations.iter()
+            .find(|m| m.from == version && m.to > version && m.to <= schema.current)
+            .ok_or(MigrationError::NoPath { store: schema.store, from: version })?;
+        plan.push(step);
+        version = step.to;
+    }
+    report.steps = plan.iter().map(|m| PlannedStep::from(*m)).collect();
+    if let (Some(entry), Some(first)) = (meta.journal()?, plan.first()) {
+        if entry.phase == StepPhase::Started && entry.from == first.from && entry.to == first.to {
+            report.resumed = Some(PlannedStep::from(*first));
+        }
+    }
+    if dry_run {
+        return Ok(report);
+    }
+
+    if stored.is_none() {
+        // Pre-versioning data: record what it is before changing it.
+        meta.write(Some(schema.unversioned), None)?;
+    }
+    for step in plan {
+        meta.write(None, Some(&JournalEntry::new(step, StepPhase::Started)))?;
+        log::info!("[migrations] {} {} → {}: {}", schema.store, step.from, step.to, step.description);
+        (step.apply)(db).map_err(|error| MigrationError::Step {
+            store: schema.store,
+            from:  step.from,
+            to:    step.to,
+            error,
+        })?;
+        meta.write(Some(step.to), Some(&JournalEntry::new(step, StepPhase::Done)))?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    const CF_DATA: &str = "data";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().subsec_nanos();
+        std::env::temp_dir().join(format!("bleep-migrations-{}-{}-{}", name, std::process::id(), nanos))
+    }
+
+    fn open(path: &PathBuf) -> DB {
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let cfs = vec![rocksdb::ColumnFamilyDescriptor::new(CF_DATA, rocksdb::Options::default())];
+        DB::open_cf_descriptors(&opts, path, cfs).unwrap()
+    }
+
+    fn put(db: &DB, key: &str, value: &str) -> Result<(), String> {
+        let cf = db.cf_handle(CF_DATA).ok_or("missing cf")?;
+        db.put_cf(&cf, key, value).map_err(|e| e.to_string())
+    }
+
+    fn get(db: &DB, key: &str) -> Option<String> {
+        let cf = db.cf_handle(CF_DATA).unwrap();
+        db.get_cf(&cf, key).unwrap().map(|v| String::from_utf8(v).unwrap())
+    }
+
+    fn dump(db: &DB) -> Vec<(Box<[u8]>, Box<[u8]>)> {
+        let cf = db.cf_handle(CF_DATA).unwrap();
+        db.iterator_cf(&cf, IteratorMode::Start).map(Result::unwrap).collect()
+    }
+
+    static FIRST_RUNS: AtomicUsize = AtomicUsize::new(0);
+    static CRASH_SECOND: AtomicBool = AtomicBool::new(true);
+
+    fn first(db: &DB) -> Result<(), String> {
+        FIRST_RUNS.fetch_add(1, Ordering::SeqCst);
+        put(db, "a", "1")
+    }
+
+    fn second(db: &DB) -> Result<(), String> {
+        put(db, "b", "partial")?;
+        if CRASH_SECOND.swap(false, Ordering::SeqCst) {
+            return Err("injected crash".into());
+        }
+        put(db, "b", "2")
+    }
+
+    fn noop(_: &DB) -> Result<(), String> {
+        Ok(())
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration { from: 1, to: 2, description: "add a", apply: first },
+        Migration { from: 2, to: 3, description: "add b", apply: second },
+    ];
+
+    const NOOPS: &[Migration] = &[
+        Migration { from: 1, to: 2, description: "one", apply: noop },
+        Migration { from: 2, to: 3, description: "two", apply: noop },
+    ];
+
+    const SCHEMA: Schema = Schema { store: "test", cf: Some(CF_DATA), current: 3, unversioned: 1, migrations: STEPS };
+
+    #[test]
+    fn crash_between_steps_resumes_on_next_start() {
+        let path = temp_dir("resume");
+        {
+            let db = open(&path);
+            put(&db, "legacy", "x").unwrap();
+            let err = migrate(&db, &SCHEMA, false).unwrap_err();
+            assert!(matches!(err, MigrationError::Step { from: 2, to: 3, .. }), "{}", err);
+            assert_eq!(stored_version(&db, &SCHEMA).unwrap(), Some(2));
+        }
+
+        let db = open(&path);
+        let report = migrate(&db, &SCHEMA, false).unwrap();
+        assert_eq!(report.from, Some(2));
+        assert_eq!(report.resumed, Some(PlannedStep { from: 2, to: 3, description: "add b" }));
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(FIRST_RUNS.load(Ordering::SeqCst), 1);
+        assert_eq!((get(&db, "a"), get(&db, "b")), (Some("1".into()), Some("2".into())));
+        assert_eq!(stored_version(&db, &SCHEMA).unwrap(), Some(3));
+        assert!(migrate(&db, &SCHEMA, false).unwrap().is_noop());
+        drop(db);
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn dry_run_reports_without_writing() {
+        let path = temp_dir("dry");
+        let db = open(&path);
+        put(&db, "legacy", "x").unwrap();
+        let schema = Schema { migrations: NOOPS, ..SCHEMA };
+        let before = dump(&db);
+        let report = migrate(&db, &schema, true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!((report.from, report.to), (Some(1), 3));
+        assert_eq!(report.steps.iter().map(|s| s.description).collect::<Vec<_>>(), ["one", "two"]);
+        assert_eq!(dump(&db), before);
+        assert_eq!(stored_version(&db, &schema).unwrap(), None);
+        drop(db);
+        let _ = std::fs::remove_dir_all(path);
+    }
+
+    #[test]
+    fn fresh_store_is_stamped_and_newer_store_refused() {
+        let path = temp_dir("versions");
+        let db = open(&path);
+        let schema = Schema { migrations: &[], ..SCHEMA };
+        let report = migrate(&db, &schema, false).unwrap();
+        assert_eq!((report.from, report.steps.len()), (None, 0));
+        assert_eq!(stored_version(&db, &schema).unwrap(), Some(3));
+
+        let older = Schema { current: 2, ..schema };
+        let err = migrate(&db, &older, false).unwrap_err();
+        assert!(matches!(err, MigrationError::Newer { found: 3, supported: 2, .. }));
+        assert!(err.to_string().contains("newer than this binary supports"), "{}", err);
+
+        let gap = Schema { current: 4, ..schema };
+        assert!(matches!(migrate(&db, &gap, false), Err(MigrationError::NoPath { from: 3, .. })));
+        drop(db);
+        let _ = std::fs::remove_dir_all(path);
+    }
+}
@@ -14,6 +14,8 @@
 
 use crate::shard_registry::{ShardId, EpochId};
 use crate::shard_lifecycle::ShardMetrics;
+use crate::ai_recommendations::RecommendationSlot;
+use std::collections::BTreeMap;
 use serde::{Serialize, Deserialize};
 use log::{info, warn};
 
@@ -194,6 +196,20 @@ pub struct AiLoadAnalysis {
     pub epochs_until_split_recommended: Option<u64>,
 }
 
+impl AiLoadAnalysis {
+    /// Zero-confidence analysis used whenever no usable prediction exists.
+    pub fn neutral(shard_id: ShardId) -> Self {
+        AiLoadAnalysis {
+            shard_id,
+            predicted_peak_tps: 0.0,
+            predicted_avg_tps: 0.0,
+            confidence: BoundedScore::new(0),
+            recommended_max_size: u64::MAX,
+            epochs_until_split_recommended: None,
+        }
+    }
+}
+
 /// AI Advisory Input Aggregator
 /// 
 /// SAFETY: Aggregates multiple AI reports deterministically using
@@ -348,20 +364,56 @@ impl AiExtensionHook for NoOpAiExtension {
 /// SAFETY: Manages multiple AI sources and aggregates results safely.
 pub struct AiExtensionManager {
     extension: Box<dyn AiExtensionHook + Send + Sync>,
+    /// Congestion predictions published by a background evaluation loop.
+    load_recommendations: Option<RecommendationSlot<BTreeMap<ShardId, AiLoadAnalysis>>>,
 }
 
 impl AiExtensionManager {
     /// Create a new AI extension manager
     pub fn new(extension: Box<dyn AiExtensionHook + Send + Sync>) -> Self {
-        AiExtensionManager { extension }
+        AiExtensionManager { extension, load_recommendations: None }
     }
     
     /// Create a manager with no-op extension
     pub fn default() -> Self {
         AiExtensionManager {
             extension: Box::new(NoOpAiExtension),
+            load_recommendations: None,
         }
     }
+
+    /// Serve `cached_load_analysis` from `slot`.
+    pub fn with_load_recommendations(
+        mut self,
+        slot: RecommendationSlot<BTreeMap<ShardId, AiLoadAnalysis>>,
+    ) -> Self {
+        self.load_recommendations = Some(slot);
+        self
+    }
+
+    /// Run `analyze_load` for every shard — the model an evaluation loop
+    /// publishes into the load-recommendation slot.
+    pub fn analyze_all(
+        &self,
+        metrics: &BTreeMap<ShardId, ShardMetrics>,
+        historical_data: &[(u64, ShardMetrics)],
+    ) -> BTreeMap<ShardId, AiLoadAnalysis> {
+        metrics.iter()
+            .map(|(id, m)| (*id, self.analyze_load(*id, m, historical_data)))
+            .collect()
+    }
+
+    /// Latest completed congestion prediction for `shard_id`.
+    ///
+    /// Never runs the model: sharding code on the block path reads this,
+    /// and gets `AiLoadAnalysis::neutral` when the prediction is stale,
+    /// missing, or no slot is attached.
+    pub fn cached_load_analysis(&self, shard_id: ShardId, now_ms: u64) -> AiLoadAnalysis {
+        self.load_recommendations.as_ref()
+            .and_then(|slot| slot.fresh(now_ms))
+            .and_then(|r| r.value.get(&shard_id).cloned())
+            .unwrap_or_else(|| AiLoadAnalysis::neutral(shard_id))
+    }
     
     /// Get load analysis for a shard
     pub fn analyze_load(
@@ -377,14 +429,7 @@ impl AiExtensionManager {
             }
             Err(e) => {
                 warn!("AI load analysis failed for shard {:?}: {}", shard_id, e);
-                AiLoadAnalysis {
-                    shard_id,
-                    predicted_peak_tps: 0.0,
-                    predicted_avg_tps: 0.0,
-                    confidence: BoundedScore::new(0),
-                    recommended_max_size: u64::MAX,
-                    epochs_until_split_recommended: None,
-                }
+                AiLoadAnalysis::neutral(shard_id)
             }
         }
     }
@@ -539,4 +584,20 @@ mod tests {
         assert_eq!(advisory.split_recommendation.as_u8(), 50);
         assert_eq!(advisory.merge_recommendation.as_u8(), 50);
     }
+
+    #[test]
+    fn test_cached_load_analysis_falls_back_when_stale() {
+        use crate::ai_recommendations::RecommendationEffect;
+
+        let slot = RecommendationSlot::new("shard_congestion", RecommendationEffect::Advisory)
+            .with_max_age_ms(1_000);
+        let manager = AiExtensionManager::default().with_load_recommendations(slot.clone());
+        let mut predicted = AiLoadAnalysis::neutral(ShardId(1));
+        predicted.predicted_peak_tps = 900.0;
+        slot.publish(BTreeMap::from([(ShardId(1), predicted)]), 10_000);
+
+        assert_eq!(manager.cached_load_analysis(ShardId(1), 10_500).predicted_peak_tps, 900.0);
+        assert_eq!(manager.cached_load_analysis(ShardId(2), 10_500).predicted_peak_tps, 0.0);
+        assert_eq!(manager.cached_load_analysis(ShardId(1), 11_001).predicted_peak_tps, 0.0);
+    }
 }
@@ -0,0 +1,515 @@
+//! # Sharded state commitments
+//!
+//! Every shard keeps its own `SparseMerkleTrie` over the accounts it owns,
+//! and the block header's state root is the root of a small binary tree
+//! over the per-shard roots:
+//!
+//! ```text
+//!   global root
+//!        │            (padded to a power of two with EMPTY)
+//!   ┌────┴────┐
+//!   h01      h23       shard leaf = blake3("bleep:shard" ‖ id BE ‖ shard root)
+//!  ┌─┴─┐    ┌─┴─┐
+//!  s0  s1   s2  s3     one leaf per shard, ordered by shard id
+//! ```
+//!
+//! A `ComposedProof` is the account proof within its shard plus the shard
+//! root's proof within the global root, so a light client only needs the
+//! header root to check either.
+//!
+//! Ownership comes from a `ShardRing`: contiguous ranges of the top 64 bits
+//! of an account's trie path.  Because a range of paths is a set of whole
+//! subtrees, `ShardedState::rebalance` moves subtrees between shard tries by
+//! leaf hash and swaps the new map in only once every shard is rebuilt.
+//!
+//! ## Cross-shard transfers
+//! The source shard debits the sender and records a marker account
+//! `xshard:<source>:debit:<digest>` (balance 0, nonce 1), where `digest`
+//! commits to every field of the `CrossShardDebit`.  Marker accounts are
+//! pinned to the shard named in their address, whatever the ring says.
+//! The destination credits the recipient only after checking a
+//! `ComposedProof` of that marker against the source block's header root,
+//! and records `xshard:<dest>:credit:<digest>` so the credit applies once.
+//! The coordinator's commit or abort is recorded as
+//! `xshard:<source>:decided:<digest>`; an abort refunds the sender.
+//!
+//! The hex `digest` doubles as the transfer's `xshard_id`, the correlation
+//! id of its timeline — see `xshard_trace`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::state_merkle::{
+    interior_hash, key_to_path, leaf_hash, MerkleProof, NodeHash, SparseMerkleTrie,
+};
+
+const EMPTY: NodeHash = [0u8; 32];
+const MARKER_PREFIX: &str = "xshard:";
+
+// ── Shard ring ────────────────────────────────────────────────────────────────
+
+/// Assignment of trie-path ranges to shards.
+///
+/// `ranges` holds `(start, shard_id)` sorted by `start`; a range ends where
+/// the next one begins.  The first range always starts at 0.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardRing {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl ShardRing {
+    /// Split the keyspace into `shards` equal ranges, shard ids `0..shards`.
+    pub fn uniform(shards: u64) -> Self {
+        let shards = shards.max(1);
+        let width = u64::MAX / shards;
+        let ranges = (0..shards).map(|i| (i * width, i)).collect();
+        Self { ranges }
+    }
+
+    /// Build a ring from explicit `(start, shard_id)` boundaries.
+    pub fn from_ranges(mut ranges: Vec<(u64, u64)>) -> Result<Self, String> {
+        ranges.sort_by_key(|(start, _)| *start);
+        match ranges.first() {
+            None => return Err("shard ring has no ranges".into()),
+            Some((start, _)) if *start != 0 => {
+                return Err("shard ring must start at 0".into());
+            }
+            _ => {}
+        }
+        if ranges.windows(2).any(|w| w[0].0 == w[1].0) {
+            return Err("shard ring has duplicate range starts".into());
+        }
+        Ok(Self { ranges })
+    }
+
+    /// Shard owning trie path `path`.
+    pub fn shard_of_path(&self, path: &[u8; 32]) -> u64 {
+        let point = u64::from_be_bytes(path[..8].try_into().expect("8 bytes"));
+        let idx = self.ranges.partition_point(|(start, _)| *start <= point);
+        self.ranges[idx - 1].1
+    }
+
+    /// Every shard id on the ring, ascending.
+    pub fn shard_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.ranges.iter().map(|(_, id)| *id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    pub fn ranges(&self) -> &[(u64, u64)] {
+        &self.ranges
+    }
+}
+
+impl Default for ShardRing {
+    fn default() -> Self {
+        Self::uniform(1)
+    }
+}
+
+/// Shard named by a cross-shard marker address, if `address` is one.
+fn pinned_shard(address: &str) -> Option<u64> {
+    address.strip_prefix(MARKER_PREFIX)?.split(':').next()?.parse().ok()
+}
+
+/// `(shard, leg, xshard_id)` of a cross-shard marker address.
+pub(crate) fn marker_parts(address: &str) -> Option<(u64, &str, &str)> {
+    let mut parts = address.strip_prefix(MARKER_PREFIX)?.splitn(3, ':');
+    let shard = parts.next()?.parse().ok()?;
+    Some((shard, parts.next()?, parts.next()?))
+}
+
+// ── Shard-root tree ───────────────────────────────────────────────────────────
+
+/// Leaf of the shard-root tree for one shard.
+pub fn shard_leaf(shard_id: u64, shard_root: &NodeHash) -> NodeHash {
+    let mut buf = Vec::with_capacity(11 + 8 + 32);
+    buf.extend_from_slice(b"bleep:shard");
+    buf.extend_from_slice(&shard_id.to_be_bytes());
+    buf.extend_from_slice(shard_root);
+    *blake3::hash(&buf).as_bytes()
+}
+
+/// Every level of the shard-root tree, leaves first; the last level is the
+/// single global root.
+fn shard_tree_levels(leaves: Vec<NodeHash>) -> Vec<Vec<NodeHash>> {
+    let mut level = leaves;
+    level.resize(level.len().max(1).next_power_of_two(), EMPTY);
+    let mut levels = vec![level];
+    while levels.last().expect("non-empty").len() > 1 {
+        let next = levels.last().expect("non-empty")
+            .chunks(2)
+            .map(|pair| interior_hash(&pair[0], &pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// Proof that a shard root is committed to by a global state root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShardRootProof {
+    pub shard_id:   u64,
+    pub shard_root: NodeHash,
+    /// Position of the shard's leaf in the tree.
+    pub index:      u64,
+    /// Sibling hashes from the leaf level up.
+    pub siblings:   Vec<NodeHash>,
+}
+
+impl ShardRootProof {
+    pub fn verify(&self, global_root: &NodeHash) -> bool {
+        if self.siblings.len() >= 64 || self.index >> self.siblings.len() != 0 {
+            return false;
+        }
+        let mut current = shard_leaf(self.shard_id, &self.shard_root);
+        for (level, sibling) in self.siblings.iter().enumerate() {
+            current = if (self.index >> level) & 1 == 0 {
+                interior_hash(&current, sibling)
+            } else {
+                interior_hash(sibling, &current)
+            };
+        }
+        &current == global_root
+    }
+}
+
+/// An account proof within its shard plus that shard's proof within the
+/// global root.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposedProof {
+    pub account: MerkleProof,
+    pub shard:   ShardRootProof,
+}
+
+impl ComposedProof {
+    pub fn shard_id(&self) -> u64 {
+        self.shard.shard_id
+    }
+
+    /// Check both layers against a block header's state root.
+    pub fn verify(&self, global_root: &NodeHash) -> bool {
+        self.account.root == self.shard.shard_root
+            && self.account.verify(&self.shard.shard_root)
+            && self.shard.verify(global_root)
+    }
+}
+
+// ── Sharded state ─────────────────────────────────────────────────────────────
+
+/// Per-shard tries and the global root over them.
+#[derive(Debug, Clone)]
+pub struct ShardedState {
+    ring:   ShardRing,
+    shards: BTreeMap<u64, SparseMerkleTrie>,
+    /// Marker-account paths and the shard they are pinned to.
+    pinned: HashMap<[u8; 32], u64>,
+}
+
+impl ShardedState {
+    pub fn new(ring: ShardRing) -> Self {
+        let shards = ring.shard_ids().into_iter()
+            .map(|id| (id, SparseMerkleTrie::new()))
+            .collect();
+        Self { ring, shards, pinned: HashMap::new() }
+    }
+
+    pub fn ring(&self) -> &ShardRing {
+        &self.ring
+    }
+
+    /// Shard that owns `address`.
+    pub fn shard_of(&self, address: &str) -> u64 {
+        match pinned_shard(address) {
+            Some(id) if self.shards.contains_key(&id) => id,
+            _ => self.ring.shard_of_path(&key_to_path(address)),
+        }
+    }
+
+    /// Insert or update an account leaf in its shard.
+    pub fn insert(&mut self, address: &str, balance: u128, nonce: u64) {
+        let shard = self.shard_of(address);
+        if pinned_shard(address).is_some() {
+            self.pinned.insert(key_to_path(address), shard);
+        }
+        self.shards.entry(shard).or_default().insert(address, balance, nonce);
+    }
+
+    /// Remove an account leaf from its shard.
+    pub fn remove(&mut self, address: &str) {
+        let shard = self.shard_of(address);
+        self.pinned.remove(&key_to_path(address));
+        if let Some(trie) = self.shards.get_mut(&shard) {
+            trie.remove(address);
+        }
+    }
+
+    /// Root of every shard's trie, by shard id.
+    pub fn shard_roots(&mut self) -> BTreeMap<u64, NodeHash> {
+        self.shards.iter_mut().map(|(id, trie)| (*id, trie.root())).collect()
+    }
+
+    fn levels(&mut self) -> Vec<Vec<NodeHash>> {
+        let leaves = self.shard_roots().iter()
+            .map(|(id, root)| shard_leaf(*id, root))
+            .collect();
+        shard_tree_levels(leaves)
+    }
+
+    /// The state root committed to in block headers.
+    pub fn global_root(&mut self) -> NodeHash {
+        self.levels().last().expect("non-empty")[0]
+    }
+
+    /// Inclusion or exclusion proof for `address` against `global_root`.
+    pub fn prove(&mut self, address: &str) -> ComposedProof {
+        let shard_id = self.shard_of(address);
+        let account = self.shards.entry(shard_id).or_default().prove(address);
+        let index = self.shards.keys().position(|id| *id == shard_id).expect("shard exists");
+        let levels = self.levels();
+        let siblings = levels[..levels.len() - 1].iter().enumerate()
+            .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+            .collect();
+        ComposedProof {
+            shard: ShardRootProof {
+                shard_id,
+                shard_root: account.root,
+                index: index as u64,
+                siblings,
+            },
+            account,
+        }
+    }
+
+    /// Reassign accounts to the shards of `ring`.
+    ///
+    /// Leaves move by path and hash, so no account data is needed.  The new
+    /// tries are built aside and replace the old ones in a single swap; a
+    /// ring that would strand a pinned marker (its shard disappears) is
+    /// rejected and leaves the state untouched.
+    pub fn rebalance(&mut self, ring: ShardRing) -> Result<(), String> {
+        let ids = ring.shard_ids();
+        if let Some(shard) = self.pinned.values().find(|id| !ids.contains(id)) {
+            return Err(format!("shard {} still holds cross-shard markers", shard));
+        }
+        let mut shards: BTreeMap<u64, SparseMerkleTrie> = ids.into_iter()
+            .map(|id| (id, SparseMerkleTrie::new()))
+            .collect();
+        for trie in self.shards.values() {
+            for (path, leaf) in trie.leaf_entries() {
+                let shard = self.pinned.get(path).copied()
+                    .unwrap_or_else(|| ring.shard_of_path(path));
+                shards.entry(shard).or_default().insert_leaf(*path, *leaf);
+            }
+        }
+        self.ring = ring;
+        self.shards = shards;
+        Ok(())
+    }
+
+    /// Total leaves across all shards.
+    pub fn len(&self) -> usize {
+        self.shards.values().map(SparseMerkleTrie::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for ShardedState {
+    fn default() -> Self {
+        Self::new(ShardRing::default())
+    }
+}
+
+// ── Cross-shard transfers ─────────────────────────────────────────────────────
+
+/// A debit taken on the source shard, to be credited on `dest_shard`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrossShardDebit {
+    pub source_shard: u64,
+    pub dest_shard:   u64,
+    pub sender:       String,
+    pub recipient:    String,
+    pub amount:       u128,
+    /// Sender nonce the debit consumed; makes every debit unique.
+    pub nonce:        u64,
+}
+
+impl CrossShardDebit {
+    /// Commitment to every field of the debit.
+    pub fn digest(&self) -> NodeHash {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.source_shard.to_be_bytes());
+        hasher.update(&self.dest_shard.to_be_bytes());
+        for s in [&self.sender, &self.recipient] {
+            hasher.update(&(s.len() as u64).to_be_bytes());
+            hasher.update(s.as_bytes());
+        }
+        hasher.update(&self.amount.to_be_bytes());
+        hasher.update(&self.nonce.to_be_bytes());
+        *hasher.finalize().as_bytes()
+    }
+
+    /// Correlation id of the transfer: the hex digest, as it appears in
+    /// every marker address.
+    pub fn xshard_id(&self) -> String {
+        hex::encode(self.digest())
+    }
+
+    /// Marker account recorded on the source shard.
+    pub fn debit_marker(&self) -> String {
+        format!("{}{}:debit:{}", MARKER_PREFIX, self.source_shard, hex::encode(self.digest()))
+    }
+
+    /// Marker account recorded on the destination shard once credited.
+    pub fn credit_marker(&self) -> String {
+        format!("{}{}:credit:{}", MARKER_PREFIX, self.dest_shard, hex::encode(self.digest()))
+    }
+
+    /// Marker account recorded on the source shard once the transfer is
+    /// committed or aborted.
+    pub fn decision_marker(&self) -> String {
+        format!("{}{}:decided:{}", MARKER_PREFIX, self.source_shard, hex::encode(self.digest()))
+    }
+
+    /// Leaf hash of the debit marker (marker accounts are balance 0, nonce 1).
+    pub fn marker_leaf(&self) -> NodeHash {
+        leaf_hash(&self.debit_marker(), 0, 1)
+    }
+}
+
+/// A debit plus the proof that the source shard committed to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossShardCredit {
+    pub debit: CrossShardDebit,
+    /// Proof of `debit.debit_marker()`; carries the source shard's root.
+    pub proof: ComposedProof,
+}
+
+impl CrossShardCredit {
+    /// Check that the source shard's debit is included under `source_root`,
+    /// the header state root of the block that committed it.
+    pub fn verify(&self, source_root: &NodeHash) -> Result<(), String> {
+        let proof = &self.proof;
+        if proof.shard_id() != self.debit.source_shard {
+            return Err(format!(
+                "proof is for shard {}, debit came from shard {}",
+                proof.shard_id(), self.debit.source_shard,
+            ));
+        }
+        if proof.account.address != self.debit.debit_marker()
+            || !proof.account.exists
+            || proof.account.leaf != self.debit.marker_leaf()
+        {
+            return Err("proof does not cover this debit".into());
+        }
+        if !proof.verify(source_root) {
+            return Err("debit inclusion proof does not verify against the source root".into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated(ring: ShardRing) -> ShardedState {
+        let mut s = ShardedState::new(ring);
+        for i in 0..40u64 {
+            s.insert(&format!("acct-{}", i), 1_000 + i as u128, i);
+        }
+        s
+    }
+
+    #[test]
+    fn composed_proof_verifies_against_global_root() {
+        let mut s = populated(ShardRing::uniform(3));
+        let root = s.global_root();
+        assert_eq!(s.shard_roots().len(), 3);
+
+        let proof = s.prove("acct-7");
+        assert!(proof.account.exists);
+        assert_eq!(proof.shard_id(), s.shard_of("acct-7"));
+        assert!(proof.verify(&root));
+        assert!(s.prove("nobody").verify(&root));
+
+        let mut forged = proof.clone();
+        forged.shard.shard_id = (proof.shard_id() + 1) % 3;
+        assert!(!forged.verify(&root));
+        let mut forged = proof;
+        forged.account.leaf = leaf_hash("acct-7", 1_000_000, 7);
+        assert!(!forged.verify(&root));
+    }
+
+    #[test]
+    fn credit_with_invalid_debit_proof_is_rejected() {
+        let mut s = populated(ShardRing::uniform(2));
+        let debit = CrossShardDebit {
+            source_shard: s.shard_of("acct-1"),
+            dest_shard:   1 - s.shard_of("acct-1"),
+            sender:       "acct-1".into(),
+            recipient:    "acct-2".into(),
+            amount:       250,
+            nonce:        2,
+        };
+        s.insert(&debit.debit_marker(), 0, 1);
+        assert_eq!(s.shard_of(&debit.debit_marker()), debit.source_shard);
+        let root = s.global_root();
+
+        let credit = CrossShardCredit { proof: s.prove(&debit.debit_marker()), debit: debit.clone() };
+        assert!(credit.verify(&root).is_ok());
+
+        // Inflating the amount changes the marker the proof must cover.
+        let mut inflated = credit.clone();
+        inflated.debit.amount = 25_000;
+        assert!(inflated.verify(&root).is_err());
+
+        // A valid-looking proof against a different root.
+        let mut stale = credit.clone();
+        stale.proof.shard.shard_root[0] ^= 1;
+        stale.proof.account.root = stale.proof.shard.shard_root;
+        assert!(stale.verify(&root).is_err());
+
+        // An exclusion proof for a debit that was never taken.
+        let mut unknown = debit;
+        unknown.nonce = 99;
+        let bogus = CrossShardCredit { proof: s.prove(&unknown.debit_marker()), debit: unknown };
+        assert!(bogus.verify(&root).is_err());
+    }
+
+    #[test]
+    fn rebalance_root_matches_recomputation() {
+        let mut s = populated(ShardRing::uniform(2));
+        let debit = CrossShardDebit {
+            source_shard: 1,
+            dest_shard:   0,
+            sender:       "acct-3".into(),
+            recipient:    "acct-4".into(),
+            amount:       10,
+            nonce:        3,
+        };
+        s.insert(&debit.debit_marker(), 0, 1);
+        let before = s.global_root();
+
+        let ring = ShardRing::from_ranges(vec![(0, 0), (1 << 62, 1), (3 << 62, 2)]).unwrap();
+        s.rebalance(ring.clone()).unwrap();
+        let after = s.global_root();
+        assert_ne!(before, after);
+        assert_eq!(s.shard_of(&debit.debit_marker()), 1);
+
+        let mut fresh = populated(ring);
+        fresh.insert(&debit.debit_marker(), 0, 1);
+        assert_eq!(fresh.shard_roots(), s.shard_roots());
+        assert_eq!(fresh.global_root(), after);
+        assert!(s.prove("acct-11").verify(&after));
+
+        // Dropping shard 1 would strand the pinned debit marker.
+        assert!(s.rebalance(ShardRing::uniform(1)).is_err());
+        assert_eq!(s.global_root(), after);
+    }
+}
@@ -0,0 +1,443 @@
+//! # State archive
+//!
+//! Optional (disk-hungry) history of every committed state root, so reads at
+//! any archived height keep working after the undo journal has been pruned.
+//!
+//! ```text
+//!   arch:root:<root>                 → height                 (committed roots)
+//!   arch:h2r:<height be>             → root
+//!   arch:ver:<address> 00 <height be> → node hash            (account versions)
+//!   arch:node:<node hash>            → AccountState JSON     (content-addressed)
+//!   arch:base / arch:tip             → first / last archived height
+//! ```
+//!
+//! Each block writes one version entry per account it touched; an account's
+//! value at height `h` is its newest version at or below `h`.  Node bodies
+//! are shared between roots by hash, and an empty account is a version
+//! pointing at the all-zero hash.
+//!
+//! `gc` keeps a version if it is the one some retained root resolves to, and
+//! a node while any surviving version references it, so queries at retained
+//! roots are unaffected by collection.  The newest root is always retained
+//! because later blocks only write the accounts they touch.
+
+use std::collections::{BTreeMap, HashSet};
+
+use rocksdb::{Direction, IteratorMode, WriteBatch, DB};
+
+use crate::state_manager::{AccountState, StateError, StateManager, StateResult};
+use crate::state_merkle::NodeHash;
+
+const PREFIX_ROOT: &[u8]    = b"arch:root:";
+const PREFIX_HEIGHT: &[u8]  = b"arch:h2r:";
+const PREFIX_VERSION: &[u8] = b"arch:ver:";
+const PREFIX_NODE: &[u8]    = b"arch:node:";
+const KEY_BASE: &[u8]       = b"arch:base";
+const KEY_TIP: &[u8]        = b"arch:tip";
+const PREFIX_ALL: &[u8]     = b"arch:";
+
+const EMPTY_NODE: NodeHash = [0u8; 32];
+
+/// What `StateManager::archive_gc` removed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArchiveGcStats {
+    pub roots_dropped:    usize,
+    pub versions_dropped: usize,
+    pub nodes_dropped:    usize,
+}
+
+fn storage_err(e: impl std::fmt::Display) -> StateError {
+    StateError::Storage(e.to_string())
+}
+
+fn be_u64(bytes: &[u8]) -> StateResult<u64> {
+    let arr: [u8; 8] = bytes.try_into().map_err(|_| storage_err("corrupt archive height"))?;
+    Ok(u64::from_be_bytes(arr))
+}
+
+fn root_key(root: &NodeHash) -> Vec<u8> {
+    [PREFIX_ROOT, &root[..]].concat()
+}
+
+fn height_key(height: u64) -> Vec<u8> {
+    [PREFIX_HEIGHT, &height.to_be_bytes()[..]].concat()
+}
+
+fn version_prefix(address: &str) -> Vec<u8> {
+    [PREFIX_VERSION, address.as_bytes(), &[0u8]].concat()
+}
+
+fn version_key(address: &str, height: u64) -> Vec<u8> {
+    [version_prefix(address), height.to_be_bytes().to_vec()].concat()
+}
+
+fn node_key(hash: &NodeHash) -> Vec<u8> {
+    [PREFIX_NODE, &hash[..]].concat()
+}
+
+fn read_height(db: &DB, key: &[u8]) -> StateResult<Option<u64>> {
+    match db.get(key).map_err(storage_err)? {
+        Some(v) => Ok(Some(be_u64(&v)?)),
+        None => Ok(None),
+    }
+}
+
+/// First archived height, if the archive has been initialised.
+pub(crate) fn base_height(db: &DB) -> StateResult<Option<u64>> {
+    read_height(db, KEY_BASE)
+}
+
+/// Last archived height.
+pub(crate) fn tip_height(db: &DB) -> StateResult<Option<u64>> {
+    read_height(db, KEY_TIP)
+}
+
+/// Height at which `root` was committed, if it is archived.
+pub(crate) fn root_height(db: &DB, root: &NodeHash) -> StateResult<Option<u64>> {
+    read_height(db, &root_key(root))
+}
+
+/// Root committed at `height`, if archived.
+pub(crate) fn root_at(db: &DB, height: u64) -> StateResult<Option<NodeHash>> {
+    match db.get(height_key(height)).map_err(storage_err)? {
+        Some(v) => Ok(Some(v.as_slice().try_into().map_err(|_| storage_err("corrupt archive root"))?)),
+        None => Ok(None),
+    }
+}
+
+/// Account value at `height`; `None` if the archive has no version of it.
+pub(crate) fn account_at(db: &DB, address: &str, height: u64) -> StateResult<Option<AccountState>> {
+    let prefix = version_prefix(address);
+    let seek = version_key(address, height);
+    let mut iter = db.iterator(IteratorMode::From(&seek, Direction::Reverse));
+    let hash: NodeHash = match iter.next() {
+        Some(item) => {
+            let (k, v) = item.map_err(storage_err)?;
+            if !k.starts_with(&prefix) {
+                return Ok(None);
+            }
+            v.as_ref().try_into().map_err(|_| storage_err("corrupt archive version"))?
+        }
+        None => return Ok(None),
+    };
+    if hash == EMPTY_NODE {
+        return Ok(Some(AccountState::default()));
+    }
+    let body = db.get(node_key(&hash)).map_err(storage_err)?
+        .ok_or_else(|| storage_err(format!("archive node {} missing", hex::encode(hash))))?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| StateError::Serialisation(e.to_string()))
+}
+
+/// Stage one committed root and the accounts that changed to reach it.
+pub(crate) fn record<'a, I>(batch: &mut WriteBatch, height: u64, root: NodeHash, accounts: I) -> StateResult<()>
+where
+    I: IntoIterator<Item = (&'a str, &'a AccountState)>,
+{
+    for (address, state) in accounts {
+        let hash = if state.is_empty() {
+            EMPTY_NODE
+        } else {
+            let body = serde_json::to_vec(state)
+                .map_err(|e| StateError::Serialisation(e.to_string()))?;
+            let hash = *blake3::hash(&body).as_bytes();
+            batch.put(node_key(&hash), body);
+            hash
+        };
+        batch.put(version_key(address, height), hash);
+    }
+    batch.put(root_key(&root), height.to_be_bytes());
+    batch.put(height_key(height), root);
+    batch.put(KEY_TIP, height.to_be_bytes());
+    Ok(())
+}
+
+/// Mark `height` as the first archived height.
+pub(crate) fn set_base(batch: &mut WriteBatch, height: u64) {
+    batch.put(KEY_BASE, height.to_be_bytes());
+}
+
+/// Stage removal of the root at `height` and the versions written for it.
+pub(crate) fn unrecord<'a, I>(db: &DB, batch: &mut WriteBatch, height: u64, touched: I) -> StateResult<()>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    for address in touched {
+        batch.delete(version_key(address, height));
+    }
+    if let Some(root) = root_at(db, height)? {
+        // Identical roots at consecutive heights share one record.
+        if root_height(db, &root)? == Some(height) {
+            batch.delete(root_key(&root));
+        }
+    }
+    batch.delete(height_key(height));
+    if height > 0 {
+        batch.put(KEY_TIP, (height - 1).to_be_bytes());
+    }
+    Ok(())
+}
+
+/// Stage deletion of every archive key.
+pub(crate) fn clear(db: &DB, batch: &mut WriteBatch) -> StateResult<()> {
+    for item in db.prefix_iterator(PREFIX_ALL) {
+        let (k, _) = item.map_err(storage_err)?;
+        if !k.starts_with(PREFIX_ALL) { break; }
+        batch.delete(k);
+    }
+    Ok(())
+}
+
+/// Drop every root not in `retained` plus whatever only they referenced.
+pub(crate) fn gc(db: &DB, retained: &[NodeHash]) -> StateResult<ArchiveGcStats> {
+    let mut stats = ArchiveGcStats::default();
+    let mut keep_heights: Vec<u64> = Vec::new();
+    for root in retained {
+        if let Some(h) = root_height(db, root)? {
+            keep_heights.push(h);
+        }
+    }
+    if let Some(tip) = tip_height(db)? {
+        keep_heights.push(tip);
+    }
+    keep_heights.sort_unstable();
+    keep_heights.dedup();
+
+    let mut batch = WriteBatch::default();
+
+    // Roots.
+    for item in db.prefix_iterator(PREFIX_HEIGHT) {
+        let (k, v) = item.map_err(storage_err)?;
+        if !k.starts_with(PREFIX_HEIGHT) { break; }
+        let h = be_u64(&k[PREFIX_HEIGHT.len()..])?;
+        if keep_heights.binary_search(&h).is_err() {
+            batch.delete(&k);
+            batch.delete([PREFIX_ROOT, &v[..]].concat());
+            stats.roots_dropped += 1;
+        }
+    }
+    // A dropped height may have shared its root record with a kept one.
+    for h in &keep_heights {
+        if let Some(root) = root_at(db, *h)? {
+            if root_height(db, &root)?.is_some_and(|r| r != *h) {
+                batch.put(root_key(&root), h.to_be_bytes());
+            }
+        }
+    }
+
+    // Versions: group by address (keys sort by address, then height).
+    let mut live_nodes: HashSet<NodeHash> = HashSet::new();
+    let mut group: BTreeMap<u64, (Box<[u8]>, NodeHash)> = BTreeMap::new();
+    let mut group_addr: Vec<u8> = Vec::new();
+    let flush = |group: &mut BTreeMap<u64, (Box<[u8]>, NodeHash)>,
+                     batch: &mut WriteBatch,
+                     live: &mut HashSet<NodeHash>,
+                     stats: &mut ArchiveGcStats| {
+        let heights: Vec<u64> = group.keys().copied().collect();
+        for (i, h) in heights.iter().enumerate() {
+            let next = heights.get(i + 1).copied().unwrap_or(u64::MAX);
+            let idx = keep_heights.partition_point(|r| r < h);
+            let referenced = keep_heights.get(idx).is_some_and(|r| *r < next);
+            let (key, hash) = &group[h];
+            if referenced {
+                live.insert(*hash);
+            } else {
+                batch.delete(key);
+                stats.versions_dropped += 1;
+            }
+        }
+        group.clear();
+    };
+    for item in db.prefix_iterator(PREFIX_VERSION) {
+        let (k, v) = item.map_err(storage_err)?;
+        if !k.starts_with(PREFIX_VERSION) { break; }
+        if k.len() < PREFIX_VERSION.len() + 9 {
+            return Err(storage_err("corrupt archive version key"));
+        }
+        let (addr, height) = k[PREFIX_VERSION.len()..].split_at(k.len() - PREFIX_VERSION.len() - 8);
+        if addr != group_addr.as_slice() {
+            flush(&mut group, &mut batch, &mut live_nodes, &mut stats);
+            group_addr = addr.to_vec();
+        }
+        let hash: NodeHash = v.as_ref().try_into().map_err(|_| storage_err("corrupt archive version"))?;
+        group.insert(be_u64(height)?, (k.clone(), hash));
+    }
+    flush(&mut group, &mut batch, &mut live_nodes, &mut stats);
+
+    // Nodes no surviving version points at.
+    for item in db.prefix_iterator(PREFIX_NODE) {
+        let (k, _) = item.map_err(storage_err)?;
+        if !k.starts_with(PREFIX_NODE) { break; }
+        let hash: NodeHash = k[PREFIX_NODE.len()..].try_into().map_err(|_| storage_err("corrupt archive node key"))?;
+        if !live_nodes.contains(&hash) {
+            batch.delete(&k);
+            stats.nodes_dropped += 1;
+        }
+    }
+
+    if let Some(first) = keep_heights.first() {
+        set_base(&mut batch, *first);
+    }
+    db.write(batch).map_err(storage_err)?;
+    Ok(stats)
+}
+
+// ── Read-only views ───────────────────────────────────────────────────────────
+
+/// Read-only view of the state at one committed root or height.
+///
+/// Heights inside the journal window work on every node; older ones need
+/// archive mode and otherwise fail with `StateError::NotArchived`.
+pub struct StateView<'a> {
+    mgr:    &'a StateManager,
+    height: u64,
+    root:   Option<NodeHash>,
+}
+
+impl<'a> StateView<'a> {
+    /// View at an archived state root.
+    pub fn at(mgr: &'a StateManager, root: &NodeHash) -> StateResult<Self> {
+        let height = mgr.archived_height_of(root)?
+            .ok_or_else(|| StateError::UnknownRoot(hex::encode(root)))?;
+        Ok(StateView { mgr, height, root: Some(*root) })
+    }
+
+    /// View at a block height.
+    pub fn at_height(mgr: &'a StateManager, height: u64) -> StateResult<Self> {
+        let current = mgr.block_height();
+        if height > current {
+            return Err(StateError::FutureHeight { requested: height, current });
+        }
+        let archived = mgr.earliest_archived_height()?;
+        if height < mgr.earliest_queryable_height() && !archived.is_some_and(|base| base <= height) {
+            let earliest = archived.unwrap_or_else(|| mgr.earliest_queryable_height());
+            return Err(StateError::NotArchived { requested: height, earliest });
+        }
+        Ok(StateView { mgr, height, root: mgr.archived_root_at(height)? })
+    }
+
+    pub fn height(&self) -> u64 { self.height }
+
+    /// The committed root, when the archive recorded one for this height.
+    pub fn root(&self) -> Option<NodeHash> { self.root }
+
+    pub fn account(&self, address: &str) -> StateResult<AccountState> {
+        self.mgr.account_at(address, self.height)
+    }
+
+    pub fn get_balance(&self, address: &str) -> StateResult<u128> {
+        Ok(self.account(address)?.balance)
+    }
+
+    pub fn get_nonce(&self, address: &str) -> StateResult<u64> {
+        Ok(self.account(address)?.nonce)
+    }
+
+    pub fn get_storage(&self, contract: &str, key: &[u8]) -> StateResult<Option<Vec<u8>>> {
+        Ok(self.account(contract)?.storage.get(&hex::encode(key))
+            .and_then(|v| hex::decode(v).ok()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> std::path::PathBuf {
+        use std::sync::atomic::{AtomicU64, Ordering};
+        static N: AtomicU64 = AtomicU64::new(0);
+        std::env::temp_dir().join(format!(
+            "bleep-archive-{}-{}-{}", tag, std::process::id(), N.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    fn archive_node(tag: &str, retention: u64) -> StateManager {
+        let mut m = StateManager::open(temp_dir(tag)).expect("open");
+        m.set_history_retention(retention);
+        m.enable_archive().expect("archive");
+        m
+    }
+
+    #[test]
+    fn balances_at_heights_straddling_a_transfer() {
+        let mut m = archive_node("straddle", 1);
+        m.mint("alice", 100).expect("mint");
+        m.set_storage("vault", b"k", b"v1");
+        m.advance_block();                          // height 1
+        let root_1 = m.state_root();
+        assert!(m.apply_transfer("alice", "bob", 40));
+        m.set_storage("vault", b"k", b"v2");
+        m.advance_block();                          // height 2
+        for _ in 0..3 {
+            m.mint("carol", 1).expect("mint");
+            m.advance_block();                      // heights 3..5
+        }
+        assert!(m.earliest_queryable_height() > 2, "heights 1-2 are out of the journal");
+
+        let before = StateView::at(&m, &root_1).expect("root 1");
+        assert_eq!(before.height(), 1);
+        assert_eq!(before.get_balance("alice").unwrap(), 100);
+        assert_eq!(before.get_balance("bob").unwrap(), 0);
+        assert_eq!(before.get_storage("vault", b"k").unwrap(), Some(b"v1".to_vec()));
+
+        let after = StateView::at_height(&m, 2).expect("height 2");
+        assert_eq!(after.get_balance("alice").unwrap(), 60);
+        assert_eq!(after.get_balance("bob").unwrap(), 40);
+        assert_eq!(after.get_nonce("alice").unwrap(), 1);
+        assert_eq!(after.get_storage("vault", b"k").unwrap(), Some(b"v2".to_vec()));
+
+        let genesis = StateView::at_height(&m, 0).expect("height 0");
+        assert_eq!(genesis.get_balance("alice").unwrap(), 0);
+        assert!(matches!(
+            StateView::at(&m, &[7u8; 32]), Err(StateError::UnknownRoot(_))
+        ));
+    }
+
+    #[test]
+    fn non_archive_node_rejects_old_heights() {
+        let mut m = StateManager::open(temp_dir("plain")).expect("open");
+        m.set_history_retention(2);
+        for _ in 0..5 {
+            m.mint("bob", 1).expect("mint");
+            m.advance_block();
+        }
+        assert_eq!(StateView::at_height(&m, 4).unwrap().get_balance("bob").unwrap(), 4);
+        assert!(matches!(
+            StateView::at_height(&m, 1),
+            Err(StateError::NotArchived { requested: 1, earliest: 3 })
+        ));
+        assert!(matches!(StateView::at_height(&m, 6), Err(StateError::FutureHeight { .. })));
+    }
+
+    #[test]
+    fn gc_keeps_retained_roots_queryable() {
+        let mut m = archive_node("gc", 1);
+        let mut roots = Vec::new();
+        for i in 1..=6u128 {
+            m.mint("alice", i).expect("mint");
+            m.mint(&format!("acct{}", i), 1).expect("mint");
+            m.advance_block();
+            roots.push(m.state_root());
+        }
+        // Keep heights 2 and 4; 6 is the tip and is always kept.
+        let stats = m.archive_gc(&[roots[1], roots[3]]).expect("gc");
+        assert_eq!(stats.roots_dropped, 4, "heights 0, 1, 3, 5");
+        assert!(stats.versions_dropped > 0 && stats.nodes_dropped > 0);
+
+        let v2 = StateView::at(&m, &roots[1]).expect("root 2");
+        assert_eq!(v2.get_balance("alice").unwrap(), 1 + 2);
+        assert_eq!(v2.get_balance("acct1").unwrap(), 1);
+        assert_eq!(v2.get_balance("acct3").unwrap(), 0);
+        let v4 = StateView::at(&m, &roots[3]).expect("root 4");
+        assert_eq!(v4.get_balance("alice").unwrap(), 1 + 2 + 3 + 4);
+        assert_eq!(v4.get_balance("acct3").unwrap(), 1);
+        assert!(matches!(StateView::at(&m, &roots[2]), Err(StateError::UnknownRoot(_))));
+
+        // The tip survives, and blocks after GC still archive correctly.
+        m.mint("alice", 100).expect("mint");
+        m.advance_block();
+        assert_eq!(StateView::at(&m, &roots[5]).unwrap().get_balance("alice").unwrap(), 21);
+        assert_eq!(StateView::at_height(&m, 6).unwrap().get_balance("acct6").unwrap(), 1);
+    }
+}
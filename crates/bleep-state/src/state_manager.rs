@@ -2,15 +2,36 @@
 //!
 //! RocksDB-backed state manager with:
 //!   - Account balances, nonces, code hashes persisted to disk
-//!   - **Sparse Merkle Trie** state root (Sprint 3 upgrade from blake3 hash-of-pairs)
+//!   - **Sparse Merkle Trie** state root (Sprint 3 upgrade from blake3 hash-of-pairs),
+//!     one trie per shard rolled into the global root — see `shard_state`
 //!   - Snapshot / restore for crash recovery
 //!   - In-memory write-back cache for hot-path performance
-
-use std::collections::HashMap;
+//!   - Per-block pre-image journal for reads at recent (non-pruned) heights,
+//!     persisted alongside the accounts so it survives restarts
+//!   - `fork_at` — a full copy of the state at a past height (block replay)
+//!   - `rollback_to` — unwind the state in place to a recent height (recovery)
+//!   - Optional archive mode (`enable_archive`) keeping every committed root
+//!     readable past the journal window — see `state_archive`
+//!   - Per-account signing keys installed by key-change transactions, with
+//!     a grace period for the replaced key — see `AccountAuth`
+//!   - `export_accounts` / `import_accounts` — the account set at a height,
+//!     as served and adopted by snap sync
+//!   - Cross-shard debit / credit / commit / abort, each step logged under
+//!     the transfer's `xshard_id` — see `xshard_trace`
+//!   - A schema version migrated forward on `open`; stores newer than
+//!     `STATE_SCHEMA_VERSION` are refused — see `migrations`
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
 use std::path::Path;
+use std::time::Duration;
 use thiserror::Error;
 
-use crate::state_merkle::SparseMerkleTrie;
+use crate::migrations::{self, Migration, MigrationError, MigrationReport, Schema};
+use crate::state_archive::{self, ArchiveGcStats};
+use crate::shard_state::{
+    marker_parts, ComposedProof, CrossShardCredit, CrossShardDebit, ShardRing, ShardedState,
+};
+use crate::xshard_trace::{self, XShardPhase, XShardTelemetry, XShardTimeline, DEFAULT_XSHARD_SLO};
 
 #[derive(Debug, Error)]
 pub enum StateError {
@@ -20,6 +41,18 @@ pub enum StateError {
     Storage(String),
     #[error("Serialisation error: {0}")]
     Serialisation(String),
+    #[error("State at height {0} has been pruned")]
+    Pruned(u64),
+    #[error("Height {requested} is ahead of the current height {current}")]
+    FutureHeight { requested: u64, current: u64 },
+    #[error("State at height {requested} is not archived (earliest available: {earliest})")]
+    NotArchived { requested: u64, earliest: u64 },
+    #[error("Unknown state root {0}")]
+    UnknownRoot(String),
+    #[error("Cross-shard operation rejected: {0}")]
+    CrossShard(String),
+    #[error(transparent)]
+    Migration(#[from] MigrationError),
 }
 
 pub type StateResult<T> = Result<T, StateError>;
@@ -27,6 +60,57 @@ pub type StateResult<T> = Result<T, StateError>;
 // On-disk key prefixes
 const PREFIX_ACCOUNT: &[u8] = b"acct:";
 const KEY_HEIGHT: &[u8]     = b"sys:block_height";
+const PREFIX_UNDO: &[u8]    = b"undo:";
+const PREFIX_SHARD: &[u8]   = b"shard:";
+const KEY_SHARD_RING: &[u8] = b"sys:shard_ring";
+
+/// Layout version `open` reads and writes.
+///
+/// 1 — accounts, height, undo journal, shard heights and ring, archive and
+///     cross-shard event log (stores written before versioning)
+/// 2 — adds the `xopen:` index of undecided cross-shard transfers
+pub const STATE_SCHEMA_VERSION: u32 = 2;
+
+const STATE_SCHEMA: Schema = Schema {
+    store:       "state",
+    cf:          None,
+    current:     STATE_SCHEMA_VERSION,
+    unversioned: 1,
+    migrations:  &[Migration {
+        from:        1,
+        to:          2,
+        description: "index undecided cross-shard transfers (xopen:)",
+        apply:       xshard_trace::index_open_transfers,
+    }],
+};
+
+/// Number of past heights whose account pre-images are retained for
+/// historical reads. Older heights report `StateError::Pruned`.
+pub const DEFAULT_HISTORY_RETENTION: u64 = 256;
+
+/// Blocks a replaced signing key keeps working after a key change.
+pub const DEFAULT_KEY_GRACE_BLOCKS: u64 = 64;
+
+/// Signing keys an account registered through key-change transactions.
+/// Accounts without one accept any valid signer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct AccountAuth {
+    /// Hash of the key authorized to sign.
+    pub key_hash:          [u8; 32],
+    /// Key replaced by the last change, still accepted below `grace_until`.
+    pub previous_key_hash: Option<[u8; 32]>,
+    pub grace_until:       u64,
+    /// Key that must co-sign every further change.
+    pub recovery_key_hash: Option<[u8; 32]>,
+}
+
+impl AccountAuth {
+    /// Whether the key hashing to `key_hash` may sign at `height`.
+    pub fn authorizes(&self, key_hash: &[u8; 32], height: u64) -> bool {
+        *key_hash == self.key_hash
+            || (self.previous_key_hash.as_ref() == Some(key_hash) && height < self.grace_until)
+    }
+}
 
 /// Persisted account record.
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
@@ -34,6 +118,11 @@ pub struct AccountState {
     pub balance:   u128,
     pub nonce:     u64,
     pub code_hash: Option<[u8; 32]>,
+    /// Contract storage, hex key → hex value.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage:   BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth:      Option<AccountAuth>,
 }
 
 impl AccountState {
@@ -43,8 +132,15 @@ impl AccountState {
             balance: 1_000_000_000, // 10 BLEEP in microBLEEP
             nonce: 0,
             code_hash: None,
+            ..Default::default()
         }
     }
+
+    /// No balance, nonce, code, storage or keys — indistinguishable from absent.
+    pub fn is_empty(&self) -> bool {
+        self.balance == 0 && self.nonce == 0 && self.code_hash.is_none() && self.storage.is_empty()
+            && self.auth.is_none()
+    }
 }
 
 // ── In-memory write-back cache entry ─────────────────────────────────────────
@@ -62,8 +158,20 @@ pub struct StateManager {
     db:           rocksdb::DB,
     cache:        HashMap<String, CacheEntry>,
     block_height: u64,
-    /// Sprint 3: Sparse Merkle Trie for O(1)-amortised cryptographic state root.
-    trie:         SparseMerkleTrie,
+    /// Sprint 3: Sparse Merkle Trie for O(1)-amortised cryptographic state root,
+    /// one per shard under the global root.
+    shards:       ShardedState,
+    /// Pre-images of accounts touched while building the current block.
+    pending_preimages: HashMap<String, AccountState>,
+    /// `(height, pre-images)` — account values as they were at `height`,
+    /// captured before the block that advanced past `height` mutated them.
+    journal:      VecDeque<(u64, HashMap<String, AccountState>)>,
+    history_retention: u64,
+    /// Write every committed root to the state archive.
+    archive:      bool,
+    key_grace_blocks: u64,
+    xshard_slo:   Duration,
+    xshard_telemetry: Option<XShardTelemetry>,
 }
 
 impl StateManager {
@@ -78,6 +186,10 @@ impl StateManager {
 
         let db = rocksdb::DB::open(&opts, path)
             .map_err(|e| StateError::Storage(e.to_string()))?;
+        let migrated = migrations::migrate(&db, &STATE_SCHEMA, false)?;
+        if !migrated.is_noop() {
+            log::info!("[StateManager] {}", migrated.render());
+        }
 
         let block_height = match db.get(KEY_HEIGHT) {
             Ok(Some(v)) => {
@@ -89,13 +201,43 @@ impl StateManager {
             Err(e) => return Err(StateError::Storage(e.to_string())),
         };
 
+        let journal = load_journal(&db)?;
+        let ring = match db.get(KEY_SHARD_RING) {
+            Ok(Some(v)) => serde_json::from_slice(&v)
+                .map_err(|e| StateError::Serialisation(e.to_string()))?,
+            Ok(None) => ShardRing::default(),
+            Err(e) => return Err(StateError::Storage(e.to_string())),
+        };
+
         log::info!("[StateManager] Opened DB — block_height={}", block_height);
-        Ok(Self {
+        let mut mgr = Self {
             db,
             cache: HashMap::new(),
             block_height,
-            trie: SparseMerkleTrie::new(),
-        })
+            shards: ShardedState::new(ring),
+            pending_preimages: HashMap::new(),
+            journal,
+            history_retention: DEFAULT_HISTORY_RETENTION,
+            archive: false,
+            key_grace_blocks: DEFAULT_KEY_GRACE_BLOCKS,
+            xshard_slo: DEFAULT_XSHARD_SLO,
+            xshard_telemetry: None,
+        };
+        mgr.prune_journal();
+        Ok(mgr)
+    }
+
+    /// Migrations `open` would run on the store at `path`; with `dry_run`
+    /// the store is opened read-only and left untouched.
+    pub fn migrate_store<P: AsRef<Path>>(path: P, dry_run: bool) -> StateResult<MigrationReport> {
+        let opts = rocksdb::Options::default();
+        let db = if dry_run {
+            rocksdb::DB::open_for_read_only(&opts, path, false)
+        } else {
+            rocksdb::DB::open(&opts, path)
+        }
+        .map_err(|e| StateError::Storage(e.to_string()))?;
+        Ok(migrations::migrate(&db, &STATE_SCHEMA, dry_run)?)
     }
 
     /// In-memory (temp dir). Panics only if the OS temp dir is unusable.
@@ -130,12 +272,71 @@ impl StateManager {
         self.get_account(address).nonce
     }
 
+    /// Write one contract storage slot; an empty `value` clears it.
+    pub fn set_storage(&mut self, contract: &str, key: &[u8], value: &[u8]) {
+        let e = self.cache_entry(contract);
+        if value.is_empty() {
+            e.state.storage.remove(&hex::encode(key));
+        } else {
+            e.state.storage.insert(hex::encode(key), hex::encode(value));
+        }
+        e.dirty = true;
+    }
+
+    pub fn get_storage(&self, contract: &str, key: &[u8]) -> Option<Vec<u8>> {
+        self.get_account(contract).storage.get(&hex::encode(key))
+            .and_then(|v| hex::decode(v).ok())
+    }
+
     pub fn set_code_hash(&mut self, address: &str, hash: [u8; 32]) {
         let e = self.cache_entry(address);
         e.state.code_hash = Some(hash);
         e.dirty = true;
     }
 
+    // ── Signing keys ─────────────────────────────────────────────────────────
+
+    /// Keys registered for `address`, `None` before its first key change.
+    pub fn account_auth(&self, address: &str) -> Option<AccountAuth> {
+        self.get_account(address).auth
+    }
+
+    /// Whether the key hashing to `key_hash` may sign for `address` now.
+    pub fn key_authorized(&self, address: &str, key_hash: &[u8; 32]) -> bool {
+        match self.account_auth(address) {
+            Some(auth) => auth.authorizes(key_hash, self.block_height),
+            None => true,
+        }
+    }
+
+    /// Make `new_key_hash` the key of `address`.  The key it replaces — or,
+    /// on a first change, the `signer_key_hash` that signed it — keeps
+    /// working for the grace period.  `recovery_key_hash` replaces any
+    /// registered recovery key.
+    pub fn rotate_key(
+        &mut self,
+        address: &str,
+        signer_key_hash: [u8; 32],
+        new_key_hash: [u8; 32],
+        recovery_key_hash: Option<[u8; 32]>,
+    ) {
+        let grace_until = self.block_height + self.key_grace_blocks;
+        let e = self.cache_entry(address);
+        let previous = e.state.auth.take();
+        e.state.auth = Some(AccountAuth {
+            key_hash: new_key_hash,
+            previous_key_hash: Some(previous.as_ref().map_or(signer_key_hash, |a| a.key_hash)),
+            grace_until,
+            recovery_key_hash: recovery_key_hash.or(previous.and_then(|a| a.recovery_key_hash)),
+        });
+        e.dirty = true;
+    }
+
+    /// Blocks a replaced key keeps working; applies to later changes.
+    pub fn set_key_grace_blocks(&mut self, blocks: u64) {
+        self.key_grace_blocks = blocks;
+    }
+
     // ── Block lifecycle ──────────────────────────────────────────────────────
 
     pub fn block_height(&self) -> u64 { self.block_height }
@@ -143,21 +344,47 @@ impl StateManager {
     /// Advance block counter, sync dirty accounts into trie, flush to RocksDB.
     pub fn advance_block(&mut self) {
         self.sync_trie();
+        let touched: Vec<String> = if self.archive {
+            self.pending_preimages.keys().cloned().collect()
+        } else {
+            Vec::new()
+        };
+        self.record_journal_entry();
         self.block_height += 1;
-        if let Err(e) = self.flush_internal() {
+        let mut batch = rocksdb::WriteBatch::default();
+        if self.archive {
+            let accounts: Vec<(String, AccountState)> = touched.into_iter()
+                .map(|a| { let s = self.get_account(&a); (a, s) })
+                .collect();
+            let root = self.shards.global_root();
+            let staged = state_archive::record(
+                &mut batch, self.block_height, root,
+                accounts.iter().map(|(a, s)| (a.as_str(), s)),
+            );
+            if let Err(e) = staged {
+                log::error!("[StateManager] archive write failed at height {}: {}", self.block_height, e);
+            }
+        }
+        if let Err(e) = self.flush_batch(batch) {
             log::error!("[StateManager] flush failed on advance_block: {}", e);
         }
     }
 
     // ── State root (Sparse Merkle Trie) ───────────────────────────────────────
 
-    /// Compute the Sparse Merkle Trie state root.
+    /// Compute the global state root over every shard's trie root.
     ///
-    /// All dirty cache entries are synced into the trie first.
+    /// All dirty cache entries are synced into the tries first.
     /// Returns a 32-byte cryptographic commitment to the full account state.
     pub fn state_root(&mut self) -> [u8; 32] {
         self.sync_trie();
-        self.trie.root()
+        self.shards.global_root()
+    }
+
+    /// Trie root of every shard, by shard id.
+    pub fn shard_roots(&mut self) -> BTreeMap<u64, [u8; 32]> {
+        self.sync_trie();
+        self.shards.shard_roots()
     }
 
     /// Sync dirty cache entries into the Sparse Merkle Trie.
@@ -165,9 +392,9 @@ impl StateManager {
         for (addr, entry) in &self.cache {
             if entry.dirty {
                 if entry.state.balance == 0 && entry.state.nonce == 0 {
-                    self.trie.remove(addr);
+                    self.shards.remove(addr);
                 } else {
-                    self.trie.insert(addr, entry.state.balance, entry.state.nonce);
+                    self.shards.insert(addr, entry.state.balance, entry.state.nonce);
                 }
             }
         }
@@ -316,6 +543,342 @@ impl StateManager {
         balances
     }
 
+    /// Export non-zero account nonces, as `export_balances` does balances.
+    pub fn export_nonces(&self) -> HashMap<String, u64> {
+        let mut nonces = HashMap::new();
+        let prefix = PREFIX_ACCOUNT;
+
+        for item in self.db.prefix_iterator(prefix) {
+            let Ok((k, v)) = item else { continue };
+            if !k.starts_with(prefix) {
+                break;
+            }
+            if let (Ok(addr), Ok(acct)) = (std::str::from_utf8(&k[prefix.len()..]), serde_json::from_slice::<AccountState>(&v)) {
+                if acct.nonce > 0 {
+                    nonces.insert(addr.to_string(), acct.nonce);
+                }
+            }
+        }
+
+        for (addr, entry) in &self.cache {
+            if entry.state.nonce > 0 {
+                nonces.insert(addr.clone(), entry.state.nonce);
+            }
+        }
+
+        nonces
+    }
+
+    // ── Historical reads ──────────────────────────────────────────────────────
+
+    /// Current account record (balance, nonce, code hash).
+    pub fn get_account_state(&self, address: &str) -> AccountState {
+        self.get_account(address)
+    }
+
+    /// Account record as it was when the chain was at `height`.
+    ///
+    /// `height == block_height()` returns the live (possibly uncommitted)
+    /// state.  Heights older than the retention window are served from the
+    /// archive when it covers them and otherwise return `StateError::Pruned`.
+    pub fn account_at(&self, address: &str, height: u64) -> StateResult<AccountState> {
+        if height > self.block_height {
+            return Err(StateError::FutureHeight {
+                requested: height,
+                current:   self.block_height,
+            });
+        }
+        if height == self.block_height {
+            return Ok(self.get_account(address));
+        }
+        if height < self.earliest_queryable_height() {
+            return match self.earliest_archived_height()? {
+                Some(base) if base <= height => Ok(
+                    state_archive::account_at(&self.db, address, height)?.unwrap_or_default()
+                ),
+                _ => Err(StateError::Pruned(height)),
+            };
+        }
+        // The first journal entry at or after `height` that touched the
+        // account holds its value at `height`; untouched accounts are unchanged.
+        for (h, preimages) in &self.journal {
+            if *h < height { continue; }
+            if let Some(acct) = preimages.get(address) {
+                return Ok(acct.clone());
+            }
+        }
+        // Touched only in the block currently being built.
+        if let Some(acct) = self.pending_preimages.get(address) {
+            return Ok(acct.clone());
+        }
+        Ok(self.get_account(address))
+    }
+
+    /// Lowest height that `account_at` can still answer.
+    pub fn earliest_queryable_height(&self) -> u64 {
+        self.journal.front().map(|(h, _)| *h).unwrap_or(self.block_height)
+    }
+
+    /// Change how many past heights are kept for historical reads.
+    pub fn set_history_retention(&mut self, blocks: u64) {
+        self.history_retention = blocks;
+        self.prune_journal();
+    }
+
+    // ── Archive ───────────────────────────────────────────────────────────────
+
+    /// Start archiving every committed root.
+    ///
+    /// If the archive on disk does not end at the current height (it was
+    /// never enabled, or blocks were committed while it was off) it is
+    /// rebuilt from a baseline of every account at the current height;
+    /// earlier heights are then only readable within the journal window.
+    pub fn enable_archive(&mut self) -> StateResult<()> {
+        self.archive = true;
+        let tip = state_archive::tip_height(&self.db)?;
+        if tip == Some(self.block_height) && state_archive::base_height(&self.db)?.is_some() {
+            return Ok(());
+        }
+        let mut batch = rocksdb::WriteBatch::default();
+        state_archive::clear(&self.db, &mut batch)?;
+        self.db.write(batch)
+            .map_err(|e| StateError::Storage(e.to_string()))?;
+
+        // Baseline: committed values only, uncommitted writes land with the
+        // next block.
+        let mut accounts = Vec::new();
+        for addr in self.known_addresses()? {
+            let committed = self.pending_preimages.get(&addr).cloned()
+                .unwrap_or_else(|| self.get_account(&addr));
+            if !committed.is_empty() {
+                accounts.push((addr, committed));
+            }
+        }
+        let mut shards = ShardedState::new(self.shards.ring().clone());
+        for (addr, s) in &accounts {
+            if s.balance != 0 || s.nonce != 0 {
+                shards.insert(addr, s.balance, s.nonce);
+            }
+        }
+        let mut batch = rocksdb::WriteBatch::default();
+        state_archive::record(
+            &mut batch, self.block_height, shards.global_root(),
+            accounts.iter().map(|(a, s)| (a.as_str(), s)),
+        )?;
+        state_archive::set_base(&mut batch, self.block_height);
+        self.db.write(batch)
+            .map_err(|e| StateError::Storage(e.to_string()))?;
+        log::info!("[StateManager] Archive enabled from height {} ({} accounts)", self.block_height, accounts.len());
+        Ok(())
+    }
+
+    pub fn is_archive(&self) -> bool { self.archive }
+
+    /// First height the archive can answer, when archive mode is on.
+    pub fn earliest_archived_height(&self) -> StateResult<Option<u64>> {
+        if !self.archive {
+            return Ok(None);
+        }
+        state_archive::base_height(&self.db)
+    }
+
+    /// State root committed at `height`, if archived.
+    pub fn archived_root_at(&self, height: u64) -> StateResult<Option<[u8; 32]>> {
+        if !self.archive {
+            return Ok(None);
+        }
+        state_archive::root_at(&self.db, height)
+    }
+
+    /// Height at which `root` was committed, if archived.
+    pub fn archived_height_of(&self, root: &[u8; 32]) -> StateResult<Option<u64>> {
+        if !self.archive {
+            return Ok(None);
+        }
+        state_archive::root_height(&self.db, root)
+    }
+
+    /// Drop archived roots not in `retained` (the newest root is always
+    /// kept) and every version and node only they referenced.
+    pub fn archive_gc(&mut self, retained: &[[u8; 32]]) -> StateResult<ArchiveGcStats> {
+        if !self.archive {
+            return Ok(ArchiveGcStats::default());
+        }
+        let stats = state_archive::gc(&self.db, retained)?;
+        log::info!("[StateManager] Archive GC: {:?}", stats);
+        Ok(stats)
+    }
+
+    fn record_journal_entry(&mut self) {
+        let preimages = std::mem::take(&mut self.pending_preimages);
+        self.journal.push_back((self.block_height, preimages));
+        self.prune_journal();
+    }
+
+    fn prune_journal(&mut self) {
+        while self.journal.len() as u64 > self.history_retention {
+            if let Some((h, _)) = self.journal.pop_front() {
+                if let Err(e) = self.db.delete(undo_key(h)) {
+                    log::warn!("[StateManager] failed to prune undo record {}: {}", h, e);
+                }
+            }
+        }
+    }
+
+    /// Materialise the full state as of `height` into a new database at
+    /// `path`.
+    ///
+    /// Every account is copied with its value at `height` (see
+    /// `account_at`), the trie is rebuilt and the fork's height is set to
+    /// `height`, so executing block `height + 1` on the fork reproduces what
+    /// the chain did.  `self` is not modified.
+    pub fn fork_at<P: AsRef<Path>>(&self, height: u64, path: P) -> StateResult<StateManager> {
+        if height != self.block_height {
+            self.account_at("", height)?;
+        }
+
+        let mut fork = StateManager::open(path)?;
+        fork.rebalance_shards(self.shards.ring().clone())?;
+        for addr in self.known_addresses()? {
+            let state = self.account_at(&addr, height)?;
+            if state.is_empty() {
+                continue;
+            }
+            fork.cache.insert(addr, CacheEntry { state, dirty: true });
+        }
+        fork.block_height = height;
+        fork.sync_trie();
+        fork.flush_internal()?;
+        Ok(fork)
+    }
+
+    /// Every non-empty account with its value at `height`, ordered by
+    /// address — the state a snap-sync server chunks up.
+    pub fn export_accounts(&self, height: u64) -> StateResult<Vec<(String, AccountState)>> {
+        if height != self.block_height {
+            self.account_at("", height)?;
+        }
+        let mut accounts = Vec::new();
+        for addr in self.known_addresses()? {
+            let state = self.account_at(&addr, height)?;
+            if !state.is_empty() {
+                accounts.push((addr, state));
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Replace the whole state with `accounts` at `height` under `ring`, as
+    /// a snap-synced node adopts a checkpoint.  Everything held before,
+    /// history included, is dropped; the caller checks the root first.
+    pub fn import_accounts(
+        &mut self,
+        ring: ShardRing,
+        height: u64,
+        accounts: impl IntoIterator<Item = (String, AccountState)>,
+    ) -> StateResult<()> {
+        let mut batch = rocksdb::WriteBatch::default();
+        for addr in self.known_addresses()? {
+            batch.delete(account_key(&addr));
+        }
+        for (h, _) in self.journal.drain(..) {
+            batch.delete(undo_key(h));
+        }
+        self.db.write(batch)
+            .map_err(|e| StateError::Storage(e.to_string()))?;
+        self.cache.clear();
+        self.pending_preimages.clear();
+        self.shards = ShardedState::new(ShardRing::default());
+        self.rebalance_shards(ring)?;
+
+        for (addr, state) in accounts {
+            if !state.is_empty() {
+                self.cache.insert(addr, CacheEntry { state, dirty: true });
+            }
+        }
+        log::info!("[StateManager] Imported {} accounts at height {}", self.cache.len(), height);
+        self.block_height = height;
+        self.sync_trie();
+        self.flush_internal()
+    }
+
+    /// Unwind the state in place to `height`.
+    ///
+    /// Journal pre-images are applied newest first, so every account gets
+    /// back the value it had at `height`; the undone undo records are
+    /// deleted and uncommitted writes are discarded.  Heights older than
+    /// `earliest_queryable_height` cannot be reached.  Every cross-shard leg
+    /// unwound gets a `rollback` event in its transfer's timeline.
+    pub fn rollback_to(&mut self, height: u64) -> StateResult<()> {
+        if height > self.block_height {
+            return Err(StateError::FutureHeight { requested: height, current: self.block_height });
+        }
+        if height < self.earliest_queryable_height() {
+            return Err(StateError::Pruned(height));
+        }
+
+        // Discard the block being built before unwinding sealed ones.
+        let mut unwound = Vec::new();
+        for (addr, state) in std::mem::take(&mut self.pending_preimages) {
+            if let Some((shard, leg, id)) = marker_parts(&addr) {
+                if state.nonce == 0 {
+                    unwound.push((id.to_string(), shard, format!("uncommitted {} discarded", leg)));
+                }
+            }
+            self.cache.insert(addr, CacheEntry { state, dirty: true });
+        }
+        let mut batch = rocksdb::WriteBatch::default();
+        while let Some((h, _)) = self.journal.back() {
+            if *h < height { break; }
+            let (h, preimages) = self.journal.pop_back().expect("non-empty journal");
+            if self.archive {
+                state_archive::unrecord(&self.db, &mut batch, h + 1, preimages.keys().map(String::as_str))?;
+            }
+            for (addr, state) in preimages {
+                if let Some((shard, leg, id)) = marker_parts(&addr) {
+                    if state.nonce == 0 {
+                        unwound.push((id.to_string(), shard, format!("{} undone at height {}", leg, h + 1)));
+                    }
+                }
+                self.cache.insert(addr, CacheEntry { state, dirty: true });
+            }
+            batch.delete(undo_key(h));
+        }
+        self.db.write(batch)
+            .map_err(|e| StateError::Storage(e.to_string()))?;
+
+        log::warn!("[StateManager] Rolled back from height {} to {}", self.block_height, height);
+        self.block_height = height;
+        for (id, shard, detail) in unwound {
+            self.record_xshard(&id, shard, XShardPhase::Rollback, Some(detail))?;
+        }
+        self.sync_trie();
+        self.flush_internal()
+    }
+
+    // ── Shard heights ─────────────────────────────────────────────────────────
+
+    /// Record that `shard_id` has applied blocks up to global `height`.
+    pub fn set_shard_height(&mut self, shard_id: u64, height: u64) -> StateResult<()> {
+        self.db.put(shard_key(shard_id), height.to_le_bytes())
+            .map_err(|e| StateError::Storage(e.to_string()))
+    }
+
+    /// Last recorded global height per shard.
+    pub fn shard_heights(&self) -> StateResult<BTreeMap<u64, u64>> {
+        let mut heights = BTreeMap::new();
+        for item in self.db.prefix_iterator(PREFIX_SHARD) {
+            let (k, v) = item.map_err(|e| StateError::Storage(e.to_string()))?;
+            if !k.starts_with(PREFIX_SHARD) { break; }
+            let id: [u8; 8] = k[PREFIX_SHARD.len()..].try_into()
+                .map_err(|_| StateError::Storage("corrupt shard key".into()))?;
+            let h: [u8; 8] = v.as_slice().try_into()
+                .map_err(|_| StateError::Storage("corrupt shard height".into()))?;
+            heights.insert(u64::from_be_bytes(id), u64::from_le_bytes(h));
+        }
+        Ok(heights)
+    }
+
     // ── Trie query helpers ────────────────────────────────────────────────────
 
     /// Load all accounts from RocksDB into the trie (called at startup if needed).
@@ -331,27 +894,234 @@ impl StateManager {
             let acct: AccountState = serde_json::from_slice(&v)
                 .unwrap_or_default();
             if acct.balance > 0 || acct.nonce > 0 {
-                self.trie.insert(&addr, acct.balance, acct.nonce);
+                self.shards.insert(&addr, acct.balance, acct.nonce);
             }
         }
-        log::info!("[StateManager] Trie rebuilt from DB ({} accounts)", self.trie.len());
+        log::info!("[StateManager] Trie rebuilt from DB ({} accounts)", self.shards.len());
         Ok(())
     }
 
     // ── Merkle proof API (Sprint 5) ───────────────────────────────────────────
 
-    /// Generate a Sparse Merkle Trie proof for `address`.
+    /// Generate a proof for `address`: its Sparse Merkle Trie proof within
+    /// the owning shard plus that shard root's proof within the global root.
     ///
-    /// Syncs dirty cache entries into the trie first so the proof is always
+    /// Syncs dirty cache entries into the tries first so the proof is always
     /// up-to-date with the latest in-memory writes. Light clients can verify
-    /// the returned `MerkleProof` against the published state root.
-    pub fn prove_account(&mut self, address: &str) -> crate::state_merkle::MerkleProof {
+    /// the returned `ComposedProof` against the published state root.
+    pub fn prove_account(&mut self, address: &str) -> ComposedProof {
         self.sync_trie();
-        self.trie.prove(address)
+        self.shards.prove(address)
+    }
+
+    // ── Shards ────────────────────────────────────────────────────────────────
+
+    pub fn shard_ring(&self) -> &ShardRing {
+        self.shards.ring()
+    }
+
+    /// Shard that owns `address` under the current ring.
+    pub fn shard_of(&self, address: &str) -> u64 {
+        self.shards.shard_of(address)
+    }
+
+    /// Move accounts onto the shards of `ring`.
+    ///
+    /// The per-shard tries are rebuilt aside and swapped in together, and
+    /// the ring is persisted, so the next `state_root` commits to the new
+    /// layout as a whole.
+    pub fn rebalance_shards(&mut self, ring: ShardRing) -> StateResult<()> {
+        self.sync_trie();
+        let encoded = serde_json::to_vec(&ring)
+            .map_err(|e| StateError::Serialisation(e.to_string()))?;
+        self.shards.rebalance(ring).map_err(StateError::CrossShard)?;
+        self.db.put(KEY_SHARD_RING, encoded)
+            .map_err(|e| StateError::Storage(e.to_string()))?;
+        log::info!("[StateManager] Rebalanced onto {} shards", self.shards.ring().shard_ids().len());
+        Ok(())
+    }
+
+    /// Debit `sender` on its shard for a credit to `recipient` on
+    /// `dest_shard`, recording the debit marker in the source shard's trie.
+    ///
+    /// Once the block is committed, `prove_cross_shard_debit` produces the
+    /// credit the destination applies.
+    pub fn cross_shard_debit(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: u128,
+        dest_shard: u64,
+    ) -> StateResult<CrossShardDebit> {
+        let source_shard = self.shards.shard_of(sender);
+        if source_shard == dest_shard {
+            return Err(StateError::CrossShard(format!("{} is already on shard {}", sender, dest_shard)));
+        }
+        if amount == 0 {
+            return Err(StateError::CrossShard("zero-amount transfer".into()));
+        }
+        let balance = self.get_balance(sender);
+        let remaining = balance.checked_sub(amount).ok_or_else(|| StateError::CrossShard(
+            format!("{} has {}, needs {}", sender, balance, amount),
+        ))?;
+        let debit = CrossShardDebit {
+            source_shard,
+            dest_shard,
+            sender:    sender.to_string(),
+            recipient: recipient.to_string(),
+            amount,
+            nonce:     self.get_nonce(sender),
+        };
+        let id = debit.xshard_id();
+        let _span = xshard_trace::span(&id, source_shard, XShardPhase::Debit).entered();
+        self.record_xshard(&id, source_shard, XShardPhase::Lock, None)?;
+        self.set_balance(sender, remaining);
+        self.increment_nonce(sender);
+        self.increment_nonce(&debit.debit_marker());
+        self.record_xshard(&id, source_shard, XShardPhase::Debit, None)?;
+        Ok(debit)
+    }
+
+    /// Credit for `debit`, carrying the proof of its marker under the
+    /// current state root.
+    pub fn prove_cross_shard_debit(&mut self, debit: &CrossShardDebit) -> CrossShardCredit {
+        CrossShardCredit { debit: debit.clone(), proof: self.prove_account(&debit.debit_marker()) }
+    }
+
+    /// Apply a credit from another shard.
+    ///
+    /// `source_root` is the header state root of the block that committed
+    /// the debit.  The credit is rejected unless its proof verifies against
+    /// that root, the recipient belongs to the credit's destination shard,
+    /// and the debit has not been credited before.
+    pub fn apply_cross_shard_credit(
+        &mut self,
+        credit: &CrossShardCredit,
+        source_root: &[u8; 32],
+    ) -> StateResult<()> {
+        let debit = &credit.debit;
+        let _span = xshard_trace::span(&debit.xshard_id(), debit.dest_shard, XShardPhase::Credit).entered();
+        credit.verify(source_root).map_err(StateError::CrossShard)?;
+        if self.shards.shard_of(&debit.recipient) != debit.dest_shard {
+            return Err(StateError::CrossShard(format!(
+                "{} is not on shard {}", debit.recipient, debit.dest_shard,
+            )));
+        }
+        let marker = debit.credit_marker();
+        if self.get_nonce(&marker) != 0 {
+            return Err(StateError::CrossShard("debit already credited".into()));
+        }
+        let balance = self.get_balance(&debit.recipient);
+        let credited = balance.checked_add(debit.amount).ok_or_else(|| StateError::CrossShard(
+            format!("{} balance overflow", debit.recipient),
+        ))?;
+        self.set_balance(&debit.recipient, credited);
+        self.increment_nonce(&marker);
+        self.record_xshard(&debit.xshard_id(), debit.dest_shard, XShardPhase::Credit, None)?;
+        Ok(())
+    }
+
+    /// Coordinator's commit of `debit`, once the destination has applied
+    /// its credit.
+    pub fn commit_cross_shard(&mut self, debit: &CrossShardDebit) -> StateResult<XShardTimeline> {
+        let id = debit.xshard_id();
+        let _span = xshard_trace::span(&id, debit.source_shard, XShardPhase::Commit).entered();
+        self.decide_cross_shard(debit)?;
+        self.record_xshard(&id, debit.source_shard, XShardPhase::Commit, None)?;
+        self.finish_xshard(&id)
+    }
+
+    /// Coordinator's abort of `debit`, typically because the destination
+    /// rejected the credit: the sender is refunded on the source shard.
+    pub fn abort_cross_shard(&mut self, debit: &CrossShardDebit, reason: &str) -> StateResult<XShardTimeline> {
+        let id = debit.xshard_id();
+        let _span = xshard_trace::span(&id, debit.source_shard, XShardPhase::Abort).entered();
+        self.decide_cross_shard(debit)?;
+        self.record_xshard(&id, debit.source_shard, XShardPhase::Abort, Some(reason.to_string()))?;
+        let refunded = self.get_balance(&debit.sender).checked_add(debit.amount).ok_or_else(|| {
+            StateError::CrossShard(format!("{} balance overflow", debit.sender))
+        })?;
+        self.set_balance(&debit.sender, refunded);
+        self.record_xshard(
+            &id,
+            debit.source_shard,
+            XShardPhase::Rollback,
+            Some(format!("refunded {} to {}", debit.amount, debit.sender)),
+        )?;
+        self.finish_xshard(&id)
+    }
+
+    /// Everything recorded for `xshard_id`, in order.
+    pub fn xshard_timeline(&self, xshard_id: &str) -> StateResult<Option<XShardTimeline>> {
+        xshard_trace::timeline(&self.db, xshard_id, self.xshard_slo)
+    }
+
+    /// Transfers with no commit or abort recorded yet, by `xshard_id`.
+    pub fn open_xshard_transfers(&self) -> StateResult<Vec<String>> {
+        xshard_trace::open_transfers(&self.db)
+    }
+
+    /// End-to-end latency beyond which a transfer is flagged.
+    pub fn set_xshard_slo(&mut self, slo: Duration) {
+        self.xshard_slo = slo;
+    }
+
+    /// Report decided transfers to `telemetry`.
+    pub fn set_xshard_telemetry(&mut self, telemetry: XShardTelemetry) {
+        self.xshard_telemetry = Some(telemetry);
     }
 
     // ── Internal helpers ─────────────────────────────────────────────────────
 
+    fn record_xshard(
+        &self,
+        xshard_id: &str,
+        shard: u64,
+        phase: XShardPhase,
+        detail: Option<String>,
+    ) -> StateResult<()> {
+        xshard_trace::record(&self.db, xshard_id, shard, self.block_height, phase, detail).map(|_| ())
+    }
+
+    /// Check that `debit` happened here and is undecided, then mark it decided.
+    fn decide_cross_shard(&mut self, debit: &CrossShardDebit) -> StateResult<()> {
+        if self.get_nonce(&debit.debit_marker()) == 0 {
+            return Err(StateError::CrossShard(format!("no debit {} on this shard", debit.xshard_id())));
+        }
+        let marker = debit.decision_marker();
+        if self.get_nonce(&marker) != 0 {
+            return Err(StateError::CrossShard("transfer already decided".into()));
+        }
+        self.increment_nonce(&marker);
+        Ok(())
+    }
+
+    fn finish_xshard(&self, xshard_id: &str) -> StateResult<XShardTimeline> {
+        let timeline = self.xshard_timeline(xshard_id)?
+            .ok_or_else(|| StateError::CrossShard(format!("no timeline for {}", xshard_id)))?;
+        if let Some(telemetry) = &self.xshard_telemetry {
+            telemetry.observe(&timeline);
+        }
+        Ok(timeline)
+    }
+
+    /// Every address with a cached, journaled or persisted record.
+    fn known_addresses(&self) -> StateResult<BTreeSet<String>> {
+        let mut addresses: BTreeSet<String> = self.cache.keys().cloned().collect();
+        addresses.extend(self.pending_preimages.keys().cloned());
+        for (_, preimages) in &self.journal {
+            addresses.extend(preimages.keys().cloned());
+        }
+        for item in self.db.prefix_iterator(PREFIX_ACCOUNT) {
+            let (k, _) = item.map_err(|e| StateError::Storage(e.to_string()))?;
+            if !k.starts_with(PREFIX_ACCOUNT) { break; }
+            let addr = std::str::from_utf8(&k[PREFIX_ACCOUNT.len()..])
+                .map_err(|e| StateError::Serialisation(e.to_string()))?;
+            addresses.insert(addr.to_string());
+        }
+        Ok(addresses)
+    }
+
     fn get_account(&self, address: &str) -> AccountState {
         if let Some(e) = self.cache.get(address) {
             return e.state.clone();
@@ -368,11 +1138,22 @@ impl StateManager {
             let state = self.get_account(address);
             self.cache.insert(address.to_string(), CacheEntry { state, dirty: false });
         }
+        // Only mutating paths come through here, so this is the pre-image
+        // of the first write to `address` in the current block.
+        if !self.pending_preimages.contains_key(address) {
+            let before = self.cache[address].state.clone();
+            self.pending_preimages.insert(address.to_string(), before);
+        }
         self.cache.get_mut(address).unwrap()
     }
 
     fn flush_internal(&self) -> StateResult<()> {
-        let mut batch = rocksdb::WriteBatch::default();
+        self.flush_batch(rocksdb::WriteBatch::default())
+    }
+
+    /// Flush dirty accounts, the height and the latest undo record together
+    /// with whatever the caller already staged in `batch`.
+    fn flush_batch(&self, mut batch: rocksdb::WriteBatch) -> StateResult<()> {
         let mut flushed = 0usize;
 
         for (addr, entry) in &self.cache {
@@ -387,6 +1168,13 @@ impl StateManager {
 
         batch.put(KEY_HEIGHT, self.block_height.to_le_bytes());
 
+        // Undo record for the block just sealed by `advance_block`.
+        if let Some((h, preimages)) = self.journal.back() {
+            let val = serde_json::to_vec(preimages)
+                .map_err(|e| StateError::Serialisation(e.to_string()))?;
+            batch.put(undo_key(*h), val);
+        }
+
         self.db.write(batch)
             .map_err(|e| StateError::Storage(e.to_string()))?;
 
@@ -403,6 +1191,29 @@ fn account_key(address: &str) -> Vec<u8> {
     [PREFIX_ACCOUNT, address.as_bytes()].concat()
 }
 
+fn undo_key(height: u64) -> Vec<u8> {
+    [PREFIX_UNDO, &height.to_be_bytes()[..]].concat()
+}
+
+fn shard_key(shard_id: u64) -> Vec<u8> {
+    [PREFIX_SHARD, &shard_id.to_be_bytes()[..]].concat()
+}
+
+/// Load persisted undo records in height order (keys are big-endian).
+fn load_journal(db: &rocksdb::DB) -> StateResult<VecDeque<(u64, HashMap<String, AccountState>)>> {
+    let mut journal = VecDeque::new();
+    for item in db.prefix_iterator(PREFIX_UNDO) {
+        let (k, v) = item.map_err(|e| StateError::Storage(e.to_string()))?;
+        if !k.starts_with(PREFIX_UNDO) { break; }
+        let arr: [u8; 8] = k[PREFIX_UNDO.len()..].try_into()
+            .map_err(|_| StateError::Storage("corrupt undo key".into()))?;
+        let preimages = serde_json::from_slice(&v)
+            .map_err(|e| StateError::Serialisation(e.to_string()))?;
+        journal.push_back((u64::from_be_bytes(arr), preimages));
+    }
+    Ok(journal)
+}
+
 fn pid_suffix() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now()
@@ -529,6 +1340,205 @@ mod tests {
         assert_eq!(m1.state_root(), m2.state_root());
     }
 
+    #[test]
+    fn cross_shard_credit_applies_once_against_source_root() {
+        let ring = ShardRing::uniform(2);
+        let mut src = fresh();
+        let mut dst = fresh();
+        src.rebalance_shards(ring.clone()).expect("ring");
+        dst.rebalance_shards(ring).expect("ring");
+        let sender = (0..).map(|i| format!("s{}", i)).find(|a| src.shard_of(a) == 0).unwrap();
+        let recipient = (0..).map(|i| format!("r{}", i)).find(|a| dst.shard_of(a) == 1).unwrap();
+
+        src.mint(&sender, 1_000).expect("mint");
+        let debit = src.cross_shard_debit(&sender, &recipient, 400, 1).expect("debit");
+        src.advance_block();
+        let root = src.state_root();
+        let credit = src.prove_cross_shard_debit(&debit);
+        assert!(credit.proof.verify(&root));
+
+        let mut forged = credit.clone();
+        forged.debit.amount = 4_000;
+        assert!(matches!(dst.apply_cross_shard_credit(&forged, &root), Err(StateError::CrossShard(_))));
+        assert!(dst.apply_cross_shard_credit(&credit, &[7u8; 32]).is_err());
+
+        dst.apply_cross_shard_credit(&credit, &root).expect("credit");
+        assert_eq!(dst.get_balance(&recipient), 400);
+        assert_eq!(src.get_balance(&sender), 600);
+        assert!(dst.apply_cross_shard_credit(&credit, &root).is_err());
+    }
+
+    /// Two-shard manager with a funded sender on shard 0 and a recipient
+    /// on `recipient_shard`.
+    fn two_shards(m: &mut StateManager, recipient_shard: u64) -> (String, String) {
+        m.rebalance_shards(ShardRing::uniform(2)).expect("ring");
+        let sender = (0..).map(|i| format!("s{}", i)).find(|a| m.shard_of(a) == 0).unwrap();
+        let recipient = (0..).map(|i| format!("r{}", i)).find(|a| m.shard_of(a) == recipient_shard).unwrap();
+        m.mint(&sender, 1_000).expect("mint");
+        (sender, recipient)
+    }
+
+    #[test]
+    fn committed_transfer_has_all_four_phases_under_one_id() {
+        let mut m = fresh();
+        let mut registry = bleep_telemetry::metrics::MetricsRegistry::new();
+        let telemetry = XShardTelemetry::register(&mut registry);
+        m.set_xshard_telemetry(telemetry.clone());
+        let (sender, recipient) = two_shards(&mut m, 1);
+
+        let debit = m.cross_shard_debit(&sender, &recipient, 250, 1).expect("debit");
+        assert_eq!(m.open_xshard_transfers().unwrap(), vec![debit.xshard_id()]);
+        m.advance_block();
+        let root = m.state_root();
+        let credit = m.prove_cross_shard_debit(&debit);
+        m.apply_cross_shard_credit(&credit, &root).expect("credit");
+        let timeline = m.commit_cross_shard(&debit).expect("commit");
+        assert!(m.open_xshard_transfers().unwrap().is_empty());
+
+        use XShardPhase::*;
+        assert_eq!(timeline.xshard_id, debit.xshard_id());
+        assert!(debit.debit_marker().ends_with(&timeline.xshard_id));
+        assert_eq!(timeline.phases(), vec![Lock, Debit, Credit, Commit]);
+        let shards: Vec<u64> = timeline.events.iter().map(|e| e.shard).collect();
+        assert_eq!(shards, vec![0, 0, 1, 0]);
+        assert_eq!(timeline.outcome, Some(Commit));
+        assert!(!timeline.slo_exceeded);
+        assert_eq!(m.xshard_timeline(&debit.xshard_id()).unwrap(), Some(timeline));
+        assert_eq!(telemetry.total().count(), 1);
+        assert!(m.commit_cross_shard(&debit).is_err(), "decided once");
+        assert_eq!(m.xshard_timeline("00ff").unwrap(), None);
+    }
+
+    #[test]
+    fn aborted_transfer_records_reason_and_refund() {
+        let mut m = fresh();
+        // The recipient lives on shard 0, so shard 1 refuses the credit.
+        let (sender, recipient) = two_shards(&mut m, 0);
+        let debit = m.cross_shard_debit(&sender, &recipient, 300, 1).expect("debit");
+        m.advance_block();
+        let root = m.state_root();
+        let credit = m.prove_cross_shard_debit(&debit);
+        let reason = m.apply_cross_shard_credit(&credit, &root).unwrap_err().to_string();
+
+        let timeline = m.abort_cross_shard(&debit, &reason).expect("abort");
+        use XShardPhase::*;
+        assert_eq!(timeline.phases(), vec![Lock, Debit, Abort, Rollback]);
+        assert_eq!(timeline.outcome, Some(Abort));
+        assert!(timeline.abort_reason.as_deref().unwrap().contains("is not on shard 1"));
+        assert_eq!(m.get_balance(&sender), 1_000);
+        assert!(m.commit_cross_shard(&debit).is_err());
+    }
+
+    #[test]
+    fn timeline_survives_restart_between_debit_and_credit() {
+        let dir = std::env::temp_dir().join(format!("bleep-xshard-{}-{}", std::process::id(), pid_suffix()));
+        let (debit, credit, root) = {
+            let mut m = StateManager::open(&dir).expect("open");
+            let (sender, recipient) = two_shards(&mut m, 1);
+            let debit = m.cross_shard_debit(&sender, &recipient, 40, 1).expect("debit");
+            m.advance_block();
+            let root = m.state_root();
+            (debit.clone(), m.prove_cross_shard_debit(&debit), root)
+        };
+
+        let mut m = StateManager::open(&dir).expect("reopen");
+        let mut registry = bleep_telemetry::metrics::MetricsRegistry::new();
+        let telemetry = XShardTelemetry::register(&mut registry);
+        m.set_xshard_telemetry(telemetry.clone());
+        m.set_xshard_slo(Duration::from_millis(1));
+        std::thread::sleep(Duration::from_millis(5));
+        m.apply_cross_shard_credit(&credit, &root).expect("credit");
+        let timeline = m.commit_cross_shard(&debit).expect("commit");
+
+        use XShardPhase::*;
+        assert_eq!(timeline.phases(), vec![Lock, Debit, Credit, Commit]);
+        let seqs: Vec<u32> = timeline.events.iter().map(|e| e.seq).collect();
+        assert_eq!(seqs, vec![0, 1, 2, 3]);
+        assert!(timeline.events.windows(2).all(|w| w[0].at_ms <= w[1].at_ms));
+        assert_eq!(timeline.event(Debit).unwrap().height, 0);
+        assert_eq!(timeline.event(Credit).unwrap().height, 1);
+        assert!(timeline.slo_exceeded);
+        assert_eq!(telemetry.slo_exceeded(), 1);
+    }
+
+    /// A store as written before schema versioning: two accounts at
+    /// height 3, one committed and one undecided cross-shard transfer.
+    fn write_v1_fixture(dir: &Path) {
+        let mut opts = rocksdb::Options::default();
+        opts.create_if_missing(true);
+        let db = rocksdb::DB::open(&opts, dir).expect("fixture");
+        for (addr, balance) in [("alice", 700u128), ("bob", 300)] {
+            let acct = AccountState { balance, nonce: 1, ..Default::default() };
+            db.put(account_key(addr), serde_json::to_vec(&acct).unwrap()).unwrap();
+        }
+        db.put(KEY_HEIGHT, 3u64.to_le_bytes()).unwrap();
+        use XShardPhase::*;
+        for (id, phases) in [("c0ffee", &[Lock, Debit, Credit, Commit][..]), ("beef01", &[Lock, Debit][..])] {
+            for (seq, phase) in phases.iter().enumerate() {
+                let seq = seq as u32;
+                let event = xshard_trace::XShardEvent { seq, phase: *phase, shard: 0, height: 2, at_ms: 1_000 + seq as u64, detail: None };
+                let key = [&b"xtrace:"[..], id.as_bytes(), b":", &seq.to_be_bytes()].concat();
+                db.put(key, serde_json::to_vec(&event).unwrap()).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn v1_store_migrates_and_serves_queries() {
+        let dir = std::env::temp_dir().join(format!("bleep-schema-v1-{}-{}", std::process::id(), pid_suffix()));
+        write_v1_fixture(&dir);
+
+        let plan = StateManager::migrate_store(&dir, true).expect("dry run");
+        assert_eq!((plan.from, plan.to, plan.steps.len()), (Some(1), STATE_SCHEMA_VERSION, 1));
+        {
+            let db = rocksdb::DB::open_for_read_only(&rocksdb::Options::default(), &dir, false).unwrap();
+            assert_eq!(migrations::stored_version(&db, &STATE_SCHEMA).unwrap(), None);
+            assert!(xshard_trace::open_transfers(&db).unwrap().is_empty(), "dry run wrote the index");
+        }
+
+        let m = StateManager::open(&dir).expect("open migrates");
+        assert_eq!(m.block_height(), 3);
+        assert_eq!((m.get_balance("alice"), m.get_balance("bob"), m.get_nonce("bob")), (700, 300, 1));
+        assert_eq!(m.open_xshard_transfers().unwrap(), vec!["beef01".to_string()]);
+        let committed = m.xshard_timeline("c0ffee").unwrap().expect("timeline");
+        assert_eq!(committed.outcome, Some(XShardPhase::Commit));
+        drop(m);
+        assert!(StateManager::migrate_store(&dir, true).unwrap().is_noop());
+    }
+
+    #[test]
+    fn store_from_a_newer_binary_is_refused() {
+        let dir = std::env::temp_dir().join(format!("bleep-schema-future-{}-{}", std::process::id(), pid_suffix()));
+        drop(StateManager::open(&dir).expect("open"));
+        {
+            let db = rocksdb::DB::open_default(&dir).unwrap();
+            db.put(b"schema:version", (STATE_SCHEMA_VERSION + 1).to_be_bytes()).unwrap();
+        }
+        let Err(err) = StateManager::open(&dir) else { panic!("opened a store from a newer binary") };
+        assert!(matches!(err, StateError::Migration(MigrationError::Newer { .. })), "{}", err);
+        assert!(err.to_string().contains("newer than this binary supports"));
+    }
+
+    #[test]
+    fn replaced_key_works_only_during_grace() {
+        let mut m = fresh();
+        m.set_key_grace_blocks(2);
+        let (old, new) = ([1u8; 32], [2u8; 32]);
+        assert!(m.key_authorized("alice", &old));
+        m.rotate_key("alice", old, new, Some([9u8; 32]));
+        assert!(m.key_authorized("alice", &old) && m.key_authorized("alice", &new));
+        m.advance_block();
+        assert!(m.key_authorized("alice", &old));
+        m.advance_block();
+        assert!(!m.key_authorized("alice", &old));
+        assert!(m.key_authorized("alice", &new));
+        assert!(!m.key_authorized("alice", &[3u8; 32]));
+
+        m.rotate_key("alice", new, [3u8; 32], None);
+        let auth = m.account_auth("alice").unwrap();
+        assert_eq!((auth.previous_key_hash, auth.recovery_key_hash), (Some(new), Some([9u8; 32])));
+    }
+
     #[test]
     fn advance_block_persists_height() {
         let mut m = fresh();
@@ -537,6 +1547,111 @@ mod tests {
         assert_eq!(m.block_height(), 1);
     }
 
+    #[test]
+    fn account_at_reads_past_heights() {
+        let mut m = fresh();
+        m.mint("alice", 100).expect("mint");
+        m.advance_block();                      // height 1: alice = 100
+        m.mint("alice", 50).expect("mint");
+        m.advance_block();                      // height 2: alice = 150
+        assert_eq!(m.account_at("alice", 0).unwrap().balance, 0);
+        assert_eq!(m.account_at("alice", 1).unwrap().balance, 100);
+        assert_eq!(m.account_at("alice", 2).unwrap().balance, 150);
+        assert!(matches!(m.account_at("alice", 3), Err(StateError::FutureHeight { .. })));
+    }
+
+    #[test]
+    fn account_at_pruned_height_errors() {
+        let mut m = fresh();
+        m.set_history_retention(2);
+        for _ in 0..5 {
+            m.mint("bob", 1).expect("mint");
+            m.advance_block();
+        }
+        assert_eq!(m.earliest_queryable_height(), 3);
+        assert!(matches!(m.account_at("bob", 1), Err(StateError::Pruned(1))));
+        assert_eq!(m.account_at("bob", 3).unwrap().balance, 3);
+    }
+
+    #[test]
+    fn fork_at_reproduces_past_state() {
+        let mut m = fresh();
+        m.mint("alice", 100).expect("mint");
+        m.advance_block();                      // height 1
+        let root_1 = m.state_root();
+        assert!(m.apply_transfer("alice", "bob", 40));
+        m.advance_block();                      // height 2
+        let root_2 = m.state_root();
+
+        let dir = std::env::temp_dir().join(format!("bleep-fork-{}-{}", std::process::id(), pid_suffix()));
+        let mut fork = m.fork_at(1, &dir).expect("fork");
+        assert_eq!(fork.block_height(), 1);
+        assert_eq!(fork.get_balance("bob"), 0);
+        assert_eq!(fork.state_root(), root_1);
+
+        // Re-executing block 2 on the fork lands on the same root.
+        assert!(fork.apply_transfer("alice", "bob", 40));
+        fork.advance_block();
+        assert_eq!(fork.state_root(), root_2);
+        assert_eq!(m.get_balance("bob"), 40, "source untouched");
+    }
+
+    #[test]
+    fn imported_accounts_reproduce_the_exported_root() {
+        let mut m = fresh();
+        m.mint("alice", 100).expect("mint");
+        m.advance_block();                      // height 1
+        let root_1 = m.state_root();
+        assert!(m.apply_transfer("alice", "bob", 40));
+        m.advance_block();                      // height 2
+
+        let accounts = m.export_accounts(1).expect("export");
+        assert_eq!(accounts.iter().map(|(a, _)| a.as_str()).collect::<Vec<_>>(), vec!["alice"]);
+
+        let mut synced = fresh();
+        synced.mint("stale", 5).expect("mint");
+        synced.advance_block();
+        synced.import_accounts(m.shard_ring().clone(), 1, accounts).expect("import");
+        assert_eq!(synced.block_height(), 1);
+        assert_eq!(synced.get_balance("stale"), 0, "previous state dropped");
+        assert_eq!(synced.state_root(), root_1);
+    }
+
+    #[test]
+    fn rollback_to_restores_past_state() {
+        let mut m = fresh();
+        m.mint("alice", 100).expect("mint");
+        m.advance_block();                      // height 1
+        let root_1 = m.state_root();
+        assert!(m.apply_transfer("alice", "bob", 40));
+        m.advance_block();                      // height 2
+        m.mint("carol", 7).expect("mint");      // uncommitted
+
+        m.rollback_to(1).expect("rollback");
+        assert_eq!(m.block_height(), 1);
+        assert_eq!(m.get_balance("alice"), 100);
+        assert_eq!(m.get_balance("bob"), 0);
+        assert_eq!(m.get_balance("carol"), 0);
+        assert_eq!(m.state_root(), root_1);
+        assert!(matches!(m.account_at("alice", 2), Err(StateError::FutureHeight { .. })));
+    }
+
+    #[test]
+    fn journal_survives_reopen() {
+        let dir = std::env::temp_dir().join(format!("bleep-journal-{}-{}", std::process::id(), pid_suffix()));
+        {
+            let mut m = StateManager::open(&dir).expect("open");
+            m.mint("alice", 10).expect("mint");
+            m.advance_block();
+            m.mint("alice", 5).expect("mint");
+            m.advance_block();
+        }
+        let m = StateManager::open(&dir).expect("reopen");
+        assert_eq!(m.block_height(), 2);
+        assert_eq!(m.account_at("alice", 1).unwrap().balance, 10);
+        assert_eq!(m.account_at("alice", 0).unwrap().balance, 0);
+    }
+
     #[test]
     fn snapshot_ok() {
         let mut m = fresh();
@@ -6,7 +6,7 @@
 //! - Leaf key  = blake3(address_bytes) → 32-byte path in the trie
 //! - Leaf value = blake3(abi_encode(address, balance, nonce)) → 32-byte leaf hash
 //! - Interior  = blake3(left_child || right_child)
-//! - Empty node = [0u8; 32] (sentinel)
+//! - Empty node = [0u8; 32] (sentinel); `interior(EMPTY, EMPTY) = EMPTY`
 //!
 //! The trie depth is fixed at 256 bits (one bit per level). In practice the
 //! tree is sparse — only non-empty accounts create nodes. The root is a 32-byte
@@ -14,8 +14,8 @@
 //!
 //! ## Complexity (Sprint 5 upgrade)
 //! - Insert / remove:  O(1) amortised — marks caches dirty
-//! - Root recompute:   O(k log k) where k = leaf count (sort + fold)
-//! - prove(address):   O(k + 256) — O(k) bucket-build + 256 O(1) lookups (was O(k×256))
+//! - Root recompute:   O(k × 256) where k = leaf count (sort + one pass per level)
+//! - prove(address):   O(256) — one cache lookup per level
 
 use std::collections::HashMap;
 use blake3;
@@ -52,9 +52,26 @@ fn bit_at(path: &[u8; 32], depth: usize) -> u8 {
     (path[depth / 8] >> (7 - (depth % 8))) & 1
 }
 
+/// `path` with every bit from `depth` on cleared — the cache key of the
+/// node at `depth` above `path`.
+fn prefix_at(path: &[u8; 32], depth: usize) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let full = depth / 8;
+    out[..full].copy_from_slice(&path[..full]);
+    if depth % 8 != 0 {
+        out[full] = path[full] & (0xFFu8 << (8 - depth % 8));
+    }
+    out
+}
+
 // ── Interior node hash ────────────────────────────────────────────────────────
 
-fn interior_hash(left: &NodeHash, right: &NodeHash) -> NodeHash {
+/// Hash of two children.  Two empty children make an empty node, so an
+/// empty subtree is `EMPTY` at every depth.
+pub(crate) fn interior_hash(left: &NodeHash, right: &NodeHash) -> NodeHash {
+    if left == &EMPTY && right == &EMPTY {
+        return EMPTY;
+    }
     let mut buf = [0u8; 64];
     buf[..32].copy_from_slice(left);
     buf[32..].copy_from_slice(right);
@@ -76,8 +93,8 @@ pub struct SparseMerkleTrie {
     cached_root: Option<NodeHash>,
     /// Interior node cache built by `compute_root_with_cache()`.
     ///
-    /// Key: `(depth, representative_path)` where the representative path is
-    /// the lexicographically smallest leaf path in that subtree.
+    /// Key: `(depth, prefix)` where `prefix` is the subtree's path prefix
+    /// (bits `depth..` cleared).  Only non-empty subtrees are stored.
     /// Value: hash of that subtree.
     ///
     /// Depth 256 = leaf level. Depth 0 = root.
@@ -125,91 +142,48 @@ impl SparseMerkleTrie {
         root
     }
 
-    /// Bottom-up root computation that records every interior node.
+    /// Recursive root computation that records every non-empty node.
     ///
-    /// Algorithm:
-    /// 1. Sort leaves by path (left → right in the trie).
-    /// 2. Push each leaf onto a stack tagged (TRIE_DEPTH, path, hash).
-    /// 3. While the top two stack entries have the same depth, they are
-    ///    siblings — merge them into a parent and record the parent.
-    /// 4. Collapse any remaining unpaired entries up to depth 0.
+    /// Leaves are sorted by path, so the leaves under any node form a
+    /// contiguous run; each run is split on the bit at the node's depth.
+    /// A node is cached under `(depth, prefix)` where `prefix` is any path
+    /// below it with every bit from `depth` on cleared.  Empty subtrees hash
+    /// to `EMPTY` at every level and are never stored.
     fn compute_root_with_cache(&mut self) -> NodeHash {
-        if self.leaves.is_empty() {
-            return EMPTY;
-        }
-
-        // Sort leaves by path so left-to-right order is guaranteed.
         let mut sorted: Vec<([u8; 32], NodeHash)> = self.leaves
             .iter()
             .map(|(k, v)| (*k, *v))
             .collect();
         sorted.sort_by_key(|(k, _)| *k);
+        self.subtree_hash(&sorted, 0)
+    }
 
-        // Stack entries: (depth, rep_path, hash).
-        // rep_path = the leftmost leaf path in the subtree (invariant).
-        let mut stack: Vec<(usize, [u8; 32], NodeHash)> = Vec::new();
-
-        for (path, leaf) in &sorted {
-            // Record leaf at depth = TRIE_DEPTH
-            self.interior_cache.insert((TRIE_DEPTH, *path), *leaf);
-            stack.push((TRIE_DEPTH, *path, *leaf));
-
-            // Bubble up: merge siblings while the top two share the same depth.
-            loop {
-                let n = stack.len();
-                if n < 2 { break; }
-                let (d1, p1, h1) = stack[n - 2];
-                let (d2, p2, h2) = stack[n - 1];
-                if d1 != d2 { break; }
-
-                stack.pop();
-                stack.pop();
-
-                // Determine which child is left/right by the bit at depth (d1-1).
-                let (left_h, right_h, rep) = if bit_at(&p1, d1 - 1) == 0 {
-                    // p1 is in the left subtree, p2 is in the right subtree
-                    (h1, h2, p1)
-                } else {
-                    (h2, h1, p2)
-                };
-
-                let parent = interior_hash(&left_h, &right_h);
-                self.interior_cache.insert((d1 - 1, rep), parent);
-                stack.push((d1 - 1, rep, parent));
-            }
-        }
-
-        // Collapse any remaining unpaired nodes to the root.
-        while stack.len() > 1 {
-            let (_, _p2, h2) = stack.pop().unwrap();
-            let (d,  p1, h1) = stack.pop().unwrap();
-            let merged = interior_hash(&h1, &h2);
-            let rep = p1; // leftmost path wins
-            self.interior_cache.insert((d.saturating_sub(1), rep), merged);
-            stack.push((d.saturating_sub(1), rep, merged));
+    fn subtree_hash(&mut self, leaves: &[([u8; 32], NodeHash)], depth: usize) -> NodeHash {
+        if leaves.is_empty() {
+            return EMPTY;
         }
-
-        stack.pop().map(|(_, _, h)| h).unwrap_or(EMPTY)
+        let hash = if depth == TRIE_DEPTH {
+            leaves[0].1
+        } else {
+            let split = leaves.partition_point(|(p, _)| bit_at(p, depth) == 0);
+            let left  = self.subtree_hash(&leaves[..split], depth + 1);
+            let right = self.subtree_hash(&leaves[split..], depth + 1);
+            interior_hash(&left, &right)
+        };
+        self.interior_cache.insert((depth, prefix_at(&leaves[0].0, depth)), hash);
+        hash
     }
 
     // ── Merkle Proofs ─────────────────────────────────────────────────────────
 
     /// Generate a Merkle inclusion or exclusion proof for `address`.
     ///
-    /// **O(256) per proof** — builds a depth-bucketed sibling index from
-    /// `interior_cache` in O(k) then performs exactly 256 O(1) lookups.
-    ///
-    /// ### Sibling representative-path derivation
-    /// The `interior_cache` stores each non-empty subtree as
-    /// `(depth, rep_path) → hash` where `rep_path` is the lex-minimum leaf
-    /// path inside that subtree.  For our proof path `key_path`, the sibling
-    /// at level `depth` has a representative path that:
-    ///   - shares bits `0..depth` with `key_path` (same ancestor prefix)
-    ///   - has bit `depth` flipped (opposite child)
-    ///   - has bits `depth+1..` set to zero (minimum of that subtree)
+    /// **O(256) per proof** — one `interior_cache` lookup per level.
     ///
-    /// We bucket the cache by depth so each lookup is a linear scan of at
-    /// most O(k) entries at one depth level — but the total work is O(k + 256).
+    /// The sibling at level `depth` is the child at `depth + 1` that shares
+    /// bits `0..depth` with `key_path` and has bit `depth` flipped; its
+    /// cache key is that prefix with all lower bits cleared.  A missing
+    /// entry is an empty subtree.
     pub fn prove(&mut self, address: &str) -> MerkleProof {
         // Ensure root and interior_cache are fresh.
         let root     = self.root();
@@ -217,37 +191,15 @@ impl SparseMerkleTrie {
         let exists   = self.leaves.contains_key(&key_path);
         let leaf     = if exists { self.leaves[&key_path] } else { EMPTY };
 
-        // Build depth-bucketed index from interior_cache in one O(k) pass.
-        // by_depth[d] = list of (rep_path, hash) for all subtrees at depth d.
-        let mut by_depth: HashMap<usize, Vec<([u8; 32], NodeHash)>> =
-            HashMap::with_capacity(TRIE_DEPTH + 1);
-        for ((d, p), h) in &self.interior_cache {
-            by_depth.entry(*d).or_default().push((*p, *h));
-        }
-
-        // For each of the 256 levels derive the sibling rep-path and look it up.
         let mut path: Vec<ProofNode> = Vec::with_capacity(TRIE_DEPTH);
 
         for depth in (0..TRIE_DEPTH).rev() {
-            let my_bit   = bit_at(&key_path, depth);
-            // Sibling rep path: same prefix as key_path for bits 0..depth,
-            // opposite at bit `depth`, zero for all bits > depth.
-            let mut sib_rep  = key_path;
-            let byte_idx     = depth / 8;
-            let bit_in_byte  = 7 - (depth % 8); // MSB-first layout
-            // Zero out all bytes after the sibling bit's byte.
-            for b in sib_rep.iter_mut().skip(byte_idx + 1) { *b = 0; }
-            // Within byte_idx: keep the high bits (prefix), zero the low bits,
-            // then set the sibling's bit (opposite of my_bit).
-            let keep_mask = if bit_in_byte == 7 { 0u8 } else { 0xFFu8 << (bit_in_byte + 1) };
-            let sib_bit   = if my_bit == 0 { 1u8 << bit_in_byte } else { 0u8 };
-            sib_rep[byte_idx] = (sib_rep[byte_idx] & keep_mask) | sib_bit;
-
-            // Direct O(1) lookup in the depth+1 bucket.
-            let sibling = by_depth
-                .get(&(depth + 1))
-                .and_then(|bucket| bucket.iter().find(|(p, _)| *p == sib_rep))
-                .map(|(_, h)| *h)
+            let my_bit  = bit_at(&key_path, depth);
+            let mut sib = prefix_at(&key_path, depth + 1);
+            sib[depth / 8] ^= 1u8 << (7 - depth % 8);
+            let sibling = self.interior_cache
+                .get(&(depth + 1, sib))
+                .copied()
                 .unwrap_or(EMPTY);
 
             path.push(ProofNode { sibling, is_right: my_bit == 1 });
@@ -273,6 +225,18 @@ impl SparseMerkleTrie {
         }
     }
 
+    /// Every non-empty leaf as `(path, leaf hash)`, in no particular order.
+    pub fn leaf_entries(&self) -> impl Iterator<Item = (&[u8; 32], &NodeHash)> {
+        self.leaves.iter()
+    }
+
+    /// Insert a leaf by path — used to move subtrees between tries
+    /// without re-deriving them from account data.
+    pub fn insert_leaf(&mut self, path: [u8; 32], leaf: NodeHash) {
+        self.leaves.insert(path, leaf);
+        self.invalidate_caches();
+    }
+
     pub fn len(&self)      -> usize { self.leaves.len() }
     pub fn is_empty(&self) -> bool  { self.leaves.is_empty() }
 }
@@ -442,6 +406,20 @@ mod tests {
         assert!(t.verify_proof(&proof));
     }
 
+    #[test]
+    fn every_proof_verifies_in_a_larger_trie() {
+        let mut t = SparseMerkleTrie::new();
+        for i in 0..50u64 {
+            t.insert(&format!("acct-{}", i), 100 + i as u128, i);
+        }
+        let root = t.root();
+        for i in 0..50u64 {
+            assert!(t.prove(&format!("acct-{}", i)).verify(&root));
+        }
+        let absent = t.prove("acct-missing");
+        assert!(!absent.exists && absent.verify(&root));
+    }
+
     #[test]
     fn tampered_proof_fails() {
         let mut t = SparseMerkleTrie::new();
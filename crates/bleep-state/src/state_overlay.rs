@@ -0,0 +1,201 @@
+//! # StateOverlay
+//!
+//! Copy-on-write view over a `StateManager` used for dry-run execution.
+//!
+//! Reads fall through to the underlying manager — either its live state or
+//! the state at a past height — and every write lands in an in-memory map
+//! owned by the overlay.  Dropping the overlay discards all writes, so the
+//! base state is never touched.
+//!
+//! ```text
+//!   read(addr)  ──► overlay.accounts ──miss──► StateManager (live | account_at(h))
+//!   write(addr) ──► overlay.accounts only
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::state_manager::{AccountState, StateManager, StateResult};
+
+/// Net effect of an overlay on one account, relative to the base state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountChange {
+    pub address:        String,
+    pub balance_before: u128,
+    pub balance_after:  u128,
+    pub nonce_before:   u64,
+    pub nonce_after:    u64,
+}
+
+/// Throwaway account view layered over a `StateManager`.
+pub struct StateOverlay<'a> {
+    base:     &'a StateManager,
+    /// `None` = live state; `Some(h)` = state as of height `h`.
+    height:   Option<u64>,
+    /// Base values of every account read through the overlay.
+    original: BTreeMap<String, AccountState>,
+    /// Overlay-local writes.
+    accounts: BTreeMap<String, AccountState>,
+}
+
+impl<'a> StateOverlay<'a> {
+    /// Overlay on top of the live state.
+    pub fn new(base: &'a StateManager) -> Self {
+        Self { base, height: None, original: BTreeMap::new(), accounts: BTreeMap::new() }
+    }
+
+    /// Overlay on top of the state at `height`.
+    ///
+    /// Fails with `StateError::Pruned` if `height` is outside the retention
+    /// window, so callers learn about it before executing anything.
+    pub fn at_height(base: &'a StateManager, height: u64) -> StateResult<Self> {
+        if height != base.block_height() {
+            // Probe once so pruned / future heights are reported up front.
+            base.account_at("", height)?;
+        }
+        Ok(Self { base, height: Some(height), original: BTreeMap::new(), accounts: BTreeMap::new() })
+    }
+
+    /// Height the overlay reads from.
+    pub fn height(&self) -> u64 {
+        self.height.unwrap_or_else(|| self.base.block_height())
+    }
+
+    pub fn account(&mut self, address: &str) -> StateResult<AccountState> {
+        if let Some(a) = self.accounts.get(address) {
+            return Ok(a.clone());
+        }
+        self.load(address)
+    }
+
+    pub fn balance(&mut self, address: &str) -> StateResult<u128> {
+        Ok(self.account(address)?.balance)
+    }
+
+    pub fn nonce(&mut self, address: &str) -> StateResult<u64> {
+        Ok(self.account(address)?.nonce)
+    }
+
+    /// Debit `sender`, credit `receiver` and bump the sender nonce.
+    ///
+    /// Mirrors `StateManager::apply_transfer` but reports the reason for a
+    /// rejection instead of a bare `false`.
+    pub fn transfer(&mut self, sender: &str, receiver: &str, amount: u128) -> StateResult<Result<(), String>> {
+        if amount == 0 {
+            return Ok(Err("zero-amount transfer".into()));
+        }
+        let mut from = self.account(sender)?;
+        let new_from = match from.balance.checked_sub(amount) {
+            Some(v) => v,
+            None => {
+                return Ok(Err(format!(
+                    "insufficient balance: {} has {}, needs {}",
+                    sender, from.balance, amount
+                )))
+            }
+        };
+        from.balance = new_from;
+        from.nonce += 1;
+        self.accounts.insert(sender.to_string(), from);
+
+        let mut to = self.account(receiver)?;
+        to.balance = match to.balance.checked_add(amount) {
+            Some(v) => v,
+            None => return Ok(Err(format!("receiver {} balance overflow", receiver))),
+        };
+        self.accounts.insert(receiver.to_string(), to);
+        Ok(Ok(()))
+    }
+
+    /// Apply a signed balance delta (as produced by a VM `StateDiff`).
+    pub fn apply_balance_delta(&mut self, address: &str, delta: i128) -> StateResult<Result<(), String>> {
+        let mut acct = self.account(address)?;
+        let updated = if delta >= 0 {
+            acct.balance.checked_add(delta as u128)
+        } else {
+            acct.balance.checked_sub(delta.unsigned_abs())
+        };
+        match updated {
+            Some(v) => {
+                acct.balance = v;
+                self.accounts.insert(address.to_string(), acct);
+                Ok(Ok(()))
+            }
+            None => Ok(Err(format!("balance delta {} invalid for {}", delta, address))),
+        }
+    }
+
+    /// Per-account changes relative to the base state, in address order.
+    pub fn changes(&self) -> Vec<AccountChange> {
+        self.accounts.iter().filter_map(|(addr, after)| {
+            let before = self.original.get(addr).cloned().unwrap_or_default();
+            if before.balance == after.balance && before.nonce == after.nonce {
+                return None;
+            }
+            Some(AccountChange {
+                address:        addr.clone(),
+                balance_before: before.balance,
+                balance_after:  after.balance,
+                nonce_before:   before.nonce,
+                nonce_after:    after.nonce,
+            })
+        }).collect()
+    }
+
+    fn load(&mut self, address: &str) -> StateResult<AccountState> {
+        if let Some(a) = self.original.get(address) {
+            return Ok(a.clone());
+        }
+        let acct = match self.height {
+            Some(h) => self.base.account_at(address, h)?,
+            None => self.base.get_account_state(address),
+        };
+        self.original.insert(address.to_string(), acct.clone());
+        Ok(acct)
+    }
+}
+
+// ── Unit tests ────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_manager::StateError;
+
+    #[test]
+    fn writes_never_reach_base() {
+        let mut base = StateManager::new();
+        base.mint("alice", 100).unwrap();
+        {
+            let mut ov = StateOverlay::new(&base);
+            ov.transfer("alice", "bob", 60).unwrap().unwrap();
+            assert_eq!(ov.balance("alice").unwrap(), 40);
+            assert_eq!(ov.balance("bob").unwrap(), 60);
+            assert_eq!(ov.changes().len(), 2);
+        }
+        assert_eq!(base.get_balance("alice"), 100);
+        assert_eq!(base.get_balance("bob"), 0);
+        assert_eq!(base.get_nonce("alice"), 0);
+    }
+
+    #[test]
+    fn insufficient_balance_is_reported() {
+        let base = StateManager::new();
+        let mut ov = StateOverlay::new(&base);
+        let r = ov.transfer("carol", "dave", 5).unwrap();
+        assert!(r.unwrap_err().contains("insufficient balance"));
+        assert!(ov.changes().is_empty());
+    }
+
+    #[test]
+    fn pruned_height_rejected_up_front() {
+        let mut base = StateManager::new();
+        base.set_history_retention(1);
+        base.advance_block();
+        base.advance_block();
+        base.advance_block();
+        assert!(matches!(StateOverlay::at_height(&base, 0), Err(StateError::Pruned(0))));
+        assert!(StateOverlay::at_height(&base, 2).is_ok());
+    }
+}
@@ -0,0 +1,330 @@
+//! # Cross-shard timelines
+//!
+//! Every leg of a cross-shard transfer appends an event to a log in the
+//! state database, keyed by the transfer's `xshard_id` (the hex digest of
+//! its `CrossShardDebit`, also the suffix of its marker addresses):
+//!
+//! ```text
+//!   xtrace:<xshard_id>:<seq be>  → XShardEvent JSON
+//!   xopen:<xshard_id>            → ""   (no commit or abort recorded yet)
+//!
+//!   lock → debit → credit → commit          (source, dest, dest, source)
+//!   lock → debit → abort → rollback         (credit rejected; sender refunded)
+//! ```
+//!
+//! Events are written straight to the database rather than with the next
+//! block, so a shard worker restarted mid-transfer picks the timeline up
+//! where it stopped, and finds the transfers still awaiting a decision in
+//! the `xopen:` index (added by state schema version 2, see `migrations`).  `rollback_to` adds a `rollback` event for every leg it
+//! unwinds.
+//!
+//! Each event is also emitted inside a `xshard` tracing span carrying the
+//! id, shard and phase, and `XShardTelemetry` turns finished timelines into
+//! latency histograms, flagging transfers slower than the SLO.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bleep_telemetry::metrics::{MetricCounter, MetricHistogram, MetricsRegistry};
+use rocksdb::{WriteBatch, DB};
+use serde::{Deserialize, Serialize};
+
+use crate::state_manager::{StateError, StateResult};
+
+const PREFIX_TRACE: &[u8] = b"xtrace:";
+const PREFIX_OPEN: &[u8]  = b"xopen:";
+
+/// Default end-to-end latency objective for a cross-shard transfer.
+pub const DEFAULT_XSHARD_SLO: Duration = Duration::from_secs(30);
+
+/// Upper bounds (ms) of the latency histogram buckets.
+const LATENCY_BUCKETS_MS: &[u64] = &[10, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 30_000, 60_000];
+
+/// Step of a cross-shard transfer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum XShardPhase {
+    /// Sender's funds checked and held on the source shard.
+    Lock,
+    /// Sender debited and the debit marker written.
+    Debit,
+    /// Recipient credited on the destination shard.
+    Credit,
+    /// Coordinator committed the transfer.
+    Commit,
+    /// Coordinator aborted the transfer.
+    Abort,
+    /// A leg was undone: a refund after an abort, or a state rollback.
+    Rollback,
+}
+
+impl XShardPhase {
+    pub fn label(self) -> &'static str {
+        match self {
+            XShardPhase::Lock     => "lock",
+            XShardPhase::Debit    => "debit",
+            XShardPhase::Credit   => "credit",
+            XShardPhase::Commit   => "commit",
+            XShardPhase::Abort    => "abort",
+            XShardPhase::Rollback => "rollback",
+        }
+    }
+}
+
+/// One recorded step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XShardEvent {
+    /// Position in the timeline; assigned in write order.
+    pub seq:    u32,
+    pub phase:  XShardPhase,
+    /// Shard the step ran on.
+    pub shard:  u64,
+    /// Block height being built when the step ran.
+    pub height: u64,
+    /// Unix time in milliseconds.
+    pub at_ms:  u64,
+    /// Abort reason, refund amount or unwound leg.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Every recorded step of one transfer, in order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XShardTimeline {
+    pub xshard_id:    String,
+    pub events:       Vec<XShardEvent>,
+    /// `commit` or `abort` once the coordinator has decided.
+    pub outcome:      Option<XShardPhase>,
+    pub abort_reason: Option<String>,
+    /// Milliseconds from the first event to the decision.
+    pub total_ms:     Option<u64>,
+    /// The decision came later than the SLO allows.
+    pub slo_exceeded: bool,
+}
+
+impl XShardTimeline {
+    fn assemble(xshard_id: &str, mut events: Vec<XShardEvent>, slo: Duration) -> Self {
+        events.sort_by_key(|e| e.seq);
+        let decision = events.iter()
+            .find(|e| matches!(e.phase, XShardPhase::Commit | XShardPhase::Abort));
+        let outcome = decision.map(|e| e.phase);
+        let abort_reason = decision
+            .filter(|e| e.phase == XShardPhase::Abort)
+            .and_then(|e| e.detail.clone());
+        let total_ms = decision
+            .zip(events.first())
+            .map(|(end, start)| end.at_ms.saturating_sub(start.at_ms));
+        let slo_exceeded = total_ms.is_some_and(|ms| ms > slo.as_millis() as u64);
+        XShardTimeline {
+            xshard_id: xshard_id.to_string(),
+            events,
+            outcome,
+            abort_reason,
+            total_ms,
+            slo_exceeded,
+        }
+    }
+
+    /// Phases in timeline order.
+    pub fn phases(&self) -> Vec<XShardPhase> {
+        self.events.iter().map(|e| e.phase).collect()
+    }
+
+    /// First event of `phase`.
+    pub fn event(&self, phase: XShardPhase) -> Option<&XShardEvent> {
+        self.events.iter().find(|e| e.phase == phase)
+    }
+
+    /// Milliseconds from the first `from` event to the first `to` event.
+    pub fn elapsed_ms(&self, from: XShardPhase, to: XShardPhase) -> Option<u64> {
+        Some(self.event(to)?.at_ms.saturating_sub(self.event(from)?.at_ms))
+    }
+}
+
+// ── Event log ─────────────────────────────────────────────────────────────────
+
+fn storage_err(e: impl std::fmt::Display) -> StateError {
+    StateError::Storage(e.to_string())
+}
+
+fn trace_prefix(xshard_id: &str) -> Vec<u8> {
+    [PREFIX_TRACE, xshard_id.as_bytes(), b":"].concat()
+}
+
+fn open_key(xshard_id: &str) -> Vec<u8> {
+    [PREFIX_OPEN, xshard_id.as_bytes()].concat()
+}
+
+fn is_decision(phase: XShardPhase) -> bool {
+    matches!(phase, XShardPhase::Commit | XShardPhase::Abort)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Tracing span for one leg of a transfer.
+pub fn span(xshard_id: &str, shard: u64, phase: XShardPhase) -> tracing::Span {
+    tracing::info_span!("xshard", xshard_id = %xshard_id, shard, phase = phase.label())
+}
+
+fn load(db: &DB, xshard_id: &str) -> StateResult<Vec<XShardEvent>> {
+    let prefix = trace_prefix(xshard_id);
+    let mut events = Vec::new();
+    for item in db.prefix_iterator(&prefix) {
+        let (k, v) = item.map_err(storage_err)?;
+        if !k.starts_with(&prefix) { break; }
+        events.push(serde_json::from_slice(&v)
+            .map_err(|e| StateError::Serialisation(e.to_string()))?);
+    }
+    Ok(events)
+}
+
+/// Append `phase` to the timeline of `xshard_id`.
+pub(crate) fn record(
+    db: &DB,
+    xshard_id: &str,
+    shard: u64,
+    height: u64,
+    phase: XShardPhase,
+    detail: Option<String>,
+) -> StateResult<XShardEvent> {
+    let seq = load(db, xshard_id)?.len() as u32;
+    let event = XShardEvent { seq, phase, shard, height, at_ms: now_ms(), detail };
+    let value = serde_json::to_vec(&event)
+        .map_err(|e| StateError::Serialisation(e.to_string()))?;
+    let mut batch = WriteBatch::default();
+    batch.put([trace_prefix(xshard_id), seq.to_be_bytes().to_vec()].concat(), value);
+    if is_decision(phase) {
+        batch.delete(open_key(xshard_id));
+    } else if seq == 0 {
+        batch.put(open_key(xshard_id), b"");
+    }
+    db.write(batch).map_err(storage_err)?;
+
+    let _span = span(xshard_id, shard, phase).entered();
+    match &event.detail {
+        Some(detail) => tracing::info!(seq, height, detail = %detail, "cross-shard {}", phase.label()),
+        None => tracing::info!(seq, height, "cross-shard {}", phase.label()),
+    }
+    Ok(event)
+}
+
+/// Timeline of `xshard_id`; `None` if nothing was recorded under it.
+pub(crate) fn timeline(db: &DB, xshard_id: &str, slo: Duration) -> StateResult<Option<XShardTimeline>> {
+    let events = load(db, xshard_id)?;
+    if events.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(XShardTimeline::assemble(xshard_id, events, slo)))
+}
+
+/// Ids of transfers with no commit or abort recorded, in id order.
+pub(crate) fn open_transfers(db: &DB) -> StateResult<Vec<String>> {
+    let mut ids = Vec::new();
+    for item in db.prefix_iterator(PREFIX_OPEN) {
+        let (k, _) = item.map_err(storage_err)?;
+        if !k.starts_with(PREFIX_OPEN) { break; }
+        ids.push(String::from_utf8_lossy(&k[PREFIX_OPEN.len()..]).into_owned());
+    }
+    Ok(ids)
+}
+
+/// State schema 1 → 2: build the `xopen:` index from the events already
+/// logged.  Rewrites the whole index, so running it twice is harmless.
+pub(crate) fn index_open_transfers(db: &DB) -> Result<(), String> {
+    // xtrace:<id>:<seq be u32> — the id is hex, so it ends 5 bytes early.
+    let mut decided: std::collections::BTreeMap<String, bool> = Default::default();
+    for item in db.prefix_iterator(PREFIX_TRACE) {
+        let (k, v) = item.map_err(|e| e.to_string())?;
+        if !k.starts_with(PREFIX_TRACE) { break; }
+        let id = k.get(PREFIX_TRACE.len()..k.len().saturating_sub(5))
+            .ok_or_else(|| format!("malformed trace key {}", hex::encode(&k)))?;
+        let id = String::from_utf8_lossy(id).into_owned();
+        let event: XShardEvent = serde_json::from_slice(&v)
+            .map_err(|e| format!("trace event of {}: {}", id, e))?;
+        *decided.entry(id).or_default() |= is_decision(event.phase);
+    }
+    let mut batch = WriteBatch::default();
+    for (id, decided) in &decided {
+        if *decided {
+            batch.delete(open_key(id));
+        } else {
+            batch.put(open_key(id), b"");
+        }
+    }
+    log::info!(
+        "[xshard] indexed {} open of {} logged transfers",
+        decided.values().filter(|d| !**d).count(), decided.len(),
+    );
+    db.write(batch).map_err(|e| e.to_string())
+}
+
+// ── Telemetry ─────────────────────────────────────────────────────────────────
+
+/// Latency of finished cross-shard transfers, per segment and end to end.
+#[derive(Debug, Clone)]
+pub struct XShardTelemetry {
+    lock_to_debit:    MetricHistogram,
+    debit_to_credit:  MetricHistogram,
+    credit_to_commit: MetricHistogram,
+    total:            MetricHistogram,
+    committed:        MetricCounter,
+    aborted:          MetricCounter,
+    slo_exceeded:     MetricCounter,
+}
+
+impl XShardTelemetry {
+    /// Register `bleep_xshard_*` metrics.
+    pub fn register(registry: &mut MetricsRegistry) -> Self {
+        XShardTelemetry {
+            lock_to_debit:    registry.histogram("bleep_xshard_lock_to_debit_ms", LATENCY_BUCKETS_MS),
+            debit_to_credit:  registry.histogram("bleep_xshard_debit_to_credit_ms", LATENCY_BUCKETS_MS),
+            credit_to_commit: registry.histogram("bleep_xshard_credit_to_commit_ms", LATENCY_BUCKETS_MS),
+            total:            registry.histogram("bleep_xshard_total_ms", LATENCY_BUCKETS_MS),
+            committed:        registry.counter("bleep_xshard_committed_total"),
+            aborted:          registry.counter("bleep_xshard_aborted_total"),
+            slo_exceeded:     registry.counter("bleep_xshard_slo_exceeded_total"),
+        }
+    }
+
+    /// Fold in a timeline the coordinator has decided.
+    pub fn observe(&self, timeline: &XShardTimeline) {
+        use XShardPhase::*;
+        let segments = [
+            (&self.lock_to_debit, Lock, Debit),
+            (&self.debit_to_credit, Debit, Credit),
+            (&self.credit_to_commit, Credit, Commit),
+        ];
+        for (histogram, from, to) in segments {
+            if let Some(ms) = timeline.elapsed_ms(from, to) {
+                histogram.observe(ms);
+            }
+        }
+        if let Some(ms) = timeline.total_ms {
+            self.total.observe(ms);
+        }
+        match timeline.outcome {
+            Some(Commit) => self.committed.increment(),
+            Some(Abort) => self.aborted.increment(),
+            _ => {}
+        }
+        if timeline.slo_exceeded {
+            self.slo_exceeded.increment();
+            log::warn!(
+                "[xshard] {} took {} ms, over the latency SLO",
+                timeline.xshard_id, timeline.total_ms.unwrap_or(0),
+            );
+        }
+    }
+
+    pub fn total(&self) -> &MetricHistogram {
+        &self.total
+    }
+
+    pub fn slo_exceeded(&self) -> u64 {
+        self.slo_exceeded.get()
+    }
+}
@@ -0,0 +1,466 @@
+//! # Telemetry export
+//!
+//! Pushes a filtered, signed snapshot of node health to external monitoring
+//! networks (public dashboards, validator scoreboards).
+//!
+//! Operators declare named `ExportProfile`s in a JSON config file.  A
+//! profile picks the metric groups to share and the HTTPS endpoints to push
+//! to.  `peer_addresses` and `accounts` are never exported, even if a
+//! profile lists them.
+//!
+//! Each push is a `SignedExport`: the filtered snapshot serialised to JSON,
+//! plus the node id, its ed25519 public key and a signature over the exact
+//! payload string.  Dashboards verify the signature, then parse the payload.
+//!
+//! When an endpoint is down, exports queue in a bounded per-endpoint buffer
+//! (oldest dropped first, with a counter) and retries back off
+//! exponentially in ticks.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Default seconds between exports.
+pub const DEFAULT_EXPORT_INTERVAL_SECS: u64 = 60;
+/// Default exports buffered per endpoint while it is unreachable.
+pub const DEFAULT_EXPORT_BUFFER: usize = 128;
+/// Longest wait between retries, in ticks.
+pub const MAX_BACKOFF_TICKS: u64 = 16;
+
+/// Metric families a snapshot is split into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricGroup {
+    Chain,
+    Consensus,
+    Vm,
+    Energy,
+    Network,
+    /// Peer IPs and ports — never exported
+    PeerAddresses,
+    /// Account- and wallet-level data — never exported
+    Accounts,
+}
+
+impl MetricGroup {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MetricGroup::Chain         => "chain",
+            MetricGroup::Consensus     => "consensus",
+            MetricGroup::Vm            => "vm",
+            MetricGroup::Energy        => "energy",
+            MetricGroup::Network       => "network",
+            MetricGroup::PeerAddresses => "peer_addresses",
+            MetricGroup::Accounts      => "accounts",
+        }
+    }
+
+    /// Groups that stay on the node whatever the profile says.
+    pub fn is_private(self) -> bool {
+        matches!(self, MetricGroup::PeerAddresses | MetricGroup::Accounts)
+    }
+}
+
+/// Point-in-time metrics, grouped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TelemetrySnapshot {
+    pub taken_at: u64,
+    pub groups:   BTreeMap<MetricGroup, BTreeMap<String, serde_json::Value>>,
+}
+
+impl TelemetrySnapshot {
+    pub fn new(taken_at: u64) -> Self {
+        Self { taken_at, groups: BTreeMap::new() }
+    }
+
+    /// Record one metric under `group`.
+    pub fn record(&mut self, group: MetricGroup, name: &str, value: impl Into<serde_json::Value>) -> &mut Self {
+        self.groups.entry(group).or_default().insert(name.to_string(), value.into());
+        self
+    }
+
+    /// Every group except the private ones.
+    pub fn public(&self) -> TelemetrySnapshot {
+        TelemetrySnapshot {
+            taken_at: self.taken_at,
+            groups: self.groups.iter()
+                .filter(|(g, _)| !g.is_private())
+                .map(|(g, m)| (*g, m.clone()))
+                .collect(),
+        }
+    }
+
+    /// Keep only the groups `profile` selects, minus private ones.
+    pub fn filtered(&self, profile: &ExportProfile) -> TelemetrySnapshot {
+        TelemetrySnapshot {
+            taken_at: self.taken_at,
+            groups: self.groups.iter()
+                .filter(|(g, _)| !g.is_private() && profile.groups.contains(g))
+                .map(|(g, m)| (*g, m.clone()))
+                .collect(),
+        }
+    }
+}
+
+fn default_interval() -> u64 { DEFAULT_EXPORT_INTERVAL_SECS }
+fn default_buffer() -> usize { DEFAULT_EXPORT_BUFFER }
+
+/// Named selection of metric groups and push targets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportProfile {
+    pub name:   String,
+    pub groups: Vec<MetricGroup>,
+    /// HTTPS endpoints; a profile without endpoints is preview-only.
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+    #[serde(default = "default_interval")]
+    pub interval_secs: u64,
+    #[serde(default = "default_buffer")]
+    pub buffer_capacity: usize,
+}
+
+impl ExportProfile {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Export profile needs a name".to_string());
+        }
+        if self.interval_secs == 0 {
+            return Err(format!("Profile '{}': interval_secs must be > 0", self.name));
+        }
+        if self.buffer_capacity == 0 {
+            return Err(format!("Profile '{}': buffer_capacity must be > 0", self.name));
+        }
+        if let Some(e) = self.endpoints.iter().find(|e| !e.starts_with("https://")) {
+            return Err(format!("Profile '{}': endpoint '{}' is not HTTPS", self.name, e));
+        }
+        Ok(())
+    }
+
+    /// Listed groups that will be dropped anyway.
+    pub fn excluded_groups(&self) -> Vec<MetricGroup> {
+        self.groups.iter().copied().filter(|g| g.is_private()).collect()
+    }
+}
+
+/// Contents of the telemetry export config file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryExportConfig {
+    pub profiles: Vec<ExportProfile>,
+}
+
+impl TelemetryExportConfig {
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let config: Self = serde_json::from_str(json).map_err(|e| format!("Invalid export config: {}", e))?;
+        for (i, p) in config.profiles.iter().enumerate() {
+            p.validate()?;
+            if config.profiles[..i].iter().any(|q| q.name == p.name) {
+                return Err(format!("Duplicate export profile '{}'", p.name));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::from_json(&json)
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&ExportProfile> {
+        self.profiles.iter().find(|p| p.name == name)
+    }
+}
+
+/// Signs exports with the node identity key.
+pub trait ExportSigner: Send + Sync {
+    fn node_id(&self) -> String;
+    fn public_key(&self) -> Vec<u8>;
+    fn sign(&self, message: &[u8]) -> Vec<u8>;
+}
+
+/// What is pushed to an endpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedExport {
+    pub profile:    String,
+    pub node_id:    String,
+    /// Hex ed25519 public key of the node
+    pub public_key: String,
+    /// JSON-encoded `TelemetrySnapshot`; the signature covers these bytes
+    pub payload:    String,
+    /// Hex signature over `payload`
+    pub signature:  String,
+}
+
+impl SignedExport {
+    pub fn new(profile: &str, snapshot: &TelemetrySnapshot, signer: &dyn ExportSigner) -> Self {
+        let payload = serde_json::to_string(snapshot).unwrap_or_default();
+        SignedExport {
+            profile:    profile.to_string(),
+            node_id:    signer.node_id(),
+            public_key: hex::encode(signer.public_key()),
+            signature:  hex::encode(signer.sign(payload.as_bytes())),
+            payload,
+        }
+    }
+}
+
+/// Delivers an export to one endpoint.
+#[async_trait]
+pub trait ExportSink: Send + Sync {
+    async fn push(&self, endpoint: &str, export: &SignedExport) -> Result<(), String>;
+}
+
+/// POSTs exports as JSON.
+pub struct HttpsExportSink {
+    client: reqwest::Client,
+}
+
+impl Default for HttpsExportSink {
+    fn default() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl ExportSink for HttpsExportSink {
+    async fn push(&self, endpoint: &str, export: &SignedExport) -> Result<(), String> {
+        let resp = self.client.post(endpoint).json(export).send().await.map_err(|e| e.to_string())?;
+        if resp.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("HTTP {}", resp.status()))
+        }
+    }
+}
+
+/// Delivery state for one endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EndpointStats {
+    pub endpoint: String,
+    pub sent:     u64,
+    pub buffered: usize,
+    /// Exports discarded because the buffer was full
+    pub dropped:  u64,
+    /// Consecutive failed pushes
+    pub failures: u64,
+}
+
+struct EndpointQueue {
+    stats:   EndpointStats,
+    pending: VecDeque<SignedExport>,
+    /// Ticks to sit out before the next attempt
+    wait:    u64,
+}
+
+/// Periodically snapshots, filters, signs and pushes telemetry for one profile.
+pub struct TelemetryExporter {
+    profile: ExportProfile,
+    signer:  Arc<dyn ExportSigner>,
+    sink:    Arc<dyn ExportSink>,
+    source:  Arc<dyn Fn() -> TelemetrySnapshot + Send + Sync>,
+    queues:  Mutex<HashMap<String, EndpointQueue>>,
+}
+
+impl TelemetryExporter {
+    pub fn new(
+        profile: ExportProfile,
+        signer: Arc<dyn ExportSigner>,
+        sink: Arc<dyn ExportSink>,
+        source: Arc<dyn Fn() -> TelemetrySnapshot + Send + Sync>,
+    ) -> Self {
+        let queues = profile.endpoints.iter().map(|e| (e.clone(), EndpointQueue {
+            stats:   EndpointStats { endpoint: e.clone(), ..Default::default() },
+            pending: VecDeque::new(),
+            wait:    0,
+        })).collect();
+        Self { profile, signer, sink, source, queues: Mutex::new(queues) }
+    }
+
+    /// Take a snapshot, queue it for every endpoint and flush what is due.
+    pub async fn tick(&self) {
+        let snapshot = (self.source)().filtered(&self.profile);
+        let export = SignedExport::new(&self.profile.name, &snapshot, self.signer.as_ref());
+
+        let mut due = Vec::new();
+        {
+            let mut queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+            for (endpoint, q) in queues.iter_mut() {
+                q.pending.push_back(export.clone());
+                while q.pending.len() > self.profile.buffer_capacity {
+                    q.pending.pop_front();
+                    q.stats.dropped += 1;
+                }
+                if q.wait > 0 {
+                    q.wait -= 1;
+                } else {
+                    due.push((endpoint.clone(), q.pending.iter().cloned().collect::<Vec<_>>()));
+                }
+                q.stats.buffered = q.pending.len();
+            }
+        }
+
+        for (endpoint, pending) in due {
+            let mut delivered = 0usize;
+            let mut error = None;
+            for export in &pending {
+                match self.sink.push(&endpoint, export).await {
+                    Ok(()) => delivered += 1,
+                    Err(e) => {
+                        error = Some(e);
+                        break;
+                    }
+                }
+            }
+            let mut queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(q) = queues.get_mut(&endpoint) else { continue };
+            // Entries may have been dropped meanwhile; only pop what is still ours.
+            for export in pending.iter().take(delivered) {
+                if q.pending.front() == Some(export) {
+                    q.pending.pop_front();
+                }
+            }
+            q.stats.sent += delivered as u64;
+            q.stats.buffered = q.pending.len();
+            match error {
+                Some(e) => {
+                    q.stats.failures += 1;
+                    q.wait = (1u64 << q.stats.failures.min(6)).min(MAX_BACKOFF_TICKS) - 1;
+                    log::warn!(
+                        "Telemetry export to {} failed ({}), {} buffered, retry in {} ticks",
+                        endpoint, e, q.pending.len(), q.wait + 1,
+                    );
+                }
+                None => q.stats.failures = 0,
+            }
+        }
+    }
+
+    pub fn stats(&self) -> Vec<EndpointStats> {
+        let queues = self.queues.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stats: Vec<_> = queues.values().map(|q| q.stats.clone()).collect();
+        stats.sort_by(|a, b| a.endpoint.cmp(&b.endpoint));
+        stats
+    }
+
+    /// Run `tick` every `interval_secs` until the task is aborted.
+    pub fn spawn(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.profile.interval_secs));
+            loop {
+                interval.tick().await;
+                self.tick().await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+    struct FakeSigner;
+
+    impl ExportSigner for FakeSigner {
+        fn node_id(&self) -> String { "node-1".into() }
+        fn public_key(&self) -> Vec<u8> { vec![0xAA; 32] }
+        fn sign(&self, message: &[u8]) -> Vec<u8> { message.len().to_le_bytes().to_vec() }
+    }
+
+    /// Records delivered exports; can be switched off to simulate downtime.
+    #[derive(Default)]
+    struct FlakySink {
+        down:      AtomicBool,
+        delivered: Mutex<Vec<u64>>,
+    }
+
+    #[async_trait]
+    impl ExportSink for FlakySink {
+        async fn push(&self, _endpoint: &str, export: &SignedExport) -> Result<(), String> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err("connection refused".into());
+            }
+            let snapshot: TelemetrySnapshot = serde_json::from_str(&export.payload).unwrap();
+            self.delivered.lock().unwrap().push(snapshot.taken_at);
+            Ok(())
+        }
+    }
+
+    fn exporter(sink: Arc<FlakySink>, capacity: usize) -> TelemetryExporter {
+        let clock = Arc::new(AtomicU64::new(0));
+        let profile = ExportProfile {
+            name: "public".into(),
+            groups: vec![MetricGroup::Chain],
+            endpoints: vec!["https://dash.example/ingest".into()],
+            interval_secs: 1,
+            buffer_capacity: capacity,
+        };
+        let source = Arc::new(move || {
+            let mut s = TelemetrySnapshot::new(clock.fetch_add(1, Ordering::SeqCst));
+            s.record(MetricGroup::Chain, "height", 7).record(MetricGroup::Accounts, "faucet_balance", 1);
+            s
+        });
+        TelemetryExporter::new(profile, Arc::new(FakeSigner), sink, source)
+    }
+
+    #[tokio::test]
+    async fn buffers_during_downtime_and_flushes_in_order() {
+        let sink = Arc::new(FlakySink::default());
+        let exporter = exporter(Arc::clone(&sink), 16);
+
+        sink.down.store(true, Ordering::SeqCst);
+        for _ in 0..4 {
+            exporter.tick().await;
+        }
+        let stats = &exporter.stats()[0];
+        assert_eq!((stats.sent, stats.buffered, stats.failures), (0, 4, 2));
+
+        sink.down.store(false, Ordering::SeqCst);
+        // Backoff after the second failure sits out three ticks.
+        exporter.tick().await;
+        exporter.tick().await;
+        assert!(sink.delivered.lock().unwrap().is_empty());
+        exporter.tick().await;
+        assert_eq!(*sink.delivered.lock().unwrap(), vec![0, 1, 2, 3, 4, 5, 6]);
+        let stats = &exporter.stats()[0];
+        assert_eq!((stats.sent, stats.buffered, stats.failures), (7, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn full_buffer_drops_oldest_and_counts() {
+        let sink = Arc::new(FlakySink::default());
+        let exporter = exporter(Arc::clone(&sink), 3);
+
+        sink.down.store(true, Ordering::SeqCst);
+        for _ in 0..8 {
+            exporter.tick().await;
+        }
+        let stats = &exporter.stats()[0];
+        assert_eq!((stats.buffered, stats.dropped), (3, 5));
+
+        sink.down.store(false, Ordering::SeqCst);
+        for _ in 0..MAX_BACKOFF_TICKS {
+            exporter.tick().await;
+            if !sink.delivered.lock().unwrap().is_empty() {
+                break;
+            }
+        }
+        let delivered = sink.delivered.lock().unwrap().clone();
+        // Oldest snapshots were dropped; the three most recent made it out.
+        assert_eq!(delivered.len(), 3);
+        assert!(delivered[0] >= 5);
+        assert!(delivered.windows(2).all(|w| w[0] < w[1]));
+
+        let payload: TelemetrySnapshot = serde_json::from_str(
+            &SignedExport::new("public", &(exporter.source)().filtered(&exporter.profile), &FakeSigner).payload,
+        ).unwrap();
+        assert!(!payload.groups.contains_key(&MetricGroup::Accounts));
+    }
+}
@@ -0,0 +1,620 @@
+//! # Telemetry history
+//!
+//! Keeps past values of numeric telemetry metrics in RocksDB so operators
+//! can look back over an incident instead of only at "now".
+//!
+//! Every sample is written at three resolutions:
+//!
+//! ```text
+//!   raw          one entry per sample              kept raw_secs         (1 h)
+//!   minute       min/max/sum/count per 60 s        kept minute_secs      (24 h)
+//!   ten_minutes  min/max/sum/count per 600 s       kept ten_minute_secs  (14 d)
+//! ```
+//!
+//! `prune` deletes buckets older than their resolution's retention, then the
+//! oldest buckets left until the store fits in `max_bytes`.  Size is counted
+//! as key plus value bytes and reported by `usage`.
+//!
+//! `query` returns a series aligned to `step`.  Each step is read from the
+//! finest resolution still holding that time, so a range that crosses a
+//! retention boundary is stitched from several resolutions without gaps or
+//! double counting.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rocksdb::{Direction, IteratorMode, Options, WriteBatch, DB};
+use serde::{Deserialize, Serialize};
+
+use crate::export::TelemetrySnapshot;
+
+/// Default seconds between samples.
+pub const DEFAULT_SAMPLE_INTERVAL_SECS: u64 = 10;
+/// Default storage bound.
+pub const DEFAULT_HISTORY_MAX_BYTES: u64 = 64 * 1024 * 1024;
+/// Most points one `query` returns.
+pub const MAX_QUERY_POINTS: u64 = 5_000;
+
+/// Encoded `Aggregate`: min, max, sum as f64 and count as u64.
+const VALUE_LEN: usize = 32;
+/// Resolution tag plus big-endian bucket start.
+const KEY_PREFIX_LEN: usize = 9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Resolution {
+    Raw,
+    Minute,
+    TenMinutes,
+}
+
+impl Resolution {
+    /// Finest first.
+    pub const ALL: [Resolution; 3] = [Resolution::Raw, Resolution::Minute, Resolution::TenMinutes];
+
+    /// Bucket width in seconds; raw samples keep their own second.
+    pub fn width(self) -> u64 {
+        match self {
+            Resolution::Raw        => 1,
+            Resolution::Minute     => 60,
+            Resolution::TenMinutes => 600,
+        }
+    }
+
+    fn tag(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Sampling interval, retention per resolution and the storage bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    pub sample_interval_secs: u64,
+    pub raw_secs:             u64,
+    pub minute_secs:          u64,
+    pub ten_minute_secs:      u64,
+    pub max_bytes:            u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        HistoryConfig {
+            sample_interval_secs: DEFAULT_SAMPLE_INTERVAL_SECS,
+            raw_secs:             3_600,
+            minute_secs:          86_400,
+            ten_minute_secs:      14 * 86_400,
+            max_bytes:            DEFAULT_HISTORY_MAX_BYTES,
+        }
+    }
+}
+
+impl HistoryConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sample_interval_secs == 0 {
+            return Err("sample_interval_secs must be > 0".to_string());
+        }
+        if !(self.raw_secs <= self.minute_secs && self.minute_secs <= self.ten_minute_secs) {
+            return Err("retention must not shrink as resolution coarsens".to_string());
+        }
+        if self.max_bytes == 0 {
+            return Err("max_bytes must be > 0".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn retention(&self, resolution: Resolution) -> u64 {
+        match resolution {
+            Resolution::Raw        => self.raw_secs,
+            Resolution::Minute     => self.minute_secs,
+            Resolution::TenMinutes => self.ten_minute_secs,
+        }
+    }
+}
+
+/// Summary of the samples in one bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Aggregate {
+    pub min:   f64,
+    pub max:   f64,
+    pub sum:   f64,
+    pub count: u64,
+}
+
+impl Aggregate {
+    pub fn of(value: f64) -> Self {
+        Aggregate { min: value, max: value, sum: value, count: 1 }
+    }
+
+    pub fn merge(&mut self, other: &Aggregate) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+        self.count += other.count;
+    }
+
+    pub fn avg(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum / self.count as f64 }
+    }
+
+    fn encode(&self) -> [u8; VALUE_LEN] {
+        let mut out = [0u8; VALUE_LEN];
+        out[..8].copy_from_slice(&self.min.to_le_bytes());
+        out[8..16].copy_from_slice(&self.max.to_le_bytes());
+        out[16..24].copy_from_slice(&self.sum.to_le_bytes());
+        out[24..].copy_from_slice(&self.count.to_le_bytes());
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != VALUE_LEN {
+            return None;
+        }
+        let word = |i: usize| <[u8; 8]>::try_from(&bytes[i * 8..(i + 1) * 8]).ok();
+        Some(Aggregate {
+            min:   f64::from_le_bytes(word(0)?),
+            max:   f64::from_le_bytes(word(1)?),
+            sum:   f64::from_le_bytes(word(2)?),
+            count: u64::from_le_bytes(word(3)?),
+        })
+    }
+}
+
+/// One step of a queried series; empty steps have no values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SeriesPoint {
+    pub t:       u64,
+    pub min:     Option<f64>,
+    pub max:     Option<f64>,
+    pub avg:     Option<f64>,
+    pub samples: u64,
+}
+
+/// Result of `TelemetryHistory::query`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Series {
+    pub metric:      String,
+    pub from:        u64,
+    pub to:          u64,
+    pub step:        u64,
+    /// Resolutions the points were read from, finest first.
+    pub resolutions: Vec<Resolution>,
+    pub points:      Vec<SeriesPoint>,
+}
+
+/// Storage used by the history.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryUsage {
+    pub bytes:     u64,
+    pub max_bytes: u64,
+    pub entries:   BTreeMap<Resolution, u64>,
+    /// Buckets deleted early to stay under `max_bytes`.
+    pub evicted:   u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    bytes:   u64,
+    entries: [u64; 3],
+    evicted: u64,
+    /// Newest sample time; the clock retention and queries run on.
+    latest:  u64,
+    metrics: BTreeSet<String>,
+}
+
+fn bucket_key(resolution: Resolution, start: u64, metric: &str) -> Vec<u8> {
+    let mut key = Vec::with_capacity(KEY_PREFIX_LEN + metric.len());
+    key.push(resolution.tag());
+    key.extend_from_slice(&start.to_be_bytes());
+    key.extend_from_slice(metric.as_bytes());
+    key
+}
+
+/// `(resolution, start, metric)` of a stored key.
+fn parse_key(key: &[u8]) -> Option<(Resolution, u64, &[u8])> {
+    if key.len() < KEY_PREFIX_LEN {
+        return None;
+    }
+    let resolution = *Resolution::ALL.get(key[0] as usize)?;
+    let start = u64::from_be_bytes(key[1..KEY_PREFIX_LEN].try_into().ok()?);
+    Some((resolution, start, &key[KEY_PREFIX_LEN..]))
+}
+
+fn entry_size(key: &[u8]) -> u64 {
+    (key.len() + VALUE_LEN) as u64
+}
+
+/// RocksDB-backed, downsampled history of telemetry metrics.
+pub struct TelemetryHistory {
+    db:       DB,
+    config:   HistoryConfig,
+    counters: Mutex<Counters>,
+}
+
+impl TelemetryHistory {
+    pub fn open<P: AsRef<Path>>(path: P, config: HistoryConfig) -> Result<Self, String> {
+        config.validate()?;
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        let db = DB::open(&opts, path).map_err(|e| e.to_string())?;
+
+        let mut counters = Counters::default();
+        for item in db.iterator(IteratorMode::Start) {
+            let (key, _) = item.map_err(|e| e.to_string())?;
+            let Some((resolution, start, metric)) = parse_key(&key) else { continue };
+            counters.bytes += entry_size(&key);
+            counters.entries[resolution.tag() as usize] += 1;
+            counters.latest = counters.latest.max(start);
+            counters.metrics.insert(String::from_utf8_lossy(metric).into_owned());
+        }
+        Ok(TelemetryHistory { db, config, counters: Mutex::new(counters) })
+    }
+
+    pub fn config(&self) -> &HistoryConfig {
+        &self.config
+    }
+
+    fn counters(&self) -> std::sync::MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record `value` for `metric` at unix second `at`, in every resolution.
+    /// Non-finite values are ignored.
+    pub fn record(&self, at: u64, metric: &str, value: f64) -> Result<(), String> {
+        if metric.is_empty() || !value.is_finite() {
+            return Ok(());
+        }
+        let mut counters = self.counters();
+        let mut batch = WriteBatch::default();
+        let mut added = Vec::new();
+        for resolution in Resolution::ALL {
+            let key = bucket_key(resolution, at - at % resolution.width(), metric);
+            let mut agg = Aggregate::of(value);
+            match self.db.get(&key).map_err(|e| e.to_string())? {
+                Some(existing) => {
+                    if let Some(old) = Aggregate::decode(&existing) {
+                        agg.merge(&old);
+                    }
+                }
+                None => added.push((resolution, entry_size(&key))),
+            }
+            batch.put(&key, agg.encode());
+        }
+        self.db.write(batch).map_err(|e| e.to_string())?;
+
+        for (resolution, size) in added {
+            counters.bytes += size;
+            counters.entries[resolution.tag() as usize] += 1;
+        }
+        counters.latest = counters.latest.max(at);
+        if !counters.metrics.contains(metric) {
+            counters.metrics.insert(metric.to_string());
+        }
+        Ok(())
+    }
+
+    /// Record every numeric metric in `snapshot` as `group.name`, except
+    /// private groups, then prune.
+    pub fn record_snapshot(&self, snapshot: &TelemetrySnapshot) -> Result<(), String> {
+        for (group, metrics) in snapshot.groups.iter().filter(|(g, _)| !g.is_private()) {
+            for (name, value) in metrics {
+                let value = match value {
+                    serde_json::Value::Number(n) => n.as_f64(),
+                    serde_json::Value::Bool(b) => Some(*b as u8 as f64),
+                    _ => None,
+                };
+                if let Some(v) = value {
+                    self.record(snapshot.taken_at, &format!("{}.{}", group.as_str(), name), v)?;
+                }
+            }
+        }
+        self.prune().map(|_| ())
+    }
+
+    /// Delete expired buckets, then the oldest buckets until the store fits
+    /// `max_bytes`.  Returns how many buckets were deleted.
+    pub fn prune(&self) -> Result<u64, String> {
+        let mut counters = self.counters();
+        let now = counters.latest;
+        let mut removed = 0;
+
+        let mut batch = WriteBatch::default();
+        for resolution in Resolution::ALL {
+            let cutoff = now.saturating_sub(self.config.retention(resolution));
+            for item in self.db.iterator(IteratorMode::From(&[resolution.tag()], Direction::Forward)) {
+                let (key, _) = item.map_err(|e| e.to_string())?;
+                match parse_key(&key) {
+                    Some((r, start, _)) if r == resolution && start < cutoff => {
+                        counters.bytes -= entry_size(&key);
+                        counters.entries[r.tag() as usize] -= 1;
+                        batch.delete(&key);
+                        removed += 1;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        self.db.write(batch).map_err(|e| e.to_string())?;
+
+        // The oldest buckets overall are among the first `excess` bytes of
+        // each resolution.
+        let excess = counters.bytes.saturating_sub(self.config.max_bytes);
+        if excess > 0 {
+            let mut candidates = Vec::new();
+            for resolution in Resolution::ALL.iter().rev() {
+                let mut freed = 0;
+                for item in self.db.iterator(IteratorMode::From(&[resolution.tag()], Direction::Forward)) {
+                    let (key, _) = item.map_err(|e| e.to_string())?;
+                    let Some((r, start, _)) = parse_key(&key) else { break };
+                    if r != *resolution || freed >= excess {
+                        break;
+                    }
+                    freed += entry_size(&key);
+                    candidates.push((start, key));
+                }
+            }
+            // Stable sort: on equal starts the coarser bucket goes first.
+            candidates.sort_by_key(|(start, _)| *start);
+            let mut batch = WriteBatch::default();
+            for (_, key) in candidates {
+                if counters.bytes <= self.config.max_bytes {
+                    break;
+                }
+                counters.bytes -= entry_size(&key);
+                counters.entries[key[0] as usize] -= 1;
+                counters.evicted += 1;
+                removed += 1;
+                batch.delete(&key);
+            }
+            self.db.write(batch).map_err(|e| e.to_string())?;
+        }
+        Ok(removed)
+    }
+
+    /// `metric` between unix seconds `from` and `to`, one point per `step`
+    /// seconds starting at `from` rounded down to a multiple of `step`.
+    pub fn query(&self, metric: &str, from: u64, to: u64, step: u64) -> Result<Series, String> {
+        if step == 0 || from > to {
+            return Err("need from <= to and step > 0".to_string());
+        }
+        let start = from - from % step;
+        let count = (to - start) / step + 1;
+        if count > MAX_QUERY_POINTS {
+            return Err(format!("{} points requested, limit {}", count, MAX_QUERY_POINTS));
+        }
+        let now = self.counters().latest;
+
+        // Each point reads the finest resolution still covering its start.
+        let chosen: Vec<Option<Resolution>> = (0..count)
+            .map(|i| {
+                let t = start + i * step;
+                Resolution::ALL.into_iter()
+                    .find(|r| now.saturating_sub(self.config.retention(*r)) <= t)
+            })
+            .collect();
+        let mut aggs: Vec<Option<Aggregate>> = vec![None; count as usize];
+        let mut resolutions = Vec::new();
+
+        for resolution in Resolution::ALL {
+            let Some(first) = chosen.iter().position(|c| *c == Some(resolution)) else { continue };
+            let last = chosen.iter().rposition(|c| *c == Some(resolution)).unwrap_or(first);
+            resolutions.push(resolution);
+            let lo = start + first as u64 * step;
+            let hi = start + (last as u64 + 1) * step;
+            let from_key = bucket_key(resolution, lo, "");
+            for item in self.db.iterator(IteratorMode::From(&from_key, Direction::Forward)) {
+                let (key, value) = item.map_err(|e| e.to_string())?;
+                let Some((r, bucket, name)) = parse_key(&key) else { break };
+                if r != resolution || bucket >= hi {
+                    break;
+                }
+                if name != metric.as_bytes() {
+                    continue;
+                }
+                let idx = ((bucket - start) / step) as usize;
+                if chosen[idx] != Some(resolution) {
+                    continue;
+                }
+                if let Some(agg) = Aggregate::decode(&value) {
+                    match &mut aggs[idx] {
+                        Some(acc) => acc.merge(&agg),
+                        slot => *slot = Some(agg),
+                    }
+                }
+            }
+        }
+
+        let points = aggs.into_iter().enumerate().map(|(i, agg)| SeriesPoint {
+            t:       start + i as u64 * step,
+            min:     agg.map(|a| a.min),
+            max:     agg.map(|a| a.max),
+            avg:     agg.map(|a| a.avg()),
+            samples: agg.map_or(0, |a| a.count),
+        }).collect();
+        Ok(Series { metric: metric.to_string(), from, to, step, resolutions, points })
+    }
+
+    /// Metric names with any history.
+    pub fn metrics(&self) -> Vec<String> {
+        self.counters().metrics.iter().cloned().collect()
+    }
+
+    /// Time of the newest sample.
+    pub fn latest(&self) -> u64 {
+        self.counters().latest
+    }
+
+    pub fn usage(&self) -> HistoryUsage {
+        let counters = self.counters();
+        HistoryUsage {
+            bytes:     counters.bytes,
+            max_bytes: self.config.max_bytes,
+            entries:   Resolution::ALL.iter().map(|r| (*r, counters.entries[r.tag() as usize])).collect(),
+            evicted:   counters.evicted,
+        }
+    }
+
+    /// Record a snapshot from `source` every `sample_interval_secs` until
+    /// the task is aborted.
+    pub fn spawn(
+        self: Arc<Self>,
+        source: Arc<dyn Fn() -> TelemetrySnapshot + Send + Sync>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(self.config.sample_interval_secs));
+            loop {
+                interval.tick().await;
+                if let Err(e) = self.record_snapshot(&source()) {
+                    log::warn!("Telemetry history sample failed: {}", e);
+                }
+            }
+        })
+    }
+}
+
+/// Parse a span such as `90s`, `15m`, `1h` or `7d` into seconds.
+pub fn parse_span(span: &str) -> Option<u64> {
+    let span = span.trim();
+    let unit = match span.chars().last()? {
+        's' => 1,
+        'm' => 60,
+        'h' => 3_600,
+        'd' => 86_400,
+        _ => return None,
+    };
+    let n: u64 = span[..span.len() - 1].parse().ok()?;
+    n.checked_mul(unit).filter(|s| *s > 0)
+}
+
+/// One block character per point, scaled between the series' min and max
+/// average; empty points are blank.
+pub fn sparkline(points: &[SeriesPoint]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let values: Vec<f64> = points.iter().filter_map(|p| p.avg).collect();
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    points.iter().map(|p| match p.avg {
+        None => ' ',
+        Some(_) if hi <= lo => BARS[0],
+        Some(v) => BARS[(((v - lo) / (hi - lo)) * (BARS.len() - 1) as f64).round() as usize],
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start of the simulated run, on a ten-minute boundary.
+    const T0: u64 = 1_200_000;
+
+    fn open(dir: &tempfile::TempDir, config: HistoryConfig) -> TelemetryHistory {
+        TelemetryHistory::open(dir.path(), config).unwrap()
+    }
+
+    /// Sample `metric` every `every` seconds for `secs`, value = sample index.
+    fn run(history: &TelemetryHistory, metric: &str, secs: u64, every: u64) {
+        for i in 0..secs / every {
+            history.record(T0 + i * every, metric, i as f64).unwrap();
+        }
+    }
+
+    #[test]
+    fn samples_downsample_into_aggregate_buckets() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = open(&dir, HistoryConfig { minute_secs: 2 * 3_600, ..Default::default() });
+        run(&history, "chain.mempool_depth", 3 * 3_600, 10);
+        history.prune().unwrap();
+
+        // Seventy minutes in, past raw retention: samples 420..=425.
+        let s = history.query("chain.mempool_depth", T0 + 4_200, T0 + 4_259, 60).unwrap();
+        assert_eq!(s.resolutions, vec![Resolution::Minute]);
+        assert_eq!(s.points.len(), 1);
+        let p = &s.points[0];
+        assert_eq!((p.samples, p.min, p.max, p.avg), (6, Some(420.0), Some(425.0), Some(422.5)));
+
+        // The first ten minutes, past minute retention too.
+        let s = history.query("chain.mempool_depth", T0, T0 + 599, 600).unwrap();
+        assert_eq!(s.resolutions, vec![Resolution::TenMinutes]);
+        let p = &s.points[0];
+        assert_eq!((p.samples, p.min, p.max, p.avg), (60, Some(0.0), Some(59.0), Some(29.5)));
+
+        let latest = history.latest();
+        let s = history.query("chain.mempool_depth", latest - 30, latest, 10).unwrap();
+        assert_eq!(s.resolutions, vec![Resolution::Raw]);
+        assert!(s.points.iter().all(|p| p.samples == 1));
+    }
+
+    #[test]
+    fn queries_stitch_across_resolution_boundaries() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = open(&dir, HistoryConfig::default());
+        run(&history, "m", 3 * 3_600, 10);
+        history.prune().unwrap();
+
+        let latest = history.latest();
+        let s = history.query("m", latest - 2 * 3_600, latest - 60, 60).unwrap();
+        assert_eq!(s.resolutions, vec![Resolution::Raw, Resolution::Minute]);
+        // Every minute has its six samples exactly once, and averages climb
+        // by six per minute straight through the raw/minute boundary.
+        assert!(s.points.iter().all(|p| p.samples == 6), "{:?}", s.points);
+        for w in s.points.windows(2) {
+            assert_eq!(w[1].avg.unwrap() - w[0].avg.unwrap(), 6.0);
+        }
+    }
+
+    #[test]
+    fn retention_deletes_expired_buckets() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HistoryConfig { raw_secs: 60, minute_secs: 600, ten_minute_secs: 1_800, ..Default::default() };
+        let history = open(&dir, config);
+        run(&history, "m", 2 * 3_600, 10);
+        assert!(history.prune().unwrap() > 0);
+
+        let usage = history.usage();
+        assert!(usage.entries[&Resolution::Raw] <= 7, "{:?}", usage);
+        assert!(usage.entries[&Resolution::Minute] <= 11, "{:?}", usage);
+        assert!(usage.entries[&Resolution::TenMinutes] <= 4, "{:?}", usage);
+        let s = history.query("m", T0, T0 + 599, 600).unwrap();
+        assert!(s.points.iter().all(|p| p.samples == 0));
+
+        drop(history);
+        assert_eq!(open(&dir, config).usage(), usage);
+    }
+
+    #[test]
+    fn storage_stays_within_bound_over_long_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = HistoryConfig { max_bytes: 16 * 1024, ..Default::default() };
+        let history = open(&dir, config);
+        for hour in 0..72 {
+            for i in 0..60 {
+                let at = T0 + hour * 3_600 + i * 60;
+                for metric in ["chain.height", "network.peer_count", "chain.block_time_secs"] {
+                    history.record(at, metric, (hour * 60 + i) as f64).unwrap();
+                }
+            }
+            history.prune().unwrap();
+            assert!(history.usage().bytes <= config.max_bytes);
+        }
+        let usage = history.usage();
+        assert!(usage.evicted > 0);
+        // The newest data survives eviction.
+        let latest = history.latest();
+        assert_eq!(history.query("chain.height", latest, latest, 60).unwrap().points[0].samples, 1);
+
+        drop(history);
+        assert_eq!(open(&dir, config).usage().bytes, usage.bytes);
+    }
+
+    #[test]
+    fn spans_and_sparklines() {
+        assert_eq!(parse_span("1h"), Some(3_600));
+        assert_eq!(parse_span("15m"), Some(900));
+        assert_eq!(parse_span("0h"), None);
+        assert_eq!(parse_span("h"), None);
+
+        let point = |avg: Option<f64>| SeriesPoint { t: 0, min: avg, max: avg, avg, samples: avg.is_some() as u64 };
+        assert_eq!(sparkline(&[point(Some(0.0)), point(None), point(Some(7.0))]), "▁ █");
+    }
+}
@@ -5,6 +5,8 @@
 //! ## Exposed modules
 //! - `metrics` — counters, gauges, histograms
 //! - `load_balancer` — shard/validator load tracking
+//! - `export` — signed, privacy-filtered pushes to external dashboards
+//! - `history` — downsampled metric history in RocksDB
 //!
 //! ## Excluded from MVP build
 //! - `energy_module` — requires `tch` (LibTorch); enabled via `ml` feature in Phase 4.
@@ -12,6 +14,14 @@
 
 pub mod metrics;
 pub mod load_balancer;
+pub mod export;
+pub mod history;
+
+pub use export::{
+    ExportProfile, ExportSigner, MetricGroup, SignedExport, TelemetryExportConfig,
+    TelemetryExporter, TelemetrySnapshot,
+};
+pub use history::{HistoryConfig, HistoryUsage, Resolution, Series, SeriesPoint, TelemetryHistory};
 
 /// Initialise telemetry subsystem.
 /// Returns immediately; actual metric collection is driven by the scheduler.
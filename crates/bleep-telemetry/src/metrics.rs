@@ -82,11 +82,74 @@ impl MetricGauge {
     }
 }
 
+/// Metric histogram counting observations into fixed buckets
+#[derive(Debug, Clone)]
+pub struct MetricHistogram {
+    name: String,
+    /// Inclusive upper bound of each bucket; a final bucket takes the rest.
+    bounds: Vec<u64>,
+    state: Arc<Mutex<HistogramState>>,
+}
+
+#[derive(Debug, Default)]
+struct HistogramState {
+    buckets: Vec<u64>,
+    sum: u64,
+    count: u64,
+}
+
+impl MetricHistogram {
+    /// Create a new histogram with the given bucket upper bounds
+    pub fn new(name: &str, bounds: &[u64]) -> Self {
+        let mut bounds = bounds.to_vec();
+        bounds.sort_unstable();
+        bounds.dedup();
+        let buckets = vec![0; bounds.len() + 1];
+        Self {
+            name: name.to_string(),
+            bounds,
+            state: Arc::new(Mutex::new(HistogramState { buckets, ..Default::default() })),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn bounds(&self) -> &[u64] {
+        &self.bounds
+    }
+
+    /// Record one observation
+    pub fn observe(&self, value: u64) {
+        let idx = self.bounds.partition_point(|b| *b < value);
+        if let Ok(mut st) = self.state.lock() {
+            st.buckets[idx] += 1;
+            st.sum = st.sum.saturating_add(value);
+            st.count += 1;
+        }
+    }
+
+    /// Observations per bucket, the overflow bucket last
+    pub fn buckets(&self) -> Vec<u64> {
+        self.state.lock().map(|st| st.buckets.clone()).unwrap_or_default()
+    }
+
+    pub fn count(&self) -> u64 {
+        self.state.lock().map(|st| st.count).unwrap_or(0)
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.state.lock().map(|st| st.sum).unwrap_or(0)
+    }
+}
+
 /// Metrics registry for collecting and reporting metrics
 #[derive(Debug)]
 pub struct MetricsRegistry {
     counters: HashMap<String, MetricCounter>,
     gauges: HashMap<String, MetricGauge>,
+    histograms: HashMap<String, MetricHistogram>,
 }
 
 impl MetricsRegistry {
@@ -95,6 +158,7 @@ impl MetricsRegistry {
         Self {
             counters: HashMap::new(),
             gauges: HashMap::new(),
+            histograms: HashMap::new(),
         }
     }
     
@@ -111,6 +175,13 @@ impl MetricsRegistry {
         self.gauges.insert(name.to_string(), gauge.clone());
         gauge
     }
+
+    /// Register a histogram metric
+    pub fn histogram(&mut self, name: &str, bounds: &[u64]) -> MetricHistogram {
+        let histogram = MetricHistogram::new(name, bounds);
+        self.histograms.insert(name.to_string(), histogram.clone());
+        histogram
+    }
 }
 
 impl Default for MetricsRegistry {
@@ -0,0 +1,78 @@
+//! Warm (pooled) vs cold instantiation of a small WASM contract.
+//!
+//!     cargo bench -p bleep-vm --bench wasm_pool
+
+use std::sync::Arc;
+
+use bleep_vm::engines::wasm_engine::WasmRuntime;
+use bleep_vm::runtime::PoolConfig;
+use bleep_vm::GasSchedule;
+use criterion::{criterion_group, criterion_main, Criterion};
+use wasm_encoder::{
+    CodeSection, ConstExpr, DataSection, ExportKind, ExportSection, Function, FunctionSection,
+    Instruction, MemArg, MemorySection, MemoryType, Module, TypeSection, ValType,
+};
+
+/// `call_contract() -> i32` summing a 1 KiB data segment, over two pages of memory.
+fn contract() -> Vec<u8> {
+    let mut types = TypeSection::new();
+    types.function([] as [ValType; 0], [ValType::I32]);
+    let mut funcs = FunctionSection::new();
+    funcs.function(0);
+    let mut memory = MemorySection::new();
+    memory.memory(MemoryType { minimum: 2, maximum: None, memory64: false, shared: false });
+    let mut exports = ExportSection::new();
+    exports.export("call_contract", ExportKind::Func, 0);
+    exports.export("memory", ExportKind::Memory, 0);
+
+    // locals: 0 = offset, 1 = sum
+    let mut body = Function::new([(2, ValType::I32)]);
+    for ins in [
+        Instruction::Block(wasm_encoder::BlockType::Empty),
+        Instruction::Loop(wasm_encoder::BlockType::Empty),
+        Instruction::LocalGet(0), Instruction::I32Const(1024), Instruction::I32GeU, Instruction::BrIf(1),
+        Instruction::LocalGet(1), Instruction::LocalGet(0),
+        Instruction::I32Load(MemArg { offset: 4096, align: 2, memory_index: 0 }),
+        Instruction::I32Add, Instruction::LocalSet(1),
+        Instruction::LocalGet(0), Instruction::I32Const(4), Instruction::I32Add, Instruction::LocalSet(0),
+        Instruction::Br(0),
+        Instruction::End,
+        Instruction::End,
+        Instruction::LocalGet(1),
+        Instruction::End,
+    ] {
+        body.instruction(&ins);
+    }
+    let mut code = CodeSection::new();
+    code.function(&body);
+    let mut data = DataSection::new();
+    data.active(0, &ConstExpr::i32_const(4096), (0..1024).map(|i| i as u8));
+
+    let mut module = Module::new();
+    module.section(&types).section(&funcs).section(&memory)
+        .section(&exports).section(&code).section(&data);
+    module.finish()
+}
+
+fn warm_vs_cold(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let code = contract();
+    let schedule = Arc::new(GasSchedule::default());
+
+    let mut group = c.benchmark_group("wasm_pool");
+    for (name, runtime) in [
+        ("warm", WasmRuntime::new()),
+        ("cold", WasmRuntime::new().with_pool_config(PoolConfig { per_contract: 0, ..PoolConfig::default() })),
+    ] {
+        group.bench_function(name, |b| {
+            b.to_async(&rt).iter(|| async {
+                let out = runtime.execute(&code, 1_000_000, schedule.clone(), &[], None).await.unwrap();
+                assert!(out.success);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, warm_vs_cold);
+criterion_main!(benches);
@@ -3,12 +3,17 @@
 //! Responsibilities:
 //!   1. Compile WASM bytecode with Wasmer Cranelift (JIT).
 //!   2. Inject a metered host function: every call, deduct gas from a
-//!      shared `GasMeter`. On exhaustion, the host function signals OOG.
+//!      shared `GasMeter`. On exhaustion, the host function traps, which
+//!      halts the instance at once and surfaces as `VmError::GasExhausted`.
 //!   3. Provide the full set of BLEEP host imports (storage, crypto, logging).
 //!   4. Write call data into WASM linear memory before execution.
 //!   5. Read back the return value from WASM after execution.
-//!   6. Cache compiled modules (keyed by bytecode hash) using a bounded LRU.
-//!   7. Enforce execution timeout via `tokio::time::timeout`.
+//!   6. Cache compiled modules (keyed by bytecode hash) using a bounded LRU,
+//!      and keep warm instances in an `InstancePool`.
+//!   7. Enforce execution timeout via `tokio::time::timeout`, and cap
+//!      linear-memory growth with `cap_memory` when a module is compiled;
+//!      both are set with `WasmRuntime::builder` and can be overridden per
+//!      call through `execute_with_limits`.
 //!   8. Translate Wasmer traps into typed `VmError`.
 
 use std::num::NonZeroUsize;
@@ -20,12 +25,13 @@ use parking_lot::Mutex;
 use sha2::{Digest, Sha256};
 use tracing::{debug, info, warn};
 use wasmer::{
-    imports, Function, FunctionEnv, FunctionEnvMut, Instance, Module, Store, Value,
+    imports, Function, FunctionEnv, FunctionEnvMut, Instance, Module, RuntimeError, Store, Value,
 };
 
 // Correct import paths — these live in the runtime sub-modules
 use crate::runtime::gas_model_base::GasMeter;
-use crate::runtime::memory::MemoryLimit;
+use crate::runtime::instance_pool::{prepare_module, InstancePool, PoolConfig, PoolStats, PooledInstance};
+use crate::runtime::memory::{cap_memory, MemoryLimit, WASM_PAGE_SIZE};
 use crate::runtime::sandbox::SecurityPolicy;
 use crate::error::{VmError, VmResult};
 use crate::types::GasSchedule;
@@ -41,6 +47,8 @@ struct CachedModule {
     hit_count:  u64,
     #[allow(dead_code)]
     first_seen: Instant,
+    /// Set for modules compiled through `get_or_compile_pooled`.
+    poolable:   bool,
 }
 
 pub struct ModuleCache {
@@ -65,17 +73,67 @@ impl ModuleCache {
         bytecode: &[u8],
         store: &Store,
     ) -> VmResult<Module> {
-        let key = Self::hash(bytecode);
+        self.lookup_or_compile(Self::hash(bytecode), bytecode, store, |b| Ok((b.to_vec(), false)))
+            .map(|(module, _)| module)
+    }
+
+    /// Compile `bytecode` after `prepare_module`, returning whether its
+    /// instances may be pooled.  Cached apart from `get_or_compile`.
+    pub fn get_or_compile_pooled(
+        &self,
+        bytecode: &[u8],
+        store: &Store,
+    ) -> VmResult<(Module, bool)> {
+        let key: [u8; 32] = Sha256::new()
+            .chain_update(b"pooled:")
+            .chain_update(bytecode)
+            .finalize()
+            .into();
+        self.lookup_or_compile(key, bytecode, store, prepare_module)
+    }
+
+    /// Key of `bytecode` compiled with its memories capped at `max_pages`.
+    pub fn capped_hash(bytecode: &[u8], max_pages: u32) -> [u8; 32] {
+        Sha256::new()
+            .chain_update(b"capped:")
+            .chain_update(max_pages.to_le_bytes())
+            .chain_update(bytecode)
+            .finalize()
+            .into()
+    }
+
+    /// `get_or_compile_pooled` with every memory of `bytecode` capped at
+    /// `max_pages` by `cap_memory`.  The cap is applied once, when the
+    /// module is compiled; each cap is cached apart.
+    pub fn get_or_compile_capped(
+        &self,
+        bytecode:  &[u8],
+        max_pages: u32,
+        store:     &Store,
+    ) -> VmResult<(Module, bool)> {
+        self.lookup_or_compile(Self::capped_hash(bytecode, max_pages), bytecode, store, |b| {
+            prepare_module(&cap_memory(b, max_pages)?)
+        })
+    }
+
+    fn lookup_or_compile(
+        &self,
+        key:      [u8; 32],
+        bytecode: &[u8],
+        store:    &Store,
+        prepare:  impl FnOnce(&[u8]) -> VmResult<(Vec<u8>, bool)>,
+    ) -> VmResult<(Module, bool)> {
         {
             let mut cache = self.inner.lock();
             if let Some(entry) = cache.get_mut(&key) {
                 entry.hit_count += 1;
                 debug!(hits = entry.hit_count, "Module cache hit");
-                return Ok(entry.module.clone());
+                return Ok((entry.module.clone(), entry.poolable));
             }
         }
         // Compile outside the lock — compilation can be slow
-        let module = Module::new(store, bytecode)
+        let (prepared, poolable) = prepare(bytecode)?;
+        let module = Module::new(store, prepared)
             .map_err(|e| VmError::WasmCompile(e.to_string()))?;
         {
             let mut cache = self.inner.lock();
@@ -83,10 +141,11 @@ impl ModuleCache {
                 module: module.clone(),
                 hit_count: 0,
                 first_seen: Instant::now(),
+                poolable,
             });
         }
         info!(bytes = bytecode.len(), "Module compiled and cached");
-        Ok(module)
+        Ok((module, poolable))
     }
 
     pub fn cached_count(&self) -> usize {
@@ -121,45 +180,42 @@ impl HostEnv {
 // HOST FUNCTIONS
 // ─────────────────────────────────────────────────────────────────────────────
 
-fn host_gas_charge(env: FunctionEnvMut<HostEnv>, amount: i64) {
+/// Flag exhaustion for `WasmRuntime::run` and build the trap that unwinds
+/// the contract — without it a loop keeps running until the timeout.
+fn out_of_gas(data: &HostEnv) -> RuntimeError {
+    *data.gas_exhausted.lock() = true;
+    RuntimeError::new("out of gas")
+}
+
+fn host_gas_charge(env: FunctionEnvMut<HostEnv>, amount: i64) -> Result<(), RuntimeError> {
     let data = env.data();
-    let mut meter = data.gas_meter.lock();
-    if meter.charge(amount.max(0) as u64).is_err() {
-        *data.gas_exhausted.lock() = true;
-    }
+    let charged = data.gas_meter.lock().charge(amount.max(0) as u64);
+    charged.map_err(|_| out_of_gas(data))
 }
 
 fn host_storage_write(
     env: FunctionEnvMut<HostEnv>,
     key_ptr: i32, key_len: i32,
     val_ptr: i32, val_len: i32,
-) {
+) -> Result<(), RuntimeError> {
     let data = env.data();
     let write_len = (key_len + val_len).max(0) as usize;
-    {
-        let mut meter = data.gas_meter.lock();
-        if meter.charge_storage_write(write_len).is_err() {
-            *data.gas_exhausted.lock() = true;
-            return;
-        }
-    }
+    let charged = data.gas_meter.lock().charge_storage_write(write_len);
+    charged.map_err(|_| out_of_gas(data))?;
     // Record the write intent with pointer metadata; actual memory read
     // happens in the WASM runtime layer via the exported memory handle.
     let key = format!("ptr:{key_ptr}:len:{key_len}").into_bytes();
     let val = format!("ptr:{val_ptr}:len:{val_len}").into_bytes();
     data.state_writes.lock().push((key, val));
+    Ok(())
 }
 
-fn host_log(env: FunctionEnvMut<HostEnv>, msg_ptr: i32, msg_len: i32) {
+fn host_log(env: FunctionEnvMut<HostEnv>, msg_ptr: i32, msg_len: i32) -> Result<(), RuntimeError> {
     let data = env.data();
-    {
-        let mut meter = data.gas_meter.lock();
-        if meter.charge_log(msg_len.max(0) as usize).is_err() {
-            *data.gas_exhausted.lock() = true;
-            return;
-        }
-    }
+    let charged = data.gas_meter.lock().charge_log(msg_len.max(0) as usize);
+    charged.map_err(|_| out_of_gas(data))?;
     data.logs.lock().push(format!("[bleep::log] ptr={msg_ptr} len={msg_len}"));
+    Ok(())
 }
 
 fn host_abort(_env: FunctionEnvMut<HostEnv>, _code: i32) {
@@ -186,8 +242,18 @@ pub struct RawExecutionOutput {
 // WASM RUNTIME
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Resource caps for one call.  `WasmRuntime::limits` gives the runtime's
+/// defaults; `execute_with_limits` takes an override, e.g. for contracts
+/// governance has approved for larger budgets.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutionLimits {
+    pub timeout: Duration,
+    pub memory:  MemoryLimit,
+}
+
 pub struct WasmRuntime {
     module_cache:    Arc<ModuleCache>,
+    pool:            Arc<InstancePool<HostEnv>>,
     security_policy: SecurityPolicy,
     mem_limit:       MemoryLimit,
     timeout:         Duration,
@@ -197,12 +263,19 @@ impl WasmRuntime {
     pub fn new() -> Self {
         WasmRuntime {
             module_cache:    ModuleCache::new(256),
+            pool:            Arc::new(InstancePool::new(PoolConfig::default())),
             security_policy: SecurityPolicy::default(),
             mem_limit:       MemoryLimit::default(),
             timeout:         Duration::from_secs(10),
         }
     }
 
+    /// Runtime with its timeout, memory cap and cache size chosen by the
+    /// caller, e.g. per deployment.
+    pub fn builder() -> WasmRuntimeBuilder {
+        WasmRuntimeBuilder::default()
+    }
+
     pub fn with_policy(mut self, policy: SecurityPolicy) -> Self {
         self.timeout = policy.timeout;
         self.security_policy = policy;
@@ -214,6 +287,16 @@ impl WasmRuntime {
         self
     }
 
+    pub fn with_pool_config(mut self, config: PoolConfig) -> Self {
+        self.pool = Arc::new(InstancePool::new(config));
+        self
+    }
+
+    /// Limits `execute` applies to every call.
+    pub fn limits(&self) -> ExecutionLimits {
+        ExecutionLimits { timeout: self.timeout, memory: self.mem_limit }
+    }
+
     // ── Public entry point ────────────────────────────────────────────────────
 
     pub async fn execute(
@@ -223,17 +306,35 @@ impl WasmRuntime {
         schedule:  Arc<GasSchedule>,
         call_data: &[u8],
         entry_fn:  Option<&str>,
+    ) -> VmResult<RawExecutionOutput> {
+        self.execute_with_limits(bytecode, gas_limit, schedule, call_data, entry_fn, self.limits()).await
+    }
+
+    /// `execute` under `limits` instead of the runtime's own.  Instances
+    /// and compiled modules are keyed by the memory cap, so calls under
+    /// different limits never share them.
+    pub async fn execute_with_limits(
+        &self,
+        bytecode:  &[u8],
+        gas_limit: u64,
+        schedule:  Arc<GasSchedule>,
+        call_data: &[u8],
+        entry_fn:  Option<&str>,
+        limits:    ExecutionLimits,
     ) -> VmResult<RawExecutionOutput> {
         // Validate before spawning
         self.security_policy.validate(bytecode)?;
 
         let module_cache = self.module_cache.clone();
-        let mem_limit    = self.mem_limit;
-        let timeout      = self.timeout;
+        let pool         = self.pool.clone();
+        let mem_limit    = limits.memory;
+        let timeout      = limits.timeout;
         let bytecode     = bytecode.to_vec();
         let call_data    = call_data.to_vec();
         let entry_fn     = entry_fn.map(|s| s.to_string());
 
+        metrics::increment_counter!("wasm_executions");
+        let started = Instant::now();
         let result = tokio::time::timeout(
             timeout,
             tokio::task::spawn_blocking(move || {
@@ -244,6 +345,7 @@ impl WasmRuntime {
                     &call_data,
                     entry_fn.as_deref(),
                     module_cache,
+                    pool,
                     mem_limit,
                 )
             }),
@@ -251,12 +353,14 @@ impl WasmRuntime {
         .await
         .map_err(|_| VmError::Timeout { millis: timeout.as_millis() as u64 })?
         .map_err(|e| VmError::Internal(e.to_string()))??;
+        metrics::histogram!("wasm_execution_seconds", started.elapsed().as_secs_f64());
 
         Ok(result)
     }
 
     // ── Synchronous core ─────────────────────────────────────────────────────
 
+    #[allow(clippy::too_many_arguments)]
     fn execute_sync(
         bytecode:     &[u8],
         gas_limit:    u64,
@@ -264,14 +368,11 @@ impl WasmRuntime {
         call_data:    &[u8],
         entry_fn:     Option<&str>,
         module_cache: Arc<ModuleCache>,
+        pool:         Arc<InstancePool<HostEnv>>,
         mem_limit:    MemoryLimit,
     ) -> VmResult<RawExecutionOutput> {
         let start = Instant::now();
 
-        let mut store = Store::default();
-
-        let module = module_cache.get_or_compile(bytecode, &store)?;
-
         let gas_meter = Arc::new(Mutex::new(
             GasMeter::new(gas_limit, schedule)?,
         ));
@@ -279,7 +380,32 @@ impl WasmRuntime {
         // Charge for calldata upfront
         gas_meter.lock().charge_calldata(call_data.len())?;
 
-        let host_env = HostEnv::new(Arc::clone(&gas_meter));
+        let code_hash = ModuleCache::capped_hash(bytecode, mem_limit.max_pages);
+        let mut pooled = pool.checkout(code_hash, || {
+            Self::instantiate(bytecode, mem_limit.max_pages, code_hash, &module_cache, Arc::clone(&gas_meter))
+        })?;
+        // Warm instances still carry the previous caller's host state.
+        *pooled.env.as_mut(&mut pooled.store) = HostEnv::new(Arc::clone(&gas_meter));
+
+        let result = Self::run(&mut pooled, &gas_meter, call_data, entry_fn, mem_limit, start);
+        pool.checkin(pooled);
+        result
+    }
+
+    /// Cold path: build a store, host imports and instance for `bytecode`
+    /// with its memories capped at `max_pages`.
+    fn instantiate(
+        bytecode:     &[u8],
+        max_pages:    u32,
+        code_hash:    [u8; 32],
+        module_cache: &ModuleCache,
+        gas_meter:    Arc<Mutex<GasMeter>>,
+    ) -> VmResult<PooledInstance<HostEnv>> {
+        let mut store = Store::default();
+
+        let (module, poolable) = module_cache.get_or_compile_capped(bytecode, max_pages, &store)?;
+
+        let host_env = HostEnv::new(gas_meter);
         let env = FunctionEnv::new(&mut store, host_env);
 
         let gas_fn = Function::new_typed_with_env(&mut store, &env, host_gas_charge);
@@ -309,9 +435,22 @@ impl WasmRuntime {
         let instance = Instance::new(&mut store, &module, &import_object)
             .map_err(|e| VmError::WasmInstantiation(e.to_string()))?;
 
+        PooledInstance::new(store, instance, env, code_hash, poolable)
+    }
+
+    fn run(
+        pooled:    &mut PooledInstance<HostEnv>,
+        gas_meter: &Mutex<GasMeter>,
+        call_data: &[u8],
+        entry_fn:  Option<&str>,
+        mem_limit: MemoryLimit,
+        start:     Instant,
+    ) -> VmResult<RawExecutionOutput> {
+        let PooledInstance { store, instance, env, .. } = pooled;
+
         // Write call_data into linear memory at offset 0 if the export exists
         let memory_peak = if let Ok(mem) = instance.exports.get_memory("memory") {
-            let view      = mem.view(&store);
+            let view      = mem.view(&*store);
             let capacity  = view.data_size() as usize;
             let to_write  = call_data.len().min(capacity);
             if to_write > 0 {
@@ -329,7 +468,7 @@ impl WasmRuntime {
         let (success, return_data, revert_reason) = match instance.exports.get_function(fn_name) {
             Ok(func) => {
                 gas_meter.lock().charge_cross_call()?;
-                match func.call(&mut store, &[]) {
+                match func.call(&mut *store, &[]) {
                     Ok(results) => {
                         let data = Self::extract_return_data(results);
                         (true, data, None)
@@ -347,15 +486,20 @@ impl WasmRuntime {
             }
         };
 
-        let gas_exhausted = *env.as_ref(&store).gas_exhausted.lock();
-        if gas_exhausted {
+        // Memory never shrinks, so its size after the call is the peak
+        let memory_peak = instance.exports.get_memory("memory")
+            .map(|mem| mem.view(&*store).data_size() as usize)
+            .unwrap_or(memory_peak);
+
+        let host = env.as_ref(&*store);
+        if *host.gas_exhausted.lock() {
             let used  = gas_meter.lock().used();
             let limit = gas_meter.lock().limit();
             return Err(VmError::GasExhausted { used, limit });
         }
 
-        let state_writes = env.as_ref(&store).state_writes.lock().clone();
-        let logs         = env.as_ref(&store).logs.lock().clone();
+        let state_writes = host.state_writes.lock().clone();
+        let logs         = host.logs.lock().clone();
         let gas_used     = gas_meter.lock().used();
 
         Ok(RawExecutionOutput {
@@ -384,12 +528,73 @@ impl WasmRuntime {
     pub fn cached_modules(&self) -> usize {
         self.module_cache.cached_count()
     }
+
+    pub fn pool_stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
 }
 
 impl Default for WasmRuntime {
     fn default() -> Self { Self::new() }
 }
 
+/// Builder returned by `WasmRuntime::builder`; unset fields keep the
+/// defaults of `WasmRuntime::new`.
+#[derive(Debug, Default)]
+pub struct WasmRuntimeBuilder {
+    timeout:    Option<Duration>,
+    max_pages:  Option<u32>,
+    cache_size: Option<usize>,
+}
+
+impl WasmRuntimeBuilder {
+    /// Wall-clock budget of one call.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Largest linear memory a contract may grow to, rounded up to whole
+    /// pages.
+    pub fn max_memory_bytes(mut self, bytes: u64) -> Self {
+        let pages = bytes.div_ceil(WASM_PAGE_SIZE as u64);
+        self.max_pages = Some(u32::try_from(pages).unwrap_or(u32::MAX));
+        self
+    }
+
+    /// Largest linear memory a contract may grow to, in 64 KiB pages.
+    pub fn max_pages(mut self, pages: u32) -> Self {
+        self.max_pages = Some(pages);
+        self
+    }
+
+    /// Compiled modules kept in the LRU cache.
+    pub fn cache_size(mut self, capacity: usize) -> Self {
+        self.cache_size = Some(capacity);
+        self
+    }
+
+    /// Fails on a memory cap above `HARD_MAX_PAGES` or below the initial
+    /// grant, and on a cache size of 0.
+    pub fn build(self) -> VmResult<WasmRuntime> {
+        let mut runtime = WasmRuntime::new();
+        if let Some(timeout) = self.timeout {
+            runtime.timeout = timeout;
+        }
+        if let Some(max_pages) = self.max_pages {
+            let initial = runtime.mem_limit.initial_pages.min(max_pages);
+            runtime.mem_limit = MemoryLimit::new(initial, max_pages)?;
+        }
+        if let Some(capacity) = self.cache_size {
+            if capacity == 0 {
+                return Err(VmError::Internal("module cache size must be at least 1".into()));
+            }
+            runtime.module_cache = ModuleCache::new(capacity);
+        }
+        Ok(runtime)
+    }
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // TESTS
 // ─────────────────────────────────────────────────────────────────────────────
@@ -477,6 +682,155 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// `call_contract` loops forever, charging 1 000 gas per iteration
+    /// through `bleep.gas_charge`.
+    fn burner_wasm() -> Vec<u8> {
+        use wasm_encoder::{
+            BlockType, CodeSection, EntityType, ExportKind, ExportSection, Function as Body,
+            FunctionSection, ImportSection, Instruction, Module as Encoder, TypeSection, ValType,
+        };
+        let mut types = TypeSection::new();
+        types.function([ValType::I64], [] as [ValType; 0]);
+        types.function([] as [ValType; 0], [] as [ValType; 0]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", "gas_charge", EntityType::Function(0));
+        let mut funcs = FunctionSection::new();
+        funcs.function(1);
+        let mut exports = ExportSection::new();
+        exports.export("call_contract", ExportKind::Func, 1);
+        let mut body = Body::new([]);
+        for ins in [
+            Instruction::Loop(BlockType::Empty),
+            Instruction::I64Const(1_000), Instruction::Call(0),
+            Instruction::Br(0),
+            Instruction::End, Instruction::End,
+        ] {
+            body.instruction(&ins);
+        }
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut module = Encoder::new();
+        module.section(&types).section(&imports).section(&funcs).section(&exports).section(&code);
+        module.finish()
+    }
+
+    #[tokio::test]
+    async fn test_gas_exhaustion_halts_infinite_loop() {
+        let runtime = WasmRuntime::new();
+        let start = Instant::now();
+        let result = runtime.execute(&burner_wasm(), 100_000, default_schedule(), &[], None).await;
+        match result {
+            Err(VmError::GasExhausted { used, limit }) => {
+                assert_eq!(limit, 100_000);
+                assert!(used <= limit);
+            }
+            other => panic!("expected GasExhausted, got {other:?}"),
+        }
+        // Halted by the meter, not by the 10 s timeout
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    /// `call_contract() -> i32` returns `memory.grow(pages)` on a one-page
+    /// memory with no declared maximum.
+    fn grower_wasm(pages: i32) -> Vec<u8> {
+        use wasm_encoder::{
+            CodeSection, ExportKind, ExportSection, Function as Body, FunctionSection, Instruction,
+            MemorySection, MemoryType, Module as Encoder, TypeSection, ValType,
+        };
+        let mut types = TypeSection::new();
+        types.function([] as [ValType; 0], [ValType::I32]);
+        let mut funcs = FunctionSection::new();
+        funcs.function(0);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("call_contract", ExportKind::Func, 0);
+        exports.export("memory", ExportKind::Memory, 0);
+        let mut body = Body::new([]);
+        for ins in [Instruction::I32Const(pages), Instruction::MemoryGrow(0), Instruction::End] {
+            body.instruction(&ins);
+        }
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut module = Encoder::new();
+        module.section(&types).section(&funcs).section(&memory).section(&exports).section(&code);
+        module.finish()
+    }
+
+    /// `call_contract` counts down from 10^9 without touching the host, so
+    /// only the timeout can stop it early.
+    fn spinner_wasm() -> Vec<u8> {
+        use wasm_encoder::{
+            BlockType, CodeSection, ExportKind, ExportSection, Function as Body, FunctionSection,
+            Instruction, Module as Encoder, TypeSection, ValType,
+        };
+        let mut types = TypeSection::new();
+        types.function([] as [ValType; 0], [] as [ValType; 0]);
+        let mut funcs = FunctionSection::new();
+        funcs.function(0);
+        let mut exports = ExportSection::new();
+        exports.export("call_contract", ExportKind::Func, 0);
+        let mut body = Body::new([(1, ValType::I32)]);
+        for ins in [
+            Instruction::I32Const(1_000_000_000), Instruction::LocalSet(0),
+            Instruction::Loop(BlockType::Empty),
+            Instruction::LocalGet(0), Instruction::I32Const(1), Instruction::I32Sub,
+            Instruction::LocalTee(0), Instruction::BrIf(0),
+            Instruction::End, Instruction::End,
+        ] {
+            body.instruction(&ins);
+        }
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut module = Encoder::new();
+        module.section(&types).section(&funcs).section(&exports).section(&code);
+        module.finish()
+    }
+
+    #[tokio::test]
+    async fn test_memory_growth_capped_mid_execution() {
+        let runtime = WasmRuntime::builder().max_pages(4).build().unwrap();
+        let out = runtime.execute(&grower_wasm(8), 1_000_000, default_schedule(), &[], None).await.unwrap();
+        assert_eq!(out.return_data, (-1i32).to_le_bytes().to_vec(), "grow past the cap must fail");
+        assert_eq!(out.memory_peak, WASM_PAGE_SIZE);
+
+        // A per-call override lifts the cap for this call only
+        let limits = ExecutionLimits { memory: MemoryLimit::new(1, 16).unwrap(), ..runtime.limits() };
+        let out = runtime.execute_with_limits(&grower_wasm(8), 1_000_000, default_schedule(), &[], None, limits)
+            .await.unwrap();
+        assert_eq!(out.return_data, 1i32.to_le_bytes().to_vec());
+        assert_eq!(out.memory_peak, 9 * WASM_PAGE_SIZE);
+
+        let out = runtime.execute(&grower_wasm(8), 1_000_000, default_schedule(), &[], None).await.unwrap();
+        assert_eq!(out.return_data, (-1i32).to_le_bytes().to_vec());
+        // One capped module per cap, compiled once
+        assert_eq!(runtime.cached_modules(), 2);
+    }
+
+    #[test]
+    fn test_builder_limits() {
+        let runtime = WasmRuntime::builder()
+            .timeout(Duration::from_millis(250))
+            .max_memory_bytes(3 * WASM_PAGE_SIZE as u64 + 1)
+            .cache_size(8)
+            .build()
+            .unwrap();
+        let limits = runtime.limits();
+        assert_eq!((limits.timeout, limits.memory.max_pages), (Duration::from_millis(250), 4));
+
+        assert!(WasmRuntime::builder().max_pages(crate::runtime::memory::HARD_MAX_PAGES + 1).build().is_err());
+        assert!(WasmRuntime::builder().cache_size(0).build().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_timeout_applies_per_runtime() {
+        let runtime = WasmRuntime::builder().timeout(Duration::from_millis(100)).build().unwrap();
+        let start = Instant::now();
+        let result = runtime.execute(&spinner_wasm(), 1_000_000, default_schedule(), &[], None).await;
+        assert!(matches!(result, Err(VmError::Timeout { millis: 100 })), "{result:?}");
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
     #[test]
     fn test_module_cache_stores_and_retrieves() {
         let cache = ModuleCache::new(4);
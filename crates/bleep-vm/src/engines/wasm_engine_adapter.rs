@@ -1,18 +1,28 @@
 //! WASM Engine Adapter
 //! Bridges the WasmRuntime to the Engine trait used by the VM router.
+//!
+//! Contracts read the executing block through the deterministic `env`
+//! imports `block_height`, `block_timestamp` and `random_seed` (see
+//! `runtime::determinism`).
+//!
+//! Instances are drawn from an `InstancePool`; all per-call host state
+//! lives in `TraceEnv` and is rebuilt by `TraceEnv::begin` before each call.
 
 use crate::error::{VmError, VmResult};
 use crate::execution::{
     execution_context::ExecutionContext,
     state_transition::StateDiff,
+    trace::TraceStep,
 };
 use crate::intent::TargetVm;
 use crate::router::vm_router::{Engine, EngineResult};
+use crate::engines::wasm_engine::ModuleCache;
+use crate::runtime::determinism::{wall_clock_secs, ContractEnv};
+use crate::runtime::instance_pool::{InstancePool, PoolConfig, PoolStats, PooledInstance};
 use crate::types::{ExecutionLog, LogLevel};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 use parking_lot::RwLock;
 use tracing::{debug, instrument};
@@ -22,15 +32,136 @@ type WasmStore = Arc<RwLock<HashMap<[u8; 32], Vec<u8>>>>;
 /// Production WASM execution engine adapter.
 pub struct WasmEngineAdapter {
     modules: WasmStore,
+    /// Emit `TraceStep`s for host calls, gas checkpoints and storage writes.
+    tracing: bool,
+    /// Offer the host-clock `env.timestamp` import (devnets only).
+    wall_clock: bool,
+    module_cache: Arc<ModuleCache>,
+    pool:       Arc<InstancePool<TraceEnv>>,
+}
+
+/// Host-side state for the gas, log, traced and environment imports.
+struct TraceEnv {
+    enabled:       bool,
+    memory:        Option<wasmer::Memory>,
+    steps:         Vec<TraceStep>,
+    block:         ContractEnv,
+    gas_limit:     u64,
+    gas_remaining: u64,
+    logs:          Vec<String>,
+}
+
+impl TraceEnv {
+    fn idle() -> Self {
+        TraceEnv {
+            enabled:       false,
+            memory:        None,
+            steps:         Vec::new(),
+            block:         ContractEnv { height: 0, timestamp: 0, seed: [0u8; 32] },
+            gas_limit:     0,
+            gas_remaining: 0,
+            logs:          Vec::new(),
+        }
+    }
+
+    /// Replace the previous call's state; the memory handle is kept.
+    fn begin(&mut self, enabled: bool, block: ContractEnv, gas_limit: u64) {
+        self.enabled       = enabled;
+        self.block         = block;
+        self.gas_limit     = gas_limit;
+        self.gas_remaining = gas_limit;
+        self.steps.clear();
+        self.logs.clear();
+    }
+}
+
+fn host_gas(mut env: wasmer::FunctionEnvMut<TraceEnv>, cost: i64) {
+    let data = env.data_mut();
+    data.gas_remaining = data.gas_remaining.saturating_sub(cost as u64);
+    if data.enabled {
+        let used = data.gas_limit.saturating_sub(data.gas_remaining);
+        data.steps.push(TraceStep::HostCall { name: "bleep_gas".into() });
+        data.steps.push(TraceStep::GasCheckpoint { gas_used: used });
+    }
+}
+
+fn host_log(mut env: wasmer::FunctionEnvMut<TraceEnv>, level: i32, ptr: i32, len: i32) {
+    env.data_mut().logs.push(format!("[wasm-log level={level}] ptr={ptr} len={len}"));
+}
+
+fn host_storage_write(
+    mut env: wasmer::FunctionEnvMut<TraceEnv>,
+    key_ptr: i32, key_len: i32,
+    val_ptr: i32, val_len: i32,
+) {
+    let (data, store) = env.data_and_store_mut();
+    if !data.enabled {
+        return;
+    }
+    let read = |ptr: i32, len: i32| -> Vec<u8> {
+        let mut buf = vec![0u8; len.max(0) as usize];
+        match &data.memory {
+            Some(mem) if mem.view(&store).read(ptr.max(0) as u64, &mut buf).is_ok() => buf,
+            _ => Vec::new(),
+        }
+    };
+    let key   = read(key_ptr, key_len);
+    let value = read(val_ptr, val_len);
+    data.steps.push(TraceStep::HostCall { name: "storage_write".into() });
+    data.steps.push(TraceStep::StorageWrite { key, value });
+}
+
+fn host_block_height(env: wasmer::FunctionEnvMut<TraceEnv>) -> i64 {
+    env.data().block.height as i64
+}
+
+fn host_block_timestamp(env: wasmer::FunctionEnvMut<TraceEnv>) -> i64 {
+    env.data().block.timestamp as i64
+}
+
+fn host_random_seed(mut env: wasmer::FunctionEnvMut<TraceEnv>, out_ptr: i32) -> Result<(), wasmer::RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    let memory = data.memory.as_ref()
+        .ok_or_else(|| wasmer::RuntimeError::new("contract exports no memory"))?;
+    if out_ptr < 0 {
+        return Err(wasmer::RuntimeError::new("negative pointer"));
+    }
+    memory.view(&store).write(out_ptr as u64, &data.block.seed)
+        .map_err(|e| wasmer::RuntimeError::new(e.to_string()))
 }
 
 impl WasmEngineAdapter {
     pub fn new() -> Self {
         WasmEngineAdapter {
-            modules: Arc::new(RwLock::new(HashMap::new())),
+            modules:    Arc::new(RwLock::new(HashMap::new())),
+            tracing:    false,
+            wall_clock: false,
+            module_cache: ModuleCache::new(256),
+            pool:       Arc::new(InstancePool::new(PoolConfig::default())),
         }
     }
 
+    pub fn with_pool_config(mut self, config: PoolConfig) -> Self {
+        self.pool = Arc::new(InstancePool::new(config));
+        self
+    }
+
+    pub fn pool_stats(&self) -> PoolStats {
+        self.pool.stats()
+    }
+
+    pub fn with_tracing(mut self, enabled: bool) -> Self {
+        self.tracing = enabled;
+        self
+    }
+
+    /// Provide `env.timestamp` (host clock).  Its value differs between
+    /// validators, so only devnets turn it on.
+    pub fn with_wall_clock(mut self, enabled: bool) -> Self {
+        self.wall_clock = enabled;
+        self
+    }
+
     fn derive_address(bytecode: &[u8], salt: Option<[u8; 32]>) -> [u8; 32] {
         let mut h = Sha256::new();
         h.update(bytecode);
@@ -38,53 +169,32 @@ impl WasmEngineAdapter {
         h.finalize().into()
     }
 
-    fn execute_wasm(
-        &self,
-        bytecode:  &[u8],
-        calldata:  &[u8],
-        gas_limit: u64,
-    ) -> VmResult<(bool, Vec<u8>, u64, Vec<ExecutionLog>)> {
-        use wasmer::{imports, Instance, Module, Store, Value};
+    /// Cold path: compile (cached) and instantiate `bytecode` with this
+    /// adapter's imports.
+    fn instantiate(&self, bytecode: &[u8], code_hash: [u8; 32]) -> VmResult<PooledInstance<TraceEnv>> {
+        use wasmer::{imports, FunctionEnv, Instance, Store};
 
         let mut store = Store::default();
 
-        let module = Module::new(&store, bytecode)
+        let (module, poolable) = self.module_cache.get_or_compile_pooled(bytecode, &store)
             .map_err(|e| VmError::ExecutionFailed(format!("WASM compile error: {e}")))?;
 
-        let gas_remaining = Arc::new(AtomicU64::new(gas_limit));
-        let gas_ref = gas_remaining.clone();
+        let trace_env = FunctionEnv::new(&mut store, TraceEnv::idle());
 
-        let log_store = Arc::new(RwLock::new(Vec::<String>::new()));
-        let log_ref = log_store.clone();
-
-        let import_object = imports! {
+        let mut import_object = imports! {
             "env" => {
-                "bleep_gas" => wasmer::Function::new_typed(&mut store,
-                    move |cost: i64| {
-                        let cost = cost as u64;
-                        let cur = gas_ref.load(Ordering::Relaxed);
-                        if cost > cur {
-                            gas_ref.store(0, Ordering::Relaxed);
-                        } else {
-                            gas_ref.fetch_sub(cost, Ordering::Relaxed);
-                        }
-                    }
-                ),
-                "bleep_log" => wasmer::Function::new_typed(&mut store,
-                    move |level: i32, ptr: i32, len: i32| {
-                        let msg = format!("[wasm-log level={level}] ptr={ptr} len={len}");
-                        log_ref.write().push(msg);
-                    }
-                ),
+                "bleep_gas"       => wasmer::Function::new_typed_with_env(&mut store, &trace_env, host_gas),
+                "bleep_log"       => wasmer::Function::new_typed_with_env(&mut store, &trace_env, host_log),
                 "abort" => wasmer::Function::new_typed(&mut store,
                     |_msg: i32, _file: i32, _line: i32, _col: i32| {}
                 ),
+                "block_height"    => wasmer::Function::new_typed_with_env(&mut store, &trace_env, host_block_height),
+                "block_timestamp" => wasmer::Function::new_typed_with_env(&mut store, &trace_env, host_block_timestamp),
+                "random_seed"     => wasmer::Function::new_typed_with_env(&mut store, &trace_env, host_random_seed),
             },
             "bleep" => {
                 "gas_charge"    => wasmer::Function::new_typed(&mut store, |_: i64| {}),
-                "storage_write" => wasmer::Function::new_typed(&mut store,
-                    |_kp: i32, _kl: i32, _vp: i32, _vl: i32| {}
-                ),
+                "storage_write" => wasmer::Function::new_typed_with_env(&mut store, &trace_env, host_storage_write),
                 "log"   => wasmer::Function::new_typed(&mut store, |_: i32, _: i32| {}),
                 "abort" => wasmer::Function::new_typed(&mut store, |_: i32| {}),
             },
@@ -95,9 +205,42 @@ impl WasmEngineAdapter {
                 ),
             },
         };
+        if self.wall_clock {
+            import_object.define("env", "timestamp", wasmer::Function::new_typed(&mut store, wall_clock_secs));
+        }
 
         let instance = Instance::new(&mut store, &module, &import_object)
             .map_err(|e| VmError::ExecutionFailed(format!("WASM instantiate error: {e}")))?;
+        if let Ok(mem) = instance.exports.get_memory("memory") {
+            trace_env.as_mut(&mut store).memory = Some(mem.clone());
+        }
+
+        PooledInstance::new(store, instance, trace_env, code_hash, poolable)
+    }
+
+    fn execute_wasm(
+        &self,
+        bytecode:  &[u8],
+        calldata:  &[u8],
+        gas_limit: u64,
+        block:     ContractEnv,
+    ) -> VmResult<(bool, Vec<u8>, u64, Vec<ExecutionLog>)> {
+        let code_hash: [u8; 32] = Sha256::digest(bytecode).into();
+        let mut pooled = self.pool.checkout(code_hash, || self.instantiate(bytecode, code_hash))?;
+        pooled.env.as_mut(&mut pooled.store).begin(self.tracing, block, gas_limit);
+
+        let result = Self::call(&mut pooled, calldata);
+        self.pool.checkin(pooled);
+        result
+    }
+
+    fn call(
+        pooled:   &mut PooledInstance<TraceEnv>,
+        calldata: &[u8],
+    ) -> VmResult<(bool, Vec<u8>, u64, Vec<ExecutionLog>)> {
+        use wasmer::Value;
+
+        let PooledInstance { store, instance, env, .. } = pooled;
 
         let entry_points = ["execute", "call", "main", "_start", "invoke"];
         let mut output  = Vec::new();
@@ -107,7 +250,7 @@ impl WasmEngineAdapter {
         for entry in &entry_points {
             if let Ok(func) = instance.exports.get_function(entry) {
                 let args = vec![Value::I32(calldata.len() as i32)];
-                match func.call(&mut store, &args) {
+                match func.call(&mut *store, &args) {
                     Ok(results) => {
                         success = true;
                         if let Some(Value::I32(v)) = results.first() {
@@ -134,15 +277,16 @@ impl WasmEngineAdapter {
             success = true;
         }
 
-        let collected = log_store.read().clone();
-        let logs: Vec<ExecutionLog> = collected.into_iter().map(|msg| ExecutionLog {
+        let host = env.as_mut(&mut *store);
+        let mut logs: Vec<ExecutionLog> = host.logs.drain(..).map(|msg| ExecutionLog {
             level:   LogLevel::Info,
             message: msg,
             data:    Vec::new(),
         }).collect();
+        logs.extend(host.steps.drain(..).map(|step| step.to_log()));
 
-        let gas_used = gas_limit
-            .saturating_sub(gas_remaining.load(Ordering::Relaxed))
+        let gas_used = host.gas_limit
+            .saturating_sub(host.gas_remaining)
             .max(1_000);
 
         Ok((success, output, gas_used, logs))
@@ -171,11 +315,11 @@ impl Engine for WasmEngineAdapter {
     ) -> VmResult<EngineResult> {
         let start = Instant::now();
 
-        let effective_bytecode = if bytecode.is_empty() {
+        let (contract, effective_bytecode) = if bytecode.is_empty() {
             let addr = ctx.tx.caller;
-            self.modules.read().get(&addr).cloned().unwrap_or_default()
+            (addr, self.modules.read().get(&addr).cloned().unwrap_or_default())
         } else {
-            bytecode.to_vec()
+            (Self::derive_address(bytecode, None), bytecode.to_vec())
         };
 
         if effective_bytecode.is_empty() {
@@ -191,7 +335,7 @@ impl Engine for WasmEngineAdapter {
         }
 
         let (success, output, gas_used, logs) =
-            self.execute_wasm(&effective_bytecode, calldata, gas_limit)?;
+            self.execute_wasm(&effective_bytecode, calldata, gas_limit, ContractEnv::new(&ctx.block, &contract))?;
 
         debug!(success, gas_used, output_len = output.len(), "WASM execution complete");
 
@@ -209,7 +353,7 @@ impl Engine for WasmEngineAdapter {
     #[instrument(skip(self, bytecode, init_args), fields(engine = "wasm-wasmer"))]
     async fn deploy(
         &self,
-        _ctx:      &ExecutionContext,
+        ctx:       &ExecutionContext,
         bytecode:  &[u8],
         init_args: &[u8],
         gas_limit: u64,
@@ -224,7 +368,7 @@ impl Engine for WasmEngineAdapter {
         }
 
         let (success, output, gas_used, logs) =
-            self.execute_wasm(bytecode, init_args, gas_limit)
+            self.execute_wasm(bytecode, init_args, gas_limit, ContractEnv::new(&ctx.block, &address))
                 .unwrap_or((true, Vec::new(), 50_000, Vec::new()));
 
         let mut diff = StateDiff::empty();
@@ -289,6 +433,146 @@ mod tests {
         assert_ne!(a1, a2);
     }
 
+    /// Module exporting `execute(i32) -> i32` that writes k1=v1 then k2=v2.
+    fn two_writes_module() -> Vec<u8> {
+        use wasm_encoder::{
+            CodeSection, ConstExpr, DataSection, EntityType, ExportKind, ExportSection,
+            Function, FunctionSection, ImportSection, Instruction, MemorySection,
+            MemoryType, Module, TypeSection, ValType,
+        };
+        let mut types = TypeSection::new();
+        types.function([ValType::I32; 4], [] as [ValType; 0]);
+        types.function([ValType::I32], [ValType::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", "storage_write", EntityType::Function(0));
+        let mut funcs = FunctionSection::new();
+        funcs.function(1);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("execute", ExportKind::Func, 1);
+        exports.export("memory", ExportKind::Memory, 0);
+        let mut body = Function::new([] as [(u32, ValType); 0]);
+        for (k, v) in [(0, 2), (4, 6)] {
+            body.instruction(&Instruction::I32Const(k));
+            body.instruction(&Instruction::I32Const(2));
+            body.instruction(&Instruction::I32Const(v));
+            body.instruction(&Instruction::I32Const(2));
+            body.instruction(&Instruction::Call(0));
+        }
+        body.instruction(&Instruction::I32Const(0));
+        body.instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut data = DataSection::new();
+        data.active(0, &ConstExpr::i32_const(0), b"k1v1k2v2".iter().copied());
+
+        let mut module = Module::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code).section(&data);
+        module.finish()
+    }
+
+    #[tokio::test]
+    async fn test_trace_lists_storage_writes_in_order() {
+        use crate::execution::trace::extract_trace;
+        let e = WasmEngineAdapter::new().with_tracing(true);
+        let result = e.execute(&ctx(1_000_000), &two_writes_module(), &[], 1_000_000).await.unwrap();
+        assert!(result.success);
+        let writes: Vec<TraceStep> = extract_trace(&result.logs).into_iter()
+            .filter(|s| matches!(s, TraceStep::StorageWrite { .. }))
+            .collect();
+        assert_eq!(writes, vec![
+            TraceStep::StorageWrite { key: b"k1".to_vec(), value: b"v1".to_vec() },
+            TraceStep::StorageWrite { key: b"k2".to_vec(), value: b"v2".to_vec() },
+        ]);
+
+        // Tracing off: same execution, no trace entries.
+        let plain = WasmEngineAdapter::new();
+        let result = plain.execute(&ctx(1_000_000), &two_writes_module(), &[], 1_000_000).await.unwrap();
+        assert!(extract_trace(&result.logs).is_empty());
+    }
+
+    /// Module exporting `execute(i32) -> i32` that stores its random seed
+    /// under `seed` and returns the block timestamp.
+    fn env_reader_module() -> Vec<u8> {
+        use wasm_encoder::{
+            CodeSection, ConstExpr, DataSection, EntityType, ExportKind, ExportSection,
+            Function, FunctionSection, ImportSection, Instruction, MemorySection,
+            MemoryType, Module, TypeSection, ValType,
+        };
+        let mut types = TypeSection::new();
+        types.function([ValType::I32], [] as [ValType; 0]);
+        types.function([] as [ValType; 0], [ValType::I64]);
+        types.function([ValType::I32; 4], [] as [ValType; 0]);
+        types.function([ValType::I32], [ValType::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("env", "random_seed", EntityType::Function(0));
+        imports.import("env", "block_timestamp", EntityType::Function(1));
+        imports.import("bleep", "storage_write", EntityType::Function(2));
+        let mut funcs = FunctionSection::new();
+        funcs.function(3);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("execute", ExportKind::Func, 3);
+        exports.export("memory", ExportKind::Memory, 0);
+        let mut body = Function::new([] as [(u32, ValType); 0]);
+        body.instruction(&Instruction::I32Const(0));
+        body.instruction(&Instruction::Call(0));
+        for arg in [32, 4, 0, 32] {
+            body.instruction(&Instruction::I32Const(arg));
+        }
+        body.instruction(&Instruction::Call(2));
+        body.instruction(&Instruction::Call(1));
+        body.instruction(&Instruction::I32WrapI64);
+        body.instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut data = DataSection::new();
+        data.active(0, &ConstExpr::i32_const(32), b"seed".iter().copied());
+
+        let mut module = Module::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code).section(&data);
+        module.finish()
+    }
+
+    #[tokio::test]
+    async fn test_env_imports_are_deterministic_per_block_and_contract() {
+        use crate::execution::trace::extract_trace;
+        use crate::runtime::determinism::ContractEnv;
+
+        let block = BlockEnv { number: 42, timestamp: 1_700_000_123, prev_hash: [9u8; 32], ..BlockEnv::default() };
+        let in_block = |contract: [u8; 32]| ExecutionContext::new(
+            block.clone(), TxEnv { caller: contract, ..TxEnv::default() }, 1_000_000,
+            ChainId::Bleep, uuid::Uuid::new_v4(), 128,
+        );
+        let seed_of = |result: &EngineResult| extract_trace(&result.logs).into_iter()
+            .find_map(|s| match s { TraceStep::StorageWrite { value, .. } => Some(value), _ => None })
+            .unwrap();
+
+        let e = WasmEngineAdapter::new().with_tracing(true);
+        let code = env_reader_module();
+        let (a, b) = (
+            WasmEngineAdapter::derive_address(&code, Some([1u8; 32])),
+            WasmEngineAdapter::derive_address(&code, Some([2u8; 32])),
+        );
+        e.deploy(&ctx(1_000_000), &code, &[], 1_000_000, Some([1u8; 32])).await.unwrap();
+        e.deploy(&ctx(1_000_000), &code, &[], 1_000_000, Some([2u8; 32])).await.unwrap();
+
+        let first  = e.execute(&in_block(a), &[], &[], 1_000_000).await.unwrap();
+        let second = e.execute(&in_block(a), &[], &[], 1_000_000).await.unwrap();
+        assert!(first.success);
+        assert_eq!(first.output, 1_700_000_123i32.to_le_bytes().to_vec());
+        assert_eq!((&first.output, seed_of(&first)), (&second.output, seed_of(&second)));
+        assert_eq!(seed_of(&first), ContractEnv::new(&block, &a).seed.to_vec());
+
+        let other = e.execute(&in_block(b), &[], &[], 1_000_000).await.unwrap();
+        assert_eq!(other.output, first.output);
+        assert_ne!(seed_of(&other), seed_of(&first));
+    }
+
     #[tokio::test]
     async fn test_minimal_wasm_passive_execution() {
         // Minimal valid WASM: magic + version, no exports
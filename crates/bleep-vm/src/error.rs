@@ -50,6 +50,18 @@ pub enum VmError {
     #[error("Invariant violated: {0}")]
     InvariantViolation(String),
 
+    #[error("Contract not found: {0}")]
+    ContractNotFound(String),
+
+    #[error("Upgrade rejected: {0}")]
+    UpgradeRejected(String),
+
+    #[error("Event rejected: {0}")]
+    EventRejected(String),
+
+    #[error("Precompile rejected: {0}")]
+    PrecompileRejected(String),
+
     // ── Optimiser ────────────────────────────────────────────────────────────
     #[error("WASM optimisation failed: {0}")]
     OptimisationFailed(String),
@@ -0,0 +1,1235 @@
+//! # Contract registry with opt-in upgrades
+//!
+//! Holds deployed WASM contracts, their storage namespaces and, for
+//! contracts deployed with `upgradeable = true`, the upgrade history.
+//!
+//! ```text
+//!   upgrade(address, new_code, caller)
+//!       │ caller == admin?                     else UpgradeRejected
+//!       │ SecurityPolicy::validate(new_code)   else SecurityViolation
+//!       │ new_code exports `migrate`?          else UpgradeRejected
+//!       ▼
+//!   migrate(from_version) on a copy of the contract's storage, gas-limited
+//!       │ trap / out of gas / non-zero return  → nothing changes
+//!       ▼
+//!   swap code hash + storage under one lock, push old hash, emit event
+//! ```
+//!
+//! Old code is never deleted: every code hash ever deployed stays in the
+//! registry so `rollback` can restore the previous one and the history can
+//! be audited.  Contracts deployed without the flag reject every upgrade.
+//!
+//! Contracts see their storage through the `bleep` host imports
+//! `storage_read(key_ptr, key_len, out_ptr, out_cap) -> i32` (value length,
+//! or -1 if unset) and `storage_write(key_ptr, key_len, val_ptr, val_len)`.
+//! As in `WasmEngineAdapter`, `execute` receives the calldata length and
+//! its `i32` result is returned little-endian.  `calldata(out_ptr, out_cap)
+//! -> i32` copies the calldata the same way `storage_read` copies a value:
+//! only if it fits, always returning its length.
+//!
+//! Contracts emit events with `emit_event(name_ptr, name_len, data_ptr,
+//! data_len)`.  An event named in the contract's `EventSchema` (see
+//! `event_abi`) must match its declared fields and every event must fit in
+//! `MAX_EVENT_DATA` bytes, or the call fails.  Events of successful calls
+//! are appended to the contract's event log.
+//!
+//! The `crypto_*` imports in the `bleep` module are precompiles; see
+//! `precompiles` for their signatures, limits and gas schedule.  Groth16
+//! verifying keys are registered up front with `register_groth16_key`.
+//!
+//! A registry built `with_deploy_gate` runs the gate over all code before it
+//! is deployed or upgraded to; nodes use it to refuse contracts whose
+//! static-analysis report exceeds the network's severity limit.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use bleep_crypto::zkp_verification::Groth16Key;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::info;
+use wasmer::{imports, Function, FunctionEnv, FunctionEnvMut, Instance, Memory, MemoryView, Module, RuntimeError, Store};
+
+use crate::error::{VmError, VmResult};
+use crate::execution::event_abi::{event_topic, EventSchema, EVENT_SCHEMA_SECTION, MAX_EVENT_DATA};
+use crate::execution::precompiles::{self, PrecompileGas, Precompiles};
+use crate::execution::state_transition::EmittedEvent;
+use crate::runtime::sandbox::SecurityPolicy;
+
+/// Export every upgradeable contract's new code must provide.
+pub const MIGRATE_EXPORT: &str = "migrate";
+/// Entry point used by `ContractRegistry::call`.
+pub const EXECUTE_EXPORT: &str = "execute";
+
+/// Gas charged before any contract code runs.
+pub const CALL_BASE_GAS: u64 = 1_000;
+/// Gas per `storage_read`.
+pub const STORAGE_READ_GAS: u64 = 200;
+/// Gas per `storage_write`, plus `STORAGE_BYTE_GAS` per key and value byte.
+pub const STORAGE_WRITE_GAS: u64 = 5_000;
+pub const STORAGE_BYTE_GAS: u64 = 50;
+/// Gas per `emit_event`, plus `EVENT_BYTE_GAS` per name and data byte.
+pub const EVENT_GAS: u64 = 375;
+pub const EVENT_BYTE_GAS: u64 = 8;
+/// Events kept per contract for the event-log query; oldest are dropped.
+pub const MAX_EVENT_LOG: usize = 1_024;
+
+/// Who may upgrade a contract, and who is asking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpgradeAuthority {
+    /// A single account.
+    Account([u8; 32]),
+    /// An executed governance proposal.
+    Governance,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpgradeKind {
+    Upgrade,
+    Rollback,
+}
+
+/// One entry in a contract's upgrade history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UpgradeRecord {
+    /// Contract version after this change; deployment is version 0.
+    pub version:          u32,
+    pub kind:             UpgradeKind,
+    pub from_code_hash:   [u8; 32],
+    pub to_code_hash:     [u8; 32],
+    pub authority:        UpgradeAuthority,
+    /// Gas used by `migrate` (0 for rollbacks).
+    pub migrate_gas_used: u64,
+}
+
+/// Public view of a deployed contract.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContractInfo {
+    pub address:     [u8; 32],
+    pub code_hash:   [u8; 32],
+    pub version:     u32,
+    pub upgradeable: bool,
+    pub admin:       Option<UpgradeAuthority>,
+}
+
+/// Result of `ContractRegistry::call`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallOutput {
+    pub output:   Vec<u8>,
+    pub gas_used: u64,
+}
+
+/// Result of `ContractRegistry::dry_run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRun {
+    pub output:   Vec<u8>,
+    pub gas_used: u64,
+    /// The contract's storage as the call left it.
+    pub storage:  BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Events the call emitted, indexed from 0.
+    pub events:   Vec<EmittedEvent>,
+}
+
+struct ContractEntry {
+    code_hash:   [u8; 32],
+    version:     u32,
+    upgradeable: bool,
+    admin:       Option<UpgradeAuthority>,
+    storage:     BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Code hashes replaced by upgrades, most recent last.
+    previous:    Vec<[u8; 32]>,
+    history:     Vec<UpgradeRecord>,
+    schema:      Option<EventSchema>,
+    log:         Vec<EmittedEvent>,
+}
+
+/// Check run on code before deployment; `Err` carries the refusal reason.
+pub type DeployGate = Arc<dyn Fn(&[u8]) -> Result<(), String> + Send + Sync>;
+
+/// Deployed contracts, their code and storage.
+pub struct ContractRegistry {
+    policy:    SecurityPolicy,
+    gate:      Option<DeployGate>,
+    code:      RwLock<HashMap<[u8; 32], Vec<u8>>>,
+    contracts: RwLock<HashMap<[u8; 32], ContractEntry>>,
+    events:    RwLock<Vec<EmittedEvent>>,
+    precompile_gas: RwLock<PrecompileGas>,
+    groth16_keys:   RwLock<HashMap<u32, Arc<Groth16Key>>>,
+}
+
+impl ContractRegistry {
+    pub fn new() -> Self {
+        Self::with_policy(SecurityPolicy::default())
+    }
+
+    pub fn with_policy(policy: SecurityPolicy) -> Self {
+        ContractRegistry {
+            policy,
+            gate:      None,
+            code:      RwLock::new(HashMap::new()),
+            contracts: RwLock::new(HashMap::new()),
+            events:    RwLock::new(Vec::new()),
+            precompile_gas: RwLock::new(PrecompileGas::default()),
+            groth16_keys:   RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Refuse deployments and upgrades to code the gate rejects.
+    pub fn with_deploy_gate(mut self, gate: DeployGate) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    pub fn with_precompile_gas(self, gas: PrecompileGas) -> Self {
+        *self.precompile_gas.write() = gas;
+        self
+    }
+
+    /// Replace the precompile gas schedule; calls already running keep the old one.
+    pub fn set_precompile_gas(&self, gas: PrecompileGas) {
+        *self.precompile_gas.write() = gas;
+    }
+
+    pub fn precompile_gas(&self) -> PrecompileGas {
+        *self.precompile_gas.read()
+    }
+
+    /// Make the compressed Groth16 verifying key `vk_bytes` available to
+    /// `crypto_verify_groth16` as `vk_id`.  Ids are never reused.
+    pub fn register_groth16_key(&self, vk_id: u32, vk_bytes: &[u8]) -> VmResult<()> {
+        let key = Groth16Key::from_bytes(vk_bytes)
+            .map_err(|e| VmError::ValidationError(format!("Groth16 verifying key {vk_id}: {e}")))?;
+        let mut keys = self.groth16_keys.write();
+        if keys.contains_key(&vk_id) {
+            return Err(VmError::ValidationError(format!("Groth16 verifying key {vk_id} already registered")));
+        }
+        keys.insert(vk_id, Arc::new(key));
+        Ok(())
+    }
+
+    fn precompiles(&self) -> Precompiles {
+        Precompiles { gas: self.precompile_gas(), keys: self.groth16_keys.read().clone() }
+    }
+
+    fn check_gate(&self, code: &[u8]) -> VmResult<()> {
+        match &self.gate {
+            Some(gate) => gate(code).map_err(VmError::SecurityViolation),
+            None => Ok(()),
+        }
+    }
+
+    fn code_hash(code: &[u8]) -> [u8; 32] {
+        Sha256::digest(code).into()
+    }
+
+    fn derive_address(code: &[u8], salt: Option<[u8; 32]>) -> [u8; 32] {
+        let mut h = Sha256::new();
+        h.update(code);
+        if let Some(s) = salt { h.update(s); }
+        h.finalize().into()
+    }
+
+    /// Deploy `code`.  An upgradeable contract needs an `admin`.
+    pub fn deploy(
+        &self,
+        code:        &[u8],
+        upgradeable: bool,
+        admin:       Option<UpgradeAuthority>,
+        salt:        Option<[u8; 32]>,
+    ) -> VmResult<[u8; 32]> {
+        self.deploy_with_schema(code, upgradeable, admin, salt, None)
+    }
+
+    /// Deploy `code` with an event schema passed alongside it.  The schema
+    /// may instead be embedded in the code's `bleep.events` section, but
+    /// not both.
+    pub fn deploy_with_schema(
+        &self,
+        code:        &[u8],
+        upgradeable: bool,
+        admin:       Option<UpgradeAuthority>,
+        salt:        Option<[u8; 32]>,
+        schema:      Option<EventSchema>,
+    ) -> VmResult<[u8; 32]> {
+        if upgradeable && admin.is_none() {
+            return Err(VmError::ValidationError("Upgradeable contract needs an admin".into()));
+        }
+        self.policy.validate(code)?;
+        self.check_gate(code)?;
+        let schema = match (schema, EventSchema::from_wasm(code)?) {
+            (Some(_), Some(_)) => {
+                return Err(VmError::ValidationError("Event schema given both in code and at deploy".into()));
+            }
+            (Some(s), None) => { s.validate()?; Some(s) }
+            (None, embedded) => embedded,
+        };
+
+        let address = Self::derive_address(code, salt);
+        let code_hash = Self::code_hash(code);
+        let mut contracts = self.contracts.write();
+        if contracts.contains_key(&address) {
+            return Err(VmError::ValidationError(format!(
+                "Contract already deployed at {}", hex::encode(address)
+            )));
+        }
+        self.code.write().insert(code_hash, code.to_vec());
+        contracts.insert(address, ContractEntry {
+            code_hash,
+            version: 0,
+            upgradeable,
+            admin: if upgradeable { admin } else { None },
+            storage:  BTreeMap::new(),
+            previous: Vec::new(),
+            history:  Vec::new(),
+            schema,
+            log:      Vec::new(),
+        });
+        Ok(address)
+    }
+
+    /// Number of deployed contracts.
+    pub fn contract_count(&self) -> usize {
+        self.contracts.read().len()
+    }
+
+    pub fn info(&self, address: &[u8; 32]) -> Option<ContractInfo> {
+        self.contracts.read().get(address).map(|c| ContractInfo {
+            address:     *address,
+            code_hash:   c.code_hash,
+            version:     c.version,
+            upgradeable: c.upgradeable,
+            admin:       c.admin,
+        })
+    }
+
+    /// Upgrade and rollback history, oldest first.
+    pub fn history(&self, address: &[u8; 32]) -> VmResult<Vec<UpgradeRecord>> {
+        self.contracts.read().get(address)
+            .map(|c| c.history.clone())
+            .ok_or_else(|| not_found(address))
+    }
+
+    /// Code stored under `code_hash`, including replaced versions.
+    pub fn code(&self, code_hash: &[u8; 32]) -> Option<Vec<u8>> {
+        self.code.read().get(code_hash).cloned()
+    }
+
+    pub fn storage_get(&self, address: &[u8; 32], key: &[u8]) -> Option<Vec<u8>> {
+        self.contracts.read().get(address)?.storage.get(key).cloned()
+    }
+
+    pub fn event_schema(&self, address: &[u8; 32]) -> Option<EventSchema> {
+        self.contracts.read().get(address)?.schema.clone()
+    }
+
+    /// Events emitted by the contract's successful calls, oldest first.
+    pub fn event_log(&self, address: &[u8; 32]) -> VmResult<Vec<EmittedEvent>> {
+        self.contracts.read().get(address)
+            .map(|c| c.log.clone())
+            .ok_or_else(|| not_found(address))
+    }
+
+    /// Contract and upgrade events emitted since the last call.
+    pub fn take_events(&self) -> Vec<EmittedEvent> {
+        std::mem::take(&mut *self.events.write())
+    }
+
+    /// Run `execute` on the contract; storage writes are kept only if the
+    /// call succeeds.
+    pub fn call(&self, address: &[u8; 32], calldata: &[u8], gas_limit: u64) -> VmResult<CallOutput> {
+        let (code_hash, storage, schema) = {
+            let contracts = self.contracts.read();
+            let c = contracts.get(address).ok_or_else(|| not_found(address))?;
+            (c.code_hash, c.storage.clone(), c.schema.clone())
+        };
+        let code = self.code(&code_hash).ok_or_else(|| missing_code(&code_hash))?;
+        let run = run_export(
+            &code, EXECUTE_EXPORT, calldata, storage, schema, self.precompiles(), gas_limit,
+        )?;
+
+        let mut contracts = self.contracts.write();
+        let c = contracts.get_mut(address).ok_or_else(|| not_found(address))?;
+        if c.code_hash != code_hash {
+            return Err(VmError::ExecutionFailed("Contract code changed during call".into()));
+        }
+        c.storage = run.storage;
+        let mut events = self.events.write();
+        for (name, data) in run.events {
+            let event = EmittedEvent {
+                contract:  *address,
+                topics:    vec![event_topic(&name)],
+                data,
+                log_index: events.len() as u32,
+            };
+            if c.log.len() == MAX_EVENT_LOG {
+                c.log.remove(0);
+            }
+            c.log.push(event.clone());
+            events.push(event);
+        }
+        Ok(CallOutput { output: run.result.to_le_bytes().to_vec(), gas_used: run.gas_used })
+    }
+
+    /// Run `execute` of the code stored under `code_hash` as contract
+    /// `address`, over `storage` instead of the registry's copy.  Nothing
+    /// is kept: the resulting storage and events are returned.  Replaced
+    /// code versions can be run too, so a caller holding a past state can
+    /// execute against it.
+    pub fn dry_run(
+        &self,
+        address:   &[u8; 32],
+        code_hash: &[u8; 32],
+        calldata:  &[u8],
+        gas_limit: u64,
+        storage:   BTreeMap<Vec<u8>, Vec<u8>>,
+    ) -> VmResult<DryRun> {
+        let code = self.code(code_hash).ok_or_else(|| missing_code(code_hash))?;
+        let run = run_export(
+            &code, EXECUTE_EXPORT, calldata, storage, self.event_schema(address), self.precompiles(), gas_limit,
+        )?;
+        let events = run.events.into_iter().enumerate().map(|(i, (name, data))| EmittedEvent {
+            contract:  *address,
+            topics:    vec![event_topic(&name)],
+            data,
+            log_index: i as u32,
+        }).collect();
+        Ok(DryRun {
+            output:   run.result.to_le_bytes().to_vec(),
+            gas_used: run.gas_used,
+            storage:  run.storage,
+            events,
+        })
+    }
+
+    /// Replace the contract's code with `new_code`, running its `migrate`
+    /// export on the existing storage first.  Nothing changes unless
+    /// migration returns 0 within `migrate_gas`.
+    pub fn upgrade(
+        &self,
+        address:     &[u8; 32],
+        new_code:    &[u8],
+        caller:      UpgradeAuthority,
+        migrate_gas: u64,
+    ) -> VmResult<UpgradeRecord> {
+        // Held throughout so calls and other upgrades never see a half-applied upgrade.
+        let mut contracts = self.contracts.write();
+        let c = contracts.get_mut(address).ok_or_else(|| not_found(address))?;
+        check_authority(c, caller)?;
+
+        let report = self.policy.validate(new_code)?;
+        self.check_gate(new_code)?;
+        if !report.exports.iter().any(|e| e == MIGRATE_EXPORT) {
+            return Err(VmError::UpgradeRejected(format!("New code does not export `{MIGRATE_EXPORT}`")));
+        }
+        let new_hash = Self::code_hash(new_code);
+        if new_hash == c.code_hash {
+            return Err(VmError::UpgradeRejected("New code is identical to the current code".into()));
+        }
+        // New code without an embedded schema keeps the current one.
+        let schema = EventSchema::from_wasm(new_code)?.or_else(|| c.schema.clone());
+
+        // Events emitted by `migrate` are checked but not logged.
+        let run = run_export_with_arg(
+            new_code, MIGRATE_EXPORT, c.version as i32, &[], c.storage.clone(), schema.clone(),
+            self.precompiles(), migrate_gas,
+        )?;
+        if run.result != 0 {
+            return Err(VmError::UpgradeRejected(format!("`{MIGRATE_EXPORT}` returned {}", run.result)));
+        }
+
+        self.code.write().entry(new_hash).or_insert_with(|| new_code.to_vec());
+        let record = UpgradeRecord {
+            version:          c.version + 1,
+            kind:             UpgradeKind::Upgrade,
+            from_code_hash:   c.code_hash,
+            to_code_hash:     new_hash,
+            authority:        caller,
+            migrate_gas_used: run.gas_used,
+        };
+        c.previous.push(c.code_hash);
+        c.code_hash = new_hash;
+        c.storage = run.storage;
+        c.schema = schema;
+        self.commit(address, c, record.clone());
+        Ok(record)
+    }
+
+    /// Restore the code hash replaced by the most recent upgrade.  Storage
+    /// is left as migrated.
+    pub fn rollback(&self, address: &[u8; 32], caller: UpgradeAuthority) -> VmResult<UpgradeRecord> {
+        let mut contracts = self.contracts.write();
+        let c = contracts.get_mut(address).ok_or_else(|| not_found(address))?;
+        check_authority(c, caller)?;
+        let prev = c.previous.pop()
+            .ok_or_else(|| VmError::UpgradeRejected("No previous code to roll back to".into()))?;
+
+        let record = UpgradeRecord {
+            version:          c.version + 1,
+            kind:             UpgradeKind::Rollback,
+            from_code_hash:   c.code_hash,
+            to_code_hash:     prev,
+            authority:        caller,
+            migrate_gas_used: 0,
+        };
+        c.code_hash = prev;
+        self.commit(address, c, record.clone());
+        Ok(record)
+    }
+
+    fn commit(&self, address: &[u8; 32], c: &mut ContractEntry, record: UpgradeRecord) {
+        c.version = record.version;
+        let topic = match record.kind {
+            UpgradeKind::Upgrade  => b"ContractUpgraded".as_slice(),
+            UpgradeKind::Rollback => b"ContractRolledBack".as_slice(),
+        };
+        let mut events = self.events.write();
+        let log_index = events.len() as u32;
+        events.push(EmittedEvent {
+            contract:  *address,
+            topics:    vec![Sha256::digest(topic).into(), record.from_code_hash, record.to_code_hash],
+            data:      record.version.to_le_bytes().to_vec(),
+            log_index,
+        });
+        info!(
+            address = %hex::encode(address),
+            version = record.version,
+            kind    = ?record.kind,
+            code    = %hex::encode(record.to_code_hash),
+            "Contract code replaced"
+        );
+        c.history.push(record);
+    }
+}
+
+impl Default for ContractRegistry {
+    fn default() -> Self { Self::new() }
+}
+
+fn not_found(address: &[u8; 32]) -> VmError {
+    VmError::ContractNotFound(hex::encode(address))
+}
+
+fn missing_code(code_hash: &[u8; 32]) -> VmError {
+    VmError::Internal(format!("Code {} missing from registry", hex::encode(code_hash)))
+}
+
+fn check_authority(c: &ContractEntry, caller: UpgradeAuthority) -> VmResult<()> {
+    if !c.upgradeable {
+        return Err(VmError::UpgradeRejected("Contract is not upgradeable".into()));
+    }
+    if c.admin != Some(caller) {
+        return Err(VmError::UpgradeRejected("Caller is not the contract's upgrade admin".into()));
+    }
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// EXECUTION
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct HostState {
+    memory:      Option<Memory>,
+    calldata:    Vec<u8>,
+    storage:     BTreeMap<Vec<u8>, Vec<u8>>,
+    schema:      Option<EventSchema>,
+    events:      Vec<(String, Vec<u8>)>,
+    precompiles: Precompiles,
+    host_error:  Option<VmError>,
+    gas_left:    u64,
+    out_of_gas:  bool,
+}
+
+impl HostState {
+    fn charge(&mut self, cost: u64) -> Result<(), RuntimeError> {
+        if cost > self.gas_left {
+            self.gas_left = 0;
+            self.out_of_gas = true;
+            return Err(RuntimeError::new("out of gas"));
+        }
+        self.gas_left -= cost;
+        Ok(())
+    }
+}
+
+fn read_bytes(view: &MemoryView, ptr: i32, len: i32) -> Result<Vec<u8>, RuntimeError> {
+    if ptr < 0 || len < 0 {
+        return Err(RuntimeError::new("negative pointer or length"));
+    }
+    let mut buf = vec![0u8; len as usize];
+    view.read(ptr as u64, &mut buf).map_err(|e| RuntimeError::new(e.to_string()))?;
+    Ok(buf)
+}
+
+fn host_gas(mut env: FunctionEnvMut<HostState>, cost: i64) -> Result<(), RuntimeError> {
+    env.data_mut().charge(cost.max(0) as u64)
+}
+
+fn host_storage_read(
+    mut env: FunctionEnvMut<HostState>,
+    key_ptr: i32, key_len: i32,
+    out_ptr: i32, out_cap: i32,
+) -> Result<i32, RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    data.charge(STORAGE_READ_GAS)?;
+    let memory = data.memory.as_ref().ok_or_else(|| RuntimeError::new("contract exports no memory"))?;
+    let view = memory.view(&store);
+    let key = read_bytes(&view, key_ptr, key_len)?;
+    let value = match data.storage.get(&key) {
+        Some(v) => v,
+        None => return Ok(-1),
+    };
+    // A value larger than the buffer is not copied; the caller retries with its length.
+    if out_ptr >= 0 && value.len() <= out_cap.max(0) as usize {
+        view.write(out_ptr as u64, value).map_err(|e| RuntimeError::new(e.to_string()))?;
+    }
+    Ok(value.len() as i32)
+}
+
+fn host_calldata(mut env: FunctionEnvMut<HostState>, out_ptr: i32, out_cap: i32) -> Result<i32, RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    data.charge(STORAGE_READ_GAS)?;
+    let memory = data.memory.as_ref().ok_or_else(|| RuntimeError::new("contract exports no memory"))?;
+    if out_ptr >= 0 && data.calldata.len() <= out_cap.max(0) as usize {
+        memory.view(&store).write(out_ptr as u64, &data.calldata).map_err(|e| RuntimeError::new(e.to_string()))?;
+    }
+    Ok(data.calldata.len() as i32)
+}
+
+fn host_storage_write(
+    mut env: FunctionEnvMut<HostState>,
+    key_ptr: i32, key_len: i32,
+    val_ptr: i32, val_len: i32,
+) -> Result<(), RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    let bytes = (key_len.max(0) as u64).saturating_add(val_len.max(0) as u64);
+    data.charge(STORAGE_WRITE_GAS.saturating_add(bytes.saturating_mul(STORAGE_BYTE_GAS)))?;
+    let memory = data.memory.as_ref().ok_or_else(|| RuntimeError::new("contract exports no memory"))?;
+    let view = memory.view(&store);
+    let key = read_bytes(&view, key_ptr, key_len)?;
+    let value = read_bytes(&view, val_ptr, val_len)?;
+    data.storage.insert(key, value);
+    Ok(())
+}
+
+fn host_emit_event(
+    mut env: FunctionEnvMut<HostState>,
+    name_ptr: i32, name_len: i32,
+    data_ptr: i32, data_len: i32,
+) -> Result<(), RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    let bytes = (name_len.max(0) as u64).saturating_add(data_len.max(0) as u64);
+    data.charge(EVENT_GAS.saturating_add(bytes.saturating_mul(EVENT_BYTE_GAS)))?;
+    if data_len.max(0) as usize > MAX_EVENT_DATA {
+        let msg = format!("event data is {data_len} bytes, limit {MAX_EVENT_DATA}");
+        return reject(data, VmError::EventRejected(msg));
+    }
+    let memory = data.memory.as_ref().ok_or_else(|| RuntimeError::new("contract exports no memory"))?;
+    let view = memory.view(&store);
+    let name = read_bytes(&view, name_ptr, name_len)?;
+    let payload = read_bytes(&view, data_ptr, data_len)?;
+    let name = match String::from_utf8(name) {
+        Ok(n) if !n.is_empty() => n,
+        _ => return reject(data, VmError::EventRejected("event name must be non-empty UTF-8".into())),
+    };
+    let checked = data.schema.as_ref().map_or(Ok(()), |s| s.check(&name, &payload));
+    if let Err(e) = checked {
+        return reject(data, e);
+    }
+    data.events.push((name, payload));
+    Ok(())
+}
+
+fn host_crypto_sha3(
+    mut env: FunctionEnvMut<HostState>,
+    ptr: i32, len: i32, out_ptr: i32,
+) -> Result<(), RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    let len = match precompiles::check_sha3(len) {
+        Ok(n) => n,
+        Err(e) => return reject(data, e),
+    };
+    data.charge(data.precompiles.gas.sha3(len))?;
+    let memory = data.memory.as_ref().ok_or_else(|| RuntimeError::new("contract exports no memory"))?;
+    let view = memory.view(&store);
+    let digest = precompiles::sha3(&read_bytes(&view, ptr, len as i32)?);
+    if out_ptr < 0 {
+        return Err(RuntimeError::new("negative output pointer"));
+    }
+    view.write(out_ptr as u64, &digest).map_err(|e| RuntimeError::new(e.to_string()))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn host_crypto_verify_sig(
+    mut env: FunctionEnvMut<HostState>,
+    scheme: i32,
+    msg_ptr: i32, msg_len: i32,
+    sig_ptr: i32, sig_len: i32,
+    pk_ptr: i32, pk_len: i32,
+) -> Result<i32, RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    let (msg_len, sig_len, pk_len) = match precompiles::check_sig(scheme, msg_len, sig_len, pk_len) {
+        Ok(lens) => lens,
+        Err(e) => return reject(data, e),
+    };
+    data.charge(data.precompiles.gas.verify_sig(scheme))?;
+    let memory = data.memory.as_ref().ok_or_else(|| RuntimeError::new("contract exports no memory"))?;
+    let view = memory.view(&store);
+    let msg = read_bytes(&view, msg_ptr, msg_len as i32)?;
+    let sig = read_bytes(&view, sig_ptr, sig_len as i32)?;
+    let pk = read_bytes(&view, pk_ptr, pk_len as i32)?;
+    Ok(precompiles::verify_sig(scheme, &msg, &sig, &pk) as i32)
+}
+
+fn host_crypto_verify_groth16(
+    mut env: FunctionEnvMut<HostState>,
+    proof_ptr: i32, proof_len: i32,
+    inputs_ptr: i32, inputs_len: i32,
+    vk_id: i32,
+) -> Result<i32, RuntimeError> {
+    let (data, store) = env.data_and_store_mut();
+    let checked = precompiles::check_groth16(proof_len, inputs_len)
+        .and_then(|lens| data.precompiles.key(vk_id as u32).map(|_| lens));
+    let (proof_len, inputs_len) = match checked {
+        Ok(lens) => lens,
+        Err(e) => return reject(data, e),
+    };
+    data.charge(data.precompiles.gas.groth16(inputs_len / precompiles::GROTH16_INPUT_BYTES))?;
+    let memory = data.memory.as_ref().ok_or_else(|| RuntimeError::new("contract exports no memory"))?;
+    let view = memory.view(&store);
+    let proof = read_bytes(&view, proof_ptr, proof_len as i32)?;
+    let inputs = read_bytes(&view, inputs_ptr, inputs_len as i32)?;
+    match data.precompiles.verify_groth16(vk_id as u32, &proof, &inputs) {
+        Ok(valid) => Ok(valid as i32),
+        Err(e) => reject(data, e),
+    }
+}
+
+/// Trap, remembering why so `run_export` can report a typed error.
+fn reject<T>(data: &mut HostState, err: VmError) -> Result<T, RuntimeError> {
+    let trap = RuntimeError::new(err.to_string());
+    data.host_error = Some(err);
+    Err(trap)
+}
+
+struct ExportRun {
+    result:   i32,
+    storage:  BTreeMap<Vec<u8>, Vec<u8>>,
+    events:   Vec<(String, Vec<u8>)>,
+    gas_used: u64,
+}
+
+/// Call `export(calldata.len()) -> i32` with `storage` as the contract's namespace.
+fn run_export(
+    code:        &[u8],
+    export:      &str,
+    calldata:    &[u8],
+    storage:     BTreeMap<Vec<u8>, Vec<u8>>,
+    schema:      Option<EventSchema>,
+    precompiles: Precompiles,
+    gas_limit:   u64,
+) -> VmResult<ExportRun> {
+    run_export_with_arg(code, export, calldata.len() as i32, calldata, storage, schema, precompiles, gas_limit)
+}
+
+/// Call `export(arg) -> i32` with `storage` as the contract's namespace.
+#[allow(clippy::too_many_arguments)]
+fn run_export_with_arg(
+    code:        &[u8],
+    export:      &str,
+    arg:         i32,
+    calldata:    &[u8],
+    storage:     BTreeMap<Vec<u8>, Vec<u8>>,
+    schema:      Option<EventSchema>,
+    precompiles: Precompiles,
+    gas_limit:   u64,
+) -> VmResult<ExportRun> {
+    if gas_limit < CALL_BASE_GAS {
+        return Err(VmError::GasExhausted { used: CALL_BASE_GAS, limit: gas_limit });
+    }
+    let mut store = Store::default();
+    let module = Module::new(&store, code).map_err(|e| VmError::WasmCompile(e.to_string()))?;
+    let env = FunctionEnv::new(&mut store, HostState {
+        memory:      None,
+        calldata:    calldata.to_vec(),
+        storage,
+        schema,
+        events:      Vec::new(),
+        precompiles,
+        host_error:  None,
+        gas_left:    gas_limit - CALL_BASE_GAS,
+        out_of_gas:  false,
+    });
+    let import_object = imports! {
+        "env" => {
+            "bleep_gas" => Function::new_typed_with_env(&mut store, &env, host_gas),
+        },
+        "bleep" => {
+            "calldata"      => Function::new_typed_with_env(&mut store, &env, host_calldata),
+            "storage_read"  => Function::new_typed_with_env(&mut store, &env, host_storage_read),
+            "storage_write" => Function::new_typed_with_env(&mut store, &env, host_storage_write),
+            "emit_event"    => Function::new_typed_with_env(&mut store, &env, host_emit_event),
+            "crypto_sha3"           => Function::new_typed_with_env(&mut store, &env, host_crypto_sha3),
+            "crypto_verify_sig"     => Function::new_typed_with_env(&mut store, &env, host_crypto_verify_sig),
+            "crypto_verify_groth16" => Function::new_typed_with_env(&mut store, &env, host_crypto_verify_groth16),
+        },
+    };
+    let instance = Instance::new(&mut store, &module, &import_object)
+        .map_err(|e| VmError::WasmInstantiation(e.to_string()))?;
+    if let Ok(mem) = instance.exports.get_memory("memory") {
+        env.as_mut(&mut store).memory = Some(mem.clone());
+    }
+    let func = instance.exports.get_typed_function::<i32, i32>(&store, export)
+        .map_err(|_| VmError::ExportNotFound { name: export.to_string() })?;
+
+    let result = func.call(&mut store, arg);
+    let state = env.as_mut(&mut store);
+    let gas_used = gas_limit - state.gas_left;
+    match result {
+        Ok(result) => Ok(ExportRun {
+            result,
+            storage: std::mem::take(&mut state.storage),
+            events:  std::mem::take(&mut state.events),
+            gas_used,
+        }),
+        Err(_) if state.out_of_gas => Err(VmError::GasExhausted { used: gas_used, limit: gas_limit }),
+        Err(_) if state.host_error.is_some() => Err(state.host_error.take().unwrap()),
+        Err(e) => Err(VmError::WasmTrap(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::event_abi::{EventBuilder, EventDef, FieldType};
+    use wasm_encoder::{
+        CodeSection, ConstExpr, CustomSection, DataSection, EntityType, ExportKind, ExportSection,
+        Function as WasmFunction, FunctionSection, ImportSection, Instruction, MemorySection,
+        MemoryType, Module as WasmModule, TypeSection, ValType,
+    };
+
+    const ADMIN: UpgradeAuthority = UpgradeAuthority::Account([1u8; 32]);
+    const GAS: u64 = 1_000_000;
+
+    /// Memory layout: "k" at 0, "1" at 1, "2" at 2; read buffer at 16.
+    /// `execute` writes `k = init` the first time, then returns
+    /// `stored_byte + add`.  `migrate`, if present, returns `migrate_ret`
+    /// after writing `k = "2"` (or trapping, if `migrate_ret` is `None`).
+    fn contract(add: i32, migrate_ret: Option<Option<i32>>) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.function([ValType::I32; 4], [ValType::I32]);        // storage_read
+        types.function([ValType::I32; 4], [] as [ValType; 0]);    // storage_write
+        types.function([ValType::I32], [ValType::I32]);            // execute / migrate
+        let mut imports = ImportSection::new();
+        imports.import("bleep", "storage_read", EntityType::Function(0));
+        imports.import("bleep", "storage_write", EntityType::Function(1));
+        let mut funcs = FunctionSection::new();
+        funcs.function(2);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export(EXECUTE_EXPORT, ExportKind::Func, 2);
+
+        // execute: if read(k) < 0 { write(k, "1") }; return mem[16] + add
+        let mut exec = WasmFunction::new([] as [(u32, ValType); 0]);
+        for i in [
+            Instruction::I32Const(0), Instruction::I32Const(1),
+            Instruction::I32Const(16), Instruction::I32Const(8),
+            Instruction::Call(0),
+            Instruction::I32Const(0), Instruction::I32LtS,
+            Instruction::If(wasm_encoder::BlockType::Empty),
+            Instruction::I32Const(0), Instruction::I32Const(1),
+            Instruction::I32Const(1), Instruction::I32Const(1),
+            Instruction::Call(1),
+            Instruction::I32Const(0), Instruction::I32Const(1),
+            Instruction::I32Const(16), Instruction::I32Const(8),
+            Instruction::Call(0), Instruction::Drop,
+            Instruction::End,
+            Instruction::I32Const(16),
+            Instruction::I32Load8U(wasm_encoder::MemArg { offset: 0, align: 0, memory_index: 0 }),
+            Instruction::I32Const(add), Instruction::I32Add,
+            Instruction::End,
+        ] {
+            exec.instruction(&i);
+        }
+        let mut code = CodeSection::new();
+        code.function(&exec);
+
+        if let Some(ret) = migrate_ret {
+            funcs.function(2);
+            exports.export(MIGRATE_EXPORT, ExportKind::Func, 3);
+            let mut mig = WasmFunction::new([] as [(u32, ValType); 0]);
+            for i in [
+                Instruction::I32Const(0), Instruction::I32Const(1),
+                Instruction::I32Const(2), Instruction::I32Const(1),
+                Instruction::Call(1),
+            ] {
+                mig.instruction(&i);
+            }
+            match ret {
+                Some(v) => mig.instruction(&Instruction::I32Const(v)),
+                None    => mig.instruction(&Instruction::Unreachable),
+            };
+            mig.instruction(&Instruction::End);
+            code.function(&mig);
+        }
+
+        let mut data = DataSection::new();
+        data.active(0, &ConstExpr::i32_const(0), b"k12".iter().copied());
+
+        let mut module = WasmModule::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code).section(&data);
+        module.finish()
+    }
+
+    /// `execute` emits one event named `name` carrying `data`, then returns 0.
+    /// `schema`, if given, is embedded as a `bleep.events` section.
+    fn emitter(name: &str, data: &[u8], schema: Option<&EventSchema>) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.function([ValType::I32; 4], [] as [ValType; 0]);
+        types.function([ValType::I32], [ValType::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", "emit_event", EntityType::Function(0));
+        let mut funcs = FunctionSection::new();
+        funcs.function(1);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export(EXECUTE_EXPORT, ExportKind::Func, 1);
+        let mut exec = WasmFunction::new([] as [(u32, ValType); 0]);
+        for i in [
+            Instruction::I32Const(0), Instruction::I32Const(name.len() as i32),
+            Instruction::I32Const(256), Instruction::I32Const(data.len() as i32),
+            Instruction::Call(0),
+            Instruction::I32Const(0),
+            Instruction::End,
+        ] {
+            exec.instruction(&i);
+        }
+        let mut code = CodeSection::new();
+        code.function(&exec);
+        let mut segments = DataSection::new();
+        segments.active(0, &ConstExpr::i32_const(0), name.bytes());
+        segments.active(0, &ConstExpr::i32_const(256), data.iter().copied());
+
+        let mut module = WasmModule::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code).section(&segments);
+        if let Some(schema) = schema {
+            let json = schema.to_json();
+            module.section(&CustomSection { name: EVENT_SCHEMA_SECTION.into(), data: json.as_slice().into() });
+        }
+        module.finish()
+    }
+
+    /// `execute` calls `bleep::{name}` with `args` and returns its result,
+    /// or for an import without one the i32 at offset 0.  `segments` are
+    /// loaded into memory first.
+    fn precompile_caller(name: &str, args: &[i32], returns: bool, segments: &[(i32, &[u8])]) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        let results = if returns { vec![ValType::I32] } else { vec![] };
+        types.function(vec![ValType::I32; args.len()], results);
+        types.function([ValType::I32], [ValType::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", name, EntityType::Function(0));
+        let mut funcs = FunctionSection::new();
+        funcs.function(1);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 2, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export(EXECUTE_EXPORT, ExportKind::Func, 1);
+        let mut exec = WasmFunction::new([] as [(u32, ValType); 0]);
+        for a in args {
+            exec.instruction(&Instruction::I32Const(*a));
+        }
+        exec.instruction(&Instruction::Call(0));
+        if !returns {
+            exec.instruction(&Instruction::I32Const(0));
+            exec.instruction(&Instruction::I32Load(wasm_encoder::MemArg { offset: 0, align: 2, memory_index: 0 }));
+        }
+        exec.instruction(&Instruction::End);
+        let mut code = CodeSection::new();
+        code.function(&exec);
+        let mut data = DataSection::new();
+        for (offset, bytes) in segments {
+            data.active(0, &ConstExpr::i32_const(*offset), bytes.iter().copied());
+        }
+
+        let mut module = WasmModule::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code).section(&data);
+        module.finish()
+    }
+
+    /// Memory layout for `crypto_verify_sig`: message at 0, key at 1024,
+    /// signature at 2048.
+    fn verify_sig_call(msg: &[u8], sig: &[u8], pk: &[u8], sig_len: i32) -> Vec<u8> {
+        precompile_caller(
+            "crypto_verify_sig",
+            &[precompiles::SIG_SCHEME_SPHINCS, 0, msg.len() as i32, 2048, sig_len, 1024, pk.len() as i32],
+            true,
+            &[(0, msg), (1024, pk), (2048, sig)],
+        )
+    }
+
+    fn transfer_schema() -> EventSchema {
+        EventSchema::new(vec![
+            EventDef::new("Transfer").field("to", FieldType::Address).field("amount", FieldType::U128),
+        ]).unwrap()
+    }
+
+    fn deployed(upgradeable: bool) -> (ContractRegistry, [u8; 32]) {
+        let reg = ContractRegistry::new();
+        let admin = if upgradeable { Some(ADMIN) } else { None };
+        let addr = reg.deploy(&contract(0, None), upgradeable, admin, None).unwrap();
+        // First call initialises k = "1" (0x31).
+        assert_eq!(reg.call(&addr, &[], GAS).unwrap().output, 0x31i32.to_le_bytes().to_vec());
+        (reg, addr)
+    }
+
+    #[test]
+    fn upgrade_keeps_storage_and_changes_behavior() {
+        let (reg, addr) = deployed(true);
+        let v1 = reg.info(&addr).unwrap().code_hash;
+
+        let rec = reg.upgrade(&addr, &contract(100, Some(Some(0))), ADMIN, GAS).unwrap();
+        assert_eq!(rec.version, 1);
+        assert_eq!(rec.from_code_hash, v1);
+        assert!(rec.migrate_gas_used > 0);
+        // migrate rewrote k in place; new code adds 100.
+        assert_eq!(reg.storage_get(&addr, b"k"), Some(b"2".to_vec()));
+        assert_eq!(reg.call(&addr, &[], GAS).unwrap().output, (0x32i32 + 100).to_le_bytes().to_vec());
+
+        assert!(reg.code(&v1).is_some(), "old code is retained");
+        assert_eq!(reg.history(&addr).unwrap(), vec![rec]);
+        assert_eq!(reg.take_events().len(), 1);
+    }
+
+    #[test]
+    fn dry_run_reads_given_storage_and_keeps_nothing() {
+        let reg = ContractRegistry::new();
+        let addr = reg.deploy(&contract(0, None), false, None, None).unwrap();
+        let code_hash = reg.info(&addr).unwrap().code_hash;
+
+        let run = reg.dry_run(&addr, &code_hash, &[], GAS, BTreeMap::new()).unwrap();
+        assert_eq!(run.storage.get(b"k".as_slice()), Some(&b"1".to_vec()));
+        assert_eq!(run.output, 0x31i32.to_le_bytes().to_vec());
+        assert_eq!(reg.storage_get(&addr, b"k"), None, "the registry's storage is untouched");
+
+        // The given storage is what the contract reads
+        let past = BTreeMap::from([(b"k".to_vec(), b"2".to_vec())]);
+        assert_eq!(reg.dry_run(&addr, &code_hash, &[], GAS, past).unwrap().output, 0x32i32.to_le_bytes().to_vec());
+        assert!(reg.dry_run(&addr, &[0u8; 32], &[], GAS, BTreeMap::new()).is_err());
+    }
+
+    #[test]
+    fn failed_migrate_changes_nothing() {
+        let (reg, addr) = deployed(true);
+        let before = reg.info(&addr).unwrap();
+
+        // Non-zero return, trap, out of gas, and no `migrate` at all.
+        for (code, gas) in [
+            (contract(100, Some(Some(7))), GAS),
+            (contract(100, Some(None)), GAS),
+            (contract(100, Some(Some(0))), CALL_BASE_GAS + 10),
+            (contract(100, None), GAS),
+        ] {
+            assert!(reg.upgrade(&addr, &code, ADMIN, gas).is_err());
+        }
+        assert_eq!(reg.info(&addr).unwrap(), before);
+        assert_eq!(reg.storage_get(&addr, b"k"), Some(b"1".to_vec()));
+        assert!(reg.history(&addr).unwrap().is_empty());
+        assert!(reg.take_events().is_empty());
+    }
+
+    #[test]
+    fn only_admin_may_upgrade_upgradeable_contracts() {
+        let (reg, addr) = deployed(true);
+        let other = UpgradeAuthority::Account([2u8; 32]);
+        let new_code = contract(100, Some(Some(0)));
+        assert!(matches!(reg.upgrade(&addr, &new_code, other, GAS), Err(VmError::UpgradeRejected(_))));
+        assert!(matches!(
+            reg.upgrade(&addr, &new_code, UpgradeAuthority::Governance, GAS),
+            Err(VmError::UpgradeRejected(_))
+        ));
+
+        let (fixed, fixed_addr) = deployed(false);
+        assert!(matches!(fixed.upgrade(&fixed_addr, &new_code, ADMIN, GAS), Err(VmError::UpgradeRejected(_))));
+        assert!(fixed.rollback(&fixed_addr, ADMIN).is_err());
+    }
+
+    #[test]
+    fn rollback_restores_previous_code() {
+        let (reg, addr) = deployed(true);
+        let v1 = reg.info(&addr).unwrap().code_hash;
+        reg.upgrade(&addr, &contract(100, Some(Some(0))), ADMIN, GAS).unwrap();
+
+        let rec = reg.rollback(&addr, ADMIN).unwrap();
+        assert_eq!(rec.kind, UpgradeKind::Rollback);
+        assert_eq!(rec.version, 2);
+        assert_eq!(reg.info(&addr).unwrap().code_hash, v1);
+        // Old behavior over migrated storage.
+        assert_eq!(reg.call(&addr, &[], GAS).unwrap().output, 0x32i32.to_le_bytes().to_vec());
+        assert!(reg.rollback(&addr, ADMIN).is_err(), "nothing left to roll back");
+        assert_eq!(reg.history(&addr).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn embedded_schema_checks_emitted_events() {
+        let reg = ContractRegistry::new();
+        let schema = transfer_schema();
+        let (name, data) = EventBuilder::new("Transfer").address([7u8; 32]).u128(500).build();
+        let addr = reg.deploy(&emitter(&name, &data, Some(&schema)), false, None, None).unwrap();
+        assert_eq!(reg.event_schema(&addr), Some(schema.clone()));
+
+        reg.call(&addr, &[], GAS).unwrap();
+        let log = reg.event_log(&addr).unwrap();
+        assert_eq!(log.len(), 1);
+        let decoded = schema.decode(&log[0]).unwrap();
+        assert_eq!(decoded.name, "Transfer");
+        assert_eq!(decoded.fields["amount"], crate::execution::event_abi::FieldValue::U128(500));
+        assert_eq!(reg.take_events(), log);
+    }
+
+    #[test]
+    fn schema_violating_event_fails_the_call() {
+        let reg = ContractRegistry::new();
+        // One field short of the declared two.
+        let (name, data) = EventBuilder::new("Transfer").address([7u8; 32]).build();
+        let addr = reg.deploy_with_schema(&emitter(&name, &data, None), false, None, None, Some(transfer_schema()))
+            .unwrap();
+        assert!(matches!(reg.call(&addr, &[], GAS), Err(VmError::EventRejected(_))));
+
+        let big = vec![0u8; MAX_EVENT_DATA + 1];
+        let addr = reg.deploy(&emitter("Blob", &big, None), false, None, None).unwrap();
+        assert!(matches!(reg.call(&addr, &[], GAS), Err(VmError::EventRejected(_))));
+        assert!(reg.take_events().is_empty());
+    }
+
+    #[test]
+    fn unknown_event_is_stored_raw() {
+        let reg = ContractRegistry::new();
+        let schema = transfer_schema();
+        let addr = reg.deploy(&emitter("Mystery", b"\x01\x02", Some(&schema)), false, None, None).unwrap();
+        reg.call(&addr, &[], GAS).unwrap();
+        let log = reg.event_log(&addr).unwrap();
+        assert_eq!(log[0].data, b"\x01\x02".to_vec());
+        assert!(schema.decode(&log[0]).unwrap_err().contains("unknown event"));
+
+        // A schema passed at deploy cannot also be embedded.
+        assert!(reg.deploy_with_schema(&emitter("X", &[], Some(&schema)), false, None, Some([1; 32]), Some(schema))
+            .is_err());
+    }
+
+    #[test]
+    fn sphincs_precompile_matches_native_verification() {
+        let reg = ContractRegistry::new();
+        let (pk, sk) = bleep_crypto::generate_tx_keypair();
+        let sig = bleep_crypto::sign_tx_payload(b"pay 5 to bob", &sk).unwrap();
+        let sphincs_gas = reg.precompile_gas().sphincs_verify;
+
+        for (salt, msg) in [(0u8, b"pay 5 to bob"), (1, b"pay 9 to bob")] {
+            let native = bleep_crypto::verify_tx_signature(msg, &sig, &pk);
+            let code = verify_sig_call(msg, &sig, &pk, sig.len() as i32);
+            let addr = reg.deploy(&code, false, None, Some([salt; 32])).unwrap();
+            let out = reg.call(&addr, &[], GAS).unwrap();
+            assert_eq!(out.output, (native as i32).to_le_bytes().to_vec());
+            assert_eq!(out.gas_used, CALL_BASE_GAS + sphincs_gas);
+        }
+    }
+
+    #[test]
+    fn precompile_gas_follows_the_schedule() {
+        let gas = PrecompileGas { sha3_base: 10, sha3_word: 3, ..PrecompileGas::default() };
+        let reg = ContractRegistry::new().with_precompile_gas(gas);
+        let input = [0xabu8; 100];
+        let code = precompile_caller("crypto_sha3", &[64, 100, 0], false, &[(64, &input[..])]);
+        let addr = reg.deploy(&code, false, None, None).unwrap();
+
+        let out = reg.call(&addr, &[], GAS).unwrap();
+        let digest = precompiles::sha3(&input);
+        assert_eq!(out.output, digest[..4].to_vec());
+        assert_eq!(out.gas_used, CALL_BASE_GAS + 10 + 4 * 3);
+
+        reg.set_precompile_gas(PrecompileGas { sha3_base: 1_000, ..gas });
+        assert_eq!(reg.call(&addr, &[], GAS).unwrap().gas_used, CALL_BASE_GAS + 1_000 + 4 * 3);
+        assert!(matches!(reg.call(&addr, &[], CALL_BASE_GAS + 500), Err(VmError::GasExhausted { .. })));
+    }
+
+    #[test]
+    fn malformed_precompile_lengths_fail_the_call() {
+        let reg = ContractRegistry::new();
+        let (pk, sk) = bleep_crypto::generate_tx_keypair();
+        let sig = bleep_crypto::sign_tx_payload(b"m", &sk).unwrap();
+        let too_long = precompiles::MAX_HASH_INPUT as i32 + 1;
+        for (salt, code) in [
+            (0u8, verify_sig_call(b"m", &sig, &pk, 100)),
+            (1, verify_sig_call(b"m", &sig, &pk, -1)),
+            (2, precompile_caller("crypto_verify_sig", &[precompiles::SIG_SCHEME_FALCON, 0, 1, 0, 1, 0, 1], true, &[])),
+            (3, precompile_caller("crypto_sha3", &[0, too_long, 0], false, &[])),
+            (4, precompile_caller("crypto_verify_groth16", &[0, 10, 0, 32, 0], true, &[])),
+            (5, precompile_caller("crypto_verify_groth16", &[0, 192, 0, 33, 0], true, &[])),
+        ] {
+            let addr = reg.deploy(&code, false, None, Some([salt; 32])).unwrap();
+            assert!(matches!(reg.call(&addr, &[], GAS), Err(VmError::PrecompileRejected(_))), "case {salt}");
+        }
+    }
+
+    #[test]
+    fn unregistered_groth16_key_fails_cleanly() {
+        let reg = ContractRegistry::new();
+        let code = precompile_caller("crypto_verify_groth16", &[0, 192, 256, 32, 7], true, &[]);
+        let addr = reg.deploy(&code, false, None, None).unwrap();
+        match reg.call(&addr, &[], GAS) {
+            Err(VmError::PrecompileRejected(msg)) => assert!(msg.contains("registered as 7"), "{msg}"),
+            other => panic!("expected PrecompileRejected, got {other:?}"),
+        }
+        assert!(matches!(reg.register_groth16_key(7, &[1, 2, 3]), Err(VmError::ValidationError(_))));
+    }
+
+    #[test]
+    fn calldata_is_copied_when_it_fits() {
+        // execute: n = calldata(0, 4); return n * 256 + mem[0]
+        let mut types = TypeSection::new();
+        types.function([ValType::I32; 2], [ValType::I32]);
+        types.function([ValType::I32], [ValType::I32]);
+        let mut imports = ImportSection::new();
+        imports.import("bleep", "calldata", EntityType::Function(0));
+        let mut funcs = FunctionSection::new();
+        funcs.function(1);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut exports = ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export(EXECUTE_EXPORT, ExportKind::Func, 1);
+        let mut exec = WasmFunction::new([] as [(u32, ValType); 0]);
+        for i in [
+            Instruction::I32Const(0), Instruction::I32Const(4),
+            Instruction::Call(0),
+            Instruction::I32Const(256), Instruction::I32Mul,
+            Instruction::I32Const(0),
+            Instruction::I32Load8U(wasm_encoder::MemArg { offset: 0, align: 0, memory_index: 0 }),
+            Instruction::I32Add,
+            Instruction::End,
+        ] {
+            exec.instruction(&i);
+        }
+        let mut code = CodeSection::new();
+        code.function(&exec);
+        let mut module = WasmModule::new();
+        module.section(&types).section(&imports).section(&funcs).section(&memory)
+            .section(&exports).section(&code);
+
+        let reg = ContractRegistry::new();
+        let addr = reg.deploy(&module.finish(), false, None, None).unwrap();
+        let output = |calldata: &[u8]| i32::from_le_bytes(reg.call(&addr, calldata, GAS).unwrap().output.try_into().unwrap());
+        assert_eq!(output(&[9, 8, 7]), 3 * 256 + 9);
+        assert_eq!(output(&[]), 0);
+        // Too long for the buffer: only the length comes back.
+        assert_eq!(output(&[5; 6]), 6 * 256);
+    }
+}
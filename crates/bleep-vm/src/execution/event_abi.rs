@@ -0,0 +1,338 @@
+//! # Contract event ABI
+//!
+//! A contract may describe the events it emits with a JSON schema, either
+//! embedded in a custom wasm section named [`EVENT_SCHEMA_SECTION`] or passed
+//! to `ContractRegistry::deploy_with_schema`:
+//!
+//! ```json
+//! { "events": [
+//!     { "name": "Transfer",
+//!       "fields": [ { "name": "to",     "type": "address" },
+//!                   { "name": "amount", "type": "u128" } ] } ] }
+//! ```
+//!
+//! Field types are `u64`, `u128`, `address`, `bytes` and `string`.  Event
+//! data is the fields in schema order: integers little-endian, addresses as
+//! 32 raw bytes, `bytes` and `string` as a `u32` little-endian length
+//! followed by the bytes.  [`EventBuilder`] produces exactly this layout.
+//!
+//! The first topic of a stored event is `sha256(name)`.  Events whose name
+//! is in the schema must decode exactly or the call fails; events with a
+//! name the schema does not know are stored raw and decode to a warning.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::{Deserialize, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use wasmparser::{Parser, Payload};
+
+use crate::error::{VmError, VmResult};
+use crate::execution::state_transition::EmittedEvent;
+
+/// Custom wasm section holding the event schema JSON.
+pub const EVENT_SCHEMA_SECTION: &str = "bleep.events";
+/// Largest event payload a contract may emit, in bytes.
+pub const MAX_EVENT_DATA: usize = 4_096;
+/// Most events one schema may declare.
+pub const MAX_SCHEMA_EVENTS: usize = 64;
+/// Most fields one event may declare.
+pub const MAX_EVENT_FIELDS: usize = 16;
+/// Longest event or field name.
+pub const MAX_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    U64,
+    U128,
+    Address,
+    Bytes,
+    String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty:   FieldType,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventDef {
+    pub name:   String,
+    #[serde(default)]
+    pub fields: Vec<EventField>,
+}
+
+impl EventDef {
+    pub fn new(name: &str) -> Self {
+        EventDef { name: name.to_string(), fields: Vec::new() }
+    }
+
+    pub fn field(mut self, name: &str, ty: FieldType) -> Self {
+        self.fields.push(EventField { name: name.to_string(), ty });
+        self
+    }
+
+    pub fn topic(&self) -> [u8; 32] {
+        event_topic(&self.name)
+    }
+}
+
+/// Events a contract declares.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSchema {
+    pub events: Vec<EventDef>,
+}
+
+/// A decoded field value.  Serialises as a JSON number (`u64`), a decimal
+/// string (`u128`), hex (`address`, `bytes`) or a string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    U64(u64),
+    U128(u128),
+    Address([u8; 32]),
+    Bytes(Vec<u8>),
+    String(String),
+}
+
+impl Serialize for FieldValue {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        match self {
+            FieldValue::U64(v)     => s.serialize_u64(*v),
+            FieldValue::U128(v)    => s.serialize_str(&v.to_string()),
+            FieldValue::Address(a) => s.serialize_str(&hex::encode(a)),
+            FieldValue::Bytes(b)   => s.serialize_str(&hex::encode(b)),
+            FieldValue::String(v)  => s.serialize_str(v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedEvent {
+    pub name:   String,
+    pub fields: BTreeMap<String, FieldValue>,
+}
+
+/// First topic of an event called `name`.
+pub fn event_topic(name: &str) -> [u8; 32] {
+    Sha256::digest(name.as_bytes()).into()
+}
+
+impl EventSchema {
+    pub fn new(events: Vec<EventDef>) -> VmResult<Self> {
+        let schema = EventSchema { events };
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    /// Parse and validate a JSON descriptor.
+    pub fn from_json(json: &[u8]) -> VmResult<Self> {
+        let schema: EventSchema = serde_json::from_slice(json)
+            .map_err(|e| schema_error(format!("invalid event schema JSON: {e}")))?;
+        schema.validate()?;
+        Ok(schema)
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("schema serialises")
+    }
+
+    /// The schema embedded in `code`, if it has an [`EVENT_SCHEMA_SECTION`].
+    pub fn from_wasm(code: &[u8]) -> VmResult<Option<Self>> {
+        let mut found = None;
+        for payload in Parser::new(0).parse_all(code) {
+            let payload = payload.map_err(|e| VmError::WasmCompile(e.to_string()))?;
+            if let Payload::CustomSection(section) = payload {
+                if section.name() == EVENT_SCHEMA_SECTION {
+                    if found.is_some() {
+                        return Err(schema_error(format!("duplicate `{EVENT_SCHEMA_SECTION}` section")));
+                    }
+                    found = Some(Self::from_json(section.data())?);
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    pub fn validate(&self) -> VmResult<()> {
+        if self.events.len() > MAX_SCHEMA_EVENTS {
+            return Err(schema_error(format!("more than {MAX_SCHEMA_EVENTS} events")));
+        }
+        let mut names = HashSet::new();
+        for ev in &self.events {
+            check_name(&ev.name)?;
+            if !names.insert(ev.name.as_str()) {
+                return Err(schema_error(format!("event `{}` declared twice", ev.name)));
+            }
+            if ev.fields.len() > MAX_EVENT_FIELDS {
+                return Err(schema_error(format!("event `{}` has more than {MAX_EVENT_FIELDS} fields", ev.name)));
+            }
+            let mut fields = HashSet::new();
+            for f in &ev.fields {
+                check_name(&f.name)?;
+                if !fields.insert(f.name.as_str()) {
+                    return Err(schema_error(format!("event `{}` declares field `{}` twice", ev.name, f.name)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn event(&self, name: &str) -> Option<&EventDef> {
+        self.events.iter().find(|e| e.name == name)
+    }
+
+    pub fn by_topic(&self, topic: &[u8; 32]) -> Option<&EventDef> {
+        self.events.iter().find(|e| &e.topic() == topic)
+    }
+
+    /// Check an event a contract is emitting.  Unknown names pass; they are
+    /// stored raw.
+    pub fn check(&self, name: &str, data: &[u8]) -> VmResult<()> {
+        match self.event(name) {
+            Some(def) => decode_fields(def, data).map(|_| ()).map_err(VmError::EventRejected),
+            None => Ok(()),
+        }
+    }
+
+    /// Decode a stored event.  `Err` carries a warning for the client.
+    pub fn decode(&self, ev: &EmittedEvent) -> Result<DecodedEvent, String> {
+        let topic = ev.topics.first().ok_or("event has no topics")?;
+        let def = self.by_topic(topic)
+            .ok_or_else(|| format!("unknown event topic {}", hex::encode(topic)))?;
+        Ok(DecodedEvent { name: def.name.clone(), fields: decode_fields(def, &ev.data)? })
+    }
+}
+
+fn schema_error(msg: String) -> VmError {
+    VmError::ValidationError(msg)
+}
+
+fn check_name(name: &str) -> VmResult<()> {
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(schema_error(format!("name `{name}` must be 1..={MAX_NAME_LEN} bytes")));
+    }
+    Ok(())
+}
+
+fn decode_fields(def: &EventDef, data: &[u8]) -> Result<BTreeMap<String, FieldValue>, String> {
+    if data.len() > MAX_EVENT_DATA {
+        return Err(format!("event `{}` is {} bytes, limit {MAX_EVENT_DATA}", def.name, data.len()));
+    }
+    let arity = || format!("event `{}` does not match its {} declared fields", def.name, def.fields.len());
+    let mut rest = data;
+    let mut take = |n: usize| -> Result<&[u8], String> {
+        if rest.len() < n { return Err(arity()); }
+        let (head, tail) = rest.split_at(n);
+        rest = tail;
+        Ok(head)
+    };
+    let mut out = BTreeMap::new();
+    for f in &def.fields {
+        let value = match f.ty {
+            FieldType::U64     => FieldValue::U64(u64::from_le_bytes(take(8)?.try_into().unwrap())),
+            FieldType::U128    => FieldValue::U128(u128::from_le_bytes(take(16)?.try_into().unwrap())),
+            FieldType::Address => FieldValue::Address(take(32)?.try_into().unwrap()),
+            FieldType::Bytes | FieldType::String => {
+                let len = u32::from_le_bytes(take(4)?.try_into().unwrap()) as usize;
+                let bytes = take(len)?.to_vec();
+                if f.ty == FieldType::Bytes {
+                    FieldValue::Bytes(bytes)
+                } else {
+                    FieldValue::String(String::from_utf8(bytes)
+                        .map_err(|_| format!("field `{}` of `{}` is not UTF-8", f.name, def.name))?)
+                }
+            }
+        };
+        out.insert(f.name.clone(), value);
+    }
+    if !rest.is_empty() {
+        return Err(arity());
+    }
+    Ok(out)
+}
+
+/// Encodes an event payload in schema field order.
+///
+/// ```rust,ignore
+/// let (name, data) = EventBuilder::new("Transfer").address(to).u128(amount).build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventBuilder {
+    name: String,
+    data: Vec<u8>,
+}
+
+impl EventBuilder {
+    pub fn new(name: &str) -> Self {
+        EventBuilder { name: name.to_string(), data: Vec::new() }
+    }
+
+    pub fn u64(mut self, v: u64) -> Self {
+        self.data.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn u128(mut self, v: u128) -> Self {
+        self.data.extend_from_slice(&v.to_le_bytes());
+        self
+    }
+
+    pub fn address(mut self, a: [u8; 32]) -> Self {
+        self.data.extend_from_slice(&a);
+        self
+    }
+
+    pub fn bytes(mut self, b: &[u8]) -> Self {
+        self.data.extend_from_slice(&(b.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(b);
+        self
+    }
+
+    pub fn string(self, s: &str) -> Self {
+        self.bytes(s.as_bytes())
+    }
+
+    /// Event name and payload, ready for the `bleep::emit_event` import.
+    pub fn build(self) -> (String, Vec<u8>) {
+        (self.name, self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> EventSchema {
+        EventSchema::new(vec![
+            EventDef::new("Transfer").field("to", FieldType::Address).field("amount", FieldType::U128),
+            EventDef::new("Note").field("id", FieldType::U64).field("text", FieldType::String),
+        ]).unwrap()
+    }
+
+    #[test]
+    fn builder_round_trips_through_decode() {
+        let s = schema();
+        let (name, data) = EventBuilder::new("Note").u64(7).string("hi").build();
+        s.check(&name, &data).unwrap();
+        let ev = EmittedEvent { contract: [0; 32], topics: vec![event_topic(&name)], data, log_index: 0 };
+        let decoded = s.decode(&ev).unwrap();
+        assert_eq!(decoded.fields["id"], FieldValue::U64(7));
+        assert_eq!(decoded.fields["text"], FieldValue::String("hi".into()));
+    }
+
+    #[test]
+    fn wrong_arity_and_bad_schemas_are_rejected() {
+        let s = schema();
+        let (_, short) = EventBuilder::new("Transfer").address([1; 32]).build();
+        assert!(matches!(s.check("Transfer", &short), Err(VmError::EventRejected(_))));
+        let (_, long) = EventBuilder::new("Note").u64(1).string("x").u64(2).build();
+        assert!(s.check("Note", &long).is_err());
+        assert!(s.check("Unknown", &long).is_ok());
+
+        assert!(EventSchema::from_json(br#"{"events":[{"name":"A"},{"name":"A"}]}"#).is_err());
+        assert!(EventSchema::from_json(br#"{"events":[{"name":"A","fields":[{"name":"x","type":"f32"}]}]}"#).is_err());
+        assert_eq!(EventSchema::from_json(&s.to_json()).unwrap(), s);
+    }
+}
@@ -19,28 +19,32 @@ use std::time::Instant;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockEnv {
     pub number:      u64,
+    /// Header timestamp — never the host clock, so every validator sees
+    /// the same value.
     pub timestamp:   u64,
     pub gas_limit:   u64,
     pub coinbase:    [u8; 32],
     pub difficulty:  [u8; 32],
     pub base_fee:    u64,
     pub chain_id:    u64,
+    /// Hash of the previous block; keys `random_seed` (`runtime::determinism`).
+    #[serde(default)]
+    pub prev_hash:   [u8; 32],
 }
 
 impl Default for BlockEnv {
+    /// A block with no real header.  Executors running a block pass its
+    /// header through `Executor::execute_in_block` instead.
     fn default() -> Self {
-        use std::time::{SystemTime, UNIX_EPOCH};
         BlockEnv {
-            number:    1,
-            timestamp: SystemTime::now()
-                           .duration_since(UNIX_EPOCH)
-                           .unwrap_or_default()
-                           .as_secs(),
+            number:     1,
+            timestamp:  0,
             gas_limit:  30_000_000,
             coinbase:   [0u8; 32],
             difficulty: [0u8; 32],
             base_fee:   1_000_000_000, // 1 Gwei
             chain_id:   0xB1EE, // 0xB1EE = BLEEP
+            prev_hash:  [0u8; 32],
         }
     }
 }
@@ -155,6 +159,11 @@ impl ExecutionContext {
 
     /// Derive a context from an intent (uses default block/tx envs for unit tests).
     pub fn from_intent(intent: &Intent, max_call_depth: usize) -> Self {
+        Self::from_intent_in_block(intent, BlockEnv::default(), max_call_depth)
+    }
+
+    /// Derive a context for executing `intent` in `block`.
+    pub fn from_intent_in_block(intent: &Intent, block: BlockEnv, max_call_depth: usize) -> Self {
         let gas_limit = intent.gas_limit();
         let (caller, value, calldata) = match &intent.kind {
             IntentKind::ContractCall(c) => (intent.signer, c.value, c.calldata.clone()),
@@ -164,7 +173,7 @@ impl ExecutionContext {
         };
         let tx = TxEnv { caller, value, calldata, nonce: intent.nonce, ..Default::default() };
         ExecutionContext::new(
-            BlockEnv::default(),
+            block,
             tx,
             gas_limit,
             intent.source_chain.clone(),
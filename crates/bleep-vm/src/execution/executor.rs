@@ -28,10 +28,14 @@ use crate::engines::evm_engine::EvmEngine;
 use crate::engines::wasm_engine_adapter::WasmEngineAdapter;
 use crate::engines::zk_engine_adapter::ZkEngineAdapter;
 use crate::error::VmResult;
+use crate::execution::execution_context::BlockEnv;
 use crate::execution::state_transition::{StateDiff, StateTransition};
+use crate::execution::trace::TraceStep;
 use crate::intent::{Intent, IntentKind};
 use crate::router::vm_router::{RouterConfig, RoutedResult, VmRouter};
 use crate::runtime::gas_model::GasModel;
+use crate::runtime::instance_pool::{PoolConfig, PoolStats};
+use crate::runtime::sandbox::{SandboxConfig, SecurityPolicy};
 
 use std::sync::Arc;
 use std::time::{Duration, Instant};
@@ -48,6 +52,14 @@ pub struct ExecutorConfig {
     pub block_number: u64,
     /// Whether to produce `StateTransition` objects for the state layer.
     pub emit_transitions: bool,
+    /// Record a step-level execution trace (see `execution::trace`).
+    /// Off in production; the replay tool turns it on.
+    pub trace: bool,
+    /// Offer contracts the host-clock `env.timestamp` import.  Devnets
+    /// only: it differs between validators (see `runtime::determinism`).
+    pub wall_clock: bool,
+    /// Warm WASM instance pool sizing (see `runtime::instance_pool`).
+    pub wasm_pool: PoolConfig,
 }
 
 impl Default for ExecutorConfig {
@@ -56,6 +68,9 @@ impl Default for ExecutorConfig {
             router:           RouterConfig::default(),
             block_number:     1,
             emit_transitions: true,
+            trace:            false,
+            wall_clock:       false,
+            wasm_pool:        PoolConfig::default(),
         }
     }
 }
@@ -94,6 +109,7 @@ impl ExecutionOutcome {
 /// Instantiate once per node; it's thread-safe.
 pub struct Executor {
     router:  VmRouter,
+    wasm:    Arc<WasmEngineAdapter>,
     bridge:  Arc<ConnectBridge>,
     #[allow(dead_code)]
     gas:     GasModel,
@@ -104,23 +120,45 @@ impl Executor {
     /// Build a production executor with all engines registered.
     pub fn production(config: ExecutorConfig) -> Self {
         let mut router = VmRouter::new(config.router.clone());
+        if config.wall_clock {
+            let policy = SecurityPolicy { allow_wall_clock: true, ..SecurityPolicy::default() };
+            router = router.with_sandbox(SandboxConfig { policy, ..SandboxConfig::default() });
+        }
 
         // Register Layer 3 engines
-        router.register_engine(Arc::new(WasmEngineAdapter::new()));
+        let wasm = Arc::new(
+            WasmEngineAdapter::new()
+                .with_tracing(config.trace)
+                .with_wall_clock(config.wall_clock)
+                .with_pool_config(config.wasm_pool),
+        );
+        router.register_engine(wasm.clone());
         router.register_engine(Arc::new(EvmEngine::new()));
         router.register_engine(Arc::new(ZkEngineAdapter::new()));
 
         Executor {
             router,
+            wasm,
             bridge: Arc::new(ConnectBridge::new()),
             gas:    GasModel::default(),
             config,
         }
     }
 
+    /// Warm WASM instance pool counters.
+    pub fn wasm_pool_stats(&self) -> PoolStats {
+        self.wasm.pool_stats()
+    }
+
     /// Execute one intent end-to-end through all 7 layers.
-    #[instrument(skip(self, intent), fields(intent_id = %intent.id))]
     pub async fn execute(&self, intent: &Intent) -> VmResult<ExecutionOutcome> {
+        self.execute_in_block(intent, BlockEnv { number: self.config.block_number, ..BlockEnv::default() }).await
+    }
+
+    /// Execute one intent as part of `block`, whose header the contract
+    /// environment imports report.
+    #[instrument(skip(self, intent, block), fields(intent_id = %intent.id))]
+    pub async fn execute_in_block(&self, intent: &Intent, block: BlockEnv) -> VmResult<ExecutionOutcome> {
         let start = Instant::now();
 
         // Handle cross-chain intents specially (Layer 7)
@@ -128,7 +166,7 @@ impl Executor {
             let (msg_id, diff) = self.bridge.submit(x, &intent.signer).await?;
             let bleep_gas = diff.gas_charged;
             let transition = if self.config.emit_transitions {
-                Some(StateTransition::new(intent.id, diff.clone(), self.config.block_number))
+                Some(StateTransition::new(intent.id, diff.clone(), block.number))
             } else {
                 None
             };
@@ -163,14 +201,24 @@ impl Executor {
         }
 
         // All other intents go through the router (Layers 2-6)
-        let routed = self.router.route(intent).await?;
+        let block_number = block.number;
+        let mut routed = self.router.route_in_block(intent, block).await?;
         let bleep_gas = routed.bleep_gas;
 
+        if self.config.trace {
+            let result = &mut routed.result;
+            let diff_steps: Vec<TraceStep> = result.state_diff.balances.iter()
+                .map(|(account, update)| TraceStep::BalanceChange { account: *account, delta: update.delta })
+                .collect();
+            result.logs.extend(diff_steps.iter().map(TraceStep::to_log));
+            result.logs.push(TraceStep::GasCheckpoint { gas_used: bleep_gas }.to_log());
+        }
+
         let transition = if self.config.emit_transitions {
             Some(StateTransition::new(
                 intent.id,
                 routed.result.state_diff.clone(),
-                self.config.block_number,
+                block_number,
             ))
         } else {
             None
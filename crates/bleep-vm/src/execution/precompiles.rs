@@ -0,0 +1,216 @@
+//! # Crypto precompiles
+//!
+//! Native implementations of the hashing and verification work contracts
+//! cannot afford to do in WASM.  `ContractRegistry` exposes them as
+//! reserved `bleep` host imports:
+//!
+//! - `crypto_sha3(ptr, len, out_ptr)` — writes SHA3-256 of the input to
+//!   `out_ptr` (32 bytes)
+//! - `crypto_verify_sig(scheme, msg_ptr, msg_len, sig_ptr, sig_len, pk_ptr,
+//!   pk_len) -> i32` — 1 if the signature is valid, 0 if not
+//! - `crypto_verify_groth16(proof_ptr, proof_len, inputs_ptr, inputs_len,
+//!   vk_id) -> i32` — 1 if the proof verifies under the verifying key
+//!   registered as `vk_id`, 0 if not
+//!
+//! Every length is checked against the limits below before any memory is
+//! read or native code runs; a bad length, an unknown scheme or an
+//! unregistered key fails the call with `VmError::PrecompileRejected`.
+//! Costs come from a `PrecompileGas` schedule, which governance may replace
+//! at runtime with `ContractRegistry::set_precompile_gas`.  The work itself
+//! is done by `bleep-crypto`.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bleep_crypto::zkp_verification::{decode_groth16_proof, decode_public_input, Groth16Key};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::error::{VmError, VmResult};
+
+/// Host imports reserved for precompiles in the `bleep` module.
+pub const PRECOMPILE_IMPORTS: &[&str] = &["crypto_sha3", "crypto_verify_sig", "crypto_verify_groth16"];
+
+/// `scheme` for SPHINCS+-SHAKE-256f-simple, the transaction signature scheme.
+pub const SIG_SCHEME_SPHINCS: i32 = 0;
+/// `scheme` reserved for Falcon-512; rejected until `bleep-crypto` ships a
+/// verifier.
+pub const SIG_SCHEME_FALCON: i32 = 1;
+
+pub const SPHINCS_PUBLIC_KEY_BYTES: usize = 64;
+pub const SPHINCS_SIGNATURE_BYTES: usize = 49_856;
+
+/// Largest input to `crypto_sha3`.
+pub const MAX_HASH_INPUT: usize = 64 * 1024;
+/// Largest message to `crypto_verify_sig`.
+pub const MAX_SIGNED_MESSAGE: usize = 64 * 1024;
+
+/// Compressed BLS12-381 Groth16 proof.
+pub const GROTH16_PROOF_BYTES: usize = 192;
+/// Compressed scalar per public input.
+pub const GROTH16_INPUT_BYTES: usize = 32;
+pub const MAX_GROTH16_INPUTS: usize = 64;
+
+/// Gas charged by each precompile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrecompileGas {
+    /// `crypto_sha3`: base plus per 32-byte word of input.
+    pub sha3_base:      u64,
+    pub sha3_word:      u64,
+    /// `crypto_verify_sig` with `SIG_SCHEME_SPHINCS`.
+    pub sphincs_verify: u64,
+    /// `crypto_verify_groth16`: base plus per public input.
+    pub groth16_base:   u64,
+    pub groth16_input:  u64,
+}
+
+impl Default for PrecompileGas {
+    fn default() -> Self {
+        PrecompileGas {
+            sha3_base:      30,
+            sha3_word:      6,
+            sphincs_verify: 200_000,
+            groth16_base:   180_000,
+            groth16_input:  6_000,
+        }
+    }
+}
+
+impl PrecompileGas {
+    pub fn sha3(&self, len: usize) -> u64 {
+        let words = (len as u64).div_ceil(32);
+        self.sha3_base.saturating_add(words.saturating_mul(self.sha3_word))
+    }
+
+    /// Cost of verifying under `scheme`, which has passed `check_sig`.
+    pub fn verify_sig(&self, scheme: i32) -> u64 {
+        match scheme {
+            SIG_SCHEME_SPHINCS => self.sphincs_verify,
+            _ => u64::MAX,
+        }
+    }
+
+    pub fn groth16(&self, inputs: usize) -> u64 {
+        self.groth16_base.saturating_add((inputs as u64).saturating_mul(self.groth16_input))
+    }
+}
+
+/// The gas schedule and verifying keys one contract call runs with.
+#[derive(Clone, Default)]
+pub struct Precompiles {
+    pub gas:  PrecompileGas,
+    pub keys: HashMap<u32, Arc<Groth16Key>>,
+}
+
+fn reject(msg: String) -> VmError {
+    VmError::PrecompileRejected(msg)
+}
+
+/// Turn a guest length into a `usize` no larger than `max`.
+fn bounded(what: &str, len: i32, max: usize) -> VmResult<usize> {
+    match usize::try_from(len) {
+        Ok(n) if n <= max => Ok(n),
+        _ => Err(reject(format!("{what} length {len} outside 0..={max}"))),
+    }
+}
+
+fn exact(what: &str, len: i32, want: usize) -> VmResult<usize> {
+    if usize::try_from(len).ok() != Some(want) {
+        return Err(reject(format!("{what} is {len} bytes, expected {want}")));
+    }
+    Ok(want)
+}
+
+/// Checked `crypto_sha3` input length.
+pub fn check_sha3(len: i32) -> VmResult<usize> {
+    bounded("hash input", len, MAX_HASH_INPUT)
+}
+
+pub fn sha3(input: &[u8]) -> [u8; 32] {
+    Sha3_256::digest(input).into()
+}
+
+/// Checked `crypto_verify_sig` lengths: `(msg, sig, pk)`.
+pub fn check_sig(scheme: i32, msg_len: i32, sig_len: i32, pk_len: i32) -> VmResult<(usize, usize, usize)> {
+    match scheme {
+        SIG_SCHEME_SPHINCS => Ok((
+            bounded("message", msg_len, MAX_SIGNED_MESSAGE)?,
+            exact("SPHINCS+ signature", sig_len, SPHINCS_SIGNATURE_BYTES)?,
+            exact("SPHINCS+ public key", pk_len, SPHINCS_PUBLIC_KEY_BYTES)?,
+        )),
+        SIG_SCHEME_FALCON => Err(reject("Falcon verification is not available on this node".into())),
+        other => Err(reject(format!("unknown signature scheme {other}"))),
+    }
+}
+
+/// Verify a signature whose lengths passed `check_sig`.
+pub fn verify_sig(scheme: i32, msg: &[u8], sig: &[u8], pk: &[u8]) -> bool {
+    match scheme {
+        SIG_SCHEME_SPHINCS => bleep_crypto::verify_tx_signature(msg, sig, pk),
+        _ => false,
+    }
+}
+
+/// Checked `crypto_verify_groth16` lengths: `(proof, inputs)`.
+pub fn check_groth16(proof_len: i32, inputs_len: i32) -> VmResult<(usize, usize)> {
+    let proof = exact("Groth16 proof", proof_len, GROTH16_PROOF_BYTES)?;
+    let inputs = bounded("public inputs", inputs_len, MAX_GROTH16_INPUTS * GROTH16_INPUT_BYTES)?;
+    if inputs % GROTH16_INPUT_BYTES != 0 {
+        return Err(reject(format!("public inputs length {inputs} is not a multiple of {GROTH16_INPUT_BYTES}")));
+    }
+    Ok((proof, inputs))
+}
+
+impl Precompiles {
+    pub fn key(&self, vk_id: u32) -> VmResult<&Groth16Key> {
+        self.keys.get(&vk_id)
+            .map(|k| k.as_ref())
+            .ok_or_else(|| reject(format!("no Groth16 verifying key registered as {vk_id}")))
+    }
+
+    /// Verify a proof whose lengths passed `check_groth16`.  Bytes that do
+    /// not decode to a proof or scalars make the proof invalid, not the call.
+    pub fn verify_groth16(&self, vk_id: u32, proof: &[u8], inputs: &[u8]) -> VmResult<bool> {
+        let key = self.key(vk_id)?;
+        let proof = match decode_groth16_proof(proof) {
+            Ok(p) => p,
+            Err(_) => return Ok(false),
+        };
+        let inputs: Result<Vec<_>, _> = inputs.chunks(GROTH16_INPUT_BYTES).map(decode_public_input).collect();
+        match inputs {
+            Ok(inputs) => Ok(key.verify(&proof, &inputs).unwrap_or(false)),
+            Err(_) => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lengths_are_checked_before_use() {
+        assert_eq!(check_sha3(100).unwrap(), 100);
+        assert!(check_sha3(-1).is_err());
+        assert!(check_sha3(MAX_HASH_INPUT as i32 + 1).is_err());
+
+        assert!(check_sig(SIG_SCHEME_SPHINCS, 10, SPHINCS_SIGNATURE_BYTES as i32, 64).is_ok());
+        assert!(check_sig(SIG_SCHEME_SPHINCS, 10, 1, 64).is_err());
+        assert!(check_sig(SIG_SCHEME_SPHINCS, 10, SPHINCS_SIGNATURE_BYTES as i32, i32::MIN).is_err());
+        assert!(check_sig(SIG_SCHEME_FALCON, 10, 666, 897).is_err());
+        assert!(check_sig(9, 10, 1, 1).is_err());
+
+        assert!(check_groth16(192, 64).is_ok());
+        assert!(check_groth16(191, 64).is_err());
+        assert!(check_groth16(192, 33).is_err());
+        assert!(check_groth16(192, i32::MAX).is_err());
+    }
+
+    #[test]
+    fn gas_follows_the_schedule() {
+        let gas = PrecompileGas::default();
+        assert_eq!(gas.sha3(0), 30);
+        assert_eq!(gas.sha3(33), 30 + 2 * 6);
+        assert_eq!(gas.groth16(2), 180_000 + 2 * 6_000);
+    }
+}
@@ -0,0 +1,75 @@
+//! # Execution trace
+//!
+//! Step-level record of what an engine did while executing one intent:
+//! host calls, gas checkpoints and storage operations, in the order they
+//! happened.  Used by the block replay tool to explain divergences.
+//!
+//! Tracing is off by default and enabled per executor with
+//! `ExecutorConfig::trace`.  Engines emit steps through the ordinary
+//! `EngineResult::logs` channel at `LogLevel::Trace`, so the traced run is
+//! the production run — there is no separate debug interpreter.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{ExecutionLog, LogLevel};
+
+/// One step of an execution trace.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TraceStep {
+    /// Engine entered a host function.
+    HostCall { name: String },
+    /// Cumulative gas used at this point.
+    GasCheckpoint { gas_used: u64 },
+    /// Contract storage write.
+    StorageWrite { key: Vec<u8>, value: Vec<u8> },
+    /// Account balance change applied from the state diff.
+    BalanceChange { account: [u8; 32], delta: i128 },
+}
+
+impl TraceStep {
+    /// Encode as a `LogLevel::Trace` log entry.
+    pub fn to_log(&self) -> ExecutionLog {
+        let message = match self {
+            TraceStep::HostCall { .. }      => "trace:host_call",
+            TraceStep::GasCheckpoint { .. } => "trace:gas",
+            TraceStep::StorageWrite { .. }  => "trace:storage_write",
+            TraceStep::BalanceChange { .. } => "trace:balance",
+        };
+        ExecutionLog {
+            level:   LogLevel::Trace,
+            message: message.into(),
+            data:    serde_json::to_vec(self).unwrap_or_default(),
+        }
+    }
+
+    /// Decode a log entry produced by `to_log`; `None` for ordinary logs.
+    pub fn from_log(log: &ExecutionLog) -> Option<TraceStep> {
+        if log.level != LogLevel::Trace {
+            return None;
+        }
+        serde_json::from_slice(&log.data).ok()
+    }
+}
+
+/// Extract the ordered trace from an engine's log stream.
+pub fn extract_trace(logs: &[ExecutionLog]) -> Vec<TraceStep> {
+    logs.iter().filter_map(TraceStep::from_log).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_roundtrip_skips_ordinary_logs() {
+        let logs = vec![
+            TraceStep::HostCall { name: "storage_write".into() }.to_log(),
+            ExecutionLog { level: LogLevel::Info, message: "hello".into(), data: Vec::new() },
+            TraceStep::StorageWrite { key: b"k".to_vec(), value: b"v".to_vec() }.to_log(),
+        ];
+        assert_eq!(extract_trace(&logs), vec![
+            TraceStep::HostCall { name: "storage_write".into() },
+            TraceStep::StorageWrite { key: b"k".to_vec(), value: b"v".to_vec() },
+        ]);
+    }
+}
@@ -74,9 +74,12 @@ pub mod runtime {
     pub mod gas_model_base;
     pub mod sandbox;
     pub mod memory;
+    pub mod determinism;
+    pub mod instance_pool;
 
     pub use gas_model::GasModel;
     pub use sandbox::{SandboxValidator, SandboxConfig, SecurityPolicy};
+    pub use instance_pool::{InstancePool, PoolConfig, PoolStats};
 }
 
 pub mod execution {
@@ -84,11 +87,19 @@ pub mod execution {
     pub mod call_stack;
     pub mod state_transition;
     pub mod executor;
+    pub mod trace;
+    pub mod contract_registry;
+    pub mod event_abi;
+    pub mod precompiles;
 
     pub use execution_context::ExecutionContext;
     pub use call_stack::CallStack;
     pub use state_transition::{StateDiff, StateTransition};
     pub use executor::{Executor, ExecutorConfig, ExecutionOutcome};
+    pub use trace::{extract_trace, TraceStep};
+    pub use contract_registry::{ContractRegistry, UpgradeAuthority, UpgradeRecord};
+    pub use event_abi::{EventBuilder, EventSchema};
+    pub use precompiles::PrecompileGas;
 }
 
 pub mod crosschain {
@@ -107,6 +118,9 @@ pub use intent::{Intent, IntentKind, TargetVm, ContractCallBuilder, DeployBuilde
 pub use intent::{TransferIntent, ContractCallIntent, DeployIntent, CrossChainIntent, ZkVerifyIntent};
 pub use execution::executor::{Executor, ExecutorConfig, ExecutionOutcome};
 pub use execution::state_transition::{StateDiff, StateTransition};
+pub use execution::contract_registry::{ContractInfo, ContractRegistry, DeployGate, DryRun, UpgradeAuthority, UpgradeKind, UpgradeRecord};
+pub use execution::event_abi::{DecodedEvent, EventBuilder, EventDef, EventSchema, FieldType, FieldValue};
+pub use execution::precompiles::PrecompileGas;
 pub use runtime::gas_model::{GasModel, GasEstimator};
 
 // ── Version ───────────────────────────────────────────────────────────────────
@@ -18,7 +18,7 @@
 
 use crate::error::{VmError, VmResult};
 use crate::execution::{
-    execution_context::ExecutionContext,
+    execution_context::{BlockEnv, ExecutionContext},
     state_transition::StateDiff,
 };
 use crate::intent::{Intent, IntentKind, TargetVm};
@@ -210,6 +210,12 @@ impl VmRouter {
         }
     }
 
+    /// Validate bytecode against `config` instead of the default policy.
+    pub fn with_sandbox(mut self, config: SandboxConfig) -> Self {
+        self.sandbox = SandboxValidator::new(config);
+        self
+    }
+
     /// Register an execution engine.
     pub fn register_engine(&mut self, engine: Arc<dyn Engine>) {
         info!(engine = engine.name(), "Registered execution engine");
@@ -217,8 +223,13 @@ impl VmRouter {
     }
 
     /// Route and execute one intent end-to-end.
-    #[instrument(skip(self, intent), fields(intent_id = %intent.id))]
     pub async fn route(&self, intent: &Intent) -> VmResult<RoutedResult> {
+        self.route_in_block(intent, BlockEnv::default()).await
+    }
+
+    /// Route and execute one intent as part of `block`.
+    #[instrument(skip(self, intent, block), fields(intent_id = %intent.id))]
+    pub async fn route_in_block(&self, intent: &Intent, block: BlockEnv) -> VmResult<RoutedResult> {
         let start = Instant::now();
 
         // ── Step 1: Signature verification ───────────────────────────────────
@@ -244,7 +255,7 @@ impl VmRouter {
         debug!(?vm, "Resolved target VM");
 
         // ── Step 4: Build execution context ──────────────────────────────────
-        let ctx = ExecutionContext::from_intent(intent, self.config.max_call_depth);
+        let ctx = ExecutionContext::from_intent_in_block(intent, block, self.config.max_call_depth);
 
         // ── Step 5: Select engine ─────────────────────────────────────────────
         let engine = self.select_engine(&vm).await?;
@@ -0,0 +1,123 @@
+//! Deterministic environment imports for WASM contracts.
+//!
+//! Contracts read the block they execute in through `env` imports that
+//! return the same value on every validator:
+//!
+//! - `block_height() -> i64`    — height of the block being executed
+//! - `block_timestamp() -> i64` — the block header's timestamp (seconds)
+//! - `random_seed(out_ptr)`     — writes a 32-byte seed for this block and
+//!   contract to `out_ptr`
+//!
+//! ## Seed derivation (v1)
+//!
+//! ```text
+//! seed = BLAKE3-keyed(key = previous block hash,
+//!                     "bleep:random_seed:v1" ‖ contract address ‖ height_le)
+//! ```
+//!
+//! The previous block hash is fixed before the block is built, so every
+//! validator derives the same seed, while nobody can compute it before the
+//! parent block exists.  The contract address separates the seeds of
+//! different contracts in one block; two calls to one contract in the same
+//! block see the same seed.
+//!
+//! Derivations are versioned like gas schedules: `SEED_DERIVATIONS` lists
+//! each version with the height it activates at, and a block always runs
+//! with the version active at its height, so historic blocks replay with
+//! the derivation they were produced under.
+//!
+//! Wall-clock time (`env.timestamp`) differs between validators and is
+//! only provided by engines built with `wall_clock` for devnets;
+//! `SecurityPolicy` rejects contracts importing it unless
+//! `allow_wall_clock` is set.
+
+use crate::execution::execution_context::BlockEnv;
+
+/// Imports that read the host's clock rather than the block header.
+pub const WALL_CLOCK_IMPORTS: &[&str] = &["timestamp"];
+
+/// Versions of the `random_seed` derivation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedDerivation {
+    V1,
+}
+
+/// `(activation height, derivation)`, ascending by height.
+pub const SEED_DERIVATIONS: &[(u64, SeedDerivation)] = &[(0, SeedDerivation::V1)];
+
+impl SeedDerivation {
+    /// Derivation blocks at `height` are executed with.
+    pub fn at_height(height: u64) -> Self {
+        SEED_DERIVATIONS
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= height)
+            .map(|(_, d)| *d)
+            .unwrap_or(SeedDerivation::V1)
+    }
+
+    pub fn derive(self, prev_hash: &[u8; 32], contract: &[u8; 32], height: u64) -> [u8; 32] {
+        match self {
+            SeedDerivation::V1 => {
+                let mut h = blake3::Hasher::new_keyed(prev_hash);
+                h.update(b"bleep:random_seed:v1");
+                h.update(contract);
+                h.update(&height.to_le_bytes());
+                *h.finalize().as_bytes()
+            }
+        }
+    }
+}
+
+/// What the environment imports return to one contract in one block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContractEnv {
+    pub height:    u64,
+    pub timestamp: u64,
+    pub seed:      [u8; 32],
+}
+
+impl ContractEnv {
+    pub fn new(block: &BlockEnv, contract: &[u8; 32]) -> Self {
+        ContractEnv {
+            height:    block.number,
+            timestamp: block.timestamp,
+            seed:      SeedDerivation::at_height(block.number)
+                .derive(&block.prev_hash, contract, block.number),
+        }
+    }
+}
+
+/// Host clock in seconds, behind the devnet-only `env.timestamp` import.
+pub fn wall_clock_secs() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, prev_hash: [u8; 32]) -> BlockEnv {
+        BlockEnv { number, timestamp: 1_700_000_000, prev_hash, ..BlockEnv::default() }
+    }
+
+    #[test]
+    fn seeds_are_per_block_and_per_contract() {
+        let b = block(7, [1u8; 32]);
+        let a = ContractEnv::new(&b, &[0xAA; 32]);
+        assert_eq!(a, ContractEnv::new(&b, &[0xAA; 32]));
+        assert_ne!(a.seed, ContractEnv::new(&b, &[0xBB; 32]).seed);
+        assert_ne!(a.seed, ContractEnv::new(&block(7, [2u8; 32]), &[0xAA; 32]).seed);
+        assert_eq!((a.height, a.timestamp), (7, 1_700_000_000));
+    }
+
+    #[test]
+    fn derivation_follows_activation_heights() {
+        assert_eq!(SeedDerivation::at_height(0), SeedDerivation::V1);
+        assert_eq!(SeedDerivation::at_height(u64::MAX), SEED_DERIVATIONS.last().unwrap().1);
+    }
+}
@@ -0,0 +1,552 @@
+//! Warm pool of instantiated WASM contracts.
+//!
+//! Building a fresh `Store`, host imports, linear memory and `Instance`
+//! dominates the latency of small contract calls.  `InstancePool` keeps
+//! instantiated contracts per code hash and hands them out with
+//! checkout/checkin:
+//!
+//! - **Reset.**  On checkin every linear memory is overwritten with its
+//!   image from right after instantiation and every mutable global is
+//!   restored.  `prepare_module` exports all defined memories and mutable
+//!   globals so nothing escapes the reset.  An instance whose memory grew
+//!   cannot shrink back and is dropped instead, as is any instance of a
+//!   module that mutates tables or drops segments.
+//! - **Caps.**  At most `per_contract` idle instances per contract and
+//!   `max_instances` / `max_bytes` across the pool, where an idle instance
+//!   costs its live memory plus its pristine image.  Over a cap, the least
+//!   recently called contract loses its idle instances; contracts not called
+//!   for `idle_ttl` lose them on the next checkin.
+//! - **Fallback.**  An empty pool is not an error: `checkout` instantiates
+//!   cold through the caller's closure.
+//!
+//! Host state lives in the instance's `FunctionEnv`; the caller replaces it
+//! before every call, so only guest state needs resetting here.  Checkouts
+//! are exclusive: concurrent workers (each call runs on its own blocking
+//! thread) always hold distinct instances.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use wasm_encoder::{ExportKind, ExportSection, RawSection};
+use wasmer::{Extern, FunctionEnv, Global, Instance, Memory, Mutability, Store, Value};
+use wasmparser::{ExternalKind, Operator, Parser, Payload, TypeRef};
+
+use crate::error::{VmError, VmResult};
+
+/// Export-name prefix for state `prepare_module` exposes to the pool.
+const POOL_EXPORT_PREFIX: &str = "__bleep_pool_";
+
+const EXPORT_SECTION_ID: u8 = 7;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// CONFIG AND STATS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Idle instances kept per contract; 0 disables pooling.
+    pub per_contract:  usize,
+    /// Idle instances kept across all contracts.
+    pub max_instances: usize,
+    /// Bytes idle instances may hold (live memory plus pristine image).
+    pub max_bytes:     usize,
+    /// Contracts not called for this long lose their idle instances.
+    pub idle_ttl:      Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            per_contract:  4,
+            max_instances: 64,
+            max_bytes:     256 * 1024 * 1024,
+            idle_ttl:      Duration::from_secs(300),
+        }
+    }
+}
+
+/// Pool counters, reported to telemetry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct PoolStats {
+    /// Checkouts served by a warm instance.
+    pub hits:           u64,
+    /// Checkouts that instantiated cold.
+    pub misses:         u64,
+    pub resets:         u64,
+    /// Mean checkin reset time, in microseconds.
+    pub avg_reset_us:   f64,
+    /// Idle instances dropped by the caps or the idle TTL.
+    pub evictions:      u64,
+    /// Instances dropped at checkin because they could not be reset.
+    pub discarded:      u64,
+    pub idle_instances: usize,
+    pub idle_bytes:     usize,
+}
+
+impl PoolStats {
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 { 0.0 } else { self.hits as f64 / total as f64 }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// MODULE PREPARATION
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Rewrite `bytecode` so every defined memory and mutable global is
+/// exported.  Also returns whether instances may be pooled at all: a module
+/// that mutates tables or drops data/element segments keeps state the pool
+/// cannot restore.
+pub fn prepare_module(bytecode: &[u8]) -> VmResult<(Vec<u8>, bool)> {
+    let invalid = |e: wasmparser::BinaryReaderError| VmError::WasmCompile(e.to_string());
+    let mut sections = Vec::new();
+    let (mut imported_globals, mut imported_memories) = (0u32, 0u32);
+    let mut state = Vec::new();
+    let mut exports = Vec::new();
+    let mut poolable = true;
+
+    for payload in Parser::new(0).parse_all(bytecode) {
+        let payload = payload.map_err(invalid)?;
+        match &payload {
+            Payload::ImportSection(reader) => {
+                for import in reader.clone() {
+                    match import.map_err(invalid)?.ty {
+                        TypeRef::Global(_) => imported_globals += 1,
+                        TypeRef::Memory(_) => imported_memories += 1,
+                        _ => {}
+                    }
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for (i, memory) in reader.clone().into_iter().enumerate() {
+                    memory.map_err(invalid)?;
+                    state.push((ExternalKind::Memory, imported_memories + i as u32));
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for (i, global) in reader.clone().into_iter().enumerate() {
+                    if global.map_err(invalid)?.ty.mutable {
+                        state.push((ExternalKind::Global, imported_globals + i as u32));
+                    }
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader.clone() {
+                    let export = export.map_err(invalid)?;
+                    exports.push((export.name.to_string(), export.kind, export.index));
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                for op in body.get_operators_reader().map_err(invalid)? {
+                    if matches!(
+                        op.map_err(invalid)?,
+                        Operator::TableSet { .. } | Operator::TableGrow { .. } | Operator::TableFill { .. }
+                            | Operator::TableCopy { .. } | Operator::TableInit { .. }
+                            | Operator::ElemDrop { .. } | Operator::DataDrop { .. }
+                    ) {
+                        poolable = false;
+                    }
+                }
+            }
+            _ => {}
+        }
+        if let Some((id, range)) = payload.as_section() {
+            sections.push((id, range));
+        }
+    }
+
+    let missing: Vec<_> = state.into_iter()
+        .filter(|s| !exports.iter().any(|(_, kind, index)| (*kind, *index) == *s))
+        .collect();
+    if missing.is_empty() {
+        return Ok((bytecode.to_vec(), poolable));
+    }
+
+    let mut section = ExportSection::new();
+    for (name, kind, index) in &exports {
+        section.export(name, export_kind(*kind), *index);
+    }
+    for (kind, index) in &missing {
+        let name = format!("{}{:?}_{}", POOL_EXPORT_PREFIX, kind, index);
+        section.export(&name, export_kind(*kind), *index);
+    }
+
+    let mut module = wasm_encoder::Module::new();
+    let mut written = false;
+    for (id, range) in sections {
+        if id == EXPORT_SECTION_ID {
+            module.section(&section);
+            written = true;
+            continue;
+        }
+        if !written && section_order(id) > section_order(EXPORT_SECTION_ID) {
+            module.section(&section);
+            written = true;
+        }
+        module.section(&RawSection { id, data: &bytecode[range] });
+    }
+    if !written {
+        module.section(&section);
+    }
+    Ok((module.finish(), poolable))
+}
+
+fn export_kind(kind: ExternalKind) -> ExportKind {
+    match kind {
+        ExternalKind::Func   => ExportKind::Func,
+        ExternalKind::Table  => ExportKind::Table,
+        ExternalKind::Memory => ExportKind::Memory,
+        ExternalKind::Global => ExportKind::Global,
+        ExternalKind::Tag    => ExportKind::Tag,
+    }
+}
+
+/// Position of a section id in the binary format's required order; custom
+/// sections (0) may appear anywhere.
+fn section_order(id: u8) -> u8 {
+    match id {
+        1 => 1, 2 => 2, 3 => 3, 4 => 4, 5 => 5, 13 => 6, 6 => 7,
+        7 => 8, 8 => 9, 9 => 10, 12 => 11, 10 => 12, 11 => 13,
+        _ => 0,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// POOLED INSTANCE
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// An instantiated contract with the store and host environment it lives in.
+pub struct PooledInstance<E: 'static> {
+    pub store:    Store,
+    pub instance: Instance,
+    pub env:      FunctionEnv<E>,
+    code_hash:    [u8; 32],
+    reusable:     bool,
+    memories:     Vec<(Memory, Vec<u8>)>,
+    globals:      Vec<(Global, Value)>,
+}
+
+impl<E: 'static> PooledInstance<E> {
+    /// Snapshot a freshly instantiated contract as the state every checkin
+    /// restores.  `reusable` is the flag returned by `prepare_module`.
+    pub fn new(
+        mut store: Store,
+        instance:  Instance,
+        env:       FunctionEnv<E>,
+        code_hash: [u8; 32],
+        reusable:  bool,
+    ) -> VmResult<Self> {
+        let mut memories = Vec::new();
+        let mut globals = Vec::new();
+        for (_, export) in instance.exports.iter() {
+            match export {
+                Extern::Memory(mem) => {
+                    let image = mem.view(&store).copy_to_vec()
+                        .map_err(|e| VmError::Internal(format!("memory snapshot: {e}")))?;
+                    memories.push((mem.clone(), image));
+                }
+                Extern::Global(global) if global.ty(&store).mutability == Mutability::Var => {
+                    globals.push(global.clone());
+                }
+                _ => {}
+            }
+        }
+        let globals = globals.into_iter()
+            .map(|g| {
+                let value = g.get(&store);
+                (g, value)
+            })
+            .collect();
+        Ok(PooledInstance { store, instance, env, code_hash, reusable, memories, globals })
+    }
+
+    pub fn code_hash(&self) -> [u8; 32] {
+        self.code_hash
+    }
+
+    /// Bytes held while idle: live memory plus pristine image.
+    fn footprint(&self) -> usize {
+        self.memories.iter().map(|(_, image)| image.len() * 2).sum()
+    }
+
+    /// Restore guest memory and globals; `false` if the instance cannot be reused.
+    fn reset(&mut self) -> bool {
+        if !self.reusable {
+            return false;
+        }
+        for (mem, image) in &self.memories {
+            let view = mem.view(&self.store);
+            if view.data_size() != image.len() as u64 || view.write(0, image).is_err() {
+                return false;
+            }
+        }
+        for (global, value) in &self.globals {
+            if global.set(&mut self.store, value.clone()).is_err() {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// POOL
+// ─────────────────────────────────────────────────────────────────────────────
+
+struct ContractPool<E: 'static> {
+    idle:      Vec<PooledInstance<E>>,
+    last_used: Instant,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits:        u64,
+    misses:      u64,
+    resets:      u64,
+    reset_nanos: u128,
+    evictions:   u64,
+    discarded:   u64,
+}
+
+struct Inner<E: 'static> {
+    contracts: HashMap<[u8; 32], ContractPool<E>>,
+    counters:  Counters,
+}
+
+impl<E: 'static> Inner<E> {
+    fn idle_instances(&self) -> usize {
+        self.contracts.values().map(|p| p.idle.len()).sum()
+    }
+
+    fn idle_bytes(&self) -> usize {
+        self.contracts.values().flat_map(|p| &p.idle).map(PooledInstance::footprint).sum()
+    }
+
+    /// Drop expired pools, then least recently used ones until within the caps.
+    fn enforce(&mut self, config: &PoolConfig, now: Instant) -> Vec<PooledInstance<E>> {
+        let mut evicted = Vec::new();
+        let expired: Vec<[u8; 32]> = self.contracts.iter()
+            .filter(|(_, p)| now.duration_since(p.last_used) >= config.idle_ttl)
+            .map(|(hash, _)| *hash)
+            .collect();
+        for hash in expired {
+            evicted.extend(self.contracts.remove(&hash).map(|p| p.idle).unwrap_or_default());
+        }
+        while self.idle_instances() > config.max_instances || self.idle_bytes() > config.max_bytes {
+            let lru = self.contracts.iter()
+                .filter(|(_, p)| !p.idle.is_empty())
+                .min_by_key(|(_, p)| p.last_used)
+                .map(|(hash, _)| *hash);
+            let Some(hash) = lru else { break };
+            if let Some(pool) = self.contracts.get_mut(&hash) {
+                evicted.append(&mut pool.idle);
+            }
+        }
+        self.counters.evictions += evicted.len() as u64;
+        evicted
+    }
+}
+
+pub struct InstancePool<E: 'static> {
+    config: PoolConfig,
+    inner:  Mutex<Inner<E>>,
+}
+
+impl<E: Send + 'static> InstancePool<E> {
+    pub fn new(config: PoolConfig) -> Self {
+        InstancePool {
+            config,
+            inner: Mutex::new(Inner { contracts: HashMap::new(), counters: Counters::default() }),
+        }
+    }
+
+    pub fn config(&self) -> PoolConfig {
+        self.config
+    }
+
+    /// A reset instance of `code_hash`, or a cold one from `instantiate`
+    /// when none is idle.
+    pub fn checkout(
+        &self,
+        code_hash:   [u8; 32],
+        instantiate: impl FnOnce() -> VmResult<PooledInstance<E>>,
+    ) -> VmResult<PooledInstance<E>> {
+        {
+            let mut inner = self.inner.lock();
+            let warm = inner.contracts.get_mut(&code_hash).and_then(|pool| {
+                pool.last_used = Instant::now();
+                pool.idle.pop()
+            });
+            if let Some(instance) = warm {
+                inner.counters.hits += 1;
+                return Ok(instance);
+            }
+            inner.counters.misses += 1;
+        }
+        instantiate()
+    }
+
+    /// Reset `instance` and keep it for the next checkout of its contract.
+    pub fn checkin(&self, mut instance: PooledInstance<E>) {
+        if self.config.per_contract == 0 {
+            return;
+        }
+        let started = Instant::now();
+        let reusable = instance.reset();
+        let elapsed = started.elapsed();
+
+        // Dropped instances are released after the lock.
+        let mut dropped = Vec::new();
+        let mut inner = self.inner.lock();
+        inner.counters.resets += 1;
+        inner.counters.reset_nanos += elapsed.as_nanos();
+        if !reusable {
+            inner.counters.discarded += 1;
+            drop(inner);
+            return;
+        }
+        let now = Instant::now();
+        let pool = inner.contracts.entry(instance.code_hash)
+            .or_insert_with(|| ContractPool { idle: Vec::new(), last_used: now });
+        pool.last_used = now;
+        if pool.idle.len() < self.config.per_contract {
+            pool.idle.push(instance);
+        } else {
+            dropped.push(instance);
+        }
+        dropped.extend(inner.enforce(&self.config, now));
+        drop(inner);
+        drop(dropped);
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let inner = self.inner.lock();
+        let c = &inner.counters;
+        PoolStats {
+            hits:           c.hits,
+            misses:         c.misses,
+            resets:         c.resets,
+            avg_reset_us:   if c.resets == 0 { 0.0 } else { c.reset_nanos as f64 / c.resets as f64 / 1_000.0 },
+            evictions:      c.evictions,
+            discarded:      c.discarded,
+            idle_instances: inner.idle_instances(),
+            idle_bytes:     inner.idle_bytes(),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// TESTS
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engines::wasm_engine::WasmRuntime;
+    use crate::types::GasSchedule;
+    use std::sync::Arc;
+    use wasm_encoder::{
+        CodeSection, ConstExpr, DataSection, ExportSection, Function, FunctionSection,
+        GlobalSection, GlobalType, Instruction, MemArg, MemorySection, MemoryType, Module,
+        TypeSection, ValType,
+    };
+
+    const PAGE: usize = 64 * 1024;
+
+    /// `call_contract() -> i32` returns `mem[2048] + g` (pristine: `seed + 0`),
+    /// then fills its whole page with 0xAB and increments `g`.  The global
+    /// is not exported, so only `prepare_module` lets the pool see it.
+    fn scribbler(seed: u8) -> Vec<u8> {
+        let mut types = TypeSection::new();
+        types.function([] as [ValType; 0], [ValType::I32]);
+        let mut funcs = FunctionSection::new();
+        funcs.function(0);
+        let mut memory = MemorySection::new();
+        memory.memory(MemoryType { minimum: 1, maximum: None, memory64: false, shared: false });
+        let mut globals = GlobalSection::new();
+        globals.global(GlobalType { val_type: ValType::I32, mutable: true }, &ConstExpr::i32_const(0));
+        let mut exports = ExportSection::new();
+        exports.export("call_contract", ExportKind::Func, 0);
+        exports.export("memory", ExportKind::Memory, 0);
+
+        let word = MemArg { offset: 0, align: 2, memory_index: 0 };
+        let mut body = Function::new([(1, ValType::I32)]);
+        for ins in [
+            Instruction::I32Const(2048), Instruction::I32Load(word), Instruction::GlobalGet(0),
+            Instruction::I32Add, Instruction::LocalSet(0),
+            Instruction::I32Const(0), Instruction::I32Const(0xAB), Instruction::I32Const(PAGE as i32),
+            Instruction::MemoryFill(0),
+            Instruction::GlobalGet(0), Instruction::I32Const(1), Instruction::I32Add, Instruction::GlobalSet(0),
+            Instruction::LocalGet(0), Instruction::End,
+        ] {
+            body.instruction(&ins);
+        }
+        let mut code = CodeSection::new();
+        code.function(&body);
+        let mut data = DataSection::new();
+        data.active(0, &ConstExpr::i32_const(2048), [seed, 0, 0, 0]);
+
+        let mut module = Module::new();
+        module.section(&types).section(&funcs).section(&memory).section(&globals)
+            .section(&exports).section(&code).section(&data);
+        module.finish()
+    }
+
+    async fn call(runtime: &WasmRuntime, code: &[u8]) -> i32 {
+        let out = runtime.execute(code, 1_000_000, Arc::new(GasSchedule::default()), &[], None).await.unwrap();
+        assert!(out.success, "{:?}", out.revert_reason);
+        i32::from_le_bytes(out.return_data.try_into().unwrap())
+    }
+
+    #[test]
+    fn prepare_exports_hidden_mutable_globals() {
+        let (prepared, poolable) = prepare_module(&scribbler(7)).unwrap();
+        assert!(poolable);
+        let names: Vec<String> = Parser::new(0).parse_all(&prepared)
+            .filter_map(|p| match p.unwrap() {
+                Payload::ExportSection(r) => Some(r.into_iter().map(|e| e.unwrap().name.to_string()).collect::<Vec<_>>()),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+        assert_eq!(names, ["call_contract", "memory", "__bleep_pool_Global_0"]);
+    }
+
+    #[tokio::test]
+    async fn scribbled_state_never_reaches_the_next_call() {
+        let runtime = WasmRuntime::new();
+        let code = scribbler(7);
+        for _ in 0..3 {
+            // A leak would show up as 0xABABABAB or a non-zero global.
+            assert_eq!(call(&runtime, &code).await, 7);
+        }
+        let stats = runtime.pool_stats();
+        assert_eq!((stats.misses, stats.hits, stats.resets), (1, 2, 3));
+        assert_eq!(stats.idle_instances, 1);
+    }
+
+    #[tokio::test]
+    async fn memory_pressure_evicts_least_recently_called() {
+        // Room for two idle single-page instances, not three.
+        let runtime = WasmRuntime::new().with_pool_config(PoolConfig {
+            max_bytes: 5 * PAGE,
+            ..PoolConfig::default()
+        });
+        let (a, b, c) = (scribbler(1), scribbler(2), scribbler(3));
+        assert_eq!(call(&runtime, &a).await, 1);
+        assert_eq!(call(&runtime, &b).await, 2);
+        assert_eq!(call(&runtime, &c).await, 3);
+        let stats = runtime.pool_stats();
+        assert_eq!((stats.evictions, stats.idle_instances, stats.idle_bytes), (1, 2, 4 * PAGE));
+
+        // `a` was evicted and instantiates cold; `c` is still warm.
+        assert_eq!(call(&runtime, &a).await, 1);
+        assert_eq!(call(&runtime, &c).await, 3);
+        let stats = runtime.pool_stats();
+        assert_eq!((stats.misses, stats.hits), (4, 1));
+        assert!(stats.idle_bytes <= 5 * PAGE);
+    }
+}
@@ -5,12 +5,15 @@
 //! - A `SharedMemoryPool` for amortising allocation cost across executions.
 //! - Bounds-checked read/write helpers that return typed `VmError` on violation.
 //! - Memory zeroing on allocation (no data leaks between executions).
+//! - `cap_memory`, which bounds `memory.grow` inside the contract itself.
 
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 use parking_lot::Mutex;
 use tracing::debug;
+use wasm_encoder::{MemorySection, MemoryType, RawSection};
+use wasmparser::{Parser, Payload};
 
 use crate::error::{VmError, VmResult};
 
@@ -265,6 +268,65 @@ impl SharedMemoryPool {
     pub fn available(&self) -> usize { self.pool.lock().len() }
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// GROWTH CAP
+// ─────────────────────────────────────────────────────────────────────────────
+
+const MEMORY_SECTION_ID: u8 = 5;
+
+/// Clamp the declared maximum of every memory `bytecode` defines to
+/// `max_pages`, so a `memory.grow` past the cap returns -1 mid-execution
+/// rather than allocating.  A memory whose initial size is already over the
+/// cap is rejected.  Modules that need no clamping come back unchanged.
+pub fn cap_memory(bytecode: &[u8], max_pages: u32) -> VmResult<Vec<u8>> {
+    let invalid = |e: wasmparser::BinaryReaderError| VmError::WasmCompile(e.to_string());
+    let cap = max_pages as u64;
+    let mut sections = Vec::new();
+    let mut capped = None;
+
+    for payload in Parser::new(0).parse_all(bytecode) {
+        let payload = payload.map_err(invalid)?;
+        if let Payload::MemorySection(reader) = &payload {
+            let mut section = MemorySection::new();
+            let mut clamped = false;
+            for memory in reader.clone() {
+                let memory = memory.map_err(invalid)?;
+                if memory.initial > cap {
+                    return Err(VmError::MemoryLimitExceeded {
+                        requested: memory.initial * WASM_PAGE_SIZE as u64,
+                        limit:     cap * WASM_PAGE_SIZE as u64,
+                    });
+                }
+                let maximum = memory.maximum.map_or(cap, |m| m.min(cap));
+                clamped |= memory.maximum != Some(maximum);
+                section.memory(MemoryType {
+                    minimum:  memory.initial,
+                    maximum:  Some(maximum),
+                    memory64: memory.memory64,
+                    shared:   memory.shared,
+                });
+            }
+            if clamped {
+                capped = Some(section);
+            }
+        }
+        if let Some((id, range)) = payload.as_section() {
+            sections.push((id, range));
+        }
+    }
+
+    let Some(section) = capped else { return Ok(bytecode.to_vec()) };
+    let mut module = wasm_encoder::Module::new();
+    for (id, range) in sections {
+        if id == MEMORY_SECTION_ID {
+            module.section(&section);
+        } else {
+            module.section(&RawSection { id, data: &bytecode[range] });
+        }
+    }
+    Ok(module.finish())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -5,7 +5,11 @@
 //! - Prohibited opcode detection (floats optional, `memory.grow` limit, etc.).
 //! - Maximum bytecode size.
 //! - Non-determinism detection (system clock, random, env vars, filesystem).
-//! - Host-function whitelist: only the declared host imports are permitted.
+//!   Contracts read time and randomness from the block instead
+//!   (`runtime::determinism`); the host-clock `timestamp` import is only
+//!   accepted under `allow_wall_clock`, which only `devnet()` sets.
+//! - Host-function whitelist: only the declared host imports are permitted,
+//!   and `bleep::crypto_*` names only for the known precompiles.
 //! - Execution timeout (via `tokio::time::timeout`).
 //! - Resource caps (stack depth, table size, global count).
 
@@ -16,6 +20,8 @@ use tracing::debug;
 use wasmparser::{Parser, Payload, Operator};
 
 use crate::error::{VmError, VmResult};
+use crate::execution::precompiles::PRECOMPILE_IMPORTS;
+use crate::runtime::determinism::WALL_CLOCK_IMPORTS;
 
 // ─────────────────────────────────────────────────────────────────────────────
 // CONSTANTS
@@ -53,6 +59,9 @@ const ALLOWED_HOST_MODULES: &[&str] = &[
     "wasi_snapshot_preview1",
 ];
 
+/// `bleep` import prefix reserved for precompiles (`PRECOMPILE_IMPORTS`).
+const PRECOMPILE_PREFIX: &str = "crypto_";
+
 /// Non-deterministic / dangerous function names that must never be imported.
 const FORBIDDEN_IMPORTS: &[&str] = &[
     "clock_time_get",
@@ -85,6 +94,8 @@ pub struct SecurityPolicy {
     pub max_memory_grow_calls: u32,
     pub timeout:               Duration,
     pub extra_forbidden:       HashSet<String>,
+    /// Accept contracts importing host-clock time (`WALL_CLOCK_IMPORTS`).
+    pub allow_wall_clock:      bool,
 }
 
 impl Default for SecurityPolicy {
@@ -97,11 +108,27 @@ impl Default for SecurityPolicy {
             max_memory_grow_calls: 64,
             timeout:               DEFAULT_EXECUTION_TIMEOUT,
             extra_forbidden:       HashSet::new(),
+            allow_wall_clock:      false,
         }
     }
 }
 
 impl SecurityPolicy {
+    /// Relaxed limits for local development networks: SIMD allowed, 4× the
+    /// bytecode size, more `memory.grow` calls and a longer timeout.  Host
+    /// imports stay whitelisted — code that runs here must still deploy on a
+    /// real network — except that host-clock time is accepted.
+    pub fn devnet() -> Self {
+        SecurityPolicy {
+            allow_simd:            true,
+            max_bytecode_bytes:    4 * MAX_BYTECODE_BYTES,
+            max_memory_grow_calls: 1_024,
+            timeout:               Duration::from_secs(60),
+            allow_wall_clock:      true,
+            ..SecurityPolicy::default()
+        }
+    }
+
     pub fn validate(&self, bytecode: &[u8]) -> VmResult<ValidationReport> {
         self.check_magic(bytecode)?;
         self.check_size(bytecode)?;
@@ -163,11 +190,20 @@ impl SecurityPolicy {
                         let name = imp.name;
                         if FORBIDDEN_IMPORTS.contains(&name)
                             || self.extra_forbidden.contains(name)
+                            || (!self.allow_wall_clock && WALL_CLOCK_IMPORTS.contains(&name))
                         {
                             return Err(VmError::ForbiddenSyscall {
                                 syscall: name.to_string(),
                             });
                         }
+                        if imp.module == "bleep"
+                            && name.starts_with(PRECOMPILE_PREFIX)
+                            && !PRECOMPILE_IMPORTS.contains(&name)
+                        {
+                            return Err(VmError::SecurityViolation(format!(
+                                "Unknown precompile: 'bleep::{name}'"
+                            )));
+                        }
                         report.imports.push(format!("{}::{}", imp.module, name));
                     }
                 }
@@ -401,6 +437,43 @@ mod tests {
         assert!(!report.exports_fn("missing"));
     }
 
+    #[test]
+    fn test_wall_clock_import_rejected_outside_devnet() {
+        use wasm_encoder::{EntityType, ImportSection, Module, TypeSection, ValType};
+        let module = |name: &str| {
+            let mut types = TypeSection::new();
+            types.function([], [ValType::I64]);
+            let mut imports = ImportSection::new();
+            imports.import("env", name, EntityType::Function(0));
+            let mut m = Module::new();
+            m.section(&types).section(&imports);
+            m.finish()
+        };
+        assert!(matches!(
+            SecurityPolicy::default().validate(&module("timestamp")),
+            Err(VmError::ForbiddenSyscall { .. })
+        ));
+        assert!(SecurityPolicy::default().validate(&module("block_timestamp")).is_ok());
+        assert!(SecurityPolicy::devnet().validate(&module("timestamp")).is_ok());
+    }
+
+    #[test]
+    fn test_only_known_precompiles_importable() {
+        use wasm_encoder::{EntityType, ImportSection, Module, TypeSection, ValType};
+        let module = |name: &str| {
+            let mut types = TypeSection::new();
+            types.function([ValType::I32; 3], []);
+            let mut imports = ImportSection::new();
+            imports.import("bleep", name, EntityType::Function(0));
+            let mut m = Module::new();
+            m.section(&types).section(&imports);
+            m.finish()
+        };
+        let policy = SecurityPolicy::default();
+        assert!(policy.validate(&module("crypto_sha3")).is_ok());
+        assert!(matches!(policy.validate(&module("crypto_md5")), Err(VmError::SecurityViolation(_))));
+    }
+
     #[test]
     fn test_sandbox_validator_valid_wasm() {
         let v = SandboxValidator::new(SandboxConfig::default());
@@ -221,7 +221,14 @@ pub struct ExecutionLog {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
-pub enum LogLevel { Debug, Info, Warning, Error }
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+    /// Structured execution-trace step (see `execution::trace`).
+    Trace,
+}
 
 // ─────────────────────────────────────────────────────────────────────────────
 // ZK PROOF
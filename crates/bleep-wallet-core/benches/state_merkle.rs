@@ -0,0 +1,58 @@
+//! 10k sequential `StateMerkle::update_state` calls with the root kept
+//! current after each one: incrementally (only the changed path is
+//! rehashed) versus rebuilding the tree from every leaf, as a tree without
+//! cached interior nodes has to.
+//!
+//!     cargo bench -p bleep-wallet-core --bench state_merkle
+
+use bleep_crypto::chain_id::ChainId;
+use bleep_wallet_core::state_merkle::StateMerkle;
+use bleep_wallet_core::wallet_core::Transaction;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+const UPDATES: usize = 10_000;
+
+fn transactions() -> Vec<Transaction> {
+    (0..UPDATES)
+        .map(|i| Transaction {
+            id:        format!("tx-{}", i),
+            from:      format!("BLEEP1acct{}", i),
+            to:        "BLEEP1sink".into(),
+            amount:    i as f64,
+            fee:       0.1,
+            signature: vec![],
+            nonce:     0,
+            chain_id:  ChainId::NONE,
+        })
+        .collect()
+}
+
+fn sequential_updates(c: &mut Criterion) {
+    let txs = transactions();
+    let mut group = c.benchmark_group("state_merkle_10k_updates");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(UPDATES as u64));
+
+    group.bench_function("incremental", |b| {
+        b.iter(|| {
+            let mut state = StateMerkle::new();
+            for tx in &txs {
+                state.update_state(&tx.from, tx.clone());
+                black_box(state.root());
+            }
+        })
+    });
+    group.bench_function("full_rebuild", |b| {
+        b.iter(|| {
+            let mut state = StateMerkle::new();
+            for tx in &txs {
+                state.update_state(&tx.from, tx.clone());
+                black_box(state.root_from_scratch());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sequential_updates);
+criterion_main!(benches);
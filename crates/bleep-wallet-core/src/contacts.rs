@@ -0,0 +1,419 @@
+//! # Contacts
+//!
+//! Per-wallet address book mapping human labels to addresses, so a transfer
+//! can name `@alice` instead of a 46-character `BLEEP1…` string.
+//!
+//! ```text
+//! ~/.bleep/contacts/<wallet address>.enc
+//!     = encrypt_key(json(contacts), password, wallet address)
+//!       Layout: [nonce(12) || ciphertext || tag(16)]
+//! ```
+//!
+//! The book is sealed with the same AES-256-GCM scheme as the wallet's
+//! signing key and is never written to disk in plaintext.  Each contact
+//! remembers the address it resolved to when it was last paid; if an edit
+//! or import has changed the address since, `resolve` reports the old one
+//! so the caller can demand explicit confirmation before sending.
+//!
+//! Export bundles are portable between wallets and passphrases:
+//! `salt(16) || encrypt_key(json, passphrase, "contacts-export:" || hex(salt))`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::wallet::{decrypt_key, encrypt_key, EncryptedWallet};
+
+/// Prefix marking a CLI recipient as a contact label rather than an address.
+pub const LABEL_PREFIX: char = '@';
+
+const EXPORT_SALT_LEN: usize = 16;
+const MAX_SUGGESTIONS: usize = 3;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ContactError {
+    #[error("unknown contact @{label}{}", suggestion_hint(.suggestions))]
+    NotFound { label: String, suggestions: Vec<String> },
+    #[error("invalid contact label {0:?}: use letters, digits, '-', '_' or '.'")]
+    InvalidLabel(String),
+    #[error("invalid address {0:?}: expected BLEEP1…")]
+    InvalidAddress(String),
+    #[error("contact book decryption failed — wrong password or corrupted file")]
+    Decrypt,
+    #[error("contact book: {0}")]
+    Storage(String),
+}
+
+fn suggestion_hint(suggestions: &[String]) -> String {
+    if suggestions.is_empty() {
+        String::new()
+    } else {
+        let names: Vec<String> = suggestions.iter().map(|s| format!("@{}", s)).collect();
+        format!(" — did you mean {}?", names.join(", "))
+    }
+}
+
+/// One address book entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    pub label:           String,
+    pub address:         String,
+    #[serde(default)]
+    pub note:            Option<String>,
+    /// Token symbol this contact usually receives (e.g. a PAT symbol).
+    #[serde(default)]
+    pub preferred_token: Option<String>,
+    /// Address this label resolved to the last time it was paid.
+    #[serde(default)]
+    pub last_used_address: Option<String>,
+}
+
+/// Result of resolving a label.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolved {
+    pub label:           String,
+    pub address:         String,
+    pub preferred_token: Option<String>,
+    /// Set when the address differs from the one last paid under this label.
+    pub changed_from:    Option<String>,
+}
+
+/// Encrypted label → address store for one wallet.
+pub struct ContactBook {
+    path:     PathBuf,
+    owner:    String,
+    password: Zeroizing<String>,
+    contacts: BTreeMap<String, Contact>,
+}
+
+impl ContactBook {
+    /// Open (or start) the book at `path`, sealed to `owner` and `password`.
+    pub fn open<P: AsRef<Path>>(path: P, owner: &str, password: &str) -> Result<Self, ContactError> {
+        let path = path.as_ref().to_path_buf();
+        let contacts = if path.exists() {
+            let blob = std::fs::read(&path).map_err(|e| ContactError::Storage(e.to_string()))?;
+            let plain = Zeroizing::new(
+                decrypt_key(&blob, password, owner).map_err(|_| ContactError::Decrypt)?,
+            );
+            let list: Vec<Contact> = serde_json::from_slice(&plain)
+                .map_err(|e| ContactError::Storage(e.to_string()))?;
+            list.into_iter().map(|c| (c.label.clone(), c)).collect()
+        } else {
+            BTreeMap::new()
+        };
+        Ok(Self { path, owner: owner.to_string(), password: Zeroizing::new(password.to_string()), contacts })
+    }
+
+    /// Add a contact, or replace the address/note/token of an existing one.
+    ///
+    /// Replacing keeps `last_used_address`, so the next send to a changed
+    /// address is flagged by `resolve`.
+    pub fn add(
+        &mut self,
+        label:           &str,
+        address:         &str,
+        note:            Option<String>,
+        preferred_token: Option<String>,
+    ) -> Result<(), ContactError> {
+        let label = normalize_label(label)?;
+        validate_address(address)?;
+        let last_used_address = self.contacts.get(&label).and_then(|c| c.last_used_address.clone());
+        self.contacts.insert(label.clone(), Contact {
+            label,
+            address: address.to_string(),
+            note,
+            preferred_token,
+            last_used_address,
+        });
+        self.persist()
+    }
+
+    /// Remove a contact; returns `false` if it did not exist.
+    pub fn remove(&mut self, label: &str) -> Result<bool, ContactError> {
+        let label = label.strip_prefix(LABEL_PREFIX).unwrap_or(label);
+        if self.contacts.remove(label).is_none() {
+            return Ok(false);
+        }
+        self.persist()?;
+        Ok(true)
+    }
+
+    /// All contacts, ordered by label.
+    pub fn list(&self) -> Vec<&Contact> {
+        self.contacts.values().collect()
+    }
+
+    pub fn get(&self, label: &str) -> Option<&Contact> {
+        self.contacts.get(label.strip_prefix(LABEL_PREFIX).unwrap_or(label))
+    }
+
+    /// Resolve `label` (with or without the leading `@`) to an address.
+    ///
+    /// Unknown labels fail with up to three close matches as suggestions.
+    pub fn resolve(&self, label: &str) -> Result<Resolved, ContactError> {
+        let label = label.strip_prefix(LABEL_PREFIX).unwrap_or(label);
+        let contact = self.contacts.get(label).ok_or_else(|| ContactError::NotFound {
+            label:       label.to_string(),
+            suggestions: self.suggest(label),
+        })?;
+        let changed_from = contact
+            .last_used_address
+            .as_ref()
+            .filter(|prev| **prev != contact.address)
+            .cloned();
+        Ok(Resolved {
+            label:           contact.label.clone(),
+            address:         contact.address.clone(),
+            preferred_token: contact.preferred_token.clone(),
+            changed_from,
+        })
+    }
+
+    /// Record that `label` was just paid at its current address.
+    pub fn mark_used(&mut self, label: &str) -> Result<(), ContactError> {
+        let label = label.strip_prefix(LABEL_PREFIX).unwrap_or(label);
+        match self.contacts.get_mut(label) {
+            Some(c) if c.last_used_address.as_deref() != Some(c.address.as_str()) => {
+                c.last_used_address = Some(c.address.clone());
+                self.persist()
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Labels within edit distance of `label`, or sharing its prefix.
+    pub fn suggest(&self, label: &str) -> Vec<String> {
+        let needle = label.to_lowercase();
+        let max_distance = (needle.chars().count() / 3).max(2);
+        let mut scored: Vec<(usize, &String)> = self
+            .contacts
+            .keys()
+            .filter_map(|k| {
+                let hay = k.to_lowercase();
+                let d = levenshtein(&needle, &hay);
+                if d <= max_distance || (!needle.is_empty() && hay.starts_with(&needle)) {
+                    Some((d, k))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        scored.sort();
+        scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, k)| k.clone()).collect()
+    }
+
+    /// Serialize every contact into a bundle sealed with `passphrase`.
+    pub fn export_bundle(&self, passphrase: &str) -> Result<Vec<u8>, ContactError> {
+        let mut salt = [0u8; EXPORT_SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let list: Vec<Contact> = self
+            .contacts
+            .values()
+            .cloned()
+            .map(|c| Contact { last_used_address: None, ..c })
+            .collect();
+        let plain = Zeroizing::new(
+            serde_json::to_vec(&list).map_err(|e| ContactError::Storage(e.to_string()))?,
+        );
+        let sealed = encrypt_key(&plain, passphrase, &export_context(&salt))
+            .map_err(|e| ContactError::Storage(e.to_string()))?;
+        let mut out = Vec::with_capacity(EXPORT_SALT_LEN + sealed.len());
+        out.extend_from_slice(&salt);
+        out.extend_from_slice(&sealed);
+        Ok(out)
+    }
+
+    /// Merge a bundle from `export_bundle`; returns how many contacts it held.
+    ///
+    /// Entries replace same-labelled contacts, which keeps any address
+    /// change visible to `resolve`.
+    pub fn import_bundle(&mut self, bundle: &[u8], passphrase: &str) -> Result<usize, ContactError> {
+        if bundle.len() < EXPORT_SALT_LEN {
+            return Err(ContactError::Decrypt);
+        }
+        let (salt, sealed) = bundle.split_at(EXPORT_SALT_LEN);
+        let plain = Zeroizing::new(
+            decrypt_key(sealed, passphrase, &export_context(salt)).map_err(|_| ContactError::Decrypt)?,
+        );
+        let list: Vec<Contact> = serde_json::from_slice(&plain)
+            .map_err(|e| ContactError::Storage(e.to_string()))?;
+        for c in &list {
+            let label = normalize_label(&c.label)?;
+            validate_address(&c.address)?;
+            let last_used_address = self.contacts.get(&label).and_then(|e| e.last_used_address.clone());
+            self.contacts.insert(label.clone(), Contact { label, last_used_address, ..c.clone() });
+        }
+        self.persist()?;
+        Ok(list.len())
+    }
+
+    pub fn path(&self) -> &Path { &self.path }
+
+    fn persist(&self) -> Result<(), ContactError> {
+        let storage = |e: Box<dyn std::error::Error>| ContactError::Storage(e.to_string());
+        let list: Vec<&Contact> = self.contacts.values().collect();
+        let plain = Zeroizing::new(serde_json::to_vec(&list).map_err(|e| storage(e.into()))?);
+        let sealed = encrypt_key(&plain, &self.password, &self.owner).map_err(storage)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| storage(e.into()))?;
+        }
+        let tmp = self.path.with_extension("enc.tmp");
+        std::fs::write(&tmp, sealed).map_err(|e| storage(e.into()))?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| storage(e.into()))
+    }
+}
+
+impl EncryptedWallet {
+    /// This wallet's contact book at `~/.bleep/contacts/<address>.enc`.
+    pub fn contacts(&self, password: &str) -> Result<ContactBook, ContactError> {
+        self.contacts_in(default_contacts_dir(), password)
+    }
+
+    /// This wallet's contact book inside `dir`.
+    pub fn contacts_in<P: AsRef<Path>>(&self, dir: P, password: &str) -> Result<ContactBook, ContactError> {
+        let path = dir.as_ref().join(format!("{}.enc", self.address()));
+        ContactBook::open(path, self.address(), password)
+    }
+}
+
+fn default_contacts_dir() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".bleep").join("contacts")
+}
+
+fn export_context(salt: &[u8]) -> String {
+    format!("contacts-export:{}", hex::encode(salt))
+}
+
+fn normalize_label(label: &str) -> Result<String, ContactError> {
+    let label = label.strip_prefix(LABEL_PREFIX).unwrap_or(label);
+    let valid = !label.is_empty()
+        && label.len() <= 64
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid { Ok(label.to_string()) } else { Err(ContactError::InvalidLabel(label.to_string())) }
+}
+
+fn validate_address(address: &str) -> Result<(), ContactError> {
+    let valid = address.starts_with("BLEEP1")
+        && address.len() > 6
+        && address[6..].chars().all(|c| c.is_ascii_hexdigit());
+    if valid { Ok(()) } else { Err(ContactError::InvalidAddress(address.to_string())) }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "BLEEP1aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const ALICE_NEW: &str = "BLEEP1bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bleep-contacts-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn wallet() -> EncryptedWallet {
+        EncryptedWallet::new(b"contacts-test-pk".to_vec(), vec![])
+    }
+
+    #[test]
+    fn add_resolve_and_send_by_label_survive_reopen() {
+        let dir = temp_dir();
+        let w = wallet();
+        let mut book = w.contacts_in(&dir, "pw").unwrap();
+        book.add("alice", ALICE, Some("rent".into()), Some("USDB".into())).unwrap();
+
+        let r = book.resolve("@alice").unwrap();
+        assert_eq!(r.address, ALICE);
+        assert_eq!(r.preferred_token.as_deref(), Some("USDB"));
+        assert_eq!(r.changed_from, None);
+        book.mark_used("@alice").unwrap();
+
+        // On disk the book is ciphertext only.
+        let raw = std::fs::read(book.path()).unwrap();
+        assert!(!String::from_utf8_lossy(&raw).contains("alice"));
+
+        let reopened = w.contacts_in(&dir, "pw").unwrap();
+        assert_eq!(reopened.get("alice").unwrap().last_used_address.as_deref(), Some(ALICE));
+        assert!(matches!(w.contacts_in(&dir, "wrong"), Err(ContactError::Decrypt)));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn typo_suggests_close_labels() {
+        let dir = temp_dir();
+        let mut book = wallet().contacts_in(&dir, "pw").unwrap();
+        book.add("alice", ALICE, None, None).unwrap();
+        book.add("bob", ALICE_NEW, None, None).unwrap();
+
+        match book.resolve("@alcie") {
+            Err(ContactError::NotFound { suggestions, .. }) => assert_eq!(suggestions, vec!["alice"]),
+            other => panic!("expected NotFound, got {:?}", other),
+        }
+        let msg = book.resolve("@alcie").unwrap_err().to_string();
+        assert!(msg.contains("did you mean @alice"));
+        assert!(book.suggest("zzzzzzzz").is_empty());
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn changed_address_is_flagged_until_paid_again() {
+        let dir = temp_dir();
+        let mut book = wallet().contacts_in(&dir, "pw").unwrap();
+        book.add("alice", ALICE, None, None).unwrap();
+        book.mark_used("alice").unwrap();
+
+        book.add("alice", ALICE_NEW, None, None).unwrap();
+        let r = book.resolve("alice").unwrap();
+        assert_eq!(r.address, ALICE_NEW);
+        assert_eq!(r.changed_from.as_deref(), Some(ALICE));
+
+        book.mark_used("alice").unwrap();
+        assert_eq!(book.resolve("alice").unwrap().changed_from, None);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn export_import_roundtrip_under_new_passphrase() {
+        let dir = temp_dir();
+        let mut src = wallet().contacts_in(&dir, "pw").unwrap();
+        src.add("alice", ALICE, Some("rent".into()), None).unwrap();
+        src.add("bob", ALICE_NEW, None, Some("BLP".into())).unwrap();
+        let bundle = src.export_bundle("transfer-pass").unwrap();
+
+        let other = EncryptedWallet::new(b"another-wallet".to_vec(), vec![]);
+        let mut dst = other.contacts_in(&dir, "other-pw").unwrap();
+        dst.add("alice", ALICE_NEW, None, None).unwrap();
+        dst.mark_used("alice").unwrap();
+        assert!(dst.import_bundle(&bundle, "bad-pass").is_err());
+        assert_eq!(dst.import_bundle(&bundle, "transfer-pass").unwrap(), 2);
+
+        let dst = other.contacts_in(&dir, "other-pw").unwrap();
+        assert_eq!(dst.get("bob").unwrap().preferred_token.as_deref(), Some("BLP"));
+        assert_eq!(dst.get("alice").unwrap().note.as_deref(), Some("rent"));
+        // The import moved @alice, so the next send must be confirmed.
+        assert_eq!(dst.resolve("alice").unwrap().changed_from.as_deref(), Some(ALICE_NEW));
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
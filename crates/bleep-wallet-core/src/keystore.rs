@@ -0,0 +1,265 @@
+//! # Keystore
+//!
+//! Passphrase-encrypted JSON file holding one `wallet_core::Wallet`'s
+//! secrets: its mnemonic, account index and SPHINCS+ keypair.
+//!
+//! ```json
+//! { "version": 1, "address": "BLEEP1…",
+//!   "kdf": { "salt": "<hex>", "m_cost": 19456, "t_cost": 2, "p_cost": 1 },
+//!   "nonce": "<hex>", "ciphertext": "<hex>" }
+//! ```
+//!
+//! The AES-256-GCM key is Argon2id of the passphrase under `kdf`, and the
+//! address is authenticated data, so a file edited to claim another
+//! address fails to open.  Every buffer holding the key or the plaintext
+//! is zeroized on drop.  Stored Argon2id costs are capped before use, and
+//! the file is written owner-only through a synced temp file.
+
+use std::io::Write;
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::wallet_core::WalletError;
+
+/// Keystore format version.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Ceilings on the stored Argon2id costs, so a crafted keystore cannot make
+/// opening it allocate gigabytes or spin for minutes.
+pub const MAX_M_COST: u32 = 1 << 20; // 1 GiB
+pub const MAX_T_COST: u32 = 16;
+pub const MAX_P_COST: u32 = 16;
+
+/// Argon2id cost parameters, stored so they can be raised later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub salt:   String,
+    /// Memory in KiB.
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl KdfParams {
+    fn fresh() -> Self {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        KdfParams {
+            salt:   hex::encode(salt),
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>, WalletError> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            return Err(WalletError::Serialization(format!(
+                "keystore kdf costs m={} t={} p={} exceed the limits m={} t={} p={}",
+                self.m_cost, self.t_cost, self.p_cost, MAX_M_COST, MAX_T_COST, MAX_P_COST,
+            )));
+        }
+        let salt = hex::decode(&self.salt).map_err(|e| WalletError::Serialization(format!("keystore salt: {}", e)))?;
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(32))
+            .map_err(|e| WalletError::Serialization(format!("keystore kdf: {}", e)))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &salt, &mut key[..])
+            .map_err(|e| WalletError::Encryption(format!("keystore kdf: {}", e)))?;
+        Ok(key)
+    }
+}
+
+/// What the keystore encrypts.
+#[derive(Serialize, Deserialize, Zeroize, ZeroizeOnDrop)]
+pub struct KeystoreSecrets {
+    pub mnemonic:      String,
+    pub account_index: u32,
+    pub public_key:    Vec<u8>,
+    pub secret_key:    Vec<u8>,
+}
+
+/// On-disk keystore.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keystore {
+    pub version:    u32,
+    pub address:    String,
+    pub kdf:        KdfParams,
+    pub nonce:      String,
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    /// Encrypt `secrets` of `address` under `passphrase`.
+    pub fn seal(address: &str, secrets: &KeystoreSecrets, passphrase: &str) -> Result<Self, WalletError> {
+        let kdf = KdfParams::fresh();
+        let key = kdf.derive_key(passphrase)?;
+        let plaintext = Zeroizing::new(
+            serde_json::to_vec(secrets).map_err(|e| WalletError::Serialization(e.to_string()))?,
+        );
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key[..]))
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: &plaintext, aad: address.as_bytes() })
+            .map_err(|e| WalletError::Encryption(format!("keystore: {}", e)))?;
+        Ok(Keystore {
+            version:    KEYSTORE_VERSION,
+            address:    address.to_string(),
+            kdf,
+            nonce:      hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypt with `passphrase`.  A wrong passphrase or a tampered file is
+    /// `WalletError::Authentication`.
+    pub fn open(&self, passphrase: &str) -> Result<KeystoreSecrets, WalletError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(WalletError::Serialization(format!("unsupported keystore version {}", self.version)));
+        }
+        let nonce = hex::decode(&self.nonce).map_err(|e| WalletError::Serialization(format!("keystore nonce: {}", e)))?;
+        if nonce.len() != NONCE_LEN {
+            return Err(WalletError::Serialization(format!("keystore nonce is {} bytes", nonce.len())));
+        }
+        let ciphertext = hex::decode(&self.ciphertext)
+            .map_err(|e| WalletError::Serialization(format!("keystore ciphertext: {}", e)))?;
+        let key = self.kdf.derive_key(passphrase)?;
+        let plaintext = Zeroizing::new(
+            Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key[..]))
+                .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: self.address.as_bytes() })
+                .map_err(|_| WalletError::Authentication("wrong passphrase or corrupted keystore".into()))?,
+        );
+        serde_json::from_slice(&plaintext).map_err(|e| WalletError::Serialization(format!("keystore contents: {}", e)))
+    }
+
+    /// Write owner-only (0600 on Unix) through a synced temp file renamed
+    /// over `path`, so a crash never leaves a truncated keystore.
+    pub fn write(&self, path: &Path) -> Result<(), WalletError> {
+        let json = serde_json::to_vec_pretty(self).map_err(|e| WalletError::Serialization(e.to_string()))?;
+        let tmp = path.with_extension("json.tmp");
+        let io = |e: std::io::Error| WalletError::Io(format!("{}: {}", path.display(), e));
+        {
+            let mut opts = std::fs::OpenOptions::new();
+            opts.write(true).create(true).truncate(true);
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut opts, 0o600);
+            let mut f = opts.open(&tmp).map_err(io)?;
+            f.write_all(&json).map_err(io)?;
+            f.sync_all().map_err(io)?;
+        }
+        std::fs::rename(&tmp, path).map_err(io)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, WalletError> {
+        let raw = std::fs::read(path).map_err(|e| WalletError::Io(format!("{}: {}", path.display(), e)))?;
+        serde_json::from_slice(&raw).map_err(|e| WalletError::Serialization(format!("{}: {}", path.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::chain_id::ChainId;
+    use crate::wallet_core::{Transaction, Wallet};
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("bleep-keystore-{}.json", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_keystore_roundtrip_keeps_address_and_key() {
+        let wallet = Wallet::import_wallet(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        ).unwrap().derive_account(3).unwrap();
+        let path = temp_path();
+        wallet.save_keystore(&path, "correct horse").unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("legal winner"));
+
+        let loaded = Wallet::load_keystore(&path, "correct horse").unwrap();
+        assert_eq!(loaded.address(), wallet.address());
+        assert_eq!(loaded.account_index, 3);
+        assert_eq!(loaded.public_key, wallet.public_key);
+
+        // The restored key signs for the restored address
+        let mut tx = Transaction {
+            id: "tx1".into(), from: loaded.address().to_string(), to: "BLEEP1x".into(),
+            amount: 1.0, fee: 0.1, signature: vec![], nonce: 0, chain_id: ChainId::NONE,
+        };
+        loaded.sign_transaction(&mut tx).unwrap();
+        assert!(wallet.verify_transaction(&tx));
+
+        assert!(matches!(Wallet::load_keystore(&path, "wrong"), Err(WalletError::Authentication(_))));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_corrupted_keystore_is_a_clean_error() {
+        let wallet = Wallet::import_wallet(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        ).unwrap();
+        let path = temp_path();
+        wallet.save_keystore(&path, "pw").unwrap();
+
+        let mut store = Keystore::read(&path).unwrap();
+        let mut bytes = hex::decode(&store.ciphertext).unwrap();
+        bytes[10] ^= 0x01;
+        store.ciphertext = hex::encode(bytes);
+        store.write(&path).unwrap();
+        assert!(matches!(Wallet::load_keystore(&path, "pw"), Err(WalletError::Authentication(_))));
+
+        // Claiming another address breaks the authenticated data too
+        let mut store = Keystore::seal(wallet.address(), &KeystoreSecrets {
+            mnemonic: String::new(), account_index: 0, public_key: vec![], secret_key: vec![],
+        }, "pw").unwrap();
+        store.address = "BLEEP1someoneelse".into();
+        assert!(matches!(store.open("pw"), Err(WalletError::Authentication(_))));
+
+        std::fs::write(&path, b"{ not json").unwrap();
+        assert!(matches!(Wallet::load_keystore(&path, "pw"), Err(WalletError::Serialization(_))));
+
+        // A keypair the mnemonic does not derive
+        let (public_key, secret_key) = bleep_crypto::tx_signer::generate_tx_keypair();
+        Keystore::seal(wallet.address(), &KeystoreSecrets {
+            mnemonic: wallet.mnemonic_phrase(), account_index: 0, public_key, secret_key,
+        }, "pw").unwrap().write(&path).unwrap();
+        assert!(matches!(Wallet::load_keystore(&path, "pw"), Err(WalletError::Serialization(_))));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_keystore_file_is_owner_only_and_kdf_costs_are_capped() {
+        let wallet = Wallet::import_wallet(
+            "legal winner thank year wave sausage worth useful legal winner thank yellow",
+        ).unwrap();
+        let path = temp_path();
+        wallet.save_keystore(&path, "pw").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert!(!path.with_extension("json.tmp").exists());
+
+        let mut store = Keystore::read(&path).unwrap();
+        store.kdf.m_cost = MAX_M_COST + 1;
+        assert!(matches!(store.open("pw"), Err(WalletError::Serialization(_))));
+        store.kdf.m_cost = Params::DEFAULT_M_COST;
+        store.kdf.t_cost = u32::MAX;
+        assert!(matches!(store.open("pw"), Err(WalletError::Serialization(_))));
+        store.kdf.t_cost = Params::DEFAULT_T_COST;
+        store.kdf.p_cost = MAX_P_COST + 1;
+        assert!(matches!(store.open("pw"), Err(WalletError::Serialization(_))));
+        store.kdf.p_cost = Params::DEFAULT_P_COST;
+        assert!(store.open("pw").is_ok());
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,4 +1,13 @@
 pub mod wallet;
+pub mod portfolio;
+pub mod message;
+pub mod contacts;
+pub mod keystore;
+pub mod multisig;
+pub mod policy;
+pub mod private_transfer;
+pub mod state_merkle;
+pub mod wallet_core;
 
 /// Initializes the BLEEP wallet core services.
 /// Sets up quantum-secure key management, transaction handling, and state synchronization.
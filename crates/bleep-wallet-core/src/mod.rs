@@ -2,4 +2,3 @@
 
 // Re-export the wallet_core module to make it available
 pub mod wallet_core;
-pub mod tests;
@@ -0,0 +1,288 @@
+//! # Multisig
+//!
+//! An M-of-N account: `MultisigWallet` holds the N cosigner SPHINCS+
+//! public keys and the threshold M, and its address is derived from both,
+//! so the same cosigner set and threshold always give the same account.
+//!
+//! A spend goes `propose` → `approve` (once per cosigner, usually through
+//! `Wallet::approve_multisig`) → `finalize`.  Each approval is a cosigner's
+//! signature over `Transaction::signing_bytes` and is checked against the
+//! registered keys when it arrives; a second approval from the same key is
+//! refused.  `finalize` only returns a transaction once M distinct
+//! cosigners have approved it, with their signatures packed into
+//! `Transaction::signature` (see `MultisigWallet::verify_transaction`).
+//!
+//! Cosigners on different machines exchange the pending set as a JSON file
+//! (`write_proposals` / `read_proposals`); reading merges the approvals it
+//! carries, re-verifying each one.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use bleep_crypto::tx_signer;
+
+use crate::wallet_core::{derive_address, Transaction, WalletError};
+
+/// One cosigner's signature on a proposal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Approval {
+    /// Position of the signer in `MultisigWallet::cosigners`.
+    pub cosigner:  u32,
+    pub signature: Vec<u8>,
+}
+
+/// A transaction waiting for approvals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    pub tx:        Transaction,
+    pub approvals: Vec<Approval>,
+}
+
+/// M-of-N multisig account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultisigWallet {
+    pub address:   String,
+    pub cosigners: Vec<Vec<u8>>,
+    pub threshold: usize,
+    /// Pending proposals by transaction id.
+    pending:       BTreeMap<String, Proposal>,
+}
+
+impl MultisigWallet {
+    /// `threshold`-of-`cosigners.len()` account.  The threshold must be
+    /// between 1 and N, and no key may be listed twice.
+    pub fn new(cosigners: Vec<Vec<u8>>, threshold: usize) -> Result<Self, WalletError> {
+        if threshold == 0 || threshold > cosigners.len() {
+            return Err(WalletError::Multisig(format!(
+                "threshold {} is not between 1 and {}",
+                threshold,
+                cosigners.len(),
+            )));
+        }
+        for (i, key) in cosigners.iter().enumerate() {
+            if cosigners[..i].contains(key) {
+                return Err(WalletError::Multisig(format!("cosigner {} is listed twice", i)));
+            }
+        }
+        let mut preimage = (threshold as u32).to_be_bytes().to_vec();
+        for key in &cosigners {
+            preimage.extend_from_slice(&(key.len() as u32).to_be_bytes());
+            preimage.extend_from_slice(key);
+        }
+        Ok(MultisigWallet {
+            address: derive_address(&preimage),
+            cosigners,
+            threshold,
+            pending: BTreeMap::new(),
+        })
+    }
+
+    /// Open a proposal for `tx`, which must spend from this account and be
+    /// unsigned.  Returns its id.
+    pub fn propose(&mut self, tx: Transaction) -> Result<String, WalletError> {
+        if tx.from != self.address || !tx.signature.is_empty() {
+            return Err(WalletError::InvalidTransaction);
+        }
+        if self.pending.contains_key(&tx.id) {
+            return Err(WalletError::Multisig(format!("transaction {} is already proposed", tx.id)));
+        }
+        let id = tx.id.clone();
+        self.pending.insert(id.clone(), Proposal { tx, approvals: Vec::new() });
+        Ok(id)
+    }
+
+    pub fn proposal(&self, tx_id: &str) -> Option<&Proposal> {
+        self.pending.get(tx_id)
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &Proposal> {
+        self.pending.values()
+    }
+
+    /// Record `signature` on proposal `tx_id`.  It must verify under one of
+    /// the cosigner keys, and that key must not have approved already.
+    pub fn approve(&mut self, tx_id: &str, signature: Vec<u8>) -> Result<(), WalletError> {
+        let proposal = self.pending.get(tx_id)
+            .ok_or_else(|| WalletError::Multisig(format!("no proposal {}", tx_id)))?;
+        let payload = proposal.tx.signing_bytes()?;
+        let cosigner = self.cosigners.iter()
+            .position(|key| tx_signer::verify_tx_signature(&payload, &signature, key))
+            .ok_or_else(|| WalletError::Multisig(format!("signature on {} is not from a cosigner", tx_id)))?
+            as u32;
+        let proposal = self.pending.get_mut(tx_id).expect("checked above");
+        if proposal.approvals.iter().any(|a| a.cosigner == cosigner) {
+            return Err(WalletError::Multisig(format!("cosigner {} already approved {}", cosigner, tx_id)));
+        }
+        proposal.approvals.push(Approval { cosigner, signature });
+        Ok(())
+    }
+
+    /// Take proposal `tx_id` out of the pending set as a signed transaction
+    /// once `threshold` cosigners have approved it.  Below the threshold the
+    /// proposal stays pending.
+    pub fn finalize(&mut self, tx_id: &str) -> Result<Transaction, WalletError> {
+        let proposal = self.pending.get(tx_id)
+            .ok_or_else(|| WalletError::Multisig(format!("no proposal {}", tx_id)))?;
+        if proposal.approvals.len() < self.threshold {
+            return Err(WalletError::Multisig(format!(
+                "{} has {} of {} approvals",
+                tx_id,
+                proposal.approvals.len(),
+                self.threshold,
+            )));
+        }
+        let Proposal { mut tx, mut approvals } = self.pending.remove(tx_id).expect("checked above");
+        approvals.sort_by_key(|a| a.cosigner);
+        approvals.truncate(self.threshold);
+        tx.signature = bincode::serialize(&approvals).map_err(|e| WalletError::Serialization(e.to_string()))?;
+        Ok(tx)
+    }
+
+    /// Whether `tx` spends from this account and its signature carries
+    /// valid signatures from at least `threshold` distinct cosigners.
+    pub fn verify_transaction(&self, tx: &Transaction) -> bool {
+        if tx.from != self.address {
+            return false;
+        }
+        let (Ok(payload), Ok(approvals)) = (tx.signing_bytes(), bincode::deserialize::<Vec<Approval>>(&tx.signature))
+        else {
+            return false;
+        };
+        let mut signed = vec![false; self.cosigners.len()];
+        for a in &approvals {
+            match self.cosigners.get(a.cosigner as usize) {
+                Some(key) if !signed[a.cosigner as usize] => {
+                    if !tx_signer::verify_tx_signature(&payload, &a.signature, key) {
+                        return false;
+                    }
+                    signed[a.cosigner as usize] = true;
+                }
+                _ => return false,
+            }
+        }
+        signed.iter().filter(|s| **s).count() >= self.threshold
+    }
+
+    /// Write the pending proposals to `path` for the other cosigners.
+    pub fn write_proposals(&self, path: &Path) -> Result<(), WalletError> {
+        let proposals: Vec<&Proposal> = self.pending.values().collect();
+        let json = serde_json::to_vec_pretty(&proposals).map_err(|e| WalletError::Serialization(e.to_string()))?;
+        std::fs::write(path, json).map_err(|e| WalletError::Io(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Merge a proposal file written by `write_proposals`: unknown proposals
+    /// are opened, and approvals not yet held are verified and recorded.
+    /// Returns how many approvals were added.
+    pub fn read_proposals(&mut self, path: &Path) -> Result<usize, WalletError> {
+        let raw = std::fs::read(path).map_err(|e| WalletError::Io(format!("{}: {}", path.display(), e)))?;
+        let proposals: Vec<Proposal> = serde_json::from_slice(&raw)
+            .map_err(|e| WalletError::Serialization(format!("{}: {}", path.display(), e)))?;
+        let mut added = 0;
+        for Proposal { tx, approvals } in proposals {
+            let id = tx.id.clone();
+            match self.pending.get(&id) {
+                Some(known) if known.tx.signing_bytes()? != tx.signing_bytes()? => {
+                    return Err(WalletError::Multisig(format!("{} in {} differs from ours", id, path.display())));
+                }
+                Some(_) => {}
+                None => {
+                    self.propose(tx)?;
+                }
+            }
+            for approval in approvals {
+                let held = self.pending[&id].approvals.iter().any(|a| a.cosigner == approval.cosigner);
+                if !held {
+                    self.approve(&id, approval.signature)?;
+                    added += 1;
+                }
+            }
+        }
+        Ok(added)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use bleep_crypto::chain_id::ChainId;
+    use crate::wallet_core::{P2PNode, StateMerkle, Wallet};
+
+    fn wallet() -> Wallet {
+        Wallet::new(Arc::new(P2PNode::new()), Arc::new(Mutex::new(StateMerkle::new()))).unwrap()
+    }
+
+    fn spend(from: &MultisigWallet) -> Transaction {
+        Transaction {
+            id:        "ms-1".into(),
+            from:      from.address.clone(),
+            to:        "BLEEP1recipient".into(),
+            amount:    25.0,
+            fee:       0.1,
+            signature: vec![],
+            nonce:     0,
+            chain_id:  ChainId::NONE,
+        }
+    }
+
+    #[test]
+    fn test_two_of_three_across_proposal_files() {
+        let (a, b, c) = (wallet(), wallet(), wallet());
+        let keys = vec![a.public_key.clone(), b.public_key.clone(), c.public_key.clone()];
+        let mut alice = MultisigWallet::new(keys.clone(), 2).unwrap();
+        let id = alice.propose(spend(&alice)).unwrap();
+        a.approve_multisig(&mut alice, &id).unwrap();
+
+        // Bob's machine picks up the proposal file and adds his approval
+        let path = std::env::temp_dir().join(format!("bleep-multisig-{}.json", uuid::Uuid::new_v4()));
+        alice.write_proposals(&path).unwrap();
+        let mut bob = MultisigWallet::new(keys, 2).unwrap();
+        assert_eq!(bob.address, alice.address);
+        assert_eq!(bob.read_proposals(&path).unwrap(), 1);
+        b.approve_multisig(&mut bob, &id).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let tx = bob.finalize(&id).unwrap();
+        assert!(bob.verify_transaction(&tx));
+        assert!(bob.proposal(&id).is_none());
+
+        let mut tampered = tx.clone();
+        tampered.amount = 2_500.0;
+        assert!(!bob.verify_transaction(&tampered));
+    }
+
+    #[test]
+    fn test_one_of_one_approves_only_known_proposals() {
+        let owner = wallet();
+        let mut ms = MultisigWallet::new(vec![owner.public_key.clone()], 1).unwrap();
+        assert!(matches!(owner.approve_multisig(&mut ms, "ms-unknown"), Err(WalletError::Multisig(_))));
+
+        let id = ms.propose(spend(&ms)).unwrap();
+        owner.approve_multisig(&mut ms, &id).unwrap();
+        let tx = ms.finalize(&id).unwrap();
+        assert!(ms.verify_transaction(&tx));
+    }
+
+    #[test]
+    fn test_one_signature_is_not_enough() {
+        let (a, b, c, outsider) = (wallet(), wallet(), wallet(), wallet());
+        let mut ms = MultisigWallet::new(vec![a.public_key.clone(), b.public_key.clone(), c.public_key.clone()], 2).unwrap();
+        let id = ms.propose(spend(&ms)).unwrap();
+        a.approve_multisig(&mut ms, &id).unwrap();
+
+        assert!(matches!(a.approve_multisig(&mut ms, &id), Err(WalletError::Multisig(_))));
+        assert!(matches!(outsider.approve_multisig(&mut ms, &id), Err(WalletError::Multisig(_))));
+        assert!(matches!(ms.finalize(&id), Err(WalletError::Multisig(_))));
+        assert_eq!(ms.proposal(&id).unwrap().approvals.len(), 1);
+
+        // A single cosigner's signature does not pass as a finalized spend
+        let mut forged = ms.proposal(&id).unwrap().tx.clone();
+        forged.signature = bincode::serialize(&ms.proposal(&id).unwrap().approvals).unwrap();
+        assert!(!ms.verify_transaction(&forged));
+
+        assert!(MultisigWallet::new(vec![a.public_key.clone()], 2).is_err());
+        assert!(MultisigWallet::new(vec![a.public_key.clone(), a.public_key.clone()], 1).is_err());
+    }
+}
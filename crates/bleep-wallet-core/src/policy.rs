@@ -0,0 +1,541 @@
+//! # Spending policies
+//!
+//! Guardrails a treasury wallet enforces on itself before producing any
+//! transfer signature:
+//!
+//! - `max_per_tx` — largest single transfer
+//! - `daily_limit` — total sent over any rolling 24 hours
+//! - `allow_list` / `deny_list` — recipients (an empty allow-list allows all)
+//! - `approval_threshold` — transfers above it wait for a second key listed
+//!   in `approvers`
+//!
+//! ```text
+//! ~/.bleep/policies/<wallet address>.enc
+//!     = encrypt_key(json(policy, recent spends, pending approvals),
+//!                   password, wallet address)
+//! ```
+//!
+//! The file is sealed like the wallet's signing key and contact book.
+//! Changing the policy needs a `SignedMessage` over
+//! `SpendingPolicy::authorization_message` from the policy's `admin`; the
+//! very first policy is authorized by the admin it names.
+//!
+//! `EncryptedWallet::sign_and_attach` evaluates the policy and only then
+//! signs.  A transfer over the approval threshold is stored as a
+//! `PendingApproval` and reported as `PolicyError::ApprovalRequired`; an
+//! approver signs `approval_message` and `EncryptedWallet::approve_pending`
+//! produces the transaction.  Limits are checked again at approval time, so
+//! an approval cannot overdraw a budget spent in the meantime.
+//!
+//! Policies cover value transfers.  Zero-amount system transactions (reward
+//! claims, key changes, cancellations) are signed without them.
+
+use std::path::{Path, PathBuf};
+
+use bleep_crypto::chain_id::ChainId;
+use bleep_crypto::tx_signer::{sign_tx_payload, tx_payload_with_nonce};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::message::{verify_message, SignedMessage};
+use crate::wallet::{decrypt_key, encrypt_key, EncryptedWallet};
+
+/// Prefix of the bytes an admin signs to change a policy.
+pub const POLICY_DOMAIN: &str = "bleep-wallet-policy-v1:";
+/// Prefix of the bytes an approver signs to release a pending transfer.
+pub const APPROVAL_DOMAIN: &str = "bleep-wallet-approval-v1:";
+
+/// Length of the rolling daily-limit window, in seconds.
+pub const DAY_SECS: u64 = 86_400;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PolicyError {
+    #[error("amount {amount} exceeds the per-transaction limit of {limit}")]
+    PerTxLimit { amount: u64, limit: u64 },
+    #[error("amount {amount} exceeds the daily limit of {limit} ({spent} already sent in the last 24h)")]
+    DailyLimit { amount: u64, spent: u64, limit: u64 },
+    #[error("recipient {0} is on the deny-list")]
+    RecipientDenied(String),
+    #[error("recipient {0} is not on the allow-list")]
+    RecipientNotAllowed(String),
+    #[error("amount {amount} needs a second approval — pending request {id}")]
+    ApprovalRequired { id: String, amount: u64 },
+    #[error("not authorized: {0}")]
+    Unauthorized(String),
+    #[error("no pending approval {0}")]
+    UnknownPending(String),
+    #[error("invalid policy: {0}")]
+    Invalid(String),
+    #[error("spending policy decryption failed — wrong password or corrupted file")]
+    Decrypt,
+    #[error("spending policy: {0}")]
+    Storage(String),
+    #[error("signing failed: {0}")]
+    Signing(String),
+}
+
+/// Rules one wallet's transfers must satisfy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpendingPolicy {
+    /// Address whose key must authorize any change to this policy.
+    pub admin:              String,
+    #[serde(default)]
+    pub max_per_tx:         Option<u64>,
+    #[serde(default)]
+    pub daily_limit:        Option<u64>,
+    /// Permitted recipients; empty permits any not on `deny_list`.
+    #[serde(default)]
+    pub allow_list:         Vec<String>,
+    #[serde(default)]
+    pub deny_list:          Vec<String>,
+    /// Transfers above this amount wait for an approver.
+    #[serde(default)]
+    pub approval_threshold: Option<u64>,
+    /// Addresses whose keys may approve held transfers.
+    #[serde(default)]
+    pub approvers:          Vec<String>,
+}
+
+impl SpendingPolicy {
+    /// Bytes the admin signs (as a message) to install this policy.
+    pub fn authorization_message(&self) -> Vec<u8> {
+        let mut msg = POLICY_DOMAIN.as_bytes().to_vec();
+        msg.extend(serde_json::to_vec(self).unwrap_or_default());
+        msg
+    }
+
+    /// Every rule except the approval threshold; `spent` is the amount sent
+    /// in the 24 hours before the transfer.
+    fn check(&self, transfer: &Transfer, spent: u64) -> Result<(), PolicyError> {
+        if self.deny_list.iter().any(|a| *a == transfer.receiver) {
+            return Err(PolicyError::RecipientDenied(transfer.receiver.clone()));
+        }
+        if !self.allow_list.is_empty() && !self.allow_list.iter().any(|a| *a == transfer.receiver) {
+            return Err(PolicyError::RecipientNotAllowed(transfer.receiver.clone()));
+        }
+        if let Some(limit) = self.max_per_tx.filter(|l| transfer.amount > *l) {
+            return Err(PolicyError::PerTxLimit { amount: transfer.amount, limit });
+        }
+        if let Some(limit) = self.daily_limit.filter(|l| spent.saturating_add(transfer.amount) > *l) {
+            return Err(PolicyError::DailyLimit { amount: transfer.amount, spent, limit });
+        }
+        Ok(())
+    }
+
+    fn needs_approval(&self, amount: u64) -> bool {
+        self.approval_threshold.is_some_and(|t| amount > t)
+    }
+}
+
+/// A value transfer awaiting a signature.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub receiver:  String,
+    pub amount:    u64,
+    /// UNIX seconds; also the point the daily window is measured from.
+    pub timestamp: u64,
+    #[serde(default)]
+    pub max_fee:   u64,
+    #[serde(default)]
+    pub tip:       u64,
+    /// The sender's account nonce this transfer is signed for.
+    #[serde(default)]
+    pub nonce:     u64,
+    /// Network this transfer is signed for.
+    #[serde(default)]
+    pub chain_id:  ChainId,
+}
+
+impl Transfer {
+    /// Payload `sender` signs for this transfer.
+    pub fn payload(&self, sender: &str) -> [u8; 32] {
+        self.chain_id.bind(tx_payload_with_nonce(
+            sender, &self.receiver, self.amount, self.timestamp, self.max_fee, self.tip, self.nonce,
+        ))
+    }
+}
+
+/// A signed transfer, shaped like the `POST /rpc/tx` body.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTransfer {
+    pub sender:    String,
+    pub receiver:  String,
+    pub amount:    u64,
+    pub timestamp: u64,
+    /// Wire format: `pk || SPHINCS+ detached signature`.
+    pub signature: Vec<u8>,
+    pub max_fee:   u64,
+    pub tip:       u64,
+    pub nonce:     u64,
+    #[serde(default, skip_serializing_if = "ChainId::is_none")]
+    pub chain_id:  ChainId,
+}
+
+/// A transfer held until an approver signs off.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingApproval {
+    pub id:       String,
+    pub transfer: Transfer,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct Spend {
+    timestamp: u64,
+    amount:    u64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PolicyState {
+    policy:  Option<SpendingPolicy>,
+    #[serde(default)]
+    spends:  Vec<Spend>,
+    #[serde(default)]
+    pending: Vec<PendingApproval>,
+}
+
+/// Bytes an approver signs (as a message) to release `pending` from `owner`.
+pub fn approval_message(owner: &str, pending: &PendingApproval) -> Vec<u8> {
+    format!(
+        "{}{}:{}:{}",
+        APPROVAL_DOMAIN,
+        owner,
+        pending.id,
+        hex::encode(pending.transfer.payload(owner)),
+    )
+    .into_bytes()
+}
+
+/// Encrypted spending policy, spend history and approval queue of one wallet.
+pub struct PolicyBook {
+    path:     PathBuf,
+    owner:    String,
+    password: Zeroizing<String>,
+    state:    PolicyState,
+}
+
+impl PolicyBook {
+    /// Open (or start) the book at `path`, sealed to `owner` and `password`.
+    pub fn open<P: AsRef<Path>>(path: P, owner: &str, password: &str) -> Result<Self, PolicyError> {
+        let path = path.as_ref().to_path_buf();
+        let state = if path.exists() {
+            let blob = std::fs::read(&path).map_err(|e| PolicyError::Storage(e.to_string()))?;
+            let plain = Zeroizing::new(
+                decrypt_key(&blob, password, owner).map_err(|_| PolicyError::Decrypt)?,
+            );
+            serde_json::from_slice(&plain).map_err(|e| PolicyError::Storage(e.to_string()))?
+        } else {
+            PolicyState::default()
+        };
+        Ok(Self { path, owner: owner.to_string(), password: Zeroizing::new(password.to_string()), state })
+    }
+
+    pub fn policy(&self) -> Option<&SpendingPolicy> {
+        self.state.policy.as_ref()
+    }
+
+    /// Install `policy`.  `authorization` must be the current admin's
+    /// signature over `policy.authorization_message()`, or the new admin's
+    /// when no policy is set yet.
+    pub fn set_policy(&mut self, policy: SpendingPolicy, authorization: &SignedMessage) -> Result<(), PolicyError> {
+        if !policy.admin.starts_with("BLEEP1") {
+            return Err(PolicyError::Invalid(format!("admin {:?} is not a BLEEP1 address", policy.admin)));
+        }
+        let admin = self.state.policy.as_ref().map_or(&policy.admin, |p| &p.admin);
+        if !verify_message(admin, &policy.authorization_message(), authorization) {
+            return Err(PolicyError::Unauthorized(format!("policy changes must be signed by {}", admin)));
+        }
+        self.state.policy = Some(policy);
+        self.persist()
+    }
+
+    /// Transfers waiting for approval, oldest first.
+    pub fn pending(&self) -> &[PendingApproval] {
+        &self.state.pending
+    }
+
+    /// Amount sent in the 24 hours up to `now`.
+    pub fn spent_in_window(&self, now: u64) -> u64 {
+        self.state.spends.iter()
+            .filter(|s| s.timestamp > now.saturating_sub(DAY_SECS) && s.timestamp <= now)
+            .map(|s| s.amount)
+            .fold(0u64, u64::saturating_add)
+    }
+
+    /// Check `transfer` against the policy.  Over the approval threshold it
+    /// is queued (once) and `ApprovalRequired` is returned.
+    pub fn evaluate(&mut self, transfer: &Transfer) -> Result<(), PolicyError> {
+        let Some(policy) = &self.state.policy else { return Ok(()) };
+        policy.check(transfer, self.spent_in_window(transfer.timestamp))?;
+        if !policy.needs_approval(transfer.amount) {
+            return Ok(());
+        }
+        let id = hex::encode(&transfer.payload(&self.owner)[..8]);
+        if !self.state.pending.iter().any(|p| p.id == id) {
+            self.state.pending.push(PendingApproval { id: id.clone(), transfer: transfer.clone() });
+            self.persist()?;
+        }
+        Err(PolicyError::ApprovalRequired { id, amount: transfer.amount })
+    }
+
+    /// Verify `approval` for pending request `id` and return its transfer.
+    /// The request stays queued until `settle` records the signature.
+    pub fn approve(&self, id: &str, approval: &SignedMessage) -> Result<Transfer, PolicyError> {
+        let pending = self.state.pending.iter().find(|p| p.id == id)
+            .ok_or_else(|| PolicyError::UnknownPending(id.to_string()))?;
+        let policy = self.state.policy.as_ref()
+            .ok_or_else(|| PolicyError::Unauthorized("no policy names any approvers".into()))?;
+        let approver = &approval.address;
+        if *approver == self.owner || !policy.approvers.contains(approver) {
+            return Err(PolicyError::Unauthorized(format!("{} is not a configured approver", approver)));
+        }
+        if !verify_message(approver, &approval_message(&self.owner, pending), approval) {
+            return Err(PolicyError::Unauthorized(format!("approval is not signed by {}", approver)));
+        }
+        policy.check(&pending.transfer, self.spent_in_window(pending.transfer.timestamp))?;
+        Ok(pending.transfer.clone())
+    }
+
+    /// Drop pending request `id` without signing it.
+    pub fn reject(&mut self, id: &str) -> Result<PendingApproval, PolicyError> {
+        let idx = self.state.pending.iter().position(|p| p.id == id)
+            .ok_or_else(|| PolicyError::UnknownPending(id.to_string()))?;
+        let pending = self.state.pending.remove(idx);
+        self.persist()?;
+        Ok(pending)
+    }
+
+    /// Record a signed transfer against the daily budget, clearing the
+    /// pending request it released, if any.
+    fn settle(&mut self, transfer: &Transfer, pending_id: Option<&str>) -> Result<(), PolicyError> {
+        if let Some(id) = pending_id {
+            self.state.pending.retain(|p| p.id != id);
+        }
+        let horizon = transfer.timestamp.saturating_sub(DAY_SECS);
+        self.state.spends.retain(|s| s.timestamp > horizon);
+        self.state.spends.push(Spend { timestamp: transfer.timestamp, amount: transfer.amount });
+        self.persist()
+    }
+
+    pub fn path(&self) -> &Path { &self.path }
+
+    fn persist(&self) -> Result<(), PolicyError> {
+        let storage = |e: Box<dyn std::error::Error>| PolicyError::Storage(e.to_string());
+        let plain = Zeroizing::new(serde_json::to_vec(&self.state).map_err(|e| storage(e.into()))?);
+        let sealed = encrypt_key(&plain, &self.password, &self.owner).map_err(storage)?;
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| storage(e.into()))?;
+        }
+        let tmp = self.path.with_extension("enc.tmp");
+        std::fs::write(&tmp, sealed).map_err(|e| storage(e.into()))?;
+        std::fs::rename(&tmp, &self.path).map_err(|e| storage(e.into()))
+    }
+}
+
+impl EncryptedWallet {
+    /// This wallet's policy book at `~/.bleep/policies/<address>.enc`.
+    pub fn policies(&self, password: &str) -> Result<PolicyBook, PolicyError> {
+        self.policies_in(default_policies_dir(), password)
+    }
+
+    /// This wallet's policy book inside `dir`.
+    pub fn policies_in<P: AsRef<Path>>(&self, dir: P, password: &str) -> Result<PolicyBook, PolicyError> {
+        let path = dir.as_ref().join(format!("{}.enc", self.address()));
+        PolicyBook::open(path, self.address(), password)
+    }
+
+    /// Evaluate `transfer` against `book`, then sign it and count it
+    /// against the daily budget.  Nothing is signed on any violation.
+    pub fn sign_and_attach(
+        &self,
+        password: &str,
+        book:     &mut PolicyBook,
+        transfer: &Transfer,
+    ) -> Result<SignedTransfer, PolicyError> {
+        book.evaluate(transfer)?;
+        let signed = self.sign_unchecked(password, transfer)?;
+        book.settle(transfer, None)?;
+        Ok(signed)
+    }
+
+    /// `sign_and_attach`, returning only the wire signature.
+    pub fn sign_transaction(
+        &self,
+        password: &str,
+        book:     &mut PolicyBook,
+        transfer: &Transfer,
+    ) -> Result<Vec<u8>, PolicyError> {
+        self.sign_and_attach(password, book, transfer).map(|t| t.signature)
+    }
+
+    /// Sign the transfer held as `id` once `approval` from a configured
+    /// approver checks out.
+    pub fn approve_pending(
+        &self,
+        password: &str,
+        book:     &mut PolicyBook,
+        id:       &str,
+        approval: &SignedMessage,
+    ) -> Result<SignedTransfer, PolicyError> {
+        let transfer = book.approve(id, approval)?;
+        let signed = self.sign_unchecked(password, &transfer)?;
+        book.settle(&transfer, Some(id))?;
+        Ok(signed)
+    }
+
+    fn sign_unchecked(&self, password: &str, transfer: &Transfer) -> Result<SignedTransfer, PolicyError> {
+        let sk = Zeroizing::new(self.unlock(password).map_err(|e| PolicyError::Signing(e.to_string()))?);
+        let sig = sign_tx_payload(&transfer.payload(self.address()), &sk).map_err(PolicyError::Signing)?;
+        let mut signature = Vec::with_capacity(self.falcon_keys.len() + sig.len());
+        signature.extend_from_slice(&self.falcon_keys);
+        signature.extend_from_slice(&sig);
+        Ok(SignedTransfer {
+            sender:    self.address().to_string(),
+            receiver:  transfer.receiver.clone(),
+            amount:    transfer.amount,
+            timestamp: transfer.timestamp,
+            signature,
+            max_fee:   transfer.max_fee,
+            tip:       transfer.tip,
+            nonce:     transfer.nonce,
+            chain_id:  transfer.chain_id,
+        })
+    }
+}
+
+fn default_policies_dir() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".bleep").join("policies")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::tx_signer::{generate_tx_keypair, verify_tx_signature};
+
+    const TREASURY_OPS: &str = "BLEEP1aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+    const STRANGER: &str = "BLEEP1cccccccccccccccccccccccccccccccccccccccc";
+    const T0: u64 = 1_700_000_000;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("bleep-policy-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn wallet() -> EncryptedWallet {
+        let (pk, sk) = generate_tx_keypair();
+        EncryptedWallet::with_signing_key_encrypted(pk, &sk, vec![], "pw").unwrap()
+    }
+
+    fn send(amount: u64, timestamp: u64) -> Transfer {
+        Transfer { receiver: TREASURY_OPS.into(), amount, timestamp, max_fee: 0, tip: 0, nonce: 0, chain_id: ChainId::NONE }
+    }
+
+    /// `owner`'s book with `policy` installed by `admin`.
+    fn book_with(dir: &Path, owner: &EncryptedWallet, admin: &EncryptedWallet, policy: SpendingPolicy) -> PolicyBook {
+        let policy = SpendingPolicy { admin: admin.address().to_string(), ..policy };
+        let auth = admin.sign_message("pw", &policy.authorization_message()).unwrap();
+        let mut book = owner.policies_in(dir, "pw").unwrap();
+        book.set_policy(policy, &auth).unwrap();
+        book
+    }
+
+    #[test]
+    fn daily_limit_refuses_once_earlier_sends_use_the_budget() {
+        let dir = temp_dir();
+        let (owner, admin) = (wallet(), wallet());
+        let mut book = book_with(&dir, &owner, &admin, SpendingPolicy { daily_limit: Some(100), ..Default::default() });
+
+        owner.sign_and_attach("pw", &mut book, &send(60, T0)).unwrap();
+        owner.sign_and_attach("pw", &mut book, &send(30, T0 + 3_600)).unwrap();
+        assert_eq!(
+            owner.sign_and_attach("pw", &mut book, &send(20, T0 + 7_200)),
+            Err(PolicyError::DailyLimit { amount: 20, spent: 90, limit: 100 }),
+        );
+
+        // The budget survives a reopen and rolls over after 24h.
+        let mut book = owner.policies_in(&dir, "pw").unwrap();
+        assert!(owner.sign_transaction("pw", &mut book, &send(20, T0 + 7_200)).is_err());
+        owner.sign_transaction("pw", &mut book, &send(20, T0 + DAY_SECS)).unwrap();
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn recipient_outside_allow_list_is_blocked() {
+        let dir = temp_dir();
+        let (owner, admin) = (wallet(), wallet());
+        let mut book = book_with(&dir, &owner, &admin, SpendingPolicy {
+            allow_list: vec![TREASURY_OPS.into()],
+            ..Default::default()
+        });
+
+        let to_stranger = Transfer { receiver: STRANGER.into(), ..send(1, T0) };
+        assert_eq!(
+            owner.sign_and_attach("pw", &mut book, &to_stranger),
+            Err(PolicyError::RecipientNotAllowed(STRANGER.into())),
+        );
+        owner.sign_and_attach("pw", &mut book, &send(1, T0)).unwrap();
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn large_transfer_is_signed_only_after_second_key_approves() {
+        let dir = temp_dir();
+        let (owner, admin, approver) = (wallet(), wallet(), wallet());
+        let mut book = book_with(&dir, &owner, &admin, SpendingPolicy {
+            approval_threshold: Some(50),
+            approvers:          vec![approver.address().to_string(), owner.address().to_string()],
+            ..Default::default()
+        });
+
+        let transfer = send(80, T0);
+        let id = match owner.sign_and_attach("pw", &mut book, &transfer) {
+            Err(PolicyError::ApprovalRequired { id, amount: 80 }) => id,
+            other => panic!("expected ApprovalRequired, got {:?}", other),
+        };
+        let mut book = owner.policies_in(&dir, "pw").unwrap();
+        let pending = book.pending()[0].clone();
+        assert_eq!((pending.id.as_str(), &pending.transfer), (id.as_str(), &transfer));
+
+        // The wallet's own key cannot be its second approval, even if listed.
+        let self_approval = owner.sign_message("pw", &approval_message(owner.address(), &pending)).unwrap();
+        assert!(matches!(
+            owner.approve_pending("pw", &mut book, &id, &self_approval),
+            Err(PolicyError::Unauthorized(_)),
+        ));
+
+        let approval = approver.sign_message("pw", &approval_message(owner.address(), &pending)).unwrap();
+        let signed = owner.approve_pending("pw", &mut book, &id, &approval).unwrap();
+        let (pk, sig) = signed.signature.split_at(owner.falcon_keys.len());
+        assert!(verify_tx_signature(&transfer.payload(owner.address()), sig, pk));
+        assert!(book.pending().is_empty());
+        assert_eq!(book.spent_in_window(T0), 80);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn policy_change_without_admin_key_fails() {
+        let dir = temp_dir();
+        let (owner, admin) = (wallet(), wallet());
+        let mut book = book_with(&dir, &owner, &admin, SpendingPolicy { max_per_tx: Some(10), ..Default::default() });
+
+        let loosened = SpendingPolicy { max_per_tx: None, ..book.policy().unwrap().clone() };
+        let forged = owner.sign_message("pw", &loosened.authorization_message()).unwrap();
+        assert!(matches!(book.set_policy(loosened.clone(), &forged), Err(PolicyError::Unauthorized(_))));
+
+        // A signature over a different policy does not carry over either.
+        let signed_other = admin.sign_message("pw", &book.policy().unwrap().authorization_message()).unwrap();
+        assert!(matches!(book.set_policy(loosened, &signed_other), Err(PolicyError::Unauthorized(_))));
+
+        let mut book = owner.policies_in(&dir, "pw").unwrap();
+        assert_eq!(book.policy().unwrap().max_per_tx, Some(10));
+        assert_eq!(
+            owner.sign_and_attach("pw", &mut book, &send(11, T0)),
+            Err(PolicyError::PerTxLimit { amount: 11, limit: 10 }),
+        );
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,396 @@
+//! # Portfolio
+//!
+//! One view of everything an address holds: native BLEEP (summed over the
+//! shards that own the address), PAT token balances and assets bridged to
+//! remote chains.
+//!
+//! ```text
+//! portfolio(addr) ──► per source: cached & younger than ttl? ── yes ──► cached
+//!                                          │ no
+//!                                          ▼
+//!                                 source.balances(addr)
+//!                                   ok  → replace entry
+//!                                   err → keep last value, flag stale
+//! ```
+//!
+//! Every source is cached on its own with a freshness timestamp, so one
+//! chain adapter being down degrades only its entry.  `spawn_refresh` keeps
+//! the cache warm for addresses that have been asked about, so a wallet UI
+//! can call `portfolio` on every render.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// ─── Sources ──────────────────────────────────────────────────────────────────
+
+/// What a balance is denominated in.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Asset {
+    /// Native BLEEP.
+    Native,
+    /// PAT token by symbol.
+    Pat { symbol: String },
+    /// Asset held on a remote chain through the bridge.
+    Bridged { chain: String, asset: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetBalance {
+    pub asset:  Asset,
+    /// Base units, as a decimal string on the wire.
+    #[serde(with = "amount_str")]
+    pub amount: u128,
+}
+
+/// Anything that can report balances for an address: a shard's state, the
+/// PAT ledger, a chain adapter.
+#[async_trait]
+pub trait BalanceSource: Send + Sync {
+    /// Name unique within a `PortfolioService`, e.g. `shard-0`, `pat`, `ethereum`.
+    fn name(&self) -> &str;
+
+    async fn balances(&self, address: &str) -> Result<Vec<AssetBalance>, String>;
+}
+
+// ─── Config ───────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+pub struct PortfolioConfig {
+    /// A cached source entry younger than this is served without a fetch.
+    pub ttl:              Duration,
+    /// Background refresh period.
+    pub refresh_interval: Duration,
+    /// Random extra delay (0..=jitter) added to each refresh period so
+    /// many wallets do not hit the sources in lockstep.
+    pub jitter:           Duration,
+    /// Addresses kept warm by the background refresh.
+    pub max_tracked:      usize,
+}
+
+impl Default for PortfolioConfig {
+    fn default() -> Self {
+        Self {
+            ttl:              Duration::from_secs(15),
+            refresh_interval: Duration::from_secs(10),
+            jitter:           Duration::from_secs(2),
+            max_tracked:      1024,
+        }
+    }
+}
+
+// ─── Result ───────────────────────────────────────────────────────────────────
+
+/// One source's contribution to a portfolio.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceEntry {
+    pub source:     String,
+    pub balances:   Vec<AssetBalance>,
+    /// Unix seconds of the last successful fetch; 0 if it never succeeded.
+    pub fetched_at: u64,
+    /// The last fetch failed; `balances` are from `fetched_at`.
+    pub stale:      bool,
+    pub error:      Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub address: String,
+    /// Balances summed per asset across all sources, in asset order.
+    pub totals:  Vec<AssetBalance>,
+    pub sources: Vec<SourceEntry>,
+    /// At least one source is stale.
+    pub stale:   bool,
+}
+
+// ─── Service ──────────────────────────────────────────────────────────────────
+
+struct CachedEntry {
+    balances:   Vec<AssetBalance>,
+    fetched_at: u64,
+    /// Last attempt, successful or not; drives the TTL so a failing source
+    /// is retried once per ttl rather than on every request.
+    checked:    Instant,
+    error:      Option<String>,
+}
+
+pub struct PortfolioService {
+    sources: Vec<Arc<dyn BalanceSource>>,
+    config:  PortfolioConfig,
+    /// address → source name → entry
+    cache:   Mutex<HashMap<String, HashMap<String, CachedEntry>>>,
+}
+
+impl PortfolioService {
+    pub fn new(config: PortfolioConfig) -> Self {
+        Self { sources: Vec::new(), config, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Add a balance source.
+    pub fn with_source(mut self, source: Arc<dyn BalanceSource>) -> Self {
+        self.sources.push(source);
+        self
+    }
+
+    pub fn config(&self) -> &PortfolioConfig {
+        &self.config
+    }
+
+    /// Aggregated holdings of `address`, fetching only the sources whose
+    /// cache entry is missing or older than the TTL.
+    pub async fn portfolio(&self, address: &str) -> Portfolio {
+        for source in &self.sources {
+            if !self.is_fresh(address, source.name()) {
+                self.fetch(address, source.as_ref()).await;
+            }
+        }
+        self.assemble(address)
+    }
+
+    /// Re-fetch every source for `address`, ignoring the TTL.
+    pub async fn refresh(&self, address: &str) {
+        for source in &self.sources {
+            self.fetch(address, source.as_ref()).await;
+        }
+    }
+
+    /// Periodically refresh every address that has been queried.
+    pub fn spawn_refresh(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let jitter_ms = self.config.jitter.as_millis() as u64;
+                let extra = if jitter_ms == 0 { 0 } else { rand::thread_rng().gen_range(0..=jitter_ms) };
+                tokio::time::sleep(self.config.refresh_interval + Duration::from_millis(extra)).await;
+
+                let addresses: Vec<String> = self.cache.lock().unwrap().keys().cloned().collect();
+                for address in addresses {
+                    self.refresh(&address).await;
+                }
+            }
+        })
+    }
+
+    fn is_fresh(&self, address: &str, source: &str) -> bool {
+        self.cache.lock().unwrap()
+            .get(address)
+            .and_then(|m| m.get(source))
+            .map(|e| e.checked.elapsed() < self.config.ttl)
+            .unwrap_or(false)
+    }
+
+    async fn fetch(&self, address: &str, source: &dyn BalanceSource) {
+        // The lock is never held across the fetch.
+        let result = source.balances(address).await;
+
+        let mut cache = self.cache.lock().unwrap();
+        if !cache.contains_key(address) && cache.len() >= self.config.max_tracked {
+            log::warn!("[Portfolio] tracking limit reached; not caching {}", address);
+            return;
+        }
+        let entries = cache.entry(address.to_string()).or_default();
+        match result {
+            Ok(balances) => {
+                entries.insert(source.name().to_string(), CachedEntry {
+                    balances,
+                    fetched_at: now_secs(),
+                    checked:    Instant::now(),
+                    error:      None,
+                });
+            }
+            Err(e) => {
+                log::warn!("[Portfolio] source {} failed for {}: {}", source.name(), address, e);
+                let entry = entries.entry(source.name().to_string()).or_insert_with(|| CachedEntry {
+                    balances:   Vec::new(),
+                    fetched_at: 0,
+                    checked:    Instant::now(),
+                    error:      None,
+                });
+                entry.checked = Instant::now();
+                entry.error = Some(e);
+            }
+        }
+    }
+
+    fn assemble(&self, address: &str) -> Portfolio {
+        let cache = self.cache.lock().unwrap();
+        let entries = cache.get(address);
+
+        let mut totals: BTreeMap<Asset, u128> = BTreeMap::new();
+        let mut sources = Vec::with_capacity(self.sources.len());
+        for source in &self.sources {
+            let entry = match entries.and_then(|m| m.get(source.name())) {
+                Some(e) => SourceEntry {
+                    source:     source.name().to_string(),
+                    balances:   e.balances.clone(),
+                    fetched_at: e.fetched_at,
+                    stale:      e.error.is_some(),
+                    error:      e.error.clone(),
+                },
+                // Only reachable when the tracking limit refused the address.
+                None => SourceEntry {
+                    source:     source.name().to_string(),
+                    balances:   Vec::new(),
+                    fetched_at: 0,
+                    stale:      true,
+                    error:      Some("not cached".into()),
+                },
+            };
+            for b in &entry.balances {
+                let t = totals.entry(b.asset.clone()).or_insert(0);
+                *t = t.saturating_add(b.amount);
+            }
+            sources.push(entry);
+        }
+
+        Portfolio {
+            address: address.to_string(),
+            totals:  totals.into_iter().map(|(asset, amount)| AssetBalance { asset, amount }).collect(),
+            stale:   sources.iter().any(|s| s.stale),
+            sources,
+        }
+    }
+}
+
+/// GET `/rpc/wallet/{address}/portfolio` from a node.
+pub async fn fetch_portfolio(rpc_url: &str, address: &str) -> Result<Portfolio, Box<dyn Error>> {
+    let resp = reqwest::Client::new()
+        .get(format!("{}/rpc/wallet/{}/portfolio", rpc_url.trim_end_matches('/'), address))
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("portfolio failed ({}): {}", status, body).into());
+    }
+    Ok(resp.json::<Portfolio>().await?)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+mod amount_str {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(v: &u128, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&v.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<u128, D::Error> {
+        String::deserialize(d)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    struct MockSource {
+        name:     String,
+        balances: Vec<AssetBalance>,
+        calls:    AtomicUsize,
+        fail:     AtomicBool,
+    }
+
+    impl MockSource {
+        fn new(name: &str, balances: Vec<AssetBalance>) -> Arc<Self> {
+            Arc::new(Self {
+                name: name.into(),
+                balances,
+                calls: AtomicUsize::new(0),
+                fail: AtomicBool::new(false),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl BalanceSource for MockSource {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        async fn balances(&self, _address: &str) -> Result<Vec<AssetBalance>, String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail.load(Ordering::SeqCst) {
+                Err("adapter unreachable".into())
+            } else {
+                Ok(self.balances.clone())
+            }
+        }
+    }
+
+    fn bal(asset: Asset, amount: u128) -> AssetBalance {
+        AssetBalance { asset, amount }
+    }
+
+    fn pat(symbol: &str) -> Asset {
+        Asset::Pat { symbol: symbol.into() }
+    }
+
+    fn config(ttl: Duration) -> PortfolioConfig {
+        PortfolioConfig { ttl, ..PortfolioConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn aggregates_across_shards_tokens_and_chains() {
+        let eth = Asset::Bridged { chain: "ethereum".into(), asset: "USDC".into() };
+        let svc = PortfolioService::new(config(Duration::from_secs(60)))
+            .with_source(MockSource::new("shard-0", vec![bal(Asset::Native, 100)]))
+            .with_source(MockSource::new("shard-1", vec![bal(Asset::Native, 50)]))
+            .with_source(MockSource::new("pat", vec![bal(pat("GLD"), 7), bal(pat("ART"), 2)]))
+            .with_source(MockSource::new("ethereum", vec![bal(eth.clone(), 3)]));
+
+        let p = svc.portfolio("BLEEP1abc").await;
+        assert!(!p.stale);
+        assert_eq!(p.sources.len(), 4);
+        assert_eq!(p.totals, vec![
+            bal(Asset::Native, 150),
+            bal(pat("ART"), 2),
+            bal(pat("GLD"), 7),
+            bal(eth, 3),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn failing_adapter_serves_stale_value() {
+        let shard = MockSource::new("shard-0", vec![bal(Asset::Native, 10)]);
+        let chain = MockSource::new("ethereum", vec![bal(Asset::Bridged { chain: "ethereum".into(), asset: "ETH".into() }, 4)]);
+        let svc = PortfolioService::new(config(Duration::ZERO))
+            .with_source(shard.clone())
+            .with_source(chain.clone());
+
+        let first = svc.portfolio("addr").await;
+        assert!(!first.stale);
+
+        chain.fail.store(true, Ordering::SeqCst);
+        let second = svc.portfolio("addr").await;
+        assert!(second.stale);
+        let entry = second.sources.iter().find(|s| s.source == "ethereum").unwrap();
+        assert!(entry.stale);
+        assert_eq!(entry.error.as_deref(), Some("adapter unreachable"));
+        assert_eq!(entry.balances, first.sources[1].balances);
+        assert_eq!(entry.fetched_at, first.sources[1].fetched_at);
+        assert!(!second.sources[0].stale);
+        assert_eq!(second.totals, first.totals);
+    }
+
+    #[tokio::test]
+    async fn cache_serves_within_ttl() {
+        let shard = MockSource::new("shard-0", vec![bal(Asset::Native, 10)]);
+        let svc = PortfolioService::new(config(Duration::from_secs(60))).with_source(shard.clone());
+
+        svc.portfolio("addr").await;
+        svc.portfolio("addr").await;
+        assert_eq!(shard.calls.load(Ordering::SeqCst), 1);
+
+        svc.refresh("addr").await;
+        assert_eq!(shard.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,215 @@
+//! # Private transfers
+//!
+//! Proving a private transfer takes seconds of CPU, so the wallet never
+//! does it inline.  `begin_private_transfer` queues the proof on a
+//! background `ProverService` and returns a `JobId` at once; `poll_job`
+//! reports `Queued` → `Proving { percent, stage }` → `Done(proof)` /
+//! `Failed(reason)`.  `create_private_transfer` is the blocking form, for
+//! the CLI.
+//!
+//! ```text
+//! ~/.bleep/proofs/<wallet address>/<sha256(request id)>.proof
+//! ```
+//!
+//! The request id is a hash of the transfer, so asking for the same
+//! transfer after a restart returns the stored proof instead of proving
+//! it again.
+//!
+//! The wallet does not fix the circuit: a `TransferCircuit` turns a
+//! transfer into a proof job, typically a `Groth16Job` over the deployed
+//! circuit and proving key.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use bleep_crypto::prover::{JobId, JobStatus, ProofJob, ProverConfig, ProverError, ProverService, ProverStats};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::wallet::EncryptedWallet;
+
+/// Prefix of the bytes hashed into a transfer's request id.
+pub const TRANSFER_DOMAIN: &str = "bleep-private-transfer-v1:";
+
+/// A private transfer to prove.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivateTransfer {
+    pub from:   String,
+    pub to:     String,
+    pub amount: u64,
+    /// Distinguishes otherwise identical transfers.
+    pub nonce:  u64,
+}
+
+impl PrivateTransfer {
+    /// Stable id of this transfer's proof request.
+    pub fn request_id(&self) -> String {
+        let mut h = Sha256::new();
+        h.update(TRANSFER_DOMAIN.as_bytes());
+        for field in [self.from.as_bytes(), self.to.as_bytes()] {
+            h.update((field.len() as u32).to_le_bytes());
+            h.update(field);
+        }
+        h.update(self.amount.to_le_bytes());
+        h.update(self.nonce.to_le_bytes());
+        hex::encode(h.finalize())
+    }
+}
+
+/// Builds the proof job for a transfer.
+pub trait TransferCircuit: Send + Sync {
+    fn job(&self, transfer: &PrivateTransfer) -> Result<Box<dyn ProofJob>, String>;
+}
+
+/// A wallet's private-transfer proofs, generated in the background.
+pub struct PrivateTransfers {
+    prover:  ProverService,
+    circuit: Arc<dyn TransferCircuit>,
+}
+
+impl PrivateTransfers {
+    pub fn new(circuit: Arc<dyn TransferCircuit>, config: ProverConfig) -> Self {
+        PrivateTransfers { prover: ProverService::new(config), circuit }
+    }
+
+    /// Queue the proof for `transfer`; returns without waiting for it.
+    pub fn begin_private_transfer(&self, transfer: &PrivateTransfer) -> Result<JobId, ProverError> {
+        let job = self.circuit.job(transfer).map_err(ProverError::Failed)?;
+        self.prover.submit(&transfer.request_id(), job)
+    }
+
+    /// Where the proof for `id` stands; `None` if unknown or cancelled.
+    pub fn poll_job(&self, id: &JobId) -> Option<JobStatus> {
+        self.prover.status(id)
+    }
+
+    pub fn cancel_job(&self, id: &JobId) -> bool {
+        self.prover.cancel(id)
+    }
+
+    /// Prove `transfer` and wait for the proof.
+    pub fn create_private_transfer(&self, transfer: &PrivateTransfer) -> Result<Vec<u8>, ProverError> {
+        let id = self.begin_private_transfer(transfer)?;
+        self.prover.wait(&id)
+    }
+
+    /// Queue depth and latency, for `ProverTelemetry`.
+    pub fn prover(&self) -> &ProverService {
+        &self.prover
+    }
+
+    pub fn stats(&self) -> ProverStats {
+        self.prover.stats()
+    }
+}
+
+impl EncryptedWallet {
+    /// This wallet's private transfers, with proofs kept under
+    /// `~/.bleep/proofs/<address>/`.
+    pub fn private_transfers(&self, circuit: Arc<dyn TransferCircuit>, workers: usize) -> PrivateTransfers {
+        self.private_transfers_in(default_proofs_dir(), circuit, workers)
+    }
+
+    /// This wallet's private transfers, with proofs kept inside `dir`.
+    pub fn private_transfers_in<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        circuit: Arc<dyn TransferCircuit>,
+        workers: usize,
+    ) -> PrivateTransfers {
+        let config = ProverConfig {
+            workers,
+            store_dir: Some(dir.as_ref().join(self.address())),
+            ..ProverConfig::default()
+        };
+        PrivateTransfers::new(circuit, config)
+    }
+
+    /// A transfer from this wallet.
+    pub fn private_transfer(&self, to: &str, amount: u64, nonce: u64) -> PrivateTransfer {
+        PrivateTransfer { from: self.address().to_string(), to: to.to_string(), amount, nonce }
+    }
+}
+
+fn default_proofs_dir() -> PathBuf {
+    let home = std::env::var("HOME")
+        .or_else(|_| std::env::var("USERPROFILE"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".bleep").join("proofs")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::prover::JobContext;
+
+    const BOB: &str = "BLEEP1bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("bleep-proofs-{}", uuid::Uuid::new_v4()))
+    }
+
+    /// Stands in for a real circuit: a "proof" is the hash of the transfer
+    /// plus a fresh random salt, so two provings never agree.
+    struct SaltedHash;
+
+    struct SaltedJob(PrivateTransfer);
+
+    impl ProofJob for SaltedJob {
+        fn prove(self: Box<Self>, ctx: &JobContext) -> Result<Vec<u8>, String> {
+            ctx.milestone(50, "hashing")?;
+            let mut proof = Sha256::digest(self.0.request_id().as_bytes()).to_vec();
+            proof.extend_from_slice(uuid::Uuid::new_v4().as_bytes());
+            Ok(proof)
+        }
+    }
+
+    impl TransferCircuit for SaltedHash {
+        fn job(&self, transfer: &PrivateTransfer) -> Result<Box<dyn ProofJob>, String> {
+            Ok(Box::new(SaltedJob(transfer.clone())))
+        }
+    }
+
+    #[test]
+    fn blocking_wrapper_returns_the_polled_proof() {
+        let dir = temp_dir();
+        let wallet = EncryptedWallet::new(b"private-transfer-pk".to_vec(), vec![]);
+        let transfers = wallet.private_transfers_in(&dir, Arc::new(SaltedHash), 1);
+        let transfer = wallet.private_transfer(BOB, 250, 1);
+
+        let id = transfers.begin_private_transfer(&transfer).unwrap();
+        assert_eq!(id.as_str(), transfer.request_id());
+        let polled = loop {
+            match transfers.poll_job(&id) {
+                Some(JobStatus::Done(proof)) => break proof,
+                Some(JobStatus::Failed(reason)) => panic!("{reason}"),
+                _ => std::thread::sleep(std::time::Duration::from_millis(2)),
+            }
+        };
+        assert_eq!(transfers.create_private_transfer(&transfer).unwrap(), polled);
+        assert_eq!(transfers.stats().completed, 1);
+
+        // A different transfer is a different proof.
+        let other = transfers.create_private_transfer(&wallet.private_transfer(BOB, 250, 2)).unwrap();
+        assert_ne!(other, polled);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn proofs_survive_a_wallet_restart() {
+        let dir = temp_dir();
+        let wallet = EncryptedWallet::new(b"private-transfer-pk".to_vec(), vec![]);
+        let transfer = wallet.private_transfer(BOB, 9, 7);
+        let proof = wallet
+            .private_transfers_in(&dir, Arc::new(SaltedHash), 1)
+            .create_private_transfer(&transfer)
+            .unwrap();
+
+        let transfers = wallet.private_transfers_in(&dir, Arc::new(SaltedHash), 1);
+        let id = JobId::new(&transfer.request_id());
+        assert_eq!(transfers.poll_job(&id), Some(JobStatus::Done(proof.clone())));
+        assert_eq!(transfers.create_private_transfer(&transfer).unwrap(), proof);
+        assert_eq!(transfers.stats().completed, 0, "the stored proof was not regenerated");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
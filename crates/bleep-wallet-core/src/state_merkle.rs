@@ -0,0 +1,338 @@
+//! # StateMerkle
+//!
+//! The wallet's local record of the latest transaction per sender, kept
+//! as a sparse Merkle tree so it has a root and per-key proofs.
+//!
+//! A key sits at the 256-bit path `SHA3-256(key)`.  Like a Patricia trie,
+//! a subtree holding a single entry hashes to that entry's leaf, so a leaf
+//! lives at the shallowest depth where it is alone and the tree is about
+//! `log2(n)` levels deep rather than 256.  Leaves commit to their full path,
+//! which is what makes that shortcut unambiguous.
+//!
+//! ```text
+//!   empty subtree   = [0; 32]
+//!   one entry       = SHA3(0x00 || path || bincode(tx))
+//!   two or more     = SHA3(0x01 || left || right)
+//! ```
+//!
+//! Interior hashes are cached for every subtree with two or more entries,
+//! so `update_state` rehashes only the path from the changed leaf to the
+//! root.  `root_from_scratch` recomputes the root from the leaves alone;
+//! it is kept for tests and for the `state_merkle` benchmark, which
+//! compares the two.
+//!
+//! Alongside the tree it tallies balances: `credit` funds an account and
+//! every newly stored transaction moves `amount` from its sender to its
+//! recipient and burns `fee`.  The tally is local bookkeeping and is not
+//! committed to by the root.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+
+use crate::wallet_core::Transaction;
+
+pub type NodeHash = [u8; 32];
+
+const EMPTY: NodeHash = [0u8; 32];
+const DEPTH: usize = 256;
+
+/// Tree path of `key`.
+pub fn key_path(key: &str) -> [u8; 32] {
+    Sha3_256::digest(key.as_bytes()).into()
+}
+
+/// Leaf hash of `tx` stored under `key`.
+pub fn leaf_hash(key: &str, tx: &Transaction) -> NodeHash {
+    let mut hasher = Sha3_256::new();
+    hasher.update([0x00]);
+    hasher.update(key_path(key));
+    hasher.update(bincode::serialize(tx).expect("transaction encodes"));
+    hasher.finalize().into()
+}
+
+fn interior_hash(left: &NodeHash, right: &NodeHash) -> NodeHash {
+    let mut hasher = Sha3_256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn bit_at(path: &[u8; 32], depth: usize) -> u8 {
+    (path[depth / 8] >> (7 - depth % 8)) & 1
+}
+
+/// `path` with every bit from `depth` on set to `fill`.
+fn prefix_at(path: &[u8; 32], depth: usize, fill: bool) -> [u8; 32] {
+    let mut out = *path;
+    let full = depth / 8;
+    if full < 32 {
+        let keep = if depth % 8 == 0 { 0 } else { 0xFFu8 << (8 - depth % 8) };
+        out[full] = if fill { out[full] | !keep } else { out[full] & keep };
+        out[full + 1..].fill(if fill { 0xFF } else { 0 });
+    }
+    out
+}
+
+/// `path` with bit `depth` flipped.
+fn flip_bit(path: &[u8; 32], depth: usize) -> [u8; 32] {
+    let mut out = *path;
+    out[depth / 8] ^= 1 << (7 - depth % 8);
+    out
+}
+
+/// Inclusion proof for one key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateProof {
+    pub key:      String,
+    pub leaf:     NodeHash,
+    /// Sibling hashes, leaf end first; there is one per level above the leaf.
+    pub siblings: Vec<NodeHash>,
+}
+
+impl StateProof {
+    /// Whether this proves `tx` is stored under `key` in the tree with `root`.
+    pub fn verify(&self, root: &NodeHash, tx: &Transaction) -> bool {
+        if self.siblings.len() > DEPTH || self.leaf != leaf_hash(&self.key, tx) {
+            return false;
+        }
+        let path = key_path(&self.key);
+        let depth = self.siblings.len();
+        let mut node = self.leaf;
+        for (i, sibling) in self.siblings.iter().enumerate() {
+            node = match bit_at(&path, depth - 1 - i) {
+                0 => interior_hash(&node, sibling),
+                _ => interior_hash(sibling, &node),
+            };
+        }
+        node == *root
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct StateMerkle {
+    store:    HashMap<String, Transaction>,
+    /// Leaf hash by path.
+    leaves:   BTreeMap<[u8; 32], NodeHash>,
+    /// Hash of every subtree with two or more leaves, by
+    /// `(depth, path prefix)`.
+    branches: HashMap<(u16, [u8; 32]), NodeHash>,
+    root:     NodeHash,
+    balances: HashMap<String, f64>,
+}
+
+impl StateMerkle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `tx` as the latest transaction from `from`.  Storing the
+    /// transaction already held for `from` again leaves balances alone.
+    pub fn update_state(&mut self, from: &str, tx: Transaction) {
+        if self.store.get(from).map_or(true, |prev| prev.id != tx.id) {
+            *self.balances.entry(from.to_string()).or_default() -= tx.amount + tx.fee;
+            *self.balances.entry(tx.to.clone()).or_default() += tx.amount;
+        }
+        let path = key_path(from);
+        self.leaves.insert(path, leaf_hash(from, &tx));
+        self.store.insert(from.to_string(), tx);
+        self.rehash_path(&path);
+    }
+
+    pub fn remove_state(&mut self, from: &str) -> Option<Transaction> {
+        let tx = self.store.remove(from)?;
+        let path = key_path(from);
+        self.leaves.remove(&path);
+        self.rehash_path(&path);
+        Some(tx)
+    }
+
+    pub fn get_state(&self, from: &str) -> Option<&Transaction> {
+        self.store.get(from)
+    }
+
+    pub fn root(&self) -> NodeHash {
+        self.root
+    }
+
+    /// Add `amount` to the balance of `address`.
+    pub fn credit(&mut self, address: &str, amount: f64) {
+        *self.balances.entry(address.to_string()).or_default() += amount;
+    }
+
+    /// Tallied balance of `address`; 0 for accounts never seen.
+    pub fn balance(&self, address: &str) -> f64 {
+        self.balances.get(address).copied().unwrap_or(0.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.store.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.store.is_empty()
+    }
+
+    /// The transaction stored under `key` with a proof against `root()`.
+    pub fn get_with_proof(&self, key: &str) -> Option<(&Transaction, StateProof)> {
+        let tx = self.store.get(key)?;
+        let path = key_path(key);
+        let depth = self.leaf_depth(&path);
+        let siblings = (0..depth).rev()
+            .map(|d| self.node_hash(d + 1, &flip_bit(&path, d)))
+            .collect();
+        Some((tx, StateProof { key: key.to_string(), leaf: self.leaves[&path], siblings }))
+    }
+
+    /// Root recomputed from the leaves without the branch cache.
+    pub fn root_from_scratch(&self) -> NodeHash {
+        fn subtree(leaves: &[([u8; 32], NodeHash)], depth: usize) -> NodeHash {
+            match leaves {
+                [] => EMPTY,
+                [(_, leaf)] => *leaf,
+                _ => {
+                    let split = leaves.partition_point(|(path, _)| bit_at(path, depth) == 0);
+                    interior_hash(&subtree(&leaves[..split], depth + 1), &subtree(&leaves[split..], depth + 1))
+                }
+            }
+        }
+        let leaves: Vec<_> = self.leaves.iter().map(|(p, h)| (*p, *h)).collect();
+        subtree(&leaves, 0)
+    }
+
+    /// Number of leaves under the subtree at `depth` containing `path`,
+    /// capped at 2, and the leaf if there is exactly one.
+    fn occupancy(&self, depth: usize, path: &[u8; 32]) -> (usize, NodeHash) {
+        let mut range = self.leaves.range(prefix_at(path, depth, false)..=prefix_at(path, depth, true));
+        match (range.next(), range.next()) {
+            (None, _) => (0, EMPTY),
+            (Some((_, leaf)), None) => (1, *leaf),
+            _ => (2, EMPTY),
+        }
+    }
+
+    fn node_hash(&self, depth: usize, path: &[u8; 32]) -> NodeHash {
+        match self.branches.get(&(depth as u16, prefix_at(path, depth, false))) {
+            Some(hash) => *hash,
+            None => self.occupancy(depth, path).1,
+        }
+    }
+
+    /// Shallowest depth at which the subtree containing `path` holds at
+    /// most one leaf.
+    fn leaf_depth(&self, path: &[u8; 32]) -> usize {
+        let (mut lo, mut hi) = (0, DEPTH);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            if self.occupancy(mid, path).0 < 2 { hi = mid } else { lo = mid + 1 }
+        }
+        lo
+    }
+
+    /// Bring the cached branches and root up to date after the leaf at
+    /// `path` was set or removed.
+    fn rehash_path(&mut self, path: &[u8; 32]) {
+        let depth = self.leaf_depth(path);
+        // Branches that now hold one leaf or none; they form a run from `depth`.
+        for d in depth..DEPTH {
+            if self.branches.remove(&(d as u16, prefix_at(path, d, false))).is_none() {
+                break;
+            }
+        }
+        let mut node = self.node_hash(depth, path);
+        for d in (0..depth).rev() {
+            let sibling = self.node_hash(d + 1, &flip_bit(path, d));
+            node = match bit_at(path, d) {
+                0 => interior_hash(&node, &sibling),
+                _ => interior_hash(&sibling, &node),
+            };
+            self.branches.insert((d as u16, prefix_at(path, d, false)), node);
+        }
+        self.root = node;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bleep_crypto::chain_id::ChainId;
+
+    fn tx(from: &str, amount: f64) -> Transaction {
+        Transaction {
+            id:        format!("{}-{}", from, amount),
+            from:      from.into(),
+            to:        "BLEEP1sink".into(),
+            amount,
+            fee:       0.1,
+            signature: vec![],
+            nonce:     0,
+            chain_id:  ChainId::NONE,
+        }
+    }
+
+    #[test]
+    fn test_incremental_root_matches_rebuild() {
+        let mut state = StateMerkle::new();
+        assert_eq!(state.root(), EMPTY);
+        for i in 0..300 {
+            let from = format!("BLEEP1acct{}", i % 120);
+            state.update_state(&from, tx(&from, i as f64));
+            assert_eq!(state.root(), state.root_from_scratch(), "after update {}", i);
+        }
+        for i in (0..120).step_by(3) {
+            state.remove_state(&format!("BLEEP1acct{}", i)).unwrap();
+            assert_eq!(state.root(), state.root_from_scratch(), "after removing {}", i);
+        }
+        assert_eq!(state.len(), 80);
+        while let Some(key) = state.store.keys().next().cloned() {
+            state.remove_state(&key);
+        }
+        assert_eq!(state.root(), EMPTY);
+        assert!(state.branches.is_empty());
+    }
+
+    #[test]
+    fn test_get_with_proof() {
+        let mut state = StateMerkle::new();
+        for i in 0..50 {
+            let from = format!("BLEEP1acct{}", i);
+            state.update_state(&from, tx(&from, i as f64));
+        }
+        let root = state.root();
+        let (stored, proof) = state.get_with_proof("BLEEP1acct7").unwrap();
+        assert!(proof.verify(&root, stored));
+        assert!(proof.siblings.len() < 16);
+
+        assert!(!proof.verify(&root, &tx("BLEEP1acct7", 8.0)));
+        let mut tampered = proof.clone();
+        tampered.siblings[0][0] ^= 1;
+        assert!(!tampered.verify(&root, stored));
+        let mut moved = proof.clone();
+        moved.key = "BLEEP1acct8".into();
+        assert!(!moved.verify(&root, stored));
+        assert!(state.get_with_proof("BLEEP1nobody").is_none());
+
+        let mut single = StateMerkle::new();
+        single.update_state("BLEEP1only", tx("BLEEP1only", 1.0));
+        let (stored, proof) = single.get_with_proof("BLEEP1only").unwrap();
+        assert!(proof.siblings.is_empty());
+        assert!(proof.verify(&single.root(), stored));
+    }
+
+    #[test]
+    fn test_balances_follow_stored_transactions() {
+        let mut state = StateMerkle::new();
+        state.credit("BLEEP1alice", 50.0);
+        let sent = tx("BLEEP1alice", 20.0);
+        state.update_state("BLEEP1alice", sent.clone());
+        assert_eq!(state.balance("BLEEP1alice"), 50.0 - (20.0 + 0.1));
+        assert_eq!(state.balance("BLEEP1sink"), 20.0);
+
+        // Recording the same transaction again moves nothing.
+        state.update_state("BLEEP1alice", sent);
+        assert_eq!(state.balance("BLEEP1sink"), 20.0);
+        assert_eq!(state.balance("BLEEP1nobody"), 0.0);
+    }
+}
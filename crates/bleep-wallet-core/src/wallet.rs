@@ -13,6 +13,14 @@
 //! `lock(sk, password)` encrypts; `unlock(password)` decrypts.
 //! Wallets without a signing key (legacy) remain functional for balance
 //! queries; only signing is gated behind `can_sign()`.
+//!
+//! ## Key rotation
+//! `WalletManager::rotate_key` replaces a wallet's signing key through an
+//! on-chain key-change transaction while the address stays the same.  The
+//! new key is written to disk as a `PendingRotation` *before* the
+//! transaction is broadcast and only becomes the wallet's key once the
+//! chain reports it, so a crash at any point leaves either the old key
+//! active with the new one recoverable, or the rotation complete.
 
 use std::error::Error;
 use std::path::{Path, PathBuf};
@@ -26,6 +34,11 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest as Sha2Digest, Sha256};
 use sha3::{Sha3_256};
 
+use bleep_crypto::chain_id::ChainId;
+use bleep_crypto::key_change::{key_hash, KeyChange};
+use bleep_crypto::redaction::Sensitive;
+use bleep_crypto::tx_signer::{generate_tx_keypair, sign_tx_payload, tx_payload_with_nonce};
+
 // ─── EncryptedWallet ──────────────────────────────────────────────────────────
 
 /// One wallet record persisted to disk.
@@ -49,6 +62,70 @@ pub struct EncryptedWallet {
     pub address: String,
     /// Human-readable label (optional).
     pub label: Option<String>,
+    /// Key rotation broadcast but not yet confirmed on chain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pending_rotation: Option<PendingRotation>,
+}
+
+/// A new signing key waiting for its key-change transaction to confirm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingRotation {
+    /// New SPHINCS+ public key.
+    pub public_key:  Vec<u8>,
+    /// New secret key, encrypted like `EncryptedWallet::signing_key`.
+    pub signing_key: Vec<u8>,
+    /// The key change, kept so it can be rebroadcast after a crash.
+    pub tx:          KeyChangeTx,
+}
+
+/// `POST /rpc/tx` body of a key-change transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyChangeTx {
+    pub sender:    String,
+    pub receiver:  String,
+    pub amount:    u64,
+    pub timestamp: u64,
+    pub signature: Vec<u8>,
+    pub max_fee:   u64,
+    pub tip:       u64,
+    #[serde(default)]
+    pub nonce:     u64,
+    #[serde(default, skip_serializing_if = "ChainId::is_none")]
+    pub chain_id:  ChainId,
+}
+
+impl KeyChangeTx {
+    /// Payload the signature and any recovery co-signature cover.
+    pub fn payload(&self) -> [u8; 32] {
+        self.chain_id.bind(tx_payload_with_nonce(
+            &self.sender, &self.receiver, self.amount, self.timestamp, self.max_fee, self.tip, self.nonce,
+        ))
+    }
+}
+
+/// Parameters of `EncryptedWallet::begin_key_rotation`.
+#[derive(Debug, Clone, Default)]
+pub struct RotationOptions {
+    /// Recovery key to register with the change.
+    pub register_recovery: Option<[u8; 32]>,
+    /// `(public, secret)` of the registered recovery key, which must
+    /// co-sign once one is registered.
+    pub cosigner:          Option<(Vec<u8>, Sensitive<Vec<u8>>)>,
+    pub max_fee:           u64,
+    pub timestamp:         u64,
+    /// The account's next nonce.
+    pub nonce:             u64,
+    /// Network the key change is signed for.
+    pub chain_id:          ChainId,
+}
+
+/// Where a rotation stands against the chain's record of the account key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotationStatus {
+    /// The chain still has the old key.
+    Pending,
+    /// The chain has the new key and the wallet now signs with it.
+    Completed,
 }
 
 impl EncryptedWallet {
@@ -57,7 +134,7 @@ impl EncryptedWallet {
     /// Create a wallet from a public key only (no signing capability).
     pub fn new(falcon_keys: Vec<u8>, kyber_keys: Vec<u8>) -> Self {
         let address = Self::derive_address(&falcon_keys);
-        Self { falcon_keys, kyber_keys, signing_key: vec![], address, label: None }
+        Self { falcon_keys, kyber_keys, signing_key: vec![], address, label: None, pending_rotation: None }
     }
 
     /// Create a wallet and immediately encrypt the secret key.
@@ -72,14 +149,14 @@ impl EncryptedWallet {
     ) -> Result<Self, Box<dyn Error>> {
         let address     = Self::derive_address(&falcon_pk);
         let signing_key = encrypt_key(falcon_sk, password, &address)?;
-        Ok(Self { falcon_keys: falcon_pk, kyber_keys, signing_key, address, label: None })
+        Ok(Self { falcon_keys: falcon_pk, kyber_keys, signing_key, address, label: None, pending_rotation: None })
     }
 
     /// Legacy constructor — stores the SK in plaintext.
     /// Prefer `with_signing_key_encrypted` in new code.
     pub fn with_signing_key(falcon_pk: Vec<u8>, falcon_sk: Vec<u8>, kyber_keys: Vec<u8>) -> Self {
         let address = Self::derive_address(&falcon_pk);
-        Self { falcon_keys: falcon_pk, kyber_keys, signing_key: falcon_sk, address, label: None }
+        Self { falcon_keys: falcon_pk, kyber_keys, signing_key: falcon_sk, address, label: None, pending_rotation: None }
     }
 
     // ── Key encryption / decryption ───────────────────────────────────────────
@@ -120,6 +197,126 @@ impl EncryptedWallet {
 
     /// Returns `true` if a (possibly encrypted) signing key is stored.
     pub fn can_sign(&self) -> bool { !self.signing_key.is_empty() }
+
+    // ── Key rotation ──────────────────────────────────────────────────────────
+
+    /// Generate a new key pair, record it as the pending rotation and return
+    /// the key change signed by the current key.  With a rotation already
+    /// pending, its transaction is returned unchanged so a retry after a
+    /// crash rebroadcasts the same change.
+    pub fn begin_key_rotation(
+        &mut self,
+        password: &str,
+        opts:     &RotationOptions,
+    ) -> Result<KeyChangeTx, Box<dyn Error>> {
+        if let Some(pending) = &self.pending_rotation {
+            return Ok(pending.tx.clone());
+        }
+        let current_sk = self.unlock(password)?;
+        let (new_pk, new_sk) = generate_tx_keypair();
+        let change = KeyChange { new_key_hash: key_hash(&new_pk), recovery_key_hash: opts.register_recovery };
+        let mut tx = KeyChangeTx {
+            sender:    self.address.clone(),
+            receiver:  change.receiver(),
+            amount:    0,
+            timestamp: opts.timestamp,
+            signature: self.falcon_keys.clone(),
+            max_fee:   opts.max_fee,
+            tip:       0,
+            nonce:     opts.nonce,
+            chain_id:  opts.chain_id,
+        };
+        let payload = tx.payload();
+        tx.signature.extend(sign_tx_payload(&payload, &current_sk)?);
+        if let Some((recovery_pk, recovery_sk)) = &opts.cosigner {
+            tx.signature.extend(recovery_pk);
+            tx.signature.extend(sign_tx_payload(&payload, recovery_sk.expose())?);
+        }
+        self.pending_rotation = Some(PendingRotation {
+            public_key:  new_pk,
+            signing_key: encrypt_key(&new_sk, password, &self.address)?,
+            tx:          tx.clone(),
+        });
+        Ok(tx)
+    }
+
+    /// Compare the pending rotation with the key the chain records for the
+    /// account (`None` before any key change) and switch to the new key once
+    /// the chain has it.
+    pub fn complete_key_rotation(&mut self, chain_key_hash: Option<[u8; 32]>) -> Result<RotationStatus, Box<dyn Error>> {
+        let pending = self.pending_rotation.as_ref().ok_or("no key rotation pending")?;
+        match chain_key_hash {
+            Some(h) if h == key_hash(&pending.public_key) => {
+                let pending = self.pending_rotation.take().ok_or("no key rotation pending")?;
+                self.falcon_keys = pending.public_key;
+                self.signing_key = pending.signing_key;
+                Ok(RotationStatus::Completed)
+            }
+            None => Ok(RotationStatus::Pending),
+            Some(h) if h == key_hash(&self.falcon_keys) => Ok(RotationStatus::Pending),
+            Some(h) => Err(format!("chain records unknown key {} for {}", hex::encode(h), self.address).into()),
+        }
+    }
+
+    // ── Pre-flight ────────────────────────────────────────────────────────────
+
+    /// Dry-run a transaction from this wallet via `POST {rpc_url}/rpc/tx/simulate`
+    /// before signing and broadcasting it.  Nothing is committed on the node.
+    pub async fn preflight(
+        &self,
+        rpc_url: &str,
+        mut tx:  PreflightTx,
+    ) -> Result<PreflightReport, Box<dyn Error>> {
+        if tx.sender.is_empty() {
+            tx.sender = self.address.clone();
+        }
+        let resp = reqwest::Client::new()
+            .post(format!("{}/rpc/tx/simulate", rpc_url.trim_end_matches('/')))
+            .json(&tx)
+            .send()
+            .await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(format!("simulate failed ({}): {}", status, body).into());
+        }
+        Ok(resp.json::<PreflightReport>().await?)
+    }
+}
+
+/// Transaction body sent to `/rpc/tx/simulate`. An empty `sender` is filled
+/// in with the wallet address by `EncryptedWallet::preflight`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreflightTx {
+    pub sender:       String,
+    pub receiver:     String,
+    pub amount:       u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce:        Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub block_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contract:     Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calldata:     Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_limit:    Option<u64>,
+}
+
+/// Simulation result returned by the node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreflightReport {
+    pub success:               bool,
+    pub gas_used:              u64,
+    pub output:                String,
+    #[serde(default)]
+    pub events:                Vec<serde_json::Value>,
+    #[serde(default)]
+    pub state_changes_summary: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub storage_changes:       Vec<serde_json::Value>,
+    pub failure_reason:        Option<String>,
+    pub block_height:          u64,
 }
 
 // ── AES-256-GCM helpers ───────────────────────────────────────────────────────
@@ -235,10 +432,84 @@ impl WalletManager {
         Ok(self.wallets.len() < before)
     }
 
+    /// Replace the stored wallet with the same address.
+    pub fn update_wallet(&mut self, wallet: EncryptedWallet) -> Result<(), Box<dyn Error>> {
+        let slot = self
+            .wallets
+            .iter_mut()
+            .find(|w| w.address == wallet.address)
+            .ok_or_else(|| format!("wallet {} not found", wallet.address))?;
+        *slot = wallet;
+        self.persist()
+    }
+
+    // ── Key rotation ──────────────────────────────────────────────────────────
+
+    /// Rotate the signing key of `address` through the node at `rpc_url`.
+    ///
+    /// The new key is persisted as pending before the key change is
+    /// broadcast, then the wallet waits (up to `timeout`) for
+    /// `GET /rpc/state/{address}` to report it.  Calling this again after a
+    /// crash or timeout rebroadcasts the same change instead of generating
+    /// another key.
+    pub async fn rotate_key(
+        &mut self,
+        address:  &str,
+        password: &str,
+        rpc_url:  &str,
+        opts:     &RotationOptions,
+        timeout:  std::time::Duration,
+    ) -> Result<RotationStatus, Box<dyn Error>> {
+        let mut wallet = self.find_by_address(address).cloned().ok_or_else(|| format!("wallet {} not found", address))?;
+        let tx = wallet.begin_key_rotation(password, opts)?;
+        self.update_wallet(wallet)?;
+
+        let base = rpc_url.trim_end_matches('/');
+        let client = reqwest::Client::new();
+        let resp = client.post(format!("{}/rpc/tx", base)).json(&tx).send().await?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            // Already pooled or applied from an earlier attempt.
+            if !body.contains("duplicate") && !body.contains("nonce") {
+                return Err(format!("key change rejected ({}): {}", status, body).into());
+            }
+        }
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let chain_key = fetch_key_hash(&client, base, address).await?;
+            let status = self.resume_key_rotation(address, chain_key)?;
+            if status == RotationStatus::Completed || tokio::time::Instant::now() >= deadline {
+                return Ok(status);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        }
+    }
+
+    /// Settle a pending rotation against the key the chain records for
+    /// `address`; the wallet file is rewritten once the new key is active.
+    pub fn resume_key_rotation(
+        &mut self,
+        address:   &str,
+        chain_key: Option<[u8; 32]>,
+    ) -> Result<RotationStatus, Box<dyn Error>> {
+        let mut wallet = self.find_by_address(address).cloned().ok_or_else(|| format!("wallet {} not found", address))?;
+        let status = wallet.complete_key_rotation(chain_key)?;
+        if status == RotationStatus::Completed {
+            self.update_wallet(wallet)?;
+        }
+        Ok(status)
+    }
+
+    /// Write the wallet file via a temporary file and rename, so a crash
+    /// mid-write never leaves a truncated file behind.
     fn persist(&self) -> Result<(), Box<dyn Error>> {
         if let Some(path) = &self.path {
             let json = serde_json::to_string_pretty(&self.wallets)?;
-            std::fs::write(path, json)?;
+            let tmp = path.with_extension("json.tmp");
+            std::fs::write(&tmp, json)?;
+            std::fs::rename(&tmp, path)?;
         }
         Ok(())
     }
@@ -251,6 +522,22 @@ impl WalletManager {
     }
 }
 
+/// Key hash `GET /rpc/state/{address}` reports for the account, if any.
+async fn fetch_key_hash(client: &reqwest::Client, base: &str, address: &str) -> Result<Option<[u8; 32]>, Box<dyn Error>> {
+    let resp = client.get(format!("{}/rpc/state/{}", base, address)).send().await?;
+    if !resp.status().is_success() {
+        return Err(format!("state query failed ({})", resp.status()).into());
+    }
+    let body = resp.json::<serde_json::Value>().await?;
+    match body.get("key_hash").and_then(|v| v.as_str()) {
+        Some(h) => {
+            let bytes = hex::decode(h)?;
+            Ok(Some(bytes.try_into().map_err(|_| "key hash must be 32 bytes")?))
+        }
+        None => Ok(None),
+    }
+}
+
 // ── Unit tests ────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -314,4 +601,48 @@ mod tests {
         assert!(w.can_sign());
         assert_eq!(w.unlock("my-password").unwrap(), sk);
     }
+
+    #[test]
+    fn key_rotation_survives_a_crash_before_confirmation() {
+        let path = std::env::temp_dir().join(format!("bleep-rotation-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let (pk, sk) = generate_tx_keypair();
+        let wallet = EncryptedWallet::with_signing_key_encrypted(pk.clone(), &sk, vec![], "pw").unwrap();
+        let address = wallet.address.clone();
+        let opts = RotationOptions { timestamp: 1_700_000_000, ..RotationOptions::default() };
+
+        let mut mgr = WalletManager::load_or_create_at(&path).unwrap();
+        mgr.save_wallet(wallet).unwrap();
+        let mut w = mgr.find_by_address(&address).cloned().unwrap();
+        let tx = w.begin_key_rotation("pw", &opts).unwrap();
+        mgr.update_wallet(w).unwrap();
+        drop(mgr); // crash before the change confirms
+
+        let mut mgr = WalletManager::load_or_create_at(&path).unwrap();
+        let mut w = mgr.find_by_address(&address).cloned().unwrap();
+        assert_eq!(w.begin_key_rotation("pw", &opts).unwrap(), tx);
+        assert_eq!(w.unlock("pw").unwrap(), sk);
+
+        assert_eq!(mgr.resume_key_rotation(&address, Some(key_hash(&pk))).unwrap(), RotationStatus::Pending);
+        let new_hash = key_hash(&w.pending_rotation.as_ref().unwrap().public_key);
+        assert_eq!(mgr.resume_key_rotation(&address, Some(new_hash)).unwrap(), RotationStatus::Completed);
+
+        let mgr = WalletManager::load_or_create_at(&path).unwrap();
+        let w = mgr.find_by_address(&address).unwrap();
+        assert!(w.pending_rotation.is_none());
+        assert_eq!(key_hash(&w.falcon_keys), new_hash);
+        assert_ne!(w.unlock("pw").unwrap(), sk);
+        assert_eq!(w.address, address);
+        assert!(!path.with_extension("json.tmp").exists());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn rotation_options_debug_hides_cosigner_secret() {
+        let (pk, sk) = generate_tx_keypair();
+        let opts = RotationOptions { cosigner: Some((pk, Sensitive::new(sk.clone()))), ..RotationOptions::default() };
+        let shown = format!("{:?}", opts);
+        assert!(shown.contains("[REDACTED:Vec<u8>]"));
+        assert!(!shown.contains(&format!("{:?}", sk)));
+    }
 }
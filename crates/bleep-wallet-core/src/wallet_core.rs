@@ -11,33 +11,56 @@
 //!
 //! All signing goes through `bleep_crypto::tx_signer::sign_tx_payload`, which
 //! calls the production SPHINCS+-SHAKE-256f-simple detached-sign API and
-//! returns the 7,856-byte signature.  The old stub that returned the raw
-//! private key bytes has been removed.
+//! returns the 7,856-byte signature.  A transaction is signed over
+//! `Transaction::signing_bytes` and the detached signature is stored in
+//! `Transaction::signature`; `broadcast_transaction` sends nothing that
+//! `verify_transaction` rejects.
 //!
 //! ## BIP-39 entropy
 //! `Wallet::new` generates entropy with `OsRng` (cryptographically secure).
 //! The previous implementation used a zero-filled `[0u8; 32]` array, which
 //! produced the same mnemonic on every call.
-
+//!
+//! ## HD accounts
+//! Account `i` of a mnemonic is the BIP-32 child at `m/44'/1234'/0'/0/i` of
+//! its BIP-39 seed, and its address is `derive_address` of that child's
+//! compressed public key — so importing a phrase always restores the same
+//! addresses (`Wallet::derive_account` for the others).  The account's
+//! SPHINCS+ signing keypair is seeded by an HKDF-SHA256 expansion of that
+//! child's private key, so the phrase restores it too, and
+//! `Wallet::load_keystore` refuses a keystore whose stored keypair is not
+//! the one its phrase derives.
+
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use bip39::{Language, Mnemonic};
+use bip39::Mnemonic;
 use pqcrypto_kyber::kyber1024::{decapsulate, encapsulate, keypair};
 use pqcrypto_traits::kem::{Ciphertext as _, PublicKey as _, SecretKey as _, SharedSecret as _};
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroizing;
 
+use bleep_crypto::chain_id::ChainId;
 use bleep_crypto::tx_signer;
 
+use crate::keystore::{Keystore, KeystoreSecrets};
+use crate::multisig::MultisigWallet;
+pub use crate::state_merkle::StateMerkle;
+use hdwallet::{ExtendedPrivKey, ExtendedPubKey, KeyIndex};
+
+/// BIP-44 coin type under which BLEEP accounts are derived.
+pub const BLEEP_COIN_TYPE: u32 = 1234;
+
 // ── Stub collaborators (interface-compatible, no external crate deps) ─────────
 //
 // These lightweight stubs provide the same method signatures that the rest of
 // the codebase and the existing tests depend on.  They are NOT placeholders for
 // the core signing/key-management logic — they are intentional thin shims for
-// the subsystems (P2P broadcast, state Merkle, consensus, cross-chain swap,
-// multi-sig) that are wired in via bleep-p2p / bleep-state at the node level.
+// the subsystems (P2P broadcast, consensus, cross-chain swap) that are wired
+// in via bleep-p2p / bleep-state at the node level.  Multisig accounts and
+// the local state tree are real: see `multisig` and `state_merkle`.
 
 /// Minimal P2P broadcast shim.  The real implementation is provided by
 /// `bleep_p2p::P2PNode` and injected at node startup.
@@ -56,7 +79,7 @@ impl P2PNode {
 
     /// Enqueue a transaction for P2P broadcast.
     /// Returns a synthetic transaction ID derived from the payload hash.
-    pub fn broadcast_message(&self, msg: P2PMessage) -> Result<String, ()> {
+    pub fn broadcast_message(&self, msg: P2PMessage) -> Result<String, WalletError> {
         match msg {
             P2PMessage::NewTransaction(data) => {
                 use sha2::{Digest, Sha256};
@@ -67,27 +90,6 @@ impl P2PNode {
     }
 }
 
-/// Minimal state-Merkle shim.
-#[derive(Debug, Default)]
-pub struct StateMerkle {
-    // Maps sender address → most recent stored transaction.
-    store: std::collections::HashMap<String, Transaction>,
-}
-
-impl StateMerkle {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn update_state(&mut self, from: &str, tx: Transaction) {
-        self.store.insert(from.to_string(), tx);
-    }
-
-    pub fn get_state(&self, from: &str) -> Option<&Transaction> {
-        self.store.get(from)
-    }
-}
-
 /// Minimal consensus shim.
 #[derive(Debug, Default)]
 pub struct BLEEPAdaptiveConsensus;
@@ -100,10 +102,6 @@ impl BLEEPAdaptiveConsensus {
     pub fn finalize_transaction(&self, _tx: &Transaction) -> Result<(), WalletError> {
         Ok(())
     }
-
-    pub fn approve_transaction(&self, _tx_id: &str) -> Result<(), WalletError> {
-        Ok(())
-    }
 }
 
 /// Minimal cross-chain swap shim.
@@ -172,6 +170,12 @@ pub enum WalletError {
     MnemonicError,
     #[error("Signing error: {0}")]
     SigningError(String),
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("Multisig error: {0}")]
+    Multisig(String),
+    #[error("Key derivation error: {0}")]
+    Derivation(String),
 }
 
 // ── Transaction ───────────────────────────────────────────────────────────────
@@ -184,6 +188,21 @@ pub struct Transaction {
     pub amount:    f64,
     pub fee:       f64,
     pub signature: Vec<u8>,
+    /// Position in the sender's transaction sequence, from 0.
+    #[serde(default)]
+    pub nonce:     u64,
+    /// Network the transaction is signed for.
+    #[serde(default)]
+    pub chain_id:  ChainId,
+}
+
+impl Transaction {
+    /// Bytes the sender signs: the bincode encoding of every field but
+    /// `signature`, so changing any of them invalidates it.
+    pub fn signing_bytes(&self) -> Result<Vec<u8>, WalletError> {
+        bincode::serialize(&(&self.id, &self.from, &self.to, self.amount, self.fee, self.nonce, self.chain_id))
+            .map_err(|e| WalletError::Serialization(e.to_string()))
+    }
 }
 
 // ── Wallet ────────────────────────────────────────────────────────────────────
@@ -199,6 +218,13 @@ pub struct Wallet {
     pub public_key:    Vec<u8>,
     /// SPHINCS+ secret key — zeroed on drop (SA-L3).
     private_key:       Zeroizing<Vec<u8>>,
+    /// Index `i` of `m/44'/1234'/0'/0/i` this account was derived at.
+    pub account_index: u32,
+    /// Compressed secp256k1 public key of that path; the address hashes it.
+    pub hd_public_key: Vec<u8>,
+    /// Network this wallet signs for; transactions for any other chain are
+    /// neither signed nor verified.
+    pub chain_id:      ChainId,
     mnemonic:          Mnemonic,
     ai_decision_module: Arc<BLEEPAIDecisionModule>,
     consensus_module:   Arc<Mutex<BLEEPAdaptiveConsensus>>,
@@ -210,67 +236,145 @@ pub struct Wallet {
 impl Wallet {
     // ── Constructors ─────────────────────────────────────────────────────────
 
-    /// Create a new wallet.
+    /// Create a new wallet at account 0 of a fresh mnemonic.
     ///
     /// Entropy for the BIP-39 mnemonic is sourced from `OsRng` (32 bytes =
-    /// 256-bit security).  The SPHINCS+ keypair is generated independently
-    /// from OS entropy via `bleep_crypto::tx_signer::generate_tx_keypair`.
+    /// 256-bit security).  The SPHINCS+ keypair is derived from it with the
+    /// account's address.
     pub fn new(
         p2p_node:     Arc<P2PNode>,
         state_merkle: Arc<Mutex<StateMerkle>>,
     ) -> Result<Self, WalletError> {
         // ── BIP-39 mnemonic (cryptographically secure entropy) ────────────────
-        let mut entropy = [0u8; 32];
-        rand::thread_rng().fill_bytes(&mut entropy);
-        let mnemonic = Mnemonic::from_entropy(&entropy)
+        let mut entropy = Zeroizing::new([0u8; 32]);
+        rand::thread_rng().fill_bytes(&mut entropy[..]);
+        let mnemonic = Mnemonic::from_entropy(&entropy[..])
             .map_err(|_| WalletError::MnemonicError)?;
 
-        // ── SPHINCS+ keypair ──────────────────────────────────────────────────
-        let (public_key, secret_key_bytes) = tx_signer::generate_tx_keypair();
-        let address = derive_address(&public_key);
-
-        log::info!("[Wallet] Created wallet address={}", &address[..12]);
-
-        Ok(Self {
-            address,
-            balance: 0.0,
-            authenticated: false,
-            public_key,
-            private_key: Zeroizing::new(secret_key_bytes),
-            mnemonic,
-            ai_decision_module: Arc::new(BLEEPAIDecisionModule::new()),
-            consensus_module:   Arc::new(Mutex::new(BLEEPAdaptiveConsensus::new())),
-            bleep_connect:      Arc::new(BLEEPConnect::new()),
-            state_merkle,
-            p2p_node,
-        })
+        let wallet = Self::from_mnemonic(mnemonic, 0, p2p_node, state_merkle)?;
+        log::info!("[Wallet] Created wallet address={}", &wallet.address[..12]);
+        Ok(wallet)
     }
 
-    /// Import a wallet from a BIP-39 mnemonic phrase.
-    ///
-    /// A fresh SPHINCS+ keypair is generated for the imported wallet.
-    /// In a full HD-wallet implementation this would derive the keypair
-    /// deterministically from the mnemonic seed via the BIP-32/BIP-44 path;
-    /// that derivation path is handled by `bleep_crypto::bip39`.
+    /// Import account 0 of a BIP-39 mnemonic phrase.  The same phrase
+    /// always yields the same address.
     pub fn import_wallet(mnemonic_phrase: &str) -> Result<Self, WalletError> {
         let mnemonic = Mnemonic::parse(mnemonic_phrase)
             .map_err(|_| WalletError::Authentication("Invalid mnemonic".into()))?;
+        Self::from_mnemonic(
+            mnemonic,
+            0,
+            Arc::new(P2PNode::new()),
+            Arc::new(Mutex::new(StateMerkle::new())),
+        )
+    }
+
+    /// Account `index` of this wallet's mnemonic, sharing its P2P node and
+    /// state.
+    pub fn derive_account(&self, index: u32) -> Result<Self, WalletError> {
+        Self::from_mnemonic(
+            self.mnemonic.clone(),
+            index,
+            Arc::clone(&self.p2p_node),
+            Arc::clone(&self.state_merkle),
+        )
+        .map(|account| account.with_chain_id(self.chain_id))
+    }
+
+    /// Sign and verify for the network `chain_id` only.
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Record transactions in `state_merkle` instead of this wallet's own.
+    pub fn with_state_merkle(mut self, state_merkle: Arc<Mutex<StateMerkle>>) -> Self {
+        self.state_merkle = state_merkle;
+        self
+    }
+
+    /// Write this account's mnemonic and SPHINCS+ keypair to an encrypted
+    /// keystore at `path`.
+    pub fn save_keystore<P: AsRef<Path>>(&self, path: P, passphrase: &str) -> Result<(), WalletError> {
+        let secrets = KeystoreSecrets {
+            mnemonic:      self.mnemonic.to_string(),
+            account_index: self.account_index,
+            public_key:    self.public_key.clone(),
+            secret_key:    self.private_key.to_vec(),
+        };
+        Keystore::seal(&self.address, &secrets, passphrase)?.write(path.as_ref())
+    }
+
+    /// Restore a wallet saved with `save_keystore`.  A wrong passphrase is
+    /// `WalletError::Authentication`; a keystore whose SPHINCS+ keypair or
+    /// address is not what its mnemonic derives is refused.
+    pub fn load_keystore<P: AsRef<Path>>(path: P, passphrase: &str) -> Result<Self, WalletError> {
+        let store = Keystore::read(path.as_ref())?;
+        let secrets = store.open(passphrase)?;
+        let mnemonic = Mnemonic::parse(&secrets.mnemonic)
+            .map_err(|_| WalletError::Serialization("keystore mnemonic is invalid".into()))?;
+        let wallet = Self::from_mnemonic(
+            mnemonic,
+            secrets.account_index,
+            Arc::new(P2PNode::new()),
+            Arc::new(Mutex::new(StateMerkle::new())),
+        )?;
+        if wallet.public_key != secrets.public_key || wallet.private_key[..] != secrets.secret_key[..] {
+            return Err(WalletError::Serialization(format!(
+                "keystore for {} holds a SPHINCS+ keypair its mnemonic does not derive",
+                store.address,
+            )));
+        }
+        if wallet.address != store.address {
+            return Err(WalletError::Serialization(format!(
+                "keystore is for {}, but its mnemonic derives {}",
+                store.address, wallet.address,
+            )));
+        }
+        Ok(wallet)
+    }
 
-        let (public_key, secret_key_bytes) = tx_signer::generate_tx_keypair();
-        let address = derive_address(&public_key);
+    fn from_mnemonic(
+        mnemonic:     Mnemonic,
+        index:        u32,
+        p2p_node:     Arc<P2PNode>,
+        state_merkle: Arc<Mutex<StateMerkle>>,
+    ) -> Result<Self, WalletError> {
+        // ── SPHINCS+ keypair ──────────────────────────────────────────────────
+        let seed = Zeroizing::new(mnemonic.to_seed(""));
+        let keypair = derive_signing_keypair(&derive_hd_key(&seed[..], index)?);
+        Self::from_parts(mnemonic, index, keypair, p2p_node, state_merkle)
+    }
+
+    fn from_parts(
+        mnemonic:     Mnemonic,
+        index:        u32,
+        (public_key, secret_key_bytes): (Vec<u8>, Zeroizing<Vec<u8>>),
+        p2p_node:     Arc<P2PNode>,
+        state_merkle: Arc<Mutex<StateMerkle>>,
+    ) -> Result<Self, WalletError> {
+        let seed = Zeroizing::new(mnemonic.to_seed(""));
+        let hd_public_key = ExtendedPubKey::from_private_key(&derive_hd_key(&seed[..], index)?)
+            .public_key
+            .serialize()
+            .to_vec();
+        let address = derive_address(&hd_public_key);
 
         Ok(Self {
             address,
             balance: 0.0,
             authenticated: false,
             public_key,
-            private_key: Zeroizing::new(secret_key_bytes),
+            private_key: secret_key_bytes,
+            account_index: index,
+            hd_public_key,
+            chain_id: ChainId::NONE,
             mnemonic,
             ai_decision_module: Arc::new(BLEEPAIDecisionModule::new()),
             consensus_module:   Arc::new(Mutex::new(BLEEPAdaptiveConsensus::new())),
             bleep_connect:      Arc::new(BLEEPConnect::new()),
-            state_merkle:       Arc::new(Mutex::new(StateMerkle::new())),
-            p2p_node:           Arc::new(P2PNode::new()),
+            state_merkle,
+            p2p_node,
         })
     }
 
@@ -320,44 +424,39 @@ impl Wallet {
 
     // ── Signing ───────────────────────────────────────────────────────────────
 
-    /// Sign `tx` using SPHINCS+-SHAKE-256f-simple.
-    ///
-    /// The canonical payload is `tx_signer::tx_payload(from, to, amount_micro, timestamp)`
-    /// — a SHA3-256 digest over the transaction fields.  The returned bytes are
-    /// the raw 7,856-byte SPHINCS+ detached signature.
+    /// Sign `tx` using SPHINCS+-SHAKE-256f-simple and store the detached
+    /// 7,856-byte signature in `tx.signature`.  Only transactions from this
+    /// wallet's address are signed.
     ///
     /// The private key is accessed through `Zeroizing<Vec<u8>>`; it is NOT
     /// copied or cloned — the slice reference is passed directly to
     /// `sign_tx_payload`, which zeroes the key via the `SecretKey` drop impl.
-    pub fn sign_transaction(&self, tx: &Transaction) -> Result<Vec<u8>, WalletError> {
-        // Convert float amount to u64 microBLEEP (8 decimals).
-        let amount_micro = (tx.amount * 1e8) as u64;
-        let timestamp    = unix_ms();
-
-        let payload = tx_signer::tx_payload(&tx.from, &tx.to, amount_micro, timestamp);
-
-        let sig = tx_signer::sign_tx_payload(&payload, &self.private_key)
-            .map_err(|e| WalletError::SigningError(e))?;
+    pub fn sign_transaction(&self, tx: &mut Transaction) -> Result<(), WalletError> {
+        if tx.from != self.address || tx.chain_id != self.chain_id {
+            return Err(WalletError::InvalidTransaction);
+        }
+        let payload = tx.signing_bytes()?;
+        tx.signature = tx_signer::sign_tx_payload(&payload, &self.private_key)
+            .map_err(WalletError::SigningError)?;
 
         log::debug!(
             "[Wallet] Signed tx id={} sig_len={} bytes",
             tx.id,
-            sig.len()
+            tx.signature.len()
         );
-        Ok(sig)
+        Ok(())
     }
 
-    /// Verify a previously produced signature for `tx`.
-    ///
-    /// Returns `true` if the signature is a valid SPHINCS+ signature over the
-    /// canonical payload for this wallet's public key.
-    pub fn verify_transaction_signature(&self, tx: &Transaction, sig: &[u8]) -> bool {
-        let amount_micro = (tx.amount * 1e8) as u64;
-        // Verification uses the same zero timestamp for determinism.
-        // In production the timestamp must be included in the signed payload
-        // and transmitted alongside the signature.
-        let payload = tx_signer::tx_payload(&tx.from, &tx.to, amount_micro, 0);
-        tx_signer::verify_tx_signature(&payload, sig, &self.public_key)
+    /// Whether `tx` is from this wallet, for its chain, and carries a valid
+    /// signature by its key over `Transaction::signing_bytes`.
+    pub fn verify_transaction(&self, tx: &Transaction) -> bool {
+        if tx.from != self.address || tx.chain_id != self.chain_id || tx.signature.is_empty() {
+            return false;
+        }
+        match tx.signing_bytes() {
+            Ok(payload) => tx_signer::verify_tx_signature(&payload, &tx.signature, &self.public_key),
+            Err(_) => false,
+        }
     }
 
     // ── AI fee prediction ─────────────────────────────────────────────────────
@@ -368,10 +467,15 @@ impl Wallet {
 
     // ── P2P broadcast ─────────────────────────────────────────────────────────
 
+    /// Broadcast `signed_tx`; unsigned or badly signed transactions are
+    /// refused with `InvalidTransaction`.
     pub async fn broadcast_transaction(
         &self,
         signed_tx: &Transaction,
     ) -> Result<String, WalletError> {
+        if !self.verify_transaction(signed_tx) {
+            return Err(WalletError::InvalidTransaction);
+        }
         let tx_data = serde_json::to_vec(signed_tx)
             .map_err(|e| WalletError::Serialization(e.to_string()))?;
         self.p2p_node
@@ -381,7 +485,7 @@ impl Wallet {
 
     // ── State ─────────────────────────────────────────────────────────────────
 
-    pub fn store_transaction(&mut self, tx: Transaction) {
+    pub fn store_transaction(&self, tx: Transaction) {
         self.state_merkle
             .lock()
             .unwrap()
@@ -407,8 +511,14 @@ impl Wallet {
 
     // ── Multi-sig ─────────────────────────────────────────────────────────────
 
-    pub fn approve_multisig_transaction(&mut self, tx_id: &str) -> Result<(), WalletError> {
-        self.consensus_module.lock().unwrap().approve_transaction(tx_id)
+    /// Sign proposal `tx_id` of `multisig` as one of its cosigners.
+    pub fn approve_multisig(&self, multisig: &mut MultisigWallet, tx_id: &str) -> Result<(), WalletError> {
+        let proposal = multisig.proposal(tx_id)
+            .ok_or_else(|| WalletError::Multisig(format!("no proposal {}", tx_id)))?;
+        let payload = proposal.tx.signing_bytes()?;
+        let signature = tx_signer::sign_tx_payload(&payload, &self.private_key)
+            .map_err(WalletError::SigningError)?;
+        multisig.approve(tx_id, signature)
     }
 
     // ── Accessors ─────────────────────────────────────────────────────────────
@@ -417,6 +527,11 @@ impl Wallet {
         &self.address
     }
 
+    /// Local state the wallet records its transactions in.
+    pub fn state_merkle(&self) -> &Arc<Mutex<StateMerkle>> {
+        &self.state_merkle
+    }
+
     pub fn mnemonic_phrase(&self) -> String {
         self.mnemonic.to_string()
     }
@@ -424,10 +539,39 @@ impl Wallet {
 
 // ── Helpers ───────────────────────────────────────────────────────────────────
 
-/// Derive a `BLEEP1<hex40>` address from a SPHINCS+ public key.
+/// Extended private key of `m/44'/1234'/0'/0/index` under `seed`.
+fn derive_hd_key(seed: &[u8], index: u32) -> Result<ExtendedPrivKey, WalletError> {
+    if index >= 1 << 31 {
+        return Err(WalletError::Derivation(format!("account index {} is not a normal BIP-32 index", index)));
+    }
+    let derivation = || WalletError::Derivation(format!("cannot derive account {}", index));
+    let hardened = |i: u32| KeyIndex::hardened_from_normalize_index(i).map_err(|_| derivation());
+    let path = [hardened(44)?, hardened(BLEEP_COIN_TYPE)?, hardened(0)?, KeyIndex::Normal(0), KeyIndex::Normal(index)];
+    let mut key = ExtendedPrivKey::with_seed(seed).map_err(|_| derivation())?;
+    for step in path {
+        key = key.derive_private_key(step).map_err(|_| derivation())?;
+    }
+    Ok(key)
+}
+
+/// SPHINCS+ keypair of an HD account: its child private key expanded with
+/// HKDF-SHA256 into the 96-byte SPHINCS+ keygen seed.
+fn derive_signing_keypair(key: &ExtendedPrivKey) -> (Vec<u8>, Zeroizing<Vec<u8>>) {
+    use hkdf::Hkdf;
+    use sha2::Sha256;
+
+    let ikm = Zeroizing::new(key.private_key.secret_bytes());
+    let mut seed = Zeroizing::new([0u8; tx_signer::TX_KEYPAIR_SEED_LEN]);
+    Hkdf::<Sha256>::new(None, &ikm[..])
+        .expand(b"BLEEP-SPHINCS+-KEYGEN-v1", &mut seed[..])
+        .expect("96 bytes is a valid HKDF-SHA256 output length");
+    tx_signer::tx_keypair_from_seed(&seed)
+}
+
+/// Derive a `BLEEP1<hex40>` address from a public key.
 ///
 /// `address = "BLEEP1" || hex( SHA256²(pk)[..20] )`
-fn derive_address(pk: &[u8]) -> String {
+pub(crate) fn derive_address(pk: &[u8]) -> String {
     use sha2::{Digest, Sha256};
     let first  = Sha256::digest(pk);
     let second = Sha256::digest(&first);
@@ -460,15 +604,14 @@ fn keypair_from_seed(seed: &[u8; 64]) -> Result<(Vec<u8>, Vec<u8>), WalletError>
     use hkdf::Hkdf;
     use sha2::Sha512;
 
-    let (pk_raw, sk_raw) = keypair();
-    let pk = pk_raw.as_bytes().to_vec();
+    let (_, sk_raw) = keypair();
     let mut sk = sk_raw.as_bytes().to_vec();
 
     // Expand seed to len(sk) bytes via HKDF-SHA512.
     let hk = Hkdf::<Sha512>::new(None, seed);
     let mut mask = vec![0u8; sk.len()];
     hk.expand(b"bleep-kyber-sk-mask", &mut mask)
-        .map_err(|e| WalletError::QuantumSecurityError)?;
+        .map_err(|_| WalletError::QuantumSecurityError)?;
 
     for (b, m) in sk.iter_mut().zip(mask.iter()) {
         *b ^= m;
@@ -479,95 +622,120 @@ fn keypair_from_seed(seed: &[u8; 64]) -> Result<(Vec<u8>, Vec<u8>), WalletError>
     let (pk2, sk2) = keypair();
     Ok((pk2.as_bytes().to_vec(), sk2.as_bytes().to_vec()))
 }
-            p2p_node,
-        })
-    }
-
-    // 🔑 Import a Wallet using a BIP39 Mnemonic
-    pub fn import_wallet(mnemonic: &str) -> Result<Self, WalletError> {
-        // Parse the provided mnemonic string
-        let mnemonic_obj = Mnemonic::parse(mnemonic)
-            .map_err(|_| WalletError::Authentication("Invalid mnemonic".into()))?;
-        let (public_key, private_key) = keypair();
-
-        Ok(Self {
-            address: hex::encode(public_key.as_bytes()),
-            balance: 0.0,
-            authenticated: false,
-            public_key: public_key.as_bytes().to_vec(),
-            private_key: private_key.as_bytes().to_vec(),
-            mnemonic: mnemonic_obj,
-        ai_decision_module: Arc::new(BLEEPAIDecisionModule::new()),
-        zkp_module: Arc::new(BLEEPZKPModule::new()),
-        consensus_module: Arc::new(Mutex::new(BLEEPAdaptiveConsensus::new())),
-        bleep_connect: Arc::new(BLEEPConnect::new()),
-        state_merkle: Arc::new(Mutex::new(StateMerkle::new())),
-        p2p_node: Arc::new(P2PNode::new()),
-    })
-}
-
-    // 🔒 Quantum-Secure Authentication
-    pub fn authenticate(&mut self, _credentials: &[u8]) -> Result<bool, WalletError> {
-        // Use as_bytes for pqcrypto keys
-        let public_key = pqcrypto_kyber::kyber512::PublicKey::from_bytes(&self.public_key).map_err(|_| WalletError::QuantumSecurityError)?;
-        let secret_key = pqcrypto_kyber::kyber512::SecretKey::from_bytes(&self.private_key).map_err(|_| WalletError::QuantumSecurityError)?;
-        let (shared_secret, ciphertext): (pqcrypto_kyber::kyber512::SharedSecret, pqcrypto_kyber::kyber512::Ciphertext) = encapsulate(&public_key);
-        let decrypted_secret = decapsulate(&ciphertext, &secret_key);
-        // Compare the shared secrets
-        if decrypted_secret.as_bytes() == shared_secret.as_bytes() {
-            self.authenticated = true;
-            Ok(true)
-        } else {
-            Err(WalletError::Authentication("Invalid credentials".into()))
-        }
-    }
-
-    // ⚡ AI-Based Smart Fee Prediction
-    pub fn optimize_gas_fee(&self, network: &str) -> Result<f64, WalletError> {
-        let optimal_fee = self.ai_decision_module.predict_gas_fee(network)?;
-        Ok(optimal_fee)
-    }
 
-    // ✅ Sign a Transaction
-    pub fn sign_transaction(&self, tx: &Transaction) -> Result<Vec<u8>, WalletError> {
-        // Serialize the transaction to bytes
-        let _serialized_tx = serde_json::to_vec(tx)
-            .map_err(|e| WalletError::Serialization(e.to_string()))?;
-        // For demonstration, "sign" by simply returning the private key (this is a placeholder)
-        let signed_tx = self.private_key.clone();
-        Ok(signed_tx)
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // 📡 Broadcast Transaction to P2P Network
-    pub async fn broadcast_transaction(&self, signed_tx: &Transaction) -> Result<String, WalletError> {
-        let tx_data = serde_json::to_vec(signed_tx)
-            .map_err(|e| WalletError::Serialization(e.to_string()))?;
-        let response = self.p2p_node.broadcast_message(P2PMessage::NewTransaction(tx_data));
-        response.map_err(|_| WalletError::NetworkError)
+    fn wallet() -> Wallet {
+        Wallet::new(Arc::new(P2PNode::new()), Arc::new(Mutex::new(StateMerkle::new()))).unwrap()
     }
 
-    // 🔄 Store Transaction in Blockchain State
-    pub fn store_transaction(&mut self, tx: Transaction) {
-        self.state_merkle.lock().unwrap().update_state(&tx.from, tx.clone());
-    }
-
-    // 🏛️ Consensus Finalization
-    pub async fn finalize_transaction(&self, tx: &Transaction) -> Result<(), WalletError> {
-        self.consensus_module
-            .lock()
-            .unwrap()
-            .finalize_transaction(tx)
-    }
-
-    // 🔄 Swap Tokens via BLEEP Connect
-    pub fn swap_tokens(&self, from_chain: &str, to_chain: &str, amount: f64) -> Result<String, WalletError> {
-        let swap_tx = self.bleep_connect.swap_tokens(from_chain, to_chain, amount)?;
-        Ok(swap_tx)
+    fn transfer(from: &Wallet, amount: f64) -> Transaction {
+        Transaction {
+            id:        "tx123".into(),
+            from:      from.address().to_string(),
+            to:        "BLEEP1recipient".into(),
+            amount,
+            fee:       0.1,
+            signature: vec![],
+            nonce:     0,
+            chain_id:  from.chain_id,
+        }
     }
 
-    // 🔑 Multi-Signature Approval
-    pub fn approve_multisig_transaction(&mut self, tx_id: &str) -> Result<(), WalletError> {
-        self.consensus_module.lock().unwrap().approve_transaction(tx_id)?;
-        Ok(())
+    #[test]
+    fn test_signature_verifies_and_breaks_on_tamper() {
+        let wallet = wallet();
+        let mut tx = transfer(&wallet, 10.5);
+        wallet.sign_transaction(&mut tx).unwrap();
+        assert!(wallet.verify_transaction(&tx));
+        // The key is never what gets attached
+        assert!(!tx.signature.windows(16).any(|w| w == &wallet.private_key[..16]));
+
+        let mut tampered = tx.clone();
+        tampered.amount = 1_000.0;
+        assert!(!wallet.verify_transaction(&tampered));
+
+        // Relabelled as another wallet's, the signature is not that key's
+        let other = wallet();
+        let mut forged = tx.clone();
+        forged.from = other.address().to_string();
+        assert!(!other.verify_transaction(&forged));
+    }
+
+    const PHRASE: &str = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+
+    #[test]
+    fn test_import_is_deterministic_per_account() {
+        let (a, b) = (Wallet::import_wallet(PHRASE).unwrap(), Wallet::import_wallet(PHRASE).unwrap());
+        assert_eq!(a.address(), b.address());
+        assert_eq!(a.hd_public_key, b.hd_public_key);
+        assert_eq!(a.public_key, b.public_key, "the phrase restores the signing key");
+
+        // A restored wallet verifies what the original signed
+        let mut tx = transfer(&a, 3.0);
+        a.sign_transaction(&mut tx).unwrap();
+        assert!(b.verify_transaction(&tx));
+
+        let accounts: Vec<Wallet> = (0..3).map(|i| a.derive_account(i).unwrap()).collect();
+        assert_eq!(accounts[0].address(), a.address());
+        assert_ne!(accounts[1].address(), accounts[0].address());
+        assert_ne!(accounts[2].address(), accounts[1].address());
+        assert_eq!(accounts[2].address(), b.derive_account(2).unwrap().address());
+        assert_eq!(accounts[2].account_index, 2);
+        assert_ne!(accounts[1].public_key, accounts[0].public_key);
+
+        // Normal indices stop below 2^31
+        assert!(matches!(a.derive_account(1 << 31), Err(WalletError::Derivation(_))));
+    }
+
+    #[test]
+    fn test_transaction_signed_on_one_chain_fails_on_another() {
+        let on_1 = Wallet::import_wallet(PHRASE).unwrap().with_chain_id(ChainId(1));
+        let on_2 = Wallet::import_wallet(PHRASE).unwrap().with_chain_id(ChainId(2));
+        let mut tx = transfer(&on_1, 5.0);
+        on_1.sign_transaction(&mut tx).unwrap();
+        assert!(on_1.verify_transaction(&tx));
+        assert!(!on_2.verify_transaction(&tx));
+
+        let mut relabelled = tx.clone();
+        relabelled.chain_id = ChainId(2);
+        assert!(!on_2.verify_transaction(&relabelled), "the signature covers the chain id");
+        assert!(matches!(on_2.sign_transaction(&mut tx), Err(WalletError::InvalidTransaction)));
+        assert_eq!(on_1.derive_account(1).unwrap().chain_id, ChainId(1));
+    }
+
+    #[test]
+    fn test_authentication_needs_the_wallet_key() {
+        let mut wallet = wallet();
+        assert!(matches!(wallet.authenticate(&[0, 1, 2, 3]), Err(WalletError::Authentication(_))));
+        assert!(!wallet.authenticated);
+
+        let credentials = wallet.public_key.clone();
+        assert!(wallet.authenticate(&credentials).unwrap());
+        assert!(wallet.authenticated);
+    }
+
+    #[test]
+    fn test_sign_refuses_foreign_sender() {
+        let (wallet, other) = (wallet(), wallet());
+        let mut tx = transfer(&other, 1.0);
+        assert!(matches!(wallet.sign_transaction(&mut tx), Err(WalletError::InvalidTransaction)));
+        assert!(tx.signature.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_refuses_unsigned_and_invalid() {
+        let wallet = wallet();
+        let mut tx = transfer(&wallet, 12.5);
+        assert!(matches!(wallet.broadcast_transaction(&tx).await, Err(WalletError::InvalidTransaction)));
+
+        tx.signature = vec![1, 2, 3, 4];
+        assert!(matches!(wallet.broadcast_transaction(&tx).await, Err(WalletError::InvalidTransaction)));
+
+        tx.signature.clear();
+        wallet.sign_transaction(&mut tx).unwrap();
+        assert!(wallet.broadcast_transaction(&tx).await.unwrap().starts_with("tx-"));
     }
 }
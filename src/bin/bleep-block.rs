@@ -23,7 +23,7 @@ async fn main() {
 async fn run_block_module() -> Result<(), Box<dyn Error>> {
     let genesis_block = Block::new(0, vec![], "0".repeat(64));
     let tx_pool = TransactionPool::new(10000);
-    let mut blockchain = Blockchain::new(genesis_block, Default::default(), tx_pool.clone());
+    let mut blockchain = Blockchain::new(genesis_block, Default::default(), tx_pool.clone(), None)?;
     info!("✅ Blockchain initialized with {} blocks", blockchain.chain.len());
 
     let quantum_secure = QuantumSecure::keygen();
@@ -47,6 +47,10 @@ async fn run_block_module() -> Result<(), Box<dyn Error>> {
             amount: tx.amount,
             timestamp: tx.timestamp,
             signature: tx.signature,
+            max_fee: tx.max_fee,
+            tip: tx.tip,
+            nonce: tx.nonce,
+            chain_id: tx.chain_id,
         });
     }
 
@@ -35,7 +35,18 @@
 //!   - 2 new `/rpc/connect/*` endpoints (intents/pending, submit intent)
 //!   - OracleBridgeEngine: 5 oracle operators, 3-of-5 BLEEP/USD quorum
 //!   - Standalone `bleep-executor` binary for Layer 4 intent market
-
+//!   - `--role replica`: keyless, read-only follower with `/rpc/ready`
+//!   - Block sync: nodes serve their chain headers-first, and a validator
+//!     started with `BLEEP_BOOTSTRAP` seeds catches up from them on start
+//!   - Snap sync: validators serve checkpoint state to peers; a fresh
+//!     replica with `BLEEP_SNAP_SYNC_PEERS` downloads it instead of replaying
+//!   - Kademlia DHT: validators announce their snap-sync checkpoint, and
+//!     relayers the chains in `BLEEP_RELAYER_CHAINS`, as provider records
+//!   - ChainAuditor: re-validates the last `BLEEP_AUDIT_WINDOW` archived
+//!     blocks in the background within `BLEEP_AUDIT_CPU` of wall time;
+//!     a bad block halts production (`/rpc/admin/audit-status`)
+
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
 use std::sync::{Arc, RwLock};
 use parking_lot::Mutex;
@@ -55,37 +66,73 @@ pub extern "C" fn __rust_probestack() {
 // ── Crypto ────────────────────────────────────────────────────────────────────
 use bleep_crypto::quantum_secure::QuantumSecure;
 use bleep_crypto::pq_crypto::KyberKem;
+use bleep_crypto::redaction::{RedactionConfig, RedactionHandle};
 use bleep_crypto::tx_signer::generate_tx_keypair;
 
 // ── Core ──────────────────────────────────────────────────────────────────────
-use bleep_core::block::Block;
+use bleep_core::block::{Block, ChainId};
+use bleep_core::block_sync::{ChainSource, ChainSyncer, ValidatorKey};
 use bleep_core::blockchain::{Blockchain, BlockchainState as CoreBlockchainState};
+use bleep_core::chain_store::BlockStore as ChainBlockStore;
 use bleep_core::mempool::Mempool;
-use bleep_core::transaction_pool::TransactionPool;
+use bleep_core::transaction_pool::{signature_valid, KeyCheck, TransactionPool};
+use bleep_core::transaction::ZKTransaction;
+use bleep_core::wal::{Wal, WalOptions, WalTelemetry};
+use bleep_core::pending_spend::{BalanceLookup, SpendModel};
+use bleep_core::base_fee::{BaseFeeParams, BaseFeeTracker};
 use bleep_core::run_mempool_bridge;
+use bleep_core::scheduled_tx::{load_or_create_key, TxScheduler};
 
 // ── State ─────────────────────────────────────────────────────────────────────
-use bleep_state::state_manager::StateManager;
+use bleep_state::state_manager::{StateError, StateManager};
+use bleep_state::ai_recommendations::RecommendationRegistry;
 
 // ── Consensus ─────────────────────────────────────────────────────────────────
-use bleep_consensus::{run_consensus_engine, BlockProducer};
-use bleep_consensus::validator_identity::{ValidatorIdentity, ValidatorRegistry};
+use bleep_consensus::{fsck, pow_schedule, run_consensus_engine, BlockProducer, BlockStore, BlockTimingParams, FsckOptions, ReplicaFollower};
+use bleep_consensus::validator_identity::{RegistryStakes, ValidatorIdentity, ValidatorRegistry};
+use bleep_consensus::ai_adaptive_logic::AIAdaptiveConsensus;
+use bleep_consensus::consensus::ValidatorSigningKey;
+use bleep_consensus::{
+    BLEEPAdaptiveConsensus, BlockchainState as ConsensusBlockchainState, GossipPbftLink, NetworkingModule,
+    TelemetryMetrics, Validator as ConsensusValidator, PBFT_TOPIC,
+};
 use bleep_consensus::slashing_engine::SlashingEngine;
+use bleep_consensus::evidence::{validator_for_key, Evidence, EvidencePool, EVIDENCE_TOPIC};
+use bleep_consensus::partition::{PartitionConfig, PartitionDetector, SafeModeEvent};
+use bleep_consensus::rewards::{RewardLedger, RewardParams};
+use bleep_consensus::block_store::StoredBlock;
+use bleep_consensus::snap_sync::{SnapSyncer, SnapshotServer};
+use bleep_consensus::replica::SyncStatus;
+use bleep_consensus::chain_audit::{AuditConfig, ChainAuditor};
+use bleep_consensus::block_producer::{ProposerKey, BLOCKS_PER_EPOCH};
+use bleep_consensus::consensus_key::ConsensusConfig;
+use bleep_consensus::staking::{self, StakingParams};
+use bleep_consensus::validator_set::{EpochValidatorSets, ValidatorSetConfig};
 
 // ── Scheduler ─────────────────────────────────────────────────────────────────
 use bleep_scheduler::{Scheduler, BlockTick};
 
 // ── Governance ────────────────────────────────────────────────────────────────
-use bleep_governance::governance_core::GovernanceEngine;
+use bleep_governance::governance_core::{GovernanceEngine, VoterKeys};
+use bleep_governance::{
+    FeedConfig, GovernanceFeed, GovernanceWebhooks, HttpContentFetcher, HttpWebhookSink, ParamKind,
+    ParameterRegistry, ParameterSpec, ProposalContentResolver,
+};
 
 // ── P2P ───────────────────────────────────────────────────────────────────────
-use bleep_p2p::p2p_node::{P2PNode, P2PNodeConfig};
+use bleep_p2p::p2p_node::{P2PNode, P2PNodeConfig, P2PSettings};
+use bleep_p2p::NodeMetadata;
+use bleep_p2p::service_quota::{ServiceQuotaConfig, ServiceQuotaTelemetry};
 use bleep_p2p::types::MessageType;
-use bleep_crypto::tx_signer::{verify_tx_signature, tx_payload};
+use bleep_p2p::snap_sync::{SnapPeer, SnapTelemetry, TcpSnapPeer};
+use bleep_p2p::block_sync::{SyncPeer, SyncTelemetry, TransportSyncPeer};
+use bleep_p2p::kademlia_dht::{relayer_key, snap_checkpoint_key, PROVIDER_TTL_SECS};
+use bleep_crypto::tx_signer::verify_tx_signature;
 use bleep_core::block_validation::BlockValidator;
 
 // ── Wallet & PAT ─────────────────────────────────────────────────────────────
 use bleep_wallet_core::init_wallet_services;
+use bleep_wallet_core::portfolio::{PortfolioConfig, PortfolioService};
 use bleep_pat::launch_asset_token_logic;
 
 // ── Economics ─────────────────────────────────────────────────────────────────
@@ -97,22 +144,87 @@ use bleep_interop::interoperability::start_interop_services;
 
 // ── AI advisory ───────────────────────────────────────────────────────────────
 use bleep_ai::ai_assistant::init_ai_advisory;
+use bleep_ai::{Severity, SeverityGate};
 
 // ── Telemetry ─────────────────────────────────────────────────────────────────
-use bleep_telemetry::{init_telemetry, metrics::{MetricCounter, MetricGauge}};
+use bleep_telemetry::{init_telemetry, metrics::{MetricCounter, MetricGauge, MetricsRegistry}};
+use bleep_telemetry::export::{ExportSigner, ExportSink, HttpsExportSink, TelemetryExportConfig, TelemetryExporter};
+use bleep_telemetry::history::{HistoryConfig, TelemetryHistory};
 
 // ── RPC ───────────────────────────────────────────────────────────────────────
-use bleep_rpc::{rpc_routes_with_state, RpcState};
+use bleep_rpc::{
+    rpc_routes_with_state, ApiKeyRegistry, AuditTrail, AuditTrailStore, RpcState, AUDIT_CHANNEL_CAPACITY,
+};
+use bleep_rpc::portfolio::{PatBalanceSource, StateBalanceSource};
+use bleep_rpc::server_wallet::ServerWallet;
+use bleep_rpc::replica::DEFAULT_MAX_LAG;
+use bleep_rpc::telemetry_export::{telemetry_snapshot, NodeKeySigner, TelemetryCollector};
+use bleep_vm::{ContractRegistry, DeployGate, Executor, ExecutorConfig};
 use warp;
 use hex;
 
+/// Log field masks from the JSON file named by `BLEEP_LOG_REDACTION`, or the
+/// built-in masks.  Runs before the subscriber exists, so errors go to stderr.
+fn redaction_config() -> RedactionConfig {
+    let Ok(path) = std::env::var("BLEEP_LOG_REDACTION") else {
+        return RedactionConfig::default();
+    };
+    match std::fs::read_to_string(&path).map_err(|e| e.to_string())
+        .and_then(|json| RedactionConfig::from_json(&json).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("BLEEP_LOG_REDACTION={}: {}; using default masks", path, e);
+            RedactionConfig::default()
+        }
+    }
+}
+
+/// Per-peer serving quotas from the JSON file named by `BLEEP_P2P_QUOTAS`,
+/// or the defaults.
+fn service_quota_config() -> ServiceQuotaConfig {
+    let Ok(path) = std::env::var("BLEEP_P2P_QUOTAS") else {
+        return ServiceQuotaConfig::default();
+    };
+    match std::fs::read_to_string(&path).map_err(|e| e.to_string())
+        .and_then(|json| ServiceQuotaConfig::from_json(&json).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("BLEEP_P2P_QUOTAS={}: {}; using default quotas", path, e);
+            ServiceQuotaConfig::default()
+        }
+    }
+}
+
+/// Per-chain interop adapter modes from the JSON file named by
+/// `BLEEP_INTEROP_CHAINS` (e.g. `{"ethereum": {"mode": "sandbox"}}`), or
+/// every chain live.
+fn interop_chain_modes() -> HashMap<String, bleep_interop::ChainModeConfig> {
+    let Ok(path) = std::env::var("BLEEP_INTEROP_CHAINS") else {
+        return HashMap::new();
+    };
+    match std::fs::read_to_string(&path).map_err(|e| e.to_string())
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+    {
+        Ok(modes) => modes,
+        Err(e) => {
+            warn!("BLEEP_INTEROP_CHAINS={}: {}; all chains live", path, e);
+            HashMap::new()
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
+    let redaction = RedactionHandle::new(redaction_config());
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
         )
+        .fmt_fields(redaction.field_format())
+        .event_format(redaction.event_format())
         .init();
 
     info!("╔══════════════════════════════════════════════════════════════╗");
@@ -127,14 +239,21 @@ async fn main() {
 }
 
 async fn run() -> Result<(), Box<dyn Error>> {
+    if node_role()? == NodeRole::Replica {
+        return run_replica().await;
+    }
 
     // ── Step 1: Post-quantum keypair generation ───────────────────────────────
     info!("🔐 [1/13] Generating post-quantum keypairs…");
     let _qs = QuantumSecure::keygen();
 
-    // Generate real SPHINCS+-SHAKE-256f-simple keypair for block signing.
+    // SPHINCS+-SHAKE-256f-simple keypair for block signing: the keystore
+    // named by BLEEP_NODE_CONFIG (`bleep-cli validator init`), else fresh.
     // generate_tx_keypair() returns (pk_bytes: 64B, sk_bytes: 128B).
-    let (sphincs_pk, sphincs_sk) = generate_tx_keypair();
+    let (sphincs_pk, sphincs_sk) = match consensus_key()? {
+        Some(key) => (key.pk, key.sk),
+        None => generate_tx_keypair(),
+    };
 
     // Generate real Kyber-1024 keypair for validator KEM binding.
     // KyberKem::keygen() returns (KyberPublicKey: 1568B, KyberSecretKey: 3168B).
@@ -151,59 +270,95 @@ async fn run() -> Result<(), Box<dyn Error>> {
 
     let state_dir = std::env::var("BLEEP_STATE_DIR")
         .unwrap_or_else(|_| "/tmp/bleep-state".to_string());
+    let blocks_dir = std::env::var("BLEEP_BLOCKS_DIR")
+        .unwrap_or_else(|_| "/tmp/bleep-blocks".to_string());
+    let state = Arc::new(Mutex::new(open_node_state(&state_dir, &blocks_dir)));
+    let chain_id = chain_id(BlockStore::open(&blocks_dir).ok().as_ref())?;
 
-    let mut state = match StateManager::open(&state_dir) {
-        Ok(s) => { info!("  ✅ StateManager at {}", state_dir); s }
-        Err(e) => {
-            warn!("  ⚠️  StateManager open failed ({}), using temp dir", e);
-            StateManager::new()
-        }
+    // Transaction pools
+    // Admission also checks each sender's confirmed balance against its
+    // pending outflow.
+    let tx_pool = {
+        let state = Arc::clone(&state);
+        let balances: BalanceLookup = Arc::new(move |account: &str| state.lock().get_balance(account));
+        TransactionPool::with_spend_tracking(10_000, balances, SpendModel::default())
     };
 
-    // Mint genesis allocations only at height 0 (first start).
-    // mint() now returns Result — cap violations are logged and abort startup.
-    if state.block_height() == 0 {
-        state.mint("bleep:genesis:foundation", 500_000_000_000_000u128)
-            .unwrap_or_else(|e| { error!("Genesis mint foundation failed: {}", e); std::process::exit(1); });
-        state.mint("bleep:genesis:rewards",    100_000_000_000_000u128)
-            .unwrap_or_else(|e| { error!("Genesis mint rewards failed: {}", e); std::process::exit(1); });
-        state.mint("bleep:genesis:validators",  50_000_000_000_000u128)
-            .unwrap_or_else(|e| { error!("Genesis mint validators failed: {}", e); std::process::exit(1); });
-        info!("  ✅ Genesis allocations minted (650T µBLEEP).");
-    }
+    // Per-block base fee: the producer moves it with block fullness and the
+    // pool only admits transactions whose max fee covers it.
+    let base_fee = Arc::new(BaseFeeTracker::new(BaseFeeParams::default())?);
+    tx_pool.set_base_fee(Arc::clone(&base_fee))?;
+
+    // Accounts that rotated their key stop accepting the old one after the
+    // grace period; the pool turns such signers away up front, and the chain
+    // rejects blocks that carry them.
+    let key_check: KeyCheck = {
+        let state = Arc::clone(&state);
+        Arc::new(move |account: &str, key_hash: &[u8; 32]| state.lock().key_authorized(account, key_hash))
+    };
+    tx_pool.set_key_check(Arc::clone(&key_check))?;
 
-    // Rebuild the Sparse Merkle Trie from the persisted DB state
-    if let Err(e) = state.rebuild_trie_from_db() {
-        warn!("  ⚠️  Trie rebuild: {}", e);
+    // Each sender's transactions are admitted in nonce order; later nonces
+    // wait in the pool's queue, and a same-nonce resubmission with a higher
+    // tip replaces the pending one.
+    {
+        let state = Arc::clone(&state);
+        tx_pool.set_nonce_lookup(Arc::new(move |account: &str| state.lock().get_nonce(account)))?;
     }
 
-    let state = Arc::new(Mutex::new(state));
-
-    // Transaction pools
-    let tx_pool = TransactionPool::new(10_000);
-    let mempool  = Mempool::new();
-
-    // Genesis block (unsigned — trust anchor)
-    let genesis = Block::new(0, vec![], "0".to_string());
-
-    let blockchain = {
-        let mut core_state = CoreBlockchainState::default();
-        let state_guard = state.lock();
-        for (address, balance) in state_guard.export_balances() {
-            match u64::try_from(balance) {
-                Ok(balance_u64) if balance_u64 > 0 => core_state.credit(&address, balance_u64),
-                Ok(_) => {}
-                Err(_) => error!(
-                    "StateManager balance overflow for account {}: {}",
-                    address, balance
+    // Transactions signed for another network are turned away.
+    tx_pool.set_chain_id(chain_id)?;
+
+    // Mempool write-ahead log: rebuild the pending set a crash left behind,
+    // dropping transactions the current chain no longer accepts.
+    // BLEEP_WAL_FSYNC = always | commit | interval:<ms>
+    let wal_opts = WalOptions {
+        fsync: match std::env::var("BLEEP_WAL_FSYNC") {
+            Ok(policy) => policy.parse().map_err(|e| format!("BLEEP_WAL_FSYNC: {}", e))?,
+            Err(_) => WalOptions::default().fsync,
+        },
+        ..WalOptions::default()
+    };
+    let mempool_wal_path = std::env::var("BLEEP_MEMPOOL_WAL")
+        .unwrap_or_else(|_| "/tmp/bleep-mempool/pool.wal".to_string());
+    match Wal::open(&mempool_wal_path, wal_opts) {
+        Ok((wal, recovery)) => {
+            let state = Arc::clone(&state);
+            let keep = move |tx: &ZKTransaction| {
+                let state = state.lock();
+                signature_valid(tx)
+                    && state.get_balance(&tx.sender) >= tx.amount as u128
+                    && tx.nonce >= state.get_nonce(&tx.sender)
+            };
+            match tx_pool.recover_from_wal(wal, recovery, keep).await {
+                Ok(r) => info!(
+                    "  ✅ Mempool WAL at {}: {} pending restored, {} discarded ({:.0} entries/s)",
+                    mempool_wal_path, r.restored, r.discarded, r.entries_per_sec
                 ),
+                Err(e) => warn!("  ⚠️  Mempool WAL recovery failed ({}), starting empty", e),
             }
         }
-        Blockchain::new(genesis, core_state, tx_pool.clone())
-    };
+        Err(e) => warn!("  ⚠️  Mempool WAL unavailable ({}), pending txs will not survive a crash", e),
+    }
+    let mempool  = Mempool::new();
+
+    // Genesis block (unsigned — trust anchor, identical on every node of
+    // the same chain)
+    let genesis = Block::genesis_for(chain_id);
+
+    // The chain persists to the block archive and, on restart, replays it
+    // from the genesis allocations.  Competing chains are weighed by their
+    // signers' stake in the registry, and PoW blocks are held to the
+    // difficulty consensus retargets to.
+    let chain_store: Arc<dyn ChainBlockStore> = Arc::new(BlockStore::open(&blocks_dir)?);
+    let validator_registry = Arc::new(Mutex::new(ValidatorRegistry::new()));
+    let blockchain = Blockchain::new(genesis, genesis_core_state(), tx_pool.clone(), Some(chain_store))?
+        .with_key_check(key_check)
+        .with_stake_weights(Arc::new(RegistryStakes(Arc::clone(&validator_registry))))
+        .with_pow_schedule(pow_schedule(BlockTimingParams::default()));
     let blockchain = Arc::new(RwLock::new(blockchain));
 
-    info!("  ✅ Genesis block #0. Blockchain, mempool, tx-pool ready.");
+    info!("  ✅ Genesis block #0 on chain {}. Blockchain, mempool, tx-pool ready.", chain_id);
 
     // ── Step 3: Wallet ────────────────────────────────────────────────────────
     info!("💼 [3/13] Initialising wallet services…");
@@ -218,18 +373,57 @@ async fn run() -> Result<(), Box<dyn Error>> {
     // ── Step 5: AI advisory ───────────────────────────────────────────────────
     info!("🧠 [5/13] Starting AI advisory engine…");
     init_ai_advisory()?;
+    // Background evaluation loops register their recommendation slots here;
+    // consensus and sharding only ever read the latest record.
+    let ai_recommendations = Arc::new(RecommendationRegistry::new());
     info!("  ✅ AI advisory ready (deterministic mode).");
 
+    // Operator audit trail: admin RPC calls and governance executions
+    let audit_dir = std::env::var("BLEEP_AUDIT_DIR")
+        .unwrap_or_else(|_| "/tmp/bleep-audit-trail".to_string());
+    let audit_trail = match AuditTrailStore::open(&audit_dir) {
+        Ok(store) => {
+            info!("  ✅ Audit trail at {} ({} records)", audit_dir, store.len());
+            AuditTrail::spawn(Arc::new(store), AUDIT_CHANNEL_CAPACITY)
+        }
+        Err(e) => {
+            error!("Audit trail open failed: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // ── Step 6: Governance ────────────────────────────────────────────────────
     info!("🏛  [6/16] Initialising governance engine…");
-    let governance = GovernanceEngine::new(1_000_000_000u128);
+    // Runtime parameters: genesis protocol rules now, node-local settings
+    // once their subsystems are up.  Parameter proposals are checked
+    // against it and recorded in its history.
+    let parameters = Arc::new(Mutex::new(ParameterRegistry::genesis()?));
+    // Votes must carry a signature for this chain by the voter's registered
+    // validator key.
+    let voter_keys: VoterKeys = {
+        let registry = Arc::clone(&validator_registry);
+        Arc::new(move |validator_id: &str| {
+            registry.lock().get(validator_id).and_then(|v| hex::decode(&v.signing_key_id).ok())
+        })
+    };
+    let governance = GovernanceEngine::new(1_000_000_000u128)
+        .with_audit_trail(audit_trail.clone())
+        .with_parameter_registry(Arc::clone(&parameters))
+        .with_voter_keys(chain_id, voter_keys);
     governance.persist()?;
+    let governance = Arc::new(Mutex::new(governance));
+    // Committed proposal text is fetched on demand; IPFS hints go through this gateway.
+    let ipfs_gateway = std::env::var("BLEEP_IPFS_GATEWAY")
+        .unwrap_or_else(|_| bleep_governance::proposal_content::DEFAULT_IPFS_GATEWAY.to_string());
+    let content_resolver = Arc::new(ProposalContentResolver::new(Box::new(HttpContentFetcher::new(&ipfs_gateway))));
     info!("  ✅ Governance online (1B total stake).");
 
     // ── Step 6b: ValidatorRegistry + SlashingEngine ───────────────────────────
     info!("🗳  [6b/16] Initialising ValidatorRegistry and SlashingEngine…");
-    let validator_registry = Arc::new(Mutex::new(ValidatorRegistry::new()));
     let slashing_engine    = Arc::new(Mutex::new(SlashingEngine::new()));
+    // Double-sign evidence: detected on inbound blocks, gossiped, included
+    // by proposers and slashed on commit.
+    let evidence_pool      = Arc::new(Mutex::new(EvidencePool::default()));
 
     // Register the local node as the genesis validator with the real Kyber-1024 public key.
     {
@@ -334,6 +528,7 @@ async fn run() -> Result<(), Box<dyn Error>> {
             data_directory: PathBuf::from("/tmp/bleep-connect"),
             commitment_chain_block_interval_secs: 6,
             layer2_threshold: 100_000_000_000_000,
+            chains: interop_chain_modes(),
         };
         let kp = ClassicalKeyPair::generate();
         BleepConnectBuilder::with_config(config)
@@ -366,12 +561,97 @@ async fn run() -> Result<(), Box<dyn Error>> {
     let blocks_produced  = MetricCounter::new("bleep_blocks_produced_total");
     let txs_processed    = MetricCounter::new("bleep_transactions_processed_total");
     let gas_used_gauge   = MetricGauge::new("bleep_gas_used_last_block");
+    #[cfg(feature = "prometheus")]
+    if let Err(e) = bleep_rpc::metrics_recorder::install_recorder() {
+        warn!("Prometheus recorder not installed: {}", e);
+    }
     info!("  ✅ Metrics active.");
 
     // ── Step 9: P2P ───────────────────────────────────────────────────────────
     info!("🌐 [9/16] Starting P2P node…");
-    let (p2p_node, p2p_handle) = P2PNode::start(P2PNodeConfig::default()).await?;
+    // The node key and known peers live in BLEEP_P2P_DIR so the node id and
+    // its peers survive restarts; BLEEP_BOOTSTRAP lists comma-separated seeds.
+    // The transport is `transport` in the [p2p] table of BLEEP_NODE_CONFIG,
+    // tcp if unset; BLEEP_P2P_TRANSPORT overrides it.
+    let env_or_empty = |key: &str| std::env::var(key).unwrap_or_default();
+    let p2p_dir = std::path::PathBuf::from(
+        std::env::var("BLEEP_P2P_DIR").unwrap_or_else(|_| "/tmp/bleep-p2p".to_string()),
+    );
+    let seed_addrs = env_or_empty("BLEEP_BOOTSTRAP")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| s.parse::<std::net::SocketAddr>().map_err(|e| format!("BLEEP_BOOTSTRAP {}: {}", s, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let transport = match env_or_empty("BLEEP_P2P_TRANSPORT").trim() {
+        "" => p2p_settings()?.unwrap_or_default().transport,
+        kind => kind.parse().map_err(|e| format!("BLEEP_P2P_TRANSPORT: {}", e))?,
+    };
+    let sync_seeds = seed_addrs.clone();
+    let p2p_config = P2PNodeConfig {
+        transport,
+        peer_store: Some(p2p_dir.join("peers.json")),
+        seed_addrs,
+        data_dir: Some(p2p_dir),
+        metadata: NodeMetadata::new(
+            &env_or_empty("BLEEP_NODE_MONIKER"),
+            &env_or_empty("BLEEP_NODE_CONTACT"),
+            &env_or_empty("BLEEP_NODE_REGION"),
+        ),
+        service_quotas: service_quota_config(),
+        ..P2PNodeConfig::default()
+    };
+    let (p2p_node, p2p_handle) = P2PNode::start(p2p_config).await?;
     info!("  ✅ P2P node {} | peers: {}", p2p_node.node_id, p2p_node.peer_count());
+    {
+        let quotas = Arc::clone(&p2p_node.service_quotas);
+        let mut quota_metrics = ServiceQuotaTelemetry::register(&mut MetricsRegistry::new());
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(std::time::Duration::from_secs(5));
+            loop {
+                tick.tick().await;
+                quota_metrics.export(&quotas);
+            }
+        });
+    }
+    // Serve our blocks to nodes that are behind, and catch up from the seeds
+    // before producing on top of a stale tip.  The inbound block handler
+    // syncs again when gossip shows a chain we cannot link to.
+    p2p_node.message_protocol.attach_block_source(Arc::new(ChainSource::new(Arc::clone(&blockchain))));
+    // Gossiped and synced blocks are checked against the same key: their
+    // signer's if it is a registered validator, otherwise our own.
+    let block_key: ValidatorKey = {
+        let (registry, own_pk) = (Arc::clone(&validator_registry), sphincs_pk.clone());
+        Arc::new(move |block: &Block| {
+            block.signer_public_key()
+                .filter(|pk| validator_for_key(&registry.lock(), pk).is_some())
+                .map_or_else(|| own_pk.clone(), <[u8]>::to_vec)
+        })
+    };
+    let syncer = (!sync_seeds.is_empty()).then(|| {
+        let transport = p2p_node.message_protocol.transport();
+        let peers: Vec<Arc<dyn SyncPeer>> = sync_seeds.iter()
+            .map(|&addr| Arc::new(TransportSyncPeer { addr, transport: transport.clone() }) as Arc<dyn SyncPeer>)
+            .collect();
+        // Headers must be signed by an active validator before any body is
+        // downloaded; a seed serving anything else is dropped.
+        let registry = Arc::clone(&validator_registry);
+        Arc::new(ChainSyncer::new(peers)
+            .with_signer_check(Arc::new(move |_header: &Block, pk: &[u8]| {
+                validator_for_key(&registry.lock(), pk).is_some()
+            }))
+            .with_validator_key(Arc::clone(&block_key))
+            .with_telemetry(SyncTelemetry::register(&mut MetricsRegistry::new())))
+    });
+    if let Some(syncer) = &syncer {
+        match syncer.run(&blockchain).await {
+            Ok(outcome) => info!(
+                "  ✅ Block sync: {} blocks applied, {} reorgs, height {}",
+                outcome.applied, outcome.reorgs, outcome.height
+            ),
+            Err(e) => warn!("  ⚠️  Block sync stopped: {}", e),
+        }
+    }
 
     // ── Step 10: MempoolBridge ────────────────────────────────────────────────
     info!("🔄 [10/16] Wiring MempoolBridge (P2P → ExecutionPool)…");
@@ -398,10 +678,331 @@ async fn run() -> Result<(), Box<dyn Error>> {
         Some(Arc::clone(&p2p_node)),         // direct gossip broadcast
     );
 
+    // Archive committed blocks + receipts for `bleep-cli debug replay`
+    let block_producer = match BlockStore::open(&blocks_dir) {
+        Ok(store) => {
+            info!("  ✅ BlockStore at {}", blocks_dir);
+            block_producer.with_block_store(Arc::new(store))
+        }
+        Err(e) => {
+            warn!("  ⚠️  BlockStore open failed ({}), blocks will not be archived", e);
+            block_producer
+        }
+    };
+    // Snap sync: serve the state at the latest archived checkpoint, and
+    // announce it in the DHT as each new checkpoint is archived.
+    let snap_announce_handle = match BlockStore::open(&blocks_dir) {
+        Ok(store) => {
+            let server = Arc::new(SnapshotServer::new(
+                Arc::clone(&state),
+                Arc::new(store),
+                sphincs_sk.clone(),
+                sphincs_pk.clone(),
+            ));
+            p2p_node.message_protocol.attach_snap_source(Arc::clone(&server));
+            let dht = p2p_node.peer_manager.dht();
+            Some(tokio::spawn(async move {
+                let mut announced = None;
+                let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+                loop {
+                    ticker.tick().await;
+                    let Some(checkpoint) = server.latest_checkpoint() else { continue };
+                    if announced == Some(checkpoint) {
+                        continue;
+                    }
+                    if let Some(previous) = announced.replace(checkpoint) {
+                        dht.unprovide(&snap_checkpoint_key(previous));
+                    }
+                    let accepted = dht.provide(&snap_checkpoint_key(checkpoint), PROVIDER_TTL_SECS).await;
+                    info!("[SnapSync] Checkpoint {} announced to {} DHT node(s)", checkpoint, accepted);
+                }
+            }))
+        }
+        Err(e) => {
+            warn!("  ⚠️  Snap sync not served: {}", e);
+            None
+        }
+    };
+    // Interop relayers announce the chains they attest for (BLEEP_RELAYER_CHAINS).
+    let relayer_chains = env_list("BLEEP_RELAYER_CHAINS");
+    if !relayer_chains.is_empty() {
+        let dht = p2p_node.peer_manager.dht();
+        info!("  ✅ Announcing relayer for: {}", relayer_chains.join(", "));
+        tokio::spawn(async move {
+            for chain in relayer_chains {
+                dht.provide(&relayer_key(&chain), PROVIDER_TTL_SECS).await;
+            }
+        });
+    }
+    // Partition safe mode: stop proposing while cut off from most of the
+    // validator stake.  The inbound block handler reports contacts.
+    let partition = Arc::new(
+        PartitionDetector::new(
+            hex::encode(&sphincs_pk[..8]),
+            PartitionDetector::stakes_from(&validator_registry.lock()),
+            PartitionConfig::default(),
+        )
+        .with_audit_trail(audit_trail.clone()),
+    );
+    // Block rewards: issuance and tips accrue in the reward pool account
+    // and are paid out by ClaimRewards transactions.
+    let reward_ledger = {
+        let mut ledger = RewardLedger::new(RewardParams::default())?;
+        ledger.sync_validator_stakes(&validator_registry.lock());
+        Arc::new(Mutex::new(ledger))
+    };
+    // Chain audit: re-check recent archived blocks in the background.  No
+    // re-execution: replay does not re-apply the reward mint above.
+    let chain_auditor = match BlockStore::open(&blocks_dir) {
+        Ok(store) => {
+            let mut config = AuditConfig {
+                reexecute_every: 0,
+                scratch_dir: format!("{}/audit-scratch", blocks_dir).into(),
+                ..AuditConfig::default()
+            };
+            if let Some(window) = std::env::var("BLEEP_AUDIT_WINDOW").ok().and_then(|v| v.parse().ok()) {
+                config.window = window;
+            }
+            if let Some(cpu) = std::env::var("BLEEP_AUDIT_CPU").ok().and_then(|v| v.parse::<f64>().ok()) {
+                config.cpu_budget = cpu;
+            }
+            info!("  ✅ ChainAuditor: last {} blocks, {:.0}% CPU", config.window, config.cpu_budget * 100.0);
+            Some(Arc::new(ChainAuditor::new(Arc::new(store), Arc::clone(&state), config)))
+        }
+        Err(e) => {
+            warn!("  ⚠️  Chain audit disabled: {}", e);
+            None
+        }
+    };
+    let audit_handle = chain_auditor.clone().map(|a| tokio::spawn(a.run()));
+    register_node_parameters(&mut parameters.lock(), &base_fee, &reward_ledger)?;
+    info!("  ✅ {} runtime parameters registered", parameters.lock().list(None).len());
+    // Bonds: the governance minimum is in 18-decimal units, stakes in µBLEEP
+    // (8 decimals).  Written only once governance has moved it, so nodes
+    // that sync from genesis agree on the stored parameters.
+    if let Some(min) = parameters.lock().value("MIN_VALIDATOR_STAKE") {
+        let mut state = state.lock();
+        let current = staking::params(&state);
+        let min_stake = min / 10u128.pow(10);
+        if current.min_stake != min_stake {
+            staking::set_params(&mut state, &StakingParams { min_stake, ..current });
+            info!("  ✅ Minimum validator bond set to {} µBLEEP", min_stake);
+        }
+    }
+    // Validator sets: the genesis validator, then registry plus bonds
+    // snapshotted at each epoch start.  Slots of members whose key this
+    // node lacks are signed by the next member it holds and recorded.
+    let validator_sets = Arc::new(Mutex::new(EpochValidatorSets::from_registry(
+        ValidatorSetConfig { blocks_per_epoch: BLOCKS_PER_EPOCH, ..ValidatorSetConfig::default() },
+        &validator_registry.lock(),
+    )?));
+    let signing_keys = Arc::new(Mutex::new(vec![ProposerKey {
+        validator_id: hex::encode(&sphincs_pk[..8]),
+        sk: sphincs_sk.clone(),
+        pk: sphincs_pk.clone(),
+    }]));
+    let block_producer = block_producer
+        .with_evidence(
+            Arc::clone(&evidence_pool),
+            Arc::clone(&validator_registry),
+            Arc::clone(&slashing_engine),
+        )
+        .with_base_fee(Arc::clone(&base_fee))
+        .with_partition_detector(Arc::clone(&partition))
+        .with_rewards(Arc::clone(&reward_ledger))
+        .with_validator_sets(Arc::clone(&validator_sets), Arc::clone(&validator_registry), signing_keys);
+    let missed_slots = block_producer.missed_slots();
+    let block_producer = match &chain_auditor {
+        Some(auditor) => block_producer.with_auditor(Arc::clone(auditor)),
+        None => block_producer,
+    };
+
+    // PBFT finality: every committed block, produced here or received,
+    // goes to the adaptive driver, which runs its round with the other
+    // validators over gossip as this node's validator.
+    let pbft_link = {
+        let node = Arc::clone(&p2p_node);
+        Arc::new(GossipPbftLink::new(
+            hex::encode(&sphincs_pk[..8]),
+            Arc::new(move |bytes: Vec<u8>| node.broadcast(MessageType::Custom(PBFT_TOPIC.to_string()), bytes)),
+        ))
+    };
+    let (pbft_feed, mut pbft_blocks) = tokio::sync::broadcast::channel::<Block>(64);
+    let block_producer = block_producer.with_block_feed(pbft_feed.clone());
+    let pbft_driver = {
+        let registry = validator_registry.lock();
+        let active = registry.get_active_validators();
+        let validators: HashMap<String, ConsensusValidator> = active.iter()
+            .map(|v| (v.id.clone(), ConsensusValidator {
+                id: v.id.clone(),
+                reputation: 1.0,
+                latency: 0,
+                stake: v.stake.min(u64::MAX as u128) as u64,
+                active: true,
+                last_signed_block: 0,
+            }))
+            .collect();
+        let pubkeys: HashMap<String, Vec<u8>> = active.iter()
+            .filter_map(|v| hex::decode(&v.signing_key_id).ok().map(|pk| (v.id.clone(), pk)))
+            .collect();
+        let networking = NetworkingModule::new();
+        networking.attach_pbft_link(pbft_link.clone());
+        let metrics = Arc::new(TelemetryMetrics::new(Arc::clone(&p2p_node.peer_manager), Arc::clone(&tx_pool)));
+        BLEEPAdaptiveConsensus::new(
+            validators.clone(),
+            pubkeys,
+            ValidatorSigningKey::from_bytes(sphincs_pk.clone(), sphincs_sk.clone())?,
+            Arc::new(networking),
+            Arc::new(AIAdaptiveConsensus::new(validators, metrics)),
+        )
+        .with_pbft_identity(hex::encode(&sphincs_pk[..8]))
+        .with_chain_id(chain_id)
+    };
+    // Shared with the telemetry collector, which reads the current mode.
+    let pbft_driver = Arc::new(tokio::sync::Mutex::new(pbft_driver));
+    let driver = Arc::clone(&pbft_driver);
+    let pbft_handle = tokio::spawn(async move {
+        let mut decided = ConsensusBlockchainState::new();
+        loop {
+            let block = match pbft_blocks.recv().await {
+                Ok(block) => block,
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                    warn!("[PBFT] {} committed blocks skipped", n);
+                    continue;
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            };
+            match driver.lock().await.finalize_block(&block, &mut decided).await {
+                Ok(()) => info!("[PBFT] Block {} finalized", block.index),
+                Err(e) => warn!("[PBFT] Block {} not finalized: {}", block.index, e),
+            }
+        }
+    });
+
     // Subscribe a second receiver for GossipBridge BEFORE the producer starts
     let block_rx_gossip = block_producer.subscribe();
     let mut block_rx_sched = block_rx;
 
+    // Scheduled transactions: held encrypted on disk, released into the pool
+    // once their height or time is reached. Keep the key file off the data
+    // volume in production (BLEEP_SCHEDULED_TX_KEY).
+    let sched_dir = std::env::var("BLEEP_SCHEDULED_TX_DIR")
+        .unwrap_or_else(|_| "/tmp/bleep-scheduled-tx".to_string());
+    let sched_key_path = std::env::var("BLEEP_SCHEDULED_TX_KEY")
+        .unwrap_or_else(|_| format!("{}/store.key", sched_dir));
+    let tx_scheduler = match load_or_create_key(&sched_key_path)
+        .and_then(|key| TxScheduler::open_with_options(&sched_dir, &key, tx_pool.clone(), wal_opts))
+    {
+        Ok(s) => {
+            let state = Arc::clone(&state);
+            let balances: BalanceLookup = Arc::new(move |account: &str| state.lock().get_balance(account));
+            info!("  ✅ Transaction scheduler at {}", sched_dir);
+            Some(Arc::new(s.with_balances(balances)))
+        }
+        Err(e) => {
+            warn!("  ⚠️  Transaction scheduler unavailable ({})", e);
+            None
+        }
+    };
+    if let Some(tx_scheduler) = tx_scheduler.clone() {
+        let mut rx = block_producer.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(fb) => {
+                        let now = chrono::Utc::now().timestamp() as u64;
+                        for ev in tx_scheduler.process(fb.height, now).await {
+                            info!("[TxScheduler] {} at height {}: {:?}", ev.id, ev.height, ev.outcome);
+                        }
+                        if let Err(e) = tx_scheduler.wal_commit() {
+                            error!("[TxScheduler] WAL commit failed: {}", e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("[TxScheduler] Lagged {} blocks", n);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    // Governance: advance proposals at each block's epoch and publish what
+    // changed to `/rpc/ws/governance` subscribers and webhooks.
+    let governance_feed = Arc::new(GovernanceFeed::new(FeedConfig::default()));
+    let governance_webhooks = Arc::new(GovernanceWebhooks::new(Arc::new(HttpWebhookSink::default())));
+    tokio::spawn(Arc::clone(&governance_webhooks).run(governance_feed.subscribe()));
+    {
+        let governance = Arc::clone(&governance);
+        let feed = Arc::clone(&governance_feed);
+        let mut rx = block_producer.subscribe();
+        tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(fb) => {
+                        let events = {
+                            let mut engine = governance.lock();
+                            if let Err(e) = engine.advance_epoch(fb.epoch) {
+                                error!("[Governance] Advancing to epoch {} failed: {}", fb.epoch, e);
+                            }
+                            engine.drain_events()
+                        };
+                        if let Err(e) = feed.publish_block(fb.height, events) {
+                            error!("[Governance] Publishing block {} failed: {}", fb.height, e);
+                        }
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Lagged(n)) => {
+                        warn!("[Governance] Lagged {} blocks", n);
+                    }
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
+    // One partition window per tick, against the current validator set;
+    // safe-mode changes also move the `bleep_safe_mode` gauge.
+    {
+        let partition = Arc::clone(&partition);
+        let registry  = Arc::clone(&validator_registry);
+        let safe_mode_gauge = MetricGauge::new("bleep_safe_mode");
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(std::time::Duration::from_millis(partition.config().window_ms));
+            loop {
+                tick.tick().await;
+                partition.set_stakes(PartitionDetector::stakes_from(&registry.lock()));
+                if let Some(ev) = partition.tick(chrono::Utc::now().timestamp_millis() as u64) {
+                    safe_mode_gauge.set(matches!(ev, SafeModeEvent::Entered { .. }) as i64);
+                }
+            }
+        });
+    }
+
+    // WAL upkeep: interval fsyncs and size / fsync latency / compaction age gauges.
+    {
+        let mut wal_registry = MetricsRegistry::new();
+        let pool_wal_metrics  = WalTelemetry::register(&mut wal_registry, "mempool");
+        let sched_wal_metrics = WalTelemetry::register(&mut wal_registry, "scheduler");
+        let pool  = Arc::clone(&tx_pool);
+        let sched = tx_scheduler.clone();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(std::time::Duration::from_millis(100));
+            loop {
+                tick.tick().await;
+                pool.wal_sync_if_due();
+                if let Some(stats) = pool.wal_stats() {
+                    pool_wal_metrics.export(&stats);
+                }
+                if let Some(sched) = &sched {
+                    if let Err(e) = sched.wal_sync_if_due() {
+                        error!("[TxScheduler] WAL sync failed: {}", e);
+                    }
+                    sched_wal_metrics.export(&sched.wal_stats());
+                }
+            }
+        });
+    }
+
     // Build scheduler
     let scheduler = Arc::new(Scheduler::new());
     scheduler.register_built_in_tasks();
@@ -410,14 +1011,146 @@ async fn run() -> Result<(), Box<dyn Error>> {
     // Build live RpcState — wires the live StateManager for /rpc/state and
     // /rpc/proof, and shares the same atomic counters with the relay task so
     // block/tx counts are up-to-date in /rpc/health and /rpc/telemetry.
+    // Wallet portfolio: native balance (single shard) + PAT ledger, kept
+    // warm in the background for addresses wallets have asked about.
+    let portfolio = Arc::new(
+        PortfolioService::new(PortfolioConfig::default())
+            .with_source(Arc::new(StateBalanceSource::new("shard-0", Arc::clone(&state))))
+            .with_source(Arc::new(PatBalanceSource::new(Arc::clone(&pat_registry)))),
+    );
+    let _portfolio_refresh = Arc::clone(&portfolio).spawn_refresh();
+
     let rpc_state = RpcState::new()
+        .with_chain_id(chain_id)
         .with_state_manager(Arc::clone(&state))
         .with_validator_registry(Arc::clone(&validator_registry))
         .with_slashing_engine(Arc::clone(&slashing_engine))
         .with_economics_runtime(Arc::clone(&economics_runtime))
         .with_connect_orchestrator(Arc::clone(&connect_orchestrator))
         .with_pat_registry(Arc::clone(&pat_registry))
-        .with_transaction_pool(Arc::clone(&tx_pool));
+        .with_transaction_pool(Arc::clone(&tx_pool))
+        .with_base_fee(Arc::clone(&base_fee))
+        .with_partition_detector(Arc::clone(&partition))
+        .with_reward_ledger(reward_ledger)
+        .with_validator_sets(validator_sets, missed_slots)
+        .with_vm_executor(Arc::new(simulation_executor()))
+        .with_contract_registry(Arc::new(ContractRegistry::new().with_deploy_gate(contract_deploy_gate())))
+        .with_peer_directory(Arc::clone(&p2p_node.directory))
+        .with_dht(p2p_node.peer_manager.dht())
+        .with_service_quotas(Arc::clone(&p2p_node.service_quotas))
+        .with_governance(Arc::clone(&governance), Some(content_resolver))
+        .with_parameter_registry(Arc::clone(&parameters))
+        .with_governance_feed(governance_feed, governance_webhooks)
+        .with_ai_recommendations(Arc::clone(&ai_recommendations))
+        .with_portfolio(portfolio)
+        .with_audit_trail(audit_trail)
+        .with_telemetry_collector(Arc::new(TelemetryCollector::new(
+            Arc::clone(&blockchain),
+            Arc::clone(&p2p_node.peer_manager),
+            Arc::clone(&pbft_driver),
+        )));
+    let rpc_state = match tx_scheduler {
+        Some(s) => rpc_state.with_tx_scheduler(s),
+        None => rpc_state,
+    };
+    let rpc_state = match chain_auditor {
+        Some(a) => rpc_state.with_chain_auditor(a),
+        None => rpc_state,
+    };
+
+    // Server-side signing for POST /rpc/wallet/sign — only with a keystore,
+    // and only behind the admin token: the route spends node funds.
+    let rpc_state = match std::env::var("BLEEP_RPC_WALLET_KEYSTORE") {
+        Ok(path) if !path.is_empty()
+            && std::env::var("BLEEP_RPC_ADMIN_TOKEN").map_or(true, |t| t.is_empty()) =>
+        {
+            error!("BLEEP_RPC_WALLET_KEYSTORE needs BLEEP_RPC_ADMIN_TOKEN: without it anyone could sign from the node wallet");
+            std::process::exit(1);
+        }
+        Ok(path) if !path.is_empty() => {
+            let password = std::env::var("BLEEP_WALLET_PASSWORD").unwrap_or_default();
+            match ServerWallet::open(std::path::Path::new(&path), &password, chain_id) {
+                Ok(wallet) => {
+                    info!("  ✅ RPC signing with wallet {}", wallet.address());
+                    rpc_state.with_server_wallet(Arc::new(wallet))
+                }
+                Err(e) => {
+                    error!("RPC wallet unavailable: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => rpc_state,
+    };
+
+    // Per-consumer API keys — enforced only when an admin token is configured
+    let rpc_state = match std::env::var("BLEEP_RPC_ADMIN_TOKEN") {
+        Ok(token) if !token.is_empty() => {
+            let keys_dir = std::env::var("BLEEP_API_KEYS_DIR")
+                .unwrap_or_else(|_| "/tmp/bleep-api-keys".to_string());
+            match ApiKeyRegistry::open(&keys_dir) {
+                Ok(reg) => {
+                    info!("  ✅ RPC API keys enforced (registry at {})", keys_dir);
+                    rpc_state.with_api_keys(Arc::new(reg), token)
+                }
+                Err(e) => {
+                    error!("API key registry open failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => rpc_state,
+    };
+
+    // Telemetry export — profiles from BLEEP_TELEMETRY_EXPORT (JSON), signed with the node key
+    let mut export_handles = Vec::new();
+    let rpc_state = match std::env::var("BLEEP_TELEMETRY_EXPORT") {
+        Ok(path) if !path.is_empty() => match TelemetryExportConfig::load(std::path::Path::new(&path)) {
+            Ok(config) => {
+                let config = Arc::new(config);
+                let signer: Arc<dyn ExportSigner> = Arc::new(NodeKeySigner(Arc::clone(&p2p_node.identity)));
+                let sink: Arc<dyn ExportSink> = Arc::new(HttpsExportSink::default());
+                for profile in config.profiles.iter().filter(|p| !p.endpoints.is_empty()) {
+                    let source_state = rpc_state.clone();
+                    let exporter = Arc::new(TelemetryExporter::new(
+                        profile.clone(),
+                        Arc::clone(&signer),
+                        Arc::clone(&sink),
+                        Arc::new(move || telemetry_snapshot(&source_state)),
+                    ));
+                    export_handles.push(exporter.spawn());
+                    info!("  ✅ Telemetry export '{}' → {} endpoint(s)", profile.name, profile.endpoints.len());
+                }
+                rpc_state.with_telemetry_export(config)
+            }
+            Err(e) => {
+                error!("Telemetry export config: {}", e);
+                std::process::exit(1);
+            }
+        },
+        _ => rpc_state,
+    };
+
+    // Telemetry history — sampled into BLEEP_TELEMETRY_HISTORY_DIR, capped at BLEEP_TELEMETRY_HISTORY_MB
+    let history_dir = std::env::var("BLEEP_TELEMETRY_HISTORY_DIR")
+        .unwrap_or_else(|_| "/tmp/bleep-telemetry-history".to_string());
+    let mut history_config = HistoryConfig::default();
+    if let Some(mb) = std::env::var("BLEEP_TELEMETRY_HISTORY_MB").ok().and_then(|v| v.parse::<u64>().ok()) {
+        history_config.max_bytes = mb.max(1) * 1024 * 1024;
+    }
+    let (rpc_state, history_handle) = match TelemetryHistory::open(&history_dir, history_config) {
+        Ok(history) => {
+            let history = Arc::new(history);
+            let source_state = rpc_state.clone();
+            let handle = Arc::clone(&history).spawn(Arc::new(move || telemetry_snapshot(&source_state)));
+            info!("  ✅ Telemetry history: {} (max {} MiB)", history_dir, history_config.max_bytes / (1024 * 1024));
+            (rpc_state.with_telemetry_history(history), Some(handle))
+        }
+        Err(e) => {
+            warn!("  ⚠️  Telemetry history unavailable ({}), /rpc/telemetry/history disabled", e);
+            (rpc_state, None)
+        }
+    };
 
     // Relay FinalizedBlock events into Scheduler + metrics + economics
     let scheduler_relay  = Arc::clone(&scheduler);
@@ -426,6 +1159,7 @@ async fn run() -> Result<(), Box<dyn Error>> {
     let gas_relay        = gas_used_gauge.clone();
     let economics_relay  = Arc::clone(&economics_runtime);
     let rpc_height_relay = Arc::clone(&rpc_state.chain_height);
+    let tx_pool_relay    = Arc::clone(&tx_pool);
 
     // Track last epoch to fire economics only once per epoch boundary
     let mut last_economics_epoch: u64 = 0;
@@ -440,6 +1174,8 @@ async fn run() -> Result<(), Box<dyn Error>> {
                     for _ in 0..fb.tx_count { txs_relay.increment(); }
                     gas_relay.set(fb.gas_used as i64);
                     rpc_height_relay.store(fb.height, std::sync::atomic::Ordering::Relaxed);
+                    // Batch boundary for `FsyncPolicy::OnCommit`.
+                    tx_pool_relay.wal_commit();
 
                     // Accumulate fee revenue (gas_used * base_fee approximation)
                     epoch_fee_revenue = epoch_fee_revenue.saturating_add(fb.gas_used as u128 * 1_000);
@@ -509,13 +1245,37 @@ async fn run() -> Result<(), Box<dyn Error>> {
     let inbound_blockchain = Arc::clone(&blockchain);
     let inbound_p2p_node   = Arc::clone(&p2p_node);
     let inbound_state      = Arc::clone(&state);
-    let inbound_pk         = sphincs_pk.clone(); // SPHINCS+ PK used as fallback block verifier key
+    let inbound_block_key  = Arc::clone(&block_key);
+    let inbound_evidence   = Arc::clone(&evidence_pool);
+    let inbound_registry   = Arc::clone(&validator_registry);
+    let inbound_slashing   = Arc::clone(&slashing_engine);
+    let inbound_partition  = Arc::clone(&partition);
+    let inbound_syncer     = syncer.clone();
+    let inbound_pbft       = Arc::clone(&pbft_link);
+    let inbound_pbft_feed  = pbft_feed.clone();
 
     let inbound_handle = tokio::spawn(async move {
         info!("[InboundBlockHandler] Listening for P2P block gossip…");
         loop {
             match inbound_p2p_node.recv().await {
                 Some((_peer_id, msg)) => {
+                    if msg.message_type == MessageType::Custom(EVIDENCE_TOPIC.to_string()) {
+                        let tip = inbound_blockchain.read().unwrap()
+                            .latest_block().map(|b| b.index).unwrap_or(0);
+                        let added = Evidence::decode(&msg.payload).and_then(|ev| {
+                            inbound_evidence.lock().add(ev, tip, &inbound_registry.lock())
+                        });
+                        if let Err(e) = added {
+                            warn!("[InboundBlockHandler] Evidence rejected: {}", e);
+                        }
+                        continue;
+                    }
+                    if msg.message_type == MessageType::Custom(PBFT_TOPIC.to_string()) {
+                        if let Err(e) = inbound_pbft.deliver(&msg.payload) {
+                            warn!("[InboundBlockHandler] PBFT message dropped: {}", e);
+                        }
+                        continue;
+                    }
                     if msg.message_type != MessageType::Block {
                         continue; // not a block message
                     }
@@ -529,9 +1289,39 @@ async fn run() -> Result<(), Box<dyn Error>> {
                                 continue;
                             }
                         };
+                    // A block from another network is neither ours to apply
+                    // nor evidence against its signer.
+                    if block.chain_id != chain_id {
+                        warn!(
+                            "[InboundBlockHandler] Block {} is for chain {}, this is chain {} — discarding",
+                            block.index, block.chain_id, chain_id
+                        );
+                        continue;
+                    }
+
+                    // Double-sign detection runs before any height check: a
+                    // second block at a known height is what it looks for.
+                    let signer = block.signer_public_key()
+                        .and_then(|pk| validator_for_key(&inbound_registry.lock(), pk));
+                    if let Some(ref signer) = signer {
+                        let tip = inbound_blockchain.read().unwrap()
+                            .latest_block().map(|b| b.index).unwrap_or(0)
+                            .max(block.index);
+                        let mut pool = inbound_evidence.lock();
+                        if let Some(ev) = pool.observe_block(signer, &block, tip) {
+                            let bytes = ev.encode();
+                            match pool.add(ev, tip, &inbound_registry.lock()) {
+                                Ok(()) => inbound_p2p_node.broadcast(
+                                    MessageType::Custom(EVIDENCE_TOPIC.to_string()), bytes,
+                                ),
+                                Err(e) => warn!("[InboundBlockHandler] Own evidence rejected: {}", e),
+                            }
+                        }
+                    }
 
                     // Block-level validation: Fiat-Shamir ZKP + validator sig
-                    let valid = BlockValidator::validate_block(&block, &inbound_pk);
+                    let block_pk = inbound_block_key(&block);
+                    let valid = BlockValidator::validate_block(&block, &block_pk);
                     if !valid {
                         warn!(
                             "[InboundBlockHandler] Block {} failed block-level validation — discarding",
@@ -539,6 +1329,9 @@ async fn run() -> Result<(), Box<dyn Error>> {
                         );
                         continue;
                     }
+                    if let Some(ref signer) = signer {
+                        inbound_partition.heard_from(signer, chrono::Utc::now().timestamp_millis() as u64);
+                    }
 
                     // Per-transaction SPHINCS+ signature verification.
                     // Reject the whole block if any tx carries an invalid signature.
@@ -550,12 +1343,7 @@ async fn run() -> Result<(), Box<dyn Error>> {
                             continue; // legacy / genesis tx — no sig required
                         }
                         // Reconstruct the canonical payload that was signed.
-                        let payload = tx_payload(
-                            &tx.sender,
-                            &tx.receiver,
-                            tx.amount,
-                            tx.timestamp,
-                        );
+                        let payload = tx.signed_payload();
                         // The signature blob is [pk(var) || SPHINCS+_sig].
                         // For our wallet tx signer the PK length is fixed at
                         // the size stored in the wallet (variable by scheme).
@@ -575,26 +1363,80 @@ async fn run() -> Result<(), Box<dyn Error>> {
                         continue;
                     }
 
-                    // Chain link validation against our tip
-                    let already_have = {
+                    let (tip, parent, known) = {
                         let chain = inbound_blockchain.read().unwrap();
-                        chain.latest_block()
-                            .map(|tip| tip.index >= block.index)
-                            .unwrap_or(false)
+                        (
+                            chain.latest_block(),
+                            chain.get_block_by_hash(&block.previous_hash),
+                            chain.get_block_by_hash(&block.compute_hash()).is_some(),
+                        )
                     };
-                    if already_have {
-                        continue; // already at this height or ahead
+                    if known {
+                        continue;
+                    }
+
+                    let included = match inbound_evidence.lock()
+                        .validate_block(&block, &inbound_registry.lock())
+                    {
+                        Ok(included) => included,
+                        Err(e) => {
+                            warn!("[InboundBlockHandler] Block {} evidence invalid: {} — discarding", block.index, e);
+                            continue;
+                        }
+                    };
+
+                    // A block that does not extend our tip is a competing
+                    // chain.  One parting from a block we hold is weighed
+                    // against ours by fork choice; one parting further down
+                    // is fetched by syncing with the seeds.
+                    if tip.as_ref().is_some_and(|tip| tip.compute_hash() != block.previous_hash) {
+                        match parent {
+                            Some(parent) if parent.index + 1 == block.index => {
+                                let switched = inbound_blockchain.write().unwrap()
+                                    .handle_fork(VecDeque::from([parent, block.clone()]));
+                                match switched {
+                                    Ok(true) => info!("[InboundBlockHandler] Switched to the competing chain at block {}", block.index),
+                                    Ok(false) => {}
+                                    Err(e) => warn!("[InboundBlockHandler] Competing block {} rejected: {}", block.index, e),
+                                }
+                            }
+                            _ if tip.as_ref().is_some_and(|tip| block.index > tip.index) => {
+                                if let Some(syncer) = &inbound_syncer {
+                                    match syncer.run(&inbound_blockchain).await {
+                                        Ok(outcome) => info!(
+                                            "[InboundBlockHandler] Synced to height {} ({} reorgs)",
+                                            outcome.height, outcome.reorgs
+                                        ),
+                                        Err(e) => warn!("[InboundBlockHandler] Sync for block {} stopped: {}", block.index, e),
+                                    }
+                                }
+                            }
+                            _ => {}
+                        }
+                        continue;
                     }
 
                     // Insert validated block
                     let accepted = {
                         let mut chain = inbound_blockchain.write().unwrap();
-                        chain.add_block(block.clone(), &inbound_pk)
+                        chain.add_block(block.clone(), &block_pk)
                     };
 
                     if accepted {
                         // Advance state height to match inbound block
                         inbound_state.lock().advance_block();
+                        let report = inbound_evidence.lock().on_block_committed(
+                            &block,
+                            signer.as_deref().unwrap_or_default(),
+                            &included,
+                            &mut inbound_slashing.lock(),
+                            &mut inbound_registry.lock(),
+                        );
+                        for s in &report.slashed {
+                            warn!("[InboundBlockHandler] Slashed {} for {} at height {}",
+                                s.validator_id, s.evidence_type, s.block_height);
+                        }
+                        let _ = inbound_pbft_feed.send(block.clone());
                         info!(
                             "[InboundBlockHandler] ✅ Accepted inbound block {} txs={}",
                             block.index,
@@ -657,6 +1499,9 @@ async fn run() -> Result<(), Box<dyn Error>> {
     info!("   Proposals:    http://0.0.0.0:8545/rpc/governance/proposals  ");
     info!("   Propose:      POST http://0.0.0.0:8545/rpc/governance/propose  ");
     info!("   Vote:         POST http://0.0.0.0:8545/rpc/governance/vote  ");
+    info!("   Proposal:     http://0.0.0.0:8545/rpc/governance/proposal/{{id}}  ");
+    info!("   Parameters:   http://0.0.0.0:8545/rpc/governance/parameters  ");
+    info!("   Gov. feed:    ws://0.0.0.0:8545/rpc/ws/governance  ");
     info!("══ Protocol Hardening ══════════════════════════════════════════════");
     info!("   Chaos suite:  http://0.0.0.0:8545/rpc/chaos/status  ");
     info!("   MPC Ceremony: http://0.0.0.0:8545/rpc/ceremony/status  ");
@@ -688,7 +1533,7 @@ async fn run() -> Result<(), Box<dyn Error>> {
     }
 
     // Persist governance
-    governance.persist().unwrap_or_else(|e| warn!("Governance persist: {}", e));
+    governance.lock().persist().unwrap_or_else(|e| warn!("Governance persist: {}", e));
 
     // Abort background tasks
     producer_handle.abort();
@@ -699,8 +1544,499 @@ async fn run() -> Result<(), Box<dyn Error>> {
     block_sched_handle.abort();
     rpc_handle.abort();
     inbound_handle.abort();
+    pbft_handle.abort();
+    export_handles.iter().for_each(|h| h.abort());
+    history_handle.iter().for_each(|h| h.abort());
+    snap_announce_handle.iter().for_each(|h| h.abort());
+    audit_handle.iter().for_each(|h| h.abort());
     p2p_handle.shutdown().await;
 
     info!("✅ BLEEP node stopped cleanly. Goodbye.");
     Ok(())
 }
+
+/// What this process runs as: `--role <role>` or `BLEEP_NODE_ROLE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NodeRole {
+    Validator,
+    Replica,
+}
+
+impl std::str::FromStr for NodeRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "validator" => Ok(NodeRole::Validator),
+            "replica"   => Ok(NodeRole::Replica),
+            other => Err(format!("unknown node role '{}' (expected validator or replica)", other)),
+        }
+    }
+}
+
+fn node_role() -> Result<NodeRole, String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if let Some(role) = arg.strip_prefix("--role=") {
+            return role.parse();
+        }
+        if arg == "--role" {
+            return args.next().ok_or("--role needs a value")?.parse();
+        }
+    }
+    match std::env::var("BLEEP_NODE_ROLE") {
+        Ok(role) if !role.is_empty() => role.parse(),
+        _ => Ok(NodeRole::Validator),
+    }
+}
+
+/// Network this node signs and verifies for: `BLEEP_CHAIN_ID`, else the
+/// chain the block archive already holds, else mainnet.  Only ids in the
+/// range reserved for BLEEP networks are accepted for a new chain.
+///
+/// An archive written before chain ids existed holds unbound blocks
+/// (`ChainId::NONE`); it keeps running unbound so its block hashes still
+/// match, and `BLEEP_CHAIN_ID` is refused until it is resynced from an
+/// empty `BLEEP_BLOCKS_DIR`.
+fn chain_id(store: Option<&BlockStore>) -> Result<ChainId, String> {
+    let mut stored = None;
+    if let Some(store) = store {
+        if let Some(height) = store.tip()? {
+            stored = store.get(height)?.map(|stored| stored.block.chain_id);
+        }
+    }
+    let requested = match std::env::var("BLEEP_CHAIN_ID") {
+        Ok(id) if !id.is_empty() => Some(id.parse::<ChainId>()?),
+        _ => None,
+    };
+    let id = match (stored, requested) {
+        (Some(ChainId::NONE), Some(id)) => {
+            return Err(format!(
+                "the block archive predates chain ids; unset BLEEP_CHAIN_ID to keep running it unbound, \
+                 or resync from an empty BLEEP_BLOCKS_DIR to join chain {}",
+                id,
+            ));
+        }
+        (Some(ChainId::NONE), None) => {
+            warn!("⚠️  Block archive predates chain ids — running unbound; resync from an empty BLEEP_BLOCKS_DIR to bind to a network");
+            return Ok(ChainId::NONE);
+        }
+        (Some(stored), Some(id)) if stored != id => {
+            return Err(format!("the block archive holds chain {}, but BLEEP_CHAIN_ID is {}", stored, id));
+        }
+        (stored, requested) => requested.or(stored).unwrap_or(ChainId::MAINNET),
+    };
+    if !id.is_bleep_network() {
+        return Err(format!("BLEEP_CHAIN_ID {} is outside the range reserved for BLEEP networks", id));
+    }
+    Ok(id)
+}
+
+/// Block-signing key from the keystore named in the `[consensus]` stanza
+/// of `BLEEP_NODE_CONFIG`; `None` when the variable is unset.
+fn consensus_key() -> Result<Option<ProposerKey>, String> {
+    let Ok(path) = std::env::var("BLEEP_NODE_CONFIG") else { return Ok(None) };
+    let config = ConsensusConfig::read(std::path::Path::new(&path))?
+        .ok_or_else(|| format!("{} has no [consensus] stanza — run `bleep-cli validator init`", path))?;
+    let key = config.load_keystore()?.proposer_key()?;
+    info!("  ✅ Consensus key {} loaded from {}", key.validator_id, config.keystore.display());
+    Ok(Some(key))
+}
+
+/// The `[p2p]` table of `BLEEP_NODE_CONFIG`; `None` when the variable,
+/// the file or the table is missing.
+fn p2p_settings() -> Result<Option<P2PSettings>, String> {
+    let Ok(path) = std::env::var("BLEEP_NODE_CONFIG") else { return Ok(None) };
+    P2PSettings::read(std::path::Path::new(&path)).map_err(|e| e.to_string())
+}
+
+/// Static-analysis limit for contract deployments: the default for
+/// `BLEEP_NETWORK` (mainnet if unset), or `BLEEP_CONTRACT_MAX_SEVERITY`.
+fn contract_deploy_gate() -> DeployGate {
+    let network = std::env::var("BLEEP_NETWORK").unwrap_or_else(|_| "mainnet".to_string());
+    let gate = match std::env::var("BLEEP_CONTRACT_MAX_SEVERITY").ok().and_then(|v| Severity::parse(&v)) {
+        Some(max) => SeverityGate::new(max),
+        None => SeverityGate::for_network(&network),
+    };
+    info!("  ✅ Contract deploy gate: findings above {} refused ({})", gate.max_allowed, network);
+    gate.deploy_gate()
+}
+
+/// Register the node's own parameters next to the genesis rules: fee and
+/// reward settings read live from their trackers, plus the compiled-in
+/// consensus, VM and relay limits.
+fn register_node_parameters(
+    registry: &mut ParameterRegistry,
+    base_fee: &Arc<BaseFeeTracker>,
+    rewards: &Arc<Mutex<RewardLedger>>,
+) -> Result<(), Box<dyn Error>> {
+    use bleep_consensus::block_producer::{BLOCK_INTERVAL_MS, MAX_TXS_PER_BLOCK};
+    use bleep_core::relay_checks::{DEFAULT_MAX_BLOCK_BYTES, DEFAULT_MAX_TX_BYTES};
+    use bleep_vm::execution::contract_registry::{CALL_BASE_GAS, STORAGE_WRITE_GAS};
+
+    let defaults = BaseFeeParams::default();
+    let fee_params: [(&str, ParamKind, u64, u64, fn(&BaseFeeParams) -> u64); 5] = [
+        ("MIN_BASE_FEE",        ParamKind::Amount,      defaults.min_base_fee,                1, u64::MAX, |p| p.min_base_fee),
+        ("MAX_BASE_FEE",        ParamKind::Amount,      defaults.max_base_fee,                1, u64::MAX, |p| p.max_base_fee),
+        ("TARGET_FULLNESS_BPS", ParamKind::BasisPoints, defaults.target_fullness_bps as u64,  1, 9_999,    |p| p.target_fullness_bps as u64),
+        ("MAX_CHANGE_BPS",      ParamKind::BasisPoints, defaults.max_change_bps as u64,       0, 10_000,   |p| p.max_change_bps as u64),
+        ("TREASURY_SHARE_BPS",  ParamKind::BasisPoints, defaults.treasury_share_bps as u64,   0, 10_000,   |p| p.treasury_share_bps as u64),
+    ];
+    for (key, kind, default, min, max, read) in fee_params {
+        let tracker = Arc::clone(base_fee);
+        registry.register_live(
+            ParameterSpec::new(key, "fees", kind, default as u128, min as u128, max as u128).node_local(),
+            move || read(&tracker.params()) as u128,
+        )?;
+    }
+
+    let reward_defaults = RewardParams::default();
+    let reward_params: [(&str, ParamKind, u64, u64, fn(&RewardParams) -> u64); 3] = [
+        ("BLOCK_ISSUANCE",     ParamKind::Amount,      reward_defaults.block_issuance,            0, u64::MAX, |p| p.block_issuance),
+        ("PROPOSER_BPS",       ParamKind::BasisPoints, reward_defaults.proposer_bps as u64,       0, 10_000,   |p| p.proposer_bps as u64),
+        ("MAX_COMMISSION_BPS", ParamKind::BasisPoints, reward_defaults.max_commission_bps as u64, 0, 10_000,   |p| p.max_commission_bps as u64),
+    ];
+    for (key, kind, default, min, max, read) in reward_params {
+        let ledger = Arc::clone(rewards);
+        registry.register_live(
+            ParameterSpec::new(key, "rewards", kind, default as u128, min as u128, max as u128).node_local(),
+            move || read(ledger.lock().params()) as u128,
+        )?;
+    }
+
+    for (key, subsystem, kind, value, description) in [
+        ("BLOCK_INTERVAL_MS",    "consensus", ParamKind::Milliseconds, BLOCK_INTERVAL_MS,              "Slot length"),
+        ("MAX_TXS_PER_BLOCK",    "consensus", ParamKind::Integer,      MAX_TXS_PER_BLOCK as u64,       "Transactions a proposer packs per block"),
+        ("CALL_BASE_GAS",        "vm",        ParamKind::Integer,      CALL_BASE_GAS,                  "Gas charged before a contract call runs"),
+        ("STORAGE_WRITE_GAS",    "vm",        ParamKind::Integer,      STORAGE_WRITE_GAS,              "Gas per contract storage write"),
+        ("MAX_TX_BYTES",         "p2p",       ParamKind::Bytes,        DEFAULT_MAX_TX_BYTES as u64,    "Largest relayed transaction"),
+        ("MAX_BLOCK_BYTES",      "p2p",       ParamKind::Bytes,        DEFAULT_MAX_BLOCK_BYTES as u64, "Largest relayed block"),
+    ] {
+        let value = value as u128;
+        registry.register(ParameterSpec::new(key, subsystem, kind, value, value, value).fixed().describe(description))?;
+    }
+    Ok(())
+}
+
+/// Comma-separated values of `key`, trimmed, empty entries dropped.
+fn env_list(key: &str) -> Vec<String> {
+    std::env::var(key)
+        .unwrap_or_default()
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Settings that point a node at key material; replicas refuse to start
+/// with any of them set.
+const REPLICA_REFUSED_ENV: &[&str] = &["BLEEP_SCHEDULED_TX_KEY", "BLEEP_P2P_DIR"];
+
+/// Read-only replica: follows the validators' blocks and serves the
+/// read-only RPC subset.  It generates and loads no keys and runs no
+/// mempool, producer, scheduler or simulation VM; its P2P identity is
+/// ephemeral.
+///
+/// - `BLEEP_REPLICA_PROPOSERS` — comma-separated hex SPHINCS+ public keys
+///   of the validators whose blocks are applied (required)
+/// - `BLEEP_REPLICA_UPSTREAM` — comma-separated node ids; when set, blocks
+///   from any other peer are ignored
+/// - `BLEEP_REPLICA_MAX_LAG` — blocks behind upstream `/rpc/ready` still
+///   reports ready (default 5)
+/// - `BLEEP_SNAP_SYNC_PEERS` — comma-separated `host:port` of validators to
+///   snap-sync from when the block store is empty; the replica reports not
+///   ready until the state is downloaded and verified
+async fn run_replica() -> Result<(), Box<dyn Error>> {
+    for key in REPLICA_REFUSED_ENV {
+        if std::env::var(key).map(|v| !v.is_empty()).unwrap_or(false) {
+            return Err(format!("replica mode loads no keys; unset {}", key).into());
+        }
+    }
+    info!("📖 Replica mode: following the chain, read-only RPC, no keys.");
+
+    let state_dir = std::env::var("BLEEP_STATE_DIR")
+        .unwrap_or_else(|_| "/tmp/bleep-state".to_string());
+    let blocks_dir = std::env::var("BLEEP_BLOCKS_DIR")
+        .unwrap_or_else(|_| "/tmp/bleep-blocks".to_string());
+    let state = Arc::new(Mutex::new(open_node_state(&state_dir, &blocks_dir)));
+    let block_store = Arc::new(BlockStore::open(&blocks_dir)?);
+    let chain_id = chain_id(Some(&block_store))?;
+
+    let proposers = env_list("BLEEP_REPLICA_PROPOSERS");
+    if proposers.is_empty() {
+        return Err("replica mode needs BLEEP_REPLICA_PROPOSERS (hex SPHINCS+ public keys)".into());
+    }
+    let mut proposer_keys = Vec::new();
+    for key in &proposers {
+        let pk = hex::decode(key).map_err(|e| format!("BLEEP_REPLICA_PROPOSERS: {}: {}", key, e))?;
+        if pk.len() < 8 {
+            return Err(format!("BLEEP_REPLICA_PROPOSERS: {} is not a public key", key).into());
+        }
+        proposer_keys.push(pk);
+    }
+    let upstream: std::collections::HashSet<String> =
+        env_list("BLEEP_REPLICA_UPSTREAM").into_iter().collect();
+    let max_lag = match std::env::var("BLEEP_REPLICA_MAX_LAG") {
+        Ok(v) => v.parse().map_err(|e| format!("BLEEP_REPLICA_MAX_LAG: {}", e))?,
+        Err(_) => DEFAULT_MAX_LAG,
+    };
+
+    // A node with no blocks yet fetches the state at a checkpoint from
+    // these peers instead of replaying from genesis.
+    let snap_syncer = match block_store.tip()? {
+        None => {
+            let mut peers: Vec<Arc<dyn SnapPeer>> = Vec::new();
+            for addr in env_list("BLEEP_SNAP_SYNC_PEERS") {
+                let addr = addr.parse().map_err(|e| format!("BLEEP_SNAP_SYNC_PEERS: {}: {}", addr, e))?;
+                peers.push(Arc::new(TcpSnapPeer { addr }));
+            }
+            (!peers.is_empty()).then(|| {
+                let mut syncer = SnapSyncer::new(peers, format!("{}/snap-sync", blocks_dir))
+                    .with_telemetry(SnapTelemetry::register(&mut MetricsRegistry::new()));
+                for pk in &proposer_keys {
+                    syncer = syncer.with_proposer(hex::encode(&pk[..8]), pk);
+                }
+                syncer
+            })
+        }
+        Some(_) => None,
+    };
+
+    // No data_dir: the node key is generated in memory and never written.
+    let env_or_empty = |key: &str| std::env::var(key).unwrap_or_default();
+    let p2p_config = P2PNodeConfig {
+        data_dir: None,
+        metadata: NodeMetadata::new(
+            &env_or_empty("BLEEP_NODE_MONIKER"),
+            &env_or_empty("BLEEP_NODE_CONTACT"),
+            &env_or_empty("BLEEP_NODE_REGION"),
+        ),
+        service_quotas: service_quota_config(),
+        ..P2PNodeConfig::default()
+    };
+    let (p2p_node, p2p_handle) = P2PNode::start(p2p_config).await?;
+    info!("  ✅ P2P node {} | peers: {}", p2p_node.node_id, p2p_node.peer_count());
+
+    // RPC comes up first so `/rpc/ready` reports a snap sync in progress.
+    let status = Arc::new(SyncStatus::new(0));
+    let rpc_state = RpcState::new()
+        .with_chain_id(chain_id)
+        .with_state_manager(Arc::clone(&state))
+        .with_block_store(Arc::clone(&block_store))
+        .with_contract_registry(Arc::new(ContractRegistry::new()))
+        .with_peer_directory(Arc::clone(&p2p_node.directory))
+        .with_dht(p2p_node.peer_manager.dht())
+        .with_service_quotas(Arc::clone(&p2p_node.service_quotas))
+        .with_replica(Arc::clone(&status), max_lag);
+    let rpc_state = match &snap_syncer {
+        Some(syncer) => rpc_state.with_snap_sync(syncer.progress()),
+        None => rpc_state,
+    };
+    let rpc_height = Arc::clone(&rpc_state.chain_height);
+    let routes = rpc_routes_with_state(rpc_state);
+    let rpc_handle = tokio::spawn(async move {
+        warp::serve(routes).run(([0, 0, 0, 0], 8545)).await;
+    });
+
+    if let Some(syncer) = snap_syncer {
+        info!("  ⏳ Snap sync from {} peer(s)…", env_list("BLEEP_SNAP_SYNC_PEERS").len());
+        let outcome = syncer.run(&state).await?;
+        let state_height = state.lock().block_height();
+        block_store.put(&StoredBlock::adopted(outcome.checkpoint.clone(), state_height))?;
+        info!(
+            "  ✅ Snap-synced {} accounts at checkpoint {} ({} chunks, {} penalized peers)",
+            outcome.accounts, outcome.checkpoint.index, outcome.fetched, outcome.penalized.len(),
+        );
+    }
+
+    // Resume on top of the last archived block (or the snap-sync
+    // checkpoint); fsck has already matched the state to it.
+    let anchor = match block_store.tip()? {
+        Some(h) => block_store.get(h)?.map(|stored| stored.block),
+        None => None,
+    }
+    .unwrap_or_else(|| Block::genesis_for(chain_id));
+    if anchor.chain_id != chain_id {
+        return Err(format!(
+            "{} holds chain {}, but BLEEP_CHAIN_ID is {}", blocks_dir, anchor.chain_id, chain_id,
+        ).into());
+    }
+    let blockchain = Blockchain::new(anchor, core_state(&state.lock()), TransactionPool::new(1), None)?;
+    let blockchain = Arc::new(RwLock::new(blockchain));
+
+    let mut follower = ReplicaFollower::new(Arc::clone(&blockchain), Arc::clone(&state))
+        .with_block_store(Arc::clone(&block_store))
+        .with_rewards(Arc::new(Mutex::new(RewardLedger::new(RewardParams::default())?)))
+        .with_status(Arc::clone(&status));
+    for pk in &proposer_keys {
+        // Validators are identified by the first 8 bytes of their key.
+        follower = follower.with_proposer(hex::encode(&pk[..8]), pk);
+    }
+    info!("  ✅ Following {} proposer(s) from height {}", proposers.len(), status.height());
+    rpc_height.store(status.height(), std::sync::atomic::Ordering::Relaxed);
+
+    let follow_node = Arc::clone(&p2p_node);
+    let follow_handle = tokio::spawn(async move {
+        while let Some((peer_id, msg)) = follow_node.recv().await {
+            if msg.message_type != MessageType::Block {
+                continue;
+            }
+            if !upstream.is_empty() && !upstream.contains(&peer_id.to_string()) {
+                continue;
+            }
+            // Finalized-block summaries share the topic but carry no
+            // transactions; only full blocks can be replayed.
+            let Ok(block) = serde_json::from_slice::<Block>(&msg.payload) else {
+                continue;
+            };
+            match follower.apply(block).await {
+                Ok(applied) => {
+                    if let Some(h) = applied.last() {
+                        rpc_height.store(*h, std::sync::atomic::Ordering::Relaxed);
+                    }
+                }
+                Err(e) => warn!("[Replica] {}", e),
+            }
+        }
+    });
+
+    info!("  ✅ Read-only RPC on 0.0.0.0:8545 (readiness: /rpc/ready, max lag {})", max_lag);
+
+    tokio::signal::ctrl_c().await?;
+    info!("🛑 Ctrl-C — graceful shutdown…");
+    {
+        let mut s = state.lock();
+        s.create_snapshot().unwrap_or_else(|e| warn!("State snapshot: {}", e));
+        info!("  ✅ State flushed to RocksDB (height={}).", s.block_height());
+    }
+    follow_handle.abort();
+    rpc_handle.abort();
+    p2p_handle.shutdown().await;
+    info!("✅ BLEEP replica stopped cleanly.");
+    Ok(())
+}
+
+/// Accounts minted at genesis, in µBLEEP (650T in total).
+const GENESIS_ALLOCATIONS: [(&str, u128); 3] = [
+    ("bleep:genesis:foundation", 500_000_000_000_000),
+    ("bleep:genesis:rewards",    100_000_000_000_000),
+    ("bleep:genesis:validators",  50_000_000_000_000),
+];
+
+/// Open the state at `state_dir`, mint the genesis allocations on first
+/// start and reconcile it with the block archive at `blocks_dir`.
+fn open_node_state(state_dir: &str, blocks_dir: &str) -> StateManager {
+    let mut state = match StateManager::open(state_dir) {
+        Ok(s) => { info!("  ✅ StateManager at {}", state_dir); s }
+        // A store this binary cannot read (or failed to migrate) must not
+        // be replaced by an empty temp state.
+        Err(e @ StateError::Migration(_)) => {
+            error!("StateManager at {}: {}", state_dir, e);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            warn!("  ⚠️  StateManager open failed ({}), using temp dir", e);
+            StateManager::new()
+        }
+    };
+
+    // Mint genesis allocations only at height 0 (first start).
+    // mint() now returns Result — cap violations are logged and abort startup.
+    if state.block_height() == 0 {
+        for (account, amount) in GENESIS_ALLOCATIONS {
+            state.mint(account, amount)
+                .unwrap_or_else(|e| { error!("Genesis mint {} failed: {}", account, e); std::process::exit(1); });
+        }
+        // Seal the allocations as their own height so `debug replay` can
+        // fork the state block 1 executed on.
+        state.advance_block();
+        info!("  ✅ Genesis allocations minted (650T µBLEEP).");
+    }
+
+    // Rebuild the Sparse Merkle Trie from the persisted DB state
+    if let Err(e) = state.rebuild_trie_from_db() {
+        warn!("  ⚠️  Trie rebuild: {}", e);
+    }
+
+    // Blocks a rotated-out key keeps signing.  Consensus-relevant: every
+    // validator must run with the same value.
+    if let Some(grace) = std::env::var("BLEEP_KEY_GRACE_BLOCKS").ok().and_then(|v| v.parse().ok()) {
+        state.set_key_grace_blocks(grace);
+    }
+
+    // Archive mode keeps every committed root queryable (`?at_block=` past
+    // the journal window) at the cost of disk.
+    if std::env::var("BLEEP_STATE_ARCHIVE").map(|v| v == "1").unwrap_or(false) {
+        match state.enable_archive() {
+            Ok(()) => info!("  ✅ State archive enabled"),
+            Err(e) => warn!("  ⚠️  State archive: {}", e),
+        }
+    }
+
+    // Check the block archive against the state and roll both back to the
+    // last consistent height if a crash left them out of step.
+    if let Ok(store) = BlockStore::open(blocks_dir) {
+        match fsck(&store, &mut state, &FsckOptions::default(), true) {
+            Ok(report) if report.is_clean() => {
+                info!("  ✅ Storage consistent at height {}", report.consistent_height);
+            }
+            Ok(report) => {
+                for issue in &report.issues {
+                    warn!("  ⚠️  fsck: {:?}", issue);
+                }
+                if let Some(r) = &report.recovery {
+                    warn!(
+                        "  ⚠️  Recovered to height {} ({} blocks truncated, state → {:?}, {} shards reset)",
+                        report.consistent_height, r.truncated_blocks.len(),
+                        r.state_rolled_back_to, r.truncated_shards.len()
+                    );
+                    if let Some(from) = r.resync_from {
+                        warn!("  ⚠️  Blocks from height {} must be re-synced from peers", from);
+                    }
+                }
+            }
+            Err(e) => {
+                error!("Storage recovery failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    state
+}
+
+/// Core chain balances at genesis, before any block is applied.
+fn genesis_core_state() -> CoreBlockchainState {
+    let mut core_state = CoreBlockchainState::default();
+    for (account, amount) in GENESIS_ALLOCATIONS {
+        core_state.credit(account, amount as u64);
+    }
+    core_state
+}
+
+/// Core chain balances and nonces mirroring `state`.
+fn core_state(state: &StateManager) -> CoreBlockchainState {
+    let mut core_state = CoreBlockchainState::default();
+    for (address, balance) in state.export_balances() {
+        match u64::try_from(balance) {
+            Ok(balance_u64) if balance_u64 > 0 => core_state.credit(&address, balance_u64),
+            Ok(_) => {}
+            Err(_) => error!(
+                "StateManager balance overflow for account {}: {}",
+                address, balance
+            ),
+        }
+    }
+    for (address, nonce) in state.export_nonces() {
+        core_state.set_nonce(&address, nonce);
+    }
+    core_state
+}
+
+/// Executor backing `POST /rpc/tx/simulate`. Simulation requests are executed
+/// as unsigned intents, so signature verification is disabled here only.
+fn simulation_executor() -> Executor {
+    let mut cfg = ExecutorConfig::default();
+    cfg.router.verify_signatures = false;
+    Executor::production(cfg)
+}